@@ -26,14 +26,18 @@ pub mod helpers;
 pub mod relay_chain_queries;
 
 use beefy_light_client_primitives::{
-	ClientState, HostFunctions, MmrUpdateProof, ParachainHeader, PartialMmrLeaf,
+	ClientState, HostFunctions, MerkleHasher, MmrUpdateProof, ParachainHeader, PartialMmrLeaf,
+	SignedCommitment,
 };
-use beefy_primitives::mmr::{BeefyNextAuthoritySet, MmrLeaf};
-use codec::Decode;
+use beefy_primitives::{
+	known_payloads::MMR_ROOT_ID,
+	mmr::{BeefyNextAuthoritySet, MmrLeaf},
+};
+use codec::{Decode, Encode};
 use error::Error;
 use helpers::{
-	fetch_timestamp_extrinsic_with_proof, prove_parachain_headers, ParaHeadsProof,
-	TimeStampExtWithProof,
+	fetch_timestamp_extrinsic_with_proof, hash_authority_addresses, prove_authority_set,
+	prove_parachain_headers, AuthorityProofWithSignatures, ParaHeadsProof, TimeStampExtWithProof,
 };
 use hex_literal::hex;
 use pallet_mmr_primitives::Proof;
@@ -48,7 +52,9 @@ use subxt::{
 
 use crate::relay_chain_queries::parachain_header_storage_key;
 use light_client_common::config::{AsInner, BeefyAuthoritySetT, RuntimeStorage};
-use relay_chain_queries::{fetch_finalized_parachain_heads, fetch_mmr_proof, FinalizedParaHeads};
+use relay_chain_queries::{
+	fetch_beefy_justification, fetch_finalized_parachain_heads, fetch_mmr_proof, FinalizedParaHeads,
+};
 
 /// Host function implementation for beefy light client.
 #[derive(Clone, PartialEq, Eq, Debug, Default)]
@@ -298,13 +304,11 @@ where
 	/// mmr root hash.
 	pub async fn fetch_mmr_update_proof_for(
 		&self,
-		_signed_commitment: beefy_primitives::SignedCommitment<
+		signed_commitment: beefy_primitives::SignedCommitment<
 			u32,
 			beefy_primitives::crypto::Signature,
 		>,
 	) -> Result<MmrUpdateProof, Error> {
-		todo!("fetch beefy authorities")
-		/*
 		let subxt_block_number: subxt::rpc::types::BlockNumber =
 			signed_commitment.commitment.block_number.into();
 		let block_hash =
@@ -317,7 +321,7 @@ where
 				},
 			)?;
 
-		let current_authorities: Vec<Public> = {
+		let current_authorities = {
 			let key = T::Storage::beefy_authorities();
 			self.relay_client
 				.storage()
@@ -353,38 +357,41 @@ where
 			mmr_proof,
 			authority_proof,
 		})
-		 */
 	}
 
 	/// Construct a beefy client state to be submitted to the counterparty chain
 	pub async fn construct_beefy_client_state(&self) -> Result<ClientState, Error> {
-		todo!("fetch beefy authorities")
-		/*
 		let (signed_commitment, latest_beefy_finalized) =
 			fetch_beefy_justification(&self.relay_client).await?;
 
-		// Encoding and decoding to fix dependency version conflicts
-		let next_authority_set = {
+		let next_authorities = {
 			let key = T::Storage::mmr_leaf_beefy_next_authorities();
+			let data = self
+				.relay_client
+				.storage()
+				.at(latest_beefy_finalized)
+				.fetch(&key)
+				.await?
+				.ok_or_else(|| Error::Custom(format!("No next beefy authority set found!")))?;
+			<T::Storage as RuntimeStorage>::BeefyAuthoritySet::from_inner(data)
+		};
+		let validator_set_id = {
+			let key = T::Storage::beefy_validator_set_id();
 			self.relay_client
 				.storage()
 				.at(latest_beefy_finalized)
 				.fetch(&key)
 				.await?
-				.expect("Should retrieve next authority set")
-				.encode()
+				.ok_or_else(|| Error::Custom(format!("No beefy validator set id found!")))?
 		};
-		let next_authority_set = BeefyNextAuthoritySet::decode(&mut &*next_authority_set)
-			.expect("Should decode next authority set correctly");
-
-		let current_authorities: Vec<Public> = {
+		let current_authorities = {
 			let key = T::Storage::beefy_authorities();
 			self.relay_client
 				.storage()
 				.at(latest_beefy_finalized)
 				.fetch(&key)
 				.await?
-				.expect("Should retrieve next authority set")
+				.ok_or_else(|| Error::Custom(format!("No beefy authorities found!")))?
 		};
 
 		let authority_address_hashes = hash_authority_addresses(
@@ -392,29 +399,30 @@ where
 		)?;
 		let tree =
 			rs_merkle::MerkleTree::<MerkleHasher<Crypto>>::from_leaves(&authority_address_hashes);
-
-		let authority_root = tree.root().expect("Should generate root");
-		let authority_root: H256 = authority_root.into();
-		let current_authority_set = BeefyNextAuthoritySet {
-			id: next_authority_set.id - 1,
-			len: authority_address_hashes.len() as u32,
-			root: authority_root,
-		};
+		let authority_root: H256 = tree
+			.root()
+			.ok_or_else(|| Error::Custom(format!("Empty beefy authority set")))?
+			.into();
 
 		let mmr_root_hash = signed_commitment
 			.commitment
 			.payload
 			.get_decoded::<H256>(&MMR_ROOT_ID)
-			.expect("Mmr root hash should decode correctly");
+			.ok_or_else(|| Error::Custom(format!("Commitment doesn't contain an mmr root")))?;
 
-		let client_state = ClientState {
+		Ok(ClientState {
 			mmr_root_hash,
-			latest_beefy_height: signed_commitment.commitment.block_number as u32,
-			current_authorities: current_authority_set.clone(),
-			next_authorities: next_authority_set.clone(),
-		};
-
-		Ok(client_state)
-		 */
+			latest_beefy_height: signed_commitment.commitment.block_number,
+			current_authorities: BeefyNextAuthoritySet {
+				id: validator_set_id,
+				len: authority_address_hashes.len() as u32,
+				root: authority_root,
+			},
+			next_authorities: BeefyNextAuthoritySet {
+				id: validator_set_id + 1,
+				len: next_authorities.len(),
+				root: next_authorities.root(),
+			},
+		})
 	}
 }
@@ -149,6 +149,65 @@ where
 		Ok(())
 	}
 
+	/// Strips the justification down to what `voters` need to accept it: just enough precommits
+	/// to reach the supermajority threshold, preferring those that vote for the commit target
+	/// directly, and only the ancestry headers that the remaining precommits route through.
+	/// The justification is left untouched if the threshold can't be reached.
+	pub fn prune(&mut self, voters: &VoterSet<AuthorityId>) -> Result<(), error::Error> {
+		use finality_grandpa::Chain;
+
+		let target_hash = self.commit.target_hash;
+		let mut precommits = self.commit.precommits.clone();
+		// the sort is stable, so precommits for the same kind of target keep their order.
+		precommits.sort_by_key(|signed| signed.precommit.target_hash != target_hash);
+
+		let threshold = voters.threshold().get();
+		let mut weight = 0u64;
+		let mut seen_voters = BTreeSet::new();
+		let mut kept = vec![];
+		for signed in precommits {
+			if weight >= threshold {
+				break
+			}
+			let Some(info) = voters.get(&signed.id) else { continue };
+			if !seen_voters.insert(signed.id.clone()) {
+				continue
+			}
+			weight = weight.saturating_add(info.weight().get());
+			kept.push(signed);
+		}
+
+		if weight < threshold {
+			Err(anyhow!("precommits in grandpa justification don't reach the voters' threshold"))?
+		}
+
+		let base_hash = kept
+			.iter()
+			.map(|signed| &signed.precommit)
+			.min_by_key(|precommit| precommit.target_number)
+			.map(|precommit| precommit.target_hash)
+			.ok_or_else(|| anyhow!("grandpa justification has no precommits"))?;
+
+		let ancestry_chain = AncestryChain::<H>::new(&self.votes_ancestries);
+		let mut visited_hashes = BTreeSet::new();
+		for signed in kept.iter() {
+			if base_hash == signed.precommit.target_hash {
+				continue
+			}
+
+			let route = ancestry_chain
+				.ancestry(base_hash, signed.precommit.target_hash)
+				.map_err(|_| anyhow!("[prune] Invalid ancestry!"))?;
+			visited_hashes.insert(signed.precommit.target_hash);
+			visited_hashes.extend(route);
+		}
+
+		self.votes_ancestries.retain(|header| visited_hashes.contains(&header.hash()));
+		self.commit.precommits = kept;
+
+		Ok(())
+	}
+
 	/// The target block number and hash that this justifications proves finality for.
 	pub fn target(&self) -> (H::Number, H::Hash) {
 		(self.commit.target_number, self.commit.target_hash)
@@ -343,4 +402,69 @@ mod tests {
 
 		assert_eq!(route, expected);
 	}
+
+	#[test]
+	fn test_prune_keeps_threshold_of_direct_precommits() {
+		type RelayHeader = Header<u32, BlakeTwo256>;
+
+		let target = RelayHeader::new(
+			10,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		let mut child = RelayHeader::new(
+			11,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		child.parent_hash = target.hash();
+
+		let authorities = (0u8..4)
+			.map(|i| (AuthorityId::from(sp_core::ed25519::Public::from_raw([i; 32])), 1))
+			.collect::<AuthorityList>();
+		let voters = VoterSet::new(authorities.iter().cloned()).unwrap();
+
+		let precommits = authorities
+			.iter()
+			.enumerate()
+			.map(|(i, (id, _))| {
+				let header = if i == 0 { &child } else { &target };
+				finality_grandpa::SignedPrecommit {
+					precommit: finality_grandpa::Precommit {
+						target_hash: header.hash(),
+						target_number: *header.number(),
+					},
+					signature: AuthoritySignature::from(sp_core::ed25519::Signature::from_raw(
+						[0; 64],
+					)),
+					id: id.clone(),
+				}
+			})
+			.collect::<Vec<_>>();
+
+		let mut justification = GrandpaJustification::<RelayHeader> {
+			round: 1,
+			commit: finality_grandpa::Commit {
+				target_hash: target.hash(),
+				target_number: *target.number(),
+				precommits,
+			},
+			votes_ancestries: vec![child.clone(), target.clone()],
+		};
+
+		justification.prune(&voters).unwrap();
+
+		// 3 out of 4 voters is the threshold, and all of them vote for the target directly.
+		assert_eq!(justification.commit.precommits.len(), 3);
+		assert!(justification
+			.commit
+			.precommits
+			.iter()
+			.all(|signed| signed.precommit.target_hash == target.hash()));
+		assert!(justification.votes_ancestries.is_empty());
+	}
 }
@@ -0,0 +1,352 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Finality proofs of a simulated relay chain and parachain, signed by authority sets whose keys
+//! are derived from fixed seeds, so that the verifier can be tested without a live network.
+//!
+//! ```ignore
+//! let mut fixtures = GrandpaFixtures::new(AuthoritySet::new(0, 4), 2000);
+//! let client_state = fixtures.client_state();
+//! let proof = fixtures.finalize(3);
+//! let client_state = verify_parachain_headers_with_grandpa_finality_proof::<
+//! 	Header,
+//! 	HostFunctionsProvider,
+//! >(client_state, proof)?;
+//! ```
+
+use codec::{Compact, Encode};
+use primitives::{
+	parachain_header_storage_key, ClientState, FinalityProof, ParachainHeaderProofs,
+	ParachainHeadersWithFinalityProof,
+};
+use sp_consensus_grandpa::{
+	AuthorityId, AuthorityList, AuthoritySignature, ConsensusLog, ScheduledChange,
+	GRANDPA_ENGINE_ID,
+};
+use sp_core::{blake2_256, ed25519, Pair, H256};
+use sp_runtime::{
+	traits::{BlakeTwo256, Header as _},
+	Digest, DigestItem,
+};
+use sp_trie::{generate_trie_proof, LayoutV0, MemoryDB, TrieDBMutBuilder, TrieMut};
+use std::collections::BTreeMap;
+
+/// Header of the simulated relay chain and parachain.
+pub type Header = sp_runtime::generic::Header<u32, BlakeTwo256>;
+
+/// Justification of the simulated relay chain.
+pub type Justification = primitives::justification::GrandpaJustification<Header>;
+
+/// A GRANDPA authority set with equal weights, whose keys are derived from the set id.
+#[derive(Clone)]
+pub struct AuthoritySet {
+	/// Id of the set
+	pub set_id: u64,
+	/// Keys of the authorities
+	pub pairs: Vec<ed25519::Pair>,
+}
+
+impl AuthoritySet {
+	/// The set `set_id` of `size` authorities.
+	pub fn new(set_id: u64, size: usize) -> Self {
+		let pairs = (0..size as u64)
+			.map(|index| ed25519::Pair::from_seed(&blake2_256(&(set_id, index).encode())))
+			.collect();
+		Self { set_id, pairs }
+	}
+
+	/// Authorities of the set, as found in a client state.
+	pub fn authorities(&self) -> AuthorityList {
+		self.pairs.iter().map(|pair| (AuthorityId::from(pair.public()), 1)).collect()
+	}
+
+	/// Minimum number of precommits for a commit to be valid: more than two thirds of the set.
+	pub fn threshold(&self) -> usize {
+		let size = self.pairs.len();
+		size - size.saturating_sub(1) / 3
+	}
+}
+
+/// A deliberate defect of a finality proof, which the verifier must reject.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Defect {
+	/// A precommit signature is corrupted
+	InvalidSignature,
+	/// The precommits are signed for the next authority set id
+	WrongSetId,
+	/// One precommit less than the threshold is included
+	BelowThreshold,
+	/// A precommit is signed by a key outside of the authority set
+	UnknownAuthority,
+	/// A precommit is included twice
+	DuplicatePrecommit,
+	/// The justification finalizes the parent of the block the finality proof is for
+	TargetMismatch,
+	/// A header between the latest finalized block and the new one is missing
+	MissingAncestor,
+	/// The justification includes a vote ancestry that no precommit routes through
+	UnusedVoteAncestry,
+	/// The parachain headers are stored under the key of another parachain
+	WrongParaId,
+	/// The timestamp extrinsic doesn't match its proof
+	InvalidExtrinsicProof,
+	/// The latest parachain height doesn't match the proven parachain headers
+	WrongParaHeight,
+}
+
+impl Defect {
+	/// All the defects.
+	pub const ALL: [Defect; 11] = [
+		Defect::InvalidSignature,
+		Defect::WrongSetId,
+		Defect::BelowThreshold,
+		Defect::UnknownAuthority,
+		Defect::DuplicatePrecommit,
+		Defect::TargetMismatch,
+		Defect::MissingAncestor,
+		Defect::UnusedVoteAncestry,
+		Defect::WrongParaId,
+		Defect::InvalidExtrinsicProof,
+		Defect::WrongParaHeight,
+	];
+}
+
+/// A simulated relay chain finalized by GRANDPA, and a parachain producing a block in each relay
+/// chain block.
+#[derive(Clone)]
+pub struct GrandpaFixtures {
+	/// Current authority set
+	pub authorities: AuthoritySet,
+	/// Id of the parachain
+	pub para_id: u32,
+	/// Latest finalized relay chain header
+	pub finalized: Header,
+	/// Latest parachain header included in a finalized relay chain block
+	pub para_header: Header,
+	/// Round of the next justification
+	pub round: u64,
+	/// Authority set enacted by the next finalized block
+	next_authorities: Option<AuthoritySet>,
+}
+
+impl GrandpaFixtures {
+	/// Starts both chains at their genesis.
+	pub fn new(authorities: AuthoritySet, para_id: u32) -> Self {
+		let para_header = header(0, H256::zero(), H256::zero(), H256::zero(), Digest::default());
+		let finalized = header(0, H256::zero(), H256::zero(), H256::zero(), Digest::default());
+		Self { authorities, para_id, finalized, para_header, round: 1, next_authorities: None }
+	}
+
+	/// Client state of a light client which verified all the blocks finalized so far.
+	pub fn client_state(&self) -> ClientState {
+		ClientState {
+			current_authorities: self.authorities.authorities(),
+			current_set_id: self.authorities.set_id,
+			latest_relay_height: *self.finalized.number(),
+			latest_para_height: *self.para_header.number(),
+			latest_relay_hash: self.finalized.hash(),
+			para_id: self.para_id,
+		}
+	}
+
+	/// Makes the next finalized block enact the next authority set, of `size` authorities.
+	pub fn schedule_authority_set_change(&mut self, size: usize) {
+		self.next_authorities = Some(AuthoritySet::new(self.authorities.set_id + 1, size));
+	}
+
+	/// Produces `blocks` relay chain blocks and finalizes the last one, returns its finality proof
+	/// and the proofs of the parachain headers included in the new blocks.
+	pub fn finalize(&mut self, blocks: u32) -> ParachainHeadersWithFinalityProof<Header> {
+		self.prove_finality(blocks, None)
+	}
+
+	/// Same as [`Self::finalize`] with a `defect` in the proof, without finalizing any block.
+	pub fn finalize_invalid(
+		&self,
+		blocks: u32,
+		defect: Defect,
+	) -> ParachainHeadersWithFinalityProof<Header> {
+		self.clone().prove_finality(blocks, Some(defect))
+	}
+
+	fn prove_finality(
+		&mut self,
+		blocks: u32,
+		defect: Option<Defect>,
+	) -> ParachainHeadersWithFinalityProof<Header> {
+		assert!(blocks > 0, "at least one block must be finalized");
+		let para_id =
+			if defect == Some(Defect::WrongParaId) { self.para_id + 1 } else { self.para_id };
+
+		let mut unknown_headers = vec![];
+		let mut parachain_headers = BTreeMap::new();
+		let mut parent = self.finalized.clone();
+		for index in 0..blocks {
+			let extrinsic = timestamp_extrinsic(*self.para_header.number() + 1);
+			let extrinsic_key = Compact(0u64).encode();
+			let (extrinsics_root, extrinsics) = trie(&extrinsic_key, &extrinsic);
+			let para_header = header(
+				*self.para_header.number() + 1,
+				self.para_header.hash(),
+				H256::zero(),
+				extrinsics_root,
+				Digest::default(),
+			);
+			let (state_root, state) =
+				trie(&parachain_header_storage_key(para_id).0, &para_header.encode().encode());
+
+			let mut digest = Digest::default();
+			if let Some(next) = self.next_authorities.as_ref().filter(|_| index == blocks - 1) {
+				let change = ScheduledChange { next_authorities: next.authorities(), delay: 0 };
+				digest.push(DigestItem::Consensus(
+					GRANDPA_ENGINE_ID,
+					ConsensusLog::ScheduledChange(change).encode(),
+				));
+			}
+			let relay_header =
+				header(parent.number() + 1, parent.hash(), state_root, H256::zero(), digest);
+
+			let extrinsic_proof = generate_trie_proof::<LayoutV0<BlakeTwo256>, _, _, _>(
+				&extrinsics,
+				extrinsics_root,
+				&[extrinsic_key],
+			)
+			.expect("the extrinsic was just inserted");
+			let mut extrinsic = extrinsic;
+			if defect == Some(Defect::InvalidExtrinsicProof) {
+				extrinsic.push(0);
+			}
+			parachain_headers.insert(
+				relay_header.hash(),
+				ParachainHeaderProofs { state_proof: nodes(state), extrinsic, extrinsic_proof },
+			);
+			unknown_headers.push(relay_header.clone());
+			self.para_header = para_header;
+			parent = relay_header;
+		}
+
+		let target = parent;
+		let justification_target = match defect {
+			Some(Defect::TargetMismatch) =>
+				unknown_headers.iter().rev().nth(1).unwrap_or(&self.finalized).clone(),
+			_ => target.clone(),
+		};
+		let mut justification = self.justification(&justification_target, defect);
+		if defect == Some(Defect::UnusedVoteAncestry) {
+			justification.votes_ancestries.push(target.clone());
+		}
+		if defect == Some(Defect::MissingAncestor) {
+			unknown_headers.remove(0);
+		}
+
+		let mut latest_para_height = *self.para_header.number();
+		if defect == Some(Defect::WrongParaHeight) {
+			latest_para_height += 1;
+		}
+		self.finalized = target.clone();
+		self.round += 1;
+		if let Some(next) = self.next_authorities.take() {
+			self.authorities = next;
+		}
+
+		ParachainHeadersWithFinalityProof {
+			finality_proof: FinalityProof {
+				block: target.hash(),
+				justification: justification.encode(),
+				unknown_headers,
+			},
+			parachain_headers,
+			latest_para_height,
+		}
+	}
+
+	/// Justification of `target` by the current authority set, with the given defect.
+	fn justification(&self, target: &Header, defect: Option<Defect>) -> Justification {
+		let precommit = finality_grandpa::Precommit {
+			target_hash: target.hash(),
+			target_number: *target.number(),
+		};
+		let set_id = match defect {
+			Some(Defect::WrongSetId) => self.authorities.set_id + 1,
+			_ => self.authorities.set_id,
+		};
+		let message = (finality_grandpa::Message::Precommit(precommit.clone()), self.round, set_id);
+		let sign = |pair: &ed25519::Pair| finality_grandpa::SignedPrecommit {
+			precommit: precommit.clone(),
+			signature: AuthoritySignature::from(pair.sign(&message.encode())),
+			id: AuthorityId::from(pair.public()),
+		};
+
+		let signers = match defect {
+			Some(Defect::BelowThreshold) => self.authorities.threshold() - 1,
+			_ => self.authorities.pairs.len(),
+		};
+		let mut precommits =
+			self.authorities.pairs.iter().take(signers).map(sign).collect::<Vec<_>>();
+		match defect {
+			Some(Defect::InvalidSignature) => {
+				let mut signature = precommits[0].signature.as_ref().0;
+				signature[0] ^= 1;
+				precommits[0].signature =
+					AuthoritySignature::from(ed25519::Signature::from_raw(signature));
+			},
+			Some(Defect::UnknownAuthority) =>
+				precommits[0] = sign(&ed25519::Pair::from_seed(&blake2_256(b"unknown authority"))),
+			Some(Defect::DuplicatePrecommit) => precommits.push(precommits[0].clone()),
+			_ => {},
+		}
+
+		Justification {
+			round: self.round,
+			commit: finality_grandpa::Commit {
+				target_hash: target.hash(),
+				target_number: *target.number(),
+				precommits,
+			},
+			votes_ancestries: vec![],
+		}
+	}
+}
+
+fn header(
+	number: u32,
+	parent_hash: H256,
+	state_root: H256,
+	extrinsics_root: H256,
+	digest: Digest,
+) -> Header {
+	Header::new(number, extrinsics_root, state_root, parent_hash, digest)
+}
+
+/// An encoded `timestamp.set` call, which is never decoded, unique to the parachain height.
+fn timestamp_extrinsic(para_height: u32) -> Vec<u8> {
+	(b"timestamp.set", Compact(u64::from(para_height) * 12_000)).encode()
+}
+
+/// A trie holding a single `value` at `key`, and its root.
+fn trie(key: &[u8], value: &[u8]) -> (H256, MemoryDB<BlakeTwo256>) {
+	let mut db = MemoryDB::<BlakeTwo256>::default();
+	let mut root = H256::default();
+	{
+		let mut trie = TrieDBMutBuilder::<LayoutV0<BlakeTwo256>>::new(&mut db, &mut root).build();
+		trie.insert(key, value).expect("inserting into an in-memory trie can't fail");
+	}
+	(root, db)
+}
+
+/// All the nodes of a trie, which prove any of its values.
+fn nodes(mut db: MemoryDB<BlakeTwo256>) -> Vec<Vec<u8>> {
+	db.drain().into_values().map(|(node, _)| node).collect()
+}
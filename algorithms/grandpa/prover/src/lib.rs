@@ -51,6 +51,8 @@ pub const PROCESS_BLOCKS_BATCH_SIZE: usize = 100;
 
 /// Host function implementation for the verifier
 pub mod host_functions;
+/// Finality proofs of a simulated relay chain, for tests
+pub mod fixtures;
 
 /// Contains methods useful for proving parachain header finality using GRANDPA
 pub struct GrandpaProver<T: Config> {
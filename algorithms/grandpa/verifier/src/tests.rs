@@ -17,7 +17,10 @@ use crate::verify_parachain_headers_with_grandpa_finality_proof;
 use codec::{Decode, Encode};
 use futures::StreamExt;
 use grandpa_prover::{
-	beefy_prover::helpers::unsafe_arc_cast, host_functions::HostFunctionsProvider, GrandpaProver,
+	beefy_prover::helpers::unsafe_arc_cast,
+	fixtures::{AuthoritySet, Defect, GrandpaFixtures},
+	host_functions::HostFunctionsProvider,
+	GrandpaProver,
 };
 use hyperspace_core::substrate::DefaultConfig as PolkadotConfig;
 use polkadot_core_primitives::Header;
@@ -151,3 +154,69 @@ async fn follow_grandpa_justifications() {
 		println!("========= Successfully verified grandpa justification =========");
 	}
 }
+
+fn verify(
+	client_state: primitives::ClientState,
+	proof: ParachainHeadersWithFinalityProof<Header>,
+) -> Result<primitives::ClientState, primitives::error::Error> {
+	verify_parachain_headers_with_grandpa_finality_proof::<Header, HostFunctionsProvider>(
+		client_state,
+		proof,
+	)
+}
+
+#[test]
+fn verifies_generated_finality_proofs() {
+	let mut fixtures = GrandpaFixtures::new(AuthoritySet::new(0, 4), 2000);
+	let mut client_state = fixtures.client_state();
+
+	for blocks in [1, 3, 5] {
+		let proof = fixtures.finalize(blocks);
+		client_state = verify(client_state, proof).unwrap();
+		assert_eq!(client_state.latest_relay_hash, fixtures.finalized.hash());
+		assert_eq!(client_state.latest_relay_height, fixtures.finalized.number);
+		assert_eq!(client_state.latest_para_height, fixtures.para_header.number);
+	}
+	assert_eq!(client_state.latest_relay_height, 9);
+	assert_eq!(client_state.latest_para_height, 9);
+
+	// the block enacting the change is finalized by the current set, the next ones by the new set
+	fixtures.schedule_authority_set_change(7);
+	client_state = verify(client_state, fixtures.finalize(2)).unwrap();
+	assert_eq!(client_state.current_set_id, 1);
+	assert_eq!(client_state.current_authorities, fixtures.authorities.authorities());
+	client_state = verify(client_state, fixtures.finalize(1)).unwrap();
+	assert_eq!(client_state.latest_relay_height, 12);
+}
+
+#[test]
+fn rejects_defective_finality_proofs() {
+	let mut fixtures = GrandpaFixtures::new(AuthoritySet::new(0, 7), 2000);
+	let client_state = verify(fixtures.client_state(), fixtures.finalize(2)).unwrap();
+
+	for defect in Defect::ALL {
+		for blocks in [1, 3] {
+			let proof = fixtures.finalize_invalid(blocks, defect);
+			assert!(
+				verify(client_state.clone(), proof).is_err(),
+				"proof of {blocks} blocks with {defect:?} was accepted"
+			);
+		}
+	}
+
+	// the defective proofs didn't finalize anything
+	verify(client_state, fixtures.finalize(3)).unwrap();
+}
+
+#[test]
+fn rejects_finality_proofs_of_a_previous_authority_set() {
+	let mut fixtures = GrandpaFixtures::new(AuthoritySet::new(0, 4), 2000);
+	let client_state = fixtures.client_state();
+	fixtures.schedule_authority_set_change(4);
+	let client_state = verify(client_state, fixtures.finalize(1)).unwrap();
+
+	let mut stale = fixtures.clone();
+	stale.authorities = AuthoritySet::new(0, 4);
+	assert!(verify(client_state.clone(), stale.finalize(1)).is_err());
+	verify(client_state, fixtures.finalize(1)).unwrap();
+}
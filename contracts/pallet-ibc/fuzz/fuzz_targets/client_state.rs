@@ -0,0 +1,20 @@
+//! Decoding of client states, e.g. the ones submitted by a counterparty in connection handshakes.
+
+#![no_main]
+
+use ibc_proto::google::protobuf::Any;
+use libfuzzer_sys::fuzz_target;
+use pallet_ibc::light_clients::AnyClientState;
+use pallet_ibc_fuzz::{any_of, CLIENT_STATE_TYPE_URLS};
+use prost::Message;
+
+fuzz_target!(|data: &[u8]| {
+	if let Ok(any) = Any::decode(data) {
+		let _ = AnyClientState::try_from(any);
+	}
+	if let Some(any) = any_of(CLIENT_STATE_TYPE_URLS, data) {
+		let _ = AnyClientState::try_from(any.clone());
+		// wasm client states wrap the encoding of another client state
+		let _ = AnyClientState::decode_recursive(any, |_| true);
+	}
+});
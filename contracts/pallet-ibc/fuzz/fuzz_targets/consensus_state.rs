@@ -0,0 +1,19 @@
+//! Decoding of consensus states, e.g. the ones submitted by a counterparty in connection
+//! handshakes.
+
+#![no_main]
+
+use ibc_proto::google::protobuf::Any;
+use libfuzzer_sys::fuzz_target;
+use pallet_ibc::light_clients::AnyConsensusState;
+use pallet_ibc_fuzz::{any_of, CONSENSUS_STATE_TYPE_URLS};
+use prost::Message;
+
+fuzz_target!(|data: &[u8]| {
+	if let Ok(any) = Any::decode(data) {
+		let _ = AnyConsensusState::try_from(any);
+	}
+	if let Some(any) = any_of(CONSENSUS_STATE_TYPE_URLS, data) {
+		let _ = AnyConsensusState::try_from(any);
+	}
+});
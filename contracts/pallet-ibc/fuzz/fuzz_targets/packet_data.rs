@@ -0,0 +1,23 @@
+//! Decoding of the data of the packets received from a counterparty by the applications of the
+//! pallet.
+
+#![no_main]
+
+use ibc::applications::{
+	interchain_accounts::packet::InterchainAccountPacketData, nft_transfer, transfer,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+	if let Ok(packet_data) = serde_json::from_slice::<transfer::packet::PacketData>(data) {
+		let _ = packet_data.validate_memo();
+	}
+	if let Ok(packet_data) = serde_json::from_slice::<nft_transfer::packet::PacketData>(data) {
+		if packet_data.validate_basic().is_ok() {
+			let _ = packet_data.tokens().count();
+		}
+	}
+	if let Ok(packet_data) = InterchainAccountPacketData::from_bytes(data) {
+		let _ = packet_data.messages();
+	}
+});
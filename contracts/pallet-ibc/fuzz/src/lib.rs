@@ -0,0 +1,28 @@
+//! Helpers shared by the fuzz targets.
+
+use ibc_proto::google::protobuf::Any;
+
+/// Type urls of the client states the pallet decodes.
+pub const CLIENT_STATE_TYPE_URLS: &[&str] = &[
+	ics07_tendermint::client_state::TENDERMINT_CLIENT_STATE_TYPE_URL,
+	ics08_wasm::client_state::WASM_CLIENT_STATE_TYPE_URL,
+	ics10_grandpa::client_state::GRANDPA_CLIENT_STATE_TYPE_URL,
+	ics11_beefy::client_state::BEEFY_CLIENT_STATE_TYPE_URL,
+];
+
+/// Type urls of the consensus states the pallet decodes.
+pub const CONSENSUS_STATE_TYPE_URLS: &[&str] = &[
+	ics07_tendermint::consensus_state::TENDERMINT_CONSENSUS_STATE_TYPE_URL,
+	ics08_wasm::consensus_state::WASM_CONSENSUS_STATE_TYPE_URL,
+	ics10_grandpa::consensus_state::GRANDPA_CONSENSUS_STATE_TYPE_URL,
+	ics11_beefy::consensus_state::BEEFY_CONSENSUS_STATE_TYPE_URL,
+];
+
+/// An `Any` of one of the given type urls, picked by the first byte of the input, with the rest
+/// of the input as its value. Random type urls are almost never known, so without this the
+/// fuzzer would hardly get past the lookup of the type url.
+pub fn any_of(type_urls: &[&str], data: &[u8]) -> Option<Any> {
+	let (selector, value) = data.split_first()?;
+	let type_url = type_urls[*selector as usize % type_urls.len()];
+	Some(Any { type_url: type_url.to_string(), value: value.to_vec() })
+}
@@ -6,7 +6,13 @@ use alloc::string::{String, ToString};
 use codec::{Decode, Encode};
 use frame_support::{weights::Weight, RuntimeDebug};
 use ibc::{
-	applications::transfer::{error::Error as Ics20Error, PrefixedCoin, VERSION},
+	applications::{
+		cross_chain_queries::{
+			msgs::MsgSubmitCrossChainQuery,
+			query::{QueryId, QueryResult},
+		},
+		transfer::{error::Error as Ics20Error, PrefixedCoin, VERSION},
+	},
 	core::{
 		ics04_channel::{
 			channel::{ChannelEnd, Order},
@@ -187,6 +193,14 @@ pub struct QueryClientStateResponse {
 	pub trie_key: Vec<u8>,
 }
 
+/// A page of the clients on chain
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo)]
+pub struct QueryClientsResponse {
+	pub clients: Vec<IdentifiedClientState>,
+	/// Key to query the next page with, if there are more clients
+	pub next_key: Option<Vec<u8>>,
+}
+
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo)]
 pub struct QueryClientStatesResponse {
 	pub client_states: Vec<Vec<u8>>,
@@ -221,12 +235,30 @@ pub struct QueryChannelResponse {
 pub struct QueryChannelsResponse {
 	pub channels: Vec<IdentifiedChannel>,
 	pub height: u64,
+	/// Key to query the next page with, if there are more channels
+	pub next_key: Option<Vec<u8>>,
 }
 
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo)]
 pub struct QueryConnectionsResponse {
 	pub connections: Vec<IdentifiedConnection>,
 	pub height: u64,
+	/// Key to query the next page with, if there are more connections
+	pub next_key: Option<Vec<u8>>,
+}
+
+/// [`QueryChannelsResponse`] of the runtime apis older than the version 2, without pagination
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo)]
+pub struct QueryChannelsResponseV1 {
+	pub channels: Vec<IdentifiedChannel>,
+	pub height: u64,
+}
+
+/// [`QueryConnectionsResponse`] of the runtime apis older than the version 2, without pagination
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo)]
+pub struct QueryConnectionsResponseV1 {
+	pub connections: Vec<IdentifiedConnection>,
+	pub height: u64,
 }
 
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo)]
@@ -282,6 +314,15 @@ pub struct QueryDenomTraceResponse {
 	pub denom: Vec<u8>,
 }
 
+/// Amount of a denom accounted as escrowed by ics20, along with the balance actually held by the
+/// escrow accounts, which should never be lower
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo)]
+pub struct EscrowBalance {
+	pub denom: Vec<u8>,
+	pub total_escrow: u128,
+	pub escrow_balance: u128,
+}
+
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo)]
 pub struct QueryDenomTracesResponse {
 	pub denoms: Vec<Vec<u8>>,
@@ -331,6 +372,10 @@ pub enum Error {
 	DecodingError { msg: Option<String> },
 	/// Failed to decode commitment prefix
 	ErrorDecodingPrefix,
+	/// Failed to submit or resolve a cross-chain query
+	CrossChainQueryError { msg: Option<String> },
+	/// Transfer exceeds a rate limit
+	RateLimitExceeded { msg: Option<String> },
 	/// Some other error
 	Other { msg: Option<String> },
 }
@@ -355,6 +400,7 @@ impl TryFrom<&OpenChannelParams> for Order {
 		match value.order {
 			1 => Ok(Order::Unordered),
 			2 => Ok(Order::Ordered),
+			3 => Ok(Order::OrderedAllowTimeout),
 			_ => Err(Error::Other { msg: None }),
 		}
 	}
@@ -371,6 +417,10 @@ pub trait IbcHandler<AccountId> {
 	) -> Result<(Height, Timestamp), Error>;
 	/// Handle a message
 	fn handle_message(msg: HandlerMessage<AccountId>) -> Result<(), Error>;
+	/// Submit a cross-chain query for relayers to answer, returns the query identifier
+	fn submit_cross_chain_query(msg: MsgSubmitCrossChainQuery) -> Result<QueryId, Error>;
+	/// Result of a cross-chain query, `None` while the query is still pending
+	fn cross_chain_query_result(query_id: QueryId) -> Result<Option<QueryResult>, Error>;
 	/// testing related methods
 	#[cfg(feature = "runtime-benchmarks")]
 	fn create_client() -> Result<ClientId, Error>;
@@ -24,11 +24,12 @@ use ibc_proto::{
 				QueryPacketCommitmentsResponse, QueryPacketReceiptResponse,
 			},
 			client::v1::{
-				Height, IdentifiedClientState, QueryClientStateResponse,
+				Height, IdentifiedClientState, QueryClientStateResponse, QueryClientStatesResponse,
 				QueryConsensusStateResponse,
 			},
 			connection::v1::{
-				IdentifiedConnection, QueryConnectionResponse, QueryConnectionsResponse,
+				IdentifiedConnection, Params as ConnectionParams, QueryConnectionParamsResponse,
+				QueryConnectionResponse, QueryConnectionsResponse,
 			},
 		},
 	},
@@ -47,7 +48,7 @@ use pallet_ibc::{
 use sc_chain_spec::Properties;
 use sc_client_api::{BlockBackend, ProofProvider};
 use serde::{Deserialize, Serialize};
-use sp_api::ProvideRuntimeApi;
+use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_core::{blake2_256, storage::ChildInfo};
 use sp_runtime::{
@@ -136,6 +137,59 @@ pub struct PacketInfo {
 	pub ack: Option<Vec<u8>>,
 }
 
+/// Escrow invariant of a denom
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct EscrowInvariant {
+	/// Denom
+	pub denom: String,
+	/// Amount accounted as escrowed by ics20
+	pub total_escrow: String,
+	/// Balance held by the escrow accounts
+	pub escrow_balance: String,
+	/// Whether the escrow accounts hold at least the amount accounted as escrowed
+	pub holds: bool,
+}
+
+/// Maximum number of blocks `ibc_queryPacketEvents` scans in a single call
+pub const MAX_PACKET_EVENTS_RANGE: u32 = 1000;
+
+/// Maximum number of sets of keys `ibc_queryProofs` proves in a single call
+pub const MAX_PROOFS_PER_QUERY: usize = 256;
+
+/// Selects the packet events returned by `ibc_queryPacketEvents`, empty fields don't filter.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacketEventsFilter {
+	/// Local `(channel_id, port_id)` of the packets: the source channel for sent, acknowledged and
+	/// timed out packets, the destination channel for received packets and written
+	/// acknowledgements
+	pub channels: Vec<(String, String)>,
+	/// Packet sequences
+	pub sequences: Vec<u64>,
+	/// Event types, e.g. `send_packet`
+	pub event_types: Vec<String>,
+}
+
+impl PacketEventsFilter {
+	/// Whether `event` is a packet event selected by the filter.
+	pub fn matches(&self, event: &RawIbcEvent) -> bool {
+		let Some(packet) = event.packet() else { return false };
+		let (channel_id, port_id) = match event {
+			RawIbcEvent::ReceivePacket(_) | RawIbcEvent::WriteAcknowledgement(_) =>
+				(&packet.destination_channel, &packet.destination_port),
+			_ => (&packet.source_channel, &packet.source_port),
+		};
+		let channel_matches = self.channels.is_empty() ||
+			self.channels.iter().any(|(channel, port)| {
+				*channel == channel_id.to_string() && *port == port_id.to_string()
+			});
+		let sequence_matches =
+			self.sequences.is_empty() || self.sequences.contains(&u64::from(packet.sequence));
+		let event_type_matches = self.event_types.is_empty() ||
+			self.event_types.iter().any(|ty| ty == event.event_type().as_str());
+		channel_matches && sequence_matches && event_type_matches
+	}
+}
+
 impl TryFrom<RawPacketInfo> for PacketInfo {
 	type Error = ();
 
@@ -197,6 +251,11 @@ where
 	#[method(name = "ibc_queryProof")]
 	fn query_proof(&self, height: u32, keys: Vec<Vec<u8>>) -> Result<Proof>;
 
+	/// Generate a proof for every set of keys, all at the same height, at most
+	/// [`MAX_PROOFS_PER_QUERY`] sets at a time
+	#[method(name = "ibc_queryProofs")]
+	fn query_proofs(&self, height: u32, keys: Vec<Vec<Vec<u8>>>) -> Result<Vec<Proof>>;
+
 	/// Query latest height
 	#[method(name = "ibc_queryLatestHeight")]
 	fn query_latest_height(&self) -> Result<BlockNumber>;
@@ -238,9 +297,14 @@ where
 	#[method(name = "ibc_queryUpgradedConnectionState")]
 	fn query_upgraded_cons_state(&self, height: u32) -> Result<QueryConsensusStateResponse>;
 
-	/// Query all client states
+	/// Query at most `limit` client states, starting at the `next_key` of the pagination of a
+	/// previous response. All the client states are returned without a limit.
 	#[method(name = "ibc_queryClients")]
-	fn query_clients(&self) -> Result<Vec<IdentifiedClientState>>;
+	fn query_clients(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<QueryClientStatesResponse>;
 
 	/// Query a connection state
 	#[method(name = "ibc_queryConnection")]
@@ -252,7 +316,15 @@ where
 
 	/// Query all connection states
 	#[method(name = "ibc_queryConnections")]
-	fn query_connections(&self) -> Result<QueryConnectionsResponse>;
+	fn query_connections(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<QueryConnectionsResponse>;
+
+	/// Query the parameters of the connection submodule
+	#[method(name = "ibc_queryConnectionParams")]
+	fn query_connection_params(&self) -> Result<QueryConnectionParamsResponse>;
 
 	/// Query all connection states for associated client
 	#[method(name = "ibc_queryConnectionUsingClient")]
@@ -289,17 +361,26 @@ where
 		port_id: String,
 	) -> Result<IdentifiedClientState>;
 
-	/// Query all channel states for associated connection
+	/// Query at most `limit` channel states for associated connection, starting at the `next_key`
+	/// of the pagination of a previous response. All the channel states are returned without a
+	/// limit.
 	#[method(name = "ibc_queryConnectionChannels")]
 	fn query_connection_channels(
 		&self,
 		height: u32,
 		connection_id: String,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
 	) -> Result<QueryChannelsResponse>;
 
-	/// Query all channel states
+	/// Query at most `limit` channel states, starting at the `next_key` of the pagination of a
+	/// previous response. All the channel states are returned without a limit.
 	#[method(name = "ibc_queryChannels")]
-	fn query_channels(&self) -> Result<QueryChannelsResponse>;
+	fn query_channels(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<QueryChannelsResponse>;
 
 	/// Query packet commitments
 	#[method(name = "ibc_queryPacketCommitments")]
@@ -404,6 +485,25 @@ where
 	#[method(name = "ibc_queryDenomTrace")]
 	fn query_denom_trace(&self, asset_id: AssetId) -> Result<QueryDenomTraceResponse>;
 
+	/// Query the ICS-29 fees escrowed for the packet `seq` sent on the channel, as protobuf encoded
+	/// `PacketFees`, empty if no fee was escrowed for the packet
+	#[method(name = "ibc_queryPacketFees")]
+	fn query_packet_fees(&self, channel_id: String, port_id: String, seq: u64) -> Result<Vec<u8>>;
+
+	/// Query the amount of a denom accounted as escrowed by the transfer app
+	#[method(name = "ibc_queryTotalEscrow")]
+	fn query_total_escrow(&self, denom: String) -> Result<Coin>;
+
+	/// Check for every escrowed denom that the escrow accounts hold at least the amount
+	/// accounted as escrowed by the transfer app
+	#[method(name = "ibc_queryEscrowInvariants")]
+	fn query_escrow_invariants(&self) -> Result<Vec<EscrowInvariant>>;
+
+	/// Query the denom trace of a voucher from the hash it is referred to by, either as
+	/// `ibc/{hash}` or just the hash
+	#[method(name = "ibc_queryDenomTraceByHash")]
+	fn query_denom_trace_by_hash(&self, denom_hash: String) -> Result<QueryDenomTraceResponse>;
+
 	/// Query the denom traces for ibc denoms
 	/// key is the asset id from which to start paginating results
 	/// The next_key value in the pagination field of the returned result is a scale encoded u128
@@ -418,6 +518,16 @@ where
 		count_total: bool,
 	) -> Result<QueryDenomTracesResponse>;
 
+	/// Query the protobuf encoded cross-chain queries waiting for a relayer to answer them
+	#[method(name = "ibc_queryCrossChainQueries")]
+	fn query_cross_chain_queries(&self) -> Result<Vec<Vec<u8>>>;
+
+	/// Query the value stored in the ibc store at the given path with a proof of its membership,
+	/// or of its absence if there's no value
+	#[method(name = "ibc_queryValueWithProof")]
+	fn query_value_with_proof(&self, height: u32, path: String)
+		-> Result<(Option<Vec<u8>>, Proof)>;
+
 	/// Query newly created client in block and extrinsic
 	#[method(name = "ibc_queryNewlyCreatedClient")]
 	fn query_newly_created_client(
@@ -449,6 +559,16 @@ where
 		&self,
 		block_numbers: Vec<BlockNumberOrHash<Hash>>,
 	) -> Result<HashMap<String, Vec<RawIbcEvent>>>;
+
+	/// Query the packet events deposited in the blocks `from_height..=to_height` that match the
+	/// filter, at most [`MAX_PACKET_EVENTS_RANGE`] blocks at a time
+	#[method(name = "ibc_queryPacketEvents")]
+	fn query_packet_events(
+		&self,
+		from_height: u32,
+		to_height: u32,
+		filter: PacketEventsFilter,
+	) -> Result<Vec<RawIbcEvent>>;
 }
 
 /// Converts a runtime trap into an RPC error.
@@ -460,6 +580,30 @@ fn runtime_error_into_rpc_error(e: impl std::fmt::Display) -> RpcError {
 	)))
 }
 
+/// Pagination of a response whose next page starts at `next_key`, if there is one.
+fn page_response(next_key: Option<Vec<u8>>) -> Option<PageResponse> {
+	next_key.map(|next_key| PageResponse { next_key, total: 0 })
+}
+
+/// Takes at most `limit` of the keyed `entries` from the one whose key is `next_key`, along with
+/// the key of the entry following them, the way the runtime paginates the queries since the
+/// version 2 of its api. Runtimes older than that return all the entries at once.
+fn paginate<E>(
+	entries: impl Iterator<Item = (Vec<u8>, E)>,
+	next_key: Option<Vec<u8>>,
+	limit: Option<u64>,
+) -> Result<(Vec<E>, Option<Vec<u8>>)> {
+	let mut entries = entries
+		.skip_while(|(key, _)| next_key.as_ref().map_or(false, |next_key| key != next_key))
+		.peekable();
+	if next_key.is_some() && entries.peek().is_none() {
+		return Err(runtime_error_into_rpc_error("The next key isn't the key of an entry"))
+	}
+	let limit = limit.map_or(usize::MAX, |limit| limit.try_into().unwrap_or(usize::MAX));
+	let page = entries.by_ref().take(limit).map(|(_, entry)| entry).collect();
+	Ok((page, entries.next().map(|(key, _)| key)))
+}
+
 /// An implementation of IBC specific RPC methods.
 pub struct IbcRpcHandler<C, B, AssetId> {
 	client: Arc<C>,
@@ -634,7 +778,19 @@ where
 		})
 	}
 
-	fn query_proof(&self, height: u32, mut keys: Vec<Vec<u8>>) -> Result<Proof> {
+	fn query_proof(&self, height: u32, keys: Vec<Vec<u8>>) -> Result<Proof> {
+		self.query_proofs(height, vec![keys])?
+			.pop()
+			.ok_or_else(|| runtime_error_into_rpc_error("Failed to generate proof"))
+	}
+
+	fn query_proofs(&self, height: u32, keys: Vec<Vec<Vec<u8>>>) -> Result<Vec<Proof>> {
+		if keys.len() > MAX_PROOFS_PER_QUERY {
+			return Err(RpcError::Custom(format!(
+				"{} sets of keys exceed the {MAX_PROOFS_PER_QUERY} proofs of a single query",
+				keys.len()
+			)))
+		}
 		let api = self.client.runtime_api();
 		let at = BlockId::Number(height.into());
 		let hash_at = self
@@ -649,17 +805,28 @@ where
 			.child_trie_key(hash_at)
 			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
 		let child_info = ChildInfo::new_default(&child_trie_key);
-		let proof = self
-			.client
-			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
-			.map_err(runtime_error_into_rpc_error)?
-			.iter_nodes()
-			.collect::<Vec<_>>()
-			.encode();
-		Ok(Proof {
-			proof,
-			height: Height { revision_number: para_id.into(), revision_height: height as u64 },
-		})
+		keys.into_iter()
+			.map(|mut keys| {
+				let proof = self
+					.client
+					.read_child_proof(
+						hash_at,
+						&child_info,
+						&mut keys.iter_mut().map(|nodes| &nodes[..]),
+					)
+					.map_err(runtime_error_into_rpc_error)?
+					.iter_nodes()
+					.collect::<Vec<_>>()
+					.encode();
+				Ok(Proof {
+					proof,
+					height: Height {
+						revision_number: para_id.into(),
+						revision_height: height as u64,
+					},
+				})
+			})
+			.collect()
 	}
 
 	fn query_latest_height(&self) -> Result<<<Block as BlockT>::Header as HeaderT>::Number> {
@@ -804,28 +971,27 @@ where
 		Err(runtime_error_into_rpc_error("Unimplemented"))
 	}
 
-	fn query_clients(&self) -> Result<Vec<IdentifiedClientState>> {
-		let api = self.client.runtime_api();
-
-		let client_states: Option<Vec<(Vec<u8>, Vec<u8>)>> =
-			api.clients(self.client.info().best_hash).ok().flatten();
-		match client_states {
-			Some(client_states) => client_states
-				.into_iter()
-				.map(|(client_id, client_state)| {
-					let client_state = AnyClientState::decode_vec(&client_state).map_err(|_| {
-						runtime_error_into_rpc_error("Failed to decode client state")
-					})?;
-					Ok(IdentifiedClientState {
-						client_id: String::from_utf8(client_id).map_err(|_| {
-							runtime_error_into_rpc_error("Failed to decode client id")
-						})?,
-						client_state: Some(client_state.into()),
-					})
+	fn query_clients(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<QueryClientStatesResponse> {
+		let result = self.clients_page(self.client.info().best_hash, next_key, limit)?;
+		let client_states = result
+			.clients
+			.into_iter()
+			.map(|client| {
+				let client_state = AnyClientState::decode_vec(&client.client_state)
+					.map_err(|_| runtime_error_into_rpc_error("Failed to decode client state"))?;
+				Ok(IdentifiedClientState {
+					client_id: String::from_utf8(client.client_id)
+						.map_err(|_| runtime_error_into_rpc_error("Failed to decode client id"))?,
+					client_state: Some(client_state.into()),
 				})
-				.collect(),
-			_ => Err(runtime_error_into_rpc_error("Failed to fetch client states")),
-		}
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		Ok(QueryClientStatesResponse { client_states, pagination: page_response(result.next_key) })
 	}
 
 	fn query_connection(
@@ -874,7 +1040,11 @@ where
 		})
 	}
 
-	fn query_connections(&self) -> Result<QueryConnectionsResponse> {
+	fn query_connections(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<QueryConnectionsResponse> {
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Hash(self.client.info().best_hash);
@@ -883,11 +1053,7 @@ where
 			.block_hash_from_id(&at)
 			.map_err(|_| RpcError::Custom("Unknown block".into()))?
 			.ok_or_else(|| RpcError::Custom("Unknown block".into()))?;
-		let result: ibc_primitives::QueryConnectionsResponse = api
-			.connections(hash_at)
-			.ok()
-			.flatten()
-			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch connections"))?;
+		let result = self.connections_page(hash_at, next_key, limit)?;
 		let para_id = api
 			.para_id(hash_at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
@@ -914,7 +1080,7 @@ where
 			.collect::<Result<Vec<_>>>()?;
 		Ok(QueryConnectionsResponse {
 			connections,
-			pagination: None,
+			pagination: page_response(result.next_key),
 			height: Some(ibc_proto::ibc::core::client::v1::Height {
 				revision_number: para_id.into(),
 				revision_height: result.height,
@@ -922,6 +1088,22 @@ where
 		})
 	}
 
+	fn query_connection_params(&self) -> Result<QueryConnectionParamsResponse> {
+		let api = self.client.runtime_api();
+		let at = self.client.info().best_hash;
+		if self.api_version(at)? < 2 {
+			return Err(runtime_error_into_rpc_error(
+				"The connection params are only exposed since the version 2 of the ibc runtime api",
+			))
+		}
+		let max_expected_time_per_block = api
+			.max_expected_time_per_block(at)
+			.map_err(|_| runtime_error_into_rpc_error("Failed to fetch connection params"))?;
+		Ok(QueryConnectionParamsResponse {
+			params: Some(ConnectionParams { max_expected_time_per_block }),
+		})
+	}
+
 	fn query_connection_using_client(
 		&self,
 		height: u32,
@@ -1096,6 +1278,8 @@ where
 		&self,
 		height: u32,
 		connection_id: String,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
 	) -> Result<QueryChannelsResponse> {
 		let api = self.client.runtime_api();
 
@@ -1108,13 +1292,8 @@ where
 		let para_id = api
 			.para_id(hash_at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
-		let result: ibc_primitives::QueryChannelsResponse = api
-			.connection_channels(hash_at, connection_id.as_bytes().to_vec())
-			.ok()
-			.flatten()
-			.ok_or_else(|| {
-				runtime_error_into_rpc_error("Failed to fetch channels state for connection")
-			})?;
+		let result =
+			self.channels_page(hash_at, Some(connection_id.as_bytes().to_vec()), next_key, limit)?;
 		let channels = result
 			.channels
 			.into_iter()
@@ -1141,7 +1320,7 @@ where
 
 		Ok(QueryChannelsResponse {
 			channels,
-			pagination: None,
+			pagination: page_response(result.next_key),
 			height: Some(ibc_proto::ibc::core::client::v1::Height {
 				revision_number: para_id.into(),
 				revision_height: result.height,
@@ -1149,7 +1328,11 @@ where
 		})
 	}
 
-	fn query_channels(&self) -> Result<QueryChannelsResponse> {
+	fn query_channels(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<QueryChannelsResponse> {
 		let api = self.client.runtime_api();
 		let at = BlockId::Hash(self.client.info().best_hash);
 		let hash_at = self
@@ -1160,11 +1343,7 @@ where
 		let para_id = api
 			.para_id(hash_at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
-		let result: ibc_primitives::QueryChannelsResponse = api
-			.channels(hash_at)
-			.ok()
-			.flatten()
-			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch channels"))?;
+		let result = self.channels_page(hash_at, None, next_key, limit)?;
 		let channels = result
 			.channels
 			.into_iter()
@@ -1191,7 +1370,7 @@ where
 
 		Ok(QueryChannelsResponse {
 			channels,
-			pagination: None,
+			pagination: page_response(result.next_key),
 			height: Some(ibc_proto::ibc::core::client::v1::Height {
 				revision_number: para_id.into(),
 				revision_height: result.height,
@@ -1550,6 +1729,57 @@ where
 		})
 	}
 
+	fn query_cross_chain_queries(&self) -> Result<Vec<Vec<u8>>> {
+		let api = self.client.runtime_api();
+		let block_hash = self.client.info().best_hash;
+		api.cross_chain_queries(block_hash).map_err(|_| {
+			runtime_error_into_rpc_error("[ibc_rpc]: Failed to fetch cross-chain queries")
+		})
+	}
+
+	fn query_value_with_proof(
+		&self,
+		height: u32,
+		path: String,
+	) -> Result<(Option<Vec<u8>>, Proof)> {
+		let api = self.client.runtime_api();
+		let at = BlockId::Number(height.into());
+		let hash_at = self
+			.client
+			.block_hash_from_id(&at)
+			.map_err(|_| RpcError::Custom("Unknown block".into()))?
+			.ok_or_else(|| RpcError::Custom("Unknown block".into()))?;
+		let value = api
+			.ibc_store_value(hash_at, path.as_bytes().to_vec())
+			.map_err(|_| runtime_error_into_rpc_error("Failed to fetch ibc store value"))?;
+		let mut key = api
+			.child_trie_key(hash_at)
+			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
+		key.extend(path.into_bytes());
+		let proof = self.query_proof(height, vec![key])?;
+		Ok((value, proof))
+	}
+
+	fn query_packet_fees(&self, channel_id: String, port_id: String, seq: u64) -> Result<Vec<u8>> {
+		let block_hash = self.client.info().best_hash;
+		if self.api_version(block_hash)? < 3 {
+			return Err(runtime_error_into_rpc_error(
+				"[ibc_rpc]: The packet fees are only exposed since the version 3 of the ibc runtime api",
+			))
+		}
+		let packet_fees = self
+			.client
+			.runtime_api()
+			.packet_fees(
+				block_hash,
+				channel_id.as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+				seq,
+			)
+			.map_err(|_| runtime_error_into_rpc_error("[ibc_rpc]: Failed to fetch packet fees"))?;
+		Ok(packet_fees.unwrap_or_default())
+	}
+
 	fn query_denom_trace(&self, asset_id: AssetId) -> Result<QueryDenomTraceResponse> {
 		let api = self.client.runtime_api();
 		let block_hash = self.client.info().best_hash;
@@ -1582,6 +1812,75 @@ where
 		Ok(QueryDenomTraceResponse { denom_trace: Some(denom_trace) })
 	}
 
+	fn query_total_escrow(&self, denom: String) -> Result<Coin> {
+		let api = self.client.runtime_api();
+		let block_hash = self.client.info().best_hash;
+
+		let amount = api
+			.total_escrow(block_hash, denom.as_bytes().to_vec())
+			.map_err(|_| runtime_error_into_rpc_error("[ibc_rpc]: Error querying total escrow"))?;
+		Ok(Coin { denom, amount: amount.to_string() })
+	}
+
+	fn query_escrow_invariants(&self) -> Result<Vec<EscrowInvariant>> {
+		let api = self.client.runtime_api();
+		let block_hash = self.client.info().best_hash;
+
+		let balances = api.escrow_balances(block_hash).map_err(|_| {
+			runtime_error_into_rpc_error("[ibc_rpc]: Error querying escrow balances")
+		})?;
+		balances
+			.into_iter()
+			.map(|balance| {
+				let denom = String::from_utf8(balance.denom).map_err(|_| {
+					runtime_error_into_rpc_error(
+						"[ibc_rpc]: Could not decode ibc denom into a valid string",
+					)
+				})?;
+				Ok(EscrowInvariant {
+					denom,
+					total_escrow: balance.total_escrow.to_string(),
+					escrow_balance: balance.escrow_balance.to_string(),
+					holds: balance.escrow_balance >= balance.total_escrow,
+				})
+			})
+			.collect()
+	}
+
+	fn query_denom_trace_by_hash(&self, denom_hash: String) -> Result<QueryDenomTraceResponse> {
+		let api = self.client.runtime_api();
+		let block_hash = self.client.info().best_hash;
+
+		let denom_hash = if denom_hash.starts_with("ibc/") {
+			denom_hash
+		} else {
+			format!("ibc/{}", denom_hash.to_uppercase())
+		};
+		let denom_trace = api
+			.denom_trace_by_hash(block_hash, denom_hash.into_bytes())
+			.ok()
+			.flatten()
+			.ok_or_else(|| {
+				runtime_error_into_rpc_error(
+					"[ibc_rpc]: Could not find a denom trace for the hash provided",
+				)
+			})?;
+
+		let denom_str = String::from_utf8(denom_trace.denom).map_err(|_| {
+			runtime_error_into_rpc_error(
+				"[ibc_rpc]: Could not decode ibc denom into a valid string",
+			)
+		})?;
+		let denom_trace = ibc::applications::transfer::PrefixedDenom::from_str(&denom_str)
+			.map_err(|_| {
+				runtime_error_into_rpc_error(
+					"[ibc_rpc]: Could not derive a valid ibc denom from string",
+				)
+			})?;
+
+		Ok(QueryDenomTraceResponse { denom_trace: Some(denom_trace.into()) })
+	}
+
 	fn query_denom_traces(
 		&self,
 		key: Option<AssetId>,
@@ -1719,6 +2018,8 @@ where
 					String::from_utf8(connection_id).map_err(|_| {
 						runtime_error_into_rpc_error("connection id should be valid utf8")
 					})?,
+					None,
+					None,
 				)?;
 				let channel = channels
 					.channels
@@ -1766,6 +2067,45 @@ where
 		}
 		Ok(events)
 	}
+
+	fn query_packet_events(
+		&self,
+		from_height: u32,
+		to_height: u32,
+		filter: PacketEventsFilter,
+	) -> Result<Vec<RawIbcEvent>> {
+		if from_height > to_height {
+			return Err(RpcError::Custom(format!(
+				"Invalid range: {from_height} is greater than {to_height}"
+			)))
+		}
+		if to_height - from_height >= MAX_PACKET_EVENTS_RANGE {
+			return Err(RpcError::Custom(format!(
+				"Range {from_height}..={to_height} exceeds {MAX_PACKET_EVENTS_RANGE} blocks"
+			)))
+		}
+		let api = self.client.runtime_api();
+		let mut events = vec![];
+		for block_number in from_height..=to_height {
+			let hash_at = self
+				.client
+				.block_hash_from_id(&BlockId::Number(block_number.into()))
+				.map_err(|_| RpcError::Custom("Unknown block".into()))?
+				.ok_or_else(|| RpcError::Custom("Unknown block".into()))?;
+			let block_events = api.block_events(hash_at, None).map_err(|_| {
+				runtime_error_into_rpc_error("[ibc_rpc]: failed to read block events")
+			})?;
+			events.extend(
+				block_events
+					.into_iter()
+					.filter_map(|event| {
+						filter_map_pallet_event::<C, Block, AssetId>(hash_at, &api, event.ok()?)
+					})
+					.filter(|event| filter.matches(event)),
+			);
+		}
+		Ok(events)
+	}
 }
 
 impl<C, Block, AssetId> IbcRpcHandler<C, Block, AssetId>
@@ -1781,6 +2121,114 @@ where
 	C::Api: IbcRuntimeApi<Block, AssetId>,
 	AssetId: codec::Codec + Copy,
 {
+	/// Version of the ibc runtime api of the runtime at `at`
+	fn api_version(&self, at: Block::Hash) -> Result<u32> {
+		self.client
+			.runtime_api()
+			.api_version::<dyn IbcRuntimeApi<Block, AssetId>>(at)
+			.map_err(runtime_error_into_rpc_error)?
+			.ok_or_else(|| runtime_error_into_rpc_error("The runtime doesn't have the ibc api"))
+	}
+
+	/// A page of the clients of the runtime at `at`
+	fn clients_page(
+		&self,
+		at: Block::Hash,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<ibc_primitives::QueryClientsResponse> {
+		let api = self.client.runtime_api();
+		if self.api_version(at)? < 2 {
+			#[allow(deprecated)]
+			let clients = api
+				.clients_before_version_2(at)
+				.ok()
+				.flatten()
+				.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch client states"))?;
+			let clients = clients.into_iter().map(|(client_id, client_state)| {
+				(
+					client_id.clone(),
+					ibc_primitives::IdentifiedClientState { client_id, client_state },
+				)
+			});
+			let (clients, next_key) = paginate(clients, next_key, limit)?;
+			return Ok(ibc_primitives::QueryClientsResponse { clients, next_key })
+		}
+		api.clients(at, next_key, limit).ok().flatten().ok_or_else(|| {
+			runtime_error_into_rpc_error("Failed to fetch client states, or unknown next key")
+		})
+	}
+
+	/// A page of the connections of the runtime at `at`
+	fn connections_page(
+		&self,
+		at: Block::Hash,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<ibc_primitives::QueryConnectionsResponse> {
+		let api = self.client.runtime_api();
+		if self.api_version(at)? < 2 {
+			#[allow(deprecated)]
+			let result = api
+				.connections_before_version_2(at)
+				.ok()
+				.flatten()
+				.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch connections"))?;
+			let connections = result
+				.connections
+				.into_iter()
+				.map(|connection| (connection.connection_id.clone(), connection));
+			let (connections, next_key) = paginate(connections, next_key, limit)?;
+			return Ok(ibc_primitives::QueryConnectionsResponse {
+				connections,
+				height: result.height,
+				next_key,
+			})
+		}
+		api.connections(at, next_key, limit).ok().flatten().ok_or_else(|| {
+			runtime_error_into_rpc_error("Failed to fetch connections, or unknown next key")
+		})
+	}
+
+	/// A page of the channels of the runtime at `at`, of the connection `connection_id` if given
+	fn channels_page(
+		&self,
+		at: Block::Hash,
+		connection_id: Option<Vec<u8>>,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<ibc_primitives::QueryChannelsResponse> {
+		let api = self.client.runtime_api();
+		if self.api_version(at)? < 2 {
+			#[allow(deprecated)]
+			let result = match connection_id {
+				Some(connection_id) => api.connection_channels_before_version_2(at, connection_id),
+				None => api.channels_before_version_2(at),
+			}
+			.ok()
+			.flatten()
+			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch channels"))?;
+			let channels = result.channels.into_iter().map(|channel| {
+				((channel.port_id.as_slice(), channel.channel_id.as_slice()).encode(), channel)
+			});
+			let (channels, next_key) = paginate(channels, next_key, limit)?;
+			return Ok(ibc_primitives::QueryChannelsResponse {
+				channels,
+				height: result.height,
+				next_key,
+			})
+		}
+		match connection_id {
+			Some(connection_id) => api.connection_channels(at, connection_id, next_key, limit),
+			None => api.channels(at, next_key, limit),
+		}
+		.ok()
+		.flatten()
+		.ok_or_else(|| {
+			runtime_error_into_rpc_error("Failed to fetch channels, or unknown next key")
+		})
+	}
+
 	fn ibc_event_by_tx_id(
 		&self,
 		block_hash: <Block as BlockT>::Hash,
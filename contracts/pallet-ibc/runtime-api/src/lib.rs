@@ -7,6 +7,10 @@ use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
 	/// IBC Runtime Apis
+	///
+	/// Version 2 paginates the queries of the clients, connections and channels, and adds
+	/// `max_expected_time_per_block`. Version 3 adds `packet_fees`.
+	#[api_version(3)]
 	pub trait IbcRuntimeApi<AssetId: codec::Codec> {
 		/// Get Ibc child trie prefix
 		fn child_trie_key() -> Vec<u8>;
@@ -32,13 +36,27 @@ sp_api::decl_runtime_apis! {
 		fn client_consensus_state(client_id: Vec<u8>, revision_number: u64, revision_height: u64, latest_cs: bool) -> Option<QueryConsensusStateResponse>;
 
 		/// Returns client states for all clients on chain
+		#[changed_in(2)]
 		fn clients() -> Option<Vec<(Vec<u8>, Vec<u8>)>>;
 
+		/// Returns the client states of at most `limit` clients on chain, starting at `next_key`,
+		/// `None` if `next_key` isn't the key of a client
+		fn clients(next_key: Option<Vec<u8>>, limit: Option<u64>) -> Option<QueryClientsResponse>;
+
 		/// Query the given connection state with proof
 		fn connection(connection_id: Vec<u8>) -> Option<QueryConnectionResponse>;
 
 		/// Returns all connections registered on chain
-		fn connections() -> Option<QueryConnectionsResponse>;
+		#[changed_in(2)]
+		fn connections() -> Option<QueryConnectionsResponseV1>;
+
+		/// Returns at most `limit` connections registered on chain, starting at `next_key`, `None`
+		/// if `next_key` isn't the key of a connection
+		fn connections(next_key: Option<Vec<u8>>, limit: Option<u64>) -> Option<QueryConnectionsResponse>;
+
+		/// Maximum expected time per block in nanoseconds, the connection parameter used to enforce
+		/// block delays. Since version 2
+		fn max_expected_time_per_block() -> u64;
 
 		/// Returns all connections associated with the given client
 		fn connection_using_client(client_id: Vec<u8>) -> Option<Vec<IdentifiedConnection>>;
@@ -49,10 +67,20 @@ sp_api::decl_runtime_apis! {
 		fn channel_client(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<IdentifiedClientState>;
 
 		/// Returns all channels associated with this connection
-		fn connection_channels(connection_id: Vec<u8>) -> Option<QueryChannelsResponse>;
+		#[changed_in(2)]
+		fn connection_channels(connection_id: Vec<u8>) -> Option<QueryChannelsResponseV1>;
+
+		/// Returns at most `limit` channels associated with this connection, starting at `next_key`,
+		/// `None` if `next_key` isn't the key of one of its channels
+		fn connection_channels(connection_id: Vec<u8>, next_key: Option<Vec<u8>>, limit: Option<u64>) -> Option<QueryChannelsResponse>;
 
 		/// Returns all channels registered on chain
-		fn channels() -> Option<QueryChannelsResponse>;
+		#[changed_in(2)]
+		fn channels() -> Option<QueryChannelsResponseV1>;
+
+		/// Returns at most `limit` channels registered on chain, starting at `next_key`, `None` if
+		/// `next_key` isn't the key of a channel
+		fn channels(next_key: Option<Vec<u8>>, limit: Option<u64>) -> Option<QueryChannelsResponse>;
 
 		fn connection_handshake(client_id: Vec<u8>, connection_id: Vec<u8>) -> Option<ConnectionHandshake>;
 
@@ -74,9 +102,28 @@ sp_api::decl_runtime_apis! {
 
 		fn denom_trace(asset_id: AssetId) -> Option<QueryDenomTraceResponse>;
 
+		/// Protobuf encoded `PacketFees` escrowed by the ICS-29 fee middleware for the packet `seq`
+		/// sent on the channel, `None` if no fee was escrowed for it. Since version 3
+		fn packet_fees(channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<Vec<u8>>;
+
+		/// Amount of the denom accounted as escrowed by ics20
+		fn total_escrow(denom: Vec<u8>) -> u128;
+
+		/// Total escrow of every denom along with the balance held by the escrow accounts
+		fn escrow_balances() -> Vec<EscrowBalance>;
+
+		/// Denom trace of the voucher referred to by an `ibc/{hash}` denom
+		fn denom_trace_by_hash(denom_hash: Vec<u8>) -> Option<QueryDenomTraceResponse>;
+
 		/// Key is the asset id from which to start looking up results
 		fn denom_traces(key: Option<AssetId>, offset: Option<u32>, limit: u64, count_total: bool) -> QueryDenomTracesResponse;
 
+		/// Returns the protobuf encoded cross-chain queries waiting for a result
+		fn cross_chain_queries() -> Vec<Vec<u8>>;
+
+		/// Returns the value stored in the ibc store at the given path
+		fn ibc_store_value(path: Vec<u8>) -> Option<Vec<u8>>;
+
 		fn block_events(extrinsic_index: Option<u32>) -> Vec<Result<pallet_ibc::events::IbcEvent, pallet_ibc::errors::IbcError>>;
 	}
 }
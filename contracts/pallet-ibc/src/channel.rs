@@ -7,9 +7,14 @@ use sp_core::crypto::AccountId32;
 
 use crate::{
 	ics23::{
-		acknowledgements::Acknowledgements, channels::Channels, next_seq_ack::NextSequenceAck,
-		next_seq_recv::NextSequenceRecv, next_seq_send::NextSequenceSend,
-		packet_commitments::PacketCommitment, receipts::PacketReceipt,
+		acknowledgements::Acknowledgements,
+		channel_upgrades::{ChannelUpgradeErrors, ChannelUpgrades, CounterpartyChannelUpgrades},
+		channels::Channels,
+		next_seq_ack::NextSequenceAck,
+		next_seq_recv::NextSequenceRecv,
+		next_seq_send::NextSequenceSend,
+		packet_commitments::PacketCommitment,
+		receipts::PacketReceipt,
 	},
 	impls::host_height,
 	routing::Context,
@@ -24,6 +29,7 @@ use ibc::{
 			error::Error as ICS04Error,
 			msgs::acknowledgement::Acknowledgement,
 			packet::{Receipt, Sequence},
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 	},
@@ -161,6 +167,7 @@ where
 			})?;
 			let data = match data.as_ref() {
 				"Ok" => Receipt::Ok,
+				"Timeout" => Receipt::Timeout,
 				_ => return Err(ICS04Error::packet_receipt_not_found(seq.into())),
 			};
 			log::trace!(target: "pallet_ibc", "in channel : [get_packet_receipt] >> packet_receipt = {:?}", data);
@@ -257,6 +264,50 @@ where
 		let expected = T::ExpectedBlockTime::get();
 		Duration::from_millis(expected)
 	}
+
+	fn channel_upgrade(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<Upgrade, ICS04Error> {
+		let data = <ChannelUpgrades<T>>::get(port_channel_id.0.clone(), port_channel_id.1)
+			.ok_or_else(|| {
+				ICS04Error::missing_upgrade(port_channel_id.0.clone(), port_channel_id.1)
+			})?;
+		Upgrade::decode_vec(&data)
+			.map_err(|e| ICS04Error::implementation_specific(format!("[channel_upgrade]: {e}")))
+	}
+
+	fn channel_counterparty_upgrade(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<Upgrade, ICS04Error> {
+		let data =
+			<CounterpartyChannelUpgrades<T>>::get(port_channel_id.0.clone(), port_channel_id.1)
+				.ok_or_else(|| {
+					ICS04Error::missing_upgrade(port_channel_id.0.clone(), port_channel_id.1)
+				})?;
+		Upgrade::decode_vec(&data).map_err(|e| {
+			ICS04Error::implementation_specific(format!("[channel_counterparty_upgrade]: {e}"))
+		})
+	}
+
+	fn channel_upgrade_error_receipt(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<ErrorReceipt, ICS04Error> {
+		let data = <ChannelUpgradeErrors<T>>::get(port_channel_id.0.clone(), port_channel_id.1)
+			.ok_or_else(ICS04Error::missing_error_receipt)?;
+		ErrorReceipt::decode_vec(&data).map_err(|e| {
+			ICS04Error::implementation_specific(format!("[channel_upgrade_error_receipt]: {e}"))
+		})
+	}
+
+	fn has_inflight_packets(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<bool, ICS04Error> {
+		Ok(<PacketCommitment<T>>::contains_channel(port_channel_id.0.clone(), port_channel_id.1))
+	}
 }
 
 impl<T: Config + Sync + Send> ChannelKeeper for Context<T>
@@ -338,9 +389,7 @@ where
 		key: (PortId, ChannelId, Sequence),
 		receipt: Receipt,
 	) -> Result<(), ICS04Error> {
-		let receipt = match receipt {
-			Receipt::Ok => b"Ok".to_vec(),
-		};
+		let receipt = receipt.as_bytes().to_vec();
 
 		<PacketReceipt<T>>::insert((key.0.clone(), key.1, key.2), receipt);
 
@@ -468,6 +517,48 @@ where
 	/// Called upon channel identifier creation (Init or Try message processing).
 	/// Increases the counter which keeps track of how many channels have been created.
 	/// Should never fail.
+	fn store_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: Upgrade,
+	) -> Result<(), ICS04Error> {
+		log::trace!(target: "pallet_ibc", "in channel: [store_upgrade] >> port_channel_id = {:?}", port_channel_id);
+		<ChannelUpgrades<T>>::insert(port_channel_id.0, port_channel_id.1, upgrade);
+		Ok(())
+	}
+
+	fn delete_upgrade(&mut self, port_channel_id: (PortId, ChannelId)) -> Result<(), ICS04Error> {
+		<ChannelUpgrades<T>>::remove(port_channel_id.0, port_channel_id.1);
+		Ok(())
+	}
+
+	fn store_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: Upgrade,
+	) -> Result<(), ICS04Error> {
+		<CounterpartyChannelUpgrades<T>>::insert(port_channel_id.0, port_channel_id.1, upgrade);
+		Ok(())
+	}
+
+	fn delete_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+	) -> Result<(), ICS04Error> {
+		<CounterpartyChannelUpgrades<T>>::remove(port_channel_id.0, port_channel_id.1);
+		Ok(())
+	}
+
+	fn store_upgrade_error_receipt(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		error_receipt: ErrorReceipt,
+	) -> Result<(), ICS04Error> {
+		log::trace!(target: "pallet_ibc", "in channel: [store_upgrade_error_receipt] >> port_channel_id = {:?}, error_receipt = {:?}", port_channel_id, error_receipt);
+		<ChannelUpgradeErrors<T>>::insert(port_channel_id.0, port_channel_id.1, error_receipt);
+		Ok(())
+	}
+
 	fn increase_channel_counter(&mut self) {
 		log::trace!(target: "pallet_ibc", "in channel: [increase_channel_counter]");
 		let _ = ChannelCounter::<T>::try_mutate::<_, (), _>(|val| {
@@ -360,8 +360,11 @@ where
 			client_id, height, consensus_state);
 
 		let data = consensus_state.encode_to_vec().map_err(ICS02Error::encode)?;
-		// todo: pruning
 		ConsensusStates::<T>::insert(client_id.clone(), height, data);
+		let pruned = Pallet::<T>::prune_consensus_states_on_update(&client_id, height);
+		if pruned > 0 {
+			log::trace!(target: "pallet_ibc", "in client : [store_consensus_state] >> pruned {} consensus states of {}", pruned, client_id);
+		}
 		// We do not need this hack for neither beefy nor grandpa clients
 		if !client_id.as_str().starts_with("10-grandpa") &&
 			!client_id.as_str().starts_with("11-beefy")
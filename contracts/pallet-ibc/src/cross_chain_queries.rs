@@ -0,0 +1,78 @@
+use super::*;
+
+use crate::routing::Context;
+use ibc::applications::cross_chain_queries::{
+	context::{CrossChainQueryContext, CrossChainQueryKeeper, CrossChainQueryReader},
+	error::Error as ICS31Error,
+	query::{CrossChainQuery, CrossChainQueryResult, QueryId},
+};
+use sp_core::crypto::AccountId32;
+use tendermint_proto::Protobuf;
+
+impl<T: Config + Sync + Send> CrossChainQueryReader for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn query_counter(&self) -> Result<u64, ICS31Error> {
+		Ok(CrossChainQueryCounter::<T>::get())
+	}
+
+	fn cross_chain_query(&self, query_id: QueryId) -> Result<CrossChainQuery, ICS31Error> {
+		let query = CrossChainQueries::<T>::get(query_id.value())
+			.ok_or_else(|| ICS31Error::query_not_found(query_id))?;
+		CrossChainQuery::decode_vec(&query).map_err(ICS31Error::decode_raw_msg)
+	}
+
+	fn cross_chain_query_result(
+		&self,
+		query_id: QueryId,
+	) -> Result<CrossChainQueryResult, ICS31Error> {
+		let result = CrossChainQueryResults::<T>::get(query_id.value())
+			.ok_or_else(|| ICS31Error::query_not_found(query_id))?;
+		CrossChainQueryResult::decode_vec(&result).map_err(ICS31Error::decode_raw_msg)
+	}
+}
+
+impl<T: Config + Sync + Send> CrossChainQueryKeeper for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn increase_query_counter(&mut self) {
+		if let Some(val) = <CrossChainQueryCounter<T>>::get().checked_add(1) {
+			<CrossChainQueryCounter<T>>::put(val);
+		}
+	}
+
+	fn store_cross_chain_query(&mut self, query: CrossChainQuery) -> Result<(), ICS31Error> {
+		let encoded = query
+			.encode_vec()
+			.map_err(|e| ICS31Error::implementation_specific(e.to_string()))?;
+		CrossChainQueries::<T>::insert(query.id.value(), encoded);
+		Ok(())
+	}
+
+	fn delete_cross_chain_query(&mut self, query_id: QueryId) -> Result<(), ICS31Error> {
+		CrossChainQueries::<T>::remove(query_id.value());
+		Ok(())
+	}
+
+	fn store_cross_chain_query_result(
+		&mut self,
+		result: CrossChainQueryResult,
+	) -> Result<(), ICS31Error> {
+		let encoded = result
+			.encode_vec()
+			.map_err(|e| ICS31Error::implementation_specific(e.to_string()))?;
+		CrossChainQueryResults::<T>::insert(result.id.value(), encoded);
+		Ok(())
+	}
+}
+
+impl<T: Config + Sync + Send> CrossChainQueryContext for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+}
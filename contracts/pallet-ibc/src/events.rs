@@ -7,11 +7,11 @@ use ibc::{
 			events::{CodeHash, NewBlock},
 		},
 		ics03_connection::events as ConnectionEvents,
-		ics04_channel::{events as ChannelEvents, packet::Packet},
+		ics04_channel::{channel::State as ChannelState, events as ChannelEvents, packet::Packet},
 		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 		ics26_routing::{context::ModuleId, error::Error as RoutingError},
 	},
-	events::{IbcEvent as RawIbcEvent, ModuleEvent},
+	events::{IbcEvent as RawIbcEvent, IbcEventType, ModuleEvent},
 	timestamp::Timestamp,
 	Height,
 };
@@ -219,6 +219,18 @@ pub enum IbcEvent {
 	AppModule { kind: Vec<u8>, module_id: Vec<u8> },
 	/// Push WASM Code
 	PushWasmCode { wasm_code_hash: CodeHash },
+	/// Channel upgrade handshake step
+	UpgradeChannel {
+		revision_height: u64,
+		revision_number: u64,
+		step: Vec<u8>,
+		port_id: Vec<u8>,
+		channel_id: Vec<u8>,
+		counterparty_port_id: Vec<u8>,
+		counterparty_channel_id: Option<Vec<u8>>,
+		upgrade_sequence: u64,
+		channel_state: Vec<u8>,
+	},
 }
 
 impl From<RawIbcEvent> for IbcEvent {
@@ -442,6 +454,19 @@ impl From<RawIbcEvent> for IbcEvent {
 				let wasm_code_hash = ev.0;
 				IbcEvent::PushWasmCode { wasm_code_hash }
 			},
+			RawIbcEvent::UpgradeChannel(ev) => IbcEvent::UpgradeChannel {
+				revision_height: ev.height().revision_height,
+				revision_number: ev.height().revision_number,
+				step: ev.step.event_type().as_str().as_bytes().to_vec(),
+				port_id: ev.port_id.as_bytes().to_vec(),
+				channel_id: ev.channel_id.to_string().as_bytes().to_vec(),
+				counterparty_port_id: ev.counterparty_port_id.as_bytes().to_vec(),
+				counterparty_channel_id: ev
+					.counterparty_channel_id
+					.map(|val| val.to_string().as_bytes().to_vec()),
+				upgrade_sequence: ev.upgrade_sequence,
+				channel_state: ev.channel_state.as_string().as_bytes().to_vec(),
+			},
 		}
 	}
 }
@@ -1050,6 +1075,44 @@ impl TryFrom<IbcEvent> for RawIbcEvent {
 				.map_err(|_| ERROR_STR)?,
 				attributes: Default::default(),
 			})),
+			IbcEvent::UpgradeChannel {
+				revision_height,
+				revision_number,
+				step,
+				port_id,
+				channel_id,
+				counterparty_port_id,
+				counterparty_channel_id,
+				upgrade_sequence,
+				channel_state,
+			} => Ok(RawIbcEvent::UpgradeChannel(ChannelEvents::Upgrade {
+				height: Height::new(revision_number, revision_height),
+				step: IbcEventType::from_str(&String::from_utf8(step).map_err(|_| ERROR_STR)?)
+					.ok()
+					.as_ref()
+					.and_then(ChannelEvents::UpgradeStep::from_event_type)
+					.ok_or(ERROR_STR)?,
+				port_id: PortId::from_str(&String::from_utf8(port_id).map_err(|_| ERROR_STR)?)
+					.map_err(|_| ERROR_STR)?,
+				channel_id: ChannelId::from_str(
+					&String::from_utf8(channel_id).map_err(|_| ERROR_STR)?,
+				)
+				.map_err(|_| ERROR_STR)?,
+				counterparty_port_id: PortId::from_str(
+					&String::from_utf8(counterparty_port_id).map_err(|_| ERROR_STR)?,
+				)
+				.map_err(|_| ERROR_STR)?,
+				counterparty_channel_id: counterparty_channel_id.and_then(|channel_id| {
+					String::from_utf8(channel_id)
+						.ok()
+						.and_then(|channel_id| ChannelId::from_str(&channel_id).ok())
+				}),
+				upgrade_sequence,
+				channel_state: ChannelState::from_str(
+					&String::from_utf8(channel_state).map_err(|_| ERROR_STR)?,
+				)
+				.map_err(|_| ERROR_STR)?,
+			})),
 		}
 	}
 }
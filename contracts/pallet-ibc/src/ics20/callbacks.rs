@@ -0,0 +1,347 @@
+use crate::{Config, Event, Pallet};
+use alloc::string::String;
+use core::{fmt::Debug, str::FromStr};
+use ibc::{
+	applications::transfer::{
+		acknowledgement::Acknowledgement as Ics20Acknowledgement, packet::PacketData,
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			error::Error,
+			msgs::acknowledgement::{Acknowledgement as GenericAcknowledgement, Acknowledgement},
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+
+/// Callback requested by the sender of a transfer through the `src_callback` field of the memo,
+/// as described by [ADR-8](https://github.com/cosmos/ibc-go/blob/main/docs/architecture/adr-008-app-caller-cbs.md)
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CallbackData {
+	/// Address of the runtime handler or wasm contract to call
+	pub address: String,
+	/// Gas the callback is allowed to consume, left to the handler to enforce
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub gas_limit: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct CallbackMemo {
+	src_callback: Option<CallbackData>,
+}
+
+impl CallbackData {
+	/// Callback requested in the memo of a transfer packet, if any
+	pub fn from_packet(packet: &Packet) -> Option<(Self, PacketData)> {
+		let data: PacketData = serde_json::from_slice(&packet.data).ok()?;
+		let memo: CallbackMemo = serde_json::from_str(&data.memo).ok()?;
+		Some((memo.src_callback?, data))
+	}
+}
+
+/// Handlers invoked on the sending chain once the outcome of a transfer carrying a
+/// `src_callback` memo is known. A runtime can dispatch them to its own pallets or to a wasm
+/// contract identified by [`CallbackData::address`].
+pub trait TransferCallbacks<T: Config> {
+	fn on_acknowledgement(
+		_callback: &CallbackData,
+		_packet: &Packet,
+		_data: &PacketData,
+		_success: bool,
+	) -> Result<(), String> {
+		Ok(())
+	}
+
+	fn on_timeout(
+		_callback: &CallbackData,
+		_packet: &Packet,
+		_data: &PacketData,
+	) -> Result<(), String> {
+		Ok(())
+	}
+}
+
+impl<T: Config> TransferCallbacks<T> for () {}
+
+/// This middleware should be used to wrap ics20 to invoke [`Config::TransferCallbacks`] when a
+/// transfer with a `src_callback` memo is acknowledged or times out.
+/// Callbacks run after the wrapped module has processed the packet, so refunds have already been
+/// made when they are invoked. A failing callback never reverts the acknowledgement or timeout.
+/// USAGE:
+/// ```rust
+/// # #[cfg(any())] // This is just to make the doc test compile
+/// pub struct Router {
+///     ics20: crate::ics20::callbacks::Callbacks<
+///         Runtime,
+///         crate::ics20::memo::Memo<Runtime, crate::ics20::IbcModule<Runtime>>,
+///     >,
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Callbacks<T: Config, S: Module + Clone + Default + PartialEq + Eq + Debug> {
+	inner: S,
+	_phantom: core::marker::PhantomData<T>,
+}
+
+impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Debug> Default
+	for Callbacks<T, S>
+{
+	fn default() -> Self {
+		Self { inner: S::default(), _phantom: Default::default() }
+	}
+}
+
+impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Debug> Module
+	for Callbacks<T, S>
+{
+	fn on_chan_open_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner.on_chan_open_init(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+			relayer,
+		)
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<Version, Error> {
+		self.inner.on_chan_open_try(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+			counterparty_version,
+			relayer,
+		)
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner
+			.on_chan_open_ack(ctx, output, port_id, channel_id, counterparty_version, relayer)
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner.on_chan_open_confirm(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner.on_chan_close_init(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner.on_chan_close_confirm(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_upgrade_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		proposed_connection_hops: &[ConnectionId],
+		proposed_version: &Version,
+	) -> Result<Version, Error> {
+		self.inner.on_chan_upgrade_init(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			proposed_connection_hops,
+			proposed_version,
+		)
+	}
+
+	fn on_chan_upgrade_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		proposed_connection_hops: &[ConnectionId],
+		counterparty_version: &Version,
+	) -> Result<Version, Error> {
+		self.inner.on_chan_upgrade_try(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			proposed_connection_hops,
+			counterparty_version,
+		)
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+	) -> Result<(), Error> {
+		self.inner
+			.on_chan_upgrade_ack(ctx, output, port_id, channel_id, counterparty_version)
+	}
+
+	fn on_chan_upgrade_open(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<(), Error> {
+		self.inner.on_chan_upgrade_open(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			version,
+		)
+	}
+
+	fn on_recv_packet(
+		&self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Error> {
+		self.inner.on_recv_packet(ctx, output, packet, relayer)
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &GenericAcknowledgement,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner
+			.on_acknowledgement_packet(ctx, output, packet, acknowledgement, relayer)?;
+		if let Some((callback, data)) = CallbackData::from_packet(packet) {
+			let success =
+				Ics20Acknowledgement::from_str(&String::from_utf8_lossy(acknowledgement.as_ref()))
+					.map(|ack| ack.is_successful())
+					.unwrap_or_default();
+			let result = <T as Config>::TransferCallbacks::on_acknowledgement(
+				&callback, packet, &data, success,
+			);
+			Self::deposit_result(packet, callback, result);
+		}
+		Ok(())
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner.on_timeout_packet(ctx, output, packet, relayer)?;
+		if let Some((callback, data)) = CallbackData::from_packet(packet) {
+			let result = <T as Config>::TransferCallbacks::on_timeout(&callback, packet, &data);
+			Self::deposit_result(packet, callback, result);
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Debug>
+	Callbacks<T, S>
+{
+	fn deposit_result(packet: &Packet, callback: CallbackData, result: Result<(), String>) {
+		let channel = packet.source_channel.sequence();
+		let sequence = u64::from(packet.sequence);
+		match result {
+			Ok(()) => Pallet::<T>::deposit_event(Event::<T>::TransferCallbackExecuted {
+				channel,
+				sequence,
+				address: callback.address,
+			}),
+			Err(error) => {
+				log::error!(target: "pallet_ibc", "Error while executing transfer callback: {:?}", error);
+				Pallet::<T>::deposit_event(Event::<T>::TransferCallbackFailed {
+					channel,
+					sequence,
+					address: callback.address,
+					error,
+				})
+			},
+		}
+	}
+}
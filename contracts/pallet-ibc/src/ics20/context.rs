@@ -1,5 +1,5 @@
-use super::super::*;
-use crate::routing::Context;
+use super::{super::*, denom_hash};
+use crate::{routing::Context, DenomTraces, TotalEscrow};
 use frame_support::traits::{
 	fungibles::Mutate,
 	tokens::{Fortitude, Precision},
@@ -9,7 +9,7 @@ use ibc::{
 	applications::transfer::{
 		context::{BankKeeper, Ics20Context, Ics20Keeper, Ics20Reader},
 		error::Error as Ics20Error,
-		PORT_ID_STR,
+		Amount, PrefixedDenom, PORT_ID_STR,
 	},
 	core::ics24_host::identifier::{ChannelId, PortId},
 };
@@ -47,6 +47,19 @@ where
 	fn is_receive_enabled(&self) -> bool {
 		T::IsReceiveEnabled::get()
 	}
+
+	fn denom_hash_string(&self, denom: &PrefixedDenom) -> Option<String> {
+		Some(denom_hash(denom))
+	}
+
+	fn get_total_escrow(&self, denom: &PrefixedDenom) -> Amount {
+		Amount::from(TotalEscrow::<T>::get(denom.to_string().as_bytes()))
+	}
+
+	fn get_denom_trace(&self, denom_hash: &str) -> Option<PrefixedDenom> {
+		DenomTraces::<T>::get(denom_hash.as_bytes())
+			.and_then(|denom| PrefixedDenom::from_str(&String::from_utf8(denom).ok()?).ok())
+	}
 }
 
 impl<T: Config + Send + Sync> Ics20Keeper for Context<T>
@@ -55,6 +68,27 @@ where
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
 {
 	type AccountId = T::AccountIdConversion;
+
+	fn store_denom_trace(
+		&mut self,
+		denom_hash: String,
+		denom: PrefixedDenom,
+	) -> Result<(), Ics20Error> {
+		DenomTraces::<T>::insert(denom_hash.into_bytes(), denom.to_string().into_bytes());
+		Ok(())
+	}
+
+	fn set_total_escrow(
+		&mut self,
+		denom: &PrefixedDenom,
+		amount: Amount,
+	) -> Result<(), Ics20Error> {
+		if amount.as_u256() > u128::MAX.into() {
+			return Err(Ics20Error::invalid_token())
+		}
+		TotalEscrow::<T>::insert(denom.to_string().into_bytes(), amount.as_u256().low_u128());
+		Ok(())
+	}
 }
 
 impl<T: Config + Send + Sync> Ics20Context for Context<T>
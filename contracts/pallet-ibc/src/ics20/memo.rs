@@ -152,6 +152,81 @@ impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Deb
 		self.inner.on_chan_close_confirm(ctx, output, port_id, channel_id, relayer)
 	}
 
+	fn on_chan_upgrade_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		proposed_connection_hops: &[ConnectionId],
+		proposed_version: &Version,
+	) -> Result<Version, Error> {
+		self.inner.on_chan_upgrade_init(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			proposed_connection_hops,
+			proposed_version,
+		)
+	}
+
+	fn on_chan_upgrade_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		proposed_connection_hops: &[ConnectionId],
+		counterparty_version: &Version,
+	) -> Result<Version, Error> {
+		self.inner.on_chan_upgrade_try(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			proposed_connection_hops,
+			counterparty_version,
+		)
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+	) -> Result<(), Error> {
+		self.inner
+			.on_chan_upgrade_ack(ctx, output, port_id, channel_id, counterparty_version)
+	}
+
+	fn on_chan_upgrade_open(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<(), Error> {
+		self.inner.on_chan_upgrade_open(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			version,
+		)
+	}
+
 	fn on_recv_packet(
 		&self,
 		ctx: &dyn ModuleCallbackContext,
@@ -196,7 +271,16 @@ impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Deb
 		relayer: &Signer,
 	) -> Result<(), Error> {
 		self.inner
-			.on_acknowledgement_packet(ctx, output, packet, acknowledgement, relayer)
+			.on_acknowledgement_packet(ctx, output, packet, acknowledgement, relayer)?;
+		let success =
+			Ics20Acknowledgement::from_str(&String::from_utf8_lossy(acknowledgement.as_ref()))
+				.map(|ack| ack.is_successful())
+				.unwrap_or_default();
+		// Failing to retry or refund a forwarded transfer must not revert the acknowledgement
+		if let Err(err) = <T as Config>::HandleMemo::default().on_acknowledgement(packet, success) {
+			log::error!(target: "pallet_ibc", "Error while handling memo acknowledgement: {:?}", err);
+		}
+		Ok(())
 	}
 
 	fn on_timeout_packet(
@@ -206,7 +290,11 @@ impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Deb
 		packet: &mut Packet,
 		relayer: &Signer,
 	) -> Result<(), Error> {
-		self.inner.on_timeout_packet(ctx, output, packet, relayer)
+		self.inner.on_timeout_packet(ctx, output, packet, relayer)?;
+		if let Err(err) = <T as Config>::HandleMemo::default().on_timeout(packet) {
+			log::error!(target: "pallet_ibc", "Error while handling memo timeout: {:?}", err);
+		}
+		Ok(())
 	}
 }
 
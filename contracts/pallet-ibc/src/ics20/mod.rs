@@ -1,23 +1,27 @@
+pub mod callbacks;
 pub mod context;
 pub mod memo;
 
 use crate::{
-	routing::Context, ChannelIds, Config, DenomToAssetId, Event, Pallet, SequenceFee, WeightInfo,
+	routing::Context, ChannelIds, Config, DenomToAssetId, Event, ForwardedPackets, Pallet,
+	SequenceFee, WeightInfo,
 };
 use alloc::{
 	format,
 	str::FromStr,
 	string::{String, ToString},
+	vec::Vec,
 };
 
-use frame_support::weights::Weight;
+use frame_support::{dispatch::DispatchResult, weights::Weight};
 pub use ibc::applications::transfer::{MODULE_ID_STR, PORT_ID_STR};
 use ibc::{
 	applications::transfer::{
 		acknowledgement::{Acknowledgement as Ics20Acknowledgement, ACK_ERR_STR},
 		context::{
 			on_chan_close_confirm, on_chan_close_init, on_chan_open_ack, on_chan_open_confirm,
-			on_chan_open_init, on_chan_open_try, BankKeeper,
+			on_chan_open_init, on_chan_open_try, on_chan_upgrade_ack, on_chan_upgrade_init,
+			on_chan_upgrade_try, BankKeeper,
 		},
 		is_receiver_chain_source, is_sender_chain_source,
 		packet::PacketData,
@@ -30,6 +34,7 @@ use ibc::{
 	core::{
 		ics04_channel::{
 			channel::{Counterparty, Order},
+			context::ChannelReader,
 			error::Error as Ics04Error,
 			msgs::acknowledgement::Acknowledgement,
 			packet::Packet,
@@ -200,6 +205,63 @@ where
 			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
 	}
 
+	fn on_chan_upgrade_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		_proposed_connection_hops: &[ConnectionId],
+		proposed_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		on_chan_upgrade_init(
+			&mut ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			proposed_version,
+		)
+		.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
+	fn on_chan_upgrade_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		_proposed_connection_hops: &[ConnectionId],
+		counterparty_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		on_chan_upgrade_try(
+			&mut ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			counterparty_version,
+		)
+		.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		on_chan_upgrade_ack(&mut ctx, output, port_id, channel_id, counterparty_version)
+			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
 	fn on_recv_packet(
 		&self,
 		_ctx: &dyn ModuleCallbackContext,
@@ -528,10 +590,27 @@ pub fn full_ibc_denom(packet: &Packet, mut token: PrefixedCoin) -> String {
 	}
 }
 
+/// Hash an ibc denom is referred to by, `ibc/{hex(sha256(denom))}` as in ibc-go
+pub fn denom_hash(denom: &PrefixedDenom) -> String {
+	format!("ibc/{}", hex::encode_upper(sp_io::hashing::sha2_256(denom.to_string().as_bytes())))
+}
+
 use ibc::applications::transfer::error::Error as Ics20Error;
 
 pub trait HandleMemo<T: Config> {
 	fn execute_memo(&self, packet: &Packet) -> Result<(), Ics20Error>;
+
+	/// Called after the transfer module has processed the acknowledgement of a packet sent from
+	/// this chain.
+	fn on_acknowledgement(&self, _packet: &Packet, _success: bool) -> Result<(), Ics20Error> {
+		Ok(())
+	}
+
+	/// Called after the transfer module has refunded a packet sent from this chain that timed
+	/// out.
+	fn on_timeout(&self, _packet: &Packet) -> Result<(), Ics20Error> {
+		Ok(())
+	}
 }
 
 impl<T: Config> HandleMemo<T> for () {
@@ -568,7 +647,7 @@ impl<T: Config> SubstrateMultihopXcmHandler for SubstrateMultihopXcmHandlerNone<
 }
 
 use frame_system::RawOrigin;
-use scale_info::prelude::boxed::Box;
+use scale_info::{prelude::boxed::Box, TypeInfo};
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct Forward {
 	pub receiver: String,
@@ -627,6 +706,28 @@ pub struct MemoIbc {
 	pub retries: u64,
 }
 
+/// A transfer sent by [`IbcMemoHandler`] on behalf of the receiver of an incoming packet, kept
+/// until the forwarded packet is acknowledged or times out.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct ForwardedPacket<AccountId, AssetId, Balance, MemoMessage> {
+	/// Receiver of the incoming packet, which sends the forwarded transfer
+	pub forwarder: AccountId,
+	/// Sender of the incoming packet on the previous hop
+	pub original_sender: Vec<u8>,
+	/// Channel the incoming packet was received on
+	pub original_channel: u64,
+	/// Receiver of the forwarded transfer on the next hop
+	pub receiver: Vec<u8>,
+	/// Channel the transfer is forwarded over
+	pub channel: u64,
+	pub asset_id: AssetId,
+	pub amount: Balance,
+	pub timeout: u64,
+	/// Number of times the transfer is re-sent if it times out before being refunded
+	pub retries: u64,
+	pub next_memo: Option<MemoMessage>,
+}
+
 pub struct MemoXcm {
 	pub receiver: String,
 	pub para_id: Option<u32>, //if para id is none, it means send to relay-chain
@@ -675,7 +776,7 @@ pub struct IbcMemoHandler<H, T> {
 	pub _phantom: PhantomData<T>,
 }
 
-use codec::Decode;
+use codec::{Decode, Encode};
 impl<T, H: HandleMemo<T>> HandleMemo<T> for IbcMemoHandler<H, T>
 where
 	T: Config + Send + Sync + pallet_timestamp::Config,
@@ -821,10 +922,6 @@ where
 
 		let raw_bytes = memo_forward.receiver.into_bytes();
 
-		let transfer_ibc_account_to =
-			crate::MultiAddress::<<T as frame_system::Config>::AccountId>::Raw(raw_bytes.clone());
-
-		let origin = RawOrigin::Signed(receiver.clone());
 		let channel_id = memo_forward
 			.channel
 			.split('-')
@@ -850,15 +947,6 @@ where
 				Ics20Error::implementation_specific("Failed to parse channel ID".to_string())
 			})?;
 
-		let params = crate::TransferParams::<<T as frame_system::Config>::AccountId> {
-			to: transfer_ibc_account_to,
-			source_channel: channel_id,
-			timeout: ibc_primitives::Timeout::Offset {
-				timestamp: Some(memo_forward.timeout),
-				height: Some(1000),
-			},
-		};
-
 		let mut next_memo: Option<T::MemoMessage> = None;
 		if let Some(memo) = memo.forward.next {
 			let memo_result = <T as crate::Config>::MemoMessage::try_from(*memo).map_err(|_| {
@@ -873,14 +961,20 @@ where
 			next_memo = Some(memo_result);
 		}
 
-		crate::Pallet::<T>::transfer(
-			origin.into(),
-			params,
-			asset_id.clone(),
-			amount.into(),
-			next_memo.clone(),
-		)
-		.map_err(|_| {
+		let forward = ForwardedPacket {
+			forwarder: receiver.clone(),
+			original_sender: packet_data.sender.as_ref().as_bytes().to_vec(),
+			original_channel: packet.destination_channel.sequence(),
+			receiver: raw_bytes.clone(),
+			channel: channel_id,
+			asset_id: asset_id.clone(),
+			amount: amount.into(),
+			timeout: memo_forward.timeout,
+			retries: memo_forward.retries,
+			next_memo: next_memo.clone(),
+		};
+
+		Self::send_forward(forward).map_err(|_| {
 			crate::Pallet::<T>::deposit_event(Event::<T>::ExecuteMemoIbcTokenTransferFailed {
 				from: receiver.clone(),
 				to: raw_bytes.clone(),
@@ -904,6 +998,120 @@ where
 		});
 		Ok(())
 	}
+
+	fn on_acknowledgement(&self, packet: &Packet, success: bool) -> Result<(), Ics20Error> {
+		self.inner.on_acknowledgement(packet, success)?;
+
+		let key = (packet.source_channel.sequence(), u64::from(packet.sequence));
+		let Some(forward) = ForwardedPackets::<T>::take(key) else { return Ok(()) };
+		if success {
+			return Ok(())
+		}
+		// The transfer module has already refunded the forwarder, so we hand the tokens back to
+		// the account that initiated the transfer on the previous hop.
+		Self::refund_forward(forward)
+	}
+
+	fn on_timeout(&self, packet: &Packet) -> Result<(), Ics20Error> {
+		self.inner.on_timeout(packet)?;
+
+		let key = (packet.source_channel.sequence(), u64::from(packet.sequence));
+		let Some(mut forward) = ForwardedPackets::<T>::take(key) else { return Ok(()) };
+		if forward.retries == 0 {
+			return Self::refund_forward(forward)
+		}
+
+		forward.retries -= 1;
+		let (from, to, channel, retries) =
+			(forward.forwarder.clone(), forward.receiver.clone(), forward.channel, forward.retries);
+		match Self::send_forward(forward.clone()) {
+			Ok(()) => {
+				crate::Pallet::<T>::deposit_event(Event::<T>::ExecuteMemoIbcTokenTransferRetried {
+					from,
+					to,
+					channel,
+					retries,
+				});
+				Ok(())
+			},
+			Err(e) => {
+				log::warn!(target: "pallet_ibc", "Failed to retry forwarded transfer: {:?}", e);
+				Self::refund_forward(forward)
+			},
+		}
+	}
+}
+
+impl<T, H> IbcMemoHandler<H, T>
+where
+	T: Config + Send + Sync,
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	/// Sends the forwarded transfer from the forwarder account and records it in
+	/// [`ForwardedPackets`] under the sequence it was sent with, so that it can be retried on
+	/// timeout or refunded on failure.
+	fn send_forward(
+		forward: ForwardedPacket<T::AccountId, T::AssetId, T::Balance, T::MemoMessage>,
+	) -> DispatchResult {
+		let channel_id = ChannelId::new(forward.channel);
+		let sequence = Context::<T>::default()
+			.get_next_sequence_send(&(PortId::transfer(), channel_id))
+			.map_err(|_| crate::Error::<T>::ChannelNotFound)?;
+		let params = crate::TransferParams::<<T as frame_system::Config>::AccountId> {
+			to: crate::MultiAddress::Raw(forward.receiver.clone()),
+			source_channel: forward.channel,
+			timeout: ibc_primitives::Timeout::Offset {
+				timestamp: Some(forward.timeout),
+				height: Some(1000),
+			},
+		};
+		crate::Pallet::<T>::transfer(
+			RawOrigin::Signed(forward.forwarder.clone()).into(),
+			params,
+			forward.asset_id.clone(),
+			forward.amount,
+			forward.next_memo.clone(),
+		)?;
+		ForwardedPackets::<T>::insert((forward.channel, u64::from(sequence)), forward);
+		Ok(())
+	}
+
+	/// Sends the tokens of a forward that could not be delivered back over the channel they were
+	/// received on. If the refund itself fails the tokens remain with the forwarder.
+	fn refund_forward(
+		forward: ForwardedPacket<T::AccountId, T::AssetId, T::Balance, T::MemoMessage>,
+	) -> Result<(), Ics20Error> {
+		let params = crate::TransferParams::<<T as frame_system::Config>::AccountId> {
+			to: crate::MultiAddress::Raw(forward.original_sender.clone()),
+			source_channel: forward.original_channel,
+			timeout: ibc_primitives::Timeout::Offset {
+				timestamp: Some(forward.timeout),
+				height: Some(1000),
+			},
+		};
+		crate::Pallet::<T>::transfer(
+			RawOrigin::Signed(forward.forwarder.clone()).into(),
+			params,
+			forward.asset_id.clone(),
+			forward.amount,
+			None,
+		)
+		.map_err(|e| {
+			Ics20Error::implementation_specific(format!(
+				"Failed to refund forwarded transfer: {e:?}"
+			))
+		})?;
+
+		crate::Pallet::<T>::deposit_event(Event::<T>::ExecuteMemoIbcTokenTransferRefunded {
+			from: forward.forwarder,
+			to: forward.original_sender,
+			asset_id: forward.asset_id,
+			amount: forward.amount,
+			channel: forward.original_channel,
+		});
+		Ok(())
+	}
 }
 
 impl<T> IbcModule<T>
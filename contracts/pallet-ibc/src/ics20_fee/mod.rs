@@ -289,6 +289,81 @@ where
 		self.inner.on_chan_close_confirm(ctx, output, port_id, channel_id, relayer)
 	}
 
+	fn on_chan_upgrade_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		proposed_connection_hops: &[ConnectionId],
+		proposed_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		self.inner.on_chan_upgrade_init(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			proposed_connection_hops,
+			proposed_version,
+		)
+	}
+
+	fn on_chan_upgrade_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		proposed_connection_hops: &[ConnectionId],
+		counterparty_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		self.inner.on_chan_upgrade_try(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			proposed_connection_hops,
+			counterparty_version,
+		)
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+	) -> Result<(), Ics04Error> {
+		self.inner
+			.on_chan_upgrade_ack(ctx, output, port_id, channel_id, counterparty_version)
+	}
+
+	fn on_chan_upgrade_open(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_upgrade_open(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			version,
+		)
+	}
+
 	fn on_recv_packet(
 		&self,
 		_ctx: &dyn ModuleCallbackContext,
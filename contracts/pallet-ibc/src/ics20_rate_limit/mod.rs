@@ -0,0 +1,530 @@
+use crate::ics20::full_ibc_denom;
+use alloc::{
+	format,
+	string::{String, ToString},
+};
+use core::{fmt::Debug, marker::PhantomData, str::FromStr};
+use ibc::{
+	applications::transfer::{
+		acknowledgement::Acknowledgement as Ics20Ack, error::Error as Ics20Error,
+		packet::PacketData,
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			error::Error as Ics04Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module as IbcModule, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+use sp_runtime::Perbill;
+
+pub use pallet::*;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::OriginFor;
+	use sp_std::vec::Vec;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + crate::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub (super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// denom => quota enforced on ics20 transfers of that denom. Denoms without a quota are not
+	/// limited.
+	pub type RateLimits<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, RateLimit, OptionQuery>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// denom => amounts transferred in the current and previous window
+	pub type Flows<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, Flow, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub (super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		RateLimitSet { denom: Vec<u8>, limit: RateLimit },
+		RateLimitRemoved { denom: Vec<u8> },
+		FlowReset { denom: Vec<u8> },
+		InflowRateLimited { denom: Vec<u8>, amount: u128 },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The window of a rate limit must be at least one block
+		InvalidWindow,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T>
+	where
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+	{
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		pub fn set_rate_limit(
+			origin: OriginFor<T>,
+			denom: Vec<u8>,
+			limit: RateLimit,
+		) -> DispatchResult {
+			<T as crate::Config>::AdminOrigin::ensure_origin(origin)?;
+			ensure!(limit.window > 0, Error::<T>::InvalidWindow);
+			RateLimits::<T>::insert(denom.clone(), limit.clone());
+			Self::deposit_event(Event::<T>::RateLimitSet { denom, limit });
+			Ok(())
+		}
+
+		#[pallet::call_index(1)]
+		#[pallet::weight(0)]
+		pub fn remove_rate_limit(origin: OriginFor<T>, denom: Vec<u8>) -> DispatchResult {
+			<T as crate::Config>::AdminOrigin::ensure_origin(origin)?;
+			RateLimits::<T>::remove(denom.clone());
+			Flows::<T>::remove(denom.clone());
+			Self::deposit_event(Event::<T>::RateLimitRemoved { denom });
+			Ok(())
+		}
+
+		/// Clears the amounts accounted for a denom, e.g. after an incident has been resolved.
+		#[pallet::call_index(2)]
+		#[pallet::weight(0)]
+		pub fn reset_flow(origin: OriginFor<T>, denom: Vec<u8>) -> DispatchResult {
+			<T as crate::Config>::AdminOrigin::ensure_origin(origin)?;
+			Flows::<T>::remove(denom.clone());
+			Self::deposit_event(Event::<T>::FlowReset { denom });
+			Ok(())
+		}
+	}
+}
+
+/// Maximum amounts of a denom that may enter and leave the chain over ics20 within `window`
+/// blocks.
+#[derive(Clone, PartialEq, Eq, codec::Encode, codec::Decode, scale_info::TypeInfo, Debug)]
+pub struct RateLimit {
+	pub max_inflow: u128,
+	pub max_outflow: u128,
+	/// Length of the sliding window in blocks
+	pub window: u32,
+}
+
+/// Amounts of a denom transferred in the current window, along with the totals of the window
+/// before it.
+///
+/// The quota is checked against a sliding window approximated from these two fixed windows: the
+/// previous window's total is weighted by how much of it still overlaps the sliding window.
+#[derive(
+	Clone, Default, PartialEq, Eq, codec::Encode, codec::Decode, scale_info::TypeInfo, Debug,
+)]
+pub struct Flow {
+	pub window_start: u32,
+	pub inflow: u128,
+	pub outflow: u128,
+	pub previous_inflow: u128,
+	pub previous_outflow: u128,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+	In,
+	Out,
+}
+
+impl Flow {
+	/// Moves the current window forward so that it contains `now`.
+	fn roll(&mut self, now: u32, window: u32) {
+		let elapsed = now.saturating_sub(self.window_start);
+		if elapsed < window {
+			return
+		}
+		if elapsed < window.saturating_mul(2) {
+			self.previous_inflow = self.inflow;
+			self.previous_outflow = self.outflow;
+		} else {
+			self.previous_inflow = 0;
+			self.previous_outflow = 0;
+		}
+		self.inflow = 0;
+		self.outflow = 0;
+		self.window_start = now - elapsed % window;
+	}
+
+	/// Amount transferred in `direction` within the sliding window ending at `now`.
+	fn sliding_total(&self, direction: Direction, now: u32, window: u32) -> u128 {
+		let (current, previous) = match direction {
+			Direction::In => (self.inflow, self.previous_inflow),
+			Direction::Out => (self.outflow, self.previous_outflow),
+		};
+		let elapsed = now.saturating_sub(self.window_start).min(window);
+		current.saturating_add(Perbill::from_rational(window - elapsed, window) * previous)
+	}
+}
+
+/// Hook through which pallet-ibc accounts for ics20 transfers leaving the chain.
+pub trait Ics20RateLimiter {
+	/// Checks that sending `amount` of `denom` stays within its outflow quota
+	fn ensure_outflow(denom: &str, amount: u128) -> Result<(), String>;
+	/// Records `amount` of `denom` that has been sent
+	fn record_outflow(denom: &str, amount: u128);
+}
+
+impl Ics20RateLimiter for () {
+	fn ensure_outflow(_denom: &str, _amount: u128) -> Result<(), String> {
+		Ok(())
+	}
+
+	fn record_outflow(_denom: &str, _amount: u128) {}
+}
+
+impl<T: Config> Ics20RateLimiter for Pallet<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	fn ensure_outflow(denom: &str, amount: u128) -> Result<(), String> {
+		Self::ensure_flow(denom, amount, Direction::Out)
+	}
+
+	fn record_outflow(denom: &str, amount: u128) {
+		Self::record_flow(denom, amount, Direction::Out)
+	}
+}
+
+impl<T: Config> Pallet<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	fn ensure_flow(denom: &str, amount: u128, direction: Direction) -> Result<(), String> {
+		let Some(limit) = RateLimits::<T>::get(denom.as_bytes()) else { return Ok(()) };
+		let now = u32::from(frame_system::Pallet::<T>::block_number());
+		let mut flow = Flows::<T>::get(denom.as_bytes());
+		flow.roll(now, limit.window);
+		let max = match direction {
+			Direction::In => limit.max_inflow,
+			Direction::Out => limit.max_outflow,
+		};
+		let total = flow.sliding_total(direction, now, limit.window).saturating_add(amount);
+		if total > max {
+			return Err(format!(
+				"{direction:?} rate limit exceeded for {denom}: {total} over a quota of {max}"
+			))
+		}
+		Ok(())
+	}
+
+	fn record_flow(denom: &str, amount: u128, direction: Direction) {
+		let Some(limit) = RateLimits::<T>::get(denom.as_bytes()) else { return };
+		let now = u32::from(frame_system::Pallet::<T>::block_number());
+		Flows::<T>::mutate(denom.as_bytes(), |flow| {
+			flow.roll(now, limit.window);
+			match direction {
+				Direction::In => flow.inflow = flow.inflow.saturating_add(amount),
+				Direction::Out => flow.outflow = flow.outflow.saturating_add(amount),
+			}
+		});
+	}
+
+	/// Gives back the outflow quota used by a transfer that was refunded.
+	fn revert_outflow(denom: &str, amount: u128) {
+		if !Flows::<T>::contains_key(denom.as_bytes()) {
+			return
+		}
+		Flows::<T>::mutate(denom.as_bytes(), |flow| {
+			flow.outflow = flow.outflow.saturating_sub(amount);
+		});
+	}
+}
+
+/// Middleware enforcing the inflow quotas of [`RateLimits`] on received ics20 packets and
+/// releasing outflow quota for sent packets that fail or time out.
+///
+/// Packets exceeding a quota are rejected with an error acknowledgement, which refunds the sender
+/// on the counterparty chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ics20RateLimit<T: Config, S: IbcModule + Clone + Default + PartialEq + Eq + Debug> {
+	inner: S,
+	_phantom: PhantomData<T>,
+}
+
+impl<T: Config + Send + Sync, S: IbcModule + Clone + Default + PartialEq + Eq + Debug> Default
+	for Ics20RateLimit<T, S>
+{
+	fn default() -> Self {
+		Self { inner: S::default(), _phantom: Default::default() }
+	}
+}
+
+impl<T: Config + Send + Sync, S: IbcModule + Clone + Default + PartialEq + Eq + Debug> IbcModule
+	for Ics20RateLimit<T, S>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_open_init(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+			relayer,
+		)
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		self.inner.on_chan_open_try(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+			counterparty_version,
+			relayer,
+		)
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner
+			.on_chan_open_ack(ctx, output, port_id, channel_id, counterparty_version, relayer)
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_open_confirm(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_close_init(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_close_confirm(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_upgrade_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		proposed_connection_hops: &[ConnectionId],
+		proposed_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		self.inner.on_chan_upgrade_init(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			proposed_connection_hops,
+			proposed_version,
+		)
+	}
+
+	fn on_chan_upgrade_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		proposed_connection_hops: &[ConnectionId],
+		counterparty_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		self.inner.on_chan_upgrade_try(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			proposed_connection_hops,
+			counterparty_version,
+		)
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+	) -> Result<(), Ics04Error> {
+		self.inner
+			.on_chan_upgrade_ack(ctx, output, port_id, channel_id, counterparty_version)
+	}
+
+	fn on_chan_upgrade_open(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_upgrade_open(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			version,
+		)
+	}
+
+	fn on_recv_packet(
+		&self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		let packet_data =
+			serde_json::from_slice::<PacketData>(packet.data.as_slice()).map_err(|e| {
+				Ics04Error::implementation_specific(format!("Failed to decode packet data {e:?}"))
+			})?;
+		let denom = full_ibc_denom(packet, packet_data.token.clone());
+		let amount = packet_data.token.amount.as_u256().low_u128();
+
+		if let Err(e) = Pallet::<T>::ensure_flow(&denom, amount, Direction::In) {
+			log::warn!(target: "pallet_ibc", "Rejecting packet: {e}");
+			Pallet::<T>::deposit_event(Event::<T>::InflowRateLimited {
+				denom: denom.into_bytes(),
+				amount,
+			});
+			return Ok(Acknowledgement::from_bytes(
+				Ics20Ack::from_error(Ics20Error::implementation_specific(e))
+					.to_string()
+					.into_bytes(),
+			))
+		}
+
+		let ack = self.inner.on_recv_packet(ctx, output, packet, relayer)?;
+		let is_successful = Ics20Ack::from_str(&String::from_utf8_lossy(ack.as_ref()))
+			.map(|ack| ack.is_successful())
+			.unwrap_or_default();
+		if is_successful {
+			Pallet::<T>::record_flow(&denom, amount, Direction::In);
+		}
+		Ok(ack)
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &Acknowledgement,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner
+			.on_acknowledgement_packet(ctx, output, packet, acknowledgement, relayer)?;
+		let is_successful = Ics20Ack::from_str(&String::from_utf8_lossy(acknowledgement.as_ref()))
+			.map(|ack| ack.is_successful())
+			.unwrap_or_default();
+		if !is_successful {
+			Self::revert_outflow(packet);
+		}
+		Ok(())
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_timeout_packet(ctx, output, packet, relayer)?;
+		Self::revert_outflow(packet);
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync, S: IbcModule + Clone + Default + PartialEq + Eq + Debug>
+	Ics20RateLimit<T, S>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	fn revert_outflow(packet: &Packet) {
+		if let Ok(packet_data) = serde_json::from_slice::<PacketData>(packet.data.as_slice()) {
+			Pallet::<T>::revert_outflow(
+				&packet_data.token.denom.to_string(),
+				packet_data.token.amount.as_u256().low_u128(),
+			);
+		}
+	}
+}
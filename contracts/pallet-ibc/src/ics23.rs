@@ -5,6 +5,7 @@
 //! just to recalculate its root hash.
 
 pub mod acknowledgements;
+pub mod channel_upgrades;
 pub mod channels;
 pub mod client_states;
 pub mod clients;
@@ -15,3 +16,33 @@ pub mod next_seq_recv;
 pub mod next_seq_send;
 pub mod packet_commitments;
 pub mod receipts;
+
+use crate::Config;
+use frame_support::storage::{child, child::ChildInfo};
+use sp_core::Get;
+use sp_std::{iter, prelude::*};
+
+/// Iterates over the entries of the ibc child trie whose keys start with `prefix` in the order of
+/// their keys, from the one whose key is `start` when given. Unlike a prefix iterator, the entries
+/// before `start` are never read, so that a page of a paginated query only reads its own entries.
+pub fn iter_from<T: Config>(
+	prefix: Vec<u8>,
+	start: Option<Vec<u8>>,
+) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> {
+	let child_info = ChildInfo::new_default(T::PalletPrefix::get());
+	let start = start.unwrap_or_else(|| prefix.clone());
+	let first = if child::exists(&child_info, &start) {
+		Some(start)
+	} else {
+		sp_io::default_child_storage::next_key(child_info.storage_key(), &start)
+	};
+	let keys_info = child_info.clone();
+	iter::successors(first, move |key| {
+		sp_io::default_child_storage::next_key(keys_info.storage_key(), key)
+	})
+	.take_while(move |key| key.starts_with(&prefix))
+	.filter_map(move |key| {
+		let value = child::get_raw(&child_info, &key)?;
+		Some((key, value))
+	})
+}
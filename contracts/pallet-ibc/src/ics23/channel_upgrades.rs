@@ -0,0 +1,94 @@
+use crate::{format, Config};
+use frame_support::storage::{child, child::ChildInfo};
+use ibc::core::{
+	ics04_channel::upgrade::{ErrorReceipt, Upgrade},
+	ics24_host::{
+		identifier::{ChannelId, PortId},
+		path::{ChannelUpgradeErrorPath, ChannelUpgradePath},
+	},
+};
+use ibc_primitives::apply_prefix;
+use sp_core::Get;
+use sp_std::{marker::PhantomData, prelude::*};
+use tendermint_proto::Protobuf;
+
+/// (port_id, channel_id) => Upgrade
+/// trie key path: "channelUpgrades/upgrades/ports/{port_id}/channels/{channel_id}"
+pub struct ChannelUpgrades<T>(PhantomData<T>);
+
+impl<T: Config> ChannelUpgrades<T> {
+	pub fn get(port_id: PortId, channel_id: ChannelId) -> Option<Vec<u8>> {
+		let upgrade_path = format!("{}", ChannelUpgradePath(port_id, channel_id));
+		let upgrade_key = apply_prefix(T::PalletPrefix::get(), vec![upgrade_path]);
+		child::get(&ChildInfo::new_default(T::PalletPrefix::get()), &upgrade_key)
+	}
+
+	pub fn insert(port_id: PortId, channel_id: ChannelId, upgrade: Upgrade) {
+		let upgrade_path = format!("{}", ChannelUpgradePath(port_id, channel_id));
+		let upgrade_key = apply_prefix(T::PalletPrefix::get(), vec![upgrade_path]);
+		child::put(
+			&ChildInfo::new_default(T::PalletPrefix::get()),
+			&upgrade_key,
+			&upgrade.encode_vec().expect("encode upgrade"),
+		);
+	}
+
+	pub fn remove(port_id: PortId, channel_id: ChannelId) {
+		let upgrade_path = format!("{}", ChannelUpgradePath(port_id, channel_id));
+		let upgrade_key = apply_prefix(T::PalletPrefix::get(), vec![upgrade_path]);
+		child::kill(&ChildInfo::new_default(T::PalletPrefix::get()), &upgrade_key)
+	}
+}
+
+/// (port_id, channel_id) => Upgrade
+/// trie key path: "channelUpgrades/counterpartyUpgrade/ports/{port_id}/channels/{channel_id}"
+pub struct CounterpartyChannelUpgrades<T>(PhantomData<T>);
+
+impl<T: Config> CounterpartyChannelUpgrades<T> {
+	fn key(port_id: PortId, channel_id: ChannelId) -> Vec<u8> {
+		let upgrade_path =
+			format!("channelUpgrades/counterpartyUpgrade/ports/{port_id}/channels/{channel_id}");
+		apply_prefix(T::PalletPrefix::get(), vec![upgrade_path])
+	}
+
+	pub fn get(port_id: PortId, channel_id: ChannelId) -> Option<Vec<u8>> {
+		child::get(&ChildInfo::new_default(T::PalletPrefix::get()), &Self::key(port_id, channel_id))
+	}
+
+	pub fn insert(port_id: PortId, channel_id: ChannelId, upgrade: Upgrade) {
+		child::put(
+			&ChildInfo::new_default(T::PalletPrefix::get()),
+			&Self::key(port_id, channel_id),
+			&upgrade.encode_vec().expect("encode upgrade"),
+		);
+	}
+
+	pub fn remove(port_id: PortId, channel_id: ChannelId) {
+		child::kill(
+			&ChildInfo::new_default(T::PalletPrefix::get()),
+			&Self::key(port_id, channel_id),
+		)
+	}
+}
+
+/// (port_id, channel_id) => ErrorReceipt
+/// trie key path: "channelUpgrades/upgradeError/ports/{port_id}/channels/{channel_id}"
+pub struct ChannelUpgradeErrors<T>(PhantomData<T>);
+
+impl<T: Config> ChannelUpgradeErrors<T> {
+	pub fn get(port_id: PortId, channel_id: ChannelId) -> Option<Vec<u8>> {
+		let error_path = format!("{}", ChannelUpgradeErrorPath(port_id, channel_id));
+		let error_key = apply_prefix(T::PalletPrefix::get(), vec![error_path]);
+		child::get(&ChildInfo::new_default(T::PalletPrefix::get()), &error_key)
+	}
+
+	pub fn insert(port_id: PortId, channel_id: ChannelId, error_receipt: ErrorReceipt) {
+		let error_path = format!("{}", ChannelUpgradeErrorPath(port_id, channel_id));
+		let error_key = apply_prefix(T::PalletPrefix::get(), vec![error_path]);
+		child::put(
+			&ChildInfo::new_default(T::PalletPrefix::get()),
+			&error_key,
+			&error_receipt.encode_vec().expect("encode error receipt"),
+		);
+	}
+}
@@ -1,3 +1,4 @@
+use super::iter_from;
 use crate::{format, Config};
 use alloc::string::{String, ToString};
 use frame_support::storage::{child, child::ChildInfo, ChildTriePrefixIterator};
@@ -55,4 +56,24 @@ impl<T: Config> Channels<T> {
 				None
 			})
 	}
+
+	/// The channel ends from the one whose trie key is `start` on, along with their trie keys.
+	pub fn iter_from(
+		start: Option<Vec<u8>>,
+	) -> impl Iterator<Item = (Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>)> {
+		let prefix = apply_prefix(T::PalletPrefix::get(), vec!["channelEnds/ports/".to_string()]);
+		let path_start = T::PalletPrefix::get().len();
+		iter_from::<T>(prefix, start).filter_map(move |(key, value)| {
+			let path = core::str::from_utf8(&key[path_start..]).ok()?;
+			match Path::from_str(path).ok()? {
+				Path::ChannelEnds(ChannelEndsPath(port_id, channel_id)) => Some((
+					key,
+					port_id.as_bytes().to_vec(),
+					channel_id.to_string().as_bytes().to_vec(),
+					value,
+				)),
+				_ => None,
+			}
+		})
+	}
 }
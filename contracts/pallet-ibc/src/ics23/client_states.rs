@@ -1,3 +1,4 @@
+use super::iter_from;
 use crate::{format, Config};
 use alloc::string::{String, ToString};
 use frame_support::storage::{child, child::ChildInfo, ChildTriePrefixIterator};
@@ -47,4 +48,17 @@ impl<T: Config> ClientStates<T> {
 				None
 			})
 	}
+
+	/// The client states from the one whose trie key is `start` on, along with their trie keys.
+	pub fn iter_from(start: Option<Vec<u8>>) -> impl Iterator<Item = (Vec<u8>, ClientId, Vec<u8>)> {
+		let prefix = apply_prefix(T::PalletPrefix::get(), vec!["clients/".to_string()]);
+		let path_start = T::PalletPrefix::get().len();
+		iter_from::<T>(prefix, start).filter_map(move |(key, value)| {
+			let path = core::str::from_utf8(&key[path_start..]).ok()?;
+			match Path::from_str(path).ok()? {
+				Path::ClientState(ClientStatePath(client_id)) => Some((key, client_id, value)),
+				_ => None,
+			}
+		})
+	}
 }
@@ -1,3 +1,4 @@
+use super::iter_from;
 use crate::{format, Config};
 use alloc::string::ToString;
 use frame_support::storage::{child, child::ChildInfo, ChildTriePrefixIterator};
@@ -38,4 +39,13 @@ impl<T: Config> Connections<T> {
 		let key = apply_prefix(T::PalletPrefix::get(), vec![prefix_path]);
 		ChildTriePrefixIterator::with_prefix(&ChildInfo::new_default(T::PalletPrefix::get()), &key)
 	}
+
+	/// The connection ends from the one whose trie key is `start` on, along with their trie keys
+	/// and the identifiers of the connections.
+	pub fn iter_from(start: Option<Vec<u8>>) -> impl Iterator<Item = (Vec<u8>, Vec<u8>, Vec<u8>)> {
+		let prefix = apply_prefix(T::PalletPrefix::get(), vec!["connections/".to_string()]);
+		let id_start = prefix.len();
+		iter_from::<T>(prefix, start)
+			.map(move |(key, value)| (key.clone(), key[id_start..].to_vec(), value))
+	}
 }
@@ -35,4 +35,15 @@ impl<T: Config> ConsensusStates<T> {
 		let key = apply_prefix(T::PalletPrefix::get(), vec![path]);
 		child::put(&ChildInfo::new_default(T::PalletPrefix::get()), &key, &consensus_state)
 	}
+
+	pub fn remove(client_id: ClientId, height: Height) {
+		let consensus_path = ClientConsensusStatePath {
+			client_id,
+			epoch: height.revision_number,
+			height: height.revision_height,
+		};
+		let path = format!("{consensus_path}");
+		let key = apply_prefix(T::PalletPrefix::get(), vec![path]);
+		child::kill(&ChildInfo::new_default(T::PalletPrefix::get()), &key)
+	}
 }
@@ -53,6 +53,18 @@ impl<T: Config> PacketCommitment<T> {
 		child::exists(&ChildInfo::new_default(T::PalletPrefix::get()), &commitment_key)
 	}
 
+	/// Returns true if any packet sent on the given channel still has a commitment.
+	pub fn contains_channel(port_id: PortId, channel_id: ChannelId) -> bool {
+		let prefix = format!("commitments/ports/{port_id}/channels/{channel_id}/sequences/");
+		let prefix_key = apply_prefix(T::PalletPrefix::get(), vec![prefix]);
+		ChildTriePrefixIterator::with_prefix(
+			&ChildInfo::new_default(T::PalletPrefix::get()),
+			&prefix_key,
+		)
+		.next()
+		.is_some()
+	}
+
 	// WARNING: too expensive to be called from an on-chain context, only here for rpc layer.
 	pub fn iter() -> impl Iterator<Item = ((PortId, ChannelId, Sequence), Vec<u8>)> {
 		let prefix = "commitments/ports/".to_string();
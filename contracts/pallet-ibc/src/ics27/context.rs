@@ -0,0 +1,190 @@
+use super::{pallet::*, IcaHostExecutor};
+use crate::routing::Context;
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use codec::Encode;
+use core::str::FromStr;
+use frame_support::{
+	storage::{with_transaction, TransactionOutcome},
+	traits::Get,
+};
+use ibc::{
+	applications::interchain_accounts::{
+		controller::context::{IcaControllerContext, IcaControllerKeeper, IcaControllerReader},
+		error::Error as IcaError,
+		host::context::{IcaHostContext, IcaHostKeeper, IcaHostReader},
+	},
+	core::ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+	signer::Signer,
+};
+use ibc_proto::google::protobuf::Any;
+use sp_core::crypto::AccountId32;
+use sp_io::hashing::blake2_256;
+use sp_runtime::{traits::AccountIdConversion, DispatchError};
+
+fn channel_id(channel_id: Vec<u8>) -> Option<ChannelId> {
+	String::from_utf8(channel_id).ok().and_then(|id| ChannelId::from_str(&id).ok())
+}
+
+impl<T: Config + Send + Sync> IcaControllerReader for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn is_controller_enabled(&self) -> bool {
+		T::IsControllerEnabled::get()
+	}
+
+	fn active_channel(&self, connection_id: &ConnectionId, port_id: &PortId) -> Option<ChannelId> {
+		ControllerActiveChannels::<T>::get(connection_id.as_bytes(), port_id.as_bytes())
+			.and_then(channel_id)
+	}
+
+	fn interchain_account_address(
+		&self,
+		connection_id: &ConnectionId,
+		port_id: &PortId,
+	) -> Option<String> {
+		ControllerAccounts::<T>::get(connection_id.as_bytes(), port_id.as_bytes())
+			.and_then(|address| String::from_utf8(address).ok())
+	}
+}
+
+impl<T: Config + Send + Sync> IcaControllerKeeper for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn store_active_channel(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+		channel_id: ChannelId,
+	) -> Result<(), IcaError> {
+		ControllerActiveChannels::<T>::insert(
+			connection_id.as_bytes(),
+			port_id.as_bytes(),
+			channel_id.to_string().into_bytes(),
+		);
+		Ok(())
+	}
+
+	fn store_interchain_account_address(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+		address: String,
+	) -> Result<(), IcaError> {
+		ControllerAccounts::<T>::insert(
+			connection_id.as_bytes(),
+			port_id.as_bytes(),
+			address.into_bytes(),
+		);
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> IcaControllerContext for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+}
+
+impl<T: Config + Send + Sync> IcaHostReader for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	type AccountId = T::AccountId;
+
+	fn is_host_enabled(&self) -> bool {
+		T::IsHostEnabled::get()
+	}
+
+	fn is_message_allowed(&self, type_url: &str) -> bool {
+		T::HostExecutor::is_message_allowed(type_url)
+	}
+
+	fn active_channel(&self, connection_id: &ConnectionId, port_id: &PortId) -> Option<ChannelId> {
+		HostActiveChannels::<T>::get(connection_id.as_bytes(), port_id.as_bytes())
+			.and_then(channel_id)
+	}
+
+	fn interchain_account(
+		&self,
+		connection_id: &ConnectionId,
+		port_id: &PortId,
+	) -> Option<<Self as IcaHostReader>::AccountId> {
+		HostAccounts::<T>::get(connection_id.as_bytes(), port_id.as_bytes())
+	}
+}
+
+impl<T: Config + Send + Sync> IcaHostKeeper for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	type AccountId = T::AccountId;
+
+	fn register_interchain_account(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+	) -> Result<Signer, IcaError> {
+		let (connection, port) = (connection_id.as_bytes(), port_id.as_bytes());
+		let account = match HostAccounts::<T>::get(connection, port) {
+			Some(account) => account,
+			None => {
+				// owner ports are too long for the account to be derived from them directly
+				let account: T::AccountId = <T as Config>::PalletId::get()
+					.into_sub_account_truncating(blake2_256(&(connection, port).encode()));
+				HostAccounts::<T>::insert(connection, port, account.clone());
+				Pallet::<T>::deposit_event(Event::<T>::HostAccountRegistered {
+					connection_id: connection.to_vec(),
+					controller_port_id: port.to_vec(),
+					account: account.clone(),
+				});
+				account
+			},
+		};
+		Ok(crate::signer::<T>(account))
+	}
+
+	fn store_active_channel(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+		channel_id: ChannelId,
+	) -> Result<(), IcaError> {
+		HostActiveChannels::<T>::insert(
+			connection_id.as_bytes(),
+			port_id.as_bytes(),
+			channel_id.to_string().into_bytes(),
+		);
+		Ok(())
+	}
+
+	fn execute_tx(
+		&mut self,
+		account: &<Self as IcaHostKeeper>::AccountId,
+		messages: Vec<Any>,
+	) -> Result<Vec<u8>, IcaError> {
+		with_transaction(|| match T::HostExecutor::execute(account, messages) {
+			Ok(result) => TransactionOutcome::Commit(Ok(result)),
+			Err(e) => TransactionOutcome::Rollback(Err::<_, DispatchError>(e)),
+		})
+		.map_err(|e| IcaError::implementation_specific(format!("{e:?}")))
+	}
+}
+
+impl<T: Config + Send + Sync> IcaHostContext for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	type AccountId = T::AccountId;
+}
@@ -0,0 +1,702 @@
+//! ICS-27 interchain accounts.
+//!
+//! As a controller, the pallet lets accounts register interchain accounts on other chains with
+//! [`Pallet::register_interchain_account`] and execute transactions with them through
+//! [`Pallet::send_tx`]. The results are handed to [`Config::ControllerHooks`]. As a host, it
+//! registers the accounts of controller chains and executes the messages they send with
+//! [`Config::HostExecutor`].
+//!
+//! [`ControllerModule`] and [`HostModule`] have to be added to the runtime router, which can use
+//! [`lookup_module_by_port`] to route the `icacontroller-{owner}` and `icahost` ports.
+
+mod context;
+
+use crate::routing::Context;
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use core::str::FromStr;
+use ibc::{
+	applications::interchain_accounts::{
+		controller::context as controller, host::context as host, is_controller_port,
+		CONTROLLER_MODULE_ID_STR, CONTROLLER_PORT_PREFIX, HOST_MODULE_ID_STR, HOST_PORT_ID_STR,
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			context::ChannelReader,
+			error::Error as Ics04Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module, ModuleCallbackContext, ModuleId, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+use ibc_primitives::{HandlerMessage, IbcAccount, IbcHandler};
+use ibc_proto::google::protobuf::Any;
+use sp_core::crypto::AccountId32;
+use sp_runtime::{traits::IdentifyAccount, DispatchError};
+use sp_std::marker::PhantomData;
+
+pub use pallet::*;
+
+/// Executes the transactions of the interchain accounts hosted by this chain.
+pub trait IcaHostExecutor<AccountId> {
+	/// Returns true iff interchain accounts may execute messages of this type
+	fn is_message_allowed(type_url: &str) -> bool;
+
+	/// Executes the messages on behalf of the interchain account, returns the result
+	/// acknowledged to the controller chain. The pallet reverts the changes of failed executions.
+	fn execute(account: &AccountId, messages: Vec<Any>) -> Result<Vec<u8>, DispatchError>;
+}
+
+/// Notified of the outcome of the transactions sent to interchain accounts.
+pub trait IcaControllerHooks<AccountId> {
+	/// The host executed the transaction: `result` is the base64 encoded result of the execution,
+	/// or its error.
+	fn on_acknowledgement(
+		owner: &AccountId,
+		connection_id: &[u8],
+		sequence: u64,
+		result: Result<Vec<u8>, Vec<u8>>,
+	);
+
+	/// The transaction timed out, the channel of the account is closed and has to be reopened
+	/// with [`Pallet::register_interchain_account`].
+	fn on_timeout(owner: &AccountId, connection_id: &[u8], sequence: u64);
+}
+
+impl<AccountId> IcaControllerHooks<AccountId> for () {
+	fn on_acknowledgement(
+		_owner: &AccountId,
+		_connection_id: &[u8],
+		_sequence: u64,
+		_result: Result<Vec<u8>, Vec<u8>>,
+	) {
+	}
+
+	fn on_timeout(_owner: &AccountId, _connection_id: &[u8], _sequence: u64) {}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::{IcaControllerHooks, IcaHostExecutor};
+	use crate::{routing::Context, signer};
+	use alloc::{string::String, vec::Vec};
+	use core::str::FromStr;
+	use frame_support::{pallet_prelude::*, PalletId};
+	use frame_system::pallet_prelude::{ensure_signed, OriginFor};
+	use ibc::{
+		applications::interchain_accounts::{
+			controller::{
+				handler,
+				msgs::{MsgRegisterInterchainAccount, MsgSendTx},
+			},
+			packet::InterchainAccountPacketData,
+		},
+		core::{ics04_channel::Version, ics24_host::identifier::ConnectionId},
+		handler::HandlerOutputBuilder,
+	};
+	use ibc_primitives::{HandlerMessage, IbcAccount, IbcHandler};
+	use ibc_proto::google::protobuf::Any;
+	use sp_core::crypto::AccountId32;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + crate::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		/// Id the interchain accounts hosted by this chain are derived from
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// Whether accounts of this chain can register interchain accounts on other chains
+		type IsControllerEnabled: Get<bool>;
+		/// Whether other chains can register interchain accounts on this chain
+		type IsHostEnabled: Get<bool>;
+		/// Executes the transactions of hosted interchain accounts
+		type HostExecutor: IcaHostExecutor<Self::AccountId>;
+		/// Notified of the outcome of the transactions sent to interchain accounts
+		type ControllerHooks: IcaControllerHooks<Self::AccountId>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	/// connection_id, controller port_id => channel of the interchain account registered by an
+	/// account of this chain
+	pub type ControllerActiveChannels<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// connection_id, controller port_id => address of the interchain account on the host chain
+	pub type ControllerAccounts<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// connection_id, controller port_id => channel of the interchain account hosted by this chain
+	pub type HostActiveChannels<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// connection_id, controller port_id => interchain account hosted by this chain
+	pub type HostAccounts<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		T::AccountId,
+		OptionQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub (super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The channel of an interchain account is being opened
+		InterchainAccountRequested { owner: T::AccountId, connection_id: Vec<u8> },
+		/// The host chain registered an interchain account
+		InterchainAccountRegistered {
+			owner: T::AccountId,
+			connection_id: Vec<u8>,
+			address: Vec<u8>,
+		},
+		/// A transaction was sent to an interchain account
+		TxSent { owner: T::AccountId, connection_id: Vec<u8>, sequence: u64 },
+		/// A controller chain registered an interchain account hosted by this chain
+		HostAccountRegistered {
+			connection_id: Vec<u8>,
+			controller_port_id: Vec<u8>,
+			account: T::AccountId,
+		},
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Invalid connection identifier
+		InvalidIdentifier,
+		/// Invalid channel version
+		InvalidVersion,
+		/// The interchain accounts controller rejected the message
+		Controller,
+		/// Failed to open the channel of the interchain account
+		ChannelInitFailed,
+	}
+
+	/// A message executed by an interchain account, protobuf encoded
+	#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub struct IcaMessage {
+		pub type_url: Vec<u8>,
+		pub value: Vec<u8>,
+	}
+
+	#[pallet::call]
+	impl<T: Config + Send + Sync> Pallet<T>
+	where
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+		AccountId32: From<<T as frame_system::Config>::AccountId>,
+		<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+	{
+		/// Opens the channel of an interchain account of the caller on the host chain of the
+		/// connection. The default metadata of the connection is used if the version is empty.
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		#[frame_support::transactional]
+		pub fn register_interchain_account(
+			origin: OriginFor<T>,
+			connection_id: Vec<u8>,
+			version: Vec<u8>,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let msg = MsgRegisterInterchainAccount {
+				owner: signer::<T>(owner.clone()),
+				connection_id: Self::parse_connection(&connection_id)?,
+				version: Version::new(
+					String::from_utf8(version).map_err(|_| Error::<T>::InvalidVersion)?,
+				),
+			};
+			let ctx = Context::<T>::default();
+			let msg = handler::register_interchain_account(&ctx, msg).map_err(|e| {
+				log::debug!(target: "pallet_ibc", "[ics27]: register failed: {:?}", e);
+				Error::<T>::Controller
+			})?;
+			crate::Pallet::<T>::handle_message(HandlerMessage::OpenChannel {
+				port_id: msg.port_id,
+				channel_end: msg.channel,
+			})
+			.map_err(|e| {
+				log::debug!(target: "pallet_ibc", "[ics27]: channel init failed: {:?}", e);
+				Error::<T>::ChannelInitFailed
+			})?;
+			Self::deposit_event(Event::<T>::InterchainAccountRequested { owner, connection_id });
+			Ok(())
+		}
+
+		/// Sends messages to be executed by the interchain account of the caller, as a single
+		/// transaction. `relative_timeout` is in nanoseconds.
+		#[pallet::call_index(1)]
+		#[pallet::weight(0)]
+		#[frame_support::transactional]
+		pub fn send_tx(
+			origin: OriginFor<T>,
+			connection_id: Vec<u8>,
+			messages: Vec<IcaMessage>,
+			memo: Vec<u8>,
+			relative_timeout: u64,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let messages = messages
+				.into_iter()
+				.map(|msg| {
+					Ok(Any {
+						type_url: String::from_utf8(msg.type_url)
+							.map_err(|_| Error::<T>::Controller)?,
+						value: msg.value,
+					})
+				})
+				.collect::<Result<Vec<_>, Error<T>>>()?;
+			let msg = MsgSendTx {
+				owner: signer::<T>(owner.clone()),
+				connection_id: Self::parse_connection(&connection_id)?,
+				packet_data: InterchainAccountPacketData::execute_tx(
+					messages,
+					String::from_utf8(memo).map_err(|_| Error::<T>::Controller)?,
+				),
+				relative_timeout,
+			};
+			let mut ctx = Context::<T>::default();
+			let mut output = HandlerOutputBuilder::new();
+			let sequence = handler::send_tx(&mut ctx, &mut output, msg).map_err(|e| {
+				log::debug!(target: "pallet_ibc", "[ics27]: send tx failed: {:?}", e);
+				Error::<T>::Controller
+			})?;
+			crate::Pallet::<T>::deposit_event(output.with_result(()).events.into());
+			Self::deposit_event(Event::<T>::TxSent {
+				owner,
+				connection_id,
+				sequence: sequence.into(),
+			});
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn parse_connection(connection_id: &[u8]) -> Result<ConnectionId, Error<T>> {
+			core::str::from_utf8(connection_id)
+				.ok()
+				.and_then(|connection_id| ConnectionId::from_str(connection_id).ok())
+				.ok_or(Error::<T>::InvalidIdentifier)
+		}
+	}
+}
+
+/// The account of this chain owning the interchain accounts of a controller port
+fn owner<T: Config>(port_id: &PortId) -> Option<T::AccountId> {
+	let owner = port_id.as_str().strip_prefix(CONTROLLER_PORT_PREFIX)?;
+	let owner = Signer::from_str(owner).ok()?;
+	<T as crate::Config>::AccountIdConversion::try_from(owner)
+		.ok()
+		.map(|account| account.into_account())
+}
+
+/// The connection of the channel a packet was sent on
+fn packet_connection<T: Config + Send + Sync>(packet: &Packet) -> Result<ConnectionId, Ics04Error>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	let channel_end = Context::<T>::default()
+		.channel_end(&(packet.source_port.clone(), packet.source_channel))?;
+	channel_end
+		.connection_hops()
+		.first()
+		.cloned()
+		.ok_or_else(|| Ics04Error::implementation_specific("channel has no connection".into()))
+}
+
+/// Routes the interchain accounts ports to their modules
+pub fn lookup_module_by_port(port_id: &PortId) -> Option<ModuleId> {
+	if is_controller_port(port_id) {
+		ModuleId::from_str(CONTROLLER_MODULE_ID_STR).ok()
+	} else if port_id.as_str() == HOST_PORT_ID_STR {
+		ModuleId::from_str(HOST_MODULE_ID_STR).ok()
+	} else {
+		None
+	}
+}
+
+fn to_ics04_error(e: ibc::applications::interchain_accounts::error::Error) -> Ics04Error {
+	Ics04Error::implementation_specific(e.to_string())
+}
+
+#[derive(Clone, Eq, Debug, PartialEq)]
+pub struct ControllerModule<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for ControllerModule<T> {
+	fn default() -> Self {
+		Self(PhantomData::default())
+	}
+}
+
+impl<T: Config + Send + Sync> Module for ControllerModule<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		controller::on_chan_open_init(
+			&mut ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+		)
+		.map_err(to_ics04_error)
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		controller::on_chan_open_try(
+			&mut ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+			counterparty_version,
+		)
+		.map_err(to_ics04_error)
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		controller::on_chan_open_ack(&mut ctx, output, port_id, channel_id, counterparty_version)
+			.map_err(to_ics04_error)
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		controller::on_chan_open_confirm(&mut ctx, output, port_id, channel_id)
+			.map_err(to_ics04_error)
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		controller::on_chan_close_init(&mut ctx, output, port_id, channel_id)
+			.map_err(to_ics04_error)
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		controller::on_chan_close_confirm(&mut ctx, output, port_id, channel_id)
+			.map_err(to_ics04_error)
+	}
+
+	fn on_recv_packet(
+		&self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		let ctx = Context::<T>::default();
+		controller::on_recv_packet(&ctx, output, packet, relayer).map_err(to_ics04_error)?;
+		Err(Ics04Error::implementation_specific("the controller doesn't receive packets".into()))
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &Acknowledgement,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		let ack = controller::on_acknowledgement_packet(
+			&mut ctx,
+			output,
+			packet,
+			acknowledgement,
+			relayer,
+		)
+		.map_err(to_ics04_error)?;
+		if let Some(owner) = owner::<T>(&packet.source_port) {
+			let connection_id = packet_connection::<T>(packet)?;
+			let result = ack.into_result().map(String::into_bytes).map_err(String::into_bytes);
+			T::ControllerHooks::on_acknowledgement(
+				&owner,
+				connection_id.as_bytes(),
+				packet.sequence.into(),
+				result,
+			);
+		}
+		Ok(())
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		controller::on_timeout_packet(&mut ctx, output, packet, relayer).map_err(to_ics04_error)?;
+		if let Some(owner) = owner::<T>(&packet.source_port) {
+			let connection_id = packet_connection::<T>(packet)?;
+			T::ControllerHooks::on_timeout(
+				&owner,
+				connection_id.as_bytes(),
+				packet.sequence.into(),
+			);
+		}
+		Ok(())
+	}
+}
+
+#[derive(Clone, Eq, Debug, PartialEq)]
+pub struct HostModule<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for HostModule<T> {
+	fn default() -> Self {
+		Self(PhantomData::default())
+	}
+}
+
+impl<T: Config + Send + Sync> Module for HostModule<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		host::on_chan_open_init(
+			&mut ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+		)
+		.map_err(to_ics04_error)
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		host::on_chan_open_try(
+			&mut ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+			counterparty_version,
+		)
+		.map_err(to_ics04_error)
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		host::on_chan_open_ack(&mut ctx, output, port_id, channel_id, counterparty_version)
+			.map_err(to_ics04_error)
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		host::on_chan_open_confirm(&mut ctx, output, port_id, channel_id).map_err(to_ics04_error)
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		host::on_chan_close_init(&mut ctx, output, port_id, channel_id).map_err(to_ics04_error)
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		host::on_chan_close_confirm(&mut ctx, output, port_id, channel_id).map_err(to_ics04_error)
+	}
+
+	fn on_recv_packet(
+		&self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		let ack = host::on_recv_packet(&mut ctx, output, packet, relayer).to_string().into_bytes();
+		crate::Pallet::<T>::handle_message(HandlerMessage::WriteAck {
+			packet: packet.clone(),
+			ack: ack.clone(),
+		})
+		.map_err(|e| Ics04Error::implementation_specific(format!("[on_recv_packet] {e:#?}")))?;
+		Ok(Acknowledgement::from_bytes(ack))
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &Acknowledgement,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		host::on_acknowledgement_packet(&mut ctx, output, packet, acknowledgement, relayer)
+			.map_err(to_ics04_error)
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		host::on_timeout_packet(&mut ctx, output, packet, relayer).map_err(to_ics04_error)
+	}
+}
@@ -0,0 +1,100 @@
+//! Benchmarks of the ICS-29 fee middleware calls
+
+use super::pallet::*;
+use crate::routing::Context;
+use alloc::{string::ToString, vec, vec::Vec};
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::traits::{Currency, Get};
+use frame_system::RawOrigin;
+use ibc::core::{
+	ics04_channel::context::ChannelKeeper,
+	ics24_host::identifier::{ChannelId, PortId},
+};
+use ibc_primitives::IbcAccount;
+use sp_core::crypto::AccountId32;
+
+const MILLIS: u128 = 1_000_000;
+
+/// Port and channel ids of a fee enabled channel whose next packet has the sequence 1
+fn fee_enabled_channel<T: Config + Send + Sync>() -> (Vec<u8>, Vec<u8>)
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	let port_id = PortId::transfer();
+	let channel_id = ChannelId::new(0);
+	let mut ctx = Context::<T>::default();
+	ctx.store_next_sequence_send((port_id.clone(), channel_id), 1.into()).unwrap();
+	ctx.store_packet_commitment((port_id.clone(), channel_id, 1.into()), vec![1u8; 32].into())
+		.unwrap();
+	let port_id = port_id.as_bytes().to_vec();
+	let channel_id = channel_id.to_string().as_bytes().to_vec();
+	FeeEnabledChannels::<T>::insert(port_id.clone(), channel_id.clone(), ());
+	(port_id, channel_id)
+}
+
+/// A fee in the native asset, funding the payer with it
+fn native_fee<T: Config>(payer: &T::AccountId) -> FeeParams<T::AssetId, T::Balance> {
+	let _ = <<T as crate::Config>::NativeCurrency as Currency<T::AccountId>>::deposit_creating(
+		payer,
+		(100_000 * MILLIS).into(),
+	);
+	let asset_id = <T as crate::Config>::NativeAssetId::get();
+	FeeParams {
+		recv_fee: vec![(asset_id.clone(), (300 * MILLIS).into())],
+		ack_fee: vec![(asset_id.clone(), (200 * MILLIS).into())],
+		timeout_fee: vec![(asset_id, (100 * MILLIS).into())],
+	}
+}
+
+benchmarks! {
+	where_clause {
+		where u32: From<<T as frame_system::Config>::BlockNumber>,
+				T: Send + Sync + Config,
+		AccountId32: From<<T as frame_system::Config>::AccountId>,
+		<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+	}
+
+	// Run these benchmarks via
+	// ```bash
+	// cargo +nightly test -p pallet-ibc  --features=runtime-benchmarks
+	// ```
+	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+
+	pay_packet_fee {
+		let caller: T::AccountId = whitelisted_caller();
+		let (port_id, channel_id) = fee_enabled_channel::<T>();
+		let fee = native_fee::<T>(&caller);
+	}:_(RawOrigin::Signed(caller), port_id.clone(), channel_id.clone(), fee)
+	verify {
+		assert!(PacketFees::<T>::contains_key((port_id, channel_id), 1));
+	}
+
+	pay_packet_fee_async {
+		let caller: T::AccountId = whitelisted_caller();
+		let (port_id, channel_id) = fee_enabled_channel::<T>();
+		let fee = native_fee::<T>(&caller);
+	}:_(RawOrigin::Signed(caller), port_id.clone(), channel_id.clone(), 1, fee)
+	verify {
+		assert!(PacketFees::<T>::contains_key((port_id, channel_id), 1));
+	}
+
+	register_payee {
+		let caller: T::AccountId = whitelisted_caller();
+		let payee: T::AccountId = account("payee", 0, 0);
+		let (port_id, channel_id) = fee_enabled_channel::<T>();
+	}:_(RawOrigin::Signed(caller), port_id, channel_id.clone(), payee)
+	verify {
+		assert_eq!(Payees::<T>::iter_prefix(channel_id).count(), 1);
+	}
+
+	register_counterparty_payee {
+		let caller: T::AccountId = whitelisted_caller();
+		let (port_id, channel_id) = fee_enabled_channel::<T>();
+		let counterparty_payee = "cosmos1hjm2d7uqg9alvhhnwsqjxq7hslul8ml3pmm4c4".as_bytes().to_vec();
+	}:_(RawOrigin::Signed(caller), port_id, channel_id.clone(), counterparty_payee)
+	verify {
+		assert_eq!(CounterpartyPayees::<T>::iter_prefix(channel_id).count(), 1);
+	}
+}
@@ -0,0 +1,161 @@
+use super::{decode_packet_fees, pallet::*};
+use crate::routing::Context;
+use alloc::{string::ToString, vec::Vec};
+use core::str::FromStr;
+use frame_support::traits::Get;
+use ibc::{
+	applications::fee::{
+		context::{FeeContext, FeeKeeper, FeeReader},
+		error::Error as FeeError,
+		PacketFee,
+	},
+	core::{
+		ics04_channel::packet::{PacketId, Sequence},
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	signer::Signer,
+};
+use ibc_primitives::IbcAccount;
+use prost::Message;
+use sp_core::crypto::AccountId32;
+use sp_runtime::traits::{AccountIdConversion, IdentifyAccount};
+
+fn channel_key(port_id: &PortId, channel_id: &ChannelId) -> (Vec<u8>, Vec<u8>) {
+	(port_id.as_bytes().to_vec(), channel_id.to_string().as_bytes().to_vec())
+}
+
+/// Key the payees of a relayer are stored under, the hex address of its account whether its
+/// messages are signed with an ss58 or a hex address
+fn relayer_key<T: Config>(relayer: &Signer) -> Vec<u8>
+where
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	<T as crate::Config>::AccountIdConversion::try_from(relayer.clone())
+		.map(|account| crate::signer::<T>(account.into_account()))
+		.unwrap_or_else(|_| relayer.clone())
+		.as_ref()
+		.as_bytes()
+		.to_vec()
+}
+
+impl<T: Config + Send + Sync> FeeReader for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	type AccountId = T::AccountIdConversion;
+
+	fn is_fee_enabled(&self, port_id: &PortId, channel_id: &ChannelId) -> bool {
+		let (port_id, channel_id) = channel_key(port_id, channel_id);
+		FeeEnabledChannels::<T>::contains_key(port_id, channel_id)
+	}
+
+	fn packet_fees(&self, packet_id: &PacketId) -> Vec<PacketFee> {
+		PacketFees::<T>::get(
+			channel_key(&packet_id.port_id, &packet_id.channel_id),
+			u64::from(packet_id.sequence),
+		)
+		.map(|bytes| decode_packet_fees(&bytes))
+		.unwrap_or_default()
+	}
+
+	fn incentivized_packets(&self, port_id: &PortId, channel_id: &ChannelId) -> Vec<PacketId> {
+		PacketFees::<T>::iter_key_prefix(channel_key(port_id, channel_id))
+			.map(|sequence| PacketId::new(port_id.clone(), *channel_id, Sequence::from(sequence)))
+			.collect()
+	}
+
+	fn payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<Signer> {
+		Payees::<T>::get(channel_id.to_string().as_bytes(), relayer_key::<T>(relayer))
+			.and_then(|payee| Signer::from_str(core::str::from_utf8(&payee).ok()?).ok())
+	}
+
+	fn counterparty_payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<Signer> {
+		CounterpartyPayees::<T>::get(channel_id.to_string().as_bytes(), relayer_key::<T>(relayer))
+			.and_then(|payee| Signer::from_str(core::str::from_utf8(&payee).ok()?).ok())
+	}
+
+	fn fee_escrow_address(&self) -> Result<<Self as FeeReader>::AccountId, FeeError> {
+		Ok(IbcAccount(<T as Config>::PalletId::get().into_account_truncating()).into())
+	}
+}
+
+impl<T: Config + Send + Sync> FeeKeeper for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	type AccountId = T::AccountIdConversion;
+
+	fn set_fee_enabled(&mut self, port_id: &PortId, channel_id: &ChannelId) {
+		let (port_id, channel_id) = channel_key(port_id, channel_id);
+		FeeEnabledChannels::<T>::insert(port_id, channel_id, ());
+	}
+
+	fn delete_fee_enabled(&mut self, port_id: &PortId, channel_id: &ChannelId) {
+		let (port_id, channel_id) = channel_key(port_id, channel_id);
+		FeeEnabledChannels::<T>::remove(port_id, channel_id);
+	}
+
+	fn store_packet_fees(
+		&mut self,
+		packet_id: PacketId,
+		packet_fees: Vec<PacketFee>,
+	) -> Result<(), FeeError> {
+		let packet_fees = ibc_proto::ibc::applications::fee::v1::PacketFees {
+			packet_fees: packet_fees.into_iter().map(Into::into).collect(),
+		};
+		PacketFees::<T>::insert(
+			channel_key(&packet_id.port_id, &packet_id.channel_id),
+			u64::from(packet_id.sequence),
+			packet_fees.encode_to_vec(),
+		);
+		Ok(())
+	}
+
+	fn delete_packet_fees(&mut self, packet_id: &PacketId) {
+		PacketFees::<T>::remove(
+			channel_key(&packet_id.port_id, &packet_id.channel_id),
+			u64::from(packet_id.sequence),
+		);
+	}
+
+	fn store_payee(
+		&mut self,
+		relayer: Signer,
+		channel_id: ChannelId,
+		payee: Signer,
+	) -> Result<(), FeeError> {
+		Payees::<T>::insert(
+			channel_id.to_string().as_bytes(),
+			relayer_key::<T>(&relayer),
+			payee.as_ref().as_bytes(),
+		);
+		Ok(())
+	}
+
+	fn store_counterparty_payee(
+		&mut self,
+		relayer: Signer,
+		channel_id: ChannelId,
+		counterparty_payee: Signer,
+	) -> Result<(), FeeError> {
+		CounterpartyPayees::<T>::insert(
+			channel_id.to_string().as_bytes(),
+			relayer_key::<T>(&relayer),
+			counterparty_payee.as_ref().as_bytes(),
+		);
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> FeeContext for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	type AccountId = T::AccountIdConversion;
+}
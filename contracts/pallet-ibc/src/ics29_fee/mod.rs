@@ -0,0 +1,669 @@
+//! ICS-29 fee middleware.
+//!
+//! Packet senders escrow fees for the relayers of their packets with [`Pallet::pay_packet_fee`]
+//! (for the next packet sent on a channel) or [`Pallet::pay_packet_fee_async`] (for a packet in
+//! flight). Relayers pick the accounts their fees are paid to with [`Pallet::register_payee`] and
+//! [`Pallet::register_counterparty_payee`]. [`FeeMiddleware`] wraps an application module to
+//! negotiate fee enabled channels and distribute the fees when the packet lifecycle completes.
+//!
+//! USAGE:
+//! ```rust
+//! # #[cfg(any())] // This is just to make the doc test compile
+//! pub struct Router {
+//!     ics20: crate::ics29_fee::FeeMiddleware<
+//!         Runtime,
+//!         crate::ics20::memo::Memo<Runtime, crate::ics20::IbcModule<Runtime>>,
+//!     >,
+//! }
+//! ```
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod context;
+pub mod weight;
+
+use crate::routing::Context;
+use alloc::{string::ToString, vec::Vec};
+use core::{fmt::Debug, str::FromStr};
+use ibc::{
+	applications::{
+		fee::{context as fee, metadata::Metadata},
+		transfer::acknowledgement::Acknowledgement as Ics20Ack,
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			error::Error as Ics04Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module as IbcModule, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+use ibc_primitives::IbcAccount;
+use sp_core::crypto::AccountId32;
+
+pub use pallet::*;
+pub use weight::WeightInfo;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::{fee_error, WeightInfo};
+	use crate::{routing::Context, signer, DenomToAssetId};
+	use alloc::{format, string::String, vec::Vec};
+	use core::str::FromStr;
+	use frame_support::{pallet_prelude::*, PalletId};
+	use frame_system::pallet_prelude::{ensure_signed, OriginFor};
+	use ibc::{
+		applications::{
+			fee::{
+				error::Error as FeeError,
+				handler,
+				msgs::{
+					pay_packet_fee::{MsgPayPacketFee, MsgPayPacketFeeAsync},
+					register_payee::{MsgRegisterCounterpartyPayee, MsgRegisterPayee},
+				},
+				Fee, PacketFee,
+			},
+			transfer::{Amount, PrefixedCoin, PrefixedDenom},
+		},
+		core::{
+			ics04_channel::{context::ChannelReader, packet::PacketId},
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		handler::HandlerOutputBuilder,
+		signer::Signer,
+	};
+	use ibc_primitives::IbcAccount;
+	use sp_core::crypto::AccountId32;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + crate::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		/// Id of the account escrowing the packet fees
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// Weights of the calls of the fee middleware
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// port_id, channel_id => () for the channels that negotiated the fee middleware
+	pub type FeeEnabledChannels<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Blake2_128Concat, Vec<u8>, (), OptionQuery>;
+
+	#[pallet::storage]
+	/// (port_id, channel_id), sequence => protobuf encoded `PacketFees` escrowed for the packet
+	pub type PacketFees<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(Vec<u8>, Vec<u8>),
+		Blake2_128Concat,
+		u64,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// channel_id, relayer => account the relayer's ack and timeout fees are paid to
+	pub type Payees<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// channel_id, relayer => counterparty address the relayer's recv fees are paid to
+	pub type CounterpartyPayees<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub (super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		PacketFeePaid {
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			sequence: u64,
+		},
+		PayeeRegistered {
+			channel_id: Vec<u8>,
+			relayer: T::AccountId,
+			payee: T::AccountId,
+		},
+		CounterpartyPayeeRegistered {
+			channel_id: Vec<u8>,
+			relayer: T::AccountId,
+			counterparty_payee: Vec<u8>,
+		},
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Invalid port or channel identifier
+		InvalidIdentifier,
+		/// Asset id that isn't bridged over IBC
+		InvalidAssetId,
+		/// Invalid counterparty payee address
+		InvalidAddress,
+		/// The fee middleware rejected the message
+		FeeMiddleware,
+	}
+
+	/// Fees escrowed for a packet, as amounts of local assets
+	#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub struct FeeParams<AssetId, Balance> {
+		/// Paid to the relayer that delivers the packet to the destination chain
+		pub recv_fee: Vec<(AssetId, Balance)>,
+		/// Paid to the relayer that delivers the acknowledgement back to this chain
+		pub ack_fee: Vec<(AssetId, Balance)>,
+		/// Paid to the relayer that delivers the timeout back to this chain
+		pub timeout_fee: Vec<(AssetId, Balance)>,
+	}
+
+	impl<T: Config + Send + Sync> Pallet<T>
+	where
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+		AccountId32: From<<T as frame_system::Config>::AccountId>,
+		<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+	{
+		fn parse_channel(
+			port_id: &[u8],
+			channel_id: &[u8],
+		) -> Result<(PortId, ChannelId), DispatchError> {
+			let port_id = core::str::from_utf8(port_id)
+				.ok()
+				.and_then(|port_id| PortId::from_str(port_id).ok())
+				.ok_or(Error::<T>::InvalidIdentifier)?;
+			let channel_id = core::str::from_utf8(channel_id)
+				.ok()
+				.and_then(|channel_id| ChannelId::from_str(channel_id).ok())
+				.ok_or(Error::<T>::InvalidIdentifier)?;
+			Ok((port_id, channel_id))
+		}
+
+		fn parse_fee(fee: FeeParams<T::AssetId, T::Balance>) -> Result<Fee, DispatchError> {
+			let coins = |coins: Vec<(T::AssetId, T::Balance)>| {
+				coins
+					.into_iter()
+					.map(|(asset_id, amount)| {
+						let denom =
+							T::IbcDenomToAssetIdConversion::from_asset_id_to_denom(asset_id)
+								.and_then(|denom| PrefixedDenom::from_str(&denom).ok())
+								.ok_or(Error::<T>::InvalidAssetId)?;
+						let amount = Amount::from_str(&format!("{amount:?}"))
+							.map_err(|_| Error::<T>::InvalidAssetId)?;
+						Ok(PrefixedCoin { denom, amount })
+					})
+					.collect::<Result<Vec<_>, DispatchError>>()
+			};
+			Ok(Fee {
+				recv_fee: coins(fee.recv_fee)?,
+				ack_fee: coins(fee.ack_fee)?,
+				timeout_fee: coins(fee.timeout_fee)?,
+			})
+		}
+
+		fn handle(
+			f: impl FnOnce(&mut Context<T>, &mut HandlerOutputBuilder<()>) -> Result<(), FeeError>,
+		) -> DispatchResult {
+			let mut ctx = Context::<T>::default();
+			let mut output = HandlerOutputBuilder::new();
+			f(&mut ctx, &mut output).map_err(|e| {
+				log::debug!(target: "pallet_ibc", "[ics29_fee]: error: {:?}", e);
+				Error::<T>::FeeMiddleware
+			})?;
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Protobuf encoded `PacketFees` escrowed for the packet `sequence` sent on
+		/// `port_id`/`channel_id`, `None` if no fee was escrowed for it
+		pub fn packet_fees(
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			sequence: u64,
+		) -> Option<Vec<u8>> {
+			PacketFees::<T>::get((port_id, channel_id), sequence)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config + Send + Sync> Pallet<T>
+	where
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+		AccountId32: From<<T as frame_system::Config>::AccountId>,
+		<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+	{
+		/// Escrows a fee for the next packet sent on the channel. Submit it in a batch right
+		/// before the call sending the packet.
+		#[pallet::call_index(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::pay_packet_fee())]
+		#[frame_support::transactional]
+		pub fn pay_packet_fee(
+			origin: OriginFor<T>,
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			fee: FeeParams<T::AssetId, T::Balance>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let (source_port_id, source_channel_id) = Self::parse_channel(&port_id, &channel_id)?;
+			let msg = MsgPayPacketFee {
+				fee: Self::parse_fee(fee)?,
+				source_port_id,
+				source_channel_id,
+				signer: signer::<T>(who),
+				relayers: Vec::new(),
+			};
+			let sequence = Context::<T>::default()
+				.get_next_sequence_send(&(msg.source_port_id.clone(), msg.source_channel_id))
+				.map_err(fee_error::<T>)?;
+			Self::handle(|ctx, output| handler::pay_packet_fee(ctx, output, msg))?;
+			Self::deposit_event(Event::<T>::PacketFeePaid {
+				port_id,
+				channel_id,
+				sequence: sequence.into(),
+			});
+			Ok(())
+		}
+
+		/// Escrows a fee for a packet that was sent but isn't acknowledged or timed out yet.
+		#[pallet::call_index(1)]
+		#[pallet::weight(<T as Config>::WeightInfo::pay_packet_fee_async())]
+		#[frame_support::transactional]
+		pub fn pay_packet_fee_async(
+			origin: OriginFor<T>,
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			sequence: u64,
+			fee: FeeParams<T::AssetId, T::Balance>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let (parsed_port_id, parsed_channel_id) = Self::parse_channel(&port_id, &channel_id)?;
+			let msg = MsgPayPacketFeeAsync {
+				packet_id: PacketId::new(parsed_port_id, parsed_channel_id, sequence.into()),
+				packet_fee: PacketFee {
+					fee: Self::parse_fee(fee)?,
+					refund_address: signer::<T>(who),
+					relayers: Vec::new(),
+				},
+			};
+			Self::handle(|ctx, output| handler::pay_packet_fee_async(ctx, output, msg))?;
+			Self::deposit_event(Event::<T>::PacketFeePaid { port_id, channel_id, sequence });
+			Ok(())
+		}
+
+		/// Registers the account the ack and timeout fees earned by the caller on the channel are
+		/// paid to.
+		#[pallet::call_index(2)]
+		#[pallet::weight(<T as Config>::WeightInfo::register_payee())]
+		#[frame_support::transactional]
+		pub fn register_payee(
+			origin: OriginFor<T>,
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			payee: T::AccountId,
+		) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+			let (parsed_port_id, parsed_channel_id) = Self::parse_channel(&port_id, &channel_id)?;
+			let msg = MsgRegisterPayee {
+				port_id: parsed_port_id,
+				channel_id: parsed_channel_id,
+				relayer: signer::<T>(relayer.clone()),
+				payee: signer::<T>(payee.clone()),
+			};
+			Self::handle(|ctx, output| handler::register_payee(ctx, output, msg))?;
+			Self::deposit_event(Event::<T>::PayeeRegistered { channel_id, relayer, payee });
+			Ok(())
+		}
+
+		/// Registers the counterparty chain address the recv fees earned by the caller on the
+		/// channel are paid to.
+		#[pallet::call_index(3)]
+		#[pallet::weight(<T as Config>::WeightInfo::register_counterparty_payee())]
+		#[frame_support::transactional]
+		pub fn register_counterparty_payee(
+			origin: OriginFor<T>,
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			counterparty_payee: Vec<u8>,
+		) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+			let (parsed_port_id, parsed_channel_id) = Self::parse_channel(&port_id, &channel_id)?;
+			let counterparty_payee_signer = String::from_utf8(counterparty_payee.clone())
+				.ok()
+				.and_then(|address| Signer::from_str(&address).ok())
+				.ok_or(Error::<T>::InvalidAddress)?;
+			let msg = MsgRegisterCounterpartyPayee {
+				port_id: parsed_port_id,
+				channel_id: parsed_channel_id,
+				relayer: signer::<T>(relayer.clone()),
+				counterparty_payee: counterparty_payee_signer,
+			};
+			Self::handle(|ctx, output| handler::register_counterparty_payee(ctx, output, msg))?;
+			Self::deposit_event(Event::<T>::CounterpartyPayeeRegistered {
+				channel_id,
+				relayer,
+				counterparty_payee,
+			});
+			Ok(())
+		}
+	}
+}
+
+fn fee_error<T: Config>(e: Ics04Error) -> pallet::Error<T> {
+	log::debug!(target: "pallet_ibc", "[ics29_fee]: error: {:?}", e);
+	pallet::Error::<T>::FeeMiddleware
+}
+
+fn to_ics04_error(e: ibc::applications::fee::error::Error) -> Ics04Error {
+	Ics04Error::app_module(e.to_string())
+}
+
+/// Wraps an application module with the fee middleware
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeMiddleware<T: Config, S: IbcModule + Clone + Default + PartialEq + Eq + Debug> {
+	inner: S,
+	_phantom: core::marker::PhantomData<T>,
+}
+
+impl<T: Config + Send + Sync, S: IbcModule + Clone + Default + PartialEq + Eq + Debug> Default
+	for FeeMiddleware<T, S>
+{
+	fn default() -> Self {
+		Self { inner: S::default(), _phantom: Default::default() }
+	}
+}
+
+impl<T: Config + Send + Sync, S: IbcModule + Clone + Default + PartialEq + Eq + Debug> IbcModule
+	for FeeMiddleware<T, S>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		// Module ModuleCallbackContext does not have the fee context as part of its trait bounds
+		// so we define a new context
+		let mut fee_ctx = Context::<T>::default();
+		let app_version = fee::on_chan_open_init(&mut fee_ctx, port_id, channel_id, version)
+			.map_err(to_ics04_error)?;
+		self.inner.on_chan_open_init(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			&app_version,
+			relayer,
+		)
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		let mut fee_ctx = Context::<T>::default();
+		let app_counterparty_version =
+			fee::on_chan_open_try(&mut fee_ctx, port_id, channel_id, counterparty_version)
+				.map_err(to_ics04_error)?;
+		let app_version = Metadata::from_version(version)
+			.map_err(to_ics04_error)?
+			.map_or_else(|| version.clone(), |metadata| metadata.app_version());
+		let app_version = self.inner.on_chan_open_try(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			&app_version,
+			&app_counterparty_version,
+			relayer,
+		)?;
+		Ok(fee::channel_version(&fee_ctx, port_id, channel_id, &app_version))
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut fee_ctx = Context::<T>::default();
+		let app_counterparty_version =
+			fee::on_chan_open_ack(&mut fee_ctx, port_id, channel_id, counterparty_version)
+				.map_err(to_ics04_error)?;
+		self.inner.on_chan_open_ack(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			&app_counterparty_version,
+			relayer,
+		)
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_open_confirm(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut fee_ctx = Context::<T>::default();
+		fee::on_chan_close(&mut fee_ctx, output, port_id, channel_id).map_err(to_ics04_error)?;
+		self.inner.on_chan_close_init(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut fee_ctx = Context::<T>::default();
+		fee::on_chan_close(&mut fee_ctx, output, port_id, channel_id).map_err(to_ics04_error)?;
+		self.inner.on_chan_close_confirm(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_upgrade_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		proposed_connection_hops: &[ConnectionId],
+		proposed_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		let (app_version, fee_enabled) =
+			fee::upgrade_app_version(proposed_version).map_err(to_ics04_error)?;
+		let app_version = self.inner.on_chan_upgrade_init(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			proposed_connection_hops,
+			&app_version,
+		)?;
+		Ok(if fee_enabled { Metadata::new(&app_version).into() } else { app_version })
+	}
+
+	fn on_chan_upgrade_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		proposed_order: Order,
+		proposed_connection_hops: &[ConnectionId],
+		counterparty_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		let (app_version, fee_enabled) =
+			fee::upgrade_app_version(counterparty_version).map_err(to_ics04_error)?;
+		let app_version = self.inner.on_chan_upgrade_try(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			proposed_order,
+			proposed_connection_hops,
+			&app_version,
+		)?;
+		Ok(if fee_enabled { Metadata::new(&app_version).into() } else { app_version })
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+	) -> Result<(), Ics04Error> {
+		let (app_counterparty_version, _) =
+			fee::upgrade_app_version(counterparty_version).map_err(to_ics04_error)?;
+		self.inner
+			.on_chan_upgrade_ack(ctx, output, port_id, channel_id, &app_counterparty_version)
+	}
+
+	fn on_chan_upgrade_open(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<(), Ics04Error> {
+		let mut fee_ctx = Context::<T>::default();
+		let app_version =
+			fee::on_chan_upgrade_open(&mut fee_ctx, output, port_id, channel_id, version)
+				.map_err(to_ics04_error)?;
+		self.inner.on_chan_upgrade_open(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			&app_version,
+		)
+	}
+
+	fn on_recv_packet(
+		&self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		let ack = self.inner.on_recv_packet(ctx, output, packet, relayer)?;
+		// acknowledgements that aren't ics20 ones are assumed to be successful
+		let success = core::str::from_utf8(ack.as_ref())
+			.ok()
+			.and_then(|ack| Ics20Ack::from_str(ack).ok())
+			.map_or(true, |ack| ack.is_successful());
+		let fee_ctx = Context::<T>::default();
+		Ok(fee::on_recv_packet(&fee_ctx, packet, relayer, ack, success))
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &Acknowledgement,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut fee_ctx = Context::<T>::default();
+		let app_acknowledgement =
+			fee::on_acknowledgement_packet(&mut fee_ctx, output, packet, acknowledgement, relayer)
+				.map_err(to_ics04_error)?;
+		self.inner
+			.on_acknowledgement_packet(ctx, output, packet, &app_acknowledgement, relayer)
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut fee_ctx = Context::<T>::default();
+		fee::on_timeout_packet(&mut fee_ctx, output, packet, relayer).map_err(to_ics04_error)?;
+		self.inner.on_timeout_packet(ctx, output, packet, relayer)
+	}
+}
+
+/// Decodes the protobuf encoded fees of a packet, as stored in [`PacketFees`]
+pub fn decode_packet_fees(bytes: &[u8]) -> Vec<ibc::applications::fee::PacketFee> {
+	use prost::Message;
+	ibc_proto::ibc::applications::fee::v1::PacketFees::decode(bytes)
+		.map(|fees| fees.packet_fees.into_iter().filter_map(|fee| fee.try_into().ok()).collect())
+		.unwrap_or_default()
+}
@@ -0,0 +1,38 @@
+use frame_support::weights::{constants::RocksDbWeight, Weight};
+
+pub trait WeightInfo {
+	fn pay_packet_fee() -> Weight;
+	fn pay_packet_fee_async() -> Weight;
+	fn register_payee() -> Weight;
+	fn register_counterparty_payee() -> Weight;
+}
+
+/// Upper bounds for runtimes that didn't run the benchmarks of the fee middleware, weighing the
+/// storage accesses of each call
+impl WeightInfo for () {
+	fn pay_packet_fee() -> Weight {
+		// fee enabled flag, next sequence send, packet fees, payer and escrow balances
+		Weight::from_ref_time(100_000_000)
+			.saturating_add(RocksDbWeight::get().reads(6))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+
+	fn pay_packet_fee_async() -> Weight {
+		// fee enabled flag, packet commitment, packet fees, payer and escrow balances
+		Weight::from_ref_time(100_000_000)
+			.saturating_add(RocksDbWeight::get().reads(6))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+
+	fn register_payee() -> Weight {
+		Weight::from_ref_time(30_000_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+
+	fn register_counterparty_payee() -> Weight {
+		Weight::from_ref_time(30_000_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+}
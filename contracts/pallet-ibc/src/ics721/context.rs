@@ -0,0 +1,221 @@
+use super::{pallet::*, CollectionIdOf, ItemIdOf, NonFungibles};
+use crate::routing::Context;
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use core::str::FromStr;
+use frame_support::traits::Get;
+use ibc::{
+	applications::nft_transfer::{
+		context::{Metadata, NftContext, NftKeeper, NftReader},
+		error::Error as NftError,
+		PrefixedClassId, TokenId, PORT_ID_STR,
+	},
+	core::ics24_host::identifier::{ChannelId, PortId},
+};
+use ibc_primitives::IbcAccount;
+use sp_core::crypto::AccountId32;
+use sp_runtime::{
+	traits::{AccountIdConversion, IdentifyAccount},
+	DispatchError,
+};
+
+fn nft_error(e: DispatchError) -> NftError {
+	NftError::implementation_specific(format!("{e:?}"))
+}
+
+fn to_metadata((uri, data): (Option<Vec<u8>>, Option<Vec<u8>>)) -> Metadata {
+	let to_string = |bytes: Option<Vec<u8>>| bytes.and_then(|bytes| String::from_utf8(bytes).ok());
+	Metadata { uri: to_string(uri), data: to_string(data) }
+}
+
+fn from_metadata(metadata: Metadata) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
+	(metadata.uri.map(String::into_bytes), metadata.data.map(String::into_bytes))
+}
+
+/// The collection of a class: the voucher collection of foreign classes, or the local collection
+/// the class id is the string representation of
+fn collection_id<T: Config>(class_id: &PrefixedClassId) -> Result<CollectionIdOf<T>, NftError> {
+	let class = class_id.to_string();
+	VoucherClasses::<T>::get(class.as_bytes())
+		.or_else(|| CollectionIdOf::<T>::from_str(&class).ok())
+		.ok_or_else(|| NftError::class_not_found(class))
+}
+
+/// The item of a token: the voucher item of foreign tokens, or the local item the token id is the
+/// string representation of
+fn item_id<T: Config>(
+	class_id: &PrefixedClassId,
+	collection_id: &CollectionIdOf<T>,
+	token_id: &TokenId,
+) -> Result<ItemIdOf<T>, NftError> {
+	VoucherItems::<T>::get(collection_id, token_id.as_str().as_bytes())
+		.or_else(|| ItemIdOf::<T>::from_str(token_id.as_str()).ok())
+		.ok_or_else(|| NftError::token_not_found(class_id.to_string(), token_id.to_string()))
+}
+
+impl<T: Config + Send + Sync> NftReader for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	type AccountId = T::AccountIdConversion;
+
+	fn get_port(&self) -> Result<PortId, NftError> {
+		PortId::from_str(PORT_ID_STR)
+			.map_err(|e| NftError::invalid_port_id(PORT_ID_STR.to_string(), e))
+	}
+
+	fn get_channel_escrow_address(
+		&self,
+		port_id: &PortId,
+		channel_id: ChannelId,
+	) -> Result<<Self as NftReader>::AccountId, NftError> {
+		let account = <T as Config>::PalletId::get()
+			.into_sub_account_truncating((port_id.as_bytes(), channel_id.sequence()));
+		Ok(IbcAccount(account).into())
+	}
+
+	fn is_send_enabled(&self) -> bool {
+		T::IsSendEnabled::get()
+	}
+
+	fn is_receive_enabled(&self) -> bool {
+		T::IsReceiveEnabled::get()
+	}
+
+	fn class_metadata(&self, class_id: &PrefixedClassId) -> Result<Metadata, NftError> {
+		let collection_id = collection_id::<T>(class_id)?;
+		Ok(ClassMetadata::<T>::get(collection_id).map(to_metadata).unwrap_or_default())
+	}
+
+	fn token_metadata(
+		&self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+	) -> Result<Metadata, NftError> {
+		let collection_id = collection_id::<T>(class_id)?;
+		let item_id = item_id::<T>(class_id, &collection_id, token_id)?;
+		Ok(TokenMetadata::<T>::get(collection_id, item_id)
+			.map(to_metadata)
+			.unwrap_or_default())
+	}
+}
+
+impl<T: Config + Send + Sync> Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	fn ensure_nft_owner(
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+		owner: &T::AccountIdConversion,
+	) -> Result<(CollectionIdOf<T>, ItemIdOf<T>), NftError> {
+		let collection_id = collection_id::<T>(class_id)?;
+		let item_id = item_id::<T>(class_id, &collection_id, token_id)?;
+		if T::NonFungibles::owner(&collection_id, &item_id) != Some(owner.clone().into_account()) {
+			return Err(NftError::token_not_owned(class_id.to_string(), token_id.to_string()))
+		}
+		Ok((collection_id, item_id))
+	}
+}
+
+impl<T: Config + Send + Sync> NftKeeper for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	type AccountId = T::AccountIdConversion;
+
+	fn create_or_update_class(
+		&mut self,
+		class_id: &PrefixedClassId,
+		metadata: Metadata,
+	) -> Result<(), NftError> {
+		let class = class_id.to_string().as_bytes().to_vec();
+		let collection_id = match VoucherClasses::<T>::get(&class) {
+			Some(collection_id) => collection_id,
+			None => {
+				let owner = <T as Config>::PalletId::get().into_account_truncating();
+				let collection_id =
+					T::NonFungibles::create_collection(&owner).map_err(nft_error)?;
+				VoucherClasses::<T>::insert(&class, collection_id.clone());
+				VoucherClassIds::<T>::insert(collection_id.clone(), class.clone());
+				Pallet::<T>::deposit_event(Event::<T>::VoucherClassCreated {
+					class_id: class,
+					collection_id: collection_id.clone(),
+				});
+				collection_id
+			},
+		};
+		ClassMetadata::<T>::insert(collection_id, from_metadata(metadata));
+		Ok(())
+	}
+
+	fn transfer_token(
+		&mut self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+		from: &Self::AccountId,
+		to: &Self::AccountId,
+	) -> Result<(), NftError> {
+		let (collection_id, item_id) = Self::ensure_nft_owner(class_id, token_id, from)?;
+		T::NonFungibles::transfer(&collection_id, &item_id, &to.clone().into_account())
+			.map_err(nft_error)
+	}
+
+	fn mint_token(
+		&mut self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+		metadata: Metadata,
+		to: &Self::AccountId,
+	) -> Result<(), NftError> {
+		// only vouchers are minted, local tokens are unescrowed
+		let collection_id = VoucherClasses::<T>::get(class_id.to_string().as_bytes())
+			.ok_or_else(|| NftError::class_not_found(class_id.to_string()))?;
+		let token = token_id.as_str().as_bytes();
+		let item_id = match VoucherItems::<T>::get(&collection_id, token) {
+			Some(item_id) => item_id,
+			None => {
+				let item_id: ItemIdOf<T> = NextVoucherItem::<T>::mutate(&collection_id, |next| {
+					let item_id = *next;
+					*next = next.saturating_add(1);
+					item_id
+				})
+				.into();
+				VoucherItems::<T>::insert(&collection_id, token, item_id.clone());
+				VoucherTokenIds::<T>::insert(&collection_id, &item_id, token);
+				item_id
+			},
+		};
+		TokenMetadata::<T>::insert(&collection_id, &item_id, from_metadata(metadata));
+		T::NonFungibles::mint_into(&collection_id, &item_id, &to.clone().into_account())
+			.map_err(nft_error)
+	}
+
+	fn burn_token(
+		&mut self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+		owner: &Self::AccountId,
+	) -> Result<(), NftError> {
+		let (collection_id, item_id) = Self::ensure_nft_owner(class_id, token_id, owner)?;
+		T::NonFungibles::burn(&collection_id, &item_id).map_err(nft_error)
+	}
+}
+
+impl<T: Config + Send + Sync> NftContext for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	type AccountId = T::AccountIdConversion;
+}
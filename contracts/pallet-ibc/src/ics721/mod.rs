@@ -0,0 +1,466 @@
+//! ICS-721 non-fungible token transfer.
+//!
+//! Tokens of the collections of [`Config::NonFungibles`] are sent to other chains with
+//! [`Pallet::transfer`]: the tokens of local collections are escrowed in a per channel account
+//! while the vouchers of foreign classes are burnt. Vouchers are minted into collections this
+//! pallet creates for every foreign class it receives tokens of. [`IbcModule`] has to be added
+//! to the runtime router for the `nft-transfer` port.
+
+mod context;
+
+use crate::routing::Context;
+use alloc::{format, string::ToString};
+use core::str::FromStr;
+use frame_support::storage::{with_transaction, TransactionOutcome};
+use ibc::{
+	applications::nft_transfer::context as nft,
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			error::Error as Ics04Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+use ibc_primitives::{HandlerMessage, IbcAccount, IbcHandler};
+use sp_core::crypto::AccountId32;
+use sp_runtime::DispatchError;
+use sp_std::marker::PhantomData;
+
+pub use ibc::applications::nft_transfer::{MODULE_ID_STR, PORT_ID_STR};
+pub use pallet::*;
+
+/// The non-fungible tokens implementation (e.g. pallet-nfts) the tokens are escrowed, minted and
+/// burnt with.
+pub trait NonFungibles<AccountId> {
+	/// Identifier of a collection, its string representation is the ICS-721 class id
+	type CollectionId: frame_support::Parameter + core::fmt::Display + FromStr;
+	/// Identifier of an item, its string representation is the ICS-721 token id
+	type ItemId: frame_support::Parameter + core::fmt::Display + FromStr + From<u32>;
+
+	/// Returns the owner of an item, if it exists
+	fn owner(collection: &Self::CollectionId, item: &Self::ItemId) -> Option<AccountId>;
+
+	/// Creates a new collection owned by `owner`
+	fn create_collection(owner: &AccountId) -> Result<Self::CollectionId, DispatchError>;
+
+	/// Mints a new item of a collection into `who`
+	fn mint_into(
+		collection: &Self::CollectionId,
+		item: &Self::ItemId,
+		who: &AccountId,
+	) -> Result<(), DispatchError>;
+
+	/// Destroys an item
+	fn burn(collection: &Self::CollectionId, item: &Self::ItemId) -> Result<(), DispatchError>;
+
+	/// Moves an item to `destination`
+	fn transfer(
+		collection: &Self::CollectionId,
+		item: &Self::ItemId,
+		destination: &AccountId,
+	) -> Result<(), DispatchError>;
+}
+
+pub type CollectionIdOf<T> = <<T as Config>::NonFungibles as NonFungibles<
+	<T as frame_system::Config>::AccountId,
+>>::CollectionId;
+
+pub type ItemIdOf<T> =
+	<<T as Config>::NonFungibles as NonFungibles<<T as frame_system::Config>::AccountId>>::ItemId;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::{CollectionIdOf, ItemIdOf, NonFungibles};
+	use crate::{routing::Context, signer, MultiAddress, TransferParams};
+	use alloc::{string::String, vec::Vec};
+	use core::str::FromStr;
+	use frame_support::{pallet_prelude::*, PalletId};
+	use frame_system::pallet_prelude::{ensure_signed, OriginFor};
+	use ibc::{
+		applications::nft_transfer::{
+			msgs::transfer::MsgTransfer, relay::send_transfer::send_transfer, PrefixedClassId,
+			TokenId,
+		},
+		core::ics24_host::identifier::{ChannelId, PortId},
+		handler::HandlerOutputBuilder,
+		signer::Signer,
+	};
+	use ibc_primitives::IbcAccount;
+	use sp_core::crypto::AccountId32;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + crate::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		/// Id of the accounts escrowing the tokens sent over a channel and owning the voucher
+		/// collections
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// The non-fungible tokens implementation
+		type NonFungibles: NonFungibles<Self::AccountId>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	/// class_id => local collection minting the vouchers of the class
+	pub type VoucherClasses<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, CollectionIdOf<T>, OptionQuery>;
+
+	#[pallet::storage]
+	/// voucher collection => class_id
+	pub type VoucherClassIds<T: Config> =
+		StorageMap<_, Blake2_128Concat, CollectionIdOf<T>, Vec<u8>, OptionQuery>;
+
+	#[pallet::storage]
+	/// voucher collection, token_id => local item of the voucher
+	pub type VoucherItems<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		CollectionIdOf<T>,
+		Blake2_128Concat,
+		Vec<u8>,
+		ItemIdOf<T>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// voucher collection, voucher item => token_id
+	pub type VoucherTokenIds<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		CollectionIdOf<T>,
+		Blake2_128Concat,
+		ItemIdOf<T>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// voucher collection => next item id
+	pub type NextVoucherItem<T: Config> =
+		StorageMap<_, Blake2_128Concat, CollectionIdOf<T>, u32, ValueQuery>;
+
+	#[pallet::storage]
+	/// voucher collection => (class uri, class data) received with the class
+	pub type ClassMetadata<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		CollectionIdOf<T>,
+		(Option<Vec<u8>>, Option<Vec<u8>>),
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// voucher collection, voucher item => (token uri, token data) received with the token
+	pub type TokenMetadata<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		CollectionIdOf<T>,
+		Blake2_128Concat,
+		ItemIdOf<T>,
+		(Option<Vec<u8>>, Option<Vec<u8>>),
+		OptionQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub (super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Tokens were sent to another chain
+		TransferInitiated {
+			from: T::AccountId,
+			to: Vec<u8>,
+			class_id: Vec<u8>,
+			token_ids: Vec<Vec<u8>>,
+			source_channel: Vec<u8>,
+		},
+		/// A collection was created for the vouchers of a foreign class
+		VoucherClassCreated { class_id: Vec<u8>, collection_id: CollectionIdOf<T> },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Invalid receiver address
+		Utf8Error,
+		/// No token to transfer
+		EmptyTransfer,
+		/// The collection or item isn't a valid ICS-721 class or token id
+		InvalidClassOrTokenId,
+		/// The NFT transfer application rejected the transfer
+		TransferFailed,
+	}
+
+	#[pallet::call]
+	impl<T: Config + Send + Sync> Pallet<T>
+	where
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+		AccountId32: From<<T as frame_system::Config>::AccountId>,
+		<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+	{
+		/// Sends items of a collection over an `nft-transfer` channel.
+		#[pallet::call_index(0)]
+		#[pallet::weight(0)]
+		#[frame_support::transactional]
+		pub fn transfer(
+			origin: OriginFor<T>,
+			params: TransferParams<<T as frame_system::Config>::AccountId>,
+			collection_id: CollectionIdOf<T>,
+			items: Vec<ItemIdOf<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!items.is_empty(), Error::<T>::EmptyTransfer);
+
+			let receiver = match params.to {
+				MultiAddress::Id(id) => signer::<T>(id),
+				MultiAddress::Raw(bytes) => String::from_utf8(bytes)
+					.ok()
+					.and_then(|address| Signer::from_str(&address).ok())
+					.ok_or(Error::<T>::Utf8Error)?,
+			};
+			let class_id = Self::class_id(&collection_id)?;
+			let token_ids = items
+				.iter()
+				.map(|item| Self::token_id(&collection_id, item))
+				.collect::<Result<Vec<_>, _>>()?;
+
+			let source_port =
+				PortId::from_str(super::PORT_ID_STR).expect("nft-transfer is a valid port id");
+			let source_channel = ChannelId::new(params.source_channel);
+			let (timeout_height, timeout_timestamp) =
+				crate::Pallet::<T>::packet_timeout(&source_port, &source_channel, params.timeout)?;
+
+			let msg = MsgTransfer {
+				source_port,
+				source_channel,
+				class_id: class_id.clone(),
+				token_ids: token_ids.clone(),
+				sender: signer::<T>(who.clone()),
+				receiver: receiver.clone(),
+				timeout_height,
+				timeout_timestamp,
+				memo: String::new(),
+			};
+
+			let mut ctx = Context::<T>::default();
+			let mut output = HandlerOutputBuilder::new();
+			send_transfer(&mut ctx, &mut output, msg).map_err(|e| {
+				log::debug!(target: "pallet_ibc", "[ics721]: transfer failed: {:?}", e);
+				Error::<T>::TransferFailed
+			})?;
+			crate::Pallet::<T>::deposit_event(output.with_result(()).events.into());
+
+			Self::deposit_event(Event::<T>::TransferInitiated {
+				from: who,
+				to: receiver.as_ref().as_bytes().to_vec(),
+				class_id: class_id.to_string().as_bytes().to_vec(),
+				token_ids: token_ids.iter().map(|id| id.as_str().as_bytes().to_vec()).collect(),
+				source_channel: source_channel.to_string().as_bytes().to_vec(),
+			});
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The ICS-721 class id of a local collection
+		pub fn class_id(collection_id: &CollectionIdOf<T>) -> Result<PrefixedClassId, Error<T>> {
+			let class_id = VoucherClassIds::<T>::get(collection_id)
+				.and_then(|class_id| String::from_utf8(class_id).ok())
+				.unwrap_or_else(|| collection_id.to_string());
+			PrefixedClassId::from_str(&class_id).map_err(|_| Error::<T>::InvalidClassOrTokenId)
+		}
+
+		/// The ICS-721 token id of a local item
+		pub fn token_id(
+			collection_id: &CollectionIdOf<T>,
+			item: &ItemIdOf<T>,
+		) -> Result<TokenId, Error<T>> {
+			let token_id = VoucherTokenIds::<T>::get(collection_id, item)
+				.and_then(|token_id| String::from_utf8(token_id).ok())
+				.unwrap_or_else(|| item.to_string());
+			TokenId::from_str(&token_id).map_err(|_| Error::<T>::InvalidClassOrTokenId)
+		}
+	}
+}
+
+fn to_ics04_error(e: ibc::applications::nft_transfer::error::Error) -> Ics04Error {
+	Ics04Error::implementation_specific(e.to_string())
+}
+
+#[derive(Clone, Eq, Debug, PartialEq)]
+pub struct IbcModule<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for IbcModule<T> {
+	fn default() -> Self {
+		Self(PhantomData::default())
+	}
+}
+
+impl<T: Config + Send + Sync> Module for IbcModule<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+	<T as crate::Config>::AccountIdConversion: From<IbcAccount<T::AccountId>>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		nft::on_chan_open_init(
+			&mut ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+		)
+		.map_err(to_ics04_error)
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		nft::on_chan_open_try(
+			&mut ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+			counterparty_version,
+		)
+		.map_err(to_ics04_error)
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		nft::on_chan_open_ack(&mut ctx, output, port_id, channel_id, counterparty_version)
+			.map_err(to_ics04_error)
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		nft::on_chan_open_confirm(&mut ctx, output, port_id, channel_id).map_err(to_ics04_error)
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		nft::on_chan_close_init(&mut ctx, output, port_id, channel_id).map_err(to_ics04_error)
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		nft::on_chan_close_confirm(&mut ctx, output, port_id, channel_id).map_err(to_ics04_error)
+	}
+
+	fn on_recv_packet(
+		&self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		// A packet can carry several tokens, none of them must be received if one fails
+		let ack = with_transaction(|| {
+			let ack = nft::on_recv_packet(&mut ctx, output, packet, relayer);
+			if ack.is_successful() {
+				TransactionOutcome::Commit(Ok::<_, DispatchError>(ack))
+			} else {
+				TransactionOutcome::Rollback(Ok(ack))
+			}
+		})
+		.map_err(|e| Ics04Error::implementation_specific(format!("{e:?}")))?;
+		let ack = ack.to_string().into_bytes();
+		crate::Pallet::<T>::handle_message(HandlerMessage::WriteAck {
+			packet: packet.clone(),
+			ack: ack.clone(),
+		})
+		.map_err(|e| Ics04Error::implementation_specific(format!("[on_recv_packet] {e:#?}")))?;
+		Ok(Acknowledgement::from_bytes(ack))
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &Acknowledgement,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		nft::on_acknowledgement_packet(&mut ctx, output, packet, acknowledgement, relayer)
+			.map_err(to_ics04_error)
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		nft::on_timeout_packet(&mut ctx, output, packet, relayer).map_err(to_ics04_error)
+	}
+}
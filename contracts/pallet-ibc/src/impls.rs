@@ -2,6 +2,7 @@ use alloc::{format, str::FromStr, string::String};
 use core::time::Duration;
 
 use crate::{
+	ics20_rate_limit::Ics20RateLimiter,
 	ics23::{
 		acknowledgements::Acknowledgements, channels::Channels, client_states::ClientStates,
 		connections::Connections, consensus_states::ConsensusStates,
@@ -10,15 +11,26 @@ use crate::{
 	},
 	light_clients::AnyClientState,
 	routing::Context,
-	Acks, ChannelsConnection, Config, ConnectionClient, DenomToAssetId, Error, EscrowAddresses,
+	Acks, ChannelsConnection, ClientUpdateHeight, ClientUpdateTime, Config, ConnectionClient,
+	ConsensusHeights, CrossChainQueries, DenomToAssetId, DenomTraces, Error, EscrowAddresses,
 	IbcAssets, Pallet, PendingRecvPacketSeqs, PendingSendPacketSeqs, RecvPackets, SendPackets,
-	MODULE_ID,
+	TotalEscrow, MAX_CONSENSUS_STATES_PRUNED_PER_UPDATE, MODULE_ID,
 };
 use codec::{Decode, Encode};
-use frame_support::traits::{fungibles::Inspect, Currency};
+use frame_support::{
+	storage::{child, child::ChildInfo},
+	traits::{fungibles::Inspect, Currency, UnixTime},
+};
 use ibc::{
-	applications::transfer::{
-		msgs::transfer::MsgTransfer, relay::send_transfer::send_transfer, PrefixedCoin,
+	applications::{
+		cross_chain_queries::{
+			context::CrossChainQueryReader,
+			msgs::MsgSubmitCrossChainQuery,
+			query::{QueryId, QueryResult},
+		},
+		transfer::{
+			msgs::transfer::MsgTransfer, relay::send_transfer::send_transfer, PrefixedCoin,
+		},
 	},
 	core::{
 		ics02_client::{
@@ -30,7 +42,7 @@ use ibc::{
 			context::{ChannelKeeper, ChannelReader},
 			error::Error as Ics04Error,
 			msgs::{chan_close_init::MsgChannelCloseInit, chan_open_init::MsgChannelOpenInit},
-			packet::{Packet, Sequence},
+			packet::{Packet, Receipt, Sequence},
 		},
 		ics24_host::{
 			identifier::*,
@@ -52,8 +64,8 @@ use ibc_primitives::{
 	get_channel_escrow_address, port_id_from_bytes, runtime_interface, ConnectionHandshake,
 	Error as IbcHandlerError, HandlerMessage, IbcHandler, IdentifiedChannel, IdentifiedClientState,
 	IdentifiedConnection, PacketInfo, PacketState, QueryChannelResponse, QueryChannelsResponse,
-	QueryClientStateResponse, QueryConnectionResponse, QueryConnectionsResponse,
-	QueryConsensusStateResponse, QueryNextSequenceReceiveResponse,
+	QueryClientStateResponse, QueryClientsResponse, QueryConnectionResponse,
+	QueryConnectionsResponse, QueryConsensusStateResponse, QueryNextSequenceReceiveResponse,
 	QueryPacketAcknowledgementResponse, QueryPacketAcknowledgementsResponse,
 	QueryPacketCommitmentResponse, QueryPacketCommitmentsResponse, QueryPacketReceiptResponse,
 	Timeout,
@@ -62,7 +74,7 @@ use scale_info::prelude::string::ToString;
 use sp_core::crypto::AccountId32;
 use sp_runtime::{
 	traits::{Get, IdentifyAccount},
-	Either,
+	Either, SaturatedConversion,
 };
 use sp_std::prelude::*;
 use tendermint_proto::Protobuf;
@@ -149,12 +161,23 @@ where
 		Ok(QueryClientStateResponse { client_state, trie_key: key, height: host_height::<T>() })
 	}
 
-	/// Get all client states
-	/// Returns a Vec of (client_id, client_state)
-	pub fn clients() -> Vec<(Vec<u8>, Vec<u8>)> {
-		ClientStates::<T>::iter()
-			.map(|(client_id, client_state)| (client_id.as_bytes().to_vec(), client_state))
-			.collect::<Vec<_>>()
+	/// Get at most `limit` client states, starting at the client whose trie key is `next_key`
+	pub fn clients(
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<QueryClientsResponse, Error<T>> {
+		let clients =
+			ClientStates::<T>::iter_from(next_key.clone()).map(|(key, client_id, client_state)| {
+				(
+					key,
+					IdentifiedClientState {
+						client_id: client_id.as_bytes().to_vec(),
+						client_state,
+					},
+				)
+			});
+		let (clients, next_key) = paginate(clients, next_key, limit)?;
+		Ok(QueryClientsResponse { clients, next_key })
 	}
 
 	/// Get a consensus state for client
@@ -240,31 +263,60 @@ where
 		Ok(IdentifiedClientState { client_id: client_id.as_bytes().to_vec(), client_state })
 	}
 
-	/// Get all channel states
-	pub fn channels() -> Result<QueryChannelsResponse, Error<T>> {
-		let channels = Channels::<T>::iter()
-			.map(|(port_id, channel_id, channel_end)| {
-				Ok(IdentifiedChannel { channel_id, port_id, channel_end })
-			})
-			.collect::<Result<Vec<_>, Error<T>>>()?;
+	/// Get at most `limit` channel states, starting at the channel whose trie key is `next_key`
+	pub fn channels(
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<QueryChannelsResponse, Error<T>> {
+		let channels = Channels::<T>::iter_from(next_key.clone()).map(
+			|(key, port_id, channel_id, channel_end)| {
+				(key, IdentifiedChannel { channel_id, port_id, channel_end })
+			},
+		);
+		let (channels, next_key) = paginate(channels, next_key, limit)?;
 
-		Ok(QueryChannelsResponse { channels, height: host_height::<T>() })
+		Ok(QueryChannelsResponse { channels, height: host_height::<T>(), next_key })
 	}
 
-	/// Get all connection states
-	pub fn connections() -> Result<QueryConnectionsResponse, Error<T>> {
-		let connections = Connections::<T>::iter()
-			.map(|(connection_id, connection_end)| {
-				Ok(IdentifiedConnection { connection_id, connection_end })
-			})
-			.collect::<Result<Vec<_>, Error<T>>>()?;
+	/// Get at most `limit` connection states, starting at the connection whose trie key is
+	/// `next_key`
+	pub fn connections(
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<QueryConnectionsResponse, Error<T>> {
+		let connections = Connections::<T>::iter_from(next_key.clone()).map(
+			|(key, connection_id, connection_end)| {
+				(key, IdentifiedConnection { connection_id, connection_end })
+			},
+		);
+		let (connections, next_key) = paginate(connections, next_key, limit)?;
+
+		Ok(QueryConnectionsResponse { connections, height: host_height::<T>(), next_key })
+	}
 
-		Ok(QueryConnectionsResponse { connections, height: host_height::<T>() })
+	/// Maximum expected time per block in nanoseconds, used to enforce the block delay of
+	/// connections
+	pub fn max_expected_time_per_block() -> u64 {
+		let ctx = Context::<T>::default();
+		ctx.max_expected_time_per_block().as_nanos().saturated_into()
 	}
 
-	/// Get all channels bound to this connection
-	pub fn connection_channels(connection_id: Vec<u8>) -> Result<QueryChannelsResponse, Error<T>> {
-		let identifiers = ChannelsConnection::<T>::get(connection_id);
+	/// Get at most `limit` channels bound to this connection, starting at the channel identified
+	/// by `next_key`
+	pub fn connection_channels(
+		connection_id: Vec<u8>,
+		next_key: Option<Vec<u8>>,
+		limit: Option<u64>,
+	) -> Result<QueryChannelsResponse, Error<T>> {
+		// the identifiers of the channels of a connection are stored in a single value
+		let start = next_key.clone();
+		let identifiers = ChannelsConnection::<T>::get(connection_id)
+			.into_iter()
+			.map(|(port_id, channel_id)| {
+				(channel_key(&port_id, &channel_id), (port_id, channel_id))
+			})
+			.skip_while(move |(key, _)| start.as_ref().map_or(false, |start| key != start));
+		let (identifiers, next_key) = paginate(identifiers, next_key, limit)?;
 
 		let channels = identifiers
 			.into_iter()
@@ -283,7 +335,7 @@ where
 				})
 			})
 			.collect::<Result<Vec<_>, Error<T>>>()?;
-		Ok(QueryChannelsResponse { channels, height: host_height::<T>() })
+		Ok(QueryChannelsResponse { channels, height: host_height::<T>(), next_key })
 	}
 
 	pub fn packet_commitments(
@@ -350,7 +402,9 @@ where
 		Ok(seqs
 			.into_iter()
 			.filter(|s| {
-				!PacketReceipt::<T>::contains_key((port_id.clone(), channel_id, (*s).into()))
+				// packets skipped after timing out weren't received
+				PacketReceipt::<T>::get((port_id.clone(), channel_id, (*s).into()))
+					.map_or(true, |receipt| receipt == Receipt::Timeout.as_bytes())
 			})
 			.collect())
 	}
@@ -698,6 +752,96 @@ where
 }
 
 impl<T: Config> Pallet<T> {
+	/// Prunes the oldest consensus states of a client, along with their update time and height,
+	/// right after a consensus state was stored at `inserted_height`.
+	///
+	/// Consensus states are pruned once the client holds more than
+	/// `T::MaxConsensusStatesPerClient` of them, or once they were processed more than
+	/// `T::ConsensusStateMaxAge` ago and are past the client's trusting period. The consensus
+	/// state at the latest height of the client is never pruned.
+	///
+	/// An update prunes at most [`MAX_CONSENSUS_STATES_PRUNED_PER_UPDATE`] consensus states, and
+	/// none if the client holds more than that past its limit: the consensus states piled up
+	/// before pruning was enabled are pruned with the `prune_consensus_states` call instead.
+	pub(crate) fn prune_consensus_states_on_update(
+		client_id: &ClientId,
+		inserted_height: Height,
+	) -> usize {
+		let max_read = T::MaxConsensusStatesPerClient::get()
+			.saturating_add(MAX_CONSENSUS_STATES_PRUNED_PER_UPDATE);
+		Self::prune_consensus_states_up_to(
+			client_id,
+			Some(inserted_height),
+			max_read,
+			MAX_CONSENSUS_STATES_PRUNED_PER_UPDATE,
+		)
+		.unwrap_or_else(|| {
+			log::warn!(target: "pallet_ibc", "{} holds more than {} consensus states, prune them with the `prune_consensus_states` call", client_id, max_read);
+			0
+		})
+	}
+
+	/// Prunes at most `max_pruned` consensus states of a client, see
+	/// [`Self::prune_consensus_states_on_update`]. Returns `None`, without pruning anything, if the
+	/// client holds more than `max_read` consensus states besides the one at `inserted_height`.
+	pub(crate) fn prune_consensus_states_up_to(
+		client_id: &ClientId,
+		inserted_height: Option<Height>,
+		max_read: u32,
+		max_pruned: u32,
+	) -> Option<usize> {
+		let client_id_bytes = client_id.as_bytes().to_vec();
+		// update times are stored after the consensus state, so the inserted height isn't
+		// accounted for yet
+		let updates = ClientUpdateTime::<T>::iter_prefix(&client_id_bytes)
+			.take(max_read as usize + 1)
+			.collect::<Vec<_>>();
+		if updates.len() > max_read as usize {
+			return None
+		}
+		let mut updates = updates
+			.into_iter()
+			.filter_map(|(height, time)| Some((Height::decode_vec(&height).ok()?, time)))
+			.filter(|(height, _)| Some(*height) != inserted_height)
+			.collect::<Vec<_>>();
+		updates.sort();
+
+		let client_state = ClientStates::<T>::get(client_id)
+			.and_then(|data| AnyClientState::decode_vec(&data).ok());
+		let latest_height = client_state.as_ref().map(|client_state| client_state.latest_height());
+		let now = T::TimeProvider::now().as_nanos().saturated_into::<u64>();
+		let max_age = Duration::from_millis(T::ConsensusStateMaxAge::get());
+		let stored = updates.len() + usize::from(inserted_height.is_some());
+		let excess = stored.saturating_sub(T::MaxConsensusStatesPerClient::get() as usize);
+		let mut pruned = 0;
+		for (i, (height, processed_time)) in updates.into_iter().enumerate() {
+			if pruned >= max_pruned as usize {
+				break
+			}
+			if Some(height) == latest_height {
+				continue
+			}
+			let elapsed = Duration::from_nanos(now.saturating_sub(processed_time));
+			let expired = client_state
+				.as_ref()
+				.map_or(false, |client_state| client_state.expired(elapsed));
+			if i >= excess && !(expired && elapsed > max_age) {
+				continue
+			}
+
+			ConsensusStates::<T>::remove(client_id.clone(), height);
+			if let Ok(encoded_height) = height.encode_vec() {
+				ClientUpdateTime::<T>::remove(&client_id_bytes, &encoded_height);
+				ClientUpdateHeight::<T>::remove(&client_id_bytes, &encoded_height);
+			}
+			ConsensusHeights::<T>::mutate(&client_id_bytes, |heights| {
+				heights.remove(&height);
+			});
+			pruned += 1;
+		}
+		Some(pruned)
+	}
+
 	pub fn remove_channel_escrow_address(
 		port_id: &PortId,
 		channel_id: ChannelId,
@@ -737,6 +881,48 @@ impl<T: Config> Pallet<T> {
 		})
 	}
 
+	/// Amount of `denom` accounted as escrowed by ics20
+	pub fn get_total_escrow(denom: Vec<u8>) -> u128 {
+		TotalEscrow::<T>::get(denom)
+	}
+
+	/// Total escrow of every denom along with the balance held by the escrow accounts, the latter
+	/// should never be lower than the former
+	pub fn get_escrow_balances() -> Vec<ibc_primitives::EscrowBalance> {
+		let escrow_accounts = EscrowAddresses::<T>::get();
+		TotalEscrow::<T>::iter()
+			.map(|(denom, total_escrow)| {
+				let asset_id = core::str::from_utf8(&denom).ok().and_then(|denom| {
+					T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(denom).ok()
+				});
+				let escrow_balance = asset_id
+					.map(|asset_id| {
+						escrow_accounts
+							.iter()
+							.map(|account| {
+								let balance = if asset_id == T::NativeAssetId::get() {
+									T::NativeCurrency::free_balance(account)
+								} else {
+									T::Fungibles::balance(asset_id.clone(), account)
+								};
+								balance.saturated_into::<u128>()
+							})
+							.fold(0u128, u128::saturating_add)
+					})
+					.unwrap_or_default();
+				ibc_primitives::EscrowBalance { denom, total_escrow, escrow_balance }
+			})
+			.collect()
+	}
+
+	/// Denom trace of the voucher referred to by an `ibc/{hash}` denom
+	pub fn get_denom_trace_by_hash(
+		denom_hash: Vec<u8>,
+	) -> Option<ibc_primitives::QueryDenomTraceResponse> {
+		DenomTraces::<T>::get(denom_hash)
+			.map(|denom| ibc_primitives::QueryDenomTraceResponse { denom })
+	}
+
 	pub fn get_denom_traces(
 		key: Option<Either<T::AssetId, u32>>,
 		limit: u64,
@@ -750,6 +936,18 @@ impl<T: Config> Pallet<T> {
 			next_key: next_id.map(|key| key.encode()),
 		}
 	}
+
+	/// Protobuf encoded cross-chain queries waiting for a relayer to answer them
+	pub fn get_cross_chain_queries() -> Vec<Vec<u8>> {
+		CrossChainQueries::<T>::iter_values().collect()
+	}
+
+	/// Value stored in the ibc child trie at the given path, proven by the `query_proof` rpc
+	pub fn get_ibc_store_value(path: Vec<u8>) -> Option<Vec<u8>> {
+		let path = String::from_utf8(path).ok()?;
+		let key = apply_prefix(T::PalletPrefix::get(), vec![path]);
+		child::get_raw(&ChildInfo::new_default(T::PalletPrefix::get()), &key)
+	}
 }
 
 impl<T: Config + Send + Sync> IbcHandler<<T as frame_system::Config>::AccountId> for Pallet<T>
@@ -783,6 +981,30 @@ where
 		}
 	}
 
+	fn submit_cross_chain_query(msg: MsgSubmitCrossChainQuery) -> Result<QueryId, IbcHandlerError> {
+		let mut ctx = Context::<T>::default();
+		let mut output = HandlerOutputBuilder::new();
+		let query_id = ibc::applications::cross_chain_queries::handler::submit_query(
+			&mut ctx,
+			&mut output,
+			msg,
+		)
+		.map_err(|e| IbcHandlerError::CrossChainQueryError { msg: Some(e.to_string()) })?;
+		Self::deposit_event(output.with_result(()).events.into());
+		Ok(query_id)
+	}
+
+	fn cross_chain_query_result(query_id: QueryId) -> Result<Option<QueryResult>, IbcHandlerError> {
+		let ctx = Context::<T>::default();
+		if CrossChainQueries::<T>::contains_key(query_id.value()) {
+			return Ok(None)
+		}
+		let result = ctx
+			.cross_chain_query_result(query_id)
+			.map_err(|e| IbcHandlerError::CrossChainQueryError { msg: Some(e.to_string()) })?;
+		Ok(Some(result.result))
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	fn create_client() -> Result<ClientId, IbcHandlerError> {
 		use crate::{
@@ -1042,11 +1264,62 @@ where
 		Ok(msg)
 	}
 
+	/// Computes the timeout height and timestamp of a packet sent on the channel
+	pub(crate) fn packet_timeout(
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		timeout: Timeout,
+	) -> Result<(Height, Timestamp), Error<T>> {
+		let (latest_height, _) = Pallet::<T>::latest_height_and_timestamp(port_id, channel_id)
+			.map_err(|_| Error::<T>::TimestampAndHeightNotFound)?;
+
+		let (timeout_height, timeout_timestamp) = match timeout {
+			Timeout::Offset { timestamp, height } => {
+				let latest_timestamp = T::TimeProvider::now();
+				let timestamp = timestamp
+					.map(|offset| {
+						Timestamp::from_nanoseconds(
+							(latest_timestamp + Duration::from_secs(offset)).as_nanos() as u64,
+						)
+					})
+					.transpose()
+					.map_err(|_| Error::<T>::InvalidTimestamp)?
+					.unwrap_or_default();
+				let height = height.map(|offset| latest_height.add(offset)).unwrap_or_default();
+				(height, timestamp)
+			},
+			Timeout::Absolute { timestamp, height } => {
+				let timestamp = timestamp
+					.map(Timestamp::from_nanoseconds)
+					.transpose()
+					.map_err(|_| Error::<T>::InvalidTimestamp)?
+					.unwrap_or_default();
+				let height = height
+					.map(|revision_height| {
+						Height::new(latest_height.revision_number, revision_height)
+					})
+					.unwrap_or_default();
+				(height, timestamp)
+			},
+		};
+
+		if timeout_height.is_zero() && timeout_timestamp.nanoseconds() == 0 {
+			return Err(Error::<T>::InvalidTimestamp)
+		}
+
+		Ok((timeout_height, timeout_timestamp))
+	}
+
 	pub(crate) fn send_transfer(msg: MsgTransfer<PrefixedCoin>) -> Result<(), IbcHandlerError> {
+		let denom = msg.token.denom.to_string();
+		let amount = msg.token.amount.as_u256().low_u128();
+		T::Ics20RateLimiter::ensure_outflow(&denom, amount)
+			.map_err(|e| IbcHandlerError::RateLimitExceeded { msg: Some(e) })?;
 		let mut ctx = Context::<T>::default();
 		let mut handler_output = HandlerOutputBuilder::default();
 		send_transfer::<_, _>(&mut ctx, &mut handler_output, msg)
 			.map_err(|e| IbcHandlerError::SendTransferError { msg: Some(e.to_string()) })?;
+		T::Ics20RateLimiter::record_outflow(&denom, amount);
 		let result = handler_output.with_result(());
 		Self::deposit_event(result.events.into());
 		Ok(())
@@ -1109,6 +1382,33 @@ where
 	}
 }
 
+/// Key identifying a channel of a connection in paginated queries: its scale encoded port and
+/// channel ids
+fn channel_key(port_id: &[u8], channel_id: &[u8]) -> Vec<u8> {
+	(port_id, channel_id).encode()
+}
+
+/// Takes at most `limit` of the keyed `entries`, along with the key of the entry following them if
+/// there is one. Without a limit all the entries are taken. The entries start at `next_key` when
+/// given, which must be the key of an entry: it may have been removed since the previous page was
+/// queried, paging on from the following entry would silently skip or repeat entries.
+fn paginate<T: Config, E>(
+	entries: impl Iterator<Item = (Vec<u8>, E)>,
+	next_key: Option<Vec<u8>>,
+	limit: Option<u64>,
+) -> Result<(Vec<E>, Option<Vec<u8>>), Error<T>> {
+	let mut entries = entries.peekable();
+	if let Some(next_key) = next_key {
+		if entries.peek().map(|(key, _)| key) != Some(&next_key) {
+			return Err(Error::<T>::InvalidPageKey)
+		}
+	}
+	let limit = limit.map_or(usize::MAX, |limit| limit.saturated_into());
+	let page = entries.by_ref().take(limit).map(|(_, entry)| entry).collect();
+	let next_key = entries.next().map(|(key, _)| key);
+	Ok((page, next_key))
+}
+
 pub fn host_height<T: Config>() -> u64
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
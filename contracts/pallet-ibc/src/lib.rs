@@ -50,6 +50,7 @@ use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
 mod channel;
 mod client;
 mod connection;
+mod cross_chain_queries;
 pub mod errors;
 pub mod events;
 pub mod ics20;
@@ -62,6 +63,8 @@ pub use ibc_primitives::Timeout;
 pub use light_client_common;
 
 pub const MODULE_ID: &str = "pallet_ibc";
+/// Maximum number of consensus states pruned when a client is updated
+pub const MAX_CONSENSUS_STATES_PRUNED_PER_UPDATE: u32 = 4;
 
 #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
 pub struct Any {
@@ -129,6 +132,10 @@ mod mock;
 mod tests;
 
 pub mod ics20_fee;
+pub mod ics20_rate_limit;
+pub mod ics27;
+pub mod ics29_fee;
+pub mod ics721;
 mod impls;
 pub mod weight;
 
@@ -155,7 +162,7 @@ pub mod pallet {
 	};
 	use frame_system::pallet_prelude::*;
 	pub use ibc::signer::Signer;
-	use sp_core::{crypto::ByteArray, storage::ChildInfo};
+	use sp_core::storage::ChildInfo;
 
 	#[cfg(feature = "testing")]
 	use crate::ics23::{
@@ -163,7 +170,8 @@ pub mod pallet {
 		next_seq_send::NextSequenceSend,
 	};
 	use crate::{
-		ics20::{HandleMemo, SubstrateMultihopXcmHandler},
+		ics20::{ForwardedPacket, HandleMemo, SubstrateMultihopXcmHandler},
+		ics20_rate_limit::Ics20RateLimiter,
 		light_clients::AnyConsensusState,
 		routing::{Context, ModuleRouter},
 	};
@@ -279,6 +287,12 @@ pub mod pallet {
 
 		type SubstrateMultihopXcmHandler: SubstrateMultihopXcmHandler<AccountId = Self::AccountId>;
 
+		/// Enforces outflow quotas on ics20 transfers, see [`ics20_rate_limit`]
+		type Ics20RateLimiter: Ics20RateLimiter;
+		/// Invoked on acknowledgement and timeout of transfers requesting a callback in their
+		/// memo, see [`ics20::callbacks`]
+		type TransferCallbacks: crate::ics20::callbacks::TransferCallbacks<Self>;
+
 		type IsSendEnabled: Get<bool>;
 		type IsReceiveEnabled: Get<bool>;
 		type FeeAccount: Get<Self::AccountIdConversion>;
@@ -286,6 +300,15 @@ pub mod pallet {
 		#[pallet::constant]
 		type CleanUpPacketsPeriod: Get<Self::BlockNumber>;
 
+		/// Maximum number of consensus states kept per client, the oldest ones are pruned first
+		#[pallet::constant]
+		type MaxConsensusStatesPerClient: Get<u32>;
+
+		/// Consensus states processed longer than this (in milliseconds) are pruned, once they're
+		/// also past the trusting period of their client
+		#[pallet::constant]
+		type ConsensusStateMaxAge: Get<u64>;
+
 		#[pallet::constant]
 		/// `ServiceChargeOut` represents the service charge rate applied to assets that will be
 		/// sent via IBC.
@@ -453,6 +476,46 @@ pub mod pallet {
 	pub type PendingRecvPacketSeqs<T: Config> =
 		StorageMap<_, Blake2_128Concat, (Vec<u8>, Vec<u8>), (BTreeSet<u64>, u64), ValueQuery>;
 
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// hash of an ibc denom (`ibc/{hex}`) => full denom, populated when vouchers are first minted
+	pub type DenomTraces<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<u8>, OptionQuery>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// denom => total amount held by the escrow accounts of all channels, as accounted by ics20
+	pub type TotalEscrow<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, u128, ValueQuery>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// counter for cross-chain queries
+	pub type CrossChainQueryCounter<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// query_id => protobuf encoded pending cross-chain query
+	pub type CrossChainQueries<T: Config> =
+		StorageMap<_, Blake2_128Concat, u64, Vec<u8>, OptionQuery>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// query_id => protobuf encoded cross-chain query result
+	pub type CrossChainQueryResults<T: Config> =
+		StorageMap<_, Blake2_128Concat, u64, Vec<u8>, OptionQuery>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// (source channel, sequence) => transfer forwarded by the memo handler, pending
+	/// acknowledgement
+	pub type ForwardedPackets<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		(u64, u64),
+		ForwardedPacket<T::AccountId, T::AssetId, T::Balance, T::MemoMessage>,
+		OptionQuery,
+	>;
+
 	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 	pub struct AssetConfig<AssetId> {
 		pub id: AssetId,
@@ -605,6 +668,16 @@ pub mod pallet {
 			client_id: String,
 			height: Height,
 		},
+		/// An expired or frozen client was recovered with the state of a substitute client
+		ClientRecovered {
+			subject_client_id: String,
+			substitute_client_id: String,
+		},
+		/// Consensus states of a client were pruned by the `prune_consensus_states` call
+		ConsensusStatesPruned {
+			client_id: String,
+			count: u32,
+		},
 		ExecuteMemoStarted {
 			account_id: T::AccountId,
 			memo: Option<String>,
@@ -630,6 +703,30 @@ pub mod pallet {
 			channel: u64,
 			next_memo: Option<T::MemoMessage>,
 		},
+		ExecuteMemoIbcTokenTransferRetried {
+			from: T::AccountId,
+			to: Vec<u8>,
+			channel: u64,
+			retries: u64,
+		},
+		ExecuteMemoIbcTokenTransferRefunded {
+			from: T::AccountId,
+			to: Vec<u8>,
+			asset_id: T::AssetId,
+			amount: T::Balance,
+			channel: u64,
+		},
+		TransferCallbackExecuted {
+			channel: u64,
+			sequence: u64,
+			address: String,
+		},
+		TransferCallbackFailed {
+			channel: u64,
+			sequence: u64,
+			address: String,
+			error: String,
+		},
 		ExecuteMemoXcmSuccess {
 			from: T::AccountId,
 			to: T::AccountId,
@@ -727,6 +824,14 @@ pub mod pallet {
 		/// - The memo is in invalid format
 		/// - The memo contains unsupported middlewares
 		InvalidMemo,
+		/// Transfer would exceed the outflow quota of the denom
+		RateLimitExceeded,
+		/// The subject client couldn't be recovered with the substitute client
+		ClientRecoveryFailed,
+		/// The key a page of a paginated query starts at isn't the key of an entry
+		InvalidPageKey,
+		/// The client holds more consensus states than declared
+		TooManyConsensusStates,
 	}
 
 	#[pallet::hooks]
@@ -816,23 +921,16 @@ pub mod pallet {
 			amount: T::Balance,
 			memo: Option<T::MemoMessage>,
 		) -> DispatchResult {
-			let account_id_32 = T::TransferOrigin::ensure_origin(origin)?.into();
+			let account_id_32: AccountId32 = T::TransferOrigin::ensure_origin(origin)?.into();
 			let denom = T::IbcDenomToAssetIdConversion::from_asset_id_to_denom(asset_id)
 				.ok_or(Error::<T>::InvalidAssetId)?;
-			let from = {
-				let mut hex_string = hex::encode(account_id_32.to_raw_vec());
-				hex_string.insert_str(0, "0x");
-				hex_string
-			};
+			let from = crate::hex_address(&account_id_32);
 
 			let to = match params.to {
 				MultiAddress::Id(id) => {
 					// we convert id to hex string instead of ss58 because destination chain could
 					// have a different ss58 prefix from source chain
-					let account_id_32: AccountId32 = id.into();
-					let mut hex_string = hex::encode(account_id_32.to_raw_vec());
-					hex_string.insert_str(0, "0x");
-					hex_string
+					crate::hex_address(&id.into())
 				},
 				MultiAddress::Raw(bytes) =>
 					String::from_utf8(bytes).map_err(|_| Error::<T>::Utf8Error)?,
@@ -844,43 +942,8 @@ pub mod pallet {
 			let mut coin = PrefixedCoin { denom, amount: ibc_amount };
 			let source_channel = ChannelId::new(params.source_channel);
 			let source_port = PortId::transfer();
-			let (latest_height, _) =
-				Pallet::<T>::latest_height_and_timestamp(&source_port, &source_channel)
-					.map_err(|_| Error::<T>::TimestampAndHeightNotFound)?;
-
-			let (timeout_height, timeout_timestamp) = match params.timeout {
-				Timeout::Offset { timestamp, height } => {
-					let latest_timestamp = T::TimeProvider::now();
-					let timestamp = timestamp
-						.map(|offset| {
-							Timestamp::from_nanoseconds(
-								(latest_timestamp + Duration::from_secs(offset)).as_nanos() as u64,
-							)
-						})
-						.transpose()
-						.map_err(|_| Error::<T>::InvalidTimestamp)?
-						.unwrap_or_default();
-					let height = height.map(|offset| latest_height.add(offset)).unwrap_or_default();
-					(height, timestamp)
-				},
-				Timeout::Absolute { timestamp, height } => {
-					let timestamp = timestamp
-						.map(Timestamp::from_nanoseconds)
-						.transpose()
-						.map_err(|_| Error::<T>::InvalidTimestamp)?
-						.unwrap_or_default();
-					let height = height
-						.map(|revision_height| {
-							Height::new(latest_height.revision_number, revision_height)
-						})
-						.unwrap_or_default();
-					(height, timestamp)
-				},
-			};
-
-			if timeout_height.is_zero() && timeout_timestamp.nanoseconds() == 0 {
-				return Err(Error::<T>::InvalidTimestamp.into())
-			}
+			let (timeout_height, timeout_timestamp) =
+				Pallet::<T>::packet_timeout(&source_port, &source_channel, params.timeout)?;
 
 			let mut ctx = Context::<T>::default();
 			let channel_end = ctx
@@ -1036,6 +1099,8 @@ pub mod pallet {
 					DecodingError { .. } => Error::<T>::TransferSerde,
 					ErrorDecodingPrefix => Error::<T>::TransferSerde,
 
+					CrossChainQueryError { .. } => Error::<T>::TransferOther,
+					RateLimitExceeded { .. } => Error::<T>::RateLimitExceeded,
 					Other { .. } => Error::<T>::TransferOther,
 				}
 			})?;
@@ -1265,9 +1330,84 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		/// Recover an expired or frozen client by copying the state of an active substitute
+		/// client of the same type over it.
+		#[pallet::call_index(10)]
+		#[pallet::weight(0)]
+		#[frame_support::transactional]
+		pub fn recover_client(
+			origin: OriginFor<T>,
+			subject_client_id: String,
+			substitute_client_id: String,
+		) -> DispatchResult {
+			use ibc::core::ics02_client::handler::recover_client;
+			<T as Config>::AdminOrigin::ensure_origin(origin)?;
+
+			let subject =
+				ClientId::from_str(&subject_client_id).map_err(|_| Error::<T>::DecodingError)?;
+			let substitute =
+				ClientId::from_str(&substitute_client_id).map_err(|_| Error::<T>::DecodingError)?;
+
+			let mut ctx = Context::<T>::new();
+			let output = recover_client::process(&ctx, subject, substitute).map_err(|e| {
+				log::warn!(target: "pallet_ibc", "Failed to recover client: {:?}", e);
+				Error::<T>::ClientRecoveryFailed
+			})?;
+			ctx.store_client_result(output.result)
+				.map_err(|_| Error::<T>::ClientRecoveryFailed)?;
+
+			Self::deposit_event(Event::<T>::ClientRecovered {
+				subject_client_id,
+				substitute_client_id,
+			});
+
+			Ok(())
+		}
+
+		/// Prunes up to `max_pruned` consensus states of a client past
+		/// `MaxConsensusStatesPerClient` or `ConsensusStateMaxAge`, for clients holding more
+		/// consensus states than their updates prune, such as the ones created before pruning was
+		/// enabled. `consensus_states` is the number of consensus states the client holds, the
+		/// call fails if it holds more.
+		#[pallet::call_index(11)]
+		#[pallet::weight(crate::weight::prune_consensus_states::<T>(*consensus_states, *max_pruned))]
+		pub fn prune_consensus_states(
+			origin: OriginFor<T>,
+			client_id: String,
+			consensus_states: u32,
+			max_pruned: u32,
+		) -> DispatchResult {
+			<T as Config>::AdminOrigin::ensure_origin(origin)?;
+			let client = ClientId::from_str(&client_id).map_err(|_| Error::<T>::DecodingError)?;
+			let count =
+				Self::prune_consensus_states_up_to(&client, None, consensus_states, max_pruned)
+					.ok_or(Error::<T>::TooManyConsensusStates)?;
+			Self::deposit_event(Event::<T>::ConsensusStatesPruned {
+				client_id,
+				count: count as u32,
+			});
+			Ok(())
+		}
 	}
 }
 
+/// Hex encoded address of an account. Accounts are stored and looked up by this encoding, which
+/// [`Config::AccountIdConversion`] parses whatever the ss58 prefix of the chain it's sent to.
+pub(crate) fn hex_address(account: &sp_runtime::AccountId32) -> String {
+	let mut hex_string = hex::encode(account.as_ref() as &[u8]);
+	hex_string.insert_str(0, "0x");
+	hex_string
+}
+
+/// The [`hex_address`] of an account, as the signer of the messages it sends
+pub(crate) fn signer<T: Config>(account: T::AccountId) -> Signer
+where
+	sp_runtime::AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	Signer::from_str(&hex_address(&account.into())).expect("hex encoded account is a valid signer")
+}
+
 /// Result of the `DenomToAssetId::ibc_assets` function.
 pub struct IbcAssets<AssetId> {
 	/// List of IBC denoms.
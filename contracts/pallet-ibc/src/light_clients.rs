@@ -271,6 +271,20 @@ impl AnyClientState {
 			c => c,
 		}
 	}
+
+	/// Returns the period after the latest consensus state within which the client
+	/// can still be updated, if the client has one.
+	pub fn trusting_period(&self) -> Option<core::time::Duration> {
+		match self {
+			AnyClientState::Grandpa(client_state) =>
+				Some(client_state.relay_chain.trusting_period()),
+			AnyClientState::Beefy(client_state) => Some(client_state.relay_chain.trusting_period()),
+			AnyClientState::Tendermint(client_state) => Some(client_state.trusting_period),
+			AnyClientState::Wasm(wasm_state) => wasm_state.inner.trusting_period(),
+			#[cfg(test)]
+			AnyClientState::Mock(_) => None,
+		}
+	}
 }
 
 impl AnyClientState {
@@ -309,6 +323,13 @@ impl AnyConsensusState {
 			inner: Box::new(inner),
 		}))
 	}
+
+	pub fn unpack_recursive_into(self) -> Self {
+		match self {
+			AnyConsensusState::Wasm(wasm_state) => wasm_state.inner.unpack_recursive_into(),
+			c => c,
+		}
+	}
 }
 
 #[derive(Clone, Debug, ClientMessage)]
@@ -436,3 +457,151 @@ mod mocks {
 	pub const MOCK_CLIENT_MESSAGE_TYPE_URL: &str = "/ibc.mock.ClientMessage";
 	pub const MOCK_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.mock.ConsensusState";
 }
+
+#[cfg(test)]
+mod tests {
+	//! Golden encodings of the grandpa client of a cosmos chain, wrapped in the 08-wasm states, see
+	//! `ibc/modules/tests/golden.rs`. The files of `golden` are generated by
+	//! `ibc/scripts/generate-golden.sh`, with the values of the tests below.
+
+	use super::*;
+	use crate::{mock::Test, routing::Context};
+	use alloc::collections::BTreeMap;
+	use core::str::FromStr;
+	use grandpa_client_primitives::{FinalityProof, ParachainHeaderProofs};
+	use ibc::{
+		core::{
+			ics02_client::msgs::{
+				create_client::MsgCreateAnyClient, update_client::MsgUpdateAnyClient,
+			},
+			ics24_host::identifier::ClientId,
+		},
+		signer::Signer,
+		tx_msg::Msg,
+		Height,
+	};
+	use light_client_common::RelayChain;
+	use tendermint::Time;
+
+	const SIGNER: &str = "cosmos1hsk6jryyqjfhp5dhc55tc9jtckygx0eph6dd02";
+	const PARA_ID: u32 = 2087;
+
+	macro_rules! golden {
+		($name:literal) => {
+			hex::decode(include_str!(concat!("golden/", $name, ".hex")).trim()).unwrap()
+		};
+	}
+
+	fn grandpa_client_state() -> AnyClientState {
+		AnyClientState::Grandpa(ics10_grandpa::client_state::ClientState {
+			relay_chain: RelayChain::Rococo,
+			latest_relay_height: 200,
+			latest_relay_hash: H256::repeat_byte(3),
+			frozen_height: None,
+			latest_para_height: 100,
+			para_id: PARA_ID,
+			current_set_id: 5,
+			current_authorities: vec![(ed25519::Public::from_raw([4; 32]).into(), 1)],
+			_phantom: Default::default(),
+		})
+	}
+
+	fn grandpa_consensus_state() -> AnyConsensusState {
+		AnyConsensusState::Grandpa(ics10_grandpa::consensus_state::ConsensusState::new(
+			vec![1; 32],
+			Time::from_unix_timestamp(1_700_000_000, 0).unwrap(),
+		))
+	}
+
+	fn grandpa_header() -> AnyClientMessage {
+		let parachain_header = ParachainHeaderProofs {
+			state_proof: vec![b"state proof".to_vec()],
+			extrinsic: b"timestamp extrinsic".to_vec(),
+			extrinsic_proof: vec![b"extrinsic proof".to_vec()],
+		};
+		AnyClientMessage::Grandpa(ics10_grandpa::client_message::ClientMessage::Header(
+			ics10_grandpa::client_message::Header {
+				finality_proof: FinalityProof {
+					block: H256::repeat_byte(6),
+					justification: b"justification".to_vec(),
+					unknown_headers: vec![],
+				},
+				parachain_headers: BTreeMap::from([(H256::repeat_byte(7), parachain_header)]),
+				height: Height::new(PARA_ID.into(), 100),
+			},
+		))
+	}
+
+	fn wasm_client_state() -> AnyClientState {
+		AnyClientState::wasm(grandpa_client_state(), vec![5; 32]).unwrap()
+	}
+
+	fn wasm_consensus_state() -> AnyConsensusState {
+		AnyConsensusState::wasm(grandpa_consensus_state()).unwrap()
+	}
+
+	#[test]
+	fn grandpa_states_match_golden_encoding() {
+		let golden = golden!("grandpa_client_state");
+		assert_eq!(
+			hex::encode(grandpa_client_state().encode_to_vec().unwrap()),
+			hex::encode(&golden)
+		);
+		assert_eq!(AnyClientState::decode_vec(&golden).unwrap(), grandpa_client_state());
+
+		let golden = golden!("grandpa_consensus_state");
+		assert_eq!(
+			hex::encode(grandpa_consensus_state().encode_to_vec().unwrap()),
+			hex::encode(&golden)
+		);
+		assert_eq!(AnyConsensusState::decode_vec(&golden).unwrap(), grandpa_consensus_state());
+	}
+
+	#[test]
+	fn wasm_states_match_golden_encoding() {
+		let golden = golden!("wasm_client_state");
+		assert_eq!(hex::encode(wasm_client_state().encode_to_vec().unwrap()), hex::encode(&golden));
+		assert_eq!(AnyClientState::decode_vec(&golden).unwrap(), wasm_client_state());
+
+		let golden = golden!("wasm_consensus_state");
+		assert_eq!(
+			hex::encode(wasm_consensus_state().encode_to_vec().unwrap()),
+			hex::encode(&golden)
+		);
+		assert_eq!(AnyConsensusState::decode_vec(&golden).unwrap(), wasm_consensus_state());
+	}
+
+	#[test]
+	fn msg_create_client_matches_golden_encoding() {
+		let golden = golden!("msg_create_client");
+		let msg = MsgCreateAnyClient::<Context<Test>>::new(
+			wasm_client_state(),
+			wasm_consensus_state(),
+			Signer::from_str(SIGNER).unwrap(),
+		)
+		.unwrap();
+		assert_eq!(hex::encode(msg.clone().to_any().encode_to_vec()), hex::encode(&golden));
+
+		let any = Any::decode(&*golden).unwrap();
+		assert_eq!(any.type_url, msg.type_url());
+		assert_eq!(MsgCreateAnyClient::<Context<Test>>::decode_vec(&any.value).unwrap(), msg);
+	}
+
+	#[test]
+	fn msg_update_client_matches_golden_encoding() {
+		let golden = golden!("msg_update_client");
+		let msg = MsgUpdateAnyClient::<Context<Test>>::new(
+			ClientId::from_str("08-wasm-0").unwrap(),
+			AnyClientMessage::wasm(grandpa_header()).unwrap(),
+			Signer::from_str(SIGNER).unwrap(),
+		);
+		assert_eq!(hex::encode(msg.clone().to_any().encode_to_vec()), hex::encode(&golden));
+
+		// client messages can't be compared, the decoded message must encode to the golden again
+		let any = Any::decode(&*golden).unwrap();
+		assert_eq!(any.type_url, msg.type_url());
+		let decoded = MsgUpdateAnyClient::<Context<Test>>::decode_vec(&any.value).unwrap();
+		assert!(matches!(decoded.client_message, AnyClientMessage::Wasm(_)));
+		assert_eq!(hex::encode(decoded.to_any().encode_to_vec()), hex::encode(&golden));
+	}
+}
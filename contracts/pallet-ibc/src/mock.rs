@@ -30,9 +30,11 @@ use sp_keystore::{testing::MemoryKeystore, KeystoreExt};
 use sp_runtime::{
 	generic,
 	traits::{BlakeTwo256, IdentityLookup},
-	MultiSignature, Perbill,
+	DispatchError, MultiSignature, Perbill,
 };
 use std::{
+	cell::RefCell,
+	collections::BTreeMap,
 	convert::Infallible,
 	sync::Arc,
 	time::{SystemTime, UNIX_EPOCH},
@@ -212,10 +214,15 @@ parameter_types! {
 	pub const IbcTriePrefix : &'static [u8] = b"ibc/";
 	pub const ServiceCharge: Perbill = Perbill::from_percent(1);
 	pub const PalletId: frame_support::PalletId = frame_support::PalletId(*b"ics20fee");
+	pub const FeeEscrowPalletId: frame_support::PalletId = frame_support::PalletId(*b"ics29fee");
+	pub const NftPalletId: frame_support::PalletId = frame_support::PalletId(*b"ics721nf");
+	pub const IcaPalletId: frame_support::PalletId = frame_support::PalletId(*b"ics27ica");
 	pub const FlatFeeAssetId: AssetId = 130;
 	pub const FlatFeeAmount: AssetId = 10_000_000;
 	pub FeeAccount: <Test as Config>::AccountIdConversion = create_alice_key();
 	pub const CleanUpPacketsPeriod: u32 = 10;
+	pub const MaxConsensusStatesPerClient: u32 = 256;
+	pub const ConsensusStateMaxAge: u64 = 30 * 24 * 60 * 60 * 1000;
 }
 
 fn create_alice_key() -> <Test as Config>::AccountIdConversion {
@@ -287,15 +294,19 @@ impl Config for Test {
 	type RelayerOrigin = EnsureSigned<Self::AccountId>;
 	type HandleMemo = IbcMemoHandler<(), Test>;
 	type MemoMessage = RawMemo;
-	type IsReceiveEnabled = sp_core::ConstBool<true>;
-	type IsSendEnabled = sp_core::ConstBool<true>;
+	type IsReceiveEnabled = frame_support::traits::ConstBool<true>;
+	type IsSendEnabled = frame_support::traits::ConstBool<true>;
 	type FeeAccount = FeeAccount;
 	type CleanUpPacketsPeriod = CleanUpPacketsPeriod;
+	type MaxConsensusStatesPerClient = MaxConsensusStatesPerClient;
+	type ConsensusStateMaxAge = ConsensusStateMaxAge;
 	type ServiceChargeOut = ServiceCharge;
 	type FlatFeeConverter = FlatFeeConverterDummy<Test>;
 	type FlatFeeAssetId = FlatFeeAssetId;
 	type FlatFeeAmount = FlatFeeAmount;
 	type SubstrateMultihopXcmHandler = SubstrateMultihopXcmHandlerNone<Test>;
+	type Ics20RateLimiter = crate::ics20_rate_limit::Pallet<Test>;
+	type TransferCallbacks = ();
 }
 
 #[derive(Debug, Clone)]
@@ -320,6 +331,102 @@ impl crate::ics20_fee::Config for Test {
 	type PalletId = PalletId;
 }
 
+impl crate::ics20_rate_limit::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+}
+
+impl crate::ics29_fee::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type PalletId = FeeEscrowPalletId;
+	type WeightInfo = ();
+}
+
+thread_local! {
+	/// collection, item => owner
+	static NFT_OWNERS: RefCell<BTreeMap<(u32, u32), AccountId>> = RefCell::new(BTreeMap::new());
+	static NEXT_COLLECTION: RefCell<u32> = RefCell::new(1000);
+}
+
+/// Non-fungible tokens kept in memory, collections are created from id 1000 on
+pub struct MockNonFungibles;
+
+impl crate::ics721::NonFungibles<AccountId> for MockNonFungibles {
+	type CollectionId = u32;
+	type ItemId = u32;
+
+	fn owner(collection: &u32, item: &u32) -> Option<AccountId> {
+		NFT_OWNERS.with(|owners| owners.borrow().get(&(*collection, *item)).cloned())
+	}
+
+	fn create_collection(_owner: &AccountId) -> Result<u32, DispatchError> {
+		Ok(NEXT_COLLECTION.with(|next| {
+			let collection = *next.borrow();
+			*next.borrow_mut() += 1;
+			collection
+		}))
+	}
+
+	fn mint_into(collection: &u32, item: &u32, who: &AccountId) -> Result<(), DispatchError> {
+		NFT_OWNERS.with(|owners| owners.borrow_mut().insert((*collection, *item), who.clone()));
+		Ok(())
+	}
+
+	fn burn(collection: &u32, item: &u32) -> Result<(), DispatchError> {
+		NFT_OWNERS
+			.with(|owners| owners.borrow_mut().remove(&(*collection, *item)))
+			.map(|_| ())
+			.ok_or(DispatchError::CannotLookup)
+	}
+
+	fn transfer(
+		collection: &u32,
+		item: &u32,
+		destination: &AccountId,
+	) -> Result<(), DispatchError> {
+		NFT_OWNERS.with(|owners| match owners.borrow_mut().get_mut(&(*collection, *item)) {
+			Some(owner) => {
+				*owner = destination.clone();
+				Ok(())
+			},
+			None => Err(DispatchError::CannotLookup),
+		})
+	}
+}
+
+impl crate::ics721::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type PalletId = NftPalletId;
+	type NonFungibles = MockNonFungibles;
+}
+
+/// The only message the hosted interchain accounts may execute
+pub const ICA_ALLOWED_MESSAGE: &str = "/cosmos.bank.v1beta1.MsgSend";
+
+/// Executes the allowed messages without effect, their result is the number of messages
+pub struct MockIcaHostExecutor;
+
+impl crate::ics27::IcaHostExecutor<AccountId> for MockIcaHostExecutor {
+	fn is_message_allowed(type_url: &str) -> bool {
+		type_url == ICA_ALLOWED_MESSAGE
+	}
+
+	fn execute(
+		_account: &AccountId,
+		messages: Vec<ibc_proto::google::protobuf::Any>,
+	) -> Result<Vec<u8>, DispatchError> {
+		Ok((messages.len() as u32).encode())
+	}
+}
+
+impl crate::ics27::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type PalletId = IcaPalletId;
+	type IsControllerEnabled = frame_support::traits::ConstBool<true>;
+	type IsHostEnabled = frame_support::traits::ConstBool<true>;
+	type HostExecutor = MockIcaHostExecutor;
+	type ControllerHooks = ();
+}
+
 #[derive(
 	Debug, codec::Encode, Clone, codec::Decode, PartialEq, Eq, scale_info::TypeInfo, Default,
 )]
@@ -423,9 +530,21 @@ where
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub struct Router {
 	ibc_ping: pallet_ibc_ping::IbcModule<Test>,
-	ics20: crate::ics20::memo::Memo<
+	ics721: crate::ics721::IbcModule<Test>,
+	ics27_controller: crate::ics27::ControllerModule<Test>,
+	ics27_host: crate::ics27::HostModule<Test>,
+	ics20: crate::ics29_fee::FeeMiddleware<
 		Test,
-		crate::ics20_fee::Ics20ServiceCharge<Test, crate::ics20::IbcModule<Test>>,
+		crate::ics20_rate_limit::Ics20RateLimit<
+			Test,
+			crate::ics20::callbacks::Callbacks<
+				Test,
+				crate::ics20::memo::Memo<
+					Test,
+					crate::ics20_fee::Ics20ServiceCharge<Test, crate::ics20::IbcModule<Test>>,
+				>,
+			>,
+		>,
 	>,
 }
 
@@ -437,6 +556,11 @@ impl ModuleRouter for Router {
 		match module_id.as_ref() {
 			pallet_ibc_ping::MODULE_ID => Some(&mut self.ibc_ping),
 			ibc::applications::transfer::MODULE_ID_STR => Some(&mut self.ics20),
+			crate::ics721::MODULE_ID_STR => Some(&mut self.ics721),
+			ibc::applications::interchain_accounts::CONTROLLER_MODULE_ID_STR =>
+				Some(&mut self.ics27_controller),
+			ibc::applications::interchain_accounts::HOST_MODULE_ID_STR =>
+				Some(&mut self.ics27_host),
 			&_ => None,
 		}
 	}
@@ -444,7 +568,11 @@ impl ModuleRouter for Router {
 	fn has_route(module_id: &ibc::core::ics26_routing::context::ModuleId) -> bool {
 		matches!(
 			module_id.as_ref(),
-			pallet_ibc_ping::MODULE_ID | ibc::applications::transfer::MODULE_ID_STR
+			pallet_ibc_ping::MODULE_ID |
+				ibc::applications::transfer::MODULE_ID_STR |
+				crate::ics721::MODULE_ID_STR |
+				ibc::applications::interchain_accounts::CONTROLLER_MODULE_ID_STR |
+				ibc::applications::interchain_accounts::HOST_MODULE_ID_STR
 		)
 	}
 
@@ -460,7 +588,10 @@ impl ModuleRouter for Router {
 					ibc::applications::transfer::MODULE_ID_STR,
 				)
 				.ok(),
-			_ => None,
+			crate::ics721::PORT_ID_STR =>
+				ibc::core::ics26_routing::context::ModuleId::from_str(crate::ics721::MODULE_ID_STR)
+					.ok(),
+			_ => crate::ics27::lookup_module_by_port(port_id),
 		}
 	}
 }
@@ -480,6 +611,10 @@ frame_support::construct_runtime!(
 		PalletBalances: balances,
 		IbcPing: pallet_ibc_ping,
 		Ics20Fee: crate::ics20_fee,
+		Ics20RateLimit: crate::ics20_rate_limit,
+		Ics29Fee: crate::ics29_fee,
+		Ics721: crate::ics721,
+		Ics27: crate::ics27,
 		Ibc: pallet_ibc,
 		Aura: pallet_aura,
 		Membership: pallet_membership::<Instance2>,
@@ -1,9 +1,11 @@
 use crate::{
+	ics20::{ForwardedPacket, HandleMemo},
 	ics20_fee::FlatFeeConverter,
+	ics20_rate_limit::RateLimit,
 	light_clients::{AnyClientState, AnyConsensusState},
 	mock::*,
 	routing::Context,
-	Any, Config, ConsensusHeights, DenomToAssetId, Event, MultiAddress, Pallet,
+	Any, Config, ConsensusHeights, DenomToAssetId, Event, ForwardedPackets, MultiAddress, Pallet,
 	PendingRecvPacketSeqs, PendingSendPacketSeqs, Timeout, TransferParams, MODULE_ID,
 };
 use core::time::Duration;
@@ -17,8 +19,10 @@ use frame_support::{
 };
 use ibc::{
 	applications::transfer::{
-		acknowledgement::Acknowledgement as Ics20Acknowledgement, packet::PacketData, Coin,
-		PrefixedDenom, VERSION,
+		acknowledgement::Acknowledgement as Ics20Acknowledgement,
+		context::{Ics20Keeper, Ics20Reader},
+		packet::PacketData,
+		Coin, PrefixedDenom, VERSION,
 	},
 	core::{
 		ics02_client::{
@@ -57,7 +61,7 @@ use ibc::{
 use ibc_primitives::{get_channel_escrow_address, HandlerMessage, IbcHandler};
 use sp_core::Pair;
 use sp_runtime::{
-	traits::{Bounded, IdentifyAccount},
+	traits::{AccountIdConversion, Bounded, IdentifyAccount},
 	AccountId32,
 };
 use std::{
@@ -331,6 +335,158 @@ fn send_transfer_with_invalid_memo() {
 	});
 }
 
+#[test]
+fn forwarded_transfer_is_retried_on_timeout_then_refunded() {
+	let mut ext = new_test_ext();
+	let balance = 100000 * MILLIS;
+	ext.execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let ss58_address =
+			ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		setup_client_and_consensus_state(PortId::transfer());
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				"PICA",
+			)
+			.unwrap();
+		let forwarder = AccountId32::new([0; 32]);
+		let _ = <<Test as Config>::NativeCurrency as Currency<
+			<Test as frame_system::Config>::AccountId,
+		>>::deposit_creating(&forwarder, balance);
+		Ibc::add_channels_to_feeless_channel_list(RuntimeOrigin::root(), 0, 1)
+			.expect("expect add channels to feeless list");
+
+		let forward = ForwardedPacket {
+			forwarder: forwarder.clone(),
+			original_sender: ss58_address.as_bytes().to_vec(),
+			original_channel: 0,
+			receiver: ss58_address.as_bytes().to_vec(),
+			channel: 0,
+			asset_id,
+			amount: balance / 2,
+			timeout: 1000,
+			retries: 1,
+			next_memo: None,
+		};
+		ForwardedPackets::<Test>::insert((0, 7), forward.clone());
+		let mut packet = Packet {
+			sequence: 7u64.into(),
+			source_port: PortId::transfer(),
+			source_channel: ChannelId::new(0),
+			destination_port: PortId::transfer(),
+			destination_channel: ChannelId::new(1),
+			data: vec![],
+			timeout_height: Height::new(0, 5),
+			timeout_timestamp: ibc::timestamp::Timestamp::none(),
+		};
+		let handler = <Test as Config>::HandleMemo::default();
+
+		// The first timeout re-sends the transfer with the next sequence
+		handler.on_timeout(&packet).unwrap();
+		assert!(!ForwardedPackets::<Test>::contains_key((0, 7)));
+		assert_eq!(
+			ForwardedPackets::<Test>::get((0, 1)),
+			Some(ForwardedPacket { retries: 0, ..forward })
+		);
+
+		// Once retries are exhausted the tokens are sent back to the original sender
+		packet.sequence = 1u64.into();
+		handler.on_timeout(&packet).unwrap();
+		assert!(!ForwardedPackets::<Test>::contains_key((0, 1)));
+		assert!(!ForwardedPackets::<Test>::contains_key((0, 2)));
+		assert!(System::events().iter().any(|record| matches!(
+			record.event,
+			RuntimeEvent::Ibc(Event::<Test>::ExecuteMemoIbcTokenTransferRefunded { .. })
+		)));
+	});
+}
+
+#[test]
+fn forwarded_transfer_is_cleared_on_successful_acknowledgement() {
+	new_test_ext().execute_with(|| {
+		let forward = ForwardedPacket {
+			forwarder: AccountId32::new([0; 32]),
+			original_sender: b"sender".to_vec(),
+			original_channel: 1,
+			receiver: b"receiver".to_vec(),
+			channel: 0,
+			asset_id: 1,
+			amount: 100,
+			timeout: 1000,
+			retries: 0,
+			next_memo: None,
+		};
+		ForwardedPackets::<Test>::insert((0, 3), forward);
+		let packet = Packet {
+			sequence: 3u64.into(),
+			source_port: PortId::transfer(),
+			source_channel: ChannelId::new(0),
+			destination_port: PortId::transfer(),
+			destination_channel: ChannelId::new(1),
+			data: vec![],
+			timeout_height: Height::new(0, 5),
+			timeout_timestamp: ibc::timestamp::Timestamp::none(),
+		};
+
+		<Test as Config>::HandleMemo::default()
+			.on_acknowledgement(&packet, true)
+			.unwrap();
+		assert!(!ForwardedPackets::<Test>::contains_key((0, 3)));
+	});
+}
+
+#[test]
+fn send_transfer_exceeding_rate_limit() {
+	let mut ext = new_test_ext();
+	let balance = 100000 * MILLIS;
+	ext.execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let ss58_address =
+			ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		setup_client_and_consensus_state(PortId::transfer());
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				"PICA",
+			)
+			.unwrap();
+		let _ = <<Test as Config>::NativeCurrency as Currency<
+			<Test as frame_system::Config>::AccountId,
+		>>::deposit_creating(&AccountId32::new([0; 32]), balance);
+		Ibc::add_channels_to_feeless_channel_list(RuntimeOrigin::root(), 0, 1)
+			.expect("expect add channels to feeless list");
+		Ics20RateLimit::set_rate_limit(
+			RuntimeOrigin::root(),
+			b"PICA".to_vec(),
+			RateLimit { max_inflow: 100, max_outflow: 100, window: 10 },
+		)
+		.unwrap();
+
+		let transfer = |amount| {
+			Ibc::transfer(
+				RuntimeOrigin::signed(AccountId32::new([0; 32])),
+				TransferParams {
+					to: MultiAddress::Raw(ss58_address.as_bytes().to_vec()),
+					source_channel: 0,
+					timeout: Timeout::Offset { timestamp: Some(1000), height: Some(5) },
+				},
+				asset_id,
+				amount,
+				None,
+			)
+		};
+
+		assert_ok!(transfer(60));
+		assert_noop!(transfer(60), crate::Error::<Test>::RateLimitExceeded);
+
+		// Halfway through the next window only half of the previous window's outflow counts
+		frame_system::Pallet::<Test>::set_block_number(15u32);
+		assert_ok!(transfer(60));
+		assert_noop!(transfer(20), crate::Error::<Test>::RateLimitExceeded);
+	});
+}
+
 #[test]
 fn send_transfer_no_fee_feeless_channels() {
 	let mut ext = new_test_ext();
@@ -499,6 +655,65 @@ fn on_deliver_ics20_recv_packet() {
 	})
 }
 
+#[test]
+fn denom_traces_are_stored_by_hash() {
+	new_test_ext().execute_with(|| {
+		let mut ctx = Context::<Test>::default();
+		let denom = PrefixedDenom::from_str("transfer/channel-0/uatom").unwrap();
+		let hash = ctx.denom_hash_string(&denom).unwrap();
+		assert_eq!(hash, "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2");
+		assert_eq!(ctx.get_denom_trace(&hash), None);
+
+		ctx.store_denom_trace(hash.clone(), denom.clone()).unwrap();
+		assert_eq!(ctx.get_denom_trace(&hash), Some(denom));
+		assert_eq!(
+			Pallet::<Test>::get_denom_trace_by_hash(hash.into_bytes()).map(|trace| trace.denom),
+			Some(b"transfer/channel-0/uatom".to_vec())
+		);
+	})
+}
+
+#[test]
+fn send_transfer_tracks_total_escrow() {
+	new_test_ext().execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let ss58_address =
+			ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		setup_client_and_consensus_state(PortId::transfer());
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				"PICA",
+			)
+			.unwrap();
+		let balance = 100000 * MILLIS;
+		let _ = <<Test as Config>::NativeCurrency as Currency<
+			<Test as frame_system::Config>::AccountId,
+		>>::deposit_creating(&AccountId32::new([0; 32]), balance);
+		assert_eq!(Pallet::<Test>::get_total_escrow(b"PICA".to_vec()), 0);
+
+		Ibc::transfer(
+			RuntimeOrigin::signed(AccountId32::new([0; 32])),
+			TransferParams {
+				to: MultiAddress::Raw(ss58_address.as_bytes().to_vec()),
+				source_channel: 0,
+				timeout: Timeout::Offset { timestamp: Some(1000), height: Some(5) },
+			},
+			asset_id,
+			balance,
+			None,
+		)
+		.unwrap();
+
+		let fee = <Test as crate::ics20_fee::Config>::ServiceChargeIn::get() * balance;
+		assert_eq!(Pallet::<Test>::get_total_escrow(b"PICA".to_vec()), balance - fee);
+		let escrow_balances = Pallet::<Test>::get_escrow_balances();
+		assert_eq!(escrow_balances.len(), 1);
+		assert_eq!(escrow_balances[0].denom, b"PICA".to_vec());
+		assert_eq!(escrow_balances[0].total_escrow, balance - fee);
+		assert!(escrow_balances[0].escrow_balance >= escrow_balances[0].total_escrow);
+	})
+}
+
 #[test]
 fn on_deliver_ics20_recv_packet_incorrect_memo() {
 	let mut ext = new_test_ext();
@@ -817,6 +1032,73 @@ fn on_ack_transfer_with_custom_success_result() {
 	})
 }
 
+#[test]
+fn on_ack_transfer_invokes_src_callback() {
+	new_test_ext().execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				"PICAFLATFEE",
+			)
+			.unwrap();
+		setup_client_and_consensus_state(PortId::transfer());
+
+		let channel_id = ChannelId::new(0);
+		let acc = AccountId32::new(pair.public().0);
+		<<Test as Config>::Fungibles as Mutate<
+			<Test as frame_system::Config>::AccountId,
+		>>::mint_into(asset_id, &acc, 100000 * MILLIS)
+		.unwrap();
+
+		assert_ok!(Ibc::transfer(
+			RuntimeOrigin::signed(acc.clone()),
+			TransferParams {
+				to: MultiAddress::Raw(vec![42; 10]),
+				source_channel: channel_id.sequence(),
+				timeout: Timeout::Offset { timestamp: None, height: Some(1) },
+			},
+			asset_id,
+			1000 * MILLIS,
+			Some(RawMemo(r#"{"src_callback":{"address":"callback"}}"#.to_string())),
+		));
+
+		let packet_info = Ibc::get_send_packet_info(
+			channel_id.to_string().as_bytes().to_vec(),
+			PortId::transfer().as_bytes().to_vec(),
+			vec![1],
+		)
+		.unwrap()
+		.get(0)
+		.unwrap()
+		.clone();
+		let msg = MsgAcknowledgement {
+			packet: Packet::from(packet_info),
+			acknowledgement: Acknowledgement::from_bytes(
+				Ics20Acknowledgement::success().to_string().into_bytes(),
+			),
+			proofs: Proofs::new(
+				vec![0u8; 32].try_into().unwrap(),
+				None,
+				None,
+				None,
+				Height::new(0, 1),
+			)
+			.unwrap(),
+			signer: Signer::from_str(MODULE_ID).unwrap(),
+		};
+		let msg = Any { type_url: msg.type_url(), value: msg.encode_vec().unwrap() };
+		Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]).unwrap();
+
+		assert!(System::events().iter().any(|record| record.event ==
+			RuntimeEvent::Ibc(Event::<Test>::TransferCallbackExecuted {
+				channel: 0,
+				sequence: 1,
+				address: "callback".to_string(),
+			})));
+	})
+}
+
 #[test]
 fn on_deliver_ics20_recv_packet_transfered_amount_less_then_flat_fee() {
 	let mut ext = new_test_ext();
@@ -1310,3 +1592,688 @@ fn test_next_and_previous_consensus_state_for_other_client_types() {
 		assert!(ctx.next_consensus_state(&client_id, Height::new(0, 400)).unwrap().is_some());
 	})
 }
+
+#[test]
+fn create_tendermint_client() {
+	use crate::light_clients::HostFunctionsManager;
+	use ibc::core::{
+		ics02_client::trust_threshold::TrustThreshold,
+		ics23_commitment::{commitment::CommitmentRoot, specs::ProofSpecs},
+		ics24_host::identifier::ChainId,
+	};
+
+	new_test_ext().execute_with(|| {
+		let client_state =
+			ics07_tendermint::client_state::ClientState::<HostFunctionsManager>::new(
+				ChainId::new("cosmos".to_string(), 1),
+				TrustThreshold::ONE_THIRD,
+				Duration::from_secs(64000),
+				Duration::from_secs(128000),
+				Duration::from_millis(3000),
+				Height::new(1, 10),
+				ProofSpecs::default(),
+				vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
+			)
+			.unwrap();
+		let consensus_state = ics07_tendermint::consensus_state::ConsensusState::new(
+			CommitmentRoot::from_bytes(&[1; 32]),
+			tendermint::Time::from_unix_timestamp(1_700_000_000, 0).unwrap(),
+			tendermint::Hash::Sha256([2; 32]),
+		);
+		let msg = MsgCreateAnyClient::<Context<Test>>::new(
+			AnyClientState::Tendermint(client_state.clone()),
+			AnyConsensusState::Tendermint(consensus_state.clone()),
+			Signer::from_str(MODULE_ID).unwrap(),
+		)
+		.unwrap()
+		.encode_vec()
+		.unwrap();
+		let msg = Any { type_url: TYPE_URL.to_string(), value: msg };
+		assert_ok!(Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]));
+
+		let ctx = Context::<Test>::default();
+		let client_id = ClientId::new(&client_state.client_type(), 0).unwrap();
+		assert_eq!(ctx.client_state(&client_id).unwrap(), AnyClientState::Tendermint(client_state));
+		assert_eq!(
+			ctx.consensus_state(&client_id, Height::new(1, 10)).unwrap(),
+			AnyConsensusState::Tendermint(consensus_state)
+		);
+	})
+}
+
+#[test]
+fn consensus_states_are_pruned_past_max_count() {
+	new_test_ext().execute_with(|| {
+		let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+		let mut ctx = Context::<Test>::default();
+		let mock_cs_state = MockConsensusState::new(MockHeader::default());
+		let max_count = MaxConsensusStatesPerClient::get() as u64;
+		for i in 1..=max_count + 10 {
+			let height = Height::new(0, i);
+			ctx.store_client_state(
+				client_id.clone(),
+				AnyClientState::Mock(MockClientState::new(MockClientMessage::from(
+					MockHeader::new(height),
+				))),
+			)
+			.unwrap();
+			ctx.store_consensus_state(
+				client_id.clone(),
+				height,
+				AnyConsensusState::Mock(mock_cs_state.clone()),
+			)
+			.unwrap();
+			ctx.store_update_time(client_id.clone(), height, ctx.host_timestamp()).unwrap();
+			ctx.store_update_height(client_id.clone(), height, ctx.host_height()).unwrap();
+		}
+
+		for i in 1..=10 {
+			assert!(ctx.consensus_state(&client_id, Height::new(0, i)).is_err());
+			assert!(ctx.client_update_time(&client_id, Height::new(0, i)).is_err());
+		}
+		for i in 11..=max_count + 10 {
+			assert!(ctx.consensus_state(&client_id, Height::new(0, i)).is_ok());
+		}
+		let stored_heights = ConsensusHeights::<Test>::get(client_id.as_bytes().to_vec());
+		assert_eq!(stored_heights.len() as u64, max_count);
+		assert_eq!(stored_heights.iter().next(), Some(&Height::new(0, 11)));
+	})
+}
+
+#[test]
+fn pay_packet_fee_escrows_fee_on_fee_enabled_channels() {
+	new_test_ext().execute_with(|| {
+		setup_client_and_consensus_state(PortId::transfer());
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				"PICA",
+			)
+			.unwrap();
+		let payer = AccountId32::new([0; 32]);
+		let _ = <<Test as Config>::NativeCurrency as Currency<
+			<Test as frame_system::Config>::AccountId,
+		>>::deposit_creating(&payer, 100000 * MILLIS);
+		let fee = crate::ics29_fee::FeeParams {
+			recv_fee: vec![(asset_id, 300 * MILLIS)],
+			ack_fee: vec![(asset_id, 200 * MILLIS)],
+			timeout_fee: vec![(asset_id, 100 * MILLIS)],
+		};
+		let port_id = PortId::transfer().as_bytes().to_vec();
+		let channel_id = ChannelId::new(0).to_string().as_bytes().to_vec();
+
+		assert_noop!(
+			Ics29Fee::pay_packet_fee(
+				RuntimeOrigin::signed(payer.clone()),
+				port_id.clone(),
+				channel_id.clone(),
+				fee.clone(),
+			),
+			crate::ics29_fee::Error::<Test>::FeeMiddleware
+		);
+
+		crate::ics29_fee::FeeEnabledChannels::<Test>::insert(
+			port_id.clone(),
+			channel_id.clone(),
+			(),
+		);
+		assert_ok!(Ics29Fee::pay_packet_fee(
+			RuntimeOrigin::signed(payer),
+			port_id.clone(),
+			channel_id.clone(),
+			fee,
+		));
+
+		let packet_fees = crate::ics29_fee::decode_packet_fees(
+			&crate::ics29_fee::PacketFees::<Test>::get((port_id, channel_id), 1).unwrap(),
+		);
+		assert_eq!(packet_fees.len(), 1);
+		let escrow = <Test as crate::ics29_fee::Config>::PalletId::get().into_account_truncating();
+		assert_eq!(
+			<<Test as Config>::NativeCurrency as Currency<
+				<Test as frame_system::Config>::AccountId,
+			>>::free_balance(&escrow),
+			600 * MILLIS
+		);
+	})
+}
+
+#[test]
+fn payees_are_found_whatever_the_encoding_of_the_relayer() {
+	use ibc::applications::fee::context::FeeReader;
+	use sp_core::crypto::Ss58Codec;
+
+	new_test_ext().execute_with(|| {
+		let relayer = AccountId32::new([1; 32]);
+		let payee = AccountId32::new([2; 32]);
+		let port_id = PortId::transfer().as_bytes().to_vec();
+		let channel_id = ChannelId::new(0).to_string().as_bytes().to_vec();
+		crate::ics29_fee::FeeEnabledChannels::<Test>::insert(
+			port_id.clone(),
+			channel_id.clone(),
+			(),
+		);
+		assert_ok!(Ics29Fee::register_payee(
+			RuntimeOrigin::signed(relayer.clone()),
+			port_id,
+			channel_id,
+			payee.clone(),
+		));
+
+		// relayers sign their messages with an ss58 address, the calls register a hex one
+		let ctx = Context::<Test>::default();
+		let ss58_relayer = Signer::from_str(&relayer.to_ss58check()).unwrap();
+		let hex_relayer = crate::signer::<Test>(relayer);
+		let expected = Some(crate::signer::<Test>(payee));
+		assert_eq!(ctx.payee(&ss58_relayer, &ChannelId::new(0)), expected);
+		assert_eq!(ctx.payee(&hex_relayer, &ChannelId::new(0)), expected);
+	})
+}
+
+#[test]
+fn channels_are_paginated() {
+	new_test_ext().execute_with(|| {
+		let mut ctx = Context::<Test>::default();
+		let connection_id = ConnectionId::new(0);
+		let port_id = PortId::transfer();
+		let channel_end = ChannelEnd::new(
+			State::Open,
+			Order::Unordered,
+			ChanCounterParty::new(port_id.clone(), None),
+			vec![connection_id.clone()],
+			ChanVersion::new(VERSION.to_string()),
+		);
+		for channel in 0..5 {
+			let channel_id = ChannelId::new(channel);
+			ctx.store_channel((port_id.clone(), channel_id), &channel_end).unwrap();
+			ctx.store_connection_channels(connection_id.clone(), &(port_id.clone(), channel_id))
+				.unwrap();
+		}
+
+		let all = Pallet::<Test>::channels(None, None).unwrap();
+		assert_eq!(all.channels.len(), 5);
+		assert_eq!(all.next_key, None);
+
+		let mut channels = vec![];
+		let mut next_key = None;
+		loop {
+			let page = Pallet::<Test>::channels(next_key, Some(2)).unwrap();
+			assert!(page.channels.len() <= 2);
+			channels.extend(page.channels);
+			next_key = page.next_key;
+			if next_key.is_none() {
+				break
+			}
+		}
+		assert!(channels == all.channels);
+
+		let connection_id = connection_id.as_bytes().to_vec();
+		let first =
+			Pallet::<Test>::connection_channels(connection_id.clone(), None, Some(3)).unwrap();
+		assert_eq!(first.channels.len(), 3);
+		let rest =
+			Pallet::<Test>::connection_channels(connection_id.clone(), first.next_key, Some(3))
+				.unwrap();
+		assert_eq!(rest.channels.len(), 2);
+		assert_eq!(rest.next_key, None);
+
+		// pages only start at the key of an entry
+		let unknown = Some(b"unknown".to_vec());
+		assert!(matches!(
+			Pallet::<Test>::channels(unknown.clone(), Some(2)),
+			Err(crate::Error::<Test>::InvalidPageKey)
+		));
+		assert!(matches!(
+			Pallet::<Test>::connection_channels(connection_id, unknown, Some(2)),
+			Err(crate::Error::<Test>::InvalidPageKey)
+		));
+		let channel_key = Pallet::<Test>::channels(None, Some(1)).unwrap().next_key.unwrap();
+		let mut removed = channel_key.clone();
+		removed.push(b'0');
+		assert!(matches!(
+			Pallet::<Test>::channels(Some(removed), Some(2)),
+			Err(crate::Error::<Test>::InvalidPageKey)
+		));
+		assert_eq!(Pallet::<Test>::channels(Some(channel_key), None).unwrap().channels.len(), 4);
+	})
+}
+
+fn nft_port_id() -> PortId {
+	PortId::from_str(crate::ics721::PORT_ID_STR).unwrap()
+}
+
+fn nft_transfer_params() -> TransferParams<AccountId32> {
+	TransferParams {
+		to: MultiAddress::Raw("cosmos1wxeyh7zgn4tctjzs0vtqpc6p5cxq5t2muzl7ng".as_bytes().to_vec()),
+		source_channel: 0,
+		timeout: Timeout::Offset { timestamp: Some(1000), height: Some(5) },
+	}
+}
+
+#[test]
+fn nft_transfer_escrows_items_and_refunds_them_on_timeout() {
+	use crate::ics721::NonFungibles;
+	use ibc::{
+		applications::nft_transfer::context::NftReader,
+		core::ics26_routing::context::{Module, ModuleOutputBuilder},
+	};
+
+	let mut ext = new_test_ext();
+	let sender = AccountId32::new([0; 32]);
+	ext.execute_with(|| {
+		setup_client_and_consensus_state(nft_port_id());
+		MockNonFungibles::mint_into(&7, &1, &sender).unwrap();
+
+		assert_ok!(Ics721::transfer(
+			RuntimeOrigin::signed(sender.clone()),
+			nft_transfer_params(),
+			7,
+			vec![1],
+		));
+		let escrow = NftReader::get_channel_escrow_address(
+			&Context::<Test>::default(),
+			&nft_port_id(),
+			ChannelId::new(0),
+		)
+		.unwrap()
+		.into_account();
+		assert_eq!(MockNonFungibles::owner(&7, &1), Some(escrow));
+	});
+
+	ext.persist_offchain_overlay();
+
+	ext.execute_with(|| {
+		let mut packet: Packet = Pallet::<Test>::get_send_packet_info(
+			ChannelId::new(0).to_string().as_bytes().to_vec(),
+			nft_port_id().as_bytes().to_vec(),
+			vec![1],
+		)
+		.unwrap()
+		.remove(0)
+		.into();
+		let data: ibc::applications::nft_transfer::packet::PacketData =
+			serde_json::from_slice(&packet.data).unwrap();
+		assert_eq!(data.class_id.to_string(), "7");
+		assert_eq!(data.token_ids.len(), 1);
+
+		crate::ics721::IbcModule::<Test>::default()
+			.on_timeout_packet(
+				&Context::<Test>::default(),
+				&mut ModuleOutputBuilder::new(),
+				&mut packet,
+				&Signer::from_str(MODULE_ID).unwrap(),
+			)
+			.unwrap();
+		assert_eq!(MockNonFungibles::owner(&7, &1), Some(sender));
+	})
+}
+
+#[test]
+fn nft_transfer_fails_for_items_of_others() {
+	use crate::ics721::NonFungibles;
+
+	new_test_ext().execute_with(|| {
+		setup_client_and_consensus_state(nft_port_id());
+		MockNonFungibles::mint_into(&7, &1, &AccountId32::new([1; 32])).unwrap();
+
+		assert_noop!(
+			Ics721::transfer(
+				RuntimeOrigin::signed(AccountId32::new([0; 32])),
+				nft_transfer_params(),
+				7,
+				vec![1],
+			),
+			crate::ics721::Error::<Test>::TransferFailed
+		);
+		assert_noop!(
+			Ics721::transfer(
+				RuntimeOrigin::signed(AccountId32::new([0; 32])),
+				nft_transfer_params(),
+				7,
+				vec![],
+			),
+			crate::ics721::Error::<Test>::EmptyTransfer
+		);
+	})
+}
+
+#[test]
+fn nft_vouchers_are_minted_into_a_collection_per_class() {
+	use crate::ics721::NonFungibles;
+	use ibc::{
+		applications::nft_transfer::{packet::PacketData, PrefixedClassId, TokenId},
+		core::ics26_routing::context::{Module, ModuleOutputBuilder},
+	};
+
+	new_test_ext().execute_with(|| {
+		setup_client_and_consensus_state(nft_port_id());
+		let receiver = AccountId32::new([1; 32]);
+		let data = PacketData {
+			class_id: PrefixedClassId::from_str("kitties").unwrap(),
+			class_uri: Some("https://kitties.example".to_string()),
+			class_data: None,
+			token_ids: vec![TokenId::from_str("kitty-1").unwrap()],
+			token_uris: vec![],
+			token_data: vec![],
+			sender: Signer::from_str("cosmos1wxeyh7zgn4tctjzs0vtqpc6p5cxq5t2muzl7ng").unwrap(),
+			receiver: crate::signer::<Test>(receiver.clone()),
+			memo: String::new(),
+		};
+		let mut packet = Packet {
+			sequence: 1.into(),
+			source_port: nft_port_id(),
+			source_channel: ChannelId::new(1),
+			destination_port: nft_port_id(),
+			destination_channel: ChannelId::new(0),
+			data: serde_json::to_vec(&data).unwrap(),
+			timeout_height: Height::new(0, 100),
+			timeout_timestamp: Default::default(),
+		};
+
+		let ack = crate::ics721::IbcModule::<Test>::default()
+			.on_recv_packet(
+				&Context::<Test>::default(),
+				&mut ModuleOutputBuilder::new(),
+				&mut packet,
+				&Signer::from_str(MODULE_ID).unwrap(),
+			)
+			.unwrap();
+		let ack: Ics20Acknowledgement = serde_json::from_slice(ack.as_ref()).unwrap();
+		assert!(ack.is_successful());
+
+		let class_id = b"nft-transfer/channel-0/kitties".to_vec();
+		let collection = crate::ics721::VoucherClasses::<Test>::get(&class_id).unwrap();
+		let item =
+			crate::ics721::VoucherItems::<Test>::get(collection, b"kitty-1".to_vec()).unwrap();
+		assert_eq!(MockNonFungibles::owner(&collection, &item), Some(receiver));
+		assert_eq!(crate::ics721::VoucherClassIds::<Test>::get(collection), Some(class_id));
+		assert_eq!(
+			crate::ics721::ClassMetadata::<Test>::get(collection),
+			Some((Some(b"https://kitties.example".to_vec()), None))
+		);
+	})
+}
+
+fn ica_message() -> crate::ics27::IcaMessage {
+	crate::ics27::IcaMessage { type_url: ICA_ALLOWED_MESSAGE.as_bytes().to_vec(), value: vec![1] }
+}
+
+/// Registers the interchain account of `owner` on connection-0 and opens its channel the way
+/// the relayer would, returns the controller port and the channel.
+fn open_interchain_account(owner: AccountId32) -> (PortId, ChannelId) {
+	use ibc::{
+		applications::interchain_accounts::{controller_port_id, metadata::Metadata},
+		core::ics26_routing::context::{Module, ModuleOutputBuilder},
+	};
+
+	let mut ctx = Context::<Test>::default();
+	let channel_id = ChannelId::new(ctx.channel_counter().unwrap());
+	assert_ok!(Ics27::register_interchain_account(
+		RuntimeOrigin::signed(owner.clone()),
+		b"connection-0".to_vec(),
+		vec![],
+	));
+	let port_id = controller_port_id(&crate::signer::<Test>(owner)).unwrap();
+
+	let mut metadata = Metadata::new(&ConnectionId::new(0), &ConnectionId::new(1));
+	metadata.address = "cosmos1wxeyh7zgn4tctjzs0vtqpc6p5cxq5t2muzl7ng".to_string();
+	let version: ChanVersion = metadata.into();
+	crate::ics27::ControllerModule::<Test>::default()
+		.on_chan_open_ack(
+			&ctx,
+			&mut ModuleOutputBuilder::new(),
+			&port_id,
+			&channel_id,
+			&version,
+			&Signer::from_str(MODULE_ID).unwrap(),
+		)
+		.unwrap();
+	let mut channel_end = ctx.channel_end(&(port_id.clone(), channel_id)).unwrap();
+	channel_end.set_state(State::Open);
+	channel_end.set_counterparty_channel_id(ChannelId::new(1));
+	ctx.store_channel((port_id.clone(), channel_id), &channel_end).unwrap();
+	(port_id, channel_id)
+}
+
+#[test]
+fn register_interchain_account_opens_a_channel_on_the_controller_port() {
+	use ibc::applications::interchain_accounts::{controller_port_id, metadata::Metadata};
+
+	new_test_ext().execute_with(|| {
+		setup_client_and_consensus_state(PortId::transfer());
+		let owner = AccountId32::new([0; 32]);
+		let ctx = Context::<Test>::default();
+		let channel_id = ChannelId::new(ctx.channel_counter().unwrap());
+
+		assert_noop!(
+			Ics27::register_interchain_account(
+				RuntimeOrigin::signed(owner.clone()),
+				vec![0xff],
+				vec![],
+			),
+			crate::ics27::Error::<Test>::InvalidIdentifier
+		);
+		assert_ok!(Ics27::register_interchain_account(
+			RuntimeOrigin::signed(owner.clone()),
+			b"connection-0".to_vec(),
+			vec![],
+		));
+
+		let port_id = controller_port_id(&crate::signer::<Test>(owner)).unwrap();
+		let channel_end = ctx.channel_end(&(port_id, channel_id)).unwrap();
+		assert_eq!(channel_end.state(), &State::Init);
+		assert_eq!(channel_end.ordering(), &Order::Ordered);
+		assert_eq!(
+			Metadata::from_version(channel_end.version()).unwrap(),
+			Metadata::new(&ConnectionId::new(0), &ConnectionId::new(1))
+		);
+	})
+}
+
+#[test]
+fn register_interchain_account_fails_while_a_channel_is_active() {
+	new_test_ext().execute_with(|| {
+		setup_client_and_consensus_state(PortId::transfer());
+		let owner = AccountId32::new([0; 32]);
+		let (port_id, channel_id) = open_interchain_account(owner.clone());
+		assert_eq!(
+			crate::ics27::ControllerActiveChannels::<Test>::get(
+				b"connection-0".to_vec(),
+				port_id.as_bytes().to_vec()
+			),
+			Some(channel_id.to_string().into_bytes())
+		);
+		assert_eq!(
+			crate::ics27::ControllerAccounts::<Test>::get(
+				b"connection-0".to_vec(),
+				port_id.as_bytes().to_vec()
+			),
+			Some(b"cosmos1wxeyh7zgn4tctjzs0vtqpc6p5cxq5t2muzl7ng".to_vec())
+		);
+
+		assert_noop!(
+			Ics27::register_interchain_account(
+				RuntimeOrigin::signed(owner),
+				b"connection-0".to_vec(),
+				vec![],
+			),
+			crate::ics27::Error::<Test>::Controller
+		);
+	})
+}
+
+#[test]
+fn send_tx_sends_on_the_active_channel() {
+	new_test_ext().execute_with(|| {
+		setup_client_and_consensus_state(PortId::transfer());
+		let owner = AccountId32::new([0; 32]);
+		let (port_id, channel_id) = open_interchain_account(owner.clone());
+		let ctx = Context::<Test>::default();
+		let sequence = ctx.get_next_sequence_send(&(port_id.clone(), channel_id)).unwrap();
+
+		assert_ok!(Ics27::send_tx(
+			RuntimeOrigin::signed(owner.clone()),
+			b"connection-0".to_vec(),
+			vec![ica_message()],
+			b"memo".to_vec(),
+			60_000_000_000,
+		));
+		assert_eq!(
+			ctx.get_next_sequence_send(&(port_id, channel_id)).unwrap(),
+			sequence.increment()
+		);
+
+		assert_noop!(
+			Ics27::send_tx(
+				RuntimeOrigin::signed(owner.clone()),
+				b"connection-0".to_vec(),
+				vec![ica_message()],
+				vec![],
+				0,
+			),
+			crate::ics27::Error::<Test>::Controller
+		);
+		assert_noop!(
+			Ics27::send_tx(
+				RuntimeOrigin::signed(owner),
+				b"connection-0".to_vec(),
+				vec![],
+				vec![],
+				60_000_000_000,
+			),
+			crate::ics27::Error::<Test>::Controller
+		);
+		// no interchain account was registered for this owner
+		assert_noop!(
+			Ics27::send_tx(
+				RuntimeOrigin::signed(AccountId32::new([1; 32])),
+				b"connection-0".to_vec(),
+				vec![ica_message()],
+				vec![],
+				60_000_000_000,
+			),
+			crate::ics27::Error::<Test>::Controller
+		);
+	})
+}
+
+#[test]
+fn host_registers_the_interchain_accounts_of_controller_ports() {
+	use ibc::{
+		applications::interchain_accounts::{host_port_id, metadata::Metadata},
+		core::ics26_routing::context::{Module, ModuleOutputBuilder},
+	};
+
+	new_test_ext().execute_with(|| {
+		setup_client_and_consensus_state(PortId::transfer());
+		let controller_port_id = PortId::from_str("icacontroller-cosmos1owner").unwrap();
+		let counterparty =
+			ChanCounterParty::new(controller_port_id.clone(), Some(ChannelId::new(1)));
+		let counterparty_version: ChanVersion =
+			Metadata::new(&ConnectionId::new(1), &ConnectionId::new(0)).into();
+		let open_try = |order| {
+			crate::ics27::HostModule::<Test>::default().on_chan_open_try(
+				&Context::<Test>::default(),
+				&mut ModuleOutputBuilder::new(),
+				order,
+				&[ConnectionId::new(0)],
+				&host_port_id(),
+				&ChannelId::new(1),
+				&counterparty,
+				&ChanVersion::empty(),
+				&counterparty_version,
+				&Signer::from_str(MODULE_ID).unwrap(),
+			)
+		};
+
+		assert!(open_try(Order::Unordered).is_err());
+		let version = open_try(Order::Ordered).unwrap();
+		let account = crate::ics27::HostAccounts::<Test>::get(
+			b"connection-0".to_vec(),
+			controller_port_id.as_bytes().to_vec(),
+		)
+		.unwrap();
+		assert_eq!(
+			Metadata::from_version(&version).unwrap().address,
+			crate::signer::<Test>(account.clone()).to_string()
+		);
+		// the account is derived once per connection and controller port
+		open_try(Order::Ordered).unwrap();
+		assert_eq!(
+			crate::ics27::HostAccounts::<Test>::get(
+				b"connection-0".to_vec(),
+				controller_port_id.as_bytes().to_vec(),
+			),
+			Some(account)
+		);
+	})
+}
+
+#[test]
+fn consensus_states_piled_up_are_pruned_by_the_admin_call() {
+	new_test_ext().execute_with(|| {
+		let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+		let mut ctx = Context::<Test>::default();
+		let mock_cs_state = MockConsensusState::new(MockHeader::default());
+		let max_count = MaxConsensusStatesPerClient::get() as u64;
+		let piled_up = max_count + crate::MAX_CONSENSUS_STATES_PRUNED_PER_UPDATE as u64 + 1;
+		// consensus states stored before pruning was enabled
+		for i in 1..=piled_up {
+			ctx.store_update_time(client_id.clone(), Height::new(0, i), ctx.host_timestamp())
+				.unwrap();
+		}
+		let update = |ctx: &mut Context<Test>, height: Height| {
+			ctx.store_client_state(
+				client_id.clone(),
+				AnyClientState::Mock(MockClientState::new(MockClientMessage::from(
+					MockHeader::new(height),
+				))),
+			)
+			.unwrap();
+			ctx.store_consensus_state(
+				client_id.clone(),
+				height,
+				AnyConsensusState::Mock(mock_cs_state.clone()),
+			)
+			.unwrap();
+			ctx.store_update_time(client_id.clone(), height, ctx.host_timestamp()).unwrap();
+		};
+
+		// too many consensus states for an update to prune them
+		update(&mut ctx, Height::new(0, piled_up + 1));
+		assert!(ctx.client_update_time(&client_id, Height::new(0, 1)).is_ok());
+
+		assert_noop!(
+			Ibc::prune_consensus_states(
+				RuntimeOrigin::signed(AccountId32::new([0; 32])),
+				client_id.to_string(),
+				piled_up as u32 + 1,
+				2,
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Ibc::prune_consensus_states(
+				RuntimeOrigin::root(),
+				client_id.to_string(),
+				piled_up as u32,
+				2,
+			),
+			crate::Error::<Test>::TooManyConsensusStates
+		);
+		assert_ok!(Ibc::prune_consensus_states(
+			RuntimeOrigin::root(),
+			client_id.to_string(),
+			piled_up as u32 + 1,
+			2,
+		));
+		assert!(ctx.client_update_time(&client_id, Height::new(0, 2)).is_err());
+		assert!(ctx.client_update_time(&client_id, Height::new(0, 3)).is_ok());
+
+		// updates prune the oldest consensus states again, a few at a time
+		update(&mut ctx, Height::new(0, piled_up + 2));
+		let pruned = 2 + crate::MAX_CONSENSUS_STATES_PRUNED_PER_UPDATE as u64;
+		assert!(ctx.client_update_time(&client_id, Height::new(0, pruned)).is_err());
+		assert!(ctx.client_update_time(&client_id, Height::new(0, pruned + 1)).is_ok());
+		assert!(ctx.client_update_time(&client_id, Height::new(0, piled_up + 2)).is_ok());
+	})
+}
@@ -1,7 +1,9 @@
 use super::*;
 use crate::{light_clients::AnyClientMessage, routing::Context};
 use core::marker::PhantomData;
-use frame_support::{pallet_prelude::Weight, weights::constants::WEIGHT_REF_TIME_PER_MILLIS};
+use frame_support::{
+	pallet_prelude::Weight, traits::Get, weights::constants::WEIGHT_REF_TIME_PER_MILLIS,
+};
 use grandpa_client_primitives::justification::GrandpaJustification;
 use ibc::core::{
 	ics02_client::msgs::ClientMsg,
@@ -180,6 +182,16 @@ pub fn channel_client<T: Config>(channel_id: &[u8], port_id: &[u8]) -> Result<Cl
 	Err(Error::<T>::Other)
 }
 
+/// Weight of pruning at most `max_pruned` consensus states of a client holding at most
+/// `max_read` of them.
+pub(crate) fn prune_consensus_states<T: Config>(max_read: u32, max_pruned: u32) -> Weight {
+	// the update times and the client state are read, then every pruned consensus state is
+	// removed along with its update time, update height and entry in the consensus heights
+	let reads = max_read as u64 + 1 + max_pruned as u64;
+	let writes = 4 * max_pruned as u64;
+	T::DbWeight::get().reads_writes(reads, writes)
+}
+
 pub(crate) fn deliver<T: Config + Send + Sync>(msgs: &[Any]) -> Weight
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
@@ -192,6 +204,19 @@ where
 			msg
 		})
 		.fold(Weight::default(), |acc, msg| {
+			// Storing a consensus state prunes the oldest ones of the client
+			let pruning = match &msg {
+				Ics26Envelope::Ics2Msg(
+					ClientMsg::CreateClient(_) |
+					ClientMsg::UpdateClient(_) |
+					ClientMsg::UpgradeClient(_),
+				) => prune_consensus_states::<T>(
+					T::MaxConsensusStatesPerClient::get()
+						.saturating_add(MAX_CONSENSUS_STATES_PRUNED_PER_UPDATE),
+					MAX_CONSENSUS_STATES_PRUNED_PER_UPDATE,
+				),
+				_ => Weight::default(),
+			};
 			// Add benchmarked weight for that message type
 			// Add benchmarked weight for module callback
 			let temp = match msg {
@@ -258,6 +283,7 @@ where
 						}
 					},
 					ClientMsg::UpgradeClient(_) => Weight::default(),
+					ClientMsg::Misbehaviour(_) => Weight::default(),
 				},
 				Ics26Envelope::Ics3Msg(msgs) => match msgs {
 					ConnectionMsg::ConnectionOpenInit(_) =>
@@ -570,6 +596,6 @@ where
 				},
 				_ => Weight::default(),
 			};
-			acc.saturating_add(temp)
+			acc.saturating_add(temp).saturating_add(pruning)
 		})
 }
@@ -0,0 +1,74 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `cargo bench -p hyperspace-bench [scenario]`
+//!
+//! - `HYPERSPACE_BENCH_SAVE=<path>` writes the reports as the new baseline.
+//! - `HYPERSPACE_BENCH_BASELINE=<path>` fails if a report regressed from the baseline by more than
+//!   `HYPERSPACE_BENCH_TOLERANCE`, 0.2 by default.
+
+use anyhow::anyhow;
+use hyperspace_bench::{run, Report, Scenario};
+use std::collections::BTreeMap;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+	env_logger::init();
+	// cargo passes `--bench` along with the filter
+	let filter = std::env::args().skip(1).find(|arg| !arg.starts_with("--"));
+	let baseline = match std::env::var("HYPERSPACE_BENCH_BASELINE") {
+		Ok(path) =>
+			Some(serde_json::from_str::<BTreeMap<String, Report>>(&std::fs::read_to_string(path)?)?),
+		Err(_) => None,
+	};
+	let tolerance = match std::env::var("HYPERSPACE_BENCH_TOLERANCE") {
+		Ok(tolerance) => tolerance.parse::<f64>()?,
+		Err(_) => 0.2,
+	};
+
+	let mut reports = BTreeMap::new();
+	let mut regressions = vec![];
+	for scenario in Scenario::defaults() {
+		if filter.as_ref().map_or(false, |filter| !scenario.name.contains(filter.as_str())) {
+			continue
+		}
+		let report = run(&scenario).await?;
+		println!(
+			"{:<16} {:>5} packets in {:>9.2?}: {:>8.2?}/packet, p50 {:>9.2?}, p95 {:>9.2?}, {:>6.1} queries/cycle over {} cycles",
+			report.scenario,
+			report.packets,
+			report.total,
+			report.per_packet(),
+			report.latency_p50,
+			report.latency_p95,
+			report.queries_per_cycle,
+			report.cycles,
+		);
+		if let Some(baseline) = baseline.as_ref().and_then(|baseline| baseline.get(scenario.name)) {
+			regressions.extend(report.regressions(baseline, tolerance));
+		}
+		reports.insert(report.scenario.clone(), report);
+	}
+
+	if let Ok(path) = std::env::var("HYPERSPACE_BENCH_SAVE") {
+		std::fs::write(path, serde_json::to_string_pretty(&reports)?)?;
+	}
+	if !regressions.is_empty() {
+		for regression in &regressions {
+			println!("regression: {regression}");
+		}
+		return Err(anyhow!("{} regressions from the baseline", regressions.len()))
+	}
+	Ok(())
+}
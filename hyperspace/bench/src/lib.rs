@@ -0,0 +1,207 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runs the relay loop between two mock chains with a synthetic load of packets and measures how
+//! long every packet takes to be received and acknowledged, and how many queries the relayer makes
+//! per finality event. Reports are compared to a baseline so that regressions fail the benchmark.
+
+use anyhow::anyhow;
+use hyperspace_core::shutdown::Shutdown;
+use mock::{MockChain, MockConfig};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{BTreeMap, BTreeSet, HashSet},
+	time::{Duration, Instant},
+};
+
+/// How often the driver checks which packets were acknowledged.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+#[derive(Debug, Clone)]
+pub struct Scenario {
+	pub name: &'static str,
+	/// Packets sent from chain A to chain B.
+	pub packets: usize,
+	/// Number of blocks the packets are spread over.
+	pub blocks: usize,
+	pub block_time: Duration,
+	/// Size in bytes of the proofs returned by the mock chains.
+	pub proof_size: usize,
+	/// The scenario fails if the packets aren't all acknowledged by then.
+	pub timeout: Duration,
+}
+
+impl Scenario {
+	/// The scenarios run by `cargo bench -p hyperspace-bench`.
+	pub fn defaults() -> Vec<Scenario> {
+		let scenario = |name, packets, blocks| Scenario {
+			name,
+			packets,
+			blocks,
+			block_time: Duration::from_millis(50),
+			proof_size: 512,
+			timeout: Duration::from_secs(120),
+		};
+		vec![
+			scenario("single-packet", 1, 1),
+			scenario("burst-100", 100, 1),
+			scenario("steady-1000", 1000, 20),
+		]
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+	pub scenario: String,
+	pub packets: usize,
+	/// Time between the first packet being sent and the last one being acknowledged.
+	pub total: Duration,
+	/// Time between a packet being sent and acknowledged, median and 95th percentile.
+	pub latency_p50: Duration,
+	pub latency_p95: Duration,
+	/// Finality events processed by the relayer, on both chains.
+	pub cycles: u64,
+	pub queries_per_cycle: f64,
+	/// Queries made to both chains, by method.
+	pub queries: BTreeMap<String, u64>,
+}
+
+impl Report {
+	pub fn per_packet(&self) -> Duration {
+		self.total / self.packets.max(1) as u32
+	}
+
+	/// The ways `self` is worse than `baseline` by more than `tolerance`, a fraction of the
+	/// baseline.
+	pub fn regressions(&self, baseline: &Report, tolerance: f64) -> Vec<String> {
+		let mut regressions = vec![];
+		let limit = 1.0 + tolerance;
+		let mut check = |metric: &str, value: f64, baseline: f64| {
+			if value > baseline * limit {
+				regressions.push(format!(
+					"{}: {metric} went from {baseline:.2} to {value:.2}",
+					self.scenario
+				));
+			}
+		};
+		check(
+			"per-packet latency (ms)",
+			self.per_packet().as_secs_f64() * 1000.0,
+			baseline.per_packet().as_secs_f64() * 1000.0,
+		);
+		check(
+			"p95 latency (ms)",
+			self.latency_p95.as_secs_f64() * 1000.0,
+			baseline.latency_p95.as_secs_f64() * 1000.0,
+		);
+		check("queries per cycle", self.queries_per_cycle, baseline.queries_per_cycle);
+		regressions
+	}
+}
+
+/// Relays the packets of `scenario` from a mock chain A to a mock chain B until they are all
+/// acknowledged on A.
+pub async fn run(scenario: &Scenario) -> anyhow::Result<Report> {
+	let config = |name: &str, revision| MockConfig {
+		block_time: scenario.block_time,
+		proof_size: scenario.proof_size,
+		..MockConfig::new(name, revision)
+	};
+	let (chain_a, chain_b) = MockChain::pair(config("mock-a", 2000), config("mock-b", 2001));
+
+	let relay = tokio::spawn(hyperspace_core::relay(
+		chain_a.clone(),
+		chain_b.clone(),
+		None,
+		None,
+		None,
+		Shutdown::default(),
+	));
+	let blocks_a = chain_a.spawn_block_production();
+	let blocks_b = chain_b.spawn_block_production();
+
+	let result = drive(scenario, &chain_a).await;
+	relay.abort();
+	blocks_a.abort();
+	blocks_b.abort();
+	let (total, mut latencies) = result?;
+
+	let mut queries = chain_a.query_counts().snapshot();
+	for (method, count) in chain_b.query_counts().snapshot() {
+		*queries.entry(method).or_default() += count;
+	}
+	// every finality event starts with fetching the events of the source
+	let cycles = queries.get("query_latest_ibc_events").copied().unwrap_or_default();
+	let total_queries = queries.values().sum::<u64>();
+
+	latencies.sort();
+	let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+	Ok(Report {
+		scenario: scenario.name.to_string(),
+		packets: scenario.packets,
+		total,
+		latency_p50: percentile(50),
+		latency_p95: percentile(95),
+		cycles,
+		queries_per_cycle: total_queries as f64 / cycles.max(1) as f64,
+		queries: queries.into_iter().map(|(method, count)| (method.to_string(), count)).collect(),
+	})
+}
+
+/// Sends the packets of the scenario on `chain_a`, returns the total time and the latency of every
+/// packet.
+async fn drive(
+	scenario: &Scenario,
+	chain_a: &MockChain,
+) -> anyhow::Result<(Duration, Vec<Duration>)> {
+	let start = Instant::now();
+	let per_block = (scenario.packets / scenario.blocks.max(1)).max(1);
+	let mut sent_at = BTreeMap::new();
+	let mut in_flight = BTreeSet::new();
+	let mut latencies = Vec::with_capacity(scenario.packets);
+	let mut next_send = start;
+
+	while sent_at.len() < scenario.packets || !in_flight.is_empty() {
+		let now = Instant::now();
+		if sent_at.len() < scenario.packets && now >= next_send {
+			let count = per_block.min(scenario.packets - sent_at.len());
+			for sequence in chain_a.send_transfers(count) {
+				sent_at.insert(sequence, now);
+				in_flight.insert(sequence);
+			}
+			next_send = now + scenario.block_time;
+		}
+
+		tokio::time::sleep(POLL_INTERVAL).await;
+		let pending = chain_a.pending_packets().into_iter().collect::<HashSet<_>>();
+		let now = Instant::now();
+		in_flight.retain(|sequence| {
+			if pending.contains(sequence) {
+				return true
+			}
+			latencies.push(now - sent_at[sequence]);
+			false
+		});
+
+		if start.elapsed() > scenario.timeout {
+			return Err(anyhow!(
+				"{}: {} packets still not acknowledged after {:?}",
+				scenario.name,
+				in_flight.len() + scenario.packets - sent_at.len(),
+				scenario.timeout
+			))
+		}
+	}
+	Ok((start.elapsed(), latencies))
+}
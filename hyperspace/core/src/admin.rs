@@ -0,0 +1,193 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HTTP control API of a running relayer.
+//!
+//! `POST /chains/<name>/signing-key` swaps the key a chain signs its messages with, taking either
+//! `{"private_key": "..."}` or `{"keystore": "<path>", "passphrase": "..."}`. The chain clients
+//! share their signing state with the relay loop, so the new key is picked up by the next
+//! submission without reconnecting. Rotated keys aren't written back to the config file.
+//!
+//! Requests must carry the `admin_token` of the core config as a bearer token. The API is only
+//! served without one on a loopback address.
+
+use crate::chain::AnyChain;
+use anyhow::anyhow;
+use hyper::{
+	header::AUTHORIZATION,
+	server::conn::AddrIncoming,
+	service::{make_service_fn, service_fn},
+	Body, Method, Request, Response, Server, StatusCode,
+};
+use primitives::{keystore::EncryptedKeystore, Chain};
+use serde::Deserialize;
+use std::{convert::Infallible, net::SocketAddr, path::PathBuf, sync::Arc};
+
+#[derive(Deserialize)]
+struct RotateKeyRequest {
+	#[serde(default)]
+	private_key: Option<String>,
+	#[serde(default)]
+	keystore: Option<PathBuf>,
+	#[serde(default)]
+	passphrase: Option<String>,
+}
+
+impl RotateKeyRequest {
+	fn into_secret(self) -> Result<String, String> {
+		match (self.private_key, self.keystore) {
+			(Some(private_key), None) => Ok(private_key),
+			(None, Some(path)) => EncryptedKeystore::load(&path)
+				.and_then(|keystore| keystore.decrypt(&self.passphrase.unwrap_or_default()))
+				.map_err(|e| e.to_string()),
+			_ => Err("Exactly one of `private_key` and `keystore` must be given".to_string()),
+		}
+	}
+}
+
+struct AdminState {
+	chains: Vec<AnyChain>,
+	token: Option<String>,
+}
+
+/// Parses the address the control API is served on, and checks that it requires a token unless
+/// it's only reachable from this host.
+pub fn admin_api_address(endpoint: &str, token: Option<&str>) -> anyhow::Result<SocketAddr> {
+	let addr = endpoint
+		.parse::<SocketAddr>()
+		.map_err(|e| anyhow!("Invalid admin_endpoint {endpoint}: {e}"))?;
+	if token.is_none() && !addr.ip().is_loopback() {
+		return Err(anyhow!(
+			"The admin API on {addr} would be reachable without authentication, set `admin_token` or serve it on a loopback address"
+		))
+	}
+	Ok(addr)
+}
+
+/// Serves the control API for the given chains until the server fails.
+pub async fn serve_admin_api(
+	addr: SocketAddr,
+	token: Option<String>,
+	chains: Vec<AnyChain>,
+) -> anyhow::Result<()> {
+	admin_api_address(&addr.to_string(), token.as_deref())?;
+	if token.is_none() {
+		log::warn!(target: "hyperspace", "The admin API on {addr} is served without authentication, set `admin_token` to require one");
+	}
+	let listener = tokio::net::TcpListener::bind(&addr).await?;
+	let state = Arc::new(AdminState { chains, token });
+	let service = make_service_fn(move |_| {
+		let state = state.clone();
+		async move { Ok::<_, Infallible>(service_fn(move |req| handle_request(req, state.clone()))) }
+	});
+	log::info!(target: "hyperspace", "Serving the admin API on {addr}");
+	Server::builder(AddrIncoming::from_listener(listener)?).serve(service).await?;
+	Ok(())
+}
+
+async fn handle_request(
+	req: Request<Body>,
+	state: Arc<AdminState>,
+) -> Result<Response<Body>, Infallible> {
+	if let Some(token) = &state.token {
+		let authorized = req
+			.headers()
+			.get(AUTHORIZATION)
+			.and_then(|value| value.to_str().ok())
+			.and_then(|value| value.strip_prefix("Bearer "))
+			.map_or(false, |given| tokens_match(given.as_bytes(), token.as_bytes()));
+		if !authorized {
+			return Ok(respond(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token"))
+		}
+	}
+
+	let segments = req.uri().path().trim_matches('/').split('/').collect::<Vec<_>>();
+	let response = match (req.method(), segments.as_slice()) {
+		(&Method::POST, ["chains", name, "signing-key"]) => {
+			let name = name.to_string();
+			rotate_signing_key(&state, &name, req.into_body()).await
+		},
+		_ => respond(StatusCode::NOT_FOUND, "Not found"),
+	};
+	Ok(response)
+}
+
+async fn rotate_signing_key(state: &AdminState, name: &str, body: Body) -> Response<Body> {
+	let Some(chain) = state.chains.iter().find(|chain| chain.name() == name) else {
+		return respond(StatusCode::NOT_FOUND, &format!("Unknown chain {name}"))
+	};
+	let secret = match hyper::body::to_bytes(body)
+		.await
+		.map_err(|e| e.to_string())
+		.and_then(|body| {
+			serde_json::from_slice::<RotateKeyRequest>(&body).map_err(|e| e.to_string())
+		})
+		.and_then(RotateKeyRequest::into_secret)
+	{
+		Ok(secret) => secret,
+		Err(e) => return respond(StatusCode::BAD_REQUEST, &e),
+	};
+	match chain.rotate_signing_key(secret).await {
+		Ok(account) => {
+			log::info!(target: "hyperspace", "Rotated the signing key of {name}, relayer account is now {account}");
+			Response::new(Body::from(
+				serde_json::json!({ "account": account.to_string() }).to_string(),
+			))
+		},
+		Err(e) => respond(StatusCode::UNPROCESSABLE_ENTITY, &e.to_string()),
+	}
+}
+
+/// Compares the tokens in a time that doesn't depend on where they differ, only their length
+/// leaks.
+fn tokens_match(given: &[u8], expected: &[u8]) -> bool {
+	given.len() == expected.len() &&
+		given
+			.iter()
+			.zip(expected)
+			.fold(0u8, |diff, (given, expected)| diff | (given ^ expected)) ==
+			0
+}
+
+fn respond(status: StatusCode, message: &str) -> Response<Body> {
+	let mut response =
+		Response::new(Body::from(serde_json::json!({ "error": message }).to_string()));
+	*response.status_mut() = status;
+	response
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn tokens_are_compared_in_full() {
+		assert!(tokens_match(b"secret", b"secret"));
+		assert!(!tokens_match(b"secreT", b"secret"));
+		assert!(!tokens_match(b"Secret", b"secret"));
+		assert!(!tokens_match(b"secret", b"secret-token"));
+		assert!(!tokens_match(b"", b"secret"));
+	}
+
+	#[test]
+	fn api_without_token_is_only_served_on_loopback() {
+		assert!(admin_api_address("127.0.0.1:8090", None).is_ok());
+		assert!(admin_api_address("[::1]:8090", None).is_ok());
+		assert!(admin_api_address("0.0.0.0:8090", None).is_err());
+		assert!(admin_api_address("10.0.0.1:8090", None).is_err());
+		assert!(admin_api_address("0.0.0.0:8090", Some("secret")).is_ok());
+		assert!(admin_api_address("localhost:8090", Some("secret")).is_err());
+		assert!(admin_api_address("127.0.0.1", Some("secret")).is_err());
+	}
+}
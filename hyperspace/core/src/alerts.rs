@@ -0,0 +1,50 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Alerts about conditions the relayer can't resolve on its own and that need an operator, e.g. an
+//! ordered channel that stopped making progress. Alerts are logged and, when a webhook is
+//! configured, posted to it as JSON: `{"alert": "<kind>", "chain": "<name>", "message": "..."}`.
+
+use anyhow::anyhow;
+use hyper::{Body, Client, Request};
+use std::sync::Mutex;
+
+static WEBHOOK: Mutex<Option<String>> = Mutex::new(None);
+
+/// Sets the http endpoint alerts are posted to, `None` only logs them.
+pub fn set_webhook(url: Option<String>) {
+	*WEBHOOK.lock().unwrap() = url;
+}
+
+/// Raises an alert of the given kind about `chain`. Failing to post it to the webhook is only
+/// logged, alerts never interrupt the relay.
+pub async fn raise(kind: &str, chain: &str, message: String) {
+	log::error!(target: "hyperspace", "[alert: {kind}] {chain}: {message}");
+	let Some(url) = WEBHOOK.lock().unwrap().clone() else { return };
+	let body = serde_json::json!({ "alert": kind, "chain": chain, "message": message });
+	if let Err(e) = post(&url, body.to_string()).await {
+		log::warn!(target: "hyperspace", "Failed to post {kind} alert to the webhook: {e:?}");
+	}
+}
+
+async fn post(url: &str, body: String) -> Result<(), anyhow::Error> {
+	let request = Request::post(url)
+		.header("content-type", "application/json")
+		.body(Body::from(body))?;
+	let response = Client::new().request(request).await?;
+	if !response.status().is_success() {
+		return Err(anyhow!("webhook responded with {}", response.status()))
+	}
+	Ok(())
+}
@@ -14,6 +14,8 @@
 
 #![allow(unreachable_patterns)]
 
+#[cfg(feature = "chaos")]
+use crate::chaos::{ChaosChain, ChaosConfig};
 use crate::{
 	chains,
 	substrate::{
@@ -27,7 +29,9 @@ use futures::Stream;
 #[cfg(any(test, feature = "testing"))]
 use ibc::applications::transfer::msgs::transfer::MsgTransfer;
 use ibc::{
-	applications::transfer::PrefixedCoin,
+	applications::{
+		cross_chain_queries::query::CrossChainQuery, fee::PacketFee, transfer::PrefixedCoin,
+	},
 	core::{
 		ics02_client::{
 			client_state::ClientType,
@@ -38,7 +42,10 @@ use ibc::{
 			conn_open_ack::MsgConnectionOpenAck, conn_open_try::MsgConnectionOpenTry,
 		},
 		ics23_commitment::commitment::CommitmentPrefix,
-		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		ics24_host::{
+			identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
+			Path,
+		},
 	},
 	downcast,
 	events::IbcEvent,
@@ -49,24 +56,35 @@ use ibc::{
 };
 use ibc_proto::{
 	google::protobuf::Any,
-	ibc::core::{
-		channel::v1::{
-			QueryChannelResponse, QueryChannelsResponse, QueryNextSequenceReceiveResponse,
-			QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
-			QueryPacketReceiptResponse,
+	ibc::{
+		applications::transfer::v1::{QueryDenomTraceResponse, QueryDenomTracesResponse},
+		core::{
+			channel::v1::{
+				QueryChannelResponse, QueryChannelsResponse, QueryNextSequenceReceiveResponse,
+				QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
+				QueryPacketReceiptResponse,
+			},
+			client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
+			connection::v1::{
+				IdentifiedConnection, Params as ConnectionParams, QueryConnectionResponse,
+				QueryConnectionsResponse,
+			},
 		},
-		client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
-		connection::v1::{IdentifiedConnection, QueryConnectionResponse},
 	},
 };
 use ics08_wasm::Bytes;
+#[cfg(feature = "mock")]
+use mock::{MockChain, MockClientConfig};
 use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState};
 #[cfg(any(test, feature = "testing"))]
 use pallet_ibc::Timeout;
-use parachain::{ParachainClient, ParachainClientConfig};
+use parachain::{admin::AdminCall, ParachainClient, ParachainClientConfig};
 use primitives::{
-	mock::LocalClientTypes, Chain, CommonClientState, IbcProvider, KeyProvider, LightClientSync,
-	MisbehaviourHandler, UpdateType,
+	cost::{TxBudget, TxCost},
+	mock::LocalClientTypes,
+	strategy::ChannelStrategy,
+	Chain, CommonClientState, IbcEventProvider, IbcQueryProvider, KeyProvider, LightClientSync,
+	MisbehaviourHandler, Page, ProofProvider, UpdateType,
 };
 use serde::{Deserialize, Serialize};
 use std::{pin::Pin, time::Duration};
@@ -80,9 +98,78 @@ pub struct Config {
 	pub core: CoreConfig,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Default, Serialize, Deserialize)]
 pub struct CoreConfig {
 	pub prometheus_endpoint: Option<String>,
+	/// Path where messages that couldn't be submitted before the relayer stopped are persisted,
+	/// they're resubmitted on the next start.
+	pub pending_messages_path: Option<String>,
+	/// Address of the control API, e.g. `127.0.0.1:8090`. See [`crate::admin`].
+	#[serde(default)]
+	pub admin_endpoint: Option<String>,
+	/// Bearer token required by the control API
+	#[serde(default)]
+	pub admin_token: Option<String>,
+	/// Maximum level of the relayer logs, e.g. `warn`. Applied again when the configs are
+	/// reloaded, see [`crate::reload`].
+	#[serde(default)]
+	pub log_level: Option<String>,
+	/// Channels that aren't relayed with the default strategy
+	#[serde(default)]
+	pub channel_strategies: Vec<ChannelStrategyConfig>,
+	/// Http endpoint the alerts are posted to, see [`crate::alerts`]
+	#[serde(default)]
+	pub alert_webhook: Option<String>,
+	/// Faults injected in the calls made to both chains, see [`crate::chaos`]. Only built with the
+	/// `chaos` feature.
+	#[cfg(feature = "chaos")]
+	#[serde(default)]
+	pub chaos: Option<ChaosConfig>,
+}
+
+/// Strategy of a channel, which applies to the packets relayed in both directions of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelStrategyConfig {
+	/// Name of the chain the channel id and port id are on
+	pub chain: String,
+	pub channel_id: ChannelId,
+	pub port_id: PortId,
+	#[serde(flatten)]
+	pub strategy: ChannelStrategy,
+}
+
+impl CoreConfig {
+	/// Sets the strategies configured for the channels of `chain`.
+	pub fn apply_channel_strategies(&self, chain: &mut impl Chain) -> Result<(), anyhow::Error> {
+		for config in self.channel_strategies.iter().filter(|config| config.chain == chain.name()) {
+			if let Some(min_fee) = &config.strategy.min_fee {
+				min_fee.amount()?;
+			}
+			chain
+				.common_state_mut()
+				.channel_strategies
+				.insert((config.channel_id, config.port_id.clone()), config.strategy.clone());
+		}
+		Ok(())
+	}
+
+	/// Wraps both chains in a [`ChaosChain`] when faults are configured, chain B draws its faults
+	/// from the seed following the one of chain A.
+	#[cfg(feature = "chaos")]
+	pub fn apply_chaos(
+		&self,
+		chain_a: AnyChain,
+		chain_b: AnyChain,
+	) -> Result<(AnyChain, AnyChain), anyhow::Error> {
+		let Some(config) = &self.chaos else { return Ok((chain_a, chain_b)) };
+		config.validate()?;
+		log::warn!(target: "hyperspace", "Injecting faults in the calls to {} and {} with seed {}", chain_a.name(), chain_b.name(), config.seed);
+		let config_b = config.with_seed(config.seed.wrapping_add(1));
+		Ok((
+			AnyChain::Chaos(ChaosChain::new(chain_a, config.clone())),
+			AnyChain::Chaos(ChaosChain::new(chain_b, config_b)),
+		))
+	}
 }
 
 impl From<String> for AnyError {
@@ -99,6 +186,47 @@ chains! {
 	PicassoKusama(ParachainClientConfig, ParachainClient<PicassoKusamaConfig>),
 	#[cfg(feature = "cosmos")]
 	Cosmos(CosmosClientConfig, CosmosClient<DefaultConfig>),
+	#[cfg(feature = "mock")]
+	Mock(MockClientConfig, MockChain),
+}
+
+impl AnyChain {
+	/// SCALE encoded privileged pallet-ibc call, suitable for submitting as a governance
+	/// proposal on this chain.
+	pub fn encode_admin_call(&self, call: &AdminCall) -> anyhow::Result<Vec<u8>> {
+		let encoded = match self {
+			Self::Parachain(chain) => chain.encode_admin_call(call)?,
+			Self::Composable(chain) => chain.encode_admin_call(call)?,
+			Self::PicassoRococo(chain) => chain.encode_admin_call(call)?,
+			Self::PicassoKusama(chain) => chain.encode_admin_call(call)?,
+			#[cfg(feature = "cosmos")]
+			Self::Cosmos(_) => return Err(anyhow::anyhow!("{} doesn't host pallet-ibc", self.name())),
+			#[cfg(feature = "mock")]
+			Self::Mock(_) => return Err(anyhow::anyhow!("{} doesn't host pallet-ibc", self.name())),
+			Self::Wasm(chain) => return chain.inner.encode_admin_call(call),
+			#[cfg(feature = "chaos")]
+			Self::Chaos(chain) => return chain.inner.encode_admin_call(call),
+		};
+		Ok(encoded)
+	}
+
+	/// Whether the chain hosts pallet-ibc, which only dispatches the core IBC messages delivered
+	/// to it and exposes the application messages as dedicated extrinsics.
+	pub fn hosts_pallet_ibc(&self) -> bool {
+		match self {
+			Self::Parachain(_) |
+			Self::Composable(_) |
+			Self::PicassoRococo(_) |
+			Self::PicassoKusama(_) => true,
+			#[cfg(feature = "cosmos")]
+			Self::Cosmos(_) => false,
+			#[cfg(feature = "mock")]
+			Self::Mock(_) => false,
+			Self::Wasm(chain) => chain.inner.hosts_pallet_ibc(),
+			#[cfg(feature = "chaos")]
+			Self::Chaos(chain) => chain.inner.hosts_pallet_ibc(),
+		}
+	}
 }
 
 fn wrap_any_msg_into_wasm(msg: Any, code_hash: Bytes) -> Result<Any, anyhow::Error> {
@@ -0,0 +1,276 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fault injection, to exercise the retries, reconnections and deduplication of the relayer
+//! against chains that misbehave. A chain wrapped in a [`ChaosChain`] delays, fails or loses the
+//! response of its RPC calls and submissions at random, drawn from a seeded generator. Only built
+//! with the `chaos` feature, and enabled by the `chaos` section of the core config:
+//!
+//! ```toml
+//! [core.chaos]
+//! seed = 42
+//! delay_probability = 0.2
+//! max_delay_ms = 500
+//! error_probability = 0.05
+//! drop_probability = 0.05
+//! ```
+//!
+//! The generator is shared by the relay loop, the submit stages and the channel workers calling a
+//! chain concurrently, so a seed draws the same sequence of faults but the calls they hit depend
+//! on the order the tasks are scheduled in. A run is only replayed exactly by the calls of a single
+//! task, e.g. with a single whitelisted channel and the prefetching of client updates idle.
+
+use crate::chain::{AnyChain, AnyError};
+use primitives::Chain;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{
+	fmt::{Display, Formatter},
+	future::Future,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+fn default_max_delay_ms() -> u64 {
+	1000
+}
+
+/// Probabilities of the faults injected in every call, which add up to at most 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChaosConfig {
+	/// Seed of the faults of chain A, chain B uses the next one. The same seed draws the same
+	/// faults for the same sequence of calls, see the module docs.
+	pub seed: u64,
+	/// Probability of a call being delayed
+	#[serde(default)]
+	pub delay_probability: f64,
+	/// Delays are drawn uniformly up to this many milliseconds
+	#[serde(default = "default_max_delay_ms")]
+	pub max_delay_ms: u64,
+	/// Probability of a call failing without reaching the chain
+	#[serde(default)]
+	pub error_probability: f64,
+	/// Probability of the response of a call being lost: the call reaches the chain, e.g. the
+	/// transaction is submitted, but the relayer gets an error
+	#[serde(default)]
+	pub drop_probability: f64,
+}
+
+impl ChaosConfig {
+	/// Same faults, drawn from another seed.
+	pub fn with_seed(&self, seed: u64) -> Self {
+		Self { seed, ..self.clone() }
+	}
+
+	/// Checks that the probabilities are valid and add up to at most 1, the faults are drawn from
+	/// consecutive ranges of a single roll.
+	pub fn validate(&self) -> Result<(), anyhow::Error> {
+		let probabilities = [
+			("delay_probability", self.delay_probability),
+			("error_probability", self.error_probability),
+			("drop_probability", self.drop_probability),
+		];
+		for (name, probability) in probabilities {
+			if !(0.0..=1.0).contains(&probability) {
+				return Err(anyhow::anyhow!("chaos {name} {probability} isn't between 0 and 1"))
+			}
+		}
+		let total = probabilities.iter().map(|(_, probability)| probability).sum::<f64>();
+		if total > 1.0 {
+			return Err(anyhow::anyhow!(
+				"chaos probabilities add up to {total}, which is more than 1"
+			))
+		}
+		Ok(())
+	}
+}
+
+/// Fault injected in a call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+	Delay(Duration),
+	Error,
+	Drop,
+}
+
+impl Display for Fault {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Delay(delay) => write!(f, "delayed by {delay:?}"),
+			Self::Error => write!(f, "failed"),
+			Self::Drop => write!(f, "response dropped"),
+		}
+	}
+}
+
+/// Draws the faults of the calls to a chain from a seeded generator shared by all the clones.
+#[derive(Clone)]
+pub struct Faults {
+	/// Name of the chain, used in logs
+	chain: String,
+	config: ChaosConfig,
+	rng: Arc<Mutex<StdRng>>,
+}
+
+impl Faults {
+	pub fn new(chain: impl Into<String>, config: ChaosConfig) -> Self {
+		let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(config.seed)));
+		Self { chain: chain.into(), config, rng }
+	}
+
+	/// Fault of the next call, if any.
+	pub fn draw(&self) -> Option<Fault> {
+		let mut rng = self.rng.lock().unwrap();
+		let roll = rng.gen::<f64>();
+		let config = &self.config;
+		if roll < config.error_probability {
+			Some(Fault::Error)
+		} else if roll < config.error_probability + config.drop_probability {
+			Some(Fault::Drop)
+		} else if roll <
+			config.error_probability + config.drop_probability + config.delay_probability
+		{
+			Some(Fault::Delay(Duration::from_millis(rng.gen_range(0..=config.max_delay_ms))))
+		} else {
+			None
+		}
+	}
+
+	/// Makes the call `name` with the next fault drawn.
+	pub async fn call<T, E: From<AnyError>>(
+		&self,
+		name: &str,
+		call: impl Future<Output = Result<T, E>>,
+	) -> Result<T, E> {
+		let Some(fault) = self.draw() else { return call.await };
+		log::debug!(target: "hyperspace", "Chaos: {name} on {} {fault}", self.chain);
+		match fault {
+			Fault::Delay(delay) => {
+				tokio::time::sleep(delay).await;
+				call.await
+			},
+			Fault::Error => Err(AnyError::Other(format!("chaos: {name} failed")).into()),
+			Fault::Drop => {
+				let _ = call.await;
+				Err(AnyError::Other(format!("chaos: response of {name} dropped")).into())
+			},
+		}
+	}
+}
+
+/// A chain whose RPC calls and submissions are subject to the faults drawn by [`Faults`]. Calls
+/// that only read the local state of the client, and the error handling and reconnection of the
+/// chain, are never faulted. Neither are the finality subscriptions, the relay loop doesn't retry
+/// the one it starts with.
+#[derive(Clone)]
+pub struct ChaosChain {
+	pub inner: Box<AnyChain>,
+	pub faults: Faults,
+}
+
+impl ChaosChain {
+	pub fn new(inner: AnyChain, config: ChaosConfig) -> Self {
+		let faults = Faults::new(inner.name(), config);
+		Self { inner: Box::new(inner), faults }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn config(delay: f64, error: f64, drop: f64) -> ChaosConfig {
+		ChaosConfig {
+			seed: 42,
+			delay_probability: delay,
+			max_delay_ms: 10,
+			error_probability: error,
+			drop_probability: drop,
+		}
+	}
+
+	fn draws(faults: &Faults, count: usize) -> Vec<Option<Fault>> {
+		(0..count).map(|_| faults.draw()).collect()
+	}
+
+	#[test]
+	fn probabilities_are_validated() {
+		assert!(config(0.2, 0.05, 0.05).validate().is_ok());
+		assert!(config(0.5, 0.25, 0.25).validate().is_ok());
+		assert!(config(0.0, 0.0, 0.0).validate().is_ok());
+		assert!(config(0.5, 0.3, 0.3).validate().is_err());
+		assert!(config(1.5, 0.0, 0.0).validate().is_err());
+		assert!(config(0.0, -0.1, 0.0).validate().is_err());
+		assert!(config(f64::NAN, 0.0, 0.0).validate().is_err());
+	}
+
+	#[test]
+	fn draws_the_configured_faults_only() {
+		assert!(draws(&Faults::new("chain", config(0.0, 0.0, 0.0)), 100)
+			.iter()
+			.all(Option::is_none));
+		assert!(draws(&Faults::new("chain", config(0.0, 1.0, 0.0)), 100)
+			.iter()
+			.all(|fault| *fault == Some(Fault::Error)));
+		assert!(draws(&Faults::new("chain", config(0.0, 0.0, 1.0)), 100)
+			.iter()
+			.all(|fault| *fault == Some(Fault::Drop)));
+		assert!(draws(&Faults::new("chain", config(1.0, 0.0, 0.0)), 100).iter().all(
+			|fault| matches!(fault, Some(Fault::Delay(delay)) if *delay <= Duration::from_millis(10))
+		));
+	}
+
+	#[test]
+	fn clones_share_the_sequence_of_faults() {
+		let config = config(0.3, 0.3, 0.3);
+		let expected = draws(&Faults::new("chain", config.clone()), 20);
+		let faults = Faults::new("chain", config);
+		let clone = faults.clone();
+		let interleaved = (0..10).flat_map(|_| [faults.draw(), clone.draw()]).collect::<Vec<_>>();
+		assert_eq!(interleaved, expected);
+	}
+
+	#[tokio::test]
+	async fn calls_are_faulted_as_drawn() {
+		let call = |calls: &Arc<Mutex<u32>>| {
+			let calls = calls.clone();
+			async move {
+				*calls.lock().unwrap() += 1;
+				Ok::<_, AnyError>(1)
+			}
+		};
+
+		let calls = Arc::new(Mutex::new(0));
+		let faults = Faults::new("chain", config(0.0, 0.0, 0.0));
+		assert_eq!(faults.call("query", call(&calls)).await.unwrap(), 1);
+		assert_eq!(*calls.lock().unwrap(), 1);
+
+		// the call doesn't reach the chain
+		let calls = Arc::new(Mutex::new(0));
+		let faults = Faults::new("chain", config(0.0, 1.0, 0.0));
+		assert!(faults.call("query", call(&calls)).await.is_err());
+		assert_eq!(*calls.lock().unwrap(), 0);
+
+		// the call reaches the chain, its response is lost
+		let calls = Arc::new(Mutex::new(0));
+		let faults = Faults::new("chain", config(0.0, 0.0, 1.0));
+		assert!(faults.call("submit", call(&calls)).await.is_err());
+		assert_eq!(*calls.lock().unwrap(), 1);
+
+		let calls = Arc::new(Mutex::new(0));
+		let faults = Faults::new("chain", config(1.0, 0.0, 0.0));
+		assert_eq!(faults.call("query", call(&calls)).await.unwrap(), 1);
+		assert_eq!(*calls.lock().unwrap(), 1);
+	}
+}
@@ -13,16 +13,34 @@
 // limitations under the License.
 
 use crate::{
+	admin::{admin_api_address, serve_admin_api},
+	alerts,
 	chain::{AnyConfig, Config, CoreConfig},
-	fish, relay, Mode,
+	config::ConfigCmd,
+	doctor::DoctorCmd,
+	fish,
+	init::InitCmd,
+	interpolation,
+	keys::KeysCmd,
+	query::QueryCmd,
+	queue, relay,
+	reload::{self, ConfigPaths},
+	shutdown::{self, Shutdown},
+	tx::TxCmd,
+	wiring, Mode,
 };
 use anyhow::{anyhow, Result};
 use clap::Parser;
-use ibc::core::{ics04_channel::channel::Order, ics24_host::identifier::PortId};
+use ibc::core::{
+	ics04_channel::channel::Order,
+	ics24_host::identifier::{ClientId, PortId},
+};
 use metrics::{data::Metrics, handler::MetricsHandler, init_prometheus};
+use parachain::admin::AdminCall;
 use primitives::{
+	keystore,
 	utils::{create_channel, create_clients, create_connection},
-	Chain, IbcProvider,
+	Chain, IbcQueryProvider,
 };
 use prometheus::Registry;
 use std::{num::NonZeroU64, path::PathBuf, str::FromStr, time::Duration};
@@ -31,6 +49,9 @@ use std::{num::NonZeroU64, path::PathBuf, str::FromStr, time::Duration};
 pub struct Cli {
 	#[structopt(subcommand)]
 	pub subcommand: Subcommand,
+	/// Build and log the messages of `relay` and `tx` without submitting them.
+	#[clap(long, global = true)]
+	pub dry_run: bool,
 }
 
 /// Possible subcommands of the main binary.
@@ -51,6 +72,34 @@ pub enum Subcommand {
 	CreateConnection(Cmd),
 	#[clap(name = "create-channel", about = "Creates a channel on the specified port")]
 	CreateChannel(Cmd),
+	#[clap(
+		name = "encrypt-key",
+		about = "Moves the raw signing key of a chain config into an encrypted keystore"
+	)]
+	EncryptKey(EncryptKeyCmd),
+	#[clap(name = "keys", about = "Manages the keys of the relayer keyring")]
+	Keys(KeysCmd),
+	#[clap(
+		name = "recover-client",
+		about = "Prints the encoded call recovering a client, to be submitted as a governance proposal"
+	)]
+	RecoverClient(RecoverClientCmd),
+	#[clap(name = "config", about = "Inspects the relayer configs")]
+	Config(ConfigCmd),
+	#[clap(name = "query", about = "Queries the IBC state of a chain")]
+	Query(QueryCmd),
+	#[clap(name = "tx", about = "Submits a transaction with the relayer account of a chain")]
+	Tx(TxCmd),
+	#[clap(
+		name = "doctor",
+		about = "Diagnoses the nodes, relayer accounts, clients and packets of both chains"
+	)]
+	Doctor(DoctorCmd),
+	#[clap(
+		name = "init",
+		about = "Sets up the clients, connection and channels of both chain configs interactively"
+	)]
+	Init(InitCmd),
 }
 
 #[derive(Debug, Clone, Parser)]
@@ -102,7 +151,7 @@ impl UploadWasmCmd {
 		use tokio::fs::read_to_string;
 		let path: PathBuf = self.config.parse()?;
 		let file_content = read_to_string(path).await?;
-		let mut config: AnyConfig = toml::from_str(&file_content)?;
+		let mut config: AnyConfig = interpolation::from_toml_str(&file_content)?;
 		let client = config.clone().into_client().await?;
 		let wasm = tokio::fs::read(&self.wasm_path).await?;
 		let code_hash = client.upload_wasm(wasm).await?;
@@ -114,10 +163,111 @@ impl UploadWasmCmd {
 
 	pub async fn save_config(&self, new_config: &AnyConfig) -> Result<()> {
 		let path = self.out_config.as_ref().cloned().unwrap_or_else(|| self.config.clone());
-		write_config(path, new_config).await
+		write_config(&self.config, path, new_config).await
 	}
 }
 
+#[derive(Debug, Clone, Parser)]
+pub struct EncryptKeyCmd {
+	/// Relayer chain config path.
+	#[clap(long)]
+	config: String,
+	/// New config path to avoid overriding existing configuration.
+	#[clap(long)]
+	pub out_config: Option<String>,
+	/// Path of the keystore file to create.
+	#[clap(long)]
+	keystore: PathBuf,
+}
+
+impl EncryptKeyCmd {
+	/// Encrypts the raw key of the config with a passphrase read from
+	/// [`keystore::KEYSTORE_PASSPHRASE_ENV`] or the terminal, and returns the config pointing at
+	/// the new keystore.
+	pub async fn run(&self) -> Result<AnyConfig> {
+		let mut config: AnyConfig = interpolation::read_config(&self.config).await?;
+		if self.keystore.exists() {
+			return Err(anyhow!("Keystore {} already exists", self.keystore.display()))
+		}
+		let passphrase = keystore::read_passphrase(true)?;
+		config.migrate_to_keystore(self.keystore.clone(), &passphrase)?;
+		println!("Key encrypted to {}", self.keystore.display());
+		Ok(config)
+	}
+
+	pub async fn save_config(&self, new_config: &AnyConfig) -> Result<()> {
+		let path = self.out_config.as_ref().cloned().unwrap_or_else(|| self.config.clone());
+		write_config(&self.config, path, new_config).await
+	}
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct RecoverClientCmd {
+	/// Config path of the chain hosting both clients.
+	#[clap(long)]
+	config: String,
+	/// Expired or frozen client to recover.
+	#[clap(long)]
+	subject_client_id: String,
+	/// Active client of the same type whose state is copied over the subject client.
+	#[clap(long)]
+	substitute_client_id: String,
+}
+
+impl RecoverClientCmd {
+	pub async fn run(&self) -> Result<()> {
+		let config: AnyConfig = interpolation::read_config(&self.config).await?;
+		let client = config.into_client().await?;
+		let call = AdminCall::RecoverClient {
+			subject_client_id: ClientId::from_str(&self.subject_client_id)?,
+			substitute_client_id: ClientId::from_str(&self.substitute_client_id)?,
+		};
+		let encoded = client.encode_admin_call(&call)?;
+		println!("0x{}", hex::encode(encoded));
+		Ok(())
+	}
+}
+
+/// Submits the messages persisted by a previous run to the chains they were built for. The packet
+/// messages that were delivered by someone else in the meantime are skipped, other messages may
+/// still fail so errors aren't fatal here.
+async fn resubmit_pending_messages(
+	path: &str,
+	chain_a: &impl Chain,
+	chain_b: &impl Chain,
+) -> Result<()> {
+	let pending = shutdown::take_persisted_pending_messages(path).await?;
+	if pending.is_empty() {
+		return Ok(())
+	}
+	log::info!(target: "hyperspace", "Resubmitting {} pending messages", pending.len());
+	let (mut msgs_a, mut msgs_b) = (vec![], vec![]);
+	let (chain_id_a, chain_id_b) = (chain_a.chain_id(), chain_b.chain_id());
+	for msg in pending {
+		// messages persisted by older versions are keyed by the name of the chain
+		if msg.chain == chain_id_a.as_str() || msg.chain == chain_a.name() {
+			msgs_a.push(msg.to_any()?);
+		} else if msg.chain == chain_id_b.as_str() || msg.chain == chain_b.name() {
+			msgs_b.push(msg.to_any()?);
+		} else {
+			log::warn!(target: "hyperspace", "Dropping pending message for unknown chain {}", msg.chain);
+		}
+	}
+	let msgs_a = queue::retain_undelivered_messages(msgs_a, chain_a).await;
+	if !msgs_a.is_empty() {
+		if let Err(e) = queue::flush_message_batch(msgs_a, None, chain_a).await {
+			log::warn!(target: "hyperspace", "Failed to resubmit pending messages to {}: {e:?}", chain_a.name());
+		}
+	}
+	let msgs_b = queue::retain_undelivered_messages(msgs_b, chain_b).await;
+	if !msgs_b.is_empty() {
+		if let Err(e) = queue::flush_message_batch(msgs_b, None, chain_b).await {
+			log::warn!(target: "hyperspace", "Failed to resubmit pending messages to {}: {e:?}", chain_b.name());
+		}
+	}
+	Ok(())
+}
+
 impl Cmd {
 	async fn parse_config(&self) -> Result<Config> {
 		use tokio::fs::read_to_string;
@@ -125,11 +275,11 @@ impl Cmd {
 		let path_b: PathBuf = self.config_b.parse()?;
 		let path_core: PathBuf = self.config_core.parse()?;
 		let file_content = read_to_string(path_a).await?;
-		let config_a: AnyConfig = toml::from_str(&file_content)?;
+		let config_a: AnyConfig = interpolation::from_toml_str(&file_content)?;
 		let file_content = read_to_string(path_b).await?;
-		let config_b: AnyConfig = toml::from_str(&file_content)?;
+		let config_b: AnyConfig = interpolation::from_toml_str(&file_content)?;
 		let file_content = read_to_string(path_core).await?;
-		let config_core: CoreConfig = toml::from_str(&file_content)?;
+		let config_core: CoreConfig = interpolation::from_toml_str(&file_content)?;
 
 		Ok(Config { chain_a: config_a, chain_b: config_b, core: config_core })
 	}
@@ -138,13 +288,23 @@ impl Cmd {
 	/// Run the command
 	pub async fn run(&self) -> Result<()> {
 		let config = self.parse_config().await?;
-		let chain_a = config.chain_a.into_client().await?;
-		let chain_b = config.chain_b.into_client().await?;
+		if let Some(level) = &config.core.log_level {
+			reload::apply_log_level(level)?;
+		}
+		alerts::set_webhook(config.core.alert_webhook.clone());
+		let mut chain_a = config.chain_a.into_client().await?;
+		let mut chain_b = config.chain_b.into_client().await?;
+		config.core.apply_channel_strategies(&mut chain_a)?;
+		config.core.apply_channel_strategies(&mut chain_b)?;
+		wiring::check_wiring(&chain_a, &chain_b).await?;
+		// faults only start once the relayer is known to be able to start
+		#[cfg(feature = "chaos")]
+		let (chain_a, chain_b) = config.core.apply_chaos(chain_a, chain_b)?;
 
 		let registry =
 			Registry::new_custom(None, None).expect("this can only fail if the prefix is empty");
-		let metrics_a = Metrics::register(chain_a.name(), &registry)?;
-		let metrics_b = Metrics::register(chain_b.name(), &registry)?;
+		let metrics_a = Metrics::register(chain_a.name(), chain_a.chain_id().as_str(), &registry)?;
+		let metrics_b = Metrics::register(chain_b.name(), chain_b.chain_id().as_str(), &registry)?;
 		let mut metrics_handler_a = MetricsHandler::new(registry.clone(), metrics_a);
 		let mut metrics_handler_b = MetricsHandler::new(registry.clone(), metrics_b);
 		metrics_handler_a.link_with_counterparty(&mut metrics_handler_b);
@@ -153,7 +313,56 @@ impl Cmd {
 			tokio::spawn(init_prometheus(addr, registry.clone()));
 		}
 
-		relay(chain_a, chain_b, Some(metrics_handler_a), Some(metrics_handler_b), None).await
+		if let Some(endpoint) = &config.core.admin_endpoint {
+			let token = config.core.admin_token.clone();
+			let addr = admin_api_address(endpoint, token.as_deref())?;
+			let chains = vec![chain_a.clone(), chain_b.clone()];
+			tokio::spawn(async move {
+				if let Err(e) = serve_admin_api(addr, token, chains).await {
+					log::error!(target: "hyperspace", "Admin API failed: {e:?}");
+				}
+			});
+		}
+
+		match &config.core.pending_messages_path {
+			Some(path) if queue::is_dry_run() =>
+				log::info!(target: "hyperspace", "[dry-run] Not resubmitting the pending messages persisted in {path}"),
+			Some(path) => resubmit_pending_messages(path, &chain_a, &chain_b).await?,
+			None => {},
+		}
+
+		let paths = ConfigPaths {
+			config_a: self.config_a.clone(),
+			config_b: self.config_b.clone(),
+			config_core: self.config_core.clone(),
+		};
+		tokio::spawn(reload::listen_for_reloads(paths, (chain_a.clone(), chain_b.clone())));
+
+		let shutdown = Shutdown::default();
+		let pending_messages_path =
+			config.core.pending_messages_path.clone().filter(|_| !queue::is_dry_run());
+		tokio::spawn({
+			let (shutdown, path) = (shutdown.clone(), pending_messages_path.clone());
+			async move {
+				if let Err(e) = shutdown::listen_for_signals(shutdown, path.map(Into::into)).await {
+					log::error!(target: "hyperspace", "Failed to listen for shutdown signals: {e:?}");
+				}
+			}
+		});
+
+		let result = relay(
+			chain_a,
+			chain_b,
+			Some(metrics_handler_a),
+			Some(metrics_handler_b),
+			None,
+			shutdown.clone(),
+		)
+		.await;
+		if let Some(path) = &pending_messages_path {
+			shutdown.persist_pending_messages(path).await?;
+		}
+		result
 	}
 
 	/// Run fisherman
@@ -162,7 +371,7 @@ impl Cmd {
 		let chain_a = config.chain_a.into_client().await?;
 		let chain_b = config.chain_b.into_client().await?;
 
-		fish(chain_a, chain_b).await
+		fish(chain_a, chain_b, Shutdown::default()).await
 	}
 
 	pub async fn create_clients(&self) -> Result<Config> {
@@ -203,7 +412,7 @@ impl Cmd {
 		let chain_a_clone = chain_a.clone();
 		let chain_b_clone = chain_b.clone();
 		let handle = tokio::task::spawn(async move {
-			relay(chain_a_clone, chain_b_clone, None, None, Some(Mode::Light))
+			relay(chain_a_clone, chain_b_clone, None, None, Some(Mode::Light), Shutdown::default())
 				.await
 				.unwrap();
 		});
@@ -241,7 +450,7 @@ impl Cmd {
 		let chain_a_clone = chain_a.clone();
 		let chain_b_clone = chain_b.clone();
 		let handle = tokio::task::spawn(async move {
-			relay(chain_a_clone, chain_b_clone, None, None, Some(Mode::Light))
+			relay(chain_a_clone, chain_b_clone, None, None, Some(Mode::Light), Shutdown::default())
 				.await
 				.unwrap();
 		});
@@ -270,13 +479,16 @@ impl Cmd {
 	pub async fn save_config(&self, new_config: &Config) -> Result<()> {
 		let path_a = self.out_config_a.as_ref().cloned().unwrap_or_else(|| self.config_a.clone());
 		let path_b = self.out_config_b.as_ref().cloned().unwrap_or_else(|| self.config_b.clone());
-		write_config(path_a, &new_config.chain_a).await?;
-		write_config(path_b, &new_config.chain_b).await
+		write_config(&self.config_a, path_a, &new_config.chain_a).await?;
+		write_config(&self.config_b, path_b, &new_config.chain_b).await
 	}
 }
 
-async fn write_config(path: String, config: &AnyConfig) -> Result<()> {
-	tokio::fs::write(path.parse::<PathBuf>()?, toml::to_string(config)?)
+/// Writes `config`, keeping the references of the config it was read from at `source`, see
+/// [`interpolation`].
+pub(crate) async fn write_config(source: &str, path: String, config: &AnyConfig) -> Result<()> {
+	let original = tokio::fs::read_to_string(source.parse::<PathBuf>()?).await?;
+	tokio::fs::write(path.parse::<PathBuf>()?, interpolation::to_toml_string(config, &original)?)
 		.await
 		.map_err(|e| anyhow!(e))
 }
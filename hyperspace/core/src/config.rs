@@ -0,0 +1,273 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commands inspecting the relayer configs, so that misconfigurations are reported upfront
+//! instead of surfacing deep inside the relay loop.
+
+use crate::{
+	chain::{AnyChain, AnyConfig, CoreConfig},
+	hermes::ImportHermesCmd,
+	interpolation,
+};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use ibc::Height;
+use primitives::{Chain, IbcQueryProvider};
+use std::fmt::Display;
+
+/// State of an open connection or channel end in the proto encoding.
+const STATE_OPEN: i32 = 3;
+
+#[derive(Debug, Clone, Parser)]
+pub struct ConfigCmd {
+	#[clap(subcommand)]
+	subcommand: ConfigSubcommand,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub enum ConfigSubcommand {
+	#[clap(
+		name = "validate",
+		about = "Checks the configs against both chains and reports every problem found"
+	)]
+	Validate(ValidateConfigCmd),
+	#[clap(
+		name = "import-hermes",
+		about = "Converts a path of a Hermes config into hyperspace configs"
+	)]
+	ImportHermes(ImportHermesCmd),
+}
+
+impl ConfigCmd {
+	pub async fn run(&self) -> Result<()> {
+		match &self.subcommand {
+			ConfigSubcommand::Validate(cmd) => cmd.run().await,
+			ConfigSubcommand::ImportHermes(cmd) => cmd.run().await,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct ValidateConfigCmd {
+	/// Relayer chain A config path.
+	#[clap(long)]
+	config_a: String,
+	/// Relayer chain B config path.
+	#[clap(long)]
+	config_b: String,
+	/// Relayer core config path.
+	#[clap(long)]
+	config_core: Option<String>,
+}
+
+/// Collects the outcome of every check, so that all the problems are reported at once.
+#[derive(Default)]
+struct Report {
+	errors: usize,
+}
+
+impl Report {
+	fn ok(&mut self, check: impl Display) {
+		println!("  ok     {check}");
+	}
+
+	fn error(&mut self, check: impl Display, hint: impl Display) {
+		self.errors += 1;
+		println!("  error  {check}");
+		println!("         hint: {hint}");
+	}
+}
+
+impl ValidateConfigCmd {
+	pub async fn run(&self) -> Result<()> {
+		let mut report = Report::default();
+
+		println!("Configs");
+		let config_a = parse_config::<AnyConfig>(&self.config_a, &mut report).await;
+		let config_b = parse_config::<AnyConfig>(&self.config_b, &mut report).await;
+		if let Some(path) = &self.config_core {
+			parse_config::<CoreConfig>(path, &mut report).await;
+		}
+
+		if let (Some(config_a), Some(config_b)) = (config_a, config_b) {
+			let chain_a = connect(&config_a, &mut report).await;
+			let chain_b = connect(&config_b, &mut report).await;
+			if let (Some((chain_a, height_a)), Some((chain_b, height_b))) = (chain_a, chain_b) {
+				let side_a = (&config_a, &chain_a, height_a);
+				let side_b = (&config_b, &chain_b, height_b);
+				check_ibc_state(side_a, side_b, &mut report).await;
+				check_ibc_state(side_b, side_a, &mut report).await;
+			}
+		}
+
+		match report.errors {
+			0 => {
+				println!("Configs are valid");
+				Ok(())
+			},
+			errors => Err(anyhow!("Found {errors} problems in the configs")),
+		}
+	}
+}
+
+async fn parse_config<C: serde::de::DeserializeOwned>(
+	path: &str,
+	report: &mut Report,
+) -> Option<C> {
+	let content = match tokio::fs::read_to_string(path).await {
+		Ok(content) => content,
+		Err(e) => {
+			report.error(format!("{path} can't be read: {e}"), "check the path of the config");
+			return None
+		},
+	};
+	match interpolation::from_toml_str(&content) {
+		Ok(config) => {
+			report.ok(format!("{path} is well formed"));
+			Some(config)
+		},
+		Err(e) => {
+			report.error(
+				format!("{path} is malformed: {e}"),
+				"fix the field reported above, identifiers such as `channel-0` or `07-tendermint-0` must be valid ICS-24 identifiers and `${...}` references must resolve",
+			);
+			None
+		},
+	}
+}
+
+/// Connects to the chain of `config`, which also loads its signing key.
+async fn connect(config: &AnyConfig, report: &mut Report) -> Option<(AnyChain, Height)> {
+	let name = config.name().to_string();
+	println!("Chain {name}");
+	let chain = match config.clone().into_client().await {
+		Ok(chain) => chain,
+		Err(e) => {
+			report.error(
+				format!("can't connect to {name}: {e}"),
+				"check that the rpc urls are reachable and that the signing key (raw secret, keystore, ledger, remote signer or vault) is configured",
+			);
+			return None
+		},
+	};
+	report.ok(format!("connected, relayer account is {}", chain.account_id()));
+
+	match chain.latest_height_and_timestamp().await {
+		Ok((height, _)) => {
+			report.ok(format!("latest height is {height}"));
+			Some((chain, height))
+		},
+		Err(e) => {
+			report.error(
+				format!("can't query the latest height of {name}: {e:?}"),
+				"check that the node is synced and exposes the ibc rpc methods",
+			);
+			None
+		},
+	}
+}
+
+/// Checks that the client, connection and channels configured for `chain` exist. The client of
+/// `chain` lives on the counterparty, while its connection and channels live on `chain` itself.
+async fn check_ibc_state(
+	(config, chain, height): (&AnyConfig, &AnyChain, Height),
+	(counterparty_config, counterparty, counterparty_height): (&AnyConfig, &AnyChain, Height),
+	report: &mut Report,
+) {
+	println!("IBC state of {}", config.name());
+	let counterparty_name = counterparty_config.name();
+
+	match config.client_id() {
+		Some(client_id) => match counterparty.query_client_state(counterparty_height, client_id.clone()).await {
+			Ok(response) if response.client_state.is_some() =>
+				report.ok(format!("client {client_id} exists on {counterparty_name}")),
+			result => report.error(
+				format!("client {client_id} not found on {counterparty_name}: {:?}", result.err()),
+				format!("`client_id` is the id of the light client of {} on {counterparty_name}, run `create-clients` if it doesn't exist yet", config.name()),
+			),
+		},
+		None => report.error(
+			"`client_id` isn't set",
+			"run `create-clients`, it writes the client ids into the configs",
+		),
+	}
+
+	let connection_id = match config.connection_id() {
+		Some(connection_id) => connection_id,
+		None => {
+			report.error(
+				"`connection_id` isn't set",
+				"run `create-connection`, it writes the connection ids into the configs",
+			);
+			return
+		},
+	};
+	match chain.query_connection_end(height, connection_id.clone()).await {
+		Ok(response) => match response.connection {
+			Some(connection) if connection.state != STATE_OPEN => report.error(
+				format!("connection {connection_id} isn't open"),
+				"wait for the connection handshake to complete before relaying",
+			),
+			Some(connection) => match counterparty_config.client_id() {
+				Some(client_id) if connection.client_id != client_id.as_str() => report.error(
+					format!(
+						"connection {connection_id} uses client {}, but the `client_id` of {counterparty_name} is {client_id}",
+						connection.client_id
+					),
+					"the `connection_id` of a chain must be built on the `client_id` configured for its counterparty",
+				),
+				_ => report.ok(format!(
+					"connection {connection_id} is open on client {}",
+					connection.client_id
+				)),
+			},
+			None => report.error(
+				format!("connection {connection_id} not found"),
+				"check the `connection_id`, or run `create-connection`",
+			),
+		},
+		Err(e) => report.error(
+			format!("can't query connection {connection_id}: {e:?}"),
+			"check the `connection_id`, or run `create-connection`",
+		),
+	}
+
+	for (channel_id, port_id) in config.channel_whitelist() {
+		match chain.query_channel_end(height, channel_id, port_id.clone()).await {
+			Ok(response) => match response.channel {
+				Some(channel) if channel.state != STATE_OPEN => report.error(
+					format!("channel {channel_id}/{port_id} isn't open"),
+					"packets are only relayed on open channels, complete the handshake or remove it from `channel_whitelist`",
+				),
+				Some(channel) if !channel.connection_hops.contains(&connection_id.to_string()) =>
+					report.error(
+						format!(
+							"channel {channel_id}/{port_id} runs over {:?}, not {connection_id}",
+							channel.connection_hops
+						),
+						"only channels of the configured connection can be relayed",
+					),
+				Some(_) => report.ok(format!("channel {channel_id}/{port_id} is open")),
+				None => report.error(
+					format!("channel {channel_id}/{port_id} not found"),
+					"check the channel and port ids in `channel_whitelist`",
+				),
+			},
+			Err(e) => report.error(
+				format!("can't query channel {channel_id}/{port_id}: {e:?}"),
+				"check the channel and port ids in `channel_whitelist`",
+			),
+		}
+	}
+}
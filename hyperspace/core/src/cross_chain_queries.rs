@@ -0,0 +1,85 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Responder role for ICS-31 cross-chain queries: answers the queries a chain makes about the state
+//! of its counterparty with the queried value and its proof.
+
+use anyhow::anyhow;
+use ibc::{
+	applications::cross_chain_queries::{
+		msgs::MsgSubmitCrossChainQueryResult, query::CrossChainQuery,
+	},
+	tx_msg::Msg,
+	Height,
+};
+use ibc_proto::google::protobuf::Any;
+use primitives::Chain;
+
+/// Builds the results of the pending cross-chain queries `sink` made about the state of `source`
+/// over the connection relayed by this path.
+pub async fn query_cross_chain_query_results<A: Chain, B: Chain>(
+	source: &A,
+	sink: &B,
+) -> Result<Vec<Any>, anyhow::Error> {
+	let connection_id = match sink.connection_id() {
+		Some(connection_id) => connection_id,
+		None => return Ok(vec![]),
+	};
+	let queries = sink
+		.query_cross_chain_queries()
+		.await
+		.map_err(|e| anyhow!("Failed to query cross-chain queries of {}: {:?}", sink.name(), e))?;
+
+	let mut msgs = vec![];
+	for query in queries.into_iter().filter(|query| query.connection_id == connection_id) {
+		let query_id = query.id;
+		match answer_query(source, sink, query).await {
+			Ok(msg) => msgs.push(msg),
+			Err(e) => log::warn!(
+				target: "hyperspace",
+				"Failed to answer cross-chain query {} of {}: {:?}", query_id, sink.name(), e
+			),
+		}
+	}
+	Ok(msgs)
+}
+
+async fn answer_query<A: Chain, B: Chain>(
+	source: &A,
+	sink: &B,
+	query: CrossChainQuery,
+) -> Result<Any, anyhow::Error> {
+	// the proof must be verifiable against the consensus state at the query height, which for some
+	// chains commits to the state of the previous block
+	let proof_height_offset = source
+		.get_proof_height(query.query_height)
+		.await
+		.revision_height
+		.saturating_sub(query.query_height.revision_height);
+	let at = Height::new(
+		query.query_height.revision_number,
+		query.query_height.revision_height.saturating_sub(proof_height_offset),
+	);
+	let (data, proof) = source
+		.query_value_with_proof(at, query.path)
+		.await
+		.map_err(|e| anyhow!("Failed to query value with proof: {:?}", e))?;
+
+	let msg = MsgSubmitCrossChainQueryResult {
+		query_id: query.id,
+		data,
+		proof: proof.try_into()?,
+		signer: sink.account_id(),
+	};
+	Ok(msg.to_any())
+}
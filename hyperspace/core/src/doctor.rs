@@ -0,0 +1,281 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Diagnostics of a running relayer setup: the health of both nodes, their IBC modules, the
+//! relayer accounts, the light clients and the packets waiting to be relayed.
+
+use crate::{
+	chain::{AnyChain, AnyConfig},
+	interpolation,
+	packets::client_expiry::{query_client_expiry, ClientExpiryStatus},
+	wiring::query_wiring_problems,
+};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use ibc::{
+	applications::transfer::Amount, core::ics24_host::identifier::ClientId, timestamp::Timestamp,
+	Height,
+};
+use primitives::{query_undelivered_acks, query_undelivered_sequences, Chain, IbcQueryProvider};
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+/// Number of expected block times after which the latest block of a chain is considered stale.
+const STALE_BLOCKS: u32 = 10;
+
+#[derive(Debug, Clone, Parser)]
+pub struct DoctorCmd {
+	/// Relayer chain A config path.
+	#[clap(long)]
+	config_a: String,
+	/// Relayer chain B config path.
+	#[clap(long)]
+	config_b: String,
+	/// Asset the relayer account of chain A pays fees with, its balance isn't checked otherwise.
+	#[clap(long)]
+	asset_a: Option<String>,
+	/// Asset the relayer account of chain B pays fees with, its balance isn't checked otherwise.
+	#[clap(long)]
+	asset_b: Option<String>,
+	/// Balance below which the relayer accounts are reported as running low.
+	#[clap(long)]
+	min_balance: Option<String>,
+}
+
+/// Collects the outcome of every check, so that all the problems are reported at once.
+#[derive(Default)]
+struct Report {
+	warnings: usize,
+	failures: usize,
+}
+
+impl Report {
+	fn pass(&mut self, check: impl Display) {
+		println!("  pass  {check}");
+	}
+
+	fn warn(&mut self, check: impl Display) {
+		self.warnings += 1;
+		println!("  warn  {check}");
+	}
+
+	fn fail(&mut self, check: impl Display) {
+		self.failures += 1;
+		println!("  fail  {check}");
+	}
+}
+
+impl DoctorCmd {
+	pub async fn run(&self) -> Result<()> {
+		let min_balance = self
+			.min_balance
+			.as_deref()
+			.map(Amount::from_str)
+			.transpose()
+			.map_err(|e| anyhow!("Invalid minimum balance: {e}"))?;
+		let (chain_a, client_id_a) = connect(&self.config_a).await?;
+		let (chain_b, client_id_b) = connect(&self.config_b).await?;
+		let mut report = Report::default();
+
+		let mut heights = vec![];
+		for (chain, asset) in [(&chain_a, &self.asset_a), (&chain_b, &self.asset_b)] {
+			println!("Chain {}", chain.name());
+			heights.push(check_node(chain, &mut report).await);
+			check_balance(chain, asset.as_deref(), min_balance, &mut report).await;
+		}
+
+		println!("Wiring of {} and {}", chain_a.name(), chain_b.name());
+		if client_id_a.is_some() && client_id_b.is_some() {
+			check_wiring(&chain_a, &chain_b, &mut report).await;
+		} else {
+			report.fail("clients, connections and channels not checked, a client isn't configured");
+		}
+
+		for ((source, client_id), sink) in
+			[((&chain_a, &client_id_a), &chain_b), ((&chain_b, &client_id_b), &chain_a)]
+		{
+			println!("Client of {} on {}", source.name(), sink.name());
+			match client_id {
+				Some(client_id) => check_client_expiry(source, client_id, sink, &mut report).await,
+				None => report.fail(format!("no client of {} configured", source.name())),
+			}
+		}
+
+		if let [Some(height_a), Some(height_b)] = heights[..] {
+			for ((source, source_height), (sink, sink_height)) in [
+				((&chain_a, height_a), (&chain_b, height_b)),
+				((&chain_b, height_b), (&chain_a, height_a)),
+			] {
+				println!("Relaying from {} to {}", source.name(), sink.name());
+				check_undelivered_packets(
+					(source, source_height),
+					(sink, sink_height),
+					&mut report,
+				)
+				.await;
+			}
+		}
+
+		println!("{} failures, {} warnings", report.failures, report.warnings);
+		match report.failures {
+			0 => Ok(()),
+			failures => Err(anyhow!("{failures} checks failed")),
+		}
+	}
+}
+
+/// Connects to the chain of the config at `path`, returns it along with the id of its light client
+/// on the counterparty, which isn't configured until the client is created.
+async fn connect(path: &str) -> Result<(AnyChain, Option<ClientId>)> {
+	let config: AnyConfig = interpolation::read_config(path).await?;
+	let client_id = config.client_id();
+	Ok((config.into_client().await?, client_id))
+}
+
+/// Checks that the node is synced and hosts an IBC module, returns its latest height.
+async fn check_node(chain: &AnyChain, report: &mut Report) -> Option<Height> {
+	match chain.is_node_syncing().await {
+		Ok(Some(true)) => report.fail("node is still syncing"),
+		Ok(Some(false)) => report.pass("node is synced"),
+		Ok(None) => {},
+		Err(e) => report.warn(format!("can't query the sync state of the node: {e:?}")),
+	}
+
+	match chain.ibc_module_version().await {
+		Ok(Some(version)) => report.pass(format!("IBC module: {version}")),
+		Ok(None) => report.pass("IBC module is present"),
+		Err(e) => report.fail(format!("IBC module not found: {e:?}")),
+	}
+
+	let (height, timestamp) = match chain.latest_height_and_timestamp().await {
+		Ok(latest) => latest,
+		Err(e) => {
+			report.fail(format!("can't query the latest height: {e:?}"));
+			return None
+		},
+	};
+	let block_age = Duration::from_nanos(
+		Timestamp::now().nanoseconds().saturating_sub(timestamp.nanoseconds()),
+	);
+	if block_age > chain.expected_block_time() * STALE_BLOCKS {
+		report.warn(format!("latest block {height} is {block_age:?} old"));
+	} else {
+		report.pass(format!("latest block {height} is {block_age:?} old"));
+	}
+	Some(height)
+}
+
+async fn check_balance(
+	chain: &AnyChain,
+	asset: Option<&str>,
+	min_balance: Option<Amount>,
+	report: &mut Report,
+) {
+	let account = chain.account_id();
+	let Some(asset) = asset else {
+		report.warn(format!("balance of {account} not checked, no asset given"));
+		return
+	};
+	let balances = match chain.parse_asset_id(asset) {
+		Ok(asset_id) => chain.query_ibc_balance(asset_id).await.map_err(anyhow::Error::from),
+		Err(e) => Err(e),
+	};
+	let balance = match balances {
+		Ok(balances) => balances
+			.into_iter()
+			.try_fold(Amount::from(0u64), |total, coin| total.checked_add(coin.amount)),
+		Err(e) => {
+			report.fail(format!("can't query the balance of {account}: {e:?}"));
+			return
+		},
+	};
+	match balance {
+		Some(balance) if balance == Amount::from(0u64) =>
+			report.fail(format!("{account} has no {asset} to pay fees with")),
+		Some(balance) if min_balance.map_or(false, |min_balance| balance < min_balance) =>
+			report.warn(format!("{account} is running low on {asset}: {balance}")),
+		Some(balance) => report.pass(format!("{account} has {balance} {asset}")),
+		None => report.fail(format!("balance of {account} overflows")),
+	}
+}
+
+async fn check_wiring(chain_a: &AnyChain, chain_b: &AnyChain, report: &mut Report) {
+	match query_wiring_problems(chain_a, chain_b).await {
+		Ok(problems) if problems.is_empty() =>
+			report.pass("clients, connections and channels reference each other"),
+		Ok(problems) =>
+			for problem in problems {
+				report.fail(problem)
+			},
+		Err(e) => report.fail(format!("can't query the clients, connections and channels: {e:?}")),
+	}
+}
+
+async fn check_client_expiry(
+	source: &AnyChain,
+	client_id: &ClientId,
+	sink: &AnyChain,
+	report: &mut Report,
+) {
+	match query_client_expiry(source, sink).await {
+		Ok(Some(expiry)) => {
+			let check = format!("client {client_id} expires in {:?}", expiry.time_until_expiry);
+			match expiry.status() {
+				ClientExpiryStatus::Healthy => report.pass(check),
+				ClientExpiryStatus::Warning => report.warn(check),
+				ClientExpiryStatus::Critical => report.fail(check),
+				ClientExpiryStatus::Expired => report.fail(format!("client {client_id} expired")),
+			}
+		},
+		Ok(None) => report.pass(format!("client {client_id} doesn't expire")),
+		Err(e) => report.fail(format!("can't query client {client_id}: {e:?}")),
+	}
+}
+
+async fn check_undelivered_packets(
+	(source, source_height): (&AnyChain, Height),
+	(sink, sink_height): (&AnyChain, Height),
+	report: &mut Report,
+) {
+	for (channel_id, port_id) in source.channel_whitelist() {
+		let packets = query_undelivered_sequences(
+			source_height,
+			sink_height,
+			channel_id,
+			port_id.clone(),
+			source,
+			sink,
+		)
+		.await;
+		let acks = query_undelivered_acks(
+			source_height,
+			sink_height,
+			channel_id,
+			port_id.clone(),
+			source,
+			sink,
+		)
+		.await;
+		match (packets, acks) {
+			(Ok(packets), Ok(acks)) if packets.is_empty() && acks.is_empty() =>
+				report.pass(format!("no undelivered packets on {channel_id}/{port_id}")),
+			(Ok(packets), Ok(acks)) => report.warn(format!(
+				"{} undelivered packets and {} undelivered acknowledgements on {channel_id}/{port_id}",
+				packets.len(),
+				acks.len()
+			)),
+			(Err(e), _) | (_, Err(e)) =>
+				report.fail(format!("can't query the packets of {channel_id}/{port_id}: {e:?}")),
+		}
+	}
+}
@@ -14,11 +14,17 @@
 
 #[cfg(feature = "testing")]
 use crate::send_packet_relay::packet_relay_status;
-use crate::Mode;
+use crate::{
+	packets::utils::{construct_ack_messages, construct_recv_messages, retain_unexpired_packets},
+	Mode,
+};
+use anyhow::anyhow;
 use codec::Encode;
 use ibc::{
 	core::{
-		ics02_client::client_state::ClientState as ClientStateT,
+		ics02_client::{
+			client_consensus::ConsensusState as _, client_state::ClientState as ClientStateT,
+		},
 		ics03_connection::{
 			connection::{ConnectionEnd, Counterparty},
 			msgs::{
@@ -27,24 +33,37 @@ use ibc::{
 			},
 		},
 		ics04_channel::{
-			channel::{ChannelEnd, Counterparty as ChannelCounterparty, State},
+			channel::{ChannelEnd, Counterparty as ChannelCounterparty, Order, State},
+			events::{Upgrade as UpgradeEvent, UpgradeStep},
 			msgs::{
-				acknowledgement::MsgAcknowledgement, chan_close_confirm::MsgChannelCloseConfirm,
-				chan_open_ack::MsgChannelOpenAck, chan_open_confirm::MsgChannelOpenConfirm,
-				chan_open_try::MsgChannelOpenTry, recv_packet::MsgRecvPacket,
+				chan_close_confirm::MsgChannelCloseConfirm, chan_open_ack::MsgChannelOpenAck,
+				chan_open_confirm::MsgChannelOpenConfirm, chan_open_try::MsgChannelOpenTry,
+				chan_upgrade_ack::MsgChannelUpgradeAck,
+				chan_upgrade_cancel::MsgChannelUpgradeCancel,
+				chan_upgrade_confirm::MsgChannelUpgradeConfirm,
+				chan_upgrade_open::MsgChannelUpgradeOpen, chan_upgrade_try::MsgChannelUpgradeTry,
 			},
+			upgrade::{ErrorReceipt, Upgrade},
+		},
+		ics23_commitment::{
+			commitment::{CommitmentPrefix, CommitmentProofBytes},
+			merkle::{apply_prefix, MerkleProof},
+		},
+		ics24_host::{
+			identifier::ConnectionId,
+			path::{AcksPath, ChannelUpgradeErrorPath, ChannelUpgradePath, CommitmentsPath},
+			Path,
 		},
-		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes},
-		ics24_host::identifier::ConnectionId,
 	},
 	events::{IbcEvent, IbcEventType},
 	proofs::{ConsensusProof, Proofs},
 	tx_msg::Msg,
 	Height,
 };
-use ibc_proto::google::protobuf::Any;
-use pallet_ibc::light_clients::AnyClientState;
+use ibc_proto::{google::protobuf::Any, ibc::core::commitment::v1::MerkleProof as RawMerkleProof};
+use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState, HostFunctionsManager};
 use primitives::{error::Error, mock::LocalClientTypes, Chain};
+use sp_runtime::traits::BlakeTwo256;
 use std::str::FromStr;
 use tendermint_proto::Protobuf;
 
@@ -65,6 +84,12 @@ pub async fn parse_events(
 	mode: Option<Mode>,
 ) -> Result<Vec<Any>, anyhow::Error> {
 	let mut messages = vec![];
+	// the proofs of the packets and acknowledgements are fetched together
+	let mut recv_packets = vec![];
+	// the timed out packets of ordered channels that allow timeouts are received on purpose, the
+	// sink skips their sequence
+	let mut allow_timeout_packets = vec![];
+	let mut acks = vec![];
 	// 1. translate events to messages
 	for event in events {
 		match event {
@@ -141,9 +166,7 @@ pub async fn parse_events(
 						host_consensus_state_proof,
 					};
 
-					let value = msg.encode_vec()?;
-					let msg = Any { value, type_url: msg.type_url() };
-					messages.push(msg)
+					messages.push(msg.to_any())
 				}
 			},
 			IbcEvent::OpenTryConnection(open_try) => {
@@ -224,9 +247,7 @@ pub async fn parse_events(
 						signer: sink.account_id(),
 					};
 
-					let value = msg.encode_vec()?;
-					let msg = Any { value, type_url: msg.type_url() };
-					messages.push(msg)
+					messages.push(msg.to_any())
 				}
 			},
 			IbcEvent::OpenAckConnection(open_ack) => {
@@ -267,9 +288,7 @@ pub async fn parse_events(
 						signer: sink.account_id(),
 					};
 
-					let value = msg.encode_vec()?;
-					let msg = Any { value, type_url: msg.type_url() };
-					messages.push(msg)
+					messages.push(msg.to_any())
 				}
 			},
 			IbcEvent::OpenInitChannel(open_init) => {
@@ -334,9 +353,7 @@ pub async fn parse_events(
 						signer: sink.account_id(),
 					};
 
-					let value = msg.encode_vec()?;
-					let msg = Any { value, type_url: msg.type_url() };
-					messages.push(msg)
+					messages.push(msg.to_any())
 				}
 			},
 			IbcEvent::OpenTryChannel(open_try) =>
@@ -371,9 +388,7 @@ pub async fn parse_events(
 						signer: sink.account_id(),
 					};
 
-					let value = msg.encode_vec()?;
-					let msg = Any { value, type_url: msg.type_url() };
-					messages.push(msg)
+					messages.push(msg.to_any())
 				},
 			IbcEvent::OpenAckChannel(open_ack) =>
 				if let Some(channel_id) = open_ack.channel_id {
@@ -403,9 +418,7 @@ pub async fn parse_events(
 						signer: sink.account_id(),
 					};
 
-					let value = msg.encode_vec()?;
-					let msg = Any { value, type_url: msg.type_url() };
-					messages.push(msg)
+					messages.push(msg.to_any())
 				},
 			IbcEvent::CloseInitChannel(close_init) => {
 				let channel_id = close_init.channel_id;
@@ -435,10 +448,12 @@ pub async fn parse_events(
 					signer: sink.account_id(),
 				};
 
-				let value = msg.encode_vec()?;
-				let msg = Any { value, type_url: msg.type_url() };
-				messages.push(msg)
+				messages.push(msg.to_any())
 			},
+			IbcEvent::UpgradeChannel(upgrade) =>
+				if let Some(msg) = parse_channel_upgrade(source, sink, upgrade).await? {
+					messages.push(msg)
+				},
 			IbcEvent::SendPacket(send_packet) => {
 				#[cfg(feature = "testing")]
 				if !packet_relay_status() {
@@ -480,7 +495,6 @@ pub async fn parse_events(
 					);
 					continue
 				}
-				let seq = u64::from(send_packet.packet.sequence);
 				let packet = send_packet.packet;
 
 				if packet.timeout_height.is_zero() && packet.timeout_timestamp.nanoseconds() == 0 {
@@ -492,27 +506,12 @@ pub async fn parse_events(
 					continue
 				}
 
-				let packet_commitment_response = source
-					.query_packet_commitment(send_packet.height, &port_id, &channel_id, seq)
-					.await?;
-				let commitment_proof =
-					CommitmentProofBytes::try_from(packet_commitment_response.proof)?;
-
-				let proof_height = packet_commitment_response
-					.proof_height
-					.expect("Proof height should be present");
-				let proof_height =
-					Height::new(proof_height.revision_number, proof_height.revision_height);
-				let msg = MsgRecvPacket {
-					packet: packet.clone(),
-					proofs: Proofs::new(commitment_proof, None, None, None, proof_height)?,
-					signer: sink.account_id(),
-				};
-
-				let value = msg.encode_vec()?;
-				let msg = Any { value, type_url: msg.type_url() };
-				messages.push(msg);
 				log::debug!(target: "hyperspace", "Sending packet {:?}", packet);
+				if channel_end.ordering == Order::OrderedAllowTimeout {
+					allow_timeout_packets.push((packet, send_packet.height));
+				} else {
+					recv_packets.push((packet, send_packet.height));
+				}
 			},
 			IbcEvent::WriteAcknowledgement(write_ack) => {
 				let port_id = &write_ack.packet.destination_port.clone();
@@ -544,35 +543,17 @@ pub async fn parse_events(
 					// We can't send this packet immediately because of connection delays
 					continue
 				}
-				let seq = u64::from(write_ack.packet.sequence);
 				let packet = write_ack.packet;
-				let packet_acknowledgement_response = source
-					.query_packet_acknowledgement(write_ack.height, port_id, channel_id, seq)
-					.await?;
-				let acknowledgement = write_ack.ack;
-				let commitment_proof =
-					CommitmentProofBytes::try_from(packet_acknowledgement_response.proof)?;
-
-				let proof_height = packet_acknowledgement_response
-					.proof_height
-					.expect("Proof height should be present");
-				let proof_height =
-					Height::new(proof_height.revision_number, proof_height.revision_height);
-				let msg = MsgAcknowledgement {
-					packet,
-					acknowledgement: acknowledgement.into(),
-					proofs: Proofs::new(commitment_proof, None, None, None, proof_height)?,
-
-					signer: sink.account_id(),
-				};
-
-				let value = msg.encode_vec()?;
-				let msg = Any { value, type_url: msg.type_url() };
-				messages.push(msg)
+				acks.push((packet, write_ack.ack, write_ack.height));
 			},
 			_ => continue,
 		}
 	}
+	// packets that already timed out are left to the timeout path of the packet relay
+	retain_unexpired_packets(&*sink, &mut recv_packets).await?;
+	recv_packets.append(&mut allow_timeout_packets);
+	messages.extend(construct_recv_messages(&*source, &*sink, recv_packets).await?);
+	messages.extend(construct_ack_messages(&*source, &*sink, acks).await?);
 
 	// In light mode do not try to query channel state
 	if let Some(Mode::Light) = mode {
@@ -582,6 +563,354 @@ pub async fn parse_events(
 	Ok(messages)
 }
 
+/// Number of times a held back packet event is checked before it's dropped, see
+/// [`retain_proven_events`].
+pub const MAX_EVENT_PROOF_ATTEMPTS: u32 = 10;
+
+/// Outcome of checking a packet event against the light client of its chain.
+#[derive(Debug, PartialEq, Eq)]
+enum EventProof {
+	Valid,
+	Invalid,
+	/// The light client wasn't updated to the proof height yet
+	Pending,
+}
+
+/// Drops the packet events that aren't backed by the state of `source`: the commitment of a sent
+/// packet, or the acknowledgement of a received one, must be proven against the consensus state
+/// of the light client of `source` on `sink`. This protects against an rpc node that makes up
+/// events or reports them from a fork. Other events are kept, their messages are built from the
+/// queried state anyway.
+///
+/// Events that can't be checked yet, because the light client isn't updated to their height or a
+/// query failed, are held back for [`recheck_unproven_events`]. Returns the proven events, and
+/// whether any was held back, in which case the client update of their height must be submitted.
+pub async fn retain_proven_events(
+	source: &impl Chain,
+	sink: &impl Chain,
+	events: Vec<IbcEvent>,
+) -> (Vec<IbcEvent>, bool) {
+	let (proven, unproven) = check_events(source, sink, events.into_iter().map(|e| (e, 0))).await;
+	let held_back = !unproven.is_empty();
+	source.common_state().unproven_events.lock().unwrap().extend(unproven);
+	(proven, held_back)
+}
+
+/// Checks the events held back by [`retain_proven_events`] again, returning the ones that are
+/// proven now. Events are dropped after [`MAX_EVENT_PROOF_ATTEMPTS`] checks.
+pub async fn recheck_unproven_events(source: &impl Chain, sink: &impl Chain) -> Vec<IbcEvent> {
+	let held_back = std::mem::take(&mut *source.common_state().unproven_events.lock().unwrap());
+	if held_back.is_empty() {
+		return vec![]
+	}
+	let (proven, unproven) = check_events(source, sink, held_back).await;
+	source.common_state().unproven_events.lock().unwrap().extend(unproven);
+	proven
+}
+
+async fn check_events(
+	source: &impl Chain,
+	sink: &impl Chain,
+	events: impl IntoIterator<Item = (IbcEvent, u32)>,
+) -> (Vec<IbcEvent>, Vec<(IbcEvent, u32)>) {
+	let (mut proven, mut unproven) = (vec![], vec![]);
+	for (event, attempts) in events {
+		match check_event_proof(source, sink, &event).await {
+			Ok(EventProof::Valid) => proven.push(event),
+			Ok(EventProof::Invalid) => {
+				log::warn!(target: "hyperspace", "Dropping event of {} that isn't backed by its state: {event:?}", source.name());
+			},
+			_ if attempts + 1 >= MAX_EVENT_PROOF_ATTEMPTS => {
+				log::warn!(target: "hyperspace", "Dropping event of {} that couldn't be checked against its state {MAX_EVENT_PROOF_ATTEMPTS} times: {event:?}", source.name());
+			},
+			Ok(EventProof::Pending) => unproven.push((event, attempts + 1)),
+			Err(e) => {
+				log::debug!(target: "hyperspace", "Failed to check event of {} against its state, retrying later: {event:?}: {e:?}", source.name());
+				unproven.push((event, attempts + 1));
+			},
+		}
+	}
+	(proven, unproven)
+}
+
+async fn check_event_proof(
+	source: &impl Chain,
+	sink: &impl Chain,
+	event: &IbcEvent,
+) -> Result<EventProof, anyhow::Error> {
+	let (path, value, proof, proof_height): (Path, _, _, _) = match event {
+		IbcEvent::SendPacket(send_packet) => {
+			let packet = &send_packet.packet;
+			let response = source
+				.query_packet_commitment(
+					send_packet.height,
+					&packet.source_port,
+					&packet.source_channel,
+					packet.sequence.into(),
+				)
+				.await?;
+			let path = CommitmentsPath {
+				port_id: packet.source_port.clone(),
+				channel_id: packet.source_channel,
+				sequence: packet.sequence,
+			};
+			let proof_height = response
+				.proof_height
+				.map_or(send_packet.height, |h| Height::new(h.revision_number, h.revision_height));
+			(path.into(), response.commitment, response.proof, proof_height)
+		},
+		IbcEvent::WriteAcknowledgement(write_ack) => {
+			let packet = &write_ack.packet;
+			let response = source
+				.query_packet_acknowledgement(
+					write_ack.height,
+					&packet.destination_port,
+					&packet.destination_channel,
+					packet.sequence.into(),
+				)
+				.await?;
+			let path = AcksPath {
+				port_id: packet.destination_port.clone(),
+				channel_id: packet.destination_channel,
+				sequence: packet.sequence,
+			};
+			let proof_height = response
+				.proof_height
+				.map_or(write_ack.height, |h| Height::new(h.revision_number, h.revision_height));
+			(path.into(), response.acknowledgement, response.proof, proof_height)
+		},
+		_ => return Ok(EventProof::Valid),
+	};
+	if value.is_empty() || proof.is_empty() {
+		return Ok(EventProof::Invalid)
+	}
+
+	// the root the light client of `source` trusts at the proof height
+	let client_id = source.client_id();
+	let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+	let client_state = sink
+		.query_client_state(sink_height, client_id.clone())
+		.await?
+		.client_state
+		.map(AnyClientState::try_from)
+		.ok_or_else(|| {
+			anyhow!("Client state of {} not found on {}", source.name(), sink.name())
+		})??;
+	if client_state.latest_height() < proof_height {
+		return Ok(EventProof::Pending)
+	}
+	let Some(consensus_state) = sink
+		.query_client_consensus(sink_height, client_id, proof_height)
+		.await?
+		.consensus_state
+	else {
+		return Ok(EventProof::Pending)
+	};
+	let consensus_state = AnyConsensusState::try_from(consensus_state)?.unpack_recursive_into();
+	let root = consensus_state.root();
+
+	let prefix = source.connection_prefix();
+	let proof = CommitmentProofBytes::try_from(proof)?;
+	let verified = match client_state.unpack_recursive() {
+		AnyClientState::Tendermint(client_state) => {
+			let merkle_proof: MerkleProof<HostFunctionsManager> = RawMerkleProof::try_from(proof)
+				.map_err(|e| anyhow!("Invalid merkle proof: {e:?}"))?
+				.into();
+			merkle_proof
+				.verify_membership(
+					&client_state.proof_specs,
+					root.clone().into(),
+					apply_prefix(&prefix, vec![path.to_string()]),
+					value,
+					0,
+				)
+				.map_err(|e| anyhow!("{e:?}"))
+		},
+		AnyClientState::Grandpa(_) | AnyClientState::Beefy(_) =>
+			light_client_common::verify_membership::<BlakeTwo256, _>(
+				&prefix, &proof, root, path, value,
+			),
+		// the proofs of other clients can only be checked by the chain hosting them
+		_ => return Ok(EventProof::Valid),
+	};
+	match verified {
+		Ok(()) => Ok(EventProof::Valid),
+		Err(e) => {
+			log::debug!(target: "hyperspace", "Invalid proof of {event:?} at {proof_height}: {e:?}");
+			Ok(EventProof::Invalid)
+		},
+	}
+}
+
+/// Translates a step of the channel upgrade handshake on the source chain into the message that
+/// advances the handshake on the sink chain, if there's one to send.
+async fn parse_channel_upgrade(
+	source: &impl Chain,
+	sink: &impl Chain,
+	upgrade: UpgradeEvent,
+) -> Result<Option<Any>, anyhow::Error> {
+	let Some(counterparty_channel_id) = upgrade.counterparty_channel_id else { return Ok(None) };
+	let counterparty_port_id = upgrade.counterparty_port_id.clone();
+	let channel_response = source
+		.query_channel_end(upgrade.height(), upgrade.channel_id, upgrade.port_id.clone())
+		.await?;
+	let channel_end = ChannelEnd::try_from(channel_response.channel.ok_or_else(|| {
+		Error::Custom(format!(
+			"[get_messages_for_events - upgrade_chan] ChannelEnd not found for {:?}/{:?}",
+			upgrade.channel_id, upgrade.port_id
+		))
+	})?)?;
+	let channel_proof = CommitmentProofBytes::try_from(channel_response.proof)?;
+	let proof_height = channel_response.proof_height.ok_or_else(|| {
+		Error::Custom(
+			"[get_messages_for_events - upgrade_chan] Proof height not found in response"
+				.to_string(),
+		)
+	})?;
+	let proof_height = Height::new(proof_height.revision_number, proof_height.revision_height);
+	// The upgrade is proven at the same height as the channel end, so both proofs are verified
+	// against the same consensus state on the sink.
+	let query_upgrade = || async {
+		let (upgrade_bytes, upgrade_proof) = source
+			.query_value_with_proof(
+				upgrade.height(),
+				ChannelUpgradePath(upgrade.port_id.clone(), upgrade.channel_id).into(),
+			)
+			.await?;
+		let channel_upgrade = Upgrade::decode_vec(&upgrade_bytes.ok_or_else(|| {
+			Error::Custom(format!(
+				"[get_messages_for_events - upgrade_chan] Upgrade not found for {:?}/{:?}",
+				upgrade.channel_id, upgrade.port_id
+			))
+		})?)?;
+		Ok::<_, anyhow::Error>((channel_upgrade, CommitmentProofBytes::try_from(upgrade_proof)?))
+	};
+
+	let msg = match upgrade.step {
+		UpgradeStep::Init => {
+			let (counterparty_upgrade, upgrade_proof) = query_upgrade().await?;
+			let connection_id = counterparty_upgrade
+				.fields
+				.connection_hops
+				.get(0)
+				.ok_or_else(|| Error::Custom("Upgrade missing connection id".to_string()))?
+				.clone();
+			let connection_response =
+				source.query_connection_end(upgrade.height(), connection_id.clone()).await?;
+			let counterparty_connection = connection_response
+				.connection
+				.and_then(|connection| connection.counterparty)
+				.ok_or_else(|| {
+					Error::Custom(format!(
+						"[get_messages_for_events - upgrade_chan_init] Connection counterparty not found for {connection_id:?}"
+					))
+				})?;
+			let msg = MsgChannelUpgradeTry {
+				port_id: counterparty_port_id,
+				channel_id: counterparty_channel_id,
+				proposed_upgrade_connection_hops: vec![ConnectionId::from_str(
+					&counterparty_connection.connection_id,
+				)?],
+				counterparty_upgrade_fields: counterparty_upgrade.fields,
+				counterparty_upgrade_sequence: channel_end.upgrade_sequence,
+				proofs: Proofs::new(channel_proof, None, None, Some(upgrade_proof), proof_height)?,
+				signer: sink.account_id(),
+			};
+			msg.to_any()
+		},
+		UpgradeStep::Try => {
+			let (counterparty_upgrade, upgrade_proof) = query_upgrade().await?;
+			let msg = MsgChannelUpgradeAck {
+				port_id: counterparty_port_id,
+				channel_id: counterparty_channel_id,
+				counterparty_upgrade,
+				proofs: Proofs::new(channel_proof, None, None, Some(upgrade_proof), proof_height)?,
+				signer: sink.account_id(),
+			};
+			msg.to_any()
+		},
+		UpgradeStep::Ack => {
+			let (counterparty_upgrade, upgrade_proof) = query_upgrade().await?;
+			let msg = MsgChannelUpgradeConfirm {
+				port_id: counterparty_port_id,
+				channel_id: counterparty_channel_id,
+				counterparty_channel_state: channel_end.state,
+				counterparty_upgrade,
+				proofs: Proofs::new(channel_proof, None, None, Some(upgrade_proof), proof_height)?,
+				signer: sink.account_id(),
+			};
+			msg.to_any()
+		},
+		// Both ends can only be opened once the counterparty has flushed its packets, and the
+		// end that opens last must not be sent another open.
+		UpgradeStep::Confirm | UpgradeStep::Open => {
+			if !matches!(channel_end.state, State::FlushComplete | State::Open) {
+				return Ok(None)
+			}
+			let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+			let sink_channel = sink
+				.query_channel_end(
+					sink_height,
+					counterparty_channel_id,
+					counterparty_port_id.clone(),
+				)
+				.await?
+				.channel
+				.map(ChannelEnd::try_from)
+				.transpose()?;
+			if !matches!(sink_channel, Some(ref channel) if channel.state != State::Open) {
+				return Ok(None)
+			}
+			let msg = MsgChannelUpgradeOpen {
+				port_id: counterparty_port_id,
+				channel_id: counterparty_channel_id,
+				counterparty_channel_state: channel_end.state,
+				counterparty_upgrade_sequence: channel_end.upgrade_sequence,
+				proofs: Proofs::new(channel_proof, None, None, None, proof_height)?,
+				signer: sink.account_id(),
+			};
+			msg.to_any()
+		},
+		UpgradeStep::Timeout | UpgradeStep::Cancel => {
+			let (receipt_bytes, receipt_proof) = source
+				.query_value_with_proof(
+					upgrade.height(),
+					ChannelUpgradeErrorPath(upgrade.port_id.clone(), upgrade.channel_id).into(),
+				)
+				.await?;
+			let Some(receipt_bytes) = receipt_bytes else { return Ok(None) };
+			// Only an end that is still upgrading can be cancelled
+			let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+			let (sink_upgrade, _) = sink
+				.query_value_with_proof(
+					sink_height,
+					ChannelUpgradePath(counterparty_port_id.clone(), counterparty_channel_id)
+						.into(),
+				)
+				.await?;
+			if sink_upgrade.is_none() {
+				return Ok(None)
+			}
+			let msg = MsgChannelUpgradeCancel {
+				port_id: counterparty_port_id,
+				channel_id: counterparty_channel_id,
+				error_receipt: ErrorReceipt::decode_vec(&receipt_bytes)?,
+				proofs: Proofs::new(
+					CommitmentProofBytes::try_from(receipt_proof)?,
+					None,
+					None,
+					None,
+					proof_height,
+				)?,
+				signer: sink.account_id(),
+			};
+			msg.to_any()
+		},
+	};
+
+	Ok(Some(msg))
+}
+
 /// Fetch the consensus state proof for the sink chain.
 async fn query_host_consensus_state_proof(
 	sink: &impl Chain,
@@ -604,3 +933,43 @@ pub fn has_packet_events(event_types: &[IbcEventType]) -> bool {
 		.iter()
 		.any(|event_type| matches!(event_type, &IbcEventType::SendPacket | &IbcEventType::WriteAck))
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ibc::core::ics04_channel::msgs::recv_packet;
+	use mock::{MockChain, MockConfig};
+	use primitives::IbcEventProvider;
+
+	/// Number of receive messages built from the events of a packet sent by chain A that already
+	/// timed out on chain B, on a channel of ordering `order`.
+	async fn recv_messages_of_expired_packet(order: Order) -> usize {
+		let (mut chain_a, mut chain_b) =
+			MockChain::pair(MockConfig::new("mock-a", 2000), MockConfig::new("mock-b", 2001));
+		chain_a.set_channel_order(order);
+		chain_b.set_channel_order(order);
+		chain_a.send_expired_packet();
+		let height = chain_a.produce_block();
+		let events = chain_a
+			.query_latest_ibc_events(height, &chain_b)
+			.await
+			.unwrap()
+			.into_iter()
+			.flat_map(|(_, _, events, _)| events)
+			.collect();
+
+		let messages = parse_events(&mut chain_a, &mut chain_b, events, None).await.unwrap();
+		messages.iter().filter(|msg| msg.type_url == recv_packet::TYPE_URL).count()
+	}
+
+	#[tokio::test]
+	async fn expired_packets_are_not_received() {
+		assert_eq!(recv_messages_of_expired_packet(Order::Unordered).await, 0);
+		assert_eq!(recv_messages_of_expired_packet(Order::Ordered).await, 0);
+	}
+
+	#[tokio::test]
+	async fn expired_packets_of_ordered_channels_allowing_timeouts_are_received() {
+		assert_eq!(recv_messages_of_expired_packet(Order::OrderedAllowTimeout).await, 1);
+	}
+}
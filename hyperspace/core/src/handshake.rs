@@ -0,0 +1,458 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resumption of the connection and channel handshakes between two chains from the state of their
+//! ends. The steps of a handshake are normally built from the events of the previous step, which
+//! are missed when the light client on the sink was updated past them without the step being
+//! submitted: the relayer was down, or the transaction carrying the step was lost while another
+//! relayer updated the client. The ends still tell which step is missing, and the event it would
+//! have been built from is synthesized again.
+
+use ibc::{
+	core::{
+		ics03_connection::{
+			connection::{ConnectionEnd, IdentifiedConnectionEnd, State as ConnectionState},
+			events as connection_events,
+		},
+		ics04_channel::{
+			channel::{ChannelEnd, IdentifiedChannelEnd, State as ChannelState},
+			events as channel_events,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+	},
+	events::IbcEvent,
+	Height,
+};
+use ibc_proto::google::protobuf::Any;
+use primitives::{Chain, UpdateType};
+use std::{
+	collections::{HashMap, HashSet},
+	time::{Duration, Instant},
+};
+
+/// Number of expected block times of the source between two resumptions of the handshakes.
+pub const RESUME_INTERVAL_BLOCKS: u32 = 50;
+
+/// Number of times the missing step of a handshake is resumed before giving up on it.
+pub const MAX_RESUME_ATTEMPTS: u32 = 5;
+
+/// Progress of one end of a handshake.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EndState {
+	Init,
+	TryOpen,
+	Open,
+	Closed,
+}
+
+impl EndState {
+	/// `None` for an uninitialized connection.
+	pub fn from_connection(state: ConnectionState) -> Option<Self> {
+		match state {
+			ConnectionState::Uninitialized => None,
+			ConnectionState::Init => Some(Self::Init),
+			ConnectionState::TryOpen => Some(Self::TryOpen),
+			ConnectionState::Open => Some(Self::Open),
+		}
+	}
+
+	/// Channels being upgraded are open.
+	pub fn from_channel(state: ChannelState) -> Self {
+		match state {
+			ChannelState::Init => Self::Init,
+			ChannelState::TryOpen => Self::TryOpen,
+			ChannelState::Open | ChannelState::Flushing | ChannelState::FlushComplete => Self::Open,
+			ChannelState::Closed => Self::Closed,
+		}
+	}
+}
+
+/// A step of a handshake, submitted to the sink.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandshakeStep {
+	OpenTry,
+	OpenAck,
+	OpenConfirm,
+}
+
+/// State of both ends of a handshake started on the source or on the sink.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HandshakeState {
+	pub source: EndState,
+	/// `None` until the source knows the end on the sink.
+	pub sink: Option<EndState>,
+}
+
+impl HandshakeState {
+	/// The step the sink is missing, if any. The steps submitted to the source are the ones of the
+	/// handshake seen from the sink.
+	pub fn next_step(&self) -> Option<HandshakeStep> {
+		match (self.source, self.sink) {
+			(EndState::Init, None) => Some(HandshakeStep::OpenTry),
+			(EndState::TryOpen, Some(EndState::Init)) => Some(HandshakeStep::OpenAck),
+			(EndState::Open, Some(EndState::TryOpen)) => Some(HandshakeStep::OpenConfirm),
+			_ => None,
+		}
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.source == EndState::Open && self.sink == Some(EndState::Open)
+	}
+}
+
+/// The end of a handshake on the source.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HandshakeEnd {
+	Connection(ConnectionId),
+	Channel(PortId, ChannelId),
+}
+
+impl HandshakeEnd {
+	/// The end `event` was emitted for, if it is a handshake event.
+	pub fn of_event(event: &IbcEvent) -> Option<Self> {
+		let end = match event {
+			IbcEvent::OpenInitConnection(ev) => Self::Connection(ev.connection_id()?.clone()),
+			IbcEvent::OpenTryConnection(ev) => Self::Connection(ev.connection_id()?.clone()),
+			IbcEvent::OpenAckConnection(ev) => Self::Connection(ev.connection_id()?.clone()),
+			IbcEvent::OpenConfirmConnection(ev) => Self::Connection(ev.connection_id()?.clone()),
+			IbcEvent::OpenInitChannel(ev) => Self::Channel(ev.port_id.clone(), ev.channel_id?),
+			IbcEvent::OpenTryChannel(ev) => Self::Channel(ev.port_id.clone(), ev.channel_id?),
+			IbcEvent::OpenAckChannel(ev) => Self::Channel(ev.port_id.clone(), ev.channel_id?),
+			IbcEvent::OpenConfirmChannel(ev) => Self::Channel(ev.port_id.clone(), ev.channel_id?),
+			_ => return None,
+		};
+		Some(end)
+	}
+}
+
+/// A handshake the sink is missing a step of.
+#[derive(Clone, Debug)]
+pub struct PendingHandshake {
+	pub end: HandshakeEnd,
+	pub state: HandshakeState,
+	pub step: HandshakeStep,
+	/// The event of the source the step is built from, emitted at the height the handshakes were
+	/// queried at.
+	pub event: IbcEvent,
+}
+
+/// The handshakes of the connections between `source` and `sink`, and of the channels of their
+/// open connections, that `sink` is missing a step of. The ends on the source are queried at
+/// `at`, the ones on the sink at its latest height. The end on the sink of a handshake started on
+/// the source is looked up by its counterparty, as the source only learns its identifier with the
+/// ack.
+pub async fn pending_handshakes<A: Chain, B: Chain>(
+	source: &A,
+	sink: &B,
+	at: Height,
+) -> anyhow::Result<Vec<PendingHandshake>> {
+	let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+	let connections = source
+		.query_connection_using_client(at.revision_height as u32, sink.client_id().to_string())
+		.await?;
+	let sink_connections = sink
+		.query_connection_using_client(
+			sink_height.revision_height as u32,
+			source.client_id().to_string(),
+		)
+		.await?
+		.into_iter()
+		.map(IdentifiedConnectionEnd::try_from)
+		.collect::<Result<Vec<_>, _>>()?;
+
+	let mut pending = vec![];
+	for connection in connections {
+		let IdentifiedConnectionEnd { connection_id, connection_end } = connection.try_into()?;
+		let Some(source_state) = EndState::from_connection(*connection_end.state()) else {
+			continue
+		};
+		let counterparty_id = connection_end.counterparty().connection_id();
+		let sink_state = sink_connections
+			.iter()
+			.find(|sink_connection| match counterparty_id {
+				Some(counterparty_id) => sink_connection.connection_id == *counterparty_id,
+				None =>
+					sink_connection.connection_end.counterparty().connection_id() ==
+						Some(&connection_id),
+			})
+			.and_then(|sink_connection| {
+				EndState::from_connection(*sink_connection.connection_end.state())
+			});
+		let state = HandshakeState { source: source_state, sink: sink_state };
+		if let Some(step) = state.next_step() {
+			let event = connection_event(step, at, &connection_id, &connection_end);
+			let end = HandshakeEnd::Connection(connection_id.clone());
+			pending.push(PendingHandshake { end, state, step, event });
+		}
+		let (EndState::Open, Some(counterparty_id)) = (source_state, counterparty_id) else {
+			continue
+		};
+
+		let sink_channels = sink
+			.query_all_connection_channels(sink_height, counterparty_id)
+			.await?
+			.into_iter()
+			.map(IdentifiedChannelEnd::try_from)
+			.collect::<Result<Vec<_>, _>>()?;
+		for channel in source.query_all_connection_channels(at, &connection_id).await? {
+			let IdentifiedChannelEnd { port_id, channel_id, channel_end } = channel.try_into()?;
+			let counterparty = channel_end.counterparty();
+			let sink_state = sink_channels
+				.iter()
+				.find(|sink_channel| match counterparty.channel_id() {
+					Some(counterparty_id) =>
+						sink_channel.channel_id == *counterparty_id &&
+							sink_channel.port_id == *counterparty.port_id(),
+					None => {
+						let sink_counterparty = sink_channel.channel_end.counterparty();
+						sink_counterparty.channel_id() == Some(&channel_id) &&
+							*sink_counterparty.port_id() == port_id
+					},
+				})
+				.map(|sink_channel| EndState::from_channel(*sink_channel.channel_end.state()));
+			let state = HandshakeState {
+				source: EndState::from_channel(*channel_end.state()),
+				sink: sink_state,
+			};
+			if let Some(step) = state.next_step() {
+				let event = channel_event(step, at, &port_id, channel_id, &channel_end);
+				let end = HandshakeEnd::Channel(port_id, channel_id);
+				pending.push(PendingHandshake { end, state, step, event });
+			}
+		}
+	}
+	Ok(pending)
+}
+
+/// The event of the connection end on the source the `step` is built from.
+fn connection_event(
+	step: HandshakeStep,
+	height: Height,
+	connection_id: &ConnectionId,
+	connection_end: &ConnectionEnd,
+) -> IbcEvent {
+	let attributes = connection_events::Attributes {
+		height,
+		connection_id: Some(connection_id.clone()),
+		client_id: connection_end.client_id().clone(),
+		counterparty_connection_id: connection_end.counterparty().connection_id().cloned(),
+		counterparty_client_id: connection_end.counterparty().client_id().clone(),
+	};
+	match step {
+		HandshakeStep::OpenTry =>
+			IbcEvent::OpenInitConnection(connection_events::OpenInit(attributes)),
+		HandshakeStep::OpenAck =>
+			IbcEvent::OpenTryConnection(connection_events::OpenTry(attributes)),
+		HandshakeStep::OpenConfirm =>
+			IbcEvent::OpenAckConnection(connection_events::OpenAck(attributes)),
+	}
+}
+
+/// The event of the channel end on the source the `step` is built from.
+fn channel_event(
+	step: HandshakeStep,
+	height: Height,
+	port_id: &PortId,
+	channel_id: ChannelId,
+	channel_end: &ChannelEnd,
+) -> IbcEvent {
+	let port_id = port_id.clone();
+	let channel_id = Some(channel_id);
+	let connection_id = channel_end.connection_hops()[0].clone();
+	let counterparty_port_id = channel_end.counterparty().port_id().clone();
+	let counterparty_channel_id = channel_end.counterparty().channel_id().copied();
+	match step {
+		HandshakeStep::OpenTry => IbcEvent::OpenInitChannel(channel_events::OpenInit {
+			height,
+			port_id,
+			channel_id,
+			connection_id,
+			counterparty_port_id,
+			counterparty_channel_id,
+		}),
+		HandshakeStep::OpenAck => IbcEvent::OpenTryChannel(channel_events::OpenTry {
+			height,
+			port_id,
+			channel_id,
+			connection_id,
+			counterparty_port_id,
+			counterparty_channel_id,
+		}),
+		HandshakeStep::OpenConfirm => IbcEvent::OpenAckChannel(channel_events::OpenAck {
+			height,
+			port_id,
+			channel_id,
+			counterparty_channel_id,
+			connection_id,
+			counterparty_port_id,
+		}),
+	}
+}
+
+/// Resumes the handshakes from a source to a sink that are stalled: missing the same step at two
+/// checks `interval` apart, the first one on the first finality event. A step in flight, or
+/// whose event is yet to be relayed, is never built twice this way. A step that is resumed without
+/// the handshake making progress is retried after twice as many checks as the previous time, and
+/// given up on after [`MAX_RESUME_ATTEMPTS`].
+pub struct HandshakeResumer {
+	interval: Duration,
+	last_check: Option<Instant>,
+	/// The handshakes missing a step at the last check.
+	pending: HashMap<HandshakeEnd, Stalled>,
+}
+
+/// A handshake missing the same step since it was first seen stalled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Stalled {
+	state: HandshakeState,
+	/// Number of times the missing step was resumed.
+	attempts: u32,
+	/// Number of checks left before the next attempt.
+	backoff: u32,
+}
+
+impl HandshakeResumer {
+	pub fn new(interval: Duration) -> Self {
+		Self { interval, last_check: None, pending: HashMap::new() }
+	}
+
+	/// Checks the handshakes every [`RESUME_INTERVAL_BLOCKS`] blocks of `source`.
+	pub fn for_source(source: &impl Chain) -> Self {
+		Self::new(source.expected_block_time() * RESUME_INTERVAL_BLOCKS)
+	}
+
+	pub fn is_due(&self) -> bool {
+		self.last_check.map_or(true, |last| last.elapsed() >= self.interval)
+	}
+
+	/// Adds the events of the steps of the stalled handshakes to the last of the `updates` of
+	/// `source`, proven at its height. The handshakes the updates carry a step of aren't stalled.
+	pub async fn resume<A: Chain, B: Chain>(
+		&mut self,
+		source: &A,
+		sink: &B,
+		updates: &mut [(Any, Height, Vec<IbcEvent>, UpdateType)],
+	) -> anyhow::Result<()> {
+		let relayed = updates
+			.iter()
+			.flat_map(|(_, _, events, _)| events)
+			.filter_map(HandshakeEnd::of_event)
+			.collect::<HashSet<_>>();
+		let Some((_, height, events, _)) = updates.last_mut() else { return Ok(()) };
+		self.last_check = Some(Instant::now());
+		let handshakes = pending_handshakes(source, sink, *height).await?;
+		let resumed = self.check(
+			handshakes.iter().map(|handshake| (handshake.end.clone(), handshake.state)),
+			&relayed,
+		);
+		for handshake in handshakes {
+			let Some(attempt) = resumed.get(&handshake.end) else { continue };
+			log::info!(
+				target: "hyperspace",
+				"Resuming the stalled handshake of {:?} from {} to {} (attempt {attempt}/{MAX_RESUME_ATTEMPTS}): {:?}, next step {:?}",
+				handshake.end, source.name(), sink.name(), handshake.state, handshake.step
+			);
+			if *attempt == MAX_RESUME_ATTEMPTS {
+				log::warn!(
+					target: "hyperspace",
+					"Giving up on the handshake of {:?} from {} to {} if this attempt fails",
+					handshake.end, source.name(), sink.name()
+				);
+			}
+			events.push(handshake.event);
+		}
+		Ok(())
+	}
+
+	/// Records the handshakes missing a step at this check, returns the ones to resume now with
+	/// the number of the attempt. Handshakes whose step is `relayed` keep their attempts.
+	fn check(
+		&mut self,
+		handshakes: impl IntoIterator<Item = (HandshakeEnd, HandshakeState)>,
+		relayed: &HashSet<HandshakeEnd>,
+	) -> HashMap<HandshakeEnd, u32> {
+		let mut previous = std::mem::take(&mut self.pending);
+		let mut resumed = HashMap::new();
+		for (end, state) in handshakes {
+			let stalled = match previous.remove(&end) {
+				Some(stalled) if stalled.state == state => stalled,
+				// first seen, or the handshake made progress since the last check
+				_ => {
+					self.pending.insert(end, Stalled { state, attempts: 0, backoff: 0 });
+					continue
+				},
+			};
+			let stalled = if relayed.contains(&end) || stalled.attempts >= MAX_RESUME_ATTEMPTS {
+				stalled
+			} else if stalled.backoff > 0 {
+				Stalled { backoff: stalled.backoff - 1, ..stalled }
+			} else {
+				let attempts = stalled.attempts + 1;
+				resumed.insert(end.clone(), attempts);
+				Stalled { state, attempts, backoff: 2u32.pow(attempts) - 1 }
+			};
+			self.pending.insert(end, stalled);
+		}
+		resumed
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn stalled() -> (HandshakeEnd, HandshakeState) {
+		let end = HandshakeEnd::Connection(ConnectionId::new(0));
+		(end, HandshakeState { source: EndState::Init, sink: None })
+	}
+
+	/// The checks of a handshake stalled in the same state at which its step is resumed.
+	fn resumed_at(resumer: &mut HandshakeResumer, checks: usize) -> Vec<(usize, u32)> {
+		let (end, _) = stalled();
+		(0..checks)
+			.filter_map(|check| {
+				let attempt = resumer.check([stalled()], &HashSet::new()).get(&end).copied()?;
+				Some((check, attempt))
+			})
+			.collect()
+	}
+
+	#[test]
+	fn backs_off_and_gives_up_on_stalled_handshakes() {
+		let mut resumer = HandshakeResumer::new(Duration::ZERO);
+		assert_eq!(resumed_at(&mut resumer, 64), vec![(1, 1), (3, 2), (7, 3), (15, 4), (31, 5)]);
+	}
+
+	#[test]
+	fn progress_resets_the_attempts() {
+		let mut resumer = HandshakeResumer::new(Duration::ZERO);
+		assert_eq!(resumed_at(&mut resumer, 4), vec![(1, 1), (3, 2)]);
+		let (end, state) = stalled();
+		let progressed = HandshakeState { source: EndState::TryOpen, sink: Some(EndState::Init) };
+		assert!(resumer.check([(end.clone(), progressed)], &HashSet::new()).is_empty());
+		assert!(resumer.check([(end.clone(), state)], &HashSet::new()).is_empty());
+		assert_eq!(resumed_at(&mut resumer, 1), vec![(0, 1)]);
+	}
+
+	#[test]
+	fn relayed_steps_are_not_resumed() {
+		let mut resumer = HandshakeResumer::new(Duration::ZERO);
+		let (end, _) = stalled();
+		let relayed = [end.clone()].into_iter().collect();
+		for _ in 0..4 {
+			assert!(resumer.check([stalled()], &relayed).is_empty());
+		}
+		assert_eq!(resumed_at(&mut resumer, 1), vec![(0, 1)]);
+	}
+}
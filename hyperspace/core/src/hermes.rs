@@ -0,0 +1,487 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversion of a Hermes `config.toml` into hyperspace configs, so that operators of Hermes can
+//! move a path over without rewriting the chain settings by hand. Only the settings hyperspace
+//! has a counterpart for are carried over, everything else is reported as skipped.
+
+use crate::{
+	chain::{AnyConfig, CoreConfig},
+	keys,
+};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Default of `max_gas` in Hermes.
+const HERMES_DEFAULT_MAX_GAS: u64 = 400_000;
+/// Default of `max_tx_size` in Hermes.
+const HERMES_DEFAULT_MAX_TX_SIZE: u64 = 180_000;
+
+#[derive(Debug, Clone, Parser)]
+pub struct ImportHermesCmd {
+	/// Path of the Hermes config, usually `$HOME/.hermes/config.toml`.
+	#[clap(long)]
+	hermes_config: PathBuf,
+	/// Id of the Hermes chain written to `out_config_a`, can be omitted if Hermes has two chains.
+	#[clap(long)]
+	chain_a: Option<String>,
+	/// Id of the Hermes chain written to `out_config_b`, can be omitted if Hermes has two chains.
+	#[clap(long)]
+	chain_b: Option<String>,
+	/// Keyring the Hermes keys are expected in, defaults to `$HOME/.hyperspace/keys`.
+	#[clap(long)]
+	keyring: Option<PathBuf>,
+	/// Path the chain A config is written to.
+	#[clap(long)]
+	out_config_a: PathBuf,
+	/// Path the chain B config is written to.
+	#[clap(long)]
+	out_config_b: PathBuf,
+	/// Path the core config is written to.
+	#[clap(long)]
+	out_config_core: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+struct HermesConfig {
+	#[serde(default)]
+	global: HermesGlobal,
+	#[serde(default)]
+	telemetry: HermesTelemetry,
+	#[serde(default)]
+	chains: Vec<HermesChain>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct HermesGlobal {
+	log_level: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct HermesTelemetry {
+	#[serde(default)]
+	enabled: bool,
+	host: Option<String>,
+	port: Option<u16>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HermesChain {
+	id: String,
+	#[serde(rename = "type")]
+	chain_type: Option<String>,
+	rpc_addr: String,
+	grpc_addr: String,
+	websocket_addr: Option<String>,
+	event_source: Option<HermesEventSource>,
+	account_prefix: String,
+	key_name: String,
+	store_prefix: Option<String>,
+	max_gas: Option<u64>,
+	gas_price: Option<HermesGasPrice>,
+	fee_granter: Option<String>,
+	max_tx_size: Option<u64>,
+	trusting_period: Option<String>,
+	clock_drift: Option<String>,
+	packet_filter: Option<HermesPacketFilter>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HermesEventSource {
+	mode: String,
+	url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HermesGasPrice {
+	price: f64,
+	denom: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct HermesPacketFilter {
+	#[serde(default = "default_packet_filter_policy")]
+	policy: String,
+	/// `[port, channel]` pairs, possibly with wildcards.
+	#[serde(default)]
+	list: Vec<(String, String)>,
+}
+
+fn default_packet_filter_policy() -> String {
+	"allowall".to_string()
+}
+
+impl ImportHermesCmd {
+	pub async fn run(&self) -> Result<()> {
+		let content = tokio::fs::read_to_string(&self.hermes_config).await?;
+		let hermes: HermesConfig = toml::from_str(&content)
+			.map_err(|e| anyhow!("{} isn't a Hermes config: {e}", self.hermes_config.display()))?;
+		let keyring = match &self.keyring {
+			Some(keyring) => keyring.clone(),
+			None => keys::default_keyring()?,
+		};
+
+		let (chain_a, chain_b) = self.select_chains(&hermes)?;
+		let mut skipped = vec![];
+		let config_a = convert_chain(chain_a, &keyring, &mut skipped)?;
+		let config_b = convert_chain(chain_b, &keyring, &mut skipped)?;
+		let config_core = convert_core(&hermes)?;
+
+		for path in [&self.out_config_a, &self.out_config_b, &self.out_config_core] {
+			if path.exists() {
+				return Err(anyhow!("{} already exists, pick another output path", path.display()))
+			}
+		}
+		write_config(&self.out_config_a, &config_a).await?;
+		write_config(&self.out_config_b, &config_b).await?;
+		write_config(&self.out_config_core, &config_core).await?;
+
+		println!(
+			"Imported {} into {} and {} into {}",
+			chain_a.id,
+			self.out_config_a.display(),
+			chain_b.id,
+			self.out_config_b.display()
+		);
+		for skipped in skipped {
+			println!("  skipped: {skipped}");
+		}
+		for chain in [chain_a, chain_b] {
+			println!(
+				"Hermes keyrings don't keep the mnemonic, add the key of {} with `hyperspace keys add {}`",
+				chain.id, chain.key_name
+			);
+		}
+		println!("Then run `create-clients` or set the `client_id` and `connection_id` of the existing path in the configs");
+		Ok(())
+	}
+
+	/// Picks the chains given on the command line, or the only two chains of the Hermes config.
+	fn select_chains<'a>(
+		&self,
+		hermes: &'a HermesConfig,
+	) -> Result<(&'a HermesChain, &'a HermesChain)> {
+		let find = |id: &str| {
+			hermes
+				.chains
+				.iter()
+				.find(|chain| chain.id == id)
+				.ok_or_else(|| anyhow!("Chain {id} isn't in the Hermes config"))
+		};
+		match (&self.chain_a, &self.chain_b, hermes.chains.as_slice()) {
+			(Some(a), Some(b), _) => Ok((find(a)?, find(b)?)),
+			(None, None, [a, b]) => Ok((a, b)),
+			_ => Err(anyhow!(
+				"The Hermes config has {} chains, pick the path with `--chain-a` and `--chain-b`",
+				hermes.chains.len()
+			)),
+		}
+	}
+}
+
+fn convert_chain(
+	chain: &HermesChain,
+	keyring: &Path,
+	skipped: &mut Vec<String>,
+) -> Result<AnyConfig> {
+	match chain.chain_type.as_deref() {
+		None | Some("CosmosSdk") => {},
+		Some(chain_type) =>
+			return Err(anyhow!(
+				"Chain {} has type {chain_type}, only CosmosSdk chains can be imported",
+				chain.id
+			)),
+	}
+
+	let mut config = Table::new();
+	config.insert("type".into(), "cosmos".into());
+	config.insert("name".into(), chain.id.clone().into());
+	config.insert("chain_id".into(), chain.id.clone().into());
+	config.insert("rpc_url".into(), chain.rpc_addr.clone().into());
+	config.insert("grpc_url".into(), chain.grpc_addr.clone().into());
+	config.insert("websocket_url".into(), websocket_url(chain)?.into());
+	config.insert("account_prefix".into(), chain.account_prefix.clone().into());
+	config.insert(
+		"store_prefix".into(),
+		chain.store_prefix.clone().unwrap_or_else(|| "ibc".to_string()).into(),
+	);
+	config.insert(
+		"max_tx_size".into(),
+		to_integer(chain.max_tx_size.unwrap_or(HERMES_DEFAULT_MAX_TX_SIZE))?,
+	);
+
+	// hyperspace pays a fixed fee for `gas_limit` instead of simulating every transaction.
+	let gas_limit = chain.max_gas.unwrap_or(HERMES_DEFAULT_MAX_GAS);
+	config.insert("gas_limit".into(), to_integer(gas_limit)?);
+	if let Some(gas_price) = &chain.gas_price {
+		let fee_amount = (gas_price.price * gas_limit as f64).ceil() as u128;
+		config.insert("fee_denom".into(), gas_price.denom.clone().into());
+		config.insert("fee_amount".into(), fee_amount.to_string().into());
+	}
+	if let Some(fee_granter) = &chain.fee_granter {
+		config.insert("fee_granter".into(), fee_granter.clone().into());
+	}
+	if let Some(trusting_period) = &chain.trusting_period {
+		config.insert("trusting_period_secs".into(), to_integer(parse_duration(trusting_period)?)?);
+	}
+	if let Some(clock_drift) = &chain.clock_drift {
+		config.insert("max_clock_drift_secs".into(), to_integer(parse_duration(clock_drift)?)?);
+	}
+
+	let channel_whitelist = match &chain.packet_filter {
+		Some(filter) if filter.policy == "allow" => filter
+			.list
+			.iter()
+			.filter_map(|(port_id, channel_id)| {
+				if port_id.contains('*') || channel_id.contains('*') {
+					skipped.push(format!(
+						"wildcard packet filter {port_id}/{channel_id} of {}, list the channels explicitly",
+						chain.id
+					));
+					return None
+				}
+				Some(Value::Array(vec![channel_id.clone().into(), port_id.clone().into()]))
+			})
+			.collect(),
+		Some(filter) => {
+			skipped.push(format!(
+				"packet filter policy `{}` of {}, hyperspace only relays whitelisted channels",
+				filter.policy, chain.id
+			));
+			vec![]
+		},
+		None => vec![],
+	};
+	config.insert("channel_whitelist".into(), Value::Array(channel_whitelist));
+
+	let keystore = keys::key_path(keyring, &chain.key_name)?;
+	config.insert("keystore".into(), keystore.display().to_string().into());
+
+	Value::Table(config)
+		.try_into()
+		.map_err(|e| anyhow!("Can't convert chain {}: {e}", chain.id))
+}
+
+fn convert_core(hermes: &HermesConfig) -> Result<CoreConfig> {
+	let mut config = Table::new();
+	if hermes.telemetry.enabled {
+		let host = hermes.telemetry.host.as_deref().unwrap_or("127.0.0.1");
+		let port = hermes.telemetry.port.unwrap_or(3001);
+		config.insert("prometheus_endpoint".into(), format!("http://{host}:{port}").into());
+	}
+	if let Some(log_level) = &hermes.global.log_level {
+		config.insert("log_level".into(), log_level.clone().into());
+	}
+	Value::Table(config)
+		.try_into()
+		.map_err(|e| anyhow!("Can't convert the global settings: {e}"))
+}
+
+/// Events are either pushed over the websocket of the Hermes config, or pulled from the rpc in
+/// which case the websocket is derived from the rpc address.
+fn websocket_url(chain: &HermesChain) -> Result<String> {
+	match &chain.event_source {
+		Some(HermesEventSource { mode, url: Some(url) }) if mode == "push" =>
+			return Ok(url.clone()),
+		Some(_) => {},
+		None =>
+			if let Some(url) = &chain.websocket_addr {
+				return Ok(url.clone())
+			},
+	}
+	let rpc = chain.rpc_addr.trim_end_matches('/');
+	let url = match rpc.split_once("://") {
+		Some(("https", rest)) => format!("wss://{rest}/websocket"),
+		Some(("http", rest)) => format!("ws://{rest}/websocket"),
+		_ => return Err(anyhow!("Can't derive the websocket of {} from {rpc}", chain.id)),
+	};
+	Ok(url)
+}
+
+/// Parses the durations of Hermes, such as `14days`, `336h` or `1h 30m`, into seconds. The configs
+/// of hyperspace take whole seconds, durations with a fraction of a second are rejected rather
+/// than rounded.
+fn parse_duration(duration: &str) -> Result<u64> {
+	let invalid = || anyhow!("Invalid duration {duration}");
+	let mut millis = 0u64;
+	let mut rest = duration.trim();
+	if rest.is_empty() {
+		return Err(invalid())
+	}
+	while !rest.is_empty() {
+		let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+		let value = rest[..digits].parse::<u64>().map_err(|_| invalid())?;
+		rest = &rest[digits..];
+		let unit_len = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+		let unit: u64 = match &rest[..unit_len] {
+			"ms" | "msec" | "millis" => 1,
+			"s" | "sec" | "secs" | "second" | "seconds" => 1000,
+			"m" | "min" | "mins" | "minute" | "minutes" => 60 * 1000,
+			"h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60 * 1000,
+			"d" | "day" | "days" => 24 * 60 * 60 * 1000,
+			"w" | "week" | "weeks" => 7 * 24 * 60 * 60 * 1000,
+			_ => return Err(invalid()),
+		};
+		millis = value
+			.checked_mul(unit)
+			.and_then(|value| millis.checked_add(value))
+			.ok_or_else(|| anyhow!("Duration {duration} is too long"))?;
+		rest = rest[unit_len..].trim_start();
+	}
+	if millis % 1000 != 0 {
+		return Err(anyhow!("Duration {duration} isn't a whole number of seconds"))
+	}
+	Ok(millis / 1000)
+}
+
+fn to_integer(value: u64) -> Result<Value> {
+	Ok(Value::Integer(i64::try_from(value)?))
+}
+
+async fn write_config<T: serde::Serialize>(path: &Path, config: &T) -> Result<()> {
+	tokio::fs::write(path, toml::to_string(config)?).await?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn chain(rpc_addr: &str, settings: &str) -> HermesChain {
+		toml::from_str(&format!(
+			r#"
+			id = "osmosis-1"
+			rpc_addr = "{rpc_addr}"
+			grpc_addr = "http://localhost:9090"
+			account_prefix = "osmo"
+			key_name = "relayer"
+			{settings}
+			"#
+		))
+		.unwrap()
+	}
+
+	#[test]
+	fn parses_the_durations_of_hermes() {
+		let cases = [
+			("14days", Some(14 * 24 * 60 * 60)),
+			("336h", Some(336 * 60 * 60)),
+			("1h 30m", Some(90 * 60)),
+			("1h30m", Some(90 * 60)),
+			(" 5s ", Some(5)),
+			("2w", Some(14 * 24 * 60 * 60)),
+			("3000ms", Some(3)),
+			("1s 1000ms", Some(2)),
+			("500ms", None),
+			("1s 500ms", None),
+			("", None),
+			("10", None),
+			("s", None),
+			("10 parsecs", None),
+			("10parsecs", None),
+			("99999999999999999w", None),
+			("18446744073709551615s", None),
+			("18446744073709551615ms 1ms", None),
+		];
+		for (duration, secs) in cases {
+			assert_eq!(parse_duration(duration).ok(), secs, "{duration}");
+		}
+	}
+
+	#[test]
+	fn websocket_follows_the_event_source() {
+		let cases = [
+			("https://rpc.osmosis.zone/", "", Some("wss://rpc.osmosis.zone/websocket")),
+			("http://localhost:26657", "", Some("ws://localhost:26657/websocket")),
+			(
+				"http://localhost:26657",
+				r#"websocket_addr = "ws://node:26657/websocket""#,
+				Some("ws://node:26657/websocket"),
+			),
+			(
+				"http://localhost:26657",
+				r#"event_source = { mode = "push", url = "ws://node:26657/websocket" }"#,
+				Some("ws://node:26657/websocket"),
+			),
+			(
+				"http://localhost:26657",
+				r#"event_source = { mode = "pull", url = "ws://node:26657/websocket" }"#,
+				Some("ws://localhost:26657/websocket"),
+			),
+			("tcp://localhost:26657", "", None),
+		];
+		for (rpc_addr, settings, url) in cases {
+			let url = url.map(str::to_string);
+			assert_eq!(
+				websocket_url(&chain(rpc_addr, settings)).ok(),
+				url,
+				"{rpc_addr} {settings}"
+			);
+		}
+	}
+
+	#[cfg(feature = "cosmos")]
+	#[test]
+	fn converts_cosmos_chains() {
+		use ibc::core::ics24_host::identifier::{ChannelId, PortId};
+
+		let chain = chain(
+			"http://localhost:26657",
+			r#"
+			max_gas = 1000000
+			gas_price = { price = 0.25, denom = "uosmo" }
+			trusting_period = "14days"
+			clock_drift = "5s"
+			packet_filter = { policy = "allow", list = [["transfer", "channel-0"], ["transfer", "channel-*"]] }
+			"#,
+		);
+		let keyring = Path::new("/keys");
+		let mut skipped = vec![];
+		let AnyConfig::Cosmos(config) = convert_chain(&chain, keyring, &mut skipped).unwrap()
+		else {
+			panic!("osmosis-1 isn't a cosmos chain")
+		};
+		assert_eq!(config.chain_id, "osmosis-1");
+		assert_eq!(config.websocket_url.as_str(), "ws://localhost:26657/websocket");
+		assert_eq!(config.store_prefix, "ibc");
+		assert_eq!(config.max_tx_size, HERMES_DEFAULT_MAX_TX_SIZE as usize);
+		assert_eq!(config.gas_limit, 1_000_000);
+		assert_eq!((config.fee_denom.as_str(), config.fee_amount.as_str()), ("uosmo", "250000"));
+		assert_eq!(config.trusting_period_secs, 14 * 24 * 60 * 60);
+		assert_eq!(config.max_clock_drift_secs, 5);
+		assert_eq!(config.channel_whitelist, vec![(ChannelId::new(0), PortId::transfer())]);
+		assert_eq!(config.keystore, Some(keys::key_path(keyring, "relayer").unwrap()));
+		// the wildcard can't be carried over
+		assert_eq!(skipped.len(), 1);
+	}
+
+	#[test]
+	fn rejects_chains_that_cant_be_converted() {
+		let mut skipped = vec![];
+		let keyring = Path::new("/keys");
+		let cases = [
+			("http://localhost:26657", r#"type = "Namada""#),
+			("http://localhost:26657", r#"trusting_period = "500ms""#),
+			("http://localhost:26657", r#"clock_drift = "forever""#),
+			("tcp://localhost:26657", ""),
+		];
+		for (rpc_addr, settings) in cases {
+			let chain = chain(rpc_addr, settings);
+			assert!(convert_chain(&chain, keyring, &mut skipped).is_err(), "{rpc_addr} {settings}");
+		}
+	}
+}
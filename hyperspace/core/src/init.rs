@@ -0,0 +1,313 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interactive setup of a pair of chain configs: reuses the clients, connection and channels that
+//! already exist between both chains, creates the missing ones and writes the configs.
+
+use crate::{
+	chain::{AnyChain, AnyConfig, CoreConfig},
+	command::write_config,
+	interpolation, relay,
+	shutdown::Shutdown,
+	Mode,
+};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use ibc::core::{
+	ics04_channel::channel::Order,
+	ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+};
+use primitives::{
+	utils::{create_channel, create_clients, create_connection},
+	Chain, IbcQueryProvider,
+};
+use std::{
+	io::{BufRead, Write},
+	path::Path,
+	str::FromStr,
+	time::Duration,
+};
+
+/// State of an open connection or channel end in the proto encoding.
+const STATE_OPEN: i32 = 3;
+
+#[derive(Debug, Clone, Parser)]
+pub struct InitCmd {
+	/// Relayer chain A config path, with at least the endpoints and the signing key of the chain.
+	#[clap(long)]
+	config_a: String,
+	/// Relayer chain B config path, with at least the endpoints and the signing key of the chain.
+	#[clap(long)]
+	config_b: String,
+	/// Relayer core config path, written with the defaults if it doesn't exist yet.
+	#[clap(long)]
+	config_core: String,
+	/// Create every missing object without asking.
+	#[clap(long)]
+	yes: bool,
+}
+
+impl InitCmd {
+	pub async fn run(&self) -> Result<()> {
+		let mut config_a: AnyConfig = interpolation::read_config(&self.config_a).await?;
+		let mut config_b: AnyConfig = interpolation::read_config(&self.config_b).await?;
+		let mut chain_a = config_a.clone().into_client().await?;
+		let mut chain_b = config_b.clone().into_client().await?;
+
+		// clients
+		if config_a.client_id().is_none() || config_b.client_id().is_none() {
+			let client_a = match config_a.client_id() {
+				Some(client_id) => Some(client_id),
+				None => self.choose_existing_client(&chain_a, &chain_b).await?,
+			};
+			let client_b = match config_b.client_id() {
+				Some(client_id) => Some(client_id),
+				None => self.choose_existing_client(&chain_b, &chain_a).await?,
+			};
+			let (client_a, client_b) = match (client_a, client_b) {
+				(Some(client_a), Some(client_b)) => (client_a, client_b),
+				_ => {
+					self.confirm("Create new light clients on both chains?")?;
+					create_clients(&mut chain_a, &mut chain_b).await?
+				},
+			};
+			println!("Client of {} on {}: {client_a}", chain_a.name(), chain_b.name());
+			println!("Client of {} on {}: {client_b}", chain_b.name(), chain_a.name());
+			chain_a.set_client_id(client_a.clone());
+			chain_b.set_client_id(client_b.clone());
+			config_a.set_client_id(client_a);
+			config_b.set_client_id(client_b);
+		}
+
+		// connection
+		let relay_task = spawn_light_relay(&chain_a, &chain_b);
+		let connection_id = match (config_a.connection_id(), config_b.connection_id()) {
+			(Some(connection_id_a), Some(_)) => connection_id_a,
+			_ => {
+				let (connection_id_a, connection_id_b) =
+					match self.choose_existing_connection(&chain_a, &chain_b).await? {
+						Some(connection_ids) => connection_ids,
+						None => {
+							self.confirm("Create a new connection?")?;
+							let delay = self.prompt("Connection delay in seconds", "0")?;
+							let delay = Duration::from_secs(delay.parse()?);
+							create_connection(&mut chain_a, &mut chain_b, delay).await?
+						},
+					};
+				println!("Connection on {}: {connection_id_a}", chain_a.name());
+				println!("Connection on {}: {connection_id_b}", chain_b.name());
+				chain_a.set_connection_id(connection_id_a.clone());
+				chain_b.set_connection_id(connection_id_b.clone());
+				config_a.set_connection_id(connection_id_a.clone());
+				config_b.set_connection_id(connection_id_b);
+				connection_id_a
+			},
+		};
+		relay_task.abort();
+
+		// channels
+		if config_a.channel_whitelist().is_empty() {
+			let relay_task = spawn_light_relay(&chain_a, &chain_b);
+			let mut channels = self.choose_existing_channels(&chain_a, &connection_id).await?;
+			if channels.is_empty() {
+				self.confirm("Create a new channel?")?;
+				let port_id = PortId::from_str(&self.prompt("Port", "transfer")?)?;
+				let version = self.prompt("Version", "ics20-1")?;
+				let order = Order::from_str(&self.prompt("Order", "unordered")?)?;
+				let (channel_id_a, channel_id_b) = create_channel(
+					&mut chain_a,
+					&mut chain_b,
+					connection_id,
+					port_id.clone(),
+					version,
+					order,
+				)
+				.await?;
+				channels.push(((channel_id_a, port_id.clone()), (channel_id_b, port_id)));
+			}
+			for ((channel_id_a, port_id_a), (channel_id_b, port_id_b)) in channels {
+				println!("Channel on {}: {channel_id_a}/{port_id_a}", chain_a.name());
+				println!("Channel on {}: {channel_id_b}/{port_id_b}", chain_b.name());
+				config_a.set_channel_whitelist(channel_id_a, port_id_a);
+				config_b.set_channel_whitelist(channel_id_b, port_id_b);
+			}
+			relay_task.abort();
+		}
+
+		write_config(&self.config_a, self.config_a.clone(), &config_a).await?;
+		write_config(&self.config_b, self.config_b.clone(), &config_b).await?;
+		if !Path::new(&self.config_core).exists() {
+			tokio::fs::write(&self.config_core, toml::to_string(&CoreConfig::default())?).await?;
+		}
+		println!(
+			"Configs written, start relaying with `hyperspace relay --config-a {} --config-b {} --config-core {}`",
+			self.config_a, self.config_b, self.config_core
+		);
+		Ok(())
+	}
+
+	/// Offers the clients hosted on `counterparty` to be used as the light client of `chain`.
+	async fn choose_existing_client(
+		&self,
+		chain: &AnyChain,
+		counterparty: &AnyChain,
+	) -> Result<Option<ClientId>> {
+		let clients = counterparty.query_all_clients().await?;
+		let options = clients.iter().map(ToString::to_string).collect::<Vec<_>>();
+		let question =
+			format!("Which client of {} on {} should be used?", chain.name(), counterparty.name());
+		Ok(self.choose(&question, &options)?.map(|i| clients[i].clone()))
+	}
+
+	/// Offers the open connections between the clients of both chains.
+	async fn choose_existing_connection(
+		&self,
+		chain_a: &AnyChain,
+		chain_b: &AnyChain,
+	) -> Result<Option<(ConnectionId, ConnectionId)>> {
+		let (height, _) = chain_a.latest_height_and_timestamp().await?;
+		// connections of chain A are built on the client of chain B hosted on chain A
+		let connections = chain_a
+			.query_connection_using_client(
+				height.revision_height as u32,
+				chain_b.client_id().to_string(),
+			)
+			.await?
+			.into_iter()
+			.filter(|connection| connection.state == STATE_OPEN)
+			.filter_map(|connection| {
+				let counterparty = connection.counterparty?;
+				if counterparty.client_id != chain_a.client_id().as_str() {
+					return None
+				}
+				Some((
+					ConnectionId::from_str(&connection.id).ok()?,
+					ConnectionId::from_str(&counterparty.connection_id).ok()?,
+				))
+			})
+			.collect::<Vec<_>>();
+		let options = connections
+			.iter()
+			.map(|(connection_a, connection_b)| format!("{connection_a} <-> {connection_b}"))
+			.collect::<Vec<_>>();
+		Ok(self
+			.choose("Which connection should be used?", &options)?
+			.map(|i| connections[i].clone()))
+	}
+
+	/// Offers the open channels of the connection, all of them are relayed when chosen.
+	async fn choose_existing_channels(
+		&self,
+		chain_a: &AnyChain,
+		connection_id: &ConnectionId,
+	) -> Result<Vec<((ChannelId, PortId), (ChannelId, PortId))>> {
+		let (height, _) = chain_a.latest_height_and_timestamp().await?;
+		let channels = chain_a
+			.query_all_connection_channels(height, connection_id)
+			.await?
+			.into_iter()
+			.filter(|channel| channel.state == STATE_OPEN)
+			.filter_map(|channel| {
+				let counterparty = channel.counterparty?;
+				Some((
+					(
+						ChannelId::from_str(&channel.channel_id).ok()?,
+						PortId::from_str(&channel.port_id).ok()?,
+					),
+					(
+						ChannelId::from_str(&counterparty.channel_id).ok()?,
+						PortId::from_str(&counterparty.port_id).ok()?,
+					),
+				))
+			})
+			.collect::<Vec<_>>();
+		if channels.is_empty() {
+			return Ok(vec![])
+		}
+		for ((channel_a, port_a), (channel_b, port_b)) in &channels {
+			println!("  {channel_a}/{port_a} <-> {channel_b}/{port_b}");
+		}
+		if self.yes || self.ask(&format!("Relay these {} open channels?", channels.len()))? {
+			Ok(channels)
+		} else {
+			Ok(vec![])
+		}
+	}
+
+	/// Lets the user pick one of `options`, `None` if there are none or a new one should be
+	/// created instead.
+	fn choose(&self, question: &str, options: &[String]) -> Result<Option<usize>> {
+		if options.is_empty() {
+			return Ok(None)
+		}
+		if self.yes && options.len() == 1 {
+			return Ok(Some(0))
+		}
+		println!("{question}");
+		for (i, option) in options.iter().enumerate() {
+			println!("  [{}] {option}", i + 1);
+		}
+		println!("  [n] create a new one");
+		loop {
+			let answer = self.prompt("Choice", "n")?;
+			if answer == "n" {
+				return Ok(None)
+			}
+			match answer.parse::<usize>() {
+				Ok(i) if (1..=options.len()).contains(&i) => return Ok(Some(i - 1)),
+				_ => println!("Expected a number between 1 and {} or n", options.len()),
+			}
+		}
+	}
+
+	/// Fails unless the user agrees to create a missing object.
+	fn confirm(&self, question: &str) -> Result<()> {
+		if self.yes || self.ask(question)? {
+			Ok(())
+		} else {
+			Err(anyhow!("Aborted, the configs weren't written"))
+		}
+	}
+
+	fn ask(&self, question: &str) -> Result<bool> {
+		let answer = self.prompt(&format!("{question} [y/n]"), "y")?.to_lowercase();
+		Ok(matches!(answer.as_str(), "y" | "yes"))
+	}
+
+	fn prompt(&self, question: &str, default: &str) -> Result<String> {
+		if self.yes {
+			return Ok(default.to_string())
+		}
+		print!("{question} ({default}): ");
+		std::io::stdout().flush()?;
+		let mut answer = String::new();
+		std::io::stdin().lock().read_line(&mut answer)?;
+		match answer.trim() {
+			"" => Ok(default.to_string()),
+			answer => Ok(answer.to_string()),
+		}
+	}
+}
+
+/// The connection and channel handshakes are completed by a relayer running alongside.
+fn spawn_light_relay(chain_a: &AnyChain, chain_b: &AnyChain) -> tokio::task::JoinHandle<()> {
+	let (chain_a, chain_b) = (chain_a.clone(), chain_b.clone());
+	tokio::spawn(async move {
+		if let Err(e) =
+			relay(chain_a, chain_b, None, None, Some(Mode::Light), Shutdown::default()).await
+		{
+			log::error!(target: "hyperspace", "Relayer stopped during the handshakes: {e:?}");
+		}
+	})
+}
@@ -0,0 +1,198 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! References in the string values of the configs, so that secrets don't have to be written in
+//! the config files:
+//! - `${VAR}` or `${env:VAR}` is replaced with the environment variable `VAR`,
+//! - `${file:PATH}` is replaced with the content of the file at `PATH`, without the trailing
+//!   newline,
+//! - `$${` is an escaped `${`.
+//!
+//! References are resolved once the TOML is parsed, so a secret is never interpreted as TOML.
+//! Configs written back by the relayer keep the references of the original file.
+
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+use toml::Value;
+
+/// Reads the config at `path`, resolving the references in its string values.
+pub async fn read_config<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T> {
+	let path = path.as_ref();
+	let content = tokio::fs::read_to_string(path)
+		.await
+		.map_err(|e| anyhow!("Can't read {}: {e}", path.display()))?;
+	from_toml_str(&content)
+}
+
+/// Parses a config, resolving the references in its string values.
+pub fn from_toml_str<T: DeserializeOwned>(content: &str) -> Result<T> {
+	let mut value: Value = toml::from_str(content)?;
+	resolve_value(&mut value, "")?;
+	Ok(value.try_into()?)
+}
+
+/// Serializes a config read from `original`. Values that are still equal to the ones a reference of
+/// `original` resolves to are written as that reference again, so secrets don't end up in the file.
+pub fn to_toml_string<T: Serialize>(config: &T, original: &str) -> Result<String> {
+	let mut value = Value::try_from(config)?;
+	restore_references(&toml::from_str(original)?, &mut value);
+	Ok(toml::to_string(&value)?)
+}
+
+fn resolve_value(value: &mut Value, key: &str) -> Result<()> {
+	match value {
+		Value::String(s) => *s = resolve(s).map_err(|e| anyhow!("Can't resolve `{key}`: {e}"))?,
+		Value::Array(values) =>
+			for (i, value) in values.iter_mut().enumerate() {
+				resolve_value(value, &format!("{key}[{i}]"))?;
+			},
+		Value::Table(table) =>
+			for (name, value) in table.iter_mut() {
+				let key = if key.is_empty() { name.clone() } else { format!("{key}.{name}") };
+				resolve_value(value, &key)?;
+			},
+		_ => {},
+	}
+	Ok(())
+}
+
+fn restore_references(original: &Value, value: &mut Value) {
+	match (original, value) {
+		(Value::String(original), Value::String(s)) =>
+			if original.contains("${") && resolve(original).ok().as_ref() == Some(s) {
+				*s = original.clone();
+			},
+		(Value::Array(originals), Value::Array(values)) =>
+			for (original, value) in originals.iter().zip(values.iter_mut()) {
+				restore_references(original, value);
+			},
+		(Value::Table(originals), Value::Table(table)) =>
+			for (name, value) in table.iter_mut() {
+				if let Some(original) = originals.get(name) {
+					restore_references(original, value);
+				}
+			},
+		_ => {},
+	}
+}
+
+/// Replaces the references of `s` with the values they point to.
+fn resolve(s: &str) -> Result<String> {
+	let mut resolved = String::with_capacity(s.len());
+	let mut rest = s;
+	while let Some(start) = rest.find('$') {
+		resolved.push_str(&rest[..start]);
+		rest = &rest[start..];
+		if let Some(escaped) = rest.strip_prefix("$${") {
+			resolved.push_str("${");
+			rest = escaped;
+		} else if let Some(reference) = rest.strip_prefix("${") {
+			let end =
+				reference.find('}').ok_or_else(|| anyhow!("Unterminated reference in {s}"))?;
+			resolved.push_str(&resolve_reference(&reference[..end])?);
+			rest = &reference[end + 1..];
+		} else {
+			resolved.push('$');
+			rest = &rest[1..];
+		}
+	}
+	resolved.push_str(rest);
+	Ok(resolved)
+}
+
+fn resolve_reference(reference: &str) -> Result<String> {
+	match reference.split_once(':') {
+		Some(("file", path)) => std::fs::read_to_string(path)
+			.map(|content| content.trim_end_matches(['\r', '\n']).to_string())
+			.map_err(|e| anyhow!("Can't read secret file {path}: {e}")),
+		Some(("env", var)) => read_env(var),
+		None => read_env(reference),
+		Some(_) => Err(anyhow!(
+			"Unknown reference ${{{reference}}}, expected `${{VAR}}`, `${{env:VAR}}` or `${{file:PATH}}`"
+		)),
+	}
+}
+
+fn read_env(var: &str) -> Result<String> {
+	std::env::var(var).map_err(|e| anyhow!("Can't read environment variable {var}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const VAR: &str = "HYPERSPACE_INTERPOLATION_TEST_SECRET";
+
+	#[test]
+	fn resolves_references() {
+		std::env::set_var(VAR, "secret");
+		let path = std::env::temp_dir().join(format!("hyperspace-secret-{}", std::process::id()));
+		std::fs::write(&path, "file secret\n").unwrap();
+		let file = path.display();
+
+		let cases = [
+			("plain".to_string(), Some("plain")),
+			(format!("${{{VAR}}}"), Some("secret")),
+			(format!("${{env:{VAR}}}"), Some("secret")),
+			(format!("pre-${{{VAR}}}-post"), Some("pre-secret-post")),
+			(format!("${{file:{file}}}"), Some("file secret")),
+			(format!("$${{{VAR}}}"), Some("${HYPERSPACE_INTERPOLATION_TEST_SECRET}")),
+			("costs $5".to_string(), Some("costs $5")),
+			(format!("${{{VAR}"), None),
+			(format!("${{vault:{VAR}}}"), None),
+			("${HYPERSPACE_INTERPOLATION_TEST_UNSET}".to_string(), None),
+			("${file:/hyperspace/missing}".to_string(), None),
+		];
+		for (s, resolved) in cases {
+			assert_eq!(resolve(&s).ok().as_deref(), resolved, "{s}");
+		}
+		std::fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn written_configs_keep_the_references() {
+		std::env::set_var(VAR, "secret");
+		let original = format!(
+			r#"
+			name = "chain"
+			mnemonic = "${{{VAR}}}"
+			keys = ["${{{VAR}}}", "key"]
+			escaped = "$${{{VAR}}}"
+
+			[nested]
+			token = "${{env:{VAR}}}"
+			"#
+		);
+		let mut config: Value = from_toml_str(&original).unwrap();
+		assert_eq!(config["mnemonic"].as_str(), Some("secret"));
+		assert_eq!(config["nested"]["token"].as_str(), Some("secret"));
+
+		config["name"] = "renamed".into();
+		config["keys"][1] = "secret".into();
+		let written: Value = toml::from_str(&to_toml_string(&config, &original).unwrap()).unwrap();
+		assert_eq!(written["name"].as_str(), Some("renamed"));
+		assert_eq!(written["mnemonic"].as_str(), Some(format!("${{{VAR}}}").as_str()));
+		assert_eq!(written["keys"][0].as_str(), Some(format!("${{{VAR}}}").as_str()));
+		// only the values that came from a reference are written as one
+		assert_eq!(written["keys"][1].as_str(), Some("secret"));
+		assert_eq!(written["escaped"].as_str(), Some(format!("$${{{VAR}}}").as_str()));
+		assert_eq!(written["nested"]["token"].as_str(), Some(format!("${{env:{VAR}}}").as_str()));
+
+		// a secret changed by the relayer isn't replaced by the reference
+		config["mnemonic"] = "rotated".into();
+		let written: Value = toml::from_str(&to_toml_string(&config, &original).unwrap()).unwrap();
+		assert_eq!(written["mnemonic"].as_str(), Some("rotated"));
+	}
+}
@@ -0,0 +1,215 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Keyring of the relayer: named signing secrets, each kept in an encrypted keystore file of the
+//! keyring directory. Chain configs use a key by pointing their `keystore` field at its file.
+
+use crate::{chain::AnyConfig, interpolation};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use primitives::{
+	keystore::{prompt_secret, read_passphrase, EncryptedKeystore},
+	Chain, IbcQueryProvider, KeyProvider,
+};
+use sp_core::{sr25519, Pair};
+use std::path::{Path, PathBuf};
+
+const KEY_FILE_EXTENSION: &str = "json";
+
+/// Asset ids that can be given on the command line
+pub trait ParseAssetId: Sized {
+	fn parse_asset_id(asset_id: &str) -> Result<Self>;
+}
+
+impl ParseAssetId for u128 {
+	fn parse_asset_id(asset_id: &str) -> Result<Self> {
+		asset_id.parse().map_err(|e| anyhow!("Invalid asset id {asset_id}: {e}"))
+	}
+}
+
+impl ParseAssetId for String {
+	fn parse_asset_id(asset_id: &str) -> Result<Self> {
+		Ok(asset_id.to_string())
+	}
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct KeysCmd {
+	/// Keyring directory, defaults to `$HOME/.hyperspace/keys`.
+	#[clap(long)]
+	keyring: Option<PathBuf>,
+	#[clap(subcommand)]
+	subcommand: KeysSubcommand,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub enum KeysSubcommand {
+	#[clap(name = "add", about = "Adds a key to the keyring")]
+	Add(AddKeyCmd),
+	#[clap(name = "list", about = "Lists the keys of the keyring")]
+	List,
+	#[clap(name = "show", about = "Shows the relayer address of a key on each configured chain")]
+	Show(ShowKeyCmd),
+	#[clap(name = "delete", about = "Deletes a key from the keyring")]
+	Delete(DeleteKeyCmd),
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct AddKeyCmd {
+	/// Name of the key.
+	name: String,
+	/// Generate a new mnemonic instead of prompting for an existing secret.
+	#[clap(long)]
+	generate: bool,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct ShowKeyCmd {
+	/// Name of the key.
+	name: String,
+	/// Chain config paths to derive the relayer address for.
+	#[clap(long)]
+	config: Vec<String>,
+	/// Asset ids to query the balance of, one per `--config` and in the same order.
+	#[clap(long)]
+	asset: Vec<String>,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct DeleteKeyCmd {
+	/// Name of the key.
+	name: String,
+	/// Confirm the deletion, the key can't be recovered from the keyring afterwards.
+	#[clap(long)]
+	yes: bool,
+}
+
+impl KeysCmd {
+	pub async fn run(&self) -> Result<()> {
+		let keyring = match &self.keyring {
+			Some(keyring) => keyring.clone(),
+			None => default_keyring()?,
+		};
+		match &self.subcommand {
+			KeysSubcommand::Add(cmd) => cmd.run(&keyring).await,
+			KeysSubcommand::List => list_keys(&keyring).await,
+			KeysSubcommand::Show(cmd) => cmd.run(&keyring).await,
+			KeysSubcommand::Delete(cmd) => cmd.run(&keyring).await,
+		}
+	}
+}
+
+/// `$HOME/.hyperspace/keys`
+pub(crate) fn default_keyring() -> Result<PathBuf> {
+	Ok(PathBuf::from(std::env::var("HOME")?).join(".hyperspace").join("keys"))
+}
+
+pub(crate) fn key_path(keyring: &Path, name: &str) -> Result<PathBuf> {
+	let valid =
+		!name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+	if !valid {
+		return Err(anyhow!(
+			"Invalid key name {name}, only letters, digits, `-` and `_` are allowed"
+		))
+	}
+	Ok(keyring.join(name).with_extension(KEY_FILE_EXTENSION))
+}
+
+impl AddKeyCmd {
+	async fn run(&self, keyring: &Path) -> Result<()> {
+		let path = key_path(keyring, &self.name)?;
+		if path.exists() {
+			return Err(anyhow!("Key {} already exists", self.name))
+		}
+		let secret = if self.generate {
+			let (_, mnemonic, _) = sr25519::Pair::generate_with_phrase(None);
+			println!("Generated mnemonic, write it down as it won't be shown again:\n{mnemonic}");
+			mnemonic
+		} else {
+			prompt_secret()?
+		};
+		let passphrase = read_passphrase(true)?;
+		tokio::fs::create_dir_all(keyring).await?;
+		EncryptedKeystore::encrypt(&secret, &passphrase)?.save(&path)?;
+		println!("Added key {}, use it with `keystore = \"{}\"`", self.name, path.display());
+		Ok(())
+	}
+}
+
+async fn list_keys(keyring: &Path) -> Result<()> {
+	if !keyring.exists() {
+		return Ok(())
+	}
+	let mut names = vec![];
+	let mut entries = tokio::fs::read_dir(keyring).await?;
+	while let Some(entry) = entries.next_entry().await? {
+		let path = entry.path();
+		if path.extension().map_or(false, |extension| extension == KEY_FILE_EXTENSION) {
+			if let Some(name) = path.file_stem() {
+				names.push(name.to_string_lossy().to_string());
+			}
+		}
+	}
+	names.sort();
+	for name in names {
+		println!("{name}");
+	}
+	Ok(())
+}
+
+impl ShowKeyCmd {
+	async fn run(&self, keyring: &Path) -> Result<()> {
+		if !self.asset.is_empty() && self.asset.len() != self.config.len() {
+			return Err(anyhow!("Either no `--asset` or one per `--config` must be given"))
+		}
+		let path = key_path(keyring, &self.name)?;
+		if !path.exists() {
+			return Err(anyhow!("Key {} doesn't exist", self.name))
+		}
+		println!("{}: {}", self.name, path.display());
+		if self.config.is_empty() {
+			return Ok(())
+		}
+
+		let secret = EncryptedKeystore::load(&path)?.decrypt(&read_passphrase(false)?)?;
+		for (i, config_path) in self.config.iter().enumerate() {
+			let mut config: AnyConfig = interpolation::read_config(config_path).await?;
+			config.set_signing_secret(secret.clone());
+			let chain = config.into_client().await?;
+			println!("{}: {}", chain.name(), chain.account_id());
+			if let Some(asset) = self.asset.get(i) {
+				let balances = chain.query_ibc_balance(chain.parse_asset_id(asset)?).await?;
+				for coin in balances {
+					println!("  {} {}", coin.amount, coin.denom);
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+impl DeleteKeyCmd {
+	async fn run(&self, keyring: &Path) -> Result<()> {
+		let path = key_path(keyring, &self.name)?;
+		if !path.exists() {
+			return Err(anyhow!("Key {} doesn't exist", self.name))
+		}
+		if !self.yes {
+			return Err(anyhow!("Pass `--yes` to delete the key {}", self.name))
+		}
+		tokio::fs::remove_file(&path).await?;
+		println!("Deleted key {}", self.name);
+		Ok(())
+	}
+}
@@ -14,25 +14,82 @@
 
 #![warn(unused_variables)]
 
+pub mod admin;
+pub mod alerts;
 pub mod chain;
+#[cfg(feature = "chaos")]
+pub mod chaos;
 pub mod command;
+pub mod config;
+pub mod cross_chain_queries;
+pub mod doctor;
 pub mod events;
+pub mod handshake;
+pub mod hermes;
+pub mod init;
+pub mod interpolation;
+pub mod keys;
 pub mod logging;
 mod macros;
 pub mod packets;
+pub mod pipeline;
+pub mod query;
 pub mod queue;
+pub mod reload;
+pub mod shutdown;
 pub mod substrate;
+pub mod tx;
 mod utils;
+pub mod wiring;
 
 use crate::utils::RecentStream;
 use anyhow::anyhow;
-use events::{has_packet_events, parse_events};
-use futures::{future::ready, StreamExt, TryFutureExt};
-use ibc::{events::IbcEvent, Height};
+use events::{has_packet_events, parse_events, recheck_unproven_events, retain_proven_events};
+use futures::{future::ready, Stream, StreamExt, TryFutureExt};
+use ibc::{
+	core::{ics02_client::events::UpdateClient, ics24_host::identifier::ClientId},
+	events::IbcEvent,
+	Height,
+};
 use ibc_proto::google::protobuf::Any;
 use metrics::handler::MetricsHandler;
-use primitives::{Chain, IbcProvider, UndeliveredType, UpdateType};
-use std::collections::HashSet;
+use packets::client_expiry;
+use pipeline::{Pipeline, SubmissionLock};
+use primitives::{Chain, IbcEventProvider, IbcQueryProvider, UndeliveredType, UpdateType};
+use shutdown::{PendingBatch, Shutdown};
+use std::{collections::HashSet, pin::Pin, time::Duration};
+
+/// How often the finality subscriptions are checked for stalls.
+const STALL_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Number of expected block times without a finality notification after which the subscription is
+/// considered stalled.
+const STALL_TIMEOUT_BLOCKS: u32 = 100;
+/// Lower bound of the stall timeout, finality may lag behind block production for a while.
+const MIN_STALL_TIMEOUT: Duration = Duration::from_secs(300);
+const INITIAL_RESUBSCRIBE_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RESUBSCRIBE_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Delay before resubscribing to a stream of a chain, doubled after every attempt from
+/// [`INITIAL_RESUBSCRIBE_BACKOFF`] up to [`MAX_RESUBSCRIBE_BACKOFF`].
+struct ResubscribeBackoff(Duration);
+
+impl ResubscribeBackoff {
+	fn new() -> Self {
+		Self(INITIAL_RESUBSCRIBE_BACKOFF)
+	}
+
+	/// Returns the delay before the next attempt, and doubles the following one.
+	fn next_delay(&mut self) -> Duration {
+		let delay = self.0;
+		self.0 = (delay * 2).min(MAX_RESUBSCRIBE_BACKOFF);
+		delay
+	}
+
+	/// Starts over from the initial delay, once the stream delivers items again.
+	fn reset(&mut self) {
+		self.0 = INITIAL_RESUBSCRIBE_BACKOFF;
+	}
+}
 
 #[derive(Copy, Debug, Clone)]
 pub enum Mode {
@@ -41,13 +98,14 @@ pub enum Mode {
 }
 
 /// Core relayer loop, waits for new finality events and forwards any new [`ibc::IbcEvents`]
-/// to the counter party chain.
+/// to the counter party chain, until a shutdown is requested through `shutdown`.
 pub async fn relay<A, B>(
 	mut chain_a: A,
 	mut chain_b: B,
 	mut chain_a_metrics: Option<MetricsHandler>,
 	mut chain_b_metrics: Option<MetricsHandler>,
 	mode: Option<Mode>,
+	shutdown: Shutdown,
 ) -> Result<(), anyhow::Error>
 where
 	A: Chain,
@@ -57,31 +115,81 @@ where
 	let stream_b = RecentStream::new(chain_b.finality_notifications().await?);
 	let (mut chain_a_finality, mut chain_b_finality) = (stream_a, stream_b);
 
+	let mut stall_check = tokio::time::interval(STALL_CHECK_INTERVAL);
+
+	// packets are relayed by a worker per channel and direction, client updates by this loop and
+	// submitted by the submit stage of their direction. Both directions submit to both chains, one
+	// submission at a time per chain.
+	let (chain_a_submissions, chain_b_submissions) =
+		(SubmissionLock::default(), SubmissionLock::default());
+	let mut chain_a_pipeline = Pipeline::<A, B>::new(
+		&chain_a,
+		&chain_b,
+		chain_a_metrics.as_ref(),
+		&chain_a_submissions,
+		&chain_b_submissions,
+		&shutdown,
+	);
+	let mut chain_b_pipeline = Pipeline::<B, A>::new(
+		&chain_b,
+		&chain_a,
+		chain_b_metrics.as_ref(),
+		&chain_b_submissions,
+		&chain_a_submissions,
+		&shutdown,
+	);
+
 	// Introduce altering between branches so that each branch gets a chance to execute first after
 	// another one
 	let mut first_executed = false;
 
-	// loop forever
+	// loop until a shutdown is requested
 	loop {
+		if shutdown.is_requested() {
+			break
+		}
 		tokio::select! {
 			// new finality event from chain A
 			result = chain_a_finality.next(), if !first_executed => {
 				first_executed = true;
-				process_finality_event(&mut chain_a, &mut chain_b, &mut chain_a_metrics, mode, result, &mut chain_a_finality, &mut chain_b_finality).await?;
+				process_finality_event(&mut chain_a, &mut chain_b, &mut chain_a_metrics, &mut chain_a_pipeline, mode, result, &mut chain_a_finality, &mut chain_b_finality).await?;
 			}
 			// new finality event from chain B
 			result = chain_b_finality.next() => {
 				first_executed = false;
-				process_finality_event(&mut chain_b, &mut chain_a, &mut chain_b_metrics, mode, result, &mut chain_b_finality, &mut chain_a_finality).await?;
+				process_finality_event(&mut chain_b, &mut chain_a, &mut chain_b_metrics, &mut chain_b_pipeline, mode, result, &mut chain_b_finality, &mut chain_a_finality).await?;
+			}
+			// the subscriptions may stop yielding without being closed when the connection drops
+			_ = stall_check.tick() => {
+				if chain_a_finality.idle_for() > stall_timeout(&chain_a) {
+					log::warn!(target: "hyperspace", "No finality notifications from {} for {:?}, resubscribing", chain_a.name(), chain_a_finality.idle_for());
+					chain_a_finality = resubscribe(&mut chain_a).await;
+				}
+				if chain_b_finality.idle_for() > stall_timeout(&chain_b) {
+					log::warn!(target: "hyperspace", "No finality notifications from {} for {:?}, resubscribing", chain_b.name(), chain_b_finality.idle_for());
+					chain_b_finality = resubscribe(&mut chain_b).await;
+				}
 			}
+			// stop consuming new finality events, the event being processed is always finished
+			// before this branch is polled.
+			_ = shutdown.requested() => break,
 			else => {
 				first_executed = false;
 			}
 		}
 	}
+
+	chain_a_pipeline.stop().await;
+	chain_b_pipeline.stop().await;
+	log::info!(target: "hyperspace", "Relay between {} and {} stopped", chain_a.name(), chain_b.name());
+	Ok(())
 }
 
-pub async fn fish<A, B>(chain_a: A, chain_b: B) -> Result<(), anyhow::Error>
+pub async fn fish<A, B>(
+	mut chain_a: A,
+	mut chain_b: B,
+	shutdown: Shutdown,
+) -> Result<(), anyhow::Error>
 where
 	A: Chain,
 	A::Error: From<B::Error>,
@@ -90,30 +198,31 @@ where
 {
 	// we only care about events where the counterparty light client is updated.
 	let (mut chain_a_client_updates, mut chain_b_client_updates) = (
-		chain_a.ibc_events().await.filter_map(|ev| {
-			ready(match ev {
-				IbcEvent::UpdateClient(update) if chain_b.client_id() == *update.client_id() =>
-					Some(update),
-				_ => None,
-			})
-		}),
-		chain_b.ibc_events().await.filter_map(|ev| {
-			ready(match ev {
-				IbcEvent::UpdateClient(update) if chain_a.client_id() == *update.client_id() =>
-					Some(update),
-				_ => None,
-			})
-		}),
+		client_updates(&chain_a, chain_b.client_id()).await,
+		client_updates(&chain_b, chain_a.client_id()).await,
 	);
+	let (mut backoff_a, mut backoff_b) = (ResubscribeBackoff::new(), ResubscribeBackoff::new());
 
-	// loop forever
+	// loop until a shutdown is requested
 	loop {
 		tokio::select! {
 			// new finality event from chain A
 			update = chain_a_client_updates.next() => {
 				let update = match update {
-					Some(update) => update,
-					None => break,
+					Some(update) => {
+						backoff_a.reset();
+						update
+					},
+					None => {
+						let delay = backoff_a.next_delay();
+						log::warn!(target: "hyperspace", "IBC events stream closed for {}, resubscribing in {delay:?}", chain_a.name());
+						tokio::time::sleep(delay).await;
+						if let Err(e) = chain_a.reconnect().await {
+							log::warn!(target: "hyperspace", "Failed to reconnect to {}: {:?}", chain_a.name(), e);
+						}
+						chain_a_client_updates = client_updates(&chain_a, chain_b.client_id()).await;
+						continue
+					},
 				};
 				// The corresponding transaction on tendermint may not be indexed yet, so we wait for a bit
 				if chain_a.client_type() == "07-tendermint" {
@@ -125,8 +234,20 @@ where
 			// new finality event from chain B
 			update = chain_b_client_updates.next() => {
 				let update = match update {
-					Some(update) => update,
-					None => break,
+					Some(update) => {
+						backoff_b.reset();
+						update
+					},
+					None => {
+						let delay = backoff_b.next_delay();
+						log::warn!(target: "hyperspace", "IBC events stream closed for {}, resubscribing in {delay:?}", chain_b.name());
+						tokio::time::sleep(delay).await;
+						if let Err(e) = chain_b.reconnect().await {
+							log::warn!(target: "hyperspace", "Failed to reconnect to {}: {:?}", chain_b.name(), e);
+						}
+						chain_b_client_updates = client_updates(&chain_b, chain_a.client_id()).await;
+						continue
+					},
 				};
 				// The corresponding transaction on tendermint may not be indexed yet, so we wait for a bit
 				if chain_a.client_type() == "07-tendermint" {
@@ -135,16 +256,62 @@ where
 				let message = chain_b.query_client_message(update).await.map_err(|e| { log::info!("error: {}", e); e })?;
 				chain_a.check_for_misbehaviour(&chain_b, message).await.map_err(|e| { log::info!("error: {}", e); e })?;
 			}
+			_ = shutdown.requested() => break,
 		}
 	}
 
 	Ok(())
 }
 
+/// Subscribes to the [`UpdateClient`] events of `chain` for the light client with the given id.
+async fn client_updates(
+	chain: &impl Chain,
+	client_id: ClientId,
+) -> Pin<Box<dyn Stream<Item = UpdateClient> + Send>> {
+	Box::pin(chain.ibc_events().await.filter_map(move |ev| {
+		ready(match ev {
+			IbcEvent::UpdateClient(update) if client_id == *update.client_id() => Some(update),
+			_ => None,
+		})
+	}))
+}
+
+/// Resubscribes to the finality notifications of `chain`, reconnecting with an exponential backoff
+/// until it succeeds. Blocks finalized while the subscription was down don't need to be replayed:
+/// the first notification after resubscribing makes the relayer query all events since the latest
+/// height of the counterparty light client.
+async fn resubscribe<C: Chain>(chain: &mut C) -> RecentStream<C::FinalityEvent> {
+	let mut backoff = ResubscribeBackoff::new();
+	loop {
+		match chain.finality_notifications().await {
+			Ok(stream) => return RecentStream::new(stream),
+			Err(e) => {
+				let delay = backoff.next_delay();
+				log::error!(
+					"Failed to get finality notifications for {} {:?}. Trying again in {:?}...",
+					chain.name(),
+					e,
+					delay
+				);
+				tokio::time::sleep(delay).await;
+				if let Err(e) = chain.reconnect().await {
+					log::warn!(target: "hyperspace", "Failed to reconnect to {}: {:?}", chain.name(), e);
+				}
+			},
+		};
+	}
+}
+
+/// How long a finality subscription of `chain` may stay silent before it's considered dead.
+fn stall_timeout(chain: &impl Chain) -> Duration {
+	(chain.expected_block_time() * STALL_TIMEOUT_BLOCKS).max(MIN_STALL_TIMEOUT)
+}
+
 async fn process_finality_event<A: Chain, B: Chain>(
 	source: &mut A,
 	sink: &mut B,
 	metrics: &mut Option<MetricsHandler>,
+	pipeline: &mut Pipeline<A, B>,
 	mode: Option<Mode>,
 	result: Option<A::FinalityEvent>,
 	stream_source: &mut RecentStream<A::FinalityEvent>,
@@ -154,33 +321,23 @@ async fn process_finality_event<A: Chain, B: Chain>(
 		// stream closed
 		None => {
 			log::warn!("Stream closed for {}", source.name());
-			*stream_source = loop {
-				match source.finality_notifications().await {
-					Ok(stream) => break RecentStream::new(stream),
-					Err(e) => {
-						log::error!("Failed to get finality notifications for {} {:?}. Trying again in 30 seconds...", source.name(), e);
-						tokio::time::sleep(std::time::Duration::from_secs(30)).await;
-						let _ = source.reconnect().await;
-					},
-				};
-			};
-			*stream_sink = loop {
-				match sink.finality_notifications().await {
-					Ok(stream) => break RecentStream::new(stream),
-					Err(e) => {
-						log::error!("Failed to get finality notifications for {} {:?}. Trying again in 30 seconds...", sink.name(), e);
-						tokio::time::sleep(std::time::Duration::from_secs(30)).await;
-						let _ = sink.reconnect().await;
-					},
-				};
-			};
+			*stream_source = resubscribe(source).await;
+			*stream_sink = resubscribe(sink).await;
 		},
 		Some(finality_event) => {
 			log::info!("=======================================================");
 			log::info!("Received finality notification from {}", source.name(),);
+			let coalesced = stream_source.take_coalesced();
+			if coalesced > 0 {
+				log::debug!(target: "hyperspace", "Skipped {coalesced} finality notifications from {} superseded by this one", source.name());
+			}
+			if let (Some(metrics), Some(url)) = (metrics.as_ref(), source.active_rpc_endpoint()) {
+				metrics.handle_active_rpc_endpoint(&url);
+			}
 
 			let result =
-				process_some_finality_event(source, sink, metrics, mode, finality_event).await;
+				process_some_finality_event(source, sink, metrics, pipeline, mode, finality_event)
+					.await;
 
 			match result {
 				Ok(()) => {
@@ -208,20 +365,73 @@ async fn process_some_finality_event<A: Chain, B: Chain>(
 	source: &mut A,
 	sink: &mut B,
 	metrics: &mut Option<MetricsHandler>,
+	pipeline: &mut Pipeline<A, B>,
 	mode: Option<Mode>,
-	finality_event: <A as IbcProvider>::FinalityEvent,
+	finality_event: <A as IbcEventProvider>::FinalityEvent,
 ) -> anyhow::Result<()> {
-	let updates = source
+	// the finality event can't be proven to the light client until it has seen every authority
+	// set handoff in between, the next finality event picks up from the caught up client. The
+	// client is only behind once the queued updates are submitted.
+	let ticket = pipeline.submit.ticket();
+	if ticket.catching_up {
+		log::debug!(target: "hyperspace", "Light client of {} on {} is still being caught up, skipping this finality event", source.name(), sink.name());
+		return Ok(())
+	}
+	if ticket.idle {
+		let msgs = catch_up_light_client(source, sink, mode).await?;
+		if !msgs.is_empty() {
+			pipeline.submit.submit_catch_up(ticket, msgs).await?;
+			return Ok(())
+		}
+	}
+
+	let mut updates = source
 		.query_latest_ibc_events(finality_event, &*sink)
 		.await
 		.map_err(|e| anyhow!("Failed to fetch IBC events for finality event {e}"))?;
+	// the light client on the sink isn't updated past the queued updates yet, so they're fetched
+	// again
+	if let Some(queued_height) = ticket.queued_height {
+		updates.retain(|(_, height, ..)| *height > queued_height);
+	}
+	if source.common_state().verify_events && !updates.is_empty() {
+		// the events held back before are checked first, so that they're checked once per cycle
+		let mut proven = recheck_unproven_events(&*source, &*sink).await;
+		for (_, _, events, update_type) in updates.iter_mut() {
+			let (events_proven, held_back) =
+				retain_proven_events(&*source, &*sink, std::mem::take(events)).await;
+			*events = events_proven;
+			// the held back events are checked once the light client is updated to their height
+			if held_back {
+				*update_type = UpdateType::Mandatory;
+			}
+		}
+		if let Some((_, _, events, _)) = updates.first_mut() {
+			proven.append(events);
+			*events = proven;
+		}
+	}
+	let updates_height = updates.iter().map(|(_, height, ..)| *height).max();
 	log::trace!(target: "hyperspace", "Received updates count: {}", updates.len());
-	// query packets that can now be sent, at this sink height because of connection
-	// delay.
-	let (ready_packets, timeout_msgs) =
-		packets::query_ready_and_timed_out_packets(&*source, &*sink)
-			.await
-			.map_err(|e| anyhow!("Failed to parse events: {:?}", e))?;
+
+	// messages that depend on an expired client would be rejected, so we don't build them
+	let source_client_expired = is_client_expired(&*source, &*sink, metrics.as_ref()).await;
+	let sink_client_expired = is_client_expired(&*sink, &*source, None).await;
+
+	// the packets that can now be sent, because of connection delay, are queried and submitted by
+	// the worker of their channel.
+	if !(source_client_expired && sink_client_expired) {
+		let events =
+			updates.iter().flat_map(|(_, _, events, _)| events).cloned().collect::<Vec<_>>();
+		pipeline.workers.dispatch(
+			&*source,
+			&*sink,
+			&events,
+			metrics.as_ref(),
+			!source_client_expired,
+			!sink_client_expired,
+		);
+	}
 
 	let mut msgs = Vec::new();
 
@@ -241,19 +451,100 @@ async fn process_some_finality_event<A: Chain, B: Chain>(
 		UndeliveredType::Recvs, sink.has_undelivered_sequences(UndeliveredType::Recvs),
 	);
 
-	log::trace!(
-		target: "hyperspace",
-		"Received timeouts count: {}",
-		timeout_msgs.len()
-	);
+	let mut built_height = None;
+	if !source_client_expired {
+		// the steps of the queued batches would be built again until they're submitted
+		if ticket.idle && pipeline.handshakes.is_due() && !matches!(mode, Some(Mode::Light)) {
+			if let Err(e) = pipeline.handshakes.resume(&*source, &*sink, &mut updates).await {
+				log::warn!(
+					target: "hyperspace",
+					"Failed to resume the handshakes from {} to {}: {:?}",
+					source.name(),
+					sink.name(),
+					e
+				);
+			}
+		}
+		process_updates(source, sink, metrics, mode, updates, &mut msgs).await?;
+		built_height = updates_height;
+	}
 
-	process_updates(source, sink, metrics, mode, updates, &mut msgs).await?;
+	// submitted while the next finality event is fetched and built
+	pipeline.submit.submit(ticket, msgs, built_height).await?;
+
+	// the results are verified by the light client of the source on the sink
+	if !source_client_expired && !matches!(mode, Some(Mode::Light)) {
+		process_cross_chain_queries(
+			source,
+			sink,
+			&pipeline.sink_submissions,
+			&pipeline.shutdown,
+			metrics,
+		)
+		.await;
+	}
+	Ok(())
+}
 
-	msgs.extend(ready_packets);
+/// Builds a client update for every authority set handoff of `source` that the light client on
+/// `sink` missed, e.g. because the relayer was offline across several of them, along with the
+/// messages of their events. Returns no messages if the light client isn't behind.
+///
+/// Failing to check whether the light client is behind doesn't stop the finality event from being
+/// relayed, its updates are rejected by the sink if it is.
+async fn catch_up_light_client<A: Chain, B: Chain>(
+	source: &mut A,
+	sink: &mut B,
+	mode: Option<Mode>,
+) -> anyhow::Result<Vec<Any>> {
+	match source.is_synced(&*sink).await {
+		Ok(true) => return Ok(vec![]),
+		Ok(false) => {},
+		Err(e) => {
+			log::warn!(target: "hyperspace", "Failed to check whether the light client of {} on {} is synced: {e:?}", source.name(), sink.name());
+			return Ok(vec![])
+		},
+	}
 
-	process_messages(sink, metrics, msgs).await?;
-	process_timeouts(source, metrics, timeout_msgs).await?;
-	Ok(())
+	let (mut msgs, mut events) = source.fetch_mandatory_updates(&*sink).await?;
+	if msgs.is_empty() {
+		return Ok(vec![])
+	}
+	if source.common_state().verify_events {
+		events = retain_proven_events(&*source, &*sink, events).await.0;
+	}
+	log::info!(
+		target: "hyperspace",
+		"Light client of {} on {} is behind, catching up with {} mandatory updates",
+		source.name(), sink.name(), msgs.len()
+	);
+	let mut messages = parse_events(source, sink, events, mode)
+		.await
+		.map_err(|e| anyhow!("Failed to parse events: {:?}", e))?;
+	msgs.append(&mut messages);
+	Ok(msgs)
+}
+
+/// Answers the cross-chain queries `sink` made about `source`. Queries are retried on the next
+/// finality notification, so failing to answer them doesn't stop the relay.
+async fn process_cross_chain_queries<A: Chain, B: Chain>(
+	source: &A,
+	sink: &mut B,
+	submissions: &SubmissionLock,
+	shutdown: &Shutdown,
+	metrics: &mut Option<MetricsHandler>,
+) {
+	let msgs = match cross_chain_queries::query_cross_chain_query_results(source, &*sink).await {
+		Ok(msgs) => msgs,
+		Err(e) => {
+			log::warn!(target: "hyperspace", "{:?}", e);
+			return
+		},
+	};
+	let pending = shutdown.track(sink.chain_id().as_str(), &msgs);
+	if let Err(e) = process_messages(sink, submissions, metrics, msgs, pending).await {
+		log::warn!(target: "hyperspace", "Failed to submit cross-chain query results to {}: {:?}", sink.name(), e);
+	}
 }
 
 async fn process_updates<A: Chain, B: Chain>(
@@ -277,6 +568,8 @@ async fn process_updates<A: Chain, B: Chain>(
 			HashSet::new()
 		};
 
+	let updates = coalesce_updates(updates, &mandatory_heights_for_undelivered_seqs);
+
 	for (msg_update_client, height, events, update_type) in updates {
 		if let Some(metrics) = metrics.as_mut() {
 			if let Err(e) = metrics.handle_events(events.as_slice()).await {
@@ -333,10 +626,13 @@ async fn process_updates<A: Chain, B: Chain>(
 	Ok(())
 }
 
+/// Submits `msgs` to `sink`, they're no longer `pending` once submitted.
 async fn process_messages<B: Chain>(
 	sink: &mut B,
+	submissions: &SubmissionLock,
 	metrics: &mut Option<MetricsHandler>,
 	msgs: Vec<Any>,
+	pending: PendingBatch,
 ) -> anyhow::Result<()> {
 	if !msgs.is_empty() {
 		if let Some(metrics) = metrics.as_ref() {
@@ -345,18 +641,22 @@ async fn process_messages<B: Chain>(
 		let type_urls = msgs.iter().map(|msg| msg.type_url.as_str()).collect::<Vec<_>>();
 		log::info!("Submitting messages to {}: {type_urls:#?}", sink.name());
 
+		let _submission = submissions.acquire().await;
 		queue::flush_message_batch(msgs, metrics.as_ref(), &*sink)
 			.await
 			.map_err(|e| anyhow!("Failed to submit messages: {:?}", e))?;
 		log::debug!(target: "hyperspace", "Successfully submitted messages to {}", sink.name());
 	}
+	pending.submitted();
 	Ok(())
 }
 
 async fn process_timeouts<A: Chain>(
 	source: &mut A,
+	submissions: &SubmissionLock,
 	metrics: &mut Option<MetricsHandler>,
 	timeout_msgs: Vec<Any>,
+	pending: PendingBatch,
 ) -> anyhow::Result<()> {
 	if !timeout_msgs.is_empty() {
 		if let Some(metrics) = metrics.as_ref() {
@@ -364,14 +664,111 @@ async fn process_timeouts<A: Chain>(
 		}
 		let type_urls = timeout_msgs.iter().map(|msg| msg.type_url.as_str()).collect::<Vec<_>>();
 		log::info!("Submitting timeout messages to {}: {type_urls:#?}", source.name());
+		let _submission = submissions.acquire().await;
 		queue::flush_message_batch(timeout_msgs, metrics.as_ref(), &*source)
 			.await
 			.map_err(|e| anyhow!("Failed to submit timeout messages: {:?}", e))?;
 		log::debug!(target: "hyperspace", "Successfully submitted timeout messages to {}", source.name());
 	}
+	pending.submitted();
 	Ok(())
 }
 
+/// Checks whether the light client of `source` on `sink` has expired, alerting as it gets close to
+/// expiry.
+async fn is_client_expired<A: Chain, B: Chain>(
+	source: &A,
+	sink: &B,
+	metrics: Option<&MetricsHandler>,
+) -> bool {
+	match client_expiry::query_client_expiry(source, sink).await {
+		Ok(Some(expiry)) => {
+			client_expiry::report_client_expiry(source, sink, &expiry);
+			if let Some(metrics) = metrics {
+				metrics.handle_client_expiry(expiry.time_until_expiry).await;
+			}
+			expiry.is_expired()
+		},
+		Ok(None) => false,
+		Err(e) => {
+			log::warn!(target: "hyperspace", "Failed to check expiry of {} client on {}: {:?}", source.name(), sink.name(), e);
+			false
+		},
+	}
+}
+
+/// Drops the optional client updates whose events can be proven with the latest update that has
+/// events instead, so that a single update is submitted for the packets of a finality event.
+///
+/// Packets that were sent and acknowledgements that were written are still committed at the
+/// later height, so their events are moved over to it. The updates carrying handshake events, which
+/// are proven at the height they were emitted, and the ones required to prove undelivered
+/// sequences are kept.
+fn coalesce_updates(
+	updates: Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>,
+	mandatory_heights: &HashSet<u64>,
+) -> Vec<(Any, Height, Vec<IbcEvent>, UpdateType)> {
+	let Some(target) = updates.iter().rposition(|(_, _, events, _)| !events.is_empty()) else {
+		return updates
+	};
+	let target_height = updates[target]
+		.2
+		.iter()
+		.map(IbcEvent::height)
+		.max()
+		.expect("events aren't empty");
+	let updates_count = updates.len();
+
+	let mut moved_events = vec![];
+	let mut coalesced = Vec::with_capacity(updates_count);
+	let mut target_index = 0;
+	for (i, (msg, height, events, update_type)) in updates.into_iter().enumerate() {
+		let can_be_moved = i < target &&
+			update_type.is_optional() &&
+			!mandatory_heights.contains(&height.revision_height) &&
+			events.iter().all(can_be_proven_later);
+		if can_be_moved {
+			moved_events.extend(events);
+			continue
+		}
+		if i == target {
+			target_index = coalesced.len();
+		}
+		coalesced.push((msg, height, events, update_type));
+	}
+	if updates_count == coalesced.len() {
+		return coalesced
+	}
+
+	log::debug!(
+		target: "hyperspace",
+		"Proving {} events at {target_height} instead of submitting {} more client updates",
+		moved_events.len(), updates_count - coalesced.len()
+	);
+	for event in &mut moved_events {
+		event.set_height(target_height);
+	}
+	let (_, _, target_events, _) = &mut coalesced[target_index];
+	moved_events.append(target_events);
+	*target_events = moved_events;
+	coalesced
+}
+
+/// Whether the message built for `event` can be proven at a later height than the event's.
+fn can_be_proven_later(event: &IbcEvent) -> bool {
+	!matches!(
+		event,
+		IbcEvent::OpenInitConnection(_) |
+			IbcEvent::OpenTryConnection(_) |
+			IbcEvent::OpenAckConnection(_) |
+			IbcEvent::OpenInitChannel(_) |
+			IbcEvent::OpenTryChannel(_) |
+			IbcEvent::OpenAckChannel(_) |
+			IbcEvent::CloseInitChannel(_) |
+			IbcEvent::UpgradeChannel(_)
+	)
+}
+
 async fn find_mandatory_heights_for_undelivered_sequences<A: Chain>(
 	source: &mut A,
 	updates: &[(Any, Height, Vec<IbcEvent>, UpdateType)],
@@ -418,3 +815,165 @@ pub mod send_packet_relay {
 		RELAY_PACKETS.store(status, Ordering::SeqCst);
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ibc::core::{
+		ics02_client::events::NewBlock,
+		ics03_connection::events::{self as connection, Attributes as ConnectionAttributes},
+		ics04_channel::{
+			events::{self as channel, Attributes as ChannelAttributes},
+			packet::Packet,
+		},
+		ics24_host::identifier::ChannelId,
+	};
+	use UpdateType::{Mandatory, Optional};
+
+	fn height(height: u64) -> Height {
+		Height::new(1, height)
+	}
+
+	#[test]
+	fn resubscribe_backoff_doubles_up_to_the_maximum_until_reset() {
+		let mut backoff = ResubscribeBackoff::new();
+		let delays = (0..8).map(|_| backoff.next_delay().as_secs()).collect::<Vec<_>>();
+		assert_eq!(delays, [1, 2, 4, 8, 16, 32, 60, 60]);
+		backoff.reset();
+		assert_eq!(backoff.next_delay(), INITIAL_RESUBSCRIBE_BACKOFF);
+	}
+
+	fn send_packet(at: u64) -> IbcEvent {
+		IbcEvent::SendPacket(channel::SendPacket { height: height(at), packet: Packet::default() })
+	}
+
+	fn write_ack(at: u64) -> IbcEvent {
+		IbcEvent::WriteAcknowledgement(channel::WriteAcknowledgement {
+			height: height(at),
+			packet: Packet::default(),
+			ack: vec![1],
+		})
+	}
+
+	fn channel_attributes(at: u64) -> ChannelAttributes {
+		ChannelAttributes {
+			height: height(at),
+			channel_id: Some(ChannelId::new(0)),
+			..Default::default()
+		}
+	}
+
+	fn open_init_channel(at: u64) -> IbcEvent {
+		IbcEvent::OpenInitChannel(channel_attributes(at).try_into().unwrap())
+	}
+
+	#[test]
+	fn only_handshake_steps_are_proven_at_their_height() {
+		let connection = ConnectionAttributes { height: height(1), ..Default::default() };
+		let cases = [
+			(IbcEvent::NewBlock(NewBlock::new(height(1))), true),
+			(send_packet(1), true),
+			(write_ack(1), true),
+			(
+				IbcEvent::ReceivePacket(channel::ReceivePacket {
+					height: height(1),
+					packet: Packet::default(),
+				}),
+				true,
+			),
+			(
+				IbcEvent::AcknowledgePacket(channel::AcknowledgePacket {
+					height: height(1),
+					packet: Packet::default(),
+				}),
+				true,
+			),
+			(
+				IbcEvent::TimeoutPacket(channel::TimeoutPacket {
+					height: height(1),
+					packet: Packet::default(),
+				}),
+				true,
+			),
+			(IbcEvent::OpenInitConnection(connection::OpenInit(connection.clone())), false),
+			(IbcEvent::OpenTryConnection(connection::OpenTry(connection.clone())), false),
+			(IbcEvent::OpenAckConnection(connection::OpenAck(connection.clone())), false),
+			(IbcEvent::OpenConfirmConnection(connection::OpenConfirm(connection)), true),
+			(open_init_channel(1), false),
+			(IbcEvent::OpenTryChannel(channel_attributes(1).try_into().unwrap()), false),
+			(IbcEvent::OpenAckChannel(channel_attributes(1).try_into().unwrap()), false),
+			(IbcEvent::OpenConfirmChannel(channel_attributes(1).try_into().unwrap()), true),
+			(IbcEvent::CloseInitChannel(channel_attributes(1).try_into().unwrap()), false),
+			(IbcEvent::CloseConfirmChannel(channel_attributes(1).try_into().unwrap()), true),
+		];
+		for (event, provable_later) in cases {
+			assert_eq!(can_be_proven_later(&event), provable_later, "{event}");
+		}
+	}
+
+	#[test]
+	fn updates_are_coalesced_into_the_last_update_with_events() {
+		let cases = [
+			(
+				"no events",
+				vec![(1, vec![], Optional), (2, vec![], Optional)],
+				vec![],
+				vec![(1, vec![]), (2, vec![])],
+			),
+			(
+				"events are proven at the last update with events",
+				vec![
+					(1, vec![send_packet(1)], Optional),
+					(2, vec![write_ack(2)], Optional),
+					(3, vec![send_packet(3)], Optional),
+					(4, vec![], Optional),
+				],
+				vec![],
+				vec![(3, vec![send_packet(3), write_ack(3), send_packet(3)]), (4, vec![])],
+			),
+			(
+				"updates without events before the target are dropped",
+				vec![(1, vec![], Optional), (2, vec![send_packet(2)], Optional)],
+				vec![],
+				vec![(2, vec![send_packet(2)])],
+			),
+			(
+				"mandatory updates are kept",
+				vec![
+					(1, vec![send_packet(1)], Mandatory),
+					(2, vec![], Optional),
+					(3, vec![send_packet(3)], Optional),
+				],
+				vec![],
+				vec![(1, vec![send_packet(1)]), (3, vec![send_packet(3)])],
+			),
+			(
+				"updates proving undelivered sequences are kept",
+				vec![(1, vec![send_packet(1)], Optional), (2, vec![send_packet(2)], Optional)],
+				vec![1],
+				vec![(1, vec![send_packet(1)]), (2, vec![send_packet(2)])],
+			),
+			(
+				"handshake steps are proven at their height",
+				vec![
+					(1, vec![open_init_channel(1)], Optional),
+					(2, vec![send_packet(2)], Optional),
+					(3, vec![send_packet(3)], Optional),
+				],
+				vec![],
+				vec![(1, vec![open_init_channel(1)]), (3, vec![send_packet(3), send_packet(3)])],
+			),
+		];
+		for (case, updates, mandatory_heights, expected) in cases {
+			let updates = updates
+				.into_iter()
+				.map(|(at, events, update_type)| (Any::default(), height(at), events, update_type))
+				.collect();
+			let coalesced = coalesce_updates(updates, &mandatory_heights.into_iter().collect())
+				.into_iter()
+				.map(|(_, height, events, _)| (height.revision_height, events))
+				.collect::<Vec<_>>();
+			assert_eq!(coalesced, expected, "{case}");
+		}
+	}
+}
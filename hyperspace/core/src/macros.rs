@@ -35,13 +35,15 @@ macro_rules! chains {
 				$name($client),
 			)*
 			Wasm(WasmChain),
+			#[cfg(feature = "chaos")]
+			Chaos(ChaosChain),
 		}
 
 		#[derive(Debug)]
 		pub enum AnyFinalityEvent {
 			$(
 				$(#[$($meta)*])*
-				$name(<$client as IbcProvider>::FinalityEvent),
+				$name(<$client as IbcEventProvider>::FinalityEvent),
 			)*
 		}
 
@@ -49,7 +51,7 @@ macro_rules! chains {
 		pub enum AnyAssetId {
 			$(
 				$(#[$($meta)*])*
-				$name(<$client as IbcProvider>::AssetId),
+				$name(<$client as IbcQueryProvider>::AssetId),
 			)*
 		}
 
@@ -57,7 +59,7 @@ macro_rules! chains {
 		pub enum AnyTransactionId {
 			$(
 				$(#[$($meta)*])*
-				$name(<$client as IbcProvider>::TransactionId),
+				$name(<$client as IbcEventProvider>::TransactionId),
 			)*
 		}
 
@@ -66,7 +68,7 @@ macro_rules! chains {
 			$(
 				$(#[$($meta)*])*
 				#[error("{0}")]
-				$name(<$client as IbcProvider>::Error),
+				$name(<$client as IbcQueryProvider>::Error),
 			)*
 			#[error("{0}")]
 			Other(String),
@@ -79,43 +81,10 @@ macro_rules! chains {
 		}
 
 		#[async_trait]
-		impl IbcProvider for AnyChain {
-			type FinalityEvent = AnyFinalityEvent;
-			type TransactionId = AnyTransactionId;
+		impl IbcQueryProvider for AnyChain {
 			type Error = AnyError;
-			type AssetId = AnyAssetId;
-
-			async fn query_latest_ibc_events<T>(
-				&mut self,
-				finality_event: Self::FinalityEvent,
-				counterparty: &T,
-			) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
-			where
-				T: Chain,
-			{
-				match self {
-					$(
-						$(#[$($meta)*])*
-						Self::$name(chain) => {
-							let finality_event = downcast!(finality_event => AnyFinalityEvent::$name)
-								.ok_or_else(|| AnyError::Other("Invalid finality event type".to_owned()))?;
-							chain.query_latest_ibc_events(finality_event, counterparty).await
-						}
-					)*
-					AnyChain::Wasm(c) =>
-						c.inner.query_latest_ibc_events(finality_event, counterparty).await,
-				}
-			}
 
-			async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
-				match self {
-					$(
-						$(#[$($meta)*])*
-						Self::$name(chain) => chain.ibc_events().await,
-					)*
-					Self::Wasm(c) => c.inner.ibc_events().await,
-				}
-			}
+			type AssetId = AnyAssetId;
 
 			async fn query_client_consensus(
 				&self,
@@ -133,6 +102,13 @@ macro_rules! chains {
 					)*
 					AnyChain::Wasm(c) =>
 						c.inner.query_client_consensus(at, client_id, consensus_height).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call(
+							"query_client_consensus",
+							c.inner.query_client_consensus(at, client_id, consensus_height),
+						)
+						.await,
 				}
 			}
 
@@ -150,6 +126,10 @@ macro_rules! chains {
 							.map_err(AnyError::$name),
 					)*
 					AnyChain::Wasm(c) => c.inner.query_client_state(at, client_id).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call("query_client_state", c.inner.query_client_state(at, client_id))
+						.await,
 				}
 			}
 
@@ -167,6 +147,13 @@ macro_rules! chains {
 							.map_err(AnyError::$name),
 					)*
 					AnyChain::Wasm(c) => c.inner.query_connection_end(at, connection_id).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call(
+							"query_connection_end",
+							c.inner.query_connection_end(at, connection_id),
+						)
+						.await,
 				}
 			}
 
@@ -185,19 +172,30 @@ macro_rules! chains {
 							.map_err(AnyError::$name),
 					)*
 					AnyChain::Wasm(c) => c.inner.query_channel_end(at, channel_id, port_id).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call(
+							"query_channel_end",
+							c.inner.query_channel_end(at, channel_id, port_id),
+						)
+						.await,
 				}
 			}
 
-			async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
+			async fn query_cross_chain_queries(&self) -> Result<Vec<CrossChainQuery>, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
 						Self::$name(chain) => chain
-							.query_proof(at, keys)
+							.query_cross_chain_queries()
 							.await
 							.map_err(AnyError::$name),
 					)*
-					AnyChain::Wasm(c) => c.inner.query_proof(at, keys).await,
+					AnyChain::Wasm(c) => c.inner.query_cross_chain_queries().await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call("query_cross_chain_queries", c.inner.query_cross_chain_queries())
+						.await,
 				}
 			}
 
@@ -218,6 +216,13 @@ macro_rules! chains {
 					)*
 					AnyChain::Wasm(c) =>
 						c.inner.query_packet_commitment(at, port_id, channel_id, seq).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call(
+							"query_packet_commitment",
+							c.inner.query_packet_commitment(at, port_id, channel_id, seq),
+						)
+						.await,
 				}
 			}
 
@@ -238,6 +243,13 @@ macro_rules! chains {
 					)*
 					AnyChain::Wasm(c) =>
 						c.inner.query_packet_acknowledgement(at, port_id, channel_id, seq).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call(
+							"query_packet_acknowledgement",
+							c.inner.query_packet_acknowledgement(at, port_id, channel_id, seq),
+						)
+						.await,
 				}
 			}
 
@@ -256,6 +268,13 @@ macro_rules! chains {
 							.map_err(AnyError::$name),
 					)*
 					AnyChain::Wasm(c) => c.inner.query_next_sequence_recv(at, port_id, channel_id).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call(
+							"query_next_sequence_recv",
+							c.inner.query_next_sequence_recv(at, port_id, channel_id),
+						)
+						.await,
 				}
 			}
 
@@ -275,6 +294,13 @@ macro_rules! chains {
 							.map_err(AnyError::$name),
 					)*
 					AnyChain::Wasm(c) => c.inner.query_packet_receipt(at, port_id, channel_id, seq).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call(
+							"query_packet_receipt",
+							c.inner.query_packet_receipt(at, port_id, channel_id, seq),
+						)
+						.await,
 				}
 			}
 
@@ -288,6 +314,10 @@ macro_rules! chains {
 							.map_err(AnyError::$name),
 					)*
 					AnyChain::Wasm(c) => c.inner.latest_height_and_timestamp().await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call("latest_height_and_timestamp", c.inner.latest_height_and_timestamp())
+						.await,
 				}
 			}
 
@@ -306,6 +336,13 @@ macro_rules! chains {
 							.map_err(AnyError::$name),
 					)*
 					Self::Wasm(c) => c.inner.query_packet_commitments(at, channel_id, port_id).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_packet_commitments",
+							c.inner.query_packet_commitments(at, channel_id, port_id),
+						)
+						.await,
 				}
 			}
 
@@ -324,6 +361,13 @@ macro_rules! chains {
 							.map_err(AnyError::$name),
 					)*
 					Self::Wasm(c) => c.inner.query_packet_acknowledgements(at, channel_id, port_id).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_packet_acknowledgements",
+							c.inner.query_packet_acknowledgements(at, channel_id, port_id),
+						)
+						.await,
 				}
 			}
 
@@ -343,6 +387,13 @@ macro_rules! chains {
 							.map_err(AnyError::$name),
 					)*
 					Self::Wasm(c) => c.inner.query_unreceived_packets(at, channel_id, port_id, seqs).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_unreceived_packets",
+							c.inner.query_unreceived_packets(at, channel_id, port_id, seqs),
+						)
+						.await,
 				}
 			}
 
@@ -363,6 +414,18 @@ macro_rules! chains {
 					)*
 					Self::Wasm(c) =>
 						c.inner.query_unreceived_acknowledgements(at, channel_id, port_id, seqs).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_unreceived_acknowledgements",
+							c.inner.query_unreceived_acknowledgements(
+								at,
+								channel_id,
+								port_id,
+								seqs,
+							),
+						)
+						.await,
 				}
 			}
 
@@ -373,6 +436,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.channel_whitelist(),
 					)*
 					Self::Wasm(c) => c.inner.channel_whitelist(),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.channel_whitelist(),
 				}
 			}
 
@@ -380,111 +445,135 @@ macro_rules! chains {
 				&self,
 				at: Height,
 				connection_id: &ConnectionId,
+				next_key: Option<Vec<u8>>,
+				limit: u64,
 			) -> Result<QueryChannelsResponse, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
 						Self::$name(chain) => chain
-							.query_connection_channels(at, connection_id)
+							.query_connection_channels(at, connection_id, next_key, limit)
 							.await
 							.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.query_connection_channels(at, connection_id).await,
+					Self::Wasm(c) =>
+						c.inner.query_connection_channels(at, connection_id, next_key, limit).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_connection_channels",
+							c.inner.query_connection_channels(at, connection_id, next_key, limit),
+						)
+						.await,
 				}
 			}
 
-			async fn query_send_packets(
-				&self,
-				channel_id: ChannelId,
-				port_id: PortId,
-				seqs: Vec<u64>,
-			) -> Result<Vec<ibc_rpc::PacketInfo>, Self::Error> {
+			fn expected_block_time(&self) -> Duration {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain
-							.query_send_packets(channel_id, port_id, seqs)
-							.await
-							.map_err(AnyError::$name),
+						Self::$name(chain) => chain.expected_block_time(),
 					)*
-					Self::Wasm(c) => c.inner.query_send_packets(channel_id, port_id, seqs).await,
+					Self::Wasm(c) => c.inner.expected_block_time(),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.expected_block_time(),
 				}
 			}
 
-			async fn query_received_packets(
+			async fn query_ibc_balance(
 				&self,
-				channel_id: ChannelId,
-				port_id: PortId,
-				seqs: Vec<u64>,
-			) -> Result<Vec<ibc_rpc::PacketInfo>, Self::Error> {
-				match self {
+				asset_id: AnyAssetId,
+			) -> Result<Vec<PrefixedCoin>, Self::Error> {
+				match (self, asset_id) {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain
-							.query_received_packets(channel_id, port_id, seqs)
-							.await
-							.map_err(AnyError::$name),
+						(Self::$name(chain), AnyAssetId::$name(asset_id)) =>
+							chain.query_ibc_balance(asset_id.into()).await.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.query_received_packets(channel_id, port_id, seqs).await,
+					(Self::Wasm(c), asset_id) => c.inner.query_ibc_balance(asset_id).await,
+					#[cfg(feature = "chaos")]
+					(Self::Chaos(c), asset_id) => c.faults
+						.call("query_ibc_balance", c.inner.query_ibc_balance(asset_id))
+						.await,
+					(chain, _) => panic!("query_ibc_balance is not implemented for {}", chain.name()),
 				}
 			}
 
-			fn expected_block_time(&self) -> Duration {
+			async fn query_denom_trace(
+				&self,
+				denom_hash: String,
+			) -> Result<QueryDenomTraceResponse, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.expected_block_time(),
+						Self::$name(chain) =>
+							chain.query_denom_trace(denom_hash).await.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.expected_block_time(),
+					AnyChain::Wasm(c) => c.inner.query_denom_trace(denom_hash).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call("query_denom_trace", c.inner.query_denom_trace(denom_hash))
+						.await,
 				}
 			}
 
-			async fn query_client_update_time_and_height(
+			async fn query_denom_traces(
 				&self,
-				client_id: ClientId,
-				client_height: Height,
-			) -> Result<(Height, Timestamp), Self::Error> {
+				next_key: Option<Vec<u8>>,
+				limit: u64,
+			) -> Result<QueryDenomTracesResponse, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
 						Self::$name(chain) => chain
-							.query_client_update_time_and_height(client_id, client_height)
+							.query_denom_traces(next_key, limit)
 							.await
 							.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) =>
-						c.inner.query_client_update_time_and_height(client_id, client_height).await,
+					AnyChain::Wasm(c) => c.inner.query_denom_traces(next_key, limit).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call("query_denom_traces", c.inner.query_denom_traces(next_key, limit))
+						.await,
 				}
 			}
 
-			async fn query_host_consensus_state_proof(
-				&self,
-				client_state: &AnyClientState,
-			) -> Result<Option<Vec<u8>>, Self::Error> {
+			async fn query_denom_hash(&self, trace: String) -> Result<String, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain
-							.query_host_consensus_state_proof(client_state)
-							.await
-							.map_err(AnyError::$name),
+						Self::$name(chain) =>
+							chain.query_denom_hash(trace).await.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.query_host_consensus_state_proof(client_state).await,
+					AnyChain::Wasm(c) => c.inner.query_denom_hash(trace).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) =>
+						c.faults.call("query_denom_hash", c.inner.query_denom_hash(trace)).await,
 				}
 			}
 
-			async fn query_ibc_balance(
+			async fn query_packet_fees(
 				&self,
-				asset_id: AnyAssetId,
-			) -> Result<Vec<PrefixedCoin>, Self::Error> {
-				match (self, asset_id) {
+				channel_id: ChannelId,
+				port_id: PortId,
+				sequence: u64,
+			) -> Result<Vec<PacketFee>, Self::Error> {
+				match self {
 					$(
 						$(#[$($meta)*])*
-						(Self::$name(chain), AnyAssetId::$name(asset_id)) =>
-							chain.query_ibc_balance(asset_id.into()).await.map_err(AnyError::$name),
+						Self::$name(chain) => chain
+							.query_packet_fees(channel_id, port_id, sequence)
+							.await
+							.map_err(AnyError::$name),
 					)*
-					(Self::Wasm(c), asset_id) => c.inner.query_ibc_balance(asset_id).await,
-					(chain, _) => panic!("query_ibc_balance is not implemented for {}", chain.name()),
+					AnyChain::Wasm(c) => c.inner.query_packet_fees(channel_id, port_id, sequence).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call(
+							"query_packet_fees",
+							c.inner.query_packet_fees(channel_id, port_id, sequence),
+						)
+						.await,
 				}
 			}
 
@@ -495,6 +584,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.connection_prefix(),
 					)*
 					AnyChain::Wasm(c) => c.inner.connection_prefix(),
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.inner.connection_prefix(),
 				}
 			}
 
@@ -505,6 +596,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.client_id(),
 					)*
 					AnyChain::Wasm(c) => c.inner.client_id(),
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.inner.client_id(),
 				}
 			}
 
@@ -515,6 +608,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.set_client_id(client_id),
 					)*
 					Self::Wasm(c) => c.inner.set_client_id(client_id),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.set_client_id(client_id),
 				}
 			}
 
@@ -525,6 +620,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.connection_id(),
 					)*
 					AnyChain::Wasm(c) => c.inner.connection_id(),
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.inner.connection_id(),
 				}
 			}
 
@@ -535,6 +632,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.client_type(),
 					)*
 					AnyChain::Wasm(c) => c.inner.client_type(),
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.inner.client_type(),
 				}
 			}
 
@@ -545,26 +644,48 @@ macro_rules! chains {
 						Self::$name(chain) => chain.query_timestamp_at(block_number).await.map_err(AnyError::$name),
 					)*
 					Self::Wasm(c) => c.inner.query_timestamp_at(block_number).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("query_timestamp_at", c.inner.query_timestamp_at(block_number))
+						.await,
 				}
 			}
 
-			async fn query_clients(&self) -> Result<Vec<ClientId>, Self::Error> {
+			async fn query_clients(
+				&self,
+				next_key: Option<Vec<u8>>,
+				limit: u64,
+			) -> Result<Page<ClientId>, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.query_clients().await.map_err(AnyError::$name),
+						Self::$name(chain) =>
+							chain.query_clients(next_key, limit).await.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.query_clients().await,
+					Self::Wasm(c) => c.inner.query_clients(next_key, limit).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("query_clients", c.inner.query_clients(next_key, limit))
+						.await,
 				}
 			}
 
-			async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
+			async fn query_channels(
+				&self,
+				next_key: Option<Vec<u8>>,
+				limit: u64,
+			) -> Result<Page<(ChannelId, PortId)>, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.query_channels().await.map_err(AnyError::$name),
+						Self::$name(chain) =>
+							chain.query_channels(next_key, limit).await.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.query_channels().await,
+					Self::Wasm(c) => c.inner.query_channels(next_key, limit).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("query_channels", c.inner.query_channels(next_key, limit))
+						.await,
 				}
 			}
 
@@ -580,166 +701,522 @@ macro_rules! chains {
 							chain.query_connection_using_client(height, client_id).await.map_err(AnyError::$name),
 					)*
 					Self::Wasm(c) => c.inner.query_connection_using_client(height, client_id).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_connection_using_client",
+							c.inner.query_connection_using_client(height, client_id),
+						)
+						.await,
 				}
 			}
 
-			async fn is_update_required(
+			async fn query_connections(
 				&self,
-				latest_height: u64,
-				latest_client_height_on_counterparty: u64,
-			) -> Result<bool, Self::Error> {
+				next_key: Option<Vec<u8>>,
+				limit: u64,
+			) -> Result<QueryConnectionsResponse, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain
-							.is_update_required(latest_height, latest_client_height_on_counterparty)
-							.await
-							.map_err(AnyError::$name),
+						Self::$name(chain) =>
+							chain.query_connections(next_key, limit).await.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c
-						.inner
-						.is_update_required(latest_height, latest_client_height_on_counterparty)
-						.await
-						.map_err(Into::into),
+					Self::Wasm(c) => c.inner.query_connections(next_key, limit).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("query_connections", c.inner.query_connections(next_key, limit))
+						.await,
 				}
 			}
 
-			async fn initialize_client_state(
-				&self,
-			) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+			async fn query_connection_params(&self) -> Result<ConnectionParams, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.initialize_client_state().await.map_err(AnyError::$name),
+						Self::$name(chain) =>
+							chain.query_connection_params().await.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.initialize_client_state().await,
+					Self::Wasm(c) => c.inner.query_connection_params().await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("query_connection_params", c.inner.query_connection_params())
+						.await,
 				}
 			}
 
-			async fn query_client_id_from_tx_hash(
-				&self,
-				tx_id: Self::TransactionId,
-			) -> Result<ClientId, Self::Error> {
+			fn set_channel_whitelist(&mut self, channel_whitelist: std::collections::HashSet<(ChannelId, PortId)>) {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain
-							.query_client_id_from_tx_hash(
-								downcast!(tx_id => AnyTransactionId::$name)
-									.expect("Should be $name transaction id"),
-							)
-							.await
-							.map_err(AnyError::$name),
+						Self::$name(chain) => chain.set_channel_whitelist(channel_whitelist),
 					)*
-					Self::Wasm(c) => c.inner.query_client_id_from_tx_hash(tx_id).await,
+					Self::Wasm(c) => c.inner.set_channel_whitelist(channel_whitelist),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.set_channel_whitelist(channel_whitelist),
 				}
 			}
 
-			async fn upload_wasm(&self, wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
+			fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.upload_wasm(wasm).await.map_err(AnyError::$name),
+						Self::$name(chain) => chain.add_channel_to_whitelist(channel),
 					)*
-					Self::Wasm(c) => c.inner.upload_wasm(wasm).await,
+					Self::Wasm(c) => c.inner.add_channel_to_whitelist(channel),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.add_channel_to_whitelist(channel),
 				}
 			}
 
-			async fn query_connection_id_from_tx_hash(
-				&self,
-				tx_id: Self::TransactionId,
-			) -> Result<ConnectionId, Self::Error> {
+			fn set_connection_id(&mut self, connection_id: ConnectionId) {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain
-							.query_connection_id_from_tx_hash(
-								downcast!(tx_id => AnyTransactionId::$name)
-									.expect("Should be $name transaction id"),
-							)
-							.await
-							.map_err(AnyError::$name),
+						Self::$name(chain) => chain.set_connection_id(connection_id),
 					)*
-					Self::Wasm(c) => c.inner.query_connection_id_from_tx_hash(tx_id).await,
+					Self::Wasm(c) => c.inner.set_connection_id(connection_id),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.set_connection_id(connection_id),
 				}
 			}
+		}
 
-			async fn query_channel_id_from_tx_hash(
-				&self,
-				tx_id: Self::TransactionId,
-			) -> Result<(ChannelId, PortId), Self::Error> {
+		#[async_trait]
+		impl IbcEventProvider for AnyChain {
+			type FinalityEvent = AnyFinalityEvent;
+
+			type TransactionId = AnyTransactionId;
+
+			async fn query_latest_ibc_events<T>(
+				&mut self,
+				finality_event: Self::FinalityEvent,
+				counterparty: &T,
+			) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+			where
+				T: Chain,
+			{
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain
-							.query_channel_id_from_tx_hash(
-								downcast!(tx_id => AnyTransactionId::$name)
-									.expect("Should be $name transaction id"),
-							)
-							.await
-							.map_err(AnyError::$name),
+						Self::$name(chain) => {
+							let finality_event = downcast!(finality_event => AnyFinalityEvent::$name)
+								.ok_or_else(|| AnyError::Other("Invalid finality event type".to_owned()))?;
+							chain.query_latest_ibc_events(finality_event, counterparty).await
+						}
 					)*
-					Self::Wasm(c) => c.inner.query_channel_id_from_tx_hash(tx_id).await,
+					AnyChain::Wasm(c) =>
+						c.inner.query_latest_ibc_events(finality_event, counterparty).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call(
+							"query_latest_ibc_events",
+							c.inner.query_latest_ibc_events(finality_event, counterparty),
+						)
+						.await,
 				}
 			}
 
-			fn set_channel_whitelist(&mut self, channel_whitelist: std::collections::HashSet<(ChannelId, PortId)>) {
+			async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.set_channel_whitelist(channel_whitelist),
+						Self::$name(chain) => chain.ibc_events().await,
 					)*
-					Self::Wasm(c) => c.inner.set_channel_whitelist(channel_whitelist),
+					Self::Wasm(c) => c.inner.ibc_events().await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.ibc_events().await,
 				}
 			}
 
-			fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
+			async fn prefetch_client_update<T>(
+				&self,
+				height: Height,
+				counterparty: &T,
+			) -> Result<(), anyhow::Error>
+			where
+				T: Chain,
+			{
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.add_channel_to_whitelist(channel),
+						Self::$name(chain) => chain.prefetch_client_update(height, counterparty).await,
 					)*
-					Self::Wasm(c) => c.inner.add_channel_to_whitelist(channel),
+					Self::Wasm(c) => c.inner.prefetch_client_update(height, counterparty).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"prefetch_client_update",
+							c.inner.prefetch_client_update(height, counterparty),
+						)
+						.await,
 				}
 			}
 
-			fn set_connection_id(&mut self, connection_id: ConnectionId) {
+			async fn query_send_packets(
+				&self,
+				channel_id: ChannelId,
+				port_id: PortId,
+				seqs: Vec<u64>,
+			) -> Result<Vec<ibc_rpc::PacketInfo>, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.set_connection_id(connection_id),
+						Self::$name(chain) => chain
+							.query_send_packets(channel_id, port_id, seqs)
+							.await
+							.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.set_connection_id(connection_id),
+					Self::Wasm(c) => c.inner.query_send_packets(channel_id, port_id, seqs).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_send_packets",
+							c.inner.query_send_packets(channel_id, port_id, seqs),
+						)
+						.await,
 				}
 			}
-		}
 
-		#[async_trait]
-		impl MisbehaviourHandler for AnyChain {
-			async fn check_for_misbehaviour<C: Chain>(
+			async fn query_received_packets(
 				&self,
-				counterparty: &C,
-				client_message: AnyClientMessage,
-			) -> Result<(), anyhow::Error> {
+				channel_id: ChannelId,
+				port_id: PortId,
+				seqs: Vec<u64>,
+			) -> Result<Vec<ibc_rpc::PacketInfo>, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) =>
-							chain.check_for_misbehaviour(counterparty, client_message).await,
+						Self::$name(chain) => chain
+							.query_received_packets(channel_id, port_id, seqs)
+							.await
+							.map_err(AnyError::$name),
 					)*
-					AnyChain::Wasm(c) => c.inner.check_for_misbehaviour(counterparty, client_message).await,
+					Self::Wasm(c) => c.inner.query_received_packets(channel_id, port_id, seqs).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_received_packets",
+							c.inner.query_received_packets(channel_id, port_id, seqs),
+						)
+						.await,
 				}
 			}
-		}
 
-		impl KeyProvider for AnyChain {
-			fn account_id(&self) -> Signer {
+			async fn query_packet_events(
+				&self,
+				from_height: Height,
+				to_height: Height,
+				filter: ibc_rpc::PacketEventsFilter,
+			) -> Result<Vec<IbcEvent>, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.account_id(),
-					)*
-					AnyChain::Wasm(c) => c.inner.account_id(),
+						Self::$name(chain) => chain
+							.query_packet_events(from_height, to_height, filter)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.query_packet_events(from_height, to_height, filter).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_packet_events",
+							c.inner.query_packet_events(from_height, to_height, filter),
+						)
+						.await,
+				}
+			}
+
+			async fn query_client_update_time_and_height(
+				&self,
+				client_id: ClientId,
+				client_height: Height,
+			) -> Result<(Height, Timestamp), Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_client_update_time_and_height(client_id, client_height)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) =>
+						c.inner.query_client_update_time_and_height(client_id, client_height).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_client_update_time_and_height",
+							c.inner.query_client_update_time_and_height(client_id, client_height),
+						)
+						.await,
+				}
+			}
+
+			async fn is_update_required(
+				&self,
+				latest_height: u64,
+				latest_client_height_on_counterparty: u64,
+			) -> Result<bool, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.is_update_required(latest_height, latest_client_height_on_counterparty)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c
+						.inner
+						.is_update_required(latest_height, latest_client_height_on_counterparty)
+						.await
+						.map_err(Into::into),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c
+						.inner
+						.is_update_required(latest_height, latest_client_height_on_counterparty)
+						.await
+						.map_err(Into::into),
+				}
+			}
+
+			async fn query_client_id_from_tx_hash(
+				&self,
+				tx_id: Self::TransactionId,
+			) -> Result<ClientId, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_client_id_from_tx_hash(
+								downcast!(tx_id => AnyTransactionId::$name)
+									.expect("Should be $name transaction id"),
+							)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.query_client_id_from_tx_hash(tx_id).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_client_id_from_tx_hash",
+							c.inner.query_client_id_from_tx_hash(tx_id),
+						)
+						.await,
+				}
+			}
+
+			async fn query_connection_id_from_tx_hash(
+				&self,
+				tx_id: Self::TransactionId,
+			) -> Result<ConnectionId, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_connection_id_from_tx_hash(
+								downcast!(tx_id => AnyTransactionId::$name)
+									.expect("Should be $name transaction id"),
+							)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.query_connection_id_from_tx_hash(tx_id).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_connection_id_from_tx_hash",
+							c.inner.query_connection_id_from_tx_hash(tx_id),
+						)
+						.await,
+				}
+			}
+
+			async fn query_channel_id_from_tx_hash(
+				&self,
+				tx_id: Self::TransactionId,
+			) -> Result<(ChannelId, PortId), Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_channel_id_from_tx_hash(
+								downcast!(tx_id => AnyTransactionId::$name)
+									.expect("Should be $name transaction id"),
+							)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.query_channel_id_from_tx_hash(tx_id).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_channel_id_from_tx_hash",
+							c.inner.query_channel_id_from_tx_hash(tx_id),
+						)
+						.await,
+				}
+			}
+		}
+
+		#[async_trait]
+		impl ProofProvider for AnyChain {
+			async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_proof(at, keys)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					AnyChain::Wasm(c) => c.inner.query_proof(at, keys).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) =>
+						c.faults.call("query_proof", c.inner.query_proof(at, keys)).await,
+				}
+			}
+
+			async fn query_proofs(
+				&self,
+				at: Height,
+				keys: Vec<Vec<Vec<u8>>>,
+			) -> Result<Vec<Vec<u8>>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_proofs(at, keys)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					AnyChain::Wasm(c) => c.inner.query_proofs(at, keys).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) =>
+						c.faults.call("query_proofs", c.inner.query_proofs(at, keys)).await,
+				}
+			}
+
+			async fn query_value_with_proof(
+				&self,
+				at: Height,
+				path: Path,
+			) -> Result<(Option<Vec<u8>>, Vec<u8>), Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_value_with_proof(at, path)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					AnyChain::Wasm(c) => c.inner.query_value_with_proof(at, path).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call("query_value_with_proof", c.inner.query_value_with_proof(at, path))
+						.await,
+				}
+			}
+
+			async fn query_non_membership_proof(
+				&self,
+				at: Height,
+				path: Path,
+			) -> Result<Vec<u8>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_non_membership_proof(at, path)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					AnyChain::Wasm(c) => c.inner.query_non_membership_proof(at, path).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call(
+							"query_non_membership_proof",
+							c.inner.query_non_membership_proof(at, path),
+						)
+						.await,
+				}
+			}
+
+			async fn query_host_consensus_state_proof(
+				&self,
+				client_state: &AnyClientState,
+			) -> Result<Option<Vec<u8>>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_host_consensus_state_proof(client_state)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.query_host_consensus_state_proof(client_state).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"query_host_consensus_state_proof",
+							c.inner.query_host_consensus_state_proof(client_state),
+						)
+						.await,
+				}
+			}
+
+			async fn initialize_client_state(
+				&self,
+			) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.initialize_client_state().await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.initialize_client_state().await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("initialize_client_state", c.inner.initialize_client_state())
+						.await,
+				}
+			}
+		}
+
+		#[async_trait]
+		impl MisbehaviourHandler for AnyChain {
+			async fn check_for_misbehaviour<C: Chain>(
+				&self,
+				counterparty: &C,
+				client_message: AnyClientMessage,
+			) -> Result<(), anyhow::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) =>
+							chain.check_for_misbehaviour(counterparty, client_message).await,
+					)*
+					AnyChain::Wasm(c) => c.inner.check_for_misbehaviour(counterparty, client_message).await,
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.faults
+						.call(
+							"check_for_misbehaviour",
+							c.inner.check_for_misbehaviour(counterparty, client_message),
+						)
+						.await,
+				}
+			}
+		}
+
+		impl KeyProvider for AnyChain {
+			fn account_id(&self) -> Signer {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.account_id(),
+					)*
+					AnyChain::Wasm(c) => c.inner.account_id(),
+					#[cfg(feature = "chaos")]
+					AnyChain::Chaos(c) => c.inner.account_id(),
 				}
 			}
 		}
@@ -753,26 +1230,58 @@ macro_rules! chains {
 						Self::$name(chain) => chain.name(),
 					)*
 					Self::Wasm(c) => c.inner.name(),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.name(),
+				}
+			}
+
+			fn chain_id(&self) -> ChainId {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.chain_id(),
+					)*
+					Self::Wasm(c) => c.inner.chain_id(),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.chain_id(),
 				}
 			}
 
-			fn block_max_weight(&self) -> u64 {
+			fn tx_budget(&self) -> TxBudget {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.block_max_weight(),
+						Self::$name(chain) => chain.tx_budget(),
 					)*
-					Self::Wasm(c) => c.inner.block_max_weight(),
+					Self::Wasm(c) => c.inner.tx_budget(),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.tx_budget(),
 				}
 			}
 
-			async fn estimate_weight(&self, msg: Vec<Any>) -> Result<u64, Self::Error> {
+			fn max_transaction_size(&self) -> Option<usize> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.estimate_weight(msg).await.map_err(AnyError::$name),
+						Self::$name(chain) => chain.max_transaction_size(),
 					)*
-					Self::Wasm(c) => c.inner.estimate_weight(msg).await,
+					Self::Wasm(c) => c.inner.max_transaction_size(),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.max_transaction_size(),
+				}
+			}
+
+			async fn estimate_cost(&self, msg: Vec<Any>) -> Result<TxCost, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.estimate_cost(msg).await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.estimate_cost(msg).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("estimate_cost", c.inner.estimate_cost(msg))
+						.await,
 				}
 			}
 
@@ -792,6 +1301,8 @@ macro_rules! chains {
 						},
 					)*
 					Self::Wasm(c) => c.inner.finality_notifications().await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.finality_notifications().await,
 				}
 			}
 
@@ -812,6 +1323,81 @@ macro_rules! chains {
 							.collect::<Result<Vec<_>, _>>()?;
 						chain.inner.submit(messages).await.map_err(AnyError::into)
 					},
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults.call("submit", c.inner.submit(messages)).await,
+				}
+			}
+
+			async fn submit_batch(
+				&self,
+				batches: Vec<Vec<Any>>,
+			) -> Result<Vec<Self::TransactionId>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.submit_batch(batches)
+							.await
+							.map_err(AnyError::$name)
+							.map(|ids| ids.into_iter().map(AnyTransactionId::$name).collect()),
+					)*
+					Self::Wasm(chain) => {
+						let batches = batches
+							.into_iter()
+							.map(|messages| {
+								messages
+									.into_iter()
+									.map(|msg| wrap_any_msg_into_wasm(msg, chain.code_hash.clone()))
+									.collect::<Result<Vec<_>, _>>()
+							})
+							.collect::<Result<Vec<_>, _>>()?;
+						chain.inner.submit_batch(batches).await.map_err(AnyError::into)
+					},
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("submit_batch", c.inner.submit_batch(batches))
+						.await,
+				}
+			}
+
+			async fn submit_and_wait_for_events(
+				&self,
+				messages: Vec<Any>,
+			) -> Result<Vec<IbcEvent>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.submit_and_wait_for_events(messages)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					Self::Wasm(chain) => {
+						let messages = messages
+							.into_iter()
+							.map(|msg| wrap_any_msg_into_wasm(msg, chain.code_hash.clone()))
+							.collect::<Result<Vec<_>, _>>()?;
+						chain.inner.submit_and_wait_for_events(messages).await.map_err(AnyError::into)
+					},
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"submit_and_wait_for_events",
+							c.inner.submit_and_wait_for_events(messages),
+						)
+						.await,
+				}
+			}
+
+			async fn upload_wasm(&self, wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.upload_wasm(wasm).await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.upload_wasm(wasm).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults.call("upload_wasm", c.inner.upload_wasm(wasm)).await,
 				}
 			}
 
@@ -825,6 +1411,10 @@ macro_rules! chains {
 						Self::$name(chain) => chain.query_client_message(update).await.map_err(AnyError::$name),
 					)*
 					Self::Wasm(c) => c.inner.query_client_message(update).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("query_client_message", c.inner.query_client_message(update))
+						.await,
 				}
 			}
 
@@ -835,6 +1425,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.get_proof_height(block_height).await,
 					)*
 					Self::Wasm(c) => c.inner.get_proof_height(block_height).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.get_proof_height(block_height).await,
 				}
 			}
 
@@ -845,6 +1437,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.handle_error(e).await,
 					)*
 					Self::Wasm(c) => c.inner.handle_error(e).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.handle_error(e).await,
 				}
 			}
 
@@ -855,6 +1449,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.rpc_call_delay(),
 					)*
 					Self::Wasm(c) => c.inner.rpc_call_delay(),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.rpc_call_delay(),
 				}
 			}
 
@@ -865,6 +1461,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.set_rpc_call_delay(d),
 					)*
 					Self::Wasm(c) => c.inner.set_rpc_call_delay(d),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.set_rpc_call_delay(d),
 				}
 			}
 
@@ -875,6 +1473,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.common_state(),
 					)*
 					Self::Wasm(c) => c.inner.common_state(),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.common_state(),
 				}
 			}
 
@@ -885,6 +1485,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.common_state_mut(),
 					)*
 					Self::Wasm(c) => c.inner.common_state_mut(),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.common_state_mut(),
 				}
 			}
 
@@ -895,6 +1497,61 @@ macro_rules! chains {
 						Self::$name(chain) => chain.reconnect().await,
 					)*
 					Self::Wasm(c) => c.inner.reconnect().await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.reconnect().await,
+				}
+			}
+
+			fn active_rpc_endpoint(&self) -> Option<String> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.active_rpc_endpoint(),
+					)*
+					Self::Wasm(c) => c.inner.active_rpc_endpoint(),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.active_rpc_endpoint(),
+				}
+			}
+
+			async fn is_node_syncing(&self) -> Result<Option<bool>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.is_node_syncing().await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.is_node_syncing().await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("is_node_syncing", c.inner.is_node_syncing())
+						.await,
+				}
+			}
+
+			async fn ibc_module_version(&self) -> Result<Option<String>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.ibc_module_version().await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.ibc_module_version().await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) =>
+						c.faults.call("ibc_module_version", c.inner.ibc_module_version()).await,
+				}
+			}
+
+			async fn rotate_signing_key(&self, private_key: String) -> Result<Signer, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.rotate_signing_key(private_key).await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.rotate_signing_key(private_key).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("rotate_signing_key", c.inner.rotate_signing_key(private_key))
+						.await,
 				}
 			}
 		}
@@ -908,6 +1565,10 @@ macro_rules! chains {
 						Self::$name(chain) => chain.is_synced(counterparty).await.map_err(Into::into),
 					)*
 					Self::Wasm(c) => c.inner.is_synced(counterparty).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call("is_synced", c.inner.is_synced(counterparty))
+						.await,
 				}
 			}
 
@@ -922,6 +1583,28 @@ macro_rules! chains {
 							chain.fetch_mandatory_updates(counterparty).await.map_err(Into::into),
 					)*
 					Self::Wasm(c) => c.inner.fetch_mandatory_updates(counterparty).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.faults
+						.call(
+							"fetch_mandatory_updates",
+							c.inner.fetch_mandatory_updates(counterparty),
+						)
+						.await,
+				}
+			}
+		}
+
+		impl AnyChain {
+			/// Parses an asset id of this chain given on the command line
+			pub fn parse_asset_id(&self, asset_id: &str) -> anyhow::Result<AnyAssetId> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(_) => Ok(AnyAssetId::$name(crate::keys::ParseAssetId::parse_asset_id(asset_id)?)),
+					)*
+					Self::Wasm(c) => c.inner.parse_asset_id(asset_id),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.parse_asset_id(asset_id),
 				}
 			}
 		}
@@ -935,6 +1618,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.set_client_id(client_id),
 					)*
 					Self::Wasm(chain) => chain.inner.set_client_id(client_id),
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.set_client_id(client_id),
 				}
 			}
 		}
@@ -949,6 +1634,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.send_transfer(params).await.map_err(AnyError::$name),
 					)*
 					Self::Wasm(c) => c.inner.send_transfer(params).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.send_transfer(params).await,
 				}
 			}
 
@@ -963,6 +1650,50 @@ macro_rules! chains {
 						Self::$name(chain) => chain.send_ordered_packet(channel_id, timeout).await.map_err(AnyError::$name),
 					)*
 					Self::Wasm(c) => c.inner.send_ordered_packet(channel_id, timeout).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.send_ordered_packet(channel_id, timeout).await,
+				}
+			}
+
+			async fn open_ica_channel(&self, connection_id: ConnectionId) -> Result<(), Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.open_ica_channel(connection_id).await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.open_ica_channel(connection_id).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.open_ica_channel(connection_id).await,
+				}
+			}
+
+			async fn send_raw_packet(
+				&self,
+				port_id: PortId,
+				channel_id: ChannelId,
+				data: Vec<u8>,
+				timeout: Timeout,
+			) -> Result<(), Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.send_raw_packet(port_id, channel_id, data, timeout).await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.send_raw_packet(port_id, channel_id, data, timeout).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.send_raw_packet(port_id, channel_id, data, timeout).await,
+				}
+			}
+
+			async fn force_timeout(&self, port_id: PortId, channel_id: ChannelId) -> Result<(), Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.force_timeout(port_id, channel_id).await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.force_timeout(port_id, channel_id).await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.force_timeout(port_id, channel_id).await,
 				}
 			}
 
@@ -973,6 +1704,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.subscribe_blocks().await,
 					)*
 					Self::Wasm(c) => c.inner.subscribe_blocks().await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.subscribe_blocks().await,
 				}
 			}
 
@@ -983,6 +1716,8 @@ macro_rules! chains {
 						Self::$name(chain) => chain.increase_counters().await.map_err(AnyError::$name),
 					)*
 					Self::Wasm(c) => c.inner.increase_counters().await,
+					#[cfg(feature = "chaos")]
+					Self::Chaos(c) => c.inner.increase_counters().await,
 				}
 			}
 		}
@@ -1003,6 +1738,42 @@ macro_rules! chains {
 				}
 			}
 
+			pub fn name(&self) -> &str {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => &chain.name,
+					)*
+				}
+			}
+
+			pub fn client_id(&self) -> Option<ClientId> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.client_id.clone(),
+					)*
+				}
+			}
+
+			pub fn connection_id(&self) -> Option<ConnectionId> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.connection_id.clone(),
+					)*
+				}
+			}
+
+			pub fn channel_whitelist(&self) -> Vec<(ChannelId, PortId)> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.channel_whitelist.clone(),
+					)*
+				}
+			}
+
 			pub fn set_client_id(&mut self, client_id: ClientId) {
 				match self {
 					$(
@@ -1059,6 +1830,33 @@ macro_rules! chains {
 					)*
 				}
 			}
+
+			/// Moves the raw signing secret of the config into an encrypted keystore at `path`.
+			pub fn migrate_to_keystore(
+				&mut self,
+				path: std::path::PathBuf,
+				passphrase: &str,
+			) -> anyhow::Result<()> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => primitives::keystore::KeystoreConfig::migrate_to_keystore(
+							chain, path, passphrase,
+						)?,
+					)*
+				}
+				Ok(())
+			}
+
+			/// Signs with `secret` instead of the raw secret or keystore of the config.
+			pub fn set_signing_secret(&mut self, secret: String) {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => primitives::keystore::KeystoreConfig::set_secret(chain, secret),
+					)*
+				}
+			}
 		}
 	};
 }
@@ -17,39 +17,112 @@ use crate::send_packet_relay::packet_relay_status;
 use rand::Rng;
 use sp_runtime::Either::{Left, Right};
 use std::{
+	collections::{BTreeMap, BTreeSet},
 	sync::{
 		atomic::{AtomicUsize, Ordering},
-		Arc,
+		Arc, Mutex,
 	},
 	time::Duration,
 };
 use tokio::{task::JoinSet, time::sleep};
 
-use crate::packets::utils::{
-	construct_ack_message, construct_recv_message, construct_timeout_message,
-	get_timeout_proof_height, verify_delay_passed, VerifyDelayOn,
+use crate::packets::{
+	sequence_gap::{SequenceGap, DEFAULT_SEQUENCE_GAP_ALERT_BLOCKS},
+	stuck::{StuckPackets, DEFAULT_STUCK_PACKET_ALERT_AGE},
+	utils::{
+		construct_ack_messages, construct_recv_messages, construct_timeout_message,
+		get_timeout_proof_height, packet_pays_min_fee, retain_unexpired_packets,
+		verify_delay_passed, VerifyDelayOn,
+	},
 };
 use ibc::{
 	applications::transfer::packet::PacketData,
 	core::{
 		ics02_client::client_state::ClientState as ClientStateT,
 		ics03_connection::connection::ConnectionEnd,
-		ics04_channel::channel::{ChannelEnd, State},
+		ics04_channel::channel::{ChannelEnd, Order, State},
+		ics24_host::{
+			identifier::{ChannelId, PortId},
+			path::ReceiptsPath,
+		},
 	},
+	events::IbcEvent,
+	timestamp::Timestamp,
 	Height,
 };
 use ibc_proto::google::protobuf::Any;
 use pallet_ibc::light_clients::AnyClientState;
 use primitives::{
-	error::Error, find_suitable_proof_height_for_client, packet_info_to_packet,
-	query_undelivered_acks, query_undelivered_sequences, Chain, UndeliveredType,
+	error::Error, filter_undelivered_acks, filter_undelivered_sequences,
+	find_suitable_proof_height_for_client, packet_info_to_packet, query_undelivered_acks,
+	query_undelivered_sequences, Chain, UndeliveredType,
 };
 
+pub mod client_expiry;
 pub mod connection_delay;
+pub mod sequence_gap;
+pub mod stuck;
 pub mod utils;
+pub mod workers;
 
 pub const PROCESS_PACKETS_BATCH_SIZE: usize = 100;
 
+/// Sequences of the packets sent on a channel, and of the acknowledgements written on it, that may
+/// still have to be relayed. They're discovered from the events of the source chain, so that the
+/// commitments of the whole channel don't have to be queried on every finality notification.
+#[derive(Debug, Clone, Default)]
+pub struct PendingPackets {
+	pub sends: BTreeSet<u64>,
+	pub acks: BTreeSet<u64>,
+	/// Look for undelivered packets among all the commitments of the channel instead, e.g. to pick
+	/// up the packets sent while the relayer was offline.
+	pub full_scan: bool,
+	/// Progress of the sink, if the channel is ordered
+	pub sequence_gap: SequenceGap,
+	/// Age of the undelivered packets and why they weren't relayed yet
+	pub stuck: StuckPackets,
+}
+
+impl PendingPackets {
+	pub fn full_scan() -> Self {
+		Self { full_scan: true, ..Default::default() }
+	}
+
+	/// Records the packets sent on, and the acknowledgements written on, the channel
+	/// `channel_id`/`port_id` of the chain that emitted `events`.
+	pub fn extend_from_events<'a>(
+		&mut self,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+		events: impl IntoIterator<Item = &'a IbcEvent>,
+	) {
+		for event in events {
+			match event {
+				IbcEvent::SendPacket(send_packet)
+					if send_packet.packet.source_channel == *channel_id &&
+						send_packet.packet.source_port == *port_id =>
+				{
+					self.sends.insert(send_packet.packet.sequence.into());
+				},
+				IbcEvent::WriteAcknowledgement(write_ack)
+					if write_ack.packet.destination_channel == *channel_id &&
+						write_ack.packet.destination_port == *port_id =>
+				{
+					self.acks.insert(write_ack.packet.sequence.into());
+				},
+				_ => {},
+			}
+		}
+	}
+
+	/// Adds the sequences of `other`, keeping a full scan requested.
+	pub fn merge(&mut self, other: PendingPackets) {
+		self.sends.extend(other.sends);
+		self.acks.extend(other.acks);
+		self.full_scan |= other.full_scan;
+	}
+}
+
 /// Returns a tuple of messages, with the first item being packets that are ready to be sent to the
 /// sink chain. And the second item being packet timeouts that should be sent to the source.
 ///
@@ -65,90 +138,121 @@ pub async fn query_ready_and_timed_out_packets(
 ) -> Result<(Vec<Any>, Vec<Any>), anyhow::Error> {
 	let mut messages = vec![];
 	let mut timeout_messages = vec![];
-	let (source_height, source_timestamp) = source.latest_height_and_timestamp().await?;
-	let (sink_height, sink_timestamp) = sink.latest_height_and_timestamp().await?;
-	let channel_whitelist = source.channel_whitelist();
-
-	// TODO: parallelize this
-	for (channel_id, port_id) in channel_whitelist {
-		let source_channel_response = match source
-			.query_channel_end(source_height, channel_id, port_id.clone())
-			.await
-		{
-			Ok(response) => response,
-			// this can happen in case the channel is not yet created
-			Err(e) => {
-				log::warn!(target: "hyperspace", "Failed to query channel end for chain {}, channel {}/{}: {:?}", source.name(), channel_id, port_id, e);
-				continue
-			},
-		};
-		let source_channel_end = match source_channel_response.channel.map(ChannelEnd::try_from) {
-			Some(Ok(source_channel)) => source_channel,
-			_ => {
-				log::warn!(target: "hyperspace", "ChannelEnd not found for {:?}/{:?}", channel_id, port_id.clone());
-				continue
-			},
-		};
-		// we're only interested in open or closed channels
-		if !matches!(source_channel_end.state, State::Open | State::Closed) {
-			log::trace!(target: "hyperspace", "Skipping channel {:?}/{:?} because it is not open or closed", channel_id, port_id.clone());
-			continue
-		}
-		let connection_id = source_channel_end
-			.connection_hops
-			.get(0)
-			.ok_or_else(|| Error::Custom("Channel end missing connection id".to_string()))?
-			.clone();
-		let connection_response =
-			source.query_connection_end(source_height, connection_id.clone()).await?;
-		let source_connection_end =
-			ConnectionEnd::try_from(connection_response.connection.ok_or_else(|| {
-				Error::Custom(format!(
-					"[query_ready_and_timed_out_packets] ConnectionEnd not found for {connection_id:?}"
-				))
-			})?)?;
-
-		let sink_channel_id = source_channel_end.counterparty().channel_id.ok_or_else(|| {
-			Error::Custom(
-				" An Open Channel End should have a valid counterparty channel id".to_string(),
+	let source_latest = source.latest_height_and_timestamp().await?;
+	let sink_latest = sink.latest_height_and_timestamp().await?;
+
+	for (channel_id, port_id) in source.channel_whitelist() {
+		let (mut channel_messages, mut channel_timeout_messages) =
+			query_ready_and_timed_out_channel_packets(
+				source,
+				sink,
+				source_latest,
+				sink_latest,
+				channel_id,
+				port_id,
+				&mut PendingPackets::full_scan(),
 			)
-		})?;
-		let sink_port_id = source_channel_end.counterparty().port_id.clone();
-		let sink_channel_response = match sink
-			.query_channel_end(sink_height, sink_channel_id, sink_port_id.clone())
-			.await
-		{
-			Ok(response) => response,
-			Err(e) => {
-				// this can happen in case the channel is not yet created
-				log::warn!(target: "hyperspace", "Failed to query channel end for chain {}, channel {}/{}: {:?}", sink.name(), channel_id, port_id, e);
-				continue
-			},
-		};
-
-		let sink_channel_end = match sink_channel_response.channel.map(ChannelEnd::try_from) {
-			Some(Ok(sink_channel)) => sink_channel,
-			_ => {
-				log::warn!(target: "hyperspace", "ChannelEnd not found for {:?}/{:?}", channel_id, port_id.clone());
-				continue
-			},
-		};
-
-		let next_sequence_recv = sink
-			.query_next_sequence_recv(sink_height, &sink_port_id, &sink_channel_id)
 			.await?;
+		messages.append(&mut channel_messages);
+		timeout_messages.append(&mut channel_timeout_messages);
+	}
+
+	Ok((messages, timeout_messages))
+}
+
+/// Same as [`query_ready_and_timed_out_packets`], for a single whitelisted channel of `source`,
+/// given the latest heights and timestamps of both chains.
+///
+/// Only the `pending` packets and acknowledgements are looked at, unless a full scan of the channel
+/// is requested. The ones that are still undelivered are kept pending.
+pub async fn query_ready_and_timed_out_channel_packets(
+	source: &impl Chain,
+	sink: &impl Chain,
+	(source_height, source_timestamp): (Height, Timestamp),
+	(sink_height, sink_timestamp): (Height, Timestamp),
+	channel_id: ChannelId,
+	port_id: PortId,
+	pending: &mut PendingPackets,
+) -> Result<(Vec<Any>, Vec<Any>), anyhow::Error> {
+	let mut messages = vec![];
+	let mut timeout_messages = vec![];
+	let source_channel_response = match source
+		.query_channel_end(source_height, channel_id, port_id.clone())
+		.await
+	{
+		Ok(response) => response,
+		// this can happen in case the channel is not yet created
+		Err(e) => {
+			log::warn!(target: "hyperspace", "Failed to query channel end for chain {}, channel {}/{}: {:?}", source.name(), channel_id, port_id, e);
+			return Ok((messages, timeout_messages))
+		},
+	};
+	let source_channel_end = match source_channel_response.channel.map(ChannelEnd::try_from) {
+		Some(Ok(source_channel)) => source_channel,
+		_ => {
+			log::warn!(target: "hyperspace", "ChannelEnd not found for {:?}/{:?}", channel_id, port_id.clone());
+			return Ok((messages, timeout_messages))
+		},
+	};
+	// we're only interested in open or closed channels
+	if !matches!(source_channel_end.state, State::Open | State::Closed) {
+		log::trace!(target: "hyperspace", "Skipping channel {:?}/{:?} because it is not open or closed", channel_id, port_id.clone());
+		return Ok((messages, timeout_messages))
+	}
+	let connection_id = source_channel_end
+		.connection_hops
+		.get(0)
+		.ok_or_else(|| Error::Custom("Channel end missing connection id".to_string()))?
+		.clone();
+	let connection_response =
+		source.query_connection_end(source_height, connection_id.clone()).await?;
+	let source_connection_end =
+		ConnectionEnd::try_from(connection_response.connection.ok_or_else(|| {
+			Error::Custom(format!(
+				"[query_ready_and_timed_out_packets] ConnectionEnd not found for {connection_id:?}"
+			))
+		})?)?;
 
-		let source_client_state_on_sink =
-			sink.query_client_state(sink_height, source.client_id()).await?;
-		let source_client_state_on_sink = AnyClientState::try_from(
-			source_client_state_on_sink.client_state.ok_or_else(|| {
-				Error::Custom(format!(
-					"Client state for {} should exist on {}",
-					source.name(),
-					sink.name()
-				))
-			})?,
+	let sink_channel_id = source_channel_end.counterparty().channel_id.ok_or_else(|| {
+		Error::Custom(
+			" An Open Channel End should have a valid counterparty channel id".to_string(),
 		)
+	})?;
+	let sink_port_id = source_channel_end.counterparty().port_id.clone();
+	let sink_channel_response = match sink
+		.query_channel_end(sink_height, sink_channel_id, sink_port_id.clone())
+		.await
+	{
+		Ok(response) => response,
+		Err(e) => {
+			// this can happen in case the channel is not yet created
+			log::warn!(target: "hyperspace", "Failed to query channel end for chain {}, channel {}/{}: {:?}", sink.name(), channel_id, port_id, e);
+			return Ok((messages, timeout_messages))
+		},
+	};
+
+	let sink_channel_end = match sink_channel_response.channel.map(ChannelEnd::try_from) {
+		Some(Ok(sink_channel)) => sink_channel,
+		_ => {
+			log::warn!(target: "hyperspace", "ChannelEnd not found for {:?}/{:?}", channel_id, port_id.clone());
+			return Ok((messages, timeout_messages))
+		},
+	};
+
+	let next_sequence_recv = sink
+		.query_next_sequence_recv(sink_height, &sink_port_id, &sink_channel_id)
+		.await?;
+
+	let source_client_state_on_sink =
+		sink.query_client_state(sink_height, source.client_id()).await?;
+	let source_client_state_on_sink =
+		AnyClientState::try_from(source_client_state_on_sink.client_state.ok_or_else(|| {
+			Error::Custom(format!(
+				"Client state for {} should exist on {}",
+				source.name(),
+				sink.name()
+			))
+		})?)
 		.map_err(|_| {
 			Error::Custom(format!(
 				"Invalid Client state for {} should found on {}",
@@ -157,17 +261,16 @@ pub async fn query_ready_and_timed_out_packets(
 			))
 		})?;
 
-		let sink_client_state_on_source =
-			source.query_client_state(source_height, sink.client_id()).await?;
-		let sink_client_state_on_source = AnyClientState::try_from(
-			sink_client_state_on_source.client_state.ok_or_else(|| {
-				Error::Custom(format!(
-					"Client state for {} should exist on {}",
-					source.name(),
-					sink.name()
-				))
-			})?,
-		)
+	let sink_client_state_on_source =
+		source.query_client_state(source_height, sink.client_id()).await?;
+	let sink_client_state_on_source =
+		AnyClientState::try_from(sink_client_state_on_source.client_state.ok_or_else(|| {
+			Error::Custom(format!(
+				"Client state for {} should exist on {}",
+				source.name(),
+				sink.name()
+			))
+		})?)
 		.map_err(|_| {
 			Error::Custom(format!(
 				"Invalid Client state for {} should found on {}",
@@ -175,158 +278,161 @@ pub async fn query_ready_and_timed_out_packets(
 				sink.name()
 			))
 		})?;
-		let latest_sink_height_on_source = sink_client_state_on_source.latest_height();
-		let latest_source_height_on_sink = source_client_state_on_sink.latest_height();
-
-		let max_packets_to_process = source.common_state().max_packets_to_process;
-
-		// query packets that are waiting for connection delay.
-		let seqs = query_undelivered_sequences(
+	let latest_sink_height_on_source = sink_client_state_on_source.latest_height();
+	let latest_source_height_on_sink = source_client_state_on_sink.latest_height();
+
+	// the strategy may be configured on either end of the channel
+	let strategy = source
+		.common_state()
+		.channel_strategy(&channel_id, &port_id)
+		.or_else(|| sink.common_state().channel_strategy(&sink_channel_id, &sink_port_id))
+		.cloned()
+		.unwrap_or_default();
+	let max_packets_to_process =
+		strategy.max_batch_size.unwrap_or(source.common_state().max_packets_to_process);
+
+	// query packets that are waiting for connection delay.
+	let seqs = if pending.full_scan {
+		query_undelivered_sequences(
+			source_height,
+			sink_height,
+			channel_id,
+			port_id.clone(),
+			source,
+			sink,
+		)
+		.await?
+	} else {
+		filter_undelivered_sequences(
 			source_height,
 			sink_height,
 			channel_id,
 			port_id.clone(),
+			pending.sends.iter().copied().collect(),
 			source,
 			sink,
 		)
 		.await?
+	};
+	pending.sends = seqs.iter().copied().collect();
+	if matches!(source_channel_end.ordering, Order::Ordered | Order::OrderedAllowTimeout) {
+		pending.sequence_gap.observe(
+			next_sequence_recv.next_sequence_receive,
+			seqs.iter().min().copied(),
+			sink_height,
+			strategy.sequence_gap_alert_blocks.unwrap_or(DEFAULT_SEQUENCE_GAP_ALERT_BLOCKS),
+		);
+	}
+	pending.stuck.observe(
+		&seqs,
+		source_height,
+		strategy
+			.stuck_packet_alert_secs
+			.map(Duration::from_secs)
+			.unwrap_or(DEFAULT_STUCK_PACKET_ALERT_AGE),
+		strategy.stuck_packet_alert_blocks,
+	);
+	let seqs = source
+		.common_state()
+		.select_undelivered_sequences(&channel_id, &port_id, UndeliveredType::Recvs, seqs)
 		.into_iter()
 		.take(max_packets_to_process)
 		.collect::<Vec<_>>();
 
-		log::debug!(target: "hyperspace", "Found {} undelivered packets for {:?}/{:?} for {seqs:?}", seqs.len(), channel_id, port_id.clone());
-
-		let mut send_packets = source.query_send_packets(channel_id, port_id.clone(), seqs).await?;
-		log::trace!(target: "hyperspace", "SendPackets count before deduplication: {}", send_packets.len());
-		send_packets.sort();
-		send_packets.dedup();
-		log::trace!(target: "hyperspace", "SendPackets count after deduplication: {}", send_packets.len());
-		let mut recv_packets_join_set: JoinSet<Result<_, anyhow::Error>> = JoinSet::new();
-		let source = Arc::new(source.clone());
-		let sink = Arc::new(sink.clone());
-		let timeout_packets_count = Arc::new(AtomicUsize::new(0));
-		let send_packets_count = Arc::new(AtomicUsize::new(0));
-		for send_packets in send_packets.chunks(PROCESS_PACKETS_BATCH_SIZE) {
-			for send_packet in send_packets.iter().cloned() {
-				let source_connection_end = source_connection_end.clone();
-				let sink_channel_end = sink_channel_end.clone();
-				let source_connection_end = source_connection_end.clone();
-				let source = source.clone();
-				let sink = sink.clone();
-				let duration = Duration::from_millis(
-					rand::thread_rng().gen_range(1..source.rpc_call_delay().as_millis() as u64),
-				);
-				let timeout_packets_count = timeout_packets_count.clone();
-				let recv_packets_count = send_packets_count.clone();
-				recv_packets_join_set.spawn(async move {
-					sleep(duration).await;
-					let source = &source;
-					let sink = &sink;
-					let packet = packet_info_to_packet(&send_packet);
-					// Check if packet has timed out
-					let packet_height = send_packet.height.ok_or_else(|| {
-						Error::Custom(format!("Packet height not found for packet {packet:?}"))
-					})?;
-
-					if packet.timed_out(&sink_timestamp, sink_height) {
-						timeout_packets_count.fetch_add(1, Ordering::SeqCst);
-						// so we know this packet has timed out on the sink, we need to find the maximum
-						// consensus state height at which we can generate a non-membership proof of the
-						// packet for the sink's client on the source.
-						let proof_height =
-							if let Some(proof_height) = get_timeout_proof_height(
-								&**source,
-								&**sink,
-								source_height,
-								sink_height,
-								sink_timestamp,
-								latest_sink_height_on_source,
-								&packet,
-								packet_height,
-							)
-							.await
-						{
-							proof_height
-						} else {
-							log::trace!(target: "hyperspace", "Skipping packet as no timeout proof height could be found: {:?}", packet);
-							return Ok(None)
-						};
-
-						// given this maximum height, has the connection delay been satisfied?
-						if !verify_delay_passed(
-							&**source,
-							&**sink,
-							source_timestamp,
-							source_height,
-							sink_timestamp,
-							sink_height,
-							source_connection_end.delay_period(),
-							proof_height,
-							VerifyDelayOn::Source,
-						)
-							.await?
-						{
-							log::trace!(target: "hyperspace", "Skipping packet as connection delay has not passed {:?}", packet);
-							return Ok(None)
+	log::debug!(target: "hyperspace", "Found {} undelivered packets for {:?}/{:?} for {seqs:?}", seqs.len(), channel_id, port_id.clone());
+
+	let mut send_packets = source.query_send_packets(channel_id, port_id.clone(), seqs).await?;
+	log::trace!(target: "hyperspace", "SendPackets count before deduplication: {}", send_packets.len());
+	send_packets.sort();
+	send_packets.dedup();
+	log::trace!(target: "hyperspace", "SendPackets count after deduplication: {}", send_packets.len());
+	let mut recv_packets_join_set: JoinSet<Result<_, anyhow::Error>> = JoinSet::new();
+	let source = Arc::new(source.clone());
+	let sink = Arc::new(sink.clone());
+	let timeout_packets_count = Arc::new(AtomicUsize::new(0));
+	let send_packets_count = Arc::new(AtomicUsize::new(0));
+	let delayed_packets_count = Arc::new(AtomicUsize::new(0));
+	// why the packets that aren't relayed this time were skipped
+	let skip_reasons = Arc::new(Mutex::new(BTreeMap::<u64, &'static str>::new()));
+	for send_packets in send_packets.chunks(PROCESS_PACKETS_BATCH_SIZE) {
+		for send_packet in send_packets.iter().cloned() {
+			let source_connection_end = source_connection_end.clone();
+			let sink_channel_end = sink_channel_end.clone();
+			let source_connection_end = source_connection_end.clone();
+			let source = source.clone();
+			let sink = sink.clone();
+			let duration = Duration::from_millis(
+				rand::thread_rng().gen_range(1..source.rpc_call_delay().as_millis() as u64),
+			);
+			let timeout_packets_count = timeout_packets_count.clone();
+			let recv_packets_count = send_packets_count.clone();
+			let delayed_packets_count = delayed_packets_count.clone();
+			let skip_reasons = skip_reasons.clone();
+			let strategy = strategy.clone();
+			recv_packets_join_set.spawn(async move {
+				sleep(duration).await;
+				let source = &source;
+				let sink = &sink;
+				let packet = packet_info_to_packet(&send_packet);
+				// Check if packet has timed out
+				let packet_height = send_packet.height.ok_or_else(|| {
+					Error::Custom(format!("Packet height not found for packet {packet:?}"))
+				})?;
+
+				let timed_out = packet.timed_out(&sink_timestamp, sink_height);
+				// On ordered channels allowing timeouts, a packet that timed out is first received
+				// by the sink, which skips it and writes the receipt that proves the timeout.
+				let skip_on_sink = timed_out &&
+					sink_channel_end.ordering == Order::OrderedAllowTimeout &&
+					sink.query_value_with_proof(
+						sink_height,
+						ReceiptsPath {
+							port_id: packet.destination_port.clone(),
+							channel_id: packet.destination_channel,
+							sequence: packet.sequence,
 						}
-
-						// lets construct the timeout message to be sent to the source
-						let msg = construct_timeout_message(
-							&**source,
-							&**sink,
-							&sink_channel_end,
-							packet,
-							next_sequence_recv.next_sequence_receive,
-							proof_height,
-						)
-							.await?;
-						return Ok(Some(Left(msg)))
-					} else {
-						log::trace!(target: "hyperspace", "The packet has not timed out yet: {:?}", packet);
-					}
-
-					// If packet has not timed out but channel is closed on sink we skip
-					// Since we have no reference point for when this channel was closed so we can't
-					// calculate connection delays yet
-					if sink_channel_end.state == State::Closed {
-						log::debug!(target: "hyperspace", "Skipping packet as channel is closed on sink: {:?}", packet);
+						.into(),
+					)
+					.await?
+					.0
+					.is_none();
+
+				if timed_out && !skip_on_sink {
+					if !strategy.relay_timeouts {
+						log::trace!(target: "hyperspace", "Skipping timed out packet as timeouts aren't relayed on its channel: {:?}", packet);
+						skip_reasons.lock().unwrap().insert(packet.sequence.into(), "timeouts are not relayed on its channel");
 						return Ok(None)
 					}
-
-					#[cfg(feature = "testing")]
-					// If packet relay status is paused skip
-					if !packet_relay_status() {
+					if !packet_pays_min_fee(&**source, &strategy, &packet, |fee| &fee.fee.timeout_fee).await? {
+						log::trace!(target: "hyperspace", "Skipping timed out packet as its timeout fee is below the minimum fee: {:?}", packet);
+						skip_reasons.lock().unwrap().insert(packet.sequence.into(), "timeout fee is below the minimum fee");
 						return Ok(None)
 					}
-
-					// Check if packet is ready to be sent to sink
-					// If sink does not have a client height that is equal to or greater than the packet
-					// creation height, we can't send it yet, packet_info.height should represent the packet
-					// creation height on source chain
-					if packet_height > latest_source_height_on_sink.revision_height {
-						// Sink does not have client update required to prove recv packet message
-						log::debug!(target: "hyperspace", "Skipping packet {:?} as sink does not have client update required to prove recv packet message", packet);
-						recv_packets_count.fetch_add(1, Ordering::SeqCst);
-						return Ok(None)
-					}
-
-					let proof_height = if let Some(proof_height) = find_suitable_proof_height_for_client(
-						&**source,
-						&**sink,
-						sink_height,
-						source.client_id(),
-						Height::new(latest_source_height_on_sink.revision_number, packet_height),
-						None,
-						latest_source_height_on_sink,
-					)
+					// so we know this packet has timed out on the sink, we need to find the maximum
+					// consensus state height at which we can generate a non-membership proof of the
+					// packet for the sink's client on the source.
+					let proof_height =
+						if let Some(proof_height) = get_timeout_proof_height(
+							&**source,
+							&**sink,
+							source_height,
+							sink_height,
+							sink_timestamp,
+							latest_sink_height_on_source,
+							&packet,
+							packet_height,
+						)
 						.await
 					{
 						proof_height
 					} else {
-						log::trace!(target: "hyperspace", "Skipping packet {:?} as no proof height could be found", packet);
+						log::trace!(target: "hyperspace", "Skipping packet as no timeout proof height could be found: {:?}", packet);
+						timeout_packets_count.fetch_add(1, Ordering::SeqCst);
+						skip_reasons.lock().unwrap().insert(packet.sequence.into(), "no height to prove the timeout at");
 						return Ok(None)
 					};
 
+					// given this maximum height, has the connection delay been satisfied?
 					if !verify_delay_passed(
 						&**source,
 						&**sink,
@@ -336,65 +442,183 @@ pub async fn query_ready_and_timed_out_packets(
 						sink_height,
 						source_connection_end.delay_period(),
 						proof_height,
-						VerifyDelayOn::Sink,
+						VerifyDelayOn::Source,
 					)
 						.await?
 					{
+						// the packet is still undelivered, keep the source flagged so that it is
+						// timed out once the delay has elapsed
 						log::trace!(target: "hyperspace", "Skipping packet as connection delay has not passed {:?}", packet);
+						delayed_packets_count.fetch_add(1, Ordering::SeqCst);
+						timeout_packets_count.fetch_add(1, Ordering::SeqCst);
+						skip_reasons.lock().unwrap().insert(packet.sequence.into(), "waiting for the connection delay");
 						return Ok(None)
 					}
 
-					if packet.timeout_height.is_zero() && packet.timeout_timestamp.nanoseconds() == 0 {
-						log::warn!(target: "hyperspace", "Skipping packet as packet timeout is zero: {}", packet.sequence);
-						return Ok(None)
-					}
-
-					let list = &source.common_state().skip_tokens_list;
-
-					let decoded_dara: PacketData = serde_json::from_str(&String::from_utf8_lossy(packet.data.as_ref())).map_err(|e| {
-						Error::Custom(format!(
-						"Failed to decode packet data for packet {:?}: {:?}",
-						packet, e
-						))
-					})?;
-
-					if list.iter().any(|skiped_denom| decoded_dara.token.denom.base_denom.as_str() == skiped_denom) {
-						log::info!(target: "hyperspace", "Skipping packet as uosmo packet: {:?}", packet);
-						return Ok(None)
-					}
-
-					let msg = construct_recv_message(&**source, &**sink, packet, proof_height).await?;
-					Ok(Some(Right(msg)))
-				});
-			}
+					timeout_packets_count.fetch_add(1, Ordering::SeqCst);
+					// lets construct the timeout message to be sent to the source
+					let msg = construct_timeout_message(
+						&**source,
+						&**sink,
+						&sink_channel_end,
+						packet,
+						next_sequence_recv.next_sequence_receive,
+						proof_height,
+					)
+						.await?;
+					return Ok(Some(Left(msg)))
+				} else if skip_on_sink {
+					log::debug!(target: "hyperspace", "Relaying timed out packet to be skipped by the sink: {:?}", packet);
+				} else {
+					log::trace!(target: "hyperspace", "The packet has not timed out yet: {:?}", packet);
+				}
+
+				// If packet has not timed out but channel is closed on sink we skip
+				// Since we have no reference point for when this channel was closed so we can't
+				// calculate connection delays yet
+				if sink_channel_end.state == State::Closed {
+					log::debug!(target: "hyperspace", "Skipping packet as channel is closed on sink: {:?}", packet);
+					skip_reasons.lock().unwrap().insert(packet.sequence.into(), "channel is closed on the sink");
+					return Ok(None)
+				}
+
+				#[cfg(feature = "testing")]
+				// If packet relay status is paused skip
+				if !packet_relay_status() {
+					return Ok(None)
+				}
+
+				// Check if packet is ready to be sent to sink
+				// If sink does not have a client height that is equal to or greater than the packet
+				// creation height, we can't send it yet, packet_info.height should represent the packet
+				// creation height on source chain
+				if packet_height > latest_source_height_on_sink.revision_height {
+					// Sink does not have client update required to prove recv packet message
+					log::debug!(target: "hyperspace", "Skipping packet {:?} as sink does not have client update required to prove recv packet message", packet);
+					recv_packets_count.fetch_add(1, Ordering::SeqCst);
+					skip_reasons.lock().unwrap().insert(packet.sequence.into(), "client on the sink is not updated to the packet height");
+					return Ok(None)
+				}
+
+				let proof_height = if let Some(proof_height) = find_suitable_proof_height_for_client(
+					&**source,
+					&**sink,
+					sink_height,
+					source.client_id(),
+					Height::new(latest_source_height_on_sink.revision_number, packet_height),
+					None,
+					latest_source_height_on_sink,
+				)
+					.await
+				{
+					proof_height
+				} else {
+					log::trace!(target: "hyperspace", "Skipping packet {:?} as no proof height could be found", packet);
+					skip_reasons.lock().unwrap().insert(packet.sequence.into(), "no height to prove the packet at");
+					return Ok(None)
+				};
+
+				if !verify_delay_passed(
+					&**source,
+					&**sink,
+					source_timestamp,
+					source_height,
+					sink_timestamp,
+					sink_height,
+					source_connection_end.delay_period(),
+					proof_height,
+					VerifyDelayOn::Sink,
+				)
+					.await?
+				{
+					// the packet is still undelivered, keep the sink flagged so that it is
+					// picked up again once the delay has elapsed
+					log::trace!(target: "hyperspace", "Skipping packet as connection delay has not passed {:?}", packet);
+					delayed_packets_count.fetch_add(1, Ordering::SeqCst);
+					recv_packets_count.fetch_add(1, Ordering::SeqCst);
+					skip_reasons.lock().unwrap().insert(packet.sequence.into(), "waiting for the connection delay");
+					return Ok(None)
+				}
+
+				if packet.timeout_height.is_zero() && packet.timeout_timestamp.nanoseconds() == 0 {
+					log::warn!(target: "hyperspace", "Skipping packet as packet timeout is zero: {}", packet.sequence);
+					skip_reasons.lock().unwrap().insert(packet.sequence.into(), "packet has no timeout");
+					return Ok(None)
+				}
+
+				let list = &source.common_state().skip_tokens_list;
+
+				let decoded_dara: PacketData = serde_json::from_str(&String::from_utf8_lossy(packet.data.as_ref())).map_err(|e| {
+					Error::Custom(format!(
+					"Failed to decode packet data for packet {:?}: {:?}",
+					packet, e
+					))
+				})?;
+
+				if list.iter().any(|skiped_denom| decoded_dara.token.denom.base_denom.as_str() == skiped_denom) {
+					log::info!(target: "hyperspace", "Skipping packet as uosmo packet: {:?}", packet);
+					skip_reasons.lock().unwrap().insert(packet.sequence.into(), "token is skipped");
+					return Ok(None)
+				}
+
+				if !packet_pays_min_fee(&**source, &strategy, &packet, |fee| &fee.fee.recv_fee).await? {
+					log::trace!(target: "hyperspace", "Skipping packet as its receive fee is below the minimum fee: {:?}", packet);
+					skip_reasons.lock().unwrap().insert(packet.sequence.into(), "receive fee is below the minimum fee");
+					return Ok(None)
+				}
+
+				Ok(Some(Right((packet, proof_height))))
+			});
 		}
+	}
 
-		while let Some(result) = recv_packets_join_set.join_next().await {
-			let Some(either) = result?? else { continue };
-			match either {
-				Left(msg) => timeout_messages.push(msg),
-				Right(msg) => messages.push(msg),
-			}
+	let mut recv_packets = vec![];
+	while let Some(result) = recv_packets_join_set.join_next().await {
+		let Some(either) = result?? else { continue };
+		match either {
+			Left(msg) => timeout_messages.push(msg),
+			Right(recv_packet) => recv_packets.push(recv_packet),
 		}
+	}
+	for (sequence, reason) in skip_reasons.lock().unwrap().iter() {
+		pending.stuck.set_reason(*sequence, *reason);
+	}
+	// the sink may have moved past the timeout of some packets while they were being processed, the
+	// timed out packets of ordered channels that allow timeouts are received on purpose though
+	if sink_channel_end.ordering != Order::OrderedAllowTimeout {
+		retain_unexpired_packets(&*sink, &mut recv_packets).await?;
+	}
+	// the proofs of all the packets are fetched together
+	messages.extend(construct_recv_messages(&*source, &*sink, recv_packets).await?);
+
+	let timeouts_count = timeout_packets_count.load(Ordering::SeqCst);
+	log::debug!(target: "hyperspace", "Found {timeouts_count} packets that have timed out");
+	source
+		.on_undelivered_sequences(timeouts_count != 0, UndeliveredType::Timeouts)
+		.await;
+
+	let sends_count = send_packets_count.load(Ordering::SeqCst);
+	log::debug!(target: "hyperspace", "Found {sends_count} sent packets");
+	let delayed_count = delayed_packets_count.load(Ordering::SeqCst);
+	if delayed_count != 0 {
+		log::debug!(target: "hyperspace", "{delayed_count} packets on {channel_id}/{port_id} are waiting for the connection delay of {:?}", source_connection_end.delay_period());
+	}
+	sink.on_undelivered_sequences(sends_count != 0, UndeliveredType::Recvs).await;
 
-		let timeouts_count = timeout_packets_count.load(Ordering::SeqCst);
-		log::debug!(target: "hyperspace", "Found {timeouts_count} packets that have timed out");
-		source
-			.on_undelivered_sequences(timeouts_count != 0, UndeliveredType::Timeouts)
-			.await;
-
-		let sends_count = send_packets_count.load(Ordering::SeqCst);
-		log::debug!(target: "hyperspace", "Found {sends_count} sent packets");
-		sink.on_undelivered_sequences(sends_count != 0, UndeliveredType::Recvs).await;
+	// Get acknowledgement messages
+	if source_channel_end.state == State::Closed {
+		log::trace!(target: "hyperspace", "Skipping acknowledgements for channel {:?} as channel is closed on source", channel_id);
+		return Ok((messages, timeout_messages))
+	}
 
-		// Get acknowledgement messages
-		if source_channel_end.state == State::Closed {
-			log::trace!(target: "hyperspace", "Skipping acknowledgements for channel {:?} as channel is closed on source", channel_id);
-			continue
-		}
+	if !strategy.relay_acks {
+		log::trace!(target: "hyperspace", "Skipping acknowledgements for channel {:?} as they aren't relayed on it", channel_id);
+		return Ok((messages, timeout_messages))
+	}
 
-		// query acknowledgements that are waiting for connection delay.
-		let acks = query_undelivered_acks(
+	// query acknowledgements that are waiting for connection delay.
+	let acks = if pending.full_scan {
+		query_undelivered_acks(
 			source_height,
 			sink_height,
 			channel_id,
@@ -403,98 +627,154 @@ pub async fn query_ready_and_timed_out_packets(
 			&*sink,
 		)
 		.await?
+	} else {
+		filter_undelivered_acks(
+			source_height,
+			sink_height,
+			channel_id,
+			port_id.clone(),
+			pending.acks.iter().copied().collect(),
+			&*source,
+			&*sink,
+		)
+		.await?
+	};
+	pending.acks = acks.iter().copied().collect();
+	let acks = source
+		.common_state()
+		.select_undelivered_sequences(&channel_id, &port_id, UndeliveredType::Acks, acks)
 		.into_iter()
 		.take(max_packets_to_process)
 		.collect::<Vec<_>>();
 
-		let acknowledgements =
-			source.query_received_packets(channel_id, port_id.clone(), acks).await?;
-		log::trace!(target: "hyperspace", "Got acknowledgements for channel {:?}: {:?}", channel_id, acknowledgements);
-		let mut acknowledgements_join_set: JoinSet<Result<_, anyhow::Error>> = JoinSet::new();
-		sink.on_undelivered_sequences(!acknowledgements.is_empty(), UndeliveredType::Acks)
-			.await;
-		for acknowledgements in acknowledgements.chunks(PROCESS_PACKETS_BATCH_SIZE) {
-			for acknowledgement in acknowledgements.iter().cloned() {
-				let source_connection_end = source_connection_end.clone();
-				let source = source.clone();
-				let sink = sink.clone();
-				let duration1 = Duration::from_millis(
-					rand::thread_rng().gen_range(1..source.rpc_call_delay().as_millis() as u64),
-				);
-				acknowledgements_join_set.spawn(async move {
-					sleep(duration1).await;
-					let source = &source;
-					let sink = &sink;
-					let packet = packet_info_to_packet(&acknowledgement);
-					let ack = if let Some(ack) = acknowledgement.ack {
-						ack
-					} else {
-						// Packet has no valid acknowledgement, skip
-						log::trace!(target: "hyperspace", "Skipping acknowledgement for packet {:?} as packet has no valid acknowledgement", packet);
-						return Ok(None)
-					};
-
-					// Check if ack is ready to be sent to sink
-					// If sink does not have a client height that is equal to or greater than the packet
-					// creation height, we can't send it yet packet_info.height should represent the
-					// acknowledgement creation height on source chain
-					let ack_height = acknowledgement.height.ok_or_else(|| {
-						Error::Custom(format!("Packet height not found for packet {packet:?}"))
-					})?;
-					if ack_height > latest_source_height_on_sink.revision_height {
-						// Sink does not have client update required to prove acknowledgement packet message
-						log::trace!(target: "hyperspace", "Skipping acknowledgement for packet {:?} as sink does not have client update required to prove acknowledgement packet message", packet);
-						return Ok(None)
-					}
-
-					log::trace!(target: "hyperspace", "sink_height: {:?}, latest_source_height_on_sink: {:?}, acknowledgement.height: {}", sink_height, latest_source_height_on_sink, ack_height);
+	let acknowledgements = source.query_received_packets(channel_id, port_id.clone(), acks).await?;
+	log::trace!(target: "hyperspace", "Got acknowledgements for channel {:?}: {:?}", channel_id, acknowledgements);
+	let mut acknowledgements_join_set: JoinSet<Result<_, anyhow::Error>> = JoinSet::new();
+	sink.on_undelivered_sequences(!acknowledgements.is_empty(), UndeliveredType::Acks)
+		.await;
+	for acknowledgements in acknowledgements.chunks(PROCESS_PACKETS_BATCH_SIZE) {
+		for acknowledgement in acknowledgements.iter().cloned() {
+			let source_connection_end = source_connection_end.clone();
+			let source = source.clone();
+			let sink = sink.clone();
+			let duration1 = Duration::from_millis(
+				rand::thread_rng().gen_range(1..source.rpc_call_delay().as_millis() as u64),
+			);
+			let strategy = strategy.clone();
+			acknowledgements_join_set.spawn(async move {
+				sleep(duration1).await;
+				let source = &source;
+				let sink = &sink;
+				let packet = packet_info_to_packet(&acknowledgement);
+				let ack = if let Some(ack) = acknowledgement.ack {
+					ack
+				} else {
+					// Packet has no valid acknowledgement, skip
+					log::trace!(target: "hyperspace", "Skipping acknowledgement for packet {:?} as packet has no valid acknowledgement", packet);
+					return Ok(None)
+				};
+
+				// Check if ack is ready to be sent to sink
+				// If sink does not have a client height that is equal to or greater than the packet
+				// creation height, we can't send it yet packet_info.height should represent the
+				// acknowledgement creation height on source chain
+				let ack_height = acknowledgement.height.ok_or_else(|| {
+					Error::Custom(format!("Packet height not found for packet {packet:?}"))
+				})?;
+				if ack_height > latest_source_height_on_sink.revision_height {
+					// Sink does not have client update required to prove acknowledgement packet message
+					log::trace!(target: "hyperspace", "Skipping acknowledgement for packet {:?} as sink does not have client update required to prove acknowledgement packet message", packet);
+					return Ok(None)
+				}
+
+				log::trace!(target: "hyperspace", "sink_height: {:?}, latest_source_height_on_sink: {:?}, acknowledgement.height: {}", sink_height, latest_source_height_on_sink, ack_height);
+
+				let proof_height = if let Some(proof_height) = find_suitable_proof_height_for_client(
+					&**source,
+					&**sink,
+					sink_height,
+					source.client_id(),
+					Height::new(latest_source_height_on_sink.revision_number, ack_height),
+					None,
+					latest_source_height_on_sink,
+				)
+					.await
+				{
+					log::trace!(target: "hyperspace", "Using proof height: {}", proof_height);
+					proof_height
+				} else {
+					log::trace!(target: "hyperspace", "Skipping acknowledgement for packet {:?} as no proof height could be found", packet);
+					return Ok(None)
+				};
+
+				if !verify_delay_passed(
+					&**source,
+					&**sink,
+					source_timestamp,
+					source_height,
+					sink_timestamp,
+					sink_height,
+					source_connection_end.delay_period(),
+					proof_height,
+					VerifyDelayOn::Sink,
+				)
+					.await?
+				{
+					log::trace!(target: "hyperspace", "Skipping acknowledgement for packet as connection delay has not passed {:?}", packet);
+					return Ok(None)
+				}
+
+				// the packet was sent, and its fees escrowed, on the sink
+				if !packet_pays_min_fee(&**sink, &strategy, &packet, |fee| &fee.fee.ack_fee).await? {
+					log::trace!(target: "hyperspace", "Skipping acknowledgement for packet as its acknowledgement fee is below the minimum fee: {:?}", packet);
+					return Ok(None)
+				}
+
+				Ok(Some((packet, ack, proof_height)))
+			});
+		}
+	}
 
-					let proof_height = if let Some(proof_height) = find_suitable_proof_height_for_client(
-						&**source,
-						&**sink,
-						sink_height,
-						source.client_id(),
-						Height::new(latest_source_height_on_sink.revision_number, ack_height),
-						None,
-						latest_source_height_on_sink,
-					)
-						.await
-					{
-						log::trace!(target: "hyperspace", "Using proof height: {}", proof_height);
-						proof_height
-					} else {
-						log::trace!(target: "hyperspace", "Skipping acknowledgement for packet {:?} as no proof height could be found", packet);
-						return Ok(None)
-					};
+	let mut acks = vec![];
+	while let Some(result) = acknowledgements_join_set.join_next().await {
+		let Some(ack) = result?? else { continue };
+		acks.push(ack)
+	}
+	messages.extend(construct_ack_messages(&*source, &*sink, acks).await?);
 
-					if !verify_delay_passed(
-						&**source,
-						&**sink,
-						source_timestamp,
-						source_height,
-						sink_timestamp,
-						sink_height,
-						source_connection_end.delay_period(),
-						proof_height,
-						VerifyDelayOn::Sink,
-					)
-						.await?
-					{
-						log::trace!(target: "hyperspace", "Skipping acknowledgement for packet as connection delay has not passed {:?}", packet);
-						return Ok(None)
-					}
+	Ok((messages, timeout_messages))
+}
 
-					let msg = construct_ack_message(&**source, &**sink, packet, ack, proof_height).await?;
-					Ok(Some(msg))
-				});
-			}
-		}
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ibc::core::ics04_channel::msgs::recv_packet;
+	use mock::{MockChain, MockConfig};
+
+	/// Number of receive messages built for a packet sent by chain A that already timed out on
+	/// chain B, on a channel of ordering `order`.
+	async fn recv_messages_of_expired_packet(order: Order) -> usize {
+		let (chain_a, chain_b) =
+			MockChain::pair(MockConfig::new("mock-a", 2000), MockConfig::new("mock-b", 2001));
+		chain_a.set_channel_order(order);
+		chain_b.set_channel_order(order);
+		chain_a.send_expired_packet();
+		chain_a.produce_block();
+		// the light client of chain A on chain B must know the packet to prove it
+		chain_b.update_client_of(&chain_a).unwrap();
+		chain_b.produce_block();
+
+		let (messages, _) = query_ready_and_timed_out_packets(&chain_a, &chain_b).await.unwrap();
+		messages.iter().filter(|msg| msg.type_url == recv_packet::TYPE_URL).count()
+	}
 
-		while let Some(result) = acknowledgements_join_set.join_next().await {
-			let Some(msg) = result?? else { continue };
-			messages.push(msg)
-		}
+	#[tokio::test]
+	async fn expired_packets_are_not_received() {
+		assert_eq!(recv_messages_of_expired_packet(Order::Unordered).await, 0);
 	}
 
-	Ok((messages, timeout_messages))
+	#[tokio::test]
+	async fn expired_packets_of_ordered_channels_allowing_timeouts_are_received() {
+		assert_eq!(recv_messages_of_expired_packet(Order::OrderedAllowTimeout).await, 1);
+	}
 }
@@ -0,0 +1,171 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc::core::ics02_client::{
+	client_consensus::ConsensusState as ConsensusStateT, client_state::ClientState as ClientStateT,
+};
+use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState};
+use primitives::{error::Error, Chain};
+use std::time::Duration;
+
+/// Severity of the time left before a light client's trusting period lapses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ClientExpiryStatus {
+	/// More than half of the trusting period is left
+	Healthy,
+	/// Less than half of the trusting period is left
+	Warning,
+	/// Less than a tenth of the trusting period is left
+	Critical,
+	/// The trusting period has lapsed, the client can no longer be updated
+	Expired,
+}
+
+/// Time left before a light client's trusting period lapses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientExpiry {
+	/// Trusting period of the client
+	pub trusting_period: Duration,
+	/// Time left until the trusting period lapses, measured from the latest consensus state
+	pub time_until_expiry: Duration,
+}
+
+impl ClientExpiry {
+	pub fn new(trusting_period: Duration, elapsed: Duration) -> Self {
+		Self { trusting_period, time_until_expiry: trusting_period.saturating_sub(elapsed) }
+	}
+
+	pub fn status(&self) -> ClientExpiryStatus {
+		if self.is_expired() {
+			ClientExpiryStatus::Expired
+		} else if self.time_until_expiry <= self.trusting_period / 10 {
+			ClientExpiryStatus::Critical
+		} else if self.time_until_expiry <= self.trusting_period / 2 {
+			ClientExpiryStatus::Warning
+		} else {
+			ClientExpiryStatus::Healthy
+		}
+	}
+
+	pub fn is_expired(&self) -> bool {
+		self.time_until_expiry.is_zero()
+	}
+}
+
+/// Computes how long the light client of `source` on `sink` can still be updated. Returns `None`
+/// if the client type has no trusting period.
+pub async fn query_client_expiry(
+	source: &impl Chain,
+	sink: &impl Chain,
+) -> Result<Option<ClientExpiry>, anyhow::Error> {
+	let (sink_height, sink_timestamp) = sink.latest_height_and_timestamp().await?;
+	let client_state_response = sink.query_client_state(sink_height, source.client_id()).await?;
+	let client_state = client_state_response
+		.client_state
+		.map(AnyClientState::try_from)
+		.ok_or_else(|| {
+			Error::Custom(format!(
+				"Client state for {} should exist on {}",
+				source.name(),
+				sink.name()
+			))
+		})??;
+	let Some(trusting_period) = client_state.trusting_period() else { return Ok(None) };
+
+	let consensus_state_response = sink
+		.query_client_consensus(sink_height, source.client_id(), client_state.latest_height())
+		.await?;
+	let consensus_state = consensus_state_response
+		.consensus_state
+		.map(AnyConsensusState::try_from)
+		.ok_or_else(|| {
+			Error::Custom(format!(
+				"Consensus state for {} at {} should exist on {}",
+				source.name(),
+				client_state.latest_height(),
+				sink.name()
+			))
+		})??;
+	let elapsed = Duration::from_nanos(
+		sink_timestamp
+			.nanoseconds()
+			.saturating_sub(consensus_state.timestamp().nanoseconds()),
+	);
+
+	Ok(Some(ClientExpiry::new(trusting_period, elapsed)))
+}
+
+/// Logs the time left before the light client of `source` on `sink` expires, with a severity
+/// that escalates as the expiry gets closer.
+pub fn report_client_expiry(source: &impl Chain, sink: &impl Chain, expiry: &ClientExpiry) {
+	let client_id = source.client_id();
+	match expiry.status() {
+		ClientExpiryStatus::Healthy => log::trace!(
+			target: "hyperspace",
+			"Client {client_id} of {} on {} expires in {:?}",
+			source.name(), sink.name(), expiry.time_until_expiry
+		),
+		ClientExpiryStatus::Warning => log::warn!(
+			target: "hyperspace",
+			"Client {client_id} of {} on {} expires in {:?}, more than half of its trusting period has elapsed",
+			source.name(), sink.name(), expiry.time_until_expiry
+		),
+		ClientExpiryStatus::Critical => log::error!(
+			target: "hyperspace",
+			"Client {client_id} of {} on {} is about to expire in {:?}",
+			source.name(), sink.name(), expiry.time_until_expiry
+		),
+		ClientExpiryStatus::Expired => log::error!(
+			target: "hyperspace",
+			"Client {client_id} of {} on {} has expired, its trusting period of {:?} has lapsed. Messages that depend on it won't be relayed",
+			source.name(), sink.name(), expiry.trusting_period
+		),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ClientExpiryStatus::*;
+
+	#[test]
+	fn status_escalates_at_half_and_a_tenth_of_the_trusting_period() {
+		let trusting_period = Duration::from_secs(100);
+		// (elapsed seconds, status)
+		let cases = [
+			(0, Healthy),
+			(49, Healthy),
+			(50, Warning),
+			(89, Warning),
+			(90, Critical),
+			(99, Critical),
+			(100, Expired),
+			(150, Expired),
+		];
+		for (elapsed, status) in cases {
+			let expiry = ClientExpiry::new(trusting_period, Duration::from_secs(elapsed));
+			assert_eq!(expiry.status(), status, "{elapsed}s elapsed");
+			assert_eq!(expiry.is_expired(), status == Expired);
+		}
+	}
+
+	#[test]
+	fn time_until_expiry_is_measured_from_the_latest_consensus_state() {
+		let expiry = ClientExpiry::new(Duration::from_secs(100), Duration::from_millis(49_500));
+		assert_eq!(expiry.time_until_expiry, Duration::from_millis(50_500));
+		assert_eq!(expiry.status(), Healthy);
+		assert_eq!(ClientExpiry::new(Duration::ZERO, Duration::ZERO).status(), Expired);
+		assert!(Healthy < Warning && Warning < Critical && Critical < Expired);
+	}
+}
@@ -16,29 +16,135 @@ use ibc::{timestamp::Timestamp, Height};
 use primitives::error::Error;
 use std::time::Duration;
 
-/// Verify the time and height delays
-pub fn has_delay_elapsed(
+/// The part of a connection delay that hasn't elapsed yet, measured from the client update
+/// a packet proof depends on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RemainingDelay {
+	/// Time left until the delay period elapses
+	pub time: Duration,
+	/// Blocks left until the block delay elapses
+	pub blocks: u64,
+}
+
+impl RemainingDelay {
+	/// Returns true if both the time and the block delay have elapsed
+	pub fn is_elapsed(&self) -> bool {
+		self.time.is_zero() && self.blocks == 0
+	}
+}
+
+/// Compute how much of the time and height delays is still remaining
+pub fn remaining_delay(
 	current_time: Timestamp,
 	current_height: Height,
 	client_update_time: Timestamp,
 	client_update_height: Height,
 	delay_period_time: Duration,
 	delay_period_blocks: u64,
-) -> Result<bool, anyhow::Error> {
+) -> Result<RemainingDelay, anyhow::Error> {
 	log::trace!(target: "hyperspace",
 		"Checking if delay has elapsed: current_time: {}, current_height: {}, client_update_time: {}, client_update_height: {}, delay_period_time: {:?}, delay_period_blocks: {}",
 		current_time, current_height, client_update_time, client_update_height, delay_period_time, delay_period_blocks
 	);
 	let earliest_time = (client_update_time + delay_period_time)
 		.map_err(|_| Error::Custom("Timestamp overflow".to_string()))?;
-	if !(current_time == earliest_time || current_time.after(&earliest_time)) {
-		return Ok(false)
-	}
+	let time = Duration::from_nanos(
+		earliest_time.nanoseconds().saturating_sub(current_time.nanoseconds()),
+	);
 
 	let earliest_height = client_update_height.add(delay_period_blocks);
-	if current_height < earliest_height {
-		return Ok(false)
+	let blocks = if current_height.revision_number == earliest_height.revision_number {
+		earliest_height.revision_height.saturating_sub(current_height.revision_height)
+	} else if current_height < earliest_height {
+		// the chain hasn't upgraded yet, the best we can say is that the whole block delay
+		// is still pending
+		delay_period_blocks
+	} else {
+		0
+	};
+
+	Ok(RemainingDelay { time, blocks })
+}
+
+/// Verify the time and height delays
+pub fn has_delay_elapsed(
+	current_time: Timestamp,
+	current_height: Height,
+	client_update_time: Timestamp,
+	client_update_height: Height,
+	delay_period_time: Duration,
+	delay_period_blocks: u64,
+) -> Result<bool, anyhow::Error> {
+	remaining_delay(
+		current_time,
+		current_height,
+		client_update_time,
+		client_update_height,
+		delay_period_time,
+		delay_period_blocks,
+	)
+	.map(|remaining| remaining.is_elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SECOND: u64 = 1_000_000_000;
+
+	fn timestamp(secs: u64) -> Timestamp {
+		Timestamp::from_nanoseconds(secs * SECOND).unwrap()
+	}
+
+	/// Delay of 10 seconds and 5 blocks, remaining when the chain is at `secs` and `height`, the
+	/// client being updated at 100 seconds and height 1-50.
+	fn remaining(secs: u64, height: Height) -> RemainingDelay {
+		remaining_delay(
+			timestamp(secs),
+			height,
+			timestamp(100),
+			Height::new(1, 50),
+			Duration::from_secs(10),
+			5,
+		)
+		.unwrap()
 	}
 
-	Ok(true)
+	#[test]
+	fn zero_delay_is_elapsed_right_after_the_client_update() {
+		let remaining = remaining_delay(
+			timestamp(100),
+			Height::new(1, 50),
+			timestamp(100),
+			Height::new(1, 50),
+			Duration::ZERO,
+			0,
+		)
+		.unwrap();
+		assert_eq!(remaining, RemainingDelay::default());
+		assert!(remaining.is_elapsed());
+	}
+
+	#[test]
+	fn delay_is_bound_by_both_time_and_blocks() {
+		let cases = [
+			// right after the update
+			(100, Height::new(1, 50), Duration::from_secs(10), 5),
+			// the time delay elapsed, the block delay didn't
+			(110, Height::new(1, 52), Duration::ZERO, 3),
+			// the block delay elapsed, the time delay didn't
+			(104, Height::new(1, 60), Duration::from_secs(6), 0),
+			// both elapsed, exactly and well past
+			(110, Height::new(1, 55), Duration::ZERO, 0),
+			(500, Height::new(1, 500), Duration::ZERO, 0),
+			// the block delay can't be measured across revisions
+			(110, Height::new(0, 100), Duration::ZERO, 5),
+			(110, Height::new(2, 1), Duration::ZERO, 0),
+		];
+		for (secs, height, time, blocks) in cases {
+			let remaining = remaining(secs, height);
+			assert_eq!(remaining, RemainingDelay { time, blocks }, "at {secs}s, {height}");
+			assert_eq!(remaining.is_elapsed(), time.is_zero() && blocks == 0);
+		}
+	}
 }
@@ -0,0 +1,128 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc::Height;
+
+/// Number of sink blocks an ordered channel may wait for the same sequence before an alert is
+/// raised, unless configured in the channel's strategy.
+pub const DEFAULT_SEQUENCE_GAP_ALERT_BLOCKS: u64 = 100;
+
+/// Follows the next sequence the sink of an ordered channel expects. Packets of ordered channels
+/// are received in order, so while that sequence isn't delivered none of the packets sent after it
+/// are either.
+#[derive(Debug, Clone, Default)]
+pub struct SequenceGap {
+	/// Sequence the sink is waiting for and the sink height it was first seen at, while there are
+	/// undelivered packets.
+	stalled: Option<(u64, Height)>,
+	/// Lowest undelivered sequence on the source at the last observation
+	lowest_undelivered: u64,
+	/// Number of sink blocks the channel has been waiting for the same sequence
+	pub blocks: u64,
+	threshold: u64,
+	alerted: bool,
+}
+
+impl SequenceGap {
+	/// Records the `next_sequence_recv` of the sink at `sink_height`, along with the lowest
+	/// sequence that is still undelivered on the source, if any.
+	pub fn observe(
+		&mut self,
+		next_sequence_recv: u64,
+		lowest_undelivered: Option<u64>,
+		sink_height: Height,
+		threshold: u64,
+	) {
+		self.threshold = threshold;
+		let Some(lowest_undelivered) = lowest_undelivered else {
+			*self = Self { threshold, ..Default::default() };
+			return
+		};
+		match self.stalled {
+			Some((sequence, _)) if sequence == next_sequence_recv => {},
+			_ => {
+				self.stalled = Some((next_sequence_recv, sink_height));
+				self.alerted = false;
+			},
+		}
+		self.lowest_undelivered = lowest_undelivered;
+		self.blocks = self
+			.stalled
+			.map(|(_, since)| sink_height.revision_height.saturating_sub(since.revision_height))
+			.unwrap_or_default();
+	}
+
+	/// Describes the gap once the channel has waited for the same sequence for longer than the
+	/// threshold, only once per stalled sequence.
+	pub fn take_alert(&mut self) -> Option<String> {
+		let (sequence, since) = self.stalled?;
+		if self.alerted || self.blocks <= self.threshold {
+			return None
+		}
+		self.alerted = true;
+		Some(format!(
+			"the sink has been waiting for sequence {sequence} since height {since} ({} blocks), the lowest undelivered sequence is {}",
+			self.blocks, self.lowest_undelivered
+		))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn height(height: u64) -> Height {
+		Height::new(1, height)
+	}
+
+	#[test]
+	fn alerts_once_past_the_threshold() {
+		let mut gap = SequenceGap::default();
+		for (sink_height, blocks) in [(10, 0), (12, 2), (13, 3)] {
+			gap.observe(5, Some(5), height(sink_height), 3);
+			assert_eq!(gap.blocks, blocks);
+			assert_eq!(gap.take_alert(), None);
+		}
+
+		gap.observe(5, Some(7), height(14), 3);
+		assert_eq!(gap.blocks, 4);
+		let alert = gap.take_alert().unwrap();
+		assert!(alert.contains("sequence 5 since height 1-10 (4 blocks)"), "{alert}");
+		assert!(alert.ends_with("the lowest undelivered sequence is 7"), "{alert}");
+		gap.observe(5, Some(7), height(20), 3);
+		assert_eq!(gap.take_alert(), None);
+	}
+
+	#[test]
+	fn delivering_the_sequence_resets_the_gap() {
+		let mut gap = SequenceGap::default();
+		gap.observe(5, Some(5), height(10), 3);
+		gap.observe(5, Some(5), height(20), 3);
+		assert!(gap.take_alert().is_some());
+
+		// the sink moved on to the next sequence, which is stalled in turn
+		gap.observe(6, Some(6), height(21), 3);
+		assert_eq!(gap.blocks, 0);
+		assert_eq!(gap.take_alert(), None);
+		gap.observe(6, Some(6), height(25), 3);
+		assert!(gap.take_alert().unwrap().contains("sequence 6 since height 1-21"));
+
+		// everything was delivered
+		gap.observe(7, None, height(26), 3);
+		assert_eq!(gap.blocks, 0);
+		assert_eq!(gap.take_alert(), None);
+		gap.observe(7, Some(7), height(40), 3);
+		assert_eq!(gap.blocks, 0);
+	}
+}
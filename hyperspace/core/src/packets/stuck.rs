@@ -0,0 +1,182 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc::Height;
+use std::{
+	collections::{BTreeMap, HashSet},
+	time::{Duration, Instant},
+};
+
+/// Time a packet may stay undelivered before an alert is raised, unless configured in the
+/// channel's strategy.
+pub const DEFAULT_STUCK_PACKET_ALERT_AGE: Duration = Duration::from_secs(3600);
+
+/// Number of sequences listed in a stuck packets alert.
+const MAX_ALERT_SEQUENCES: usize = 10;
+
+#[derive(Debug, Clone)]
+struct Undelivered {
+	since: Instant,
+	since_height: Height,
+	/// Why the packet wasn't relayed the last time it was looked at
+	reason: Option<String>,
+	alerted: bool,
+}
+
+/// Age of the undelivered packets of a channel and why they're not relayed yet.
+#[derive(Debug, Clone, Default)]
+pub struct StuckPackets {
+	packets: BTreeMap<u64, Undelivered>,
+	max_age: Option<Duration>,
+	max_blocks: Option<u64>,
+}
+
+impl StuckPackets {
+	/// Starts following the `undelivered` sequences not seen before, as of `source_height`, and
+	/// forgets the ones that were delivered since.
+	pub fn observe(
+		&mut self,
+		undelivered: &[u64],
+		source_height: Height,
+		max_age: Duration,
+		max_blocks: Option<u64>,
+	) {
+		self.max_age = Some(max_age);
+		self.max_blocks = max_blocks;
+		let undelivered = undelivered.iter().copied().collect::<HashSet<_>>();
+		self.packets.retain(|sequence, _| undelivered.contains(sequence));
+		for sequence in undelivered {
+			self.packets.entry(sequence).or_insert_with(|| Undelivered {
+				since: Instant::now(),
+				since_height: source_height,
+				reason: None,
+				alerted: false,
+			});
+		}
+	}
+
+	/// Records why the packet with the given sequence wasn't relayed.
+	pub fn set_reason(&mut self, sequence: u64, reason: impl Into<String>) {
+		if let Some(packet) = self.packets.get_mut(&sequence) {
+			packet.reason = Some(reason.into());
+		}
+	}
+
+	/// Records the same reason for all the undelivered packets, e.g. a failed submission.
+	pub fn set_reason_for_all(&mut self, reason: &str) {
+		for packet in self.packets.values_mut() {
+			packet.reason = Some(reason.to_string());
+		}
+	}
+
+	/// Describes the packets that have been undelivered for longer than allowed at
+	/// `source_height` in a single alert, each packet is only reported once.
+	pub fn take_alert(&mut self, source_height: Height) -> Option<String> {
+		self.take_alert_at(Instant::now(), source_height)
+	}
+
+	fn take_alert_at(&mut self, now: Instant, source_height: Height) -> Option<String> {
+		let max_age = self.max_age?;
+		let max_blocks = self.max_blocks;
+		let stuck = self
+			.packets
+			.iter_mut()
+			.filter(|(_, packet)| !packet.alerted)
+			.filter_map(|(sequence, packet)| {
+				let age = now.saturating_duration_since(packet.since);
+				let blocks = source_height
+					.revision_height
+					.saturating_sub(packet.since_height.revision_height);
+				if age <= max_age && max_blocks.map_or(true, |max_blocks| blocks <= max_blocks) {
+					return None
+				}
+				packet.alerted = true;
+				Some((*sequence, age, blocks, packet.reason.as_deref()))
+			})
+			.collect::<Vec<_>>();
+		// sequences are in ascending order, the first packet is the oldest one
+		let (oldest, age, blocks, reason) = *stuck.first()?;
+		let mut sequences = stuck
+			.iter()
+			.take(MAX_ALERT_SEQUENCES)
+			.map(|(sequence, ..)| sequence.to_string())
+			.collect::<Vec<_>>();
+		if stuck.len() > MAX_ALERT_SEQUENCES {
+			sequences.push(format!("and {} more", stuck.len() - MAX_ALERT_SEQUENCES));
+		}
+		Some(format!(
+			"{} packets undelivered for too long ({}), packet {oldest} for {:?} ({blocks} blocks): {}",
+			stuck.len(),
+			sequences.join(", "),
+			Duration::from_secs(age.as_secs()),
+			reason.unwrap_or("no reason recorded")
+		))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const HOUR: Duration = Duration::from_secs(3600);
+
+	fn height(revision_height: u64) -> Height {
+		Height::new(1, revision_height)
+	}
+
+	#[test]
+	fn alerts_once_per_packet_after_max_blocks() {
+		let mut stuck = StuckPackets::default();
+		let now = Instant::now();
+		stuck.observe(&[1, 2], height(100), HOUR, Some(10));
+		assert_eq!(stuck.take_alert_at(now, height(110)), None);
+		let alert = stuck.take_alert_at(now, height(111)).unwrap();
+		assert!(alert.starts_with("2 packets undelivered for too long (1, 2), packet 1"));
+		assert_eq!(stuck.take_alert_at(now, height(200)), None);
+
+		// packet 1 is delivered, packet 3 is new
+		stuck.observe(&[2, 3], height(111), HOUR, Some(10));
+		stuck.set_reason(3, "no fee");
+		assert_eq!(stuck.take_alert_at(now, height(121)), None);
+		let alert = stuck.take_alert_at(now, height(122)).unwrap();
+		assert!(alert.starts_with("1 packets undelivered for too long (3), packet 3"));
+		assert!(alert.ends_with("(11 blocks): no fee"));
+	}
+
+	#[test]
+	fn alerts_after_max_age() {
+		let mut stuck = StuckPackets::default();
+		stuck.observe(&[1], height(100), HOUR, None);
+		let now = Instant::now();
+		assert_eq!(stuck.take_alert_at(now, height(1000)), None);
+		let alert = stuck.take_alert_at(now + 2 * HOUR, height(1000)).unwrap();
+		assert!(alert.contains("packet 1 for"));
+		assert!(alert.ends_with("(900 blocks): no reason recorded"));
+	}
+
+	#[test]
+	fn lists_a_bounded_number_of_sequences() {
+		let mut stuck = StuckPackets::default();
+		stuck.observe(&(1..=15).collect::<Vec<_>>(), height(100), HOUR, Some(0));
+		let alert = stuck.take_alert_at(Instant::now(), height(101)).unwrap();
+		assert!(alert.starts_with(
+			"15 packets undelivered for too long (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, and 5 more)"
+		));
+	}
+
+	#[test]
+	fn no_alert_before_observing() {
+		assert_eq!(StuckPackets::default().take_alert(height(100)), None);
+	}
+}
@@ -12,8 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::packets::connection_delay::has_delay_elapsed;
+use crate::packets::connection_delay::remaining_delay;
+use anyhow::anyhow;
 use ibc::{
+	applications::{fee::PacketFee, transfer::PrefixedCoin},
 	core::{
 		ics02_client::client_state::ClientState as ClientStateT,
 		ics04_channel::{
@@ -26,8 +28,9 @@ use ibc::{
 			packet::{Packet, TimeoutVariant},
 		},
 		ics23_commitment::commitment::CommitmentProofBytes,
-		ics24_host::path::{
-			AcksPath, ChannelEndsPath, CommitmentsPath, ReceiptsPath, SeqRecvsPath,
+		ics24_host::{
+			path::{AcksPath, ChannelEndsPath, CommitmentsPath, ReceiptsPath, SeqRecvsPath},
+			Path,
 		},
 	},
 	proofs::Proofs,
@@ -37,9 +40,8 @@ use ibc::{
 };
 use ibc_proto::google::protobuf::Any;
 use pallet_ibc::light_clients::AnyClientState;
-use primitives::{find_suitable_proof_height_for_client, Chain};
-use std::time::Duration;
-use tendermint_proto::Protobuf;
+use primitives::{find_suitable_proof_height_for_client, strategy::ChannelStrategy, Chain};
+use std::{collections::BTreeMap, time::Duration};
 
 #[allow(clippy::too_many_arguments)]
 pub async fn get_timeout_proof_height(
@@ -69,30 +71,15 @@ pub async fn get_timeout_proof_height(
 			)
 			.await,
 		TimeoutVariant::Timestamp => {
-			// Get approximate number of blocks contained in this timestamp so we can have a lower
-			// bound for where to start our search
-			// We offset the sink height when this packet was created with the approximate number of
-			// blocks contained in the difference in timestamp at packet creation until timeout
-			let height = Height::new(source_height.revision_number, packet_creation_height);
-			log::trace!(
-				target: "hyperspace",
-				"Querying client state at {height}"
-			);
-			let sink_client_state =
-				source.query_client_state(height, sink.client_id()).await.ok()?;
-			let sink_client_state =
-				AnyClientState::try_from(sink_client_state.client_state?).ok()?;
-			let height = sink_client_state.latest_height();
-			let timestamp_at_creation =
-				sink.query_timestamp_at(height.revision_height).await.ok()?;
-			// may underflow if the user have chosen timeout less than the block timestamp at which
-			// the packet was created, so we use `saturating_sub`
-			let period =
-				packet.timeout_timestamp.nanoseconds().saturating_sub(timestamp_at_creation);
-			let period = Duration::from_nanos(period);
-			let start_height = height.revision_height +
-				calculate_block_delay(period, sink.expected_block_time()).saturating_sub(1);
-			let start_height = Height::new(sink_height.revision_number, start_height);
+			let start_height = timestamp_timeout_search_start(
+				source,
+				sink,
+				Height::new(source_height.revision_number, packet_creation_height),
+				sink_height.revision_number,
+				latest_client_height_on_source,
+				packet,
+			)
+			.await;
 			find_suitable_proof_height_for_client(
 				sink,
 				source,
@@ -105,32 +92,17 @@ pub async fn get_timeout_proof_height(
 			.await
 		},
 		TimeoutVariant::Both => {
-			// Get approximate number of blocks contained in this timestamp so we can have a lower
-			// bound for where to start our search
-			let sink_client_state = source
-				.query_client_state(
-					Height::new(source_height.revision_number, packet_creation_height),
-					sink.client_id(),
-				)
-				.await
-				.ok()?;
-			let sink_client_state =
-				AnyClientState::try_from(sink_client_state.client_state?).ok()?;
-			let height = sink_client_state.latest_height();
-			let timestamp_at_creation =
-				sink.query_timestamp_at(height.revision_height).await.ok()?;
-			// may underflow if the user have chosen timeout less than the block timestamp at which
-			// the packet was created, so we use `saturating_sub`
-			let period =
-				packet.timeout_timestamp.nanoseconds().saturating_sub(timestamp_at_creation);
-			let period = Duration::from_nanos(period);
-			let start_height = height.revision_height +
-				calculate_block_delay(period, sink.expected_block_time()).saturating_sub(1);
-			let start_height = if start_height < packet.timeout_height.revision_height {
-				packet.timeout_height
-			} else {
-				Height::new(packet.timeout_height.revision_number, start_height)
-			};
+			let start_height = timestamp_timeout_search_start(
+				source,
+				sink,
+				Height::new(source_height.revision_number, packet_creation_height),
+				packet.timeout_height.revision_number,
+				latest_client_height_on_source,
+				packet,
+			)
+			.await;
+			// the proof must also be past the timeout height
+			let start_height = start_height.max(packet.timeout_height);
 			find_suitable_proof_height_for_client(
 				sink,
 				source,
@@ -145,6 +117,50 @@ pub async fn get_timeout_proof_height(
 	}
 }
 
+/// Lower bound of the search for a consensus state of the sink on the source whose timestamp is
+/// past the timeout timestamp of `packet`.
+///
+/// The height of the sink at the timeout is estimated from the latest height of its client on the
+/// source when the packet was created, offset by the number of blocks expected in the time left
+/// until the timeout. Blocks produced slower than expected put the estimate past the timeout, so it
+/// never goes beyond the latest consensus state on the source, which is the last one that can
+/// prove the timeout. The latest consensus state is also used when the client state at the packet
+/// creation can't be queried anymore, e.g. because the source pruned it.
+async fn timestamp_timeout_search_start(
+	source: &impl Chain,
+	sink: &impl Chain,
+	creation_height: Height,
+	revision_number: u64,
+	latest_client_height_on_source: Height,
+	packet: &Packet,
+) -> Height {
+	log::trace!(target: "hyperspace", "Querying client state at {creation_height}");
+	let estimate = async {
+		let response = source.query_client_state(creation_height, sink.client_id()).await.ok()?;
+		let height = AnyClientState::try_from(response.client_state?).ok()?.latest_height();
+		let timestamp_at_creation = sink.query_timestamp_at(height.revision_height).await.ok()?;
+		// may underflow if the user have chosen timeout less than the block timestamp at which
+		// the packet was created, so we use `saturating_sub`
+		let period = packet.timeout_timestamp.nanoseconds().saturating_sub(timestamp_at_creation);
+		let period = Duration::from_nanos(period);
+		Some(
+			height.revision_height +
+				calculate_block_delay(period, sink.expected_block_time()).saturating_sub(1),
+		)
+	}
+	.await;
+	match estimate {
+		Some(start_height) => Height::new(
+			revision_number,
+			start_height.min(latest_client_height_on_source.revision_height),
+		),
+		None => {
+			log::debug!(target: "hyperspace", "Couldn't estimate the height packet {} timed out at on {}, searching from the latest height of its client", packet.sequence, sink.name());
+			latest_client_height_on_source
+		},
+	}
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum VerifyDelayOn {
 	Source,
@@ -164,7 +180,10 @@ pub async fn verify_delay_passed(
 	verify_delay_on: VerifyDelayOn,
 ) -> Result<bool, anyhow::Error> {
 	log::trace!(target: "hyperspace", "Verifying delay passed for source: {source_height}, {source_timestamp}, sink: {sink_height}, {sink_timestamp}, connection delay: {}, proof height: {proof_height}, verify delay on: {verify_delay_on:?}", connection_delay.as_secs());
-	match verify_delay_on {
+	if connection_delay.is_zero() {
+		return Ok(true)
+	}
+	let remaining = match verify_delay_on {
 		VerifyDelayOn::Source => {
 			let actual_proof_height = sink.get_proof_height(proof_height).await;
 			if let Ok((source_client_update_height, source_client_update_time)) = source
@@ -173,17 +192,17 @@ pub async fn verify_delay_passed(
 			{
 				let block_delay =
 					calculate_block_delay(connection_delay, source.expected_block_time());
-				has_delay_elapsed(
+				remaining_delay(
 					source_timestamp,
 					source_height,
 					source_client_update_time,
 					source_client_update_height, // shouldn't be the latest.
 					connection_delay,
 					block_delay,
-				)
+				)?
 			} else {
 				log::trace!(target: "hyperspace", "Failed to get client update time and height for source client for height {}", actual_proof_height);
-				Ok(false)
+				return Ok(false)
 			}
 		},
 		VerifyDelayOn::Sink => {
@@ -194,38 +213,45 @@ pub async fn verify_delay_passed(
 				proof_height,
 				actual_proof_height
 			);
-			let _cs = sink
+			let has_consensus_state = sink
 				.query_client_consensus(sink_height, source.client_id(), actual_proof_height)
 				.await
-				.unwrap()
-				.consensus_state
-				.unwrap_or_else(|| {
-					panic!(
-						"query_client_consensus for {} at height {} is not found",
-						source.client_id(),
-						actual_proof_height
-					)
-				});
+				.ok()
+				.and_then(|response| response.consensus_state)
+				.is_some();
+			if !has_consensus_state {
+				log::debug!(target: "hyperspace", "Consensus state for {} at height {} is not found on {}", source.client_id(), actual_proof_height, sink.name());
+				return Ok(false)
+			}
 			if let Ok((sink_client_update_height, sink_client_update_time)) = sink
 				.query_client_update_time_and_height(source.client_id(), actual_proof_height)
 				.await
 			{
 				let block_delay =
 					calculate_block_delay(connection_delay, sink.expected_block_time());
-				has_delay_elapsed(
+				remaining_delay(
 					sink_timestamp,
 					sink_height,
 					sink_client_update_time,
 					sink_client_update_height,
 					connection_delay,
 					block_delay,
-				)
+				)?
 			} else {
 				log::trace!(target: "hyperspace", "Failed to get client update time and height for sink client for height {}", actual_proof_height);
-				Ok(false)
+				return Ok(false)
 			}
 		},
+	};
+
+	if !remaining.is_elapsed() {
+		log::debug!(
+			target: "hyperspace",
+			"Connection delay for proof height {proof_height} on {verify_delay_on:?} has not elapsed yet: {:?} and {} blocks remaining",
+			remaining.time, remaining.blocks
+		);
 	}
+	Ok(remaining.is_elapsed())
 }
 
 pub async fn construct_timeout_message(
@@ -236,14 +262,26 @@ pub async fn construct_timeout_message(
 	next_sequence_recv: u64,
 	proof_height: Height,
 ) -> Result<Any, anyhow::Error> {
-	let path_type = if sink_channel_end.ordering == Order::Ordered {
-		KeyPathType::SeqRecv
-	} else {
-		KeyPathType::ReceiptPath
+	let proof_unreceived = match sink_channel_end.ordering {
+		Order::Ordered => {
+			let key = get_key_path(KeyPathType::SeqRecv, &packet).into_bytes();
+			sink.query_proof(proof_height, vec![key]).await?
+		},
+		// the sink wrote a timeout receipt for the packet when it skipped it
+		Order::OrderedAllowTimeout => {
+			let key = get_key_path(KeyPathType::ReceiptPath, &packet).into_bytes();
+			sink.query_proof(proof_height, vec![key]).await?
+		},
+		Order::Unordered => {
+			let receipt_path = ReceiptsPath {
+				port_id: packet.destination_port.clone(),
+				channel_id: packet.destination_channel,
+				sequence: packet.sequence,
+			};
+			sink.query_non_membership_proof(proof_height, Path::Receipts(receipt_path))
+				.await?
+		},
 	};
-	let key = get_key_path(path_type, &packet).into_bytes();
-
-	let proof_unreceived = sink.query_proof(proof_height, vec![key]).await?;
 	let proof_unreceived = CommitmentProofBytes::try_from(proof_unreceived)?;
 	let msg = if sink_channel_end.state == State::Closed {
 		let channel_key = get_key_path(KeyPathType::ChannelPath, &packet).into_bytes();
@@ -262,8 +300,7 @@ pub async fn construct_timeout_message(
 			)?,
 			signer: source.account_id(),
 		};
-		let value = msg.encode_vec()?;
-		Any { value, type_url: msg.type_url() }
+		msg.to_any()
 	} else {
 		let actual_proof_height = sink.get_proof_height(proof_height).await;
 		log::debug!(target: "hyperspace", "actual_proof_height={actual_proof_height}");
@@ -273,53 +310,144 @@ pub async fn construct_timeout_message(
 			proofs: Proofs::new(proof_unreceived, None, None, None, actual_proof_height)?,
 			signer: source.account_id(),
 		};
-		let value = msg.encode_vec()?;
-		Any { value, type_url: msg.type_url() }
+		msg.to_any()
 	};
 	Ok(msg)
 }
 
-pub async fn construct_recv_message(
+/// Drops the packets that have timed out on the sink by its latest height and timestamp, as their
+/// receive messages would be rejected. They stay undelivered on the source, where they are picked
+/// up again to be timed out.
+pub async fn retain_unexpired_packets(
+	sink: &impl Chain,
+	packets: &mut Vec<(Packet, Height)>,
+) -> Result<(), anyhow::Error> {
+	if packets.is_empty() {
+		return Ok(())
+	}
+	let (sink_height, sink_timestamp) = sink.latest_height_and_timestamp().await?;
+	packets.retain(|(packet, _)| {
+		let timed_out = packet.timed_out(&sink_timestamp, sink_height);
+		if timed_out {
+			log::debug!(target: "hyperspace", "Not relaying packet {} to {} as it timed out by height {sink_height}", packet.sequence, sink.name());
+		}
+		!timed_out
+	});
+	Ok(())
+}
+
+/// Constructs the receive messages of the packets, each proven at the given height.
+pub async fn construct_recv_messages(
 	source: &impl Chain,
 	sink: &impl Chain,
-	packet: Packet,
-	proof_height: Height,
-) -> Result<Any, anyhow::Error> {
-	let key = get_key_path(KeyPathType::CommitmentPath, &packet).into_bytes();
-	let proof = source.query_proof(proof_height, vec![key]).await?;
-	let commitment_proof = CommitmentProofBytes::try_from(proof)?;
-	let actual_proof_height = source.get_proof_height(proof_height).await;
-	let msg = MsgRecvPacket {
-		packet,
-		proofs: Proofs::new(commitment_proof, None, None, None, actual_proof_height)?,
-		signer: sink.account_id(),
-	};
-	let value = msg.encode_vec()?;
-	let msg = Any { value, type_url: msg.type_url() };
-	Ok(msg)
+	packets: Vec<(Packet, Height)>,
+) -> Result<Vec<Any>, anyhow::Error> {
+	let keys = packets
+		.iter()
+		.map(|(packet, proof_height)| {
+			(*proof_height, get_key_path(KeyPathType::CommitmentPath, packet).into_bytes())
+		})
+		.collect();
+	let proofs = query_proofs_by_height(source, keys).await?;
+	let mut msgs = Vec::with_capacity(packets.len());
+	for ((packet, _), (proof, actual_proof_height)) in packets.into_iter().zip(proofs) {
+		let commitment_proof = CommitmentProofBytes::try_from(proof)?;
+		let msg = MsgRecvPacket {
+			packet,
+			proofs: Proofs::new(commitment_proof, None, None, None, actual_proof_height)?,
+			signer: sink.account_id(),
+		};
+		msgs.push(msg.to_any());
+	}
+	Ok(msgs)
 }
 
-pub async fn construct_ack_message(
+/// Constructs the acknowledgement messages of the packets, each proven at the given height.
+pub async fn construct_ack_messages(
 	source: &impl Chain,
 	sink: &impl Chain,
-	packet: Packet,
-	ack: Vec<u8>,
-	proof_height: Height,
-) -> Result<Any, anyhow::Error> {
-	let key = get_key_path(KeyPathType::AcksPath, &packet);
-	log::debug!(target: "hyperspace", "query proof for acks path: {:?}", key);
-	let proof = source.query_proof(proof_height, vec![key.into_bytes()]).await?;
-	let commitment_proof = CommitmentProofBytes::try_from(proof)?;
-	let actual_proof_height = source.get_proof_height(proof_height).await;
-	let msg = MsgAcknowledgement {
-		packet,
-		proofs: Proofs::new(commitment_proof, None, None, None, actual_proof_height)?,
-		acknowledgement: ack.into(),
-		signer: sink.account_id(),
+	acks: Vec<(Packet, Vec<u8>, Height)>,
+) -> Result<Vec<Any>, anyhow::Error> {
+	let keys = acks
+		.iter()
+		.map(|(packet, _, proof_height)| {
+			let key = get_key_path(KeyPathType::AcksPath, packet);
+			log::debug!(target: "hyperspace", "query proof for acks path: {:?}", key);
+			(*proof_height, key.into_bytes())
+		})
+		.collect();
+	let proofs = query_proofs_by_height(source, keys).await?;
+	let mut msgs = Vec::with_capacity(acks.len());
+	for ((packet, ack, _), (proof, actual_proof_height)) in acks.into_iter().zip(proofs) {
+		let commitment_proof = CommitmentProofBytes::try_from(proof)?;
+		let msg = MsgAcknowledgement {
+			packet,
+			proofs: Proofs::new(commitment_proof, None, None, None, actual_proof_height)?,
+			acknowledgement: ack.into(),
+			signer: sink.account_id(),
+		};
+		msgs.push(msg.to_any());
+	}
+	Ok(msgs)
+}
+
+/// Queries the proof of every key at its height, along with the height the proof is actually
+/// verified at. The proofs of the keys at the same height are fetched in a single round trip.
+async fn query_proofs_by_height(
+	chain: &impl Chain,
+	keys: Vec<(Height, Vec<u8>)>,
+) -> Result<Vec<(Vec<u8>, Height)>, anyhow::Error> {
+	let mut keys_by_height = BTreeMap::<Height, Vec<usize>>::new();
+	for (i, (height, _)) in keys.iter().enumerate() {
+		keys_by_height.entry(*height).or_default().push(i);
+	}
+
+	let mut proofs = vec![None; keys.len()];
+	for (height, indices) in keys_by_height {
+		let height_keys = indices.iter().map(|i| vec![keys[*i].1.clone()]).collect();
+		let height_proofs = chain.query_proofs(height, height_keys).await?;
+		if height_proofs.len() != indices.len() {
+			return Err(anyhow!(
+				"Expected {} proofs at {height} from {}, got {}",
+				indices.len(),
+				chain.name(),
+				height_proofs.len()
+			))
+		}
+		let actual_proof_height = chain.get_proof_height(height).await;
+		for (i, proof) in indices.into_iter().zip(height_proofs) {
+			proofs[i] = Some((proof, actual_proof_height));
+		}
+	}
+	Ok(proofs.into_iter().flatten().collect())
+}
+
+/// Whether the fees escrowed for `packet` on its source chain `packet_source` pay the relayer at
+/// least the minimum fee of the channel `strategy`, `fee` selects the part of the fee paid for the
+/// relayed step. The fee isn't checked when the fees can't be queried, the packet is relayed
+/// rather than held up by the node of the source.
+pub async fn packet_pays_min_fee(
+	packet_source: &impl Chain,
+	strategy: &ChannelStrategy,
+	packet: &Packet,
+	fee: impl Fn(&PacketFee) -> &[PrefixedCoin],
+) -> Result<bool, anyhow::Error> {
+	let Some(min_fee) = &strategy.min_fee else { return Ok(true) };
+	let packet_fees = match packet_source
+		.query_packet_fees(
+			packet.source_channel,
+			packet.source_port.clone(),
+			packet.sequence.into(),
+		)
+		.await
+	{
+		Ok(packet_fees) => packet_fees,
+		Err(e) => {
+			log::warn!(target: "hyperspace", "Failed to query the fees of packet {} on {}/{} from {}, relaying it without checking the minimum fee: {:?}", packet.sequence, packet.source_port, packet.source_channel, packet_source.name(), e);
+			return Ok(true)
+		},
 	};
-	let value = msg.encode_vec()?;
-	let msg = Any { value, type_url: msg.type_url() };
-	Ok(msg)
+	Ok(min_fee.is_paid_by(&packet_fees, fee)?)
 }
 
 pub enum KeyPathType {
@@ -373,3 +501,102 @@ pub fn get_key_path(key_path_type: KeyPathType, packet: &Packet) -> String {
 		},
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use mock::{MockChain, MockConfig};
+	use primitives::IbcQueryProvider;
+
+	const BLOCK_TIME: Duration = Duration::from_secs(1);
+
+	/// Heights of chain B its light client on chain A is updated at, it's created at the first one.
+	const CLIENT_UPDATES: [u64; 5] = [1, 3, 4, 6, 9];
+
+	/// Chain B produces a block every [`BLOCK_TIME`] up to height 9, its light client on chain A is
+	/// only updated at the [`CLIENT_UPDATES`]. Returns the chains with the timestamp of the genesis
+	/// of chain B.
+	async fn chains() -> (MockChain, MockChain, u64) {
+		let config_b = MockConfig { block_time: BLOCK_TIME, ..MockConfig::new("mock-b", 2001) };
+		let (chain_a, chain_b) = MockChain::pair(MockConfig::new("mock-a", 2000), config_b);
+		let genesis = chain_b.query_timestamp_at(1).await.unwrap();
+		for height in 2..=9 {
+			chain_b.produce_block_at(timestamp_at(genesis, height));
+			if CLIENT_UPDATES.contains(&height) {
+				chain_a.update_client_of(&chain_b).unwrap();
+			}
+		}
+		(chain_a, chain_b, genesis)
+	}
+
+	/// Timestamp of the block of chain B at `height`, including the ones it didn't produce yet.
+	fn timestamp_at(genesis: u64, height: u64) -> u64 {
+		genesis + (height - 1) * BLOCK_TIME.as_nanos() as u64
+	}
+
+	#[tokio::test]
+	async fn proof_height_is_the_first_client_update_past_the_start_and_timestamp() {
+		let (chain_a, chain_b, genesis) = chains().await;
+		let revision = chain_b.latest().revision_number;
+		// (start height, height of chain B whose timestamp must be reached, proof height)
+		let cases = [
+			(1, None, Some(1)),
+			(5, None, Some(6)),
+			(7, None, Some(9)),
+			(10, None, None),
+			(1, Some(4), Some(4)),
+			(1, Some(5), Some(6)),
+			// the search ends on the start height, which is past the timestamp as well
+			(3, Some(3), Some(3)),
+			// the search starts with two heights only, the end height is never a midpoint
+			(8, Some(9), Some(9)),
+			(1, Some(10), None),
+			(10, Some(9), None),
+		];
+		for (start, reached_at, expected) in cases {
+			let timestamp = reached_at
+				.map(|height| Timestamp::from_nanoseconds(timestamp_at(genesis, height)).unwrap());
+			let proof_height = find_suitable_proof_height_for_client(
+				&chain_b,
+				&chain_a,
+				chain_a.latest(),
+				chain_a.client_id(),
+				Height::new(revision, start),
+				timestamp,
+				Height::new(revision, 9),
+			)
+			.await;
+			assert_eq!(
+				proof_height,
+				expected.map(|height| Height::new(revision, height)),
+				"start {start}, timestamp of {reached_at:?}"
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn timeout_search_starts_at_the_estimated_height_up_to_the_latest_client_height() {
+		let (chain_a, chain_b, genesis) = chains().await;
+		let revision = chain_b.latest().revision_number;
+		// (height of chain B at the timeout, latest client height on chain A, start height), the
+		// client state of chain B on chain A is at height 9
+		let cases = [(7, 9, 9), (10, 20, 9), (14, 20, 13), (14, 11, 11), (14, 9, 9)];
+		for (timeout_at, latest_client_height, expected) in cases {
+			let packet = Packet {
+				timeout_timestamp: Timestamp::from_nanoseconds(timestamp_at(genesis, timeout_at))
+					.unwrap(),
+				..Default::default()
+			};
+			let start = timestamp_timeout_search_start(
+				&chain_a,
+				&chain_b,
+				chain_a.latest(),
+				revision,
+				Height::new(revision, latest_client_height),
+				&packet,
+			)
+			.await;
+			assert_eq!(start, Height::new(revision, expected), "timeout at {timeout_at}");
+		}
+	}
+}
@@ -0,0 +1,280 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Every whitelisted channel is relayed by its own worker task, so that a channel whose packets are
+//! slow to prove or to submit doesn't hold back the packets of the other channels.
+//!
+//! The workers only look at the packets they learn about from the events of the source chain,
+//! and scan all the commitments of their channel once in a while to pick up the ones they missed.
+
+use crate::{
+	alerts,
+	packets::{query_ready_and_timed_out_channel_packets, PendingPackets},
+	pipeline::SubmissionLock,
+	process_messages, process_timeouts,
+	shutdown::Shutdown,
+};
+use ibc::{
+	core::{
+		ics02_client::client_state::ClientState as _,
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	events::IbcEvent,
+	Height,
+};
+use metrics::handler::MetricsHandler;
+use pallet_ibc::light_clients::AnyClientState;
+use primitives::Chain;
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+use tokio::{
+	sync::mpsc::{self, error::TrySendError},
+	task::JoinHandle,
+};
+
+/// A round of relaying for a channel, queued on every finality notification of the source.
+pub struct ChannelJob<A, B> {
+	pub source: A,
+	pub sink: B,
+	pub metrics: Option<MetricsHandler>,
+	/// Whether packets and acknowledgements are relayed to the sink.
+	pub relay_packets: bool,
+	/// Whether timeouts are relayed back to the source.
+	pub relay_timeouts: bool,
+	pub source_submissions: SubmissionLock,
+	pub sink_submissions: SubmissionLock,
+	/// Tracks the messages built until they're submitted.
+	pub shutdown: Shutdown,
+}
+
+/// How often the workers scan all the packet commitments and acknowledgements of their channel.
+const FULL_SCAN_INTERVAL: Duration = Duration::from_secs(600);
+
+struct ChannelWorker<A, B> {
+	jobs: mpsc::Sender<ChannelJob<A, B>>,
+	/// Packets discovered since the worker's last round, recorded even when the worker is busy.
+	discovered: Arc<Mutex<PendingPackets>>,
+	handle: JoinHandle<()>,
+}
+
+/// The workers relaying the whitelisted channels of a source chain to a sink chain.
+pub struct ChannelWorkers<A, B> {
+	workers: HashMap<(ChannelId, PortId), ChannelWorker<A, B>>,
+	/// The timeouts are submitted to the source, one submission at a time with the other
+	/// submissions to it.
+	source_submissions: SubmissionLock,
+	/// The packets and acknowledgements are submitted to the sink.
+	sink_submissions: SubmissionLock,
+	shutdown: Shutdown,
+}
+
+impl<A: Chain, B: Chain> ChannelWorkers<A, B> {
+	pub fn new(
+		source_submissions: SubmissionLock,
+		sink_submissions: SubmissionLock,
+		shutdown: Shutdown,
+	) -> Self {
+		Self { workers: HashMap::new(), source_submissions, sink_submissions, shutdown }
+	}
+
+	/// Queues a job for every channel whitelisted on `source`, starting the workers of newly
+	/// whitelisted channels and stopping the ones of channels that aren't whitelisted anymore.
+	/// The packets sent and acknowledgements written in `events` are handed to their channel.
+	///
+	/// Every worker queues a single job: a worker that is still busy with the previous round skips
+	/// this one, as the next round picks up all the packets that are ready by then anyway.
+	pub fn dispatch(
+		&mut self,
+		source: &A,
+		sink: &B,
+		events: &[IbcEvent],
+		metrics: Option<&MetricsHandler>,
+		relay_packets: bool,
+		relay_timeouts: bool,
+	) {
+		let channel_whitelist = source.channel_whitelist();
+		self.workers.retain(|channel, _| channel_whitelist.contains(channel));
+
+		for (channel_id, port_id) in channel_whitelist {
+			let worker = self
+				.workers
+				.entry((channel_id, port_id.clone()))
+				.or_insert_with(|| spawn_worker(channel_id, port_id.clone()));
+			worker
+				.discovered
+				.lock()
+				.unwrap()
+				.extend_from_events(&channel_id, &port_id, events);
+			let job = ChannelJob {
+				source: source.clone(),
+				sink: sink.clone(),
+				metrics: metrics.cloned(),
+				relay_packets,
+				relay_timeouts,
+				source_submissions: self.source_submissions.clone(),
+				sink_submissions: self.sink_submissions.clone(),
+				shutdown: self.shutdown.clone(),
+			};
+			match worker.jobs.try_send(job) {
+				Ok(()) => {},
+				Err(TrySendError::Full(_)) => {
+					log::debug!(target: "hyperspace", "Channel {channel_id}/{port_id} of {} is still being relayed, skipping this round", source.name());
+				},
+				Err(TrySendError::Closed(job)) => {
+					log::error!(target: "hyperspace", "Worker of channel {channel_id}/{port_id} of {} stopped, restarting it", source.name());
+					let discovered = std::mem::take(&mut *worker.discovered.lock().unwrap());
+					*worker = spawn_worker(channel_id, port_id.clone());
+					worker.discovered.lock().unwrap().merge(discovered);
+					let _ = worker.jobs.try_send(job);
+				},
+			}
+		}
+	}
+
+	/// Stops the workers once they're done with the jobs they already picked up.
+	pub async fn stop(self) {
+		for ((channel_id, port_id), ChannelWorker { jobs, handle, .. }) in self.workers {
+			drop(jobs);
+			if let Err(e) = handle.await {
+				log::error!(target: "hyperspace", "Worker of channel {channel_id}/{port_id} failed: {e:?}");
+			}
+		}
+	}
+}
+
+fn spawn_worker<A: Chain, B: Chain>(channel_id: ChannelId, port_id: PortId) -> ChannelWorker<A, B> {
+	let (jobs, mut receiver) = mpsc::channel::<ChannelJob<A, B>>(1);
+	let discovered = Arc::new(Mutex::new(PendingPackets::default()));
+	let worker_discovered = discovered.clone();
+	let handle = tokio::spawn(async move {
+		// the packets sent before the worker started are only found by scanning the channel
+		let mut pending = PendingPackets::full_scan();
+		let mut last_full_scan = Instant::now();
+		while let Some(job) = receiver.recv().await {
+			pending.merge(std::mem::take(&mut *worker_discovered.lock().unwrap()));
+			if last_full_scan.elapsed() > FULL_SCAN_INTERVAL {
+				pending.full_scan = true;
+			}
+			let full_scan = pending.full_scan;
+			match relay_channel(job, channel_id, port_id.clone(), &mut pending).await {
+				Ok(()) if full_scan => {
+					pending.full_scan = false;
+					last_full_scan = Instant::now();
+				},
+				Ok(()) => {},
+				// the pending packets are picked up again on the next round
+				Err(e) => {
+					log::error!(target: "hyperspace", "Failed to relay channel {channel_id}/{port_id}: {e:?}");
+				},
+			}
+		}
+	});
+	ChannelWorker { jobs, discovered, handle }
+}
+
+async fn relay_channel<A: Chain, B: Chain>(
+	job: ChannelJob<A, B>,
+	channel_id: ChannelId,
+	port_id: PortId,
+	pending: &mut PendingPackets,
+) -> anyhow::Result<()> {
+	let ChannelJob {
+		mut source,
+		mut sink,
+		mut metrics,
+		relay_packets,
+		relay_timeouts,
+		source_submissions,
+		sink_submissions,
+		shutdown,
+	} = job;
+	let source_latest = source.latest_height_and_timestamp().await?;
+	let sink_latest = sink.latest_height_and_timestamp().await?;
+	let (ready_packets, timeout_msgs) = query_ready_and_timed_out_channel_packets(
+		&source,
+		&sink,
+		source_latest,
+		sink_latest,
+		channel_id,
+		port_id.clone(),
+		pending,
+	)
+	.await?;
+	log::trace!(target: "hyperspace", "Received timeouts count for {channel_id}: {}", timeout_msgs.len());
+
+	if let Some(metrics) = metrics.as_ref() {
+		metrics
+			.handle_sequence_gap(&format!("{channel_id}/{port_id}"), pending.sequence_gap.blocks);
+	}
+	if let Some(gap) = pending.sequence_gap.take_alert() {
+		alerts::raise(
+			"sequence_gap",
+			source.name(),
+			format!("ordered channel {channel_id}/{port_id} is stuck: {gap}"),
+		)
+		.await;
+	}
+
+	// the timeouts are pending from now on, so that they're kept if submitting the packets fails
+	// while shutting down
+	let pending_timeouts =
+		relay_timeouts.then(|| shutdown.track(source.chain_id().as_str(), &timeout_msgs));
+	let submitted = if relay_packets {
+		let pending_packets = shutdown.track(sink.chain_id().as_str(), &ready_packets);
+		process_messages(&mut sink, &sink_submissions, &mut metrics, ready_packets, pending_packets)
+			.await
+	} else {
+		pending
+			.stuck
+			.set_reason_for_all("the client of the source on the sink is expired");
+		Ok(())
+	};
+	if let Err(e) = &submitted {
+		let reason = match frozen_height(&source, &sink, sink_latest.0).await {
+			Some(height) => format!("the client of the source on the sink is frozen at {height}"),
+			None => format!("submitting the messages failed: {e:?}"),
+		};
+		pending.stuck.set_reason_for_all(&reason);
+	}
+	if let Some(stuck) = pending.stuck.take_alert(source_latest.0) {
+		alerts::raise(
+			"stuck_packet",
+			source.name(),
+			format!("on channel {channel_id}/{port_id}, {stuck}"),
+		)
+		.await;
+	}
+	submitted?;
+	if let Some(pending_timeouts) = pending_timeouts {
+		process_timeouts(
+			&mut source,
+			&source_submissions,
+			&mut metrics,
+			timeout_msgs,
+			pending_timeouts,
+		)
+		.await?;
+	}
+	Ok(())
+}
+
+/// Height the client of `source` on `sink` was frozen at, messages proven with it are rejected.
+async fn frozen_height<A: Chain, B: Chain>(source: &A, sink: &B, at: Height) -> Option<Height> {
+	let response = sink.query_client_state(at, source.client_id()).await.ok()?;
+	let client_state = AnyClientState::try_from(response.client_state?).ok()?;
+	client_state.frozen_height()
+}
@@ -0,0 +1,308 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The finality events of a source chain are relayed in three stages: the events are fetched, the
+//! messages proving them are built, then submitted to the sink. Submission runs in its own task
+//! behind a bounded queue, so the events and proofs of the next finality event are queried while
+//! the messages of the previous one are still being submitted.
+//!
+//! Every message submitted to a chain goes through its [`SubmissionLock`]: the submit stages and
+//! the channel workers of both directions submit to the same chains, and the transactions of a
+//! signer submitted concurrently would race on its nonce.
+//!
+//! The messages queued are tracked by the [`Shutdown`] handle until they're submitted, so that the
+//! ones that don't make it once a shutdown is requested are persisted with the pending set.
+//!
+//! The client updates of the blocks packets are sent in are prefetched: the source starts building
+//! them as soon as the packets are seen, rather than when the finality event handler gets to them.
+
+use crate::{
+	handshake::HandshakeResumer,
+	packets::workers::ChannelWorkers,
+	process_messages,
+	shutdown::{PendingBatch, Shutdown},
+};
+use futures::{FutureExt, StreamExt};
+use ibc::{events::IbcEvent, Height};
+use ibc_proto::google::protobuf::Any;
+use metrics::handler::MetricsHandler;
+use primitives::{Chain, IbcEventProvider, IbcQueryProvider};
+use std::sync::{Arc, Mutex};
+use tokio::{sync::mpsc, task::JoinHandle};
+
+/// Number of built batches waiting to be submitted before building the next one waits.
+pub const SUBMIT_QUEUE_SIZE: usize = 2;
+
+/// The stages relaying the finality events of a source chain to a sink chain.
+pub struct Pipeline<A, B> {
+	/// Relays the packets of every whitelisted channel.
+	pub workers: ChannelWorkers<A, B>,
+	/// Submits the client updates and the messages built from their events.
+	pub submit: SubmitStage,
+	/// Resumes the handshakes whose events were missed, along with the client updates.
+	pub handshakes: HandshakeResumer,
+	/// Serializes the submissions to the sink.
+	pub sink_submissions: SubmissionLock,
+	/// Tracks the messages built until they're submitted.
+	pub shutdown: Shutdown,
+	prefetch: JoinHandle<()>,
+}
+
+impl<A: Chain, B: Chain> Pipeline<A, B> {
+	/// `metrics` are the ones of the source, the messages are relayed on its behalf. The
+	/// submission locks and the shutdown handle are shared with the pipeline of the opposite
+	/// direction.
+	pub fn new(
+		source: &A,
+		sink: &B,
+		metrics: Option<&MetricsHandler>,
+		source_submissions: &SubmissionLock,
+		sink_submissions: &SubmissionLock,
+		shutdown: &Shutdown,
+	) -> Self {
+		Self {
+			workers: ChannelWorkers::new(
+				source_submissions.clone(),
+				sink_submissions.clone(),
+				shutdown.clone(),
+			),
+			submit: SubmitStage::spawn(
+				sink.clone(),
+				metrics.cloned(),
+				sink_submissions.clone(),
+				shutdown.clone(),
+			),
+			handshakes: HandshakeResumer::for_source(source),
+			sink_submissions: sink_submissions.clone(),
+			shutdown: shutdown.clone(),
+			prefetch: tokio::spawn(prefetch_client_updates(source.clone(), sink.clone())),
+		}
+	}
+
+	/// Stops the workers and waits for the queued messages to be submitted.
+	pub async fn stop(self) {
+		self.prefetch.abort();
+		self.workers.stop().await;
+		self.submit.stop().await;
+	}
+}
+
+/// Asks `source` to prefetch the client update of every block packets are sent in on a whitelisted
+/// channel. A single update is prefetched at a time, the packets sent meanwhile are covered by the
+/// next one.
+async fn prefetch_client_updates<A: Chain, B: Chain>(source: A, sink: B) {
+	let mut events = source.ibc_events().await;
+	let mut prefetched_height = None;
+	while let Some(event) = events.next().await {
+		let mut height = send_packet_height(&source, &event);
+		// the events that arrived while the previous update was being prefetched
+		while let Some(Some(event)) = events.next().now_or_never() {
+			height = height.max(send_packet_height(&source, &event));
+		}
+		let Some(height) = height.filter(|height| Some(*height) > prefetched_height) else {
+			continue
+		};
+		log::debug!(target: "hyperspace", "Prefetching the client update of {} at {height} for {}", source.name(), sink.name());
+		match source.prefetch_client_update(height, &sink).await {
+			Ok(()) => prefetched_height = Some(height),
+			Err(e) => {
+				log::debug!(target: "hyperspace", "Failed to prefetch the client update of {} at {height}: {e:?}", source.name())
+			},
+		}
+	}
+}
+
+fn send_packet_height<A: Chain>(source: &A, event: &IbcEvent) -> Option<Height> {
+	match event {
+		IbcEvent::SendPacket(send_packet)
+			if source.channel_whitelist().contains(&(
+				send_packet.packet.source_channel,
+				send_packet.packet.source_port.clone(),
+			)) =>
+			Some(send_packet.height),
+		_ => None,
+	}
+}
+
+/// Serializes the submissions to a chain, a clone is handed to everything submitting to it.
+///
+/// Chains fetch the nonce of their signer when signing a transaction, so a transaction signed
+/// while another one is in flight would reuse its nonce and be rejected.
+#[derive(Clone, Default)]
+pub struct SubmissionLock(Arc<tokio::sync::Mutex<()>>);
+
+impl SubmissionLock {
+	/// Waits for the submission in flight to complete.
+	pub async fn acquire(&self) -> tokio::sync::MutexGuard<'_, ()> {
+		self.0.lock().await
+	}
+}
+
+struct Batch {
+	msgs: Vec<Any>,
+	/// The messages are pending until they're submitted.
+	pending: PendingBatch,
+	generation: u64,
+	/// Whether the batch catches the light client on the sink up with the source.
+	catch_up: bool,
+}
+
+#[derive(Default)]
+struct Progress {
+	/// Incremented whenever a submission fails, the batches built before are dropped.
+	generation: u64,
+	/// Highest height of the source whose events were built into a queued batch.
+	queued_height: Option<Height>,
+	/// Number of batches queued or being submitted.
+	in_flight: usize,
+	/// Whether a batch catching the light client up is queued or being submitted.
+	catching_up: bool,
+}
+
+/// What the submit stage had queued when the events of a finality event started being fetched.
+#[derive(Clone, Copy, Debug)]
+pub struct Ticket {
+	generation: u64,
+	/// The events up to this height are already built into a queued batch, they're fetched again
+	/// until the light client on the sink is updated past them.
+	pub queued_height: Option<Height>,
+	/// Whether all the queued batches were submitted.
+	pub idle: bool,
+	/// Whether the light client on the sink is being caught up, the events of the source can't be
+	/// proven to it until then.
+	pub catching_up: bool,
+}
+
+/// Submits the batches of messages built for a sink chain in the order they were queued.
+///
+/// The batches built while a submission is in flight assume it succeeds: the client updates they
+/// contain build on top of it. When it fails, the batches queued after it are dropped and the
+/// events they were built from are fetched again on the next finality event.
+pub struct SubmitStage {
+	queue: mpsc::Sender<Batch>,
+	progress: Arc<Mutex<Progress>>,
+	handle: JoinHandle<()>,
+	/// Identifier of the sink, the messages queued are tracked as pending for it.
+	chain_id: String,
+	shutdown: Shutdown,
+}
+
+impl SubmitStage {
+	pub fn spawn<B: Chain>(
+		mut sink: B,
+		mut metrics: Option<MetricsHandler>,
+		submissions: SubmissionLock,
+		shutdown: Shutdown,
+	) -> Self {
+		let chain_id = sink.chain_id().to_string();
+		let (queue, mut receiver) = mpsc::channel::<Batch>(SUBMIT_QUEUE_SIZE);
+		let progress = Arc::new(Mutex::new(Progress::default()));
+		let stage_progress = progress.clone();
+		let handle = tokio::spawn(async move {
+			while let Some(Batch { msgs, pending, generation, catch_up }) = receiver.recv().await {
+				if generation != stage_progress.lock().unwrap().generation {
+					log::debug!(target: "hyperspace", "Dropping {} messages for {} built on top of a failed submission", msgs.len(), sink.name());
+					stage_progress.lock().unwrap().in_flight -= 1;
+					continue
+				}
+				let result =
+					process_messages(&mut sink, &submissions, &mut metrics, msgs, pending).await;
+				let mut progress = stage_progress.lock().unwrap();
+				progress.in_flight -= 1;
+				if catch_up {
+					progress.catching_up = false;
+				}
+				if let Err(e) = result {
+					log::error!(target: "hyperspace", "{e:?}");
+					progress.generation += 1;
+					progress.queued_height = None;
+					drop(progress);
+					if let Err(e) = sink.handle_error(&e).await {
+						log::error!(target: "hyperspace", "Failed to handle error {e:?}");
+					}
+				}
+			}
+		});
+		Self { queue, progress, handle, chain_id, shutdown }
+	}
+
+	pub fn ticket(&self) -> Ticket {
+		let progress = self.progress.lock().unwrap();
+		Ticket {
+			generation: progress.generation,
+			queued_height: progress.queued_height,
+			idle: progress.in_flight == 0,
+			catching_up: progress.catching_up,
+		}
+	}
+
+	/// Queues `msgs`, built from the events of the source up to `height`, waiting while the queue
+	/// is full. Nothing is queued if a submission failed since the `ticket` was taken.
+	pub async fn submit(
+		&self,
+		ticket: Ticket,
+		msgs: Vec<Any>,
+		height: Option<Height>,
+	) -> anyhow::Result<()> {
+		self.queue_batch(ticket, msgs, height, false).await
+	}
+
+	/// Queues the mandatory updates catching the light client on the sink up with the source,
+	/// the tickets taken until they're submitted are [`Ticket::catching_up`].
+	pub async fn submit_catch_up(&self, ticket: Ticket, msgs: Vec<Any>) -> anyhow::Result<()> {
+		self.queue_batch(ticket, msgs, None, true).await
+	}
+
+	async fn queue_batch(
+		&self,
+		ticket: Ticket,
+		msgs: Vec<Any>,
+		height: Option<Height>,
+		catch_up: bool,
+	) -> anyhow::Result<()> {
+		let pending = self.shutdown.track(&self.chain_id, &msgs);
+		{
+			let mut progress = self.progress.lock().unwrap();
+			if progress.generation != ticket.generation {
+				log::debug!(target: "hyperspace", "Dropping {} messages built on top of a failed submission", msgs.len());
+				return Ok(())
+			}
+			progress.queued_height = progress.queued_height.max(height);
+			if msgs.is_empty() {
+				return Ok(())
+			}
+			progress.in_flight += 1;
+			progress.catching_up |= catch_up;
+		}
+		let generation = ticket.generation;
+		if self.queue.send(Batch { msgs, pending, generation, catch_up }).await.is_err() {
+			let mut progress = self.progress.lock().unwrap();
+			progress.in_flight -= 1;
+			if catch_up {
+				progress.catching_up = false;
+			}
+			return Err(anyhow::anyhow!("Submit stage stopped"))
+		}
+		Ok(())
+	}
+
+	/// Waits for the queued batches to be submitted. If a shutdown is requested, the ones that fail
+	/// or are dropped meanwhile are kept as pending.
+	pub async fn stop(self) {
+		drop(self.queue);
+		if let Err(e) = self.handle.await {
+			log::error!(target: "hyperspace", "Submit stage failed: {e:?}");
+		}
+	}
+}
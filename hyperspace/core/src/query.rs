@@ -0,0 +1,248 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commands querying the IBC state of a chain through its [`IbcQueryProvider`], printing the
+//! responses as JSON (or hex encoded protobuf) for debugging.
+
+use crate::{chain::AnyConfig, interpolation::read_config};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use ibc::{
+	core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+	Height,
+};
+use primitives::IbcQueryProvider;
+use prost::Message;
+use serde::Serialize;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Parser)]
+pub struct QueryCmd {
+	/// Config path of the queried chain.
+	#[clap(long)]
+	config: String,
+	/// Height to query the state at, defaults to the latest height of the chain.
+	#[clap(long)]
+	height: Option<u64>,
+	/// Print the responses as hex encoded protobuf instead of JSON.
+	#[clap(long)]
+	proto: bool,
+	#[clap(subcommand)]
+	subcommand: QuerySubcommand,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub enum QuerySubcommand {
+	#[clap(name = "client-state", about = "Queries the state of a client")]
+	ClientState(ClientArgs),
+	#[clap(name = "consensus-state", about = "Queries a consensus state of a client")]
+	ConsensusState(ConsensusStateArgs),
+	#[clap(name = "connection", about = "Queries a connection end")]
+	Connection(ConnectionArgs),
+	#[clap(name = "channel", about = "Queries a channel end")]
+	Channel(ChannelArgs),
+	#[clap(
+		name = "packet-commitments",
+		about = "Lists the sequences of the packets sent on a channel that are still committed"
+	)]
+	PacketCommitments(ChannelArgs),
+	#[clap(
+		name = "unreceived-packets",
+		about = "Lists the packets committed by the counterparty that the channel hasn't received yet"
+	)]
+	UnreceivedPackets(UnreceivedPacketsArgs),
+	#[clap(name = "denom-trace", about = "Resolves an `ibc/{hash}` denom to its denom trace")]
+	DenomTrace(DenomTraceArgs),
+	#[clap(name = "denom-traces", about = "Lists the denom traces of the ibc denoms")]
+	DenomTraces(DenomTracesArgs),
+	#[clap(name = "denom-hash", about = "Queries the `ibc/{hash}` denom of a denom trace")]
+	DenomHash(DenomHashArgs),
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct ClientArgs {
+	/// Client id.
+	client_id: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct ConsensusStateArgs {
+	/// Client id.
+	client_id: String,
+	/// Height of the consensus state, as `<revision number>-<revision height>`.
+	consensus_height: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct ConnectionArgs {
+	/// Connection id.
+	connection_id: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct ChannelArgs {
+	/// Channel id.
+	channel_id: String,
+	/// Port id.
+	port_id: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct UnreceivedPacketsArgs {
+	/// Channel id on the queried chain.
+	channel_id: String,
+	/// Port id on the queried chain.
+	port_id: String,
+	/// Config path of the counterparty chain, which sends the packets.
+	#[clap(long)]
+	counterparty_config: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct DenomTraceArgs {
+	/// Denom hash, with or without the `ibc/` prefix.
+	denom_hash: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct DenomTracesArgs {
+	/// Maximum number of denom traces listed.
+	#[clap(long, default_value = "100")]
+	limit: u64,
+	/// Hex encoded `next_key` of a previous page to continue from.
+	#[clap(long)]
+	next_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct DenomHashArgs {
+	/// Denom trace, as `{port_id}/{channel_id}/.../{base_denom}`.
+	trace: String,
+}
+
+impl ChannelArgs {
+	fn parse(&self) -> Result<(ChannelId, PortId)> {
+		Ok((ChannelId::from_str(&self.channel_id)?, PortId::from_str(&self.port_id)?))
+	}
+}
+
+impl QueryCmd {
+	pub async fn run(&self) -> Result<()> {
+		let chain = read_config::<AnyConfig>(&self.config).await?.into_client().await?;
+		let (latest_height, _) = chain.latest_height_and_timestamp().await?;
+		let at = match self.height {
+			Some(height) => Height::new(latest_height.revision_number, height),
+			None => latest_height,
+		};
+
+		match &self.subcommand {
+			QuerySubcommand::ClientState(args) => {
+				let client_id = ClientId::from_str(&args.client_id)?;
+				let response = chain.query_client_state(at, client_id).await?;
+				self.print_proto(&response)
+			},
+			QuerySubcommand::ConsensusState(args) => {
+				let client_id = ClientId::from_str(&args.client_id)?;
+				// `Height::from_str` panics on heights without a revision number
+				if !args.consensus_height.contains('-') {
+					return Err(anyhow!(
+						"Consensus height must be `<revision number>-<revision height>`"
+					))
+				}
+				let consensus_height = Height::from_str(&args.consensus_height)
+					.map_err(|e| anyhow!("Invalid consensus height: {e}"))?;
+				let response =
+					chain.query_client_consensus(at, client_id, consensus_height).await?;
+				self.print_proto(&response)
+			},
+			QuerySubcommand::Connection(args) => {
+				let connection_id = ConnectionId::from_str(&args.connection_id)?;
+				let response = chain.query_connection_end(at, connection_id).await?;
+				self.print_proto(&response)
+			},
+			QuerySubcommand::Channel(args) => {
+				let (channel_id, port_id) = args.parse()?;
+				let response = chain.query_channel_end(at, channel_id, port_id).await?;
+				self.print_proto(&response)
+			},
+			QuerySubcommand::PacketCommitments(args) => {
+				let (channel_id, port_id) = args.parse()?;
+				let seqs = chain.query_packet_commitments(at, channel_id, port_id).await?;
+				print_json(&seqs)
+			},
+			QuerySubcommand::UnreceivedPackets(args) => {
+				let channel_id = ChannelId::from_str(&args.channel_id)?;
+				let port_id = PortId::from_str(&args.port_id)?;
+				let channel = chain
+					.query_channel_end(at, channel_id, port_id.clone())
+					.await?
+					.channel
+					.ok_or_else(|| anyhow!("Channel {channel_id}/{port_id} not found"))?;
+				let counterparty = channel
+					.counterparty
+					.ok_or_else(|| anyhow!("Channel {channel_id}/{port_id} has no counterparty"))?;
+				let counterparty_channel_id = ChannelId::from_str(&counterparty.channel_id)?;
+				let counterparty_port_id = PortId::from_str(&counterparty.port_id)?;
+
+				let counterparty_chain = read_config::<AnyConfig>(&args.counterparty_config)
+					.await?
+					.into_client()
+					.await?;
+				let (counterparty_height, _) =
+					counterparty_chain.latest_height_and_timestamp().await?;
+				let seqs = counterparty_chain
+					.query_packet_commitments(
+						counterparty_height,
+						counterparty_channel_id,
+						counterparty_port_id,
+					)
+					.await?;
+				let unreceived =
+					chain.query_unreceived_packets(at, channel_id, port_id, seqs).await?;
+				print_json(&unreceived)
+			},
+			QuerySubcommand::DenomTrace(args) => {
+				let response = chain.query_denom_trace(args.denom_hash.clone()).await?;
+				self.print_proto(&response)
+			},
+			QuerySubcommand::DenomTraces(args) => {
+				let next_key = args
+					.next_key
+					.as_deref()
+					.map(|key| hex::decode(key.trim_start_matches("0x")))
+					.transpose()?;
+				let response = chain.query_denom_traces(next_key, args.limit).await?;
+				self.print_proto(&response)
+			},
+			QuerySubcommand::DenomHash(args) => {
+				let hash = chain.query_denom_hash(args.trace.clone()).await?;
+				print_json(&hash)
+			},
+		}
+	}
+
+	fn print_proto(&self, response: &(impl Message + Serialize)) -> Result<()> {
+		if self.proto {
+			println!("0x{}", hex::encode(response.encode_to_vec()));
+			Ok(())
+		} else {
+			print_json(response)
+		}
+	}
+}
+
+fn print_json(value: &impl Serialize) -> Result<()> {
+	println!("{}", serde_json::to_string_pretty(value)?);
+	Ok(())
+}
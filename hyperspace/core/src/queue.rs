@@ -12,9 +12,118 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use anyhow::anyhow;
+use ibc::{
+	core::{
+		ics04_channel::{
+			channel::{ChannelEnd, Order},
+			msgs::{
+				acknowledgement::MsgAcknowledgement, timeout::MsgTimeout,
+				timeout_on_close::MsgTimeoutOnClose, PacketMsg,
+			},
+		},
+		ics26_routing::msgs::Ics26Envelope,
+	},
+	Height,
+};
 use ibc_proto::google::protobuf::Any;
+use itertools::Itertools;
 use metrics::handler::MetricsHandler;
-use primitives::Chain;
+use primitives::{cost::TxCost, mock::LocalClientTypes, Chain};
+/// In dry-run mode the messages are logged instead of being submitted, see
+/// [`flush_message_batch`].
+pub use primitives::{is_dry_run, set_dry_run};
+
+/// Logs the decoded contents of messages that aren't submitted because of the dry-run mode.
+pub fn log_dry_run(msgs: &[Any], cost: TxCost, sink: &impl Chain) {
+	log::info!(target: "hyperspace", "[dry-run] Not submitting {} messages to {}, estimated cost: {cost}", msgs.len(), sink.name());
+	for msg in msgs {
+		match Ics26Envelope::<LocalClientTypes>::try_from(msg.clone()) {
+			Ok(envelope) =>
+				log::info!(target: "hyperspace", "[dry-run] {}: {envelope:?}", msg.type_url),
+			Err(_) =>
+				log::info!(target: "hyperspace", "[dry-run] {}: 0x{}", msg.type_url, hex::encode(&msg.value)),
+		}
+	}
+}
+
+/// Drops the packet messages that were already delivered to `sink`, e.g. by another relayer while
+/// this one was stopped. Submitting them again would only fail and waste fees.
+pub async fn retain_undelivered_messages(msgs: Vec<Any>, sink: &impl Chain) -> Vec<Any> {
+	let latest_height = match sink.latest_height_and_timestamp().await {
+		Ok((height, _)) => height,
+		Err(e) => {
+			log::warn!(target: "hyperspace", "Failed to query the latest height of {}, submitting all the messages: {e:?}", sink.name());
+			return msgs
+		},
+	};
+	let mut undelivered = Vec::with_capacity(msgs.len());
+	for msg in msgs {
+		match is_delivered(&msg, latest_height, sink).await {
+			Ok(true) => {
+				log::info!(target: "hyperspace", "Skipping {} message already delivered to {}", msg.type_url, sink.name());
+			},
+			Ok(false) => undelivered.push(msg),
+			Err(e) => {
+				log::warn!(target: "hyperspace", "Failed to check whether {} message was delivered to {}, submitting it: {e:?}", msg.type_url, sink.name());
+				undelivered.push(msg);
+			},
+		}
+	}
+	undelivered
+}
+
+/// Whether the packet message `msg` already took effect on `sink`: the packet of a receive message
+/// has a receipt or an acknowledgement, or the next sequence to receive moved past it on ordered
+/// channels, the commitment of an acknowledged or timed out packet is deleted. Other messages are
+/// never considered delivered.
+async fn is_delivered(msg: &Any, at: Height, sink: &impl Chain) -> Result<bool, anyhow::Error> {
+	let Ok(Ics26Envelope::Ics4PacketMsg(packet_msg)) =
+		Ics26Envelope::<LocalClientTypes>::try_from(msg.clone())
+	else {
+		return Ok(false)
+	};
+	let delivered = match packet_msg {
+		PacketMsg::RecvPacket(msg) => {
+			let packet = msg.packet;
+			let (port_id, channel_id) = (&packet.destination_port, &packet.destination_channel);
+			let sequence = u64::from(packet.sequence);
+			let channel = sink
+				.query_channel_end(at, *channel_id, port_id.clone())
+				.await?
+				.channel
+				.ok_or_else(|| anyhow!("Channel {channel_id}/{port_id} not found"))?;
+			match ChannelEnd::try_from(channel)?.ordering {
+				// ordered channels don't write receipts
+				Order::Ordered | Order::OrderedAllowTimeout =>
+					sink.query_next_sequence_recv(at, port_id, channel_id)
+						.await?
+						.next_sequence_receive >
+						sequence,
+				Order::Unordered =>
+					sink.query_packet_receipt(at, port_id, channel_id, sequence).await?.received ||
+						!sink
+							.query_packet_acknowledgement(at, port_id, channel_id, sequence)
+							.await?
+							.acknowledgement
+							.is_empty(),
+			}
+		},
+		PacketMsg::AckPacket(MsgAcknowledgement { packet, .. }) |
+		PacketMsg::ToPacket(MsgTimeout { packet, .. }) |
+		PacketMsg::ToClosePacket(MsgTimeoutOnClose { packet, .. }) => sink
+			.query_packet_commitment(
+				at,
+				&packet.source_port,
+				&packet.source_channel,
+				packet.sequence.into(),
+			)
+			.await?
+			.commitment
+			.is_empty(),
+	};
+	Ok(delivered)
+}
 
 /// This sends messages to the sink chain in a gas-aware manner.
 pub async fn flush_message_batch(
@@ -22,39 +131,228 @@ pub async fn flush_message_batch(
 	metrics: Option<&MetricsHandler>,
 	sink: &impl Chain,
 ) -> Result<(), anyhow::Error> {
-	let block_max_weight = sink.block_max_weight();
-	let batch_weight = sink.estimate_weight(msgs.clone()).await?;
+	let budget = sink.tx_budget();
+	let batch_cost = sink.estimate_cost(msgs.clone()).await?;
 
 	if let Some(metrics) = metrics {
-		metrics.handle_transaction_costs(batch_weight, &msgs).await;
+		metrics.handle_transaction_costs(batch_cost.amount, &msgs).await;
 	}
 
-	log::debug!(target: "hyperspace", "Outgoing messages weight: {} block max weight: {}", batch_weight, block_max_weight);
-	let ratio = (batch_weight / block_max_weight) as usize;
-	if ratio == 0 {
-		sink.submit(msgs).await?;
+	log::debug!(target: "hyperspace", "Outgoing messages cost: {batch_cost}, transaction budget: {budget}");
+	if is_dry_run() {
+		log_dry_run(&msgs, batch_cost, sink);
 		return Ok(())
 	}
-
-	// whelp our batch exceeds the block max weight.
-	let chunk = if ratio == 1 {
-		// split the batch into ratio * 2
-		ratio * 2
+	let ratio = budget.overrun(batch_cost)? as usize;
+	let batches = if ratio == 0 {
+		vec![msgs]
 	} else {
-		// split the batch into ratio + 2
-		ratio + 2
+		// whelp our batch exceeds the transaction budget.
+		let chunk = if ratio == 1 {
+			// split the batch into ratio * 2
+			ratio * 2
+		} else {
+			// split the batch into ratio + 2
+			ratio + 2
+		};
+
+		log::info!(
+			"Outgoing messages cost: {} exceeds the transaction budget: {}. Chunking {} messages into {} chunks",
+			batch_cost, budget, msgs.len(), chunk,
+		);
+		let chunk_size = (msgs.len() / chunk).max(1);
+		into_chunks(msgs, chunk_size)
 	};
 
-	log::info!(
-		"Outgoing messages weight: {} exceeds the block max weight: {}. Chunking {} messages into {} chunks",
-        batch_weight, block_max_weight, msgs.len(), chunk,
-	);
-	let chunk_size = (msgs.len() / chunk).max(1);
-	// TODO: return number of failed messages and record it to metrics
-	for batch in msgs.chunks(chunk_size) {
-		// send out batches.
-		sink.submit(batch.to_vec()).await?;
+	let (batches, oversized) = match sink.max_transaction_size() {
+		Some(max_size) => {
+			let count = batches.len();
+			let (mut chunks, mut oversized) = (vec![], vec![]);
+			for batch in batches {
+				let (batch_chunks, batch_oversized) = split_by_encoded_size(batch, max_size);
+				chunks.extend(batch_chunks);
+				oversized.extend(batch_oversized);
+			}
+			if chunks.len() > count {
+				log::info!(target: "hyperspace", "Split the messages into {} transactions to stay under the maximum transaction size of {}", chunks.len(), sink.name());
+			}
+			(chunks, oversized.into_iter().map(|(msg, size)| (msg.type_url, size)).collect())
+		},
+		None => (batches, vec![]),
+	};
+	submit_batches(batches, sink).await?;
+	if !oversized.is_empty() {
+		let oversized = oversized
+			.iter()
+			.map(|(type_url, size)| format!("{type_url} ({size} bytes)"))
+			.join(", ");
+		return Err(anyhow!(
+			"Messages exceeding the maximum transaction size of {} weren't submitted: {oversized}",
+			sink.name()
+		))
 	}
 
 	Ok(())
 }
+
+async fn submit_batches(
+	mut batches: Vec<Vec<Any>>,
+	sink: &impl Chain,
+) -> Result<(), anyhow::Error> {
+	match batches.len() {
+		0 => {},
+		1 => {
+			sink.submit(batches.remove(0)).await?;
+		},
+		_ => {
+			// TODO: return number of failed messages and record it to metrics
+			sink.submit_batch(batches).await?;
+		},
+	}
+	Ok(())
+}
+
+/// Splits `msgs` into chunks of `chunk_size` messages without copying them, client updates and
+/// proofs can be megabytes.
+fn into_chunks(msgs: Vec<Any>, chunk_size: usize) -> Vec<Vec<Any>> {
+	let mut chunks = Vec::with_capacity(msgs.len().div_ceil(chunk_size));
+	let mut msgs = msgs.into_iter().peekable();
+	while msgs.peek().is_some() {
+		chunks.push(msgs.by_ref().take(chunk_size).collect());
+	}
+	chunks
+}
+
+/// Splits `msgs` into consecutive chunks whose encoded size stays under `max_size` bytes. The
+/// protobuf encoding of a message is at least as large as its SCALE encoding in an extrinsic.
+///
+/// A message that is larger on its own, e.g. a client update with many headers, can't be
+/// submitted: it's returned with its size instead of a chunk the chain would reject.
+fn split_by_encoded_size(msgs: Vec<Any>, max_size: usize) -> (Vec<Vec<Any>>, Vec<(Any, usize)>) {
+	let mut chunks = vec![];
+	let mut oversized = vec![];
+	let mut chunk = vec![];
+	let mut chunk_size = 0;
+	for msg in msgs {
+		let size = prost::Message::encoded_len(&msg);
+		if size > max_size {
+			oversized.push((msg, size));
+			continue
+		}
+		if !chunk.is_empty() && chunk_size + size > max_size {
+			chunks.push(std::mem::take(&mut chunk));
+			chunk_size = 0;
+		}
+		chunk_size += size;
+		chunk.push(msg);
+	}
+	if !chunk.is_empty() {
+		chunks.push(chunk);
+	}
+	(chunks, oversized)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ibc::{
+		core::{
+			ics04_channel::msgs::recv_packet::MsgRecvPacket,
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		proofs::Proofs,
+		tx_msg::Msg,
+	};
+	use mock::{MockChain, MockConfig};
+	use primitives::{packet_info_to_packet, IbcEventProvider, IbcQueryProvider, KeyProvider};
+
+	fn msg(type_url: &str, size: usize) -> Any {
+		// 1 byte for the tag and 1 for the length of each field
+		let value_len = size - type_url.len() - 4;
+		let msg = Any { type_url: type_url.to_string(), value: vec![0; value_len] };
+		assert_eq!(prost::Message::encoded_len(&msg), size);
+		msg
+	}
+
+	#[test]
+	fn splits_messages_by_encoded_size() {
+		let cases = [
+			(vec![], 100, vec![], vec![]),
+			(vec![("a", 40), ("b", 40)], 100, vec![vec!["a", "b"]], vec![]),
+			(vec![("a", 40), ("b", 40), ("c", 40)], 100, vec![vec!["a", "b"], vec!["c"]], vec![]),
+			(vec![("a", 50), ("b", 50), ("c", 50)], 100, vec![vec!["a", "b"], vec!["c"]], vec![]),
+			(vec![("a", 100), ("b", 10)], 100, vec![vec!["a"], vec!["b"]], vec![]),
+			(
+				vec![("a", 40), ("big", 120), ("c", 40), ("d", 40)],
+				100,
+				vec![vec!["a", "c"], vec!["d"]],
+				vec![("big", 120)],
+			),
+			(vec![("big", 120)], 100, vec![], vec![("big", 120)]),
+		];
+		for (msgs, max_size, expected_chunks, expected_oversized) in cases {
+			let msgs = msgs.into_iter().map(|(type_url, size)| msg(type_url, size)).collect();
+			let (chunks, oversized) = split_by_encoded_size(msgs, max_size);
+			let chunks = chunks
+				.iter()
+				.map(|chunk| chunk.iter().map(|msg| msg.type_url.as_str()).collect::<Vec<_>>())
+				.collect::<Vec<_>>();
+			let oversized = oversized
+				.iter()
+				.map(|(msg, size)| (msg.type_url.as_str(), *size))
+				.collect::<Vec<_>>();
+			assert_eq!((chunks, oversized), (expected_chunks, expected_oversized), "{max_size}");
+		}
+	}
+
+	/// Whether the receive messages of `count` packets sent by chain A, on a channel of ordering
+	/// `order`, are considered delivered to chain B once the first `received` ones were received.
+	async fn delivered_packets(order: Order, count: usize, received: usize) -> Vec<bool> {
+		let (chain_a, chain_b) =
+			MockChain::pair(MockConfig::new("mock-a", 2000), MockConfig::new("mock-b", 2001));
+		chain_a.set_channel_order(order);
+		chain_b.set_channel_order(order);
+		let sequences = chain_a.send_transfers(count);
+		chain_a.produce_block();
+		let (proof_height, _) = chain_a.latest_height_and_timestamp().await.unwrap();
+		chain_b.update_client_of(&chain_a).unwrap();
+		let msgs = chain_a
+			.query_send_packets(ChannelId::new(0), PortId::transfer(), sequences)
+			.await
+			.unwrap()
+			.iter()
+			.map(|packet_info| {
+				MsgRecvPacket {
+					packet: packet_info_to_packet(packet_info),
+					proofs: Proofs::new(
+						vec![1].try_into().unwrap(),
+						None,
+						None,
+						None,
+						proof_height,
+					)
+					.unwrap(),
+					signer: chain_b.account_id(),
+				}
+				.to_any()
+			})
+			.collect::<Vec<_>>();
+		chain_b.submit(msgs[..received].to_vec()).await.unwrap();
+		chain_b.produce_block();
+
+		let (at, _) = chain_b.latest_height_and_timestamp().await.unwrap();
+		let mut delivered = vec![];
+		for msg in &msgs {
+			delivered.push(is_delivered(msg, at, &chain_b).await.unwrap());
+		}
+		delivered
+	}
+
+	#[tokio::test]
+	async fn received_packets_are_delivered() {
+		for order in [Order::Unordered, Order::Ordered, Order::OrderedAllowTimeout] {
+			assert_eq!(delivered_packets(order, 3, 0).await, [false, false, false], "{order}");
+			assert_eq!(delivered_packets(order, 3, 2).await, [true, true, false], "{order}");
+		}
+	}
+}
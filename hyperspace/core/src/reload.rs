@@ -0,0 +1,99 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime reload of the relayer configs. On SIGHUP the config files are read again and the
+//! changes that are safe to apply to a running relayer are applied in place: the channel whitelist
+//! of each chain, the maximum log level and the alert webhook. The subscriptions and the packets
+//! being relayed are left untouched, any other change only takes effect after a restart.
+
+use crate::{
+	alerts,
+	chain::{AnyChain, AnyConfig, CoreConfig},
+	interpolation::from_toml_str,
+};
+use log::LevelFilter;
+use primitives::{Chain, IbcQueryProvider};
+use std::{collections::HashSet, str::FromStr};
+
+/// Paths of the configs the relayer was started from.
+#[derive(Debug, Clone)]
+pub struct ConfigPaths {
+	pub config_a: String,
+	pub config_b: String,
+	pub config_core: String,
+}
+
+/// Caps the level of the relayer logs, e.g. `warn` silences the info logs. Levels more verbose
+/// than the `RUST_LOG` filter the relayer was started with have no effect.
+pub fn apply_log_level(level: &str) -> Result<(), anyhow::Error> {
+	let level =
+		LevelFilter::from_str(level).map_err(|_| anyhow::anyhow!("Invalid log level {level}"))?;
+	log::set_max_level(level);
+	Ok(())
+}
+
+/// Reloads the configs on every SIGHUP until the relayer shuts down. `chains` share their channel
+/// whitelist with the chains being relayed, so updating them applies to the running relayer.
+pub async fn listen_for_reloads(paths: ConfigPaths, mut chains: (AnyChain, AnyChain)) {
+	#[cfg(unix)]
+	{
+		use tokio::signal::unix::{signal, SignalKind};
+		let mut sighup = match signal(SignalKind::hangup()) {
+			Ok(sighup) => sighup,
+			Err(e) => {
+				log::error!(target: "hyperspace", "Failed to listen for SIGHUP, config reloads are disabled: {e:?}");
+				return
+			},
+		};
+		while sighup.recv().await.is_some() {
+			log::info!(target: "hyperspace", "Received SIGHUP, reloading configs");
+			if let Err(e) = reload(&paths, &mut chains).await {
+				log::error!(target: "hyperspace", "Failed to reload configs, keeping the current ones: {e:?}");
+			}
+		}
+	}
+	#[cfg(not(unix))]
+	{
+		let _ = (paths, &mut chains);
+		log::warn!(target: "hyperspace", "Config reloads are only supported on unix");
+	}
+}
+
+/// Reads all the configs before applying anything, so that a malformed file leaves the running
+/// relayer untouched.
+async fn reload(
+	paths: &ConfigPaths,
+	(chain_a, chain_b): &mut (AnyChain, AnyChain),
+) -> Result<(), anyhow::Error> {
+	let config_a: AnyConfig = from_toml_str(&tokio::fs::read_to_string(&paths.config_a).await?)?;
+	let config_b: AnyConfig = from_toml_str(&tokio::fs::read_to_string(&paths.config_b).await?)?;
+	let config_core: CoreConfig =
+		from_toml_str(&tokio::fs::read_to_string(&paths.config_core).await?)?;
+	if let Some(level) = &config_core.log_level {
+		apply_log_level(level)?;
+	}
+	alerts::set_webhook(config_core.alert_webhook);
+
+	apply_channel_whitelist(chain_a, &config_a);
+	apply_channel_whitelist(chain_b, &config_b);
+	Ok(())
+}
+
+fn apply_channel_whitelist(chain: &mut AnyChain, config: &AnyConfig) {
+	let whitelist = config.channel_whitelist().into_iter().collect::<HashSet<_>>();
+	if whitelist != chain.channel_whitelist() {
+		log::info!(target: "hyperspace", "Channel whitelist of {} is now {whitelist:?}", chain.name());
+		chain.set_channel_whitelist(whitelist);
+	}
+}
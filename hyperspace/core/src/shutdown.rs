@@ -0,0 +1,302 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Graceful shutdown of the relayer. Once a shutdown is requested the relayer stops consuming new
+//! finality events, lets the messages it already built be submitted and exits. A second signal
+//! exits right away.
+//!
+//! The messages built for a chain are tracked by the [`Shutdown`] handle until they're submitted.
+//! The ones that are still queued, or that failed to be submitted while shutting down, make up the
+//! pending set, which is persisted so that it can be retried on the next start.
+
+use ibc_proto::google::protobuf::Any;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicBool, AtomicU64, Ordering},
+		Arc, Mutex,
+	},
+};
+use tokio::sync::Notify;
+
+/// Exit code of a relayer interrupted by a second signal, as for a process killed by SIGINT.
+const FORCED_EXIT_CODE: i32 = 130;
+
+/// A message that was built for a chain but couldn't be submitted before shutting down
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingMessage {
+	/// Identifier of the chain the message should be submitted to, see
+	/// [`primitives::Chain::chain_id`]
+	pub chain: String,
+	pub type_url: String,
+	/// Hex-encoded message
+	pub value: String,
+}
+
+impl PendingMessage {
+	pub fn new(chain_id: &str, msg: &Any) -> Self {
+		Self {
+			chain: chain_id.to_string(),
+			type_url: msg.type_url.clone(),
+			value: hex::encode(&msg.value),
+		}
+	}
+
+	pub fn to_any(&self) -> Result<Any, anyhow::Error> {
+		Ok(Any { type_url: self.type_url.clone(), value: hex::decode(&self.value)? })
+	}
+}
+
+/// Requests and tracks the shutdown of a relayer, a clone is handed to everything that needs to
+/// stop or that builds messages.
+#[derive(Clone, Default)]
+pub struct Shutdown(Arc<ShutdownState>);
+
+#[derive(Default)]
+struct ShutdownState {
+	requested: AtomicBool,
+	notify: Notify,
+	/// The batches of messages built but not submitted yet, in the order they were built.
+	pending: Mutex<BTreeMap<u64, Vec<PendingMessage>>>,
+	next_batch: AtomicU64,
+}
+
+impl Shutdown {
+	/// Returns true if the relayer was asked to shut down
+	pub fn is_requested(&self) -> bool {
+		self.0.requested.load(Ordering::SeqCst)
+	}
+
+	/// Ask the relayer to stop consuming new finality events and exit
+	pub fn request(&self) {
+		self.0.requested.store(true, Ordering::SeqCst);
+		self.0.notify.notify_waiters();
+	}
+
+	/// Resolves once a shutdown is requested
+	pub async fn requested(&self) {
+		let notified = self.0.notify.notified();
+		if self.is_requested() {
+			return
+		}
+		notified.await
+	}
+
+	/// Tracks the messages built for the chain with the given id until they're submitted, see
+	/// [`PendingBatch`].
+	pub fn track(&self, chain_id: &str, msgs: &[Any]) -> PendingBatch {
+		let id = self.0.next_batch.fetch_add(1, Ordering::SeqCst);
+		if !msgs.is_empty() {
+			let batch = msgs.iter().map(|msg| PendingMessage::new(chain_id, msg)).collect();
+			self.0.pending.lock().unwrap().insert(id, batch);
+		}
+		PendingBatch { shutdown: self.clone(), id }
+	}
+
+	/// The messages tracked that weren't submitted yet.
+	pub fn pending_messages(&self) -> Vec<PendingMessage> {
+		self.0.pending.lock().unwrap().values().flatten().cloned().collect()
+	}
+
+	/// Write the pending set to the given path, if there's any.
+	pub async fn persist_pending_messages(
+		&self,
+		path: impl AsRef<Path>,
+	) -> Result<(), anyhow::Error> {
+		let pending = self.pending_messages();
+		if pending.is_empty() {
+			return Ok(())
+		}
+		log::info!(target: "hyperspace", "Persisting {} pending messages to {}", pending.len(), path.as_ref().display());
+		tokio::fs::write(path, serde_json::to_vec_pretty(&pending)?).await?;
+		Ok(())
+	}
+}
+
+/// Messages tracked by a [`Shutdown`] handle. They're dropped from the pending set once
+/// [`PendingBatch::submitted`], and dropping the batch otherwise means they failed to be submitted
+/// or were given up on: they're kept if a shutdown is requested, otherwise they're built again
+/// from the chain state by the next relay cycles.
+#[must_use]
+pub struct PendingBatch {
+	shutdown: Shutdown,
+	id: u64,
+}
+
+impl PendingBatch {
+	/// The messages were submitted
+	pub fn submitted(self) {
+		self.shutdown.0.pending.lock().unwrap().remove(&self.id);
+	}
+}
+
+impl Drop for PendingBatch {
+	fn drop(&mut self) {
+		if !self.shutdown.is_requested() {
+			self.shutdown.0.pending.lock().unwrap().remove(&self.id);
+		}
+	}
+}
+
+/// Waits for SIGTERM or SIGINT and requests a shutdown when one is received. A second signal
+/// persists the pending set to `pending_messages_path` and exits without waiting for the
+/// messages being submitted.
+pub async fn listen_for_signals(
+	shutdown: Shutdown,
+	pending_messages_path: Option<PathBuf>,
+) -> Result<(), anyhow::Error> {
+	wait_for_signal().await?;
+	log::info!(target: "hyperspace", "Shutting down, waiting for in-flight messages to be submitted. Send the signal again to exit right away");
+	shutdown.request();
+
+	wait_for_signal().await?;
+	log::warn!(target: "hyperspace", "Exiting without waiting for in-flight messages to be submitted");
+	if let Some(path) = pending_messages_path {
+		shutdown.persist_pending_messages(path).await?;
+	}
+	std::process::exit(FORCED_EXIT_CODE)
+}
+
+async fn wait_for_signal() -> Result<(), anyhow::Error> {
+	#[cfg(unix)]
+	{
+		use tokio::signal::unix::{signal, SignalKind};
+		let mut sigterm = signal(SignalKind::terminate())?;
+		tokio::select! {
+			_ = sigterm.recv() => log::info!(target: "hyperspace", "Received SIGTERM"),
+			result = tokio::signal::ctrl_c() => {
+				result?;
+				log::info!(target: "hyperspace", "Received SIGINT")
+			},
+		}
+	}
+	#[cfg(not(unix))]
+	{
+		tokio::signal::ctrl_c().await?;
+		log::info!(target: "hyperspace", "Received SIGINT");
+	}
+	Ok(())
+}
+
+/// Read and remove the pending set persisted by a previous run.
+pub async fn take_persisted_pending_messages(
+	path: impl AsRef<Path>,
+) -> Result<Vec<PendingMessage>, anyhow::Error> {
+	let path = path.as_ref();
+	if !path.exists() {
+		return Ok(vec![])
+	}
+	let pending = serde_json::from_slice(&tokio::fs::read(path).await?)?;
+	tokio::fs::remove_file(path).await?;
+	Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn msg(value: u8) -> Any {
+		Any { type_url: "/ibc.core.channel.v1.MsgRecvPacket".to_string(), value: vec![value] }
+	}
+
+	fn path(name: &str) -> PathBuf {
+		std::env::temp_dir().join(format!("hyperspace-{name}-{}.json", std::process::id()))
+	}
+
+	#[test]
+	fn submitted_messages_are_not_pending() {
+		let shutdown = Shutdown::default();
+		let batch = shutdown.track("chain-a", &[msg(1), msg(2)]);
+		assert_eq!(shutdown.pending_messages().len(), 2);
+
+		batch.submitted();
+		assert!(shutdown.pending_messages().is_empty());
+	}
+
+	#[test]
+	fn failed_messages_are_only_kept_after_a_shutdown_is_requested() {
+		let shutdown = Shutdown::default();
+		drop(shutdown.track("chain-a", &[msg(1)]));
+		assert!(shutdown.pending_messages().is_empty());
+
+		// queued before the shutdown, failed after it
+		let queued = shutdown.track("chain-a", &[msg(2)]);
+		shutdown.request();
+		drop(queued);
+		drop(shutdown.track("chain-b", &[msg(3)]));
+		shutdown.track("chain-b", &[msg(4)]).submitted();
+
+		assert_eq!(
+			shutdown.pending_messages(),
+			vec![PendingMessage::new("chain-a", &msg(2)), PendingMessage::new("chain-b", &msg(3))]
+		);
+	}
+
+	#[test]
+	fn queued_messages_are_pending_until_submitted() {
+		let shutdown = Shutdown::default();
+		let _queued = shutdown.track("chain-a", &[msg(1)]);
+		shutdown.request();
+
+		assert_eq!(shutdown.pending_messages(), vec![PendingMessage::new("chain-a", &msg(1))]);
+	}
+
+	#[tokio::test]
+	async fn shutdown_requested_resolves_once_requested() {
+		let shutdown = Shutdown::default();
+		let requested = tokio::spawn({
+			let shutdown = shutdown.clone();
+			async move { shutdown.requested().await }
+		});
+		tokio::task::yield_now().await;
+		assert!(!requested.is_finished());
+
+		shutdown.request();
+		requested.await.unwrap();
+		// and right away once it was
+		shutdown.requested().await;
+	}
+
+	#[tokio::test]
+	async fn pending_messages_round_trip() {
+		let path = path("pending-round-trip");
+		let shutdown = Shutdown::default();
+		let _a = shutdown.track("chain-a", &[msg(1), msg(2)]);
+		let _b = shutdown.track("chain-b", &[msg(3)]);
+		shutdown.request();
+
+		shutdown.persist_pending_messages(&path).await.unwrap();
+		let pending = take_persisted_pending_messages(&path).await.unwrap();
+
+		assert_eq!(pending, shutdown.pending_messages());
+		let msgs = pending.iter().map(|msg| msg.to_any().unwrap()).collect::<Vec<_>>();
+		assert_eq!(msgs, vec![msg(1), msg(2), msg(3)]);
+		// the pending set is only resubmitted once
+		assert!(!path.exists());
+		assert!(take_persisted_pending_messages(&path).await.unwrap().is_empty());
+	}
+
+	#[tokio::test]
+	async fn nothing_is_persisted_without_pending_messages() {
+		let path = path("pending-empty");
+		let shutdown = Shutdown::default();
+		shutdown.request();
+
+		shutdown.persist_pending_messages(&path).await.unwrap();
+
+		assert!(!path.exists());
+	}
+}
@@ -162,12 +162,15 @@ impl light_client_common::config::Config for DaliConfig {
 
 	async fn custom_extrinsic_params(
 		client: &OnlineClient<Self>,
+		options: &ExtrinsicOptions,
 	) -> Result<
 		<Self::ExtrinsicParams as ExtrinsicParams<Self::Index, Self::Hash>>::OtherParams,
 		Error,
 	> {
-		let params =
-			ParachainExtrinsicsParamsBuilder::new().era(Era::Immortal, client.genesis_hash());
+		let (era, checkpoint) = options.era.checkpoint(client).await?;
+		let params = ParachainExtrinsicsParamsBuilder::new()
+			.era(era, checkpoint)
+			.tip(Tip::from(options.tip));
 		Ok(params.into())
 	}
 }
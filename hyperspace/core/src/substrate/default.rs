@@ -20,8 +20,8 @@ use async_trait::async_trait;
 use codec::{Compact, Decode, Encode};
 use ibc_proto::google::protobuf::Any;
 use light_client_common::config::{
-	EventRecordT, IbcEventsT, LocalAddress, ParaLifecycleT, RuntimeCall, RuntimeStorage,
-	RuntimeTransactions,
+	EventRecordT, ExtrinsicOptions, IbcEventsT, LocalAddress, ParaLifecycleT, RuntimeCall,
+	RuntimeStorage, RuntimeTransactions,
 };
 use pallet_ibc::{events::IbcEvent as RawIbcEvent, MultiAddress, Timeout, TransferParams};
 use pallet_ibc_ping::SendPingParams;
@@ -174,12 +174,15 @@ impl light_client_common::config::Config for DefaultConfig {
 
 	async fn custom_extrinsic_params(
 		client: &OnlineClient<Self>,
+		options: &ExtrinsicOptions,
 	) -> Result<
 		<Self::ExtrinsicParams as ExtrinsicParams<Self::Index, Self::Hash>>::OtherParams,
 		Error,
 	> {
-		let params =
-			ParachainExtrinsicsParamsBuilder::new().era(Era::Immortal, client.genesis_hash());
+		let (era, checkpoint) = options.era.checkpoint(client).await?;
+		let params = ParachainExtrinsicsParamsBuilder::new()
+			.era(era, checkpoint)
+			.tip(Tip::from(options.tip));
 		Ok(params)
 	}
 }
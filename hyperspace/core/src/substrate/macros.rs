@@ -490,6 +490,27 @@ macro_rules! define_ibc_event_wrapper {
 					MetadataIbcEvent::PushWasmCode{ wasm_code_id: wasm_code_hash } => RawIbcEvent::PushWasmCode {
 						wasm_code_hash
 					},
+					MetadataIbcEvent::UpgradeChannel {
+						revision_height,
+						revision_number,
+						step,
+						port_id,
+						channel_id,
+						counterparty_port_id,
+						counterparty_channel_id,
+						upgrade_sequence,
+						channel_state,
+					} => RawIbcEvent::UpgradeChannel {
+						revision_height,
+						revision_number,
+						step,
+						port_id,
+						channel_id,
+						counterparty_port_id,
+						counterparty_channel_id,
+						upgrade_sequence,
+						channel_state,
+					},
 					$($additional)*
 				}
 			}
@@ -882,5 +903,11 @@ macro_rules! define_asset_id {
 				serializer.serialize_u128(self.0 .0)
 			}
 		}
+
+		impl $crate::keys::ParseAssetId for $name {
+			fn parse_asset_id(asset_id: &str) -> anyhow::Result<Self> {
+				Ok(<u128 as $crate::keys::ParseAssetId>::parse_asset_id(asset_id)?.into())
+			}
+		}
 	};
 }
@@ -0,0 +1,166 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commands submitting single transactions with the relayer account of a chain, for manual
+//! intervention and testing against live chains.
+
+use crate::{chain::AnyConfig, interpolation, queue};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use ibc::{
+	applications::{
+		fee::msgs::register_payee::MsgRegisterCounterpartyPayee,
+		transfer::{msgs::transfer::MsgTransfer, Amount, PrefixedCoin, PrefixedDenom},
+	},
+	core::ics24_host::identifier::{ChannelId, PortId},
+	tx_msg::Msg,
+	Height,
+};
+use ibc_proto::google::protobuf::Any;
+use primitives::{Chain, IbcQueryProvider, KeyProvider};
+use std::{str::FromStr, time::Duration};
+
+#[derive(Debug, Clone, Parser)]
+pub struct TxCmd {
+	/// Config path of the chain the transaction is submitted to.
+	#[clap(long)]
+	config: String,
+	#[clap(subcommand)]
+	subcommand: TxSubcommand,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub enum TxSubcommand {
+	#[clap(name = "transfer", about = "Sends an ICS-20 transfer from the relayer account")]
+	Transfer(TransferArgs),
+	#[clap(name = "raw", about = "Submits a protobuf encoded message")]
+	Raw(RawArgs),
+	#[clap(
+		name = "register-counterparty-payee",
+		about = "Registers the counterparty address the ICS-29 receive fees of a channel are paid to"
+	)]
+	RegisterCounterpartyPayee(RegisterCounterpartyPayeeArgs),
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct TransferArgs {
+	/// Channel id the packet is sent on.
+	channel_id: String,
+	/// Port id the packet is sent on.
+	#[clap(long, default_value = "transfer")]
+	port_id: String,
+	/// Recipient address on the counterparty chain.
+	#[clap(long)]
+	receiver: String,
+	/// Denom of the tokens, including its trace path for vouchers.
+	#[clap(long)]
+	denom: String,
+	/// Amount of tokens.
+	#[clap(long)]
+	amount: String,
+	/// Seconds after the latest block of the chain at which the packet times out.
+	#[clap(long, default_value = "600")]
+	timeout_seconds: u64,
+	#[clap(long, default_value = "")]
+	memo: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct RawArgs {
+	/// Type url of the message, e.g. `/ibc.core.channel.v1.MsgChannelCloseInit`.
+	#[clap(long)]
+	type_url: String,
+	/// Hex encoded protobuf of the message.
+	#[clap(long)]
+	value: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct RegisterCounterpartyPayeeArgs {
+	/// Channel id of the fee enabled channel.
+	channel_id: String,
+	/// Port id of the fee enabled channel.
+	#[clap(long, default_value = "transfer")]
+	port_id: String,
+	/// Address on the counterparty chain the fees are paid to.
+	#[clap(long)]
+	counterparty_payee: String,
+}
+
+impl TxCmd {
+	pub async fn run(&self) -> Result<()> {
+		let config: AnyConfig = interpolation::read_config(&self.config).await?;
+		let chain = config.into_client().await?;
+
+		if chain.hosts_pallet_ibc() {
+			// pallet-ibc drops the application messages delivered with the core ones, they're
+			// dispatched by the `transfer` and ICS-29 extrinsics of the pallet instead
+			match &self.subcommand {
+				TxSubcommand::Transfer(_) =>
+					return Err(anyhow!(
+						"{} doesn't accept transfer messages, submit the `transfer` extrinsic of pallet-ibc instead",
+						chain.name()
+					)),
+				TxSubcommand::RegisterCounterpartyPayee(_) =>
+					return Err(anyhow!(
+						"{} doesn't accept ICS-29 messages, submit the `register_counterparty_payee` extrinsic of pallet-ibc instead",
+						chain.name()
+					)),
+				TxSubcommand::Raw(_) => {},
+			}
+		}
+
+		let msg = match &self.subcommand {
+			TxSubcommand::Transfer(args) => {
+				let (_, timestamp) = chain.latest_height_and_timestamp().await?;
+				let timeout_timestamp = (timestamp + Duration::from_secs(args.timeout_seconds))
+					.map_err(|e| anyhow!("Invalid timeout: {e}"))?;
+				MsgTransfer {
+					source_port: PortId::from_str(&args.port_id)?,
+					source_channel: ChannelId::from_str(&args.channel_id)?,
+					token: PrefixedCoin {
+						denom: PrefixedDenom::from_str(&args.denom)?,
+						amount: Amount::from_str(&args.amount)?,
+					},
+					sender: chain.account_id(),
+					receiver: args.receiver.parse()?,
+					timeout_height: Height::zero(),
+					timeout_timestamp,
+					memo: args.memo.clone(),
+				}
+				.to_any()
+			},
+			TxSubcommand::Raw(args) => Any {
+				type_url: args.type_url.clone(),
+				value: hex::decode(args.value.trim_start_matches("0x"))?,
+			},
+			TxSubcommand::RegisterCounterpartyPayee(args) => MsgRegisterCounterpartyPayee {
+				port_id: PortId::from_str(&args.port_id)?,
+				channel_id: ChannelId::from_str(&args.channel_id)?,
+				relayer: chain.account_id(),
+				counterparty_payee: args.counterparty_payee.parse()?,
+			}
+			.to_any(),
+		};
+
+		if queue::is_dry_run() {
+			let cost = chain.estimate_cost(vec![msg.clone()]).await?;
+			queue::log_dry_run(&[msg], cost, &chain);
+			return Ok(())
+		}
+		let tx_id = chain.submit(vec![msg]).await?;
+		println!("Submitted {tx_id:?}");
+		Ok(())
+	}
+}
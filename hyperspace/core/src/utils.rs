@@ -2,25 +2,70 @@ use futures::{Stream, StreamExt};
 use std::{
 	pin::Pin,
 	sync::{Arc, Mutex},
-	task::Poll,
+	task::{Poll, Waker},
+	time::{Duration, Instant},
 };
+use tokio::task::JoinHandle;
+
+struct RecentValue<T> {
+	/// The latest value, until it's consumed
+	value: Option<T>,
+	/// Whether the underlying stream has ended, the latest value is still yielded first
+	ended: bool,
+	/// Number of values that were replaced by a newer one before being consumed
+	coalesced: usize,
+	last_received_at: Instant,
+	waker: Option<Waker>,
+}
 
 /// Keeps the most recent value of a stream and acts as stream itself.
+///
+/// When the consumer falls behind, the values it didn't get to are superseded by the newer ones
+/// instead of being queued, so that work for intermediate values isn't redundantly done.
 pub struct RecentStream<T: Send + 'static> {
-	value: Arc<Mutex<Option<Option<T>>>>,
+	inner: Arc<Mutex<RecentValue<T>>>,
+	/// Task forwarding the values of the underlying stream, aborted once this stream is dropped
+	forwarder: JoinHandle<()>,
 }
 
 impl<T: Send + 'static> RecentStream<T> {
 	pub fn new(mut stream: impl Stream<Item = T> + Send + Unpin + 'static) -> Self {
-		let value = Arc::new(Mutex::new(Some(None)));
-		let value_cloned = value.clone();
-		tokio::spawn(async move {
+		let inner = Arc::new(Mutex::new(RecentValue {
+			value: None,
+			ended: false,
+			coalesced: 0,
+			last_received_at: Instant::now(),
+			waker: None,
+		}));
+		let inner_cloned = inner.clone();
+		let forwarder = tokio::spawn(async move {
 			while let Some(v) = stream.next().await {
-				*value_cloned.lock().unwrap() = Some(Some(v));
+				let mut inner = inner_cloned.lock().unwrap();
+				if inner.value.replace(v).is_some() {
+					inner.coalesced += 1;
+				}
+				inner.last_received_at = Instant::now();
+				if let Some(waker) = inner.waker.take() {
+					waker.wake();
+				}
+			}
+			let mut inner = inner_cloned.lock().unwrap();
+			inner.ended = true;
+			if let Some(waker) = inner.waker.take() {
+				waker.wake();
 			}
-			*value_cloned.lock().unwrap() = None;
 		});
-		Self { value }
+		Self { inner, forwarder }
+	}
+
+	/// Time elapsed since the underlying stream last yielded an item (or since it was created).
+	pub fn idle_for(&self) -> Duration {
+		self.inner.lock().unwrap().last_received_at.elapsed()
+	}
+
+	/// Returns the number of values that were superseded by a newer one since the last call.
+	pub fn take_coalesced(&self) -> usize {
+		std::mem::take(&mut self.inner.lock().unwrap().coalesced)
 	}
 }
 
@@ -32,16 +77,82 @@ impl<T: Send> Stream for RecentStream<T> {
 		cx: &mut std::task::Context<'_>,
 	) -> Poll<Option<Self::Item>> {
 		let this = self.get_mut();
-		let mut value = this.value.lock().unwrap();
-		match value.as_mut() {
-			Some(v) => match v.take() {
-				Some(v) => Poll::Ready(Some(v)),
-				None => {
-					cx.waker().wake_by_ref();
-					Poll::Pending
-				},
+		let mut inner = this.inner.lock().unwrap();
+		match inner.value.take() {
+			Some(v) => Poll::Ready(Some(v)),
+			None if inner.ended => Poll::Ready(None),
+			None => {
+				inner.waker = Some(cx.waker().clone());
+				Poll::Pending
 			},
-			None => Poll::Ready(None),
 		}
 	}
 }
+
+impl<T: Send + 'static> Drop for RecentStream<T> {
+	fn drop(&mut self) {
+		// the underlying stream may stay silent for good, e.g. when it's replaced because it
+		// stalled, so the forwarder can't wait for its next value to notice nobody is listening
+		// anymore
+		self.forwarder.abort();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::channel::mpsc;
+
+	const TIMEOUT: Duration = Duration::from_secs(5);
+
+	#[tokio::test]
+	async fn values_the_consumer_fell_behind_on_are_superseded() {
+		let (tx, rx) = mpsc::unbounded();
+		for v in 1..=3 {
+			tx.unbounded_send(v).unwrap();
+		}
+		drop(tx);
+		let mut stream = RecentStream::new(rx);
+
+		// the latest value is still yielded after the underlying stream ended
+		assert_eq!(stream.next().await, Some(3));
+		assert_eq!(stream.take_coalesced(), 2);
+		assert_eq!(stream.take_coalesced(), 0);
+		assert_eq!(stream.next().await, None);
+	}
+
+	#[tokio::test]
+	async fn waiting_consumer_is_woken_by_new_values_and_the_end_of_the_stream() {
+		let (tx, rx) = mpsc::unbounded();
+		let mut stream = RecentStream::new(rx);
+		let sender = tokio::spawn(async move {
+			for v in 1..=2 {
+				tokio::time::sleep(Duration::from_millis(10)).await;
+				tx.unbounded_send(v).unwrap();
+			}
+		});
+
+		assert_eq!(tokio::time::timeout(TIMEOUT, stream.next()).await.unwrap(), Some(1));
+		assert_eq!(tokio::time::timeout(TIMEOUT, stream.next()).await.unwrap(), Some(2));
+		sender.await.unwrap();
+		assert_eq!(tokio::time::timeout(TIMEOUT, stream.next()).await.unwrap(), None);
+		assert_eq!(stream.take_coalesced(), 0);
+	}
+
+	#[tokio::test]
+	async fn silent_underlying_stream_is_dropped_along_with_the_stream() {
+		let (tx, rx) = mpsc::unbounded::<u64>();
+		let stream = RecentStream::new(rx);
+		tokio::task::yield_now().await;
+		assert!(!tx.is_closed());
+
+		drop(stream);
+		tokio::time::timeout(TIMEOUT, async {
+			while !tx.is_closed() {
+				tokio::task::yield_now().await;
+			}
+		})
+		.await
+		.expect("the underlying stream is dropped");
+	}
+}
@@ -0,0 +1,194 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks that the clients, connections and channels configured on both chains reference each
+//! other. A relayer started with a client or connection id of another path would only find out
+//! when the counterparty rejects its proofs.
+
+use anyhow::anyhow;
+use ibc::{
+	core::{
+		ics02_client::client_state::ClientState as _,
+		ics03_connection::connection::ConnectionEnd,
+		ics04_channel::channel::{ChannelEnd, State},
+	},
+	Height,
+};
+use pallet_ibc::light_clients::AnyClientState;
+use primitives::Chain;
+
+/// Fails with all the mismatches between the configurations of `chain_a` and `chain_b`, if any.
+pub async fn check_wiring(chain_a: &impl Chain, chain_b: &impl Chain) -> Result<(), anyhow::Error> {
+	let problems = query_wiring_problems(chain_a, chain_b).await?;
+	if problems.is_empty() {
+		return Ok(())
+	}
+	Err(anyhow!(
+		"The configurations of {} and {} don't reference each other:\n  {}",
+		chain_a.name(),
+		chain_b.name(),
+		problems.join("\n  ")
+	))
+}
+
+/// Describes every way the client, connection and whitelisted channels of one chain don't match
+/// the ones of the other chain.
+pub async fn query_wiring_problems(
+	chain_a: &impl Chain,
+	chain_b: &impl Chain,
+) -> Result<Vec<String>, anyhow::Error> {
+	let (height_a, _) = chain_a.latest_height_and_timestamp().await?;
+	let (height_b, _) = chain_b.latest_height_and_timestamp().await?;
+	let mut problems = vec![];
+	for ((source, source_height), (sink, sink_height)) in
+		[((chain_a, height_a), (chain_b, height_b)), ((chain_b, height_b), (chain_a, height_a))]
+	{
+		problems.extend(check_client(source, sink, sink_height).await?);
+		problems.extend(check_connection(source, source_height, sink).await?);
+	}
+	problems.extend(check_channels(chain_a, height_a, chain_b, height_b).await?);
+	problems.extend(check_channels(chain_b, height_b, chain_a, height_a).await?);
+	Ok(problems)
+}
+
+/// The client of `source` must exist on `sink` and be of the type `source` is tracked with.
+async fn check_client(
+	source: &impl Chain,
+	sink: &impl Chain,
+	sink_height: Height,
+) -> Result<Option<String>, anyhow::Error> {
+	let client_id = source.client_id();
+	let response = sink.query_client_state(sink_height, client_id.clone()).await?;
+	let Some(client_state) = response.client_state.map(AnyClientState::try_from).transpose()?
+	else {
+		return Ok(Some(format!(
+			"client {client_id} of {} doesn't exist on {}",
+			source.name(),
+			sink.name()
+		)))
+	};
+	if client_state.client_type() != source.client_type() {
+		return Ok(Some(format!(
+			"client {client_id} on {} is a {} client, {} is tracked by a {} client",
+			sink.name(),
+			client_state.client_type(),
+			source.name(),
+			source.client_type()
+		)))
+	}
+	Ok(None)
+}
+
+/// The connection of `source` must be built on the client of `sink` and have the connection and
+/// client of `sink` as its counterparty.
+async fn check_connection(
+	source: &impl Chain,
+	source_height: Height,
+	sink: &impl Chain,
+) -> Result<Vec<String>, anyhow::Error> {
+	let Some(connection_id) = source.connection_id() else { return Ok(vec![]) };
+	let response = source.query_connection_end(source_height, connection_id.clone()).await?;
+	let Some(connection_end) = response.connection.map(ConnectionEnd::try_from).transpose()? else {
+		return Ok(vec![format!("connection {connection_id} doesn't exist on {}", source.name())])
+	};
+
+	let mut problems = vec![];
+	if *connection_end.client_id() != sink.client_id() {
+		problems.push(format!(
+			"connection {connection_id} on {} uses client {}, {} is configured with client {}",
+			source.name(),
+			connection_end.client_id(),
+			sink.name(),
+			sink.client_id()
+		));
+	}
+	let counterparty = connection_end.counterparty();
+	if *counterparty.client_id() != source.client_id() {
+		problems.push(format!(
+			"connection {connection_id} on {} has client {} on {} as its counterparty, {} is configured with client {}",
+			source.name(),
+			counterparty.client_id(),
+			sink.name(),
+			source.name(),
+			source.client_id()
+		));
+	}
+	if let (Some(counterparty_connection_id), Some(sink_connection_id)) =
+		(counterparty.connection_id(), sink.connection_id())
+	{
+		if *counterparty_connection_id != sink_connection_id {
+			problems.push(format!(
+				"connection {connection_id} on {} has connection {counterparty_connection_id} on {} as its counterparty, {} is configured with connection {sink_connection_id}",
+				source.name(),
+				sink.name(),
+				sink.name()
+			));
+		}
+	}
+	Ok(problems)
+}
+
+/// The whitelisted channels of `source` must be on its connection and, once open on both ends,
+/// have channel ends on `sink` that point back to them.
+async fn check_channels(
+	source: &impl Chain,
+	source_height: Height,
+	sink: &impl Chain,
+	sink_height: Height,
+) -> Result<Vec<String>, anyhow::Error> {
+	let mut problems = vec![];
+	for (channel_id, port_id) in source.channel_whitelist() {
+		let response = source.query_channel_end(source_height, channel_id, port_id.clone()).await?;
+		let Some(channel_end) = response.channel.map(ChannelEnd::try_from).transpose()? else {
+			problems.push(format!(
+				"whitelisted channel {channel_id}/{port_id} doesn't exist on {}",
+				source.name()
+			));
+			continue
+		};
+		if let Some(connection_id) = source.connection_id() {
+			if channel_end.connection_hops.first() != Some(&connection_id) {
+				problems.push(format!(
+					"channel {channel_id}/{port_id} on {} isn't on connection {connection_id}",
+					source.name()
+				));
+			}
+		}
+
+		let counterparty = channel_end.counterparty();
+		let Some(counterparty_channel_id) = counterparty.channel_id else { continue };
+		let counterparty_port_id = counterparty.port_id.clone();
+		let response = sink
+			.query_channel_end(sink_height, counterparty_channel_id, counterparty_port_id.clone())
+			.await?;
+		let Some(counterparty_end) = response.channel.map(ChannelEnd::try_from).transpose()? else {
+			problems.push(format!(
+				"channel {channel_id}/{port_id} on {} has {counterparty_channel_id}/{counterparty_port_id} on {} as its counterparty, which doesn't exist",
+				source.name(),
+				sink.name()
+			));
+			continue
+		};
+		let points_back = counterparty_end.counterparty().port_id == port_id &&
+			counterparty_end.counterparty().channel_id == Some(channel_id);
+		if counterparty_end.state != State::Init && !points_back {
+			problems.push(format!(
+				"channel {counterparty_channel_id}/{counterparty_port_id} on {} isn't the counterparty of {channel_id}/{port_id} on {}",
+				sink.name(),
+				source.name()
+			));
+		}
+	}
+	Ok(problems)
+}
@@ -1,5 +1,12 @@
-use super::{client::CosmosClient, tx::sign_tx};
-use crate::{error::Error, events::client_extract_attributes_from_tx, provider::FinalityEvent};
+use super::{
+	client::CosmosClient,
+	tx::{sign_tx, simulate_tx},
+};
+use crate::{
+	error::Error,
+	events::{client_extract_attributes_from_tx, ibc_event_try_from_abci_event},
+	provider::FinalityEvent,
+};
 use futures::{Stream, StreamExt};
 use ibc::{
 	core::{
@@ -12,24 +19,34 @@ use ibc::{
 };
 use ibc_proto::{
 	cosmos::{
-		base::v1beta1::Coin,
+		base::{
+			tendermint::v1beta1::{
+				service_client::ServiceClient as TendermintServiceClient, GetNodeInfoRequest,
+			},
+			v1beta1::Coin,
+		},
 		tx::v1beta1::{service_client::ServiceClient, Fee, GetTxsEventRequest, OrderBy},
 	},
 	google::protobuf::Any,
 };
+use ics08_wasm::msg::MsgPushNewWasmCode;
 use pallet_ibc::light_clients::AnyClientMessage;
 use primitives::{
-	mock::LocalClientTypes, Chain, CommonClientState, IbcProvider, LightClientSync,
+	cost::{TxBudget, TxCost},
+	mock::LocalClientTypes,
+	Chain, CommonClientState, IbcEventProvider, IbcQueryProvider, KeyProvider, LightClientSync,
 	MisbehaviourHandler,
 };
-use prost::Message;
 use std::{pin::Pin, time::Duration};
 use tendermint_rpc::{
 	event::{Event, EventData},
 	query::{EventType, Query},
-	SubscriptionClient, WebSocketClient,
+	Client, SubscriptionClient, WebSocketClient,
 };
 
+/// Margin added to the gas used by the simulation of a transaction when estimating its cost.
+pub const SIMULATED_GAS_MARGIN_PERCENT: u64 = 10;
+
 #[async_trait::async_trait]
 impl<H> LightClientSync for CosmosClient<H>
 where
@@ -56,44 +73,36 @@ where
 		&self.name
 	}
 
-	fn block_max_weight(&self) -> u64 {
-		self.max_tx_size as u64
+	fn chain_id(&self) -> ChainId {
+		self.chain_id.clone()
 	}
 
-	async fn estimate_weight(&self, messages: Vec<Any>) -> Result<u64, Self::Error> {
-		let account_info = self.query_account().await?;
-		let fee = self.get_fee();
-		let (_, tx_raw, _) =
-			sign_tx(self.keybase.clone(), self.chain_id.clone(), &account_info, vec![], fee)?;
-
-		let body_bytes_len = tx_raw.body_bytes.len();
-		// Full length of the transaction can then be derived from the length of the invariable
-		// envelope and the length of the body field, taking into account the varint encoding
-		// of the body field's length delimiter.
-		#[allow(unused)]
-		fn tx_len(envelope_len: usize, body_len: usize) -> usize {
-			// The caller has at least one message field length added to the body's
-			debug_assert!(body_len != 0);
-			envelope_len + 1 + prost::length_delimiter_len(body_len) + body_len
-		}
-
-		let mut current_len = body_bytes_len;
+	/// Transactions are signed with `gas_limit`, the gas of a batch mustn't exceed it.
+	fn tx_budget(&self) -> TxBudget {
+		TxBudget::gas(self.gas_limit)
+	}
 
-		for message in messages {
-			let message_len = message.encoded_len();
+	fn max_transaction_size(&self) -> Option<usize> {
+		Some(self.max_tx_size)
+	}
 
-			// The total length the message adds to the encoding includes the
-			// field tag (small varint) and the length delimiter.
-			let tagged_len = 1 + prost::length_delimiter_len(message_len) + message_len;
-			current_len += tagged_len;
-		}
-		Ok(current_len as u64)
+	async fn estimate_cost(&self, messages: Vec<Any>) -> Result<TxCost, Self::Error> {
+		let account_info = self.query_account().await?;
+		let (tx, _, tx_bytes) =
+			sign_tx(&*self.signer, self.chain_id.clone(), &account_info, messages, self.get_fee())?;
+		let response = simulate_tx(self.grpc_url.clone(), tx, tx_bytes).await?;
+		let gas_used = response
+			.gas_info
+			.ok_or_else(|| Error::from("Simulation returned no gas info".to_string()))?
+			.gas_used;
+		// the gas used by the execution can differ slightly from the simulation
+		Ok(TxCost::gas(gas_used.saturating_mul(100 + SIMULATED_GAS_MARGIN_PERCENT) / 100))
 	}
 
 	async fn finality_notifications(
 		&self,
 	) -> Result<
-		Pin<Box<dyn Stream<Item = <Self as IbcProvider>::FinalityEvent> + Send + Sync>>,
+		Pin<Box<dyn Stream<Item = <Self as IbcEventProvider>::FinalityEvent> + Send + Sync>>,
 		Error,
 	> {
 		let ws_client = self.rpc_client.clone();
@@ -137,6 +146,62 @@ where
 		Ok(Self::TransactionId { hash })
 	}
 
+	async fn submit_and_wait_for_events(&self, messages: Vec<Any>) -> Result<Vec<IbcEvent>, Error> {
+		let tx_id = self.submit(messages).await?;
+		let response = self.wait_for_tx_result(tx_id).await?;
+		let height = Height::new(
+			ChainId::chain_version(self.chain_id.to_string().as_str()),
+			response.height.value(),
+		);
+		Ok(response
+			.tx_result
+			.events
+			.iter()
+			.filter_map(|e| ibc_event_try_from_abci_event(e, height).ok())
+			.collect())
+	}
+
+	async fn upload_wasm(&self, wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
+		let msg = MsgPushNewWasmCode { signer: self.account_id(), code: wasm };
+		let hash = self.submit(vec![msg.into()]).await?;
+		let resp = self.wait_for_tx_result(hash).await?;
+		let height = Height::new(
+			ChainId::chain_version(self.chain_id.to_string().as_str()),
+			resp.height.value(),
+		);
+		let deliver_tx_result = resp.tx_result;
+		let mut result = deliver_tx_result
+			.events
+			.iter()
+			.flat_map(|e| ibc_event_try_from_abci_event(e, height).ok().into_iter())
+			.filter(|e| matches!(e, IbcEvent::PushWasmCode(_)))
+			.collect::<Vec<_>>();
+		let code_hash = if result.len() != 1 {
+			return Err(Error::from(format!(
+				"Expected exactly one PushWasmCode event, found {}",
+				result.len()
+			)))
+		} else {
+			match result.pop().unwrap() {
+				IbcEvent::PushWasmCode(ev) => ev.0,
+				_ => unreachable!(),
+			}
+		};
+		// let resp = MsgClient::connect(
+		// 	Endpoint::try_from(self.grpc_url.to_string())
+		// 		.map_err(|e| Error::from(format!("Failed to parse grpc url: {:?}", e)))?,
+		// )
+		// .await
+		// .map_err(|e| Error::from(format!("Failed to connect to grpc endpoint: {:?}", e)))?
+		// .push_new_wasm_code(msg)
+		// .await
+		// .map_err(|e| {
+		// 	Error::from(format!("Failed to upload wasm code to grpc endpoint: {:?}", e))
+		// })?;
+
+		Ok(code_hash)
+	}
+
 	async fn query_client_message(
 		&self,
 		update: UpdateClient,
@@ -260,6 +325,31 @@ where
 		&mut self.common_state
 	}
 
+	async fn is_node_syncing(&self) -> Result<Option<bool>, Self::Error> {
+		let status =
+			self.rpc_client.status().await.map_err(|e| Error::RpcError(format!("{e:?}")))?;
+		Ok(Some(status.sync_info.catching_up))
+	}
+
+	async fn ibc_module_version(&self) -> Result<Option<String>, Self::Error> {
+		let mut client = TendermintServiceClient::connect(self.grpc_url.to_string())
+			.await
+			.map_err(|e| Error::from(e.to_string()))?;
+		let node_info = client
+			.get_node_info(GetNodeInfoRequest {})
+			.await
+			.map_err(|e| Error::from(e.to_string()))?
+			.into_inner();
+		// the ibc-go version is only known from the build dependencies of the node
+		Ok(node_info.application_version.and_then(|version| {
+			version
+				.build_deps
+				.into_iter()
+				.find(|module| module.path.starts_with("github.com/cosmos/ibc-go"))
+				.map(|module| format!("ibc-go {}", module.version))
+		}))
+	}
+
 	async fn reconnect(&mut self) -> anyhow::Result<()> {
 		let (rpc_client, ws_driver) = WebSocketClient::new(self.websocket_url.clone())
 			.await
@@ -280,7 +370,7 @@ where
 			amount: vec![Coin { denom: self.fee_denom.clone(), amount: self.fee_amount.clone() }],
 			gas_limit: self.gas_limit,
 			payer: "".to_string(),
-			granter: "".to_string(),
+			granter: self.fee_granter.clone().unwrap_or_default(),
 		}
 	}
 
@@ -27,6 +27,10 @@ use ics07_tendermint::{
 };
 use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState, HostFunctionsManager};
 use primitives::{
+	address::AddressFormat,
+	keystore::{resolve_secret, KeystoreConfig},
+	signing::{ChainSigner, LocalSigner, SignatureScheme},
+	vault::VaultConfig,
 	Chain, CommonClientConfig, CommonClientState, IbcProvider, KeyProvider, UpdateType,
 };
 use prost::Message;
@@ -36,6 +40,7 @@ use ripemd::Ripemd160;
 use serde::{Deserialize, Serialize};
 use std::{
 	collections::HashSet,
+	path::PathBuf,
 	str::FromStr,
 	sync::{Arc, Mutex},
 	time::Duration,
@@ -53,6 +58,9 @@ use tokio::{
 const DEFAULT_FEE_DENOM: &str = "stake";
 const DEFAULT_FEE_AMOUNT: &str = "4000";
 const DEFAULT_GAS_LIMIT: u64 = (i64::MAX - 1) as u64;
+const DEFAULT_TRUSTING_PERIOD_SECS: u64 = 64000;
+const DEFAULT_UNBONDING_PERIOD_SECS: u64 = 1814400;
+const DEFAULT_MAX_CLOCK_DRIFT_SECS: u64 = 15;
 
 fn default_gas_limit() -> u64 {
 	DEFAULT_GAS_LIMIT
@@ -66,6 +74,18 @@ fn default_fee_amount() -> String {
 	DEFAULT_FEE_AMOUNT.to_string()
 }
 
+fn default_trusting_period() -> u64 {
+	DEFAULT_TRUSTING_PERIOD_SECS
+}
+
+fn default_unbonding_period() -> u64 {
+	DEFAULT_UNBONDING_PERIOD_SECS
+}
+
+fn default_max_clock_drift() -> u64 {
+	DEFAULT_MAX_CLOCK_DRIFT_SECS
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ConfigKeyEntry {
 	pub public_key: String,
@@ -153,18 +173,30 @@ pub struct CosmosClient<H> {
 	pub light_client: LightClient,
 	/// The key that signs transactions
 	pub keybase: KeyEntry,
+	/// Signs transactions with the key of `keybase`
+	pub signer: Arc<dyn ChainSigner>,
 	/// Account prefix
 	pub account_prefix: String,
+	/// Encoding of the relayer address in submitted messages
+	pub address_format: AddressFormat,
 	/// Reference to commitment
 	pub commitment_prefix: CommitmentPrefix,
 	/// Fee denom
 	pub fee_denom: String,
 	/// Fee amount
 	pub fee_amount: String,
+	/// Account paying the fees through a fee grant
+	pub fee_granter: Option<String>,
 	/// Fee amount
 	pub gas_limit: u64,
 	/// Maximun transaction size
 	pub max_tx_size: usize,
+	/// Trusting period of the light clients created for this chain
+	pub trusting_period: Duration,
+	/// Unbonding period of this chain
+	pub unbonding_period: Duration,
+	/// Clock drift tolerated by the light clients created for this chain
+	pub max_clock_drift: Duration,
 	/// Finality protocol to use, eg Tenderminet
 	pub _phantom: std::marker::PhantomData<H>,
 	/// Mutex used to sequentially send transactions. This is necessary because
@@ -197,12 +229,20 @@ pub struct CosmosClientConfig {
 	pub connection_id: Option<ConnectionId>,
 	/// Account prefix
 	pub account_prefix: String,
+	/// Encoding of the relayer address in submitted messages, defaults to bech32 with
+	/// `account_prefix`
+	#[serde(default)]
+	pub address_format: Option<AddressFormat>,
 	/// Fee denom
 	#[serde(default = "default_fee_denom")]
 	pub fee_denom: String,
 	/// Fee amount
 	#[serde(default = "default_fee_amount")]
 	pub fee_amount: String,
+	/// Account that pays the fees through a fee grant (`x/feegrant`) to the relayer account. IBC
+	/// messages are still signed by the relayer.
+	#[serde(default)]
+	pub fee_granter: Option<String>,
 	/// Fee amount
 	#[serde(default = "default_gas_limit")]
 	pub gas_limit: u64,
@@ -210,6 +250,17 @@ pub struct CosmosClientConfig {
 	pub store_prefix: String,
 	/// Maximun transaction size
 	pub max_tx_size: usize,
+	/// Trusting period in seconds of the light client created for this chain on the
+	/// counterparty, must be shorter than the unbonding period
+	#[serde(default = "default_trusting_period")]
+	pub trusting_period_secs: u64,
+	/// Unbonding period of this chain in seconds
+	#[serde(default = "default_unbonding_period")]
+	pub unbonding_period_secs: u64,
+	/// Clock drift in seconds tolerated by the light client created for this chain on the
+	/// counterparty
+	#[serde(default = "default_max_clock_drift")]
+	pub max_clock_drift_secs: u64,
 	/// All the client states and headers will be wrapped in WASM ones using the WASM code ID.
 	#[serde(default)]
 	pub wasm_code_hash: Option<String>,
@@ -222,7 +273,6 @@ pub struct CosmosClientConfig {
 	pub default_gas: Option<u64>,	  			// TODO: Could be set to `0` by default
 	pub max_gas: Option<u64>,                   // TODO: DEFAULT_MAX_GAS: u64 = 400_000
 	pub gas_multiplier: Option<GasMultiplier>,  // TODO: Could be set to `1.1` by default
-	pub fee_granter: Option<String>,            // TODO: DEFAULT_FEE_GRANTER: &str = ""
 	pub max_msg_num: MaxMsgNum,                 // TODO: Default is 30, Could be set usize = 1 for test
 												// TODO: Could be set to const MAX_LEN: usize = 50;
 	pub proof_specs: Option<ProofSpecs>,        // TODO: Could be set to None
@@ -235,8 +285,15 @@ pub struct CosmosClientConfig {
 	*/
 	/// Whitelisted channels
 	pub channel_whitelist: Vec<(ChannelId, PortId)>,
-	/// The key that signs transactions
+	/// The key that signs transactions, can be left empty when `keystore` is set
+	#[serde(default)]
 	pub mnemonic: String,
+	/// Encrypted keystore file holding the mnemonic, see [`primitives::keystore`]
+	#[serde(default)]
+	pub keystore: Option<PathBuf>,
+	/// Read the mnemonic from Vault's KV engine
+	#[serde(default)]
+	pub vault: Option<VaultConfig>,
 	/// Common client config
 	#[serde(flatten)]
 	pub common: CommonClientConfig,
@@ -244,6 +301,16 @@ pub struct CosmosClientConfig {
 	pub skip_tokens_list: Option<Vec<String>>,
 }
 
+impl KeystoreConfig for CosmosClientConfig {
+	fn raw_secret_mut(&mut self) -> &mut String {
+		&mut self.mnemonic
+	}
+
+	fn keystore_mut(&mut self) -> &mut Option<PathBuf> {
+		&mut self.keystore
+	}
+}
+
 impl<H> CosmosClient<H>
 where
 	Self: KeyProvider,
@@ -269,10 +336,18 @@ where
 		let commitment_prefix = CommitmentPrefix::try_from(config.store_prefix.as_bytes().to_vec())
 			.map_err(|e| Error::from(format!("Invalid store prefix {:?}", e)))?;
 
-		let keybase: KeyEntry = KeyEntry::try_from(MnemonicEntry {
-			mnemonic: config.mnemonic,
-			prefix: config.account_prefix.clone(),
-		})
+		let mnemonic = match &config.vault {
+			Some(vault) => vault.read_secret().await,
+			None => resolve_secret(&config.mnemonic, config.keystore.as_deref()),
+		}
+		.map_err(|e| e.to_string())?;
+		let keybase: KeyEntry =
+			KeyEntry::try_from(MnemonicEntry { mnemonic, prefix: config.account_prefix.clone() })
+				.map_err(|e| e.to_string())?;
+		let signer = LocalSigner::from_secret_key(
+			SignatureScheme::Secp256k1,
+			&keybase.private_key.private_key().to_bytes(),
+		)
 		.map_err(|e| e.to_string())?;
 
 		let rpc_call_delay = Duration::from_millis(1000);
@@ -289,13 +364,21 @@ where
 			connection_id: Arc::new(Mutex::new(config.connection_id)),
 			channel_whitelist: Arc::new(Mutex::new(config.channel_whitelist.into_iter().collect())),
 			light_client,
+			address_format: config
+				.address_format
+				.unwrap_or_else(|| AddressFormat::Bech32 { hrp: config.account_prefix.clone() }),
 			account_prefix: config.account_prefix,
 			commitment_prefix,
 			fee_denom: config.fee_denom,
 			fee_amount: config.fee_amount,
+			fee_granter: config.fee_granter,
 			gas_limit: config.gas_limit,
 			max_tx_size: config.max_tx_size,
+			trusting_period: Duration::from_secs(config.trusting_period_secs),
+			unbonding_period: Duration::from_secs(config.unbonding_period_secs),
+			max_clock_drift: Duration::from_secs(config.max_clock_drift_secs),
 			keybase,
+			signer: Arc::new(signer),
 			_phantom: std::marker::PhantomData,
 			tx_mutex: Default::default(),
 			light_block_cache: Arc::new(Cache::new(100000)),
@@ -309,6 +392,10 @@ where
 				skip_tokens_list: config
 					.skip_tokens_list
 					.unwrap_or_else(|| vec!["uosmo".to_string()]),
+				channel_strategies: Default::default(),
+				verify_events: config.common.verify_events,
+				unproven_events: Default::default(),
+				undelivered_sequences_hook: None,
 			},
 			join_handles: Arc::new(TokioMutex::new(vec![ws_driver_jh])),
 		})
@@ -359,13 +446,8 @@ where
 		let account_info = self.query_account().await?;
 
 		// Sign transaction
-		let (tx, _, tx_bytes) = sign_tx(
-			self.keybase.clone(),
-			self.chain_id.clone(),
-			&account_info,
-			messages,
-			self.get_fee(),
-		)?;
+		let (tx, _, tx_bytes) =
+			sign_tx(&*self.signer, self.chain_id.clone(), &account_info, messages, self.get_fee())?;
 
 		// Simulate transaction
 		let res = simulate_tx(self.grpc_url.clone(), tx, tx_bytes.clone()).await?;
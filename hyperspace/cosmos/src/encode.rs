@@ -1,4 +1,3 @@
-use super::key_provider::KeyEntry;
 use crate::error::Error;
 use ibc::core::ics24_host::identifier::ChainId;
 use ibc_proto::{
@@ -8,18 +7,23 @@ use ibc_proto::{
 	},
 	google::protobuf::Any,
 };
-use k256::ecdsa::{signature::Signer as _, Signature, SigningKey};
+use primitives::signing::{ChainSigner, PayloadFormat, PublicKey, SignatureScheme};
 use prost::Message;
 
-pub fn encode_key_bytes(key: &KeyEntry) -> Result<Vec<u8>, Error> {
+pub fn encode_key_bytes(public_key: &PublicKey) -> Result<Vec<u8>, Error> {
 	let mut pk_buf = Vec::new();
-	Message::encode(&key.public_key.public_key().to_bytes().as_slice().to_vec(), &mut pk_buf)
-		.map_err(|e| Error::from(e.to_string()))?;
+	Message::encode(&public_key.bytes, &mut pk_buf).map_err(|e| Error::from(e.to_string()))?;
 	Ok(pk_buf)
 }
 
-pub fn encode_signer_info(sequence: u64, key_bytes: Vec<u8>) -> Result<SignerInfo, Error> {
-	let pk_any = Any { type_url: "/cosmos.crypto.secp256k1.PubKey".to_string(), value: key_bytes };
+pub fn encode_signer_info(sequence: u64, public_key: &PublicKey) -> Result<SignerInfo, Error> {
+	let type_url = match public_key.scheme {
+		SignatureScheme::Secp256k1 => "/cosmos.crypto.secp256k1.PubKey",
+		SignatureScheme::Ed25519 => "/cosmos.crypto.ed25519.PubKey",
+		SignatureScheme::Sr25519 =>
+			return Err(Error::from("sr25519 keys can't sign cosmos transactions".to_string())),
+	};
+	let pk_any = Any { type_url: type_url.to_string(), value: encode_key_bytes(public_key)? };
 	let single = Single { mode: 1 };
 	let sum_single = Some(Sum::Single(single));
 	let mode = Some(ModeInfo { sum: sum_single });
@@ -36,7 +40,7 @@ pub fn encode_auth_info(signer_info: SignerInfo, fee: Fee) -> Result<(AuthInfo,
 }
 
 pub fn encode_sign_doc(
-	key: KeyEntry,
+	signer: &dyn ChainSigner,
 	body_bytes: Vec<u8>,
 	auth_info_bytes: Vec<u8>,
 	chain_id: ChainId,
@@ -49,12 +53,11 @@ pub fn encode_sign_doc(
 	let mut signdoc_buf = Vec::new();
 	Message::encode(&sign_doc, &mut signdoc_buf)?;
 
-	// Create signature
-	let private_key_bytes = key.private_key.private_key().to_bytes();
-	let signing_key = SigningKey::from_bytes(private_key_bytes.as_slice())
+	// Create signature, cosmos doesn't use the recovery id of secp256k1 signatures
+	let mut signature_bytes = signer
+		.sign(&signdoc_buf, PayloadFormat::Raw)
 		.map_err(|e| Error::from(e.to_string()))?;
-	let signature: Signature = signing_key.sign(&signdoc_buf);
-	let signature_bytes = signature.as_ref().to_vec();
+	signature_bytes.truncate(64);
 
 	Ok(signature_bytes)
 }
@@ -15,6 +15,7 @@ use ibc::{
 			events::{self as connection_events, Attributes as ConnectionAttributes},
 		},
 		ics04_channel::{
+			channel::State as ChannelState,
 			error::Error as ChannelError,
 			events::{self as channel_events, Attributes as ChannelAttributes},
 			packet::Packet,
@@ -86,7 +87,8 @@ pub fn event_is_type_channel(ev: &IbcEvent) -> bool {
 			IbcEvent::WriteAcknowledgement(_) |
 			IbcEvent::AcknowledgePacket(_) |
 			IbcEvent::TimeoutPacket(_) |
-			IbcEvent::TimeoutOnClosePacket(_)
+			IbcEvent::TimeoutOnClosePacket(_) |
+			IbcEvent::UpgradeChannel(_)
 	)
 }
 
@@ -166,6 +168,11 @@ pub fn ibc_event_try_from_abci_event(
 			timeout_packet_try_from_abci_event(abci_event, height)
 				.map_err(IbcEventError::channel)?,
 		)),
+		Ok(event_type) if channel_events::UpgradeStep::from_event_type(event_type).is_some() =>
+			Ok(IbcEvent::UpgradeChannel(
+				channel_upgrade_try_from_abci_event(abci_event, height)
+					.map_err(IbcEventError::channel)?,
+			)),
 		_ => {
 			// log::debug!("IBC event type not recognized: {}", abci_event.kind);
 			Err(IbcEventError::unsupported_abci_event(abci_event.kind.to_owned()))
@@ -375,6 +382,55 @@ pub fn timeout_packet_try_from_abci_event(
 		.map_err(|_| ChannelError::abci_conversion_failed(abci_event.kind.to_owned()))
 }
 
+pub fn channel_upgrade_try_from_abci_event(
+	abci_event: &AbciEvent,
+	height: Height,
+) -> Result<channel_events::Upgrade, ChannelError> {
+	let step = abci_event
+		.kind
+		.parse::<IbcEventType>()
+		.ok()
+		.as_ref()
+		.and_then(channel_events::UpgradeStep::from_event_type)
+		.ok_or_else(|| ChannelError::abci_conversion_failed(abci_event.kind.clone()))?;
+	let mut upgrade = channel_events::Upgrade {
+		height,
+		step,
+		port_id: Default::default(),
+		channel_id: Default::default(),
+		counterparty_port_id: Default::default(),
+		counterparty_channel_id: None,
+		upgrade_sequence: 0,
+		channel_state: ChannelState::Open,
+	};
+
+	for tag in &abci_event.attributes {
+		let key = tag.key.as_str();
+		let value = tag.value.as_str();
+		match key {
+			channel_events::PORT_ID_ATTRIBUTE_KEY =>
+				upgrade.port_id = value.parse().map_err(ChannelError::identifier)?,
+			channel_events::CHANNEL_ID_ATTRIBUTE_KEY =>
+				upgrade.channel_id = value.parse().map_err(ChannelError::identifier)?,
+			channel_events::COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY => {
+				upgrade.counterparty_port_id = value.parse().map_err(ChannelError::identifier)?;
+			},
+			channel_events::COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY => {
+				upgrade.counterparty_channel_id = value.parse().ok();
+			},
+			channel_events::UPGRADE_SEQUENCE_ATTRIBUTE_KEY => {
+				upgrade.upgrade_sequence = value
+					.parse()
+					.map_err(|_| ChannelError::abci_conversion_failed(abci_event.kind.clone()))?;
+			},
+			channel_events::CHANNEL_STATE_ATTRIBUTE_KEY => upgrade.channel_state = value.parse()?,
+			_ => {},
+		}
+	}
+
+	Ok(upgrade)
+}
+
 pub fn client_extract_attributes_from_tx(
 	event: &AbciEvent,
 	height: Height,
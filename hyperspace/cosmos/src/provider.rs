@@ -11,7 +11,11 @@ use futures::{
 	Stream, StreamExt,
 };
 use ibc::{
-	applications::transfer::{Amount, BaseDenom, PrefixedCoin, PrefixedDenom, TracePath},
+	applications::{
+		cross_chain_queries::query::CrossChainQuery,
+		fee::PacketFee,
+		transfer::{Amount, BaseDenom, PrefixedCoin, PrefixedDenom, TracePath},
+	},
 	core::{
 		ics02_client::{
 			client_state::ClientType, events as ClientEvents,
@@ -37,32 +41,47 @@ use ibc_primitives::PacketInfo as IbcPacketInfo;
 use ibc_proto::{
 	cosmos::{bank::v1beta1::QueryBalanceRequest, base::query::v1beta1::PageRequest},
 	google::protobuf::Any,
-	ibc::core::{
-		channel::v1::{
-			Channel, QueryChannelResponse, QueryChannelsRequest, QueryChannelsResponse,
-			QueryConnectionChannelsRequest, QueryNextSequenceReceiveResponse,
-			QueryPacketAcknowledgementResponse, QueryPacketAcknowledgementsRequest,
-			QueryPacketCommitmentResponse, QueryPacketCommitmentsRequest,
-			QueryPacketReceiptResponse, QueryUnreceivedAcksRequest, QueryUnreceivedPacketsRequest,
-		},
-		client::v1::{
-			QueryClientStateResponse, QueryClientStatesRequest, QueryConsensusStateResponse,
+	ibc::{
+		applications::{
+			fee::v1::{
+				query_client::QueryClient as FeeQueryClient, QueryIncentivizedPacketRequest,
+			},
+			transfer::v1::{
+				query_client::QueryClient as TransferQueryClient, QueryDenomHashRequest,
+				QueryDenomTraceRequest, QueryDenomTraceResponse, QueryDenomTracesRequest,
+				QueryDenomTracesResponse,
+			},
 		},
-		connection::v1::{
-			ConnectionEnd, IdentifiedConnection, QueryConnectionResponse, QueryConnectionsRequest,
+		core::{
+			channel::v1::{
+				Channel, PacketId as RawPacketId, QueryChannelResponse, QueryChannelsRequest,
+				QueryChannelsResponse, QueryConnectionChannelsRequest,
+				QueryNextSequenceReceiveResponse, QueryPacketAcknowledgementResponse,
+				QueryPacketAcknowledgementsRequest, QueryPacketCommitmentResponse,
+				QueryPacketCommitmentsRequest, QueryPacketReceiptResponse,
+				QueryUnreceivedAcksRequest, QueryUnreceivedPacketsRequest,
+			},
+			client::v1::{
+				QueryClientStateResponse, QueryClientStatesRequest, QueryConsensusStateResponse,
+			},
+			connection::v1::{
+				ConnectionEnd, IdentifiedConnection, Params as ConnectionParams,
+				QueryConnectionParamsRequest, QueryConnectionResponse, QueryConnectionsRequest,
+				QueryConnectionsResponse,
+			},
 		},
 	},
 };
-use ibc_rpc::PacketInfo;
+use ibc_rpc::{PacketEventsFilter, PacketInfo};
 use ics07_tendermint::{
 	client_message::ClientMessage, client_state::ClientState, consensus_state::ConsensusState,
 };
-use ics08_wasm::msg::MsgPushNewWasmCode;
 use pallet_ibc::light_clients::{
 	AnyClientMessage, AnyClientState, AnyConsensusState, HostFunctionsManager,
 };
 use primitives::{
-	filter_events_by_ids, mock::LocalClientTypes, Chain, IbcProvider, KeyProvider, UpdateType,
+	filter_events_by_ids, mock::LocalClientTypes, next_page_key, Chain, IbcEventProvider,
+	IbcQueryProvider, KeyProvider, Page, ProofProvider, UpdateType,
 };
 use prost::Message;
 use rand::Rng;
@@ -95,192 +114,13 @@ pub struct TransactionId<Hash> {
 }
 
 #[async_trait::async_trait]
-impl<H> IbcProvider for CosmosClient<H>
+impl<H> IbcQueryProvider for CosmosClient<H>
 where
 	H: Clone + Send + Sync + 'static,
 {
-	type FinalityEvent = FinalityEvent;
-	type TransactionId = TransactionId<Hash>;
 	type AssetId = String;
-	type Error = Error;
-
-	async fn query_latest_ibc_events<C>(
-		&mut self,
-		finality_event: Self::FinalityEvent,
-		counterparty: &C,
-	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
-	where
-		C: Chain,
-	{
-		let finality_event_height = match finality_event {
-			FinalityEvent::Tendermint { from: _, to } => to,
-		};
-		let client_id = self.client_id();
-		let latest_cp_height = counterparty.latest_height_and_timestamp().await?.0;
-		let latest_cp_client_state =
-			counterparty.query_client_state(latest_cp_height, client_id.clone()).await?;
-		let client_state_response = latest_cp_client_state
-			.client_state
-			.ok_or_else(|| Error::Custom("counterparty returned empty client state".to_string()))?;
-		let client_state =
-			ClientState::<HostFunctionsManager>::decode_vec(&client_state_response.value)
-				.map_err(|_| Error::Custom("failed to decode client state response".to_string()))?;
-		let latest_cp_client_height = client_state.latest_height().revision_height;
-		let latest_height = self.latest_height_and_timestamp().await?.0;
-		let latest_revision = latest_height.revision_number;
-
-		let from = TmHeight::try_from(latest_cp_client_height).unwrap();
-		let to = finality_event_height.min(
-			TmHeight::try_from(latest_cp_client_height + NUMBER_OF_BLOCKS_TO_PROCESS_PER_ITER)
-				.expect("should not overflow"),
-		);
-		log::info!(target: "hyperspace_cosmos", "Getting blocks {}..{}", from, to);
-
-		// query (exclusively) up to `to`, because the proof for the event at `to - 1` will be
-		// contained at `to` and will be fetched below by `msg_update_client_header`
-		let update_headers =
-			self.msg_update_client_header(from, to, client_state.latest_height).await?;
-		let mut block_events = Vec::new();
-		block_events.push((0, Vec::new()));
-		let mut join_set: JoinSet<Result<_, anyhow::Error>> = JoinSet::new();
-		let range = (from.value()..to.value()).collect::<Vec<_>>();
-		let to = self.rpc_call_delay().as_millis();
-		for heights in range.chunks(100) {
-			for height in heights.iter().copied() {
-				log::trace!(target: "hyperspace_cosmos", "Parsing events at height {:?}", height);
-				let client = self.clone();
-				let duration = Duration::from_millis(rand::thread_rng().gen_range(0..to) as u64);
-				let counterparty = counterparty.clone();
-				join_set.spawn(async move {
-					sleep(duration).await;
-					let xs = tokio::time::timeout(
-						Duration::from_secs(30),
-						client.parse_ibc_events_at(&counterparty, latest_revision, height),
-					)
-					.await??;
-					Ok((height, xs))
-				});
-			}
-			while let Some(res) = join_set.join_next().await {
-				let out = res??;
-				block_events.push(out);
-			}
-		}
-
-		if block_events.len() != update_headers.len() {
-			return Err(anyhow::anyhow!(
-				"block events and updates must match, got {} and {}",
-				block_events.len(),
-				update_headers.len()
-			))
-		}
-		block_events.sort_by_key(|(height, _)| *height);
-
-		let mut updates = Vec::new();
-		for (events, (update_header, update_type)) in
-			block_events.into_iter().map(|(_, events)| events).zip(update_headers)
-		{
-			let height = update_header.height();
-			let update_client_header = {
-				let msg = MsgUpdateAnyClient::<LocalClientTypes> {
-					client_id: client_id.clone(),
-					client_message: AnyClientMessage::Tendermint(ClientMessage::Header(
-						update_header,
-					)),
-					signer: counterparty.account_id(),
-				};
-				let value = msg.encode_vec().map_err(|e| {
-					Error::from(format!("Failed to encode MsgUpdateClient {msg:?}: {e:?}"))
-				})?;
-				Any { value, type_url: msg.type_url() }
-			};
-			updates.push((update_client_header, height, events, update_type));
-		}
-		Ok(updates)
-	}
-
-	// TODO: Changed result: `Item =` from `IbcEvent` to `IbcEventWithHeight` to include the
-	// necessary height field, as `height` is removed from `Attribute` from ibc-rs v0.22.0
-	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
-		// Create websocket client. Like what `EventMonitor::subscribe()` does in `hermes`
-		let ws_client = self.rpc_client.clone();
 
-		let query_all = vec![
-			Query::from(EventType::NewBlock),
-			Query::eq("message.module", "ibc_client"),
-			Query::eq("message.module", "ibc_connection"),
-			Query::eq("message.module", "ibc_channel"),
-		];
-		let mut subscriptions = vec![];
-		for query in &query_all {
-			let subscription = ws_client
-				.subscribe(query.clone())
-				.await
-				.map_err(|e| Error::from(format!("Web Socket Client Error {e:?}")))
-				.unwrap();
-			subscriptions.push(subscription);
-		}
-		// Collect IBC events from each RPC event, Like what `stream_batches()` does in `hermes`
-		let all_subs: Box<dyn Stream<Item = Result<Event, RpcError>> + Send + Sync + Unpin> =
-			Box::new(select_all(subscriptions));
-		let chain_id = self.chain_id.clone();
-		let events = all_subs
-			.map(move |event| {
-				// Like what `get_all_events()` does in `hermes`
-				let mut events_with_height: Vec<IbcEventWithHeight> = vec![];
-				let Event { data, events: _, query } = event.unwrap();
-				match data {
-					EventData::NewBlock { block, .. }
-						if query == Query::from(EventType::NewBlock).to_string() =>
-					{
-						let height = Height::new(
-							ChainId::chain_version(chain_id.to_string().as_str()),
-							u64::from(block.as_ref().ok_or("tx.height").unwrap().header.height),
-						);
-						events_with_height.push(IbcEventWithHeight::new(
-							ClientEvents::NewBlock::new(height).into(),
-							height,
-						));
-					},
-					EventData::Tx { tx_result } => {
-						let height = Height::new(
-							ChainId::chain_version(chain_id.to_string().as_str()),
-							tx_result.height as u64,
-						);
-						for abci_event in &tx_result.result.events {
-							if let Ok(ibc_event) = ibc_event_try_from_abci_event(abci_event, height)
-							{
-								log::debug!(target: "hyperspace_cosmos", "Retrieved event: {}, query: {}, parsed: {:?}", abci_event.kind, query, ibc_event);
-								let is_client_event = query == Query::eq("message.module", "ibc_client").to_string() &&
-									event_is_type_client(&ibc_event);
-								let is_connection_event = (query ==
-									Query::eq("message.module", "ibc_connection").to_string() ||
-									query ==
-										Query::eq("message.module", "ibc_client").to_string()) &&
-									event_is_type_connection(&ibc_event);
-								let is_channel_event = query ==
-									Query::eq("message.module", "ibc_channel").to_string() &&
-									event_is_type_channel(&ibc_event);
-								if is_client_event || is_connection_event || is_channel_event {
-									events_with_height
-										.push(IbcEventWithHeight::new(ibc_event, height));
-								} else {
-									log::debug!(target: "hyperspace_cosmos", "The event is unknown");
-								}
-							} else {
-								log::debug!(target: "hyperspace_cosmos", "Failed to parse event {:?}", abci_event);
-							}
-						}
-					},
-					_ => {},
-				}
-				stream::iter(events_with_height)
-			})
-			.flatten()
-			.map(|e| e.event)
-			.boxed();
-		events
-	}
+	type Error = Error;
 
 	async fn query_client_consensus(
 		&self,
@@ -355,9 +195,9 @@ where
 		})
 	}
 
-	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
-		let (_, proof) = self.query_path(keys[0].clone(), at, true).await?;
-		Ok(proof)
+	async fn query_cross_chain_queries(&self) -> Result<Vec<CrossChainQuery>, Self::Error> {
+		// cosmos chains don't host the cross-chain queries of this implementation
+		Ok(vec![])
 	}
 
 	async fn query_packet_commitment(
@@ -625,6 +465,8 @@ where
 		&self,
 		_at: Height,
 		connection_id: &ConnectionId,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
 	) -> Result<QueryChannelsResponse, Self::Error> {
 		let mut grpc_client =
 			ibc_proto::ibc::core::channel::v1::query_client::QueryClient::connect(
@@ -634,7 +476,11 @@ where
 			.map_err(|e| Error::from(format!("{e:?}")))?;
 		let request = tonic::Request::new(QueryConnectionChannelsRequest {
 			connection: connection_id.to_string(),
-			pagination: Some(PageRequest { limit: u32::MAX as _, ..Default::default() }),
+			pagination: Some(PageRequest {
+				key: next_key.unwrap_or_default(),
+				limit,
+				..Default::default()
+			}),
 		});
 
 		let response = grpc_client
@@ -651,51 +497,556 @@ where
 		Ok(channels)
 	}
 
-	async fn query_send_packets(
+	fn expected_block_time(&self) -> Duration {
+		// cosmos chain block time is roughly 6-7 seconds
+		Duration::from_secs(7)
+	}
+
+	async fn query_ibc_balance(
 		&self,
-		channel_id: ChannelId,
-		port_id: PortId,
-		seqs: Vec<u64>,
-	) -> Result<Vec<PacketInfo>, Self::Error> {
-		log::debug!(
-			target: "hyperspace_cosmos",
-			"query_send_packets: channel_id: {}, port_id: {}, seqs: {:?}", channel_id, port_id, seqs
-		);
-		let mut block_events = HashMap::<u64, PacketInfo>::new();
+		asset_id: Self::AssetId,
+	) -> Result<Vec<PrefixedCoin>, Self::Error> {
+		let denom = &asset_id;
+		let mut grpc_client = ibc_proto::cosmos::bank::v1beta1::query_client::QueryClient::connect(
+			self.grpc_url.clone().to_string(),
+		)
+		.await
+		.map_err(|e| Error::from(format!("{e:?}")))?;
 
-		for seq in seqs.iter() {
-			if block_events.contains_key(seq) {
-				continue
-			}
-			let query_str = Query::eq("send_packet.packet_src_channel", channel_id.to_string())
-				.and_eq("send_packet.packet_src_port", port_id.to_string())
-				.and_eq("send_packet.packet_sequence", seq.to_string());
+		let request = tonic::Request::new(QueryBalanceRequest {
+			address: self.keybase.clone().account,
+			denom: denom.to_string(),
+		});
 
-			let response = self
-				.rpc_http_client
-				.tx_search(
-					query_str,
-					true,
-					1,
-					1, // get only the first Tx matching the query
-					Order::Descending, /* query the most recent event, there is possibility that the same
-					    * sequence number is used twice in send_packet event (in case of an
-					    * error during the message processing) */
-				)
-				.await
-				.map_err(|e| Error::RpcError(format!("{e:?}")))?;
+		let response = grpc_client
+			.balance(request)
+			.await
+			.map(|r| r.into_inner())
+			.map_err(|e| Error::from(format!("{e:?}")))?;
 
-			for tx in response.txs {
-				for ev in &tx.tx_result.events {
-					let height = tx.height.value();
-					let ev =
-						ibc_event_try_from_abci_event(ev, Height::new(self.id().version(), height));
+		// Querying for a balance might fail, i.e. if the account doesn't actually exist
+		let balance = response
+			.balance
+			.ok_or_else(|| Error::from(format!("No balance for denom {denom}")))?;
 
-					match ev {
-						Ok(IbcEvent::SendPacket(p))
+		Ok(vec![PrefixedCoin {
+			denom: PrefixedDenom {
+				trace_path: TracePath::default(),
+				base_denom: BaseDenom::from_str(denom)?,
+			},
+			amount: Amount::from_str(balance.amount.as_str())?,
+		}])
+	}
+
+	async fn query_denom_trace(
+		&self,
+		denom_hash: String,
+	) -> Result<QueryDenomTraceResponse, Self::Error> {
+		let mut grpc_client = TransferQueryClient::connect(self.grpc_url.clone().to_string())
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?;
+		// ibc-go expects the hash without the `ibc/` prefix
+		let hash = denom_hash.trim_start_matches("ibc/").to_string();
+		let request = tonic::Request::new(QueryDenomTraceRequest { hash });
+		grpc_client
+			.denom_trace(request)
+			.await
+			.map(|r| r.into_inner())
+			.map_err(|e| Error::from(format!("{e:?}")))
+	}
+
+	async fn query_denom_traces(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<QueryDenomTracesResponse, Self::Error> {
+		let mut grpc_client = TransferQueryClient::connect(self.grpc_url.clone().to_string())
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?;
+		let request = tonic::Request::new(QueryDenomTracesRequest {
+			pagination: Some(PageRequest {
+				key: next_key.unwrap_or_default(),
+				limit,
+				..Default::default()
+			}),
+		});
+		grpc_client
+			.denom_traces(request)
+			.await
+			.map(|r| r.into_inner())
+			.map_err(|e| Error::from(format!("{e:?}")))
+	}
+
+	async fn query_denom_hash(&self, trace: String) -> Result<String, Self::Error> {
+		let mut grpc_client = TransferQueryClient::connect(self.grpc_url.clone().to_string())
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?;
+		let request = tonic::Request::new(QueryDenomHashRequest { trace });
+		let response = grpc_client
+			.denom_hash(request)
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?
+			.into_inner();
+		// ibc-go returns the hash without the `ibc/` prefix
+		Ok(format!("ibc/{}", response.hash))
+	}
+
+	async fn query_packet_fees(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		sequence: u64,
+	) -> Result<Vec<PacketFee>, Self::Error> {
+		let mut grpc_client = FeeQueryClient::connect(self.grpc_url.clone().to_string())
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?;
+		let request = tonic::Request::new(QueryIncentivizedPacketRequest {
+			packet_id: Some(RawPacketId {
+				port_id: port_id.to_string(),
+				channel_id: channel_id.to_string(),
+				sequence,
+			}),
+			query_height: 0,
+		});
+		let response = match grpc_client.incentivized_packet(request).await {
+			Ok(response) => response.into_inner(),
+			// the fee module only stores the packets that escrowed a fee
+			Err(status) if status.code() == tonic::Code::NotFound => return Ok(vec![]),
+			Err(e) => return Err(Error::from(format!("{e:?}"))),
+		};
+		response
+			.incentivized_packet
+			.map(|packet| packet.packet_fees)
+			.unwrap_or_default()
+			.into_iter()
+			.map(|fee| PacketFee::try_from(fee).map_err(|e| Error::from(format!("{e:?}"))))
+			.collect()
+	}
+
+	fn connection_prefix(&self) -> CommitmentPrefix {
+		self.commitment_prefix.clone()
+	}
+
+	fn client_id(&self) -> ClientId {
+		self.client_id()
+	}
+
+	fn set_client_id(&mut self, client_id: ClientId) {
+		*self.client_id.lock().unwrap() = Some(client_id);
+	}
+
+	fn connection_id(&self) -> Option<ConnectionId> {
+		self.connection_id.lock().unwrap().clone()
+	}
+
+	/// Set the channel whitelist for the relayer task.
+	fn set_channel_whitelist(&mut self, channel_whitelist: HashSet<(ChannelId, PortId)>) {
+		*self.channel_whitelist.lock().unwrap() = channel_whitelist;
+	}
+
+	fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
+		self.channel_whitelist.lock().unwrap().insert(channel);
+	}
+
+	fn set_connection_id(&mut self, connection_id: ConnectionId) {
+		*self.connection_id.lock().unwrap() = Some(connection_id);
+	}
+
+	fn client_type(&self) -> ClientType {
+		ClientState::<()>::client_type()
+	}
+
+	async fn query_timestamp_at(&self, block_number: u64) -> Result<u64, Self::Error> {
+		let height = TmHeight::try_from(block_number)
+			.map_err(|e| Error::from(format!("Invalid block number: {e}")))?;
+		let response = self
+			.rpc_client
+			.block(height)
+			.await
+			.map_err(|e| Error::RpcError(e.to_string()))?;
+		let time: Timestamp = response.block.header.time.into();
+		Ok(time.nanoseconds())
+	}
+
+	async fn query_clients(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<Page<ClientId>, Self::Error> {
+		let request = tonic::Request::new(QueryClientStatesRequest {
+			pagination: Some(PageRequest {
+				key: next_key.unwrap_or_default(),
+				limit,
+				..Default::default()
+			}),
+		});
+		let grpc_client = ibc_proto::ibc::core::client::v1::query_client::QueryClient::new(
+			self.grpc_client.clone(),
+		);
+		let response = grpc_client
+			.clone()
+			.client_states(request)
+			.await
+			.map_err(|e| {
+				Error::from(format!("Failed to query client states from grpc client: {e:?}"))
+			})?
+			.into_inner();
+
+		// Deserialize into domain type
+		let clients: Vec<ClientId> = response
+			.client_states
+			.into_iter()
+			.filter_map(|cs| {
+				let id = ClientId::from_str(&cs.client_id).ok()?;
+				Some(id)
+			})
+			.collect();
+		Ok(Page { items: clients, next_key: next_page_key(response.pagination) })
+	}
+
+	async fn query_channels(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<Page<(ChannelId, PortId)>, Self::Error> {
+		let request = tonic::Request::new(QueryChannelsRequest {
+			pagination: Some(PageRequest {
+				key: next_key.unwrap_or_default(),
+				limit,
+				..Default::default()
+			}),
+		});
+		let mut grpc_client =
+			ibc_proto::ibc::core::channel::v1::query_client::QueryClient::connect(
+				self.grpc_url.clone().to_string(),
+			)
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?;
+		let response = grpc_client
+			.channels(request)
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?
+			.into_inner();
+		let channels = response
+			.channels
+			.into_iter()
+			.filter_map(|c| {
+				let id = ChannelId::from_str(&c.channel_id).ok()?;
+				let port_id = PortId::from_str(&c.port_id).ok()?;
+				Some((id, port_id))
+			})
+			.collect::<Vec<_>>();
+		Ok(Page { items: channels, next_key: next_page_key(response.pagination) })
+	}
+
+	async fn query_connection_using_client(
+		&self,
+		_height: u32,
+		client_id: String,
+	) -> Result<Vec<IdentifiedConnection>, Self::Error> {
+		let mut grpc_client =
+			ibc_proto::ibc::core::connection::v1::query_client::QueryClient::connect(
+				self.grpc_url.clone().to_string(),
+			)
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?;
+
+		let request = tonic::Request::new(QueryConnectionsRequest {
+			pagination: Some(PageRequest { limit: u32::MAX as _, ..Default::default() }),
+		});
+
+		let response = grpc_client
+			.connections(request)
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?
+			.into_inner();
+
+		let connections = response
+			.connections
+			.into_iter()
+			.filter(|conn| {
+				conn.client_id == client_id ||
+					conn.counterparty.as_ref().map(|x| x.client_id == client_id).unwrap_or(false)
+			})
+			.collect();
+		Ok(connections)
+	}
+
+	async fn query_connections(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<QueryConnectionsResponse, Self::Error> {
+		let mut grpc_client =
+			ibc_proto::ibc::core::connection::v1::query_client::QueryClient::connect(
+				self.grpc_url.clone().to_string(),
+			)
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?;
+		let request = tonic::Request::new(QueryConnectionsRequest {
+			pagination: Some(PageRequest {
+				key: next_key.unwrap_or_default(),
+				limit,
+				..Default::default()
+			}),
+		});
+		grpc_client
+			.connections(request)
+			.await
+			.map(|r| r.into_inner())
+			.map_err(|e| Error::from(format!("{e:?}")))
+	}
+
+	async fn query_connection_params(&self) -> Result<ConnectionParams, Self::Error> {
+		let mut grpc_client =
+			ibc_proto::ibc::core::connection::v1::query_client::QueryClient::connect(
+				self.grpc_url.clone().to_string(),
+			)
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?;
+		let response = grpc_client
+			.connection_params(tonic::Request::new(QueryConnectionParamsRequest {}))
+			.await
+			.map_err(|e| Error::from(format!("{e:?}")))?
+			.into_inner();
+		response
+			.params
+			.ok_or_else(|| Error::from("Connection params missing from the response".to_string()))
+	}
+}
+
+#[async_trait::async_trait]
+impl<H> IbcEventProvider for CosmosClient<H>
+where
+	H: Clone + Send + Sync + 'static,
+{
+	type FinalityEvent = FinalityEvent;
+
+	type TransactionId = TransactionId<Hash>;
+
+	async fn query_latest_ibc_events<C>(
+		&mut self,
+		finality_event: Self::FinalityEvent,
+		counterparty: &C,
+	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+	where
+		C: Chain,
+	{
+		let finality_event_height = match finality_event {
+			FinalityEvent::Tendermint { from: _, to } => to,
+		};
+		let client_id = self.client_id();
+		let latest_cp_height = counterparty.latest_height_and_timestamp().await?.0;
+		let latest_cp_client_state =
+			counterparty.query_client_state(latest_cp_height, client_id.clone()).await?;
+		let client_state_response = latest_cp_client_state
+			.client_state
+			.ok_or_else(|| Error::Custom("counterparty returned empty client state".to_string()))?;
+		let client_state =
+			ClientState::<HostFunctionsManager>::decode_vec(&client_state_response.value)
+				.map_err(|_| Error::Custom("failed to decode client state response".to_string()))?;
+		let latest_cp_client_height = client_state.latest_height().revision_height;
+		let latest_height = self.latest_height_and_timestamp().await?.0;
+		let latest_revision = latest_height.revision_number;
+
+		let from = TmHeight::try_from(latest_cp_client_height).unwrap();
+		let to = finality_event_height.min(
+			TmHeight::try_from(latest_cp_client_height + NUMBER_OF_BLOCKS_TO_PROCESS_PER_ITER)
+				.expect("should not overflow"),
+		);
+		log::info!(target: "hyperspace_cosmos", "Getting blocks {}..{}", from, to);
+
+		// query (exclusively) up to `to`, because the proof for the event at `to - 1` will be
+		// contained at `to` and will be fetched below by `msg_update_client_header`
+		let update_headers =
+			self.msg_update_client_header(from, to, client_state.latest_height).await?;
+		let mut block_events = Vec::new();
+		block_events.push((0, Vec::new()));
+		let mut join_set: JoinSet<Result<_, anyhow::Error>> = JoinSet::new();
+		let range = (from.value()..to.value()).collect::<Vec<_>>();
+		let to = self.rpc_call_delay().as_millis();
+		for heights in range.chunks(100) {
+			for height in heights.iter().copied() {
+				log::trace!(target: "hyperspace_cosmos", "Parsing events at height {:?}", height);
+				let client = self.clone();
+				let duration = Duration::from_millis(rand::thread_rng().gen_range(0..to) as u64);
+				let counterparty = counterparty.clone();
+				join_set.spawn(async move {
+					sleep(duration).await;
+					let xs = tokio::time::timeout(
+						Duration::from_secs(30),
+						client.parse_ibc_events_at(&counterparty, latest_revision, height),
+					)
+					.await??;
+					Ok((height, xs))
+				});
+			}
+			while let Some(res) = join_set.join_next().await {
+				let out = res??;
+				block_events.push(out);
+			}
+		}
+
+		if block_events.len() != update_headers.len() {
+			return Err(anyhow::anyhow!(
+				"block events and updates must match, got {} and {}",
+				block_events.len(),
+				update_headers.len()
+			))
+		}
+		block_events.sort_by_key(|(height, _)| *height);
+
+		let mut updates = Vec::new();
+		for (events, (update_header, update_type)) in
+			block_events.into_iter().map(|(_, events)| events).zip(update_headers)
+		{
+			let height = update_header.height();
+			let update_client_header = {
+				let msg = MsgUpdateAnyClient::<LocalClientTypes> {
+					client_id: client_id.clone(),
+					client_message: AnyClientMessage::Tendermint(ClientMessage::Header(
+						update_header,
+					)),
+					signer: counterparty.account_id(),
+				};
+				let value = msg.encode_vec().map_err(|e| {
+					Error::from(format!("Failed to encode MsgUpdateClient {msg:?}: {e:?}"))
+				})?;
+				Any { value, type_url: msg.type_url() }
+			};
+			updates.push((update_client_header, height, events, update_type));
+		}
+		Ok(updates)
+	}
+
+	// TODO: Changed result: `Item =` from `IbcEvent` to `IbcEventWithHeight` to include the
+	// necessary height field, as `height` is removed from `Attribute` from ibc-rs v0.22.0
+	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
+		// Create websocket client. Like what `EventMonitor::subscribe()` does in `hermes`
+		let ws_client = self.rpc_client.clone();
+
+		let query_all = vec![
+			Query::from(EventType::NewBlock),
+			Query::eq("message.module", "ibc_client"),
+			Query::eq("message.module", "ibc_connection"),
+			Query::eq("message.module", "ibc_channel"),
+		];
+		let mut subscriptions = vec![];
+		for query in &query_all {
+			let subscription = ws_client
+				.subscribe(query.clone())
+				.await
+				.map_err(|e| Error::from(format!("Web Socket Client Error {e:?}")))
+				.unwrap();
+			subscriptions.push(subscription);
+		}
+		// Collect IBC events from each RPC event, Like what `stream_batches()` does in `hermes`
+		let all_subs: Box<dyn Stream<Item = Result<Event, RpcError>> + Send + Sync + Unpin> =
+			Box::new(select_all(subscriptions));
+		let chain_id = self.chain_id.clone();
+		let events = all_subs
+			.map(move |event| {
+				// Like what `get_all_events()` does in `hermes`
+				let mut events_with_height: Vec<IbcEventWithHeight> = vec![];
+				let Event { data, events: _, query } = event.unwrap();
+				match data {
+					EventData::NewBlock { block, .. }
+						if query == Query::from(EventType::NewBlock).to_string() =>
+					{
+						let height = Height::new(
+							ChainId::chain_version(chain_id.to_string().as_str()),
+							u64::from(block.as_ref().ok_or("tx.height").unwrap().header.height),
+						);
+						events_with_height.push(IbcEventWithHeight::new(
+							ClientEvents::NewBlock::new(height).into(),
+							height,
+						));
+					},
+					EventData::Tx { tx_result } => {
+						let height = Height::new(
+							ChainId::chain_version(chain_id.to_string().as_str()),
+							tx_result.height as u64,
+						);
+						for abci_event in &tx_result.result.events {
+							if let Ok(ibc_event) = ibc_event_try_from_abci_event(abci_event, height)
+							{
+								log::debug!(target: "hyperspace_cosmos", "Retrieved event: {}, query: {}, parsed: {:?}", abci_event.kind, query, ibc_event);
+								let is_client_event = query == Query::eq("message.module", "ibc_client").to_string() &&
+									event_is_type_client(&ibc_event);
+								let is_connection_event = (query ==
+									Query::eq("message.module", "ibc_connection").to_string() ||
+									query ==
+										Query::eq("message.module", "ibc_client").to_string()) &&
+									event_is_type_connection(&ibc_event);
+								let is_channel_event = query ==
+									Query::eq("message.module", "ibc_channel").to_string() &&
+									event_is_type_channel(&ibc_event);
+								if is_client_event || is_connection_event || is_channel_event {
+									events_with_height
+										.push(IbcEventWithHeight::new(ibc_event, height));
+								} else {
+									log::debug!(target: "hyperspace_cosmos", "The event is unknown");
+								}
+							} else {
+								log::debug!(target: "hyperspace_cosmos", "Failed to parse event {:?}", abci_event);
+							}
+						}
+					},
+					_ => {},
+				}
+				stream::iter(events_with_height)
+			})
+			.flatten()
+			.map(|e| e.event)
+			.boxed();
+		events
+	}
+
+	async fn query_send_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		log::debug!(
+			target: "hyperspace_cosmos",
+			"query_send_packets: channel_id: {}, port_id: {}, seqs: {:?}", channel_id, port_id, seqs
+		);
+		let mut block_events = HashMap::<u64, PacketInfo>::new();
+
+		for seq in seqs.iter() {
+			if block_events.contains_key(seq) {
+				continue
+			}
+			let query_str = Query::eq("send_packet.packet_src_channel", channel_id.to_string())
+				.and_eq("send_packet.packet_src_port", port_id.to_string())
+				.and_eq("send_packet.packet_sequence", seq.to_string());
+
+			let response = self
+				.rpc_http_client
+				.tx_search(
+					query_str,
+					true,
+					1,
+					1, // get only the first Tx matching the query
+					Order::Descending, /* query the most recent event, there is possibility that the same
+					    * sequence number is used twice in send_packet event (in case of an
+					    * error during the message processing) */
+				)
+				.await
+				.map_err(|e| Error::RpcError(format!("{e:?}")))?;
+
+			for tx in response.txs {
+				for ev in &tx.tx_result.events {
+					let height = tx.height.value();
+					let ev =
+						ibc_event_try_from_abci_event(ev, Height::new(self.id().version(), height));
+
+					match ev {
+						Ok(IbcEvent::SendPacket(p))
 							if seqs.contains(&p.packet.sequence.0) &&
-								p.packet.source_port == port_id && p.packet.source_channel ==
-								channel_id =>
+								p.packet.source_port == port_id &&
+								p.packet.source_channel == channel_id =>
 						{
 							let seq = p.packet.sequence.0;
 							let mut info = PacketInfo::try_from(IbcPacketInfo::from(p.packet))
@@ -802,12 +1153,46 @@ where
 				}
 			}
 		}
-		Ok(block_events.into_values().collect())
-	}
-
-	fn expected_block_time(&self) -> Duration {
-		// cosmos chain block time is roughly 6-7 seconds
-		Duration::from_secs(7)
+		Ok(block_events.into_values().collect())
+	}
+
+	async fn query_packet_events(
+		&self,
+		from_height: Height,
+		to_height: Height,
+		filter: PacketEventsFilter,
+	) -> Result<Vec<IbcEvent>, Self::Error> {
+		log::debug!(
+			target: "hyperspace_cosmos",
+			"query_packet_events: from {} to {}, filter: {:?}", from_height, to_height, filter
+		);
+		let query = Query::gte("tx.height", from_height.revision_height)
+			.and_lte("tx.height", to_height.revision_height);
+		let mut events = vec![];
+		let mut page = 1;
+		let mut seen_txs = 0;
+		loop {
+			let response = self
+				.rpc_http_client
+				.tx_search(query.clone(), true, page, 100, Order::Ascending)
+				.await
+				.map_err(|e| Error::RpcError(format!("{e:?}")))?;
+			seen_txs += response.txs.len() as u32;
+			for tx in &response.txs {
+				let height = Height::new(self.id().version(), tx.height.value());
+				events.extend(
+					tx.tx_result
+						.events
+						.iter()
+						.filter_map(|ev| ibc_event_try_from_abci_event(ev, height).ok())
+						.filter(|ev| filter.matches(ev)),
+				);
+			}
+			if response.txs.is_empty() || seen_txs >= response.total_count {
+				return Ok(events)
+			}
+			page += 1;
+		}
 	}
 
 	async fn query_client_update_time_and_height(
@@ -856,181 +1241,6 @@ where
 		Err(Error::from("not found".to_string()))
 	}
 
-	async fn query_host_consensus_state_proof(
-		&self,
-		_client_state: &AnyClientState,
-	) -> Result<Option<Vec<u8>>, Self::Error> {
-		unimplemented!()
-	}
-
-	async fn query_ibc_balance(
-		&self,
-		asset_id: Self::AssetId,
-	) -> Result<Vec<PrefixedCoin>, Self::Error> {
-		let denom = &asset_id;
-		let mut grpc_client = ibc_proto::cosmos::bank::v1beta1::query_client::QueryClient::connect(
-			self.grpc_url.clone().to_string(),
-		)
-		.await
-		.map_err(|e| Error::from(format!("{e:?}")))?;
-
-		let request = tonic::Request::new(QueryBalanceRequest {
-			address: self.keybase.clone().account,
-			denom: denom.to_string(),
-		});
-
-		let response = grpc_client
-			.balance(request)
-			.await
-			.map(|r| r.into_inner())
-			.map_err(|e| Error::from(format!("{e:?}")))?;
-
-		// Querying for a balance might fail, i.e. if the account doesn't actually exist
-		let balance = response
-			.balance
-			.ok_or_else(|| Error::from(format!("No balance for denom {denom}")))?;
-
-		Ok(vec![PrefixedCoin {
-			denom: PrefixedDenom {
-				trace_path: TracePath::default(),
-				base_denom: BaseDenom::from_str(denom)?,
-			},
-			amount: Amount::from_str(balance.amount.as_str())?,
-		}])
-	}
-
-	fn connection_prefix(&self) -> CommitmentPrefix {
-		self.commitment_prefix.clone()
-	}
-
-	fn client_id(&self) -> ClientId {
-		self.client_id()
-	}
-
-	fn set_client_id(&mut self, client_id: ClientId) {
-		*self.client_id.lock().unwrap() = Some(client_id);
-	}
-
-	fn connection_id(&self) -> Option<ConnectionId> {
-		self.connection_id.lock().unwrap().clone()
-	}
-
-	/// Set the channel whitelist for the relayer task.
-	fn set_channel_whitelist(&mut self, channel_whitelist: HashSet<(ChannelId, PortId)>) {
-		*self.channel_whitelist.lock().unwrap() = channel_whitelist;
-	}
-
-	fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
-		self.channel_whitelist.lock().unwrap().insert(channel);
-	}
-
-	fn set_connection_id(&mut self, connection_id: ConnectionId) {
-		*self.connection_id.lock().unwrap() = Some(connection_id);
-	}
-
-	fn client_type(&self) -> ClientType {
-		ClientState::<()>::client_type()
-	}
-
-	async fn query_timestamp_at(&self, block_number: u64) -> Result<u64, Self::Error> {
-		let height = TmHeight::try_from(block_number)
-			.map_err(|e| Error::from(format!("Invalid block number: {e}")))?;
-		let response = self
-			.rpc_client
-			.block(height)
-			.await
-			.map_err(|e| Error::RpcError(e.to_string()))?;
-		let time: Timestamp = response.block.header.time.into();
-		Ok(time.nanoseconds())
-	}
-
-	async fn query_clients(&self) -> Result<Vec<ClientId>, Self::Error> {
-		let request = tonic::Request::new(QueryClientStatesRequest {
-			pagination: Some(PageRequest { limit: u32::MAX as _, ..Default::default() }),
-		});
-		let grpc_client = ibc_proto::ibc::core::client::v1::query_client::QueryClient::new(
-			self.grpc_client.clone(),
-		);
-		let response = grpc_client
-			.clone()
-			.client_states(request)
-			.await
-			.map_err(|e| {
-				Error::from(format!("Failed to query client states from grpc client: {e:?}"))
-			})?
-			.into_inner();
-
-		// Deserialize into domain type
-		let clients: Vec<ClientId> = response
-			.client_states
-			.into_iter()
-			.filter_map(|cs| {
-				let id = ClientId::from_str(&cs.client_id).ok()?;
-				Some(id)
-			})
-			.collect();
-		Ok(clients)
-	}
-
-	async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
-		let request = tonic::Request::new(QueryChannelsRequest {
-			pagination: Some(PageRequest { limit: u32::MAX as _, ..Default::default() }),
-		});
-		let mut grpc_client =
-			ibc_proto::ibc::core::channel::v1::query_client::QueryClient::connect(
-				self.grpc_url.clone().to_string(),
-			)
-			.await
-			.map_err(|e| Error::from(format!("{e:?}")))?;
-		let response = grpc_client
-			.channels(request)
-			.await
-			.map_err(|e| Error::from(format!("{e:?}")))?
-			.into_inner()
-			.channels
-			.into_iter()
-			.filter_map(|c| {
-				let id = ChannelId::from_str(&c.channel_id).ok()?;
-				let port_id = PortId::from_str(&c.port_id).ok()?;
-				Some((id, port_id))
-			})
-			.collect::<Vec<_>>();
-		Ok(response)
-	}
-
-	async fn query_connection_using_client(
-		&self,
-		_height: u32,
-		client_id: String,
-	) -> Result<Vec<IdentifiedConnection>, Self::Error> {
-		let mut grpc_client =
-			ibc_proto::ibc::core::connection::v1::query_client::QueryClient::connect(
-				self.grpc_url.clone().to_string(),
-			)
-			.await
-			.map_err(|e| Error::from(format!("{e:?}")))?;
-
-		let request = tonic::Request::new(QueryConnectionsRequest {
-			pagination: Some(PageRequest { limit: u32::MAX as _, ..Default::default() }),
-		});
-
-		let response = grpc_client
-			.connections(request)
-			.await
-			.map_err(|e| Error::from(format!("{e:?}")))?
-			.into_inner();
-
-		let connections = response
-			.connections
-			.into_iter()
-			.filter(|conn| {
-				conn.client_id == client_id ||
-					conn.counterparty.as_ref().map(|x| x.client_id == client_id).unwrap_or(false)
-			})
-			.collect();
-		Ok(connections)
-	}
-
 	async fn is_update_required(
 		&self,
 		_latest_height: u64,
@@ -1041,33 +1251,6 @@ where
 		Ok(false)
 	}
 
-	async fn initialize_client_state(
-		&self,
-	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
-		let latest_height_timestamp = self.latest_height_and_timestamp().await?;
-		let client_state = ClientState::new(
-			self.chain_id.clone(),
-			TrustThreshold::default(),
-			Duration::from_secs(64000),
-			Duration::from_secs(1814400),
-			Duration::new(15, 0),
-			latest_height_timestamp.0,
-			ProofSpecs::default(),
-			vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
-		)
-		.map_err(|e| Error::from(format!("Invalid client state {e}")))?;
-		let light_block = self
-			.light_client
-			.verify(latest_height_timestamp.0, latest_height_timestamp.0, &client_state)
-			.await
-			.map_err(|e| Error::from(format!("Invalid light block {e}")))?;
-		let consensus_state = ConsensusState::from(light_block.signed_header.header);
-		Ok((
-			AnyClientState::Tendermint(client_state),
-			AnyConsensusState::Tendermint(consensus_state),
-		))
-	}
-
 	async fn query_client_id_from_tx_hash(
 		&self,
 		tx_id: Self::TransactionId,
@@ -1270,46 +1453,61 @@ where
 			}
 		}
 	}
+}
 
-	async fn upload_wasm(&self, wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
-		let msg = MsgPushNewWasmCode { signer: self.account_id(), code: wasm };
-		let hash = self.submit(vec![msg.into()]).await?;
-		let resp = self.wait_for_tx_result(hash).await?;
-		let height = Height::new(
-			ChainId::chain_version(self.chain_id.to_string().as_str()),
-			resp.height.value(),
-		);
-		let deliver_tx_result = resp.tx_result;
-		let mut result = deliver_tx_result
-			.events
-			.iter()
-			.flat_map(|e| ibc_event_try_from_abci_event(e, height).ok().into_iter())
-			.filter(|e| matches!(e, IbcEvent::PushWasmCode(_)))
-			.collect::<Vec<_>>();
-		let code_hash = if result.len() != 1 {
-			return Err(Error::from(format!(
-				"Expected exactly one PushWasmCode event, found {}",
-				result.len()
-			)))
-		} else {
-			match result.pop().unwrap() {
-				IbcEvent::PushWasmCode(ev) => ev.0,
-				_ => unreachable!(),
-			}
-		};
-		// let resp = MsgClient::connect(
-		// 	Endpoint::try_from(self.grpc_url.to_string())
-		// 		.map_err(|e| Error::from(format!("Failed to parse grpc url: {:?}", e)))?,
-		// )
-		// .await
-		// .map_err(|e| Error::from(format!("Failed to connect to grpc endpoint: {:?}", e)))?
-		// .push_new_wasm_code(msg)
-		// .await
-		// .map_err(|e| {
-		// 	Error::from(format!("Failed to upload wasm code to grpc endpoint: {:?}", e))
-		// })?;
-
-		Ok(code_hash)
+#[async_trait::async_trait]
+impl<H> ProofProvider for CosmosClient<H>
+where
+	H: Clone + Send + Sync + 'static,
+{
+	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
+		let (_, proof) = self.query_path(keys[0].clone(), at, true).await?;
+		Ok(proof)
+	}
+
+	async fn query_value_with_proof(
+		&self,
+		at: Height,
+		path: Path,
+	) -> Result<(Option<Vec<u8>>, Vec<u8>), Self::Error> {
+		let (query_result, proof) =
+			self.query_path(path.to_string().into_bytes(), at, true).await?;
+		let value = (!query_result.value.is_empty()).then_some(query_result.value);
+		Ok((value, proof))
+	}
+
+	async fn query_host_consensus_state_proof(
+		&self,
+		_client_state: &AnyClientState,
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		unimplemented!()
+	}
+
+	async fn initialize_client_state(
+		&self,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		let latest_height_timestamp = self.latest_height_and_timestamp().await?;
+		let client_state = ClientState::new(
+			self.chain_id.clone(),
+			TrustThreshold::default(),
+			self.trusting_period,
+			self.unbonding_period,
+			self.max_clock_drift,
+			latest_height_timestamp.0,
+			ProofSpecs::default(),
+			vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
+		)
+		.map_err(|e| Error::from(format!("Invalid client state {e}")))?;
+		let light_block = self
+			.light_client
+			.verify(latest_height_timestamp.0, latest_height_timestamp.0, &client_state)
+			.await
+			.map_err(|e| Error::from(format!("Invalid light block {e}")))?;
+		let consensus_state = ConsensusState::from(light_block.signed_header.header);
+		Ok((
+			AnyClientState::Tendermint(client_state),
+			AnyConsensusState::Tendermint(consensus_state),
+		))
 	}
 }
 
@@ -1322,7 +1520,7 @@ where
 		counterparty: &C,
 		latest_revision: u64,
 		height: u64,
-	) -> Result<Vec<IbcEvent>, <Self as IbcProvider>::Error> {
+	) -> Result<Vec<IbcEvent>, <Self as IbcQueryProvider>::Error> {
 		let mut ibc_events = Vec::new();
 
 		let block_results = self
@@ -1396,10 +1594,10 @@ where
 
 impl<H: Clone + Send + Sync + 'static> CosmosClient<H> {
 	#[allow(unused)]
-	async fn wait_for_tx_result(
+	pub(crate) async fn wait_for_tx_result(
 		&self,
-		tx_id: <Self as IbcProvider>::TransactionId,
-	) -> Result<Response, <Self as IbcProvider>::Error> {
+		tx_id: <Self as IbcEventProvider>::TransactionId,
+	) -> Result<Response, <Self as IbcQueryProvider>::Error> {
 		const WAIT_BACKOFF: Duration = Duration::from_millis(300);
 		const TIME_OUT: Duration = Duration::from_millis(30000);
 		let start_time = std::time::Instant::now();
@@ -3,11 +3,18 @@ use crate::error::Error;
 use core::pin::Pin;
 use futures::{Stream, StreamExt};
 use ibc::{
-	applications::transfer::{msgs::transfer::MsgTransfer, PrefixedCoin},
-	core::ics24_host::identifier::ChannelId,
+	applications::{
+		interchain_accounts::controller::msgs::MsgRegisterInterchainAccount,
+		transfer::{msgs::transfer::MsgTransfer, PrefixedCoin, PrefixedDenom},
+	},
+	core::{
+		ics04_channel::Version,
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+	},
 	tx_msg::Msg,
 };
-use primitives::TestProvider;
+use primitives::{utils::expired_transfer, KeyProvider, TestProvider};
+use std::str::FromStr;
 use tendermint_rpc::{
 	event::{Event, EventData},
 	query::{EventType, Query},
@@ -35,6 +42,46 @@ where
 		Err(Error::Custom("send_ordered_packet is not implemented yet".to_string()))
 	}
 
+	/// Registers an interchain account of the relayer on the host chain of the connection
+	async fn open_ica_channel(&self, connection_id: ConnectionId) -> Result<(), Self::Error> {
+		let msg = MsgRegisterInterchainAccount {
+			owner: self.account_id(),
+			connection_id,
+			version: Version::new(String::new()),
+		};
+		let hash = self.submit_call(vec![msg.to_any()]).await?;
+		log::info!(target: "hyperspace_cosmos", "🤝 Interchain account registration confirmed with hash: {:?}", hash);
+		Ok(())
+	}
+
+	/// Send a packet with arbitrary data on any channel
+	async fn send_raw_packet(
+		&self,
+		_port_id: PortId,
+		_channel_id: ChannelId,
+		_data: Vec<u8>,
+		_timeout: pallet_ibc::Timeout,
+	) -> Result<(), Self::Error> {
+		Err(Error::Custom(
+			"Cosmos chains have no message to send packets carrying arbitrary data".to_string(),
+		))
+	}
+
+	/// Send a packet that has already timed out on the counterparty
+	async fn force_timeout(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+	) -> Result<(), Self::Error> {
+		if port_id != PortId::transfer() {
+			return Err(Error::Custom(format!(
+				"Expired packets are sent as transfers, not on {port_id}"
+			)))
+		}
+		let denom = PrefixedDenom::from_str(&self.fee_denom)?;
+		self.send_transfer(expired_transfer(self, port_id, channel_id, denom)).await
+	}
+
 	/// Returns a stream that yields chain Block number
 	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>> {
 		let ws_client = self.rpc_client.clone();
@@ -1,9 +1,5 @@
-use super::{
-	encode::{
-		encode_auth_info, encode_key_bytes, encode_sign_doc, encode_signer_info, encode_tx,
-		encode_tx_body,
-	},
-	key_provider::KeyEntry,
+use super::encode::{
+	encode_auth_info, encode_sign_doc, encode_signer_info, encode_tx, encode_tx_body,
 };
 use crate::error::Error;
 use core::time::Duration;
@@ -18,6 +14,7 @@ use ibc_proto::{
 	},
 	google::protobuf::Any,
 };
+use primitives::signing::ChainSigner;
 use prost::Message;
 use tendermint::Hash;
 use tendermint_rpc::{
@@ -25,14 +22,13 @@ use tendermint_rpc::{
 };
 
 pub fn sign_tx(
-	key: KeyEntry,
+	signer: &dyn ChainSigner,
 	chain_id: ChainId,
 	account_info: &BaseAccount,
 	messages: Vec<Any>,
 	fee: Fee,
 ) -> Result<(Tx, TxRaw, Vec<u8>), Error> {
-	let pk_bytes = encode_key_bytes(&key)?;
-	let signer_info = encode_signer_info(account_info.sequence, pk_bytes)?;
+	let signer_info = encode_signer_info(account_info.sequence, &signer.public_key())?;
 
 	// Create and Encode AuthInfo
 	let (auth_info, auth_info_bytes) = encode_auth_info(signer_info, fee)?;
@@ -42,7 +38,7 @@ pub fn sign_tx(
 
 	// Create and Encode TxRaw
 	let signature_bytes = encode_sign_doc(
-		key,
+		signer,
 		body_bytes.clone(),
 		auth_info_bytes.clone(),
 		chain_id,
@@ -130,12 +126,12 @@ pub async fn confirm_tx(rpc_client: &WebSocketClient, tx_hash: Hash) -> Result<H
 }
 
 pub fn encoded_tx_metrics(
-	key: KeyEntry,
+	signer: &dyn ChainSigner,
 	chain_id: ChainId,
 	account_info: &BaseAccount,
 	fee: Fee,
 ) -> Result<(usize, usize), Error> {
-	let (_, tx_raw, _) = sign_tx(key, chain_id, account_info, vec![], fee)?;
+	let (_, tx_raw, _) = sign_tx(signer, chain_id, account_info, vec![], fee)?;
 
 	let total_len = tx_raw.encoded_len();
 	let body_bytes_len = tx_raw.body_bytes.len();
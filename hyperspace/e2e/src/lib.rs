@@ -0,0 +1,112 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end tests of hyperspace against real nodes. A [`Network`] of a relay chain and two
+//! parachains is launched in docker containers by the test itself, the relayer signs with a fresh
+//! key funded by the dev accounts of the parachains, and the containers are removed when the test
+//! ends, whether it passes or not.
+
+pub mod network;
+
+use hyperspace_core::substrate::DefaultConfig;
+use hyperspace_parachain::{ParachainClient, ParachainClientConfig};
+use hyperspace_primitives::utils::create_clients;
+use hyperspace_testsuite::setup::{self, wait_for_parachains};
+use network::Network;
+use sp_core::{
+	crypto::{AccountId32, Pair as _},
+	sr25519,
+};
+use subxt::dynamic::Value;
+
+pub type Client = ParachainClient<DefaultConfig>;
+
+/// Prefunded account of the dev parachains.
+const FUNDER: &str = "//Alice";
+
+/// Native tokens given to the relayer on each parachain, out of the 2^60 of the dev accounts.
+pub const RELAYER_FUNDS: u128 = 1 << 50;
+
+/// A fresh sr25519 key, returns its secret phrase and its account.
+pub fn generate_key() -> (String, AccountId32) {
+	let (pair, phrase, _) = sr25519::Pair::generate_with_phrase(None);
+	(phrase, pair.public().into())
+}
+
+/// Config of the client of the parachain `para_id` of `network`, signing with `private_key`.
+pub fn parachain_config(
+	network: &Network<'_>,
+	para_id: u32,
+	private_key: &str,
+) -> ParachainClientConfig {
+	setup::parachain_config(
+		&format!("parachain-{para_id}"),
+		para_id,
+		network
+			.parachain_url(para_id)
+			.unwrap_or_else(|| panic!("Parachain {para_id} isn't part of the network")),
+		network.relay_chain_url(),
+		"ibc/",
+		private_key,
+	)
+}
+
+/// Transfers `amount` native tokens to `account` on the parachain of `config`, from the
+/// prefunded dev account.
+pub async fn fund(
+	config: &ParachainClientConfig,
+	account: &AccountId32,
+	amount: u128,
+) -> anyhow::Result<()> {
+	let funder =
+		Client::new(ParachainClientConfig { private_key: FUNDER.to_string(), ..config.clone() })
+			.await?;
+	let recipient = account.clone();
+	funder
+		.submit_call(move || {
+			subxt::dynamic::tx(
+				"Balances",
+				"transfer",
+				vec![
+					Value::unnamed_variant("Id", [Value::from_bytes(&recipient)]),
+					Value::u128(amount),
+				],
+			)
+		})
+		.await?;
+	log::info!(target: "hyperspace", "Funded {account} with {amount} on {}", config.name);
+	Ok(())
+}
+
+/// Clients of both parachains of `network`, signing with a fresh key funded on both, with a light
+/// client of each other.
+pub async fn setup_clients(network: &Network<'_>) -> anyhow::Result<(Client, Client)> {
+	let (private_key, account) = generate_key();
+	let [para_a, para_b] = network.para_ids();
+	let config_a = parachain_config(network, para_a, &private_key);
+	let config_b = parachain_config(network, para_b, &private_key);
+
+	let mut chain_a = Client::new(config_a.clone()).await?;
+	let mut chain_b = Client::new(config_b.clone()).await?;
+	wait_for_parachains(&chain_a).await?;
+
+	fund(&config_a, &account, RELAYER_FUNDS).await?;
+	fund(&config_b, &account, RELAYER_FUNDS).await?;
+
+	let (client_a, client_b) = create_clients(&mut chain_a, &mut chain_b).await?;
+	log::info!(target: "hyperspace", "Client IDs: {client_a}, {client_b}");
+	chain_a.set_client_id(client_a);
+	chain_b.set_client_id(client_b);
+	Ok((chain_a, chain_b))
+}
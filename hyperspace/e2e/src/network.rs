@@ -0,0 +1,170 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A relay chain and two parachains, every node in its own container. The nodes find each other
+//! on a docker network of their own, the chain specs are the ones of `scripts/parachain-launch`,
+//! whose relay chain genesis registers both parachains.
+
+use std::path::PathBuf;
+use testcontainers::{
+	clients::Cli, core::WaitFor, images::generic::GenericImage, Container, RunnableImage,
+};
+
+/// Port of the RPC server of every node.
+const RPC_PORT: u16 = 9944;
+
+/// Logged by a node once its RPC server accepts connections. Collators log it for their embedded
+/// relay chain node first, prefixed by `[Relaychain]`.
+const RPC_READY: &str = "Running JSON-RPC";
+
+/// Validators of the relay chain, enough to validate the blocks of both parachains.
+const VALIDATORS: [&str; 4] = ["alice", "bob", "charlie", "dave"];
+
+#[derive(Debug, Clone)]
+pub struct NetworkConfig {
+	/// Name and tag of the image of the relay chain nodes.
+	pub relay_chain_image: (String, String),
+	/// Name and tag of the image of the collators, built by
+	/// `scripts/build-parachain-node-docker.sh`.
+	pub parachain_image: (String, String),
+	/// Directory of the chain specs, mounted in every container.
+	pub chain_specs: PathBuf,
+	/// Ids of the parachains, the chain spec of each is `dev-{id}-{id}.json`.
+	pub para_ids: [u32; 2],
+}
+
+impl Default for NetworkConfig {
+	fn default() -> Self {
+		Self {
+			relay_chain_image: ("parity/polkadot".to_string(), "v0.9.43".to_string()),
+			parachain_image: ("parachain-node".to_string(), "latest".to_string()),
+			chain_specs: PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+				.join("../../scripts/parachain-launch"),
+			para_ids: [2000, 2001],
+		}
+	}
+}
+
+/// The containers of the network, which are removed when dropped.
+pub struct Network<'d> {
+	para_ids: [u32; 2],
+	validators: Vec<Container<'d, GenericImage>>,
+	collators: Vec<(u32, Container<'d, GenericImage>)>,
+}
+
+impl<'d> Network<'d> {
+	/// Starts the validators of the relay chain, then a collator of each parachain. Returns once
+	/// the RPC servers of all the nodes are up, the parachains only start producing blocks once
+	/// they're onboarded by the relay chain.
+	pub fn launch(docker: &'d Cli, config: &NetworkConfig) -> Self {
+		let network = format!("hyperspace-e2e-{}", std::process::id());
+		let chain_specs = config.chain_specs.canonicalize().unwrap_or_else(|e| {
+			panic!("Chain specs not found at {}: {e}", config.chain_specs.display())
+		});
+		let run = |image: &(String, String), name: String, ready: String, args: Vec<String>| {
+			let image = GenericImage::new(image.0.clone(), image.1.clone())
+				.with_volume(chain_specs.to_string_lossy(), "/app")
+				.with_exposed_port(RPC_PORT)
+				.with_wait_for(WaitFor::message_on_stderr(ready));
+			log::info!(target: "hyperspace", "Starting {name}");
+			docker.run(
+				RunnableImage::from((image, args))
+					.with_container_name(format!("{network}-{name}"))
+					.with_network(network.clone()),
+			)
+		};
+
+		let validators = VALIDATORS
+			.iter()
+			.map(|validator| {
+				let args: &[&str] = &[
+					"--chain=/app/rococo-local.json",
+					"--tmp",
+					"--validator",
+					&format!("--{validator}"),
+					&format!("--name=relaychain-{validator}"),
+					"--rpc-external",
+					"--rpc-cors=all",
+					"--rpc-methods=unsafe",
+					&format!("--rpc-port={RPC_PORT}"),
+					"--beefy",
+					"--enable-offchain-indexing=true",
+					"--pruning=archive",
+				];
+				run(
+					&config.relay_chain_image,
+					format!("relaychain-{validator}"),
+					RPC_READY.to_string(),
+					args.iter().map(ToString::to_string).collect(),
+				)
+			})
+			.collect();
+
+		let collators = config
+			.para_ids
+			.iter()
+			.map(|&para_id| {
+				let args: &[&str] = &[
+					&format!("--chain=/app/dev-{para_id}-{para_id}.json"),
+					"--tmp",
+					"--collator",
+					"--alice",
+					"--force-authoring",
+					&format!("--name=parachain-{para_id}"),
+					"--rpc-external",
+					"--rpc-cors=all",
+					"--rpc-methods=unsafe",
+					&format!("--rpc-port={RPC_PORT}"),
+					"--enable-offchain-indexing=true",
+					"--pruning=archive",
+					"--execution=wasm",
+					"--log=pallet_ibc=trace,grandpa-verifier=trace,runtime=trace",
+					"--",
+					"--chain=/app/rococo-local.json",
+					"--execution=wasm",
+				];
+				let container = run(
+					&config.parachain_image,
+					format!("parachain-{para_id}"),
+					format!("[Parachain] {RPC_READY}"),
+					args.iter().map(ToString::to_string).collect(),
+				);
+				(para_id, container)
+			})
+			.collect();
+
+		Self { para_ids: config.para_ids, validators, collators }
+	}
+
+	pub fn para_ids(&self) -> [u32; 2] {
+		self.para_ids
+	}
+
+	/// Websocket url of the first validator of the relay chain.
+	pub fn relay_chain_url(&self) -> String {
+		ws_url(&self.validators[0])
+	}
+
+	/// Websocket url of the collator of the parachain `para_id`.
+	pub fn parachain_url(&self, para_id: u32) -> Option<String> {
+		self.collators
+			.iter()
+			.find(|(id, _)| *id == para_id)
+			.map(|(_, container)| ws_url(container))
+	}
+}
+
+fn ws_url(container: &Container<'_, GenericImage>) -> String {
+	format!("ws://127.0.0.1:{}", container.get_host_port_ipv4(RPC_PORT))
+}
@@ -0,0 +1,56 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use hyperspace_core::logging;
+use hyperspace_e2e::{
+	network::{Network, NetworkConfig},
+	setup_clients,
+};
+use hyperspace_primitives::IbcQueryProvider;
+use hyperspace_testsuite::{ibc_messaging_with_connection_delay, setup_connection_and_channel};
+use ibc::core::ics24_host::identifier::PortId;
+use std::time::Duration;
+use testcontainers::clients::Cli;
+
+const CONNECTION_DELAY: Duration = Duration::from_secs(60);
+
+/// The native token of the parachains.
+const NATIVE_ASSET: u128 = 1;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "launches docker containers, see the README"]
+async fn transfers_between_parachains_are_relayed() {
+	logging::setup_logging();
+	let docker = Cli::default();
+	let network = Network::launch(&docker, &NetworkConfig::default());
+	let (mut chain_a, mut chain_b) = setup_clients(&network).await.unwrap();
+
+	let (handle, channel_a, channel_b, connection_id_a, connection_id_b) =
+		setup_connection_and_channel(&mut chain_a, &mut chain_b, CONNECTION_DELAY).await;
+	handle.abort();
+	chain_a.set_connection_id(connection_id_a);
+	chain_b.set_connection_id(connection_id_b);
+	chain_a.set_channel_whitelist([(channel_a, PortId::transfer())].into_iter().collect());
+	chain_b.set_channel_whitelist([(channel_b, PortId::transfer())].into_iter().collect());
+
+	ibc_messaging_with_connection_delay(
+		&mut chain_a,
+		&mut chain_b,
+		NATIVE_ASSET,
+		NATIVE_ASSET,
+		channel_a,
+		channel_b,
+	)
+	.await;
+}
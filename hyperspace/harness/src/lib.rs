@@ -0,0 +1,261 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runs the full relay loop, the one `hyperspace relay` runs, between two mock chains wrapped in
+//! [`AnyChain`], all in the test process. Integration tests of the relayer can run wherever
+//! `cargo test` does, without docker or nodes.
+
+use anyhow::anyhow;
+use hyperspace_core::{
+	chain::{AnyChain, CoreConfig},
+	chaos::ChaosConfig,
+	handshake::{pending_handshakes, PendingHandshake},
+	shutdown::Shutdown,
+};
+use ibc::core::{
+	ics03_connection::connection::State as ConnectionState,
+	ics04_channel::channel::State as ChannelState,
+	ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+};
+use mock::{finality::FinalityScript, MockChain, MockConfig};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// How often the delivery of the packets and the handshakes are checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Two mock chains producing blocks and relayed by the relay loop until dropped.
+pub struct TwoChains {
+	pub chain_a: AnyChain,
+	pub chain_b: AnyChain,
+	mock_a: MockChain,
+	mock_b: MockChain,
+	relay: JoinHandle<anyhow::Result<()>>,
+	block_production: [JoinHandle<()>; 2],
+}
+
+impl TwoChains {
+	/// Starts two chains named `mock-a` and `mock-b` with the default mock config.
+	pub fn start() -> Self {
+		Self::start_with(MockConfig::new("mock-a", 2000), MockConfig::new("mock-b", 2001))
+	}
+
+	/// Starts producing blocks on both chains and relaying between them.
+	pub fn start_with(config_a: MockConfig, config_b: MockConfig) -> Self {
+		Self::spawn(config_a, config_b, None, None)
+	}
+
+	/// Starts two chains with the default mock config, the calls the relayer makes to them are
+	/// faulted as configured, see [`hyperspace_core::chaos`].
+	pub fn start_with_chaos(chaos: ChaosConfig) -> Self {
+		Self::spawn(
+			MockConfig::new("mock-a", 2000),
+			MockConfig::new("mock-b", 2001),
+			None,
+			Some(chaos),
+		)
+	}
+
+	/// Starts two chains with the default mock config, chain A sends the finality notifications of
+	/// `script` instead of one per block, chain B produces blocks normally.
+	pub fn start_with_finality_script(script: FinalityScript) -> Self {
+		Self::spawn(
+			MockConfig::new("mock-a", 2000),
+			MockConfig::new("mock-b", 2001),
+			Some(script),
+			None,
+		)
+	}
+
+	fn spawn(
+		config_a: MockConfig,
+		config_b: MockConfig,
+		script_a: Option<FinalityScript>,
+		chaos: Option<ChaosConfig>,
+	) -> Self {
+		let (mock_a, mock_b) = MockChain::pair(config_a, config_b);
+		let core = CoreConfig { chaos, ..Default::default() };
+		let (chain_a, chain_b) = core
+			.apply_chaos(AnyChain::Mock(mock_a.clone()), AnyChain::Mock(mock_b.clone()))
+			.expect("the faults injected are valid");
+		let relay = spawn_relay(&chain_a, &chain_b);
+		let block_production_a = match script_a {
+			Some(script) => mock_a.spawn_finality_script(script),
+			None => mock_a.spawn_block_production(),
+		};
+		let block_production = [block_production_a, mock_b.spawn_block_production()];
+		Self { chain_a, chain_b, mock_a, mock_b, relay, block_production }
+	}
+
+	pub fn mock_a(&self) -> &MockChain {
+		&self.mock_a
+	}
+
+	pub fn mock_b(&self) -> &MockChain {
+		&self.mock_b
+	}
+
+	/// Sends `count` packets from chain A to chain B, returns their sequences.
+	pub fn send_from_a(&self, count: usize) -> Vec<u64> {
+		self.mock_a.send_transfers(count)
+	}
+
+	/// Sends `count` packets from chain B to chain A, returns their sequences.
+	pub fn send_from_b(&self, count: usize) -> Vec<u64> {
+		self.mock_b.send_transfers(count)
+	}
+
+	/// Aborts the relay loop, as if the relayer crashed. The chains keep producing blocks.
+	pub fn stop_relayer(&mut self) {
+		self.relay.abort();
+	}
+
+	/// Starts a new relay loop, after [`TwoChains::stop_relayer`].
+	pub fn restart_relayer(&mut self) {
+		self.relay.abort();
+		self.relay = spawn_relay(&self.chain_a, &self.chain_b);
+	}
+
+	/// Updates the light clients on both chains past the blocks built so far, as another relayer
+	/// would, so that the relay loop never sees their events.
+	pub async fn skip_pending_events(&self, timeout: Duration) -> anyhow::Result<()> {
+		let heights = (self.mock_a.latest_height() + 1, self.mock_b.latest_height() + 1);
+		self.wait_for("the blocks being built to be produced", timeout, false, || {
+			(self.mock_a.latest_height() >= heights.0 && self.mock_b.latest_height() >= heights.1)
+				.then_some(())
+		})
+		.await?;
+		self.mock_b.update_client_of(&self.mock_a)?;
+		self.mock_a.update_client_of(&self.mock_b)?;
+		Ok(())
+	}
+
+	/// The handshakes chain B is missing a step of, which the relay loop resumes if they stall.
+	pub async fn pending_handshakes_from_a(&self) -> anyhow::Result<Vec<PendingHandshake>> {
+		pending_handshakes(&self.chain_a, &self.chain_b, self.mock_a.latest()).await
+	}
+
+	/// The handshakes chain A is missing a step of, which the relay loop resumes if they stall.
+	pub async fn pending_handshakes_from_b(&self) -> anyhow::Result<Vec<PendingHandshake>> {
+		pending_handshakes(&self.chain_b, &self.chain_a, self.mock_b.latest()).await
+	}
+
+	/// Waits until the ends of the connection `connection_id`, started on chain A, are in the
+	/// `states` of chain A and chain B, returns the identifier of the end on chain B.
+	pub async fn wait_for_connection(
+		&self,
+		connection_id: &ConnectionId,
+		states: (ConnectionState, ConnectionState),
+		timeout: Duration,
+	) -> anyhow::Result<ConnectionId> {
+		let what = format!("connection {connection_id} being {states:?}");
+		self.wait_for(&what, timeout, true, || {
+			let end_a = self.mock_a.connections().remove(connection_id)?;
+			let (id_b, end_b) = self
+				.mock_b
+				.connections()
+				.into_iter()
+				.find(|(_, end)| end.counterparty().connection_id() == Some(connection_id))?;
+			(*end_a.state() == states.0 && *end_b.state() == states.1).then_some(id_b)
+		})
+		.await
+	}
+
+	/// Waits until the ends of the channel `channel_id` on the transfer port, started on chain A,
+	/// are in the `states` of chain A and chain B, returns the identifier of the end on chain B.
+	pub async fn wait_for_channel(
+		&self,
+		channel_id: ChannelId,
+		states: (ChannelState, ChannelState),
+		timeout: Duration,
+	) -> anyhow::Result<ChannelId> {
+		let what = format!("channel {channel_id} being {states:?}");
+		self.wait_for(&what, timeout, true, || {
+			let end_a = self.mock_a.channels().remove(&(PortId::transfer(), channel_id))?;
+			let ((_, id_b), end_b) = self.mock_b.channels().into_iter().find(|(_, end)| {
+				end.counterparty().port_id() == &PortId::transfer() &&
+					end.counterparty().channel_id() == Some(&channel_id)
+			})?;
+			(*end_a.state() == states.0 && *end_b.state() == states.1).then_some(id_b)
+		})
+		.await
+	}
+
+	/// Polls `check` until it returns a value, fails after `timeout`, or if the relay loop stopped
+	/// while `relaying`.
+	async fn wait_for<T>(
+		&self,
+		what: &str,
+		timeout: Duration,
+		relaying: bool,
+		mut check: impl FnMut() -> Option<T>,
+	) -> anyhow::Result<T> {
+		let start = Instant::now();
+		loop {
+			if let Some(value) = check() {
+				return Ok(value)
+			}
+			if relaying && self.relay.is_finished() {
+				return Err(anyhow!("The relay loop stopped before {what}"))
+			}
+			if start.elapsed() > timeout {
+				return Err(anyhow!("Still waiting for {what} after {timeout:?}"))
+			}
+			tokio::time::sleep(POLL_INTERVAL).await;
+		}
+	}
+
+	/// Waits until the packets sent on both chains are received by the counterparty and
+	/// acknowledged, fails with the packets still pending after `timeout` or if the relay loop
+	/// stopped.
+	pub async fn wait_for_delivery(&self, timeout: Duration) -> anyhow::Result<()> {
+		let start = Instant::now();
+		loop {
+			let (pending_a, pending_b) =
+				(self.mock_a.pending_packets(), self.mock_b.pending_packets());
+			if pending_a.is_empty() && pending_b.is_empty() {
+				return Ok(())
+			}
+			if self.relay.is_finished() {
+				return Err(anyhow!("The relay loop stopped with packets pending"))
+			}
+			if start.elapsed() > timeout {
+				return Err(anyhow!(
+					"Packets still not acknowledged after {timeout:?}, from A: {pending_a:?}, from B: {pending_b:?}"
+				))
+			}
+			tokio::time::sleep(POLL_INTERVAL).await;
+		}
+	}
+}
+
+fn spawn_relay(chain_a: &AnyChain, chain_b: &AnyChain) -> JoinHandle<anyhow::Result<()>> {
+	tokio::spawn(hyperspace_core::relay(
+		chain_a.clone(),
+		chain_b.clone(),
+		None,
+		None,
+		None,
+		Shutdown::default(),
+	))
+}
+
+impl Drop for TwoChains {
+	fn drop(&mut self) {
+		self.relay.abort();
+		for task in &self.block_production {
+			task.abort();
+		}
+	}
+}
@@ -0,0 +1,52 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The relay loop catches up a light client that fell behind through the submit stage, and keeps
+//! relaying when it can't check whether it's behind.
+
+use hyperspace_harness::TwoChains;
+use mock::SyncCheck;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[tokio::test(flavor = "multi_thread")]
+async fn lagging_light_client_is_caught_up_by_the_submit_stage() {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start();
+	chains.mock_a().set_sync_check(SyncCheck::LatestBlock);
+	let sequences = chains.send_from_a(5);
+
+	chains.wait_for_delivery(TIMEOUT).await.unwrap();
+	assert_eq!(chains.mock_b().received_packets(), sequences);
+	let queries = chains.mock_a().query_counts().snapshot();
+	assert!(queries.get("fetch_mandatory_updates").copied().unwrap_or_default() > 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failing_sync_checks_dont_stop_the_relay() {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start();
+	chains.mock_a().set_sync_check(SyncCheck::Failing);
+	chains.mock_b().set_sync_check(SyncCheck::Failing);
+	let from_a = chains.send_from_a(3);
+	let from_b = chains.send_from_b(3);
+
+	chains.wait_for_delivery(TIMEOUT).await.unwrap();
+	assert_eq!(chains.mock_b().received_packets(), from_a);
+	assert_eq!(chains.mock_a().received_packets(), from_b);
+	let queries = chains.mock_a().query_counts().snapshot();
+	assert!(queries.get("is_synced").copied().unwrap_or_default() > 0);
+	assert_eq!(queries.get("fetch_mandatory_updates"), None);
+}
@@ -0,0 +1,57 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The relay loop recovers from the faults injected in the calls it makes to the chains.
+
+use hyperspace_core::chaos::{ChaosConfig, Fault, Faults};
+use hyperspace_harness::TwoChains;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+fn chaos(seed: u64) -> ChaosConfig {
+	ChaosConfig {
+		seed,
+		delay_probability: 0.2,
+		max_delay_ms: 50,
+		error_probability: 0.1,
+		drop_probability: 0.1,
+	}
+}
+
+#[test]
+fn faults_are_determined_by_the_seed() {
+	let draw = |seed| {
+		let faults = Faults::new("mock-a", chaos(seed));
+		(0..100).map(|_| faults.draw()).collect::<Vec<_>>()
+	};
+	let faults = draw(7);
+	assert_eq!(faults, draw(7));
+	assert_ne!(faults, draw(8));
+	assert!(faults.contains(&Some(Fault::Error)));
+	assert!(faults.contains(&Some(Fault::Drop)));
+	assert!(faults.contains(&None));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn packets_are_delivered_despite_faults() {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start_with_chaos(chaos(42));
+	let from_a = chains.send_from_a(10);
+	let from_b = chains.send_from_b(10);
+
+	chains.wait_for_delivery(TIMEOUT).await.unwrap();
+	assert_eq!(chains.mock_b().received_packets(), from_a);
+	assert_eq!(chains.mock_a().received_packets(), from_b);
+}
@@ -0,0 +1,55 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The relay loop delivers the packets of a chain whatever order its finality is notified in.
+
+use hyperspace_harness::TwoChains;
+use mock::finality::FinalityScript;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+async fn packets_are_delivered_with(script: FinalityScript) {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start_with_finality_script(script);
+	let sequences = chains.send_from_a(10);
+
+	chains.wait_for_delivery(TIMEOUT).await.unwrap();
+	assert_eq!(chains.mock_b().received_packets(), sequences);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn packets_are_delivered_with_normal_finality() {
+	packets_are_delivered_with(FinalityScript::normal(10)).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn packets_are_delivered_with_reordered_finality() {
+	packets_are_delivered_with(FinalityScript::reordered(10, 3)).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn packets_are_delivered_with_gapped_finality() {
+	packets_are_delivered_with(FinalityScript::gapped(10, 4)).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn packets_are_delivered_with_duplicate_finality() {
+	packets_are_delivered_with(FinalityScript::duplicated(10)).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn packets_are_delivered_when_only_a_late_block_is_notified() {
+	packets_are_delivered_with(FinalityScript::new([10, 1, 10])).await
+}
@@ -0,0 +1,155 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use hyperspace_core::handshake::{EndState, HandshakeEnd, HandshakeState, HandshakeStep};
+use hyperspace_harness::TwoChains;
+use ibc::core::{
+	ics03_connection::{
+		connection::State as ConnectionState,
+		msgs::{conn_open_ack, conn_open_try},
+	},
+	ics04_channel::{
+		channel::State as ChannelState,
+		msgs::{chan_open_ack, chan_open_try},
+	},
+	ics24_host::identifier::ConnectionId,
+};
+use mock::ledger::Fault;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+const OPEN: (ConnectionState, ConnectionState) = (ConnectionState::Open, ConnectionState::Open);
+
+#[test]
+fn next_step_follows_both_ends() {
+	let state = |source, sink| HandshakeState { source, sink };
+	assert_eq!(state(EndState::Init, None).next_step(), Some(HandshakeStep::OpenTry));
+	assert_eq!(
+		state(EndState::TryOpen, Some(EndState::Init)).next_step(),
+		Some(HandshakeStep::OpenAck)
+	);
+	assert_eq!(
+		state(EndState::Open, Some(EndState::TryOpen)).next_step(),
+		Some(HandshakeStep::OpenConfirm)
+	);
+	// the steps of the other direction, or none left
+	assert_eq!(state(EndState::Init, Some(EndState::TryOpen)).next_step(), None);
+	assert_eq!(state(EndState::TryOpen, Some(EndState::Open)).next_step(), None);
+	assert_eq!(state(EndState::Closed, Some(EndState::Open)).next_step(), None);
+	assert!(state(EndState::Open, Some(EndState::Open)).is_open());
+	assert_eq!(state(EndState::Open, Some(EndState::Open)).next_step(), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn connection_and_channel_handshakes_complete() {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start();
+	let connection_id = chains.mock_a().open_connection().unwrap();
+	chains.wait_for_connection(&connection_id, OPEN, TIMEOUT).await.unwrap();
+
+	let channel_id = chains.mock_a().open_channel(connection_id).unwrap();
+	chains
+		.wait_for_channel(channel_id, (ChannelState::Open, ChannelState::Open), TIMEOUT)
+		.await
+		.unwrap();
+	assert!(chains.pending_handshakes_from_a().await.unwrap().is_empty());
+	assert!(chains.pending_handshakes_from_b().await.unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn connection_try_with_invalid_proof_is_retried() {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start();
+	chains.mock_b().inject_fault(conn_open_try::TYPE_URL, Fault::InvalidProof, 2);
+
+	let connection_id = chains.mock_a().open_connection().unwrap();
+	chains.wait_for_connection(&connection_id, OPEN, TIMEOUT).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn channel_ack_with_invalid_proof_is_retried() {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start();
+	chains.mock_a().inject_fault(chan_open_ack::TYPE_URL, Fault::InvalidProof, 2);
+
+	let channel_id = chains.mock_a().open_channel(ConnectionId::new(0)).unwrap();
+	chains
+		.wait_for_channel(channel_id, (ChannelState::Open, ChannelState::Open), TIMEOUT)
+		.await
+		.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn channel_try_dropped_by_a_node_restart_is_submitted_again() {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start();
+	chains.mock_b().inject_fault(chan_open_try::TYPE_URL, Fault::Dropped, 1);
+
+	let channel_id = chains.mock_a().open_channel(ConnectionId::new(0)).unwrap();
+	chains
+		.wait_for_channel(channel_id, (ChannelState::Open, ChannelState::Open), TIMEOUT)
+		.await
+		.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn handshake_resumes_after_a_restart_past_the_init() {
+	let _ = env_logger::try_init();
+	let mut chains = TwoChains::start();
+	chains.stop_relayer();
+	let connection_id = chains.mock_a().open_connection().unwrap();
+	chains.skip_pending_events(TIMEOUT).await.unwrap();
+
+	let pending = chains.pending_handshakes_from_a().await.unwrap();
+	assert_eq!(pending.len(), 1);
+	assert_eq!(pending[0].end, HandshakeEnd::Connection(connection_id.clone()));
+	assert_eq!(pending[0].step, HandshakeStep::OpenTry);
+
+	chains.restart_relayer();
+	chains.wait_for_connection(&connection_id, OPEN, TIMEOUT).await.unwrap();
+	assert!(chains.pending_handshakes_from_a().await.unwrap().is_empty());
+	assert!(chains.pending_handshakes_from_b().await.unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn handshake_resumes_after_a_restart_between_try_and_ack() {
+	let _ = env_logger::try_init();
+	let mut chains = TwoChains::start();
+	// the ack is rejected until the relayer restarts, by then its event was skipped
+	chains
+		.mock_a()
+		.inject_fault(conn_open_ack::TYPE_URL, Fault::InvalidProof, usize::MAX);
+	let connection_id = chains.mock_a().open_connection().unwrap();
+	let connection_id_b = chains
+		.wait_for_connection(
+			&connection_id,
+			(ConnectionState::Init, ConnectionState::TryOpen),
+			TIMEOUT,
+		)
+		.await
+		.unwrap();
+
+	chains.stop_relayer();
+	chains.skip_pending_events(TIMEOUT).await.unwrap();
+	chains.mock_a().inject_fault(conn_open_ack::TYPE_URL, Fault::InvalidProof, 0);
+	assert!(chains.pending_handshakes_from_a().await.unwrap().is_empty());
+	let pending = chains.pending_handshakes_from_b().await.unwrap();
+	assert_eq!(pending.len(), 1);
+	assert_eq!(pending[0].end, HandshakeEnd::Connection(connection_id_b));
+	assert_eq!(pending[0].step, HandshakeStep::OpenAck);
+
+	chains.restart_relayer();
+	chains.wait_for_connection(&connection_id, OPEN, TIMEOUT).await.unwrap();
+}
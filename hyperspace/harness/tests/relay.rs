@@ -0,0 +1,66 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use hyperspace_harness::TwoChains;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[tokio::test(flavor = "multi_thread")]
+async fn packets_are_received_and_acknowledged() {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start();
+	let sequences = chains.send_from_a(10);
+
+	chains.wait_for_delivery(TIMEOUT).await.unwrap();
+	assert_eq!(chains.mock_b().received_packets(), sequences);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn packets_are_relayed_in_both_directions() {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start();
+	let from_a = chains.send_from_a(5);
+	let from_b = chains.send_from_b(3);
+
+	chains.wait_for_delivery(TIMEOUT).await.unwrap();
+	assert_eq!(chains.mock_b().received_packets(), from_a);
+	assert_eq!(chains.mock_a().received_packets(), from_b);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn packets_sent_over_many_blocks_are_all_delivered() {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start();
+	let mut sequences = vec![];
+	for _ in 0..5 {
+		sequences.extend(chains.send_from_a(20));
+		tokio::time::sleep(Duration::from_millis(150)).await;
+	}
+
+	chains.wait_for_delivery(TIMEOUT).await.unwrap();
+	assert_eq!(chains.mock_b().received_packets(), sequences);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn expired_packets_are_timed_out() {
+	let _ = env_logger::try_init();
+	let chains = TwoChains::start();
+	let delivered = chains.send_from_a(3);
+	let expired = chains.mock_a().send_expired_packet();
+
+	chains.wait_for_delivery(TIMEOUT).await.unwrap();
+	assert_eq!(chains.mock_b().received_packets(), delivered);
+	assert!(!chains.mock_b().received_packets().contains(&expired));
+}
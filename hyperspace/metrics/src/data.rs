@@ -22,9 +22,9 @@ use std::collections::HashMap;
 pub struct MetricsLink(Option<Metrics>);
 
 impl MetricsLink {
-	pub fn new(prefix: &str, registry: Option<&Registry>) -> Self {
+	pub fn new(prefix: &str, chain_id: &str, registry: Option<&Registry>) -> Self {
 		Self(registry.and_then(|registry| {
-			Metrics::register(prefix, registry)
+			Metrics::register(prefix, chain_id, registry)
 				.map_err(|err| {
 					log::warn!("Failed to register proposer prometheus metrics: {}", err)
 				})
@@ -51,6 +51,7 @@ impl LightClientMetrics {
 	pub fn register(
 		client_id: &ClientId,
 		prefix: &str,
+		chain_id: &str,
 		registry: &Registry,
 	) -> Result<Self, PrometheusError> {
 		Ok(Self {
@@ -61,7 +62,8 @@ impl LightClientMetrics {
 						"The height of the last trusted state",
 					)
 					.const_label("client_id", client_id.to_string())
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -72,7 +74,8 @@ impl LightClientMetrics {
 						"The revision of the last trusted state",
 					)
 					.const_label("client_id", client_id.to_string())
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -83,7 +86,8 @@ impl LightClientMetrics {
 						"Total number of header updates received",
 					)
 					.const_label("client_id", client_id.to_string())
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -143,31 +147,45 @@ pub struct Metrics {
 
 	/// Latest processed height - helpful to prevent pushing the same event twice
 	pub latest_processed_height: Gauge<U64>,
+	/// Seconds left until the trusting period of the counterparty light client lapses.
+	pub light_client_time_until_expiry: Gauge<U64>,
+	/// RPC endpoint the client is connected to, set to 1 for the active endpoint.
+	pub active_rpc_endpoint: GaugeVec<U64>,
+	/// Number of sink blocks the ordered channels have been waiting for the same sequence.
+	pub ordered_channel_sequence_gap: GaugeVec<U64>,
 
 	/// Metrics prefix.
 	pub prefix: String,
+	/// Canonical identifier of the chain, the `chain_id` label of all the metrics.
+	pub chain_id: String,
 }
 
 impl Metrics {
-	pub fn register(prefix: &str, registry: &Registry) -> Result<Self, PrometheusError> {
+	pub fn register(
+		prefix: &str,
+		chain_id: &str,
+		registry: &Registry,
+	) -> Result<Self, PrometheusError> {
 		Ok(Self {
 			number_of_received_send_packets: register(
 				Counter::with_opts(
 					Opts::new(
-						format!("hyperspace_{prefix}_number_of_send_packet_events"),
+						format!("hyperspace_number_of_send_packet_events"),
 						"Total number of 'send packet' events.",
 					)
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
 			number_of_received_receive_packets: register(
 				Counter::with_opts(
 					Opts::new(
-						format!("hyperspace_{prefix}_number_of_receive_packet_events"),
+						format!("hyperspace_number_of_receive_packet_events"),
 						"Total number of 'receive packet' events.",
 					)
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -177,7 +195,8 @@ impl Metrics {
 						format!("hyperspace_number_of_acknowledge_packet_events"),
 						"Total number of 'acknowledge packet' events.",
 					)
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -187,7 +206,8 @@ impl Metrics {
 						format!("hyperspace_number_of_timeout_packet_events"),
 						"Total number of 'timeout packet' events.",
 					)
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -199,7 +219,8 @@ impl Metrics {
 						format!("hyperspace_number_of_sent_packets"),
 						"Total number of sent packets",
 					)
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -209,7 +230,8 @@ impl Metrics {
 						format!("hyperspace_number_of_sent_acknowledgments"),
 						"Total number of sent acknowledgments",
 					)
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -219,7 +241,8 @@ impl Metrics {
 						format!("hyperspace_number_of_timed_out_packets"),
 						"Total number of timed out packets",
 					)
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -229,7 +252,8 @@ impl Metrics {
 						format!("hyperspace_number_of_undelivered_packets"),
 						"Number of undelivered packets over time",
 					)
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -239,7 +263,8 @@ impl Metrics {
 						format!("hyperspace_number_of_undelivered_acknowledgements"),
 						"Number of undelivered acknowledgements over time",
 					)
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -250,7 +275,8 @@ impl Metrics {
 						"Gas cost for every sent tx bundle",
 					)
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -261,7 +287,8 @@ impl Metrics {
 						"Transaction length for every sent tx bundle",
 					)
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -273,7 +300,8 @@ impl Metrics {
 						"Time it takes to process a 'send packet' event",
 					)
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -284,7 +312,8 @@ impl Metrics {
 						"Time it takes to process a 'receive packet' event",
 					)
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -295,7 +324,8 @@ impl Metrics {
 						"Time it takes to process a 'acknowledge packet' event",
 					)
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -306,7 +336,8 @@ impl Metrics {
 						"Time it takes to send and receive a packet",
 					)
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -317,7 +348,8 @@ impl Metrics {
 						"Time it takes to send and receive an acknowledgment",
 					)
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -328,7 +360,8 @@ impl Metrics {
 						"Time it takes to send and receive a timeout packet",
 					)
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -339,7 +372,8 @@ impl Metrics {
 						"Average time between client updates",
 					)
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
 				)?,
 				registry,
 			)?,
@@ -349,11 +383,48 @@ impl Metrics {
 						format!("hyperspace_latest_processed_height"),
 						"Latest processed finalized height",
 					)
-					.const_label("name", prefix.to_string()),
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
+				)?,
+				registry,
+			)?,
+			light_client_time_until_expiry: register(
+				Gauge::with_opts(
+					Opts::new(
+						format!("hyperspace_light_client_time_until_expiry"),
+						"Seconds left until the light client trusting period lapses",
+					)
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
+				)?,
+				registry,
+			)?,
+			active_rpc_endpoint: register(
+				GaugeVec::new(
+					Opts::new(
+						format!("hyperspace_active_rpc_endpoint"),
+						"RPC endpoint the client is connected to",
+					)
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
+					&["url"],
+				)?,
+				registry,
+			)?,
+			ordered_channel_sequence_gap: register(
+				GaugeVec::new(
+					Opts::new(
+						format!("hyperspace_ordered_channel_sequence_gap_blocks"),
+						"Number of blocks the sink of an ordered channel has been waiting for the same sequence",
+					)
+					.const_label("name", prefix.to_string())
+					.const_label("chain_id", chain_id.to_string()),
+					&["channel"],
 				)?,
 				registry,
 			)?,
 			prefix: prefix.to_string(),
+			chain_id: chain_id.to_string(),
 		})
 	}
 
@@ -396,8 +467,12 @@ impl Metrics {
 				Ok(())
 			},
 			None => {
-				let light_client_metrics =
-					LightClientMetrics::register(client_id, &self.prefix, registry)?;
+				let light_client_metrics = LightClientMetrics::register(
+					client_id,
+					&self.prefix,
+					&self.chain_id,
+					registry,
+				)?;
 				self.light_client_height.insert(client_id.clone(), light_client_metrics);
 				Ok(())
 			},
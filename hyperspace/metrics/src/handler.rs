@@ -29,7 +29,7 @@ use std::{
 	collections::HashMap,
 	ops::DerefMut,
 	sync::{Arc, Mutex},
-	time::Instant,
+	time::{Duration, Instant},
 };
 
 #[derive(Eq, PartialEq, Hash)]
@@ -51,6 +51,8 @@ impl From<Packet> for PacketId {
 
 pub type PacketMap = Arc<Mutex<HashMap<PacketId, Instant>>>;
 
+/// Clones share the same registry and packet timings.
+#[derive(Clone)]
 pub struct MetricsHandler {
 	registry: Registry,
 	metrics: Metrics,
@@ -218,6 +220,34 @@ impl MetricsHandler {
 		}
 	}
 
+	pub async fn handle_client_expiry(&self, time_until_expiry: Duration) {
+		self.metrics.light_client_time_until_expiry.set(time_until_expiry.as_secs());
+	}
+
+	pub fn handle_active_rpc_endpoint(&self, url: &str) {
+		if self
+			.metrics
+			.active_rpc_endpoint
+			.get_metric_with_label_values(&[url])
+			.ok()
+			.map(|gauge| gauge.get()) ==
+			Some(1)
+		{
+			return
+		}
+		self.metrics.active_rpc_endpoint.reset();
+		self.metrics.active_rpc_endpoint.with_label_values(&[url]).set(1);
+	}
+
+	/// Records how many blocks the sink of the ordered `channel` has been waiting for the same
+	/// sequence.
+	pub fn handle_sequence_gap(&self, channel: &str, blocks: u64) {
+		self.metrics
+			.ordered_channel_sequence_gap
+			.with_label_values(&[channel])
+			.set(blocks);
+	}
+
 	pub async fn handle_transaction_costs(&self, batch_weight: u64, messages: &[Any]) {
 		let batch_size = messages.iter().map(|x| x.value.len()).sum::<usize>();
 		self.metrics.gas_cost_for_sent_tx_bundle.observe(batch_weight as f64);
@@ -0,0 +1,98 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Scripted finality notifications. Real chains don't always notify the finality of their blocks
+//! one by one and in order: notifications get lost, arrive late or twice. A [`FinalityScript`]
+//! fixes the sequence of notifications a mock chain sends, so that the handling of each of these
+//! cases by the relay loop can be tested deterministically, see
+//! [`MockChain::spawn_finality_script`](crate::MockChain::spawn_finality_script).
+
+/// Heights of the finality notifications a chain sends, relative to its height when the script
+/// starts: `1` is the first block produced by the script.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FinalityScript {
+	offsets: Vec<u64>,
+}
+
+impl FinalityScript {
+	/// Notifies the given blocks, in the given order.
+	pub fn new(offsets: impl IntoIterator<Item = u64>) -> Self {
+		Self { offsets: offsets.into_iter().collect() }
+	}
+
+	/// Notifies each of the next `blocks` blocks once, in order.
+	pub fn normal(blocks: u64) -> Self {
+		Self::new(1..=blocks)
+	}
+
+	/// Notifies each of the next `blocks` blocks once, every `window` consecutive blocks in
+	/// reverse order, e.g. `2, 1, 4, 3` for windows of 2.
+	pub fn reordered(blocks: u64, window: u64) -> Self {
+		let window = window.max(1);
+		Self::new((0..blocks).step_by(window as usize).flat_map(move |start| {
+			let end = (start + window).min(blocks);
+			(start + 1..=end).rev()
+		}))
+	}
+
+	/// Notifies only every `stride`-th of the next `blocks` blocks, and the last one, e.g.
+	/// `3, 6, 7` for 7 blocks and a stride of 3.
+	pub fn gapped(blocks: u64, stride: u64) -> Self {
+		let stride = stride.max(1);
+		let mut offsets = (stride..=blocks).step_by(stride as usize).collect::<Vec<_>>();
+		if blocks > 0 && offsets.last() != Some(&blocks) {
+			offsets.push(blocks);
+		}
+		Self { offsets }
+	}
+
+	/// Notifies each of the next `blocks` blocks twice in a row.
+	pub fn duplicated(blocks: u64) -> Self {
+		Self::new((1..=blocks).flat_map(|offset| [offset, offset]))
+	}
+
+	/// Plays `next` once this script is done, its blocks following the blocks of this script.
+	pub fn then(mut self, next: Self) -> Self {
+		let blocks = self.blocks();
+		self.offsets.extend(next.offsets.into_iter().map(|offset| blocks + offset));
+		self
+	}
+
+	/// Blocks notified, in order.
+	pub fn offsets(&self) -> &[u64] {
+		&self.offsets
+	}
+
+	/// Number of blocks the script produces.
+	pub fn blocks(&self) -> u64 {
+		self.offsets.iter().copied().max().unwrap_or_default()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn scripts_notify_the_expected_blocks() {
+		assert_eq!(FinalityScript::normal(3).offsets(), [1, 2, 3]);
+		assert_eq!(FinalityScript::reordered(5, 2).offsets(), [2, 1, 4, 3, 5]);
+		assert_eq!(FinalityScript::gapped(7, 3).offsets(), [3, 6, 7]);
+		assert_eq!(FinalityScript::gapped(6, 3).offsets(), [3, 6]);
+		assert_eq!(FinalityScript::duplicated(2).offsets(), [1, 1, 2, 2]);
+		let script = FinalityScript::normal(2).then(FinalityScript::reordered(2, 2));
+		assert_eq!(script.offsets(), [1, 2, 4, 3]);
+		assert_eq!(script.blocks(), 4);
+	}
+}
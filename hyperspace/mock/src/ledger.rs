@@ -0,0 +1,670 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! State of a mock chain: its blocks, its connections and channels, the packets of its channel and
+//! the light client of the counterparty it hosts. Queries are answered from the latest state
+//! whatever height they are made at, which is enough to relay between mock chains but not to test
+//! historical queries.
+
+use ibc::{
+	core::{
+		ics02_client::msgs::update_client,
+		ics03_connection::{
+			connection::{
+				ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+			},
+			events::{
+				Attributes as ConnectionAttributes, OpenAck as ConnectionOpenAck,
+				OpenConfirm as ConnectionOpenConfirm, OpenInit as ConnectionOpenInit,
+				OpenTry as ConnectionOpenTry,
+			},
+			msgs::{
+				conn_open_ack,
+				conn_open_confirm::{self, MsgConnectionOpenConfirm},
+				conn_open_init::{self, MsgConnectionOpenInit},
+				conn_open_try,
+			},
+			version::get_compatible_versions,
+		},
+		ics04_channel::{
+			channel::{ChannelEnd, State as ChannelState},
+			events::{
+				AcknowledgePacket, OpenAck as ChannelOpenAck, OpenConfirm as ChannelOpenConfirm,
+				OpenInit as ChannelOpenInit, OpenTry as ChannelOpenTry, ReceivePacket, SendPacket,
+				TimeoutPacket, WriteAcknowledgement,
+			},
+			msgs::{
+				acknowledgement::{self, MsgAcknowledgement},
+				chan_open_ack::{self, MsgChannelOpenAck},
+				chan_open_confirm::{self, MsgChannelOpenConfirm},
+				chan_open_init::{self, MsgChannelOpenInit},
+				chan_open_try::{self, MsgChannelOpenTry},
+				recv_packet::{self, MsgRecvPacket},
+				timeout::{self, MsgTimeout},
+			},
+			packet::Packet,
+		},
+		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+	},
+	events::IbcEvent,
+	Height,
+};
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::core::{
+		channel::v1::{
+			MsgAcknowledgement as RawMsgAcknowledgement, MsgChannelOpenAck as RawMsgChannelOpenAck,
+			MsgChannelOpenConfirm as RawMsgChannelOpenConfirm,
+			MsgChannelOpenInit as RawMsgChannelOpenInit, MsgChannelOpenTry as RawMsgChannelOpenTry,
+			MsgRecvPacket as RawMsgRecvPacket, MsgTimeout as RawMsgTimeout,
+		},
+		client::v1::MsgUpdateClient as RawMsgUpdateClient,
+		connection::v1::{
+			MsgConnectionOpenAck as RawMsgConnectionOpenAck,
+			MsgConnectionOpenConfirm as RawMsgConnectionOpenConfirm,
+			MsgConnectionOpenInit as RawMsgConnectionOpenInit,
+			MsgConnectionOpenTry as RawMsgConnectionOpenTry,
+		},
+	},
+};
+use prost::Message;
+use std::{
+	collections::{BTreeMap, BTreeSet, HashSet},
+	str::FromStr,
+	time::Duration,
+};
+
+/// Acknowledgement written for every received packet, a successful ICS-20 result.
+pub const SUCCESS_ACK: &[u8] = br#"{"result":"AQ=="}"#;
+
+/// Type url of the client messages of mock chains.
+pub const HEADER_TYPE_URL: &str = "/hyperspace.mock.Header";
+
+/// Encodes the header proving the block of a mock chain at `height`.
+pub fn encode_header(height: u64, timestamp: u64) -> Any {
+	let mut value = height.to_be_bytes().to_vec();
+	value.extend_from_slice(&timestamp.to_be_bytes());
+	Any { type_url: HEADER_TYPE_URL.to_string(), value }
+}
+
+fn decode_header(header: &Any) -> Result<(u64, u64), String> {
+	if header.type_url != HEADER_TYPE_URL || header.value.len() != 16 {
+		return Err(format!("Unexpected client message {}", header.type_url))
+	}
+	let (height, timestamp) = header.value.split_at(8);
+	Ok((
+		u64::from_be_bytes(height.try_into().expect("length checked above")),
+		u64::from_be_bytes(timestamp.try_into().expect("length checked above")),
+	))
+}
+
+pub struct Block {
+	/// Timestamp of the block, in nanoseconds.
+	pub timestamp: u64,
+	pub events: Vec<IbcEvent>,
+}
+
+/// A consensus state of the counterparty, along with the block it was added in.
+#[derive(Clone, Copy, Debug)]
+pub struct ConsensusRecord {
+	pub timestamp: u64,
+	pub host_height: u64,
+	pub host_timestamp: u64,
+}
+
+/// Fault injected in the transactions carrying a message of some type url.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+	/// The transaction fails as if the proof of the message was invalid.
+	InvalidProof,
+	/// The transaction is accepted but never executed, as if the node restarted before including
+	/// it.
+	Dropped,
+}
+
+/// A message submitted to the chain, decoded.
+enum Call {
+	UpdateClient {
+		height: u64,
+		timestamp: u64,
+	},
+	RecvPacket(Packet),
+	Acknowledgement(Packet),
+	Timeout(Packet),
+	Handshake(Handshake),
+	/// Event of a handshake step, already applied to the ends staged by the transaction.
+	Emit(IbcEvent),
+}
+
+/// A step of a connection or channel handshake.
+enum Handshake {
+	ConnectionOpenInit {
+		client_id: ClientId,
+		counterparty: ConnectionCounterparty,
+		delay_period: Duration,
+	},
+	ConnectionOpenTry {
+		client_id: ClientId,
+		counterparty: ConnectionCounterparty,
+		delay_period: Duration,
+	},
+	ConnectionOpenAck {
+		connection_id: ConnectionId,
+		counterparty_connection_id: ConnectionId,
+	},
+	ConnectionOpenConfirm {
+		connection_id: ConnectionId,
+	},
+	ChannelOpenInit {
+		port_id: PortId,
+		channel: ChannelEnd,
+	},
+	ChannelOpenTry {
+		port_id: PortId,
+		channel: ChannelEnd,
+	},
+	ChannelOpenAck {
+		port_id: PortId,
+		channel_id: ChannelId,
+		counterparty_channel_id: ChannelId,
+	},
+	ChannelOpenConfirm {
+		port_id: PortId,
+		channel_id: ChannelId,
+	},
+}
+
+/// Connection and channel ends of a chain. The identifiers of new ends follow the number of ends
+/// already there, as in ibc-go.
+#[derive(Clone, Default)]
+pub struct Ends {
+	pub connections: BTreeMap<ConnectionId, ConnectionEnd>,
+	pub channels: BTreeMap<(PortId, ChannelId), ChannelEnd>,
+}
+
+impl Ends {
+	/// Applies a handshake step at `height`, fails as the IBC module would if the ends it applies
+	/// to aren't in the state it expects. Returns the event emitted.
+	fn apply(&mut self, step: Handshake, height: Height) -> Result<IbcEvent, String> {
+		let event = match step {
+			Handshake::ConnectionOpenInit { client_id, counterparty, delay_period } => {
+				let connection_id = ConnectionId::new(self.connections.len() as u64);
+				let attributes = ConnectionAttributes {
+					height,
+					connection_id: Some(connection_id.clone()),
+					client_id: client_id.clone(),
+					counterparty_connection_id: None,
+					counterparty_client_id: counterparty.client_id().clone(),
+				};
+				let end = ConnectionEnd::new(
+					ConnectionState::Init,
+					client_id,
+					counterparty,
+					get_compatible_versions(),
+					delay_period,
+				);
+				self.connections.insert(connection_id, end);
+				IbcEvent::OpenInitConnection(ConnectionOpenInit(attributes))
+			},
+			Handshake::ConnectionOpenTry { client_id, counterparty, delay_period } => {
+				let counterparty_connection_id = counterparty
+					.connection_id()
+					.cloned()
+					.ok_or("Connection open try without counterparty connection")?;
+				let connection_id = ConnectionId::new(self.connections.len() as u64);
+				let attributes = ConnectionAttributes {
+					height,
+					connection_id: Some(connection_id.clone()),
+					client_id: client_id.clone(),
+					counterparty_connection_id: Some(counterparty_connection_id),
+					counterparty_client_id: counterparty.client_id().clone(),
+				};
+				let end = ConnectionEnd::new(
+					ConnectionState::TryOpen,
+					client_id,
+					counterparty,
+					get_compatible_versions(),
+					delay_period,
+				);
+				self.connections.insert(connection_id, end);
+				IbcEvent::OpenTryConnection(ConnectionOpenTry(attributes))
+			},
+			Handshake::ConnectionOpenAck { connection_id, counterparty_connection_id } => {
+				let end = self.connection(&connection_id, ConnectionState::Init)?;
+				end.set_state(ConnectionState::Open);
+				end.set_counterparty(ConnectionCounterparty::new(
+					end.counterparty().client_id().clone(),
+					Some(counterparty_connection_id.clone()),
+					end.counterparty().prefix().clone(),
+				));
+				IbcEvent::OpenAckConnection(ConnectionOpenAck(ConnectionAttributes {
+					height,
+					connection_id: Some(connection_id),
+					client_id: end.client_id().clone(),
+					counterparty_connection_id: Some(counterparty_connection_id),
+					counterparty_client_id: end.counterparty().client_id().clone(),
+				}))
+			},
+			Handshake::ConnectionOpenConfirm { connection_id } => {
+				let end = self.connection(&connection_id, ConnectionState::TryOpen)?;
+				end.set_state(ConnectionState::Open);
+				IbcEvent::OpenConfirmConnection(ConnectionOpenConfirm(ConnectionAttributes {
+					height,
+					connection_id: Some(connection_id),
+					client_id: end.client_id().clone(),
+					counterparty_connection_id: end.counterparty().connection_id().cloned(),
+					counterparty_client_id: end.counterparty().client_id().clone(),
+				}))
+			},
+			Handshake::ChannelOpenInit { port_id, channel } => {
+				let connection_id = self.channel_connection(&channel, None)?;
+				let channel_id = ChannelId::new(self.channels.len() as u64);
+				let counterparty_port_id = channel.counterparty().port_id().clone();
+				self.channels.insert((port_id.clone(), channel_id), channel);
+				IbcEvent::OpenInitChannel(ChannelOpenInit {
+					height,
+					port_id,
+					channel_id: Some(channel_id),
+					connection_id,
+					counterparty_port_id,
+					counterparty_channel_id: None,
+				})
+			},
+			Handshake::ChannelOpenTry { port_id, channel } => {
+				let connection_id =
+					self.channel_connection(&channel, Some(ConnectionState::Open))?;
+				let channel_id = ChannelId::new(self.channels.len() as u64);
+				let counterparty = channel.counterparty().clone();
+				self.channels.insert((port_id.clone(), channel_id), channel);
+				IbcEvent::OpenTryChannel(ChannelOpenTry {
+					height,
+					port_id,
+					channel_id: Some(channel_id),
+					connection_id,
+					counterparty_port_id: counterparty.port_id().clone(),
+					counterparty_channel_id: counterparty.channel_id().copied(),
+				})
+			},
+			Handshake::ChannelOpenAck { port_id, channel_id, counterparty_channel_id } => {
+				let end = self.channel(&port_id, channel_id, ChannelState::Init)?;
+				end.set_state(ChannelState::Open);
+				end.set_counterparty_channel_id(counterparty_channel_id);
+				IbcEvent::OpenAckChannel(ChannelOpenAck {
+					height,
+					connection_id: end.connection_hops()[0].clone(),
+					counterparty_port_id: end.counterparty().port_id().clone(),
+					port_id,
+					channel_id: Some(channel_id),
+					counterparty_channel_id: Some(counterparty_channel_id),
+				})
+			},
+			Handshake::ChannelOpenConfirm { port_id, channel_id } => {
+				let end = self.channel(&port_id, channel_id, ChannelState::TryOpen)?;
+				end.set_state(ChannelState::Open);
+				IbcEvent::OpenConfirmChannel(ChannelOpenConfirm {
+					height,
+					connection_id: end.connection_hops()[0].clone(),
+					counterparty_port_id: end.counterparty().port_id().clone(),
+					counterparty_channel_id: end.counterparty().channel_id().copied(),
+					port_id,
+					channel_id: Some(channel_id),
+				})
+			},
+		};
+		Ok(event)
+	}
+
+	/// The connection `connection_id`, if it is in the `expected` state.
+	fn connection(
+		&mut self,
+		connection_id: &ConnectionId,
+		expected: ConnectionState,
+	) -> Result<&mut ConnectionEnd, String> {
+		let end = self
+			.connections
+			.get_mut(connection_id)
+			.ok_or_else(|| format!("Connection {connection_id} not found"))?;
+		if *end.state() != expected {
+			return Err(format!("Connection {connection_id} is {}, not {expected}", end.state()))
+		}
+		Ok(end)
+	}
+
+	/// The channel `channel_id` on `port_id`, if it is in the `expected` state.
+	fn channel(
+		&mut self,
+		port_id: &PortId,
+		channel_id: ChannelId,
+		expected: ChannelState,
+	) -> Result<&mut ChannelEnd, String> {
+		let end = self
+			.channels
+			.get_mut(&(port_id.clone(), channel_id))
+			.ok_or_else(|| format!("Channel {channel_id}/{port_id} not found"))?;
+		if *end.state() != expected {
+			return Err(format!("Channel {channel_id}/{port_id} is {}, not {expected}", end.state()))
+		}
+		Ok(end)
+	}
+
+	/// The connection `channel` is opened on, which must exist, and be in the `expected` state if
+	/// any.
+	fn channel_connection(
+		&self,
+		channel: &ChannelEnd,
+		expected: Option<ConnectionState>,
+	) -> Result<ConnectionId, String> {
+		let connection_id =
+			channel.connection_hops().first().ok_or("Channel without connection hops")?;
+		let end = self
+			.connections
+			.get(connection_id)
+			.ok_or_else(|| format!("Connection {connection_id} not found"))?;
+		match expected {
+			Some(expected) if *end.state() != expected =>
+				Err(format!("Connection {connection_id} is {}, not {expected}", end.state())),
+			_ => Ok(connection_id.clone()),
+		}
+	}
+}
+
+pub struct Ledger {
+	revision: u64,
+	/// The block at height `n` is `blocks[n - 1]`.
+	blocks: Vec<Block>,
+	/// Events of the block being built.
+	pending: Vec<IbcEvent>,
+	/// Packets sent and not acknowledged or timed out yet, with the height they were sent at, by
+	/// sequence.
+	pub commitments: BTreeMap<u64, (Packet, u64)>,
+	/// Sequences of the packets received.
+	pub receipts: BTreeSet<u64>,
+	/// Packets received, with the acknowledgement written for them and the height it was written
+	/// at.
+	pub acks: BTreeMap<u64, (Packet, Vec<u8>, u64)>,
+	next_sequence_send: u64,
+	/// Sequence after the latest packet received, which is what an ordered channel expects next.
+	pub next_sequence_recv: u64,
+	/// Consensus states of the light client of the counterparty, by height.
+	pub consensus_states: BTreeMap<u64, ConsensusRecord>,
+	pub ends: Ends,
+	/// Faults of the next transactions carrying a message of a type url, with the number of
+	/// transactions left to fault.
+	pub faults: BTreeMap<String, (Fault, usize)>,
+}
+
+impl Ledger {
+	/// Starts the chain with a genesis block, the light client of the counterparty is created
+	/// with a consensus state of its genesis block.
+	pub fn new(revision: u64, timestamp: u64, counterparty_timestamp: u64) -> Self {
+		let genesis = ConsensusRecord {
+			timestamp: counterparty_timestamp,
+			host_height: 1,
+			host_timestamp: timestamp,
+		};
+		Self {
+			revision,
+			blocks: vec![Block { timestamp, events: vec![] }],
+			pending: vec![],
+			commitments: BTreeMap::new(),
+			receipts: BTreeSet::new(),
+			acks: BTreeMap::new(),
+			next_sequence_send: 1,
+			next_sequence_recv: 1,
+			consensus_states: BTreeMap::from([(1, genesis)]),
+			ends: Ends::default(),
+			faults: BTreeMap::new(),
+		}
+	}
+
+	pub fn height(&self) -> u64 {
+		self.blocks.len() as u64
+	}
+
+	pub fn block(&self, height: u64) -> Option<&Block> {
+		self.blocks.get(height.checked_sub(1)? as usize)
+	}
+
+	/// Latest height of the light client of the counterparty.
+	pub fn client_height(&self) -> u64 {
+		self.consensus_states.keys().next_back().copied().unwrap_or_default()
+	}
+
+	/// Finalizes the block being built, returns its height.
+	pub fn produce_block(&mut self, timestamp: u64) -> u64 {
+		let events = std::mem::take(&mut self.pending);
+		self.blocks.push(Block { timestamp, events });
+		self.height()
+	}
+
+	/// Events of the blocks in `from..=to`.
+	pub fn events(&self, from: u64, to: u64) -> Vec<IbcEvent> {
+		(from..=to)
+			.filter_map(|height| self.block(height))
+			.flat_map(|block| block.events.iter().cloned())
+			.collect()
+	}
+
+	/// Commits `packet` in the block being built with the next sequence, returns the sequence.
+	pub fn send_packet(&mut self, mut packet: Packet) -> u64 {
+		let sequence = self.next_sequence_send;
+		self.next_sequence_send += 1;
+		packet.sequence = sequence.into();
+		let height = self.pending_height();
+		self.commitments.insert(sequence, (packet.clone(), height));
+		let height = Height::new(self.revision, height);
+		self.pending.push(IbcEvent::SendPacket(SendPacket { height, packet }));
+		sequence
+	}
+
+	/// Executes the messages of a transaction in the block being built. Either all of them are
+	/// executed or none: messages proven at a height the light client doesn't know and handshake
+	/// steps the ends aren't ready for fail the whole transaction, as do the faults injected, see
+	/// [`Ledger::faults`]. Packets that were already received or acknowledged are skipped, as the
+	/// IBC module would. Returns the events emitted by the transaction.
+	pub fn execute(&mut self, msgs: &[Any], host_timestamp: u64) -> Result<Vec<IbcEvent>, String> {
+		for msg in msgs {
+			let Some((fault, count)) = self.faults.get_mut(&msg.type_url) else { continue };
+			if *count == 0 {
+				continue
+			}
+			*count -= 1;
+			return match fault {
+				Fault::InvalidProof => Err(format!("{}: invalid proof", msg.type_url)),
+				Fault::Dropped => Ok(vec![]),
+			}
+		}
+
+		let height = Height::new(self.revision, self.pending_height());
+		let mut known_heights = HashSet::new();
+		let mut calls = Vec::with_capacity(msgs.len());
+		// the handshake steps are applied to a copy of the ends, kept if the transaction succeeds
+		let mut ends = self.ends.clone();
+		for msg in msgs {
+			let (call, proof_height) = decode(msg)?;
+			if let Call::UpdateClient { height, .. } = call {
+				known_heights.insert(height);
+			}
+			if let Some(proof_height) = proof_height {
+				let proof_height = proof_height.revision_height;
+				if !self.consensus_states.contains_key(&proof_height) &&
+					!known_heights.contains(&proof_height)
+				{
+					return Err(format!(
+						"{} proven at {proof_height}, which the light client doesn't know",
+						msg.type_url
+					))
+				}
+			}
+			let call = match call {
+				Call::Handshake(step) => Call::Emit(ends.apply(step, height)?),
+				call => call,
+			};
+			calls.push(call);
+		}
+
+		self.ends = ends;
+		let emitted = self.pending.len();
+		for call in calls {
+			match call {
+				Call::UpdateClient { height: client_height, timestamp } => {
+					let record = ConsensusRecord {
+						timestamp,
+						host_height: height.revision_height,
+						host_timestamp,
+					};
+					self.consensus_states.insert(client_height, record);
+				},
+				Call::RecvPacket(packet) => {
+					let sequence = u64::from(packet.sequence);
+					if !self.receipts.insert(sequence) {
+						continue
+					}
+					self.next_sequence_recv = self.next_sequence_recv.max(sequence + 1);
+					let ack = SUCCESS_ACK.to_vec();
+					self.acks
+						.insert(sequence, (packet.clone(), ack.clone(), height.revision_height));
+					self.pending.push(IbcEvent::ReceivePacket(ReceivePacket {
+						height,
+						packet: packet.clone(),
+					}));
+					self.pending.push(IbcEvent::WriteAcknowledgement(WriteAcknowledgement {
+						height,
+						packet,
+						ack,
+					}));
+				},
+				Call::Acknowledgement(packet) => {
+					if self.commitments.remove(&u64::from(packet.sequence)).is_some() {
+						self.pending.push(IbcEvent::AcknowledgePacket(AcknowledgePacket {
+							height,
+							packet,
+						}));
+					}
+				},
+				Call::Timeout(packet) => {
+					if self.commitments.remove(&u64::from(packet.sequence)).is_some() {
+						self.pending
+							.push(IbcEvent::TimeoutPacket(TimeoutPacket { height, packet }));
+					}
+				},
+				Call::Emit(event) => self.pending.push(event),
+				Call::Handshake(_) => unreachable!("handshake steps are applied above"),
+			}
+		}
+		Ok(self.pending[emitted..].to_vec())
+	}
+
+	fn pending_height(&self) -> u64 {
+		self.height() + 1
+	}
+}
+
+/// Decodes a message, along with the height it is proven at.
+fn decode(msg: &Any) -> Result<(Call, Option<Height>), String> {
+	let value = msg.value.as_slice();
+	match msg.type_url.as_str() {
+		update_client::TYPE_URL => {
+			let raw = RawMsgUpdateClient::decode(value).map_err(|e| e.to_string())?;
+			let header = raw.client_message.ok_or("Missing client message")?;
+			let (height, timestamp) = decode_header(&header)?;
+			Ok((Call::UpdateClient { height, timestamp }, None))
+		},
+		recv_packet::TYPE_URL => {
+			let raw = RawMsgRecvPacket::decode(value).map_err(|e| e.to_string())?;
+			let msg = MsgRecvPacket::try_from(raw).map_err(|e| e.to_string())?;
+			Ok((Call::RecvPacket(msg.packet), Some(msg.proofs.height())))
+		},
+		acknowledgement::TYPE_URL => {
+			let raw = RawMsgAcknowledgement::decode(value).map_err(|e| e.to_string())?;
+			let msg = MsgAcknowledgement::try_from(raw).map_err(|e| e.to_string())?;
+			Ok((Call::Acknowledgement(msg.packet), Some(msg.proofs.height())))
+		},
+		timeout::TYPE_URL => {
+			let raw = RawMsgTimeout::decode(value).map_err(|e| e.to_string())?;
+			let msg = MsgTimeout::try_from(raw).map_err(|e| e.to_string())?;
+			Ok((Call::Timeout(msg.packet), Some(msg.proofs.height())))
+		},
+		conn_open_init::TYPE_URL => {
+			let raw = RawMsgConnectionOpenInit::decode(value).map_err(|e| e.to_string())?;
+			let msg = MsgConnectionOpenInit::try_from(raw).map_err(|e| e.to_string())?;
+			let step = Handshake::ConnectionOpenInit {
+				client_id: msg.client_id,
+				counterparty: msg.counterparty,
+				delay_period: msg.delay_period,
+			};
+			Ok((Call::Handshake(step), None))
+		},
+		// the client states carried by the try and ack steps aren't verified by mock chains, so
+		// only the fields the ends need are decoded
+		conn_open_try::TYPE_URL => {
+			let raw = RawMsgConnectionOpenTry::decode(value).map_err(|e| e.to_string())?;
+			let step = Handshake::ConnectionOpenTry {
+				client_id: ClientId::from_str(&raw.client_id).map_err(|e| e.to_string())?,
+				counterparty: ConnectionCounterparty::try_from(
+					raw.counterparty.ok_or("Missing counterparty")?,
+				)
+				.map_err(|e| e.to_string())?,
+				delay_period: Duration::from_nanos(raw.delay_period),
+			};
+			Ok((Call::Handshake(step), raw.proof_height.map(Height::from)))
+		},
+		conn_open_ack::TYPE_URL => {
+			let raw = RawMsgConnectionOpenAck::decode(value).map_err(|e| e.to_string())?;
+			let step = Handshake::ConnectionOpenAck {
+				connection_id: ConnectionId::from_str(&raw.connection_id)
+					.map_err(|e| e.to_string())?,
+				counterparty_connection_id: ConnectionId::from_str(&raw.counterparty_connection_id)
+					.map_err(|e| e.to_string())?,
+			};
+			Ok((Call::Handshake(step), raw.proof_height.map(Height::from)))
+		},
+		conn_open_confirm::TYPE_URL => {
+			let raw = RawMsgConnectionOpenConfirm::decode(value).map_err(|e| e.to_string())?;
+			let msg = MsgConnectionOpenConfirm::try_from(raw).map_err(|e| e.to_string())?;
+			let step = Handshake::ConnectionOpenConfirm { connection_id: msg.connection_id };
+			Ok((Call::Handshake(step), Some(msg.proofs.height())))
+		},
+		chan_open_init::TYPE_URL => {
+			let raw = RawMsgChannelOpenInit::decode(value).map_err(|e| e.to_string())?;
+			let msg = MsgChannelOpenInit::try_from(raw).map_err(|e| e.to_string())?;
+			let step = Handshake::ChannelOpenInit { port_id: msg.port_id, channel: msg.channel };
+			Ok((Call::Handshake(step), None))
+		},
+		chan_open_try::TYPE_URL => {
+			let raw = RawMsgChannelOpenTry::decode(value).map_err(|e| e.to_string())?;
+			let msg = MsgChannelOpenTry::try_from(raw).map_err(|e| e.to_string())?;
+			let step = Handshake::ChannelOpenTry { port_id: msg.port_id, channel: msg.channel };
+			Ok((Call::Handshake(step), Some(msg.proofs.height())))
+		},
+		chan_open_ack::TYPE_URL => {
+			let raw = RawMsgChannelOpenAck::decode(value).map_err(|e| e.to_string())?;
+			let msg = MsgChannelOpenAck::try_from(raw).map_err(|e| e.to_string())?;
+			let step = Handshake::ChannelOpenAck {
+				port_id: msg.port_id,
+				channel_id: msg.channel_id,
+				counterparty_channel_id: msg.counterparty_channel_id,
+			};
+			Ok((Call::Handshake(step), Some(msg.proofs.height())))
+		},
+		chan_open_confirm::TYPE_URL => {
+			let raw = RawMsgChannelOpenConfirm::decode(value).map_err(|e| e.to_string())?;
+			let msg = MsgChannelOpenConfirm::try_from(raw).map_err(|e| e.to_string())?;
+			let step =
+				Handshake::ChannelOpenConfirm { port_id: msg.port_id, channel_id: msg.channel_id };
+			Ok((Call::Handshake(step), Some(msg.proofs.height())))
+		},
+		type_url => Err(format!("Mock chains don't support {type_url}")),
+	}
+}
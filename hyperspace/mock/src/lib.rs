@@ -0,0 +1,501 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-memory chains implementing [`primitives::Chain`], so that the relay loop can be driven
+//! without any node. A pair of mock chains is connected by an open connection and an unordered
+//! ICS-20 channel, more connections and channels can be opened through their handshakes; the light
+//! clients on both sides trust every header and the proofs are dummy bytes, only the heights they
+//! are made at are checked.
+
+pub mod finality;
+pub mod ledger;
+mod provider;
+
+use finality::FinalityScript;
+use ibc::{
+	core::{
+		ics03_connection::{
+			connection::{ConnectionEnd, Counterparty as ConnectionCounterparty},
+			msgs::conn_open_init::MsgConnectionOpenInit,
+			version::Version as ConnectionVersion,
+		},
+		ics04_channel::{
+			channel::{
+				ChannelEnd, Counterparty as ChannelCounterparty, Order, State as ChannelState,
+			},
+			msgs::chan_open_init::MsgChannelOpenInit,
+			packet::Packet,
+			Version as ChannelVersion,
+		},
+		ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
+	},
+	events::IbcEvent,
+	timestamp::Timestamp,
+	tx_msg::Msg,
+	Height,
+};
+use ibc_proto::google::protobuf::Any;
+use ledger::{Fault, Ledger};
+use primitives::{
+	error::Error, keystore::KeystoreConfig, CommonClientState, IbcQueryProvider, KeyProvider,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{BTreeMap, HashSet},
+	path::PathBuf,
+	str::FromStr,
+	sync::{Arc, Mutex},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{sync::mpsc, task::JoinHandle};
+
+/// Packets sent by the mock chains time out after a day.
+const PACKET_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Data of the ICS-20 packets sent by the mock chains.
+const TRANSFER_DATA: &[u8] = br#"{"denom":"stake","amount":"1","sender":"alice","receiver":"bob"}"#;
+
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+	/// Name of the chain, used in logs.
+	pub name: String,
+	/// Canonical identifier of the chain, `{name}-{revision}` by default.
+	pub chain_id: String,
+	/// Revision number of the heights of the chain.
+	pub revision: u32,
+	/// Interval between the blocks produced by [`MockChain::spawn_block_production`].
+	pub block_time: Duration,
+	/// Delay period of the connection ending on this chain.
+	pub connection_delay: Duration,
+	/// Size in bytes of the proofs returned by the queries.
+	pub proof_size: usize,
+}
+
+impl MockConfig {
+	pub fn new(name: impl Into<String>, revision: u32) -> Self {
+		let name = name.into();
+		Self {
+			chain_id: format!("{name}-{revision}"),
+			name,
+			revision,
+			block_time: Duration::from_millis(100),
+			connection_delay: Duration::ZERO,
+			proof_size: 512,
+		}
+	}
+}
+
+/// Config of a standalone mock chain, e.g. in the config of the relayer, see [`MockChain::new`].
+/// Mock chains don't sign anything, the secret fields only exist for the key management of the
+/// relayer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockClientConfig {
+	pub name: String,
+	/// Revision number of the heights of the chain
+	pub revision: u32,
+	/// Revision number of the heights of the chain tracked by the light client on this chain
+	pub counterparty_revision: u32,
+	/// `10-grandpa-0` by default
+	#[serde(default)]
+	pub client_id: Option<ClientId>,
+	/// `connection-0` by default
+	#[serde(default)]
+	pub connection_id: Option<ConnectionId>,
+	/// `channel-0/transfer` by default
+	#[serde(default)]
+	pub channel_whitelist: Vec<(ChannelId, PortId)>,
+	#[serde(default)]
+	pub wasm_code_hash: Option<String>,
+	#[serde(default)]
+	pub private_key: String,
+	#[serde(default)]
+	pub keystore: Option<PathBuf>,
+}
+
+impl KeystoreConfig for MockClientConfig {
+	fn raw_secret_mut(&mut self) -> &mut String {
+		&mut self.private_key
+	}
+
+	fn keystore_mut(&mut self) -> &mut Option<PathBuf> {
+		&mut self.keystore
+	}
+}
+
+/// Number of calls made to every query of a mock chain, by method name.
+#[derive(Clone, Default)]
+pub struct QueryCounts(Arc<Mutex<BTreeMap<&'static str, u64>>>);
+
+impl QueryCounts {
+	fn record(&self, method: &'static str) {
+		*self.0.lock().unwrap().entry(method).or_default() += 1;
+	}
+
+	pub fn snapshot(&self) -> BTreeMap<&'static str, u64> {
+		self.0.lock().unwrap().clone()
+	}
+
+	pub fn total(&self) -> u64 {
+		self.0.lock().unwrap().values().sum()
+	}
+
+	pub fn reset(&self) {
+		self.0.lock().unwrap().clear()
+	}
+}
+
+/// How a mock chain answers whether its light client on the counterparty is synced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SyncCheck {
+	/// The light client is never behind.
+	#[default]
+	AlwaysSynced,
+	/// The light client is behind until it's updated to the latest block of the chain, a single
+	/// mandatory update catches it up.
+	LatestBlock,
+	/// The check fails, as if the node didn't answer.
+	Failing,
+}
+
+#[derive(Clone)]
+pub struct MockChain {
+	name: String,
+	chain_id: ChainId,
+	revision: u64,
+	counterparty_revision: u64,
+	block_time: Duration,
+	connection_delay: Duration,
+	proof_size: usize,
+	client_id: ClientId,
+	connection_id: Option<ConnectionId>,
+	/// Channel of this chain and of the counterparty.
+	channel: (ChannelId, PortId),
+	counterparty_channel: (ChannelId, PortId),
+	channel_whitelist: HashSet<(ChannelId, PortId)>,
+	ledger: Arc<Mutex<Ledger>>,
+	finality_subscribers: Arc<Mutex<Vec<mpsc::UnboundedSender<u64>>>>,
+	queries: QueryCounts,
+	sync_check: Arc<Mutex<SyncCheck>>,
+	common_state: CommonClientState,
+}
+
+impl MockChain {
+	/// Creates two chains with a light client of each other, connected by `channel-0` on the
+	/// `transfer` port of both sides.
+	pub fn pair(config_a: MockConfig, config_b: MockConfig) -> (MockChain, MockChain) {
+		let now = now();
+		let chain_a = MockChain::with_counterparty(&config_a, &config_b, now);
+		let chain_b = MockChain::with_counterparty(&config_b, &config_a, now);
+		chain_a.open_ends();
+		chain_b.open_ends();
+		(chain_a, chain_b)
+	}
+
+	/// Creates a standalone chain, whose light client of the counterparty starts now. Packets are
+	/// only delivered between the chains of a [`MockChain::pair`], since they share nothing but
+	/// their config.
+	pub async fn new(config: MockClientConfig) -> Result<Self, Error> {
+		let counterparty = MockConfig::new("counterparty", config.counterparty_revision);
+		let mut chain = Self::with_counterparty(
+			&MockConfig::new(config.name, config.revision),
+			&counterparty,
+			now(),
+		);
+		if let Some(client_id) = config.client_id {
+			chain.client_id = client_id;
+		}
+		if let Some(connection_id) = config.connection_id {
+			chain.connection_id = Some(connection_id);
+		}
+		if !config.channel_whitelist.is_empty() {
+			chain.channel_whitelist = config.channel_whitelist.into_iter().collect();
+		}
+		chain.open_ends();
+		Ok(chain)
+	}
+
+	fn with_counterparty(
+		config: &MockConfig,
+		counterparty: &MockConfig,
+		genesis_timestamp: u64,
+	) -> Self {
+		let channel = (ChannelId::new(0), PortId::transfer());
+		let common_state = CommonClientState {
+			// the relay loop waits a random delay below this one between its queries
+			rpc_call_delay: Duration::from_millis(2),
+			initial_rpc_call_delay: Duration::from_millis(2),
+			..Default::default()
+		};
+		Self {
+			name: config.name.clone(),
+			chain_id: ChainId::from_string(&config.chain_id),
+			revision: config.revision.into(),
+			counterparty_revision: counterparty.revision.into(),
+			block_time: config.block_time,
+			connection_delay: config.connection_delay,
+			proof_size: config.proof_size,
+			client_id: ClientId::from_str("10-grandpa-0").expect("client id is valid"),
+			connection_id: Some(ConnectionId::new(0)),
+			channel: channel.clone(),
+			counterparty_channel: channel.clone(),
+			channel_whitelist: HashSet::from([channel]),
+			ledger: Arc::new(Mutex::new(Ledger::new(
+				config.revision.into(),
+				genesis_timestamp,
+				genesis_timestamp,
+			))),
+			finality_subscribers: Default::default(),
+			queries: QueryCounts::default(),
+			sync_check: Default::default(),
+			common_state,
+		}
+	}
+
+	/// Finalizes the block being built and notifies the finality subscribers, returns its height.
+	pub fn produce_block(&self) -> u64 {
+		self.produce_block_at(now())
+	}
+
+	/// Same as [`MockChain::produce_block`], the block being timestamped at `timestamp`, in
+	/// nanoseconds, instead of now.
+	pub fn produce_block_at(&self, timestamp: u64) -> u64 {
+		let height = self.ledger.lock().unwrap().produce_block(timestamp);
+		self.notify_finality(height);
+		height
+	}
+
+	/// Notifies the finality subscribers of the finality of the block at `height`, whether or not
+	/// it was already notified or produced.
+	pub fn notify_finality(&self, height: u64) {
+		self.finality_subscribers
+			.lock()
+			.unwrap()
+			.retain(|subscriber| subscriber.send(height).is_ok());
+	}
+
+	/// Produces a block every block time until the returned task is aborted.
+	pub fn spawn_block_production(&self) -> JoinHandle<()> {
+		let chain = self.clone();
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(chain.block_time);
+			loop {
+				interval.tick().await;
+				chain.produce_block();
+			}
+		})
+	}
+
+	/// Sends the finality notifications of `script` instead of one per block, one every block
+	/// time, once the relayer subscribed to them. The blocks notified are produced when they're
+	/// first notified, no block is produced once the script is done.
+	pub fn spawn_finality_script(&self, script: FinalityScript) -> JoinHandle<()> {
+		let chain = self.clone();
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(chain.block_time);
+			while chain.finality_subscribers.lock().unwrap().is_empty() {
+				interval.tick().await;
+			}
+			let start = chain.latest_height();
+			for offset in script.offsets() {
+				interval.tick().await;
+				let height = start + offset;
+				{
+					let mut ledger = chain.ledger.lock().unwrap();
+					while ledger.height() < height {
+						ledger.produce_block(now());
+					}
+				}
+				chain.notify_finality(height);
+			}
+		})
+	}
+
+	/// Sends `count` ICS-20 packets to the counterparty in the block being built, returns their
+	/// sequences.
+	pub fn send_transfers(&self, count: usize) -> Vec<u64> {
+		let timeout_timestamp =
+			Timestamp::from_nanoseconds(now() + PACKET_TIMEOUT.as_nanos() as u64)
+				.expect("timestamp is valid");
+		let mut ledger = self.ledger.lock().unwrap();
+		(0..count)
+			.map(|_| {
+				ledger.send_packet(self.packet(
+					TRANSFER_DATA.to_vec(),
+					Height::zero(),
+					timeout_timestamp,
+				))
+			})
+			.collect()
+	}
+
+	/// Sends a packet carrying `data` to the counterparty in the block being built, returns its
+	/// sequence. The counterparty acknowledges it successfully whatever its data.
+	pub fn send_packet(
+		&self,
+		data: Vec<u8>,
+		timeout_height: Height,
+		timeout_timestamp: Timestamp,
+	) -> u64 {
+		let packet = self.packet(data, timeout_height, timeout_timestamp);
+		self.ledger.lock().unwrap().send_packet(packet)
+	}
+
+	/// Sends an ICS-20 packet that already timed out on the counterparty, its timeout height being
+	/// the genesis block of the counterparty, so that the relayer times it out instead of
+	/// delivering it. Returns its sequence.
+	pub fn send_expired_packet(&self) -> u64 {
+		self.send_packet(
+			TRANSFER_DATA.to_vec(),
+			Height::new(self.counterparty_revision, 1),
+			Timestamp::none(),
+		)
+	}
+
+	/// Packet to the counterparty on the channel of this chain, its sequence is set once sent.
+	fn packet(
+		&self,
+		data: Vec<u8>,
+		timeout_height: Height,
+		timeout_timestamp: Timestamp,
+	) -> Packet {
+		Packet {
+			sequence: 0.into(),
+			source_port: self.channel.1.clone(),
+			source_channel: self.channel.0,
+			destination_port: self.counterparty_channel.1.clone(),
+			destination_channel: self.counterparty_channel.0,
+			data,
+			timeout_height,
+			timeout_timestamp,
+		}
+	}
+
+	/// Sequences of the packets sent and not acknowledged or timed out yet.
+	pub fn pending_packets(&self) -> Vec<u64> {
+		self.ledger.lock().unwrap().commitments.keys().copied().collect()
+	}
+
+	/// Sequences of the packets received from the counterparty.
+	pub fn received_packets(&self) -> Vec<u64> {
+		self.ledger.lock().unwrap().receipts.iter().copied().collect()
+	}
+
+	pub fn latest_height(&self) -> u64 {
+		self.ledger.lock().unwrap().height()
+	}
+
+	/// Starts the handshake of a connection to the counterparty in the block being built, on the
+	/// light client of the counterparty. Returns the identifier of the connection.
+	pub fn open_connection(&self) -> Result<ConnectionId, Error> {
+		let msg = MsgConnectionOpenInit {
+			client_id: self.client_id.clone(),
+			counterparty: ConnectionCounterparty::new(
+				self.client_id.clone(),
+				None,
+				self.connection_prefix(),
+			),
+			version: Some(ConnectionVersion::default()),
+			delay_period: self.connection_delay,
+			signer: self.account_id(),
+		};
+		match self.execute(msg.to_any())?.as_slice() {
+			[IbcEvent::OpenInitConnection(open_init)] => open_init
+				.connection_id()
+				.cloned()
+				.ok_or_else(|| Error::Custom("Connection open init without connection".into())),
+			events => Err(Error::Custom(format!("Unexpected events {events:?}"))),
+		}
+	}
+
+	/// Starts the handshake of an unordered ICS-20 channel to the counterparty on `connection_id`
+	/// in the block being built. Returns the identifier of the channel, on the `transfer` port.
+	pub fn open_channel(&self, connection_id: ConnectionId) -> Result<ChannelId, Error> {
+		let channel = ChannelEnd::new(
+			ChannelState::Init,
+			Order::Unordered,
+			ChannelCounterparty::new(PortId::transfer(), None),
+			vec![connection_id],
+			ChannelVersion::ics20(),
+		);
+		let msg = MsgChannelOpenInit::new(PortId::transfer(), channel, self.account_id());
+		match self.execute(msg.to_any())?.as_slice() {
+			[IbcEvent::OpenInitChannel(open_init)] => open_init
+				.channel_id
+				.ok_or_else(|| Error::Custom("Channel open init without channel".into())),
+			events => Err(Error::Custom(format!("Unexpected events {events:?}"))),
+		}
+	}
+
+	/// Connection ends of this chain, by identifier.
+	pub fn connections(&self) -> BTreeMap<ConnectionId, ConnectionEnd> {
+		self.ledger.lock().unwrap().ends.connections.clone()
+	}
+
+	/// Channel ends of this chain, by port and identifier.
+	pub fn channels(&self) -> BTreeMap<(PortId, ChannelId), ChannelEnd> {
+		self.ledger.lock().unwrap().ends.channels.clone()
+	}
+
+	/// Faults the next `count` transactions carrying a message of `type_url`, replacing the faults
+	/// injected for it before.
+	pub fn inject_fault(&self, type_url: &str, fault: Fault, count: usize) {
+		self.ledger.lock().unwrap().faults.insert(type_url.to_string(), (fault, count));
+	}
+
+	/// Changes the ordering of the channel of this chain to the counterparty, as if it was opened
+	/// with `order`. The ends on both chains of a pair must be changed.
+	pub fn set_channel_order(&self, order: Order) {
+		let (channel_id, port_id) = self.channel.clone();
+		if let Some(channel) =
+			self.ledger.lock().unwrap().ends.channels.get_mut(&(port_id, channel_id))
+		{
+			channel.ordering = order;
+		}
+	}
+
+	/// Changes how the chain answers whether its light client on the counterparty is synced.
+	pub fn set_sync_check(&self, sync_check: SyncCheck) {
+		*self.sync_check.lock().unwrap() = sync_check;
+	}
+
+	/// Updates the light client of `counterparty` on this chain to the latest block of
+	/// `counterparty`, as another relayer would, in the block being built.
+	pub fn update_client_of(&self, counterparty: &MockChain) -> Result<(), Error> {
+		let (height, timestamp) = {
+			let ledger = counterparty.ledger.lock().unwrap();
+			let height = ledger.height();
+			(height, ledger.block(height).expect("the latest block exists").timestamp)
+		};
+		self.execute(counterparty.update_client_message(height, timestamp))?;
+		Ok(())
+	}
+
+	/// Executes `msg` in the block being built, returns the events it emitted.
+	fn execute(&self, msg: Any) -> Result<Vec<IbcEvent>, Error> {
+		self.ledger.lock().unwrap().execute(&[msg], now()).map_err(Error::Custom)
+	}
+
+	/// Queries made to this chain by the relayer so far.
+	pub fn query_counts(&self) -> &QueryCounts {
+		&self.queries
+	}
+}
+
+/// Current time in nanoseconds, the timestamp of the blocks produced now.
+fn now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.expect("time is after the epoch")
+		.as_nanos() as u64
+}
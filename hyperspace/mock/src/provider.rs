@@ -0,0 +1,1004 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	ledger::{encode_header, ConsensusRecord},
+	MockChain, SyncCheck,
+};
+use futures::Stream;
+use ibc::{
+	applications::{
+		cross_chain_queries::query::CrossChainQuery, fee::PacketFee, transfer::PrefixedCoin,
+	},
+	core::{
+		ics02_client::{
+			client_state::{ClientState as _, ClientType},
+			events::UpdateClient,
+			msgs::update_client,
+		},
+		ics03_connection::{
+			connection::{
+				ConnectionEnd, Counterparty as ConnectionCounterparty, IdentifiedConnectionEnd,
+				State as ConnectionState,
+			},
+			version::get_compatible_versions,
+		},
+		ics04_channel::{
+			channel::{
+				ChannelEnd, Counterparty as ChannelCounterparty, IdentifiedChannelEnd, Order, State,
+			},
+			packet::Packet,
+			Version,
+		},
+		ics23_commitment::commitment::CommitmentPrefix,
+		ics24_host::{
+			identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
+			Path,
+		},
+	},
+	events::IbcEvent,
+	signer::Signer,
+	timestamp::Timestamp,
+	Height,
+};
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::{
+		applications::transfer::v1::{QueryDenomTraceResponse, QueryDenomTracesResponse},
+		core::{
+			channel::v1::{
+				QueryChannelResponse, QueryChannelsResponse, QueryNextSequenceReceiveResponse,
+				QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
+				QueryPacketReceiptResponse,
+			},
+			client::v1::{
+				MsgUpdateClient as RawMsgUpdateClient, QueryClientStateResponse,
+				QueryConsensusStateResponse,
+			},
+			connection::v1::{
+				IdentifiedConnection, Params as ConnectionParams, QueryConnectionResponse,
+				QueryConnectionsResponse,
+			},
+		},
+	},
+};
+use ibc_rpc::{PacketEventsFilter, PacketInfo};
+use ics10_grandpa::{
+	client_state::ClientState as GrandpaClientState,
+	consensus_state::ConsensusState as GrandpaConsensusState,
+};
+use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState};
+use primitives::{
+	cost::{TxBudget, TxCost},
+	error::Error,
+	Chain, CommonClientState, IbcEventProvider, IbcQueryProvider, KeyProvider, LightClientSync,
+	MisbehaviourHandler, Page, ProofProvider, UpdateType,
+};
+use prost::Message;
+use std::{collections::HashSet, pin::Pin, str::FromStr, time::Duration};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+impl MockChain {
+	fn height(&self, height: u64) -> Height {
+		Height::new(self.revision, height)
+	}
+
+	/// Latest height of the chain.
+	pub fn latest(&self) -> Height {
+		self.height(self.ledger.lock().unwrap().height())
+	}
+
+	fn proof(&self) -> Vec<u8> {
+		vec![0xab; self.proof_size]
+	}
+
+	fn client_state(&self, latest_height: u64) -> AnyClientState {
+		AnyClientState::Grandpa(GrandpaClientState {
+			latest_para_height: latest_height as u32,
+			para_id: self.counterparty_revision as u32,
+			..Default::default()
+		})
+	}
+
+	fn consensus_state(timestamp: u64) -> Result<AnyConsensusState, Error> {
+		let timestamp = Timestamp::from_nanoseconds(timestamp)
+			.ok()
+			.and_then(Timestamp::into_tm_time)
+			.ok_or_else(|| Error::Custom(format!("Invalid timestamp {timestamp}")))?;
+		Ok(AnyConsensusState::Grandpa(GrandpaConsensusState {
+			timestamp,
+			root: vec![0; 32].into(),
+		}))
+	}
+
+	/// Opens the connection and the channel of this chain in the ledger, as if their handshakes
+	/// were completed at genesis.
+	pub(crate) fn open_ends(&self) {
+		let mut ledger = self.ledger.lock().unwrap();
+		if let Some(connection_id) = self.connection_id.clone() {
+			let connection = ConnectionEnd::new(
+				ConnectionState::Open,
+				self.client_id.clone(),
+				ConnectionCounterparty::new(
+					self.client_id.clone(),
+					Some(connection_id.clone()),
+					self.connection_prefix(),
+				),
+				get_compatible_versions(),
+				self.connection_delay,
+			);
+			ledger.ends.connections.insert(connection_id, connection);
+		}
+		let (channel_id, port_id) = self.channel.clone();
+		let (counterparty_channel, counterparty_port) = self.counterparty_channel.clone();
+		let channel = ChannelEnd::new(
+			State::Open,
+			Order::Unordered,
+			ChannelCounterparty::new(counterparty_port, Some(counterparty_channel)),
+			self.connection_id.clone().into_iter().collect(),
+			Version::ics20(),
+		);
+		ledger.ends.channels.insert((port_id, channel_id), channel);
+	}
+
+	fn packet_info(&self, packet: &Packet, height: u64, ack: Option<Vec<u8>>) -> PacketInfo {
+		PacketInfo {
+			height: Some(height),
+			sequence: packet.sequence.into(),
+			source_port: packet.source_port.to_string(),
+			source_channel: packet.source_channel.to_string(),
+			destination_port: packet.destination_port.to_string(),
+			destination_channel: packet.destination_channel.to_string(),
+			channel_order: Order::Unordered.to_string(),
+			data: packet.data.clone(),
+			timeout_height: packet.timeout_height.into(),
+			timeout_timestamp: packet.timeout_timestamp.nanoseconds(),
+			ack,
+		}
+	}
+
+	/// Latest height of this chain the light client on `counterparty` was updated to.
+	async fn client_height_on<C: Chain>(&self, counterparty: &C) -> Result<u64, anyhow::Error> {
+		let (latest_height, _) = counterparty.latest_height_and_timestamp().await?;
+		let response = counterparty.query_client_state(latest_height, self.client_id()).await?;
+		let client_state = response
+			.client_state
+			.map(AnyClientState::try_from)
+			.ok_or_else(|| Error::Custom(format!("Client of {} not found", self.name)))??;
+		Ok(client_state.latest_height().revision_height)
+	}
+
+	pub(crate) fn update_client_message(&self, height: u64, timestamp: u64) -> Any {
+		let msg = RawMsgUpdateClient {
+			client_id: self.client_id.to_string(),
+			client_message: Some(encode_header(height, timestamp)),
+			signer: self.account_id().to_string(),
+		};
+		Any { type_url: update_client::TYPE_URL.to_string(), value: msg.encode_to_vec() }
+	}
+
+	fn identified_connections(&self) -> Vec<IdentifiedConnection> {
+		self.ledger
+			.lock()
+			.unwrap()
+			.ends
+			.connections
+			.iter()
+			.map(|(connection_id, connection)| {
+				IdentifiedConnectionEnd::new(connection_id.clone(), connection.clone()).into()
+			})
+			.collect()
+	}
+
+	fn unsupported<T>(&self, method: &str) -> Result<T, Error> {
+		Err(Error::Custom(format!("{method} isn't supported by mock chain {}", self.name)))
+	}
+
+	/// Fails unless `channel_id` on `port_id` is the channel of this chain, the only one packets
+	/// can be sent on.
+	#[cfg(feature = "testing")]
+	fn check_channel(&self, port_id: &PortId, channel_id: ChannelId) -> Result<(), Error> {
+		if (channel_id, port_id) != (self.channel.0, &self.channel.1) {
+			return Err(Error::Custom(format!(
+				"Mock chain {} has no channel {channel_id}/{port_id}",
+				self.name
+			)))
+		}
+		Ok(())
+	}
+}
+
+#[async_trait::async_trait]
+impl IbcQueryProvider for MockChain {
+	type AssetId = String;
+
+	type Error = Error;
+
+	async fn query_client_consensus(
+		&self,
+		_at: Height,
+		_client_id: ClientId,
+		consensus_height: Height,
+	) -> Result<QueryConsensusStateResponse, Self::Error> {
+		self.queries.record("query_client_consensus");
+		let record = self
+			.ledger
+			.lock()
+			.unwrap()
+			.consensus_states
+			.get(&consensus_height.revision_height)
+			.copied();
+		let ConsensusRecord { timestamp, .. } = record.ok_or_else(|| {
+			Error::Custom(format!("Consensus state at {consensus_height} not found"))
+		})?;
+		Ok(QueryConsensusStateResponse {
+			consensus_state: Some(Self::consensus_state(timestamp)?.into()),
+			proof: self.proof(),
+			proof_height: Some(self.latest().into()),
+		})
+	}
+
+	async fn query_client_state(
+		&self,
+		_at: Height,
+		_client_id: ClientId,
+	) -> Result<QueryClientStateResponse, Self::Error> {
+		self.queries.record("query_client_state");
+		let client_height = self.ledger.lock().unwrap().client_height();
+		Ok(QueryClientStateResponse {
+			client_state: Some(self.client_state(client_height).into()),
+			proof: self.proof(),
+			proof_height: Some(self.latest().into()),
+		})
+	}
+
+	/// The connection end is proven at `at`, the height of the handshake event it is queried for,
+	/// which the light client of the counterparty is updated to along with the handshake step.
+	async fn query_connection_end(
+		&self,
+		at: Height,
+		connection_id: ConnectionId,
+	) -> Result<QueryConnectionResponse, Self::Error> {
+		self.queries.record("query_connection_end");
+		let connection = self
+			.ledger
+			.lock()
+			.unwrap()
+			.ends
+			.connections
+			.get(&connection_id)
+			.cloned()
+			.ok_or_else(|| {
+				Error::Custom(format!("Connection {connection_id} not found on {}", self.name))
+			})?;
+		Ok(QueryConnectionResponse {
+			connection: Some(connection.into()),
+			proof: self.proof(),
+			proof_height: Some(at.into()),
+		})
+	}
+
+	/// The channel end is proven at `at`, as the connection ends are.
+	async fn query_channel_end(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryChannelResponse, Self::Error> {
+		self.queries.record("query_channel_end");
+		let channel = self
+			.ledger
+			.lock()
+			.unwrap()
+			.ends
+			.channels
+			.get(&(port_id.clone(), channel_id))
+			.cloned()
+			.ok_or_else(|| {
+				Error::Custom(format!("Channel {channel_id}/{port_id} not found on {}", self.name))
+			})?;
+		Ok(QueryChannelResponse {
+			channel: Some(channel.into()),
+			proof: self.proof(),
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn query_cross_chain_queries(&self) -> Result<Vec<CrossChainQuery>, Self::Error> {
+		Ok(vec![])
+	}
+
+	async fn query_packet_commitment(
+		&self,
+		_at: Height,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketCommitmentResponse, Self::Error> {
+		self.queries.record("query_packet_commitment");
+		let committed = self.ledger.lock().unwrap().commitments.contains_key(&seq);
+		Ok(QueryPacketCommitmentResponse {
+			commitment: if committed { vec![1; 32] } else { vec![] },
+			proof: self.proof(),
+			proof_height: Some(self.latest().into()),
+		})
+	}
+
+	async fn query_packet_acknowledgement(
+		&self,
+		_at: Height,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketAcknowledgementResponse, Self::Error> {
+		self.queries.record("query_packet_acknowledgement");
+		let ack = self.ledger.lock().unwrap().acks.get(&seq).map(|(_, ack, _)| ack.clone());
+		Ok(QueryPacketAcknowledgementResponse {
+			acknowledgement: ack.unwrap_or_default(),
+			proof: self.proof(),
+			proof_height: Some(self.latest().into()),
+		})
+	}
+
+	async fn query_next_sequence_recv(
+		&self,
+		_at: Height,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+	) -> Result<QueryNextSequenceReceiveResponse, Self::Error> {
+		self.queries.record("query_next_sequence_recv");
+		Ok(QueryNextSequenceReceiveResponse {
+			next_sequence_receive: self.ledger.lock().unwrap().next_sequence_recv,
+			proof: self.proof(),
+			proof_height: Some(self.latest().into()),
+		})
+	}
+
+	/// Ordered channels don't write receipts, their next sequence to receive moves past the packets
+	/// received instead.
+	async fn query_packet_receipt(
+		&self,
+		_at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketReceiptResponse, Self::Error> {
+		self.queries.record("query_packet_receipt");
+		let ledger = self.ledger.lock().unwrap();
+		let ordered = ledger
+			.ends
+			.channels
+			.get(&(port_id.clone(), *channel_id))
+			.map_or(false, |channel| channel.ordering == Order::Ordered);
+		Ok(QueryPacketReceiptResponse {
+			received: !ordered && ledger.receipts.contains(&seq),
+			proof: self.proof(),
+			proof_height: Some(self.latest().into()),
+		})
+	}
+
+	async fn latest_height_and_timestamp(&self) -> Result<(Height, Timestamp), Self::Error> {
+		self.queries.record("latest_height_and_timestamp");
+		let ledger = self.ledger.lock().unwrap();
+		let height = ledger.height();
+		let timestamp = ledger.block(height).expect("latest block exists").timestamp;
+		let timestamp = Timestamp::from_nanoseconds(timestamp)
+			.map_err(|e| Error::Custom(format!("Invalid timestamp {timestamp}: {e}")))?;
+		Ok((self.height(height), timestamp))
+	}
+
+	async fn query_packet_commitments(
+		&self,
+		_at: Height,
+		_channel_id: ChannelId,
+		_port_id: PortId,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.queries.record("query_packet_commitments");
+		Ok(self.ledger.lock().unwrap().commitments.keys().copied().collect())
+	}
+
+	async fn query_packet_acknowledgements(
+		&self,
+		_at: Height,
+		_channel_id: ChannelId,
+		_port_id: PortId,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.queries.record("query_packet_acknowledgements");
+		Ok(self.ledger.lock().unwrap().acks.keys().copied().collect())
+	}
+
+	async fn query_unreceived_packets(
+		&self,
+		_at: Height,
+		_channel_id: ChannelId,
+		_port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.queries.record("query_unreceived_packets");
+		let ledger = self.ledger.lock().unwrap();
+		Ok(seqs.into_iter().filter(|seq| !ledger.receipts.contains(seq)).collect())
+	}
+
+	async fn query_unreceived_acknowledgements(
+		&self,
+		_at: Height,
+		_channel_id: ChannelId,
+		_port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.queries.record("query_unreceived_acknowledgements");
+		let ledger = self.ledger.lock().unwrap();
+		Ok(seqs.into_iter().filter(|seq| ledger.commitments.contains_key(seq)).collect())
+	}
+
+	fn channel_whitelist(&self) -> HashSet<(ChannelId, PortId)> {
+		self.channel_whitelist.clone()
+	}
+
+	async fn query_connection_channels(
+		&self,
+		_at: Height,
+		connection_id: &ConnectionId,
+		_next_key: Option<Vec<u8>>,
+		_limit: u64,
+	) -> Result<QueryChannelsResponse, Self::Error> {
+		self.queries.record("query_connection_channels");
+		let channels = self
+			.ledger
+			.lock()
+			.unwrap()
+			.ends
+			.channels
+			.iter()
+			.filter(|(_, channel)| channel.connection_hops().first() == Some(connection_id))
+			.map(|((port_id, channel_id), channel)| {
+				IdentifiedChannelEnd::new(port_id.clone(), *channel_id, channel.clone()).into()
+			})
+			.collect();
+		Ok(QueryChannelsResponse { channels, pagination: None, height: Some(self.latest().into()) })
+	}
+
+	fn expected_block_time(&self) -> Duration {
+		self.block_time
+	}
+
+	async fn query_ibc_balance(
+		&self,
+		_asset_id: Self::AssetId,
+	) -> Result<Vec<PrefixedCoin>, Self::Error> {
+		Ok(vec![])
+	}
+
+	async fn query_denom_trace(
+		&self,
+		_denom_hash: String,
+	) -> Result<QueryDenomTraceResponse, Self::Error> {
+		self.unsupported("query_denom_trace")
+	}
+
+	async fn query_denom_traces(
+		&self,
+		_next_key: Option<Vec<u8>>,
+		_limit: u64,
+	) -> Result<QueryDenomTracesResponse, Self::Error> {
+		Ok(QueryDenomTracesResponse::default())
+	}
+
+	async fn query_denom_hash(&self, _trace: String) -> Result<String, Self::Error> {
+		self.unsupported("query_denom_hash")
+	}
+
+	async fn query_packet_fees(
+		&self,
+		_channel_id: ChannelId,
+		_port_id: PortId,
+		_sequence: u64,
+	) -> Result<Vec<PacketFee>, Self::Error> {
+		Ok(vec![])
+	}
+
+	fn connection_prefix(&self) -> CommitmentPrefix {
+		CommitmentPrefix::try_from(b"ibc".to_vec()).expect("prefix isn't empty")
+	}
+
+	fn client_id(&self) -> ClientId {
+		self.client_id.clone()
+	}
+
+	fn set_client_id(&mut self, client_id: ClientId) {
+		self.client_id = client_id;
+	}
+
+	fn connection_id(&self) -> Option<ConnectionId> {
+		self.connection_id.clone()
+	}
+
+	fn set_channel_whitelist(&mut self, channel_whitelist: HashSet<(ChannelId, PortId)>) {
+		self.channel_whitelist = channel_whitelist;
+	}
+
+	fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
+		self.channel_whitelist.insert(channel);
+	}
+
+	fn set_connection_id(&mut self, connection_id: ConnectionId) {
+		self.connection_id = Some(connection_id);
+	}
+
+	fn client_type(&self) -> ClientType {
+		GrandpaClientState::<pallet_ibc::light_clients::HostFunctionsManager>::client_type()
+	}
+
+	async fn query_timestamp_at(&self, block_number: u64) -> Result<u64, Self::Error> {
+		self.queries.record("query_timestamp_at");
+		self.ledger
+			.lock()
+			.unwrap()
+			.block(block_number)
+			.map(|block| block.timestamp)
+			.ok_or_else(|| Error::Custom(format!("Block {block_number} not found")))
+	}
+
+	async fn query_clients(
+		&self,
+		_next_key: Option<Vec<u8>>,
+		_limit: u64,
+	) -> Result<Page<ClientId>, Self::Error> {
+		Ok(Page { items: vec![self.client_id.clone()], next_key: None })
+	}
+
+	async fn query_channels(
+		&self,
+		_next_key: Option<Vec<u8>>,
+		_limit: u64,
+	) -> Result<Page<(ChannelId, PortId)>, Self::Error> {
+		Ok(Page { items: vec![self.channel.clone()], next_key: None })
+	}
+
+	async fn query_connection_using_client(
+		&self,
+		_height: u32,
+		client_id: String,
+	) -> Result<Vec<IdentifiedConnection>, Self::Error> {
+		self.queries.record("query_connection_using_client");
+		Ok(self
+			.identified_connections()
+			.into_iter()
+			.filter(|connection| connection.client_id == client_id)
+			.collect())
+	}
+
+	async fn query_connections(
+		&self,
+		_next_key: Option<Vec<u8>>,
+		_limit: u64,
+	) -> Result<QueryConnectionsResponse, Self::Error> {
+		self.queries.record("query_connections");
+		Ok(QueryConnectionsResponse {
+			connections: self.identified_connections(),
+			pagination: None,
+			height: Some(self.latest().into()),
+		})
+	}
+
+	async fn query_connection_params(&self) -> Result<ConnectionParams, Self::Error> {
+		Ok(ConnectionParams {
+			max_expected_time_per_block: self.expected_block_time().as_nanos() as u64,
+		})
+	}
+}
+
+#[async_trait::async_trait]
+impl IbcEventProvider for MockChain {
+	/// Height of the finalized block.
+	type FinalityEvent = u64;
+
+	/// Height of the block the transaction was included in.
+	type TransactionId = u64;
+
+	async fn query_latest_ibc_events<T>(
+		&mut self,
+		finality_event: Self::FinalityEvent,
+		counterparty: &T,
+	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+	where
+		T: Chain,
+	{
+		self.queries.record("query_latest_ibc_events");
+		let client_height = self.client_height_on(counterparty).await?;
+
+		let ledger = self.ledger.lock().unwrap();
+		let height = finality_event.min(ledger.height());
+		if height <= client_height {
+			return Ok(vec![])
+		}
+		// handshake steps are proven at the height of their event, which gets an update of its own
+		// as on a real chain, the other events are proven with the update of the finalized block
+		let mut updates = vec![];
+		let mut events = vec![];
+		for event_height in client_height + 1..=height {
+			let block = ledger.block(event_height).expect("height is at most the latest");
+			events.extend(block.events.iter().cloned());
+			if event_height == height || block.events.iter().any(is_handshake_event) {
+				let update = self.update_client_message(event_height, block.timestamp);
+				let events = std::mem::take(&mut events);
+				updates.push((update, self.height(event_height), events, UpdateType::Optional));
+			}
+		}
+		Ok(updates)
+	}
+
+	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
+		Box::pin(futures::stream::empty())
+	}
+
+	async fn query_send_packets(
+		&self,
+		_channel_id: ChannelId,
+		_port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		self.queries.record("query_send_packets");
+		let ledger = self.ledger.lock().unwrap();
+		Ok(seqs
+			.iter()
+			.filter_map(|seq| ledger.commitments.get(seq))
+			.map(|(packet, height)| self.packet_info(packet, *height, None))
+			.collect())
+	}
+
+	async fn query_received_packets(
+		&self,
+		_channel_id: ChannelId,
+		_port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		self.queries.record("query_received_packets");
+		let ledger = self.ledger.lock().unwrap();
+		Ok(seqs
+			.iter()
+			.filter_map(|seq| ledger.acks.get(seq))
+			.map(|(packet, ack, height)| self.packet_info(packet, *height, Some(ack.clone())))
+			.collect())
+	}
+
+	async fn query_packet_events(
+		&self,
+		from_height: Height,
+		to_height: Height,
+		filter: PacketEventsFilter,
+	) -> Result<Vec<IbcEvent>, Self::Error> {
+		self.queries.record("query_packet_events");
+		let ledger = self.ledger.lock().unwrap();
+		Ok(ledger
+			.events(from_height.revision_height, to_height.revision_height)
+			.into_iter()
+			.filter(|event| filter.matches(event))
+			.collect())
+	}
+
+	async fn query_client_update_time_and_height(
+		&self,
+		_client_id: ClientId,
+		client_height: Height,
+	) -> Result<(Height, Timestamp), Self::Error> {
+		self.queries.record("query_client_update_time_and_height");
+		let record = self
+			.ledger
+			.lock()
+			.unwrap()
+			.consensus_states
+			.get(&client_height.revision_height)
+			.copied();
+		let ConsensusRecord { host_height, host_timestamp, .. } = record
+			.ok_or_else(|| Error::Custom(format!("Client wasn't updated at {client_height}")))?;
+		let timestamp = Timestamp::from_nanoseconds(host_timestamp)
+			.map_err(|e| Error::Custom(format!("Invalid timestamp {host_timestamp}: {e}")))?;
+		Ok((self.height(host_height), timestamp))
+	}
+
+	async fn is_update_required(
+		&self,
+		_latest_height: u64,
+		_latest_client_height_on_counterparty: u64,
+	) -> Result<bool, Self::Error> {
+		Ok(false)
+	}
+
+	async fn query_client_id_from_tx_hash(
+		&self,
+		_tx_id: Self::TransactionId,
+	) -> Result<ClientId, Self::Error> {
+		self.unsupported("query_client_id_from_tx_hash")
+	}
+
+	async fn query_connection_id_from_tx_hash(
+		&self,
+		_tx_id: Self::TransactionId,
+	) -> Result<ConnectionId, Self::Error> {
+		self.unsupported("query_connection_id_from_tx_hash")
+	}
+
+	async fn query_channel_id_from_tx_hash(
+		&self,
+		_tx_id: Self::TransactionId,
+	) -> Result<(ChannelId, PortId), Self::Error> {
+		self.unsupported("query_channel_id_from_tx_hash")
+	}
+}
+
+fn is_handshake_event(event: &IbcEvent) -> bool {
+	matches!(
+		event,
+		IbcEvent::OpenInitConnection(_) |
+			IbcEvent::OpenTryConnection(_) |
+			IbcEvent::OpenAckConnection(_) |
+			IbcEvent::OpenInitChannel(_) |
+			IbcEvent::OpenTryChannel(_) |
+			IbcEvent::OpenAckChannel(_)
+	)
+}
+
+#[async_trait::async_trait]
+impl ProofProvider for MockChain {
+	async fn query_proof(&self, _at: Height, _keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
+		self.queries.record("query_proof");
+		Ok(self.proof())
+	}
+
+	async fn query_proofs(
+		&self,
+		_at: Height,
+		keys: Vec<Vec<Vec<u8>>>,
+	) -> Result<Vec<Vec<u8>>, Self::Error> {
+		self.queries.record("query_proofs");
+		Ok(keys.iter().map(|_| self.proof()).collect())
+	}
+
+	async fn query_value_with_proof(
+		&self,
+		_at: Height,
+		path: Path,
+	) -> Result<(Option<Vec<u8>>, Vec<u8>), Self::Error> {
+		self.queries.record("query_value_with_proof");
+		let ledger = self.ledger.lock().unwrap();
+		let value = match path {
+			Path::Receipts(path) =>
+				ledger.receipts.contains(&u64::from(path.sequence)).then(|| vec![1]),
+			Path::Commitments(path) =>
+				ledger.commitments.contains_key(&u64::from(path.sequence)).then(|| vec![1; 32]),
+			Path::Acks(path) =>
+				ledger.acks.get(&u64::from(path.sequence)).map(|(_, ack, _)| ack.clone()),
+			path => return self.unsupported(&format!("query_value_with_proof({path})")),
+		};
+		Ok((value, self.proof()))
+	}
+
+	/// Required by the connection handshakes of non-tendermint clients, dummy bytes like the other
+	/// proofs.
+	async fn query_host_consensus_state_proof(
+		&self,
+		_client_state: &AnyClientState,
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		Ok(Some(self.proof()))
+	}
+
+	async fn initialize_client_state(
+		&self,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		let (height, timestamp) = self.latest_height_and_timestamp().await?;
+		let client_state = AnyClientState::Grandpa(GrandpaClientState {
+			latest_para_height: height.revision_height as u32,
+			para_id: self.revision as u32,
+			..Default::default()
+		});
+		Ok((client_state, Self::consensus_state(timestamp.nanoseconds())?))
+	}
+}
+
+impl KeyProvider for MockChain {
+	fn account_id(&self) -> Signer {
+		Signer::from_str("mock-relayer").expect("signer isn't empty")
+	}
+}
+
+#[async_trait::async_trait]
+impl MisbehaviourHandler for MockChain {
+	async fn check_for_misbehaviour<C: Chain>(
+		&self,
+		_counterparty: &C,
+		_client_message: AnyClientMessage,
+	) -> Result<(), anyhow::Error> {
+		Ok(())
+	}
+}
+
+#[async_trait::async_trait]
+impl LightClientSync for MockChain {
+	/// The light clients of mock chains have no authority set handoffs to catch up with, they're
+	/// only behind when told so with [`MockChain::set_sync_check`].
+	async fn is_synced<C: Chain>(&self, counterparty: &C) -> Result<bool, anyhow::Error> {
+		self.queries.record("is_synced");
+		match *self.sync_check.lock().unwrap() {
+			SyncCheck::AlwaysSynced => return Ok(true),
+			SyncCheck::LatestBlock => {},
+			SyncCheck::Failing => return Err(Error::Custom("Sync check failed".into()).into()),
+		}
+		Ok(self.client_height_on(counterparty).await? >= self.latest_height())
+	}
+
+	/// A single update to the latest block catches the light client up, with the events of the
+	/// blocks it skips.
+	async fn fetch_mandatory_updates<C: Chain>(
+		&self,
+		counterparty: &C,
+	) -> Result<(Vec<Any>, Vec<IbcEvent>), anyhow::Error> {
+		self.queries.record("fetch_mandatory_updates");
+		if *self.sync_check.lock().unwrap() != SyncCheck::LatestBlock {
+			return Ok((vec![], vec![]))
+		}
+		let client_height = self.client_height_on(counterparty).await?;
+		let ledger = self.ledger.lock().unwrap();
+		let height = ledger.height();
+		if height <= client_height {
+			return Ok((vec![], vec![]))
+		}
+		let events = (client_height + 1..=height)
+			.flat_map(|height| {
+				ledger.block(height).expect("height is at most the latest").events.clone()
+			})
+			.collect();
+		let timestamp = ledger.block(height).expect("the latest block exists").timestamp;
+		Ok((vec![self.update_client_message(height, timestamp)], events))
+	}
+}
+
+#[async_trait::async_trait]
+impl Chain for MockChain {
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn chain_id(&self) -> ChainId {
+		self.chain_id.clone()
+	}
+
+	fn tx_budget(&self) -> TxBudget {
+		TxBudget::weight(u64::MAX)
+	}
+
+	async fn estimate_cost(&self, msg: Vec<Any>) -> Result<TxCost, Self::Error> {
+		Ok(TxCost::weight(msg.len() as u64))
+	}
+
+	async fn finality_notifications(
+		&self,
+	) -> Result<Pin<Box<dyn Stream<Item = Self::FinalityEvent> + Send + Sync>>, Self::Error> {
+		let (sender, receiver) = mpsc::unbounded_channel();
+		self.finality_subscribers.lock().unwrap().push(sender);
+		Ok(Box::pin(UnboundedReceiverStream::new(receiver)))
+	}
+
+	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Self::Error> {
+		let mut ledger = self.ledger.lock().unwrap();
+		let timestamp = crate::now();
+		ledger.execute(&messages, timestamp).map_err(Error::Custom)?;
+		Ok(ledger.height() + 1)
+	}
+
+	async fn submit_and_wait_for_events(&self, messages: Vec<Any>) -> Result<Vec<IbcEvent>, Error> {
+		let mut ledger = self.ledger.lock().unwrap();
+		let timestamp = crate::now();
+		ledger.execute(&messages, timestamp).map_err(Error::Custom)
+	}
+
+	async fn query_client_message(
+		&self,
+		_update: UpdateClient,
+	) -> Result<AnyClientMessage, Self::Error> {
+		self.unsupported("query_client_message")
+	}
+
+	async fn get_proof_height(&self, block_height: Height) -> Height {
+		block_height
+	}
+
+	async fn handle_error(&mut self, _error: &anyhow::Error) -> Result<(), anyhow::Error> {
+		Ok(())
+	}
+
+	fn common_state(&self) -> &CommonClientState {
+		&self.common_state
+	}
+
+	fn common_state_mut(&mut self) -> &mut CommonClientState {
+		&mut self.common_state
+	}
+
+	async fn reconnect(&mut self) -> anyhow::Result<()> {
+		Ok(())
+	}
+}
+
+#[cfg(feature = "testing")]
+#[async_trait::async_trait]
+impl primitives::TestProvider for MockChain {
+	async fn send_transfer(
+		&self,
+		_params: ibc::applications::transfer::msgs::transfer::MsgTransfer<PrefixedCoin>,
+	) -> Result<(), Self::Error> {
+		self.send_transfers(1);
+		Ok(())
+	}
+
+	async fn send_ordered_packet(
+		&self,
+		_channel_id: ChannelId,
+		_timeout: pallet_ibc::Timeout,
+	) -> Result<(), Self::Error> {
+		self.unsupported("send_ordered_packet")
+	}
+
+	async fn open_ica_channel(&self, _connection_id: ConnectionId) -> Result<(), Self::Error> {
+		self.unsupported("open_ica_channel")
+	}
+
+	async fn send_raw_packet(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+		data: Vec<u8>,
+		timeout: pallet_ibc::Timeout,
+	) -> Result<(), Self::Error> {
+		self.check_channel(&port_id, channel_id)?;
+		let (timeout_height, timeout_timestamp) = match timeout {
+			pallet_ibc::Timeout::Offset { timestamp, height } => (
+				height.map(|height| self.ledger.lock().unwrap().client_height() + height),
+				timestamp.map(|timestamp| crate::now() + timestamp * 1_000_000_000),
+			),
+			pallet_ibc::Timeout::Absolute { timestamp, height } => (height, timestamp),
+		};
+		let timeout_height = timeout_height
+			.map(|height| Height::new(self.counterparty_revision, height))
+			.unwrap_or_else(Height::zero);
+		let timeout_timestamp = match timeout_timestamp {
+			Some(timestamp) => Timestamp::from_nanoseconds(timestamp)
+				.map_err(|e| Error::Custom(format!("Invalid timeout timestamp: {e}")))?,
+			None => Timestamp::none(),
+		};
+		self.send_packet(data, timeout_height, timeout_timestamp);
+		Ok(())
+	}
+
+	async fn force_timeout(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+	) -> Result<(), Self::Error> {
+		self.check_channel(&port_id, channel_id)?;
+		self.send_expired_packet();
+		Ok(())
+	}
+
+	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>> {
+		let (sender, receiver) = mpsc::unbounded_channel();
+		self.finality_subscribers.lock().unwrap().push(sender);
+		Box::pin(UnboundedReceiverStream::new(receiver))
+	}
+
+	async fn increase_counters(&mut self) -> Result<(), Self::Error> {
+		self.unsupported("increase_counters")
+	}
+}
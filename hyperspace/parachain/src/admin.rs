@@ -0,0 +1,180 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Privileged pallet-ibc calls that are restricted to the admin origins of the runtime.
+//!
+//! Calls can either be submitted through `sudo` with the separately configured
+//! `sudo_private_key`, or encoded and handed off to governance.
+
+use crate::{error::Error, signer::ExtrinsicSigner, ParachainClient, WAIT_FOR_IN_BLOCK_TIMEOUT};
+use ibc::{
+	core::{ics02_client::client_state::ClientState, ics24_host::identifier::ClientId},
+	Height,
+};
+use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState};
+use primitives::{IbcProvider, KeyProvider};
+use sp_runtime::{
+	traits::{IdentifyAccount, Verify},
+	MultiSignature, MultiSigner,
+};
+use subxt::{dynamic::Value, ext::scale_value::ValueDef};
+use tendermint_proto::Protobuf;
+
+/// A pallet-ibc call that requires a privileged origin
+#[derive(Debug, Clone)]
+pub enum AdminCall {
+	/// Freeze the client at the given height
+	FreezeClient { client_id: ClientId, height: u64 },
+	/// Replace the client and consensus state of a client, e.g. to recover a frozen or expired
+	/// client
+	SubstituteClientState {
+		client_id: ClientId,
+		height: Height,
+		client_state: AnyClientState,
+		consensus_state: AnyConsensusState,
+	},
+	/// Recover an expired or frozen client with the state of an active substitute client of the
+	/// same type
+	RecoverClient { subject_client_id: ClientId, substitute_client_id: ClientId },
+}
+
+impl AdminCall {
+	/// Name of the call in the `Ibc` pallet
+	fn call_name(&self) -> &'static str {
+		match self {
+			AdminCall::FreezeClient { .. } => "freeze_client",
+			AdminCall::SubstituteClientState { .. } => "substitute_client_state",
+			AdminCall::RecoverClient { .. } => "recover_client",
+		}
+	}
+
+	/// Call arguments as dynamic values, in the order the pallet declares them
+	fn call_args(&self) -> Result<Vec<Value>, Error> {
+		let encode_error = |e: tendermint_proto::Error| {
+			Error::Custom(format!("Failed to encode {}: {e:?}", self.call_name()))
+		};
+		let args = match self {
+			AdminCall::FreezeClient { client_id, height } =>
+				vec![Value::from_bytes(client_id.as_bytes()), Value::u128(*height as u128)],
+			AdminCall::SubstituteClientState {
+				client_id,
+				height,
+				client_state,
+				consensus_state,
+			} => vec![
+				Value::string(client_id.to_string()),
+				Value::named_composite([
+					("revision_number", Value::u128(height.revision_number as u128)),
+					("revision_height", Value::u128(height.revision_height as u128)),
+				]),
+				Value::from_bytes(client_state.encode_vec().map_err(encode_error)?),
+				Value::from_bytes(consensus_state.encode_vec().map_err(encode_error)?),
+			],
+			AdminCall::RecoverClient { subject_client_id, substitute_client_id } => vec![
+				Value::string(subject_client_id.to_string()),
+				Value::string(substitute_client_id.to_string()),
+			],
+		};
+		Ok(args)
+	}
+}
+
+impl<T> ParachainClient<T>
+where
+	T: light_client_common::config::Config + Send + Sync,
+	Self: KeyProvider,
+	<<T as light_client_common::config::Config>::Signature as Verify>::Signer:
+		From<MultiSigner> + IdentifyAccount<AccountId = T::AccountId>,
+	<T as subxt::Config>::Address: From<<T as subxt::Config>::AccountId>,
+	<T as subxt::Config>::Signature: From<MultiSignature> + Send + Sync,
+	<T as subxt::Config>::AccountId: Send + Sync,
+	<T as subxt::Config>::Address: Send + Sync,
+{
+	/// SCALE encoded runtime call, suitable for submitting as a governance proposal.
+	pub fn encode_admin_call(&self, call: &AdminCall) -> Result<Vec<u8>, Error> {
+		let payload = subxt::dynamic::tx("Ibc", call.call_name(), call.call_args()?);
+		Ok(self.para_client.tx().call_data(&payload)?)
+	}
+
+	/// Submits the call wrapped in `sudo.sudo`, signed with the configured sudo key.
+	pub async fn submit_admin_call(&self, call: &AdminCall) -> Result<(T::Hash, T::Hash), Error> {
+		let sudo_public_key = self.sudo_public_key.clone().ok_or_else(|| {
+			Error::Custom("`sudo_private_key` must be configured for admin calls".to_string())
+		})?;
+		let runtime_call = Value::unnamed_variant(
+			"Ibc",
+			[Value::unnamed_variant(call.call_name(), call.call_args()?)],
+		);
+		let payload = subxt::dynamic::tx("Sudo", "sudo", vec![runtime_call]);
+
+		let signer = ExtrinsicSigner::<T, Self>::new(
+			self.key_store.clone(),
+			self.key_type_id,
+			sudo_public_key,
+		);
+		let other_params =
+			T::custom_extrinsic_params(&self.para_client, &self.extrinsic_options).await?;
+		let progress = signer
+			.create_signed(&self.para_client, &payload, None, other_params)
+			.await?
+			.submit_and_watch()
+			.await?;
+		let tx_in_block =
+			tokio::time::timeout(WAIT_FOR_IN_BLOCK_TIMEOUT, progress.wait_for_in_block())
+				.await
+				.map_err(|e| {
+					Error::from(format!(
+						"[submit_admin_call] Failed to wait for in block due to {:?}",
+						e
+					))
+				})??;
+		// `Sudid` carries the dispatch result of the inner call, `wait_for_success` only covers
+		// the outer `sudo` call
+		let events = tx_in_block.wait_for_success().await?;
+		for event in events.iter() {
+			let event = event?;
+			if event.pallet_name() == "Sudo" && event.variant_name() == "Sudid" {
+				let result = event.field_values()?;
+				let failed = result.values().any(
+					|value| matches!(&value.value, ValueDef::Variant(variant) if variant.name == "Err"),
+				);
+				if failed {
+					return Err(Error::Custom(format!(
+						"{} was dispatched with an error: {result:?}",
+						call.call_name()
+					)))
+				}
+			}
+		}
+		log::info!(target: "hyperspace_parachain", "Submitted sudo {} for {}", call.call_name(), self.name);
+		Ok((tx_in_block.extrinsic_hash(), tx_in_block.block_hash()))
+	}
+
+	/// Unfreezes `client_id` by substituting it with a freshly initialized client state of the
+	/// counterparty.
+	pub async fn unfreeze_client<C: IbcProvider>(
+		&self,
+		client_id: ClientId,
+		counterparty: &C,
+	) -> Result<(T::Hash, T::Hash), Error> {
+		let (client_state, consensus_state) = counterparty
+			.initialize_client_state()
+			.await
+			.map_err(|e| Error::Custom(format!("Failed to initialize client state: {e:?}")))?;
+		let height = client_state.latest_height();
+		let call =
+			AdminCall::SubstituteClientState { client_id, height, client_state, consensus_state };
+		self.submit_admin_call(&call).await
+	}
+}
@@ -0,0 +1,127 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	collections::BTreeMap,
+	sync::{Arc, Mutex},
+};
+
+/// Number of blocks whose hash and timestamp are kept by default.
+pub const DEFAULT_BLOCK_CACHE_SIZE: usize = 4096;
+
+#[derive(Clone, Copy)]
+struct CachedBlock<H> {
+	hash: Option<H>,
+	timestamp: Option<u64>,
+}
+
+/// Bounded cache of the hashes and timestamps of parachain blocks, keyed by block number.
+///
+/// Timeout and connection delay checks look up the same heights over and over while a backlog of
+/// packets is processed. Only blocks the relayer proves against are looked up this way, and those
+/// are finalized, so the entries never go stale. The oldest blocks are evicted first.
+#[derive(Clone)]
+pub struct BlockCache<H> {
+	capacity: usize,
+	blocks: Arc<Mutex<BTreeMap<u64, CachedBlock<H>>>>,
+}
+
+impl<H: Copy> Default for BlockCache<H> {
+	fn default() -> Self {
+		Self::new(DEFAULT_BLOCK_CACHE_SIZE)
+	}
+}
+
+impl<H: Copy> BlockCache<H> {
+	pub fn new(capacity: usize) -> Self {
+		Self { capacity, blocks: Default::default() }
+	}
+
+	pub fn hash(&self, block_number: u64) -> Option<H> {
+		self.blocks.lock().unwrap().get(&block_number)?.hash
+	}
+
+	/// Timestamp of the block, in nanoseconds.
+	pub fn timestamp(&self, block_number: u64) -> Option<u64> {
+		self.blocks.lock().unwrap().get(&block_number)?.timestamp
+	}
+
+	pub fn insert_hash(&self, block_number: u64, hash: H) {
+		self.update(block_number, |block| block.hash = Some(hash))
+	}
+
+	pub fn insert_timestamp(&self, block_number: u64, timestamp: u64) {
+		self.update(block_number, |block| block.timestamp = Some(timestamp))
+	}
+
+	fn update(&self, block_number: u64, f: impl FnOnce(&mut CachedBlock<H>)) {
+		if self.capacity == 0 {
+			return
+		}
+		let mut blocks = self.blocks.lock().unwrap();
+		f(blocks
+			.entry(block_number)
+			.or_insert(CachedBlock { hash: None, timestamp: None }));
+		while blocks.len() > self.capacity {
+			blocks.pop_first();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn caches_hashes_and_timestamps_of_blocks() {
+		let cache = BlockCache::<u8>::new(2);
+		cache.insert_hash(1, 0xaa);
+		assert_eq!((cache.hash(1), cache.timestamp(1)), (Some(0xaa), None));
+		cache.insert_timestamp(1, 1_000);
+		assert_eq!((cache.hash(1), cache.timestamp(1)), (Some(0xaa), Some(1_000)));
+		assert_eq!((cache.hash(2), cache.timestamp(2)), (None, None));
+
+		// clones share the entries
+		cache.clone().insert_timestamp(2, 2_000);
+		assert_eq!(cache.timestamp(2), Some(2_000));
+	}
+
+	#[test]
+	fn evicts_the_oldest_blocks() {
+		let cache = BlockCache::<u8>::new(2);
+		for block_number in [2, 3, 1] {
+			cache.insert_hash(block_number, block_number as u8);
+		}
+		assert_eq!(
+			[1, 2, 3].map(|block_number| cache.hash(block_number)),
+			[None, Some(2), Some(3)]
+		);
+
+		cache.insert_timestamp(3, 3_000);
+		cache.insert_hash(4, 4);
+		assert_eq!(
+			[2, 3, 4].map(|block_number| cache.hash(block_number)),
+			[None, Some(3), Some(4)]
+		);
+		assert_eq!(cache.timestamp(3), Some(3_000));
+	}
+
+	#[test]
+	fn caches_nothing_without_capacity() {
+		let cache = BlockCache::<u8>::new(0);
+		cache.insert_hash(1, 1);
+		cache.insert_timestamp(1, 1_000);
+		assert_eq!((cache.hash(1), cache.timestamp(1)), (None, None));
+	}
+}
@@ -12,8 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::{error::Error, signer::ExtrinsicSigner, ParachainClient};
-use crate::{parachain::UncheckedExtrinsic, provider::TransactionId, FinalityProtocol};
+use super::{
+	error::Error,
+	signer::ExtrinsicSigner,
+	utils::{connect_to_healthy_endpoint, refresh_runtime_metadata, spawn_runtime_upgrade_watcher},
+	ParachainClient, BATCHED_DELIVER_OVERHEAD, DELIVER_EXTRINSIC_OVERHEAD,
+};
+use crate::{
+	finality_protocol::FinalityEvent,
+	parachain::UncheckedExtrinsic,
+	provider::TransactionId,
+	relay_finality::{FinalityStream, RelayFinalityMux},
+	FinalityProtocol,
+};
 use anyhow::anyhow;
 use codec::{Decode, Encode};
 use finality_grandpa::BlockNumberOps;
@@ -24,8 +35,9 @@ use ibc::{
 	core::{
 		ics02_client::{
 			events::UpdateClient,
-			msgs::{update_client::MsgUpdateAnyClient, ClientMsg},
+			msgs::{misbehaviour::MsgSubmitMisbehaviour, ClientMsg},
 		},
+		ics24_host::identifier::ChainId,
 		ics26_routing::msgs::Ics26Envelope,
 	},
 	events::IbcEvent,
@@ -35,11 +47,14 @@ use ibc::{
 use ibc_proto::google::protobuf::Any;
 use ics10_grandpa::client_message::{ClientMessage, Misbehaviour, RelayChainHeader};
 use itertools::Itertools;
-use jsonrpsee_ws_client::WsClientBuilder;
-use light_client_common::config::{EventRecordT, RuntimeCall, RuntimeTransactions};
+use light_client_common::config::{
+	AsInnerEvent, EventRecordT, IbcEventsT, RuntimeCall, RuntimeTransactions,
+};
 use pallet_ibc::light_clients::AnyClientMessage;
 use primitives::{
-	mock::LocalClientTypes, Chain, CommonClientState, IbcProvider, MisbehaviourHandler,
+	cost::{TxBudget, TxCost},
+	mock::LocalClientTypes,
+	Chain, CommonClientState, IbcEventProvider, IbcQueryProvider, MisbehaviourHandler,
 };
 use sc_consensus_beefy_rpc::BeefyApiClient;
 use sp_core::{twox_128, H256};
@@ -98,31 +113,35 @@ where
 		&*self.name
 	}
 
-	fn block_max_weight(&self) -> u64 {
-		self.max_extrinsic_weight * 100 / 80
+	fn chain_id(&self) -> ChainId {
+		// the genesis hash tells apart parachains with the same id on different relay chains
+		let genesis_hash = self.para_client.genesis_hash();
+		ChainId::new(
+			format!("parachain-{}", hex::encode(&genesis_hash.as_ref()[..4])),
+			self.para_id.into(),
+		)
+	}
+
+	fn tx_budget(&self) -> TxBudget {
+		TxBudget::weight(self.max_extrinsic_weight * 100 / 80)
+	}
+
+	fn max_transaction_size(&self) -> Option<usize> {
+		self.max_extrinsic_size
+			.map(|size| size.saturating_sub(DELIVER_EXTRINSIC_OVERHEAD))
 	}
 
-	async fn estimate_weight(&self, messages: Vec<Any>) -> Result<u64, Self::Error> {
+	async fn estimate_cost(&self, messages: Vec<Any>) -> Result<TxCost, Self::Error> {
 		let extrinsic = {
 			// todo: put this in utils
-			let signer = ExtrinsicSigner::<T, Self>::new(
-				self.key_store.clone(),
-				self.key_type_id.clone(),
-				self.public_key.clone(),
-			);
-
-			let messages = messages
-				.into_iter()
-				.map(|msg| Any { type_url: msg.type_url.clone(), value: msg.value })
-				.collect::<Vec<_>>();
+			let signer = self.extrinsic_signer().for_estimation();
 
 			let tx_params = BaseExtrinsicParamsBuilder::new()
 				.tip(T::Tip::from(100_000u128))
 				.era(Era::Immortal, self.para_client.genesis_hash());
 			let call = T::Tx::ibc_deliver(messages);
-			self.para_client
-				.tx()
-				.create_signed(&call, &signer, tx_params.into())
+			signer
+				.create_signed(&self.para_client, &call, None, tx_params.into())
 				.await?
 				.encoded()
 				.to_vec()
@@ -133,98 +152,168 @@ where
 		>::query_info(&*self.para_ws_client, extrinsic.into(), None)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error From Estimating weight {:?}", e)))?;
-		Ok(dispatch_info.weight.ref_time())
+		Ok(TxCost::weight(dispatch_info.weight.ref_time()))
 	}
 
 	async fn finality_notifications(
 		&self,
 	) -> Result<
-		Pin<Box<dyn Stream<Item = <Self as IbcProvider>::FinalityEvent> + Send + Sync>>,
+		Pin<Box<dyn Stream<Item = <Self as IbcEventProvider>::FinalityEvent> + Send + Sync>>,
 		Error,
 	> {
-		match self.finality_protocol {
-			FinalityProtocol::Grandpa => {
-				let subscription =
-					GrandpaApiClient::<JustificationNotification, sp_core::H256, u32>::subscribe_justifications(
-						&*self.relay_ws_client,
-					)
+		// parachains under the same relay chain share a single subscription to its finality.
+		let key = format!("{}/{:?}", self.relay_chain_rpc_url, self.finality_protocol);
+		let relay_ws_client = self.relay_ws_client.clone();
+		let finality_protocol = self.finality_protocol.clone();
+		RelayFinalityMux::global()
+			.subscribe(key, || async move {
+				match finality_protocol {
+					FinalityProtocol::Grandpa => {
+						let subscription = GrandpaApiClient::<
+							JustificationNotification,
+							sp_core::H256,
+							u32,
+						>::subscribe_justifications(&*relay_ws_client)
 						.await?
 						.chunks(3)
 						.map(|mut notifs| notifs.remove(notifs.len() - 1)); // skip every 3 finality notifications
 
-				let stream = subscription.filter_map(|justification_notif| {
-					let encoded_justification = match justification_notif {
-						Ok(JustificationNotification(sp_core::Bytes(justification))) =>
-							justification,
-						Err(err) => {
-							log::error!("Failed to fetch Justification: {}", err);
-							return futures::future::ready(None)
-						},
-					};
-
-					let justification =
-						match GrandpaJustification::decode(&mut &*encoded_justification) {
-							Ok(j) => j,
-							Err(err) => {
-								log::error!("Grandpa Justification scale decode error: {}", err);
-								return futures::future::ready(None)
-							},
-						};
-					futures::future::ready(Some(Self::FinalityEvent::Grandpa(justification)))
-				});
-
-				Ok(Box::pin(Box::new(stream)))
-			},
-			FinalityProtocol::Beefy => {
-				let subscription =
-					BeefyApiClient::<JustificationNotification, sp_core::H256>::subscribe_justifications(
-						&*self.relay_ws_client,
-					)
-						.await
-						.expect("Failed to subscribe to beefy justifications");
-
-				let stream = subscription.filter_map(|commitment_notification| {
-					let encoded_commitment = match commitment_notification {
-						Ok(JustificationNotification(sp_core::Bytes(commitment))) => commitment,
-						Err(err) => {
-							log::error!("Failed to fetch Commitment: {}", err);
-							return futures::future::ready(None)
-						},
-					};
-
-					let signed_commitment =
-						match BeefyJustification::decode(&mut &*encoded_commitment) {
-							Ok(c) => c,
-							Err(err) => {
-								log::error!("SignedCommitment scale decode error: {}", err);
-								return futures::future::ready(None)
-							},
-						};
-					futures::future::ready(Some(Self::FinalityEvent::Beefy(signed_commitment)))
-				});
-
-				Ok(Box::pin(Box::new(stream)))
-			},
-		}
+						let stream = subscription.filter_map(|justification_notif| {
+							let encoded_justification = match justification_notif {
+								Ok(JustificationNotification(sp_core::Bytes(justification))) =>
+									justification,
+								Err(err) => {
+									log::error!("Failed to fetch Justification: {}", err);
+									return futures::future::ready(None)
+								},
+							};
+
+							let justification =
+								match GrandpaJustification::decode(&mut &*encoded_justification) {
+									Ok(j) => j,
+									Err(err) => {
+										log::error!(
+											"Grandpa Justification scale decode error: {}",
+											err
+										);
+										return futures::future::ready(None)
+									},
+								};
+							futures::future::ready(Some(FinalityEvent::Grandpa(justification)))
+						});
+
+						Ok::<FinalityStream, Error>(Box::pin(Box::new(stream)))
+					},
+					FinalityProtocol::Beefy => {
+						let subscription =
+							BeefyApiClient::<JustificationNotification, sp_core::H256>::subscribe_justifications(
+								&*relay_ws_client,
+							)
+								.await?;
+
+						let stream = subscription.filter_map(|commitment_notification| {
+							let encoded_commitment = match commitment_notification {
+								Ok(JustificationNotification(sp_core::Bytes(commitment))) =>
+									commitment,
+								Err(err) => {
+									log::error!("Failed to fetch Commitment: {}", err);
+									return futures::future::ready(None)
+								},
+							};
+
+							let signed_commitment =
+								match BeefyJustification::decode(&mut &*encoded_commitment) {
+									Ok(c) => c,
+									Err(err) => {
+										log::error!("SignedCommitment scale decode error: {}", err);
+										return futures::future::ready(None)
+									},
+								};
+							futures::future::ready(Some(FinalityEvent::Beefy(signed_commitment)))
+						});
+
+						Ok::<FinalityStream, Error>(Box::pin(Box::new(stream)))
+					},
+				}
+			})
+			.await
 	}
 
 	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Error> {
-		let messages = messages
-			.into_iter()
-			.map(|msg| Any { type_url: msg.type_url.clone(), value: msg.value })
-			.collect::<Vec<_>>();
-		let messages_urls = messages.iter().map(|msg| msg.type_url.clone()).join(", ");
-		let messages_urls_c = messages_urls.clone();
-		log::debug!(target: "hyperspace_parachain", "Sending message: {messages_urls_c}");
+		let messages_urls = messages.iter().map(|msg| msg.type_url.as_str()).join(", ");
+		log::debug!(target: "hyperspace_parachain", "Sending message: {messages_urls}");
 
-		let call = T::Tx::ibc_deliver(messages.clone());
-		let (ext_hash, block_hash) = self.submit_call(call).await?;
+		// the call is built again from the messages whenever its extrinsic has to be resubmitted
+		let (ext_hash, block_hash) =
+			self.submit_call(move || T::Tx::ibc_deliver(messages.clone())).await?;
 
 		log::debug!(target: "hyperspace_parachain", "Submitted extrinsic (hash: {:?}) to block {:?}", ext_hash, block_hash);
 
 		Ok(TransactionId { ext_hash, block_hash })
 	}
 
+	async fn submit_and_wait_for_events(&self, messages: Vec<Any>) -> Result<Vec<IbcEvent>, Error> {
+		let (ext_hash, block_hash, events) = self
+			.submit_call_with_events(move || T::Tx::ibc_deliver(messages.clone()))
+			.await?;
+		log::debug!(target: "hyperspace_parachain", "Submitted extrinsic (hash: {:?}) to block {:?}", ext_hash, block_hash);
+
+		let mut ibc_events = vec![];
+		for event in events.find::<<T::Events as AsInnerEvent>::Inner>() {
+			let events = <T::Events as AsInnerEvent>::from_inner(event?).events();
+			for event in events {
+				let event: IbcEvent = event.try_into().map_err(|_| {
+					Error::from(format!("Failed to decode an IBC event of extrinsic {ext_hash:?}"))
+				})?;
+				ibc_events.push(event);
+			}
+		}
+		Ok(ibc_events)
+	}
+
+	async fn submit_batch(
+		&self,
+		batches: Vec<Vec<Any>>,
+	) -> Result<Vec<Self::TransactionId>, Error> {
+		let can_batch =
+			self.batch_calls && self.para_client.metadata().pallet_by_name("Utility").is_some();
+		if !can_batch {
+			let mut ids = Vec::with_capacity(batches.len());
+			for messages in batches {
+				ids.push(self.submit(messages).await?);
+			}
+			return Ok(ids)
+		}
+
+		// pack consecutive sets into `utility.batch_all` extrinsics as long as they fit into the
+		// maximum extrinsic weight and size.
+		let max_size = self.max_transaction_size().unwrap_or(usize::MAX);
+		let mut ids = vec![];
+		let mut pending = vec![];
+		let mut pending_weight = 0u64;
+		let mut pending_size = 0usize;
+		for messages in batches {
+			let weight = self.estimate_cost(messages.clone()).await?.amount;
+			let size = BATCHED_DELIVER_OVERHEAD +
+				messages.iter().map(prost::Message::encoded_len).sum::<usize>();
+			if !pending.is_empty() &&
+				(pending_weight.saturating_add(weight) > self.max_extrinsic_weight ||
+					pending_size.saturating_add(size) > max_size)
+			{
+				ids.push(self.submit_batched_deliver(std::mem::take(&mut pending)).await?);
+				pending_weight = 0;
+				pending_size = 0;
+			}
+			pending_weight = pending_weight.saturating_add(weight);
+			pending_size = pending_size.saturating_add(size);
+			pending.push(messages);
+		}
+		if !pending.is_empty() {
+			ids.push(self.submit_batched_deliver(pending).await?);
+		}
+		Ok(ids)
+	}
+
 	async fn query_client_message(&self, update: UpdateClient) -> Result<AnyClientMessage, Error> {
 		let host_height = update.height();
 
@@ -339,38 +428,95 @@ where
 		} else if err_str.contains("RestartNeeded") || err_str.contains("restart required") {
 			self.reconnect().await?;
 			self.common_state.rpc_call_delay = self.common_state.rpc_call_delay * 2;
+		} else if err_str.contains("Metadata") ||
+			err_str.contains("bad signature") ||
+			err_str.contains("BadProof")
+		{
+			// transactions signed with an outdated spec version or encoded against outdated
+			// metadata are rejected after a runtime upgrade the watcher may have missed.
+			if refresh_runtime_metadata(&self.para_client).await? {
+				log::info!(target: "hyperspace", "Refreshed runtime metadata of {}", self.name);
+			}
+			refresh_runtime_metadata(&self.relay_client).await?;
 		}
 
 		Ok(())
 	}
 
 	async fn reconnect(&mut self) -> anyhow::Result<()> {
-		let relay_ws_client = Arc::new(
-			WsClientBuilder::default()
-				.build(&self.relay_chain_rpc_url)
-				.await
-				.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
-		);
-		let para_ws_client = Arc::new(
-			WsClientBuilder::default()
-				.build(&self.parachain_rpc_url)
-				.await
-				.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
-		);
-
-		let para_client = subxt::OnlineClient::from_rpc_client(para_ws_client.clone()).await?;
-		let relay_client = subxt::OnlineClient::from_rpc_client(relay_ws_client.clone()).await?;
+		let (relay_chain_rpc_url, relay_ws_client) = connect_to_healthy_endpoint(
+			&self.relay_chain_rpc_urls,
+			Some(&self.relay_chain_rpc_url),
+		)
+		.await?;
+		let (parachain_rpc_url, para_ws_client) =
+			connect_to_healthy_endpoint(&self.parachain_rpc_urls, Some(&self.parachain_rpc_url))
+				.await?;
+
+		// the light client manages its own connections, only the rpc nodes need reconnecting
+		if self.light_client.is_none() {
+			let para_client = subxt::OnlineClient::from_rpc_client(para_ws_client.clone()).await?;
+			let relay_client =
+				subxt::OnlineClient::from_rpc_client(relay_ws_client.clone()).await?;
+			spawn_runtime_upgrade_watcher(self.name.clone(), para_client.clone());
+			spawn_runtime_upgrade_watcher(
+				format!("{} relay chain", self.name),
+				relay_client.clone(),
+			);
+			self.relay_client = relay_client;
+			self.para_client = para_client;
+		}
 
+		if parachain_rpc_url != self.parachain_rpc_url {
+			log::info!(target: "hyperspace", "{}: failed over to parachain rpc endpoint {parachain_rpc_url}", self.name);
+		}
+		if relay_chain_rpc_url != self.relay_chain_rpc_url {
+			log::info!(target: "hyperspace", "{}: failed over to relay chain rpc endpoint {relay_chain_rpc_url}", self.name);
+		}
+		self.parachain_rpc_url = parachain_rpc_url;
+		self.relay_chain_rpc_url = relay_chain_rpc_url;
 		self.relay_ws_client = relay_ws_client;
 		self.para_ws_client = para_ws_client;
-		self.relay_client = relay_client;
-		self.para_client = para_client;
 
 		log::info!(target: "hyperspace", "Reconnected to relay chain and parachain");
 
 		Ok(())
 	}
 
+	fn active_rpc_endpoint(&self) -> Option<String> {
+		Some(self.parachain_rpc_url.clone())
+	}
+
+	async fn is_node_syncing(&self) -> Result<Option<bool>, Self::Error> {
+		let health = self.para_client.rpc().system_health().await?;
+		Ok(Some(health.is_syncing))
+	}
+
+	async fn ibc_module_version(&self) -> Result<Option<String>, Self::Error> {
+		if self.para_client.metadata().pallet_by_name("Ibc").is_none() {
+			return Err(Error::from("The runtime doesn't include pallet-ibc".to_owned()))
+		}
+		let mut storage_key = twox_128(b"Ibc").to_vec();
+		storage_key.extend(twox_128(b":__STORAGE_VERSION__:").to_vec());
+		let storage_version = match self.para_client.rpc().storage(&*storage_key, None).await? {
+			Some(data) => u16::decode(&mut &*data.0)
+				.map_err(|e| Error::from(format!("Failed to decode storage version: {:?}", e)))?,
+			None => 0,
+		};
+		Ok(Some(format!(
+			"pallet-ibc storage version {storage_version}, runtime spec version {}",
+			self.para_client.runtime_version().spec_version
+		)))
+	}
+
+	async fn rotate_signing_key(
+		&self,
+		private_key: String,
+	) -> Result<ibc::signer::Signer, Self::Error> {
+		ParachainClient::rotate_signing_key(self, &private_key)?;
+		Ok(primitives::KeyProvider::account_id(self))
+	}
+
 	fn common_state(&self) -> &CommonClientState {
 		&self.common_state
 	}
@@ -501,8 +647,12 @@ where
 						second_finality_proof: trusted_finality_proof,
 					});
 
+					if primitives::is_dry_run() {
+						log::info!(target: "hyperspace", "[dry-run] Not submitting the misbehaviour report of {} to {}", self.name(), counterparty.name());
+						return Ok(())
+					}
 					counterparty
-						.submit(vec![MsgUpdateAnyClient::<LocalClientTypes>::new(
+						.submit(vec![MsgSubmitMisbehaviour::<LocalClientTypes>::new(
 							self.client_id(),
 							AnyClientMessage::Grandpa(misbehaviour.clone()),
 							counterparty.account_id(),
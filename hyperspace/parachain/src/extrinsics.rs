@@ -0,0 +1,233 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::Error;
+use futures::{future::ready, Stream, StreamExt};
+use std::{
+	collections::VecDeque,
+	fmt::{Debug, Display, Formatter},
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+use subxt::{
+	tx::{TxInBlock, TxProgress, TxStatus},
+	Config, OnlineClient,
+};
+
+/// Number of submitted extrinsics whose status is kept.
+pub const EXTRINSIC_HISTORY_SIZE: usize = 1024;
+
+/// How long the finality of an extrinsic included in a block is followed.
+const FOLLOW_FINALITY_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Last known status of a submitted extrinsic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtrinsicStatus<H> {
+	/// In the transaction pool
+	Pending,
+	InBlock(H),
+	Finalized(H),
+	/// The block it was included in was reorganized away, it's back in the pool
+	Retracted(H),
+	/// The block it was included in wasn't finalized in time
+	FinalityTimeout(H),
+	/// Replaced in the pool by another extrinsic with the same nonce
+	Usurped,
+	/// Dropped from the pool, e.g. because it was full
+	Dropped,
+	/// Rejected by the pool, e.g. because of an outdated nonce or era
+	Invalid,
+}
+
+impl<H> ExtrinsicStatus<H> {
+	/// Whether the extrinsic left the pool without being included, so its messages have to be
+	/// submitted again.
+	pub fn is_dropped(&self) -> bool {
+		matches!(self, Self::Usurped | Self::Dropped | Self::Invalid)
+	}
+
+	/// Whether the block the extrinsic was included in won't be finalized and it wasn't included
+	/// in another one, so its call has to be submitted again.
+	pub fn is_unfinalized(&self) -> bool {
+		matches!(self, Self::Retracted(_) | Self::FinalityTimeout(_)) || self.is_dropped()
+	}
+}
+
+impl<H: Debug> Display for ExtrinsicStatus<H> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Pending => write!(f, "pending"),
+			Self::InBlock(block) => write!(f, "in block {block:?}"),
+			Self::Finalized(block) => write!(f, "finalized in block {block:?}"),
+			Self::Retracted(block) => write!(f, "retracted from block {block:?}"),
+			Self::FinalityTimeout(block) => write!(f, "not finalized in block {block:?}"),
+			Self::Usurped => write!(f, "usurped"),
+			Self::Dropped => write!(f, "dropped"),
+			Self::Invalid => write!(f, "invalid"),
+		}
+	}
+}
+
+/// Statuses of the latest extrinsics submitted by the relayer, by extrinsic hash.
+#[derive(Clone)]
+pub struct ExtrinsicTracker<H> {
+	statuses: Arc<Mutex<VecDeque<(H, ExtrinsicStatus<H>)>>>,
+}
+
+impl<H> Default for ExtrinsicTracker<H> {
+	fn default() -> Self {
+		Self { statuses: Default::default() }
+	}
+}
+
+impl<H: Copy + PartialEq + Debug + Send + 'static> ExtrinsicTracker<H> {
+	pub fn status(&self, extrinsic: &H) -> Option<ExtrinsicStatus<H>> {
+		let statuses = self.statuses.lock().unwrap();
+		statuses.iter().find(|(hash, _)| hash == extrinsic).map(|(_, status)| *status)
+	}
+
+	pub fn set_status(&self, extrinsic: H, status: ExtrinsicStatus<H>) {
+		log::debug!(target: "hyperspace_parachain", "Extrinsic {extrinsic:?} is {status}");
+		let mut statuses = self.statuses.lock().unwrap();
+		match statuses.iter_mut().find(|(hash, _)| *hash == extrinsic) {
+			Some((_, previous)) => *previous = status,
+			None => {
+				statuses.push_back((extrinsic, status));
+				if statuses.len() > EXTRINSIC_HISTORY_SIZE {
+					statuses.pop_front();
+				}
+			},
+		}
+	}
+
+	/// Follows `progress` until the extrinsic is included in a block, or leaves the pool without
+	/// being included, in which case its last status is returned.
+	pub async fn wait_for_in_block<T: Config<Hash = H>>(
+		&self,
+		progress: &mut TxProgress<T, OnlineClient<T>>,
+	) -> Result<Result<TxInBlock<T, OnlineClient<T>>, ExtrinsicStatus<H>>, Error> {
+		let extrinsic = progress.extrinsic_hash();
+		self.set_status(extrinsic, ExtrinsicStatus::Pending);
+		while let Some(tx_status) = progress.next().await {
+			let tx_status = tx_status?;
+			let status = extrinsic_status(&tx_status);
+			self.set_status(extrinsic, status);
+			match tx_status {
+				TxStatus::InBlock(tx_in_block) | TxStatus::Finalized(tx_in_block) =>
+					return Ok(Ok(tx_in_block)),
+				_ if status.is_dropped() => return Ok(Err(status)),
+				_ => {},
+			}
+		}
+		Err(Error::from(format!("Lost track of extrinsic {extrinsic:?} before it was included")))
+	}
+
+	/// Follows the statuses of an extrinsic that was included in a block until its block is
+	/// finalized, for at most [`FOLLOW_FINALITY_TIMEOUT`], and returns the last one.
+	pub async fn follow_finality(
+		&self,
+		extrinsic: H,
+		statuses: impl Stream<Item = ExtrinsicStatus<H>>,
+	) -> Option<ExtrinsicStatus<H>> {
+		futures::pin_mut!(statuses);
+		let mut last = None;
+		let follow = async {
+			while let Some(status) = statuses.next().await {
+				self.set_status(extrinsic, status);
+				last = Some(status);
+				if matches!(
+					status,
+					ExtrinsicStatus::Finalized(_) | ExtrinsicStatus::FinalityTimeout(_)
+				) || status.is_dropped()
+				{
+					break
+				}
+			}
+		};
+		if tokio::time::timeout(FOLLOW_FINALITY_TIMEOUT, follow).await.is_err() {
+			log::debug!(target: "hyperspace_parachain", "Stopped following extrinsic {extrinsic:?} before it was finalized");
+		}
+		last
+	}
+}
+
+/// Statuses of a submitted extrinsic, until the node stops reporting them.
+pub fn extrinsic_statuses<T: Config>(
+	progress: TxProgress<T, OnlineClient<T>>,
+) -> impl Stream<Item = ExtrinsicStatus<T::Hash>> {
+	progress.scan((), |_, tx_status| ready(tx_status.ok().map(|status| extrinsic_status(&status))))
+}
+
+fn extrinsic_status<T: Config>(status: &TxStatus<T, OnlineClient<T>>) -> ExtrinsicStatus<T::Hash> {
+	match status {
+		TxStatus::Future | TxStatus::Ready | TxStatus::Broadcast(_) => ExtrinsicStatus::Pending,
+		TxStatus::InBlock(tx_in_block) => ExtrinsicStatus::InBlock(tx_in_block.block_hash()),
+		TxStatus::Finalized(tx_in_block) => ExtrinsicStatus::Finalized(tx_in_block.block_hash()),
+		TxStatus::Retracted(block) => ExtrinsicStatus::Retracted(*block),
+		TxStatus::FinalityTimeout(block) => ExtrinsicStatus::FinalityTimeout(*block),
+		TxStatus::Usurped(_) => ExtrinsicStatus::Usurped,
+		TxStatus::Dropped => ExtrinsicStatus::Dropped,
+		TxStatus::Invalid => ExtrinsicStatus::Invalid,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ExtrinsicStatus::*;
+
+	#[tokio::test]
+	async fn follows_the_finality_of_included_extrinsics() {
+		let cases = [
+			(vec![InBlock(1), Finalized(1)], Some(Finalized(1)), false),
+			(
+				vec![InBlock(1), Retracted(1), Pending, InBlock(2), Finalized(2)],
+				Some(Finalized(2)),
+				false,
+			),
+			(vec![InBlock(1), Retracted(1)], Some(Retracted(1)), true),
+			(vec![InBlock(1), FinalityTimeout(1)], Some(FinalityTimeout(1)), true),
+			(vec![InBlock(1), Retracted(1), Usurped], Some(Usurped), true),
+			(vec![InBlock(1)], Some(InBlock(1)), false),
+			(vec![], None, false),
+		];
+		for (statuses, expected, unfinalized) in cases {
+			let tracker = ExtrinsicTracker::default();
+			let last = tracker.follow_finality(0u32, futures::stream::iter(statuses.clone())).await;
+			assert_eq!(last, expected, "{statuses:?}");
+			assert_eq!(last.map_or(false, |status| status.is_unfinalized()), unfinalized);
+			assert_eq!(tracker.status(&0), expected);
+		}
+	}
+
+	#[tokio::test]
+	async fn stops_following_finalized_extrinsics() {
+		let tracker = ExtrinsicTracker::default();
+		let statuses = futures::stream::iter([InBlock(1), Finalized(1), Retracted(1)]);
+		assert_eq!(tracker.follow_finality(0u32, statuses).await, Some(Finalized(1)));
+	}
+
+	#[test]
+	fn keeps_the_statuses_of_the_latest_extrinsics() {
+		let tracker = ExtrinsicTracker::default();
+		for extrinsic in 0..EXTRINSIC_HISTORY_SIZE as u32 {
+			tracker.set_status(extrinsic, Pending);
+		}
+		tracker.set_status(0, InBlock(1));
+		tracker.set_status(EXTRINSIC_HISTORY_SIZE as u32, Dropped);
+		assert_eq!(tracker.status(&0), None);
+		assert_eq!(tracker.status(&1), Some(Pending));
+		assert_eq!(tracker.status(&(EXTRINSIC_HISTORY_SIZE as u32)), Some(Dropped));
+	}
+}
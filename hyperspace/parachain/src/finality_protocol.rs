@@ -18,32 +18,38 @@ use crate::{error::Error, ParachainClient};
 use anyhow::anyhow;
 use beefy_light_client_primitives::{ClientState as BeefyPrimitivesClientState, NodesUtils};
 use codec::{Decode, Encode};
-use finality_grandpa::BlockNumberOps;
+use finality_grandpa::{voter_set::VoterSet, BlockNumberOps};
 use finality_grandpa_rpc::GrandpaApiClient;
+use futures::StreamExt;
 use grandpa_light_client_primitives::{
-	justification::find_scheduled_change, FinalityProof, ParachainHeaderProofs,
-	ParachainHeadersWithFinalityProof,
+	justification::{find_forced_change, find_scheduled_change},
+	FinalityProof, ParachainHeaderProofs, ParachainHeadersWithFinalityProof,
 };
 use ibc::{
-	core::ics02_client::{client_state::ClientState as _, msgs::update_client::MsgUpdateAnyClient},
+	core::ics02_client::{
+		client_state::ClientState as _,
+		msgs::{misbehaviour::MsgSubmitMisbehaviour, update_client::MsgUpdateAnyClient},
+	},
 	events::IbcEvent,
 	tx_msg::Msg,
 	Height,
 };
 use ibc_proto::google::protobuf::Any;
 use ibc_rpc::{BlockNumberOrHash, IbcApiClient};
-use ics10_grandpa::client_message::{ClientMessage, Header as GrandpaHeader};
+use ics10_grandpa::client_message::{
+	ClientMessage, Header as GrandpaHeader, Misbehaviour, RelayChainHeader,
+};
 use ics11_beefy::client_message::{
 	BeefyHeader, ClientMessage as BeefyClientMessage, ParachainHeadersWithProof,
 };
-use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState};
+use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState, HostFunctionsManager};
 use primitives::{
 	filter_events_by_ids, mock::LocalClientTypes, query_maximum_height_for_timeout_proofs, Chain,
-	IbcProvider, KeyProvider, UpdateType,
+	IbcEventProvider, IbcQueryProvider, KeyProvider, UpdateType,
 };
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use sp_consensus_grandpa::GRANDPA_ENGINE_ID;
+use sp_consensus_grandpa::{AuthorityList, GRANDPA_ENGINE_ID};
 use sp_core::H256;
 use sp_runtime::{
 	traits::{BlakeTwo256, IdentifyAccount, One, Verify},
@@ -52,27 +58,34 @@ use sp_runtime::{
 use std::{
 	collections::{BTreeMap, BTreeSet, HashMap},
 	fmt::{Debug, Display},
+	sync::Arc,
 	time::Duration,
 };
 
 use beefy_prover::helpers::unsafe_arc_cast;
 use grandpa_prover::{
-	GrandpaJustification, GrandpaProver, JustificationNotification, PROCESS_BLOCKS_BATCH_SIZE,
+	host_functions::HostFunctionsProvider, GrandpaJustification, GrandpaProver,
+	JustificationNotification, PROCESS_BLOCKS_BATCH_SIZE,
 };
 use subxt::config::{
 	extrinsic_params::BaseExtrinsicParamsBuilder, ExtrinsicParams, Header as HeaderT, Header,
 };
-use tendermint_proto::Protobuf;
 use tokio::task::JoinSet;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Source of the relay chain finality used to update the parachain's light client on the
+/// counterparty.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub enum FinalityProtocol {
+	/// Relay chain GRANDPA justifications, supported on every relay chain.
+	#[default]
 	Grandpa,
+	/// BEEFY commitments over the relay chain MMR, cheaper to verify when the relay chain has
+	/// BEEFY enabled.
 	Beefy,
 }
 
 /// Finality event for parachains
-#[derive(Decode, Encode, Debug)]
+#[derive(Decode, Encode, Debug, Clone)]
 pub enum FinalityEvent {
 	Grandpa(
 		grandpa_light_client_primitives::justification::GrandpaJustification<
@@ -351,12 +364,11 @@ where
 			})),
 			signer: counterparty.account_id(),
 		};
-		let value = msg.encode_vec()?;
-		Any { value, type_url: msg.type_url() }
+		msg.to_any()
 	};
 
-	// FIXME: use height from the beefy header
-	Ok(vec![(update_header, Height::new(0, 0), events, update_type)])
+	let height = Height::new(source.para_id as u64, latest_finalized_block as u64);
+	Ok(vec![(update_header, height, events, update_type)])
 }
 
 async fn find_next_justification<T>(
@@ -428,6 +440,228 @@ where
 	Ok(None)
 }
 
+/// Relay chain GRANDPA justification, as received from the finality notifications.
+pub type RelayChainJustification =
+	grandpa_light_client_primitives::justification::GrandpaJustification<
+		polkadot_core_primitives::Header,
+	>;
+
+/// Maximum number of relay chain headers fetched to prove the ancestry of a conflicting
+/// justification, from the latest relay chain block trusted by the light client.
+pub const MAX_EQUIVOCATION_ANCESTRY: u32 = 4096;
+
+/// Relay chain node queried for GRANDPA justifications conflicting with the ones relayed, run
+/// independently of the relay chain rpc endpoints.
+#[derive(Clone)]
+pub struct RelayWitness<T: subxt::Config> {
+	/// rpc url of the node
+	pub url: String,
+	/// rpc client of the node
+	pub client: subxt::OnlineClient<T>,
+	/// ws client of the node
+	pub ws_client: Arc<jsonrpsee_ws_client::WsClient>,
+}
+
+/// Asks the witness which block it finalized at the height of the observed justification, and if
+/// it's another one, returns the misbehaviour that freezes the counterparty's grandpa client.
+/// Nothing but the block hash is queried unless the witness disagrees.
+pub async fn query_grandpa_equivocation<T>(
+	prover: &GrandpaProver<T>,
+	witness: &RelayWitness<T>,
+	observed: &RelayChainJustification,
+	client_state: &grandpa_light_client_primitives::ClientState,
+) -> anyhow::Result<Option<Misbehaviour>>
+where
+	T: light_client_common::config::Config + Send + Sync,
+	u32: From<<<T as subxt::Config>::Header as HeaderT>::Number>
+		+ From<<<T as subxt::Config>::Header as Header>::Number>,
+	<<T as subxt::Config>::Header as Header>::Number:
+		BlockNumberOps + From<u32> + Display + Ord + sp_runtime::traits::Zero + One + Send + Sync,
+	T::Hash: From<sp_core::H256> + From<[u8; 32]>,
+	sp_core::H256: From<T::Hash>,
+{
+	let height = observed.commit.target_number;
+	if height <= client_state.latest_relay_height {
+		return Ok(None)
+	}
+	let Some(hash) = witness.client.rpc().block_hash(Some(height.into())).await? else {
+		return Ok(None)
+	};
+	if H256::from(hash) == observed.commit.target_hash {
+		return Ok(None)
+	}
+
+	// the witness may still be following a fork it hasn't finalized, in which case it has no
+	// proof of finality for it
+	let Ok(Some(encoded)) =
+		GrandpaApiClient::<JustificationNotification, H256, u32>::prove_finality(
+			&*witness.ws_client,
+			height,
+		)
+		.await
+	else {
+		return Ok(None)
+	};
+	let proof = FinalityProof::<RelayChainHeader>::decode(&mut &encoded.0[..])?;
+	let competing = RelayChainJustification::decode(&mut &*proof.justification)?;
+
+	let first_headers =
+		query_ancestry(&prover.relay_client, client_state, observed.commit.target_hash).await?;
+	let second_headers =
+		query_ancestry(&witness.client, client_state, competing.commit.target_hash).await?;
+	let misbehaviour = Misbehaviour {
+		first_finality_proof: FinalityProof {
+			block: observed.commit.target_hash,
+			justification: observed.encode(),
+			unknown_headers: first_headers,
+		},
+		second_finality_proof: FinalityProof {
+			block: competing.commit.target_hash,
+			justification: competing.encode(),
+			unknown_headers: second_headers,
+		},
+	};
+	if let Err(e) = check_grandpa_misbehaviour(client_state, &misbehaviour) {
+		log::debug!(target: "hyperspace_parachain", "Witness {} disagrees at relay height {height} without an equivocation: {e}", witness.url);
+		return Ok(None)
+	}
+
+	log::warn!(
+		target: "hyperspace",
+		"Found conflicting GRANDPA justifications at relay height {height}: {:?} (round {}) != {:?} (round {}) reported by {}",
+		observed.commit.target_hash,
+		observed.round,
+		competing.commit.target_hash,
+		competing.round,
+		witness.url
+	);
+	Ok(Some(misbehaviour))
+}
+
+/// Relay chain headers from the child of the latest relay chain block trusted by the light client
+/// up to `target`, walking the parent hashes back from `target`.
+async fn query_ancestry<T>(
+	client: &subxt::OnlineClient<T>,
+	client_state: &grandpa_light_client_primitives::ClientState,
+	target: H256,
+) -> anyhow::Result<Vec<RelayChainHeader>>
+where
+	T: light_client_common::config::Config + Send + Sync,
+	T::Hash: From<sp_core::H256>,
+{
+	let mut headers = vec![];
+	let mut hash = target;
+	while hash != client_state.latest_relay_hash {
+		if headers.len() as u32 >= MAX_EQUIVOCATION_ANCESTRY {
+			return Err(anyhow!(
+				"{target:?} isn't within {MAX_EQUIVOCATION_ANCESTRY} blocks of {:?}",
+				client_state.latest_relay_hash
+			))
+		}
+		let header = client
+			.rpc()
+			.header(Some(hash.into()))
+			.await?
+			.ok_or_else(|| anyhow!("Relay chain header {hash:?} not found"))?;
+		let header = RelayChainHeader::decode(&mut &*header.encode())?;
+		if header.number <= client_state.latest_relay_height {
+			return Err(anyhow!(
+				"{target:?} doesn't descend from {:?}",
+				client_state.latest_relay_hash
+			))
+		}
+		hash = header.parent_hash;
+		headers.push(header);
+	}
+	headers.reverse();
+	Ok(headers)
+}
+
+/// Runs the checks of the grandpa light client on a misbehaviour before it's submitted: both
+/// proofs must finalize blocks of conflicting forks built on the latest relay chain block trusted
+/// by the client, with justifications of its current authority set.
+pub fn check_grandpa_misbehaviour(
+	client_state: &grandpa_light_client_primitives::ClientState,
+	misbehaviour: &Misbehaviour,
+) -> anyhow::Result<()> {
+	let proofs = [&misbehaviour.first_finality_proof, &misbehaviour.second_finality_proof];
+	for (proof, other) in [(proofs[0], proofs[1]), (proofs[1], proofs[0])] {
+		let base = proof.unknown_headers.first().ok_or_else(|| anyhow!("No ancestry headers"))?;
+		if base.parent_hash != client_state.latest_relay_hash {
+			return Err(anyhow!("{:?} isn't built on the latest trusted block", proof.block))
+		}
+		let mut parent = base.parent_hash;
+		for header in &proof.unknown_headers {
+			if header.parent_hash != parent {
+				return Err(anyhow!("Broken ancestry of {:?}", proof.block))
+			}
+			parent = sp_runtime::traits::Header::hash(header);
+			if parent == other.block {
+				return Err(anyhow!("{:?} descends from {:?}", proof.block, other.block))
+			}
+		}
+		if parent != proof.block {
+			return Err(anyhow!("The ancestry of {:?} ends at {parent:?}", proof.block))
+		}
+
+		let justification = RelayChainJustification::decode(&mut &*proof.justification)?;
+		if justification.commit.target_hash != proof.block {
+			return Err(anyhow!("Justification of {:?} is for another block", proof.block))
+		}
+		justification
+			.verify::<HostFunctionsProvider>(
+				client_state.current_set_id,
+				&client_state.current_authorities,
+			)
+			.map_err(|e| anyhow!("Invalid justification of {:?}: {e:?}", proof.block))?;
+	}
+	Ok(())
+}
+
+/// Returns true if the relay chain header signals a GRANDPA authority set change, standard or
+/// forced. The counterparty's client can't follow the relay chain past such a block without
+/// seeing it, so updates targeting it are mandatory.
+pub fn is_authority_set_handoff(header: &RelayChainHeader) -> bool {
+	find_scheduled_change(header).is_some() || find_forced_change(header).is_some()
+}
+
+/// Shrinks a GRANDPA header before it's submitted to the counterparty. The justification keeps
+/// only the precommits and ancestry headers needed to convince the client's current authority
+/// set, and the relay chain block the client already trusts is dropped along with any parachain
+/// header proven against it.
+pub fn compress_grandpa_header(
+	header: &mut GrandpaHeader,
+	current_authorities: &AuthorityList,
+	latest_relay_hash: H256,
+) -> anyhow::Result<()> {
+	let voters = VoterSet::new(current_authorities.iter().cloned())
+		.ok_or_else(|| anyhow!("Invalid authority set in client state"))?;
+	let mut justification =
+		RelayChainJustification::decode(&mut &*header.finality_proof.justification)?;
+	match justification.prune(&voters) {
+		Ok(()) => header.finality_proof.justification = justification.encode(),
+		Err(e) => log::warn!(target: "hyperspace_parachain", "Not pruning justification: {e:?}"),
+	}
+
+	let unknown_headers = &mut header.finality_proof.unknown_headers;
+	if unknown_headers.len() > 1 {
+		unknown_headers.retain(|h| sp_runtime::traits::Header::hash(h) != latest_relay_hash);
+		header.parachain_headers.remove(&latest_relay_hash);
+	}
+
+	Ok(())
+}
+
+/// Client update built ahead of the finality event that needs it, see
+/// [`IbcEventProvider::prefetch_client_update`].
+pub struct PrefetchedUpdate {
+	/// Latest relay chain and parachain heights of the light client when the update was built.
+	client_heights: (u32, u32),
+	/// Relay chain height finalized by the justification the update was built from.
+	relay_height: u32,
+	updates: Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>,
+}
+
 /// Query the latest events that have been finalized by the GRANDPA finality protocol.
 pub async fn query_latest_ibc_events_with_grandpa<T, C>(
 	source: &mut ParachainClient<T>,
@@ -456,10 +690,99 @@ where
 	<T as subxt::Config>::AccountId: Send + Sync,
 	<T as subxt::Config>::Address: Send + Sync,
 {
-	let _justification = match finality_event {
+	let observed_justification = match finality_event {
 		FinalityEvent::Grandpa(justification) => justification,
 		_ => panic!("Expected grandpa finality event"),
 	};
+	let client_state = query_grandpa_client_state(source, counterparty).await?;
+
+	// the prefetched update is only used if the light client hasn't moved since it was built and it
+	// isn't behind the finality event
+	let prefetched = source.prefetched_update.lock().unwrap().take();
+	if let Some(prefetched) = prefetched {
+		if prefetched.client_heights ==
+			(client_state.latest_relay_height, client_state.latest_para_height) &&
+			prefetched.relay_height >= observed_justification.commit.target_number
+		{
+			log::debug!(target: "hyperspace_parachain", "Using the prefetched client update of {} at relay height {}", source.name(), prefetched.relay_height);
+			return Ok(prefetched.updates)
+		}
+	}
+
+	build_grandpa_update(source, observed_justification, counterparty, client_state).await
+}
+
+/// Waits for the parachain block at `height` to be finalized, then builds the GRANDPA client update
+/// that proves it and keeps it for [`query_latest_ibc_events_with_grandpa`].
+pub async fn prefetch_grandpa_update<T, C>(
+	source: &ParachainClient<T>,
+	height: Height,
+	counterparty: &C,
+) -> Result<(), anyhow::Error>
+where
+	T: light_client_common::config::Config + Send + Sync,
+	C: Chain,
+	u32: From<<<T as subxt::Config>::Header as HeaderT>::Number>
+		+ From<<<T as subxt::Config>::Header as Header>::Number>,
+	ParachainClient<T>: Chain + KeyProvider,
+	<<T as light_client_common::config::Config>::Signature as Verify>::Signer:
+		From<MultiSigner> + IdentifyAccount<AccountId = T::AccountId>,
+	<T as subxt::Config>::Address: From<<T as subxt::Config>::AccountId>,
+	<T as subxt::Config>::Signature: From<MultiSignature> + Send + Sync,
+	<<T as subxt::Config>::Header as Header>::Number:
+		BlockNumberOps + From<u32> + Display + Ord + sp_runtime::traits::Zero + One + Send + Sync,
+	T::Hash: From<sp_core::H256> + From<[u8; 32]>,
+	sp_core::H256: From<T::Hash>,
+	BTreeMap<H256, ParachainHeaderProofs>:
+		From<BTreeMap<<T as subxt::Config>::Hash, ParachainHeaderProofs>>,
+	<T::ExtrinsicParams as ExtrinsicParams<T::Index, T::Hash>>::OtherParams:
+		From<BaseExtrinsicParamsBuilder<T, T::Tip>> + Send + Sync,
+	<T as subxt::Config>::Header: Decode + Send + Sync + Clone,
+	<T as subxt::Config>::AccountId: Send + Sync,
+	<T as subxt::Config>::Address: Send + Sync,
+{
+	let prover = source.grandpa_prover();
+	let mut finality_events = source.finality_notifications().await?;
+	while let Some(finality_event) = finality_events.next().await {
+		let justification = match finality_event {
+			FinalityEvent::Grandpa(justification) => justification,
+			_ => continue,
+		};
+		let finalized_para_header = prover
+			.query_latest_finalized_parachain_header(justification.commit.target_number)
+			.await?;
+		if (u32::from(finalized_para_header.number()) as u64) < height.revision_height {
+			continue
+		}
+
+		let client_state = query_grandpa_client_state(source, counterparty).await?;
+		if client_state.latest_para_height as u64 >= height.revision_height {
+			// already proven by an update that didn't wait for us
+			return Ok(())
+		}
+		let client_heights = (client_state.latest_relay_height, client_state.latest_para_height);
+		let relay_height = justification.commit.target_number;
+		let mut source = source.clone();
+		let updates =
+			build_grandpa_update(&mut source, justification, counterparty, client_state).await?;
+		*source.prefetched_update.lock().unwrap() =
+			Some(PrefetchedUpdate { client_heights, relay_height, updates });
+		log::debug!(target: "hyperspace_parachain", "Prefetched the client update of {} for {height} at relay height {relay_height}", source.name());
+		return Ok(())
+	}
+	Ok(())
+}
+
+/// Queries the GRANDPA light client of `source` on the counterparty.
+async fn query_grandpa_client_state<T, C>(
+	source: &ParachainClient<T>,
+	counterparty: &C,
+) -> Result<ics10_grandpa::client_state::ClientState<HostFunctionsManager>, anyhow::Error>
+where
+	T: light_client_common::config::Config + Send + Sync,
+	C: Chain,
+	ParachainClient<T>: Chain,
+{
 	let client_id = source.client_id();
 	let latest_height = counterparty.latest_height_and_timestamp().await?.0;
 	let response = counterparty.query_client_state(latest_height, client_id).await?;
@@ -476,7 +799,64 @@ where
 		unreachable!()
 	};
 
+	Ok(client_state)
+}
+
+/// Builds the GRANDPA client update from the light client's `client_state` to the latest finalized
+/// relay chain block.
+async fn build_grandpa_update<T, C>(
+	source: &mut ParachainClient<T>,
+	observed_justification: RelayChainJustification,
+	counterparty: &C,
+	client_state: ics10_grandpa::client_state::ClientState<HostFunctionsManager>,
+) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+where
+	T: light_client_common::config::Config + Send + Sync,
+	C: Chain,
+	u32: From<<<T as subxt::Config>::Header as HeaderT>::Number>
+		+ From<<<T as subxt::Config>::Header as Header>::Number>,
+	ParachainClient<T>: Chain + KeyProvider,
+	<<T as light_client_common::config::Config>::Signature as Verify>::Signer:
+		From<MultiSigner> + IdentifyAccount<AccountId = T::AccountId>,
+	<T as subxt::Config>::Address: From<<T as subxt::Config>::AccountId>,
+	<T as subxt::Config>::Signature: From<MultiSignature> + Send + Sync,
+	<<T as subxt::Config>::Header as Header>::Number:
+		BlockNumberOps + From<u32> + Display + Ord + sp_runtime::traits::Zero + One + Send + Sync,
+	T::Hash: From<sp_core::H256> + From<[u8; 32]>,
+	sp_core::H256: From<T::Hash>,
+	BTreeMap<H256, ParachainHeaderProofs>:
+		From<BTreeMap<<T as subxt::Config>::Hash, ParachainHeaderProofs>>,
+	<T::ExtrinsicParams as ExtrinsicParams<T::Index, T::Hash>>::OtherParams:
+		From<BaseExtrinsicParamsBuilder<T, T::Tip>> + Send + Sync,
+	<T as subxt::Config>::Header: Decode + Send + Sync + Clone,
+	<T as subxt::Config>::AccountId: Send + Sync,
+	<T as subxt::Config>::Address: Send + Sync,
+{
 	let prover = source.grandpa_prover();
+	let misbehaviour = match &source.relay_witness {
+		Some(witness) =>
+			query_grandpa_equivocation(
+				&prover,
+				witness,
+				&observed_justification,
+				&client_state.clone().into(),
+			)
+			.await
+			.unwrap_or_else(|e| {
+				log::warn!(target: "hyperspace_parachain", "Failed to compare the justification at relay height {} with witness {}: {e:?}", observed_justification.commit.target_number, witness.url);
+				None
+			}),
+		None => None,
+	};
+	if let Some(misbehaviour) = misbehaviour {
+		let msg = MsgSubmitMisbehaviour::<LocalClientTypes>::new(
+			source.client_id(),
+			AnyClientMessage::Grandpa(ClientMessage::Misbehaviour(misbehaviour)),
+			counterparty.account_id(),
+		);
+		return Ok(vec![(msg.to_any(), client_state.latest_height(), vec![], UpdateType::Mandatory)])
+	}
+
 	// prove_finality will always give us the highest block finalized by the authority set for the
 	// block number passed, so we can't miss any authority set change since the session change block
 	// will always be finalized.
@@ -666,7 +1046,7 @@ where
 	let target = sp_runtime::generic::Header::<u32, BlakeTwo256>::decode(&mut &*target)
 		.expect("Should not panic, same struct from different crates");
 
-	let authority_set_changed_scheduled = find_scheduled_change(&target).is_some();
+	let authority_set_changed_scheduled = is_authority_set_handoff(&target);
 	log::info!(target: "hyperspace_parachain", "authority_set_changed_scheduled = {authority_set_changed_scheduled}, timeout_update_required = {timeout_update_required}, is_update_required = {is_update_required}");
 	// if validator set has changed this is a mandatory update
 	let update_type =
@@ -675,12 +1055,17 @@ where
 			false => UpdateType::Optional,
 		};
 
-	let grandpa_header = GrandpaHeader {
+	let mut grandpa_header = GrandpaHeader {
 		finality_proof: codec::Decode::decode(&mut &*finality_proof.encode())
 			.expect("Same struct from different crates,decode should not fail"),
 		parachain_headers: parachain_headers.into(),
 		height: Height::new(source.para_id as u64, finalized_para_height as u64),
 	};
+	compress_grandpa_header(
+		&mut grandpa_header,
+		&client_state.current_authorities,
+		client_state.latest_relay_hash,
+	)?;
 	let height = grandpa_header.height();
 	let update_header = {
 		let msg = MsgUpdateAnyClient::<LocalClientTypes> {
@@ -688,9 +1073,111 @@ where
 			client_message: AnyClientMessage::Grandpa(ClientMessage::Header(grandpa_header)),
 			signer: counterparty.account_id(),
 		};
-		let value = msg.encode_vec()?;
-		Any { value, type_url: msg.type_url() }
+		msg.to_any()
 	};
 
 	Ok(vec![(update_header, height, events, update_type)])
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use grandpa_prover::fixtures::{AuthoritySet, GrandpaFixtures};
+
+	fn finality_proof(
+		fixtures: &mut GrandpaFixtures,
+		blocks: u32,
+	) -> FinalityProof<RelayChainHeader> {
+		fixtures.finalize(blocks).finality_proof
+	}
+
+	/// A relay chain that finalized a few blocks, and a copy of it whose next blocks differ
+	fn forks() -> (GrandpaFixtures, GrandpaFixtures) {
+		let mut fixtures = GrandpaFixtures::new(AuthoritySet::new(0, 4), 2000);
+		fixtures.finalize(2);
+		let mut fork = fixtures.clone();
+		fork.para_id += 1;
+		(fixtures, fork)
+	}
+
+	#[test]
+	fn accepts_justifications_of_conflicting_forks() {
+		let (mut fixtures, mut fork) = forks();
+		let client_state = fixtures.client_state();
+		for (first, second) in [(1, 1), (3, 3), (2, 4)] {
+			let misbehaviour = Misbehaviour {
+				first_finality_proof: finality_proof(&mut fixtures.clone(), first),
+				second_finality_proof: finality_proof(&mut fork.clone(), second),
+			};
+			check_grandpa_misbehaviour(&client_state, &misbehaviour).unwrap();
+		}
+
+		// the headers are the ones the light client needs to verify the misbehaviour
+		let misbehaviour = Misbehaviour {
+			first_finality_proof: finality_proof(&mut fixtures, 3),
+			second_finality_proof: finality_proof(&mut fork, 3),
+		};
+		for proof in [&misbehaviour.first_finality_proof, &misbehaviour.second_finality_proof] {
+			assert_eq!(proof.unknown_headers.len(), 3);
+			assert_eq!(proof.unknown_headers[0].parent_hash, client_state.latest_relay_hash);
+		}
+	}
+
+	#[test]
+	fn rejects_justifications_of_the_same_fork() {
+		let (fixtures, _) = forks();
+		let client_state = fixtures.client_state();
+		for (first, second) in [(2, 2), (1, 3), (3, 1)] {
+			let misbehaviour = Misbehaviour {
+				first_finality_proof: finality_proof(&mut fixtures.clone(), first),
+				second_finality_proof: finality_proof(&mut fixtures.clone(), second),
+			};
+			assert!(check_grandpa_misbehaviour(&client_state, &misbehaviour).is_err());
+		}
+	}
+
+	#[test]
+	fn rejects_misbehaviour_with_missing_ancestry() {
+		let (fixtures, fork) = forks();
+		let client_state = fixtures.client_state();
+		let misbehaviour = Misbehaviour {
+			first_finality_proof: finality_proof(&mut fixtures.clone(), 3),
+			second_finality_proof: finality_proof(&mut fork.clone(), 3),
+		};
+
+		let strips: [fn(&mut Vec<RelayChainHeader>); 4] = [
+			|headers| headers.clear(),
+			|headers| {
+				headers.remove(0);
+			},
+			|headers| {
+				headers.remove(1);
+			},
+			|headers| {
+				headers.pop();
+			},
+		];
+		for strip in strips {
+			let mut stripped = misbehaviour.clone();
+			strip(&mut stripped.second_finality_proof.unknown_headers);
+			assert!(check_grandpa_misbehaviour(&client_state, &stripped).is_err());
+		}
+
+		// forks built on a block the light client doesn't trust yet
+		let mut client_state = client_state;
+		client_state.latest_relay_hash = H256::repeat_byte(1);
+		assert!(check_grandpa_misbehaviour(&client_state, &misbehaviour).is_err());
+	}
+
+	#[test]
+	fn rejects_justifications_of_another_authority_set() {
+		let (fixtures, mut fork) = forks();
+		let client_state = fixtures.client_state();
+		fork.authorities = AuthoritySet::new(1, 4);
+		let misbehaviour = Misbehaviour {
+			first_finality_proof: finality_proof(&mut fixtures.clone(), 2),
+			second_finality_proof: finality_proof(&mut fork, 2),
+		};
+		assert!(check_grandpa_misbehaviour(&client_state, &misbehaviour).is_err());
+	}
+}
@@ -0,0 +1,185 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	future::Future,
+	sync::{Arc, Mutex},
+};
+
+/// Maximum number of blocks before a new best head whose events are queried again, when the
+/// previous best head isn't its parent.
+pub const MAX_BRANCH_LENGTH: u64 = 256;
+
+struct Heads<H> {
+	finalized: Option<(u64, H)>,
+	best: Option<(u64, H)>,
+}
+
+/// Numbers and hashes of the latest finalized and best parachain blocks seen by the relayer.
+///
+/// Best blocks can be reorganized away, so events and proofs that messages are built from are only
+/// queried at finalized heights. Best blocks are only followed to notice new events early, e.g. to
+/// prefetch client updates.
+#[derive(Clone)]
+pub struct ChainHeads<H> {
+	heads: Arc<Mutex<Heads<H>>>,
+}
+
+impl<H> Default for ChainHeads<H> {
+	fn default() -> Self {
+		Self { heads: Arc::new(Mutex::new(Heads { finalized: None, best: None })) }
+	}
+}
+
+impl<H: Copy + PartialEq> ChainHeads<H> {
+	pub fn finalized(&self) -> Option<(u64, H)> {
+		self.heads.lock().unwrap().finalized
+	}
+
+	pub fn best(&self) -> Option<(u64, H)> {
+		self.heads.lock().unwrap().best
+	}
+
+	/// Whether the block with the given number is known to be finalized.
+	pub fn is_finalized(&self, number: u64) -> bool {
+		self.finalized().map_or(false, |(finalized, _)| number <= finalized)
+	}
+
+	/// Records a finalized head. Finality never goes backwards, a lower head, e.g. from an rpc
+	/// endpoint that lags behind, is ignored.
+	pub fn set_finalized(&self, number: u64, hash: H) {
+		let mut heads = self.heads.lock().unwrap();
+		if heads.finalized.map_or(true, |(finalized, _)| number > finalized) {
+			heads.finalized = Some((number, hash));
+		}
+	}
+
+	/// Records a new best head, returns the previous one if the new head isn't its child. The
+	/// blocks in between were then either skipped by the subscription or the best chain was
+	/// reorganized, see [`new_branch`] to tell them apart.
+	pub fn set_best(&self, number: u64, hash: H, parent_hash: H) -> Option<(u64, H)> {
+		let mut heads = self.heads.lock().unwrap();
+		let previous = heads.best.replace((number, hash));
+		previous
+			.filter(|(_, previous_hash)| *previous_hash != parent_hash && *previous_hash != hash)
+	}
+}
+
+/// Walks back from `parent`, the parent of a new best head, and from the `previous` best head
+/// until both chains meet, using `parent_of` to look up the parent of a block.
+///
+/// Returns the blocks of the new chain after the common ancestor, oldest first, and whether the
+/// `previous` head was retracted, i.e. it isn't an ancestor of the new head. Blocks at or below
+/// `floor`, e.g. the finalized head, are assumed to be on both chains.
+pub async fn new_branch<H, E, F, Fut>(
+	previous: (u64, H),
+	parent: (u64, H),
+	floor: u64,
+	mut parent_of: F,
+) -> Result<(Vec<(u64, H)>, bool), E>
+where
+	H: Copy + PartialEq,
+	F: FnMut(H) -> Fut,
+	Fut: Future<Output = Result<H, E>>,
+{
+	let (mut new, mut old) = (parent, previous);
+	let mut branch = vec![];
+	while new.1 != old.1 && new.0 > floor {
+		if new.0 >= old.0 {
+			branch.push(new);
+			new = (new.0 - 1, parent_of(new.1).await?);
+		} else {
+			old = (old.0 - 1, parent_of(old.1).await?);
+		}
+	}
+	branch.reverse();
+	let retracted = new.1 != old.1 || old != previous;
+	Ok((branch, retracted))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashMap;
+
+	/// Parents of the blocks of a chain `1 <- 2 <- .. <- 6` forked at 3 into `30 <- 40 <- 50`,
+	/// hashes double as the numbers of the main chain blocks and tenfold the fork ones.
+	fn parents() -> HashMap<u64, u64> {
+		[(2, 1), (3, 2), (4, 3), (5, 4), (6, 5), (30, 2), (40, 30), (50, 40)]
+			.into_iter()
+			.collect()
+	}
+
+	async fn walk(
+		previous: (u64, u64),
+		parent: (u64, u64),
+		floor: u64,
+	) -> Result<(Vec<(u64, u64)>, bool), u64> {
+		let parents = parents();
+		new_branch(previous, parent, floor, |hash| {
+			let parent = parents.get(&hash).copied().ok_or(hash);
+			async move { parent }
+		})
+		.await
+	}
+
+	#[test]
+	fn set_best_reports_heads_that_dont_extend_the_previous_one() {
+		let heads = ChainHeads::default();
+		assert_eq!(heads.set_best(4, 4, 3), None);
+		assert_eq!(heads.set_best(5, 5, 4), None);
+		// the same head announced again
+		assert_eq!(heads.set_best(5, 5, 4), None);
+		// blocks skipped by the subscription
+		assert_eq!(heads.set_best(7, 7, 6), Some((5, 5)));
+		// a fork at the same height
+		assert_eq!(heads.set_best(7, 70, 60), Some((7, 7)));
+		assert_eq!(heads.best(), Some((7, 70)));
+	}
+
+	#[test]
+	fn finality_never_goes_backwards() {
+		let heads = ChainHeads::default();
+		heads.set_finalized(5, 5);
+		heads.set_finalized(4, 4);
+		assert_eq!(heads.finalized(), Some((5, 5)));
+		assert!(heads.is_finalized(5));
+		assert!(!heads.is_finalized(6));
+	}
+
+	#[tokio::test]
+	async fn new_branch_returns_the_skipped_blocks() {
+		assert_eq!(walk((3, 3), (5, 5), 0).await, Ok((vec![(4, 4), (5, 5)], false)));
+		assert_eq!(walk((5, 5), (5, 5), 0).await, Ok((vec![], false)));
+	}
+
+	#[tokio::test]
+	async fn new_branch_returns_the_blocks_after_the_fork() {
+		// from the main chain to the fork
+		assert_eq!(walk((6, 6), (4, 40), 0).await, Ok((vec![(3, 30), (4, 40)], true)));
+		// and back to the main chain
+		assert_eq!(walk((5, 50), (5, 5), 0).await, Ok((vec![(3, 3), (4, 4), (5, 5)], true)));
+	}
+
+	#[tokio::test]
+	async fn new_branch_stops_at_the_floor() {
+		assert_eq!(walk((6, 6), (4, 40), 3).await, Ok((vec![(4, 40)], true)));
+		assert_eq!(walk((6, 6), (6, 6), 6).await, Ok((vec![], false)));
+	}
+
+	#[tokio::test]
+	async fn new_branch_fails_on_unknown_blocks() {
+		assert_eq!(walk((6, 6), (4, 400), 0).await, Err(400));
+	}
+}
@@ -14,18 +14,16 @@
 
 use super::ParachainClient;
 use primitives::KeyProvider;
-use sp_core::crypto::Ss58Codec;
 use sp_runtime::traits::IdentifyAccount;
-use std::str::FromStr;
 
 impl<T: light_client_common::config::Config> KeyProvider for ParachainClient<T> {
 	fn account_id(&self) -> ibc::signer::Signer {
-		let hex_string = self
-			.public_key
-			.clone()
-			.into_account()
-			.to_ss58check_with_version(self.ss58_version);
-
-		ibc::signer::Signer::from_str(&hex_string).expect("Account Id should be valid")
+		if let Some(signer) = &self.message_signer {
+			return signer.clone()
+		}
+		let account = self.public_key().into_account();
+		self.address_format
+			.signer(account.as_ref())
+			.expect("Account Id should be valid")
 	}
 }
@@ -0,0 +1,152 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [`ExternalSigner`] backed by a Ledger device running a Substrate app.
+//!
+//! The app parses and displays the extrinsic before signing it, so it has to support the pallets
+//! of the runtime. Payloads longer than 256 bytes are hashed by subxt before they reach the
+//! signer, which the app can't display and may refuse.
+
+use crate::{error::Error, signer::ExternalSigner, LedgerConfig};
+use ledger_apdu::{APDUAnswer, APDUCommand};
+use ledger_transport_hid::{hidapi::HidApi, TransportNativeHID};
+use sp_core::{ed25519, sr25519};
+use sp_runtime::{MultiSignature, MultiSigner};
+use std::sync::{Arc, Mutex};
+
+const INS_GET_ADDRESS: u8 = 0x01;
+const INS_SIGN: u8 = 0x02;
+
+const SIGN_INIT: u8 = 0x00;
+const SIGN_ADD: u8 = 0x01;
+const SIGN_LAST: u8 = 0x02;
+
+const CHUNK_SIZE: usize = 250;
+const RETURN_CODE_OK: u16 = 0x9000;
+const HARDENED: u32 = 0x8000_0000;
+
+/// Signature scheme of the key on the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerScheme {
+	Ed25519 = 0,
+	Sr25519 = 1,
+}
+
+pub struct LedgerSigner {
+	device: Arc<LedgerDevice>,
+	public_key: MultiSigner,
+}
+
+/// Connection to the device, used from blocking tasks since the hid transport is blocking
+struct LedgerDevice {
+	transport: Mutex<TransportNativeHID>,
+	cla: u8,
+	scheme: LedgerScheme,
+	path: Vec<u8>,
+}
+
+impl LedgerSigner {
+	/// Opens the first Ledger device found and reads the public key at the configured path.
+	pub fn connect(config: &LedgerConfig, scheme: LedgerScheme) -> Result<Self, Error> {
+		let hid_api =
+			HidApi::new().map_err(|e| Error::Custom(format!("Failed to open hid api: {e}")))?;
+		let transport = TransportNativeHID::new(&hid_api)
+			.map_err(|e| Error::Custom(format!("Failed to connect to the Ledger device: {e}")))?;
+		let path = [44, config.slip44, config.account, config.change, config.address_index]
+			.into_iter()
+			.flat_map(|index| (index | HARDENED).to_le_bytes())
+			.collect::<Vec<_>>();
+		let device =
+			LedgerDevice { transport: Mutex::new(transport), cla: config.cla, scheme, path };
+		let public_key = device.query_public_key()?;
+		Ok(Self { device: Arc::new(device), public_key })
+	}
+}
+
+impl LedgerDevice {
+	fn query_public_key(&self) -> Result<MultiSigner, Error> {
+		let transport = self.transport.lock().unwrap();
+		let answer = self.exchange(&transport, INS_GET_ADDRESS, 0, self.path.clone())?;
+		let data = answer.data();
+		if data.len() < 32 {
+			return Err(Error::Custom("Invalid public key returned by the Ledger device".into()))
+		}
+		let mut public_key = [0u8; 32];
+		public_key.copy_from_slice(&data[..32]);
+		Ok(match self.scheme {
+			LedgerScheme::Ed25519 => ed25519::Public(public_key).into(),
+			LedgerScheme::Sr25519 => sr25519::Public(public_key).into(),
+		})
+	}
+
+	fn exchange(
+		&self,
+		transport: &TransportNativeHID,
+		ins: u8,
+		p1: u8,
+		data: Vec<u8>,
+	) -> Result<APDUAnswer<Vec<u8>>, Error> {
+		let command = APDUCommand { cla: self.cla, ins, p1, p2: self.scheme as u8, data };
+		let answer = transport
+			.exchange(&command)
+			.map_err(|e| Error::Custom(format!("Ledger exchange failed: {e}")))?;
+		if answer.retcode() != RETURN_CODE_OK {
+			return Err(Error::Custom(format!(
+				"Ledger device returned error code {:#06x}",
+				answer.retcode()
+			)))
+		}
+		Ok(answer)
+	}
+
+	fn sign(&self, payload: &[u8]) -> Result<MultiSignature, Error> {
+		// chunks of concurrent signing requests must not interleave
+		let transport = self.transport.lock().unwrap();
+		self.exchange(&transport, INS_SIGN, SIGN_INIT, self.path.clone())?;
+		let chunks = payload.chunks(CHUNK_SIZE).collect::<Vec<_>>();
+		let mut answer = None;
+		for (i, chunk) in chunks.iter().enumerate() {
+			let p1 = if i + 1 == chunks.len() { SIGN_LAST } else { SIGN_ADD };
+			answer = Some(self.exchange(&transport, INS_SIGN, p1, chunk.to_vec())?);
+		}
+		let answer = answer.ok_or_else(|| Error::Custom("Empty signing payload".into()))?;
+		// the signature is prefixed with its type
+		let data = answer.data();
+		if data.len() < 65 {
+			return Err(Error::Custom("Invalid signature returned by the Ledger device".into()))
+		}
+		let mut signature = [0u8; 64];
+		signature.copy_from_slice(&data[1..65]);
+		Ok(match self.scheme {
+			LedgerScheme::Ed25519 => ed25519::Signature(signature).into(),
+			LedgerScheme::Sr25519 => sr25519::Signature(signature).into(),
+		})
+	}
+}
+
+#[async_trait::async_trait]
+impl ExternalSigner for LedgerSigner {
+	fn public_key(&self) -> MultiSigner {
+		self.public_key.clone()
+	}
+
+	async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, Error> {
+		// waits for the payload to be approved on the device
+		let device = self.device.clone();
+		let payload = payload.to_vec();
+		tokio::task::spawn_blocking(move || device.sign(&payload))
+			.await
+			.map_err(|e| Error::Custom(format!("The Ledger signing task failed: {e}")))?
+	}
+}
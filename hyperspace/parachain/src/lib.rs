@@ -19,17 +19,33 @@ use std::{
 	f32::consts::E,
 	path::PathBuf,
 	str::FromStr,
-	sync::{Arc, Mutex},
+	sync::{
+		atomic::AtomicUsize,
+		Arc, Mutex, RwLock,
+	},
 	time::Duration,
 };
 
+pub mod admin;
+pub mod block_cache;
 pub mod chain;
 pub mod error;
+pub mod extrinsics;
+pub mod heads;
 pub mod key_provider;
+#[cfg(feature = "ledger")]
+pub mod ledger;
+#[cfg(feature = "light-client")]
+pub mod light_client_rpc;
+pub mod nonce;
 pub mod parachain;
 pub mod provider;
+pub mod relay_finality;
+pub mod remote_signer;
 pub mod signer;
+pub mod threshold_signer;
 pub mod utils;
+pub mod vault;
 
 pub mod finality_protocol;
 pub mod light_client_sync;
@@ -41,17 +57,30 @@ use frame_support::Serialize;
 use serde::Deserialize;
 
 use crate::{
-	finality_protocol::FinalityProtocol, signer::ExtrinsicSigner, utils::fetch_max_extrinsic_weight,
+	block_cache::BlockCache,
+	extrinsics::{extrinsic_statuses, ExtrinsicStatus, ExtrinsicTracker},
+	finality_protocol::{FinalityProtocol, PrefetchedUpdate, RelayWitness},
+	heads::ChainHeads,
+	provider::TransactionId,
+	remote_signer::{RemoteSigner, RemoteSignerConfig},
+	signer::{ExternalSigner, ExtrinsicSigner, SigningAccount},
+	threshold_signer::{ThresholdSigner, ThresholdSignerConfig},
+	utils::{
+		connect_to_healthy_endpoint, fetch_max_extrinsic_weight, spawn_runtime_upgrade_watcher,
+	},
+	vault::VaultTransitSigner,
 };
 use beefy_light_client_primitives::{ClientState, MmrUpdateProof};
 use beefy_prover::Prover;
-use codec::Decode;
+use codec::{Decode, Encode};
 use grandpa_light_client_primitives::ParachainHeaderProofs;
 use grandpa_prover::GrandpaProver;
 use ibc::{
 	core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 	timestamp::Timestamp,
+	Height,
 };
+use ibc_proto::google::protobuf::Any;
 use ics10_grandpa::{
 	client_state::ClientState as GrandpaClientState,
 	consensus_state::ConsensusState as GrandpaConsensusState,
@@ -60,11 +89,15 @@ use ics11_beefy::{
 	client_message::ParachainHeader, client_state::ClientState as BeefyClientState,
 	consensus_state::ConsensusState as BeefyConsensusState,
 };
-use jsonrpsee_ws_client::WsClientBuilder;
-use light_client_common::config::{AsInner, RuntimeStorage};
+use light_client_common::config::{AsInner, ExtrinsicOptions, NonceStrategy, RuntimeStorage};
 use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState, HostFunctionsManager};
 use pallet_mmr_primitives::Proof;
-use primitives::{CommonClientState, KeyProvider};
+use primitives::{
+	address::AddressFormat,
+	keystore::{resolve_secret, KeystoreConfig},
+	vault::{VaultConfig, VaultKey},
+	CommonClientState, KeyProvider,
+};
 use sc_keystore::LocalKeystore;
 use sp_core::{ecdsa, ed25519, sr25519, Bytes, Pair, H256};
 use sp_keystore::KeystorePtr;
@@ -74,8 +107,9 @@ use sp_runtime::{
 };
 use ss58_registry::Ss58AddressFormat;
 use subxt::{
+	blocks::ExtrinsicEvents,
 	config::{Header as HeaderT, Header},
-	tx::{Signer, TxPayload},
+	tx::{TxInBlock, TxPayload, TxProgress},
 };
 use tokio::sync::Mutex as AsyncMutex;
 
@@ -88,16 +122,22 @@ use tokio::sync::Mutex as AsyncMutex;
 pub struct ParachainClient<T: light_client_common::config::Config> {
 	/// Chain name
 	pub name: String,
-	/// rpc url for parachain
+	/// rpc url for parachain, the endpoint currently in use
 	pub parachain_rpc_url: String,
-	/// rpc url for relay chain
+	/// rpc url for relay chain, the endpoint currently in use
 	pub relay_chain_rpc_url: String,
+	/// All rpc urls for parachain, in order of preference
+	pub parachain_rpc_urls: Vec<String>,
+	/// All rpc urls for relay chain, in order of preference
+	pub relay_chain_rpc_urls: Vec<String>,
 	/// Relay chain rpc client
 	pub relay_client: subxt::OnlineClient<T>,
 	/// Parachain rpc client
 	pub para_client: subxt::OnlineClient<T>,
 	/// Relay chain ws client
 	pub relay_ws_client: Arc<jsonrpsee_ws_client::WsClient>,
+	/// Relay chain node checked for GRANDPA equivocations, see [`RelayWitness`]
+	pub relay_witness: Option<RelayWitness<T>>,
 	/// Parachain ws client
 	pub para_ws_client: Arc<jsonrpsee_ws_client::WsClient>,
 	/// Parachain Id
@@ -110,20 +150,45 @@ pub struct ParachainClient<T: light_client_common::config::Config> {
 	pub channel_whitelist: Arc<Mutex<HashSet<(ChannelId, PortId)>>>,
 	/// ICS-23 provable store commitment prefix
 	pub commitment_prefix: Vec<u8>,
-	/// Public key for relayer on chain
-	pub public_key: MultiSigner,
 	/// Reference to keystore
 	pub key_store: KeystorePtr,
 	/// Key type Id
 	pub key_type_id: KeyTypeId,
 	/// used for encoding relayer address.
 	pub ss58_version: Ss58AddressFormat,
+	/// Encoding of the relayer address in submitted messages
+	pub address_format: AddressFormat,
+	/// Signer of IBC messages when it differs from the fee paying account
+	pub message_signer: Option<ibc::signer::Signer>,
 	/// the maximum extrinsic weight allowed by this client
 	pub max_extrinsic_weight: u64,
 	/// Finality protocol to use, eg Beefy, Grandpa
 	pub finality_protocol: FinalityProtocol,
 	/// Common relayer data
 	pub common_state: CommonClientState,
+	/// Set when the rpc clients are driven by an embedded light client
+	pub light_client: Option<LightClientConfig>,
+	/// Era, tip and nonce options for the submitted extrinsics
+	pub extrinsic_options: ExtrinsicOptions,
+	/// Bundle multiple `deliver` calls into `utility.batch_all` extrinsics
+	pub batch_calls: bool,
+	/// Maximum encoded size of the extrinsics submitted
+	pub max_extrinsic_size: Option<usize>,
+	/// Accounts that submitted extrinsics are signed by in turn, the first one is the relayer
+	/// account. Shared between clones so that keys can be rotated at runtime.
+	pub signing_accounts: Arc<RwLock<Vec<SigningAccount>>>,
+	/// Index of the signing account to use next
+	pub next_signing_account: Arc<AtomicUsize>,
+	/// Key used to sign privileged `sudo` calls, see [`admin`]
+	pub sudo_public_key: Option<MultiSigner>,
+	/// Hashes and timestamps of the finalized parachain blocks looked up by number
+	pub block_cache: BlockCache<<T as subxt::Config>::Hash>,
+	/// Latest finalized and best parachain blocks, see [`ChainHeads`]
+	pub heads: ChainHeads<<T as subxt::Config>::Hash>,
+	/// Client update built ahead of the finality event that needs it
+	pub prefetched_update: Arc<Mutex<Option<PrefetchedUpdate>>>,
+	/// Statuses of the latest submitted extrinsics
+	pub extrinsics: ExtrinsicTracker<<T as subxt::Config>::Hash>,
 }
 
 enum KeyType {
@@ -134,6 +199,16 @@ enum KeyType {
 
 pub const DEFAULT_RPC_CALL_DELAY: Duration = Duration::from_millis(10);
 pub const WAIT_FOR_IN_BLOCK_TIMEOUT: Duration = Duration::from_secs(60 * 1);
+/// Number of times the call of an extrinsic that left the pool without being included is submitted
+/// again.
+pub const MAX_RESUBMISSIONS: usize = 3;
+/// Upper bound of the encoded size of a signed extrinsic besides the messages it delivers: the
+/// length prefix and version of the extrinsic, the signer, signature and signed extensions, and the
+/// call indices and length prefixes of `utility.batch_all` and `deliver`.
+pub const DELIVER_EXTRINSIC_OVERHEAD: usize = 256;
+/// Upper bound of the encoded size of each `deliver` call packed into a `utility.batch_all` besides
+/// its messages: its call index and the length prefix of the messages.
+pub const BATCHED_DELIVER_OVERHEAD: usize = 8;
 
 impl KeyType {
 	pub fn to_key_type_id(&self) -> KeyTypeId {
@@ -169,25 +244,152 @@ pub struct ParachainClientConfig {
 	pub parachain_rpc_url: String,
 	/// rpc url for relay chain
 	pub relay_chain_rpc_url: String,
+	/// rpc urls for parachain to fail over to when `parachain_rpc_url` is unavailable
+	#[serde(default)]
+	pub parachain_rpc_fallback_urls: Vec<String>,
+	/// rpc urls for relay chain to fail over to when `relay_chain_rpc_url` is unavailable
+	#[serde(default)]
+	pub relay_chain_rpc_fallback_urls: Vec<String>,
+	/// rpc url of a relay chain node run independently of the relay chain rpc endpoints, whose
+	/// GRANDPA justifications are compared with the relayed ones to report equivocations
+	#[serde(default)]
+	pub relay_chain_witness_rpc_url: Option<String>,
 	/// Light client id on counterparty chain
 	pub client_id: Option<ClientId>,
 	/// Connection Id
 	pub connection_id: Option<ConnectionId>,
 	/// Commitment prefix
 	pub commitment_prefix: Bytes,
-	/// Raw private key for signing transactions
+	/// Raw private key for signing transactions, can be left empty when `keystore` is set
+	#[serde(default)]
 	pub private_key: String,
+	/// Private keys of further relayer accounts, using the same `key_type`. Extrinsics are signed
+	/// by all the accounts in turn, which lets them be submitted in parallel.
+	#[serde(default)]
+	pub additional_private_keys: Vec<String>,
+	/// Encrypted keystore file holding the private key, see [`primitives::keystore`]
+	#[serde(default)]
+	pub keystore: Option<PathBuf>,
+	/// Sign extrinsics with a Ledger device instead of `private_key`. Requires the `ledger`
+	/// feature.
+	#[serde(default)]
+	pub ledger: Option<LedgerConfig>,
+	/// Sign extrinsics with a remote signer instead of `private_key`
+	#[serde(default)]
+	pub remote_signer: Option<RemoteSignerConfig>,
+	/// Sign extrinsics with a key shared by a threshold signer set, through its coordinator
+	#[serde(default)]
+	pub threshold_signer: Option<ThresholdSignerConfig>,
+	/// Read the private key from Vault's KV engine, or sign with a key of its transit engine
+	#[serde(default)]
+	pub vault: Option<VaultConfig>,
 	/// used for encoding relayer address.
 	pub ss58_version: u8,
+	/// Encoding of the relayer address in submitted messages, defaults to SS58 with
+	/// `ss58_version`
+	#[serde(default)]
+	pub address_format: Option<AddressFormat>,
+	/// Address recorded as the signer of IBC messages, in place of the address of the account
+	/// that signs and pays for the extrinsics. Lets a treasury account fund the relaying while
+	/// the messages stay attributed to the relayer.
+	#[serde(default)]
+	pub message_signer: Option<String>,
 	/// Channels cleared for packet relay
 	pub channel_whitelist: Vec<(ChannelId, PortId)>,
-	/// Finality protocol
+	/// Finality protocol, defaults to GRANDPA
+	#[serde(default)]
 	pub finality_protocol: FinalityProtocol,
 	/// Digital signature scheme
 	pub key_type: String,
 	/// All the client states and headers will be wrapped in WASM ones using the WASM code ID.
 	#[serde(default)]
 	pub wasm_code_hash: Option<String>,
+	/// Run an embedded light client for storage and block queries instead of trusting the rpc
+	/// nodes. Requires the `light-client` feature.
+	#[serde(default)]
+	pub light_client: Option<LightClientConfig>,
+	/// Era, tip and nonce options for the submitted extrinsics
+	#[serde(default)]
+	pub extrinsic_options: ExtrinsicOptions,
+	/// Bundle multiple `deliver` calls into `utility.batch_all` extrinsics when the runtime has
+	/// the utility pallet
+	#[serde(default)]
+	pub batch_calls: bool,
+	/// Maximum encoded size in bytes of the extrinsics submitted, for runtimes that reject large
+	/// extrinsics regardless of their weight. Message batches are split so that the extrinsics
+	/// delivering them fit.
+	#[serde(default)]
+	pub max_extrinsic_size: Option<usize>,
+	/// Check the packet events reported by the parachain node against its state before relaying
+	/// them
+	#[serde(default)]
+	pub verify_events: bool,
+	/// Raw private key of the sudo account, enables the privileged calls in [`admin`]. Uses the
+	/// same `key_type` as `private_key`.
+	#[serde(default)]
+	pub sudo_private_key: Option<String>,
+}
+
+impl KeystoreConfig for ParachainClientConfig {
+	fn raw_secret_mut(&mut self) -> &mut String {
+		&mut self.private_key
+	}
+
+	fn keystore_mut(&mut self) -> &mut Option<PathBuf> {
+		&mut self.keystore
+	}
+}
+
+/// Ledger device and derivation path of the signing key
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LedgerConfig {
+	/// Instruction class of the Substrate app on the device
+	pub cla: u8,
+	/// SLIP-44 coin type of the chain
+	pub slip44: u32,
+	#[serde(default)]
+	pub account: u32,
+	#[serde(default)]
+	pub change: u32,
+	#[serde(default)]
+	pub address_index: u32,
+}
+
+/// Chain specs for the embedded light client
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LightClientConfig {
+	/// Path to the relay chain spec
+	pub relay_chain_spec: PathBuf,
+	/// Path to the parachain spec
+	pub parachain_spec: PathBuf,
+}
+
+fn public_key_from_seed(key_type: &KeyType, seed: &str) -> Result<MultiSigner, Error> {
+	let public_key = match key_type {
+		KeyType::Sr25519 => sr25519::Pair::from_string_with_seed(seed, None)
+			.map_err(|_| Error::Custom("invalid key".to_owned()))?
+			.0
+			.public()
+			.into(),
+		KeyType::Ed25519 => ed25519::Pair::from_string_with_seed(seed, None)
+			.map_err(|_| Error::Custom("invalid key".to_owned()))?
+			.0
+			.public()
+			.into(),
+		KeyType::Ecdsa => ecdsa::Pair::from_string_with_seed(seed, None)
+			.map_err(|_| Error::Custom("invalid key".to_owned()))?
+			.0
+			.public()
+			.into(),
+	};
+	Ok(public_key)
+}
+
+/// Converts a nonce to the account index type of the chain. `Index` is only guaranteed to be
+/// deserializable, the same way the node returns it.
+fn index_from_u64<T: subxt::Config>(nonce: u64) -> Result<T::Index, Error> {
+	serde_json::from_value(serde_json::Value::from(nonce))
+		.map_err(|e| Error::Custom(format!("Failed to convert account nonce: {e}")))
 }
 
 impl<T> ParachainClient<T>
@@ -196,22 +398,37 @@ where
 {
 	/// Initializes a [`ParachainClient`] given a [`ParachainConfig`]
 	pub async fn new(config: ParachainClientConfig) -> Result<Self, Error> {
-		let relay_ws_client = Arc::new(
-			WsClientBuilder::default()
-				.build(&config.relay_chain_rpc_url)
-				.await
-				.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
-		);
-		let para_ws_client = Arc::new(
-			WsClientBuilder::default()
-				.build(&config.parachain_rpc_url)
-				.await
-				.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
-		);
+		let relay_chain_rpc_urls = std::iter::once(config.relay_chain_rpc_url)
+			.chain(config.relay_chain_rpc_fallback_urls)
+			.collect::<Vec<_>>();
+		let parachain_rpc_urls = std::iter::once(config.parachain_rpc_url)
+			.chain(config.parachain_rpc_fallback_urls)
+			.collect::<Vec<_>>();
+		let (relay_chain_rpc_url, relay_ws_client) =
+			connect_to_healthy_endpoint(&relay_chain_rpc_urls, None).await?;
+		let (parachain_rpc_url, para_ws_client) =
+			connect_to_healthy_endpoint(&parachain_rpc_urls, None).await?;
 
-		let para_client = subxt::OnlineClient::from_rpc_client(para_ws_client.clone()).await?;
+		let (para_client, relay_client) = match &config.light_client {
+			Some(light_client) => light_client_online_clients(light_client).await?,
+			None => (
+				subxt::OnlineClient::from_rpc_client(para_ws_client.clone()).await?,
+				subxt::OnlineClient::from_rpc_client(relay_ws_client.clone()).await?,
+			),
+		};
 
-		let relay_client = subxt::OnlineClient::from_rpc_client(relay_ws_client.clone()).await?;
+		let relay_witness = match config.relay_chain_witness_rpc_url {
+			Some(url) => {
+				let (url, ws_client) =
+					connect_to_healthy_endpoint(std::slice::from_ref(&url), None).await?;
+				let client = subxt::OnlineClient::from_rpc_client(ws_client.clone()).await?;
+				Some(RelayWitness { url, client, ws_client })
+			},
+			None => None,
+		};
+
+		spawn_runtime_upgrade_watcher(config.name.clone(), para_client.clone());
+		spawn_runtime_upgrade_watcher(format!("{} relay chain", config.name), relay_client.clone());
 
 		let max_extrinsic_weight = fetch_max_extrinsic_weight(&para_client).await?;
 
@@ -220,46 +437,72 @@ where
 		let key_type = KeyType::from_str(&config.key_type)?;
 		let key_type_id = key_type.to_key_type_id();
 
-		let public_key: MultiSigner = match key_type {
-			KeyType::Sr25519 => sr25519::Pair::from_string_with_seed(&config.private_key, None)
-				.map_err(|_| Error::Custom("invalid key".to_owned()))?
-				.0
-				.public()
-				.into(),
-			KeyType::Ed25519 => ed25519::Pair::from_string_with_seed(&config.private_key, None)
-				.map_err(|_| Error::Custom("invalid key".to_owned()))?
-				.0
-				.public()
-				.into(),
-			KeyType::Ecdsa => ecdsa::Pair::from_string_with_seed(&config.private_key, None)
-				.map_err(|_| Error::Custom("invalid key".to_owned()))?
-				.0
-				.public()
-				.into(),
+		let external_signer = external_signer(&config, &key_type).await?;
+		let public_key = match &external_signer {
+			Some(external_signer) => external_signer.public_key(),
+			None => {
+				let private_key = match &config.vault {
+					Some(vault) => vault.read_secret().await,
+					None => resolve_secret(&config.private_key, config.keystore.as_deref()),
+				}
+				.map_err(|e| e.to_string())?;
+				let public_key = public_key_from_seed(&key_type, &private_key)?;
+				key_store.insert(key_type_id, &private_key, public_key.as_ref()).unwrap();
+				assert!(key_store.has_keys(&[(public_key.as_ref().to_vec(), key_type_id)]));
+				public_key
+			},
 		};
 
-		key_store
-			.insert(key_type_id, &*config.private_key, public_key.as_ref())
-			.unwrap();
+		let mut signing_accounts = vec![SigningAccount::new(public_key, external_signer)];
+		for private_key in &config.additional_private_keys {
+			let public_key = public_key_from_seed(&key_type, private_key)?;
+			key_store
+				.insert(key_type_id, private_key, public_key.as_ref())
+				.map_err(|_| Error::Custom("failed to insert additional key".to_owned()))?;
+			signing_accounts.push(SigningAccount::new(public_key, None));
+		}
+
+		let message_signer = config
+			.message_signer
+			.as_deref()
+			.map(ibc::signer::Signer::from_str)
+			.transpose()
+			.map_err(|e| Error::Custom(format!("Invalid message signer: {e}")))?;
+
+		let sudo_public_key = match &config.sudo_private_key {
+			Some(sudo_private_key) => {
+				let sudo_public_key = public_key_from_seed(&key_type, sudo_private_key)?;
+				key_store
+					.insert(key_type_id, sudo_private_key, sudo_public_key.as_ref())
+					.map_err(|_| Error::Custom("failed to insert sudo key".to_owned()))?;
+				Some(sudo_public_key)
+			},
+			None => None,
+		};
 
-		assert!(key_store.has_keys(&[(public_key.as_ref().to_vec(), key_type_id)]));
 		Ok(Self {
 			name: config.name,
-			parachain_rpc_url: config.parachain_rpc_url,
-			relay_chain_rpc_url: config.relay_chain_rpc_url,
+			parachain_rpc_url,
+			relay_chain_rpc_url,
+			parachain_rpc_urls,
+			relay_chain_rpc_urls,
 			para_client,
 			relay_client,
 			para_id: config.para_id,
 			client_id: Arc::new(Mutex::new(config.client_id)),
 			commitment_prefix: config.commitment_prefix.0,
 			connection_id: Arc::new(Mutex::new(config.connection_id)),
-			public_key,
 			key_store,
 			key_type_id,
 			max_extrinsic_weight,
 			para_ws_client,
 			relay_ws_client,
+			relay_witness,
 			ss58_version: Ss58AddressFormat::from(config.ss58_version),
+			address_format: config
+				.address_format
+				.unwrap_or(AddressFormat::Ss58 { prefix: config.ss58_version.into() }),
+			message_signer,
 			channel_whitelist: Arc::new(Mutex::new(config.channel_whitelist.into_iter().collect())),
 			finality_protocol: config.finality_protocol,
 			common_state: CommonClientState {
@@ -268,10 +511,150 @@ where
 				rpc_call_delay: DEFAULT_RPC_CALL_DELAY,
 				initial_rpc_call_delay: DEFAULT_RPC_CALL_DELAY,
 				misbehaviour_client_msg_queue: Arc::new(AsyncMutex::new(vec![])),
+				verify_events: config.verify_events,
 				..Default::default()
 			},
+			light_client: config.light_client,
+			extrinsic_options: config.extrinsic_options,
+			batch_calls: config.batch_calls,
+			max_extrinsic_size: config.max_extrinsic_size,
+			signing_accounts: Arc::new(RwLock::new(signing_accounts)),
+			next_signing_account: Arc::new(AtomicUsize::new(0)),
+			sudo_public_key,
+			block_cache: BlockCache::default(),
+			heads: ChainHeads::default(),
+			prefetched_update: Default::default(),
+			extrinsics: Default::default(),
 		})
 	}
+
+	/// Number and hash of the latest finalized parachain block.
+	pub async fn finalized_head(&self) -> Result<(u64, T::Hash), Error> {
+		let hash = self.para_client.rpc().finalized_head().await?;
+		let header = self.para_client.rpc().header(Some(hash)).await?.ok_or_else(|| {
+			Error::Custom(format!("Header of the finalized block {hash:?} not found"))
+		})?;
+		let number: u64 = header.number().into();
+		self.heads.set_finalized(number, hash);
+		self.block_cache.insert_hash(number, hash);
+		Ok((number, hash))
+	}
+
+	/// Fails if the parachain block at `at` isn't finalized yet, the events and proofs of blocks
+	/// that may still be reorganized away can't be relayed.
+	pub async fn ensure_finalized(&self, at: Height) -> Result<(), Error> {
+		if self.heads.is_finalized(at.revision_height) {
+			return Ok(())
+		}
+		let (finalized, _) = self.finalized_head().await?;
+		if at.revision_height > finalized {
+			let best = self.heads.best().map_or(finalized, |(best, _)| best);
+			return Err(Error::Custom(format!(
+				"Block {} of {} is not finalized yet, the latest finalized block is {finalized} and the best one {best}",
+				at.revision_height, self.name
+			)))
+		}
+		Ok(())
+	}
+
+	/// Hash of the finalized parachain block with the given number.
+	pub async fn finalized_block_hash(&self, block_number: u64) -> Result<T::Hash, Error> {
+		if let Some(hash) = self.block_cache.hash(block_number) {
+			return Ok(hash)
+		}
+		// the canonical block at a height above the finalized head can still change
+		self.ensure_finalized(Height::new(self.para_id.into(), block_number)).await?;
+		let subxt_block_number: subxt::rpc::types::BlockNumber = block_number.into();
+		let hash = self.para_client.rpc().block_hash(Some(subxt_block_number)).await?.ok_or_else(
+			|| Error::Custom(format!("Block hash not found for block number {block_number}")),
+		)?;
+		self.block_cache.insert_hash(block_number, hash);
+		Ok(hash)
+	}
+}
+
+/// Builds the signer configured in place of a private key, if any
+async fn external_signer(
+	config: &ParachainClientConfig,
+	key_type: &KeyType,
+) -> Result<Option<Arc<dyn ExternalSigner>>, Error> {
+	let transit_key = config
+		.vault
+		.as_ref()
+		.filter(|vault| matches!(vault.key, VaultKey::Transit { .. }));
+	let configured = [
+		config.ledger.is_some(),
+		config.remote_signer.is_some(),
+		config.threshold_signer.is_some(),
+		transit_key.is_some(),
+	]
+	.into_iter()
+	.filter(|configured| *configured)
+	.count();
+	if configured > 1 {
+		return Err(Error::Custom(
+			"Only one of `ledger`, `remote_signer`, `threshold_signer` and a Vault transit key can \
+			 be configured"
+				.to_owned(),
+		))
+	}
+
+	if let Some(ledger) = &config.ledger {
+		return Ok(Some(ledger_signer(ledger, key_type)?))
+	}
+	if let Some(remote_signer) = &config.remote_signer {
+		let signer = RemoteSigner::connect(config.name.clone(), remote_signer).await?;
+		return Ok(Some(Arc::new(signer)))
+	}
+	if let Some(threshold_signer) = &config.threshold_signer {
+		let signer = ThresholdSigner::connect(config.name.clone(), threshold_signer).await?;
+		return Ok(Some(Arc::new(signer)))
+	}
+	if let Some(vault) = transit_key {
+		return Ok(Some(Arc::new(VaultTransitSigner::new(vault.clone()).await?)))
+	}
+	Ok(None)
+}
+
+#[cfg(feature = "ledger")]
+fn ledger_signer(
+	config: &LedgerConfig,
+	key_type: &KeyType,
+) -> Result<Arc<dyn ExternalSigner>, Error> {
+	use ledger::{LedgerScheme, LedgerSigner};
+	let scheme = match key_type {
+		KeyType::Ed25519 => LedgerScheme::Ed25519,
+		KeyType::Sr25519 => LedgerScheme::Sr25519,
+		KeyType::Ecdsa =>
+			return Err(Error::Custom("Ledger signing doesn't support ecdsa keys".into())),
+	};
+	Ok(Arc::new(LedgerSigner::connect(config, scheme)?))
+}
+
+#[cfg(not(feature = "ledger"))]
+fn ledger_signer(
+	_config: &LedgerConfig,
+	_key_type: &KeyType,
+) -> Result<Arc<dyn ExternalSigner>, Error> {
+	Err(Error::Custom("hyperspace-parachain was built without the `ledger` feature".into()))
+}
+
+/// Builds the parachain and relay chain clients on top of an embedded light client.
+#[cfg(feature = "light-client")]
+async fn light_client_online_clients<T: light_client_common::config::Config>(
+	config: &LightClientConfig,
+) -> Result<(subxt::OnlineClient<T>, subxt::OnlineClient<T>), Error> {
+	let (relay_chain_rpc, parachain_rpc) = light_client_rpc::start_light_client(config).await?;
+	let para_client = subxt::OnlineClient::from_rpc_client(Arc::new(parachain_rpc)).await?;
+	let relay_client = subxt::OnlineClient::from_rpc_client(Arc::new(relay_chain_rpc)).await?;
+	Ok((para_client, relay_client))
+}
+
+#[cfg(not(feature = "light-client"))]
+async fn light_client_online_clients<T: light_client_common::config::Config>(
+	_config: &LightClientConfig,
+) -> Result<(subxt::OnlineClient<T>, subxt::OnlineClient<T>), Error> {
+	Err(Error::Custom("hyperspace-parachain was built without the `light-client` feature".into()))
 }
 
 impl<T: light_client_common::config::Config + Send + Sync> ParachainClient<T>
@@ -402,50 +785,304 @@ where
 		Ok(mmr_update)
 	}
 
-	/// Submits the given transaction to the parachain node, waits for it to be included in a block
-	/// and asserts that it was successfully dispatched on-chain.
+	/// Public key of the relayer account
+	pub fn public_key(&self) -> MultiSigner {
+		self.signing_accounts.read().unwrap()[0].public_key.clone()
+	}
+
+	/// Signer of the relayer account
+	pub fn extrinsic_signer(&self) -> ExtrinsicSigner<T, Self> {
+		let account = self.signing_accounts.read().unwrap()[0].clone();
+		self.extrinsic_signer_for(&account)
+	}
+
+	/// Signer of one of the signing accounts
+	pub fn extrinsic_signer_for(&self, account: &SigningAccount) -> ExtrinsicSigner<T, Self> {
+		ExtrinsicSigner::<T, Self>::new(
+			self.key_store.clone(),
+			self.key_type_id.clone(),
+			account.public_key.clone(),
+		)
+		.with_external_signer(account.external_signer.clone())
+	}
+
+	/// Picks the signing accounts round-robin
+	fn next_signing_account(&self) -> SigningAccount {
+		signer::round_robin(&self.signing_accounts.read().unwrap(), &self.next_signing_account)
+	}
+
+	/// Replaces the key of the relayer account with `private_key`, which must be of the same key
+	/// type. In-flight submissions finish with the previous key, and since the account changes its
+	/// nonces are tracked from scratch.
+	pub fn rotate_signing_key(&self, private_key: &str) -> Result<MultiSigner, Error> {
+		let mut accounts = self.signing_accounts.write().unwrap();
+		if accounts[0].external_signer.is_some() {
+			return Err(Error::Custom(
+				"The relayer account is held by an external signer and can't be rotated"
+					.to_string(),
+			))
+		}
+		let key_type = match &accounts[0].public_key {
+			MultiSigner::Sr25519(_) => KeyType::Sr25519,
+			MultiSigner::Ed25519(_) => KeyType::Ed25519,
+			MultiSigner::Ecdsa(_) => KeyType::Ecdsa,
+		};
+		let public_key = public_key_from_seed(&key_type, private_key)?;
+		self.key_store
+			.insert(self.key_type_id, private_key, public_key.as_ref())
+			.map_err(|_| Error::Custom("Failed to insert the key into the keystore".to_string()))?;
+		accounts[0] = SigningAccount::new(public_key.clone(), None);
+		log::info!(target: "hyperspace_parachain", "Rotated the signing key of {}", self.name);
+		Ok(public_key)
+	}
+
+	/// Submits the call built by `build_call` to the parachain node, waits for it to be included in
+	/// a block and asserts that it was successfully dispatched on-chain.
 	///
 	/// We retry sending the transaction up to 5 times in the case where the transaction pool might
-	/// reject the transaction because of conflicting nonces.
-	pub async fn submit_call<C: TxPayload>(&self, call: C) -> Result<(T::Hash, T::Hash), Error> {
-		// Try extrinsic submission five times in case of failures
+	/// reject the transaction because of conflicting nonces. When an extrinsic leaves the pool
+	/// without being included, e.g. dropped or usurped, its call is built again and submitted, up
+	/// to [`MAX_RESUBMISSIONS`] times. Once included, the extrinsic is followed until its block is
+	/// finalized and its call is submitted once more if the block is retracted or not finalized in
+	/// time. The status of every extrinsic is recorded in [`Self::extrinsics`].
+	pub async fn submit_call<C, F>(&self, build_call: F) -> Result<(T::Hash, T::Hash), Error>
+	where
+		T: Clone,
+		C: TxPayload + Send + 'static,
+		F: Fn() -> C + Send + Sync + 'static,
+	{
+		let (ext_hash, block_hash, _) = self.submit_call_with_events(build_call).await?;
+		Ok((ext_hash, block_hash))
+	}
+
+	/// Submits the call built by `build_call` like [`Self::submit_call`], also returns the events
+	/// emitted by the extrinsic.
+	pub async fn submit_call_with_events<C, F>(
+		&self,
+		build_call: F,
+	) -> Result<(T::Hash, T::Hash, ExtrinsicEvents<T>), Error>
+	where
+		T: Clone,
+		C: TxPayload + Send + 'static,
+		F: Fn() -> C + Send + Sync + 'static,
+	{
+		let account = self.next_signing_account();
+		let mut resubmissions = 0;
+		let (tx_in_block, progress) = loop {
+			let mut progress = self.sign_and_submit(&account, &build_call()).await?;
+			let ext_hash = progress.extrinsic_hash();
+			match self.wait_for_in_block(&account, &mut progress).await? {
+				Ok(tx_in_block) => break (tx_in_block, progress),
+				Err(status) if resubmissions < MAX_RESUBMISSIONS => {
+					log::warn!(target: "hyperspace_parachain", "Extrinsic {ext_hash:?} is {status}, building and submitting its call again");
+					resubmissions += 1;
+				},
+				Err(status) =>
+					return Err(Error::from(format!(
+						"Extrinsic {ext_hash:?} is {status} after {resubmissions} resubmissions"
+					))),
+			}
+		};
+		let events = tx_in_block.wait_for_success().await?;
+		self.resubmit_unless_finalized(account, progress, build_call);
+		Ok((tx_in_block.extrinsic_hash(), tx_in_block.block_hash(), events))
+	}
+
+	/// Signs `call` with `account` and submits it, retrying up to 5 times when the pool rejects it.
+	async fn sign_and_submit<C: TxPayload>(
+		&self,
+		account: &SigningAccount,
+		call: &C,
+	) -> Result<TxProgress<T, subxt::OnlineClient<T>>, Error> {
 		let mut count = 0;
-		let progress = loop {
+		loop {
 			if count == 10 {
 				Err(Error::Custom("Failed to submit extrinsic after 5 tries".to_string()))?
 			}
 
-			let other_params = T::custom_extrinsic_params(&self.para_client).await?;
-
-			let res = {
-				let signer = ExtrinsicSigner::<T, Self>::new(
-					self.key_store.clone(),
-					self.key_type_id.clone(),
-					self.public_key.clone(),
-				);
-				self.para_client
-					.tx()
-					.sign_and_submit_then_watch(&call, &signer, other_params)
-					.await
+			let other_params =
+				T::custom_extrinsic_params(&self.para_client, &self.extrinsic_options).await?;
+
+			let signer = self.extrinsic_signer_for(account);
+			let nonce = match self.extrinsic_options.nonce_strategy {
+				NonceStrategy::Pool => None,
+				NonceStrategy::Finalized =>
+					Some(self.finalized_account_nonce(&account.public_key).await?),
+				NonceStrategy::Local => {
+					let nonce = account
+						.nonce_manager
+						.reserve(|| async {
+							let nonce = self
+								.para_client
+								.rpc()
+								.system_account_next_index(signer.account_id())
+								.await?;
+							Ok(nonce.into())
+						})
+						.await?;
+					Some(index_from_u64::<T>(nonce)?)
+				},
 			};
-			match res {
-				Ok(progress) => break progress,
+			// a signer refusing or failing to sign isn't retried
+			let extrinsic =
+				match signer.create_signed(&self.para_client, call, nonce, other_params).await {
+					Ok(extrinsic) => extrinsic,
+					Err(e) => {
+						if self.extrinsic_options.nonce_strategy == NonceStrategy::Local {
+							account.nonce_manager.reset().await;
+						}
+						return Err(e)
+					},
+				};
+			match extrinsic.submit_and_watch().await {
+				Ok(progress) => return Ok(progress),
 				Err(e) => {
+					if self.extrinsic_options.nonce_strategy == NonceStrategy::Local {
+						// the reserved nonce is left unused, resync with the node to fill the gap
+						account.nonce_manager.reset().await;
+					}
 					log::warn!("Failed to submit extrinsic: {:?}. Retrying...", e);
 					count += 1;
 					tokio::time::sleep(std::time::Duration::from_secs(10)).await;
 				},
 			}
-		};
+		}
+	}
 
-		let tx_in_block =
-			tokio::time::timeout(WAIT_FOR_IN_BLOCK_TIMEOUT, progress.wait_for_in_block())
-				.await
-				.map_err(|e| {
-					Error::from(format!("[submit_call] Failed to wait for in block due to {:?}", e))
-				})??;
-		tx_in_block.wait_for_success().await?;
-		Ok((tx_in_block.extrinsic_hash(), tx_in_block.block_hash()))
+	/// Waits for a submitted extrinsic to be included in a block, or returns the status it left the
+	/// pool with.
+	async fn wait_for_in_block(
+		&self,
+		account: &SigningAccount,
+		progress: &mut TxProgress<T, subxt::OnlineClient<T>>,
+	) -> Result<Result<TxInBlock<T, subxt::OnlineClient<T>>, ExtrinsicStatus<T::Hash>>, Error> {
+		let tx_in_block = tokio::time::timeout(
+			WAIT_FOR_IN_BLOCK_TIMEOUT,
+			self.extrinsics.wait_for_in_block(progress),
+		)
+		.await
+		.map_err(|e| {
+			Error::from(format!("[submit_call] Failed to wait for in block due to {:?}", e))
+		})
+		.and_then(|res| res);
+		if !matches!(tx_in_block, Ok(Ok(_))) &&
+			self.extrinsic_options.nonce_strategy == NonceStrategy::Local
+		{
+			// the extrinsic may have been dropped from the pool, leaving a gap
+			account.nonce_manager.reset().await;
+		}
+		tx_in_block
+	}
+
+	/// Follows an included extrinsic in the background until its block is finalized. If the block
+	/// is retracted or not finalized in time, and the extrinsic isn't included in another one, its
+	/// call is built and submitted once more.
+	fn resubmit_unless_finalized<C, F>(
+		&self,
+		account: SigningAccount,
+		progress: TxProgress<T, subxt::OnlineClient<T>>,
+		build_call: F,
+	) where
+		T: Clone,
+		C: TxPayload + Send + 'static,
+		F: Fn() -> C + Send + Sync + 'static,
+	{
+		let client = self.clone();
+		tokio::spawn(async move {
+			let ext_hash = progress.extrinsic_hash();
+			let status =
+				client.extrinsics.follow_finality(ext_hash, extrinsic_statuses(progress)).await;
+			let Some(status) = status.filter(ExtrinsicStatus::is_unfinalized) else { return };
+			log::warn!(target: "hyperspace_parachain", "Extrinsic {ext_hash:?} is {status}, building and submitting its call again");
+			if client.extrinsic_options.nonce_strategy == NonceStrategy::Local {
+				account.nonce_manager.reset().await;
+			}
+			let resubmit = async {
+				let mut progress = client.sign_and_submit(&account, &build_call()).await?;
+				let resubmitted = progress.extrinsic_hash();
+				match client.wait_for_in_block(&account, &mut progress).await? {
+					Ok(tx_in_block) => {
+						tx_in_block.wait_for_success().await?;
+						// it isn't resubmitted again, only its statuses are recorded
+						client
+							.extrinsics
+							.follow_finality(resubmitted, extrinsic_statuses(progress))
+							.await;
+						Ok(())
+					},
+					Err(status) =>
+						Err(Error::from(format!("Extrinsic {resubmitted:?} is {status}"))),
+				}
+			};
+			if let Err(e) = resubmit.await {
+				log::error!(target: "hyperspace_parachain", "Failed to resubmit the call of extrinsic {ext_hash:?}: {e:?}");
+			}
+		});
+	}
+
+	/// Submits a `deliver` call for every set of messages, bundled in a single `utility.batch_all`
+	/// extrinsic so that they're included together or not at all.
+	pub async fn submit_batched_deliver(
+		&self,
+		batches: Vec<Vec<Any>>,
+	) -> Result<TransactionId<T::Hash>, Error>
+	where
+		T: Clone,
+	{
+		use subxt::dynamic::Value;
+
+		let calls = batches
+			.into_iter()
+			.map(|messages| {
+				let messages = messages
+					.into_iter()
+					.map(|msg| {
+						Value::named_composite([
+							("type_url", Value::string(msg.type_url)),
+							("value", Value::from_bytes(msg.value)),
+						])
+					})
+					.collect::<Vec<_>>();
+				Value::unnamed_variant(
+					"Ibc",
+					[Value::named_variant(
+						"deliver",
+						[("messages", Value::unnamed_composite(messages))],
+					)],
+				)
+			})
+			.collect::<Vec<_>>();
+		let (ext_hash, block_hash) = self
+			.submit_call(move || {
+				subxt::dynamic::tx(
+					"Utility",
+					"batch_all",
+					vec![Value::unnamed_composite(calls.clone())],
+				)
+			})
+			.await?;
+		log::debug!(target: "hyperspace_parachain", "Submitted batched extrinsic (hash: {:?}) to block {:?}", ext_hash, block_hash);
+		Ok(TransactionId { ext_hash, block_hash })
+	}
+
+	/// Returns the nonce of a relayer account at the latest finalized block.
+	pub async fn finalized_account_nonce(
+		&self,
+		public_key: &MultiSigner,
+	) -> Result<T::Index, Error> {
+		let account_id = public_key.clone().into_account();
+		let finalized_hash = self.para_client.rpc().finalized_head().await?;
+		let bytes = self
+			.para_client
+			.rpc()
+			.state_call(
+				"AccountNonceApi_account_nonce",
+				Some(&account_id.encode()),
+				Some(finalized_hash),
+			)
+			.await?;
+		let nonce = u32::decode(&mut &bytes[..])?;
+		index_from_u64::<T>(nonce.into())
 	}
 
 	pub fn client_id(&self) -> ClientId {
@@ -0,0 +1,273 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An [`RpcClientT`] backed by an embedded smoldot light client. Storage and block queries made
+//! through it are verified against the finalized chain instead of being trusted from an rpc node.
+//! The custom ibc, grandpa and beefy rpcs still go through the configured rpc nodes, their
+//! responses are proof-backed.
+
+use crate::{error::Error, LightClientConfig};
+use futures::{
+	channel::{mpsc, oneshot},
+	StreamExt,
+};
+use serde_json::{value::RawValue, Value};
+use smoldot_light::{
+	platform::DefaultPlatform, AddChainConfig, AddChainConfigJsonRpc, ChainId, Client,
+	JsonRpcResponses,
+};
+use std::{
+	collections::HashMap,
+	num::NonZeroU32,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc, Mutex,
+	},
+};
+use subxt::{
+	error::RpcError,
+	rpc::{RpcClientT, RpcFuture, RpcSubscription},
+};
+
+type SmoldotClient = Client<Arc<DefaultPlatform>>;
+
+const MAX_PENDING_REQUESTS: u32 = 128;
+const MAX_SUBSCRIPTIONS: u32 = 1024;
+
+/// Starts a light client for the relay chain and the parachain described by the chain specs in
+/// `config` and returns rpc clients for both, in that order.
+pub async fn start_light_client(
+	config: &LightClientConfig,
+) -> Result<(LightClientRpc, LightClientRpc), Error> {
+	let relay_chain_spec = tokio::fs::read_to_string(&config.relay_chain_spec)
+		.await
+		.map_err(|e| Error::Custom(format!("Failed to read relay chain spec: {e}")))?;
+	let parachain_spec = tokio::fs::read_to_string(&config.parachain_spec)
+		.await
+		.map_err(|e| Error::Custom(format!("Failed to read parachain spec: {e}")))?;
+
+	let client = Arc::new(Mutex::new(Client::new(DefaultPlatform::new(
+		env!("CARGO_PKG_NAME").into(),
+		env!("CARGO_PKG_VERSION").into(),
+	))));
+	let relay_chain = LightClientRpc::add_chain(client.clone(), &relay_chain_spec, None)?;
+	let parachain = LightClientRpc::add_chain(client, &parachain_spec, Some(relay_chain.chain_id))?;
+	Ok((relay_chain, parachain))
+}
+
+enum PendingRequest {
+	Request(oneshot::Sender<Result<Box<RawValue>, RpcError>>),
+	Subscription {
+		id: oneshot::Sender<Result<String, RpcError>>,
+		notifications: mpsc::UnboundedSender<Result<Box<RawValue>, RpcError>>,
+		unsubscribe_method: String,
+	},
+}
+
+struct ActiveSubscription {
+	notifications: mpsc::UnboundedSender<Result<Box<RawValue>, RpcError>>,
+	unsubscribe_method: String,
+}
+
+#[derive(Default)]
+struct PendingState {
+	requests: HashMap<u64, PendingRequest>,
+	subscriptions: HashMap<String, ActiveSubscription>,
+}
+
+/// Json rpc interface of a single chain of the embedded light client.
+#[derive(Clone)]
+pub struct LightClientRpc {
+	client: Arc<Mutex<SmoldotClient>>,
+	chain_id: ChainId,
+	next_request_id: Arc<AtomicU64>,
+	state: Arc<Mutex<PendingState>>,
+}
+
+impl LightClientRpc {
+	fn add_chain(
+		client: Arc<Mutex<SmoldotClient>>,
+		chain_spec: &str,
+		relay_chain: Option<ChainId>,
+	) -> Result<Self, Error> {
+		let success = client
+			.lock()
+			.unwrap()
+			.add_chain(AddChainConfig {
+				user_data: (),
+				specification: chain_spec,
+				database_content: "",
+				potential_relay_chains: relay_chain.into_iter(),
+				json_rpc: AddChainConfigJsonRpc::Enabled {
+					max_pending_requests: NonZeroU32::new(MAX_PENDING_REQUESTS)
+						.expect("constant is non zero; qed"),
+					max_subscriptions: MAX_SUBSCRIPTIONS,
+				},
+			})
+			.map_err(|e| Error::Custom(format!("Failed to add chain to light client: {e:?}")))?;
+		let responses = success
+			.json_rpc_responses
+			.ok_or_else(|| Error::Custom("Json rpc is disabled for light client chain".into()))?;
+
+		let rpc = Self {
+			client,
+			chain_id: success.chain_id,
+			next_request_id: Arc::new(AtomicU64::new(0)),
+			state: Arc::new(Mutex::new(PendingState::default())),
+		};
+		tokio::spawn(rpc.clone().dispatch_responses(responses));
+		Ok(rpc)
+	}
+
+	fn send(
+		&self,
+		request_id: u64,
+		method: &str,
+		params: Option<Box<RawValue>>,
+	) -> Result<(), RpcError> {
+		let params = params.map(|params| params.get().to_string()).unwrap_or_else(|| "[]".into());
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","id":{request_id},"method":{},"params":{params}}}"#,
+			Value::String(method.to_string())
+		);
+		self.client
+			.lock()
+			.unwrap()
+			.json_rpc_request(request, self.chain_id)
+			.map_err(|e| RpcError::ClientError(format!("{e:?}").into()))
+	}
+
+	/// Routes the responses of the light client to the pending requests and subscriptions.
+	async fn dispatch_responses(self, mut responses: JsonRpcResponses) {
+		while let Some(response) = responses.next().await {
+			let response: Value = match serde_json::from_str(&response) {
+				Ok(response) => response,
+				Err(e) => {
+					log::warn!(target: "hyperspace", "Invalid light client response: {e}");
+					continue
+				},
+			};
+			if let Some(request_id) = response.get("id").and_then(Value::as_u64) {
+				self.handle_response(request_id, response);
+			} else if let Some(params) = response.get("params") {
+				self.handle_notification(params);
+			}
+		}
+		log::warn!(target: "hyperspace", "Light client stopped responding");
+	}
+
+	fn handle_response(&self, request_id: u64, response: Value) {
+		let result = match response.get("error") {
+			Some(error) => Err(RpcError::RequestRejected(error.to_string())),
+			None => serde_json::value::to_raw_value(response.get("result").unwrap_or(&Value::Null))
+				.map_err(|e| RpcError::ClientError(Box::new(e))),
+		};
+		let mut state = self.state.lock().unwrap();
+		match state.requests.remove(&request_id) {
+			Some(PendingRequest::Request(sender)) => {
+				let _ = sender.send(result);
+			},
+			Some(PendingRequest::Subscription { id, notifications, unsubscribe_method }) => {
+				let subscription_id = result.and_then(|result| {
+					serde_json::from_str::<Value>(result.get())
+						.ok()
+						.map(|id| id.as_str().map(ToString::to_string).unwrap_or(id.to_string()))
+						.ok_or_else(|| RpcError::RequestRejected("Invalid subscription id".into()))
+				});
+				if let Ok(subscription_id) = &subscription_id {
+					state.subscriptions.insert(
+						subscription_id.clone(),
+						ActiveSubscription { notifications, unsubscribe_method },
+					);
+				}
+				let _ = id.send(subscription_id);
+			},
+			None => (),
+		}
+	}
+
+	fn handle_notification(&self, params: &Value) {
+		let subscription_id = match params.get("subscription") {
+			Some(Value::String(id)) => id.clone(),
+			Some(id) => id.to_string(),
+			None => return,
+		};
+		let result = serde_json::value::to_raw_value(params.get("result").unwrap_or(&Value::Null))
+			.map_err(|e| RpcError::ClientError(Box::new(e)));
+		let mut state = self.state.lock().unwrap();
+		let closed = match state.subscriptions.get(&subscription_id) {
+			Some(subscription) => subscription.notifications.unbounded_send(result).is_err(),
+			None => false,
+		};
+		if closed {
+			// the subscription stream was dropped
+			let subscription =
+				state.subscriptions.remove(&subscription_id).expect("checked above; qed");
+			drop(state);
+			let request_id = self.next_request_id.fetch_add(1, Ordering::SeqCst);
+			let params = serde_json::value::to_raw_value(&[subscription_id]).ok();
+			let _ = self.send(request_id, &subscription.unsubscribe_method, params);
+		}
+	}
+}
+
+impl RpcClientT for LightClientRpc {
+	fn request_raw<'a>(
+		&'a self,
+		method: &'a str,
+		params: Option<Box<RawValue>>,
+	) -> RpcFuture<'a, Box<RawValue>> {
+		Box::pin(async move {
+			let request_id = self.next_request_id.fetch_add(1, Ordering::SeqCst);
+			let (sender, receiver) = oneshot::channel();
+			self.state
+				.lock()
+				.unwrap()
+				.requests
+				.insert(request_id, PendingRequest::Request(sender));
+			if let Err(e) = self.send(request_id, method, params) {
+				self.state.lock().unwrap().requests.remove(&request_id);
+				return Err(e)
+			}
+			receiver.await.map_err(|_| RpcError::SubscriptionDropped)?
+		})
+	}
+
+	fn subscribe_raw<'a>(
+		&'a self,
+		sub: &'a str,
+		params: Option<Box<RawValue>>,
+		unsub: &'a str,
+	) -> RpcFuture<'a, RpcSubscription> {
+		Box::pin(async move {
+			let request_id = self.next_request_id.fetch_add(1, Ordering::SeqCst);
+			let (id_sender, id_receiver) = oneshot::channel();
+			let (notifications, stream) = mpsc::unbounded();
+			self.state.lock().unwrap().requests.insert(
+				request_id,
+				PendingRequest::Subscription {
+					id: id_sender,
+					notifications,
+					unsubscribe_method: unsub.to_string(),
+				},
+			);
+			if let Err(e) = self.send(request_id, sub, params) {
+				self.state.lock().unwrap().requests.remove(&request_id);
+				return Err(e)
+			}
+			let id = id_receiver.await.map_err(|_| RpcError::SubscriptionDropped)??;
+			Ok(RpcSubscription { stream: stream.boxed(), id: Some(id) })
+		})
+	}
+}
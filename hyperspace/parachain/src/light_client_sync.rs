@@ -19,7 +19,6 @@ use subxt::config::{
 
 use grandpa_prover::GrandpaProver;
 use ibc::core::ics02_client::msgs::update_client::MsgUpdateAnyClient;
-use tendermint_proto::Protobuf;
 
 use ibc::{
 	core::ics24_host::identifier::ClientId, events::IbcEvent, signer::Signer, tx_msg::Msg, Height,
@@ -33,7 +32,10 @@ use primitives::{
 };
 
 use super::{error::Error, ParachainClient};
-use crate::finality_protocol::FinalityProtocol;
+use crate::finality_protocol::{
+	query_latest_ibc_events_with_beefy, FinalityEvent, FinalityProtocol,
+};
+use beefy_prover::relay_chain_queries::fetch_beefy_justification;
 
 const MAX_HEADERS_PER_ITERATION: usize = 100;
 
@@ -97,7 +99,23 @@ where
 				// finalized height then the light client is still in sync
 				Ok(session_changes == 0)
 			},
-			FinalityProtocol::Beefy => unimplemented!(),
+			FinalityProtocol::Beefy => {
+				let AnyClientState::Beefy(client_state) =
+					AnyClientState::decode_recursive(any_client_state, |c| {
+						matches!(c, AnyClientState::Beefy(_))
+					})
+					.ok_or_else(|| Error::Custom(format!("Could not decode client state")))?
+				else {
+					unreachable!()
+				};
+
+				let (signed_commitment, _) = fetch_beefy_justification(&self.relay_client)
+					.await
+					.map_err(Error::BeefyProver)?;
+				// If the authority set hasn't changed since the last update then the light
+				// client is still in sync
+				Ok(signed_commitment.commitment.validator_set_id == client_state.authority.id)
+			},
 		}
 	}
 
@@ -140,8 +158,47 @@ where
 					.await?;
 				(messages, events)
 			},
-			// Current implementation of Beefy needs to be revised
-			FinalityProtocol::Beefy => unimplemented!(),
+			FinalityProtocol::Beefy => {
+				let AnyClientState::Beefy(client_state) =
+					AnyClientState::decode_recursive(any_client_state, |c| {
+						matches!(c, AnyClientState::Beefy(_))
+					})
+					.ok_or_else(|| Error::Custom(format!("Could not decode client state")))?
+				else {
+					unreachable!()
+				};
+
+				let (signed_commitment, _) = fetch_beefy_justification(&self.relay_client)
+					.await
+					.map_err(Error::BeefyProver)?;
+				// The light client can only verify commitments signed by its current or next
+				// authority set.
+				if signed_commitment.commitment.validator_set_id >
+					client_state.next_authority_set.id
+				{
+					Err(Error::HeaderConstruction(format!(
+						"Latest beefy commitment is signed by authority set {}, but the client only knows of authority set {}",
+						signed_commitment.commitment.validator_set_id,
+						client_state.next_authority_set.id
+					)))?
+				}
+
+				let mut source = self.clone();
+				let updates = query_latest_ibc_events_with_beefy(
+					&mut source,
+					FinalityEvent::Beefy(signed_commitment),
+					counterparty,
+				)
+				.await?;
+				updates.into_iter().fold(
+					(vec![], vec![]),
+					|(mut messages, mut events), (message, _, update_events, _)| {
+						messages.push(message);
+						events.extend(update_events);
+						(messages, events)
+					},
+				)
+			},
 		};
 
 		Ok((messages, events))
@@ -351,9 +408,8 @@ where
 		client_message: AnyClientMessage::Grandpa(ClientMessage::Header(grandpa_header)),
 		signer,
 	};
-	let value = msg.encode_vec()?;
 	Result::<_, anyhow::Error>::Ok((
-		Any { value, type_url: msg.type_url() },
+		msg.to_any(),
 		events,
 		latest_finalized_para_height,
 		latest_finalized_height,
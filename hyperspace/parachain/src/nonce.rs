@@ -0,0 +1,133 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::Error;
+use std::{future::Future, sync::Arc};
+use tokio::sync::Mutex as AsyncMutex;
+
+/// Hands out consecutive nonces for the relayer account, so that multiple extrinsics can be signed
+/// and submitted without waiting for the previous ones to be included.
+#[derive(Clone, Default)]
+pub struct NonceManager {
+	next: Arc<AsyncMutex<Option<u64>>>,
+}
+
+impl NonceManager {
+	/// Reserves the next nonce. `fetch` is used to get the next nonce from the chain when it isn't
+	/// tracked locally yet, or after a [`NonceManager::reset`].
+	pub async fn reserve<F, Fut>(&self, fetch: F) -> Result<u64, Error>
+	where
+		F: FnOnce() -> Fut,
+		Fut: Future<Output = Result<u64, Error>>,
+	{
+		let mut next = self.next.lock().await;
+		let nonce = match *next {
+			Some(nonce) => nonce,
+			None => fetch().await?,
+		};
+		*next = Some(nonce + 1);
+		Ok(nonce)
+	}
+
+	/// Forgets the locally tracked nonce, it will be fetched from the chain on the next
+	/// reservation. Should be called whenever a reserved nonce wasn't used, since later nonces
+	/// can't be included until the gap is filled.
+	pub async fn reset(&self) {
+		*self.next.lock().await = None;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicU64, Ordering};
+
+	/// Returns a fetch closure answering with the next index of the account on chain, and counts
+	/// the fetches.
+	fn chain(next_index: &AtomicU64, fetches: &AtomicU64) -> impl FnOnce() -> Fut + '_ {
+		move || {
+			fetches.fetch_add(1, Ordering::SeqCst);
+			let nonce = next_index.load(Ordering::SeqCst);
+			Box::pin(async move {
+				// let the other reservations run meanwhile
+				tokio::task::yield_now().await;
+				Ok(nonce)
+			})
+		}
+	}
+
+	type Fut = std::pin::Pin<Box<dyn Future<Output = Result<u64, Error>> + Send>>;
+
+	#[tokio::test]
+	async fn reserves_consecutive_nonces_after_fetching_once() {
+		let (next_index, fetches) = (AtomicU64::new(7), AtomicU64::new(0));
+		let manager = NonceManager::default();
+		for expected in 7..10 {
+			assert_eq!(manager.reserve(chain(&next_index, &fetches)).await.unwrap(), expected);
+		}
+		assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+		// clones share the reservations
+		assert_eq!(manager.clone().reserve(chain(&next_index, &fetches)).await.unwrap(), 10);
+		assert_eq!(manager.reserve(chain(&next_index, &fetches)).await.unwrap(), 11);
+	}
+
+	#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+	async fn concurrent_reservations_get_distinct_nonces() {
+		let next_index = Arc::new(AtomicU64::new(3));
+		let fetches = Arc::new(AtomicU64::new(0));
+		let manager = NonceManager::default();
+		let reservations = (0..32).map(|_| {
+			let (manager, next_index, fetches) =
+				(manager.clone(), next_index.clone(), fetches.clone());
+			tokio::spawn(async move { manager.reserve(chain(&next_index, &fetches)).await })
+		});
+		let mut nonces = futures::future::join_all(reservations)
+			.await
+			.into_iter()
+			.map(|nonce| nonce.unwrap().unwrap())
+			.collect::<Vec<_>>();
+		nonces.sort_unstable();
+
+		assert_eq!(nonces, (3..35).collect::<Vec<_>>());
+		assert_eq!(fetches.load(Ordering::SeqCst), 1);
+	}
+
+	#[tokio::test]
+	async fn reset_resyncs_with_the_chain() {
+		let (next_index, fetches) = (AtomicU64::new(5), AtomicU64::new(0));
+		let manager = NonceManager::default();
+		assert_eq!(manager.reserve(chain(&next_index, &fetches)).await.unwrap(), 5);
+		assert_eq!(manager.reserve(chain(&next_index, &fetches)).await.unwrap(), 6);
+
+		// the extrinsic with nonce 5 was included, the one with nonce 6 failed to be submitted
+		next_index.store(6, Ordering::SeqCst);
+		manager.reset().await;
+		assert_eq!(manager.reserve(chain(&next_index, &fetches)).await.unwrap(), 6);
+		assert_eq!(manager.reserve(chain(&next_index, &fetches)).await.unwrap(), 7);
+		assert_eq!(fetches.load(Ordering::SeqCst), 2);
+	}
+
+	#[tokio::test]
+	async fn failed_fetch_reserves_nothing() {
+		let (next_index, fetches) = (AtomicU64::new(4), AtomicU64::new(0));
+		let manager = NonceManager::default();
+		let failing = || async { Err::<u64, _>(Error::Custom("node unreachable".to_string())) };
+		assert!(manager.reserve(failing).await.is_err());
+
+		// the next reservation fetches again
+		assert_eq!(manager.reserve(chain(&next_index, &fetches)).await.unwrap(), 4);
+		assert_eq!(fetches.load(Ordering::SeqCst), 1);
+	}
+}
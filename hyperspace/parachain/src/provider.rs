@@ -13,18 +13,29 @@
 // limitations under the License.
 
 use super::{error::Error, ParachainClient};
-use crate::{finality_protocol::FinalityEvent, FinalityProtocol, GrandpaClientState};
+use crate::{
+	finality_protocol::{prefetch_grandpa_update, FinalityEvent},
+	heads::{new_branch, MAX_BRANCH_LENGTH},
+	FinalityProtocol, GrandpaClientState,
+};
 use beefy_prover::helpers::fetch_timestamp_extrinsic_with_proof;
 use codec::{Decode, Encode};
 use finality_grandpa::BlockNumberOps;
 use futures::Stream;
 use grandpa_light_client_primitives::ParachainHeaderProofs;
 use ibc::{
-	applications::transfer::{Amount, PrefixedCoin, PrefixedDenom},
+	applications::{
+		cross_chain_queries::query::CrossChainQuery,
+		fee::PacketFee,
+		transfer::{Amount, PrefixedCoin, PrefixedDenom},
+	},
 	core::{
 		ics02_client::client_state::{ClientState, ClientType},
 		ics23_commitment::commitment::CommitmentPrefix,
-		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		ics24_host::{
+			identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			Path,
+		},
 	},
 	events::IbcEvent,
 	timestamp::Timestamp,
@@ -32,26 +43,41 @@ use ibc::{
 };
 use ibc_proto::{
 	google::protobuf::Any,
-	ibc::core::{
-		channel::v1::{
-			IdentifiedChannel, QueryChannelResponse, QueryChannelsResponse,
-			QueryNextSequenceReceiveResponse, QueryPacketAcknowledgementResponse,
-			QueryPacketCommitmentResponse, QueryPacketReceiptResponse,
+	ibc::{
+		applications::{
+			fee::v1::PacketFees as RawPacketFees,
+			transfer::v1::{QueryDenomTraceResponse, QueryDenomTracesResponse},
 		},
-		client::v1::{
-			IdentifiedClientState, QueryClientStateResponse, QueryConsensusStateResponse,
+		core::{
+			channel::v1::{
+				IdentifiedChannel, QueryChannelResponse, QueryChannelsResponse,
+				QueryNextSequenceReceiveResponse, QueryPacketAcknowledgementResponse,
+				QueryPacketCommitmentResponse, QueryPacketReceiptResponse,
+			},
+			client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
+			connection::v1::{
+				IdentifiedConnection, Params as ConnectionParams, QueryConnectionResponse,
+				QueryConnectionsResponse,
+			},
 		},
-		connection::v1::{IdentifiedConnection, QueryConnectionResponse},
 	},
 };
-use ibc_rpc::{IbcApiClient, PacketInfo};
+use ibc_rpc::{IbcApiClient, PacketEventsFilter, PacketInfo, MAX_PROOFS_PER_QUERY};
 use ics11_beefy::client_state::ClientState as BeefyClientState;
+use jsonrpsee::{
+	core::Error as RpcError,
+	types::error::{CallError, ErrorCode},
+};
 use light_client_common::config::{AsInnerEvent, Config, IbcEventsT, RuntimeStorage};
 use pallet_ibc::{
 	light_clients::{AnyClientState, AnyConsensusState, HostFunctionsManager},
 	HostConsensusProof,
 };
-use primitives::{apply_prefix, Chain, IbcProvider, KeyProvider, UpdateType};
+use primitives::{
+	apply_prefix, Chain, IbcEventProvider, IbcQueryProvider, KeyProvider, Page, ProofProvider,
+	UpdateType,
+};
+use prost::Message;
 use sp_core::H256;
 use sp_runtime::{
 	traits::{IdentifyAccount, One, Verify},
@@ -67,6 +93,7 @@ use std::{
 use subxt::config::{
 	extrinsic_params::BaseExtrinsicParamsBuilder, ExtrinsicParams, Header as HeaderT, Header,
 };
+use tendermint_proto::Protobuf;
 use tokio_stream::wrappers::ReceiverStream;
 
 #[derive(Debug)]
@@ -76,7 +103,7 @@ pub struct TransactionId<Hash> {
 }
 
 #[async_trait::async_trait]
-impl<T: light_client_common::config::Config + Send + Sync + Clone> IbcProvider
+impl<T: light_client_common::config::Config + Send + Sync + Clone> IbcQueryProvider
 	for ParachainClient<T>
 where
 	u32: From<<<T as subxt::Config>::Header as HeaderT>::Number>,
@@ -107,78 +134,9 @@ where
 	<T as subxt::Config>::Address: Send + Sync,
 	<T as light_client_common::config::Config>::AssetId: Clone,
 {
-	type FinalityEvent = FinalityEvent;
-	type TransactionId = TransactionId<T::Hash>;
 	type Error = Error;
-	type AssetId = <T as light_client_common::config::Config>::AssetId;
-
-	async fn query_latest_ibc_events<C>(
-		&mut self,
-		finality_event: Self::FinalityEvent,
-		counterparty: &C,
-	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
-	where
-		C: Chain,
-	{
-		self.finality_protocol
-			.clone()
-			.query_latest_ibc_events(self, finality_event, counterparty)
-			.await
-	}
-
-	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
-		use futures::StreamExt;
-
-		let (tx, rx) = tokio::sync::mpsc::channel(32);
-		let event = self.para_client.events();
-		let para_client = self.para_client.clone();
-		tokio::spawn(async move {
-			let stream = para_client
-				.blocks()
-				.subscribe_all()
-				.await
-				.expect("should susbcribe to blocks")
-				.filter_map(|block| async {
-					let block = block.ok()?;
-					let hash = block.hash();
-					let events = event.at(hash).await.ok()?;
-					let result = events
-						.find::<<T::Events as AsInnerEvent>::Inner>()
-						.filter_map(|ev| {
-							let ok_event = ev
-								.map_err(|e| {
-									log::error!(target: "hyperspace_parachain", "Error event at block {hash:?}: {:?}", e);
-								})
-								.ok()?;
-							let ev = <T::Events as AsInnerEvent>::from_inner(ok_event).events();
-							ev.into_iter()
-								.map(|ev| TryInto::<IbcEvent>::try_into(ev))
-								.collect::<Result<Vec<_>, _>>()
-								.ok()
-						})
-						.flatten()
-						.collect::<Vec<_>>();
-					Some(result)
-				});
 
-			let mut stream = Box::pin(stream);
-
-			while let Some(evs) = stream.next().await {
-				let mut should_exit = false;
-				for ev in evs {
-					if let Err(_) = tx.send(ev).await {
-						should_exit = true;
-						break
-					}
-				}
-				if should_exit {
-					break
-				}
-			}
-		});
-
-		Box::pin(ReceiverStream::new(rx))
-	}
+	type AssetId = <T as light_client_common::config::Config>::AssetId;
 
 	async fn query_client_consensus(
 		&self,
@@ -247,22 +205,20 @@ where
 		Ok(response)
 	}
 
-	/// Query the proof of the given keys at the given height.
-	///
-	/// Note: all the keys will be prefixed with the connection prefix.
-	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
-		let prefix = self.connection_prefix().into_vec();
-		let prefixed_keys =
-			keys.into_iter().map(|path| apply_prefix(prefix.clone(), path)).collect();
-
-		let proof = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_proof(
-			&*self.para_ws_client,
-			at.revision_height as u32,
-			prefixed_keys,
-		)
-		.await
-		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
-		Ok(proof.proof)
+	async fn query_cross_chain_queries(&self) -> Result<Vec<CrossChainQuery>, Self::Error> {
+		let queries =
+			IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_cross_chain_queries(
+				&*self.para_ws_client,
+			)
+			.await
+			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		queries
+			.into_iter()
+			.map(|query| {
+				CrossChainQuery::decode_vec(&query)
+					.map_err(|e| Error::from(format!("Failed to decode cross-chain query {:?}", e)))
+			})
+			.collect()
 	}
 
 	async fn query_packet_commitment(
@@ -341,20 +297,10 @@ where
 	}
 
 	async fn latest_height_and_timestamp(&self) -> Result<(Height, Timestamp), Self::Error> {
-		let finalized_header = self
-			.para_client
-			.rpc()
-			.header(None)
-			.await?
-			.ok_or_else(|| Error::Custom("Latest height query returned None".to_string()))?;
-		let latest_height: u64 = (finalized_header.number()).into();
-		let height = Height::new(self.para_id.into(), latest_height.into());
-
-		let subxt_block_number: subxt::rpc::types::BlockNumber = latest_height.into();
-		let block_hash =
-			self.para_client.rpc().block_hash(Some(subxt_block_number)).await?.ok_or_else(
-				|| Error::Custom("Latest block hash query returned None".to_string()),
-			)?;
+		// the best block can still be reorganized away, everything is queried at the finalized one
+		let (latest_height, block_hash) = self.finalized_head().await?;
+		let height = Height::new(self.para_id.into(), latest_height);
+
 		let timestamp_addr = T::Storage::timestamp_now();
 		let unix_timestamp_millis = self
 			.para_client
@@ -462,49 +408,16 @@ where
 		&self,
 		at: Height,
 		connection_id: &ConnectionId,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
 	) -> Result<QueryChannelsResponse, Self::Error> {
 		let response =
 			IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_connection_channels(
 				&*self.para_ws_client,
 				at.revision_height as u32,
 				connection_id.to_string(),
-			)
-			.await
-			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
-		Ok(response)
-	}
-
-	async fn query_send_packets(
-		&self,
-		channel_id: ChannelId,
-		port_id: PortId,
-		seqs: Vec<u64>,
-	) -> Result<Vec<PacketInfo>, Self::Error> {
-		let response =
-			IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_send_packets(
-				&*self.para_ws_client,
-				channel_id.to_string(),
-				port_id.to_string(),
-				seqs,
-			)
-			.await
-			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
-
-		Ok(response)
-	}
-
-	async fn query_received_packets(
-		&self,
-		channel_id: ChannelId,
-		port_id: PortId,
-		seqs: Vec<u64>,
-	) -> Result<Vec<PacketInfo>, Self::Error> {
-		let response =
-			IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_recv_packets(
-				&*self.para_ws_client,
-				channel_id.to_string(),
-				port_id.to_string(),
-				seqs,
+				next_key,
+				Some(limit),
 			)
 			.await
 			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -516,70 +429,11 @@ where
 		Duration::from_secs(12)
 	}
 
-	async fn query_client_update_time_and_height(
-		&self,
-		client_id: ClientId,
-		client_height: Height,
-	) -> Result<(Height, Timestamp), Self::Error> {
-		log::trace!(
-			target: "hyperspace_parachain",
-			"Querying client update time and height for client {:?} at height {:?}",
-			client_id,
-			client_height
-		);
-		let response = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_client_update_time_and_height(
-			&*self.para_ws_client,
-			client_id.to_string(),
-			client_height.revision_number,
-			client_height.revision_height,
-		)
-		.await
-		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
-		Ok((
-			response.height.into(),
-			Timestamp::from_nanoseconds(response.timestamp)
-				.map_err(|_| Error::Custom("Received invalid timestamp".to_string()))?,
-		))
-	}
-
-	async fn query_host_consensus_state_proof(
-		&self,
-		client_state: &AnyClientState,
-	) -> Result<Option<Vec<u8>>, Self::Error> {
-		let hash = self
-			.para_client
-			.rpc()
-			.block_hash(Some(client_state.latest_height().revision_height.into()))
-			.await?;
-		let header = self
-			.para_client
-			.rpc()
-			.header(hash)
-			.await?
-			.ok_or_else(|| Error::Custom("Latest height query returned None".to_string()))?;
-		let extrinsic_with_proof =
-			fetch_timestamp_extrinsic_with_proof(&self.para_client, Some(header.hash()))
-				.await
-				.map_err(Error::BeefyProver)?;
-		let code_hash = if let AnyClientState::Wasm(client_state) = &client_state {
-			Some(client_state.code_hash.clone())
-		} else {
-			None
-		};
-		let host_consensus_proof = HostConsensusProof {
-			header: header.encode(),
-			extrinsic: extrinsic_with_proof.ext,
-			extrinsic_proof: extrinsic_with_proof.proof,
-			code_hash,
-		};
-		Ok(Some(host_consensus_proof.encode()))
-	}
-
 	async fn query_ibc_balance(
 		&self,
 		asset_id: Self::AssetId,
 	) -> Result<Vec<PrefixedCoin>, Self::Error> {
-		let account = self.public_key.clone().into_account();
+		let account = self.public_key().into_account();
 		let account = subxt::utils::AccountId32::from(<[u8; 32]>::from(account));
 		let mut hex_string = hex::encode(account.0.to_vec());
 		hex_string.insert_str(0, "0x");
@@ -600,6 +454,70 @@ where
 		}])
 	}
 
+	async fn query_denom_trace(
+		&self,
+		denom_hash: String,
+	) -> Result<QueryDenomTraceResponse, Self::Error> {
+		IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_denom_trace_by_hash(
+			&*self.para_ws_client,
+			denom_hash,
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))
+	}
+
+	async fn query_denom_traces(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<QueryDenomTracesResponse, Self::Error> {
+		// the next key of the pallet's pagination is the scale encoded asset id to start from
+		let key = next_key
+			.map(|key| {
+				<T as light_client_common::config::Config>::AssetId::decode(&mut &*key)
+					.map_err(|e| Error::from(format!("Invalid pagination key {:?}", e)))
+			})
+			.transpose()?;
+		IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_denom_traces(
+			&*self.para_ws_client,
+			key,
+			None,
+			Some(limit),
+			false,
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))
+	}
+
+	async fn query_denom_hash(&self, trace: String) -> Result<String, Self::Error> {
+		// the pallet refers to vouchers by the same hash as ibc-go, no need to ask the chain
+		let denom = PrefixedDenom::from_str(&trace)?;
+		Ok(pallet_ibc::ics20::denom_hash(&denom))
+	}
+
+	async fn query_packet_fees(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		sequence: u64,
+	) -> Result<Vec<PacketFee>, Self::Error> {
+		let bytes = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_packet_fees(
+			&*self.para_ws_client,
+			channel_id.to_string(),
+			port_id.to_string(),
+			sequence,
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		// the fees of a packet that didn't escrow any are returned as an empty `PacketFees`
+		RawPacketFees::decode(&*bytes)
+			.map_err(|e| Error::from(format!("Failed to decode packet fees: {e:?}")))?
+			.packet_fees
+			.into_iter()
+			.map(|fee| PacketFee::try_from(fee).map_err(|e| Error::from(format!("{e:?}"))))
+			.collect()
+	}
+
 	fn connection_prefix(&self) -> CommitmentPrefix {
 		CommitmentPrefix::try_from(self.commitment_prefix.clone()).expect("Should not fail")
 	}
@@ -624,11 +542,10 @@ where
 	}
 
 	async fn query_timestamp_at(&self, block_number: u64) -> Result<u64, Self::Error> {
-		let subxt_block_number: subxt::rpc::types::BlockNumber = block_number.into();
-		let block_hash =
-			self.para_client.rpc().block_hash(Some(subxt_block_number)).await?.ok_or_else(
-				|| Error::Custom("Block hash not found for block number".to_string()),
-			)?;
+		if let Some(timestamp) = self.block_cache.timestamp(block_number) {
+			return Ok(timestamp)
+		}
+		let block_hash = self.finalized_block_hash(block_number).await?;
 		let timestamp_addr = T::Storage::timestamp_now();
 		let unix_timestamp_millis = self
 			.para_client
@@ -638,34 +555,47 @@ where
 			.await?
 			.expect("Timestamp should exist");
 		let timestamp_nanos = Duration::from_millis(unix_timestamp_millis).as_nanos() as u64;
+		self.block_cache.insert_timestamp(block_number, timestamp_nanos);
 
 		Ok(timestamp_nanos)
 	}
 
-	async fn query_clients(&self) -> Result<Vec<ClientId>, Self::Error> {
-		let response: Vec<IdentifiedClientState> = IbcApiClient::<
+	async fn query_clients(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<Page<ClientId>, Self::Error> {
+		let response = IbcApiClient::<
 			u32,
 			H256,
 			<T as light_client_common::config::Config>::AssetId,
-		>::query_clients(&*self.para_ws_client)
+		>::query_clients(&*self.para_ws_client, next_key, Some(limit))
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
-		response
+		let clients = response
+			.client_states
 			.into_iter()
 			.map(|client| {
 				ClientId::from_str(&client.client_id)
 					.map_err(|_| Error::Custom("Invalid client id ".to_string()))
 			})
-			.collect()
+			.collect::<Result<Vec<_>, _>>()?;
+		Ok(Page { items: clients, next_key: response.pagination.map(|page| page.next_key) })
 	}
 
-	async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
+	async fn query_channels(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<Page<(ChannelId, PortId)>, Self::Error> {
 		let response = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_channels(
 			&*self.para_ws_client,
+			next_key,
+			Some(limit),
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
-		response
+		let channels = response
 			.channels
 			.into_iter()
 			.map(|identified_chan| {
@@ -676,7 +606,8 @@ where
 						.expect("Failed to convert invalid string to port id"),
 				))
 			})
-			.collect::<Result<Vec<_>, _>>()
+			.collect::<Result<Vec<_>, Self::Error>>()?;
+		Ok(Page { items: channels, next_key: response.pagination.map(|page| page.next_key) })
 	}
 
 	async fn query_connection_using_client(
@@ -695,46 +626,297 @@ where
 		Ok(response)
 	}
 
-	async fn is_update_required(
+	async fn query_connections(
 		&self,
-		latest_height: u64,
-		latest_client_height_on_counterparty: u64,
-	) -> Result<bool, Self::Error> {
-		let prover = self.grandpa_prover();
-		let session_length = prover
-			.session_length()
-			.await
-			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
-		// We divide the session into some places and if the diff in block updates is greater than
-		// this update is required
-		let base =
-			if cfg!(test) { (session_length / 2) as u64 } else { (session_length / 12) as u64 };
-		let diff = latest_height - latest_client_height_on_counterparty;
-		let pruning_len = 256;
-		Ok(diff >= base.min(pruning_len as u64))
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<QueryConnectionsResponse, Self::Error> {
+		IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_connections(
+			&*self.para_ws_client,
+			next_key,
+			Some(limit),
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))
 	}
 
-	async fn initialize_client_state(
-		&self,
-	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
-		match self.finality_protocol {
-			FinalityProtocol::Grandpa => {
-				let res = self.construct_grandpa_client_state().await?;
-				Ok(res)
-			},
-			FinalityProtocol::Beefy => {
-				let res = self.construct_beefy_client_state().await?;
-				Ok(res)
-			},
-		}
+	async fn query_connection_params(&self) -> Result<ConnectionParams, Self::Error> {
+		let response = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_connection_params(
+			&*self.para_ws_client,
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		response
+			.params
+			.ok_or_else(|| Error::from("Connection params missing from the response".to_string()))
 	}
 
-	async fn query_client_id_from_tx_hash(
-		&self,
-		tx_id: Self::TransactionId,
-	) -> Result<ClientId, Self::Error> {
-		// Query newly created client Id
-		let TransactionId { ext_hash, block_hash } = tx_id;
+	/// Set the channel whitelist for the relayer task.
+	fn set_channel_whitelist(&mut self, channel_whitelist: HashSet<(ChannelId, PortId)>) {
+		*self.channel_whitelist.lock().unwrap() = channel_whitelist;
+	}
+
+	fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
+		self.channel_whitelist.lock().unwrap().insert(channel);
+	}
+
+	fn set_connection_id(&mut self, connection_id: ConnectionId) {
+		*self.connection_id.lock().unwrap() = Some(connection_id);
+	}
+}
+
+#[async_trait::async_trait]
+impl<T: light_client_common::config::Config + Send + Sync + Clone> IbcEventProvider
+	for ParachainClient<T>
+where
+	u32: From<<<T as subxt::Config>::Header as HeaderT>::Number>,
+	u32: From<<<T as subxt::Config>::Header as Header>::Number>,
+	Self: KeyProvider,
+	<<T as light_client_common::config::Config>::Signature as Verify>::Signer:
+		From<MultiSigner> + IdentifyAccount<AccountId = T::AccountId>,
+	MultiSigner: From<MultiSigner>,
+	<T as subxt::Config>::Address: From<<T as subxt::Config>::AccountId>,
+	<T as subxt::Config>::Signature: From<MultiSignature> + Send + Sync,
+	<<T as subxt::Config>::Header as Header>::Number: BlockNumberOps
+		+ From<u32>
+		+ Display
+		+ Ord
+		+ sp_runtime::traits::Zero
+		+ One
+		+ Send
+		+ Sync
+		+ Clone,
+	<T as subxt::Config>::Header: Decode + Send + Sync + Clone,
+	T::Hash: From<sp_core::H256> + From<[u8; 32]>,
+	sp_core::H256: From<T::Hash>,
+	BTreeMap<sp_core::H256, ParachainHeaderProofs>:
+		From<BTreeMap<<T as subxt::Config>::Hash, ParachainHeaderProofs>>,
+	<T::ExtrinsicParams as ExtrinsicParams<T::Index, T::Hash>>::OtherParams:
+		From<BaseExtrinsicParamsBuilder<T, T::Tip>> + Send + Sync,
+	<T as subxt::Config>::AccountId: Send + Sync,
+	<T as subxt::Config>::Address: Send + Sync,
+	<T as light_client_common::config::Config>::AssetId: Clone,
+{
+	type FinalityEvent = FinalityEvent;
+
+	type TransactionId = TransactionId<T::Hash>;
+
+	async fn query_latest_ibc_events<C>(
+		&mut self,
+		finality_event: Self::FinalityEvent,
+		counterparty: &C,
+	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+	where
+		C: Chain,
+	{
+		self.finality_protocol
+			.clone()
+			.query_latest_ibc_events(self, finality_event, counterparty)
+			.await
+	}
+
+	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
+		use futures::StreamExt;
+
+		let (tx, rx) = tokio::sync::mpsc::channel(32);
+		let event = self.para_client.events();
+		let para_client = self.para_client.clone();
+		let heads = self.heads.clone();
+		let name = self.name.clone();
+		tokio::spawn(async move {
+			// best blocks are followed so that events are seen early, they are only relayed once
+			// finalized
+			let mut blocks =
+				para_client.blocks().subscribe_best().await.expect("should susbcribe to blocks");
+
+			while let Some(block) = blocks.next().await {
+				let Ok(block) = block else { continue };
+				let hash = block.hash();
+				let number: u64 = block.header().number().into();
+				let parent = parent_hash::<T>(block.header());
+				let mut hashes = vec![];
+				if let Some(previous) = heads.set_best(number, hash, parent) {
+					// the events of the blocks the subscription skipped, or of the new best chain
+					// after a reorg, are queried again
+					let floor = heads
+						.finalized()
+						.map_or(0, |(finalized, _)| finalized)
+						.max(number.saturating_sub(MAX_BRANCH_LENGTH));
+					let para_client = &para_client;
+					let parent_of = |hash| async move {
+						let header =
+							para_client.rpc().header(Some(hash)).await?.ok_or_else(|| {
+								Error::Custom(format!("Header of block {hash:?} not found"))
+							})?;
+						Ok::<_, Error>(parent_hash::<T>(&header))
+					};
+					match new_branch(previous, (number.saturating_sub(1), parent), floor, parent_of)
+						.await
+					{
+						Ok((branch, retracted)) => {
+							if retracted {
+								log::info!(target: "hyperspace_parachain", "Best chain of {name} reorganized from block {} ({:?}) to {number} ({hash:?})", previous.0, previous.1);
+							}
+							hashes.extend(branch.into_iter().map(|(_, hash)| hash));
+						},
+						Err(e) => {
+							log::warn!(target: "hyperspace_parachain", "Failed to query the blocks of {name} before {number} ({hash:?}): {e:?}");
+						},
+					}
+				}
+				hashes.push(hash);
+
+				for hash in hashes {
+					let Ok(events) = event.at(hash).await else { continue };
+					let events = events
+						.find::<<T::Events as AsInnerEvent>::Inner>()
+						.filter_map(|ev| {
+							let ok_event = ev
+								.map_err(|e| {
+									log::error!(target: "hyperspace_parachain", "Error event at block {hash:?}: {:?}", e);
+								})
+								.ok()?;
+							let ev = <T::Events as AsInnerEvent>::from_inner(ok_event).events();
+							ev.into_iter()
+								.map(|ev| TryInto::<IbcEvent>::try_into(ev))
+								.collect::<Result<Vec<_>, _>>()
+								.ok()
+						})
+						.flatten()
+						.collect::<Vec<_>>();
+					for ev in events {
+						if tx.send(ev).await.is_err() {
+							return
+						}
+					}
+				}
+			}
+		});
+
+		Box::pin(ReceiverStream::new(rx))
+	}
+
+	async fn prefetch_client_update<C>(
+		&self,
+		height: Height,
+		counterparty: &C,
+	) -> Result<(), anyhow::Error>
+	where
+		C: Chain,
+	{
+		match self.finality_protocol {
+			FinalityProtocol::Grandpa => prefetch_grandpa_update(self, height, counterparty).await,
+			// BEEFY updates are built from the commitment of the finality event itself
+			FinalityProtocol::Beefy => Ok(()),
+		}
+	}
+
+	async fn query_send_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		let response =
+			IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_send_packets(
+				&*self.para_ws_client,
+				channel_id.to_string(),
+				port_id.to_string(),
+				seqs,
+			)
+			.await
+			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+
+		Ok(response)
+	}
+
+	async fn query_received_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		let response =
+			IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_recv_packets(
+				&*self.para_ws_client,
+				channel_id.to_string(),
+				port_id.to_string(),
+				seqs,
+			)
+			.await
+			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		Ok(response)
+	}
+
+	async fn query_packet_events(
+		&self,
+		from_height: Height,
+		to_height: Height,
+		filter: PacketEventsFilter,
+	) -> Result<Vec<IbcEvent>, Self::Error> {
+		let events =
+			IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_packet_events(
+				&*self.para_ws_client,
+				from_height.revision_height as u32,
+				to_height.revision_height as u32,
+				filter,
+			)
+			.await
+			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		Ok(events)
+	}
+
+	async fn query_client_update_time_and_height(
+		&self,
+		client_id: ClientId,
+		client_height: Height,
+	) -> Result<(Height, Timestamp), Self::Error> {
+		log::trace!(
+			target: "hyperspace_parachain",
+			"Querying client update time and height for client {:?} at height {:?}",
+			client_id,
+			client_height
+		);
+		let response = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_client_update_time_and_height(
+			&*self.para_ws_client,
+			client_id.to_string(),
+			client_height.revision_number,
+			client_height.revision_height,
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		Ok((
+			response.height.into(),
+			Timestamp::from_nanoseconds(response.timestamp)
+				.map_err(|_| Error::Custom("Received invalid timestamp".to_string()))?,
+		))
+	}
+
+	async fn is_update_required(
+		&self,
+		latest_height: u64,
+		latest_client_height_on_counterparty: u64,
+	) -> Result<bool, Self::Error> {
+		let prover = self.grandpa_prover();
+		let session_length = prover
+			.session_length()
+			.await
+			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		// We divide the session into some places and if the diff in block updates is greater than
+		// this update is required
+		let base =
+			if cfg!(test) { (session_length / 2) as u64 } else { (session_length / 12) as u64 };
+		let diff = latest_height - latest_client_height_on_counterparty;
+		let pruning_len = 256;
+		Ok(diff >= base.min(pruning_len as u64))
+	}
+
+	async fn query_client_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<ClientId, Self::Error> {
+		// Query newly created client Id
+		let TransactionId { ext_hash, block_hash } = tx_id;
 		let identified_client_state = IbcApiClient::<
 			u32,
 			H256,
@@ -797,21 +979,177 @@ where
 			PortId::from_str(&identified_channel.port_id).expect("Should have a valid port id");
 		Ok((channel_id, port_id))
 	}
+}
 
-	/// Set the channel whitelist for the relayer task.
-	fn set_channel_whitelist(&mut self, channel_whitelist: HashSet<(ChannelId, PortId)>) {
-		*self.channel_whitelist.lock().unwrap() = channel_whitelist;
+#[async_trait::async_trait]
+impl<T: light_client_common::config::Config + Send + Sync + Clone> ProofProvider
+	for ParachainClient<T>
+where
+	u32: From<<<T as subxt::Config>::Header as HeaderT>::Number>,
+	u32: From<<<T as subxt::Config>::Header as Header>::Number>,
+	Self: KeyProvider,
+	<<T as light_client_common::config::Config>::Signature as Verify>::Signer:
+		From<MultiSigner> + IdentifyAccount<AccountId = T::AccountId>,
+	MultiSigner: From<MultiSigner>,
+	<T as subxt::Config>::Address: From<<T as subxt::Config>::AccountId>,
+	<T as subxt::Config>::Signature: From<MultiSignature> + Send + Sync,
+	<<T as subxt::Config>::Header as Header>::Number: BlockNumberOps
+		+ From<u32>
+		+ Display
+		+ Ord
+		+ sp_runtime::traits::Zero
+		+ One
+		+ Send
+		+ Sync
+		+ Clone,
+	<T as subxt::Config>::Header: Decode + Send + Sync + Clone,
+	T::Hash: From<sp_core::H256> + From<[u8; 32]>,
+	sp_core::H256: From<T::Hash>,
+	BTreeMap<sp_core::H256, ParachainHeaderProofs>:
+		From<BTreeMap<<T as subxt::Config>::Hash, ParachainHeaderProofs>>,
+	<T::ExtrinsicParams as ExtrinsicParams<T::Index, T::Hash>>::OtherParams:
+		From<BaseExtrinsicParamsBuilder<T, T::Tip>> + Send + Sync,
+	<T as subxt::Config>::AccountId: Send + Sync,
+	<T as subxt::Config>::Address: Send + Sync,
+	<T as light_client_common::config::Config>::AssetId: Clone,
+{
+	/// Query the proof of the given keys at the given height.
+	///
+	/// Note: all the keys will be prefixed with the connection prefix.
+	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
+		self.ensure_finalized(at).await?;
+		let prefix = self.connection_prefix().into_vec();
+		let prefixed_keys =
+			keys.into_iter().map(|path| apply_prefix(prefix.clone(), path)).collect();
+
+		let proof = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_proof(
+			&*self.para_ws_client,
+			at.revision_height as u32,
+			prefixed_keys,
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		Ok(proof.proof)
 	}
 
-	fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
-		self.channel_whitelist.lock().unwrap().insert(channel);
+	/// Queries all the proofs with a single request.
+	///
+	/// Note: all the keys will be prefixed with the connection prefix.
+	async fn query_proofs(
+		&self,
+		at: Height,
+		keys: Vec<Vec<Vec<u8>>>,
+	) -> Result<Vec<Vec<u8>>, Self::Error> {
+		self.ensure_finalized(at).await?;
+		let prefix = self.connection_prefix().into_vec();
+		let prefixed_keys: Vec<Vec<Vec<u8>>> = keys
+			.into_iter()
+			.map(|keys| keys.into_iter().map(|path| apply_prefix(prefix.clone(), path)).collect())
+			.collect();
+
+		let mut proofs = Vec::with_capacity(prefixed_keys.len());
+		for keys in prefixed_keys.chunks(MAX_PROOFS_PER_QUERY) {
+			let result = IbcApiClient::<
+				u32,
+				H256,
+				<T as light_client_common::config::Config>::AssetId,
+			>::query_proofs(
+				&*self.para_ws_client, at.revision_height as u32, keys.to_vec()
+			)
+			.await;
+			match result {
+				Ok(chunk) => proofs.extend(chunk.into_iter().map(|proof| proof.proof)),
+				// nodes that predate `ibc_queryProofs` prove one set of keys per request
+				Err(RpcError::Call(CallError::Custom(e)))
+					if e.code() == ErrorCode::MethodNotFound.code() =>
+					for keys in keys {
+						let proof =
+							IbcApiClient::<
+								u32,
+								H256,
+								<T as light_client_common::config::Config>::AssetId,
+							>::query_proof(
+								&*self.para_ws_client, at.revision_height as u32, keys.clone()
+							)
+							.await
+							.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+						proofs.push(proof.proof);
+					},
+				Err(e) => return Err(Error::from(format!("Rpc Error {:?}", e))),
+			}
+		}
+		Ok(proofs)
 	}
 
-	fn set_connection_id(&mut self, connection_id: ConnectionId) {
-		*self.connection_id.lock().unwrap() = Some(connection_id);
+	async fn query_value_with_proof(
+		&self,
+		at: Height,
+		path: Path,
+	) -> Result<(Option<Vec<u8>>, Vec<u8>), Self::Error> {
+		self.ensure_finalized(at).await?;
+		let (value, proof) = IbcApiClient::<
+			u32,
+			H256,
+			<T as light_client_common::config::Config>::AssetId,
+		>::query_value_with_proof(
+			&*self.para_ws_client, at.revision_height as u32, path.to_string()
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		Ok((value, proof.proof))
 	}
 
-	async fn upload_wasm(&self, _wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
-		Err(Error::Custom("Uploading WASM to parachain is not supported".to_string()))
+	async fn query_host_consensus_state_proof(
+		&self,
+		client_state: &AnyClientState,
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		let hash = self.finalized_block_hash(client_state.latest_height().revision_height).await?;
+		let header = self
+			.para_client
+			.rpc()
+			.header(Some(hash))
+			.await?
+			.ok_or_else(|| Error::Custom("Latest height query returned None".to_string()))?;
+		let extrinsic_with_proof =
+			fetch_timestamp_extrinsic_with_proof(&self.para_client, Some(header.hash()))
+				.await
+				.map_err(Error::BeefyProver)?;
+		let code_hash = if let AnyClientState::Wasm(client_state) = &client_state {
+			Some(client_state.code_hash.clone())
+		} else {
+			None
+		};
+		let host_consensus_proof = HostConsensusProof {
+			header: header.encode(),
+			extrinsic: extrinsic_with_proof.ext,
+			extrinsic_proof: extrinsic_with_proof.proof,
+			code_hash,
+		};
+		Ok(Some(host_consensus_proof.encode()))
 	}
+
+	async fn initialize_client_state(
+		&self,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		match self.finality_protocol {
+			FinalityProtocol::Grandpa => {
+				let res = self.construct_grandpa_client_state().await?;
+				Ok(res)
+			},
+			FinalityProtocol::Beefy => {
+				let res = self.construct_beefy_client_state().await?;
+				Ok(res)
+			},
+		}
+	}
+}
+
+/// Hash of the parent of a parachain block, the first field of its encoded header.
+fn parent_hash<T: Config>(header: &T::Header) -> T::Hash
+where
+	T::Hash: From<H256>,
+{
+	H256::decode(&mut &*header.encode())
+		.expect("Encoded substrate headers start with the parent hash; qed")
+		.into()
 }
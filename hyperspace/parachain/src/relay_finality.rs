@@ -0,0 +1,111 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Relay chain finality shared between the parachain clients of a process.
+//!
+//! Only the subscription to the relay chain is shared. Each parachain is still tracked by its own
+//! GRANDPA or BEEFY light client on the counterparty, whose client state holds a single para id,
+//! and each client builds and submits its own updates from the shared finality events. Proving
+//! the headers of several parachains with one light client isn't supported.
+
+use crate::{error::Error, finality_protocol::FinalityEvent};
+use futures::{Stream, StreamExt};
+use std::{
+	collections::HashMap,
+	future::Future,
+	pin::Pin,
+	sync::{Arc, OnceLock},
+};
+use tokio::sync::{broadcast, Mutex as AsyncMutex};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+
+/// Stream of relay chain finality events, as returned by `finality_notifications`.
+pub type FinalityStream = Pin<Box<dyn Stream<Item = FinalityEvent> + Send + Sync>>;
+
+/// Number of finality events kept for a client that doesn't keep up with the relay chain. Older
+/// events are dropped for it, every finality event supersedes the previous ones.
+pub const FINALITY_EVENTS_CAPACITY: usize = 16;
+
+type Subscriptions = Arc<AsyncMutex<HashMap<String, broadcast::Sender<FinalityEvent>>>>;
+
+/// Multiplexes relay chain finality notifications between every parachain client that shares the
+/// same relay chain and finality protocol, so the relay chain is subscribed to only once and each
+/// justification is fanned out to all parachains finalized by it.
+#[derive(Clone, Default)]
+pub struct RelayFinalityMux {
+	subscriptions: Subscriptions,
+}
+
+impl RelayFinalityMux {
+	/// The multiplexer shared by all the parachain clients of this process.
+	pub fn global() -> &'static RelayFinalityMux {
+		static MUX: OnceLock<RelayFinalityMux> = OnceLock::new();
+		MUX.get_or_init(Default::default)
+	}
+
+	/// Subscribes to the finality events of the relay chain identified by `key`. `subscribe` opens
+	/// the underlying subscription and is only called if no other client is currently subscribed
+	/// to the same relay chain.
+	pub async fn subscribe<F, Fut>(
+		&self,
+		key: String,
+		subscribe: F,
+	) -> Result<FinalityStream, Error>
+	where
+		F: FnOnce() -> Fut,
+		Fut: Future<Output = Result<FinalityStream, Error>>,
+	{
+		let mut subscriptions = self.subscriptions.lock().await;
+		if let Some(sender) = subscriptions.get(&key) {
+			return Ok(receiver_stream(key, sender.subscribe()))
+		}
+
+		let mut stream = subscribe().await?;
+		let (sender, receiver) = broadcast::channel(FINALITY_EVENTS_CAPACITY);
+		subscriptions.insert(key.clone(), sender.clone());
+		let all_subscriptions = self.subscriptions.clone();
+		let subscription_key = key.clone();
+		tokio::spawn(async move {
+			while let Some(event) = stream.next().await {
+				// every client is gone, the next one will open a new subscription.
+				if sender.send(event).is_err() {
+					break
+				}
+			}
+			// dropping the last sender ends the streams of the remaining clients, so that they
+			// resubscribe.
+			let mut subscriptions = all_subscriptions.lock().await;
+			if subscriptions.get(&subscription_key).map_or(false, |s| s.same_channel(&sender)) {
+				subscriptions.remove(&subscription_key);
+			}
+			log::debug!(target: "hyperspace_parachain", "Relay chain finality subscription {subscription_key} closed");
+		});
+
+		Ok(receiver_stream(key, receiver))
+	}
+}
+
+fn receiver_stream(key: String, receiver: broadcast::Receiver<FinalityEvent>) -> FinalityStream {
+	let stream = BroadcastStream::new(receiver).filter_map(move |event| {
+		let event = match event {
+			Ok(event) => Some(event),
+			Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+				log::warn!(target: "hyperspace_parachain", "Skipped {skipped} finality events of {key}, the client is behind the relay chain");
+				None
+			},
+		};
+		futures::future::ready(event)
+	});
+	Box::pin(stream)
+}
@@ -0,0 +1,102 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [`ExternalSigner`] that forwards signing payloads to a [`hyperspace_remote_signer`] service.
+
+use crate::{error::Error, signer::ExternalSigner};
+use hyperspace_remote_signer::{RemoteSignerClient, Scheme, TlsConfig};
+use serde::{Deserialize, Serialize};
+use sp_core::{ecdsa, ed25519, sr25519};
+use sp_runtime::{MultiSignature, MultiSigner};
+
+/// Remote signer endpoint and key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSignerConfig {
+	/// Url of the signer, e.g. `https://signer.internal:9577`
+	pub url: String,
+	/// Identifier of the key on the signer
+	pub key_id: String,
+	/// Client certificates for the mutual TLS connection
+	pub tls: TlsConfig,
+}
+
+pub struct RemoteSigner {
+	client: RemoteSignerClient,
+	key_id: String,
+	chain: String,
+	public_key: MultiSigner,
+}
+
+impl RemoteSigner {
+	/// Connects to the signer and fetches the public key of the configured key
+	pub async fn connect(chain: String, config: &RemoteSignerConfig) -> Result<Self, Error> {
+		let remote_error = |e: hyperspace_remote_signer::Error| Error::Custom(e.to_string());
+		let client = RemoteSignerClient::connect(config.url.clone(), &config.tls)
+			.await
+			.map_err(remote_error)?;
+		let (scheme, public_key) = client.public_key(&config.key_id).await.map_err(remote_error)?;
+		let public_key = decode_public_key(scheme, public_key, &config.key_id)?;
+		Ok(Self { client, key_id: config.key_id.clone(), chain, public_key })
+	}
+}
+
+/// Converts a raw public key returned by a signer service
+pub(crate) fn decode_public_key(
+	scheme: Scheme,
+	public_key: Vec<u8>,
+	key_id: &str,
+) -> Result<MultiSigner, Error> {
+	let invalid_key = |_| Error::Custom(format!("Invalid public key for {key_id}"));
+	let public_key = match scheme {
+		Scheme::Sr25519 => sr25519::Public(public_key.try_into().map_err(invalid_key)?).into(),
+		Scheme::Ed25519 => ed25519::Public(public_key.try_into().map_err(invalid_key)?).into(),
+		Scheme::Ecdsa => ecdsa::Public(public_key.try_into().map_err(invalid_key)?).into(),
+		Scheme::Unspecified =>
+			return Err(Error::Custom(format!("No signature scheme for {key_id}"))),
+	};
+	Ok(public_key)
+}
+
+/// Converts a raw signature returned by a signer service for the given key
+pub(crate) fn decode_signature(
+	public_key: &MultiSigner,
+	signature: Vec<u8>,
+) -> Result<MultiSignature, Error> {
+	let invalid_signature = |_| Error::Custom("Invalid signature returned by the signer".into());
+	let signature = match public_key {
+		MultiSigner::Sr25519(_) =>
+			sr25519::Signature(signature.try_into().map_err(invalid_signature)?).into(),
+		MultiSigner::Ed25519(_) =>
+			ed25519::Signature(signature.try_into().map_err(invalid_signature)?).into(),
+		MultiSigner::Ecdsa(_) =>
+			ecdsa::Signature(signature.try_into().map_err(invalid_signature)?).into(),
+	};
+	Ok(signature)
+}
+
+#[async_trait::async_trait]
+impl ExternalSigner for RemoteSigner {
+	fn public_key(&self) -> MultiSigner {
+		self.public_key.clone()
+	}
+
+	async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, Error> {
+		let signature = self
+			.client
+			.sign(&self.key_id, &self.chain, payload.to_vec())
+			.await
+			.map_err(|e| Error::Custom(e.to_string()))?;
+		decode_signature(&self.public_key, signature)
+	}
+}
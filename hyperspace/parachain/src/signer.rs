@@ -12,22 +12,68 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::{error::Error, nonce::NonceManager};
 use codec::Decode;
+use light_client_common::config::CustomExtrinsicParams;
 use primitives::KeyProvider;
 use sp_keystore::{Keystore, KeystorePtr};
 use sp_runtime::{
 	traits::{IdentifyAccount, Verify},
 	KeyTypeId, MultiSignature, MultiSigner,
 };
-use subxt::tx::Signer;
+use std::sync::{
+	atomic::{AtomicUsize, Ordering},
+	Arc,
+};
+use subxt::{
+	tx::{SubmittableExtrinsic, TxPayload},
+	OnlineClient,
+};
+
+/// Signs extrinsic payloads with a key that isn't held in the local keystore, e.g. on a hardware
+/// wallet. Signing may wait until the payload is approved.
+#[async_trait::async_trait]
+pub trait ExternalSigner: Send + Sync {
+	/// Public key of the signing account
+	fn public_key(&self) -> MultiSigner;
+
+	/// Signs the signer payload of an extrinsic, payloads longer than 256 bytes are already
+	/// hashed.
+	async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, Error>;
+}
+
+/// A relayer account that extrinsics can be signed with
+#[derive(Clone)]
+pub struct SigningAccount {
+	pub public_key: MultiSigner,
+	/// Signs in place of the key store, e.g. a Ledger device
+	pub external_signer: Option<Arc<dyn ExternalSigner>>,
+	/// Nonces reserved for the account, used with `NonceStrategy::Local`
+	pub nonce_manager: NonceManager,
+}
+
+impl SigningAccount {
+	pub fn new(public_key: MultiSigner, external_signer: Option<Arc<dyn ExternalSigner>>) -> Self {
+		Self { public_key, external_signer, nonce_manager: NonceManager::default() }
+	}
+}
 
-/// A [`Signer`] implementation.
+/// Picks the next of `accounts` round-robin, `picks` counts the accounts picked so far
+pub fn round_robin(accounts: &[SigningAccount], picks: &AtomicUsize) -> SigningAccount {
+	let index = picks.fetch_add(1, Ordering::Relaxed);
+	accounts[index % accounts.len()].clone()
+}
+
+/// Signs extrinsics with the key store or an [`ExternalSigner`]. The signature is produced before
+/// the extrinsic is built, so that a signer failing or refusing to sign is returned as an error.
 #[derive(Clone)]
 pub struct ExtrinsicSigner<T: light_client_common::config::Config, Provider: KeyProvider> {
 	account_id: T::AccountId,
 	signer: MultiSigner,
 	key_store: KeystorePtr,
 	key_type_id: KeyTypeId,
+	external_signer: Option<Arc<dyn ExternalSigner>>,
+	dummy_signatures: bool,
 	_phantom: std::marker::PhantomData<Provider>,
 }
 
@@ -36,13 +82,11 @@ where
 	T: light_client_common::config::Config + Send + Sync,
 	<<T as light_client_common::config::Config>::Signature as Verify>::Signer:
 		From<MultiSigner> + IdentifyAccount<AccountId = T::AccountId>,
+	T::AccountId: Into<<T as subxt::Config>::Address> + Clone,
+	<T as subxt::Config>::Signature: From<MultiSignature>,
 	P: KeyProvider,
-	MultiSigner: From<MultiSigner>,
-	<T as subxt::Config>::AccountId: Send + Sync,
-	<T as subxt::Config>::Address: Send + Sync,
-	<T as subxt::Config>::Signature: Send + Sync,
 {
-	/// Creates a new [`Signer`] from a key store reference and key type
+	/// Creates a new [`ExtrinsicSigner`] from a key store reference and key type
 	pub fn new(key_store: KeystorePtr, key_type_id: KeyTypeId, public_key: MultiSigner) -> Self {
 		let account_id =
 			<<T as light_client_common::config::Config>::Signature as Verify>::Signer::from(
@@ -53,30 +97,63 @@ where
 			account_id,
 			key_store,
 			key_type_id,
-			signer: MultiSigner::from(public_key),
+			signer: public_key,
+			external_signer: None,
+			dummy_signatures: false,
 			_phantom: Default::default(),
 		}
 	}
-}
 
-impl<T, P> Signer<T> for ExtrinsicSigner<T, P>
-where
-	T: light_client_common::config::Config + Send + Sync,
-	T::AccountId: Into<<T as subxt::Config>::Address> + Clone + 'static,
-	P: KeyProvider + 'static,
-	<T as subxt::Config>::Signature: From<MultiSignature> + Send + Sync,
-	<T as subxt::Config>::AccountId: Send + Sync,
-	<T as subxt::Config>::Address: Send + Sync,
-{
-	fn account_id(&self) -> &T::AccountId {
+	/// Delegates signing to the external signer, if any, instead of the key store
+	pub fn with_external_signer(
+		mut self,
+		external_signer: Option<Arc<dyn ExternalSigner>>,
+	) -> Self {
+		self.external_signer = external_signer;
+		self
+	}
+
+	/// Produces zeroed signatures instead of bothering the external signer, for extrinsics that
+	/// are only built to estimate their weight.
+	pub fn for_estimation(mut self) -> Self {
+		self.dummy_signatures = self.external_signer.is_some();
+		self
+	}
+
+	pub fn account_id(&self) -> &T::AccountId {
 		&self.account_id
 	}
 
-	fn address(&self) -> <T as subxt::Config>::Address {
-		self.account_id.clone().into()
+	/// Signs the call, with the given nonce or the next one of the account in the pool of the node
+	pub async fn create_signed<C: TxPayload>(
+		&self,
+		client: &OnlineClient<T>,
+		call: &C,
+		nonce: Option<T::Index>,
+		other_params: CustomExtrinsicParams<T>,
+	) -> Result<SubmittableExtrinsic<T, OnlineClient<T>>, Error> {
+		let partial_extrinsic = match nonce {
+			Some(nonce) =>
+				client.tx().create_partial_signed_with_nonce(call, nonce, other_params)?,
+			None => client.tx().create_partial_signed(call, &self.account_id, other_params).await?,
+		};
+		let signature = self.sign(&partial_extrinsic.signer_payload()).await?;
+		Ok(partial_extrinsic
+			.sign_with_address_and_signature(&self.account_id.clone().into(), &signature.into()))
 	}
 
-	fn sign(&self, signer_payload: &[u8]) -> <T as subxt::Config>::Signature {
+	/// Signs the signer payload of an extrinsic
+	pub async fn sign(&self, signer_payload: &[u8]) -> Result<MultiSignature, Error> {
+		if self.dummy_signatures {
+			return Ok(match self.signer {
+				MultiSigner::Ed25519(_) => sp_core::ed25519::Signature([0u8; 64]).into(),
+				MultiSigner::Sr25519(_) => sp_core::sr25519::Signature([0u8; 64]).into(),
+				MultiSigner::Ecdsa(_) => sp_core::ecdsa::Signature([0u8; 65]).into(),
+			})
+		}
+		if let Some(external_signer) = &self.external_signer {
+			return external_signer.sign(signer_payload).await
+		}
 		let (crypto_type_id, public_key) = match &self.signer {
 			MultiSigner::Ed25519(key) => (sp_core::ed25519::CRYPTO_ID, key.0.to_vec()),
 			MultiSigner::Sr25519(key) => (sp_core::sr25519::CRYPTO_ID, key.0.to_vec()),
@@ -89,20 +166,64 @@ where
 			&public_key,
 			signer_payload,
 		)
-		.ok()
-		.flatten()
-		.expect("Signing should not fail");
+		.map_err(|e| Error::Custom(format!("Failed to sign with the key store: {e}")))?
+		.ok_or_else(|| Error::Custom("The signing key isn't in the key store".to_string()))?;
+		let invalid_signature =
+			|_| Error::Custom("The key store returned a signature of another type".to_string());
 		let signature: MultiSignature = match self.signer {
 			MultiSigner::Ed25519(_) => sp_core::ed25519::Signature::decode(&mut &encoded_sig[..])
-				.expect("Should decode same signature type as public key; qed")
+				.map_err(invalid_signature)?
 				.into(),
 			MultiSigner::Sr25519(_) => sp_core::sr25519::Signature::decode(&mut &encoded_sig[..])
-				.expect("Should decode same signature type as public key; qed")
+				.map_err(invalid_signature)?
 				.into(),
 			MultiSigner::Ecdsa(_) => sp_core::ecdsa::Signature::decode(&mut &encoded_sig[..])
-				.expect("Should decode same signature type as public key; qed")
+				.map_err(invalid_signature)?
 				.into(),
 		};
-		signature.into()
+		Ok(signature)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::sr25519;
+
+	fn accounts(count: u8) -> Vec<SigningAccount> {
+		(0..count)
+			.map(|i| SigningAccount::new(sr25519::Public([i; 32]).into(), None))
+			.collect()
+	}
+
+	#[test]
+	fn picks_the_accounts_in_turn() {
+		let accounts = accounts(3);
+		let picks = AtomicUsize::new(0);
+		let picked = (0..7).map(|_| round_robin(&accounts, &picks).public_key).collect::<Vec<_>>();
+		let expected = [0, 1, 2, 0, 1, 2, 0].map(|i| accounts[i].public_key.clone()).to_vec();
+		assert_eq!(picked, expected);
+
+		// a single account signs everything
+		let accounts = &accounts[..1];
+		assert!((0..3).all(|_| round_robin(accounts, &picks).public_key == accounts[0].public_key));
+	}
+
+	#[tokio::test]
+	async fn accounts_reserve_their_own_nonces() {
+		let accounts = accounts(2);
+		let picks = AtomicUsize::new(0);
+		let fetch = |nonce: u64| move || async move { Ok::<_, Error>(nonce) };
+
+		// the picked accounts share the nonces of the listed ones, only fetched once per account
+		for (fetched, expected) in [(10, 10), (20, 20), (0, 11), (0, 21)] {
+			let account = round_robin(&accounts, &picks);
+			assert_eq!(account.nonce_manager.reserve(fetch(fetched)).await.unwrap(), expected);
+		}
+
+		// resetting one account leaves the other one untouched
+		accounts[0].nonce_manager.reset().await;
+		assert_eq!(accounts[0].nonce_manager.reserve(fetch(30)).await.unwrap(), 30);
+		assert_eq!(accounts[1].nonce_manager.reserve(fetch(40)).await.unwrap(), 22);
 	}
 }
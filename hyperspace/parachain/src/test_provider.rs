@@ -12,14 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{signer::ExtrinsicSigner, Error, ParachainClient};
+use crate::{Error, ParachainClient};
 use codec::Decode;
 use finality_grandpa::BlockNumberOps;
 use futures::{Stream, StreamExt};
 use grandpa_light_client_primitives::ParachainHeaderProofs;
 use ibc::{
-	applications::transfer::{msgs::transfer::MsgTransfer, PrefixedCoin},
-	core::ics24_host::identifier::{ChannelId, ClientId},
+	applications::transfer::{msgs::transfer::MsgTransfer, PrefixedCoin, PrefixedDenom},
+	core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 };
 use ibc_proto::google::protobuf::Any;
 use ibc_rpc::IbcApiClient;
@@ -27,7 +27,7 @@ use jsonrpsee::{core::client::SubscriptionClientT, rpc_params};
 use light_client_common::config::RuntimeTransactions;
 use pallet_ibc::{MultiAddress, Timeout, TransferParams};
 use pallet_ibc_ping::SendPingParams;
-use primitives::{KeyProvider, TestProvider};
+use primitives::{utils::expired_transfer, KeyProvider, TestProvider};
 use sp_core::{
 	crypto::{AccountId32, Ss58Codec},
 	H256,
@@ -41,7 +41,7 @@ use subxt::config::{
 	extrinsic_params::BaseExtrinsicParamsBuilder, ExtrinsicParams, Header as HeaderT, Header,
 };
 
-impl<T: light_client_common::config::Config + Send + Sync> ParachainClient<T>
+impl<T: light_client_common::config::Config + Send + Sync + Clone> ParachainClient<T>
 where
 	u32: From<<<T as subxt::Config>::Header as HeaderT>::Number>,
 	Self: KeyProvider,
@@ -69,8 +69,8 @@ where
 	}
 
 	pub async fn submit_create_client_msg(&self, msg: Any) -> Result<ClientId, Error> {
-		let call = T::Tx::ibc_deliver(vec![msg]);
-		let (ext_hash, block_hash) = self.submit_call(call).await?;
+		let (ext_hash, block_hash) =
+			self.submit_call(move || T::Tx::ibc_deliver(vec![msg.clone()])).await?;
 
 		// Query newly created client Id
 		let identified_client_state = IbcApiClient::<
@@ -95,27 +95,26 @@ where
 		amount: u128,
 	) -> Result<(), Error> {
 		// Submit extrinsic to parachain node
-		let call = T::Tx::ibc_transfer(params.into(), asset_id, amount, None);
-		self.submit_call(call).await?;
+		self.submit_call(move || {
+			T::Tx::ibc_transfer(params.clone().into(), asset_id, amount, None)
+		})
+		.await?;
 		Ok(())
 	}
 
 	pub async fn submit_sudo_call(&self, call: T::ParaRuntimeCall) -> Result<(), Error> {
-		let signer = ExtrinsicSigner::<T, Self>::new(
-			self.key_store.clone(),
-			self.key_type_id.clone(),
-			self.public_key.clone(),
-		);
+		let signer = self.extrinsic_signer();
 
 		let ext = T::Tx::sudo_sudo(call);
 		// Submit extrinsic to parachain node
 
-		let other_params = T::custom_extrinsic_params(&self.para_client).await?;
+		let other_params =
+			T::custom_extrinsic_params(&self.para_client, &self.extrinsic_options).await?;
 
-		let _progress = self
-			.para_client
-			.tx()
-			.sign_and_submit_then_watch(&ext, &signer, other_params)
+		let _progress = signer
+			.create_signed(&self.para_client, &ext, None, other_params)
+			.await?
+			.submit_and_watch()
 			.await?
 			.wait_for_in_block()
 			.await?
@@ -196,9 +195,65 @@ where
 			channel_id: channel_id.sequence(),
 		};
 
-		let call = T::Tx::ibc_ping_send_ping(params.into());
+		self.submit_call(move || T::Tx::ibc_ping_send_ping(params.clone().into()))
+			.await
+			.map(|_| ())
+	}
+
+	async fn open_ica_channel(&self, _connection_id: ConnectionId) -> Result<(), Self::Error> {
+		Err(Error::from(
+			"The runtime metadata has no call to register interchain accounts".to_string(),
+		))
+	}
+
+	async fn send_raw_packet(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+		data: Vec<u8>,
+		timeout: Timeout,
+	) -> Result<(), Self::Error> {
+		// the ping pallet is the only module of the runtime sending arbitrary data
+		if port_id.as_str() != pallet_ibc_ping::PORT_ID {
+			return Err(Error::from(format!(
+				"The runtime can only send arbitrary packets on the {} port, not {port_id}",
+				pallet_ibc_ping::PORT_ID
+			)))
+		}
+		let (timeout_height_offset, timeout_timestamp_offset) = match timeout {
+			Timeout::Offset { timestamp: Some(timestamp), height: Some(height) } =>
+				(height, timestamp),
+			_ =>
+				return Err(Error::from(
+					"The ping pallet only takes offsets of both the timeout height and timestamp"
+						.to_string(),
+				)),
+		};
+
+		let params = SendPingParams {
+			data,
+			timeout_height_offset,
+			timeout_timestamp_offset,
+			channel_id: channel_id.sequence(),
+		};
+
+		self.submit_call(move || T::Tx::ibc_ping_send_ping(params.clone().into()))
+			.await
+			.map(|_| ())
+	}
 
-		self.submit_call(call).await.map(|_| ())
+	async fn force_timeout(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+	) -> Result<(), Self::Error> {
+		if port_id != PortId::transfer() {
+			return Err(Error::from(format!(
+				"Expired packets are sent as transfers, not on {port_id}"
+			)))
+		}
+		let denom = PrefixedDenom::from_str("UNIT")?;
+		self.send_transfer(expired_transfer(self, port_id, channel_id, denom)).await
 	}
 
 	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>> {
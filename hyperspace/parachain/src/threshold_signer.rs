@@ -0,0 +1,244 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [`ExternalSigner`] for keys shared by a threshold signer set.
+//!
+//! Signing payloads are handed to a coordinator, which gathers signatures from the members of the
+//! group and returns the combined signature once enough of them approved. The extrinsic is only
+//! broadcast after that, so no single member holds a key that can submit on its own.
+
+use crate::{
+	error::Error,
+	remote_signer::{decode_public_key, decode_signature},
+	signer::ExternalSigner,
+};
+use hyperspace_remote_signer::{SessionState, ThresholdCoordinatorClient, TlsConfig};
+use serde::{Deserialize, Serialize};
+use sp_runtime::{MultiSignature, MultiSigner};
+use std::time::Duration;
+
+/// Threshold signing coordinator endpoint and signer group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdSignerConfig {
+	/// Url of the coordinator, e.g. `https://coordinator.internal:9578`
+	pub url: String,
+	/// Identifier of the signer group on the coordinator
+	pub group_id: String,
+	/// Client certificates for the mutual TLS connection
+	pub tls: TlsConfig,
+	/// How long to wait for the group to sign a payload
+	#[serde(default = "default_session_timeout")]
+	pub session_timeout_secs: u64,
+	/// Interval between polls of a signing session
+	#[serde(default = "default_poll_interval")]
+	pub poll_interval_ms: u64,
+}
+
+/// Upper bound of `session_timeout_secs`. Submission waits for the signature, and the extrinsic
+/// is built against the chain state of when the session was opened.
+pub const MAX_SESSION_TIMEOUT_SECS: u64 = 300;
+
+fn default_session_timeout() -> u64 {
+	60
+}
+
+fn default_poll_interval() -> u64 {
+	1000
+}
+
+pub struct ThresholdSigner {
+	client: ThresholdCoordinatorClient,
+	group_id: String,
+	chain: String,
+	public_key: MultiSigner,
+	session_timeout: Duration,
+	poll_interval: Duration,
+}
+
+impl ThresholdSigner {
+	/// Connects to the coordinator and fetches the group key of the configured group
+	pub async fn connect(chain: String, config: &ThresholdSignerConfig) -> Result<Self, Error> {
+		let client = ThresholdCoordinatorClient::connect(config.url.clone(), &config.tls)
+			.await
+			.map_err(coordinator_error)?;
+		Self::with_client(client, chain, config).await
+	}
+
+	/// Fetches the group key of the configured group from the connected coordinator
+	async fn with_client(
+		client: ThresholdCoordinatorClient,
+		chain: String,
+		config: &ThresholdSignerConfig,
+	) -> Result<Self, Error> {
+		if config.session_timeout_secs > MAX_SESSION_TIMEOUT_SECS {
+			return Err(Error::Custom(format!(
+				"Threshold signer session timeout of {}s exceeds the maximum of {MAX_SESSION_TIMEOUT_SECS}s",
+				config.session_timeout_secs
+			)))
+		}
+		let (scheme, public_key) =
+			client.group_key(&config.group_id).await.map_err(coordinator_error)?;
+		let public_key = decode_public_key(scheme, public_key, &config.group_id)?;
+		Ok(Self {
+			client,
+			group_id: config.group_id.clone(),
+			chain,
+			public_key,
+			session_timeout: Duration::from_secs(config.session_timeout_secs),
+			poll_interval: Duration::from_millis(config.poll_interval_ms),
+		})
+	}
+
+	/// Opens a signing session and polls it until the group signed or refused, see
+	/// [`ExternalSigner::sign`] for the timeout
+	async fn collect_signature(&self, payload: Vec<u8>) -> Result<Vec<u8>, Error> {
+		let session_id = self
+			.client
+			.open_session(&self.group_id, &self.chain, payload)
+			.await
+			.map_err(coordinator_error)?;
+		let mut approvals = 0;
+		loop {
+			let status =
+				self.client.session_status(&session_id).await.map_err(coordinator_error)?;
+			match status.state() {
+				SessionState::Complete => return Ok(status.signature),
+				SessionState::Rejected | SessionState::Expired =>
+					return Err(Error::Custom(format!(
+						"Signing session {session_id} of group {} failed: {}",
+						self.group_id, status.reason
+					))),
+				SessionState::Pending | SessionState::Unspecified => {},
+			}
+			if status.approvals != approvals {
+				approvals = status.approvals;
+				log::debug!(target: "hyperspace_parachain", "Signing session {session_id}: {approvals}/{} approvals", status.threshold);
+			}
+			tokio::time::sleep(self.poll_interval).await;
+		}
+	}
+}
+
+fn coordinator_error(e: hyperspace_remote_signer::Error) -> Error {
+	Error::Custom(format!("Threshold coordinator error: {e}"))
+}
+
+#[async_trait::async_trait]
+impl ExternalSigner for ThresholdSigner {
+	fn public_key(&self) -> MultiSigner {
+		self.public_key.clone()
+	}
+
+	async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, Error> {
+		let signature =
+			tokio::time::timeout(self.session_timeout, self.collect_signature(payload.to_vec()))
+				.await
+				.map_err(|_| {
+					Error::Custom(format!(
+						"Signing session of group {} timed out after {:?}",
+						self.group_id, self.session_timeout
+					))
+				})??;
+		decode_signature(&self.public_key, signature)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hyperspace_remote_signer::testing::{InProcessCoordinator, Outcome};
+	use sp_core::{sr25519, Pair};
+
+	const GROUP_ID: &str = "relayers";
+	const PAYLOAD: &[u8] = b"payload";
+
+	fn config(session_timeout_secs: u64) -> ThresholdSignerConfig {
+		ThresholdSignerConfig {
+			url: "http://127.0.0.1".to_string(),
+			group_id: GROUP_ID.to_string(),
+			tls: TlsConfig {
+				ca_cert: "ca.pem".into(),
+				cert: "relayer.pem".into(),
+				key: "relayer.key".into(),
+			},
+			session_timeout_secs,
+			poll_interval_ms: 10,
+		}
+	}
+
+	fn group_key() -> sr25519::Pair {
+		sr25519::Pair::from_string("//Alice", None).unwrap()
+	}
+
+	async fn signer(
+		outcome: Outcome,
+		config: &ThresholdSignerConfig,
+	) -> Result<ThresholdSigner, Error> {
+		let client = InProcessCoordinator::new(GROUP_ID, group_key(), 2, outcome)
+			.spawn()
+			.await
+			.unwrap();
+		ThresholdSigner::with_client(client, "picasso".to_string(), config).await
+	}
+
+	#[tokio::test]
+	async fn signs_with_the_group_key_once_the_threshold_is_reached() {
+		let signer = signer(Outcome::Sign { polls: 3 }, &config(5)).await.unwrap();
+		let group_key = group_key().public();
+		assert_eq!(signer.public_key(), MultiSigner::from(group_key));
+
+		let signature = match signer.sign(PAYLOAD).await.unwrap() {
+			MultiSignature::Sr25519(signature) => signature,
+			signature => panic!("Expected an sr25519 signature, got {signature:?}"),
+		};
+		assert!(sr25519::Pair::verify(&signature, PAYLOAD, &group_key));
+	}
+
+	#[tokio::test]
+	async fn rejected_and_expired_sessions_fail_with_their_reason() {
+		for (outcome, reason) in [
+			(Outcome::Reject("refused by 2 of 3 members".to_string()), "refused by 2 of 3 members"),
+			(Outcome::Expire("no quorum within 30s".to_string()), "no quorum within 30s"),
+		] {
+			let signer = signer(outcome, &config(5)).await.unwrap();
+			let error = signer.sign(PAYLOAD).await.unwrap_err().to_string();
+			assert!(error.contains(reason), "{error}");
+		}
+	}
+
+	#[tokio::test]
+	async fn session_times_out_when_the_group_never_answers() {
+		let signer = signer(Outcome::Pending, &config(1)).await.unwrap();
+		let error = tokio::time::timeout(Duration::from_secs(10), signer.sign(PAYLOAD))
+			.await
+			.expect("the session timeout bounds signing")
+			.unwrap_err()
+			.to_string();
+		assert!(error.contains("timed out after 1s"), "{error}");
+	}
+
+	#[tokio::test]
+	async fn rejects_unknown_groups_and_excessive_session_timeouts() {
+		let mut unknown_group = config(5);
+		unknown_group.group_id = "unknown".to_string();
+		assert!(signer(Outcome::Pending, &unknown_group).await.is_err());
+
+		let error = signer(Outcome::Pending, &config(MAX_SESSION_TIMEOUT_SECS + 1))
+			.await
+			.err()
+			.unwrap()
+			.to_string();
+		assert!(error.contains("exceeds the maximum"), "{error}");
+	}
+}
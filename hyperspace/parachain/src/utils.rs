@@ -19,6 +19,8 @@ use beefy_prover::helpers::unsafe_arc_cast;
 use codec::Decode;
 use frame_support::pallet_prelude::{DispatchClass, Weight};
 use frame_system::limits::BlockWeights;
+use jsonrpsee::{core::client::ClientT, rpc_params};
+use jsonrpsee_ws_client::{WsClient, WsClientBuilder};
 use sp_core::H256;
 use std::sync::Arc;
 
@@ -62,3 +64,94 @@ pub async fn fetch_max_extrinsic_weight<T: light_client_common::config::Config>(
 		.unwrap_or(Weight::from_ref_time(u64::MAX));
 	Ok(max_extrinsic_weight.ref_time())
 }
+
+/// Re-fetches the runtime version of the node and, if the spec version differs from the one the
+/// client was built with, refreshes the client's metadata. Returns `true` if the client was
+/// updated.
+pub async fn refresh_runtime_metadata<T: light_client_common::config::Config>(
+	client: &subxt::OnlineClient<T>,
+) -> Result<bool, Error> {
+	let runtime_version = client.rpc().runtime_version(None).await?;
+	if runtime_version.spec_version == client.runtime_version().spec_version {
+		return Ok(false)
+	}
+	let metadata = client.rpc().metadata().await?;
+	client.set_metadata(metadata);
+	client.set_runtime_version(runtime_version);
+	Ok(true)
+}
+
+/// Spawns a task that follows runtime upgrades of the node and keeps the metadata of the given
+/// client (and all of its clones) up to date. The task ends together with the underlying
+/// connection, a new one is spawned on reconnection.
+pub fn spawn_runtime_upgrade_watcher<T: light_client_common::config::Config>(
+	chain_name: String,
+	client: subxt::OnlineClient<T>,
+) {
+	use futures::StreamExt;
+
+	tokio::spawn(async move {
+		let updater = client.updater();
+		let mut updates = match updater.runtime_updates().await {
+			Ok(updates) => updates,
+			Err(e) => {
+				log::warn!(target: "hyperspace", "Failed to subscribe to runtime updates of {chain_name}: {e:?}");
+				return
+			},
+		};
+		while let Some(update) = updates.next().await {
+			let update = match update {
+				Ok(update) => update,
+				Err(e) => {
+					log::warn!(target: "hyperspace", "Failed to fetch runtime update of {chain_name}: {e:?}");
+					continue
+				},
+			};
+			let spec_version = update.runtime_version().spec_version;
+			// The subscription yields the current runtime version first, which is not an upgrade
+			if updater.apply_update(update).is_ok() {
+				log::info!(target: "hyperspace", "Runtime of {chain_name} upgraded to spec version {spec_version}, metadata refreshed");
+			}
+		}
+		log::debug!(target: "hyperspace", "Runtime updates subscription of {chain_name} ended");
+	});
+}
+
+/// Connects to the first healthy endpoint out of `urls`. When `current` is given, the other
+/// endpoints are tried first, in order, and `current` is only retried as the last resort. An
+/// endpoint is considered healthy if it responds to `system_health` and isn't syncing.
+pub async fn connect_to_healthy_endpoint(
+	urls: &[String],
+	current: Option<&str>,
+) -> Result<(String, Arc<WsClient>), Error> {
+	let start = current
+		.and_then(|current| urls.iter().position(|url| url == current))
+		.map(|pos| pos + 1)
+		.unwrap_or_default();
+	let mut last_error = None;
+	for url in urls.iter().cycle().skip(start).take(urls.len()) {
+		match connect_and_check_health(url).await {
+			Ok(client) => return Ok((url.clone(), client)),
+			Err(e) => {
+				log::warn!(target: "hyperspace", "RPC endpoint {url} is unavailable: {e:?}");
+				last_error = Some(e);
+			},
+		}
+	}
+	Err(last_error.unwrap_or_else(|| Error::Custom("No RPC endpoints configured".to_string())))
+}
+
+async fn connect_and_check_health(url: &str) -> Result<Arc<WsClient>, Error> {
+	let client = WsClientBuilder::default()
+		.build(url)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+	let health: serde_json::Value = client
+		.request("system_health", rpc_params![])
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+	if health.get("isSyncing").and_then(|syncing| syncing.as_bool()).unwrap_or(false) {
+		Err(Error::Custom(format!("{url} is still syncing")))?
+	}
+	Ok(Arc::new(client))
+}
@@ -0,0 +1,60 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [`ExternalSigner`] backed by a key of Vault's transit secrets engine.
+
+use crate::{error::Error, signer::ExternalSigner};
+use primitives::vault::VaultConfig;
+use sp_core::ed25519;
+use sp_runtime::{MultiSignature, MultiSigner};
+
+pub struct VaultTransitSigner {
+	config: VaultConfig,
+	public_key: ed25519::Public,
+}
+
+impl VaultTransitSigner {
+	/// Fetches the public key of the transit key
+	pub async fn new(config: VaultConfig) -> Result<Self, Error> {
+		let public_key = {
+			let config = config.clone();
+			tokio::task::spawn_blocking(move || config.transit_public_key())
+				.await
+				.map_err(|e| Error::Custom(format!("The Vault request task failed: {e}")))?
+				.map_err(|e| Error::Custom(e.to_string()))?
+		};
+		Ok(Self { config, public_key: ed25519::Public(public_key) })
+	}
+}
+
+#[async_trait::async_trait]
+impl ExternalSigner for VaultTransitSigner {
+	fn public_key(&self) -> MultiSigner {
+		self.public_key.into()
+	}
+
+	async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, Error> {
+		// the Vault client is blocking
+		let config = self.config.clone();
+		let payload = payload.to_vec();
+		let signature = tokio::task::spawn_blocking(move || config.transit_sign(&payload))
+			.await
+			.map_err(|e| Error::Custom(format!("The Vault request task failed: {e}")))?
+			.map_err(|e| Error::Custom(e.to_string()))?;
+		let signature: [u8; 64] = signature
+			.try_into()
+			.map_err(|_| Error::Custom("Invalid signature from Vault".into()))?;
+		Ok(ed25519::Signature(signature).into())
+	}
+}
@@ -0,0 +1,93 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::Error;
+use bech32::{ToBase32, Variant};
+use ibc::signer::Signer;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use subxt::ext::sp_core::crypto::{AccountId32, Ss58AddressFormat, Ss58Codec};
+
+/// Encoding of the relayer address in the [`Signer`] of submitted messages
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFormat {
+	/// SS58 with the given network prefix, for 32 byte account ids
+	Ss58 { prefix: u16 },
+	/// Bech32 with the given human readable part
+	Bech32 { hrp: String },
+	/// `0x` prefixed hex string
+	Hex,
+}
+
+impl AddressFormat {
+	/// Encodes the raw account id bytes
+	pub fn encode(&self, account: &[u8]) -> Result<String, Error> {
+		let address = match self {
+			AddressFormat::Ss58 { prefix } => {
+				let account = AccountId32::try_from(account).map_err(|_| {
+					Error::Custom(format!(
+						"SS58 addresses require a 32 byte account id, got {} bytes",
+						account.len()
+					))
+				})?;
+				account.to_ss58check_with_version(Ss58AddressFormat::custom(*prefix))
+			},
+			AddressFormat::Bech32 { hrp } =>
+				bech32::encode(hrp, account.to_base32(), Variant::Bech32)
+					.map_err(|e| Error::Custom(format!("Could not encode account id {e}")))?,
+			AddressFormat::Hex => format!("0x{}", hex::encode(account)),
+		};
+		Ok(address)
+	}
+
+	/// Encodes the raw account id bytes as a [`Signer`]
+	pub fn signer(&self, account: &[u8]) -> Result<Signer, Error> {
+		let address = self.encode(account)?;
+		Signer::from_str(&address)
+			.map_err(|e| Error::Custom(format!("Could not parse account id {address}: {e}")))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bech32::FromBase32;
+
+	const ACCOUNT: [u8; 32] = [
+		212, 53, 147, 199, 21, 253, 211, 28, 97, 20, 26, 189, 4, 169, 159, 214, 130, 44, 133, 88,
+		133, 76, 205, 227, 154, 86, 132, 231, 165, 109, 162, 125,
+	];
+
+	#[test]
+	fn encodes_account_in_each_format() {
+		assert_eq!(
+			AddressFormat::Ss58 { prefix: 42 }.encode(&ACCOUNT).unwrap(),
+			"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+		);
+		assert_eq!(
+			AddressFormat::Hex.encode(&ACCOUNT).unwrap(),
+			"0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+		);
+		let address = AddressFormat::Bech32 { hrp: "cosmos".to_string() }.encode(&ACCOUNT).unwrap();
+		let (hrp, data, _) = bech32::decode(&address).unwrap();
+		assert_eq!(hrp, "cosmos");
+		assert_eq!(Vec::<u8>::from_base32(&data).unwrap(), ACCOUNT.to_vec());
+	}
+
+	#[test]
+	fn rejects_short_accounts_for_ss58() {
+		assert!(AddressFormat::Ss58 { prefix: 42 }.encode(&ACCOUNT[..20]).is_err());
+	}
+}
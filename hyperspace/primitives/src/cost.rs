@@ -0,0 +1,148 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cost of transactions in the native unit of each chain, e.g. weight on substrate chains, and the
+//! budget a single transaction must fit into. The relayer core only compares costs with the
+//! budget of the same chain, it never converts between units.
+
+use crate::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Unit the cost of transactions is measured in on a chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CostUnit {
+	/// Substrate weight (ref time)
+	Weight,
+	/// Cosmos SDK gas
+	Gas,
+	/// Encoded size of the transaction in bytes
+	Bytes,
+}
+
+impl Display for CostUnit {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Weight => write!(f, "weight"),
+			Self::Gas => write!(f, "gas"),
+			Self::Bytes => write!(f, "bytes"),
+		}
+	}
+}
+
+/// Estimated cost of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TxCost {
+	pub unit: CostUnit,
+	pub amount: u64,
+}
+
+impl TxCost {
+	pub fn new(unit: CostUnit, amount: u64) -> Self {
+		Self { unit, amount }
+	}
+
+	pub fn weight(amount: u64) -> Self {
+		Self::new(CostUnit::Weight, amount)
+	}
+
+	pub fn gas(amount: u64) -> Self {
+		Self::new(CostUnit::Gas, amount)
+	}
+
+	pub fn bytes(amount: u64) -> Self {
+		Self::new(CostUnit::Bytes, amount)
+	}
+
+	/// Cost of the transactions of both costs together, fails if they're in different units.
+	pub fn checked_add(self, other: Self) -> Result<Self, Error> {
+		check_units(self.unit, other.unit)?;
+		Ok(Self::new(self.unit, self.amount.saturating_add(other.amount)))
+	}
+}
+
+impl Display for TxCost {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} {}", self.amount, self.unit)
+	}
+}
+
+/// Maximum cost of a single transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TxBudget {
+	pub unit: CostUnit,
+	pub max: u64,
+}
+
+impl TxBudget {
+	pub fn new(unit: CostUnit, max: u64) -> Self {
+		Self { unit, max }
+	}
+
+	pub fn weight(max: u64) -> Self {
+		Self::new(CostUnit::Weight, max)
+	}
+
+	pub fn gas(max: u64) -> Self {
+		Self::new(CostUnit::Gas, max)
+	}
+
+	pub fn bytes(max: u64) -> Self {
+		Self::new(CostUnit::Bytes, max)
+	}
+
+	/// Whether a transaction of the given cost fits into the budget.
+	pub fn fits(&self, cost: TxCost) -> Result<bool, Error> {
+		check_units(self.unit, cost.unit)?;
+		Ok(cost.amount <= self.max)
+	}
+
+	/// How many times the cost exceeds the budget, rounded down: 0 if the transaction fits.
+	pub fn overrun(&self, cost: TxCost) -> Result<u64, Error> {
+		check_units(self.unit, cost.unit)?;
+		Ok(cost.amount / self.max.max(1))
+	}
+}
+
+impl Display for TxBudget {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} {}", self.max, self.unit)
+	}
+}
+
+fn check_units(expected: CostUnit, unit: CostUnit) -> Result<(), Error> {
+	if expected != unit {
+		return Err(Error::Custom(format!("Expected a cost in {expected}, got {unit}")))
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn budget_only_compares_costs_of_its_unit() {
+		let budget = TxBudget::gas(1000);
+		assert!(budget.fits(TxCost::gas(1000)).unwrap());
+		assert!(!budget.fits(TxCost::gas(1001)).unwrap());
+		assert_eq!(budget.overrun(TxCost::gas(999)).unwrap(), 0);
+		assert_eq!(budget.overrun(TxCost::gas(2500)).unwrap(), 2);
+		assert!(budget.fits(TxCost::weight(10)).is_err());
+		assert!(TxCost::gas(1).checked_add(TxCost::bytes(1)).is_err());
+		assert_eq!(
+			TxCost::gas(1).checked_add(TxCost::gas(u64::MAX)).unwrap(),
+			TxCost::gas(u64::MAX)
+		);
+	}
+}
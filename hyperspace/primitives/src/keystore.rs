@@ -0,0 +1,220 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Passphrase protected keystore files, so that chain configs don't have to carry raw seeds.
+//!
+//! The secret is encrypted with AES-256-GCM under a key derived from the passphrase with scrypt.
+//! The passphrase is read from [`KEYSTORE_PASSPHRASE_ENV`] when set, otherwise it is prompted for
+//! on the terminal.
+
+use crate::error::Error;
+use aes_gcm::{
+	aead::{Aead, KeyInit},
+	Aes256Gcm, Nonce,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the keystore passphrase
+pub const KEYSTORE_PASSPHRASE_ENV: &str = "HYPERSPACE_KEYSTORE_PASSPHRASE";
+
+const KEYSTORE_VERSION: u8 = 1;
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Scrypt parameters used to derive the encryption key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+	pub log_n: u8,
+	pub r: u32,
+	pub p: u32,
+}
+
+impl Default for KdfParams {
+	fn default() -> Self {
+		Self { log_n: 15, r: 8, p: 1 }
+	}
+}
+
+/// Contents of an encrypted keystore file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedKeystore {
+	version: u8,
+	kdf: KdfParams,
+	/// hex encoded scrypt salt
+	salt: String,
+	/// hex encoded AES-GCM nonce
+	nonce: String,
+	/// hex encoded encrypted secret
+	ciphertext: String,
+}
+
+impl EncryptedKeystore {
+	/// Encrypts the secret with the default key derivation parameters
+	pub fn encrypt(secret: &str, passphrase: &str) -> Result<Self, Error> {
+		Self::encrypt_with(secret, passphrase, KdfParams::default())
+	}
+
+	fn encrypt_with(secret: &str, passphrase: &str, kdf: KdfParams) -> Result<Self, Error> {
+		let mut salt = [0u8; SALT_LEN];
+		let mut nonce = [0u8; NONCE_LEN];
+		rand::thread_rng().fill_bytes(&mut salt);
+		rand::thread_rng().fill_bytes(&mut nonce);
+		let cipher = cipher(passphrase, &salt, &kdf)?;
+		let ciphertext = cipher
+			.encrypt(Nonce::from_slice(&nonce), secret.as_bytes())
+			.map_err(|_| Error::Custom("Failed to encrypt the keystore secret".to_string()))?;
+		Ok(Self {
+			version: KEYSTORE_VERSION,
+			kdf,
+			salt: hex::encode(salt),
+			nonce: hex::encode(nonce),
+			ciphertext: hex::encode(ciphertext),
+		})
+	}
+
+	/// Decrypts the secret, fails if the passphrase is wrong or the file was tampered with
+	pub fn decrypt(&self, passphrase: &str) -> Result<String, Error> {
+		if self.version != KEYSTORE_VERSION {
+			return Err(Error::Custom(format!("Unsupported keystore version {}", self.version)))
+		}
+		let nonce = hex::decode(&self.nonce)?;
+		if nonce.len() != NONCE_LEN {
+			return Err(Error::Custom("Invalid keystore nonce".to_string()))
+		}
+		let cipher = cipher(passphrase, &hex::decode(&self.salt)?, &self.kdf)?;
+		let secret = cipher
+			.decrypt(Nonce::from_slice(&nonce), hex::decode(&self.ciphertext)?.as_slice())
+			.map_err(|_| Error::Custom("Wrong keystore passphrase".to_string()))?;
+		Ok(String::from_utf8(secret)?)
+	}
+
+	pub fn load(path: &Path) -> Result<Self, Error> {
+		let contents = std::fs::read_to_string(path).map_err(|e| {
+			Error::Custom(format!("Failed to read keystore {}: {e}", path.display()))
+		})?;
+		serde_json::from_str(&contents)
+			.map_err(|e| Error::Custom(format!("Failed to parse keystore {}: {e}", path.display())))
+	}
+
+	pub fn save(&self, path: &Path) -> Result<(), Error> {
+		let contents = serde_json::to_string_pretty(self)
+			.map_err(|e| Error::Custom(format!("Failed to serialize keystore: {e}")))?;
+		std::fs::write(path, contents)
+			.map_err(|e| Error::Custom(format!("Failed to write keystore {}: {e}", path.display())))
+	}
+}
+
+fn cipher(passphrase: &str, salt: &[u8], kdf: &KdfParams) -> Result<Aes256Gcm, Error> {
+	let params = scrypt::Params::new(kdf.log_n, kdf.r, kdf.p, KEY_LEN)
+		.map_err(|e| Error::Custom(format!("Invalid keystore kdf params: {e}")))?;
+	let mut key = [0u8; KEY_LEN];
+	scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+		.map_err(|e| Error::Custom(format!("Failed to derive keystore key: {e}")))?;
+	Aes256Gcm::new_from_slice(&key).map_err(|e| Error::Custom(format!("Invalid keystore key: {e}")))
+}
+
+/// Reads the passphrase from [`KEYSTORE_PASSPHRASE_ENV`] or prompts for it. New passphrases are
+/// prompted for twice.
+pub fn read_passphrase(confirm: bool) -> Result<String, Error> {
+	if let Ok(passphrase) = std::env::var(KEYSTORE_PASSPHRASE_ENV) {
+		return Ok(passphrase)
+	}
+	let passphrase = rpassword::prompt_password("Keystore passphrase: ")
+		.map_err(|e| Error::Custom(format!("Failed to read passphrase: {e}")))?;
+	if confirm {
+		let repeated = rpassword::prompt_password("Repeat passphrase: ")
+			.map_err(|e| Error::Custom(format!("Failed to read passphrase: {e}")))?;
+		if repeated != passphrase {
+			return Err(Error::Custom("Passphrases don't match".to_string()))
+		}
+	}
+	Ok(passphrase)
+}
+
+/// Prompts for a signing secret, e.g. a secret seed or mnemonic, without echoing it
+pub fn prompt_secret() -> Result<String, Error> {
+	let secret = rpassword::prompt_password("Secret seed or mnemonic: ")
+		.map_err(|e| Error::Custom(format!("Failed to read secret: {e}")))?;
+	Ok(secret.trim().to_string())
+}
+
+/// Returns the signing secret of a chain config, decrypting the keystore when one is configured
+/// and falling back to the raw secret otherwise.
+pub fn resolve_secret(raw_secret: &str, keystore: Option<&Path>) -> Result<String, Error> {
+	match keystore {
+		Some(path) => {
+			if !raw_secret.is_empty() {
+				log::warn!(target: "hyperspace", "Both a raw secret and a keystore are configured, using the keystore {}", path.display());
+			}
+			EncryptedKeystore::load(path)?.decrypt(&read_passphrase(false)?)
+		},
+		None if raw_secret.is_empty() =>
+			Err(Error::Custom("Neither a raw secret nor a keystore is configured".to_string())),
+		None => Ok(raw_secret.to_string()),
+	}
+}
+
+/// Chain configs that can keep their signing secret in an [`EncryptedKeystore`]
+pub trait KeystoreConfig {
+	/// The raw secret field of the config, e.g. the private key or mnemonic
+	fn raw_secret_mut(&mut self) -> &mut String;
+
+	/// Path of the keystore file
+	fn keystore_mut(&mut self) -> &mut Option<PathBuf>;
+
+	/// Moves the raw secret into a new keystore file at `path` and points the config at it.
+	fn migrate_to_keystore(&mut self, path: PathBuf, passphrase: &str) -> Result<(), Error> {
+		let secret = self.raw_secret_mut();
+		if secret.is_empty() {
+			return Err(Error::Custom("The config has no raw secret to migrate".to_string()))
+		}
+		EncryptedKeystore::encrypt(secret, passphrase)?.save(&path)?;
+		secret.clear();
+		*self.keystore_mut() = Some(path);
+		Ok(())
+	}
+
+	/// Signs with `secret` instead of the configured raw secret or keystore.
+	fn set_secret(&mut self, secret: String) {
+		*self.raw_secret_mut() = secret;
+		*self.keystore_mut() = None;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TEST_KDF: KdfParams = KdfParams { log_n: 4, r: 8, p: 1 };
+
+	#[test]
+	fn decrypts_with_the_right_passphrase_only() {
+		let keystore = EncryptedKeystore::encrypt_with("//Alice", "passphrase", TEST_KDF).unwrap();
+		assert_eq!(keystore.decrypt("passphrase").unwrap(), "//Alice");
+		assert!(keystore.decrypt("wrong").is_err());
+	}
+
+	#[test]
+	fn detects_tampered_ciphertext() {
+		let mut keystore =
+			EncryptedKeystore::encrypt_with("//Alice", "passphrase", TEST_KDF).unwrap();
+		let mut ciphertext = hex::decode(&keystore.ciphertext).unwrap();
+		ciphertext[0] ^= 1;
+		keystore.ciphertext = hex::encode(ciphertext);
+		assert!(keystore.decrypt("passphrase").is_err());
+	}
+}
@@ -16,15 +16,19 @@
 
 use futures::Stream;
 use ibc_proto::{
+	cosmos::base::query::v1beta1::PageResponse,
 	google::protobuf::Any,
-	ibc::core::{
-		channel::v1::{
-			QueryChannelResponse, QueryNextSequenceReceiveResponse,
-			QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
-			QueryPacketReceiptResponse,
+	ibc::{
+		applications::transfer::v1::{QueryDenomTraceResponse, QueryDenomTracesResponse},
+		core::{
+			channel::v1::{
+				QueryChannelResponse, QueryNextSequenceReceiveResponse,
+				QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
+				QueryPacketReceiptResponse,
+			},
+			client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
+			connection::v1::QueryConnectionResponse,
 		},
-		client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
-		connection::v1::QueryConnectionResponse,
 	},
 };
 use rand::Rng;
@@ -34,16 +38,25 @@ use std::{
 	fmt::Debug,
 	pin::Pin,
 	str::FromStr,
-	sync::{Arc, Mutex},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Mutex,
+	},
 	time::Duration,
 };
 use tokio::{sync::Mutex as AsyncMutex, task::JoinSet, time::sleep};
 
-use crate::error::Error;
+use crate::{
+	cost::{TxBudget, TxCost},
+	error::Error,
+	strategy::{ChannelStrategy, UndeliveredSequencesHook},
+};
 #[cfg(any(feature = "testing", test))]
 use ibc::applications::transfer::msgs::transfer::MsgTransfer;
 use ibc::{
-	applications::transfer::PrefixedCoin,
+	applications::{
+		cross_chain_queries::query::CrossChainQuery, fee::PacketFee, transfer::PrefixedCoin,
+	},
 	core::{
 		ics02_client::{
 			client_consensus::ConsensusState as ConsensusStateT,
@@ -56,7 +69,10 @@ use ibc::{
 			packet::Packet,
 		},
 		ics23_commitment::commitment::CommitmentPrefix,
-		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		ics24_host::{
+			identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
+			Path,
+		},
 	},
 	events::IbcEvent,
 	signer::Signer,
@@ -64,25 +80,34 @@ use ibc::{
 	Height,
 };
 use ibc_proto::ibc::core::{
-	channel::v1::QueryChannelsResponse, connection::v1::IdentifiedConnection,
+	channel::v1::{IdentifiedChannel, QueryChannelsResponse},
+	connection::v1::{IdentifiedConnection, Params as ConnectionParams, QueryConnectionsResponse},
 };
-use ibc_rpc::PacketInfo;
+use ibc_rpc::{PacketEventsFilter, PacketInfo};
 use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState};
 
+pub mod address;
+pub mod cost;
 pub mod error;
+pub mod keystore;
 pub mod mock;
+pub mod signing;
+pub mod strategy;
 pub mod utils;
+pub mod vault;
 
 pub enum UpdateMessage {
 	Single(Any),
 	Batch(Vec<Any>),
 }
 
+/// Whether a client update has to reach the counterparty even when it carries no packets.
 #[derive(Debug)]
 pub enum UpdateType {
-	// contains an authority set change.
+	/// Contains an authority set change, or is otherwise needed to keep the counterparty's
+	/// client live. Always relayed.
 	Mandatory,
-	// doesn't contain an authority set change
+	/// Only advances the client, and may be skipped when there are no messages to go with it.
 	Optional,
 }
 
@@ -112,6 +137,10 @@ pub struct CommonClientConfig {
 	pub skip_optional_client_updates: bool,
 	#[serde(default = "max_packets_to_process")]
 	pub max_packets_to_process: u32,
+	/// Check the packet events reported by the rpc node against the state of the chain before
+	/// relaying them
+	#[serde(default)]
+	pub verify_events: bool,
 }
 
 /// A common data that all clients should keep.
@@ -134,6 +163,17 @@ pub struct CommonClientState {
 	pub max_packets_to_process: usize,
 
 	pub skip_tokens_list: Vec<String>,
+	/// Strategies of the channels of this chain that aren't relayed with the defaults
+	pub channel_strategies: HashMap<(ChannelId, PortId), ChannelStrategy>,
+	/// Only relay the packet events that are backed by the state of the chain, instead of trusting
+	/// the event feed of the rpc node
+	pub verify_events: bool,
+	/// Packet events held back until they can be checked against the light client of this chain
+	/// on the counterparty, with the number of times they were checked
+	pub unproven_events: Arc<Mutex<Vec<(IbcEvent, u32)>>>,
+	/// Selects the undelivered sequences of the channels of this chain that are relayed, and
+	/// which first, all of them in the order they're found by default
+	pub undelivered_sequences_hook: Option<Arc<dyn UndeliveredSequencesHook>>,
 }
 
 impl Default for CommonClientState {
@@ -147,6 +187,10 @@ impl Default for CommonClientState {
 			misbehaviour_client_msg_queue: Arc::new(Default::default()),
 			max_packets_to_process: 100,
 			skip_tokens_list: vec!["uosmo".to_string()],
+			channel_strategies: Default::default(),
+			verify_events: false,
+			unproven_events: Default::default(),
+			undelivered_sequences_hook: None,
 		}
 	}
 }
@@ -178,6 +222,41 @@ impl CommonClientState {
 	pub fn set_rpc_call_delay(&mut self, delay: Duration) {
 		self.rpc_call_delay = delay;
 	}
+
+	pub fn channel_strategy(
+		&self,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+	) -> Option<&ChannelStrategy> {
+		self.channel_strategies.get(&(*channel_id, port_id.clone()))
+	}
+
+	/// The undelivered `sequences` of the channel to relay in this cycle, as selected by the
+	/// [`UndeliveredSequencesHook`] if one is set.
+	pub fn select_undelivered_sequences(
+		&self,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+		kind: UndeliveredType,
+		sequences: Vec<u64>,
+	) -> Vec<u64> {
+		match &self.undelivered_sequences_hook {
+			Some(hook) => hook.select(channel_id, port_id, kind, sequences),
+			None => sequences,
+		}
+	}
+}
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// In dry-run mode nothing is submitted to the chains: messages and misbehaviour reports are
+/// logged instead, and the messages persisted by a previous run are left alone.
+pub fn set_dry_run(dry_run: bool) {
+	DRY_RUN.store(dry_run, Ordering::SeqCst);
+}
+
+pub fn is_dry_run() -> bool {
+	DRY_RUN.load(Ordering::SeqCst)
 }
 
 pub fn apply_prefix(mut commitment_prefix: Vec<u8>, path: impl Into<Vec<u8>>) -> Vec<u8> {
@@ -186,6 +265,24 @@ pub fn apply_prefix(mut commitment_prefix: Vec<u8>, path: impl Into<Vec<u8>>) ->
 	commitment_prefix
 }
 
+/// Number of items requested per page when a list query is paged through until its end.
+pub const DEFAULT_PAGE_LIMIT: u64 = 100;
+
+/// A page of the results of a list query.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+	pub items: Vec<T>,
+	/// Key the next page starts at, if there are more results
+	pub next_key: Option<Vec<u8>>,
+}
+
+/// Key of the next page of a paginated query response, the last page may have an empty key.
+pub fn next_page_key(pagination: Option<PageResponse>) -> Option<Vec<u8>> {
+	pagination
+		.map(|pagination| pagination.next_key)
+		.filter(|next_key| !next_key.is_empty())
+}
+
 /// A type of undelivered sequences (packets). Can be:
 /// - acknowledgement packet (`Acks`),
 /// - receive packet (`Recvs`)
@@ -197,34 +294,16 @@ pub enum UndeliveredType {
 	Timeouts,
 }
 
-/// Provides an interface for accessing new events and Ibc data on the chain which must be
-/// relayed to the counterparty chain.
+/// Provides an interface for querying the IBC state of the chain: clients, connections, channels
+/// and packets, along with the identifiers the relayer was configured with. This is all a
+/// read-only client, e.g. for monitoring, needs to implement.
 #[async_trait::async_trait]
-pub trait IbcProvider {
-	/// Finality event type, passed on to [`Chain::query_latest_ibc_events`]
-	type FinalityEvent: Debug + Send + 'static;
-	/// A representation of the transaction id for the chain
-	type TransactionId: Debug;
+pub trait IbcQueryProvider {
 	/// Asset Id
 	type AssetId: Clone;
-
 	/// Error type, just needs to implement standard error trait.
 	type Error: std::error::Error + From<String> + Send + Sync + 'static;
 
-	/// Query the latest ibc events finalized by the recent finality event. Use the counterparty
-	/// [`Chain`] to query the on-chain [`ClientState`] so you can scan for new events in between
-	/// the client state and the new finality event.
-	async fn query_latest_ibc_events<T>(
-		&mut self,
-		finality_event: Self::FinalityEvent,
-		counterparty: &T,
-	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
-	where
-		T: Chain;
-
-	/// Return a stream that yields when new [`IbcEvents`] are parsed from a finality notification
-	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>>;
-
 	/// Query client consensus state with proof
 	/// return the consensus height for the client along with the response
 	async fn query_client_consensus(
@@ -256,8 +335,8 @@ pub trait IbcProvider {
 		port_id: PortId,
 	) -> Result<QueryChannelResponse, Self::Error>;
 
-	/// Query proof for provided key path
-	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error>;
+	/// Query the cross-chain queries on this chain that are waiting for a relayer to answer them
+	async fn query_cross_chain_queries(&self) -> Result<Vec<CrossChainQuery>, Self::Error>;
 
 	/// Query packet commitment with proof
 	async fn query_packet_commitment(
@@ -342,56 +421,74 @@ pub trait IbcProvider {
 	/// Channel whitelist
 	fn channel_whitelist(&self) -> HashSet<(ChannelId, PortId)>;
 
-	/// Query all channels for a connection
+	/// Query at most `limit` channels of a connection, starting at the `next_key` of the
+	/// pagination of a previous response
 	async fn query_connection_channels(
 		&self,
 		at: Height,
 		connection_id: &ConnectionId,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
 	) -> Result<QueryChannelsResponse, Self::Error>;
 
-	/// Query send packets
-	/// This represents packets that for which the `SendPacket` event was emitted
-	async fn query_send_packets(
-		&self,
-		channel_id: ChannelId,
-		port_id: PortId,
-		seqs: Vec<u64>,
-	) -> Result<Vec<PacketInfo>, Self::Error>;
-
-	/// Query received packets with their acknowledgement
-	/// This represents packets for which the `ReceivePacket` and `WriteAcknowledgement` events were
-	/// emitted.
-	async fn query_received_packets(
+	/// Query all the channels of a connection, page by page
+	async fn query_all_connection_channels(
 		&self,
-		channel_id: ChannelId,
-		port_id: PortId,
-		seqs: Vec<u64>,
-	) -> Result<Vec<PacketInfo>, Self::Error>;
+		at: Height,
+		connection_id: &ConnectionId,
+	) -> Result<Vec<IdentifiedChannel>, Self::Error> {
+		let mut channels = vec![];
+		let mut next_key = None;
+		loop {
+			let response = self
+				.query_connection_channels(at, connection_id, next_key, DEFAULT_PAGE_LIMIT)
+				.await?;
+			channels.extend(response.channels);
+			next_key = next_page_key(response.pagination);
+			if next_key.is_none() {
+				return Ok(channels)
+			}
+		}
+	}
 
 	/// Return the expected block time for this chain
 	fn expected_block_time(&self) -> Duration;
 
-	/// Query the time and height at which this client was updated on this chain for the given
-	/// client height
-	async fn query_client_update_time_and_height(
-		&self,
-		client_id: ClientId,
-		client_height: Height,
-	) -> Result<(Height, Timestamp), Self::Error>;
-
-	/// Return a proof for the host consensus state at the given height to be included in the
-	/// consensus state proof.
-	async fn query_host_consensus_state_proof(
-		&self,
-		client_state: &AnyClientState,
-	) -> Result<Option<Vec<u8>>, Self::Error>;
-
 	/// Should return the list of ibc denoms available to this account to spend.
 	async fn query_ibc_balance(
 		&self,
 		asset_id: Self::AssetId,
 	) -> Result<Vec<PrefixedCoin>, Self::Error>;
 
+	/// Should return the trace of the ibc denom referred to by `ibc/{hash}` on this chain
+	async fn query_denom_trace(
+		&self,
+		denom_hash: String,
+	) -> Result<QueryDenomTraceResponse, Self::Error>;
+
+	/// Should return at most `limit` denom traces of the ibc denoms on this chain, starting at the
+	/// `next_key` of the pagination of a previous response
+	async fn query_denom_traces(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<QueryDenomTracesResponse, Self::Error>;
+
+	/// Should return the `ibc/{hash}` denom the denom trace
+	/// `{port_id}/{channel_id}/.../{base_denom}` is referred to by on this chain
+	async fn query_denom_hash(&self, trace: String) -> Result<String, Self::Error>;
+
+	/// Should return the ICS-29 fees escrowed on this chain for the packet `sequence` sent on
+	/// `channel_id`/`port_id`
+	async fn query_packet_fees(
+		&self,
+		_channel_id: ChannelId,
+		_port_id: PortId,
+		_sequence: u64,
+	) -> Result<Vec<PacketFee>, Self::Error> {
+		Err(Self::Error::from("The ICS-29 packet fees can't be queried on this chain".to_string()))
+	}
+
 	/// Return the chain connection prefix
 	fn connection_prefix(&self) -> CommitmentPrefix;
 
@@ -419,11 +516,49 @@ pub trait IbcProvider {
 	/// Should return timestamp in nanoseconds of chain at a given block height
 	async fn query_timestamp_at(&self, block_number: u64) -> Result<u64, Self::Error>;
 
-	/// Should return a list of all clients on the chain
-	async fn query_clients(&self) -> Result<Vec<ClientId>, Self::Error>;
+	/// Should return at most `limit` clients on the chain, starting at the `next_key` of a
+	/// previous page
+	async fn query_clients(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<Page<ClientId>, Self::Error>;
+
+	/// Should return at most `limit` channels on the chain, starting at the `next_key` of a
+	/// previous page
+	async fn query_channels(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<Page<(ChannelId, PortId)>, Self::Error>;
+
+	/// Query all the clients on the chain, page by page
+	async fn query_all_clients(&self) -> Result<Vec<ClientId>, Self::Error> {
+		let mut clients = vec![];
+		let mut next_key = None;
+		loop {
+			let page = self.query_clients(next_key, DEFAULT_PAGE_LIMIT).await?;
+			clients.extend(page.items);
+			next_key = page.next_key;
+			if next_key.is_none() {
+				return Ok(clients)
+			}
+		}
+	}
 
-	/// Should return a list of all clients on the chain
-	async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error>;
+	/// Query all the channels on the chain, page by page
+	async fn query_all_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
+		let mut channels = vec![];
+		let mut next_key = None;
+		loop {
+			let page = self.query_channels(next_key, DEFAULT_PAGE_LIMIT).await?;
+			channels.extend(page.items);
+			next_key = page.next_key;
+			if next_key.is_none() {
+				return Ok(channels)
+			}
+		}
+	}
 
 	/// Query all connection states for associated client
 	async fn query_connection_using_client(
@@ -432,6 +567,108 @@ pub trait IbcProvider {
 		client_id: String,
 	) -> Result<Vec<IdentifiedConnection>, Self::Error>;
 
+	/// Query at most `limit` connections on the chain, starting at the `next_key` of the
+	/// pagination of a previous response
+	async fn query_connections(
+		&self,
+		next_key: Option<Vec<u8>>,
+		limit: u64,
+	) -> Result<QueryConnectionsResponse, Self::Error>;
+
+	/// Query all the connections on the chain, page by page
+	async fn query_all_connections(&self) -> Result<Vec<IdentifiedConnection>, Self::Error> {
+		let mut connections = vec![];
+		let mut next_key = None;
+		loop {
+			let response = self.query_connections(next_key, DEFAULT_PAGE_LIMIT).await?;
+			connections.extend(response.connections);
+			next_key = next_page_key(response.pagination);
+			if next_key.is_none() {
+				return Ok(connections)
+			}
+		}
+	}
+
+	/// Query the parameters of the connection submodule, i.e. the maximum expected time per block
+	/// used to enforce the block delay of connections
+	async fn query_connection_params(&self) -> Result<ConnectionParams, Self::Error>;
+}
+
+/// Provides an interface for accessing new events on the chain which must be relayed to the
+/// counterparty chain.
+#[async_trait::async_trait]
+pub trait IbcEventProvider: IbcQueryProvider {
+	/// Finality event type, passed on to [`Chain::query_latest_ibc_events`]
+	type FinalityEvent: Debug + Send + 'static;
+	/// A representation of the transaction id for the chain
+	type TransactionId: Debug;
+
+	/// Query the latest ibc events finalized by the recent finality event. Use the counterparty
+	/// [`Chain`] to query the on-chain [`ClientState`] so you can scan for new events in between
+	/// the client state and the new finality event.
+	async fn query_latest_ibc_events<T>(
+		&mut self,
+		finality_event: Self::FinalityEvent,
+		counterparty: &T,
+	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+	where
+		T: Chain;
+
+	/// Return a stream that yields when new [`IbcEvents`] are parsed from a finality notification
+	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>>;
+
+	/// Starts building the client update that proves the block at `height` to the light client on
+	/// the counterparty, ahead of the finality event that will need it. Called as soon as packets
+	/// are sent at `height`; chains whose updates are slow to build can keep the update and return
+	/// it from [`IbcEventProvider::query_latest_ibc_events`]. Does nothing by default.
+	async fn prefetch_client_update<T>(
+		&self,
+		_height: Height,
+		_counterparty: &T,
+	) -> Result<(), anyhow::Error>
+	where
+		T: Chain,
+	{
+		Ok(())
+	}
+
+	/// Query send packets
+	/// This represents packets that for which the `SendPacket` event was emitted
+	async fn query_send_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error>;
+
+	/// Query received packets with their acknowledgement
+	/// This represents packets for which the `ReceivePacket` and `WriteAcknowledgement` events were
+	/// emitted.
+	async fn query_received_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error>;
+
+	/// Query the packet events emitted in the blocks `from_height..=to_height` that match
+	/// `filter`, so history can be reconstructed without scanning every block individually. The
+	/// range may be capped by the chain, callers should query long ranges in chunks.
+	async fn query_packet_events(
+		&self,
+		from_height: Height,
+		to_height: Height,
+		filter: PacketEventsFilter,
+	) -> Result<Vec<IbcEvent>, Self::Error>;
+
+	/// Query the time and height at which this client was updated on this chain for the given
+	/// client height
+	async fn query_client_update_time_and_height(
+		&self,
+		client_id: ClientId,
+		client_height: Height,
+	) -> Result<(Height, Timestamp), Self::Error>;
+
 	/// Returns a boolean value that determines if the light client should receive a mandatory
 	/// update
 	async fn is_update_required(
@@ -440,11 +677,6 @@ pub trait IbcProvider {
 		latest_client_height_on_counterparty: u64,
 	) -> Result<bool, Self::Error>;
 
-	/// This should return a subjectively chosen client and consensus state for this chain.
-	async fn initialize_client_state(
-		&self,
-	) -> Result<(AnyClientState, AnyConsensusState), Self::Error>;
-
 	/// Should find client id that was created in this transaction
 	async fn query_client_id_from_tx_hash(
 		&self,
@@ -462,10 +694,73 @@ pub trait IbcProvider {
 		&self,
 		tx_id: Self::TransactionId,
 	) -> Result<(ChannelId, PortId), Self::Error>;
+}
+
+/// Provides the proofs of the IBC state of the chain that messages to the counterparty are built
+/// with, and the state its light client is created from.
+#[async_trait::async_trait]
+pub trait ProofProvider: IbcQueryProvider {
+	/// Query proof for provided key path
+	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error>;
+
+	/// Query a proof for every set of key paths, in a single round trip if the chain supports it.
+	/// By default the proofs are queried one by one.
+	async fn query_proofs(
+		&self,
+		at: Height,
+		keys: Vec<Vec<Vec<u8>>>,
+	) -> Result<Vec<Vec<u8>>, Self::Error> {
+		let mut proofs = Vec::with_capacity(keys.len());
+		for keys in keys {
+			proofs.push(self.query_proof(at, keys).await?);
+		}
+		Ok(proofs)
+	}
+
+	/// Query the value stored at the given path with a proof of its membership, or of its absence
+	/// if there's no value
+	async fn query_value_with_proof(
+		&self,
+		at: Height,
+		path: Path,
+	) -> Result<(Option<Vec<u8>>, Vec<u8>), Self::Error>;
 
-	async fn upload_wasm(&self, wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error>;
+	/// Query a proof that nothing is stored at the given path, e.g. the packet receipt absence
+	/// proof needed to time out a packet on an unordered channel. Fails if the path has a value.
+	async fn query_non_membership_proof(
+		&self,
+		at: Height,
+		path: Path,
+	) -> Result<Vec<u8>, Self::Error> {
+		let (value, proof) = self.query_value_with_proof(at, path.clone()).await?;
+		if value.is_some() {
+			return Err(Self::Error::from(format!(
+				"Cannot prove absence of {path} at {at}: a value is stored there"
+			)))
+		}
+		Ok(proof)
+	}
+
+	/// Return a proof for the host consensus state at the given height to be included in the
+	/// consensus state proof.
+	async fn query_host_consensus_state_proof(
+		&self,
+		client_state: &AnyClientState,
+	) -> Result<Option<Vec<u8>>, Self::Error>;
+
+	/// This should return a subjectively chosen client and consensus state for this chain.
+	async fn initialize_client_state(
+		&self,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error>;
 }
 
+/// Provides an interface for accessing new events and Ibc data on the chain which must be
+/// relayed to the counterparty chain. Implemented for every chain that implements
+/// [`IbcQueryProvider`], [`IbcEventProvider`] and [`ProofProvider`].
+pub trait IbcProvider: IbcQueryProvider + IbcEventProvider + ProofProvider {}
+
+impl<T: IbcQueryProvider + IbcEventProvider + ProofProvider> IbcProvider for T {}
+
 /// Provides an interface that allows us run the hyperspace-testsuite
 /// with [`Chain`] implementations.
 #[cfg(any(feature = "testing", test))]
@@ -481,6 +776,28 @@ pub trait TestProvider: Chain + Clone + 'static {
 		timeout: pallet_ibc::Timeout,
 	) -> Result<(), Self::Error>;
 
+	/// Registers an interchain account of the relayer on the host chain at the other end of
+	/// `connection_id`, which starts the handshake of its ICA channel.
+	async fn open_ica_channel(&self, connection_id: ConnectionId) -> Result<(), Self::Error>;
+
+	/// Send a packet carrying arbitrary application data on any channel, e.g. one bound to a
+	/// custom port.
+	async fn send_raw_packet(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+		data: Vec<u8>,
+		timeout: pallet_ibc::Timeout,
+	) -> Result<(), Self::Error>;
+
+	/// Send a packet that has already timed out on the counterparty, so that the relayer has to
+	/// time it out instead of delivering it.
+	async fn force_timeout(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+	) -> Result<(), Self::Error>;
+
 	/// Returns a stream that yields chain Block number
 	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>>;
 
@@ -488,7 +805,8 @@ pub trait TestProvider: Chain + Clone + 'static {
 	async fn increase_counters(&mut self) -> Result<(), Self::Error>;
 }
 
-/// Provides an interface for managing key management for signing.
+/// Provides an interface for managing key management for signing. Backends that sign their
+/// transactions themselves can use a [`signing::ChainSigner`].
 pub trait KeyProvider {
 	/// Should return the relayer's account id on the host chain as a string in the expected format
 	/// Could be a hexadecimal, bech32 or ss58 string, any format the chain supports
@@ -528,11 +846,23 @@ pub trait Chain:
 	/// Name of this chain, used in logs.
 	fn name(&self) -> &str;
 
-	/// Should return a numerical value for the max weight of transactions allowed in a block.
-	fn block_max_weight(&self) -> u64;
+	/// Canonical identifier of this chain, which unlike [`Chain::name`] doesn't change when the
+	/// chain is renamed in the config. Metrics are labelled and state is persisted with it, and
+	/// its version is the revision number of the heights of the chain.
+	fn chain_id(&self) -> ChainId;
 
-	/// Should return an estimate of the weight of a batch of messages.
-	async fn estimate_weight(&self, msg: Vec<Any>) -> Result<u64, Self::Error>;
+	/// Maximum cost of a single transaction, in the native unit of the chain, e.g. weight or gas.
+	fn tx_budget(&self) -> TxBudget;
+
+	/// Maximum encoded size in bytes of the messages submitted in a single transaction, for chains
+	/// that reject large transactions regardless of their weight.
+	fn max_transaction_size(&self) -> Option<usize> {
+		None
+	}
+
+	/// Should return an estimate of the cost of submitting a batch of messages in a single
+	/// transaction, in the unit of [`Chain::tx_budget`].
+	async fn estimate_cost(&self, msg: Vec<Any>) -> Result<TxCost, Self::Error>;
 
 	/// Return a stream that yields when new [`IbcEvents`] are ready to be queried.
 	async fn finality_notifications(
@@ -544,6 +874,33 @@ pub trait Chain:
 	/// Should return the transaction id
 	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Self::Error>;
 
+	/// Submits multiple sets of messages, in order. By default every set is submitted in its own
+	/// transaction, chains that can bundle calls should use as few transactions as possible.
+	async fn submit_batch(
+		&self,
+		batches: Vec<Vec<Any>>,
+	) -> Result<Vec<Self::TransactionId>, Self::Error> {
+		let mut ids = Vec::with_capacity(batches.len());
+		for messages in batches {
+			ids.push(self.submit(messages).await?);
+		}
+		Ok(ids)
+	}
+
+	/// Submits the messages in a single transaction and returns the IBC events it emitted once it
+	/// is included in a block, e.g. to learn the identifiers of the clients, connections and
+	/// channels it created.
+	async fn submit_and_wait_for_events(
+		&self,
+		messages: Vec<Any>,
+	) -> Result<Vec<IbcEvent>, Self::Error>;
+
+	/// Stores the wasm code of a light client on this chain and returns its hash, for chains that
+	/// support wasm light clients.
+	async fn upload_wasm(&self, _wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
+		Err(Self::Error::from(format!("Uploading wasm code to {} is not supported", self.name())))
+	}
+
 	/// Returns an [`AnyClientMessage`] for an [`UpdateClient`] event
 	async fn query_client_message(
 		&self,
@@ -579,6 +936,28 @@ pub trait Chain:
 	}
 
 	async fn reconnect(&mut self) -> anyhow::Result<()>;
+
+	/// The RPC endpoint the client is currently connected to, if the client supports more than one.
+	fn active_rpc_endpoint(&self) -> Option<String> {
+		None
+	}
+
+	/// Whether the node the client is connected to is still syncing, `None` if it can't be told.
+	async fn is_node_syncing(&self) -> Result<Option<bool>, Self::Error> {
+		Ok(None)
+	}
+
+	/// Version of the IBC module hosted by the chain, `None` if it can't be told. Fails if the
+	/// chain doesn't host an IBC module at all.
+	async fn ibc_module_version(&self) -> Result<Option<String>, Self::Error> {
+		Ok(None)
+	}
+
+	/// Replaces the key that messages are signed with, without restarting the client. Returns the
+	/// address of the new relayer account.
+	async fn rotate_signing_key(&self, _private_key: String) -> Result<Signer, Self::Error> {
+		Err(Self::Error::from(format!("{} doesn't support signing key rotation", self.name())))
+	}
 }
 
 /// Returns undelivered packet sequences that have been sent out from
@@ -607,6 +986,45 @@ pub async fn query_undelivered_sequences(
 		.into_iter()
 		.collect::<Vec<_>>();
 	log::trace!(target: "hyperspace", "Seqs: {:?}", seqs);
+	undelivered_on_sink(sink_height, &channel_end, seqs, sink).await
+}
+
+/// Same as [`query_undelivered_sequences`], for the given packet sequences only instead of all the
+/// packets committed on the channel.
+pub async fn filter_undelivered_sequences(
+	source_height: Height,
+	sink_height: Height,
+	channel_id: ChannelId,
+	port_id: PortId,
+	seqs: Vec<u64>,
+	source: &impl Chain,
+	sink: &impl Chain,
+) -> Result<Vec<u64>, anyhow::Error> {
+	if seqs.is_empty() {
+		return Ok(vec![])
+	}
+	let channel_response =
+		source.query_channel_end(source_height, channel_id, port_id.clone()).await?;
+	let channel_end = ChannelEnd::try_from(
+		channel_response
+			.channel
+			.ok_or_else(|| Error::Custom("ChannelEnd not could not be decoded".to_string()))?,
+	)
+	.map_err(|e| Error::Custom(e.to_string()))?;
+	// the commitments of the packets that were acknowledged or timed out are gone
+	let seqs = source
+		.query_unreceived_acknowledgements(source_height, channel_id, port_id, seqs)
+		.await?;
+	undelivered_on_sink(sink_height, &channel_end, seqs, sink).await
+}
+
+/// Filters the sequences of the packets sent on `channel_end` that `sink` hasn't received yet.
+async fn undelivered_on_sink(
+	sink_height: Height,
+	channel_end: &ChannelEnd,
+	seqs: Vec<u64>,
+	sink: &impl Chain,
+) -> Result<Vec<u64>, anyhow::Error> {
 	let counterparty_channel_id = channel_end
 		.counterparty()
 		.channel_id
@@ -659,6 +1077,41 @@ pub async fn query_undelivered_acks(
 		"Found {} packet acks from {} chain",
 		seqs.len(), source.name()
 	);
+	unreceived_acks_on_sink(sink_height, &channel_end, seqs, sink).await
+}
+
+/// Same as [`query_undelivered_acks`], for the acknowledgements of the given packet sequences only
+/// instead of all the acknowledgements written on the channel.
+pub async fn filter_undelivered_acks(
+	source_height: Height,
+	sink_height: Height,
+	channel_id: ChannelId,
+	port_id: PortId,
+	seqs: Vec<u64>,
+	source: &impl Chain,
+	sink: &impl Chain,
+) -> Result<Vec<u64>, anyhow::Error> {
+	if seqs.is_empty() {
+		return Ok(vec![])
+	}
+	let channel_response = source.query_channel_end(source_height, channel_id, port_id).await?;
+	let channel_end = ChannelEnd::try_from(
+		channel_response
+			.channel
+			.ok_or_else(|| Error::Custom("ChannelEnd not could not be decoded".to_string()))?,
+	)
+	.map_err(|e| Error::Custom(e.to_string()))?;
+	unreceived_acks_on_sink(sink_height, &channel_end, seqs, sink).await
+}
+
+/// Filters the sequences of the packets received on `channel_end` whose acknowledgement `sink`
+/// hasn't received yet.
+async fn unreceived_acks_on_sink(
+	sink_height: Height,
+	channel_end: &ChannelEnd,
+	seqs: Vec<u64>,
+	sink: &impl Chain,
+) -> Result<Vec<u64>, anyhow::Error> {
 	let counterparty_channel_id = channel_end
 		.counterparty()
 		.channel_id
@@ -788,24 +1241,23 @@ pub async fn find_suitable_proof_height_for_client(
 				end = mid;
 			}
 		}
-		let start_height = Height::new(start_height.revision_number, start);
-
-		let consensus_state =
-			sink.query_client_consensus(at, client_id.clone(), start_height).await.ok();
-		if let Some(Ok(consensus_state)) = consensus_state
-			.map(|x| x.consensus_state.map(AnyConsensusState::try_from))
-			.flatten()
-		{
-			if consensus_state.timestamp().nanoseconds() >= timestamp_to_match.nanoseconds() {
-				let proof_height = source.get_proof_height(start_height).await;
-				let has_client_state = sink
-					.query_client_update_time_and_height(client_id.clone(), proof_height)
-					.await
-					.ok()
-					.is_some();
-				if has_client_state {
-					return Some(start_height)
-				}
+		// the search narrows down to two heights, `end` is only checked if it was a midpoint
+		for height in [start, end] {
+			let height = Height::new(start_height.revision_number, height);
+			if is_proof_height_past_timestamp(
+				source,
+				sink,
+				at,
+				&client_id,
+				height,
+				timestamp_to_match,
+			)
+			.await
+			{
+				return Some(height)
+			}
+			if last_known_valid_height.is_some() {
+				break
 			}
 		}
 
@@ -814,6 +1266,31 @@ pub async fn find_suitable_proof_height_for_client(
 	None
 }
 
+/// Whether `sink` has a consensus state of `source` at `height` that is past `timestamp`, and that
+/// proofs of `source` can be verified with.
+async fn is_proof_height_past_timestamp(
+	source: &impl Chain,
+	sink: &impl Chain,
+	at: Height,
+	client_id: &ClientId,
+	height: Height,
+	timestamp: Timestamp,
+) -> bool {
+	let consensus_state = sink.query_client_consensus(at, client_id.clone(), height).await.ok();
+	let Some(Ok(consensus_state)) =
+		consensus_state.and_then(|x| x.consensus_state.map(AnyConsensusState::try_from))
+	else {
+		return false
+	};
+	if consensus_state.timestamp().nanoseconds() < timestamp.nanoseconds() {
+		return false
+	}
+	let proof_height = source.get_proof_height(height).await;
+	sink.query_client_update_time_and_height(client_id.clone(), proof_height)
+		.await
+		.is_ok()
+}
+
 pub async fn query_maximum_height_for_timeout_proofs(
 	source: &impl Chain,
 	sink: &impl Chain,
@@ -956,6 +1433,12 @@ pub fn filter_events_by_ids(
 			filter_channel_attributes(&ChannelAttributes::from(e.clone())),
 		IbcEvent::CloseConfirmChannel(e) =>
 			filter_channel_attributes(&ChannelAttributes::from(e.clone())),
+		IbcEvent::UpgradeChannel(e) =>
+			channel_ids.contains(&&e.channel_id) ||
+				e.counterparty_channel_id
+					.as_ref()
+					.map(|id| channel_ids.contains(&id))
+					.unwrap_or(false),
 		IbcEvent::PushWasmCode(_) => true,
 		IbcEvent::NewBlock(_) |
 		IbcEvent::AppModule(_) |
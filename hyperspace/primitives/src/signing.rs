@@ -0,0 +1,195 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Chain agnostic signing.
+//!
+//! [`ChainSigner`] signs payloads with sr25519, ed25519 or secp256k1 keys without assuming how a
+//! chain encodes its transactions. Backends pick the [`PayloadFormat`] their chain verifies
+//! signatures against and encode the returned public key and signature bytes themselves.
+
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use subxt::ext::sp_core::{
+	ecdsa, ed25519,
+	hashing::{blake2_256, keccak_256, sha2_256},
+	sr25519, Pair,
+};
+
+/// Signature scheme of a signing key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureScheme {
+	Sr25519,
+	Ed25519,
+	Secp256k1,
+}
+
+/// Public key of a signing key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+	pub scheme: SignatureScheme,
+	/// 32 bytes for sr25519 and ed25519, 33 bytes (compressed) for secp256k1
+	pub bytes: Vec<u8>,
+}
+
+/// How the payload is turned into the message that gets signed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadFormat {
+	/// The payload is signed as is by sr25519 and ed25519 keys. secp256k1 keys sign its SHA-256
+	/// digest, like the Cosmos SDK expects.
+	Raw,
+	/// The BLAKE2b-256 digest of the payload is signed, like Substrate expects from ecdsa keys
+	Blake2_256,
+	/// The Keccak-256 digest of the payload is signed, like EVM chains expect
+	Keccak256,
+}
+
+impl PayloadFormat {
+	/// The message a key of `scheme` signs for the payload
+	pub fn message(&self, scheme: SignatureScheme, payload: &[u8]) -> Vec<u8> {
+		match (self, scheme) {
+			(PayloadFormat::Raw, SignatureScheme::Secp256k1) => sha2_256(payload).to_vec(),
+			(PayloadFormat::Raw, _) => payload.to_vec(),
+			(PayloadFormat::Blake2_256, _) => blake2_256(payload).to_vec(),
+			(PayloadFormat::Keccak256, _) => keccak_256(payload).to_vec(),
+		}
+	}
+}
+
+/// Signs payloads for any chain backend
+pub trait ChainSigner: Send + Sync {
+	fn public_key(&self) -> PublicKey;
+
+	/// Signs the payload in the given format. sr25519 and ed25519 signatures are 64 bytes,
+	/// secp256k1 signatures are followed by the recovery id, which chains that don't use it drop.
+	fn sign(&self, payload: &[u8], format: PayloadFormat) -> Result<Vec<u8>, Error>;
+}
+
+/// [`ChainSigner`] holding its key in memory
+#[derive(Clone)]
+pub enum LocalSigner {
+	Sr25519(sr25519::Pair),
+	Ed25519(ed25519::Pair),
+	Secp256k1(ecdsa::Pair),
+}
+
+impl LocalSigner {
+	/// Creates a signer from a secret URI, e.g. a mnemonic, a hex seed or `//Alice`
+	pub fn from_string(scheme: SignatureScheme, secret: &str) -> Result<Self, Error> {
+		let invalid = |_| Error::Custom(format!("Invalid {scheme:?} secret"));
+		let signer = match scheme {
+			SignatureScheme::Sr25519 =>
+				Self::Sr25519(sr25519::Pair::from_string(secret, None).map_err(invalid)?),
+			SignatureScheme::Ed25519 =>
+				Self::Ed25519(ed25519::Pair::from_string(secret, None).map_err(invalid)?),
+			SignatureScheme::Secp256k1 =>
+				Self::Secp256k1(ecdsa::Pair::from_string(secret, None).map_err(invalid)?),
+		};
+		Ok(signer)
+	}
+
+	/// Creates a signer from raw secret key bytes, e.g. a key derived with BIP-32
+	pub fn from_secret_key(scheme: SignatureScheme, secret_key: &[u8]) -> Result<Self, Error> {
+		let invalid = |_| Error::Custom(format!("Invalid {scheme:?} secret key"));
+		let signer = match scheme {
+			SignatureScheme::Sr25519 =>
+				Self::Sr25519(sr25519::Pair::from_seed_slice(secret_key).map_err(invalid)?),
+			SignatureScheme::Ed25519 =>
+				Self::Ed25519(ed25519::Pair::from_seed_slice(secret_key).map_err(invalid)?),
+			SignatureScheme::Secp256k1 =>
+				Self::Secp256k1(ecdsa::Pair::from_seed_slice(secret_key).map_err(invalid)?),
+		};
+		Ok(signer)
+	}
+
+	fn scheme(&self) -> SignatureScheme {
+		match self {
+			LocalSigner::Sr25519(_) => SignatureScheme::Sr25519,
+			LocalSigner::Ed25519(_) => SignatureScheme::Ed25519,
+			LocalSigner::Secp256k1(_) => SignatureScheme::Secp256k1,
+		}
+	}
+}
+
+impl ChainSigner for LocalSigner {
+	fn public_key(&self) -> PublicKey {
+		let bytes = match self {
+			LocalSigner::Sr25519(pair) => pair.public().0.to_vec(),
+			LocalSigner::Ed25519(pair) => pair.public().0.to_vec(),
+			LocalSigner::Secp256k1(pair) => pair.public().0.to_vec(),
+		};
+		PublicKey { scheme: self.scheme(), bytes }
+	}
+
+	fn sign(&self, payload: &[u8], format: PayloadFormat) -> Result<Vec<u8>, Error> {
+		let message = format.message(self.scheme(), payload);
+		let signature = match self {
+			LocalSigner::Sr25519(pair) => pair.sign(&message).0.to_vec(),
+			LocalSigner::Ed25519(pair) => pair.sign(&message).0.to_vec(),
+			LocalSigner::Secp256k1(pair) => {
+				let digest: [u8; 32] = message.as_slice().try_into().map_err(|_| {
+					Error::Custom("secp256k1 keys can only sign 32 byte digests".to_string())
+				})?;
+				pair.sign_prehashed(&digest).0.to_vec()
+			},
+		};
+		Ok(signature)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn signatures_verify_for_each_scheme() {
+		let payload = b"hyperspace";
+		for scheme in
+			[SignatureScheme::Sr25519, SignatureScheme::Ed25519, SignatureScheme::Secp256k1]
+		{
+			let signer = LocalSigner::from_string(scheme, "//Alice").unwrap();
+			let signature = signer.sign(payload, PayloadFormat::Raw).unwrap();
+			let verified = match &signer {
+				LocalSigner::Sr25519(pair) => sr25519::Pair::verify(
+					&sr25519::Signature::from_slice(&signature).unwrap(),
+					payload,
+					&pair.public(),
+				),
+				LocalSigner::Ed25519(pair) => ed25519::Pair::verify(
+					&ed25519::Signature::from_slice(&signature).unwrap(),
+					payload,
+					&pair.public(),
+				),
+				LocalSigner::Secp256k1(pair) => ecdsa::Pair::verify_prehashed(
+					&ecdsa::Signature::from_slice(&signature).unwrap(),
+					&sha2_256(payload),
+					&pair.public(),
+				),
+			};
+			assert!(verified, "{scheme:?} signature doesn't verify");
+		}
+	}
+
+	#[test]
+	fn blake2_format_matches_substrate_ecdsa() {
+		let signer = LocalSigner::from_string(SignatureScheme::Secp256k1, "//Alice").unwrap();
+		let LocalSigner::Secp256k1(pair) = &signer else { unreachable!() };
+		let signature = signer.sign(b"hyperspace", PayloadFormat::Blake2_256).unwrap();
+		assert!(ecdsa::Pair::verify(
+			&ecdsa::Signature::from_slice(&signature).unwrap(),
+			b"hyperspace",
+			&pair.public()
+		));
+	}
+}
@@ -0,0 +1,214 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, UndeliveredType};
+use ibc::{
+	applications::{
+		fee::PacketFee,
+		transfer::{Amount, PrefixedCoin},
+	},
+	core::ics24_host::identifier::{ChannelId, PortId},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Debug, str::FromStr};
+
+fn default_true() -> bool {
+	true
+}
+
+/// How the packets of a channel are relayed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelStrategy {
+	/// Relay the acknowledgements of the packets received on the channel
+	#[serde(default = "default_true")]
+	pub relay_acks: bool,
+	/// Relay the timeouts of the packets sent on the channel
+	#[serde(default = "default_true")]
+	pub relay_timeouts: bool,
+	/// Maximum number of packets (and of acknowledgements) relayed on the channel at once,
+	/// defaults to the `max_packets_to_process` of the chain
+	#[serde(default)]
+	pub max_batch_size: Option<usize>,
+	/// Minimum ICS-29 fee a packet must escrow for the relayer of each step to be relayed
+	#[serde(default)]
+	pub min_fee: Option<MinFee>,
+	/// Number of sink blocks an ordered channel may wait for the same sequence before an alert is
+	/// raised, 100 by default
+	#[serde(default)]
+	pub sequence_gap_alert_blocks: Option<u64>,
+	/// Number of seconds a packet may stay undelivered before an alert is raised, an hour by
+	/// default
+	#[serde(default)]
+	pub stuck_packet_alert_secs: Option<u64>,
+	/// Number of source blocks a packet may stay undelivered before an alert is raised
+	#[serde(default)]
+	pub stuck_packet_alert_blocks: Option<u64>,
+}
+
+impl Default for ChannelStrategy {
+	fn default() -> Self {
+		Self {
+			relay_acks: true,
+			relay_timeouts: true,
+			max_batch_size: None,
+			min_fee: None,
+			sequence_gap_alert_blocks: None,
+			stuck_packet_alert_secs: None,
+			stuck_packet_alert_blocks: None,
+		}
+	}
+}
+
+/// Minimum fee in a single denom, e.g. `{ denom = "uatom", amount = "1000" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinFee {
+	pub denom: String,
+	/// Decimal amount, a string since fees may not fit into a TOML integer
+	pub amount: String,
+}
+
+impl MinFee {
+	pub fn amount(&self) -> Result<Amount, Error> {
+		Amount::from_str(&self.amount)
+			.map_err(|e| Error::Custom(format!("Invalid min fee amount {}: {e}", self.amount)))
+	}
+
+	/// Whether the `fee` selected from the fees escrowed for a packet pays at least this fee.
+	pub fn is_paid_by(
+		&self,
+		packet_fees: &[PacketFee],
+		fee: impl Fn(&PacketFee) -> &[PrefixedCoin],
+	) -> Result<bool, Error> {
+		let paid = packet_fees
+			.iter()
+			.flat_map(|packet_fee| fee(packet_fee))
+			.filter(|coin| coin.denom.to_string() == self.denom)
+			.try_fold(Amount::from(0u64), |total, coin| total.checked_add(coin.amount))
+			.ok_or_else(|| Error::Custom("Packet fee overflow".to_string()))?;
+		Ok(paid >= self.amount()?)
+	}
+}
+
+/// Decides which of the undelivered sequences of a channel are relayed, and which first, e.g. to
+/// relay the packets of the embedder before the others. Implemented for closures with the same
+/// signature as [`UndeliveredSequencesHook::select`].
+pub trait UndeliveredSequencesHook: Send + Sync {
+	/// Called every cycle with the undelivered `sequences` of `kind` on the channel of the source
+	/// chain: packets sent on it are offered as [`UndeliveredType::Recvs`], even those that will
+	/// be timed out, and packets received on it as [`UndeliveredType::Acks`]. Returns the
+	/// sequences to relay in this cycle, by priority: when there are more than fit in a batch only
+	/// the first ones are relayed. Sequences left out are offered again in the next cycle. Leaving
+	/// out the next sequence of an ordered channel stalls the channel.
+	fn select(
+		&self,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+		kind: UndeliveredType,
+		sequences: Vec<u64>,
+	) -> Vec<u64>;
+}
+
+impl<F> UndeliveredSequencesHook for F
+where
+	F: Fn(&ChannelId, &PortId, UndeliveredType, Vec<u64>) -> Vec<u64> + Send + Sync,
+{
+	fn select(
+		&self,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+		kind: UndeliveredType,
+		sequences: Vec<u64>,
+	) -> Vec<u64> {
+		self(channel_id, port_id, kind, sequences)
+	}
+}
+
+impl Debug for dyn UndeliveredSequencesHook {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("UndeliveredSequencesHook")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ibc::applications::fee::Fee;
+
+	fn coin(denom: &str, amount: u64) -> PrefixedCoin {
+		PrefixedCoin { denom: denom.parse().unwrap(), amount: amount.into() }
+	}
+
+	fn packet_fee(recv_fee: Vec<PrefixedCoin>) -> PacketFee {
+		PacketFee {
+			fee: Fee { recv_fee, ..Default::default() },
+			refund_address: "refund".parse().unwrap(),
+			relayers: vec![],
+		}
+	}
+
+	#[test]
+	fn min_fee_sums_the_escrowed_fees_of_its_denom() {
+		let min_fee = MinFee { denom: "uatom".to_string(), amount: "1000".to_string() };
+		let packet_fees = vec![
+			packet_fee(vec![coin("uatom", 600), coin("ppica", 5000)]),
+			packet_fee(vec![coin("uatom", 300)]),
+		];
+		assert!(!min_fee.is_paid_by(&packet_fees, |fee| &fee.fee.recv_fee).unwrap());
+		assert!(!min_fee.is_paid_by(&packet_fees, |fee| &fee.fee.ack_fee).unwrap());
+
+		let packet_fees = [packet_fees, vec![packet_fee(vec![coin("uatom", 100)])]].concat();
+		assert!(min_fee.is_paid_by(&packet_fees, |fee| &fee.fee.recv_fee).unwrap());
+		assert!(min_fee.is_paid_by(&[], |fee| &fee.fee.recv_fee).is_ok_and(|paid| !paid));
+	}
+
+	#[test]
+	fn undelivered_sequences_hook_reorders_and_defers_sequences() {
+		let mut state = crate::CommonClientState::default();
+		let channel_id = ChannelId::new(0);
+		let port_id = PortId::transfer();
+		let sequences = vec![1, 2, 3, 4];
+		assert_eq!(
+			state.select_undelivered_sequences(
+				&channel_id,
+				&port_id,
+				UndeliveredType::Recvs,
+				sequences.clone()
+			),
+			sequences
+		);
+
+		// even sequences first, acknowledgements deferred
+		state.undelivered_sequences_hook = Some(std::sync::Arc::new(
+			|_: &ChannelId, _: &PortId, kind: UndeliveredType, mut sequences: Vec<u64>| {
+				if kind == UndeliveredType::Acks {
+					return vec![]
+				}
+				sequences.sort_by_key(|sequence| sequence % 2);
+				sequences
+			},
+		));
+		assert_eq!(
+			state.select_undelivered_sequences(
+				&channel_id,
+				&port_id,
+				UndeliveredType::Recvs,
+				sequences.clone()
+			),
+			vec![2, 4, 1, 3]
+		);
+		assert!(state
+			.select_undelivered_sequences(&channel_id, &port_id, UndeliveredType::Acks, sequences)
+			.is_empty());
+	}
+}
@@ -12,10 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#[cfg(any(test, feature = "testing"))]
-use crate::TestProvider;
 use crate::{mock::LocalClientTypes, Chain};
+#[cfg(any(test, feature = "testing"))]
+use crate::{KeyProvider, TestProvider};
+use anyhow::anyhow;
 use futures::{future, StreamExt};
+#[cfg(any(test, feature = "testing"))]
+use ibc::{
+	applications::transfer::{msgs::transfer::MsgTransfer, PrefixedCoin, PrefixedDenom},
+	timestamp::Timestamp,
+	Height,
+};
 use ibc::{
 	core::{
 		ics02_client::msgs::create_client::MsgCreateAnyClient,
@@ -78,8 +85,8 @@ pub async fn create_clients(
 
 	let msg = Any { type_url: msg.type_url(), value: msg.encode_vec()? };
 
-	let tx_id = chain_a.submit(vec![msg]).await?;
-	let client_id_b_on_a = chain_a.query_client_id_from_tx_hash(tx_id).await?;
+	let events = chain_a.submit_and_wait_for_events(vec![msg]).await?;
+	let client_id_b_on_a = created_client_id(&events, chain_a.name())?;
 	chain_a.set_client_id(client_id_b_on_a.clone());
 
 	let msg = MsgCreateAnyClient::<LocalClientTypes> {
@@ -90,13 +97,23 @@ pub async fn create_clients(
 
 	let msg = Any { type_url: msg.type_url(), value: msg.encode_vec()? };
 
-	let tx_id = chain_b.submit(vec![msg]).await?;
-	let client_id_a_on_b = chain_b.query_client_id_from_tx_hash(tx_id).await?;
+	let events = chain_b.submit_and_wait_for_events(vec![msg]).await?;
+	let client_id_a_on_b = created_client_id(&events, chain_b.name())?;
 	chain_a.set_client_id(client_id_b_on_a.clone());
 
 	Ok((client_id_a_on_b, client_id_b_on_a))
 }
 
+fn created_client_id(events: &[IbcEvent], chain: &str) -> Result<ClientId, anyhow::Error> {
+	events
+		.iter()
+		.find_map(|event| match event {
+			IbcEvent::CreateClient(event) => Some(event.client_id().clone()),
+			_ => None,
+		})
+		.ok_or_else(|| anyhow!("No CreateClient event emitted on {chain}"))
+}
+
 /// Completes the connection handshake process
 /// The relayer process must be running before this function is executed
 pub async fn create_connection(
@@ -114,8 +131,14 @@ pub async fn create_connection(
 
 	let msg = Any { type_url: msg.type_url(), value: msg.encode_vec()? };
 
-	let tx_id = chain_a.submit(vec![msg]).await?;
-	let connection_id_a = chain_a.query_connection_id_from_tx_hash(tx_id).await?;
+	let events = chain_a.submit_and_wait_for_events(vec![msg]).await?;
+	let connection_id_a = events
+		.iter()
+		.find_map(|event| match event {
+			IbcEvent::OpenInitConnection(event) => event.connection_id().cloned(),
+			_ => None,
+		})
+		.ok_or_else(|| anyhow!("No OpenInitConnection event emitted on {}", chain_a.name()))?;
 	chain_a.set_connection_id(connection_id_a.clone());
 
 	log::info!(target: "hyperspace", "============= Wait till both chains have completed connection handshake =============");
@@ -171,8 +194,14 @@ pub async fn create_channel(
 
 	let msg = Any { type_url: msg.type_url(), value: msg.encode_vec()? };
 
-	let tx_id = chain_a.submit(vec![msg]).await?;
-	let channel_id_a = chain_a.query_channel_id_from_tx_hash(tx_id).await?;
+	let events = chain_a.submit_and_wait_for_events(vec![msg]).await?;
+	let channel_id_a = events
+		.iter()
+		.find_map(|event| match event {
+			IbcEvent::OpenInitChannel(event) => event.channel_id().cloned(),
+			_ => None,
+		})
+		.ok_or_else(|| anyhow!("No OpenInitChannel event emitted on {}", chain_a.name()))?;
 	chain_a.add_channel_to_whitelist(channel_id_a);
 
 	log::info!(target: "hyperspace", "============= Wait till both chains have completed channel handshake =============");
@@ -199,3 +228,26 @@ pub async fn create_channel(
 
 	Ok((channel_id_a, channel_id_b))
 }
+
+/// A transfer of a single unit of `denom` to the relayer itself, timing out at the current time.
+/// The sending chain accepts it, as its client of the counterparty lags behind the current time,
+/// but the counterparty can't receive it once it has produced its next block, so the relayer has
+/// to time it out.
+#[cfg(any(test, feature = "testing"))]
+pub fn expired_transfer(
+	chain: &impl KeyProvider,
+	port_id: PortId,
+	channel_id: ChannelId,
+	denom: PrefixedDenom,
+) -> MsgTransfer<PrefixedCoin> {
+	MsgTransfer {
+		source_port: port_id,
+		source_channel: channel_id,
+		token: PrefixedCoin { denom, amount: 1u64.into() },
+		sender: chain.account_id(),
+		receiver: chain.account_id(),
+		timeout_height: Height::zero(),
+		timeout_timestamp: Timestamp::now(),
+		memo: "".to_string(),
+	}
+}
@@ -0,0 +1,183 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signing keys held in HashiCorp Vault.
+//!
+//! Keys can either be read from the KV (version 2) secrets engine and used like a configured
+//! private key, or stay in the transit secrets engine, which signs payloads on the relayer's
+//! behalf. Transit only supports ed25519 among the schemes used by the relayer.
+
+use crate::error::Error;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Environment variable holding the Vault token when it isn't configured
+pub const VAULT_TOKEN_ENV: &str = "VAULT_TOKEN";
+
+/// Vault server and key location
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultConfig {
+	/// Address of the Vault server, e.g. `https://vault.internal:8200`
+	pub address: String,
+	/// Vault token, read from [`VAULT_TOKEN_ENV`] when not set
+	#[serde(default)]
+	pub token: Option<String>,
+	/// Where the key is held
+	pub key: VaultKey,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultKey {
+	/// Secret seed or mnemonic stored in the KV secrets engine
+	Kv {
+		#[serde(default = "default_kv_mount")]
+		mount: String,
+		path: String,
+		field: String,
+	},
+	/// Named key of the transit secrets engine, only ed25519 keys are supported
+	Transit {
+		#[serde(default = "default_transit_mount")]
+		mount: String,
+		name: String,
+	},
+}
+
+fn default_kv_mount() -> String {
+	"secret".to_string()
+}
+
+fn default_transit_mount() -> String {
+	"transit".to_string()
+}
+
+impl VaultConfig {
+	fn token(&self) -> Result<String, Error> {
+		match &self.token {
+			Some(token) => Ok(token.clone()),
+			None => std::env::var(VAULT_TOKEN_ENV)
+				.map_err(|_| Error::Custom(format!("{VAULT_TOKEN_ENV} is not set"))),
+		}
+	}
+
+	fn url(&self, path: &str) -> String {
+		format!("{}/v1/{path}", self.address.trim_end_matches('/'))
+	}
+
+	fn get(&self, path: &str) -> Result<Value, Error> {
+		ureq::get(&self.url(path))
+			.set("X-Vault-Token", &self.token()?)
+			.call()
+			.map_err(|e| Error::Custom(format!("Vault request failed: {e}")))?
+			.into_json()
+			.map_err(|e| Error::Custom(format!("Invalid Vault response: {e}")))
+	}
+
+	fn post(&self, path: &str, body: Value) -> Result<Value, Error> {
+		ureq::post(&self.url(path))
+			.set("X-Vault-Token", &self.token()?)
+			.send_json(body)
+			.map_err(|e| Error::Custom(format!("Vault request failed: {e}")))?
+			.into_json()
+			.map_err(|e| Error::Custom(format!("Invalid Vault response: {e}")))
+	}
+
+	/// Reads the secret of a [`VaultKey::Kv`] key. The Vault client is blocking, so the request is
+	/// made on the blocking thread pool, which works on any runtime
+	pub async fn read_secret(&self) -> Result<String, Error> {
+		let config = self.clone();
+		tokio::task::spawn_blocking(move || config.read_kv_secret())
+			.await
+			.map_err(|e| Error::Custom(format!("The Vault request task failed: {e}")))?
+	}
+
+	fn read_kv_secret(&self) -> Result<String, Error> {
+		let VaultKey::Kv { mount, path, field } = &self.key else {
+			return Err(Error::Custom("Only KV keys can be read from Vault".to_string()))
+		};
+		let response = self.get(&format!("{mount}/data/{path}"))?;
+		response["data"]["data"][field]
+			.as_str()
+			.map(ToString::to_string)
+			.ok_or_else(|| Error::Custom(format!("Field {field} is missing at {mount}/{path}")))
+	}
+
+	/// Returns the raw ed25519 public key of the latest version of a [`VaultKey::Transit`] key
+	pub fn transit_public_key(&self) -> Result<[u8; 32], Error> {
+		let (mount, name) = self.transit_key()?;
+		let response = self.get(&format!("{mount}/keys/{name}"))?;
+		let key_type = response["data"]["type"].as_str().unwrap_or_default();
+		if key_type != "ed25519" {
+			return Err(Error::Custom(format!(
+				"Transit key {name} is {key_type}, only ed25519 keys are supported"
+			)))
+		}
+		let version = response["data"]["latest_version"].as_u64().unwrap_or(1);
+		let public_key = response["data"]["keys"][version.to_string()]["public_key"]
+			.as_str()
+			.ok_or_else(|| Error::Custom(format!("No public key for transit key {name}")))?;
+		BASE64
+			.decode(public_key)
+			.ok()
+			.and_then(|public_key| public_key.try_into().ok())
+			.ok_or_else(|| Error::Custom(format!("Invalid public key for transit key {name}")))
+	}
+
+	/// Signs the payload with a [`VaultKey::Transit`] key
+	pub fn transit_sign(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+		let (mount, name) = self.transit_key()?;
+		let response = self.post(
+			&format!("{mount}/sign/{name}"),
+			serde_json::json!({ "input": BASE64.encode(payload) }),
+		)?;
+		let signature = response["data"]["signature"].as_str().ok_or_else(|| {
+			Error::Custom(format!("No signature returned for transit key {name}"))
+		})?;
+		parse_transit_signature(signature)
+	}
+
+	fn transit_key(&self) -> Result<(&str, &str), Error> {
+		match &self.key {
+			VaultKey::Transit { mount, name } => Ok((mount, name)),
+			VaultKey::Kv { .. } =>
+				Err(Error::Custom("Only transit keys can sign with Vault".to_string())),
+		}
+	}
+}
+
+/// Decodes a `vault:v<version>:<base64 signature>` transit signature
+fn parse_transit_signature(signature: &str) -> Result<Vec<u8>, Error> {
+	let invalid = || Error::Custom(format!("Invalid transit signature {signature}"));
+	let mut parts = signature.splitn(3, ':');
+	match (parts.next(), parts.next(), parts.next()) {
+		(Some("vault"), Some(version), Some(signature)) if version.starts_with('v') =>
+			BASE64.decode(signature).map_err(|_| invalid()),
+		_ => Err(invalid()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_transit_signatures() {
+		let signature = format!("vault:v2:{}", BASE64.encode([7u8; 64]));
+		assert_eq!(parse_transit_signature(&signature).unwrap(), vec![7u8; 64]);
+		assert!(parse_transit_signature("v2:abcd").is_err());
+		assert!(parse_transit_signature("vault:v1:not base64!").is_err());
+	}
+}
@@ -0,0 +1,53 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::Result;
+use clap::Parser;
+use hyperspace_remote_signer::{
+	server::{serve, KeySigner, KeysFile},
+	TlsConfig,
+};
+use std::{net::SocketAddr, path::PathBuf};
+
+/// Reference remote signer for hyperspace
+#[derive(Debug, Parser)]
+struct Cli {
+	/// Address to listen on
+	#[clap(long, default_value = "127.0.0.1:9577")]
+	listen: SocketAddr,
+	/// Key file listing the signing keys
+	#[clap(long)]
+	keys: PathBuf,
+	/// CA certificate that client certificates must be signed by
+	#[clap(long)]
+	ca_cert: PathBuf,
+	/// Server certificate
+	#[clap(long)]
+	cert: PathBuf,
+	/// Server private key
+	#[clap(long)]
+	key: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+	env_logger::init();
+	let cli = Cli::parse();
+	let keys: KeysFile = toml::from_str(&tokio::fs::read_to_string(&cli.keys).await?)?;
+	let signer = KeySigner::new(keys.keys)?;
+	let tls = TlsConfig { ca_cert: cli.ca_cert, cert: cli.cert, key: cli.key };
+	log::info!(target: "hyperspace_signer", "Listening on {}", cli.listen);
+	serve(cli.listen, &tls, signer).await?;
+	Ok(())
+}
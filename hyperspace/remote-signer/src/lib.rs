@@ -0,0 +1,186 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Remote signing for hyperspace, in the spirit of tmkms: the relayer sends signing payloads to a
+//! separate signer service over mutually authenticated TLS gRPC. See `proto/signer.proto` for the
+//! protocol and [`server`] for the reference signer.
+//!
+//! Keys split across a threshold signer set are reached through a coordinator instead, see
+//! `proto/coordinator.proto` and [`ThresholdCoordinatorClient`].
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity};
+
+pub mod server;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub mod proto {
+	tonic::include_proto!("hyperspace.signer.v1");
+}
+
+pub use proto::{Scheme, SessionState, SessionStatusResponse};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("Failed to read {0}: {1}")]
+	Io(PathBuf, std::io::Error),
+	#[error("Transport error: {0}")]
+	Transport(#[from] tonic::transport::Error),
+	#[error("Remote signer returned an error: {0}")]
+	Status(#[from] tonic::Status),
+	#[error("{0}")]
+	Custom(String),
+}
+
+/// Certificates for the mutually authenticated TLS connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsConfig {
+	/// CA certificate that the certificate of the other side must be signed by
+	pub ca_cert: PathBuf,
+	/// Own certificate
+	pub cert: PathBuf,
+	/// Own private key
+	pub key: PathBuf,
+}
+
+impl TlsConfig {
+	async fn read(path: &PathBuf) -> Result<Vec<u8>, Error> {
+		tokio::fs::read(path).await.map_err(|e| Error::Io(path.clone(), e))
+	}
+
+	pub(crate) async fn ca_certificate(&self) -> Result<Certificate, Error> {
+		Ok(Certificate::from_pem(Self::read(&self.ca_cert).await?))
+	}
+
+	pub(crate) async fn identity(&self) -> Result<Identity, Error> {
+		Ok(Identity::from_pem(Self::read(&self.cert).await?, Self::read(&self.key).await?))
+	}
+
+	async fn connect(&self, url: String) -> Result<Channel, Error> {
+		let tls_config = ClientTlsConfig::new()
+			.ca_certificate(self.ca_certificate().await?)
+			.identity(self.identity().await?);
+		Ok(Endpoint::from_shared(url)?.tls_config(tls_config)?.connect().await?)
+	}
+}
+
+fn scheme(scheme: i32) -> Result<Scheme, Error> {
+	Scheme::from_i32(scheme)
+		.ok_or_else(|| Error::Custom(format!("Unknown signature scheme {scheme}")))
+}
+
+/// Client of a remote signer
+#[derive(Debug, Clone)]
+pub struct RemoteSignerClient {
+	inner: proto::remote_signer_client::RemoteSignerClient<Channel>,
+}
+
+impl RemoteSignerClient {
+	/// Connects to the signer at `url`, the host of the url must match the signer certificate.
+	pub async fn connect(url: String, tls: &TlsConfig) -> Result<Self, Error> {
+		let channel = tls.connect(url).await?;
+		Ok(Self { inner: proto::remote_signer_client::RemoteSignerClient::new(channel) })
+	}
+
+	/// Returns the signature scheme and raw public key of the key
+	pub async fn public_key(&self, key_id: &str) -> Result<(Scheme, Vec<u8>), Error> {
+		let response = self
+			.inner
+			.clone()
+			.public_key(proto::PublicKeyRequest { key_id: key_id.to_string() })
+			.await?
+			.into_inner();
+		Ok((scheme(response.scheme)?, response.public_key))
+	}
+
+	/// Signs the payload with the key and returns the raw signature
+	pub async fn sign(
+		&self,
+		key_id: &str,
+		chain: &str,
+		payload: Vec<u8>,
+	) -> Result<Vec<u8>, Error> {
+		let response = self
+			.inner
+			.clone()
+			.sign(proto::SignRequest {
+				key_id: key_id.to_string(),
+				chain: chain.to_string(),
+				payload,
+			})
+			.await?
+			.into_inner();
+		Ok(response.signature)
+	}
+}
+
+/// Client of a threshold signing coordinator
+#[derive(Debug, Clone)]
+pub struct ThresholdCoordinatorClient {
+	inner: proto::threshold_coordinator_client::ThresholdCoordinatorClient<Channel>,
+}
+
+impl ThresholdCoordinatorClient {
+	/// Connects to the coordinator at `url`, the host of the url must match the coordinator
+	/// certificate.
+	pub async fn connect(url: String, tls: &TlsConfig) -> Result<Self, Error> {
+		let channel = tls.connect(url).await?;
+		Ok(Self {
+			inner: proto::threshold_coordinator_client::ThresholdCoordinatorClient::new(channel),
+		})
+	}
+
+	/// Returns the signature scheme and raw group public key of the signer group
+	pub async fn group_key(&self, group_id: &str) -> Result<(Scheme, Vec<u8>), Error> {
+		let response = self
+			.inner
+			.clone()
+			.group_key(proto::GroupKeyRequest { group_id: group_id.to_string() })
+			.await?
+			.into_inner();
+		Ok((scheme(response.scheme)?, response.public_key))
+	}
+
+	/// Opens a signing session for the payload and returns its id
+	pub async fn open_session(
+		&self,
+		group_id: &str,
+		chain: &str,
+		payload: Vec<u8>,
+	) -> Result<String, Error> {
+		let response = self
+			.inner
+			.clone()
+			.open_session(proto::OpenSessionRequest {
+				group_id: group_id.to_string(),
+				chain: chain.to_string(),
+				payload,
+			})
+			.await?
+			.into_inner();
+		Ok(response.session_id)
+	}
+
+	pub async fn session_status(&self, session_id: &str) -> Result<SessionStatusResponse, Error> {
+		let response = self
+			.inner
+			.clone()
+			.session_status(proto::SessionStatusRequest { session_id: session_id.to_string() })
+			.await?
+			.into_inner();
+		Ok(response)
+	}
+}
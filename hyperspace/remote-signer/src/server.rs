@@ -0,0 +1,199 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reference signer server, keeping its keys in memory.
+
+use crate::{
+	proto::{
+		remote_signer_server::{RemoteSigner, RemoteSignerServer},
+		PublicKeyRequest, PublicKeyResponse, SignRequest, SignResponse,
+	},
+	Error, Scheme, TlsConfig,
+};
+use serde::{Deserialize, Serialize};
+use sp_core::{ecdsa, ed25519, sr25519, Pair};
+use std::{collections::HashMap, net::SocketAddr};
+use tonic::{
+	transport::{Server, ServerTlsConfig},
+	Request, Response, Status,
+};
+
+/// Signing key as read from the key file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyConfig {
+	/// Identifier the relayer refers to the key by
+	pub id: String,
+	/// One of `sr25519`, `ed25519` or `ecdsa`
+	pub scheme: String,
+	/// Secret seed or phrase of the key
+	pub seed: String,
+	/// Chains the key may sign for, any chain if empty
+	#[serde(default)]
+	pub chains: Vec<String>,
+}
+
+/// Contents of the key file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeysFile {
+	pub keys: Vec<KeyConfig>,
+}
+
+enum KeyPair {
+	Sr25519(sr25519::Pair),
+	Ed25519(ed25519::Pair),
+	Ecdsa(ecdsa::Pair),
+}
+
+struct SigningKey {
+	pair: KeyPair,
+	chains: Vec<String>,
+}
+
+/// Signer holding its keys in memory
+pub struct KeySigner {
+	keys: HashMap<String, SigningKey>,
+}
+
+impl KeySigner {
+	pub fn new(keys: Vec<KeyConfig>) -> Result<Self, Error> {
+		let invalid_key = |id: &str| Error::Custom(format!("Invalid seed for key {id}"));
+		let keys = keys
+			.into_iter()
+			.map(|key| {
+				let pair = match key.scheme.as_str() {
+					"sr25519" => KeyPair::Sr25519(
+						sr25519::Pair::from_string(&key.seed, None)
+							.map_err(|_| invalid_key(&key.id))?,
+					),
+					"ed25519" => KeyPair::Ed25519(
+						ed25519::Pair::from_string(&key.seed, None)
+							.map_err(|_| invalid_key(&key.id))?,
+					),
+					"ecdsa" => KeyPair::Ecdsa(
+						ecdsa::Pair::from_string(&key.seed, None)
+							.map_err(|_| invalid_key(&key.id))?,
+					),
+					scheme =>
+						return Err(Error::Custom(format!(
+							"Unknown scheme {scheme} for key {}",
+							key.id
+						))),
+				};
+				Ok((key.id, SigningKey { pair, chains: key.chains }))
+			})
+			.collect::<Result<_, Error>>()?;
+		Ok(Self { keys })
+	}
+
+	fn key(&self, key_id: &str) -> Result<&SigningKey, Status> {
+		self.keys
+			.get(key_id)
+			.ok_or_else(|| Status::not_found(format!("Unknown key {key_id}")))
+	}
+}
+
+#[tonic::async_trait]
+impl RemoteSigner for KeySigner {
+	async fn public_key(
+		&self,
+		request: Request<PublicKeyRequest>,
+	) -> Result<Response<PublicKeyResponse>, Status> {
+		let key = self.key(&request.get_ref().key_id)?;
+		let (scheme, public_key) = match &key.pair {
+			KeyPair::Sr25519(pair) => (Scheme::Sr25519, pair.public().0.to_vec()),
+			KeyPair::Ed25519(pair) => (Scheme::Ed25519, pair.public().0.to_vec()),
+			KeyPair::Ecdsa(pair) => (Scheme::Ecdsa, pair.public().0.to_vec()),
+		};
+		Ok(Response::new(PublicKeyResponse { scheme: scheme as i32, public_key }))
+	}
+
+	async fn sign(&self, request: Request<SignRequest>) -> Result<Response<SignResponse>, Status> {
+		let request = request.into_inner();
+		let key = self.key(&request.key_id)?;
+		if !key.chains.is_empty() && !key.chains.contains(&request.chain) {
+			return Err(Status::permission_denied(format!(
+				"Key {} may not sign for {}",
+				request.key_id, request.chain
+			)))
+		}
+		let signature = match &key.pair {
+			KeyPair::Sr25519(pair) => pair.sign(&request.payload).0.to_vec(),
+			KeyPair::Ed25519(pair) => pair.sign(&request.payload).0.to_vec(),
+			KeyPair::Ecdsa(pair) => pair.sign(&request.payload).0.to_vec(),
+		};
+		log::info!(target: "hyperspace_signer", "Signed {} byte payload with {} for {}", request.payload.len(), request.key_id, request.chain);
+		Ok(Response::new(SignResponse { signature }))
+	}
+}
+
+/// Serves the signer on `addr`, only accepting clients with a certificate signed by the CA.
+pub async fn serve(addr: SocketAddr, tls: &TlsConfig, signer: KeySigner) -> Result<(), Error> {
+	let tls_config = ServerTlsConfig::new()
+		.identity(tls.identity().await?)
+		.client_ca_root(tls.ca_certificate().await?);
+	Server::builder()
+		.tls_config(tls_config)?
+		.add_service(RemoteSignerServer::new(signer))
+		.serve(addr)
+		.await?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::sr25519::{Public, Signature};
+
+	fn signer(chains: Vec<String>) -> KeySigner {
+		KeySigner::new(vec![KeyConfig {
+			id: "relayer".to_string(),
+			scheme: "sr25519".to_string(),
+			seed: "//Alice".to_string(),
+			chains,
+		}])
+		.unwrap()
+	}
+
+	fn sign_request(chain: &str) -> Request<SignRequest> {
+		Request::new(SignRequest {
+			key_id: "relayer".to_string(),
+			chain: chain.to_string(),
+			payload: b"payload".to_vec(),
+		})
+	}
+
+	#[tokio::test]
+	async fn signs_with_the_requested_key() {
+		let signer = signer(vec![]);
+		let public_key = signer
+			.public_key(Request::new(PublicKeyRequest { key_id: "relayer".to_string() }))
+			.await
+			.unwrap()
+			.into_inner();
+		assert_eq!(public_key.scheme, Scheme::Sr25519 as i32);
+		let public_key = Public::from_raw(public_key.public_key.try_into().unwrap());
+
+		let signature = signer.sign(sign_request("picasso")).await.unwrap().into_inner();
+		let signature = Signature::from_raw(signature.signature.try_into().unwrap());
+		assert!(sr25519::Pair::verify(&signature, b"payload", &public_key));
+	}
+
+	#[tokio::test]
+	async fn rejects_unknown_keys_and_chains() {
+		let signer = signer(vec!["picasso".to_string()]);
+		assert!(signer.sign(sign_request("composable")).await.is_err());
+		let request = Request::new(PublicKeyRequest { key_id: "unknown".to_string() });
+		assert!(signer.public_key(request).await.is_err());
+	}
+}
@@ -0,0 +1,158 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-process threshold coordinator, standing in for a signer set in tests.
+
+use crate::{
+	proto::{
+		threshold_coordinator_client,
+		threshold_coordinator_server::{ThresholdCoordinator, ThresholdCoordinatorServer},
+		GroupKeyRequest, OpenSessionRequest, OpenSessionResponse, PublicKeyResponse,
+		SessionStatusRequest,
+	},
+	Error, Scheme, SessionState, SessionStatusResponse, ThresholdCoordinatorClient,
+};
+use sp_core::{sr25519, Pair};
+use std::{collections::HashMap, sync::Mutex};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+	transport::{Endpoint, Server},
+	Request, Response, Status,
+};
+
+/// How the signer set answers the signing sessions
+#[derive(Debug, Clone)]
+pub enum Outcome {
+	/// The threshold is reached once the session was polled `polls` times
+	Sign { polls: u32 },
+	/// The members refuse to sign
+	Reject(String),
+	/// The session times out on the coordinator
+	Expire(String),
+	/// The members never answer
+	Pending,
+}
+
+struct Session {
+	payload: Vec<u8>,
+	polls: u32,
+}
+
+/// Coordinator of a single signer group, whose group key is a plain sr25519 key
+pub struct InProcessCoordinator {
+	group_id: String,
+	pair: sr25519::Pair,
+	threshold: u32,
+	outcome: Outcome,
+	sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl InProcessCoordinator {
+	pub fn new(group_id: &str, pair: sr25519::Pair, threshold: u32, outcome: Outcome) -> Self {
+		Self {
+			group_id: group_id.to_string(),
+			pair,
+			threshold,
+			outcome,
+			sessions: Default::default(),
+		}
+	}
+
+	/// Serves the coordinator on a local port without TLS and returns a client connected to it
+	pub async fn spawn(self) -> Result<ThresholdCoordinatorClient, Error> {
+		let listener = TcpListener::bind("127.0.0.1:0")
+			.await
+			.map_err(|e| Error::Custom(format!("Failed to bind the coordinator: {e}")))?;
+		let addr = listener
+			.local_addr()
+			.map_err(|e| Error::Custom(format!("Failed to bind the coordinator: {e}")))?;
+		tokio::spawn(
+			Server::builder()
+				.add_service(ThresholdCoordinatorServer::new(self))
+				.serve_with_incoming(TcpListenerStream::new(listener)),
+		);
+		let channel = Endpoint::from_shared(format!("http://{addr}"))?.connect().await?;
+		Ok(ThresholdCoordinatorClient {
+			inner: threshold_coordinator_client::ThresholdCoordinatorClient::new(channel),
+		})
+	}
+
+	fn check_group(&self, group_id: &str) -> Result<(), Status> {
+		if group_id != self.group_id {
+			return Err(Status::not_found(format!("Unknown group {group_id}")))
+		}
+		Ok(())
+	}
+}
+
+#[tonic::async_trait]
+impl ThresholdCoordinator for InProcessCoordinator {
+	async fn group_key(
+		&self,
+		request: Request<GroupKeyRequest>,
+	) -> Result<Response<PublicKeyResponse>, Status> {
+		self.check_group(&request.get_ref().group_id)?;
+		Ok(Response::new(PublicKeyResponse {
+			scheme: Scheme::Sr25519 as i32,
+			public_key: self.pair.public().0.to_vec(),
+		}))
+	}
+
+	async fn open_session(
+		&self,
+		request: Request<OpenSessionRequest>,
+	) -> Result<Response<OpenSessionResponse>, Status> {
+		let request = request.into_inner();
+		self.check_group(&request.group_id)?;
+		let mut sessions = self.sessions.lock().unwrap();
+		let session_id = format!("session-{}", sessions.len());
+		sessions.insert(session_id.clone(), Session { payload: request.payload, polls: 0 });
+		Ok(Response::new(OpenSessionResponse { session_id }))
+	}
+
+	async fn session_status(
+		&self,
+		request: Request<SessionStatusRequest>,
+	) -> Result<Response<SessionStatusResponse>, Status> {
+		let session_id = &request.get_ref().session_id;
+		let mut sessions = self.sessions.lock().unwrap();
+		let session = sessions
+			.get_mut(session_id)
+			.ok_or_else(|| Status::not_found(format!("Unknown session {session_id}")))?;
+		session.polls += 1;
+		let mut status = SessionStatusResponse {
+			approvals: session.polls.min(self.threshold.saturating_sub(1)),
+			threshold: self.threshold,
+			..Default::default()
+		};
+		match &self.outcome {
+			Outcome::Sign { polls } if session.polls >= *polls => {
+				status.set_state(SessionState::Complete);
+				status.approvals = self.threshold;
+				status.signature = self.pair.sign(&session.payload).0.to_vec();
+			},
+			Outcome::Sign { .. } | Outcome::Pending => status.set_state(SessionState::Pending),
+			Outcome::Reject(reason) => {
+				status.set_state(SessionState::Rejected);
+				status.reason = reason.clone();
+			},
+			Outcome::Expire(reason) => {
+				status.set_state(SessionState::Expired);
+				status.reason = reason.clone();
+			},
+		}
+		Ok(Response::new(status))
+	}
+}
@@ -11,17 +11,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::Parser;
 use hyperspace_core::{
 	command::{Cli, Subcommand},
-	logging,
+	logging, queue,
 };
 
 #[tokio::main]
 async fn main() -> Result<()> {
 	logging::setup_logging();
 	let cli = Cli::parse();
+	if cli.dry_run {
+		if !matches!(cli.subcommand, Subcommand::Relay(_) | Subcommand::Tx(_)) {
+			return Err(anyhow!("--dry-run is only supported by relay and tx"))
+		}
+		queue::set_dry_run(true);
+	}
 
 	match &cli.subcommand {
 		Subcommand::Relay(cmd) => cmd.run().await,
@@ -42,5 +48,16 @@ async fn main() -> Result<()> {
 			cmd.save_config(&new_config).await
 		},
 		Subcommand::Fish(cmd) => cmd.fish().await,
+		Subcommand::EncryptKey(cmd) => {
+			let new_config = cmd.run().await?;
+			cmd.save_config(&new_config).await
+		},
+		Subcommand::Keys(cmd) => cmd.run().await,
+		Subcommand::RecoverClient(cmd) => cmd.run().await,
+		Subcommand::Config(cmd) => cmd.run().await,
+		Subcommand::Query(cmd) => cmd.run().await,
+		Subcommand::Tx(cmd) => cmd.run().await,
+		Subcommand::Doctor(cmd) => cmd.run().await,
+		Subcommand::Init(cmd) => cmd.run().await,
 	}
 }
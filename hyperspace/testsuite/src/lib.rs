@@ -16,7 +16,7 @@
 
 use crate::utils::assert_timeout_packet;
 use futures::{future, StreamExt};
-use hyperspace_core::send_packet_relay::set_relay_status;
+use hyperspace_core::{send_packet_relay::set_relay_status, shutdown::Shutdown};
 use hyperspace_primitives::{
 	utils::{create_channel, create_connection, timeout_after, timeout_future},
 	TestProvider,
@@ -41,6 +41,7 @@ use tokio::task::JoinHandle;
 
 pub mod misbehaviour;
 pub mod ordered_channels;
+pub mod setup;
 mod utils;
 
 /// This will set up a connection and ics20 channel in-between the two chains.
@@ -62,9 +63,16 @@ where
 	let client_b_clone = chain_b.clone();
 	// Start relayer loop
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			Shutdown::default(),
+		)
+		.await
+		.unwrap()
 	});
 	// check if an open transfer channel exists
 	let (latest_height, ..) = chain_a.latest_height_and_timestamp().await.unwrap();
@@ -91,10 +99,9 @@ where
 		}
 
 		let channels = chain_a
-			.query_connection_channels(latest_height, &connection_id)
+			.query_all_connection_channels(latest_height, &connection_id)
 			.await
-			.unwrap()
-			.channels;
+			.unwrap();
 
 		for channel in channels {
 			let channel_id = ChannelId::from_str(&channel.channel_id).unwrap();
@@ -499,9 +506,16 @@ pub async fn ibc_messaging_packet_height_timeout_with_connection_delay<A, B>(
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			Shutdown::default(),
+		)
+		.await
+		.unwrap()
 	});
 	send_packet_and_assert_height_timeout(chain_a, chain_b, asset_a, channel_a).await;
 	handle.abort()
@@ -525,9 +539,16 @@ pub async fn ibc_messaging_packet_timestamp_timeout_with_connection_delay<A, B>(
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			Shutdown::default(),
+		)
+		.await
+		.unwrap()
 	});
 	send_packet_and_assert_timestamp_timeout(chain_a, chain_b, asset_a, channel_a).await;
 	handle.abort()
@@ -553,9 +574,16 @@ pub async fn ibc_messaging_with_connection_delay<A, B>(
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			Shutdown::default(),
+		)
+		.await
+		.unwrap()
 	});
 	send_packet_with_connection_delay(chain_a, chain_b, channel_a, channel_b, asset_a, asset_b)
 		.await;
@@ -585,9 +613,16 @@ where
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			Shutdown::default(),
+		)
+		.await
+		.unwrap()
 	});
 	send_channel_close_init_and_assert_channel_close_confirm(chain_a, chain_b, channel_id).await;
 	handle.abort()
@@ -610,9 +645,16 @@ pub async fn ibc_messaging_packet_timeout_on_channel_close<A, B>(
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			Shutdown::default(),
+		)
+		.await
+		.unwrap()
 	});
 	send_packet_and_assert_timeout_on_channel_close(chain_a, chain_b, asset_a, channel_a).await;
 	handle.abort()
@@ -633,9 +675,16 @@ where
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			Shutdown::default(),
+		)
+		.await
+		.unwrap()
 	});
 	log::info!(target: "hyperspace", "🚀🚀 Clients were successfully synced");
 	handle.abort();
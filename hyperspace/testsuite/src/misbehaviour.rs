@@ -4,6 +4,7 @@ use grandpa_client_primitives::{
 	justification::GrandpaJustification, parachain_header_storage_key, Commit, FinalityProof,
 	ParachainHeaderProofs,
 };
+use hyperspace_core::shutdown::Shutdown;
 use hyperspace_primitives::{mock::LocalClientTypes, TestProvider};
 use ibc::{
 	core::ics02_client::{height::Height, msgs::update_client::MsgUpdateAnyClient},
@@ -41,7 +42,9 @@ where
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::fish(client_a_clone, client_b_clone).await.unwrap()
+		hyperspace_core::fish(client_a_clone, client_b_clone, Shutdown::default())
+			.await
+			.unwrap()
 	});
 	info!("Waiting for the next block...");
 
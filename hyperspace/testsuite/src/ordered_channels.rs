@@ -14,7 +14,7 @@
 
 use crate::{assert_timeout_packet, timeout_future, StreamExt};
 use futures::future;
-use hyperspace_core::send_packet_relay::set_relay_status;
+use hyperspace_core::{send_packet_relay::set_relay_status, shutdown::Shutdown};
 use hyperspace_primitives::{
 	utils::{create_channel, create_connection},
 	TestProvider,
@@ -51,9 +51,16 @@ where
 	let client_b_clone = chain_b.clone();
 	// Start relayer loop
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			Shutdown::default(),
+		)
+		.await
+		.unwrap()
 	});
 	// check if an open ping channel exists
 	let (latest_height, ..) = chain_a.latest_height_and_timestamp().await.unwrap();
@@ -81,10 +88,9 @@ where
 		}
 
 		let channels = chain_a
-			.query_connection_channels(latest_height, &connection_id)
+			.query_all_connection_channels(latest_height, &connection_id)
 			.await
-			.unwrap()
-			.channels;
+			.unwrap();
 
 		for channel in channels {
 			let channel_id = ChannelId::from_str(&channel.channel_id).unwrap();
@@ -250,9 +256,16 @@ pub async fn ibc_messaging_ordered_packet_with_connection_delay<A, B>(
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			Shutdown::default(),
+		)
+		.await
+		.unwrap()
 	});
 	send_ordered_packet_and_assert_acknowledgement(chain_a, chain_b, channel_id).await;
 	handle.abort()
@@ -287,9 +300,16 @@ pub async fn ibc_messaging_ordered_packet_timeout<A, B>(
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			Shutdown::default(),
+		)
+		.await
+		.unwrap()
 	});
 	send_ordered_packet_and_assert_timeout(chain_a, chain_b, channel_id).await;
 	handle.abort()
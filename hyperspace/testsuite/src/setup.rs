@@ -0,0 +1,83 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Setup of the parachain clients shared by the integration tests.
+
+use futures::StreamExt;
+use hyperspace_core::substrate::DefaultConfig;
+use hyperspace_parachain::{
+	finality_protocol::FinalityProtocol, ParachainClient, ParachainClientConfig,
+};
+
+/// Config of a client of the parachain `para_id`, finalized with GRANDPA and signing with the
+/// sr25519 `private_key`. Every other option is left to its default.
+pub fn parachain_config(
+	name: &str,
+	para_id: u32,
+	parachain_rpc_url: String,
+	relay_chain_rpc_url: String,
+	commitment_prefix: &str,
+	private_key: &str,
+) -> ParachainClientConfig {
+	ParachainClientConfig {
+		name: name.to_string(),
+		para_id,
+		parachain_rpc_url,
+		relay_chain_rpc_url,
+		parachain_rpc_fallback_urls: vec![],
+		relay_chain_rpc_fallback_urls: vec![],
+		relay_chain_witness_rpc_url: None,
+		client_id: None,
+		connection_id: None,
+		commitment_prefix: commitment_prefix.as_bytes().to_vec().into(),
+		private_key: private_key.to_string(),
+		ss58_version: 42,
+		channel_whitelist: vec![],
+		finality_protocol: FinalityProtocol::Grandpa,
+		key_type: "sr25519".to_string(),
+		wasm_code_hash: None,
+		light_client: None,
+		extrinsic_options: Default::default(),
+		batch_calls: false,
+		max_extrinsic_size: None,
+		verify_events: false,
+		sudo_private_key: None,
+		additional_private_keys: vec![],
+		address_format: None,
+		message_signer: None,
+		keystore: None,
+		ledger: None,
+		remote_signer: None,
+		threshold_signer: None,
+		vault: None,
+	}
+}
+
+/// Waits until the relay chain is two sessions in, from then on the parachains produce blocks and
+/// the GRANDPA proofs of their headers can be built.
+pub async fn wait_for_parachains(client: &ParachainClient<DefaultConfig>) -> anyhow::Result<()> {
+	log::info!(target: "hyperspace", "Waiting for block production from the parachains");
+	let session_length = client.grandpa_prover().session_length().await?;
+	client
+		.relay_client
+		.rpc()
+		.subscribe_finalized_block_headers()
+		.await?
+		.filter_map(|result| futures::future::ready(result.ok()))
+		.skip_while(|header| futures::future::ready(header.number < (session_length * 2) + 10))
+		.next()
+		.await;
+	log::info!(target: "hyperspace", "Parachains have started block production");
+	Ok(())
+}
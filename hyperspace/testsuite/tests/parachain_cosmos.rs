@@ -13,20 +13,20 @@
 // limitations under the License.
 
 use core::time::Duration;
-use futures::StreamExt;
 use hyperspace_core::{
 	chain::{AnyAssetId, AnyChain, AnyConfig},
 	logging,
 	substrate::DefaultConfig,
 };
 use hyperspace_cosmos::client::{CosmosClient, CosmosClientConfig};
-use hyperspace_parachain::{finality_protocol::FinalityProtocol, ParachainClientConfig};
-use hyperspace_primitives::{utils::create_clients, CommonClientConfig, IbcProvider};
+use hyperspace_primitives::{utils::create_clients, Chain, CommonClientConfig, IbcQueryProvider};
 use hyperspace_testsuite::{
 	ibc_channel_close, ibc_messaging_packet_height_timeout_with_connection_delay,
 	ibc_messaging_packet_timeout_on_channel_close,
 	ibc_messaging_packet_timestamp_timeout_with_connection_delay,
-	ibc_messaging_with_connection_delay, misbehaviour::ibc_messaging_submit_misbehaviour,
+	ibc_messaging_with_connection_delay,
+	misbehaviour::ibc_messaging_submit_misbehaviour,
+	setup::{parachain_config, wait_for_parachains},
 	setup_connection_and_channel,
 };
 use ibc::core::ics24_host::identifier::PortId;
@@ -73,21 +73,14 @@ async fn setup_clients() -> (AnyChain, AnyChain) {
 	let args = Args::default();
 
 	// Create client configurations
-	let config_a = ParachainClientConfig {
-		name: "parachain".to_string(),
-		para_id: args.para_id,
-		parachain_rpc_url: args.chain_a,
-		relay_chain_rpc_url: args.relay_chain.clone(),
-		client_id: None,
-		connection_id: None,
-		commitment_prefix: args.connection_prefix_a.as_bytes().to_vec().into(),
-		ss58_version: 42,
-		channel_whitelist: vec![],
-		finality_protocol: FinalityProtocol::Grandpa,
-		private_key: "//Alice".to_string(),
-		key_type: "sr25519".to_string(),
-		wasm_code_hash: None,
-	};
+	let config_a = parachain_config(
+		"parachain",
+		args.para_id,
+		args.chain_a,
+		args.relay_chain.clone(),
+		&args.connection_prefix_a,
+		"//Alice",
+	);
 
 	let mut config_b = CosmosClientConfig {
 		name: "cosmos".to_string(),
@@ -98,11 +91,18 @@ async fn setup_clients() -> (AnyChain, AnyChain) {
 		client_id: None,
 		connection_id: None,
 		account_prefix: "cosmos".to_string(),
+		address_format: None,
+		keystore: None,
+		vault: None,
 		fee_denom: "stake".to_string(),
 		fee_amount: "4000".to_string(),
+		fee_granter: None,
 		gas_limit: (i64::MAX - 1) as u64,
 		store_prefix: args.connection_prefix_b,
 		max_tx_size: 200000,
+		trusting_period_secs: 64000,
+		unbonding_period_secs: 1814400,
+		max_clock_drift_secs: 15,
 		mnemonic:
 			"oxygen fall sure lava energy veteran enroll frown question detail include maximum"
 				.to_string(),
@@ -111,6 +111,7 @@ async fn setup_clients() -> (AnyChain, AnyChain) {
 		common: CommonClientConfig {
 			skip_optional_client_updates: true,
 			max_packets_to_process: 200,
+			verify_events: false,
 		},
 		skip_tokens_list: Some(vec!["uosmo".to_string()]),
 	};
@@ -134,26 +135,13 @@ async fn setup_clients() -> (AnyChain, AnyChain) {
 	let mut chain_a_wrapped = AnyConfig::Parachain(config_a).into_client().await.unwrap();
 	let mut chain_b_wrapped = AnyConfig::Cosmos(config_b).into_client().await.unwrap();
 
-	let AnyChain::Parachain(chain_a) = &mut chain_a_wrapped else { unreachable!() };
+	let AnyChain::Parachain(chain_a) = &chain_a_wrapped else { unreachable!() };
 
 	// Wait until for parachains to start producing blocks
-	log::info!(target: "hyperspace", "Waiting for block production from parachain");
-	let session_length = chain_a.grandpa_prover().session_length().await.unwrap();
-	let _ = chain_a
-		.relay_client
-		.rpc()
-		.subscribe_finalized_block_headers()
-		.await
-		.unwrap()
-		.filter_map(|result| futures::future::ready(result.ok()))
-		.skip_while(|h| futures::future::ready(h.number < (session_length * 2) + 10))
-		.take(1)
-		.collect::<Vec<_>>()
-		.await;
-	log::info!(target: "hyperspace", "Parachain have started block production");
-
-	let clients_on_a = chain_a_wrapped.query_clients().await.unwrap();
-	let clients_on_b = chain_b_wrapped.query_clients().await.unwrap();
+	wait_for_parachains(chain_a).await.unwrap();
+
+	let clients_on_a = chain_a_wrapped.query_all_clients().await.unwrap();
+	let clients_on_b = chain_b_wrapped.query_all_clients().await.unwrap();
 
 	if !clients_on_a.is_empty() && !clients_on_b.is_empty() {
 		chain_a_wrapped.set_client_id(clients_on_b[0].clone());
@@ -12,12 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use futures::StreamExt;
 use hyperspace_core::{logging, substrate::DefaultConfig};
-use hyperspace_parachain::{
-	finality_protocol::FinalityProtocol, ParachainClient, ParachainClientConfig,
-};
-use hyperspace_primitives::{utils::create_clients, IbcProvider, TestProvider};
+use hyperspace_parachain::ParachainClient;
+use hyperspace_primitives::{utils::create_clients, IbcQueryProvider, TestProvider};
 use hyperspace_testsuite::{
 	client_synchronization_test, ibc_channel_close,
 	ibc_messaging_packet_height_timeout_with_connection_delay,
@@ -60,62 +57,35 @@ async fn setup_clients() -> (ParachainClient<DefaultConfig>, ParachainClient<Def
 	let args = Args::default();
 
 	// Create client configurations
-	let config_a = ParachainClientConfig {
-		name: "9988".to_string(),
-		para_id: args.para_id_a,
-		parachain_rpc_url: args.chain_a,
-		relay_chain_rpc_url: args.relay_chain.clone(),
-		client_id: None,
-		connection_id: None,
-		commitment_prefix: args.connection_prefix_b.as_bytes().to_vec().into(),
-		ss58_version: 42,
-		channel_whitelist: vec![],
-		finality_protocol: FinalityProtocol::Grandpa,
-		private_key: "//Alice".to_string(),
-		key_type: "sr25519".to_string(),
-		wasm_code_hash: None,
-	};
-	let config_b = ParachainClientConfig {
-		name: "9188".to_string(),
-		para_id: args.para_id_b,
-		parachain_rpc_url: args.chain_b,
-		relay_chain_rpc_url: args.relay_chain,
-		client_id: None,
-		connection_id: None,
-		commitment_prefix: args.connection_prefix_b.as_bytes().to_vec().into(),
-		private_key: "//Alice".to_string(),
-		ss58_version: 42,
-		channel_whitelist: vec![],
-		finality_protocol: FinalityProtocol::Grandpa,
-		key_type: "sr25519".to_string(),
-		wasm_code_hash: None,
-	};
+	let config_a = parachain_config(
+		"9988",
+		args.para_id_a,
+		args.chain_a,
+		args.relay_chain.clone(),
+		&args.connection_prefix_a,
+		"//Alice",
+	);
+	let config_b = parachain_config(
+		"9188",
+		args.para_id_b,
+		args.chain_b,
+		args.relay_chain,
+		&args.connection_prefix_b,
+		"//Alice",
+	);
 
 	let mut chain_a = ParachainClient::<DefaultConfig>::new(config_a).await.unwrap();
 	let mut chain_b = ParachainClient::<DefaultConfig>::new(config_b).await.unwrap();
 
 	// Wait until for parachains to start producing blocks
-	log::info!(target: "hyperspace", "Waiting for  block production from parachains");
-	let session_length = chain_a.grandpa_prover().session_length().await.unwrap();
-	let _ = chain_a
-		.relay_client
-		.rpc()
-		.subscribe_finalized_block_headers()
-		.await
-		.unwrap()
-		.filter_map(|result| futures::future::ready(result.ok()))
-		.skip_while(|h| futures::future::ready(h.number < (session_length * 2) + 10))
-		.take(1)
-		.collect::<Vec<_>>()
-		.await;
-	log::info!(target: "hyperspace", "Parachains have started block production");
+	wait_for_parachains(&chain_a).await.unwrap();
 
 	// We need to make difference between the chains' counters to ensure that
 	// proper values are used for source/sink client, connection, channel (etc.) ids.
 	chain_a.increase_counters().await.unwrap();
 
-	let clients_on_a = chain_a.query_clients().await.unwrap();
-	let clients_on_b = chain_b.query_clients().await.unwrap();
+	let clients_on_a = chain_a.query_all_clients().await.unwrap();
+	let clients_on_b = chain_b.query_all_clients().await.unwrap();
 
 	let (client_a, client_b) = if !clients_on_a.is_empty() && !clients_on_b.is_empty() {
 		(clients_on_b[0].clone(), clients_on_b[0].clone())
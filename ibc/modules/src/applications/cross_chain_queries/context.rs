@@ -0,0 +1,51 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+	applications::cross_chain_queries::{
+		error::Error,
+		query::{CrossChainQuery, CrossChainQueryResult, QueryId},
+	},
+	prelude::*,
+};
+
+/// Read access to the cross-chain queries of this chain.
+pub trait CrossChainQueryReader {
+	/// Returns the number of cross-chain queries submitted so far, the identifier of the next one.
+	fn query_counter(&self) -> Result<u64, Error>;
+
+	/// Returns the pending cross-chain query with the given identifier.
+	fn cross_chain_query(&self, query_id: QueryId) -> Result<CrossChainQuery, Error>;
+
+	/// Returns the result of the cross-chain query with the given identifier.
+	fn cross_chain_query_result(&self, query_id: QueryId) -> Result<CrossChainQueryResult, Error>;
+}
+
+/// Write access to the cross-chain queries of this chain.
+pub trait CrossChainQueryKeeper {
+	fn increase_query_counter(&mut self);
+
+	fn store_cross_chain_query(&mut self, query: CrossChainQuery) -> Result<(), Error>;
+
+	/// Deletes a pending query once it's answered or timed out.
+	fn delete_cross_chain_query(&mut self, query_id: QueryId) -> Result<(), Error>;
+
+	fn store_cross_chain_query_result(
+		&mut self,
+		result: CrossChainQueryResult,
+	) -> Result<(), Error>;
+}
+
+/// Captures all the dependencies which the ICS31 application requires to be able to dispatch and
+/// process cross-chain queries.
+pub trait CrossChainQueryContext: CrossChainQueryReader + CrossChainQueryKeeper {}
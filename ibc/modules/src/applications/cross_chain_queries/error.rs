@@ -0,0 +1,103 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use flex_error::{define_error, TraceError};
+use tendermint_proto::Error as TendermintProtoError;
+
+use crate::{
+	applications::cross_chain_queries::query::QueryId,
+	core::{
+		ics02_client::error as client_error,
+		ics03_connection::error as connection_error,
+		ics24_host::{error::ValidationError, identifier::ConnectionId, path::PathError},
+	},
+	prelude::*,
+	proofs::ProofError,
+	signer::SignerError,
+	Height,
+};
+
+define_error! {
+	#[derive(Debug, PartialEq, Eq)]
+	Error {
+		Ics02Client
+			[ client_error::Error ]
+			| _ | { "ics02 client error" },
+
+		Ics03Connection
+			[ connection_error::Error ]
+			| _ | { "ics03 connection error" },
+
+		Signer
+			[ SignerError ]
+			| _ | { "failed to parse signer" },
+
+		InvalidConnectionId
+			[ ValidationError ]
+			| _ | { "invalid connection identifier" },
+
+		InvalidPath
+			[ PathError ]
+			| _ | { "invalid query path" },
+
+		InvalidProof
+			[ ProofError ]
+			| _ | { "invalid proof" },
+
+		MissingHeight
+			| _ | { "missing height" },
+
+		InvalidTimeout
+			| _ | { "the local timeout of the query has already passed or isn't set" },
+
+		InvalidQueryHeight
+			{ query_height: Height, latest_height: Height }
+			| e | {
+				format_args!("query height {0} is above the latest height {1} of the client",
+					e.query_height, e.latest_height)
+			},
+
+		QueryNotFound
+			{ query_id: QueryId }
+			| e | { format_args!("cross-chain query {} not found", e.query_id) },
+
+		QueryTimedOut
+			{ query_id: QueryId }
+			| e | { format_args!("cross-chain query {} timed out", e.query_id) },
+
+		QueryNotTimedOut
+			{ query_id: QueryId }
+			| e | { format_args!("cross-chain query {} hasn't timed out", e.query_id) },
+
+		FrozenClient
+			{ connection_id: ConnectionId }
+			| e | { format_args!("the client of connection {} is frozen", e.connection_id) },
+
+		ProofVerification
+			{ query_id: QueryId }
+			[ client_error::Error ]
+			| e | { format_args!("failed to verify the proof of the result of cross-chain query {}", e.query_id) },
+
+		DecodeRawMsg
+			[ TraceError<TendermintProtoError> ]
+			| _ | { "error decoding raw msg" },
+
+		UnknownMsgType
+			{ type_url: String }
+			| e | { format_args!("unknown msg type: {0}", e.type_url) },
+
+		ImplementationSpecific
+			{ reason: String }
+			| e | { format_args!("implementation specific error: {}", e.reason) },
+	}
+}
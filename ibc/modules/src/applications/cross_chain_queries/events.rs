@@ -0,0 +1,79 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+	applications::cross_chain_queries::{
+		query::{CrossChainQuery, QueryId, QueryResult},
+		MODULE_ID_STR,
+	},
+	events::{IbcEvent, ModuleEvent},
+	prelude::*,
+};
+
+/// Event type of submitted queries, relayers answer them.
+pub const EVENT_TYPE_QUERY: &str = "cross_chain_query";
+/// Event type of answered or timed out queries.
+pub const EVENT_TYPE_QUERY_RESULT: &str = "cross_chain_query_result";
+
+/// Emitted when a cross-chain query is submitted.
+pub struct QueryEvent {
+	pub query: CrossChainQuery,
+}
+
+impl From<QueryEvent> for IbcEvent {
+	fn from(ev: QueryEvent) -> Self {
+		let CrossChainQuery {
+			id,
+			connection_id,
+			path,
+			query_height,
+			local_timeout_height,
+			local_timeout_timestamp,
+			sender,
+		} = ev.query;
+		IbcEvent::AppModule(ModuleEvent {
+			kind: EVENT_TYPE_QUERY.to_string(),
+			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![
+				("query_id", id).into(),
+				("connection_id", connection_id).into(),
+				("path", path).into(),
+				("query_height", query_height).into(),
+				("local_timeout_height", local_timeout_height).into(),
+				("local_timeout_timestamp", local_timeout_timestamp.nanoseconds()).into(),
+				("sender", sender).into(),
+			],
+		})
+	}
+}
+
+/// Emitted when a cross-chain query is answered or times out.
+pub struct QueryResultEvent {
+	pub query_id: QueryId,
+	pub result: QueryResult,
+}
+
+impl From<QueryResultEvent> for IbcEvent {
+	fn from(ev: QueryResultEvent) -> Self {
+		let result = match ev.result {
+			QueryResult::Success(Some(_)) => "success",
+			QueryResult::Success(None) => "absent",
+			QueryResult::Timeout => "timeout",
+		};
+		IbcEvent::AppModule(ModuleEvent {
+			kind: EVENT_TYPE_QUERY_RESULT.to_string(),
+			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![("query_id", ev.query_id).into(), ("result", result).into()],
+		})
+	}
+}
@@ -0,0 +1,267 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+	applications::cross_chain_queries::{
+		context::CrossChainQueryContext,
+		error::Error,
+		events::{QueryEvent, QueryResultEvent},
+		msgs::{MsgSubmitCrossChainQuery, MsgSubmitCrossChainQueryResult},
+		query::{CrossChainQuery, CrossChainQueryResult, QueryId, QueryResult},
+	},
+	core::{
+		ics02_client::{
+			client_consensus::ConsensusState, client_def::ClientDef, client_state::ClientState,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	handler::HandlerOutputBuilder,
+	prelude::*,
+	timestamp::Timestamp,
+};
+
+/// Stores a new cross-chain query for relayers to answer, returns its identifier.
+pub fn submit_query<Ctx>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgSubmitCrossChainQuery,
+) -> Result<QueryId, Error>
+where
+	Ctx: CrossChainQueryContext + ReaderContext,
+{
+	let connection_end = ctx.connection_end(&msg.connection_id).map_err(Error::ics03_connection)?;
+	let client_id = connection_end.client_id();
+	let client_state = ctx.client_state(client_id).map_err(Error::ics02_client)?;
+	if client_state.is_frozen(ctx, client_id) {
+		return Err(Error::frozen_client(msg.connection_id))
+	}
+	// the proof is verified against the consensus state at the query height, so it can only be
+	// a height the client was updated to
+	let latest_height = client_state.latest_height();
+	if msg.query_height > latest_height {
+		return Err(Error::invalid_query_height(msg.query_height, latest_height))
+	}
+	ctx.consensus_state(client_id, msg.query_height).map_err(Error::ics02_client)?;
+
+	let mut query = CrossChainQuery {
+		id: QueryId::default(),
+		connection_id: msg.connection_id,
+		path: msg.path,
+		query_height: msg.query_height,
+		local_timeout_height: msg.local_timeout_height,
+		local_timeout_timestamp: msg.local_timeout_timestamp,
+		sender: msg.sender,
+	};
+	let no_timeout =
+		query.local_timeout_height.is_zero() && query.local_timeout_timestamp == Timestamp::none();
+	if no_timeout || query.has_timed_out(ctx.host_height(), &ctx.host_timestamp()) {
+		return Err(Error::invalid_timeout())
+	}
+
+	query.id = ctx.query_counter()?.into();
+	ctx.increase_query_counter();
+	ctx.store_cross_chain_query(query.clone())?;
+
+	output.log(format!("submitted cross-chain query {} of path {}", query.id, query.path));
+	output.emit(QueryEvent { query: query.clone() }.into());
+	Ok(query.id)
+}
+
+/// Verifies the proof of the result of a query and stores the result. A query that timed out is
+/// resolved as such regardless of the result.
+pub fn submit_query_result<Ctx>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgSubmitCrossChainQueryResult,
+) -> Result<(), Error>
+where
+	Ctx: CrossChainQueryContext + ReaderContext,
+{
+	let query = ctx.cross_chain_query(msg.query_id)?;
+	if query.has_timed_out(ctx.host_height(), &ctx.host_timestamp()) {
+		return resolve_query(ctx, output, query.id, QueryResult::Timeout)
+	}
+
+	let connection_end =
+		ctx.connection_end(&query.connection_id).map_err(Error::ics03_connection)?;
+	let client_id = connection_end.client_id();
+	let client_state = ctx.client_state(client_id).map_err(Error::ics02_client)?;
+	if client_state.is_frozen(ctx, client_id) {
+		return Err(Error::frozen_client(query.connection_id))
+	}
+	let consensus_state = ctx
+		.consensus_state(client_id, query.query_height)
+		.map_err(Error::ics02_client)?;
+
+	let client_def = client_state.client_def();
+	let prefix = connection_end.counterparty().prefix();
+	match &msg.data {
+		Some(value) => client_def.verify_membership(
+			ctx,
+			client_id,
+			&client_state,
+			query.query_height,
+			prefix,
+			&msg.proof,
+			consensus_state.root(),
+			query.path.clone(),
+			value.clone(),
+		),
+		None => client_def.verify_non_membership(
+			ctx,
+			client_id,
+			&client_state,
+			query.query_height,
+			prefix,
+			&msg.proof,
+			consensus_state.root(),
+			query.path.clone(),
+		),
+	}
+	.map_err(|e| Error::proof_verification(query.id, e))?;
+
+	resolve_query(ctx, output, query.id, QueryResult::Success(msg.data))
+}
+
+/// Resolves a query that wasn't answered before its local timeout.
+pub fn timeout_query<Ctx>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	query_id: QueryId,
+) -> Result<(), Error>
+where
+	Ctx: CrossChainQueryContext + ReaderContext,
+{
+	let query = ctx.cross_chain_query(query_id)?;
+	if !query.has_timed_out(ctx.host_height(), &ctx.host_timestamp()) {
+		return Err(Error::query_not_timed_out(query_id))
+	}
+	resolve_query(ctx, output, query_id, QueryResult::Timeout)
+}
+
+fn resolve_query<Ctx: CrossChainQueryContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	query_id: QueryId,
+	result: QueryResult,
+) -> Result<(), Error> {
+	ctx.delete_cross_chain_query(query_id)?;
+	ctx.store_cross_chain_query_result(CrossChainQueryResult {
+		id: query_id,
+		result: result.clone(),
+	})?;
+
+	output.log(format!("resolved cross-chain query {}: {:?}", query_id, result));
+	output.emit(QueryResultEvent { query_id, result }.into());
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		applications::cross_chain_queries::context::CrossChainQueryReader,
+		core::{
+			ics03_connection::{
+				connection::{ConnectionEnd, State as ConnectionState},
+				msgs::conn_open_init::{
+					test_util::get_dummy_raw_msg_conn_open_init, MsgConnectionOpenInit,
+				},
+				version::get_compatible_versions,
+			},
+			ics24_host::{
+				identifier::{ClientId, ConnectionId},
+				path::ClientStatePath,
+			},
+		},
+		mock::context::{MockClientTypes, MockContext},
+		test_utils::get_dummy_account_id,
+		Height,
+	};
+
+	fn context() -> MockContext<MockClientTypes> {
+		let msg_conn_init =
+			MsgConnectionOpenInit::try_from(get_dummy_raw_msg_conn_open_init()).unwrap();
+		let client_id = ClientId::default();
+		let conn_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			client_id.clone(),
+			msg_conn_init.counterparty,
+			get_compatible_versions(),
+			msg_conn_init.delay_period,
+		);
+		MockContext::default()
+			.with_client(&client_id, Height::new(0, 10))
+			.with_connection(ConnectionId::default(), conn_end)
+	}
+
+	fn msg(local_timeout_height: Height) -> MsgSubmitCrossChainQuery {
+		MsgSubmitCrossChainQuery {
+			connection_id: ConnectionId::default(),
+			path: ClientStatePath(ClientId::default()).into(),
+			query_height: Height::new(0, 10),
+			local_timeout_height,
+			local_timeout_timestamp: Timestamp::none(),
+			sender: get_dummy_account_id(),
+		}
+	}
+
+	fn result(query_id: QueryId) -> MsgSubmitCrossChainQueryResult {
+		MsgSubmitCrossChainQueryResult {
+			query_id,
+			data: Some(b"value".to_vec()),
+			proof: vec![1].try_into().unwrap(),
+			signer: get_dummy_account_id(),
+		}
+	}
+
+	#[test]
+	fn submitted_query_is_answered() {
+		let mut ctx = context();
+		let mut output = HandlerOutputBuilder::new();
+		let query_id = submit_query(&mut ctx, &mut output, msg(Height::new(0, 1000))).unwrap();
+		assert_eq!(ctx.cross_chain_query(query_id).unwrap().id, query_id);
+
+		submit_query_result(&mut ctx, &mut output, result(query_id)).unwrap();
+		assert!(ctx.cross_chain_query(query_id).is_err());
+		assert_eq!(
+			ctx.cross_chain_query_result(query_id).unwrap().result,
+			QueryResult::Success(Some(b"value".to_vec()))
+		);
+		assert_eq!(output.with_result(()).events.len(), 2);
+	}
+
+	#[test]
+	fn query_requires_timeout_and_known_height() {
+		let mut ctx = context();
+		let mut output = HandlerOutputBuilder::new();
+		assert!(submit_query(&mut ctx, &mut output, msg(Height::zero())).is_err());
+
+		let mut above_latest = msg(Height::new(0, 1000));
+		above_latest.query_height = Height::new(0, 11);
+		assert!(submit_query(&mut ctx, &mut output, above_latest).is_err());
+	}
+
+	#[test]
+	fn late_result_times_query_out() {
+		let mut ctx = context();
+		let mut output = HandlerOutputBuilder::new();
+		let timeout_height = ctx.host_height().increment();
+		let query_id = submit_query(&mut ctx, &mut output, msg(timeout_height)).unwrap();
+		assert!(timeout_query(&mut ctx, &mut output, query_id).is_err());
+
+		let mut ctx = ctx.with_height(timeout_height);
+		submit_query_result(&mut ctx, &mut output, result(query_id)).unwrap();
+		assert_eq!(ctx.cross_chain_query_result(query_id).unwrap().result, QueryResult::Timeout);
+	}
+}
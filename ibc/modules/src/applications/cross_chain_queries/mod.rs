@@ -0,0 +1,31 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! ICS 31: Cross-chain Queries lets modules of this chain query the provable state of a
+//! counterparty chain.
+//!
+//! A module submits a [`CrossChainQuery`](query::CrossChainQuery) of a path of the counterparty
+//! store at a given height of a connection. Relayers pick up the pending queries, fetch the value
+//! stored at the path with its proof and submit it with
+//! [`MsgSubmitCrossChainQueryResult`](msgs::MsgSubmitCrossChainQueryResult). The proof is
+//! verified against the consensus state of the connection's client before the result is stored
+//! for the querying module. Queries that weren't answered before their local timeout time out.
+pub mod context;
+pub mod error;
+pub mod events;
+pub mod handler;
+pub mod msgs;
+pub mod query;
+
+/// Module identifier of the ICS31 application, used in its events.
+pub const MODULE_ID_STR: &str = "crosschainqueries";
@@ -0,0 +1,232 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use core::str::FromStr;
+
+use ibc_proto::google::protobuf::Any;
+use tendermint_proto::Protobuf;
+
+use crate::{
+	applications::cross_chain_queries::{error::Error, query::QueryId},
+	core::{
+		ics23_commitment::commitment::CommitmentProofBytes,
+		ics24_host::{identifier::ConnectionId, Path},
+	},
+	prelude::*,
+	signer::Signer,
+	timestamp::Timestamp,
+	tx_msg::Msg,
+	Height,
+};
+use raw::{
+	MsgSubmitCrossChainQuery as RawMsgSubmitCrossChainQuery,
+	MsgSubmitCrossChainQueryResult as RawMsgSubmitCrossChainQueryResult,
+};
+
+pub const SUBMIT_QUERY_TYPE_URL: &str =
+	"/ibc.applications.cross_chain_queries.v1.MsgSubmitCrossChainQuery";
+pub const SUBMIT_QUERY_RESULT_TYPE_URL: &str =
+	"/ibc.applications.cross_chain_queries.v1.MsgSubmitCrossChainQueryResult";
+
+/// Protobuf encoding of the ICS31 types, package `ibc.applications.cross_chain_queries.v1`.
+pub mod raw {
+	use crate::prelude::*;
+	use ibc_proto::ibc::core::client::v1::Height;
+
+	#[derive(Clone, PartialEq, ::prost::Message)]
+	pub struct CrossChainQuery {
+		#[prost(uint64, tag = "1")]
+		pub id: u64,
+		#[prost(string, tag = "2")]
+		pub connection_id: String,
+		#[prost(string, tag = "3")]
+		pub path: String,
+		#[prost(message, optional, tag = "4")]
+		pub query_height: Option<Height>,
+		#[prost(message, optional, tag = "5")]
+		pub local_timeout_height: Option<Height>,
+		#[prost(uint64, tag = "6")]
+		pub local_timeout_timestamp: u64,
+		#[prost(string, tag = "7")]
+		pub sender: String,
+	}
+
+	#[derive(Clone, PartialEq, ::prost::Message)]
+	pub struct CrossChainQueryResult {
+		#[prost(uint64, tag = "1")]
+		pub id: u64,
+		#[prost(bool, tag = "2")]
+		pub timeout: bool,
+		#[prost(bytes = "vec", optional, tag = "3")]
+		pub data: Option<Vec<u8>>,
+	}
+
+	#[derive(Clone, PartialEq, ::prost::Message)]
+	pub struct MsgSubmitCrossChainQuery {
+		#[prost(string, tag = "1")]
+		pub connection_id: String,
+		#[prost(string, tag = "2")]
+		pub path: String,
+		#[prost(message, optional, tag = "3")]
+		pub query_height: Option<Height>,
+		#[prost(message, optional, tag = "4")]
+		pub local_timeout_height: Option<Height>,
+		#[prost(uint64, tag = "5")]
+		pub local_timeout_timestamp: u64,
+		#[prost(string, tag = "6")]
+		pub sender: String,
+	}
+
+	#[derive(Clone, PartialEq, ::prost::Message)]
+	pub struct MsgSubmitCrossChainQueryResult {
+		#[prost(uint64, tag = "1")]
+		pub query_id: u64,
+		#[prost(bytes = "vec", optional, tag = "2")]
+		pub data: Option<Vec<u8>>,
+		#[prost(bytes = "vec", tag = "3")]
+		pub proof: Vec<u8>,
+		#[prost(string, tag = "4")]
+		pub signer: String,
+	}
+}
+
+/// Queries the value stored at a path of the counterparty of a connection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgSubmitCrossChainQuery {
+	pub connection_id: ConnectionId,
+	pub path: Path,
+	/// Height of the counterparty chain the value is queried at, must be known to the client
+	pub query_height: Height,
+	/// Height of this chain after which the query times out, zero if none
+	pub local_timeout_height: Height,
+	/// Timestamp of this chain after which the query times out, none if none
+	pub local_timeout_timestamp: Timestamp,
+	pub sender: Signer,
+}
+
+impl Msg for MsgSubmitCrossChainQuery {
+	type ValidationError = Error;
+	type Raw = RawMsgSubmitCrossChainQuery;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		SUBMIT_QUERY_TYPE_URL.to_string()
+	}
+}
+
+impl Protobuf<RawMsgSubmitCrossChainQuery> for MsgSubmitCrossChainQuery {}
+
+impl TryFrom<RawMsgSubmitCrossChainQuery> for MsgSubmitCrossChainQuery {
+	type Error = Error;
+
+	fn try_from(raw: RawMsgSubmitCrossChainQuery) -> Result<Self, Self::Error> {
+		Ok(MsgSubmitCrossChainQuery {
+			connection_id: raw.connection_id.parse().map_err(Error::invalid_connection_id)?,
+			path: Path::from_str(&raw.path).map_err(Error::invalid_path)?,
+			query_height: raw.query_height.ok_or_else(Error::missing_height)?.into(),
+			local_timeout_height: raw.local_timeout_height.map(Height::from).unwrap_or_default(),
+			local_timeout_timestamp: Timestamp::from_nanoseconds(raw.local_timeout_timestamp)
+				.map_err(|_| Error::invalid_timeout())?,
+			sender: raw.sender.parse().map_err(Error::signer)?,
+		})
+	}
+}
+
+impl From<MsgSubmitCrossChainQuery> for RawMsgSubmitCrossChainQuery {
+	fn from(msg: MsgSubmitCrossChainQuery) -> Self {
+		RawMsgSubmitCrossChainQuery {
+			connection_id: msg.connection_id.to_string(),
+			path: msg.path.to_string(),
+			query_height: Some(msg.query_height.into()),
+			local_timeout_height: Some(msg.local_timeout_height.into()),
+			local_timeout_timestamp: msg.local_timeout_timestamp.nanoseconds(),
+			sender: msg.sender.to_string(),
+		}
+	}
+}
+
+impl TryFrom<Any> for MsgSubmitCrossChainQuery {
+	type Error = Error;
+
+	fn try_from(raw: Any) -> Result<Self, Self::Error> {
+		match raw.type_url.as_str() {
+			SUBMIT_QUERY_TYPE_URL =>
+				MsgSubmitCrossChainQuery::decode_vec(&raw.value).map_err(Error::decode_raw_msg),
+			_ => Err(Error::unknown_msg_type(raw.type_url)),
+		}
+	}
+}
+
+/// Answers a cross-chain query with the value stored at its path, proven at its query height.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgSubmitCrossChainQueryResult {
+	pub query_id: QueryId,
+	/// The value stored at the path, `None` if the proof is a proof of absence
+	pub data: Option<Vec<u8>>,
+	pub proof: CommitmentProofBytes,
+	pub signer: Signer,
+}
+
+impl Msg for MsgSubmitCrossChainQueryResult {
+	type ValidationError = Error;
+	type Raw = RawMsgSubmitCrossChainQueryResult;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		SUBMIT_QUERY_RESULT_TYPE_URL.to_string()
+	}
+}
+
+impl Protobuf<RawMsgSubmitCrossChainQueryResult> for MsgSubmitCrossChainQueryResult {}
+
+impl TryFrom<RawMsgSubmitCrossChainQueryResult> for MsgSubmitCrossChainQueryResult {
+	type Error = Error;
+
+	fn try_from(raw: RawMsgSubmitCrossChainQueryResult) -> Result<Self, Self::Error> {
+		Ok(MsgSubmitCrossChainQueryResult {
+			query_id: raw.query_id.into(),
+			data: raw.data,
+			proof: raw.proof.try_into().map_err(Error::invalid_proof)?,
+			signer: raw.signer.parse().map_err(Error::signer)?,
+		})
+	}
+}
+
+impl From<MsgSubmitCrossChainQueryResult> for RawMsgSubmitCrossChainQueryResult {
+	fn from(msg: MsgSubmitCrossChainQueryResult) -> Self {
+		RawMsgSubmitCrossChainQueryResult {
+			query_id: msg.query_id.into(),
+			data: msg.data,
+			proof: msg.proof.into(),
+			signer: msg.signer.to_string(),
+		}
+	}
+}
+
+impl TryFrom<Any> for MsgSubmitCrossChainQueryResult {
+	type Error = Error;
+
+	fn try_from(raw: Any) -> Result<Self, Self::Error> {
+		match raw.type_url.as_str() {
+			SUBMIT_QUERY_RESULT_TYPE_URL => MsgSubmitCrossChainQueryResult::decode_vec(&raw.value)
+				.map_err(Error::decode_raw_msg),
+			_ => Err(Error::unknown_msg_type(raw.type_url)),
+		}
+	}
+}
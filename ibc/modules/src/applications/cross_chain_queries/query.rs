@@ -0,0 +1,193 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use core::{
+	fmt::{Display, Formatter},
+	str::FromStr,
+};
+
+use ibc_proto::ibc::core::client::v1::Height as RawHeight;
+use tendermint_proto::Protobuf;
+
+use crate::{
+	applications::cross_chain_queries::{
+		error::Error,
+		msgs::raw::{
+			CrossChainQuery as RawCrossChainQuery,
+			CrossChainQueryResult as RawCrossChainQueryResult,
+		},
+	},
+	core::ics24_host::{identifier::ConnectionId, Path},
+	prelude::*,
+	signer::Signer,
+	timestamp::{Expiry, Timestamp},
+	Height,
+};
+
+/// Identifier of a cross-chain query, assigned sequentially by the querying chain.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QueryId(u64);
+
+impl QueryId {
+	pub fn value(&self) -> u64 {
+		self.0
+	}
+}
+
+impl From<u64> for QueryId {
+	fn from(id: u64) -> Self {
+		Self(id)
+	}
+}
+
+impl From<QueryId> for u64 {
+	fn from(id: QueryId) -> Self {
+		id.0
+	}
+}
+
+impl Display for QueryId {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+/// A query of the value stored at `path` in the provable store of the counterparty of a
+/// connection, at `query_height`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrossChainQuery {
+	pub id: QueryId,
+	pub connection_id: ConnectionId,
+	pub path: Path,
+	/// Height of the counterparty chain the value is queried at
+	pub query_height: Height,
+	/// Height of this chain after which the query times out, zero if none
+	pub local_timeout_height: Height,
+	/// Timestamp of this chain after which the query times out, none if none
+	pub local_timeout_timestamp: Timestamp,
+	pub sender: Signer,
+}
+
+impl CrossChainQuery {
+	/// Returns true iff the query timed out at the given height and timestamp of this chain.
+	pub fn has_timed_out(&self, host_height: Height, host_timestamp: &Timestamp) -> bool {
+		(self.local_timeout_height != Height::zero() && self.local_timeout_height <= host_height) ||
+			(self.local_timeout_timestamp != Timestamp::none() &&
+				host_timestamp.check_expiry(&self.local_timeout_timestamp) == Expiry::Expired)
+	}
+}
+
+impl Protobuf<RawCrossChainQuery> for CrossChainQuery {}
+
+impl TryFrom<RawCrossChainQuery> for CrossChainQuery {
+	type Error = Error;
+
+	fn try_from(raw: RawCrossChainQuery) -> Result<Self, Self::Error> {
+		Ok(Self {
+			id: raw.id.into(),
+			connection_id: raw.connection_id.parse().map_err(Error::invalid_connection_id)?,
+			path: Path::from_str(&raw.path).map_err(Error::invalid_path)?,
+			query_height: raw.query_height.ok_or_else(Error::missing_height)?.into(),
+			local_timeout_height: raw.local_timeout_height.map(Height::from).unwrap_or_default(),
+			local_timeout_timestamp: Timestamp::from_nanoseconds(raw.local_timeout_timestamp)
+				.map_err(|_| Error::invalid_timeout())?,
+			sender: raw.sender.parse().map_err(Error::signer)?,
+		})
+	}
+}
+
+impl From<CrossChainQuery> for RawCrossChainQuery {
+	fn from(query: CrossChainQuery) -> Self {
+		Self {
+			id: query.id.into(),
+			connection_id: query.connection_id.to_string(),
+			path: query.path.to_string(),
+			query_height: Some(query.query_height.into()),
+			local_timeout_height: Some(RawHeight::from(query.local_timeout_height)),
+			local_timeout_timestamp: query.local_timeout_timestamp.nanoseconds(),
+			sender: query.sender.to_string(),
+		}
+	}
+}
+
+/// Outcome of a cross-chain query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryResult {
+	/// The value stored at the path, `None` if it was proven that nothing is stored there
+	Success(Option<Vec<u8>>),
+	/// The query wasn't answered before its local timeout
+	Timeout,
+}
+
+/// The result of a cross-chain query, stored for the querying module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrossChainQueryResult {
+	pub id: QueryId,
+	pub result: QueryResult,
+}
+
+impl Protobuf<RawCrossChainQueryResult> for CrossChainQueryResult {}
+
+impl TryFrom<RawCrossChainQueryResult> for CrossChainQueryResult {
+	type Error = Error;
+
+	fn try_from(raw: RawCrossChainQueryResult) -> Result<Self, Self::Error> {
+		let result = match (raw.timeout, raw.data) {
+			(true, _) => QueryResult::Timeout,
+			(false, data) => QueryResult::Success(data),
+		};
+		Ok(Self { id: raw.id.into(), result })
+	}
+}
+
+impl From<CrossChainQueryResult> for RawCrossChainQueryResult {
+	fn from(result: CrossChainQueryResult) -> Self {
+		let (timeout, data) = match result.result {
+			QueryResult::Success(data) => (false, data),
+			QueryResult::Timeout => (true, None),
+		};
+		Self { id: result.id.into(), timeout, data }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::ics24_host::path::ClientStatePath;
+
+	fn query() -> CrossChainQuery {
+		CrossChainQuery {
+			id: 7.into(),
+			connection_id: ConnectionId::new(0),
+			path: ClientStatePath("07-tendermint-0".parse().unwrap()).into(),
+			query_height: Height::new(0, 10),
+			local_timeout_height: Height::new(0, 20),
+			local_timeout_timestamp: Timestamp::none(),
+			sender: "sender".parse().unwrap(),
+		}
+	}
+
+	#[test]
+	fn query_round_trips_through_proto() {
+		let query = query();
+		let decoded = CrossChainQuery::decode_vec(&query.clone().encode_vec().unwrap()).unwrap();
+		assert_eq!(query, decoded);
+	}
+
+	#[test]
+	fn query_times_out_at_local_timeout_height() {
+		let query = query();
+		assert!(!query.has_timed_out(Height::new(0, 19), &Timestamp::now()));
+		assert!(query.has_timed_out(Height::new(0, 20), &Timestamp::now()));
+	}
+}
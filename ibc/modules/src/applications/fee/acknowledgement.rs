@@ -0,0 +1,81 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Acknowledgements written by the fee middleware on fee enabled channels.
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use subtle_encoding::base64;
+
+use super::error::Error;
+use crate::{
+	core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement,
+	prelude::*,
+};
+
+/// Wraps the acknowledgement of the underlying application with the address of the relayer that
+/// delivered the packet, which is paid the recv fee once the acknowledgement is relayed back.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncentivizedAcknowledgement {
+	#[serde(serialize_with = "ser_base64", deserialize_with = "deser_base64")]
+	pub app_acknowledgement: Vec<u8>,
+	/// Counterparty payee of the relayer that delivered the packet, or the relayer itself
+	pub forward_relayer_address: String,
+	pub underlying_app_success: bool,
+}
+
+impl IncentivizedAcknowledgement {
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+		serde_json::from_slice(bytes).map_err(|_| Error::ack_deserialization())
+	}
+
+	pub fn app_acknowledgement(&self) -> GenericAcknowledgement {
+		GenericAcknowledgement::from_bytes(self.app_acknowledgement.clone())
+	}
+}
+
+impl From<IncentivizedAcknowledgement> for GenericAcknowledgement {
+	fn from(ack: IncentivizedAcknowledgement) -> Self {
+		GenericAcknowledgement::from_bytes(
+			serde_json::to_vec(&ack)
+				.expect("IncentivizedAcknowledgement's infallible Serialize impl failed"),
+		)
+	}
+}
+
+fn ser_base64<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+	let encoded = String::from_utf8(base64::encode(bytes)).map_err(serde::ser::Error::custom)?;
+	serializer.serialize_str(&encoded)
+}
+
+fn deser_base64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+	let encoded = String::deserialize(deserializer)?;
+	base64::decode(encoded).map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn incentivized_ack_round_trips() {
+		let ack = IncentivizedAcknowledgement {
+			app_acknowledgement: br#"{"result":"AQ=="}"#.to_vec(),
+			forward_relayer_address: "relayer".to_string(),
+			underlying_app_success: true,
+		};
+		let generic: GenericAcknowledgement = ack.clone().into();
+		assert_eq!(IncentivizedAcknowledgement::from_bytes(generic.as_ref()).unwrap(), ack);
+		assert_eq!(ack.app_acknowledgement().as_ref(), br#"{"result":"AQ=="}"#);
+	}
+}
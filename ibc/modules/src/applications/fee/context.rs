@@ -0,0 +1,333 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Context of the fee middleware and the channel and packet callbacks it runs around the ones of
+//! the application it wraps.
+
+use super::{
+	acknowledgement::IncentivizedAcknowledgement, error::Error, events::DistributeFeeEvent,
+	metadata::Metadata, PacketFee,
+};
+use crate::{
+	applications::transfer::{context::BankKeeper, PrefixedCoin},
+	core::{
+		ics04_channel::{
+			context::ChannelReader,
+			msgs::acknowledgement::Acknowledgement as GenericAcknowledgement,
+			packet::{Packet, PacketId},
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, PortId},
+		ics26_routing::context::ModuleOutputBuilder,
+	},
+	prelude::*,
+	signer::Signer,
+};
+
+pub trait FeeReader: ChannelReader {
+	type AccountId: TryFrom<Signer>;
+
+	/// Returns true iff the channel negotiated the fee middleware.
+	fn is_fee_enabled(&self, port_id: &PortId, channel_id: &ChannelId) -> bool;
+
+	/// Returns the fees escrowed for a packet.
+	fn packet_fees(&self, packet_id: &PacketId) -> Vec<PacketFee>;
+
+	/// Returns the packets sent on a channel that have fees escrowed.
+	fn incentivized_packets(&self, port_id: &PortId, channel_id: &ChannelId) -> Vec<PacketId>;
+
+	/// Returns the account the ack and timeout fees of a relayer are paid to on a channel.
+	fn payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<Signer>;
+
+	/// Returns the counterparty chain address the recv fees of a relayer are paid to on a
+	/// channel.
+	fn counterparty_payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<Signer>;
+
+	/// Returns the account holding the escrowed fees.
+	fn fee_escrow_address(&self) -> Result<<Self as FeeReader>::AccountId, Error>;
+}
+
+pub trait FeeKeeper: BankKeeper<AccountId = <Self as FeeKeeper>::AccountId> {
+	type AccountId;
+
+	fn set_fee_enabled(&mut self, port_id: &PortId, channel_id: &ChannelId);
+
+	fn delete_fee_enabled(&mut self, port_id: &PortId, channel_id: &ChannelId);
+
+	/// Replaces the fees escrowed for a packet.
+	fn store_packet_fees(
+		&mut self,
+		packet_id: PacketId,
+		packet_fees: Vec<PacketFee>,
+	) -> Result<(), Error>;
+
+	fn delete_packet_fees(&mut self, packet_id: &PacketId);
+
+	fn store_payee(
+		&mut self,
+		relayer: Signer,
+		channel_id: ChannelId,
+		payee: Signer,
+	) -> Result<(), Error>;
+
+	fn store_counterparty_payee(
+		&mut self,
+		relayer: Signer,
+		channel_id: ChannelId,
+		counterparty_payee: Signer,
+	) -> Result<(), Error>;
+}
+
+/// Captures all the dependencies which the fee middleware requires to escrow and distribute
+/// packet fees.
+pub trait FeeContext:
+	FeeKeeper<AccountId = <Self as FeeContext>::AccountId>
+	+ FeeReader<AccountId = <Self as FeeContext>::AccountId>
+{
+	type AccountId: TryFrom<Signer>;
+}
+
+/// Enables fees on the channel if the version is fee metadata and returns the version the wrapped
+/// application opens the channel with.
+pub fn on_chan_open_init(
+	ctx: &mut impl FeeContext,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	version: &Version,
+) -> Result<Version, Error> {
+	match Metadata::from_version(version)? {
+		Some(metadata) => {
+			ctx.set_fee_enabled(port_id, channel_id);
+			Ok(metadata.app_version())
+		},
+		None => Ok(version.clone()),
+	}
+}
+
+/// Enables fees on the channel if the counterparty version is fee metadata and returns the
+/// counterparty version of the wrapped application. The version the wrapped application agrees to
+/// must be passed through [`channel_version`] afterwards.
+pub fn on_chan_open_try(
+	ctx: &mut impl FeeContext,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	counterparty_version: &Version,
+) -> Result<Version, Error> {
+	on_chan_open_init(ctx, port_id, channel_id, counterparty_version)
+}
+
+/// Returns the version of the channel for the given version of the wrapped application.
+pub fn channel_version(
+	ctx: &impl FeeContext,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	app_version: &Version,
+) -> Version {
+	if ctx.is_fee_enabled(port_id, channel_id) {
+		Metadata::new(app_version).into()
+	} else {
+		app_version.clone()
+	}
+}
+
+/// Checks that the counterparty agreed to enable fees and returns its version of the wrapped
+/// application.
+pub fn on_chan_open_ack(
+	ctx: &mut impl FeeContext,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	counterparty_version: &Version,
+) -> Result<Version, Error> {
+	if !ctx.is_fee_enabled(port_id, channel_id) {
+		return Ok(counterparty_version.clone())
+	}
+	Metadata::from_version(counterparty_version)?
+		.map(|metadata| metadata.app_version())
+		.ok_or_else(|| Error::invalid_metadata(counterparty_version.to_string()))
+}
+
+/// Refunds the fees of all the packets of a closing channel.
+pub fn on_chan_close(
+	ctx: &mut impl FeeContext,
+	output: &mut ModuleOutputBuilder,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+) -> Result<(), Error> {
+	if !ctx.is_fee_enabled(port_id, channel_id) {
+		return Ok(())
+	}
+	for packet_id in ctx.incentivized_packets(port_id, channel_id) {
+		for packet_fee in ctx.packet_fees(&packet_id) {
+			let fee = packet_fee.fee.total().cloned().collect::<Vec<_>>();
+			distribute_fee(
+				ctx,
+				output,
+				&packet_fee.refund_address,
+				&packet_fee.refund_address,
+				fee,
+			);
+		}
+		ctx.delete_packet_fees(&packet_id);
+	}
+	ctx.delete_fee_enabled(port_id, channel_id);
+	Ok(())
+}
+
+/// Splits a version proposed for an upgraded channel into the version of the wrapped application
+/// and whether fees are to be enabled on the upgraded channel.
+pub fn upgrade_app_version(version: &Version) -> Result<(Version, bool), Error> {
+	Ok(match Metadata::from_version(version)? {
+		Some(metadata) => (metadata.app_version(), true),
+		None => (version.clone(), false),
+	})
+}
+
+/// Enables or disables fees on a channel once its upgrade is open, refunding the fees of pending
+/// packets when they are disabled, and returns the version of the wrapped application.
+pub fn on_chan_upgrade_open(
+	ctx: &mut impl FeeContext,
+	output: &mut ModuleOutputBuilder,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	version: &Version,
+) -> Result<Version, Error> {
+	let (app_version, fee_enabled) = upgrade_app_version(version)?;
+	if fee_enabled {
+		ctx.set_fee_enabled(port_id, channel_id);
+	} else {
+		on_chan_close(ctx, output, port_id, channel_id)?;
+	}
+	Ok(app_version)
+}
+
+/// Wraps the acknowledgement of the wrapped application in an [`IncentivizedAcknowledgement`]
+/// recording who the recv fee is owed to.
+pub fn on_recv_packet(
+	ctx: &impl FeeContext,
+	packet: &Packet,
+	relayer: &Signer,
+	app_acknowledgement: GenericAcknowledgement,
+	app_success: bool,
+) -> GenericAcknowledgement {
+	if !ctx.is_fee_enabled(&packet.destination_port, &packet.destination_channel) {
+		return app_acknowledgement
+	}
+	let forward_relayer = ctx
+		.counterparty_payee(relayer, &packet.destination_channel)
+		.unwrap_or_else(|| relayer.clone());
+	IncentivizedAcknowledgement {
+		app_acknowledgement: app_acknowledgement.into_bytes(),
+		forward_relayer_address: forward_relayer.to_string(),
+		underlying_app_success: app_success,
+	}
+	.into()
+}
+
+/// Pays the recv fee to the forward relayer, the ack fee to the relayer and refunds the timeout
+/// fee. Returns the acknowledgement of the wrapped application.
+pub fn on_acknowledgement_packet(
+	ctx: &mut impl FeeContext,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	acknowledgement: &GenericAcknowledgement,
+	relayer: &Signer,
+) -> Result<GenericAcknowledgement, Error> {
+	if !ctx.is_fee_enabled(&packet.source_port, &packet.source_channel) {
+		return Ok(acknowledgement.clone())
+	}
+	let acknowledgement = IncentivizedAcknowledgement::from_bytes(acknowledgement.as_ref())?;
+	let packet_id = packet.id();
+	let packet_fees = ctx.packet_fees(&packet_id);
+	if packet_fees.is_empty() {
+		return Ok(acknowledgement.app_acknowledgement())
+	}
+
+	let payee = ctx.payee(relayer, &packet.source_channel).unwrap_or_else(|| relayer.clone());
+	// the forward relayer address was written by the counterparty chain, an invalid one gets the
+	// recv fee refunded
+	let forward_relayer = acknowledgement.forward_relayer_address.parse::<Signer>().ok();
+	for packet_fee in packet_fees {
+		let PacketFee { fee, refund_address, .. } = packet_fee;
+		let recv_fee_receiver = forward_relayer.as_ref().unwrap_or(&refund_address).clone();
+		distribute_fee(ctx, output, &recv_fee_receiver, &refund_address, fee.recv_fee);
+		distribute_fee(ctx, output, &payee, &refund_address, fee.ack_fee);
+		distribute_fee(ctx, output, &refund_address, &refund_address, fee.timeout_fee);
+	}
+	ctx.delete_packet_fees(&packet_id);
+
+	Ok(acknowledgement.app_acknowledgement())
+}
+
+/// Pays the timeout fee to the relayer and refunds the recv and ack fees.
+pub fn on_timeout_packet(
+	ctx: &mut impl FeeContext,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	relayer: &Signer,
+) -> Result<(), Error> {
+	if !ctx.is_fee_enabled(&packet.source_port, &packet.source_channel) {
+		return Ok(())
+	}
+	let packet_id = packet.id();
+	let payee = ctx.payee(relayer, &packet.source_channel).unwrap_or_else(|| relayer.clone());
+	for packet_fee in ctx.packet_fees(&packet_id) {
+		let PacketFee { fee, refund_address, .. } = packet_fee;
+		let refund = fee.recv_fee.into_iter().chain(fee.ack_fee).collect();
+		distribute_fee(ctx, output, &refund_address, &refund_address, refund);
+		distribute_fee(ctx, output, &payee, &refund_address, fee.timeout_fee);
+	}
+	ctx.delete_packet_fees(&packet_id);
+	Ok(())
+}
+
+/// Pays out escrowed coins. Paying a fee must not fail the packet lifecycle, so coins that can't
+/// be paid to the receiver are refunded, and coins that can't be refunded either stay escrowed.
+fn distribute_fee(
+	ctx: &mut impl FeeContext,
+	output: &mut ModuleOutputBuilder,
+	receiver: &Signer,
+	refund_address: &Signer,
+	fee: Vec<PrefixedCoin>,
+) {
+	if fee.is_empty() {
+		return
+	}
+	let escrow = match ctx.fee_escrow_address() {
+		Ok(escrow) => escrow,
+		Err(e) => {
+			output.log(format!("failed to get the fee escrow address: {e}"));
+			return
+		},
+	};
+	for coin in fee {
+		let paid = send_coin(ctx, &escrow, receiver, &coin)
+			.or_else(|_| send_coin(ctx, &escrow, refund_address, &coin));
+		match paid {
+			Ok(receiver) => output.emit(DistributeFeeEvent { receiver, fee: vec![coin] }.into()),
+			Err(e) => output.log(format!("failed to distribute fee {coin}: {e}")),
+		}
+	}
+}
+
+fn send_coin<Ctx: FeeContext>(
+	ctx: &mut Ctx,
+	escrow: &<Ctx as FeeContext>::AccountId,
+	receiver: &Signer,
+	coin: &PrefixedCoin,
+) -> Result<Signer, Error> {
+	let account = <Ctx as FeeContext>::AccountId::try_from(receiver.clone())
+		.map_err(|_| Error::parse_account_failure())?;
+	ctx.send_coins(escrow, &account, coin).map_err(Error::ics20)?;
+	Ok(receiver.clone())
+}
@@ -0,0 +1,113 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	applications::{fee::MODULE_ID_STR, transfer::PrefixedCoin},
+	core::{ics04_channel::packet::PacketId, ics24_host::identifier::ChannelId},
+	events::{IbcEvent, ModuleEvent},
+	prelude::*,
+	signer::Signer,
+};
+
+const EVENT_TYPE_INCENTIVIZED_PACKET: &str = "incentivized_ibc_packet";
+const EVENT_TYPE_REGISTER_PAYEE: &str = "register_payee";
+const EVENT_TYPE_REGISTER_COUNTERPARTY_PAYEE: &str = "register_counterparty_payee";
+const EVENT_TYPE_DISTRIBUTE_FEE: &str = "distribute_fee";
+
+fn coins_to_string(coins: &[PrefixedCoin]) -> String {
+	coins.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+}
+
+pub struct IncentivizedPacketEvent {
+	pub packet_id: PacketId,
+	pub recv_fee: Vec<PrefixedCoin>,
+	pub ack_fee: Vec<PrefixedCoin>,
+	pub timeout_fee: Vec<PrefixedCoin>,
+}
+
+impl From<IncentivizedPacketEvent> for IbcEvent {
+	fn from(ev: IncentivizedPacketEvent) -> Self {
+		let IncentivizedPacketEvent { packet_id, recv_fee, ack_fee, timeout_fee } = ev;
+		IbcEvent::AppModule(ModuleEvent {
+			kind: EVENT_TYPE_INCENTIVIZED_PACKET.to_string(),
+			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![
+				("port_id", packet_id.port_id).into(),
+				("channel_id", packet_id.channel_id).into(),
+				("packet_sequence", packet_id.sequence).into(),
+				("recv_fee", coins_to_string(&recv_fee)).into(),
+				("ack_fee", coins_to_string(&ack_fee)).into(),
+				("timeout_fee", coins_to_string(&timeout_fee)).into(),
+			],
+		})
+	}
+}
+
+pub struct RegisterPayeeEvent {
+	pub relayer: Signer,
+	pub payee: Signer,
+	pub channel_id: ChannelId,
+}
+
+impl From<RegisterPayeeEvent> for IbcEvent {
+	fn from(ev: RegisterPayeeEvent) -> Self {
+		let RegisterPayeeEvent { relayer, payee, channel_id } = ev;
+		IbcEvent::AppModule(ModuleEvent {
+			kind: EVENT_TYPE_REGISTER_PAYEE.to_string(),
+			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![
+				("relayer", relayer).into(),
+				("payee", payee).into(),
+				("channel_id", channel_id).into(),
+			],
+		})
+	}
+}
+
+pub struct RegisterCounterpartyPayeeEvent {
+	pub relayer: Signer,
+	pub counterparty_payee: Signer,
+	pub channel_id: ChannelId,
+}
+
+impl From<RegisterCounterpartyPayeeEvent> for IbcEvent {
+	fn from(ev: RegisterCounterpartyPayeeEvent) -> Self {
+		let RegisterCounterpartyPayeeEvent { relayer, counterparty_payee, channel_id } = ev;
+		IbcEvent::AppModule(ModuleEvent {
+			kind: EVENT_TYPE_REGISTER_COUNTERPARTY_PAYEE.to_string(),
+			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![
+				("relayer", relayer).into(),
+				("counterparty_payee", counterparty_payee).into(),
+				("channel_id", channel_id).into(),
+			],
+		})
+	}
+}
+
+pub struct DistributeFeeEvent {
+	pub receiver: Signer,
+	pub fee: Vec<PrefixedCoin>,
+}
+
+impl From<DistributeFeeEvent> for IbcEvent {
+	fn from(ev: DistributeFeeEvent) -> Self {
+		let DistributeFeeEvent { receiver, fee } = ev;
+		IbcEvent::AppModule(ModuleEvent {
+			kind: EVENT_TYPE_DISTRIBUTE_FEE.to_string(),
+			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![("receiver", receiver).into(), ("fee", coins_to_string(&fee)).into()],
+		})
+	}
+}
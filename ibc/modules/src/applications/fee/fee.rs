@@ -0,0 +1,109 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fee types escrowed for incentivized packets.
+
+use ibc_proto::{
+	cosmos::base::v1beta1::Coin as RawCoin,
+	ibc::applications::fee::v1::{Fee as RawFee, PacketFee as RawPacketFee},
+};
+
+use super::error::Error;
+use crate::{applications::transfer::PrefixedCoin, prelude::*, signer::Signer};
+
+/// Fees paid to the relayers of a packet.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Fee {
+	/// Paid to the relayer that delivers the packet to the destination chain
+	pub recv_fee: Vec<PrefixedCoin>,
+	/// Paid to the relayer that delivers the acknowledgement back to the source chain
+	pub ack_fee: Vec<PrefixedCoin>,
+	/// Paid to the relayer that delivers the timeout back to the source chain
+	pub timeout_fee: Vec<PrefixedCoin>,
+}
+
+impl Fee {
+	pub fn is_empty(&self) -> bool {
+		self.recv_fee.is_empty() && self.ack_fee.is_empty() && self.timeout_fee.is_empty()
+	}
+
+	/// All the coins that are escrowed for the fee.
+	pub fn total(&self) -> impl Iterator<Item = &PrefixedCoin> {
+		self.recv_fee.iter().chain(self.ack_fee.iter()).chain(self.timeout_fee.iter())
+	}
+}
+
+fn parse_coins(coins: Vec<RawCoin>) -> Result<Vec<PrefixedCoin>, Error> {
+	coins
+		.into_iter()
+		.map(|coin| PrefixedCoin::try_from(coin).map_err(Error::ics20))
+		.collect()
+}
+
+impl TryFrom<RawFee> for Fee {
+	type Error = Error;
+
+	fn try_from(raw: RawFee) -> Result<Self, Self::Error> {
+		Ok(Fee {
+			recv_fee: parse_coins(raw.recv_fee)?,
+			ack_fee: parse_coins(raw.ack_fee)?,
+			timeout_fee: parse_coins(raw.timeout_fee)?,
+		})
+	}
+}
+
+impl From<Fee> for RawFee {
+	fn from(fee: Fee) -> Self {
+		RawFee {
+			recv_fee: fee.recv_fee.into_iter().map(Into::into).collect(),
+			ack_fee: fee.ack_fee.into_iter().map(Into::into).collect(),
+			timeout_fee: fee.timeout_fee.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// A fee escrowed for a packet, together with the account refunded with the unused part of it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PacketFee {
+	pub fee: Fee,
+	pub refund_address: Signer,
+	/// Relayers allowed to claim the fee, unrestricted when empty. Not supported yet.
+	pub relayers: Vec<Signer>,
+}
+
+impl TryFrom<RawPacketFee> for PacketFee {
+	type Error = Error;
+
+	fn try_from(raw: RawPacketFee) -> Result<Self, Self::Error> {
+		Ok(PacketFee {
+			fee: raw.fee.ok_or_else(Error::missing_fee)?.try_into()?,
+			refund_address: raw.refund_address.parse().map_err(Error::signer)?,
+			relayers: raw
+				.relayers
+				.into_iter()
+				.map(|relayer| relayer.parse().map_err(Error::signer))
+				.collect::<Result<_, _>>()?,
+		})
+	}
+}
+
+impl From<PacketFee> for RawPacketFee {
+	fn from(packet_fee: PacketFee) -> Self {
+		RawPacketFee {
+			fee: Some(packet_fee.fee.into()),
+			refund_address: packet_fee.refund_address.to_string(),
+			relayers: packet_fee.relayers.iter().map(ToString::to_string).collect(),
+		}
+	}
+}
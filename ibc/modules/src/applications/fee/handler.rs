@@ -0,0 +1,152 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Handlers of the fee middleware messages.
+//! If a handler returns an error, the runtime is expected to rollback all state modifications to
+//! the `Ctx` caused by all messages from the transaction that the message is a part of.
+
+use super::{
+	context::FeeContext,
+	error::Error,
+	events::{IncentivizedPacketEvent, RegisterCounterpartyPayeeEvent, RegisterPayeeEvent},
+	msgs::{
+		pay_packet_fee::{MsgPayPacketFee, MsgPayPacketFeeAsync},
+		register_payee::{MsgRegisterCounterpartyPayee, MsgRegisterPayee},
+	},
+	PacketFee,
+};
+use crate::{
+	core::{
+		ics04_channel::packet::PacketId,
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	handler::HandlerOutputBuilder,
+	prelude::*,
+};
+
+fn ensure_fee_enabled(
+	ctx: &impl FeeContext,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+) -> Result<(), Error> {
+	ctx.channel_end(&(port_id.clone(), *channel_id)).map_err(Error::ics04_channel)?;
+	if !ctx.is_fee_enabled(port_id, channel_id) {
+		return Err(Error::fee_not_enabled(port_id.clone(), *channel_id))
+	}
+	Ok(())
+}
+
+/// Escrows the fee and records it for the packet.
+fn escrow_packet_fee<Ctx: FeeContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	packet_id: PacketId,
+	packet_fee: PacketFee,
+) -> Result<(), Error> {
+	if packet_fee.fee.is_empty() {
+		return Err(Error::empty_fee())
+	}
+	if !packet_fee.relayers.is_empty() {
+		return Err(Error::relayers_not_supported())
+	}
+	let payer = <Ctx as FeeContext>::AccountId::try_from(packet_fee.refund_address.clone())
+		.map_err(|_| Error::parse_account_failure())?;
+	let escrow = ctx.fee_escrow_address()?;
+	for coin in packet_fee.fee.total() {
+		ctx.send_coins(&payer, &escrow, coin).map_err(Error::ics20)?;
+	}
+
+	let event = IncentivizedPacketEvent {
+		packet_id: packet_id.clone(),
+		recv_fee: packet_fee.fee.recv_fee.clone(),
+		ack_fee: packet_fee.fee.ack_fee.clone(),
+		timeout_fee: packet_fee.fee.timeout_fee.clone(),
+	};
+	let mut packet_fees = ctx.packet_fees(&packet_id);
+	packet_fees.push(packet_fee);
+	ctx.store_packet_fees(packet_id.clone(), packet_fees)?;
+
+	output.log(format!("escrowed fee for packet {packet_id}"));
+	output.emit(event.into());
+	Ok(())
+}
+
+/// Escrows a fee for the next packet sent on the channel.
+pub fn pay_packet_fee(
+	ctx: &mut impl FeeContext,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgPayPacketFee,
+) -> Result<(), Error> {
+	ensure_fee_enabled(ctx, &msg.source_port_id, &msg.source_channel_id)?;
+	let sequence = ctx
+		.get_next_sequence_send(&(msg.source_port_id.clone(), msg.source_channel_id))
+		.map_err(Error::ics04_channel)?;
+	let packet_id = PacketId::new(msg.source_port_id, msg.source_channel_id, sequence);
+	let packet_fee = PacketFee { fee: msg.fee, refund_address: msg.signer, relayers: msg.relayers };
+	escrow_packet_fee(ctx, output, packet_id, packet_fee)
+}
+
+/// Escrows a fee for a packet whose lifecycle isn't complete yet.
+pub fn pay_packet_fee_async(
+	ctx: &mut impl FeeContext,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgPayPacketFeeAsync,
+) -> Result<(), Error> {
+	let PacketId { port_id, channel_id, sequence } = &msg.packet_id;
+	ensure_fee_enabled(ctx, port_id, channel_id)?;
+	ctx.get_packet_commitment(&(port_id.clone(), *channel_id, *sequence))
+		.map_err(|_| Error::packet_commitment_not_found(msg.packet_id.clone()))?;
+	escrow_packet_fee(ctx, output, msg.packet_id, msg.packet_fee)
+}
+
+pub fn register_payee(
+	ctx: &mut impl FeeContext,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgRegisterPayee,
+) -> Result<(), Error> {
+	ensure_fee_enabled(ctx, &msg.port_id, &msg.channel_id)?;
+	ctx.store_payee(msg.relayer.clone(), msg.channel_id, msg.payee.clone())?;
+	output.log(format!("registered payee {} for relayer {}", msg.payee, msg.relayer));
+	output.emit(
+		RegisterPayeeEvent { relayer: msg.relayer, payee: msg.payee, channel_id: msg.channel_id }
+			.into(),
+	);
+	Ok(())
+}
+
+pub fn register_counterparty_payee(
+	ctx: &mut impl FeeContext,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgRegisterCounterpartyPayee,
+) -> Result<(), Error> {
+	ensure_fee_enabled(ctx, &msg.port_id, &msg.channel_id)?;
+	ctx.store_counterparty_payee(
+		msg.relayer.clone(),
+		msg.channel_id,
+		msg.counterparty_payee.clone(),
+	)?;
+	output.log(format!(
+		"registered counterparty payee {} for relayer {}",
+		msg.counterparty_payee, msg.relayer
+	));
+	output.emit(
+		RegisterCounterpartyPayeeEvent {
+			relayer: msg.relayer,
+			counterparty_payee: msg.counterparty_payee,
+			channel_id: msg.channel_id,
+		}
+		.into(),
+	);
+	Ok(())
+}
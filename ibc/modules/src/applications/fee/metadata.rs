@@ -0,0 +1,80 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Channel version negotiated by the fee middleware.
+
+use serde::{Deserialize, Serialize};
+
+use super::{error::Error, VERSION};
+use crate::{core::ics04_channel::Version, prelude::*};
+
+/// Version of a fee enabled channel, wrapping the version of the underlying application.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+	pub fee_version: String,
+	pub app_version: String,
+}
+
+impl Metadata {
+	/// Metadata enabling fees on a channel of the given application version.
+	pub fn new(app_version: &Version) -> Self {
+		Self { fee_version: VERSION.to_string(), app_version: app_version.to_string() }
+	}
+
+	/// Parses the metadata of a channel version.
+	///
+	/// Returns `None` if the version isn't fee metadata, in which case the channel isn't fee
+	/// enabled and the version belongs to the underlying application as is.
+	pub fn from_version(version: &Version) -> Result<Option<Self>, Error> {
+		let Ok(metadata) = serde_json::from_str::<Metadata>(&version.to_string()) else {
+			return Ok(None)
+		};
+		if metadata.fee_version != VERSION {
+			return Err(Error::invalid_version(metadata.fee_version))
+		}
+		Ok(Some(metadata))
+	}
+
+	pub fn app_version(&self) -> Version {
+		Version::new(self.app_version.clone())
+	}
+}
+
+impl From<Metadata> for Version {
+	fn from(metadata: Metadata) -> Self {
+		Version::new(
+			serde_json::to_string(&metadata).expect("Metadata's infallible Serialize impl failed"),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fee_metadata_round_trips_through_the_version() {
+		let version: Version = Metadata::new(&Version::ics20()).into();
+		assert_eq!(version.to_string(), r#"{"fee_version":"ics29-1","app_version":"ics20-1"}"#);
+		let metadata = Metadata::from_version(&version).unwrap().unwrap();
+		assert_eq!(metadata.app_version(), Version::ics20());
+	}
+
+	#[test]
+	fn plain_versions_are_not_fee_enabled() {
+		assert_eq!(Metadata::from_version(&Version::ics20()).unwrap(), None);
+		let version = Version::new(r#"{"fee_version":"ics29-2","app_version":"ics20-1"}"#.into());
+		assert!(Metadata::from_version(&version).is_err());
+	}
+}
@@ -0,0 +1,37 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ICS 29: Fee Middleware incentivizes relayers by letting packet senders escrow fees that are
+//! paid out to the relayers delivering the packet, its acknowledgement or its timeout.
+//!
+//! The middleware wraps an application module: channel versions are negotiated as JSON encoded
+//! [`Metadata`](metadata::Metadata), acknowledgements written on fee enabled channels are
+//! [`IncentivizedAcknowledgement`](acknowledgement::IncentivizedAcknowledgement)s and the escrowed
+//! fees are distributed when the packet lifecycle completes.
+pub mod acknowledgement;
+pub mod context;
+pub mod error;
+pub mod events;
+pub mod fee;
+pub mod handler;
+pub mod metadata;
+pub mod msgs;
+
+pub use fee::*;
+
+/// Module identifier for the ICS29 middleware.
+pub const MODULE_ID_STR: &str = "feeibc";
+
+/// ICS29 middleware current version.
+pub const VERSION: &str = "ics29-1";
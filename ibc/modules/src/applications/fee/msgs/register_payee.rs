@@ -0,0 +1,170 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Messages registering the accounts relayers are paid fees to.
+
+use crate::prelude::*;
+
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::applications::fee::v1::{
+		MsgRegisterCounterpartyPayee as RawMsgRegisterCounterpartyPayee,
+		MsgRegisterPayee as RawMsgRegisterPayee,
+	},
+};
+use tendermint_proto::Protobuf;
+
+use crate::{
+	applications::fee::error::Error,
+	core::ics24_host::identifier::{ChannelId, PortId},
+	signer::Signer,
+	tx_msg::Msg,
+};
+
+pub const REGISTER_PAYEE_TYPE_URL: &str = "/ibc.applications.fee.v1.MsgRegisterPayee";
+pub const REGISTER_COUNTERPARTY_PAYEE_TYPE_URL: &str =
+	"/ibc.applications.fee.v1.MsgRegisterCounterpartyPayee";
+
+/// Registers the account the ack and timeout fees earned by a relayer on a channel are paid to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgRegisterPayee {
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub relayer: Signer,
+	pub payee: Signer,
+}
+
+impl Msg for MsgRegisterPayee {
+	type ValidationError = Error;
+	type Raw = RawMsgRegisterPayee;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		REGISTER_PAYEE_TYPE_URL.to_string()
+	}
+}
+
+impl Protobuf<RawMsgRegisterPayee> for MsgRegisterPayee {}
+
+impl TryFrom<RawMsgRegisterPayee> for MsgRegisterPayee {
+	type Error = Error;
+
+	fn try_from(raw: RawMsgRegisterPayee) -> Result<Self, Self::Error> {
+		Ok(MsgRegisterPayee {
+			port_id: raw
+				.port_id
+				.parse()
+				.map_err(|e| Error::invalid_port_id(raw.port_id.clone(), e))?,
+			channel_id: raw
+				.channel_id
+				.parse()
+				.map_err(|e| Error::invalid_channel_id(raw.channel_id.clone(), e))?,
+			relayer: raw.relayer.parse().map_err(Error::signer)?,
+			payee: raw.payee.parse().map_err(Error::signer)?,
+		})
+	}
+}
+
+impl From<MsgRegisterPayee> for RawMsgRegisterPayee {
+	fn from(msg: MsgRegisterPayee) -> Self {
+		RawMsgRegisterPayee {
+			port_id: msg.port_id.to_string(),
+			channel_id: msg.channel_id.to_string(),
+			relayer: msg.relayer.to_string(),
+			payee: msg.payee.to_string(),
+		}
+	}
+}
+
+impl TryFrom<Any> for MsgRegisterPayee {
+	type Error = Error;
+
+	fn try_from(raw: Any) -> Result<Self, Self::Error> {
+		match raw.type_url.as_str() {
+			REGISTER_PAYEE_TYPE_URL =>
+				MsgRegisterPayee::decode_vec(&raw.value).map_err(Error::decode_raw_msg),
+			_ => Err(Error::unknown_msg_type(raw.type_url)),
+		}
+	}
+}
+
+/// Registers the counterparty chain address the recv fees earned by a relayer on a channel are
+/// paid to. It's written into the acknowledgements of the packets the relayer delivers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgRegisterCounterpartyPayee {
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub relayer: Signer,
+	pub counterparty_payee: Signer,
+}
+
+impl Msg for MsgRegisterCounterpartyPayee {
+	type ValidationError = Error;
+	type Raw = RawMsgRegisterCounterpartyPayee;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		REGISTER_COUNTERPARTY_PAYEE_TYPE_URL.to_string()
+	}
+}
+
+impl Protobuf<RawMsgRegisterCounterpartyPayee> for MsgRegisterCounterpartyPayee {}
+
+impl TryFrom<RawMsgRegisterCounterpartyPayee> for MsgRegisterCounterpartyPayee {
+	type Error = Error;
+
+	fn try_from(raw: RawMsgRegisterCounterpartyPayee) -> Result<Self, Self::Error> {
+		Ok(MsgRegisterCounterpartyPayee {
+			port_id: raw
+				.port_id
+				.parse()
+				.map_err(|e| Error::invalid_port_id(raw.port_id.clone(), e))?,
+			channel_id: raw
+				.channel_id
+				.parse()
+				.map_err(|e| Error::invalid_channel_id(raw.channel_id.clone(), e))?,
+			relayer: raw.relayer.parse().map_err(Error::signer)?,
+			counterparty_payee: raw.counterparty_payee.parse().map_err(Error::signer)?,
+		})
+	}
+}
+
+impl From<MsgRegisterCounterpartyPayee> for RawMsgRegisterCounterpartyPayee {
+	fn from(msg: MsgRegisterCounterpartyPayee) -> Self {
+		RawMsgRegisterCounterpartyPayee {
+			port_id: msg.port_id.to_string(),
+			channel_id: msg.channel_id.to_string(),
+			relayer: msg.relayer.to_string(),
+			counterparty_payee: msg.counterparty_payee.to_string(),
+		}
+	}
+}
+
+impl TryFrom<Any> for MsgRegisterCounterpartyPayee {
+	type Error = Error;
+
+	fn try_from(raw: Any) -> Result<Self, Self::Error> {
+		match raw.type_url.as_str() {
+			REGISTER_COUNTERPARTY_PAYEE_TYPE_URL =>
+				MsgRegisterCounterpartyPayee::decode_vec(&raw.value).map_err(Error::decode_raw_msg),
+			_ => Err(Error::unknown_msg_type(raw.type_url)),
+		}
+	}
+}
@@ -0,0 +1,233 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	applications::{
+		interchain_accounts::{
+			error::Error, is_controller_port, metadata::Metadata, HOST_PORT_ID_STR,
+		},
+		transfer::acknowledgement::Acknowledgement,
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order, State},
+			context::{ChannelKeeper, ChannelReader},
+			msgs::acknowledgement::Acknowledgement as GenericAcknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{ModuleOutputBuilder, ReaderContext},
+	},
+	prelude::*,
+	signer::Signer,
+};
+
+pub trait IcaControllerReader: ChannelReader {
+	/// Returns true iff the controller is enabled.
+	fn is_controller_enabled(&self) -> bool;
+
+	/// Returns the channel of the interchain account of a controller port on a connection.
+	fn active_channel(&self, connection_id: &ConnectionId, port_id: &PortId) -> Option<ChannelId>;
+
+	/// Returns the address of the interchain account of a controller port on a connection.
+	fn interchain_account_address(
+		&self,
+		connection_id: &ConnectionId,
+		port_id: &PortId,
+	) -> Option<String>;
+}
+
+pub trait IcaControllerKeeper: ChannelKeeper {
+	fn store_active_channel(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+		channel_id: ChannelId,
+	) -> Result<(), Error>;
+
+	fn store_interchain_account_address(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+		address: String,
+	) -> Result<(), Error>;
+}
+
+/// Captures all the dependencies which the ICS27 controller requires to be able to dispatch and
+/// process IBC messages.
+pub trait IcaControllerContext: IcaControllerKeeper + IcaControllerReader + ReaderContext {}
+
+/// Fails if the port already has an interchain account channel on the connection that isn't
+/// closed. A closed channel can be replaced to recover the account.
+pub(crate) fn ensure_no_active_channel(
+	ctx: &impl IcaControllerContext,
+	connection_id: &ConnectionId,
+	port_id: &PortId,
+) -> Result<(), Error> {
+	if let Some(channel_id) = ctx.active_channel(connection_id, port_id) {
+		let channel_end =
+			ctx.channel_end(&(port_id.clone(), channel_id)).map_err(Error::ics04_channel)?;
+		if channel_end.state() != &State::Closed {
+			return Err(Error::active_channel_exists(port_id.clone(), channel_id))
+		}
+	}
+	Ok(())
+}
+
+/// Validates the metadata of a channel of the controller port against its connection.
+fn validate_metadata(
+	ctx: &impl IcaControllerContext,
+	connection_hops: &[ConnectionId],
+	metadata: &Metadata,
+) -> Result<ConnectionId, Error> {
+	let connection_id = connection_hops
+		.first()
+		.ok_or_else(|| Error::invalid_metadata("channel has no connection".to_string()))?;
+	let connection_end = ctx.connection_end(connection_id).map_err(Error::ics03_connection)?;
+	let host_connection_id = connection_end
+		.counterparty()
+		.connection_id()
+		.ok_or_else(|| Error::missing_counterparty_connection(connection_id.clone()))?;
+	metadata.validate(connection_id, host_connection_id)?;
+	Ok(connection_id.clone())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_init(
+	ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	order: Order,
+	connection_hops: &[ConnectionId],
+	port_id: &PortId,
+	_channel_id: &ChannelId,
+	counterparty: &Counterparty,
+	version: &Version,
+) -> Result<(), Error> {
+	if !ctx.is_controller_enabled() {
+		return Err(Error::controller_disabled())
+	}
+	if !order.is_ordered() {
+		return Err(Error::channel_not_ordered(order))
+	}
+	if !is_controller_port(port_id) {
+		return Err(Error::invalid_controller_port(port_id.clone()))
+	}
+	if counterparty.port_id().as_str() != HOST_PORT_ID_STR {
+		return Err(Error::invalid_host_port(counterparty.port_id().clone()))
+	}
+
+	let metadata = Metadata::from_version(version)?;
+	if !metadata.address.is_empty() {
+		return Err(Error::invalid_metadata("address is set by the host".to_string()))
+	}
+	let connection_id = validate_metadata(ctx, connection_hops, &metadata)?;
+	ensure_no_active_channel(ctx, &connection_id, port_id)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_try(
+	_ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	_order: Order,
+	_connection_hops: &[ConnectionId],
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+	_counterparty: &Counterparty,
+	_version: &Version,
+	_counterparty_version: &Version,
+) -> Result<Version, Error> {
+	Err(Error::invalid_operation("channel handshake must be initiated by the controller".into()))
+}
+
+pub fn on_chan_open_ack(
+	ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	counterparty_version: &Version,
+) -> Result<(), Error> {
+	if !is_controller_port(port_id) {
+		return Err(Error::invalid_controller_port(port_id.clone()))
+	}
+
+	let metadata = Metadata::from_version(counterparty_version)?;
+	if metadata.address.is_empty() {
+		return Err(Error::invalid_metadata("host didn't set the account address".to_string()))
+	}
+	let channel_end =
+		ctx.channel_end(&(port_id.clone(), *channel_id)).map_err(Error::ics04_channel)?;
+	let connection_id = validate_metadata(ctx, channel_end.connection_hops(), &metadata)?;
+
+	ctx.store_active_channel(connection_id.clone(), port_id.clone(), *channel_id)?;
+	ctx.store_interchain_account_address(connection_id, port_id.clone(), metadata.address)
+}
+
+pub fn on_chan_open_confirm(
+	_ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Err(Error::invalid_operation("channel handshake must be initiated by the controller".into()))
+}
+
+pub fn on_chan_close_init(
+	_ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Err(Error::invalid_operation("interchain account channels can't be closed".into()))
+}
+
+pub fn on_chan_close_confirm(
+	_ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Ok(())
+}
+
+pub fn on_recv_packet(
+	_ctx: &impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	_packet: &Packet,
+	_relayer: &Signer,
+) -> Result<(), Error> {
+	Err(Error::invalid_operation("the controller doesn't receive packets".into()))
+}
+
+/// Decodes the acknowledgement of a transaction, its result is left to the owner of the account.
+pub fn on_acknowledgement_packet(
+	_ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	_packet: &Packet,
+	acknowledgement: &GenericAcknowledgement,
+	_relayer: &Signer,
+) -> Result<Acknowledgement, Error> {
+	serde_json::from_slice::<Acknowledgement>(acknowledgement.as_ref())
+		.map_err(|_| Error::ack_deserialization())
+}
+
+/// The transaction of a timed out packet wasn't executed, and the ordered channel is closed.
+pub fn on_timeout_packet(
+	_ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	_packet: &Packet,
+	_relayer: &Signer,
+) -> Result<(), Error> {
+	Ok(())
+}
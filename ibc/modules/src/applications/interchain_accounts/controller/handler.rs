@@ -0,0 +1,313 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Handlers of the interchain accounts controller messages.
+
+use core::time::Duration;
+
+use crate::{
+	applications::interchain_accounts::{
+		controller::{
+			context::{ensure_no_active_channel, IcaControllerContext},
+			msgs::{MsgRegisterInterchainAccount, MsgSendTx},
+		},
+		controller_port_id,
+		error::Error,
+		host_port_id,
+		metadata::Metadata,
+	},
+	core::{
+		ics02_client::height::Height,
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty, Order, State},
+			handler::send_packet::send_packet,
+			msgs::chan_open_init::MsgChannelOpenInit,
+			packet::{Packet, Sequence},
+		},
+	},
+	handler::{HandlerOutput, HandlerOutputBuilder},
+	prelude::*,
+};
+
+/// Builds the `ChanOpenInit` message opening the channel of a new interchain account. The host
+/// chain delivers it to start the channel handshake.
+pub fn register_interchain_account(
+	ctx: &impl IcaControllerContext,
+	msg: MsgRegisterInterchainAccount,
+) -> Result<MsgChannelOpenInit, Error> {
+	if !ctx.is_controller_enabled() {
+		return Err(Error::controller_disabled())
+	}
+
+	let port_id = controller_port_id(&msg.owner)?;
+	ensure_no_active_channel(ctx, &msg.connection_id, &port_id)?;
+
+	let version = if msg.version.to_string().is_empty() {
+		let connection_end =
+			ctx.connection_end(&msg.connection_id).map_err(Error::ics03_connection)?;
+		let host_connection_id = connection_end
+			.counterparty()
+			.connection_id()
+			.ok_or_else(|| Error::missing_counterparty_connection(msg.connection_id.clone()))?;
+		Metadata::new(&msg.connection_id, host_connection_id).into()
+	} else {
+		msg.version
+	};
+
+	let channel = ChannelEnd::new(
+		State::Init,
+		Order::Ordered,
+		Counterparty::new(host_port_id(), None),
+		vec![msg.connection_id],
+		version,
+	);
+	Ok(MsgChannelOpenInit::new(port_id, channel, msg.owner))
+}
+
+/// Sends the transaction to the interchain account of the owner.
+pub fn send_tx(
+	ctx: &mut impl IcaControllerContext,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgSendTx,
+) -> Result<Sequence, Error> {
+	if !ctx.is_controller_enabled() {
+		return Err(Error::controller_disabled())
+	}
+	if msg.relative_timeout == 0 {
+		return Err(Error::invalid_timeout())
+	}
+	// reject transactions the host would fail to decode
+	msg.packet_data.messages()?;
+
+	let port_id = controller_port_id(&msg.owner)?;
+	let channel_id = ctx.active_channel(&msg.connection_id, &port_id).ok_or_else(|| {
+		Error::active_channel_not_found(msg.connection_id.clone(), port_id.clone())
+	})?;
+	let channel_end =
+		ctx.channel_end(&(port_id.clone(), channel_id)).map_err(Error::ics04_channel)?;
+	let destination_port = channel_end.counterparty().port_id().clone();
+	let destination_channel = *channel_end.counterparty().channel_id().ok_or_else(|| {
+		Error::active_channel_not_found(msg.connection_id.clone(), port_id.clone())
+	})?;
+
+	let sequence = ctx
+		.get_next_sequence_send(&(port_id.clone(), channel_id))
+		.map_err(Error::ics04_channel)?;
+	let timeout_timestamp = (ctx.host_timestamp() + Duration::from_nanos(msg.relative_timeout))
+		.map_err(|_| Error::invalid_timeout())?;
+
+	let packet = Packet {
+		sequence,
+		source_port: port_id,
+		source_channel: channel_id,
+		destination_port,
+		destination_channel,
+		data: msg.packet_data.to_bytes(),
+		timeout_height: Height::zero(),
+		timeout_timestamp,
+	};
+
+	let HandlerOutput { result, log, events } =
+		send_packet(ctx, packet).map_err(Error::ics04_channel)?;
+
+	ctx.store_packet_result(result).map_err(Error::ics04_channel)?;
+
+	output.merge_output(HandlerOutput::builder().with_log(log).with_events(events).with_result(()));
+	output.log(format!(
+		"interchain account transaction of {} sent on connection {}",
+		msg.owner, msg.connection_id
+	));
+
+	Ok(sequence)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		applications::interchain_accounts::{
+			controller::context::IcaControllerKeeper, packet::InterchainAccountPacketData,
+		},
+		core::{
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				version::get_compatible_versions,
+			},
+			ics04_channel::{context::ChannelReader, Version},
+			ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		},
+		events::IbcEvent,
+		mock::context::{MockClientTypes, MockContext},
+		signer::Signer,
+		test_utils::{get_dummy_account_id, DummyTransferModule, DUMMY_ICA_ALLOWED_MESSAGE},
+		timestamp::ZERO_DURATION,
+	};
+	use ibc_proto::google::protobuf::Any;
+
+	type Ctx = DummyTransferModule<MockClientTypes>;
+
+	fn owner() -> Signer {
+		get_dummy_account_id()
+	}
+
+	fn port_id() -> PortId {
+		controller_port_id(&owner()).unwrap()
+	}
+
+	/// A context with the open connection-0 to connection-1 of the host chain, and the channel-0
+	/// of the owner in the given state, if any
+	fn context(channel_state: Option<State>) -> Ctx {
+		let connection_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::new(
+				ClientId::default(),
+				Some(ConnectionId::new(1)),
+				Default::default(),
+			),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+		let mut ctx = MockContext::<MockClientTypes>::default()
+			.with_client(&ClientId::default(), Height::new(0, 5))
+			.with_connection(ConnectionId::new(0), connection_end);
+		if let Some(state) = channel_state {
+			let channel_end = ChannelEnd::new(
+				state,
+				Order::Ordered,
+				Counterparty::new(host_port_id(), Some(ChannelId::new(1))),
+				vec![ConnectionId::new(0)],
+				Metadata::new(&ConnectionId::new(0), &ConnectionId::new(1)).into(),
+			);
+			ctx = ctx.with_channel(port_id(), ChannelId::new(0), channel_end).with_send_sequence(
+				port_id(),
+				ChannelId::new(0),
+				1.into(),
+			);
+		}
+		let mut ctx = DummyTransferModule::new(ctx.ibc_store_share());
+		if channel_state.is_some() {
+			IcaControllerKeeper::store_active_channel(
+				&mut ctx,
+				ConnectionId::new(0),
+				port_id(),
+				ChannelId::new(0),
+			)
+			.unwrap();
+		}
+		ctx
+	}
+
+	fn register_msg() -> MsgRegisterInterchainAccount {
+		MsgRegisterInterchainAccount {
+			owner: owner(),
+			connection_id: ConnectionId::new(0),
+			version: Version::empty(),
+		}
+	}
+
+	fn send_tx_msg(type_url: &str, relative_timeout: u64) -> MsgSendTx {
+		MsgSendTx {
+			owner: owner(),
+			connection_id: ConnectionId::new(0),
+			packet_data: InterchainAccountPacketData::execute_tx(
+				vec![Any { type_url: type_url.to_string(), value: vec![1, 2, 3] }],
+				"memo".to_string(),
+			),
+			relative_timeout,
+		}
+	}
+
+	#[test]
+	fn register_uses_the_default_metadata() {
+		let ctx = context(None);
+
+		let msg = register_interchain_account(&ctx, register_msg()).unwrap();
+		assert_eq!(msg.port_id, port_id());
+		assert_eq!(msg.channel.state(), &State::Init);
+		assert_eq!(msg.channel.ordering(), &Order::Ordered);
+		assert_eq!(msg.channel.counterparty(), &Counterparty::new(host_port_id(), None));
+		assert_eq!(msg.channel.connection_hops(), &vec![ConnectionId::new(0)]);
+		assert_eq!(
+			Metadata::from_version(msg.channel.version()).unwrap(),
+			Metadata::new(&ConnectionId::new(0), &ConnectionId::new(1))
+		);
+
+		let version = Version::new("custom".to_string());
+		let msg = register_interchain_account(
+			&ctx,
+			MsgRegisterInterchainAccount { version: version.clone(), ..register_msg() },
+		)
+		.unwrap();
+		assert_eq!(msg.channel.version(), &version);
+	}
+
+	#[test]
+	fn register_fails_while_a_channel_is_active() {
+		assert!(register_interchain_account(&context(Some(State::Init)), register_msg()).is_err());
+		assert!(register_interchain_account(&context(Some(State::Open)), register_msg()).is_err());
+		// a closed channel is replaced to recover the account
+		assert!(register_interchain_account(&context(Some(State::Closed)), register_msg()).is_ok());
+	}
+
+	#[test]
+	fn send_tx_sends_on_the_active_channel() {
+		let mut ctx = context(Some(State::Open));
+		let mut output = HandlerOutputBuilder::new();
+
+		let sequence =
+			send_tx(&mut ctx, &mut output, send_tx_msg(DUMMY_ICA_ALLOWED_MESSAGE, 1_000_000_000))
+				.unwrap();
+		assert_eq!(sequence, 1.into());
+		assert_eq!(ctx.get_next_sequence_send(&(port_id(), ChannelId::new(0))).unwrap(), 2.into());
+
+		let packet = output
+			.with_result(())
+			.events
+			.into_iter()
+			.find_map(|event| match event {
+				IbcEvent::SendPacket(event) => Some(event.packet),
+				_ => None,
+			})
+			.unwrap();
+		assert_eq!(packet.destination_port, host_port_id());
+		assert_eq!(packet.destination_channel, ChannelId::new(1));
+		assert!(packet.timeout_height.is_zero());
+		assert!(packet.timeout_timestamp.nanoseconds() > 0);
+		let data = InterchainAccountPacketData::from_bytes(&packet.data).unwrap();
+		assert_eq!(data.memo, "memo");
+		assert_eq!(data.messages().unwrap()[0].type_url, DUMMY_ICA_ALLOWED_MESSAGE);
+	}
+
+	#[test]
+	fn send_tx_rejects_invalid_transactions() {
+		let mut ctx = context(Some(State::Open));
+		let msg = send_tx_msg(DUMMY_ICA_ALLOWED_MESSAGE, 1_000_000_000);
+
+		// a zero timeout
+		let zero_timeout = MsgSendTx { relative_timeout: 0, ..msg.clone() };
+		assert!(send_tx(&mut ctx, &mut HandlerOutputBuilder::new(), zero_timeout).is_err());
+		// no message
+		let empty = MsgSendTx {
+			packet_data: InterchainAccountPacketData::execute_tx(vec![], String::new()),
+			..msg.clone()
+		};
+		assert!(send_tx(&mut ctx, &mut HandlerOutputBuilder::new(), empty).is_err());
+		// no account on the connection
+		let mut ctx = context(None);
+		assert!(send_tx(&mut ctx, &mut HandlerOutputBuilder::new(), msg).is_err());
+	}
+}
@@ -0,0 +1,163 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Messages of the interchain accounts controller.
+
+use crate::prelude::*;
+
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::applications::interchain_accounts::controller::v1::{
+		MsgRegisterInterchainAccount as RawMsgRegisterInterchainAccount, MsgSendTx as RawMsgSendTx,
+	},
+};
+use tendermint_proto::Protobuf;
+
+use crate::{
+	applications::interchain_accounts::{error::Error, packet::InterchainAccountPacketData},
+	core::{ics04_channel::Version, ics24_host::identifier::ConnectionId},
+	signer::Signer,
+	tx_msg::Msg,
+};
+
+pub const REGISTER_INTERCHAIN_ACCOUNT_TYPE_URL: &str =
+	"/ibc.applications.interchain_accounts.controller.v1.MsgRegisterInterchainAccount";
+pub const SEND_TX_TYPE_URL: &str = "/ibc.applications.interchain_accounts.controller.v1.MsgSendTx";
+
+/// Registers an interchain account of the owner on the host chain of a connection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgRegisterInterchainAccount {
+	pub owner: Signer,
+	pub connection_id: ConnectionId,
+	/// Channel version, the default metadata of the connection is used if empty
+	pub version: Version,
+}
+
+impl Msg for MsgRegisterInterchainAccount {
+	type ValidationError = Error;
+	type Raw = RawMsgRegisterInterchainAccount;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		REGISTER_INTERCHAIN_ACCOUNT_TYPE_URL.to_string()
+	}
+}
+
+impl Protobuf<RawMsgRegisterInterchainAccount> for MsgRegisterInterchainAccount {}
+
+impl TryFrom<RawMsgRegisterInterchainAccount> for MsgRegisterInterchainAccount {
+	type Error = Error;
+
+	fn try_from(raw: RawMsgRegisterInterchainAccount) -> Result<Self, Self::Error> {
+		Ok(MsgRegisterInterchainAccount {
+			owner: raw.owner.parse().map_err(Error::signer)?,
+			connection_id: raw
+				.connection_id
+				.parse()
+				.map_err(|e| Error::invalid_connection_id(raw.connection_id.clone(), e))?,
+			version: Version::new(raw.version),
+		})
+	}
+}
+
+impl From<MsgRegisterInterchainAccount> for RawMsgRegisterInterchainAccount {
+	fn from(msg: MsgRegisterInterchainAccount) -> Self {
+		RawMsgRegisterInterchainAccount {
+			owner: msg.owner.to_string(),
+			connection_id: msg.connection_id.to_string(),
+			version: msg.version.to_string(),
+		}
+	}
+}
+
+impl TryFrom<Any> for MsgRegisterInterchainAccount {
+	type Error = Error;
+
+	fn try_from(raw: Any) -> Result<Self, Self::Error> {
+		match raw.type_url.as_str() {
+			REGISTER_INTERCHAIN_ACCOUNT_TYPE_URL =>
+				MsgRegisterInterchainAccount::decode_vec(&raw.value).map_err(Error::decode_raw_msg),
+			_ => Err(Error::unknown_msg_type(raw.type_url)),
+		}
+	}
+}
+
+/// Sends a transaction to be executed by the interchain account of the owner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgSendTx {
+	pub owner: Signer,
+	pub connection_id: ConnectionId,
+	pub packet_data: InterchainAccountPacketData,
+	/// Timeout of the packet in nanoseconds, relative to the host timestamp
+	pub relative_timeout: u64,
+}
+
+impl Msg for MsgSendTx {
+	type ValidationError = Error;
+	type Raw = RawMsgSendTx;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		SEND_TX_TYPE_URL.to_string()
+	}
+}
+
+impl Protobuf<RawMsgSendTx> for MsgSendTx {}
+
+impl TryFrom<RawMsgSendTx> for MsgSendTx {
+	type Error = Error;
+
+	fn try_from(raw: RawMsgSendTx) -> Result<Self, Self::Error> {
+		Ok(MsgSendTx {
+			owner: raw.owner.parse().map_err(Error::signer)?,
+			connection_id: raw
+				.connection_id
+				.parse()
+				.map_err(|e| Error::invalid_connection_id(raw.connection_id.clone(), e))?,
+			packet_data: raw
+				.packet_data
+				.ok_or_else(Error::packet_data_deserialization)?
+				.try_into()?,
+			relative_timeout: raw.relative_timeout,
+		})
+	}
+}
+
+impl From<MsgSendTx> for RawMsgSendTx {
+	fn from(msg: MsgSendTx) -> Self {
+		RawMsgSendTx {
+			owner: msg.owner.to_string(),
+			connection_id: msg.connection_id.to_string(),
+			packet_data: Some(msg.packet_data.into()),
+			relative_timeout: msg.relative_timeout,
+		}
+	}
+}
+
+impl TryFrom<Any> for MsgSendTx {
+	type Error = Error;
+
+	fn try_from(raw: Any) -> Result<Self, Self::Error> {
+		match raw.type_url.as_str() {
+			SEND_TX_TYPE_URL => MsgSendTx::decode_vec(&raw.value).map_err(Error::decode_raw_msg),
+			_ => Err(Error::unknown_msg_type(raw.type_url)),
+		}
+	}
+}
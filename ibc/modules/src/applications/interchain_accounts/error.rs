@@ -0,0 +1,134 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use flex_error::{define_error, TraceError};
+use tendermint_proto::Error as TendermintProtoError;
+
+use crate::{
+	core::{
+		ics03_connection::error as connection_error,
+		ics04_channel::{channel::Order, error as channel_error},
+		ics24_host::{
+			error::ValidationError,
+			identifier::{ChannelId, ConnectionId, PortId},
+		},
+	},
+	prelude::*,
+	signer::SignerError,
+};
+
+define_error! {
+	#[derive(Debug, PartialEq, Eq)]
+	Error {
+		Ics03Connection
+			[ connection_error::Error ]
+			| _ | { "Ics03 connection error" },
+
+		Ics04Channel
+			[ channel_error::Error ]
+			| _ | { "Ics04 channel error" },
+
+		Signer
+			[ SignerError ]
+			| _ | { "failed to parse signer" },
+
+		InvalidPortId
+			{ context: String }
+			[ ValidationError ]
+			| _ | { "invalid port identifier" },
+
+		InvalidConnectionId
+			{ context: String }
+			[ ValidationError ]
+			| _ | { "invalid connection identifier" },
+
+		InvalidVersion
+			{ version: String }
+			| e | { format_args!("expected version '{0}', got '{1}'", super::VERSION, e.version) },
+
+		InvalidMetadata
+			{ reason: String }
+			| e | { format_args!("invalid interchain account metadata: {}", e.reason) },
+
+		ChannelNotOrdered
+			{ order: Order }
+			| e | { format_args!("expected an ordered channel, got '{0}'", e.order) },
+
+		InvalidControllerPort
+			{ port_id: PortId }
+			| e | { format_args!("port {0} isn't an interchain accounts controller port", e.port_id) },
+
+		InvalidHostPort
+			{ port_id: PortId }
+			| e | { format_args!("expected host port '{0}', got '{1}'", super::HOST_PORT_ID_STR, e.port_id) },
+
+		MissingCounterpartyConnection
+			{ connection_id: ConnectionId }
+			| e | { format_args!("counterparty of connection {0} isn't open yet", e.connection_id) },
+
+		ActiveChannelExists
+			{ port_id: PortId, channel_id: ChannelId }
+			| e | { format_args!("port {0} already has an active channel {1}", e.port_id, e.channel_id) },
+
+		ActiveChannelNotFound
+			{ connection_id: ConnectionId, port_id: PortId }
+			| e | { format_args!("no active channel for port {1} on connection {0}", e.connection_id, e.port_id) },
+
+		AccountNotFound
+			{ connection_id: ConnectionId, port_id: PortId }
+			| e | { format_args!("no interchain account for port {1} on connection {0}", e.connection_id, e.port_id) },
+
+		ControllerDisabled
+			| _ | { "interchain accounts controller is disabled" },
+
+		HostDisabled
+			| _ | { "interchain accounts host is disabled" },
+
+		InvalidOperation
+			{ reason: String }
+			| e | { format_args!("invalid operation: {}", e.reason) },
+
+		PacketDataDeserialization
+			| _ | { "failed to deserialize packet data" },
+
+		UnsupportedPacketType
+			{ packet_type: i32 }
+			| e | { format_args!("unsupported packet type {0}", e.packet_type) },
+
+		EmptyMessages
+			| _ | { "no message to execute" },
+
+		MessageNotAllowed
+			{ type_url: String }
+			| e | { format_args!("message {0} isn't allowed on the host", e.type_url) },
+
+		InvalidTimeout
+			| _ | { "relative timeout must be non zero" },
+
+		AckDeserialization
+			| _ | { "failed to deserialize acknowledgement" },
+
+		DecodeRawMsg
+			[ TraceError<TendermintProtoError> ]
+			| _ | { "error decoding raw msg" },
+
+		UnknownMsgType
+			{ msg_type: String }
+			| e | { format_args!("unknown msg type: {0}", e.msg_type) },
+
+		ImplementationSpecific
+			{ reason: String }
+			| e | { format_args!("implementation specific error: {}", e.reason) },
+	}
+}
@@ -0,0 +1,47 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	applications::interchain_accounts::HOST_MODULE_ID_STR,
+	core::ics24_host::identifier::ChannelId,
+	events::{IbcEvent, ModuleEvent},
+	prelude::*,
+};
+
+const EVENT_TYPE_PACKET: &str = "ics27_packet";
+
+/// Emitted by the host when it executes the transaction of a packet.
+pub struct PacketEvent {
+	pub host_channel_id: ChannelId,
+	pub success: bool,
+	pub error: Option<String>,
+}
+
+impl From<PacketEvent> for IbcEvent {
+	fn from(ev: PacketEvent) -> Self {
+		let PacketEvent { host_channel_id, success, error } = ev;
+		let mut ev = ModuleEvent {
+			kind: EVENT_TYPE_PACKET.to_string(),
+			module_name: HOST_MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![
+				("host_channel_id", host_channel_id).into(),
+				("success", success).into(),
+			],
+		};
+		if let Some(error) = error {
+			ev.attributes.push(("error", error).into());
+		}
+		IbcEvent::AppModule(ev)
+	}
+}
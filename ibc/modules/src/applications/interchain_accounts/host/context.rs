@@ -0,0 +1,464 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc_proto::google::protobuf::Any;
+use subtle_encoding::base64;
+
+use crate::{
+	applications::{
+		interchain_accounts::{
+			error::Error, events::PacketEvent, is_controller_port, metadata::Metadata,
+			packet::InterchainAccountPacketData, HOST_PORT_ID_STR,
+		},
+		transfer::acknowledgement::Acknowledgement,
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order, State},
+			context::{ChannelKeeper, ChannelReader},
+			msgs::acknowledgement::Acknowledgement as GenericAcknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{ModuleOutputBuilder, ReaderContext},
+	},
+	prelude::*,
+	signer::Signer,
+};
+
+pub trait IcaHostReader: ChannelReader {
+	type AccountId;
+
+	/// Returns true iff the host is enabled.
+	fn is_host_enabled(&self) -> bool;
+
+	/// Returns true iff interchain accounts may execute messages of this type.
+	fn is_message_allowed(&self, type_url: &str) -> bool;
+
+	/// Returns the channel of the interchain account of a controller port on a connection.
+	fn active_channel(&self, connection_id: &ConnectionId, port_id: &PortId) -> Option<ChannelId>;
+
+	/// Returns the interchain account of a controller port on a connection.
+	fn interchain_account(
+		&self,
+		connection_id: &ConnectionId,
+		port_id: &PortId,
+	) -> Option<<Self as IcaHostReader>::AccountId>;
+}
+
+pub trait IcaHostKeeper: ChannelKeeper {
+	type AccountId;
+
+	/// Registers the interchain account of a controller port on a connection, or returns the
+	/// existing one if the channel of the account is being reopened. Returns the address of the
+	/// account, which is sent to the controller.
+	fn register_interchain_account(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+	) -> Result<Signer, Error>;
+
+	fn store_active_channel(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+		channel_id: ChannelId,
+	) -> Result<(), Error>;
+
+	/// Executes the messages with the interchain account, atomically. Returns the result of the
+	/// transaction that is acknowledged to the controller.
+	fn execute_tx(
+		&mut self,
+		account: &<Self as IcaHostKeeper>::AccountId,
+		messages: Vec<Any>,
+	) -> Result<Vec<u8>, Error>;
+}
+
+/// Captures all the dependencies which the ICS27 host requires to be able to dispatch and process
+/// IBC messages.
+pub trait IcaHostContext:
+	IcaHostKeeper<AccountId = <Self as IcaHostContext>::AccountId>
+	+ IcaHostReader<AccountId = <Self as IcaHostContext>::AccountId>
+	+ ReaderContext
+{
+	type AccountId;
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_init(
+	_ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	_order: Order,
+	_connection_hops: &[ConnectionId],
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+	_counterparty: &Counterparty,
+	_version: &Version,
+) -> Result<(), Error> {
+	Err(Error::invalid_operation("channel handshake must be initiated by the controller".into()))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_try(
+	ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	order: Order,
+	connection_hops: &[ConnectionId],
+	port_id: &PortId,
+	_channel_id: &ChannelId,
+	counterparty: &Counterparty,
+	_version: &Version,
+	counterparty_version: &Version,
+) -> Result<Version, Error> {
+	if !ctx.is_host_enabled() {
+		return Err(Error::host_disabled())
+	}
+	if !order.is_ordered() {
+		return Err(Error::channel_not_ordered(order))
+	}
+	if port_id.as_str() != HOST_PORT_ID_STR {
+		return Err(Error::invalid_host_port(port_id.clone()))
+	}
+	let controller_port_id = counterparty.port_id();
+	if !is_controller_port(controller_port_id) {
+		return Err(Error::invalid_controller_port(controller_port_id.clone()))
+	}
+
+	let mut metadata = Metadata::from_version(counterparty_version)?;
+	let connection_id = connection_hops
+		.first()
+		.ok_or_else(|| Error::invalid_metadata("channel has no connection".to_string()))?;
+	let connection_end = ctx.connection_end(connection_id).map_err(Error::ics03_connection)?;
+	let controller_connection_id = connection_end
+		.counterparty()
+		.connection_id()
+		.ok_or_else(|| Error::missing_counterparty_connection(connection_id.clone()))?;
+	metadata.validate(controller_connection_id, connection_id)?;
+
+	if let Some(channel_id) = ctx.active_channel(connection_id, controller_port_id) {
+		let channel_end =
+			ctx.channel_end(&(port_id.clone(), channel_id)).map_err(Error::ics04_channel)?;
+		if channel_end.state() != &State::Closed {
+			return Err(Error::active_channel_exists(controller_port_id.clone(), channel_id))
+		}
+	}
+
+	let address =
+		ctx.register_interchain_account(connection_id.clone(), controller_port_id.clone())?;
+	metadata.address = address.to_string();
+	Ok(metadata.into())
+}
+
+pub fn on_chan_open_ack(
+	_ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+	_counterparty_version: &Version,
+) -> Result<(), Error> {
+	Err(Error::invalid_operation("channel handshake must be initiated by the controller".into()))
+}
+
+pub fn on_chan_open_confirm(
+	ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+) -> Result<(), Error> {
+	let channel_end =
+		ctx.channel_end(&(port_id.clone(), *channel_id)).map_err(Error::ics04_channel)?;
+	let connection_id = channel_end
+		.connection_hops()
+		.first()
+		.cloned()
+		.ok_or_else(|| Error::invalid_metadata("channel has no connection".to_string()))?;
+	let controller_port_id = channel_end.counterparty().port_id().clone();
+	ctx.store_active_channel(connection_id, controller_port_id, *channel_id)
+}
+
+pub fn on_chan_close_init(
+	_ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Err(Error::invalid_operation("interchain account channels can't be closed".into()))
+}
+
+pub fn on_chan_close_confirm(
+	_ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Ok(())
+}
+
+fn execute_packet(ctx: &mut impl IcaHostContext, packet: &Packet) -> Result<Vec<u8>, Error> {
+	if !ctx.is_host_enabled() {
+		return Err(Error::host_disabled())
+	}
+
+	let data = InterchainAccountPacketData::from_bytes(&packet.data)?;
+	let messages = data.messages()?;
+	if let Some(message) = messages.iter().find(|msg| !ctx.is_message_allowed(&msg.type_url)) {
+		return Err(Error::message_not_allowed(message.type_url.clone()))
+	}
+
+	let channel_end = ctx
+		.channel_end(&(packet.destination_port.clone(), packet.destination_channel))
+		.map_err(Error::ics04_channel)?;
+	let connection_id = channel_end
+		.connection_hops()
+		.first()
+		.ok_or_else(|| Error::invalid_metadata("channel has no connection".to_string()))?;
+	let account = ctx.interchain_account(connection_id, &packet.source_port).ok_or_else(|| {
+		Error::account_not_found(connection_id.clone(), packet.source_port.clone())
+	})?;
+
+	ctx.execute_tx(&account, messages)
+}
+
+pub fn on_recv_packet(
+	ctx: &mut impl IcaHostContext,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	_relayer: &Signer,
+) -> Acknowledgement {
+	let (ack, error) = match execute_packet(ctx, packet) {
+		Ok(result) => {
+			let result = String::from_utf8(base64::encode(result))
+				.expect("base64 encoded bytes are valid UTF8");
+			(Acknowledgement::Result(result), None)
+		},
+		Err(e) => (Acknowledgement::Error(e.to_string()), Some(e.to_string())),
+	};
+
+	log::debug!(target: "ics::interchain-accounts", "executed packet {}: {:?}", packet.sequence, ack);
+	output.emit(
+		PacketEvent {
+			host_channel_id: packet.destination_channel,
+			success: ack.is_successful(),
+			error,
+		}
+		.into(),
+	);
+
+	ack
+}
+
+pub fn on_acknowledgement_packet(
+	_ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	_packet: &Packet,
+	_acknowledgement: &GenericAcknowledgement,
+	_relayer: &Signer,
+) -> Result<(), Error> {
+	Err(Error::invalid_operation("the host doesn't send packets".into()))
+}
+
+pub fn on_timeout_packet(
+	_ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	_packet: &Packet,
+	_relayer: &Signer,
+) -> Result<(), Error> {
+	Err(Error::invalid_operation("the host doesn't send packets".into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		applications::interchain_accounts::{controller_port_id, host_port_id},
+		core::{
+			ics02_client::height::Height,
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				version::get_compatible_versions,
+			},
+			ics04_channel::channel::ChannelEnd,
+			ics24_host::identifier::ClientId,
+		},
+		mock::context::{MockClientTypes, MockContext},
+		test_utils::{get_dummy_account_id, DummyTransferModule, DUMMY_ICA_ALLOWED_MESSAGE},
+		timestamp::{Timestamp, ZERO_DURATION},
+	};
+
+	type Ctx = DummyTransferModule<MockClientTypes>;
+
+	fn controller_port() -> PortId {
+		controller_port_id(&get_dummy_account_id()).unwrap()
+	}
+
+	/// A context with the open connection-0 to connection-1 of the controller chain, and the open
+	/// icahost channel-0 of the controller port
+	fn context() -> Ctx {
+		let connection_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::new(
+				ClientId::default(),
+				Some(ConnectionId::new(1)),
+				Default::default(),
+			),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+		let channel_end = ChannelEnd::new(
+			State::Open,
+			Order::Ordered,
+			Counterparty::new(controller_port(), Some(ChannelId::new(1))),
+			vec![ConnectionId::new(0)],
+			Metadata::new(&ConnectionId::new(1), &ConnectionId::new(0)).into(),
+		);
+		let ctx = MockContext::<MockClientTypes>::default()
+			.with_client(&ClientId::default(), Height::new(0, 5))
+			.with_connection(ConnectionId::new(0), connection_end)
+			.with_channel(host_port_id(), ChannelId::new(0), channel_end);
+		DummyTransferModule::new(ctx.ibc_store_share())
+	}
+
+	fn open_try(ctx: &mut Ctx, order: Order, version: &Version) -> Result<Version, Error> {
+		on_chan_open_try(
+			ctx,
+			&mut ModuleOutputBuilder::new(),
+			order,
+			&[ConnectionId::new(0)],
+			&host_port_id(),
+			&ChannelId::new(0),
+			&Counterparty::new(controller_port(), Some(ChannelId::new(1))),
+			&Version::empty(),
+			version,
+		)
+	}
+
+	/// A packet of the controller executing a transaction with a message of the type
+	fn packet(type_url: &str) -> Packet {
+		let message = Any { type_url: type_url.to_string(), value: vec![1, 2, 3] };
+		Packet {
+			sequence: 1.into(),
+			source_port: controller_port(),
+			source_channel: ChannelId::new(1),
+			destination_port: host_port_id(),
+			destination_channel: ChannelId::new(0),
+			data: InterchainAccountPacketData::execute_tx(vec![message], String::new()).to_bytes(),
+			timeout_height: Height::zero(),
+			timeout_timestamp: Timestamp::none(),
+		}
+	}
+
+	#[test]
+	fn open_try_registers_the_account() {
+		let mut ctx = context();
+		let version: Version = Metadata::new(&ConnectionId::new(1), &ConnectionId::new(0)).into();
+
+		let metadata =
+			Metadata::from_version(&open_try(&mut ctx, Order::Ordered, &version).unwrap()).unwrap();
+		let account =
+			IcaHostReader::interchain_account(&ctx, &ConnectionId::new(0), &controller_port())
+				.unwrap();
+		assert_eq!(metadata.address, account.to_string());
+
+		// reopening the channel of the account keeps the account
+		let metadata =
+			Metadata::from_version(&open_try(&mut ctx, Order::Ordered, &version).unwrap()).unwrap();
+		assert_eq!(metadata.address, account.to_string());
+	}
+
+	#[test]
+	fn open_try_rejects_invalid_channels() {
+		let mut ctx = context();
+		let version: Version = Metadata::new(&ConnectionId::new(1), &ConnectionId::new(0)).into();
+		assert!(open_try(&mut ctx, Order::Unordered, &version).is_err());
+
+		// the connections are swapped
+		let version: Version = Metadata::new(&ConnectionId::new(0), &ConnectionId::new(1)).into();
+		assert!(open_try(&mut ctx, Order::Ordered, &version).is_err());
+
+		// the account already has an open channel
+		let version: Version = Metadata::new(&ConnectionId::new(1), &ConnectionId::new(0)).into();
+		IcaHostKeeper::store_active_channel(
+			&mut ctx,
+			ConnectionId::new(0),
+			controller_port(),
+			ChannelId::new(0),
+		)
+		.unwrap();
+		assert!(open_try(&mut ctx, Order::Ordered, &version).is_err());
+		assert!(IcaHostReader::interchain_account(&ctx, &ConnectionId::new(0), &controller_port())
+			.is_none());
+	}
+
+	#[test]
+	fn recv_executes_allowed_messages() {
+		let mut ctx = context();
+		let account = IcaHostKeeper::register_interchain_account(
+			&mut ctx,
+			ConnectionId::new(0),
+			controller_port(),
+		)
+		.unwrap();
+
+		let ack = on_recv_packet(
+			&mut ctx,
+			&mut ModuleOutputBuilder::new(),
+			&packet(DUMMY_ICA_ALLOWED_MESSAGE),
+			&get_dummy_account_id(),
+		);
+		let result = String::from_utf8(base64::encode(b"executed")).unwrap();
+		assert_eq!(ack.into_result(), Ok(result));
+		let executed = ctx.app_store().lock().unwrap().ica_executed_txs.clone();
+		assert_eq!(executed.len(), 1);
+		assert_eq!(executed[0].0, account);
+		assert_eq!(executed[0].1[0].type_url, DUMMY_ICA_ALLOWED_MESSAGE);
+	}
+
+	#[test]
+	fn recv_acks_an_error_for_disallowed_messages() {
+		let mut ctx = context();
+		IcaHostKeeper::register_interchain_account(
+			&mut ctx,
+			ConnectionId::new(0),
+			controller_port(),
+		)
+		.unwrap();
+
+		let ack = on_recv_packet(
+			&mut ctx,
+			&mut ModuleOutputBuilder::new(),
+			&packet("/cosmos.gov.v1beta1.MsgVote"),
+			&get_dummy_account_id(),
+		);
+		assert!(!ack.is_successful());
+		assert!(ctx.app_store().lock().unwrap().ica_executed_txs.is_empty());
+	}
+
+	#[test]
+	fn recv_acks_an_error_without_account() {
+		let mut ctx = context();
+
+		let ack = on_recv_packet(
+			&mut ctx,
+			&mut ModuleOutputBuilder::new(),
+			&packet(DUMMY_ICA_ALLOWED_MESSAGE),
+			&get_dummy_account_id(),
+		);
+		assert!(!ack.is_successful());
+		assert!(ctx.app_store().lock().unwrap().ica_executed_txs.is_empty());
+	}
+}
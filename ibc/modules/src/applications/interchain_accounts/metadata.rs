@@ -0,0 +1,119 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Channel version negotiated by interchain accounts channels.
+
+use serde::{Deserialize, Serialize};
+
+use super::{error::Error, VERSION};
+use crate::{
+	core::{ics04_channel::Version, ics24_host::identifier::ConnectionId},
+	prelude::*,
+};
+
+/// The only encoding of the transactions executed on the host.
+pub const ENCODING_PROTOBUF: &str = "proto3";
+
+/// The only type of the transactions executed on the host: a list of messages.
+pub const TX_TYPE_SDK_MULTI_MSG: &str = "sdk_multi_msg";
+
+/// Version of an interchain accounts channel.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+	pub version: String,
+	pub controller_connection_id: String,
+	pub host_connection_id: String,
+	/// Address of the interchain account, set by the host in its `ChanOpenTry` version.
+	#[serde(default)]
+	pub address: String,
+	pub encoding: String,
+	pub tx_type: String,
+}
+
+impl Metadata {
+	/// Metadata of a channel between the given connections, without an address.
+	pub fn new(controller_connection_id: &ConnectionId, host_connection_id: &ConnectionId) -> Self {
+		Self {
+			version: VERSION.to_string(),
+			controller_connection_id: controller_connection_id.to_string(),
+			host_connection_id: host_connection_id.to_string(),
+			address: String::new(),
+			encoding: ENCODING_PROTOBUF.to_string(),
+			tx_type: TX_TYPE_SDK_MULTI_MSG.to_string(),
+		}
+	}
+
+	/// Parses the metadata of a channel version.
+	pub fn from_version(version: &Version) -> Result<Self, Error> {
+		serde_json::from_str::<Metadata>(&version.to_string())
+			.map_err(|_| Error::invalid_metadata(format!("can't parse version {}", version)))
+	}
+
+	/// Checks the metadata is supported and matches the connections the channel is built on.
+	pub fn validate(
+		&self,
+		controller_connection_id: &ConnectionId,
+		host_connection_id: &ConnectionId,
+	) -> Result<(), Error> {
+		if self.version != VERSION {
+			return Err(Error::invalid_version(self.version.clone()))
+		}
+		if self.encoding != ENCODING_PROTOBUF {
+			return Err(Error::invalid_metadata(format!("unsupported encoding {}", self.encoding)))
+		}
+		if self.tx_type != TX_TYPE_SDK_MULTI_MSG {
+			return Err(Error::invalid_metadata(format!("unsupported tx type {}", self.tx_type)))
+		}
+		if self.controller_connection_id != controller_connection_id.as_str() {
+			return Err(Error::invalid_metadata(format!(
+				"expected controller connection {}, got {}",
+				controller_connection_id, self.controller_connection_id
+			)))
+		}
+		if self.host_connection_id != host_connection_id.as_str() {
+			return Err(Error::invalid_metadata(format!(
+				"expected host connection {}, got {}",
+				host_connection_id, self.host_connection_id
+			)))
+		}
+		Ok(())
+	}
+}
+
+impl From<Metadata> for Version {
+	fn from(metadata: Metadata) -> Self {
+		Version::new(
+			serde_json::to_string(&metadata).expect("Metadata's infallible Serialize impl failed"),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn metadata_round_trips_through_the_version() {
+		let (controller, host) = (ConnectionId::new(0), ConnectionId::new(3));
+		let version: Version = Metadata::new(&controller, &host).into();
+		assert_eq!(
+			version.to_string(),
+			r#"{"version":"ics27-1","controller_connection_id":"connection-0","host_connection_id":"connection-3","address":"","encoding":"proto3","tx_type":"sdk_multi_msg"}"#
+		);
+		let metadata = Metadata::from_version(&version).unwrap();
+		assert!(metadata.validate(&controller, &host).is_ok());
+		assert!(metadata.validate(&host, &controller).is_err());
+		assert!(Metadata::from_version(&Version::ics20()).is_err());
+	}
+}
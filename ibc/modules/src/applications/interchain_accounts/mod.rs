@@ -0,0 +1,62 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ICS 27: Interchain Accounts lets a controller chain register accounts on a host chain and
+//! execute transactions with them.
+//!
+//! Every account is bound to an ordered channel between the `icacontroller-{owner}` port of the
+//! controller chain and the `icahost` port of the host chain. The channel version is JSON encoded
+//! [`Metadata`](metadata::Metadata) through which the host chain tells the controller chain the
+//! address of the account it registered. The [`controller`] and [`host`] submodules implement
+//! both ends of the protocol.
+pub mod controller;
+pub mod error;
+pub mod events;
+pub mod host;
+pub mod metadata;
+pub mod packet;
+
+use crate::{core::ics24_host::identifier::PortId, prelude::*, signer::Signer};
+use error::Error;
+
+/// Module identifier of the ICS27 controller.
+pub const CONTROLLER_MODULE_ID_STR: &str = "icacontroller";
+
+/// Module identifier of the ICS27 host.
+pub const HOST_MODULE_ID_STR: &str = "icahost";
+
+/// The port identifier the ICS27 host binds with.
+pub const HOST_PORT_ID_STR: &str = "icahost";
+
+/// Prefix of the ports the ICS27 controller binds with, one per account owner.
+pub const CONTROLLER_PORT_PREFIX: &str = "icacontroller-";
+
+/// ICS27 application current version.
+pub const VERSION: &str = "ics27-1";
+
+/// Returns the controller port of the accounts of an owner.
+pub fn controller_port_id(owner: &Signer) -> Result<PortId, Error> {
+	let port_id = format!("{}{}", CONTROLLER_PORT_PREFIX, owner);
+	port_id.parse().map_err(|e| Error::invalid_port_id(port_id, e))
+}
+
+/// Returns true if the port is a controller port.
+pub fn is_controller_port(port_id: &PortId) -> bool {
+	port_id.as_str().starts_with(CONTROLLER_PORT_PREFIX)
+}
+
+/// Returns the host port.
+pub fn host_port_id() -> PortId {
+	HOST_PORT_ID_STR.parse().expect("icahost is a valid port identifier")
+}
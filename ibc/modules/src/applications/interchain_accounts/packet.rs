@@ -0,0 +1,128 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Packets sent by the controller to execute transactions on the host.
+
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::applications::interchain_accounts::v1::{
+		CosmosTx as RawCosmosTx, InterchainAccountPacketData as RawInterchainAccountPacketData,
+		Type as RawType,
+	},
+};
+use prost::Message;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use subtle_encoding::base64;
+
+use super::error::Error;
+use crate::prelude::*;
+
+/// Kind of the packets of interchain accounts channels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PacketType {
+	/// Execute a transaction with the interchain account
+	#[serde(rename = "TYPE_EXECUTE_TX")]
+	ExecuteTx,
+}
+
+/// ICS27 packet data, JSON encoded like the protobuf JSON mapping of ibc-go.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterchainAccountPacketData {
+	#[serde(rename = "type")]
+	pub packet_type: PacketType,
+	/// Protobuf encoded `CosmosTx`
+	#[serde(serialize_with = "ser_base64", deserialize_with = "deser_base64")]
+	pub data: Vec<u8>,
+	#[serde(default)]
+	pub memo: String,
+}
+
+impl InterchainAccountPacketData {
+	/// Packet data executing the messages as a single transaction.
+	pub fn execute_tx(messages: Vec<Any>, memo: String) -> Self {
+		Self {
+			packet_type: PacketType::ExecuteTx,
+			data: RawCosmosTx { messages }.encode_to_vec(),
+			memo,
+		}
+	}
+
+	/// Decodes the messages of the transaction.
+	pub fn messages(&self) -> Result<Vec<Any>, Error> {
+		let tx = RawCosmosTx::decode(self.data.as_slice())
+			.map_err(|_| Error::packet_data_deserialization())?;
+		if tx.messages.is_empty() {
+			return Err(Error::empty_messages())
+		}
+		Ok(tx.messages)
+	}
+
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+		serde_json::from_slice(bytes).map_err(|_| Error::packet_data_deserialization())
+	}
+
+	pub fn to_bytes(&self) -> Vec<u8> {
+		serde_json::to_vec(self)
+			.expect("InterchainAccountPacketData's infallible Serialize impl failed")
+	}
+}
+
+impl TryFrom<RawInterchainAccountPacketData> for InterchainAccountPacketData {
+	type Error = Error;
+
+	fn try_from(raw: RawInterchainAccountPacketData) -> Result<Self, Self::Error> {
+		if raw.r#type != RawType::ExecuteTx as i32 {
+			return Err(Error::unsupported_packet_type(raw.r#type))
+		}
+		Ok(Self { packet_type: PacketType::ExecuteTx, data: raw.data, memo: raw.memo })
+	}
+}
+
+impl From<InterchainAccountPacketData> for RawInterchainAccountPacketData {
+	fn from(data: InterchainAccountPacketData) -> Self {
+		let r#type = match data.packet_type {
+			PacketType::ExecuteTx => RawType::ExecuteTx,
+		};
+		Self { r#type: r#type as i32, data: data.data, memo: data.memo }
+	}
+}
+
+fn ser_base64<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+	let encoded = String::from_utf8(base64::encode(bytes)).map_err(serde::ser::Error::custom)?;
+	serializer.serialize_str(&encoded)
+}
+
+fn deser_base64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+	let encoded = String::deserialize(deserializer)?;
+	base64::decode(encoded).map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn packet_data_round_trips() {
+		let message = Any { type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(), value: vec![1] };
+		let data = InterchainAccountPacketData::execute_tx(vec![message.clone()], "memo".into());
+		let bytes = data.to_bytes();
+		assert!(String::from_utf8(bytes.clone())
+			.unwrap()
+			.starts_with(r#"{"type":"TYPE_EXECUTE_TX""#));
+		let decoded = InterchainAccountPacketData::from_bytes(&bytes).unwrap();
+		assert_eq!(decoded, data);
+		assert_eq!(decoded.messages().unwrap(), vec![message]);
+		assert!(InterchainAccountPacketData::execute_tx(vec![], "".into()).messages().is_err());
+	}
+}
@@ -0,0 +1,199 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use core::{fmt, str::FromStr};
+
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use super::error::Error;
+use crate::{
+	applications::transfer::{TracePath, TracePrefix},
+	core::ics24_host::identifier::{ChannelId, PortId},
+	prelude::*,
+};
+
+/// Class id on the chain that created the class
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Display)]
+#[serde(transparent)]
+pub struct BaseClassId(String);
+
+impl BaseClassId {
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+impl FromStr for BaseClassId {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.trim().is_empty() {
+			Err(Error::empty_base_class_id())
+		} else {
+			Ok(BaseClassId(s.to_owned()))
+		}
+	}
+}
+
+/// A class id prefixed with the `{port-id}/{channel-id}`s the class was sent through.
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String")]
+#[serde(try_from = "String")]
+pub struct PrefixedClassId {
+	/// A series of `{port-id}/{channel-id}`s for tracing the source of the class.
+	pub trace_path: TracePath,
+	/// Class id on the chain that created the class.
+	pub base_class_id: BaseClassId,
+}
+
+impl PrefixedClassId {
+	/// Removes the specified prefix from the trace path if there is a match, otherwise does
+	/// nothing.
+	pub fn remove_trace_prefix(&mut self, prefix: &TracePrefix) {
+		self.trace_path.remove_prefix(prefix)
+	}
+
+	/// Adds the specified prefix to the trace path.
+	pub fn add_trace_prefix(&mut self, prefix: TracePrefix) {
+		self.trace_path.add_prefix(prefix)
+	}
+}
+
+/// Returns true if the class originally came from the sender chain and false otherwise.
+/// See [`is_sender_chain_source`](crate::applications::transfer::is_sender_chain_source) for
+/// what being the source of a token means.
+pub fn is_sender_chain_source(
+	source_port: PortId,
+	source_channel: ChannelId,
+	class_id: &PrefixedClassId,
+) -> bool {
+	!is_receiver_chain_source(source_port, source_channel, class_id)
+}
+
+/// Returns true if the class originally came from the receiving chain and false otherwise.
+pub fn is_receiver_chain_source(
+	source_port: PortId,
+	source_channel: ChannelId,
+	class_id: &PrefixedClassId,
+) -> bool {
+	let prefix = TracePrefix::new(source_port, source_channel);
+	class_id.trace_path.starts_with(&prefix)
+}
+
+impl FromStr for PrefixedClassId {
+	type Err = Error;
+
+	/// Unlike denominations, base class ids may contain `/`. The trace is made of the leading
+	/// `{port-id}/{channel-id}` pairs and the rest is the base class id.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let parts: Vec<&str> = s.split('/').collect();
+		let mut trace_len = 0;
+		while trace_len + 2 < parts.len() &&
+			PortId::from_str(parts[trace_len]).is_ok() &&
+			ChannelId::from_str(parts[trace_len + 1]).is_ok()
+		{
+			trace_len += 2;
+		}
+		let trace_path =
+			TracePath::try_from(parts[..trace_len].to_vec()).map_err(Error::invalid_trace)?;
+		let base_class_id = BaseClassId::from_str(&parts[trace_len..].join("/"))?;
+		Ok(Self { trace_path, base_class_id })
+	}
+}
+
+impl TryFrom<String> for PrefixedClassId {
+	type Error = Error;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		PrefixedClassId::from_str(&value)
+	}
+}
+
+impl From<BaseClassId> for PrefixedClassId {
+	fn from(base_class_id: BaseClassId) -> Self {
+		Self { trace_path: Default::default(), base_class_id }
+	}
+}
+
+impl From<PrefixedClassId> for String {
+	fn from(value: PrefixedClassId) -> Self {
+		value.to_string()
+	}
+}
+
+impl fmt::Display for PrefixedClassId {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.trace_path.is_empty() {
+			write!(f, "{}", self.base_class_id)
+		} else {
+			write!(f, "{}/{}", self.trace_path, self.base_class_id)
+		}
+	}
+}
+
+/// Identifier of a token within its class
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize, Display)]
+#[serde(transparent)]
+pub struct TokenId(String);
+
+impl TokenId {
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+impl FromStr for TokenId {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.trim().is_empty() {
+			Err(Error::empty_token_id())
+		} else {
+			Ok(TokenId(s.to_owned()))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_class_id_trace() -> Result<(), Error> {
+		let class_id = PrefixedClassId::from_str("nft-transfer/channel-1/wasm.contract/kitties")?;
+		assert_eq!(class_id.base_class_id.as_str(), "wasm.contract/kitties");
+		assert_eq!(class_id.to_string(), "nft-transfer/channel-1/wasm.contract/kitties");
+
+		let class_id = PrefixedClassId::from_str("kitties")?;
+		assert!(class_id.trace_path.is_empty());
+
+		// a trailing port/channel pair is part of the base class id
+		let class_id = PrefixedClassId::from_str("nft-transfer/channel-1")?;
+		assert!(class_id.trace_path.is_empty());
+		assert_eq!(class_id.base_class_id.as_str(), "nft-transfer/channel-1");
+
+		assert!(PrefixedClassId::from_str("").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_class_id_source() -> Result<(), Error> {
+		let class_id = PrefixedClassId::from_str("nft-transfer/channel-1/kitties")?;
+		let port_id = PortId::from_str("nft-transfer").unwrap();
+		assert!(is_receiver_chain_source(port_id.clone(), ChannelId::new(1), &class_id));
+		assert!(is_sender_chain_source(port_id, ChannelId::new(0), &class_id));
+		Ok(())
+	}
+}
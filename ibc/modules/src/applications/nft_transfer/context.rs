@@ -0,0 +1,340 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use subtle_encoding::hex;
+
+use super::error::Error;
+use crate::{
+	applications::{
+		nft_transfer::{
+			events::{AckEvent, RecvEvent, TimeoutEvent},
+			packet::PacketData,
+			relay::{
+				on_ack_packet::process_ack_packet, on_recv_packet::process_recv_packet,
+				on_timeout_packet::process_timeout_packet,
+			},
+			PrefixedClassId, TokenId, VERSION,
+		},
+		transfer::acknowledgement::Acknowledgement,
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			context::{ChannelKeeper, ChannelReader},
+			msgs::acknowledgement::Acknowledgement as GenericAcknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{ModuleOutputBuilder, ReaderContext},
+	},
+	prelude::*,
+	signer::Signer,
+};
+
+/// Uri and data of a class or token, both of which are optional in ICS721.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Metadata {
+	pub uri: Option<String>,
+	pub data: Option<String>,
+}
+
+pub trait NftReader: ChannelReader
+where
+	Self: Sized,
+{
+	type AccountId: TryFrom<Signer>;
+
+	/// get_port returns the portID for the nft transfer module.
+	fn get_port(&self) -> Result<PortId, Error>;
+
+	/// Returns the escrow account id for a port and channel combination
+	fn get_channel_escrow_address(
+		&self,
+		port_id: &PortId,
+		channel_id: ChannelId,
+	) -> Result<<Self as NftReader>::AccountId, Error> {
+		let hash = cosmos_adr028_escrow_address(self, port_id, channel_id);
+
+		String::from_utf8(hex::encode_upper(hash))
+			.expect("hex encoded bytes are not valid UTF8")
+			.parse::<Signer>()
+			.map_err(Error::signer)?
+			.try_into()
+			.map_err(|_| Error::parse_account_failure())
+	}
+
+	/// Returns true iff send is enabled.
+	fn is_send_enabled(&self) -> bool;
+
+	/// Returns true iff receive is enabled.
+	fn is_receive_enabled(&self) -> bool;
+
+	/// Returns the metadata of a class, or an error if the class doesn't exist.
+	fn class_metadata(&self, class_id: &PrefixedClassId) -> Result<Metadata, Error>;
+
+	/// Returns the metadata of a token, or an error if the token doesn't exist.
+	fn token_metadata(
+		&self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+	) -> Result<Metadata, Error>;
+
+	/// Returns a hash of the prefixed class id.
+	/// Implement only if the host chain supports hashed class ids.
+	fn class_hash_string(&self, _class_id: &PrefixedClassId) -> Option<String> {
+		None
+	}
+}
+
+// https://github.com/cosmos/cosmos-sdk/blob/master/docs/architecture/adr-028-public-key-addresses.md
+fn cosmos_adr028_escrow_address(
+	ctx: &dyn ChannelReader,
+	port_id: &PortId,
+	channel_id: ChannelId,
+) -> Vec<u8> {
+	let contents = format!("{}/{}", port_id, channel_id);
+	let mut data = VERSION.as_bytes().to_vec();
+	data.extend_from_slice(&[0]);
+	data.extend_from_slice(contents.as_bytes());
+
+	let mut hash = ctx.hash(data);
+	hash.truncate(20);
+	hash
+}
+
+pub trait NftKeeper: ChannelKeeper {
+	type AccountId;
+
+	/// Creates the class if it doesn't exist yet, otherwise overwrites its metadata.
+	fn create_or_update_class(
+		&mut self,
+		class_id: &PrefixedClassId,
+		metadata: Metadata,
+	) -> Result<(), Error>;
+
+	/// Moves a token between accounts, failing if `from` doesn't own it.
+	fn transfer_token(
+		&mut self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+		from: &Self::AccountId,
+		to: &Self::AccountId,
+	) -> Result<(), Error>;
+
+	/// Mints a (voucher) token of an existing class to `to`.
+	fn mint_token(
+		&mut self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+		metadata: Metadata,
+		to: &Self::AccountId,
+	) -> Result<(), Error>;
+
+	/// Burns a (voucher) token, failing if `owner` doesn't own it.
+	fn burn_token(
+		&mut self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+		owner: &Self::AccountId,
+	) -> Result<(), Error>;
+}
+
+/// Captures all the dependencies which the ICS721 module requires to be able to dispatch and
+/// process IBC messages.
+pub trait NftContext:
+	NftKeeper<AccountId = <Self as NftContext>::AccountId>
+	+ NftReader<AccountId = <Self as NftContext>::AccountId>
+	+ ReaderContext
+{
+	type AccountId: TryFrom<Signer>;
+}
+
+fn validate_transfer_channel_params(
+	ctx: &mut impl NftContext,
+	order: Order,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	version: &Version,
+) -> Result<(), Error> {
+	if channel_id.sequence() > (u32::MAX as u64) {
+		return Err(Error::chan_seq_exceeds_limit(channel_id.sequence()))
+	}
+
+	if order != Order::Unordered {
+		return Err(Error::channel_not_unordered(order))
+	}
+
+	let bound_port = ctx.get_port()?;
+	if port_id != &bound_port {
+		return Err(Error::invalid_port(port_id.clone(), bound_port))
+	}
+
+	if version != &Version::ics721() {
+		return Err(Error::invalid_version(version.clone()))
+	}
+
+	Ok(())
+}
+
+fn validate_counterparty_version(counterparty_version: &Version) -> Result<(), Error> {
+	if counterparty_version == &Version::ics721() {
+		Ok(())
+	} else {
+		Err(Error::invalid_counterparty_version(counterparty_version.clone()))
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_init(
+	ctx: &mut impl NftContext,
+	_output: &mut ModuleOutputBuilder,
+	order: Order,
+	_connection_hops: &[ConnectionId],
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	_counterparty: &Counterparty,
+	version: &Version,
+) -> Result<(), Error> {
+	validate_transfer_channel_params(ctx, order, port_id, channel_id, version)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_try(
+	ctx: &mut impl NftContext,
+	_output: &mut ModuleOutputBuilder,
+	order: Order,
+	_connection_hops: &[ConnectionId],
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	_counterparty: &Counterparty,
+	version: &Version,
+	counterparty_version: &Version,
+) -> Result<Version, Error> {
+	validate_transfer_channel_params(ctx, order, port_id, channel_id, version)?;
+	validate_counterparty_version(counterparty_version)?;
+	Ok(Version::ics721())
+}
+
+pub fn on_chan_open_ack(
+	_ctx: &mut impl NftContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+	counterparty_version: &Version,
+) -> Result<(), Error> {
+	validate_counterparty_version(counterparty_version)
+}
+
+pub fn on_chan_open_confirm(
+	_ctx: &mut impl NftContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Ok(())
+}
+
+pub fn on_chan_close_init(
+	_ctx: &mut impl NftContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Err(Error::cant_close_channel())
+}
+
+pub fn on_chan_close_confirm(
+	_ctx: &mut impl NftContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Ok(())
+}
+
+pub fn on_recv_packet<Ctx: 'static + NftContext>(
+	ctx: &mut Ctx,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	_relayer: &Signer,
+) -> Acknowledgement {
+	let data = match serde_json::from_slice::<PacketData>(&packet.data) {
+		Ok(data) => data,
+		Err(_) => return Acknowledgement::Error(Error::packet_data_deserialization().to_string()),
+	};
+
+	log::debug!(target: "ics::nft-transfer", "dispatch {:?}", &data);
+	let ack = match process_recv_packet(ctx, output, packet, data.clone()) {
+		Ok(_) => Acknowledgement::success(),
+		Err(e) => Acknowledgement::Error(e.to_string()),
+	};
+
+	let recv_event = RecvEvent {
+		receiver: data.receiver,
+		class_id: data.class_id,
+		token_ids: data.token_ids,
+		success: ack.is_successful(),
+	};
+	output.emit(recv_event.into());
+
+	ack
+}
+
+pub fn on_acknowledgement_packet(
+	ctx: &mut impl NftContext,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	acknowledgement: &GenericAcknowledgement,
+	_relayer: &Signer,
+) -> Result<(), Error> {
+	let data = serde_json::from_slice::<PacketData>(&packet.data)
+		.map_err(|_| Error::packet_data_deserialization())?;
+
+	let acknowledgement = serde_json::from_slice::<Acknowledgement>(acknowledgement.as_ref())
+		.map_err(|_| Error::ack_deserialization())?;
+
+	process_ack_packet(ctx, packet, &data, &acknowledgement)?;
+
+	let ack_event = AckEvent {
+		receiver: data.receiver,
+		class_id: data.class_id,
+		token_ids: data.token_ids,
+		acknowledgement,
+	};
+	output.emit(ack_event.into());
+
+	Ok(())
+}
+
+pub fn on_timeout_packet(
+	ctx: &mut impl NftContext,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	_relayer: &Signer,
+) -> Result<(), Error> {
+	let data = serde_json::from_slice::<PacketData>(&packet.data)
+		.map_err(|_| Error::packet_data_deserialization())?;
+
+	process_timeout_packet(ctx, packet, &data)?;
+
+	let timeout_event = TimeoutEvent {
+		refund_receiver: data.sender,
+		refund_class_id: data.class_id,
+		refund_token_ids: data.token_ids,
+	};
+	output.emit(timeout_event.into());
+
+	Ok(())
+}
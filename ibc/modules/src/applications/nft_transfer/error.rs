@@ -0,0 +1,135 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use flex_error::{define_error, TraceError};
+use tendermint_proto::Error as TendermintProtoError;
+
+use crate::{
+	applications::transfer::error::Error as Ics20Error,
+	core::{
+		ics04_channel::{channel::Order, error as channel_error, Version},
+		ics24_host::{
+			error::ValidationError,
+			identifier::{ChannelId, PortId},
+		},
+	},
+	prelude::*,
+	signer::SignerError,
+};
+
+define_error! {
+	#[derive(Debug, PartialEq, Eq)]
+	Error {
+		Ics04Channel
+			[ channel_error::Error ]
+			|_ | { "Ics04 channel error" },
+
+		DestinationChannelNotFound
+			{ port_id: PortId, channel_id: ChannelId }
+			| e | { format_args!("destination channel not found in the counterparty of port_id {0} and channel_id {1} ", e.port_id, e.channel_id) },
+
+		InvalidPortId
+			{ context: String }
+			[ ValidationError ]
+			| _ | { "invalid port identifier" },
+
+		InvalidChannelId
+			{ context: String }
+			[ ValidationError ]
+			| _ | { "invalid channel identifier" },
+
+		InvalidTrace
+			[ Ics20Error ]
+			| _ | { "invalid class trace" },
+
+		EmptyBaseClassId
+			|_| { "base class id is empty" },
+
+		EmptyTokenId
+			|_| { "token id is empty" },
+
+		EmptyTokenIds
+			|_| { "at least one token must be transferred" },
+
+		TokenMetadataLengthMismatch
+			{ tokens: usize, uris: usize, data: usize }
+			| e | { format_args!("{0} token ids but {1} token uris and {2} token data", e.tokens, e.uris, e.data) },
+
+		TokenNotOwned
+			{ class_id: String, token_id: String }
+			| e | { format_args!("token {1} of class {0} isn't owned by the sender", e.class_id, e.token_id) },
+
+		ClassNotFound
+			{ class_id: String }
+			| e | { format_args!("class {0} doesn't exist", e.class_id) },
+
+		TokenNotFound
+			{ class_id: String, token_id: String }
+			| e | { format_args!("token {1} of class {0} doesn't exist", e.class_id, e.token_id) },
+
+		Signer
+			[ SignerError ]
+			| _ | { "failed to parse signer" },
+
+		ChanSeqExceedsLimit
+			{ sequence: u64 }
+			| e | { format_args!("channel sequence ({0}) exceeds limit of {1}", e.sequence, u32::MAX) },
+
+		ChannelNotUnordered
+			{ order: Order }
+			| e | { format_args!("expected '{0}' channel, got '{1}'", Order::Unordered, e.order) },
+
+		InvalidVersion
+			{ version: Version }
+			| e | { format_args!("expected version '{0}', got '{1}'", super::VERSION, e.version) },
+
+		InvalidCounterpartyVersion
+			{ version: Version }
+			| e | { format_args!("expected counterparty version '{0}', got '{1}'", super::VERSION, e.version) },
+
+		CantCloseChannel
+			| _ | { "channel cannot be closed" },
+
+		PacketDataDeserialization
+			| _ | { "failed to deserialize packet data" },
+
+		AckDeserialization
+			| _ | { "failed to deserialize acknowledgement" },
+
+		ReceiveDisabled
+			| _ | { "receive is not enabled" },
+
+		SendDisabled
+			| _ | { "send is not enabled" },
+
+		ParseAccountFailure
+			| _ | { "failed to parse as AccountId" },
+
+		InvalidPort
+			{ port_id: PortId, exp_port_id: PortId }
+			| e | { format_args!("invalid port: '{0}', expected '{1}'", e.port_id, e.exp_port_id) },
+
+		InvalidPacketTimeoutTimestamp
+			{ timestamp: u64 }
+			| _ | { "invalid packet timeout timestamp value" },
+
+		DecodeRawMsg
+			[ TraceError<TendermintProtoError> ]
+			| _ | { "error decoding raw msg" },
+
+		ImplementationSpecific
+			{ reason: String }
+			| e | { format_args!("implementation specific error: {}", e.reason) },
+	}
+}
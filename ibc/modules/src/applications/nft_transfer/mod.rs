@@ -0,0 +1,36 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ICS 721: Non-Fungible Token Transfer moves NFTs between chains. Like ICS 20 it escrows the
+//! tokens of classes the sending chain is the source of and mints vouchers of them on the
+//! receiving chain, tracing the path the class took in its class id.
+pub mod class;
+pub mod context;
+pub mod error;
+pub mod events;
+pub mod msgs;
+pub mod packet;
+pub mod relay;
+
+pub use class::*;
+
+/// Module identifier for the ICS721 application.
+pub const MODULE_ID_STR: &str = "nft-transfer";
+
+/// The port identifier that the ICS721 applications
+/// typically bind with.
+pub const PORT_ID_STR: &str = "nft-transfer";
+
+/// ICS721 application current version.
+pub const VERSION: &str = "ics721-1";
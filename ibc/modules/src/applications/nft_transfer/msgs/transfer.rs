@@ -0,0 +1,54 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This is the definition of the NFT transfer message that an application submits to a chain.
+
+use crate::{
+	applications::nft_transfer::{PrefixedClassId, TokenId},
+	core::{
+		ics02_client::height::Height,
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	prelude::*,
+	signer::Signer,
+	timestamp::Timestamp,
+};
+
+/// Message used to build an ICS721 NFT transfer packet.
+///
+/// Like its ICS20 counterpart, the message lacks the packet sequence and the destination
+/// port/channel, which are figured out when the packet is built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgTransfer {
+	/// the port on which the packet will be sent
+	pub source_port: PortId,
+	/// the channel by which the packet will be sent
+	pub source_channel: ChannelId,
+	/// the class of the tokens to be transferred
+	pub class_id: PrefixedClassId,
+	/// the tokens to be transferred
+	pub token_ids: Vec<TokenId>,
+	/// the sender address
+	pub sender: Signer,
+	/// the recipient address on the destination chain
+	pub receiver: Signer,
+	/// Timeout height relative to the current block height.
+	/// The timeout is disabled when set to 0.
+	pub timeout_height: Height,
+	/// Timeout timestamp relative to the current block timestamp.
+	/// The timeout is disabled when set to 0.
+	pub timeout_timestamp: Timestamp,
+	/// Memo field
+	pub memo: String,
+}
@@ -0,0 +1,93 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+use super::{error::Error, PrefixedClassId, TokenId};
+use crate::{prelude::*, signer::Signer};
+
+/// ICS721 packet data, encoded as JSON with the field names of the specification.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PacketData {
+	pub class_id: PrefixedClassId,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub class_uri: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub class_data: Option<String>,
+	pub token_ids: Vec<TokenId>,
+	/// Either empty or one per token id
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub token_uris: Vec<String>,
+	/// Either empty or one per token id
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub token_data: Vec<String>,
+	pub sender: Signer,
+	pub receiver: Signer,
+	#[serde(default)]
+	pub memo: String,
+}
+
+impl PacketData {
+	pub fn validate_basic(&self) -> Result<(), Error> {
+		if self.token_ids.is_empty() {
+			return Err(Error::empty_token_ids())
+		}
+		let tokens = self.token_ids.len();
+		let (uris, data) = (self.token_uris.len(), self.token_data.len());
+		if (uris != 0 && uris != tokens) || (data != 0 && data != tokens) {
+			return Err(Error::token_metadata_length_mismatch(tokens, uris, data))
+		}
+		Ok(())
+	}
+
+	/// Returns the token ids with their uri and data, if any.
+	pub fn tokens(&self) -> impl Iterator<Item = (&TokenId, Option<&String>, Option<&String>)> {
+		self.token_ids
+			.iter()
+			.enumerate()
+			.map(|(i, token_id)| (token_id, self.token_uris.get(i), self.token_data.get(i)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::str::FromStr;
+
+	#[test]
+	fn packet_data_uses_spec_field_names() {
+		let data = PacketData {
+			class_id: PrefixedClassId::from_str("nft-transfer/channel-0/kitties").unwrap(),
+			class_uri: Some("https://kitties.example".to_string()),
+			class_data: None,
+			token_ids: vec![TokenId::from_str("kitty-1").unwrap()],
+			token_uris: vec![],
+			token_data: vec![],
+			sender: "sender".parse().unwrap(),
+			receiver: "receiver".parse().unwrap(),
+			memo: "".to_string(),
+		};
+		let json = serde_json::to_string(&data).unwrap();
+		assert_eq!(
+			json,
+			r#"{"classId":"nft-transfer/channel-0/kitties","classUri":"https://kitties.example","tokenIds":["kitty-1"],"sender":"sender","receiver":"receiver","memo":""}"#
+		);
+		assert_eq!(serde_json::from_str::<PacketData>(&json).unwrap(), data);
+		assert!(data.validate_basic().is_ok());
+		assert!(PacketData { token_uris: vec!["a".into(), "b".into()], ..data }
+			.validate_basic()
+			.is_err());
+	}
+}
@@ -0,0 +1,393 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module implements the processing logic for ICS721 (NFT transfer) messages.
+use crate::{
+	applications::nft_transfer::{
+		context::{Metadata, NftContext},
+		error::Error,
+		is_sender_chain_source,
+		packet::PacketData,
+	},
+	core::ics04_channel::packet::Packet,
+	prelude::*,
+};
+
+pub mod on_ack_packet;
+pub mod on_recv_packet;
+pub mod on_timeout_packet;
+pub mod send_transfer;
+
+fn refund_packet_tokens(
+	ctx: &mut impl NftContext,
+	packet: &Packet,
+	data: &PacketData,
+) -> Result<(), Error> {
+	let sender = data.sender.clone().try_into().map_err(|_| Error::parse_account_failure())?;
+
+	if is_sender_chain_source(packet.source_port.clone(), packet.source_channel, &data.class_id) {
+		// unescrow tokens back to sender
+		let escrow_address =
+			ctx.get_channel_escrow_address(&packet.source_port, packet.source_channel)?;
+		for token_id in &data.token_ids {
+			ctx.transfer_token(&data.class_id, token_id, &escrow_address, &sender)?;
+		}
+	}
+	// mint vouchers back to sender
+	else {
+		for (token_id, uri, token_data) in data.tokens() {
+			let metadata = Metadata { uri: uri.cloned(), data: token_data.cloned() };
+			ctx.mint_token(&data.class_id, token_id, metadata, &sender)?;
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		applications::{
+			nft_transfer::{
+				context::{
+					on_acknowledgement_packet, on_recv_packet, on_timeout_packet, NftReader,
+				},
+				msgs::transfer::MsgTransfer,
+				relay::send_transfer::send_transfer,
+				PrefixedClassId, TokenId, PORT_ID_STR,
+			},
+			transfer::acknowledgement::Acknowledgement,
+		},
+		core::{
+			ics02_client::height::Height,
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				version::get_compatible_versions,
+			},
+			ics04_channel::{
+				channel::{ChannelEnd, Counterparty, Order, State},
+				context::ChannelReader,
+				msgs::acknowledgement::Acknowledgement as GenericAcknowledgement,
+				Version,
+			},
+			ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			ics26_routing::context::ModuleOutputBuilder,
+		},
+		events::IbcEvent,
+		handler::HandlerOutputBuilder,
+		mock::context::{MockClientTypes, MockContext},
+		signer::Signer,
+		test_utils::{get_dummy_account_id, DummyTransferModule},
+		timestamp::{Timestamp, ZERO_DURATION},
+	};
+	use core::str::FromStr;
+
+	type Ctx = DummyTransferModule<MockClientTypes>;
+
+	fn port_id() -> PortId {
+		PortId::from_str(PORT_ID_STR).unwrap()
+	}
+
+	fn class_id(s: &str) -> PrefixedClassId {
+		PrefixedClassId::from_str(s).unwrap()
+	}
+
+	fn token_id() -> TokenId {
+		TokenId::from_str("kitty-1").unwrap()
+	}
+
+	fn counterparty_account() -> Signer {
+		"cosmos1wxeyh7zgn4tctjzs0vtqpc6p5cxq5t2muzl7ng".parse().unwrap()
+	}
+
+	/// A context with an open nft-transfer channel-0, whose counterparty is channel-1
+	fn context() -> Ctx {
+		let connection_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::new(
+				ClientId::default(),
+				Some(ConnectionId::default()),
+				Default::default(),
+			),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+		let channel_end = ChannelEnd::new(
+			State::Open,
+			Order::Unordered,
+			Counterparty::new(port_id(), Some(ChannelId::new(1))),
+			vec![ConnectionId::default()],
+			Version::ics721(),
+		);
+		let ctx = MockContext::<MockClientTypes>::default()
+			.with_client(&ClientId::default(), Height::new(0, 5))
+			.with_connection(ConnectionId::default(), connection_end)
+			.with_channel(port_id(), ChannelId::new(0), channel_end)
+			.with_send_sequence(port_id(), ChannelId::new(0), 1.into());
+		DummyTransferModule::new(ctx.ibc_store_share())
+	}
+
+	fn escrow_address(ctx: &Ctx) -> Signer {
+		NftReader::get_channel_escrow_address(ctx, &port_id(), ChannelId::new(0)).unwrap()
+	}
+
+	fn store_token(ctx: &Ctx, class_id: &PrefixedClassId, owner: Signer) {
+		let app_store = ctx.app_store();
+		let mut app_store = app_store.lock().unwrap();
+		app_store.nft_classes.insert(class_id.clone(), Default::default());
+		app_store.nft_tokens.insert(
+			(class_id.clone(), token_id()),
+			(owner, Metadata { uri: Some("ipfs://kitty-1".to_string()), data: None }),
+		);
+	}
+
+	fn token_owner(ctx: &Ctx, class_id: &PrefixedClassId) -> Option<Signer> {
+		let app_store = ctx.app_store();
+		let app_store = app_store.lock().unwrap();
+		app_store
+			.nft_tokens
+			.get(&(class_id.clone(), token_id()))
+			.map(|(owner, _)| owner.clone())
+	}
+
+	/// Sends the token of the class to the counterparty, returns the packet sent
+	fn send(ctx: &mut Ctx, class_id: &PrefixedClassId) -> Result<Packet, Error> {
+		let msg = MsgTransfer {
+			source_port: port_id(),
+			source_channel: ChannelId::new(0),
+			class_id: class_id.clone(),
+			token_ids: vec![token_id()],
+			sender: get_dummy_account_id(),
+			receiver: counterparty_account(),
+			timeout_height: Height::zero(),
+			timeout_timestamp: Timestamp::none(),
+			memo: "".to_string(),
+		};
+		let mut output = HandlerOutputBuilder::new();
+		send_transfer(ctx, &mut output, msg)?;
+		Ok(output
+			.with_result(())
+			.events
+			.into_iter()
+			.find_map(|event| match event {
+				IbcEvent::SendPacket(event) => Some(event.packet),
+				_ => None,
+			})
+			.expect("the packet sent is emitted"))
+	}
+
+	/// A packet sent by the counterparty, transferring the token of the class to the receiver
+	fn received_packet(class: &str, receiver: Signer) -> Packet {
+		let data = PacketData {
+			class_id: class_id(class),
+			class_uri: Some("https://kitties.example".to_string()),
+			class_data: None,
+			token_ids: vec![token_id()],
+			token_uris: vec!["ipfs://kitty-1".to_string()],
+			token_data: vec![],
+			sender: counterparty_account(),
+			receiver,
+			memo: "".to_string(),
+		};
+		Packet {
+			sequence: 1.into(),
+			source_port: port_id(),
+			source_channel: ChannelId::new(1),
+			destination_port: port_id(),
+			destination_channel: ChannelId::new(0),
+			data: serde_json::to_vec(&data).unwrap(),
+			timeout_height: Height::zero(),
+			timeout_timestamp: Timestamp::none(),
+		}
+	}
+
+	fn ack(ack: Acknowledgement) -> GenericAcknowledgement {
+		GenericAcknowledgement::from_bytes(ack.to_string().into_bytes())
+	}
+
+	#[test]
+	fn send_escrows_source_tokens() {
+		let mut ctx = context();
+		let kitties = class_id("kitties");
+		store_token(&ctx, &kitties, get_dummy_account_id());
+
+		let packet = send(&mut ctx, &kitties).unwrap();
+		assert_eq!(token_owner(&ctx, &kitties), Some(escrow_address(&ctx)));
+		assert_eq!(packet.sequence, 1.into());
+		assert_eq!(packet.destination_channel, ChannelId::new(1));
+		let data = serde_json::from_slice::<PacketData>(&packet.data).unwrap();
+		assert_eq!(data.class_id, kitties);
+		assert_eq!(data.token_uris, vec!["ipfs://kitty-1".to_string()]);
+		assert_eq!(ctx.get_next_sequence_send(&(port_id(), ChannelId::new(0))).unwrap(), 2.into());
+	}
+
+	#[test]
+	fn send_burns_vouchers() {
+		let mut ctx = context();
+		let vouchers = class_id("nft-transfer/channel-0/kitties");
+		store_token(&ctx, &vouchers, get_dummy_account_id());
+
+		send(&mut ctx, &vouchers).unwrap();
+		assert_eq!(token_owner(&ctx, &vouchers), None);
+	}
+
+	#[test]
+	fn send_fails_for_tokens_of_others() {
+		let mut ctx = context();
+		let kitties = class_id("kitties");
+		store_token(&ctx, &kitties, counterparty_account());
+
+		assert!(send(&mut ctx, &kitties).is_err());
+		assert_eq!(token_owner(&ctx, &kitties), Some(counterparty_account()));
+		assert_eq!(ctx.get_next_sequence_send(&(port_id(), ChannelId::new(0))).unwrap(), 1.into());
+	}
+
+	#[test]
+	fn recv_mints_vouchers() {
+		let mut ctx = context();
+		let packet = received_packet("kitties", get_dummy_account_id());
+
+		let ack = on_recv_packet(
+			&mut ctx,
+			&mut ModuleOutputBuilder::new(),
+			&packet,
+			&get_dummy_account_id(),
+		);
+		assert!(ack.is_successful());
+		let vouchers = class_id("nft-transfer/channel-0/kitties");
+		assert_eq!(token_owner(&ctx, &vouchers), Some(get_dummy_account_id()));
+		assert_eq!(
+			NftReader::class_metadata(&ctx, &vouchers).unwrap().uri.as_deref(),
+			Some("https://kitties.example")
+		);
+		assert_eq!(
+			NftReader::token_metadata(&ctx, &vouchers, &token_id()).unwrap().uri.as_deref(),
+			Some("ipfs://kitty-1")
+		);
+	}
+
+	#[test]
+	fn recv_unescrows_returning_tokens() {
+		let mut ctx = context();
+		let kitties = class_id("kitties");
+		store_token(&ctx, &kitties, escrow_address(&ctx));
+		let packet = received_packet("nft-transfer/channel-1/kitties", get_dummy_account_id());
+
+		let ack = on_recv_packet(
+			&mut ctx,
+			&mut ModuleOutputBuilder::new(),
+			&packet,
+			&get_dummy_account_id(),
+		);
+		assert!(ack.is_successful());
+		assert_eq!(token_owner(&ctx, &kitties), Some(get_dummy_account_id()));
+	}
+
+	#[test]
+	fn recv_acks_an_error_for_tokens_not_escrowed() {
+		let mut ctx = context();
+		let kitties = class_id("kitties");
+		store_token(&ctx, &kitties, counterparty_account());
+		let packet = received_packet("nft-transfer/channel-1/kitties", get_dummy_account_id());
+
+		let ack = on_recv_packet(
+			&mut ctx,
+			&mut ModuleOutputBuilder::new(),
+			&packet,
+			&get_dummy_account_id(),
+		);
+		assert!(!ack.is_successful());
+		assert_eq!(token_owner(&ctx, &kitties), Some(counterparty_account()));
+	}
+
+	#[test]
+	fn successful_ack_keeps_tokens_escrowed() {
+		let mut ctx = context();
+		let kitties = class_id("kitties");
+		store_token(&ctx, &kitties, get_dummy_account_id());
+		let packet = send(&mut ctx, &kitties).unwrap();
+
+		on_acknowledgement_packet(
+			&mut ctx,
+			&mut ModuleOutputBuilder::new(),
+			&packet,
+			&ack(Acknowledgement::success()),
+			&get_dummy_account_id(),
+		)
+		.unwrap();
+		assert_eq!(token_owner(&ctx, &kitties), Some(escrow_address(&ctx)));
+	}
+
+	#[test]
+	fn error_ack_refunds_escrowed_tokens() {
+		let mut ctx = context();
+		let kitties = class_id("kitties");
+		store_token(&ctx, &kitties, get_dummy_account_id());
+		let packet = send(&mut ctx, &kitties).unwrap();
+
+		on_acknowledgement_packet(
+			&mut ctx,
+			&mut ModuleOutputBuilder::new(),
+			&packet,
+			&ack(Acknowledgement::Error("failed".to_string())),
+			&get_dummy_account_id(),
+		)
+		.unwrap();
+		assert_eq!(token_owner(&ctx, &kitties), Some(get_dummy_account_id()));
+	}
+
+	#[test]
+	fn error_ack_mints_back_burnt_vouchers() {
+		let mut ctx = context();
+		let vouchers = class_id("nft-transfer/channel-0/kitties");
+		store_token(&ctx, &vouchers, get_dummy_account_id());
+		let packet = send(&mut ctx, &vouchers).unwrap();
+		assert_eq!(token_owner(&ctx, &vouchers), None);
+
+		on_acknowledgement_packet(
+			&mut ctx,
+			&mut ModuleOutputBuilder::new(),
+			&packet,
+			&ack(Acknowledgement::Error("failed".to_string())),
+			&get_dummy_account_id(),
+		)
+		.unwrap();
+		assert_eq!(token_owner(&ctx, &vouchers), Some(get_dummy_account_id()));
+		assert_eq!(
+			NftReader::token_metadata(&ctx, &vouchers, &token_id()).unwrap().uri.as_deref(),
+			Some("ipfs://kitty-1")
+		);
+	}
+
+	#[test]
+	fn timeout_refunds_escrowed_tokens() {
+		let mut ctx = context();
+		let kitties = class_id("kitties");
+		store_token(&ctx, &kitties, get_dummy_account_id());
+		let packet = send(&mut ctx, &kitties).unwrap();
+
+		on_timeout_packet(
+			&mut ctx,
+			&mut ModuleOutputBuilder::new(),
+			&packet,
+			&get_dummy_account_id(),
+		)
+		.unwrap();
+		assert_eq!(token_owner(&ctx, &kitties), Some(get_dummy_account_id()));
+	}
+}
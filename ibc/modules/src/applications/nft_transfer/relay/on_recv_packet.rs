@@ -0,0 +1,86 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	applications::{
+		nft_transfer::{
+			context::{Metadata, NftContext},
+			error::Error,
+			events::ClassTraceEvent,
+			is_receiver_chain_source,
+			packet::PacketData,
+		},
+		transfer::TracePrefix,
+	},
+	core::{ics04_channel::packet::Packet, ics26_routing::context::ModuleOutputBuilder},
+	prelude::*,
+};
+
+pub fn process_recv_packet<Ctx: 'static + NftContext>(
+	ctx: &mut Ctx,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	data: PacketData,
+) -> Result<(), Error> {
+	if !ctx.is_receive_enabled() {
+		return Err(Error::receive_disabled())
+	}
+
+	data.validate_basic()?;
+
+	let receiver_account =
+		data.receiver.clone().try_into().map_err(|_| Error::parse_account_failure())?;
+
+	if is_receiver_chain_source(packet.source_port.clone(), packet.source_channel, &data.class_id) {
+		// sender chain is not the source, unescrow tokens
+		let prefix = TracePrefix::new(packet.source_port.clone(), packet.source_channel);
+		let class_id = {
+			let mut c = data.class_id.clone();
+			c.remove_trace_prefix(&prefix);
+			c
+		};
+
+		let escrow_address =
+			ctx.get_channel_escrow_address(&packet.destination_port, packet.destination_channel)?;
+
+		for token_id in &data.token_ids {
+			ctx.transfer_token(&class_id, token_id, &escrow_address, &receiver_account)?;
+		}
+	} else {
+		// sender chain is the source, mint vouchers
+		let prefix = TracePrefix::new(packet.destination_port.clone(), packet.destination_channel);
+		let class_id = {
+			let mut c = data.class_id.clone();
+			c.add_trace_prefix(prefix);
+			c
+		};
+
+		let class_trace_event = ClassTraceEvent {
+			trace_hash: ctx.class_hash_string(&class_id),
+			class_id: class_id.clone(),
+		};
+		output.emit(class_trace_event.into());
+
+		let class_metadata =
+			Metadata { uri: data.class_uri.clone(), data: data.class_data.clone() };
+		ctx.create_or_update_class(&class_id, class_metadata)?;
+
+		for (token_id, uri, token_data) in data.tokens() {
+			let metadata = Metadata { uri: uri.cloned(), data: token_data.cloned() };
+			ctx.mint_token(&class_id, token_id, metadata, &receiver_account)?;
+		}
+	}
+
+	Ok(())
+}
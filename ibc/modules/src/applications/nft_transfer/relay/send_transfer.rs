@@ -0,0 +1,134 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	applications::nft_transfer::{
+		context::NftContext, error::Error, events::TransferEvent, is_sender_chain_source,
+		msgs::transfer::MsgTransfer, packet::PacketData,
+	},
+	core::ics04_channel::{handler::send_packet::send_packet, packet::Packet},
+	handler::{HandlerOutput, HandlerOutputBuilder},
+	prelude::*,
+};
+
+/// This function handles the NFT transfer sending logic.
+/// If this method returns an error, the runtime is expected to rollback all state modifications to
+/// the `Ctx` caused by all messages from the transaction that this `msg` is a part of.
+pub fn send_transfer<Ctx>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgTransfer,
+) -> Result<(), Error>
+where
+	Ctx: NftContext,
+{
+	if !ctx.is_send_enabled() {
+		return Err(Error::send_disabled())
+	}
+
+	if msg.token_ids.is_empty() {
+		return Err(Error::empty_token_ids())
+	}
+
+	let source_channel_end = ctx
+		.channel_end(&(msg.source_port.clone(), msg.source_channel))
+		.map_err(Error::ics04_channel)?;
+
+	let destination_port = source_channel_end.counterparty().port_id().clone();
+	let destination_channel = *source_channel_end.counterparty().channel_id().ok_or_else(|| {
+		Error::destination_channel_not_found(msg.source_port.clone(), msg.source_channel)
+	})?;
+
+	// get the next sequence
+	let sequence = ctx
+		.get_next_sequence_send(&(msg.source_port.clone(), msg.source_channel))
+		.map_err(Error::ics04_channel)?;
+
+	let sender = msg.sender.clone().try_into().map_err(|_| Error::parse_account_failure())?;
+
+	let class_metadata = ctx.class_metadata(&msg.class_id)?;
+	let tokens_metadata = msg
+		.token_ids
+		.iter()
+		.map(|token_id| ctx.token_metadata(&msg.class_id, token_id))
+		.collect::<Result<Vec<_>, _>>()?;
+
+	if is_sender_chain_source(msg.source_port.clone(), msg.source_channel, &msg.class_id) {
+		let escrow_address =
+			ctx.get_channel_escrow_address(&msg.source_port, msg.source_channel)?;
+		for token_id in &msg.token_ids {
+			ctx.transfer_token(&msg.class_id, token_id, &sender, &escrow_address)?;
+		}
+	} else {
+		for token_id in &msg.token_ids {
+			ctx.burn_token(&msg.class_id, token_id, &sender)?;
+		}
+	}
+
+	// token uris and data are either given for every token or omitted
+	let (token_uris, token_data): (Vec<_>, Vec<_>) = tokens_metadata
+		.into_iter()
+		.map(|metadata| (metadata.uri.unwrap_or_default(), metadata.data.unwrap_or_default()))
+		.unzip();
+	let data = {
+		let data = PacketData {
+			class_id: msg.class_id.clone(),
+			class_uri: class_metadata.uri,
+			class_data: class_metadata.data,
+			token_ids: msg.token_ids.clone(),
+			token_uris: if token_uris.iter().all(String::is_empty) { vec![] } else { token_uris },
+			token_data: if token_data.iter().all(String::is_empty) { vec![] } else { token_data },
+			sender: msg.sender.clone(),
+			receiver: msg.receiver.clone(),
+			memo: msg.memo,
+		};
+		serde_json::to_vec(&data).expect("PacketData's infallible Serialize impl failed")
+	};
+
+	let packet = Packet {
+		sequence,
+		source_port: msg.source_port,
+		source_channel: msg.source_channel,
+		destination_port,
+		destination_channel,
+		data,
+		timeout_height: msg.timeout_height,
+		timeout_timestamp: msg.timeout_timestamp,
+	};
+
+	let HandlerOutput { result, log, events } =
+		send_packet(ctx, packet).map_err(Error::ics04_channel)?;
+
+	ctx.store_packet_result(result).map_err(Error::ics04_channel)?;
+
+	output.merge_output(HandlerOutput::builder().with_log(log).with_events(events).with_result(()));
+
+	output.log(format!(
+		"IBC non-fungible token transfer: {} --({}: {} tokens)--> {}",
+		msg.sender,
+		msg.class_id,
+		msg.token_ids.len(),
+		msg.receiver
+	));
+
+	let transfer_event = TransferEvent {
+		sender: msg.sender,
+		receiver: msg.receiver,
+		class_id: msg.class_id,
+		token_ids: msg.token_ids,
+	};
+	output.emit(transfer_event.into());
+
+	Ok(())
+}
@@ -24,7 +24,7 @@ use crate::{
 			on_ack_packet::process_ack_packet, on_recv_packet::process_recv_packet,
 			on_timeout_packet::process_timeout_packet,
 		},
-		PrefixedCoin, PrefixedDenom, VERSION,
+		Amount, PrefixedCoin, PrefixedDenom, VERSION,
 	},
 	core::{
 		ics04_channel::{
@@ -45,6 +45,26 @@ pub trait Ics20Keeper:
 	ChannelKeeper + BankKeeper<AccountId = <Self as Ics20Keeper>::AccountId>
 {
 	type AccountId;
+
+	/// Stores the denomination a hash returned by [`Ics20Reader::denom_hash_string`] resolves to.
+	/// Implement only if the host chain supports hashed denominations.
+	fn store_denom_trace(
+		&mut self,
+		_denom_hash: String,
+		_denom: PrefixedDenom,
+	) -> Result<(), Ics20Error> {
+		Ok(())
+	}
+
+	/// Stores the total amount of a denomination held by the escrow accounts of all channels.
+	/// Implement only if the host chain tracks escrowed amounts.
+	fn set_total_escrow(
+		&mut self,
+		_denom: &PrefixedDenom,
+		_amount: Amount,
+	) -> Result<(), Ics20Error> {
+		Ok(())
+	}
 }
 
 pub trait Ics20Reader: ChannelReader
@@ -83,6 +103,17 @@ where
 	fn denom_hash_string(&self, _denom: &PrefixedDenom) -> Option<String> {
 		None
 	}
+
+	/// Returns the denomination stored for a hash returned by
+	/// [`Ics20Reader::denom_hash_string`].
+	fn get_denom_trace(&self, _denom_hash: &str) -> Option<PrefixedDenom> {
+		None
+	}
+
+	/// Returns the total amount of a denomination held by the escrow accounts of all channels.
+	fn get_total_escrow(&self, _denom: &PrefixedDenom) -> Amount {
+		Amount::from(0u64)
+	}
 }
 
 // https://github.com/cosmos/cosmos-sdk/blob/master/docs/architecture/adr-028-public-key-addresses.md
@@ -223,6 +254,43 @@ pub fn on_chan_open_confirm(
 	Ok(())
 }
 
+/// Transfer channels can be upgraded, e.g. to be wrapped by middlewares, as long as they remain
+/// unordered ics20 channels.
+pub fn on_chan_upgrade_init(
+	ctx: &mut impl Ics20Context,
+	_output: &mut ModuleOutputBuilder,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	proposed_order: Order,
+	proposed_version: &Version,
+) -> Result<Version, Ics20Error> {
+	validate_transfer_channel_params(ctx, proposed_order, port_id, channel_id, proposed_version)?;
+	Ok(Version::ics20())
+}
+
+pub fn on_chan_upgrade_try(
+	ctx: &mut impl Ics20Context,
+	_output: &mut ModuleOutputBuilder,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	proposed_order: Order,
+	counterparty_version: &Version,
+) -> Result<Version, Ics20Error> {
+	validate_transfer_channel_params(ctx, proposed_order, port_id, channel_id, &Version::ics20())?;
+	validate_counterparty_version(counterparty_version)?;
+	Ok(Version::ics20())
+}
+
+pub fn on_chan_upgrade_ack(
+	_ctx: &mut impl Ics20Context,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+	counterparty_version: &Version,
+) -> Result<(), Ics20Error> {
+	validate_counterparty_version(counterparty_version)
+}
+
 pub fn on_chan_close_init(
 	_ctx: &mut impl Ics20Context,
 	_output: &mut ModuleOutputBuilder,
@@ -262,6 +330,7 @@ pub fn on_recv_packet<Ctx: 'static + Ics20Context>(
 		receiver: data.receiver,
 		denom: data.token.denom,
 		amount: data.token.amount,
+		memo: data.memo,
 		success: ack.is_successful(),
 	};
 	output.emit(recv_event.into());
@@ -288,6 +357,7 @@ pub fn on_acknowledgement_packet(
 		receiver: data.receiver,
 		denom: data.token.denom,
 		amount: data.token.amount,
+		memo: data.memo,
 		acknowledgement: acknowledgement.clone(),
 	};
 	output.emit(ack_event.into());
@@ -311,6 +381,7 @@ pub fn on_timeout_packet(
 		refund_receiver: data.sender,
 		refund_denom: data.token.denom,
 		refund_amount: data.token.amount,
+		memo: data.memo,
 	};
 	output.emit(timeout_event.into());
 
@@ -511,8 +511,23 @@ mod tests {
 		let string = serde_json::to_string(&packet_data).unwrap();
 		assert_eq!(
 			string,
-			r#"{"denom":"transfer/channel-0/transfer/channel-1/UNIT","amount":"10000000000","sender":"sender","receiver":"receiver","memo":""}"#
+			r#"{"denom":"transfer/channel-0/transfer/channel-1/UNIT","amount":"10000000000","sender":"sender","receiver":"receiver"}"#
 		);
 		assert_eq!(packet_data, serde_json::from_str(&string).unwrap());
+
+		let packet_data = PacketData { memo: r#"{"forward":{}}"#.to_string(), ..packet_data };
+		let string = serde_json::to_string(&packet_data).unwrap();
+		assert_eq!(
+			string,
+			r#"{"denom":"transfer/channel-0/transfer/channel-1/UNIT","amount":"10000000000","sender":"sender","receiver":"receiver","memo":"{\"forward\":{}}"}"#
+		);
+		assert_eq!(packet_data, serde_json::from_str(&string).unwrap());
+		assert!(packet_data.validate_memo().is_ok());
+
+		let packet_data = PacketData {
+			memo: "a".repeat(crate::applications::transfer::MAXIMUM_MEMO_LENGTH + 1),
+			..packet_data
+		};
+		assert!(packet_data.validate_memo().is_err());
 	}
 }
@@ -143,6 +143,10 @@ define_error! {
 		ParseAccountFailure
 			| _ | { "failed to parse as AccountId" },
 
+		MemoTooLong
+			{ length: usize, max_length: usize }
+			| e | { format_args!("memo must not exceed {0} bytes, got {1}", e.max_length, e.length) },
+
 		InvalidPort
 			{ port_id: PortId, exp_port_id: PortId }
 			| e | { format_args!("invalid port: '{0}', expected '{1}'", e.port_id, e.exp_port_id) },
@@ -39,12 +39,13 @@ pub struct RecvEvent {
 	pub receiver: Signer,
 	pub denom: PrefixedDenom,
 	pub amount: Amount,
+	pub memo: String,
 	pub success: bool,
 }
 
 impl From<RecvEvent> for IbcEvent {
 	fn from(ev: RecvEvent) -> Self {
-		let RecvEvent { receiver, denom, amount, success } = ev;
+		let RecvEvent { receiver, denom, amount, memo, success } = ev;
 		IbcEvent::AppModule(ModuleEvent {
 			kind: EVENT_TYPE_PACKET.to_string(),
 			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
@@ -52,6 +53,7 @@ impl From<RecvEvent> for IbcEvent {
 				("receiver", receiver).into(),
 				("denom", denom).into(),
 				("amount", amount).into(),
+				("memo", memo).into(),
 				("success", success).into(),
 			],
 		})
@@ -62,12 +64,13 @@ pub struct AckEvent {
 	pub receiver: Signer,
 	pub denom: PrefixedDenom,
 	pub amount: Amount,
+	pub memo: String,
 	pub acknowledgement: Acknowledgement,
 }
 
 impl From<AckEvent> for IbcEvent {
 	fn from(ev: AckEvent) -> Self {
-		let AckEvent { receiver, denom, amount, acknowledgement } = ev;
+		let AckEvent { receiver, denom, amount, memo, acknowledgement } = ev;
 		IbcEvent::AppModule(ModuleEvent {
 			kind: EVENT_TYPE_PACKET.to_string(),
 			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
@@ -75,6 +78,7 @@ impl From<AckEvent> for IbcEvent {
 				("receiver", receiver).into(),
 				("denom", denom).into(),
 				("amount", amount).into(),
+				("memo", memo).into(),
 				("acknowledgement", acknowledgement).into(),
 			],
 		})
@@ -103,11 +107,12 @@ pub struct TimeoutEvent {
 	pub refund_receiver: Signer,
 	pub refund_denom: PrefixedDenom,
 	pub refund_amount: Amount,
+	pub memo: String,
 }
 
 impl From<TimeoutEvent> for IbcEvent {
 	fn from(ev: TimeoutEvent) -> Self {
-		let TimeoutEvent { refund_receiver, refund_denom, refund_amount } = ev;
+		let TimeoutEvent { refund_receiver, refund_denom, refund_amount, memo } = ev;
 		IbcEvent::AppModule(ModuleEvent {
 			kind: EVENT_TYPE_TIMEOUT.to_string(),
 			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
@@ -115,6 +120,7 @@ impl From<TimeoutEvent> for IbcEvent {
 				("refund_receiver", refund_receiver).into(),
 				("refund_denom", refund_denom).into(),
 				("refund_amount", refund_amount).into(),
+				("memo", memo).into(),
 			],
 		})
 	}
@@ -143,15 +149,20 @@ impl From<DenomTraceEvent> for IbcEvent {
 pub struct TransferEvent {
 	pub sender: Signer,
 	pub receiver: Signer,
+	pub memo: String,
 }
 
 impl From<TransferEvent> for IbcEvent {
 	fn from(ev: TransferEvent) -> Self {
-		let TransferEvent { sender, receiver } = ev;
+		let TransferEvent { sender, receiver, memo } = ev;
 		IbcEvent::AppModule(ModuleEvent {
 			kind: EVENT_TYPE_TRANSFER.to_string(),
 			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
-			attributes: vec![("sender", sender).into(), ("receiver", receiver).into()],
+			attributes: vec![
+				("sender", sender).into(),
+				("receiver", receiver).into(),
+				("memo", memo).into(),
+			],
 		})
 	}
 }
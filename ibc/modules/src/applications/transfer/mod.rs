@@ -35,3 +35,6 @@ pub const PORT_ID_STR: &str = "transfer";
 
 /// ICS20 application current version.
 pub const VERSION: &str = "ics20-1";
+
+/// Maximum length of the memo of a transfer, matching ibc-go.
+pub const MAXIMUM_MEMO_LENGTH: usize = 32768;
@@ -18,7 +18,7 @@ use core::{convert::TryFrom, str::FromStr};
 use ibc_proto::ibc::applications::transfer::v2::FungibleTokenPacketData as RawPacketData;
 use serde::{Deserialize, Serialize};
 
-use super::{error::Error, Amount, PrefixedCoin, PrefixedDenom};
+use super::{error::Error, Amount, PrefixedCoin, PrefixedDenom, MAXIMUM_MEMO_LENGTH};
 use crate::signer::Signer;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -27,10 +27,26 @@ pub struct PacketData {
 	pub token: PrefixedCoin,
 	pub sender: Signer,
 	pub receiver: Signer,
-	#[serde(default)]
+	/// Omitted from the json encoding when empty, so that packets without a memo can still be
+	/// received by chains that don't know the field.
+	#[serde(default, skip_serializing_if = "String::is_empty")]
 	pub memo: String,
 }
 
+impl PacketData {
+	/// Checks that the memo doesn't exceed [`MAXIMUM_MEMO_LENGTH`].
+	pub fn validate_memo(&self) -> Result<(), Error> {
+		validate_memo(&self.memo)
+	}
+}
+
+pub(crate) fn validate_memo(memo: &str) -> Result<(), Error> {
+	if memo.len() > MAXIMUM_MEMO_LENGTH {
+		return Err(Error::memo_too_long(memo.len(), MAXIMUM_MEMO_LENGTH))
+	}
+	Ok(())
+}
+
 impl TryFrom<RawPacketData> for PacketData {
 	type Error = Error;
 
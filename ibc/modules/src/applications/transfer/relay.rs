@@ -16,7 +16,7 @@
 use crate::{
 	applications::transfer::{
 		context::Ics20Context, error::Error as Ics20Error, is_sender_chain_source,
-		packet::PacketData,
+		packet::PacketData, PrefixedCoin,
 	},
 	core::ics04_channel::packet::Packet,
 	prelude::*,
@@ -44,10 +44,32 @@ fn refund_packet_token(
 		let escrow_address =
 			ctx.get_channel_escrow_address(&packet.source_port, packet.source_channel)?;
 
-		ctx.send_coins(&escrow_address, &sender, &data.token)
+		ctx.send_coins(&escrow_address, &sender, &data.token)?;
+		decrease_total_escrow(ctx, &data.token)
 	}
 	// mint vouchers back to sender
 	else {
 		ctx.mint_coins(&sender, &data.token)
 	}
 }
+
+/// Accounts for `coin` being moved into the escrow account of a channel
+pub(crate) fn increase_total_escrow(
+	ctx: &mut impl Ics20Context,
+	coin: &PrefixedCoin,
+) -> Result<(), Ics20Error> {
+	let total = ctx.get_total_escrow(&coin.denom);
+	let total = total.as_u256().saturating_add(coin.amount.as_u256());
+	ctx.set_total_escrow(&coin.denom, total.into())
+}
+
+/// Accounts for `coin` being released from the escrow account of a channel
+pub(crate) fn decrease_total_escrow(
+	ctx: &mut impl Ics20Context,
+	coin: &PrefixedCoin,
+) -> Result<(), Ics20Error> {
+	let total = ctx.get_total_escrow(&coin.denom);
+	// amounts escrowed before the host started tracking them are not accounted for
+	let total = total.as_u256().saturating_sub(coin.amount.as_u256());
+	ctx.set_total_escrow(&coin.denom, total.into())
+}
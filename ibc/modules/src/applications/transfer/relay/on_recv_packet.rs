@@ -15,7 +15,7 @@
 use crate::{
 	applications::transfer::{
 		context::Ics20Context, error::Error as Ics20Error, events::DenomTraceEvent,
-		is_receiver_chain_source, packet::PacketData, TracePrefix,
+		is_receiver_chain_source, packet::PacketData, relay::decrease_total_escrow, TracePrefix,
 	},
 	core::{ics04_channel::packet::Packet, ics26_routing::context::ModuleOutputBuilder},
 	prelude::*,
@@ -30,6 +30,7 @@ pub fn process_recv_packet<Ctx: 'static + Ics20Context>(
 	if !ctx.is_receive_enabled() {
 		return Err(Ics20Error::receive_disabled())
 	}
+	data.validate_memo()?;
 
 	let receiver_account = data
 		.receiver
@@ -53,7 +54,8 @@ pub fn process_recv_packet<Ctx: 'static + Ics20Context>(
 		let escrow_address =
 			ctx.get_channel_escrow_address(&packet.destination_port, packet.destination_channel)?;
 
-		ctx.send_coins(&escrow_address, &receiver_account, &coin)
+		ctx.send_coins(&escrow_address, &receiver_account, &coin)?;
+		decrease_total_escrow(ctx, &coin)
 	} else {
 		// sender chain is the source, mint vouchers
 		let prefix = TracePrefix::new(packet.destination_port.clone(), packet.destination_channel);
@@ -63,10 +65,13 @@ pub fn process_recv_packet<Ctx: 'static + Ics20Context>(
 			c
 		};
 
-		let denom_trace_event = DenomTraceEvent {
-			trace_hash: ctx.denom_hash_string(&coin.denom),
-			denom: coin.denom.clone(),
-		};
+		let trace_hash = ctx.denom_hash_string(&coin.denom);
+		if let Some(hash) = &trace_hash {
+			if ctx.get_denom_trace(hash).is_none() {
+				ctx.store_denom_trace(hash.clone(), coin.denom.clone())?;
+			}
+		}
+		let denom_trace_event = DenomTraceEvent { trace_hash, denom: coin.denom.clone() };
 		output.emit(denom_trace_event.into());
 
 		ctx.mint_coins(&receiver_account, &coin)
@@ -14,8 +14,14 @@
 
 use crate::{
 	applications::transfer::{
-		context::Ics20Context, error::Error, events::TransferEvent, is_sender_chain_source,
-		msgs::transfer::MsgTransfer, packet::PacketData, Coin, PrefixedCoin,
+		context::Ics20Context,
+		error::Error,
+		events::TransferEvent,
+		is_sender_chain_source,
+		msgs::transfer::MsgTransfer,
+		packet::{validate_memo, PacketData},
+		relay::increase_total_escrow,
+		Coin, PrefixedCoin,
 	},
 	core::ics04_channel::{handler::send_packet::send_packet, packet::Packet},
 	handler::{HandlerOutput, HandlerOutputBuilder},
@@ -37,6 +43,7 @@ where
 	if !ctx.is_send_enabled() {
 		return Err(Error::send_disabled())
 	}
+	validate_memo(&msg.memo)?;
 
 	let source_channel_end = ctx
 		.channel_end(&(msg.source_port.clone(), msg.source_channel))
@@ -62,10 +69,12 @@ where
 		let escrow_address =
 			ctx.get_channel_escrow_address(&msg.source_port, msg.source_channel)?;
 		ctx.send_coins(&sender, &escrow_address, &coin)?;
+		increase_total_escrow(ctx, &coin)?;
 	} else {
 		ctx.burn_coins(&sender, &coin)?;
 	}
 
+	let packet_memo = msg.memo.clone();
 	let data = {
 		let data = PacketData {
 			token: coin,
@@ -99,7 +108,8 @@ where
 		msg.sender, token, msg.receiver
 	));
 
-	let transfer_event = TransferEvent { sender: msg.sender, receiver: msg.receiver };
+	let transfer_event =
+		TransferEvent { sender: msg.sender, receiver: msg.receiver, memo: packet_memo };
 	output.emit(transfer_event.into());
 
 	Ok(())
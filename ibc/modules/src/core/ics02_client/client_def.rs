@@ -24,7 +24,10 @@ use crate::{
 			packet::Sequence,
 		},
 		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
-		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		ics24_host::{
+			identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			Path,
+		},
 		ics26_routing::context::ReaderContext,
 	},
 	prelude::*,
@@ -240,4 +243,76 @@ pub trait ClientDef: Clone {
 		channel_id: &ChannelId,
 		sequence: Sequence,
 	) -> Result<(), Error>;
+
+	/// Verify a `proof` that `value` is stored at `path` in the provable store of the
+	/// counterparty, for applications querying arbitrary counterparty state. Unlike the
+	/// verification functions above it doesn't enforce a connection delay.
+	#[allow(clippy::too_many_arguments)]
+	fn verify_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		_path: Path,
+		_value: Vec<u8>,
+	) -> Result<(), Error> {
+		Err(Error::implementation_specific("membership proofs are not supported".to_string()))
+	}
+
+	/// Verify a `proof` that nothing is stored at `path` in the provable store of the
+	/// counterparty.
+	#[allow(clippy::too_many_arguments)]
+	fn verify_non_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		_path: Path,
+	) -> Result<(), Error> {
+		Err(Error::implementation_specific("non-membership proofs are not supported".to_string()))
+	}
+
+	/// Verify a batch `proof` that each value is stored at its path in the provable store of the
+	/// counterparty, so that a single proof can cover many packet commitments.
+	#[allow(clippy::too_many_arguments)]
+	fn verify_batch_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		_items: Vec<(Path, Vec<u8>)>,
+	) -> Result<(), Error> {
+		Err(Error::implementation_specific("batch membership proofs are not supported".to_string()))
+	}
+
+	/// Verify a batch `proof` that nothing is stored at any of the paths in the provable store of
+	/// the counterparty.
+	#[allow(clippy::too_many_arguments)]
+	fn verify_batch_non_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		_paths: Vec<Path>,
+	) -> Result<(), Error> {
+		Err(Error::implementation_specific(
+			"batch non-membership proofs are not supported".to_string(),
+		))
+	}
 }
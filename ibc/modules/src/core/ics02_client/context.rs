@@ -24,14 +24,14 @@ use crate::{
 			client_message::ClientMessage,
 			client_state::{ClientState, ClientType},
 			error::{Error, ErrorDetail},
-			handler::ClientResult::{self, Create, Update, Upgrade},
+			handler::ClientResult::{self, Create, Misbehaviour, Recover, Update, Upgrade},
 		},
 		ics24_host::identifier::ClientId,
 	},
 	timestamp::Timestamp,
 	Height,
 };
-use alloc::{string::String, vec::Vec};
+use alloc::{string::String, vec, vec::Vec};
 use core::fmt::Debug;
 
 /// Defines the read-only part of ICS2 (client functions) context.
@@ -218,6 +218,24 @@ where
 				}
 				Ok(())
 			},
+			Misbehaviour(res) => {
+				self.store_client_state(res.client_id, res.client_state)?;
+				Ok(())
+			},
+			Recover(res) => {
+				self.store_client_state(res.client_id.clone(), res.client_state.clone())?;
+				let cs_states = match res.consensus_state {
+					ConsensusUpdateResult::Single(cs_state) =>
+						vec![(res.client_state.latest_height(), cs_state)],
+					ConsensusUpdateResult::Batch(cs_states) => cs_states,
+				};
+				for (height, cs_state) in cs_states {
+					self.store_consensus_state(res.client_id.clone(), height, cs_state)?;
+					self.store_update_time(res.client_id.clone(), height, res.processed_time)?;
+					self.store_update_height(res.client_id.clone(), height, res.processed_height)?;
+				}
+				Ok(())
+			},
 		}
 	}
 
@@ -191,6 +191,32 @@ define_error! {
 		MissingRawMisbehaviour
 			| _ | { "missing raw misbehaviour" },
 
+		MisbehaviourVerificationFailure
+			{ reason: String }
+			| e | { format_args!("misbehaviour verification failed with reason: {}", e.reason) },
+
+		MisbehaviourNotFound
+			{ client_id: ClientId }
+			| e | { format_args!("submitted evidence isn't misbehaviour of client {0}", e.client_id) },
+
+		SubjectClientActive
+			{ client_id: ClientId }
+			| e | { format_args!("subject client {0} is active and can't be recovered", e.client_id) },
+
+		SubstituteClientNotActive
+			{ client_id: ClientId }
+			| e | { format_args!("substitute client {0} is not active", e.client_id) },
+
+		SubstituteClientTypeMismatch
+			{
+				subject_type: ClientType,
+				substitute_type: ClientType,
+			}
+			| e | {
+				format_args!("subject client of type {0} can't be substituted by a client of type {1}",
+					e.subject_type, e.substitute_type)
+			},
+
 		InvalidStringAsHeight
 			{ value: String }
 			[ HeightError ]
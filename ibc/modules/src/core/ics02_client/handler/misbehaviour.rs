@@ -0,0 +1,192 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Protocol logic specific to processing ICS2 messages of type `MsgSubmitMisbehaviour`.
+use core::fmt::Debug;
+
+use crate::{
+	core::{
+		ics02_client::{
+			client_def::ClientDef, client_state::ClientState, context::ClientTypes, error::Error,
+			events::Attributes, handler::ClientResult, msgs::misbehaviour::MsgSubmitMisbehaviour,
+		},
+		ics24_host::identifier::ClientId,
+		ics26_routing::context::ReaderContext,
+	},
+	events::IbcEvent,
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+/// The result following the successful processing of a `MsgSubmitMisbehaviour` message.
+/// Preferably this data type should be used with a qualified name `misbehaviour::Result` to avoid
+/// ambiguity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Result<C: ClientTypes> {
+	pub client_id: ClientId,
+	/// The frozen client state
+	pub client_state: C::AnyClientState,
+}
+
+pub fn process<Ctx>(
+	ctx: &Ctx,
+	msg: MsgSubmitMisbehaviour<Ctx>,
+) -> HandlerResult<ClientResult<Ctx>, Error>
+where
+	Ctx: ReaderContext,
+{
+	let mut output = HandlerOutput::builder();
+
+	let MsgSubmitMisbehaviour { client_id, misbehaviour, signer: _ } = msg;
+
+	// Read client type from the host chain store. The client should already exist.
+	let client_type = ctx.client_type(&client_id)?;
+
+	// Read client state from the host chain store.
+	let client_state = ctx.client_state(&client_id)?;
+
+	if client_state.is_frozen(ctx, &client_id) {
+		return Err(Error::client_frozen(client_id))
+	}
+
+	let client_def = client_state.client_def();
+
+	client_def
+		.verify_client_message::<Ctx>(
+			ctx,
+			client_id.clone(),
+			client_state.clone(),
+			misbehaviour.clone(),
+		)
+		.map_err(|e| Error::misbehaviour_verification_failure(e.to_string()))?;
+
+	let found_misbehaviour = client_def
+		.check_for_misbehaviour(ctx, client_id.clone(), client_state.clone(), misbehaviour.clone())
+		.map_err(|e| Error::misbehaviour_verification_failure(e.to_string()))?;
+
+	if !found_misbehaviour {
+		return Err(Error::misbehaviour_not_found(client_id))
+	}
+
+	// Freeze the client at the misbehaviour height.
+	let client_state = client_def.update_state_on_misbehaviour(client_state, misbehaviour)?;
+
+	let event_attributes = Attributes {
+		client_id: client_id.clone(),
+		height: ctx.host_height(),
+		client_type,
+		consensus_height: client_state
+			.frozen_height()
+			.unwrap_or_else(|| client_state.latest_height()),
+	};
+	output.emit(IbcEvent::ClientMisbehaviour(event_attributes.into()));
+
+	let result = ClientResult::Misbehaviour(Result { client_id, client_state });
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use test_log::test;
+
+	use crate::{
+		core::{
+			ics02_client::{
+				client_state::ClientState,
+				error::{Error, ErrorDetail},
+				handler::{dispatch, ClientResult},
+				msgs::{misbehaviour::MsgSubmitMisbehaviour, ClientMsg},
+			},
+			ics24_host::identifier::ClientId,
+		},
+		events::IbcEvent,
+		handler::HandlerOutput,
+		mock::{
+			context::{MockClientTypes, MockContext},
+			header::{MockClientMessage, MockHeader},
+			misbehaviour::MockMisbehaviour,
+		},
+		prelude::*,
+		test_utils::get_dummy_account_id,
+		timestamp::Timestamp,
+		Height,
+	};
+
+	fn misbehaviour_msg(
+		client_id: &ClientId,
+		header1: MockHeader,
+		header2: MockHeader,
+	) -> MsgSubmitMisbehaviour<MockClientTypes> {
+		MsgSubmitMisbehaviour {
+			client_id: client_id.clone(),
+			misbehaviour: MockClientMessage::Misbehaviour(MockMisbehaviour {
+				client_id: client_id.clone(),
+				header1,
+				header2,
+			})
+			.into(),
+			signer: get_dummy_account_id(),
+		}
+	}
+
+	#[test]
+	fn test_misbehaviour_freezes_client() {
+		let client_id = ClientId::default();
+		let height = Height::new(0, 46);
+		let ctx =
+			MockContext::<MockClientTypes>::default().with_client(&client_id, Height::new(0, 42));
+
+		let msg = misbehaviour_msg(
+			&client_id,
+			MockHeader::new(height).with_timestamp(Timestamp::from_nanoseconds(1).unwrap()),
+			MockHeader::new(height).with_timestamp(Timestamp::from_nanoseconds(2).unwrap()),
+		);
+
+		match dispatch(&ctx, ClientMsg::Misbehaviour(msg)) {
+			Ok(HandlerOutput { result, mut events, .. }) => {
+				assert_eq!(events.len(), 1);
+				let event = events.pop().unwrap();
+				assert!(
+					matches!(event, IbcEvent::ClientMisbehaviour(ref e) if e.client_id() == &client_id)
+				);
+				match result {
+					ClientResult::Misbehaviour(res) => {
+						assert_eq!(res.client_id, client_id);
+						assert_eq!(res.client_state.frozen_height(), Some(height));
+					},
+					_ => panic!("misbehaviour handler result has incorrect type"),
+				}
+			},
+			Err(err) => panic!("unexpected error: {}", err),
+		}
+	}
+
+	#[test]
+	fn test_misbehaviour_with_identical_headers() {
+		let client_id = ClientId::default();
+		let header = MockHeader::new(Height::new(0, 46));
+		let ctx =
+			MockContext::<MockClientTypes>::default().with_client(&client_id, Height::new(0, 42));
+
+		let msg = misbehaviour_msg(&client_id, header, header);
+
+		match dispatch(&ctx, ClientMsg::Misbehaviour(msg)) {
+			Err(Error(ErrorDetail::MisbehaviourNotFound(e), _)) => {
+				assert_eq!(e.client_id, client_id);
+			},
+			output => panic!("expected MisbehaviourNotFound error, instead got {:?}", output),
+		}
+	}
+}
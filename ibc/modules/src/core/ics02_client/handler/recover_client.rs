@@ -0,0 +1,192 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Protocol logic for recovering an expired or frozen client with the state of a substitute
+//! client. Recovery is privileged, so it isn't routed as a message but invoked by the host once
+//! governance approved it.
+use core::fmt::Debug;
+
+use crate::{
+	core::{
+		ics02_client::{
+			client_def::{ClientDef, ConsensusUpdateResult},
+			client_state::{ClientState, Status},
+			context::ClientTypes,
+			error::Error,
+			handler::ClientResult,
+			height::Height,
+		},
+		ics24_host::identifier::ClientId,
+		ics26_routing::context::ReaderContext,
+	},
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+	timestamp::Timestamp,
+};
+
+/// The result following the successful recovery of a client. Preferably this data type should
+/// be used with a qualified name `recover_client::Result` to avoid ambiguity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Result<C: ClientTypes> {
+	/// The recovered (subject) client
+	pub client_id: ClientId,
+	pub client_state: C::AnyClientState,
+	pub consensus_state: ConsensusUpdateResult<C>,
+	pub processed_time: Timestamp,
+	pub processed_height: Height,
+}
+
+pub fn process<Ctx>(
+	ctx: &Ctx,
+	subject_client_id: ClientId,
+	substitute_client_id: ClientId,
+) -> HandlerResult<ClientResult<Ctx>, Error>
+where
+	Ctx: ReaderContext,
+{
+	let output = HandlerOutput::builder();
+
+	let subject_client_state = ctx.client_state(&subject_client_id)?;
+	if is_active(ctx, &subject_client_id, &subject_client_state)? {
+		return Err(Error::subject_client_active(subject_client_id))
+	}
+
+	let substitute_client_state = ctx.client_state(&substitute_client_id)?;
+	if !is_active(ctx, &substitute_client_id, &substitute_client_state)? {
+		return Err(Error::substitute_client_not_active(substitute_client_id))
+	}
+
+	let subject_type = subject_client_state.client_type();
+	let substitute_type = substitute_client_state.client_type();
+	if subject_type != substitute_type {
+		return Err(Error::substitute_client_type_mismatch(subject_type, substitute_type))
+	}
+
+	let client_def = subject_client_state.client_def();
+	let (client_state, consensus_state) = client_def.check_substitute_and_update_state(
+		ctx,
+		subject_client_id.clone(),
+		substitute_client_id,
+		subject_client_state,
+		substitute_client_state,
+	)?;
+
+	let result = ClientResult::Recover(Result {
+		client_id: subject_client_id,
+		client_state,
+		consensus_state,
+		processed_time: ctx.host_timestamp(),
+		processed_height: ctx.host_height(),
+	});
+
+	Ok(output.with_result(result))
+}
+
+/// A client is active if it's neither frozen nor expired.
+fn is_active<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	client_id: &ClientId,
+	client_state: &Ctx::AnyClientState,
+) -> core::result::Result<bool, Error> {
+	if client_state.status(ctx, client_id) != Status::Active {
+		return Ok(false)
+	}
+
+	let last_update_time =
+		ctx.client_update_time(client_id, client_state.latest_height()).map_err(|_| {
+			Error::implementation_specific("Could not find update time for client".to_string())
+		})?;
+	let expired = ctx
+		.host_timestamp()
+		.duration_since(&last_update_time)
+		.map_or(false, |elapsed| client_state.expired(elapsed));
+
+	Ok(!expired)
+}
+
+#[cfg(test)]
+mod tests {
+	use core::str::FromStr;
+	use test_log::test;
+
+	use crate::{
+		core::{
+			ics02_client::{
+				client_state::ClientState,
+				context::ClientKeeper,
+				error::{Error, ErrorDetail},
+				handler::ClientResult,
+			},
+			ics24_host::identifier::ClientId,
+		},
+		mock::{
+			client_state::{AnyClientState, MockClientState},
+			context::{MockClientTypes, MockContext},
+			header::MockHeader,
+		},
+		prelude::*,
+		timestamp::Timestamp,
+		Height,
+	};
+
+	use super::process;
+
+	fn context(subject: &ClientId, substitute: &ClientId) -> MockContext<MockClientTypes> {
+		let mut ctx = MockContext::<MockClientTypes>::default()
+			.with_client(subject, Height::new(0, 42))
+			.with_client(substitute, Height::new(0, 50));
+		ctx.store_update_time(subject.clone(), Height::new(0, 42), Timestamp::now())
+			.unwrap();
+		ctx.store_update_time(substitute.clone(), Height::new(0, 50), Timestamp::now())
+			.unwrap();
+		ctx
+	}
+
+	#[test]
+	fn recover_frozen_client() {
+		let subject = ClientId::from_str("mockclient1").unwrap();
+		let substitute = ClientId::from_str("mockclient2").unwrap();
+		let mut ctx = context(&subject, &substitute);
+
+		let frozen = MockClientState {
+			frozen_height: Some(Height::new(0, 42)),
+			..MockClientState::new(MockHeader::new(Height::new(0, 42)).into())
+		};
+		ctx.store_client_state(subject.clone(), AnyClientState::Mock(frozen)).unwrap();
+
+		let output = process(&ctx, subject.clone(), substitute).unwrap();
+		match output.result {
+			ClientResult::Recover(res) => {
+				assert_eq!(res.client_id, subject);
+				assert_eq!(res.client_state.frozen_height(), None);
+				assert_eq!(res.client_state.latest_height(), Height::new(0, 50));
+			},
+			_ => panic!("recover handler result has incorrect type"),
+		}
+	}
+
+	#[test]
+	fn active_client_is_not_recovered() {
+		let subject = ClientId::from_str("mockclient1").unwrap();
+		let substitute = ClientId::from_str("mockclient2").unwrap();
+		let ctx = context(&subject, &substitute);
+
+		match process(&ctx, subject.clone(), substitute) {
+			Err(Error(ErrorDetail::SubjectClientActive(e), _)) => {
+				assert_eq!(e.client_id, subject);
+			},
+			output => panic!("expected SubjectClientActive error, instead got {:?}", output),
+		}
+	}
+}
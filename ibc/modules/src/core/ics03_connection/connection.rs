@@ -14,7 +14,7 @@
 
 use crate::prelude::*;
 
-use core::{fmt, str::FromStr, time::Duration, u64};
+use core::{fmt, time::Duration, u64};
 
 use serde::{Deserialize, Serialize};
 use tendermint_proto::Protobuf;
@@ -32,6 +32,7 @@ use crate::{
 		ics24_host::{
 			error::ValidationError,
 			identifier::{ClientId, ConnectionId},
+			validate::ValidationPolicy,
 		},
 	},
 	timestamp::ZERO_DURATION,
@@ -249,13 +250,16 @@ impl TryFrom<RawCounterparty> for Counterparty {
 	type Error = Error;
 
 	fn try_from(value: RawCounterparty) -> Result<Self, Self::Error> {
+		// identifiers on the counterparty are chosen by the counterparty chain
+		let policy = ValidationPolicy::CounterpartyCompatible;
 		let connection_id = Some(value.connection_id)
 			.filter(|x| !x.is_empty())
-			.map(|v| FromStr::from_str(v.as_str()))
+			.map(|v| ConnectionId::from_str_with_policy(v.as_str(), policy))
 			.transpose()
 			.map_err(Error::invalid_identifier)?;
 		Ok(Counterparty::new(
-			value.client_id.parse().map_err(Error::invalid_identifier)?,
+			ClientId::from_str_with_policy(&value.client_id, policy)
+				.map_err(Error::invalid_identifier)?,
 			connection_id,
 			value
 				.prefix
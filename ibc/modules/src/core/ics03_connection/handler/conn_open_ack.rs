@@ -28,6 +28,7 @@ use crate::{
 				ConnectionIdState, ConnectionResult,
 			},
 			msgs::conn_open_ack::MsgConnectionOpenAck,
+			version::is_supported_version,
 		},
 		ics26_routing::context::ReaderContext,
 	},
@@ -51,11 +52,15 @@ pub(crate) fn process<Ctx: ReaderContext>(
 	let mut conn_end = ctx.connection_end(&msg.connection_id)?;
 	// A connection end must be Init or TryOpen; otherwise we return an error.
 	let state_is_consistent = conn_end.state_matches(&State::Init) &&
-		conn_end.versions().contains(&msg.version) ||
+		is_supported_version(conn_end.versions(), &msg.version) ||
 		conn_end.state_matches(&State::TryOpen) &&
 			conn_end.versions().get(0).eq(&Some(&msg.version));
 
 	if !state_is_consistent {
+		// The version picked by the counterparty must be one of the versions proposed on init.
+		if conn_end.state_matches(&State::Init) {
+			return Err(Error::version_not_supported(msg.version))
+		}
 		// Old connection end is in incorrect state, propagate the error.
 		return Err(Error::connection_mismatch(msg.connection_id))
 	}
@@ -22,6 +22,7 @@ use crate::{
 			events::Attributes,
 			handler::{ConnectionIdState, ConnectionResult},
 			msgs::conn_open_init::MsgConnectionOpenInit,
+			version::is_supported_version,
 		},
 		ics24_host::identifier::ConnectionId,
 		ics26_routing::context::ReaderContext,
@@ -51,7 +52,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	let versions = match msg.version {
 		Some(version) =>
-			if ctx.get_compatible_versions().contains(&version) {
+			if is_supported_version(&ctx.get_compatible_versions(), &version) {
 				Ok(vec![version])
 			} else {
 				Err(Error::version_not_supported(version))
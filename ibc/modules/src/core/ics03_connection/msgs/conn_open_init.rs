@@ -167,7 +167,7 @@ mod tests {
 				raw: RawMsgConnectionOpenInit {
 					counterparty: Some(RawCounterparty {
 						connection_id:
-							"abcdefghijksdffjssdkflweldflsfladfsfwjkrekcmmsdfsdfjflddmnopqrstu"
+							"abcdefghijksdffjssdkflweldflsfladfsfwjkrekcmmsdfsdfjflddmnopqrstuabcdefghijksdffjssdkflweldflsfladfsfwjkrekcmmsdfsdfjflddmnopqrstu"
 								.to_string(),
 						..get_dummy_raw_counterparty()
 					}),
@@ -310,7 +310,7 @@ mod tests {
 				raw: RawMsgConnectionOpenTry {
 					counterparty: Some(RawCounterparty {
 						connection_id:
-							"abcdasdfasdfsdfasfdwefwfsdfsfsfasfwewvxcvdvwgadvaadsefghijklmnopqrstu"
+							"abcdasdfasdfsdfasfdwefwfsdfsfsfasfwewvxcvdvwgadvaadsefghijklmnopqrstuabcdasdfasdfsdfasfdwefwfsdfsfsfasfwewvxcvdvwgadvaadsefghijklmnopqrstu"
 								.to_string(),
 						..get_dummy_raw_counterparty()
 					}),
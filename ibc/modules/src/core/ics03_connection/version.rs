@@ -32,10 +32,44 @@ pub struct Version {
 }
 
 impl Version {
+	/// Creates a version advertising the given features, which may include features beyond the
+	/// channel orderings of the default version.
+	pub fn new(identifier: String, features: Vec<String>) -> Self {
+		Self { identifier, features }
+	}
+
+	pub fn identifier(&self) -> &str {
+		&self.identifier
+	}
+
+	pub fn features(&self) -> &[String] {
+		&self.features
+	}
+
 	/// Checks whether or not the given feature is supported in this versin
 	pub fn is_supported_feature(&self, feature: String) -> bool {
 		self.features.contains(&feature)
 	}
+
+	/// Checks that a version proposed by the counterparty has the same identifier as this one and
+	/// only features this version supports.
+	pub fn verify_proposed_version(&self, proposed: &Version) -> Result<(), Error> {
+		if self.identifier != proposed.identifier ||
+			!proposed.features.iter().all(|feature| self.features.contains(feature))
+		{
+			return Err(Error::version_not_supported(proposed.clone()))
+		}
+		Ok(())
+	}
+
+	/// Returns the features supported by both versions, in the order of this version's features.
+	fn feature_intersection(&self, other: &Version) -> Vec<String> {
+		self.features
+			.iter()
+			.filter(|feature| other.features.contains(feature))
+			.cloned()
+			.collect()
+	}
 }
 
 impl Protobuf<RawVersion> for Version {}
@@ -84,7 +118,16 @@ pub fn get_compatible_versions() -> Vec<Version> {
 	vec![Version::default()]
 }
 
-/// Selects a version from the intersection of locally supported and counterparty versions.
+/// Checks whether a version is supported by any of the supported versions, i.e. it has the same
+/// identifier and a subset of its features.
+pub fn is_supported_version(supported_versions: &[Version], version: &Version) -> bool {
+	supported_versions
+		.iter()
+		.any(|supported| supported.verify_proposed_version(version).is_ok())
+}
+
+/// Selects a version from the intersection of locally supported and counterparty versions, with
+/// the features advertised by both ends. Versions without any common feature can't be picked.
 pub fn pick_version(
 	supported_versions: Vec<Version>,
 	counterparty_versions: Vec<Version>,
@@ -100,7 +143,12 @@ pub fn pick_version(
 					return Err(Error::empty_features())
 				}
 			}
-			intersection.append(&mut vec![s.clone()]);
+			// only the features advertised by both ends can be negotiated
+			let features = s.feature_intersection(c);
+			if features.is_empty() {
+				continue
+			}
+			intersection.push(Version::new(s.identifier.clone(), features));
 		}
 	}
 	intersection.sort_by(|a, b| a.identifier.cmp(&b.identifier));
@@ -118,9 +166,12 @@ mod tests {
 
 	use ibc_proto::ibc::core::connection::v1::Version as RawVersion;
 
-	use crate::core::ics03_connection::{
-		error::Error,
-		version::{get_compatible_versions, pick_version, Version},
+	use crate::core::{
+		ics03_connection::{
+			error::{Error, ErrorDetail},
+			version::{get_compatible_versions, is_supported_version, pick_version, Version},
+		},
+		ics04_channel::channel::Order,
 	};
 
 	fn good_versions() -> Vec<RawVersion> {
@@ -154,31 +205,55 @@ mod tests {
 		(
 			vec![
 				Version::default(),
-				Version { identifier: "3".to_string(), features: Vec::new() },
-				Version { identifier: "4".to_string(), features: Vec::new() },
+				Version {
+					identifier: "3".to_string(),
+					features: vec![Order::Ordered.as_str().to_string()],
+				},
+				Version {
+					identifier: "4".to_string(),
+					features: vec![Order::Ordered.as_str().to_string()],
+				},
 			]
 			.into_iter()
 			.collect(),
 			vec![
-				Version { identifier: "2".to_string(), features: Vec::new() },
-				Version { identifier: "4".to_string(), features: Vec::new() },
-				Version { identifier: "3".to_string(), features: Vec::new() },
+				Version {
+					identifier: "2".to_string(),
+					features: vec![Order::Ordered.as_str().to_string()],
+				},
+				Version {
+					identifier: "4".to_string(),
+					features: vec![Order::Ordered.as_str().to_string()],
+				},
+				Version {
+					identifier: "3".to_string(),
+					features: vec![Order::Ordered.as_str().to_string()],
+				},
 			]
 			.into_iter()
 			.collect(),
 			// Should pick version 3 as it's the lowest of the intersection {3, 4}
-			Version { identifier: "3".to_string(), features: Vec::new() },
+			Version {
+				identifier: "3".to_string(),
+				features: vec![Order::Ordered.as_str().to_string()],
+			},
 		)
 	}
 
 	fn disjoint() -> (Vec<Version>, Vec<Version>) {
 		(
-			vec![Version { identifier: "1".to_string(), features: Vec::new() }]
-				.into_iter()
-				.collect(),
-			vec![Version { identifier: "2".to_string(), features: Vec::new() }]
-				.into_iter()
-				.collect(),
+			vec![Version {
+				identifier: "1".to_string(),
+				features: vec![Order::Ordered.as_str().to_string()],
+			}]
+			.into_iter()
+			.collect(),
+			vec![Version {
+				identifier: "2".to_string(),
+				features: vec![Order::Ordered.as_str().to_string()],
+			}]
+			.into_iter()
+			.collect(),
 		)
 	}
 
@@ -274,6 +349,38 @@ mod tests {
 			}
 		}
 	}
+	#[test]
+	fn pick_intersects_features() {
+		let custom = "CUSTOM_FEATURE".to_string();
+		let ordered = Order::Ordered.as_str().to_string();
+		let unordered = Order::Unordered.as_str().to_string();
+		let supported = vec![Version::new("1".to_string(), vec![ordered.clone(), custom.clone()])];
+
+		// only the features advertised by both ends are picked
+		let counterparty =
+			vec![Version::new("1".to_string(), vec![unordered.clone(), custom.clone()])];
+		assert_eq!(
+			pick_version(supported.clone(), counterparty).unwrap(),
+			Version::new("1".to_string(), vec![custom.clone()])
+		);
+
+		// versions without common features can't be picked
+		let counterparty = vec![Version::new("1".to_string(), vec![unordered])];
+		assert!(matches!(
+			pick_version(supported.clone(), counterparty).unwrap_err().detail(),
+			ErrorDetail::NoCommonVersion(_)
+		));
+
+		// a picked version is supported by the versions it was picked from
+		let picked = pick_version(supported.clone(), get_compatible_versions()).unwrap();
+		assert_eq!(picked, Version::new("1".to_string(), vec![ordered]));
+		assert!(is_supported_version(&supported, &picked));
+		assert!(!is_supported_version(
+			&get_compatible_versions(),
+			&Version::new("1".to_string(), vec![custom])
+		));
+	}
+
 	#[test]
 	fn serialize() {
 		let def = Version::default();
@@ -28,7 +28,10 @@ use crate::{
 	core::{
 		ics02_client::height::Height,
 		ics04_channel::{error::Error, packet::Sequence, Version},
-		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics24_host::{
+			identifier::{ChannelId, ConnectionId, PortId},
+			validate::ValidationPolicy,
+		},
 	},
 	events::WithBlockDataType,
 };
@@ -58,6 +61,7 @@ impl TryFrom<RawIdentifiedChannel> for IdentifiedChannelEnd {
 			counterparty: value.counterparty,
 			connection_hops: value.connection_hops,
 			version: value.version,
+			upgrade_sequence: value.upgrade_sequence,
 		};
 
 		Ok(IdentifiedChannelEnd {
@@ -83,6 +87,7 @@ impl From<IdentifiedChannelEnd> for RawIdentifiedChannel {
 			version: value.channel_end.version.to_string(),
 			port_id: value.port_id.to_string(),
 			channel_id: value.channel_id.to_string(),
+			upgrade_sequence: value.channel_end.upgrade_sequence,
 		}
 	}
 }
@@ -94,6 +99,9 @@ pub struct ChannelEnd {
 	pub remote: Counterparty,
 	pub connection_hops: Vec<ConnectionId>,
 	pub version: Version,
+	/// Sequence of the latest upgrade attempt of this channel, 0 if it was never upgraded
+	#[serde(default)]
+	pub upgrade_sequence: u64,
 }
 
 impl Default for ChannelEnd {
@@ -104,6 +112,7 @@ impl Default for ChannelEnd {
 			remote: Counterparty::default(),
 			connection_hops: Vec::new(),
 			version: Version::default(),
+			upgrade_sequence: 0,
 		}
 	}
 }
@@ -131,7 +140,10 @@ impl TryFrom<RawChannel> for ChannelEnd {
 
 		let version = value.version.into();
 
-		Ok(ChannelEnd::new(chan_state, chan_ordering, remote, connection_hops, version))
+		let mut channel_end =
+			ChannelEnd::new(chan_state, chan_ordering, remote, connection_hops, version);
+		channel_end.set_upgrade_sequence(value.upgrade_sequence);
+		Ok(channel_end)
 	}
 }
 
@@ -143,6 +155,7 @@ impl From<ChannelEnd> for RawChannel {
 			counterparty: Some(value.counterparty().clone().into()),
 			connection_hops: value.connection_hops.iter().map(|v| v.as_str().to_string()).collect(),
 			version: value.version.to_string(),
+			upgrade_sequence: value.upgrade_sequence,
 		}
 	}
 }
@@ -156,7 +169,7 @@ impl ChannelEnd {
 		connection_hops: Vec<ConnectionId>,
 		version: Version,
 	) -> Self {
-		Self { state, ordering, remote, connection_hops, version }
+		Self { state, ordering, remote, connection_hops, version, upgrade_sequence: 0 }
 	}
 
 	/// Updates the ChannelEnd to assume a new State 's'.
@@ -172,6 +185,10 @@ impl ChannelEnd {
 		self.remote.channel_id = Some(c);
 	}
 
+	pub fn set_upgrade_sequence(&mut self, upgrade_sequence: u64) {
+		self.upgrade_sequence = upgrade_sequence;
+	}
+
 	/// Returns `true` if this `ChannelEnd` is in state [`State::Open`].
 	pub fn is_open(&self) -> bool {
 		self.state_matches(&State::Open)
@@ -197,6 +214,16 @@ impl ChannelEnd {
 		&self.version
 	}
 
+	pub fn upgrade_sequence(&self) -> u64 {
+		self.upgrade_sequence
+	}
+
+	/// Returns `true` if an upgrade of this channel is being flushed, during which packets can no
+	/// longer be sent but in-flight packets are still received, acknowledged and timed out.
+	pub fn is_flushing(&self) -> bool {
+		matches!(self.state, State::Flushing | State::FlushComplete)
+	}
+
 	pub fn validate_basic(&self) -> Result<(), Error> {
 		if self.connection_hops.len() != 1 {
 			return Err(Error::invalid_connection_hops_length(1, self.connection_hops.len()))
@@ -263,7 +290,11 @@ impl TryFrom<RawCounterparty> for Counterparty {
 			.map(|v| FromStr::from_str(v.as_str()))
 			.transpose()
 			.map_err(Error::identifier)?;
-		Ok(Counterparty::new(value.port_id.parse().map_err(Error::identifier)?, channel_id))
+		// the port is chosen by the counterparty chain
+		let port_id =
+			PortId::from_str_with_policy(&value.port_id, ValidationPolicy::CounterpartyCompatible)
+				.map_err(Error::identifier)?;
+		Ok(Counterparty::new(port_id, channel_id))
 	}
 }
 
@@ -280,6 +311,9 @@ impl From<Counterparty> for RawCounterparty {
 pub enum Order {
 	Unordered = 1,
 	Ordered = 2,
+	/// Packets are received in the order they were sent, but a packet that timed out is skipped
+	/// on both ends instead of closing the channel.
+	OrderedAllowTimeout = 3,
 }
 
 impl Default for Order {
@@ -300,6 +334,22 @@ impl Order {
 		match self {
 			Self::Unordered => "ORDER_UNORDERED",
 			Self::Ordered => "ORDER_ORDERED",
+			Self::OrderedAllowTimeout => "ORDER_ORDERED_ALLOW_TIMEOUT",
+		}
+	}
+
+	/// Whether packets are received and acknowledged in the order they were sent
+	pub fn is_ordered(&self) -> bool {
+		matches!(self, Self::Ordered | Self::OrderedAllowTimeout)
+	}
+
+	/// The connection feature required to open a channel with this ordering. Channels that allow
+	/// timeouts aren't negotiated by connection versions, they're accepted over any connection
+	/// that supports ordered channels.
+	pub fn connection_feature(&self) -> &'static str {
+		match self {
+			Self::OrderedAllowTimeout => Self::Ordered.as_str(),
+			_ => self.as_str(),
 		}
 	}
 
@@ -308,6 +358,7 @@ impl Order {
 		match nr {
 			1 => Ok(Self::Unordered),
 			2 => Ok(Self::Ordered),
+			3 => Ok(Self::OrderedAllowTimeout),
 			_ => Err(Error::unknown_order_type(nr.to_string())),
 		}
 	}
@@ -320,6 +371,7 @@ impl FromStr for Order {
 		match s.to_lowercase().trim_start_matches("order_") {
 			"unordered" => Ok(Self::Unordered),
 			"ordered" => Ok(Self::Ordered),
+			"ordered_allow_timeout" => Ok(Self::OrderedAllowTimeout),
 			_ => Err(Error::unknown_order_type(s.to_string())),
 		}
 	}
@@ -331,6 +383,8 @@ pub enum State {
 	TryOpen = 2,
 	Open = 3,
 	Closed = 4,
+	Flushing = 5,
+	FlushComplete = 6,
 }
 
 impl State {
@@ -341,6 +395,8 @@ impl State {
 			Self::TryOpen => "TRYOPEN",
 			Self::Open => "OPEN",
 			Self::Closed => "CLOSED",
+			Self::Flushing => "FLUSHING",
+			Self::FlushComplete => "FLUSHCOMPLETE",
 		}
 	}
 
@@ -351,6 +407,8 @@ impl State {
 			2 => Ok(Self::TryOpen),
 			3 => Ok(Self::Open),
 			4 => Ok(Self::Closed),
+			5 => Ok(Self::Flushing),
+			6 => Ok(Self::FlushComplete),
 			_ => Err(Error::unknown_state(s)),
 		}
 	}
@@ -374,6 +432,22 @@ impl State {
 	}
 }
 
+impl FromStr for State {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_uppercase().trim_start_matches("STATE_") {
+			"INIT" => Ok(Self::Init),
+			"TRYOPEN" => Ok(Self::TryOpen),
+			"OPEN" => Ok(Self::Open),
+			"CLOSED" => Ok(Self::Closed),
+			"FLUSHING" => Ok(Self::Flushing),
+			"FLUSHCOMPLETE" => Ok(Self::FlushComplete),
+			_ => Err(Error::implementation_specific(format!("unknown channel state: {s}"))),
+		}
+	}
+}
+
 /// Provides a `to_string` method.
 impl core::fmt::Display for State {
 	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
@@ -421,6 +495,7 @@ pub mod test_util {
 			counterparty: Some(get_dummy_raw_counterparty()),
 			connection_hops: vec![ConnectionId::default().to_string()],
 			version: "ics20".to_string(), // The version is not validated.
+			upgrade_sequence: 0,
 		}
 	}
 }
@@ -528,6 +603,11 @@ mod tests {
 		let tests: Vec<Test> = vec![
 			Test { ordering: "UNORDERED", want_res: Order::Unordered, want_err: false },
 			Test { ordering: "ORDERED", want_res: Order::Ordered, want_err: false },
+			Test {
+				ordering: "ORDER_ORDERED_ALLOW_TIMEOUT",
+				want_res: Order::OrderedAllowTimeout,
+				want_err: false,
+			},
 		]
 		.into_iter()
 		.collect();
@@ -23,9 +23,12 @@ use crate::{
 			channel::ChannelEnd,
 			commitment::{AcknowledgementCommitment, PacketCommitment},
 			error::Error,
-			handler::{recv_packet::RecvPacketResult, ChannelIdState, ChannelResult},
+			handler::{
+				recv_packet::RecvPacketResult, ChannelIdState, ChannelResult, ChannelUpgradeResult,
+			},
 			msgs::acknowledgement::Acknowledgement,
 			packet::Receipt,
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 	},
@@ -90,6 +93,33 @@ pub trait ChannelReader {
 		self.hash(ack.into_bytes()).into()
 	}
 
+	/// Returns the upgrade this chain proposed for the given channel, if an upgrade is in
+	/// progress.
+	fn channel_upgrade(&self, port_channel_id: &(PortId, ChannelId)) -> Result<Upgrade, Error>;
+
+	/// Returns the upgrade proposed by the counterparty of the given channel, as stored once the
+	/// channel started flushing.
+	fn channel_counterparty_upgrade(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<Upgrade, Error>;
+
+	/// Returns the receipt written when the last upgrade of the given channel was aborted.
+	fn channel_upgrade_error_receipt(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<ErrorReceipt, Error>;
+
+	/// Returns true if packets sent on the given channel still have a commitment, i.e. they have
+	/// neither been acknowledged nor timed out.
+	fn has_inflight_packets(&self, port_channel_id: &(PortId, ChannelId)) -> Result<bool, Error>;
+
+	/// Returns how long after it started flushing on this chain an upgrade can be timed out by
+	/// the counterparty.
+	fn channel_upgrade_timeout(&self) -> Duration {
+		Duration::from_secs(10 * 60)
+	}
+
 	/// A Sha2_256 hashing function
 	fn hash(&self, value: Vec<u8>) -> Vec<u8>;
 
@@ -143,6 +173,34 @@ pub trait ChannelKeeper: ChannelReader {
 		Ok(())
 	}
 
+	fn store_channel_upgrade_result(&mut self, result: ChannelUpgradeResult) -> Result<(), Error> {
+		let port_channel_id = (result.port_id.clone(), result.channel_id);
+		self.store_channel(port_channel_id.clone(), &result.channel_end)?;
+
+		match result.upgrade {
+			Some(upgrade) => self.store_upgrade(port_channel_id.clone(), upgrade)?,
+			None => self.delete_upgrade(port_channel_id.clone())?,
+		}
+		match result.counterparty_upgrade {
+			Some(upgrade) => self.store_counterparty_upgrade(port_channel_id.clone(), upgrade)?,
+			None => self.delete_counterparty_upgrade(port_channel_id.clone())?,
+		}
+		if let Some(error_receipt) = result.error_receipt {
+			self.store_upgrade_error_receipt(port_channel_id.clone(), error_receipt)?;
+		}
+
+		// The ordering of the channel changed, packet sequences restart from the ones the
+		// upgrade was opened with.
+		if let Some(seq) = result.next_sequence_recv {
+			self.store_next_sequence_recv(port_channel_id.clone(), seq)?;
+		}
+		if let Some(seq) = result.next_sequence_ack {
+			self.store_next_sequence_ack(port_channel_id, seq)?;
+		}
+
+		Ok(())
+	}
+
 	fn store_packet_result(&mut self, general_result: PacketResult) -> Result<(), Error> {
 		match general_result {
 			PacketResult::Send(res) => {
@@ -168,6 +226,10 @@ pub trait ChannelKeeper: ChannelReader {
 					self.store_recv_packet((port_id, channel_id, packet.sequence), packet)?
 				},
 
+				RecvPacketResult::TimedOut { port_id, channel_id, next_seq_recv, sequence } => {
+					self.store_next_sequence_recv((port_id.clone(), channel_id), next_seq_recv)?;
+					self.store_packet_receipt((port_id, channel_id, sequence), Receipt::Timeout)?
+				},
 				RecvPacketResult::NoOp => unreachable!(),
 			},
 			PacketResult::WriteAck(res) => {
@@ -195,6 +257,10 @@ pub trait ChannelKeeper: ChannelReader {
 					//Ordered Channel
 					self.store_channel((res.port_id.clone(), res.channel_id), &c)?;
 				}
+				if let Some(s) = res.next_seq_ack {
+					// Ordered channel allowing timeouts
+					self.store_next_sequence_ack((res.port_id.clone(), res.channel_id), s)?;
+				}
 				self.delete_packet_commitment((res.port_id.clone(), res.channel_id, res.seq))?;
 			},
 		}
@@ -278,6 +344,31 @@ pub trait ChannelKeeper: ChannelReader {
 		seq: Sequence,
 	) -> Result<(), Error>;
 
+	fn store_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: Upgrade,
+	) -> Result<(), Error>;
+
+	fn delete_upgrade(&mut self, port_channel_id: (PortId, ChannelId)) -> Result<(), Error>;
+
+	fn store_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: Upgrade,
+	) -> Result<(), Error>;
+
+	fn delete_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+	) -> Result<(), Error>;
+
+	fn store_upgrade_error_receipt(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		error_receipt: ErrorReceipt,
+	) -> Result<(), Error>;
+
 	/// Called upon channel identifier creation (Init or Try message processing).
 	/// Increases the counter which keeps track of how many channels have been created.
 	/// Should never fail.
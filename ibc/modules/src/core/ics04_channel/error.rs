@@ -396,6 +396,68 @@ define_error! {
 
 		NotEnoughtFundsForFee
 			| _ | { "not enough funds for fee" },
+
+		MissingUpgrade
+			{ port_id: PortId, channel_id: ChannelId }
+			| e | {
+				format_args!(
+					"no upgrade in progress for port {0} and channel {1}",
+					e.port_id, e.channel_id)
+			},
+
+		UpgradeInProgress
+			{ port_id: PortId, channel_id: ChannelId }
+			| e | {
+				format_args!(
+					"an upgrade is already in progress for port {0} and channel {1}",
+					e.port_id, e.channel_id)
+			},
+
+		MissingUpgradeFields
+			| _ | { "missing upgrade fields" },
+
+		MissingUpgradeTimeout
+			| _ | { "missing upgrade timeout" },
+
+		MissingErrorReceipt
+			| _ | { "missing upgrade error receipt" },
+
+		InvalidUpgradeSequence
+			{ expected: u64, actual: u64 }
+			| e | {
+				format_args!(
+					"invalid upgrade sequence: expected {0}; actual {1}",
+					e.expected, e.actual)
+			},
+
+		UpgradeTimedOut
+			| _ | { "upgrade timeout has passed" },
+
+		UpgradeTimeoutNotReached
+			| _ | { "upgrade timeout has not been reached on the counterparty" },
+
+		IncompatibleUpgradeFields
+			| _ | { "proposed upgrade fields are incompatible with the counterparty upgrade" },
+
+		InflightPackets
+			{ port_id: PortId, channel_id: ChannelId }
+			| e | {
+				format_args!(
+					"channel {1} on port {0} still has in-flight packets",
+					e.port_id, e.channel_id)
+			},
+
+		UpgradeNotSupported
+			{ port_id: PortId }
+			| e | {
+				format_args!(
+					"the application bound to port {0} does not support channel upgrades",
+					e.port_id)
+			},
+
+		UpgradeVerificationFailed
+			[ client_error::Error ]
+			| _ | { "upgrade proof verification failed" },
 	}
 }
 
@@ -20,7 +20,7 @@ use tendermint::abci::{Event as AbciEvent, EventAttribute};
 use crate::{
 	core::{
 		ics02_client::height::Height,
-		ics04_channel::{error::Error, packet::Packet},
+		ics04_channel::{channel::State, error::Error, packet::Packet},
 		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
 	},
 	events::{
@@ -37,6 +37,8 @@ pub const CHANNEL_ID_ATTRIBUTE_KEY: &str = "channel_id";
 pub const PORT_ID_ATTRIBUTE_KEY: &str = "port_id";
 pub const COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY: &str = "counterparty_channel_id";
 pub const COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY: &str = "counterparty_port_id";
+pub const UPGRADE_SEQUENCE_ATTRIBUTE_KEY: &str = "upgrade_sequence";
+pub const CHANNEL_STATE_ATTRIBUTE_KEY: &str = "channel_state";
 
 /// Packet event attribute keys
 pub const PKT_SEQ_ATTRIBUTE_KEY: &str = "packet_sequence";
@@ -81,6 +83,17 @@ pub fn try_from_tx(event: &tendermint::abci::Event) -> Option<IbcEvent> {
 			.map(|res| res.ok().map(IbcEvent::CloseConfirmChannel))
 			.ok()
 			.flatten(),
+		Ok(
+			kind @ (IbcEventType::UpgradeInitChannel |
+			IbcEventType::UpgradeTryChannel |
+			IbcEventType::UpgradeAckChannel |
+			IbcEventType::UpgradeConfirmChannel |
+			IbcEventType::UpgradeOpenChannel |
+			IbcEventType::UpgradeTimeoutChannel |
+			IbcEventType::UpgradeCancelChannel),
+		) => UpgradeStep::from_event_type(&kind)
+			.and_then(|step| extract_upgrade_from_tx(event, step).ok())
+			.map(IbcEvent::UpgradeChannel),
 		Ok(IbcEventType::SendPacket) => {
 			extract_packet_and_write_ack_from_tx(event)
 				.map(|(packet, write_ack)| {
@@ -151,6 +164,46 @@ fn extract_attributes_from_tx(event: &tendermint::abci::Event) -> Result<Attribu
 	Ok(attr)
 }
 
+fn extract_upgrade_from_tx(
+	event: &tendermint::abci::Event,
+	step: UpgradeStep,
+) -> Result<Upgrade, Error> {
+	let mut upgrade = Upgrade {
+		height: Default::default(),
+		step,
+		port_id: Default::default(),
+		channel_id: Default::default(),
+		counterparty_port_id: Default::default(),
+		counterparty_channel_id: None,
+		upgrade_sequence: 0,
+		channel_state: State::Open,
+	};
+	for tag in &event.attributes {
+		let key = tag.key.as_str();
+		let value = tag.value.as_str();
+		match key {
+			PORT_ID_ATTRIBUTE_KEY => upgrade.port_id = value.parse().map_err(Error::identifier)?,
+			CHANNEL_ID_ATTRIBUTE_KEY =>
+				upgrade.channel_id = value.parse().map_err(Error::identifier)?,
+			COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY => {
+				upgrade.counterparty_port_id = value.parse().map_err(Error::identifier)?;
+			},
+			COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY => {
+				upgrade.counterparty_channel_id = value.parse().ok();
+			},
+			UPGRADE_SEQUENCE_ATTRIBUTE_KEY => {
+				upgrade.upgrade_sequence = value.parse().map_err(|_| {
+					Error::implementation_specific("parse upgrade_sequence error".to_string())
+				})?;
+			},
+			CHANNEL_STATE_ATTRIBUTE_KEY => upgrade.channel_state = value.parse()?,
+			_ => {},
+		}
+	}
+
+	Ok(upgrade)
+}
+
 fn extract_packet_and_write_ack_from_tx(
 	event: &tendermint::abci::Event,
 ) -> Result<(Packet, Vec<u8>), Error> {
@@ -750,6 +803,125 @@ macro_rules! impl_try_from_raw_obj_for_event {
 
 impl_try_from_raw_obj_for_event!(OpenInit, OpenTry, OpenAck, OpenConfirm, CloseInit, CloseConfirm);
 
+/// Step of the channel upgrade handshake that emitted an [`Upgrade`] event.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum UpgradeStep {
+	Init,
+	Try,
+	Ack,
+	Confirm,
+	Open,
+	Timeout,
+	Cancel,
+}
+
+impl UpgradeStep {
+	pub fn event_type(&self) -> IbcEventType {
+		match self {
+			UpgradeStep::Init => IbcEventType::UpgradeInitChannel,
+			UpgradeStep::Try => IbcEventType::UpgradeTryChannel,
+			UpgradeStep::Ack => IbcEventType::UpgradeAckChannel,
+			UpgradeStep::Confirm => IbcEventType::UpgradeConfirmChannel,
+			UpgradeStep::Open => IbcEventType::UpgradeOpenChannel,
+			UpgradeStep::Timeout => IbcEventType::UpgradeTimeoutChannel,
+			UpgradeStep::Cancel => IbcEventType::UpgradeCancelChannel,
+		}
+	}
+
+	pub fn from_event_type(event_type: &IbcEventType) -> Option<Self> {
+		match event_type {
+			IbcEventType::UpgradeInitChannel => Some(UpgradeStep::Init),
+			IbcEventType::UpgradeTryChannel => Some(UpgradeStep::Try),
+			IbcEventType::UpgradeAckChannel => Some(UpgradeStep::Ack),
+			IbcEventType::UpgradeConfirmChannel => Some(UpgradeStep::Confirm),
+			IbcEventType::UpgradeOpenChannel => Some(UpgradeStep::Open),
+			IbcEventType::UpgradeTimeoutChannel => Some(UpgradeStep::Timeout),
+			IbcEventType::UpgradeCancelChannel => Some(UpgradeStep::Cancel),
+			_ => None,
+		}
+	}
+}
+
+/// Emitted by every step of the channel upgrade handshake, with the state the channel was left
+/// in.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct Upgrade {
+	pub height: Height,
+	pub step: UpgradeStep,
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub counterparty_port_id: PortId,
+	pub counterparty_channel_id: Option<ChannelId>,
+	pub upgrade_sequence: u64,
+	pub channel_state: State,
+}
+
+impl Upgrade {
+	pub fn port_id(&self) -> &PortId {
+		&self.port_id
+	}
+	pub fn channel_id(&self) -> &ChannelId {
+		&self.channel_id
+	}
+	pub fn height(&self) -> Height {
+		self.height
+	}
+	pub fn set_height(&mut self, height: Height) {
+		self.height = height;
+	}
+}
+
+impl From<Upgrade> for IbcEvent {
+	fn from(v: Upgrade) -> Self {
+		IbcEvent::UpgradeChannel(v)
+	}
+}
+
+impl From<Upgrade> for AbciEvent {
+	fn from(v: Upgrade) -> Self {
+		let mut attributes = vec![
+			EventAttribute {
+				key: HEIGHT_ATTRIBUTE_KEY.to_string(),
+				value: v.height.to_string(),
+				index: false,
+			},
+			EventAttribute {
+				key: PORT_ID_ATTRIBUTE_KEY.to_string(),
+				value: v.port_id.to_string(),
+				index: false,
+			},
+			EventAttribute {
+				key: CHANNEL_ID_ATTRIBUTE_KEY.to_string(),
+				value: v.channel_id.to_string(),
+				index: false,
+			},
+			EventAttribute {
+				key: COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY.to_string(),
+				value: v.counterparty_port_id.to_string(),
+				index: false,
+			},
+		];
+		if let Some(channel_id) = v.counterparty_channel_id {
+			attributes.push(EventAttribute {
+				key: COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY.to_string(),
+				value: channel_id.to_string(),
+				index: false,
+			});
+		}
+		attributes.push(EventAttribute {
+			key: UPGRADE_SEQUENCE_ATTRIBUTE_KEY.to_string(),
+			value: v.upgrade_sequence.to_string(),
+			index: false,
+		});
+		attributes.push(EventAttribute {
+			key: CHANNEL_STATE_ATTRIBUTE_KEY.to_string(),
+			value: v.channel_state.as_string().to_string(),
+			index: false,
+		});
+		AbciEvent { kind: v.step.event_type().as_str().to_string(), attributes }
+	}
+}
+
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct SendPacket {
 	pub height: Height,
@@ -1163,6 +1335,22 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn upgrade_event_to_abci_event() {
+		let upgrade = Upgrade {
+			height: Height::default(),
+			step: UpgradeStep::Ack,
+			port_id: "test_port".parse().unwrap(),
+			channel_id: "channel-0".parse().unwrap(),
+			counterparty_port_id: "counterparty_test_port".parse().unwrap(),
+			counterparty_channel_id: Some("channel-1".parse().unwrap()),
+			upgrade_sequence: 2,
+			channel_state: State::FlushComplete,
+		};
+		let event = AbciEvent::from(upgrade.clone());
+		assert_eq!(try_from_tx(&event), Some(IbcEvent::UpgradeChannel(upgrade)));
+	}
+
 	#[test]
 	fn packet_event_to_abci_event() {
 		let packet = Packet {
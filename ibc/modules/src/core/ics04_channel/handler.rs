@@ -16,20 +16,23 @@
 
 use crate::{
 	core::{
+		ics03_connection::connection::{ConnectionEnd, State as ConnectionState},
 		ics04_channel::{
-			channel::ChannelEnd,
+			channel::{ChannelEnd, State},
 			error::Error,
-			events::WriteAcknowledgement,
-			msgs::{ChannelMsg, PacketMsg},
-			packet::PacketResult,
+			events::{Upgrade as UpgradeEvent, UpgradeStep, WriteAcknowledgement},
+			msgs::{ChannelMsg, ChannelUpgradeMsg, PacketMsg},
+			packet::{PacketResult, Sequence},
+			upgrade::{ErrorReceipt, Upgrade, UpgradeTimeout},
 		},
-		ics24_host::identifier::{ChannelId, PortId},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
 		ics26_routing::context::{
 			Ics26Context, ModuleId, ModuleOutputBuilder, ReaderContext, Router,
 		},
 	},
 	events::IbcEvent,
 	handler::{HandlerOutput, HandlerOutputBuilder},
+	Height,
 };
 use alloc::string::ToString;
 use core::fmt::Debug;
@@ -41,6 +44,13 @@ pub mod chan_open_ack;
 pub mod chan_open_confirm;
 pub mod chan_open_init;
 pub mod chan_open_try;
+pub mod chan_upgrade_ack;
+pub mod chan_upgrade_cancel;
+pub mod chan_upgrade_confirm;
+pub mod chan_upgrade_init;
+pub mod chan_upgrade_open;
+pub mod chan_upgrade_timeout;
+pub mod chan_upgrade_try;
 pub mod recv_packet;
 pub mod send_packet;
 pub mod timeout;
@@ -176,6 +186,184 @@ where
 	Ok(result)
 }
 
+/// The outcome of processing a message of the channel upgrade handshake. Upgrades that are `None`
+/// are deleted from the store when the result is written.
+#[derive(Clone, Debug)]
+pub struct ChannelUpgradeResult {
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub channel_end: ChannelEnd,
+	pub upgrade: Option<Upgrade>,
+	pub counterparty_upgrade: Option<Upgrade>,
+	pub error_receipt: Option<ErrorReceipt>,
+	pub next_sequence_recv: Option<Sequence>,
+	pub next_sequence_ack: Option<Sequence>,
+}
+
+pub fn channel_upgrade_validate<Ctx>(ctx: &Ctx, msg: &ChannelUpgradeMsg) -> Result<ModuleId, Error>
+where
+	Ctx: Ics26Context,
+{
+	let module_id = msg.lookup_module(ctx)?;
+	if ctx.router().has_route(&module_id) {
+		Ok(module_id)
+	} else {
+		Err(Error::route_not_found())
+	}
+}
+
+/// General entry point for processing any type of message related to the ICS4 channel upgrade
+/// handshake protocol.
+pub fn channel_upgrade_dispatch<Ctx>(
+	ctx: &Ctx,
+	msg: &ChannelUpgradeMsg,
+) -> Result<(HandlerOutputBuilder<()>, ChannelUpgradeResult), Error>
+where
+	Ctx: ReaderContext,
+{
+	log::debug!(target: "ics::channel", "dispatch {:?}",&msg);
+	let output = match msg {
+		ChannelUpgradeMsg::ChannelUpgradeInit(msg) => chan_upgrade_init::process(ctx, msg),
+		ChannelUpgradeMsg::ChannelUpgradeTry(msg) => chan_upgrade_try::process(ctx, msg),
+		ChannelUpgradeMsg::ChannelUpgradeAck(msg) => chan_upgrade_ack::process(ctx, msg),
+		ChannelUpgradeMsg::ChannelUpgradeConfirm(msg) => chan_upgrade_confirm::process(ctx, msg),
+		ChannelUpgradeMsg::ChannelUpgradeOpen(msg) => chan_upgrade_open::process(ctx, msg),
+		ChannelUpgradeMsg::ChannelUpgradeTimeout(msg) => chan_upgrade_timeout::process(ctx, msg),
+		ChannelUpgradeMsg::ChannelUpgradeCancel(msg) => chan_upgrade_cancel::process(ctx, msg),
+	}?;
+	let HandlerOutput { result, log, events } = output;
+	let builder = HandlerOutput::builder().with_log(log).with_events(events);
+	Ok((builder, result))
+}
+
+pub fn channel_upgrade_callback<Ctx>(
+	ctx: &mut Ctx,
+	module_id: &ModuleId,
+	msg: &ChannelUpgradeMsg,
+	mut result: ChannelUpgradeResult,
+	module_output: &mut ModuleOutputBuilder,
+) -> Result<ChannelUpgradeResult, Error>
+where
+	Ctx: Ics26Context,
+{
+	// Get an immutable context for module callbacks
+	let ctx_clone = ctx.clone();
+	let cb = ctx.router_mut().get_route_mut(module_id).ok_or_else(Error::route_not_found)?;
+
+	match msg {
+		// an outdated upgrade is rejected with an error receipt, without involving the module
+		ChannelUpgradeMsg::ChannelUpgradeTry(_) if result.error_receipt.is_some() => {},
+		ChannelUpgradeMsg::ChannelUpgradeInit(_) | ChannelUpgradeMsg::ChannelUpgradeTry(_) => {
+			let upgrade = result
+				.upgrade
+				.as_mut()
+				.ok_or_else(|| Error::missing_upgrade(result.port_id.clone(), result.channel_id))?;
+			let version = if let ChannelUpgradeMsg::ChannelUpgradeInit(_) = msg {
+				cb.on_chan_upgrade_init(
+					&ctx_clone,
+					module_output,
+					&result.port_id,
+					&result.channel_id,
+					upgrade.fields.ordering,
+					&upgrade.fields.connection_hops,
+					&upgrade.fields.version,
+				)?
+			} else {
+				cb.on_chan_upgrade_try(
+					&ctx_clone,
+					module_output,
+					&result.port_id,
+					&result.channel_id,
+					upgrade.fields.ordering,
+					&upgrade.fields.connection_hops,
+					&upgrade.fields.version,
+				)?
+			};
+			upgrade.fields.version = version;
+		},
+		ChannelUpgradeMsg::ChannelUpgradeAck(msg) => cb.on_chan_upgrade_ack(
+			&ctx_clone,
+			module_output,
+			&result.port_id,
+			&result.channel_id,
+			&msg.counterparty_upgrade.fields.version,
+		)?,
+		ChannelUpgradeMsg::ChannelUpgradeOpen(_) => cb.on_chan_upgrade_open(
+			&ctx_clone,
+			module_output,
+			&result.port_id,
+			&result.channel_id,
+			result.channel_end.ordering,
+			&result.channel_end.connection_hops,
+			&result.channel_end.version,
+		)?,
+		ChannelUpgradeMsg::ChannelUpgradeConfirm(_) |
+		ChannelUpgradeMsg::ChannelUpgradeTimeout(_) |
+		ChannelUpgradeMsg::ChannelUpgradeCancel(_) => {},
+	}
+	Ok(result)
+}
+
+/// Returns the open connection an upgraded channel runs over, along with the identifier of that
+/// connection on the counterparty chain.
+pub(crate) fn upgrade_connection<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	connection_hops: &[ConnectionId],
+) -> Result<(ConnectionEnd, ConnectionId), Error> {
+	if connection_hops.len() != 1 {
+		return Err(Error::invalid_connection_hops_length(1, connection_hops.len()))
+	}
+
+	let conn = ctx.connection_end(&connection_hops[0]).map_err(Error::ics03_connection)?;
+	if !conn.state_matches(&ConnectionState::Open) {
+		return Err(Error::connection_not_open(connection_hops[0].clone()))
+	}
+
+	let counterparty_connection_id = conn
+		.counterparty()
+		.connection_id()
+		.cloned()
+		.ok_or_else(|| Error::undefined_connection_counterparty(connection_hops[0].clone()))?;
+
+	Ok((conn, counterparty_connection_id))
+}
+
+/// Moves a channel end to the flushing stage of its upgrade. The upgrade timeout and the last
+/// sequence sent before the upgrade are fixed at this point, so that the counterparty knows when
+/// to give up on the upgrade and which packets remain to be flushed.
+pub(crate) fn start_flushing<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	port_channel_id: &(PortId, ChannelId),
+	channel_end: &mut ChannelEnd,
+	upgrade: &mut Upgrade,
+) -> Result<(), Error> {
+	let timestamp = (ctx.host_timestamp() + ctx.channel_upgrade_timeout())
+		.map_err(|e| Error::implementation_specific(e.to_string()))?;
+	upgrade.timeout = UpgradeTimeout::new(Height::zero(), timestamp);
+	upgrade.next_sequence_send = ctx.get_next_sequence_send(port_channel_id)?;
+	channel_end.set_state(State::Flushing);
+	Ok(())
+}
+
+pub(crate) fn upgrade_event<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	step: UpgradeStep,
+	port_id: &PortId,
+	channel_id: ChannelId,
+	channel_end: &ChannelEnd,
+) -> IbcEvent {
+	IbcEvent::UpgradeChannel(UpgradeEvent {
+		height: ctx.host_height(),
+		step,
+		port_id: port_id.clone(),
+		channel_id,
+		counterparty_port_id: channel_end.counterparty().port_id().clone(),
+		counterparty_channel_id: channel_end.counterparty().channel_id().cloned(),
+		upgrade_sequence: channel_end.upgrade_sequence(),
+		channel_state: channel_end.state,
+	})
+}
+
 pub fn get_module_for_packet_msg<Ctx>(ctx: &Ctx, msg: &PacketMsg) -> Result<ModuleId, Error>
 where
 	Ctx: Ics26Context,
@@ -280,3 +468,394 @@ where
 	};
 	Ok(())
 }
+
+#[cfg(test)]
+pub(crate) mod test_util {
+	use crate::{
+		core::{
+			ics02_client::height::Height,
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				version::get_compatible_versions,
+			},
+			ics04_channel::{
+				channel::{ChannelEnd, Counterparty, Order, State},
+				events::Upgrade as UpgradeEvent,
+				upgrade::{Upgrade, UpgradeFields, UpgradeTimeout},
+				Version,
+			},
+			ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		},
+		events::IbcEvent,
+		handler::HandlerOutputBuilder,
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+		timestamp::{Timestamp, ZERO_DURATION},
+	};
+
+	/// Height of the proofs of the upgrade messages, the client of the counterparty has a
+	/// consensus state at this height.
+	pub const PROOF_HEIGHT: u64 = 5;
+
+	/// A chain with an unordered ics20 channel end in the given state and at the given upgrade
+	/// sequence, over an open connection to the counterparty.
+	pub fn context_with_channel(
+		state: State,
+		upgrade_sequence: u64,
+	) -> MockContext<MockClientTypes> {
+		let connection_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::new(
+				ClientId::default(),
+				Some(ConnectionId::default()),
+				Default::default(),
+			),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+		let mut channel_end = ChannelEnd::new(
+			state,
+			Order::Unordered,
+			Counterparty::new(PortId::default(), Some(ChannelId::default())),
+			vec![ConnectionId::default()],
+			Version::ics20(),
+		);
+		channel_end.set_upgrade_sequence(upgrade_sequence);
+		MockContext::default()
+			.with_client(&ClientId::default(), Height::new(0, PROOF_HEIGHT))
+			.with_connection(ConnectionId::default(), connection_end)
+			.with_channel(PortId::default(), ChannelId::default(), channel_end)
+			.with_send_sequence(PortId::default(), ChannelId::default(), 1.into())
+	}
+
+	/// An upgrade of the channel to the given ordering and version, over the same connection.
+	pub fn upgrade(ordering: Order, version: Version) -> Upgrade {
+		Upgrade::new(
+			UpgradeFields::new(ordering, vec![ConnectionId::default()], version),
+			UpgradeTimeout::new(Height::zero(), Timestamp::none()),
+			1.into(),
+		)
+	}
+
+	/// Returns the single upgrade event emitted by a handler.
+	pub fn emitted_upgrade_event(output: HandlerOutputBuilder<()>) -> UpgradeEvent {
+		let mut events = output.with_result(()).events;
+		assert_eq!(events.len(), 1);
+		match events.remove(0) {
+			IbcEvent::UpgradeChannel(event) => event,
+			event => panic!("unexpected event {:?}", event),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics02_client::{context::ClientKeeper, height::Height},
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				version::get_compatible_versions,
+			},
+			ics04_channel::{
+				channel::{ChannelEnd, Counterparty, Order, State},
+				context::{ChannelKeeper, ChannelReader},
+				handler::{
+					acknowledgement, recv_packet, recv_packet::RecvPacketResult, send_packet,
+					timeout,
+				},
+				msgs::{
+					acknowledgement::MsgAcknowledgement, recv_packet::MsgRecvPacket,
+					timeout::MsgTimeout,
+				},
+				packet::{Packet, PacketResult, Sequence},
+				Version,
+			},
+			ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		},
+		mock::{
+			client_state::MockConsensusState,
+			context::{MockClientTypes, MockContext},
+			header::MockHeader,
+		},
+		prelude::*,
+		proofs::Proofs,
+		test_utils::{get_dummy_account_id, get_dummy_proof},
+		timestamp::{Timestamp, ZERO_DURATION},
+	};
+	use alloc::collections::BTreeSet;
+	use proptest::prelude::*;
+
+	/// Height of the client of the counterparty on both chains, packets can be received and
+	/// acknowledged with proofs at this height.
+	const CLIENT_HEIGHT: u64 = 10;
+	/// Timeout height of all the packets, they can be timed out with proofs at this height.
+	const TIMEOUT_HEIGHT: u64 = 20;
+
+	/// Message submitted by a relayer, the packets are picked among the ones sent so far.
+	#[derive(Debug, Clone)]
+	enum Op {
+		Send,
+		Recv(usize),
+		Ack(usize),
+		Timeout(usize),
+	}
+
+	fn op() -> impl Strategy<Value = Op> {
+		prop_oneof![
+			Just(Op::Send),
+			any::<usize>().prop_map(Op::Recv),
+			any::<usize>().prop_map(Op::Ack),
+			any::<usize>().prop_map(Op::Timeout),
+		]
+	}
+
+	fn order() -> impl Strategy<Value = Order> {
+		prop_oneof![Just(Order::Unordered), Just(Order::Ordered), Just(Order::OrderedAllowTimeout)]
+	}
+
+	/// A chain with an open channel of the given order to the other one, over a mock client that
+	/// has consensus states at the client and timeout heights.
+	fn context(order: Order) -> MockContext<MockClientTypes> {
+		let connection_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::new(
+				ClientId::default(),
+				Some(ConnectionId::default()),
+				Default::default(),
+			),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+		let channel_end = ChannelEnd::new(
+			State::Open,
+			order,
+			Counterparty::new(PortId::default(), Some(ChannelId::default())),
+			vec![ConnectionId::default()],
+			Version::ics20(),
+		);
+		let mut ctx = MockContext::default()
+			.with_client(&ClientId::default(), Height::new(0, CLIENT_HEIGHT))
+			.with_connection(ConnectionId::default(), connection_end)
+			.with_channel(PortId::default(), ChannelId::default(), channel_end)
+			.with_send_sequence(PortId::default(), ChannelId::default(), 1.into())
+			.with_recv_sequence(PortId::default(), ChannelId::default(), 1.into())
+			.with_ack_sequence(PortId::default(), ChannelId::default(), 1.into());
+		let timeout_height = Height::new(0, TIMEOUT_HEIGHT);
+		ctx.store_consensus_state(
+			ClientId::default(),
+			timeout_height,
+			MockConsensusState::new(MockHeader::new(timeout_height)).into(),
+		)
+		.unwrap();
+		ctx
+	}
+
+	fn proofs(height: u64) -> Proofs {
+		Proofs::new(get_dummy_proof().try_into().unwrap(), None, None, None, Height::new(0, height))
+			.unwrap()
+	}
+
+	fn packet(sequence: u64) -> Packet {
+		Packet {
+			sequence: sequence.into(),
+			source_port: PortId::default(),
+			source_channel: ChannelId::default(),
+			destination_port: PortId::default(),
+			destination_channel: ChannelId::default(),
+			data: sequence.to_be_bytes().to_vec(),
+			timeout_height: Height::new(0, TIMEOUT_HEIGHT),
+			timeout_timestamp: Timestamp::none(),
+		}
+	}
+
+	/// Sequences of the channel on both chains: next to send and next to acknowledge on the
+	/// source, next to receive on the sink.
+	fn sequences(
+		source: &MockContext<MockClientTypes>,
+		sink: &MockContext<MockClientTypes>,
+	) -> (Sequence, Sequence, Sequence) {
+		let port_channel = (PortId::default(), ChannelId::default());
+		(
+			source.get_next_sequence_send(&port_channel).unwrap(),
+			source.get_next_sequence_ack(&port_channel).unwrap(),
+			sink.get_next_sequence_recv(&port_channel).unwrap(),
+		)
+	}
+
+	fn is_prefix_of_sequences(sequences: &[u64]) -> bool {
+		sequences.iter().zip(1..).all(|(sequence, expected)| *sequence == expected)
+	}
+
+	proptest! {
+		#![proptest_config(ProptestConfig::with_cases(64))]
+
+		/// Relays the packets of a channel from a source to a sink chain with a random
+		/// interleaving of sends, receives, acknowledgements and timeouts, some of them invalid,
+		/// and checks the invariants of the packet lifecycle after each message handled.
+		#[test]
+		fn packet_lifecycle_invariants(
+			order in order(),
+			ops in proptest::collection::vec(op(), 1..48),
+		) {
+			let mut source = context(order);
+			let mut sink = context(order);
+			let port_channel = (PortId::default(), ChannelId::default());
+
+			let mut sent = Vec::new();
+			let mut received = Vec::new();
+			let mut acked = BTreeSet::new();
+			let mut timed_out = BTreeSet::new();
+			// sequences acknowledged or timed out, in the order they were
+			let mut resolved = Vec::new();
+			let mut closed = false;
+			let mut previous = sequences(&source, &sink);
+
+			for op in ops {
+				match op {
+					Op::Send => {
+						let packet = packet(sent.len() as u64 + 1);
+						match send_packet::send_packet(&source, packet.clone()) {
+							Ok(output) => {
+								prop_assert!(!closed, "packet sent on a closed channel");
+								source.store_packet_result(output.result).unwrap();
+								sent.push(packet);
+							},
+							Err(_) => prop_assert!(closed, "packet couldn't be sent"),
+						}
+					},
+					Op::Recv(_) | Op::Ack(_) | Op::Timeout(_) if sent.is_empty() => continue,
+					Op::Recv(index) => {
+						let msg = MsgRecvPacket {
+							packet: sent[index % sent.len()].clone(),
+							proofs: proofs(CLIENT_HEIGHT),
+							signer: get_dummy_account_id(),
+						};
+						let sequence = u64::from(msg.packet.sequence);
+						if let Ok(output) = recv_packet::process(&sink, &msg) {
+							match output.result {
+								PacketResult::Recv(RecvPacketResult::NoOp) => {
+									prop_assert!(
+										received.contains(&sequence),
+										"packet {} not received yet is a no-op",
+										sequence
+									);
+								},
+								result => {
+									prop_assert!(
+										!received.contains(&sequence),
+										"packet {} received twice",
+										sequence
+									);
+									sink.store_packet_result(result).unwrap();
+									received.push(sequence);
+								},
+							}
+						}
+					},
+					Op::Ack(index) => {
+						let msg = MsgAcknowledgement {
+							packet: sent[index % sent.len()].clone(),
+							acknowledgement: vec![1].into(),
+							proofs: proofs(CLIENT_HEIGHT),
+							signer: get_dummy_account_id(),
+						};
+						let sequence = u64::from(msg.packet.sequence);
+						if let Ok(output) = acknowledgement::process(&source, &msg) {
+							prop_assert!(!closed, "packet {} acknowledged on a closed channel", sequence);
+							source.store_packet_result(output.result).unwrap();
+							prop_assert!(acked.insert(sequence), "packet {} acknowledged twice", sequence);
+							resolved.push(sequence);
+						}
+					},
+					Op::Timeout(index) => {
+						let msg = MsgTimeout {
+							packet: sent[index % sent.len()].clone(),
+							next_sequence_recv: sink.get_next_sequence_recv(&port_channel).unwrap(),
+							proofs: proofs(TIMEOUT_HEIGHT),
+							signer: get_dummy_account_id(),
+						};
+						let sequence = u64::from(msg.packet.sequence);
+						if let Ok(output) = timeout::process(&source, &msg) {
+							prop_assert!(!closed, "packet {} timed out on a closed channel", sequence);
+							source.store_packet_result(output.result).unwrap();
+							prop_assert!(timed_out.insert(sequence), "packet {} timed out twice", sequence);
+							resolved.push(sequence);
+							if order == Order::Ordered {
+								prop_assert!(
+									!received.contains(&sequence),
+									"packet {} timed out after being received",
+									sequence
+								);
+								closed = true;
+							}
+						}
+					},
+				}
+
+				prop_assert!(
+					acked.is_disjoint(&timed_out),
+					"packets both acknowledged and timed out: {:?}",
+					acked.intersection(&timed_out).collect::<Vec<_>>()
+				);
+				for packet in &sent {
+					let sequence = u64::from(packet.sequence);
+					let commitment = source.get_packet_commitment(&(
+						PortId::default(),
+						ChannelId::default(),
+						packet.sequence,
+					));
+					let is_resolved = acked.contains(&sequence) || timed_out.contains(&sequence);
+					prop_assert_eq!(commitment.is_ok(), !is_resolved);
+				}
+				prop_assert_eq!(
+					source.channel_end(&port_channel).unwrap().state_matches(&State::Closed),
+					closed
+				);
+
+				let current = sequences(&source, &sink);
+				prop_assert!(
+					current.0 >= previous.0 && current.1 >= previous.1 && current.2 >= previous.2,
+					"sequences went backwards from {:?} to {:?}",
+					previous,
+					current
+				);
+				prop_assert_eq!(u64::from(current.0), sent.len() as u64 + 1);
+				previous = current;
+
+				if order.is_ordered() {
+					prop_assert!(
+						is_prefix_of_sequences(&received),
+						"packets received out of order: {:?}",
+						received
+					);
+					prop_assert_eq!(u64::from(current.2), received.len() as u64 + 1);
+					let acked_in_order = resolved
+						.iter()
+						.copied()
+						.filter(|sequence| acked.contains(sequence))
+						.collect::<Vec<_>>();
+					prop_assert!(
+						is_prefix_of_sequences(&acked_in_order),
+						"packets acknowledged out of order: {:?}",
+						acked_in_order
+					);
+				}
+				if order == Order::OrderedAllowTimeout {
+					prop_assert!(
+						is_prefix_of_sequences(&resolved),
+						"packets acknowledged or timed out out of order: {:?}",
+						resolved
+					);
+					prop_assert_eq!(u64::from(current.1), resolved.len() as u64 + 1);
+				}
+			}
+		}
+	}
+}
@@ -16,7 +16,7 @@ use crate::{
 	core::{
 		ics03_connection::connection::State as ConnectionState,
 		ics04_channel::{
-			channel::{Counterparty, Order, State},
+			channel::{Counterparty, State},
 			error::Error,
 			events::AcknowledgePacket,
 			handler::verify::verify_packet_acknowledgement_proofs,
@@ -51,7 +51,8 @@ pub fn process<Ctx: ReaderContext>(
 	let source_channel_end =
 		ctx.channel_end(&(packet.source_port.clone(), packet.source_channel))?;
 
-	if !source_channel_end.state_matches(&State::Open) {
+	// packets in-flight when an upgrade started are still acknowledged while it is flushing
+	if !source_channel_end.state_matches(&State::Open) && !source_channel_end.is_flushing() {
 		return Err(Error::channel_closed(packet.source_channel))
 	}
 
@@ -99,7 +100,7 @@ pub fn process<Ctx: ReaderContext>(
 		&msg.proofs,
 	)?;
 
-	let result = if source_channel_end.order_matches(&Order::Ordered) {
+	let result = if source_channel_end.ordering().is_ordered() {
 		let next_seq_ack =
 			ctx.get_next_sequence_ack(&(packet.source_port.clone(), packet.source_channel))?;
 
@@ -78,7 +78,7 @@ where
 		_ => return Err(Error::invalid_version_length_connection()),
 	};
 
-	let channel_feature = msg.channel.ordering().to_string();
+	let channel_feature = msg.channel.ordering().connection_feature().to_string();
 	if !version.is_supported_feature(channel_feature) {
 		return Err(Error::channel_feature_not_suported_by_connection())
 	}
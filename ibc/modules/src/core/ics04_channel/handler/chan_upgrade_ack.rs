@@ -0,0 +1,223 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeAck`.
+
+use crate::{
+	core::{
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty, State},
+			error::Error,
+			events::UpgradeStep,
+			handler::{
+				start_flushing, upgrade_connection, upgrade_event,
+				verify::{verify_channel_proofs, verify_channel_upgrade},
+				ChannelUpgradeResult,
+			},
+			msgs::chan_upgrade_ack::MsgChannelUpgradeAck,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeAck,
+) -> HandlerResult<ChannelUpgradeResult, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	if !channel_end.state_matches(&State::Open) {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+	let mut upgrade = ctx
+		.channel_upgrade(&port_channel_id)
+		.map_err(|_| Error::missing_upgrade(msg.port_id.clone(), msg.channel_id))?;
+
+	// The counterparty must have agreed to the ordering and run the upgrade over the counterparty
+	// of the connection proposed by this end. The version it picked is validated by the module.
+	let counterparty_upgrade = &msg.counterparty_upgrade;
+	let (_, proposed_counterparty_connection_id) =
+		upgrade_connection(ctx, &upgrade.fields.connection_hops)?;
+	if counterparty_upgrade.fields.ordering != upgrade.fields.ordering ||
+		counterparty_upgrade.fields.connection_hops != vec![proposed_counterparty_connection_id]
+	{
+		return Err(Error::incompatible_upgrade_fields())
+	}
+
+	// The counterparty must still have time to complete the upgrade.
+	if counterparty_upgrade
+		.timeout
+		.has_passed(ctx.host_height(), &ctx.host_timestamp())
+	{
+		return Err(Error::upgrade_timed_out())
+	}
+
+	// Proof verification in two steps:
+	// 1. The counterparty channel end should be flushing with the same upgrade sequence.
+	let (conn, counterparty_connection_id) = upgrade_connection(ctx, &channel_end.connection_hops)?;
+	let mut expected_channel_end = ChannelEnd::new(
+		State::Flushing,
+		channel_end.ordering,
+		Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+		vec![counterparty_connection_id],
+		channel_end.version.clone(),
+	);
+	expected_channel_end.set_upgrade_sequence(channel_end.upgrade_sequence());
+	verify_channel_proofs::<Ctx>(
+		ctx,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&expected_channel_end,
+		msg.proofs.object_proof(),
+	)?;
+
+	// 2. The counterparty should be storing the upgrade given in the message.
+	verify_channel_upgrade(
+		ctx,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		counterparty_upgrade,
+		msg.proofs.other_proof().as_ref().ok_or_else(Error::missing_channel_proof)?,
+	)?;
+
+	output.log("success: channel upgrade ack");
+
+	upgrade.fields.version = counterparty_upgrade.fields.version.clone();
+	start_flushing(ctx, &port_channel_id, &mut channel_end, &mut upgrade)?;
+	if !ctx.has_inflight_packets(&port_channel_id)? {
+		channel_end.set_state(State::FlushComplete);
+	}
+
+	output.emit(upgrade_event(ctx, UpgradeStep::Ack, &msg.port_id, msg.channel_id, &channel_end));
+
+	let result = ChannelUpgradeResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_end,
+		upgrade: Some(upgrade),
+		counterparty_upgrade: Some(counterparty_upgrade.clone()),
+		error_receipt: None,
+		next_sequence_recv: None,
+		next_sequence_ack: None,
+	};
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics02_client::height::Height,
+			ics04_channel::{
+				channel::{Order, State},
+				error::ErrorDetail,
+				events::UpgradeStep,
+				handler::{
+					channel_upgrade_dispatch,
+					test_util::{
+						context_with_channel, emitted_upgrade_event, upgrade, PROOF_HEIGHT,
+					},
+				},
+				msgs::{
+					chan_upgrade_ack::{
+						test_util::get_dummy_raw_msg_chan_upgrade_ack, MsgChannelUpgradeAck,
+					},
+					ChannelUpgradeMsg,
+				},
+				Version,
+			},
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+	};
+	use test_log::test;
+
+	fn msg() -> ChannelUpgradeMsg {
+		ChannelUpgradeMsg::ChannelUpgradeAck(
+			MsgChannelUpgradeAck::try_from(get_dummy_raw_msg_chan_upgrade_ack(PROOF_HEIGHT))
+				.unwrap(),
+		)
+	}
+
+	/// This end initialised an upgrade to a new version, the counterparty settled on `ics20-1`.
+	fn context_with_upgrade(ordering: Order) -> MockContext<MockClientTypes> {
+		context_with_channel(State::Open, 1).with_channel_upgrade(
+			PortId::default(),
+			ChannelId::default(),
+			upgrade(ordering, Version::new("ics20-2".to_string())),
+		)
+	}
+
+	#[test]
+	fn chan_upgrade_ack_completes_flushing_without_inflight_packets() {
+		let context = context_with_upgrade(Order::Unordered);
+
+		let (output, result) = channel_upgrade_dispatch(&context, &msg()).unwrap();
+
+		assert_eq!(result.channel_end.state, State::FlushComplete);
+		assert_eq!(result.upgrade.unwrap().fields.version, Version::ics20());
+		assert!(result.counterparty_upgrade.is_some());
+
+		let event = emitted_upgrade_event(output);
+		assert_eq!(event.step, UpgradeStep::Ack);
+		assert_eq!(event.upgrade_sequence, 1);
+	}
+
+	#[test]
+	fn chan_upgrade_ack_keeps_flushing_inflight_packets() {
+		let context = context_with_upgrade(Order::Unordered).with_packet_commitment(
+			PortId::default(),
+			ChannelId::default(),
+			1.into(),
+			vec![0u8].into(),
+		);
+
+		let (_, result) = channel_upgrade_dispatch(&context, &msg()).unwrap();
+
+		assert_eq!(result.channel_end.state, State::Flushing);
+	}
+
+	#[test]
+	fn chan_upgrade_ack_rejects_missing_upgrade() {
+		let context = context_with_channel(State::Open, 1);
+
+		let err = channel_upgrade_dispatch(&context, &msg()).unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::MissingUpgrade(_)));
+	}
+
+	#[test]
+	fn chan_upgrade_ack_rejects_incompatible_ordering() {
+		let context = context_with_upgrade(Order::Ordered);
+
+		let err = channel_upgrade_dispatch(&context, &msg()).unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::IncompatibleUpgradeFields(_)));
+	}
+
+	#[test]
+	fn chan_upgrade_ack_rejects_timed_out_upgrade() {
+		// The counterparty upgrade times out at height 1000.
+		let context = context_with_upgrade(Order::Unordered).with_height(Height::new(0, 1000));
+
+		let err = channel_upgrade_dispatch(&context, &msg()).unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::UpgradeTimedOut(_)));
+	}
+}
@@ -0,0 +1,197 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeCancel`.
+
+use crate::{
+	core::{
+		ics04_channel::{
+			channel::State,
+			error::Error,
+			events::UpgradeStep,
+			handler::{
+				upgrade_connection, upgrade_event, verify::verify_channel_upgrade_error,
+				ChannelUpgradeResult,
+			},
+			msgs::chan_upgrade_cancel::MsgChannelUpgradeCancel,
+			upgrade::ErrorReceipt,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeCancel,
+) -> HandlerResult<ChannelUpgradeResult, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	// Once flushed, an upgrade can only be aborted by timing out.
+	if ctx.channel_upgrade(&port_channel_id).is_err() {
+		return Err(Error::missing_upgrade(msg.port_id.clone(), msg.channel_id))
+	}
+	if channel_end.state_matches(&State::FlushComplete) {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+
+	// The counterparty must have aborted this upgrade, or a later one.
+	if msg.error_receipt.sequence < channel_end.upgrade_sequence() {
+		return Err(Error::invalid_upgrade_sequence(
+			channel_end.upgrade_sequence(),
+			msg.error_receipt.sequence,
+		))
+	}
+
+	let (conn, _) = upgrade_connection(ctx, &channel_end.connection_hops)?;
+	verify_channel_upgrade_error(
+		ctx,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&msg.error_receipt,
+		msg.proofs.object_proof(),
+	)?;
+
+	output.log("success: channel upgrade cancel");
+
+	// Restore the channel end, skipping the upgrade sequences which were aborted.
+	channel_end.set_upgrade_sequence(msg.error_receipt.sequence);
+	channel_end.set_state(State::Open);
+	let error_receipt = ErrorReceipt::new(msg.error_receipt.sequence, "upgrade cancelled");
+
+	output.emit(upgrade_event(
+		ctx,
+		UpgradeStep::Cancel,
+		&msg.port_id,
+		msg.channel_id,
+		&channel_end,
+	));
+
+	let result = ChannelUpgradeResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_end,
+		upgrade: None,
+		counterparty_upgrade: None,
+		error_receipt: Some(error_receipt),
+		next_sequence_recv: None,
+		next_sequence_ack: None,
+	};
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics04_channel::{
+				channel::{Order, State},
+				error::ErrorDetail,
+				events::UpgradeStep,
+				handler::{
+					channel_upgrade_dispatch,
+					test_util::{
+						context_with_channel, emitted_upgrade_event, upgrade, PROOF_HEIGHT,
+					},
+				},
+				msgs::{
+					chan_upgrade_cancel::{
+						test_util::get_dummy_raw_msg_chan_upgrade_cancel, MsgChannelUpgradeCancel,
+					},
+					ChannelUpgradeMsg,
+				},
+				Version,
+			},
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+	};
+	use test_log::test;
+
+	/// The counterparty aborted the upgrade at the given sequence.
+	fn msg(sequence: u64) -> ChannelUpgradeMsg {
+		let mut msg =
+			MsgChannelUpgradeCancel::try_from(get_dummy_raw_msg_chan_upgrade_cancel(PROOF_HEIGHT))
+				.unwrap();
+		msg.error_receipt.sequence = sequence;
+		ChannelUpgradeMsg::ChannelUpgradeCancel(msg)
+	}
+
+	fn context_with_upgrade(state: State, upgrade_sequence: u64) -> MockContext<MockClientTypes> {
+		context_with_channel(state, upgrade_sequence).with_channel_upgrade(
+			PortId::default(),
+			ChannelId::default(),
+			upgrade(Order::Ordered, Version::ics20()),
+		)
+	}
+
+	#[test]
+	fn chan_upgrade_cancel_restores_channel() {
+		let context = context_with_upgrade(State::Flushing, 1);
+
+		let (output, result) = channel_upgrade_dispatch(&context, &msg(1)).unwrap();
+
+		assert_eq!(result.channel_end.state, State::Open);
+		assert_eq!(result.channel_end.upgrade_sequence(), 1);
+		assert!(result.upgrade.is_none());
+		assert_eq!(result.error_receipt.unwrap().sequence, 1);
+
+		let event = emitted_upgrade_event(output);
+		assert_eq!(event.step, UpgradeStep::Cancel);
+		assert_eq!(event.upgrade_sequence, 1);
+	}
+
+	#[test]
+	fn chan_upgrade_cancel_skips_aborted_sequences() {
+		let context = context_with_upgrade(State::Open, 1);
+
+		let (_, result) = channel_upgrade_dispatch(&context, &msg(3)).unwrap();
+
+		assert_eq!(result.channel_end.state, State::Open);
+		assert_eq!(result.channel_end.upgrade_sequence(), 3);
+	}
+
+	#[test]
+	fn chan_upgrade_cancel_rejects_outdated_receipt() {
+		let context = context_with_upgrade(State::Flushing, 2);
+
+		let err = channel_upgrade_dispatch(&context, &msg(1)).unwrap_err();
+		assert!(matches!(
+			err.detail(),
+			ErrorDetail::InvalidUpgradeSequence(e) if e.expected == 2 && e.actual == 1
+		));
+	}
+
+	#[test]
+	fn chan_upgrade_cancel_rejects_flushed_channel() {
+		let context = context_with_upgrade(State::FlushComplete, 1);
+
+		let err = channel_upgrade_dispatch(&context, &msg(1)).unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::InvalidChannelState(_)));
+	}
+
+	#[test]
+	fn chan_upgrade_cancel_rejects_missing_upgrade() {
+		let context = context_with_channel(State::Flushing, 1);
+
+		let err = channel_upgrade_dispatch(&context, &msg(1)).unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::MissingUpgrade(_)));
+	}
+}
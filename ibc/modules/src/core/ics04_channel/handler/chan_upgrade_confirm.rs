@@ -0,0 +1,222 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeConfirm`.
+
+use crate::{
+	core::{
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty, State},
+			error::Error,
+			events::UpgradeStep,
+			handler::{
+				upgrade_connection, upgrade_event,
+				verify::{verify_channel_proofs, verify_channel_upgrade},
+				ChannelUpgradeResult,
+			},
+			msgs::chan_upgrade_confirm::MsgChannelUpgradeConfirm,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeConfirm,
+) -> HandlerResult<ChannelUpgradeResult, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	if !channel_end.state_matches(&State::Flushing) {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+	if !msg.counterparty_channel_state.is_flushing() {
+		return Err(Error::invalid_channel_state(msg.channel_id, msg.counterparty_channel_state))
+	}
+	let upgrade = ctx
+		.channel_upgrade(&port_channel_id)
+		.map_err(|_| Error::missing_upgrade(msg.port_id.clone(), msg.channel_id))?;
+
+	// Both ends must have settled on the same upgrade, over a pair of counterparty connections.
+	let counterparty_upgrade = &msg.counterparty_upgrade;
+	let (_, proposed_counterparty_connection_id) =
+		upgrade_connection(ctx, &upgrade.fields.connection_hops)?;
+	if counterparty_upgrade.fields.ordering != upgrade.fields.ordering ||
+		counterparty_upgrade.fields.version != upgrade.fields.version ||
+		counterparty_upgrade.fields.connection_hops != vec![proposed_counterparty_connection_id]
+	{
+		return Err(Error::incompatible_upgrade_fields())
+	}
+
+	// The counterparty must still have time to complete the upgrade.
+	if counterparty_upgrade
+		.timeout
+		.has_passed(ctx.host_height(), &ctx.host_timestamp())
+	{
+		return Err(Error::upgrade_timed_out())
+	}
+
+	// Proof verification in two steps:
+	// 1. The counterparty channel end should be flushing with the same upgrade sequence.
+	let (conn, counterparty_connection_id) = upgrade_connection(ctx, &channel_end.connection_hops)?;
+	let mut expected_channel_end = ChannelEnd::new(
+		msg.counterparty_channel_state,
+		channel_end.ordering,
+		Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+		vec![counterparty_connection_id],
+		channel_end.version.clone(),
+	);
+	expected_channel_end.set_upgrade_sequence(channel_end.upgrade_sequence());
+	verify_channel_proofs::<Ctx>(
+		ctx,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&expected_channel_end,
+		msg.proofs.object_proof(),
+	)?;
+
+	// 2. The counterparty should be storing the upgrade given in the message.
+	verify_channel_upgrade(
+		ctx,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		counterparty_upgrade,
+		msg.proofs.other_proof().as_ref().ok_or_else(Error::missing_channel_proof)?,
+	)?;
+
+	output.log("success: channel upgrade confirm");
+
+	if !ctx.has_inflight_packets(&port_channel_id)? {
+		channel_end.set_state(State::FlushComplete);
+	}
+
+	output.emit(upgrade_event(
+		ctx,
+		UpgradeStep::Confirm,
+		&msg.port_id,
+		msg.channel_id,
+		&channel_end,
+	));
+
+	let result = ChannelUpgradeResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_end,
+		upgrade: Some(upgrade),
+		counterparty_upgrade: Some(counterparty_upgrade.clone()),
+		error_receipt: None,
+		next_sequence_recv: None,
+		next_sequence_ack: None,
+	};
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics04_channel::{
+				channel::{Order, State},
+				error::ErrorDetail,
+				events::UpgradeStep,
+				handler::{
+					channel_upgrade_dispatch,
+					test_util::{
+						context_with_channel, emitted_upgrade_event, upgrade, PROOF_HEIGHT,
+					},
+				},
+				msgs::{
+					chan_upgrade_confirm::{
+						test_util::get_dummy_raw_msg_chan_upgrade_confirm, MsgChannelUpgradeConfirm,
+					},
+					ChannelUpgradeMsg,
+				},
+				Version,
+			},
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+	};
+	use test_log::test;
+
+	fn msg() -> ChannelUpgradeMsg {
+		ChannelUpgradeMsg::ChannelUpgradeConfirm(
+			MsgChannelUpgradeConfirm::try_from(get_dummy_raw_msg_chan_upgrade_confirm(
+				PROOF_HEIGHT,
+			))
+			.unwrap(),
+		)
+	}
+
+	fn context_with_upgrade(state: State, version: Version) -> MockContext<MockClientTypes> {
+		context_with_channel(state, 1).with_channel_upgrade(
+			PortId::default(),
+			ChannelId::default(),
+			upgrade(Order::Unordered, version),
+		)
+	}
+
+	#[test]
+	fn chan_upgrade_confirm_completes_flushing_without_inflight_packets() {
+		let context = context_with_upgrade(State::Flushing, Version::ics20());
+
+		let (output, result) = channel_upgrade_dispatch(&context, &msg()).unwrap();
+
+		assert_eq!(result.channel_end.state, State::FlushComplete);
+		assert!(result.upgrade.is_some());
+		assert!(result.counterparty_upgrade.is_some());
+
+		let event = emitted_upgrade_event(output);
+		assert_eq!(event.step, UpgradeStep::Confirm);
+		assert_eq!(event.upgrade_sequence, 1);
+	}
+
+	#[test]
+	fn chan_upgrade_confirm_keeps_flushing_inflight_packets() {
+		let context = context_with_upgrade(State::Flushing, Version::ics20())
+			.with_packet_commitment(
+				PortId::default(),
+				ChannelId::default(),
+				1.into(),
+				vec![0u8].into(),
+			);
+
+		let (_, result) = channel_upgrade_dispatch(&context, &msg()).unwrap();
+
+		assert_eq!(result.channel_end.state, State::Flushing);
+	}
+
+	#[test]
+	fn chan_upgrade_confirm_rejects_channel_not_flushing() {
+		let context = context_with_upgrade(State::Open, Version::ics20());
+
+		let err = channel_upgrade_dispatch(&context, &msg()).unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::InvalidChannelState(_)));
+	}
+
+	#[test]
+	fn chan_upgrade_confirm_rejects_different_version() {
+		let context = context_with_upgrade(State::Flushing, Version::new("ics20-2".to_string()));
+
+		let err = channel_upgrade_dispatch(&context, &msg()).unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::IncompatibleUpgradeFields(_)));
+	}
+}
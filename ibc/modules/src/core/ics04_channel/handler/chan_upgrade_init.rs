@@ -0,0 +1,195 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeInit`.
+
+use crate::{
+	core::{
+		ics04_channel::{
+			channel::State,
+			error::Error,
+			events::UpgradeStep,
+			handler::{upgrade_connection, upgrade_event, ChannelUpgradeResult},
+			msgs::chan_upgrade_init::MsgChannelUpgradeInit,
+			upgrade::{Upgrade, UpgradeTimeout},
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+	timestamp::Timestamp,
+	Height,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeInit,
+) -> HandlerResult<ChannelUpgradeResult, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	// Only open channels which are not already being upgraded can start an upgrade.
+	if !channel_end.state_matches(&State::Open) {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+	if ctx.channel_upgrade(&port_channel_id).is_ok() {
+		return Err(Error::upgrade_in_progress(msg.port_id.clone(), msg.channel_id))
+	}
+
+	// The upgrade must change something and run over an open connection supporting the proposed
+	// ordering.
+	let fields = &msg.fields;
+	if fields.ordering == channel_end.ordering &&
+		fields.connection_hops == channel_end.connection_hops &&
+		fields.version == channel_end.version
+	{
+		return Err(Error::incompatible_upgrade_fields())
+	}
+	let (conn, _) = upgrade_connection(ctx, &fields.connection_hops)?;
+	let version = match conn.versions() {
+		[version] => version,
+		_ => return Err(Error::invalid_version_length_connection()),
+	};
+	if !version.is_supported_feature(fields.ordering.connection_feature().to_string()) {
+		return Err(Error::channel_feature_not_suported_by_connection())
+	}
+
+	// The timeout and the next send sequence are only known once the channel starts flushing.
+	let upgrade = Upgrade::new(
+		fields.clone(),
+		UpgradeTimeout::new(Height::zero(), Timestamp::none()),
+		0.into(),
+	);
+	channel_end.set_upgrade_sequence(channel_end.upgrade_sequence() + 1);
+
+	output.log("success: channel upgrade init");
+
+	output.emit(upgrade_event(ctx, UpgradeStep::Init, &msg.port_id, msg.channel_id, &channel_end));
+
+	let result = ChannelUpgradeResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_end,
+		upgrade: Some(upgrade),
+		counterparty_upgrade: None,
+		error_receipt: None,
+		next_sequence_recv: None,
+		next_sequence_ack: None,
+	};
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics02_client::context::ClientReader,
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				msgs::test_util::get_dummy_raw_counterparty,
+				version::get_compatible_versions,
+			},
+			ics04_channel::{
+				channel::{ChannelEnd, Counterparty, Order, State},
+				events::UpgradeStep,
+				handler::channel_upgrade_dispatch,
+				msgs::{
+					chan_upgrade_init::{
+						test_util::get_dummy_raw_msg_chan_upgrade_init, MsgChannelUpgradeInit,
+					},
+					ChannelUpgradeMsg,
+				},
+				Version,
+			},
+			ics24_host::identifier::{ClientId, ConnectionId},
+		},
+		events::IbcEvent,
+		mock::{
+			client_state::MockClientState,
+			context::{MockClientTypes, MockContext},
+		},
+		prelude::*,
+		timestamp::ZERO_DURATION,
+	};
+
+	fn context_with_channel(
+		msg: &MsgChannelUpgradeInit,
+		version: Version,
+	) -> MockContext<MockClientTypes> {
+		let client_id = ClientId::new(&MockClientState::client_type(), 24).unwrap();
+		let conn_id = ConnectionId::new(0);
+
+		let conn_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			client_id.clone(),
+			ConnectionCounterparty::try_from(get_dummy_raw_counterparty()).unwrap(),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+
+		let chan_end = ChannelEnd::new(
+			State::Open,
+			Order::Unordered,
+			Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+			vec![conn_id.clone()],
+			version,
+		);
+
+		let default_context = MockContext::<MockClientTypes>::default();
+		let client_consensus_state_height = default_context.host_height();
+
+		default_context
+			.with_client(&client_id, client_consensus_state_height)
+			.with_connection(conn_id, conn_end)
+			.with_channel(msg.port_id.clone(), msg.channel_id, chan_end)
+	}
+
+	#[test]
+	fn chan_upgrade_init_starts_upgrade() {
+		let msg = MsgChannelUpgradeInit::try_from(get_dummy_raw_msg_chan_upgrade_init()).unwrap();
+		let context = context_with_channel(&msg, Version::default());
+
+		let (handler_output_builder, result) =
+			channel_upgrade_dispatch(&context, &ChannelUpgradeMsg::ChannelUpgradeInit(msg.clone()))
+				.unwrap();
+		let handler_output = handler_output_builder.with_result(());
+
+		assert_eq!(result.channel_end.upgrade_sequence(), 1);
+		assert_eq!(result.channel_end.state, State::Open);
+		assert_eq!(result.upgrade.unwrap().fields, msg.fields);
+
+		assert_eq!(handler_output.events.len(), 1);
+		match &handler_output.events[0] {
+			IbcEvent::UpgradeChannel(event) => {
+				assert_eq!(event.step, UpgradeStep::Init);
+				assert_eq!(event.upgrade_sequence, 1);
+				assert_eq!(event.height, context.host_height());
+			},
+			event => panic!("unexpected event {:?}", event),
+		}
+	}
+
+	#[test]
+	fn chan_upgrade_init_rejects_unchanged_fields() {
+		let msg = MsgChannelUpgradeInit::try_from(get_dummy_raw_msg_chan_upgrade_init()).unwrap();
+		let context = context_with_channel(&msg, msg.fields.version.clone());
+
+		assert!(channel_upgrade_dispatch(&context, &ChannelUpgradeMsg::ChannelUpgradeInit(msg))
+			.is_err());
+	}
+}
@@ -0,0 +1,239 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeOpen`.
+
+use crate::{
+	core::{
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty, State},
+			error::Error,
+			events::UpgradeStep,
+			handler::{
+				upgrade_connection, upgrade_event, verify::verify_channel_proofs,
+				ChannelUpgradeResult,
+			},
+			msgs::chan_upgrade_open::MsgChannelUpgradeOpen,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeOpen,
+) -> HandlerResult<ChannelUpgradeResult, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	// All packets sent before the upgrade must have been flushed. The last in-flight packet does
+	// not move the channel end out of `Flushing`, so the check is repeated here.
+	let flushed = match channel_end.state {
+		State::FlushComplete => true,
+		State::Flushing => !ctx.has_inflight_packets(&port_channel_id)?,
+		_ => false,
+	};
+	if !flushed {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+	if msg.counterparty_upgrade_sequence != channel_end.upgrade_sequence() {
+		return Err(Error::invalid_upgrade_sequence(
+			channel_end.upgrade_sequence(),
+			msg.counterparty_upgrade_sequence,
+		))
+	}
+
+	let upgrade = ctx
+		.channel_upgrade(&port_channel_id)
+		.map_err(|_| Error::missing_upgrade(msg.port_id.clone(), msg.channel_id))?;
+	let counterparty_upgrade = ctx
+		.channel_counterparty_upgrade(&port_channel_id)
+		.map_err(|_| Error::missing_upgrade(msg.port_id.clone(), msg.channel_id))?;
+
+	// The counterparty channel end has either flushed as well or already opened the upgraded
+	// channel.
+	let (conn, counterparty_connection_id) = upgrade_connection(ctx, &channel_end.connection_hops)?;
+	let mut expected_channel_end = match msg.counterparty_channel_state {
+		State::FlushComplete => ChannelEnd::new(
+			State::FlushComplete,
+			channel_end.ordering,
+			Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+			vec![counterparty_connection_id],
+			channel_end.version.clone(),
+		),
+		State::Open => {
+			let (_, upgraded_counterparty_connection_id) =
+				upgrade_connection(ctx, &upgrade.fields.connection_hops)?;
+			ChannelEnd::new(
+				State::Open,
+				upgrade.fields.ordering,
+				Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+				vec![upgraded_counterparty_connection_id],
+				upgrade.fields.version.clone(),
+			)
+		},
+		state => return Err(Error::invalid_channel_state(msg.channel_id, state)),
+	};
+	expected_channel_end.set_upgrade_sequence(msg.counterparty_upgrade_sequence);
+	verify_channel_proofs::<Ctx>(
+		ctx,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&expected_channel_end,
+		msg.proofs.object_proof(),
+	)?;
+
+	output.log("success: channel upgrade open");
+
+	// Packets of a channel which becomes ordered are delivered and acknowledged in sequence from
+	// the first packet sent after the upgrade.
+	let (next_sequence_recv, next_sequence_ack) =
+		if upgrade.fields.ordering.is_ordered() && !channel_end.ordering.is_ordered() {
+			(Some(counterparty_upgrade.next_sequence_send), Some(upgrade.next_sequence_send))
+		} else {
+			(None, None)
+		};
+
+	channel_end.ordering = upgrade.fields.ordering;
+	channel_end.connection_hops = upgrade.fields.connection_hops;
+	channel_end.version = upgrade.fields.version;
+	channel_end.set_state(State::Open);
+
+	output.emit(upgrade_event(ctx, UpgradeStep::Open, &msg.port_id, msg.channel_id, &channel_end));
+
+	let result = ChannelUpgradeResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_end,
+		upgrade: None,
+		counterparty_upgrade: None,
+		error_receipt: None,
+		next_sequence_recv,
+		next_sequence_ack,
+	};
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics04_channel::{
+				channel::{Order, State},
+				error::ErrorDetail,
+				events::UpgradeStep,
+				handler::{
+					channel_upgrade_dispatch,
+					test_util::{
+						context_with_channel, emitted_upgrade_event, upgrade, PROOF_HEIGHT,
+					},
+				},
+				msgs::{
+					chan_upgrade_open::{
+						test_util::get_dummy_raw_msg_chan_upgrade_open, MsgChannelUpgradeOpen,
+					},
+					ChannelUpgradeMsg,
+				},
+				packet::Sequence,
+				Version,
+			},
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+	};
+	use test_log::test;
+
+	fn msg(counterparty_upgrade_sequence: u64) -> ChannelUpgradeMsg {
+		let mut msg =
+			MsgChannelUpgradeOpen::try_from(get_dummy_raw_msg_chan_upgrade_open(PROOF_HEIGHT))
+				.unwrap();
+		msg.counterparty_upgrade_sequence = counterparty_upgrade_sequence;
+		ChannelUpgradeMsg::ChannelUpgradeOpen(msg)
+	}
+
+	/// Both ends agreed to upgrade the channel to an ordered one, this end sent two packets before
+	/// the upgrade and the counterparty six.
+	fn context_with_upgrades(state: State) -> MockContext<MockClientTypes> {
+		let mut upgrade = upgrade(Order::Ordered, Version::ics20());
+		upgrade.next_sequence_send = 3.into();
+		let mut counterparty_upgrade = upgrade.clone();
+		counterparty_upgrade.next_sequence_send = 7.into();
+		context_with_channel(state, 1)
+			.with_channel_upgrade(PortId::default(), ChannelId::default(), upgrade)
+			.with_counterparty_channel_upgrade(
+				PortId::default(),
+				ChannelId::default(),
+				counterparty_upgrade,
+			)
+	}
+
+	#[test]
+	fn chan_upgrade_open_opens_upgraded_channel() {
+		let context = context_with_upgrades(State::FlushComplete);
+
+		let (output, result) = channel_upgrade_dispatch(&context, &msg(1)).unwrap();
+
+		assert_eq!(result.channel_end.state, State::Open);
+		assert_eq!(result.channel_end.ordering, Order::Ordered);
+		assert!(result.upgrade.is_none());
+		assert!(result.counterparty_upgrade.is_none());
+		// The packets of the now ordered channel are delivered from the first one sent after the
+		// upgrade.
+		assert_eq!(result.next_sequence_recv, Some(Sequence::from(7)));
+		assert_eq!(result.next_sequence_ack, Some(Sequence::from(3)));
+
+		let event = emitted_upgrade_event(output);
+		assert_eq!(event.step, UpgradeStep::Open);
+		assert_eq!(event.upgrade_sequence, 1);
+	}
+
+	#[test]
+	fn chan_upgrade_open_accepts_flushed_channel_still_flushing() {
+		let context = context_with_upgrades(State::Flushing);
+
+		let (_, result) = channel_upgrade_dispatch(&context, &msg(1)).unwrap();
+
+		assert_eq!(result.channel_end.state, State::Open);
+	}
+
+	#[test]
+	fn chan_upgrade_open_rejects_inflight_packets() {
+		let context = context_with_upgrades(State::Flushing).with_packet_commitment(
+			PortId::default(),
+			ChannelId::default(),
+			1.into(),
+			vec![0u8].into(),
+		);
+
+		let err = channel_upgrade_dispatch(&context, &msg(1)).unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::InvalidChannelState(_)));
+	}
+
+	#[test]
+	fn chan_upgrade_open_rejects_sequence_mismatch() {
+		let context = context_with_upgrades(State::FlushComplete);
+
+		let err = channel_upgrade_dispatch(&context, &msg(2)).unwrap_err();
+		assert!(matches!(
+			err.detail(),
+			ErrorDetail::InvalidUpgradeSequence(e) if e.expected == 1 && e.actual == 2
+		));
+	}
+}
@@ -0,0 +1,204 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeTimeout`.
+
+use crate::{
+	core::{
+		ics02_client::client_consensus::ConsensusState,
+		ics04_channel::{
+			channel::{Counterparty, State},
+			error::Error,
+			events::UpgradeStep,
+			handler::{
+				upgrade_connection, upgrade_event, verify::verify_channel_proofs,
+				ChannelUpgradeResult,
+			},
+			msgs::chan_upgrade_timeout::MsgChannelUpgradeTimeout,
+			upgrade::ErrorReceipt,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeTimeout,
+) -> HandlerResult<ChannelUpgradeResult, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	if !channel_end.is_flushing() {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+	let upgrade = ctx
+		.channel_upgrade(&port_channel_id)
+		.map_err(|_| Error::missing_upgrade(msg.port_id.clone(), msg.channel_id))?;
+
+	// The upgrade timeout must have passed on the counterparty at the proof height.
+	let (conn, _) = upgrade_connection(ctx, &channel_end.connection_hops)?;
+	let proof_height = msg.proofs.height();
+	let consensus_state = ctx
+		.consensus_state(conn.client_id(), proof_height)
+		.map_err(|_| Error::error_invalid_consensus_state())?;
+	if !upgrade.timeout.has_passed(proof_height, &consensus_state.timestamp()) {
+		return Err(Error::upgrade_timeout_not_reached())
+	}
+
+	// A counterparty which has flushed or already opened the upgraded channel cannot be timed
+	// out anymore.
+	let counterparty_channel = &msg.counterparty_channel;
+	if counterparty_channel.counterparty() !=
+		&Counterparty::new(msg.port_id.clone(), Some(msg.channel_id))
+	{
+		return Err(Error::invalid_packet_counterparty(msg.port_id.clone(), msg.channel_id))
+	}
+	let upgraded = match counterparty_channel.state {
+		State::FlushComplete => true,
+		State::Open => counterparty_channel.upgrade_sequence() == channel_end.upgrade_sequence(),
+		_ => false,
+	};
+	if upgraded {
+		return Err(Error::invalid_channel_state(msg.channel_id, counterparty_channel.state))
+	}
+
+	verify_channel_proofs::<Ctx>(
+		ctx,
+		proof_height,
+		&channel_end,
+		&conn,
+		counterparty_channel,
+		msg.proofs.object_proof(),
+	)?;
+
+	output.log("success: channel upgrade timeout");
+
+	// Abort the upgrade and let the counterparty know through the error receipt.
+	let error_receipt = ErrorReceipt::new(channel_end.upgrade_sequence(), "upgrade timed out");
+	channel_end.set_state(State::Open);
+
+	output.emit(upgrade_event(
+		ctx,
+		UpgradeStep::Timeout,
+		&msg.port_id,
+		msg.channel_id,
+		&channel_end,
+	));
+
+	let result = ChannelUpgradeResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_end,
+		upgrade: None,
+		counterparty_upgrade: None,
+		error_receipt: Some(error_receipt),
+		next_sequence_recv: None,
+		next_sequence_ack: None,
+	};
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics02_client::height::Height,
+			ics04_channel::{
+				channel::{Order, State},
+				error::ErrorDetail,
+				events::UpgradeStep,
+				handler::{
+					channel_upgrade_dispatch,
+					test_util::{
+						context_with_channel, emitted_upgrade_event, upgrade, PROOF_HEIGHT,
+					},
+				},
+				msgs::{
+					chan_upgrade_timeout::{
+						test_util::get_dummy_raw_msg_chan_upgrade_timeout, MsgChannelUpgradeTimeout,
+					},
+					ChannelUpgradeMsg,
+				},
+				upgrade::UpgradeTimeout,
+				Version,
+			},
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+		timestamp::Timestamp,
+	};
+	use test_log::test;
+
+	/// The counterparty channel end is still in the initial state, it has not flushed.
+	fn msg() -> MsgChannelUpgradeTimeout {
+		MsgChannelUpgradeTimeout::try_from(get_dummy_raw_msg_chan_upgrade_timeout(PROOF_HEIGHT))
+			.unwrap()
+	}
+
+	/// This end is flushing an upgrade which times out at the given height on the counterparty.
+	fn context_with_upgrade(timeout_height: u64) -> MockContext<MockClientTypes> {
+		let mut upgrade = upgrade(Order::Ordered, Version::ics20());
+		upgrade.timeout = UpgradeTimeout::new(Height::new(0, timeout_height), Timestamp::none());
+		context_with_channel(State::Flushing, 1).with_channel_upgrade(
+			PortId::default(),
+			ChannelId::default(),
+			upgrade,
+		)
+	}
+
+	#[test]
+	fn chan_upgrade_timeout_aborts_upgrade() {
+		let context = context_with_upgrade(PROOF_HEIGHT);
+
+		let (output, result) =
+			channel_upgrade_dispatch(&context, &ChannelUpgradeMsg::ChannelUpgradeTimeout(msg()))
+				.unwrap();
+
+		assert_eq!(result.channel_end.state, State::Open);
+		assert_eq!(result.channel_end.ordering, Order::Unordered);
+		assert!(result.upgrade.is_none());
+		assert_eq!(result.error_receipt.unwrap().sequence, 1);
+
+		let event = emitted_upgrade_event(output);
+		assert_eq!(event.step, UpgradeStep::Timeout);
+		assert_eq!(event.upgrade_sequence, 1);
+	}
+
+	#[test]
+	fn chan_upgrade_timeout_rejects_timeout_not_reached() {
+		let context = context_with_upgrade(PROOF_HEIGHT + 1);
+
+		let err =
+			channel_upgrade_dispatch(&context, &ChannelUpgradeMsg::ChannelUpgradeTimeout(msg()))
+				.unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::UpgradeTimeoutNotReached(_)));
+	}
+
+	#[test]
+	fn chan_upgrade_timeout_rejects_flushed_counterparty() {
+		let context = context_with_upgrade(PROOF_HEIGHT);
+		let mut msg = msg();
+		msg.counterparty_channel.set_state(State::FlushComplete);
+
+		let err =
+			channel_upgrade_dispatch(&context, &ChannelUpgradeMsg::ChannelUpgradeTimeout(msg))
+				.unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::InvalidChannelState(_)));
+	}
+}
@@ -0,0 +1,322 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeTry`.
+
+use crate::{
+	core::{
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty, State},
+			error::Error,
+			events::UpgradeStep,
+			handler::{
+				start_flushing, upgrade_connection, upgrade_event,
+				verify::{verify_channel_proofs, verify_channel_upgrade},
+				ChannelUpgradeResult,
+			},
+			msgs::chan_upgrade_try::MsgChannelUpgradeTry,
+			upgrade::{ErrorReceipt, Upgrade, UpgradeFields, UpgradeTimeout},
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	handler::{HandlerOutput, HandlerOutputBuilder, HandlerResult},
+	prelude::*,
+	timestamp::Timestamp,
+	Height,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeTry,
+) -> HandlerResult<ChannelUpgradeResult, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	if !channel_end.state_matches(&State::Open) {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+
+	// Both ends may have initialised the same upgrade concurrently, in which case the upgrade
+	// already stored on this end is reused, otherwise a new upgrade sequence is started.
+	let counterparty_fields = &msg.counterparty_upgrade_fields;
+	let existing_upgrade = ctx.channel_upgrade(&port_channel_id).ok();
+	let upgrade_sequence = match &existing_upgrade {
+		Some(upgrade) => {
+			if upgrade.fields.ordering != counterparty_fields.ordering ||
+				upgrade.fields.version != counterparty_fields.version
+			{
+				return Err(Error::incompatible_upgrade_fields())
+			}
+			channel_end.upgrade_sequence()
+		},
+		None => channel_end.upgrade_sequence() + 1,
+	};
+	if msg.counterparty_upgrade_sequence < upgrade_sequence {
+		return Ok(reject_outdated_upgrade(ctx, msg, channel_end, existing_upgrade, output))
+	}
+
+	// The proposed connection must be open and be the counterparty of the connection the
+	// counterparty proposed.
+	let (_, proposed_counterparty_connection_id) =
+		upgrade_connection(ctx, &msg.proposed_upgrade_connection_hops)?;
+	if counterparty_fields.connection_hops != vec![proposed_counterparty_connection_id] {
+		return Err(Error::incompatible_upgrade_fields())
+	}
+
+	// Proof verification in two steps:
+	// 1. The counterparty channel end should be open, with the upgrade sequence of the upgrade it
+	//    initialised.
+	let (conn, counterparty_connection_id) = upgrade_connection(ctx, &channel_end.connection_hops)?;
+	let mut expected_channel_end = ChannelEnd::new(
+		State::Open,
+		channel_end.ordering,
+		Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+		vec![counterparty_connection_id],
+		channel_end.version.clone(),
+	);
+	expected_channel_end.set_upgrade_sequence(msg.counterparty_upgrade_sequence);
+	verify_channel_proofs::<Ctx>(
+		ctx,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&expected_channel_end,
+		msg.proofs.object_proof(),
+	)?;
+
+	// 2. The counterparty should be storing the upgrade it proposed, whose timeout and next send
+	//    sequence are not yet set.
+	let counterparty_upgrade = Upgrade::new(
+		counterparty_fields.clone(),
+		UpgradeTimeout::new(Height::zero(), Timestamp::none()),
+		0.into(),
+	);
+	verify_channel_upgrade(
+		ctx,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&counterparty_upgrade,
+		msg.proofs.other_proof().as_ref().ok_or_else(Error::missing_channel_proof)?,
+	)?;
+
+	output.log("success: channel upgrade try");
+
+	let mut upgrade = Upgrade::new(
+		UpgradeFields::new(
+			counterparty_fields.ordering,
+			msg.proposed_upgrade_connection_hops.clone(),
+			counterparty_fields.version.clone(),
+		),
+		UpgradeTimeout::new(Height::zero(), Timestamp::none()),
+		0.into(),
+	);
+	channel_end.set_upgrade_sequence(msg.counterparty_upgrade_sequence);
+	start_flushing(ctx, &port_channel_id, &mut channel_end, &mut upgrade)?;
+
+	output.emit(upgrade_event(ctx, UpgradeStep::Try, &msg.port_id, msg.channel_id, &channel_end));
+
+	let result = ChannelUpgradeResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_end,
+		upgrade: Some(upgrade),
+		counterparty_upgrade: None,
+		error_receipt: None,
+		next_sequence_recv: None,
+		next_sequence_ack: None,
+	};
+
+	Ok(output.with_result(result))
+}
+
+/// The upgrade the counterparty initialised is older than the upgrades this end has seen. It's
+/// rejected with an error receipt the counterparty cancels it with, instead of failing the
+/// transaction, so that both ends move on to a fresh upgrade sequence.
+///
+/// When both ends initialised an upgrade, the one of this end has the higher sequence and takes
+/// priority: the receipt only invalidates the sequences below it, so that the counterparty can
+/// then try the upgrade of this end.
+fn reject_outdated_upgrade<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeTry,
+	channel_end: ChannelEnd,
+	existing_upgrade: Option<Upgrade>,
+	mut output: HandlerOutputBuilder<ChannelUpgradeResult>,
+) -> HandlerOutput<ChannelUpgradeResult> {
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let sequence = match existing_upgrade {
+		Some(_) => channel_end.upgrade_sequence() - 1,
+		None => channel_end.upgrade_sequence(),
+	};
+	let error_receipt = ErrorReceipt::new(
+		sequence,
+		format!(
+			"upgrade sequence {} is lower than the upgrade sequence {} of the channel",
+			msg.counterparty_upgrade_sequence,
+			channel_end.upgrade_sequence()
+		),
+	);
+
+	output.log("failure: channel upgrade try with an outdated upgrade sequence");
+	output.emit(upgrade_event(
+		ctx,
+		UpgradeStep::Cancel,
+		&msg.port_id,
+		msg.channel_id,
+		&channel_end,
+	));
+
+	let result = ChannelUpgradeResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		counterparty_upgrade: ctx.channel_counterparty_upgrade(&port_channel_id).ok(),
+		channel_end,
+		upgrade: existing_upgrade,
+		error_receipt: Some(error_receipt),
+		next_sequence_recv: None,
+		next_sequence_ack: None,
+	};
+	output.with_result(result)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics04_channel::{
+				channel::{Order, State},
+				error::ErrorDetail,
+				events::UpgradeStep,
+				handler::{
+					channel_upgrade_dispatch,
+					test_util::{
+						context_with_channel, emitted_upgrade_event, upgrade, PROOF_HEIGHT,
+					},
+				},
+				msgs::{
+					chan_upgrade_try::{
+						test_util::get_dummy_raw_msg_chan_upgrade_try, MsgChannelUpgradeTry,
+					},
+					ChannelUpgradeMsg,
+				},
+				Version,
+			},
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		prelude::*,
+		timestamp::Timestamp,
+	};
+	use test_log::test;
+
+	/// The counterparty proposes to upgrade the channel to an ordered one.
+	fn msg(counterparty_upgrade_sequence: u64) -> ChannelUpgradeMsg {
+		let mut msg =
+			MsgChannelUpgradeTry::try_from(get_dummy_raw_msg_chan_upgrade_try(PROOF_HEIGHT))
+				.unwrap();
+		msg.counterparty_upgrade_fields.ordering = Order::Ordered;
+		msg.counterparty_upgrade_sequence = counterparty_upgrade_sequence;
+		ChannelUpgradeMsg::ChannelUpgradeTry(msg)
+	}
+
+	#[test]
+	fn chan_upgrade_try_starts_flushing() {
+		let context = context_with_channel(State::Open, 0);
+
+		let (output, result) = channel_upgrade_dispatch(&context, &msg(1)).unwrap();
+
+		assert_eq!(result.channel_end.state, State::Flushing);
+		assert_eq!(result.channel_end.upgrade_sequence(), 1);
+		assert!(result.error_receipt.is_none());
+		let upgrade = result.upgrade.unwrap();
+		assert_eq!(upgrade.fields.ordering, Order::Ordered);
+		assert_eq!(upgrade.next_sequence_send, 1.into());
+		assert_ne!(upgrade.timeout.timestamp, Timestamp::none());
+
+		let event = emitted_upgrade_event(output);
+		assert_eq!(event.step, UpgradeStep::Try);
+		assert_eq!(event.upgrade_sequence, 1);
+	}
+
+	#[test]
+	fn chan_upgrade_try_accepts_crossing_hellos() {
+		// Both ends initialised the same upgrade, this end is already at its sequence.
+		let context = context_with_channel(State::Open, 1).with_channel_upgrade(
+			PortId::default(),
+			ChannelId::default(),
+			upgrade(Order::Ordered, Version::ics20()),
+		);
+
+		let (_, result) = channel_upgrade_dispatch(&context, &msg(1)).unwrap();
+
+		assert_eq!(result.channel_end.state, State::Flushing);
+		assert_eq!(result.channel_end.upgrade_sequence(), 1);
+		assert!(result.error_receipt.is_none());
+	}
+
+	#[test]
+	fn chan_upgrade_try_rejects_incompatible_crossing_hellos() {
+		let context = context_with_channel(State::Open, 1).with_channel_upgrade(
+			PortId::default(),
+			ChannelId::default(),
+			upgrade(Order::Unordered, Version::ics20()),
+		);
+
+		let err = channel_upgrade_dispatch(&context, &msg(1)).unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::IncompatibleUpgradeFields(_)));
+	}
+
+	#[test]
+	fn chan_upgrade_try_writes_error_receipt_for_outdated_sequence() {
+		let context = context_with_channel(State::Open, 2);
+
+		let (output, result) = channel_upgrade_dispatch(&context, &msg(1)).unwrap();
+
+		// The channel end is left untouched and the counterparty can cancel its upgrade.
+		assert_eq!(result.channel_end.state, State::Open);
+		assert_eq!(result.channel_end.upgrade_sequence(), 2);
+		assert!(result.upgrade.is_none());
+		assert_eq!(result.error_receipt.unwrap().sequence, 2);
+
+		let event = emitted_upgrade_event(output);
+		assert_eq!(event.step, UpgradeStep::Cancel);
+		assert_eq!(event.upgrade_sequence, 2);
+	}
+
+	#[test]
+	fn chan_upgrade_try_keeps_own_upgrade_for_outdated_crossing_hello() {
+		let own_upgrade = upgrade(Order::Ordered, Version::ics20());
+		let context = context_with_channel(State::Open, 2).with_channel_upgrade(
+			PortId::default(),
+			ChannelId::default(),
+			own_upgrade.clone(),
+		);
+
+		let (_, result) = channel_upgrade_dispatch(&context, &msg(1)).unwrap();
+
+		// Only the sequences below the upgrade of this end are aborted.
+		assert_eq!(result.channel_end.state, State::Open);
+		assert_eq!(result.upgrade, Some(own_upgrade));
+		assert_eq!(result.error_receipt.unwrap().sequence, 1);
+	}
+
+	#[test]
+	fn chan_upgrade_try_rejects_channel_not_open() {
+		let context = context_with_channel(State::Flushing, 1);
+
+		let err = channel_upgrade_dispatch(&context, &msg(1)).unwrap_err();
+		assert!(matches!(err.detail(), ErrorDetail::InvalidChannelState(_)));
+	}
+}
@@ -50,6 +50,14 @@ pub enum RecvPacketResult {
 		next_seq_recv: Sequence,
 		packet: Packet,
 	},
+	/// The packet timed out on an ordered channel allowing timeouts, it's skipped without being
+	/// delivered to the module.
+	TimedOut {
+		port_id: PortId,
+		channel_id: ChannelId,
+		next_seq_recv: Sequence,
+		sequence: Sequence,
+	},
 }
 
 pub fn process<Ctx: ReaderContext>(
@@ -63,7 +71,7 @@ pub fn process<Ctx: ReaderContext>(
 	let dest_channel_end =
 		ctx.channel_end(&(packet.destination_port.clone(), packet.destination_channel))?;
 
-	if !dest_channel_end.state_matches(&State::Open) {
+	if !dest_channel_end.state_matches(&State::Open) && !dest_channel_end.is_flushing() {
 		return Err(Error::invalid_channel_state(packet.source_channel, dest_channel_end.state))
 	}
 
@@ -84,13 +92,21 @@ pub fn process<Ctx: ReaderContext>(
 		return Err(Error::connection_not_open(dest_channel_end.connection_hops()[0].clone()))
 	}
 
+	// Ordered channels allowing timeouts skip a packet that timed out instead of rejecting it, so
+	// that the sender can prove the packet timed out without closing the channel.
+	let allow_timeout = dest_channel_end.order_matches(&Order::OrderedAllowTimeout);
+
 	let latest_height = ctx.host_height();
-	if (!packet.timeout_height.is_zero()) && (packet.timeout_height <= latest_height) {
+	let height_timed_out =
+		(!packet.timeout_height.is_zero()) && (packet.timeout_height <= latest_height);
+	if height_timed_out && !allow_timeout {
 		return Err(Error::low_packet_height(latest_height, packet.timeout_height))
 	}
 
 	let latest_timestamp = ctx.host_timestamp();
-	if let Expiry::Expired = latest_timestamp.check_expiry(&packet.timeout_timestamp) {
+	let timestamp_timed_out =
+		matches!(latest_timestamp.check_expiry(&packet.timeout_timestamp), Expiry::Expired);
+	if timestamp_timed_out && !allow_timeout {
 		return Err(Error::low_packet_timestamp())
 	}
 
@@ -102,7 +118,7 @@ pub fn process<Ctx: ReaderContext>(
 		&msg.proofs,
 	)?;
 
-	let result = if dest_channel_end.order_matches(&Order::Ordered) {
+	let result = if dest_channel_end.ordering().is_ordered() {
 		let next_seq_recv = ctx.get_next_sequence_recv(&(
 			packet.destination_port.clone(),
 			packet.destination_channel,
@@ -118,6 +134,16 @@ pub fn process<Ctx: ReaderContext>(
 			return Err(Error::invalid_packet_sequence(packet.sequence, next_seq_recv))
 		}
 
+		if height_timed_out || timestamp_timed_out {
+			output.log("success: packet timed out");
+			return Ok(output.with_result(PacketResult::Recv(RecvPacketResult::TimedOut {
+				port_id: packet.destination_port.clone(),
+				channel_id: packet.destination_channel,
+				next_seq_recv: next_seq_recv.increment(),
+				sequence: packet.sequence,
+			})))
+		}
+
 		PacketResult::Recv(RecvPacketResult::Ordered {
 			port_id: packet.destination_port.clone(),
 			channel_id: packet.destination_channel,
@@ -180,9 +206,9 @@ mod tests {
 			},
 			ics04_channel::{
 				channel::{ChannelEnd, Counterparty, Order, State},
-				handler::recv_packet::process,
+				handler::recv_packet::{process, RecvPacketResult},
 				msgs::recv_packet::{test_util::get_dummy_raw_msg_recv_packet, MsgRecvPacket},
-				packet::Packet,
+				packet::{Packet, PacketResult},
 				Version,
 			},
 			ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
@@ -329,4 +355,76 @@ mod tests {
 			}
 		}
 	}
+
+	#[test]
+	fn recv_packet_skips_timed_out_packet_on_ordered_allow_timeout_channel() {
+		let context = MockContext::default();
+		let host_height = context.latest_height().increment();
+		let client_height = host_height.increment();
+
+		let msg =
+			MsgRecvPacket::try_from(get_dummy_raw_msg_recv_packet(client_height.revision_height))
+				.unwrap();
+		let packet = Packet {
+			sequence: 1.into(),
+			source_port: PortId::default(),
+			source_channel: ChannelId::default(),
+			destination_port: PortId::default(),
+			destination_channel: ChannelId::default(),
+			data: Vec::new(),
+			timeout_height: client_height,
+			timeout_timestamp: Timestamp::from_nanoseconds(1).unwrap(),
+		};
+		let msg = MsgRecvPacket::new(packet.clone(), msg.proofs, get_dummy_account_id());
+
+		let connection_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::new(
+				ClientId::default(),
+				Some(ConnectionId::default()),
+				Default::default(),
+			),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+		let dest_channel_end = |ordering| {
+			ChannelEnd::new(
+				State::Open,
+				ordering,
+				Counterparty::new(packet.source_port.clone(), Some(packet.source_channel)),
+				vec![ConnectionId::default()],
+				Version::ics20(),
+			)
+		};
+		let context = |ordering| {
+			MockContext::default()
+				.with_client(&ClientId::default(), client_height)
+				.with_connection(ConnectionId::default(), connection_end.clone())
+				.with_channel(
+					packet.destination_port.clone(),
+					packet.destination_channel,
+					dest_channel_end(ordering),
+				)
+				.with_recv_sequence(
+					packet.destination_port.clone(),
+					packet.destination_channel,
+					packet.sequence,
+				)
+				.with_height(host_height)
+		};
+
+		// ordered channels reject packets that timed out
+		assert!(process(&context(Order::Ordered), &msg).is_err());
+
+		let output = process(&context(Order::OrderedAllowTimeout), &msg).unwrap();
+		assert!(output.events.is_empty());
+		match output.result {
+			PacketResult::Recv(RecvPacketResult::TimedOut { next_seq_recv, sequence, .. }) => {
+				assert_eq!(sequence, packet.sequence);
+				assert_eq!(next_seq_recv, packet.sequence.increment());
+			},
+			result => panic!("unexpected result {result:?}"),
+		}
+	}
 }
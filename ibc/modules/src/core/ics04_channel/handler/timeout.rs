@@ -19,7 +19,10 @@ use crate::{
 			channel::{ChannelEnd, Counterparty, Order, State},
 			error::Error,
 			events::TimeoutPacket,
-			handler::verify::{verify_next_sequence_recv, verify_packet_receipt_absence},
+			handler::verify::{
+				verify_next_sequence_recv, verify_packet_receipt_absence,
+				verify_packet_timeout_receipt,
+			},
 			msgs::timeout::MsgTimeout,
 			packet::{PacketResult, Sequence},
 		},
@@ -38,6 +41,8 @@ pub struct TimeoutPacketResult {
 	pub channel_id: ChannelId,
 	pub seq: Sequence,
 	pub channel: Option<ChannelEnd>,
+	/// The next sequence to acknowledge on ordered channels allowing timeouts
+	pub next_seq_ack: Option<Sequence>,
 }
 
 pub fn process<Ctx>(ctx: &Ctx, msg: &MsgTimeout) -> HandlerResult<PacketResult, Error>
@@ -51,7 +56,8 @@ where
 	let mut source_channel_end =
 		ctx.channel_end(&(packet.source_port.clone(), packet.source_channel))?;
 
-	if !source_channel_end.state_matches(&State::Open) {
+	// packets in-flight when an upgrade started can still time out while it is flushing
+	if !source_channel_end.state_matches(&State::Open) && !source_channel_end.is_flushing() {
 		return Err(Error::channel_closed(packet.source_channel))
 	}
 
@@ -102,41 +108,69 @@ where
 		return Err(Error::incorrect_packet_commitment(packet.sequence))
 	}
 
-	let result = if source_channel_end.order_matches(&Order::Ordered) {
-		if packet.sequence < msg.next_sequence_recv {
-			return Err(Error::invalid_packet_sequence(packet.sequence, msg.next_sequence_recv))
-		}
-		verify_next_sequence_recv::<Ctx>(
-			ctx,
-			msg.proofs.height(),
-			&connection_end,
-			packet.clone(),
-			msg.next_sequence_recv,
-			&msg.proofs,
-		)?;
-
-		source_channel_end.state = State::Closed;
-		PacketResult::Timeout(TimeoutPacketResult {
-			port_id: packet.source_port.clone(),
-			channel_id: packet.source_channel,
-			seq: packet.sequence,
-			channel: Some(source_channel_end),
-		})
-	} else {
-		verify_packet_receipt_absence::<Ctx>(
-			ctx,
-			msg.proofs.height(),
-			&connection_end,
-			packet.clone(),
-			&msg.proofs,
-		)?;
-
-		PacketResult::Timeout(TimeoutPacketResult {
-			port_id: packet.source_port.clone(),
-			channel_id: packet.source_channel,
-			seq: packet.sequence,
-			channel: None,
-		})
+	let result = match source_channel_end.ordering {
+		Order::Ordered => {
+			if packet.sequence < msg.next_sequence_recv {
+				return Err(Error::invalid_packet_sequence(packet.sequence, msg.next_sequence_recv))
+			}
+			verify_next_sequence_recv::<Ctx>(
+				ctx,
+				msg.proofs.height(),
+				&connection_end,
+				packet.clone(),
+				msg.next_sequence_recv,
+				&msg.proofs,
+			)?;
+
+			source_channel_end.state = State::Closed;
+			PacketResult::Timeout(TimeoutPacketResult {
+				port_id: packet.source_port.clone(),
+				channel_id: packet.source_channel,
+				seq: packet.sequence,
+				channel: Some(source_channel_end),
+				next_seq_ack: None,
+			})
+		},
+		Order::OrderedAllowTimeout => {
+			// packets are timed out in the order they were sent, like they are acknowledged
+			let next_seq_ack =
+				ctx.get_next_sequence_ack(&(packet.source_port.clone(), packet.source_channel))?;
+			if packet.sequence != next_seq_ack {
+				return Err(Error::invalid_packet_sequence(packet.sequence, next_seq_ack))
+			}
+			verify_packet_timeout_receipt::<Ctx>(
+				ctx,
+				msg.proofs.height(),
+				&connection_end,
+				packet,
+				&msg.proofs,
+			)?;
+
+			PacketResult::Timeout(TimeoutPacketResult {
+				port_id: packet.source_port.clone(),
+				channel_id: packet.source_channel,
+				seq: packet.sequence,
+				channel: None,
+				next_seq_ack: Some(next_seq_ack.increment()),
+			})
+		},
+		Order::Unordered => {
+			verify_packet_receipt_absence::<Ctx>(
+				ctx,
+				msg.proofs.height(),
+				&connection_end,
+				packet.clone(),
+				&msg.proofs,
+			)?;
+
+			PacketResult::Timeout(TimeoutPacketResult {
+				port_id: packet.source_port.clone(),
+				channel_id: packet.source_channel,
+				seq: packet.sequence,
+				channel: None,
+				next_seq_ack: None,
+			})
+		},
 	};
 
 	output.log("success: packet timeout ");
@@ -167,6 +201,7 @@ mod tests {
 				context::ChannelReader,
 				handler::timeout::process,
 				msgs::timeout::{test_util::get_dummy_raw_msg_timeout, MsgTimeout},
+				packet::PacketResult,
 				Version,
 			},
 			ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
@@ -341,4 +376,73 @@ mod tests {
 			}
 		}
 	}
+
+	#[test]
+	fn timeout_packet_on_ordered_allow_timeout_channel() {
+		let height = Height::default().revision_height + 2;
+		let client_height = Height::new(0, height);
+
+		let mut msg = MsgTimeout::try_from(get_dummy_raw_msg_timeout(height, 5)).unwrap();
+		msg.packet.timeout_timestamp = Default::default();
+		let packet = msg.packet.clone();
+
+		let source_channel_end = ChannelEnd::new(
+			State::Open,
+			Order::OrderedAllowTimeout,
+			Counterparty::new(packet.destination_port.clone(), Some(packet.destination_channel)),
+			vec![ConnectionId::default()],
+			Version::ics20(),
+		);
+		let connection_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::new(
+				ClientId::default(),
+				Some(ConnectionId::default()),
+				Default::default(),
+			),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+		let context = |next_seq_ack: u64| {
+			let context = MockContext::<MockClientTypes>::default();
+			let commitment = context.packet_commitment(
+				packet.data.clone(),
+				packet.timeout_height,
+				packet.timeout_timestamp,
+			);
+			context
+				.with_client(&ClientId::default(), client_height)
+				.with_connection(ConnectionId::default(), connection_end.clone())
+				.with_channel(
+					packet.source_port.clone(),
+					packet.source_channel,
+					source_channel_end.clone(),
+				)
+				.with_packet_commitment(
+					packet.source_port.clone(),
+					packet.source_channel,
+					packet.sequence,
+					commitment,
+				)
+				.with_ack_sequence(
+					packet.source_port.clone(),
+					packet.source_channel,
+					next_seq_ack.into(),
+				)
+		};
+
+		// packets are timed out in the order they were sent
+		assert!(process(&context(u64::from(packet.sequence) + 1), &msg).is_err());
+
+		let output = process(&context(packet.sequence.into()), &msg).unwrap();
+		match output.result {
+			PacketResult::Timeout(result) => {
+				// the channel isn't closed, the next packet can be acknowledged instead
+				assert!(result.channel.is_none());
+				assert_eq!(result.next_seq_ack, Some(packet.sequence.increment()));
+			},
+			result => panic!("unexpected result {result:?}"),
+		}
+	}
 }
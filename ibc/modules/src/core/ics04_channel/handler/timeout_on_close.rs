@@ -102,7 +102,7 @@ pub fn process<Ctx: ReaderContext>(
 			.ok_or_else(|| Error::missing_channel_proof())?,
 	)?;
 
-	let result = if source_channel_end.order_matches(&Order::Ordered) {
+	let result = if source_channel_end.ordering().is_ordered() {
 		if packet.sequence < msg.next_sequence_recv {
 			return Err(Error::invalid_packet_sequence(packet.sequence, msg.next_sequence_recv))
 		}
@@ -115,11 +115,24 @@ pub fn process<Ctx: ReaderContext>(
 			&msg.proofs,
 		)?;
 
+		// packets are timed out in the order they were sent on channels allowing timeouts
+		let next_seq_ack = if source_channel_end.order_matches(&Order::OrderedAllowTimeout) {
+			let next_seq_ack =
+				ctx.get_next_sequence_ack(&(packet.source_port.clone(), packet.source_channel))?;
+			if packet.sequence != next_seq_ack {
+				return Err(Error::invalid_packet_sequence(packet.sequence, next_seq_ack))
+			}
+			Some(next_seq_ack.increment())
+		} else {
+			None
+		};
+
 		PacketResult::Timeout(TimeoutPacketResult {
 			port_id: packet.source_port.clone(),
 			channel_id: packet.source_channel,
 			seq: packet.sequence,
 			channel: Some(source_channel_end),
+			next_seq_ack,
 		})
 	} else {
 		verify_packet_receipt_absence::<Ctx>(
@@ -135,6 +148,7 @@ pub fn process<Ctx: ReaderContext>(
 			channel_id: packet.source_channel,
 			seq: packet.sequence,
 			channel: None,
+			next_seq_ack: None,
 		})
 	};
 
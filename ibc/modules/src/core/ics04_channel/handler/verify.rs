@@ -16,21 +16,25 @@ use crate::{
 	core::{
 		ics02_client::{
 			client_consensus::ConsensusState, client_def::ClientDef, client_state::ClientState,
+			error::Error as ClientError,
 		},
 		ics03_connection::connection::ConnectionEnd,
 		ics04_channel::{
 			channel::ChannelEnd,
 			error::Error,
 			msgs::acknowledgement::Acknowledgement,
-			packet::{Packet, Sequence},
+			packet::{Packet, Receipt, Sequence},
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics23_commitment::commitment::CommitmentProofBytes,
+		ics24_host::path::{ChannelUpgradeErrorPath, ChannelUpgradePath, Path, ReceiptsPath},
 		ics26_routing::context::ReaderContext,
 	},
 	prelude::*,
 	proofs::Proofs,
 	Height,
 };
+use tendermint_proto::Protobuf;
 
 /// Entry point for verifying all proofs bundled in any ICS4 message for channel protocols.
 pub fn verify_channel_proofs<Ctx>(
@@ -258,3 +262,120 @@ where
 
 	Ok(())
 }
+
+/// Entry point for verifying the proof of the receipt written by the counterparty of an ordered
+/// channel allowing timeouts when it skipped a packet that timed out.
+pub fn verify_packet_timeout_receipt<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	height: Height,
+	connection_end: &ConnectionEnd,
+	packet: &Packet,
+	proofs: &Proofs,
+) -> Result<(), Error> {
+	let path = ReceiptsPath {
+		port_id: packet.destination_port.clone(),
+		channel_id: packet.destination_channel,
+		sequence: packet.sequence,
+	};
+	verify_channel_membership(
+		ctx,
+		height,
+		connection_end,
+		path.into(),
+		Receipt::Timeout.as_bytes().to_vec(),
+		proofs.object_proof(),
+		|e| Error::packet_verification_failed(packet.sequence, e),
+	)
+}
+
+/// Entry point for verifying the proof of the upgrade proposed by the counterparty of a channel.
+pub fn verify_channel_upgrade<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	height: Height,
+	channel_end: &ChannelEnd,
+	connection_end: &ConnectionEnd,
+	upgrade: &Upgrade,
+	proof: &CommitmentProofBytes,
+) -> Result<(), Error> {
+	let path = ChannelUpgradePath(
+		channel_end.counterparty().port_id().clone(),
+		*channel_end.counterparty().channel_id().ok_or_else(Error::missing_channel_id)?,
+	);
+	let value = upgrade
+		.encode_vec()
+		.map_err(|e| Error::implementation_specific(e.to_string()))?;
+	verify_channel_membership(
+		ctx,
+		height,
+		connection_end,
+		path.into(),
+		value,
+		proof,
+		Error::upgrade_verification_failed,
+	)
+}
+
+/// Entry point for verifying the proof of the error receipt written by the counterparty of a
+/// channel when it aborted an upgrade.
+pub fn verify_channel_upgrade_error<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	height: Height,
+	channel_end: &ChannelEnd,
+	connection_end: &ConnectionEnd,
+	error_receipt: &ErrorReceipt,
+	proof: &CommitmentProofBytes,
+) -> Result<(), Error> {
+	let path = ChannelUpgradeErrorPath(
+		channel_end.counterparty().port_id().clone(),
+		*channel_end.counterparty().channel_id().ok_or_else(Error::missing_channel_id)?,
+	);
+	let value = error_receipt
+		.encode_vec()
+		.map_err(|e| Error::implementation_specific(e.to_string()))?;
+	verify_channel_membership(
+		ctx,
+		height,
+		connection_end,
+		path.into(),
+		value,
+		proof,
+		Error::upgrade_verification_failed,
+	)
+}
+
+fn verify_channel_membership<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	height: Height,
+	connection_end: &ConnectionEnd,
+	path: Path,
+	value: Vec<u8>,
+	proof: &CommitmentProofBytes,
+	on_failure: impl FnOnce(ClientError) -> Error,
+) -> Result<(), Error> {
+	let client_id = connection_end.client_id();
+	let client_state = ctx.client_state(client_id).map_err(Error::ics02_client)?;
+
+	// The client must not be frozen.
+	if client_state.is_frozen(ctx, client_id) {
+		return Err(Error::frozen_client(client_id.clone()))
+	}
+
+	let consensus_state = ctx
+		.consensus_state(client_id, height)
+		.map_err(|_| Error::error_invalid_consensus_state())?;
+
+	client_state
+		.client_def()
+		.verify_membership(
+			ctx,
+			client_id,
+			&client_state,
+			height,
+			connection_end.counterparty().prefix(),
+			proof,
+			consensus_state.root(),
+			path,
+			value,
+		)
+		.map_err(on_failure)
+}
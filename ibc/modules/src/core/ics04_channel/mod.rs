@@ -25,5 +25,6 @@ pub mod msgs;
 pub mod packet;
 
 pub mod commitment;
+pub mod upgrade;
 mod version;
 pub use version::Version;
@@ -12,8 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Message definitions for all ICS4 domain types: channel open & close handshake datagrams, as well
-//! as packets.
+//! Message definitions for all ICS4 domain types: channel open, close & upgrade handshake
+//! datagrams, as well as packets.
 
 use crate::core::{
 	ics04_channel::{
@@ -22,10 +22,15 @@ use crate::core::{
 			acknowledgement::MsgAcknowledgement, chan_close_confirm::MsgChannelCloseConfirm,
 			chan_close_init::MsgChannelCloseInit, chan_open_ack::MsgChannelOpenAck,
 			chan_open_confirm::MsgChannelOpenConfirm, chan_open_init::MsgChannelOpenInit,
-			chan_open_try::MsgChannelOpenTry, recv_packet::MsgRecvPacket, timeout::MsgTimeout,
-			timeout_on_close::MsgTimeoutOnClose,
+			chan_open_try::MsgChannelOpenTry, chan_upgrade_ack::MsgChannelUpgradeAck,
+			chan_upgrade_cancel::MsgChannelUpgradeCancel,
+			chan_upgrade_confirm::MsgChannelUpgradeConfirm,
+			chan_upgrade_init::MsgChannelUpgradeInit, chan_upgrade_open::MsgChannelUpgradeOpen,
+			chan_upgrade_timeout::MsgChannelUpgradeTimeout, chan_upgrade_try::MsgChannelUpgradeTry,
+			recv_packet::MsgRecvPacket, timeout::MsgTimeout, timeout_on_close::MsgTimeoutOnClose,
 		},
 	},
+	ics24_host::identifier::PortId,
 	ics26_routing::context::{Ics26Context, ModuleId},
 };
 
@@ -39,6 +44,15 @@ pub mod chan_open_try;
 pub mod chan_close_confirm;
 pub mod chan_close_init;
 
+// Upgrade handshake messages.
+pub mod chan_upgrade_ack;
+pub mod chan_upgrade_cancel;
+pub mod chan_upgrade_confirm;
+pub mod chan_upgrade_init;
+pub mod chan_upgrade_open;
+pub mod chan_upgrade_timeout;
+pub mod chan_upgrade_try;
+
 // Packet specific messages.
 pub mod acknowledgement;
 pub mod recv_packet;
@@ -76,6 +90,36 @@ impl ChannelMsg {
 	}
 }
 
+/// Enumeration of the messages of the ICS4 channel upgrade handshake.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChannelUpgradeMsg {
+	ChannelUpgradeInit(MsgChannelUpgradeInit),
+	ChannelUpgradeTry(MsgChannelUpgradeTry),
+	ChannelUpgradeAck(MsgChannelUpgradeAck),
+	ChannelUpgradeConfirm(MsgChannelUpgradeConfirm),
+	ChannelUpgradeOpen(MsgChannelUpgradeOpen),
+	ChannelUpgradeTimeout(MsgChannelUpgradeTimeout),
+	ChannelUpgradeCancel(MsgChannelUpgradeCancel),
+}
+
+impl ChannelUpgradeMsg {
+	pub fn port_id(&self) -> &PortId {
+		match self {
+			ChannelUpgradeMsg::ChannelUpgradeInit(msg) => &msg.port_id,
+			ChannelUpgradeMsg::ChannelUpgradeTry(msg) => &msg.port_id,
+			ChannelUpgradeMsg::ChannelUpgradeAck(msg) => &msg.port_id,
+			ChannelUpgradeMsg::ChannelUpgradeConfirm(msg) => &msg.port_id,
+			ChannelUpgradeMsg::ChannelUpgradeOpen(msg) => &msg.port_id,
+			ChannelUpgradeMsg::ChannelUpgradeTimeout(msg) => &msg.port_id,
+			ChannelUpgradeMsg::ChannelUpgradeCancel(msg) => &msg.port_id,
+		}
+	}
+
+	pub(super) fn lookup_module(&self, ctx: &impl Ics26Context) -> Result<ModuleId, Error> {
+		ctx.lookup_module_by_port(self.port_id()).map_err(Error::ics05_port)
+	}
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum PacketMsg {
 	RecvPacket(MsgRecvPacket),
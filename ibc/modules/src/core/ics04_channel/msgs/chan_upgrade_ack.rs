@@ -0,0 +1,169 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	core::{
+		ics04_channel::{error::Error, upgrade::Upgrade},
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	prelude::*,
+	proofs::Proofs,
+	signer::Signer,
+	tx_msg::Msg,
+};
+
+use ibc_proto::ibc::core::channel::v1::MsgChannelUpgradeAck as RawMsgChannelUpgradeAck;
+use tendermint_proto::Protobuf;
+
+pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelUpgradeAck";
+
+///
+/// Message definition for the third step in the channel upgrade handshake (`ChanUpgradeAck`
+/// datagram).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MsgChannelUpgradeAck {
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub counterparty_upgrade: Upgrade,
+	/// Proof of the counterparty channel end in `proofs.object_proof` and of the counterparty
+	/// upgrade in `proofs.other_proof`
+	pub proofs: Proofs,
+	pub signer: Signer,
+}
+
+impl Msg for MsgChannelUpgradeAck {
+	type ValidationError = Error;
+	type Raw = RawMsgChannelUpgradeAck;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		TYPE_URL.to_string()
+	}
+}
+
+impl Protobuf<RawMsgChannelUpgradeAck> for MsgChannelUpgradeAck {}
+
+impl TryFrom<RawMsgChannelUpgradeAck> for MsgChannelUpgradeAck {
+	type Error = Error;
+
+	fn try_from(raw_msg: RawMsgChannelUpgradeAck) -> Result<Self, Self::Error> {
+		let proofs = Proofs::new(
+			raw_msg.proof_channel.try_into().map_err(Error::invalid_proof)?,
+			None,
+			None,
+			Some(raw_msg.proof_upgrade.try_into().map_err(Error::invalid_proof)?),
+			raw_msg.proof_height.ok_or_else(Error::missing_height)?.into(),
+		)
+		.map_err(Error::invalid_proof)?;
+
+		Ok(MsgChannelUpgradeAck {
+			port_id: raw_msg.port_id.parse().map_err(Error::identifier)?,
+			channel_id: raw_msg.channel_id.parse().map_err(Error::identifier)?,
+			counterparty_upgrade: raw_msg
+				.counterparty_upgrade
+				.ok_or_else(Error::missing_upgrade_fields)?
+				.try_into()?,
+			proofs,
+			signer: raw_msg.signer.parse().map_err(Error::signer)?,
+		})
+	}
+}
+
+impl From<MsgChannelUpgradeAck> for RawMsgChannelUpgradeAck {
+	fn from(domain_msg: MsgChannelUpgradeAck) -> Self {
+		RawMsgChannelUpgradeAck {
+			port_id: domain_msg.port_id.to_string(),
+			channel_id: domain_msg.channel_id.to_string(),
+			counterparty_upgrade: Some(domain_msg.counterparty_upgrade.into()),
+			proof_channel: domain_msg.proofs.object_proof().clone().into(),
+			proof_upgrade: domain_msg
+				.proofs
+				.other_proof()
+				.clone()
+				.map_or_else(Vec::new, |proof| proof.into()),
+			proof_height: Some(domain_msg.proofs.height().into()),
+			signer: domain_msg.signer.to_string(),
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test_util {
+	use crate::{
+		core::ics24_host::identifier::{ChannelId, PortId},
+		prelude::*,
+		test_utils::{get_dummy_bech32_account, get_dummy_proof},
+	};
+	use ibc_proto::ibc::core::{
+		channel::v1::{
+			MsgChannelUpgradeAck as RawMsgChannelUpgradeAck, Order, Timeout, Upgrade, UpgradeFields,
+		},
+		client::v1::Height,
+	};
+
+	/// Returns a dummy `RawMsgChannelUpgradeAck`, for testing only!
+	pub fn get_dummy_raw_msg_chan_upgrade_ack(proof_height: u64) -> RawMsgChannelUpgradeAck {
+		RawMsgChannelUpgradeAck {
+			port_id: PortId::default().to_string(),
+			channel_id: ChannelId::default().to_string(),
+			counterparty_upgrade: Some(Upgrade {
+				fields: Some(UpgradeFields {
+					ordering: Order::Unordered as i32,
+					connection_hops: vec!["connection-0".to_string()],
+					version: "ics20-1".to_string(),
+				}),
+				timeout: Some(Timeout {
+					height: Some(Height { revision_number: 0, revision_height: 1000 }),
+					timestamp: 0,
+				}),
+				next_sequence_send: 1,
+			}),
+			proof_channel: get_dummy_proof(),
+			proof_upgrade: get_dummy_proof(),
+			proof_height: Some(Height { revision_number: 0, revision_height: proof_height }),
+			signer: get_dummy_bech32_account(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::prelude::*;
+	use ibc_proto::ibc::core::channel::v1::MsgChannelUpgradeAck as RawMsgChannelUpgradeAck;
+	use test_log::test;
+
+	use super::{test_util::get_dummy_raw_msg_chan_upgrade_ack, MsgChannelUpgradeAck};
+
+	#[test]
+	fn parse_bad_msg_chan_upgrade_ack() {
+		let raw = RawMsgChannelUpgradeAck {
+			counterparty_upgrade: None,
+			..get_dummy_raw_msg_chan_upgrade_ack(10)
+		};
+		assert!(MsgChannelUpgradeAck::try_from(raw).is_err());
+	}
+
+	#[test]
+	fn to_and_from() {
+		let raw = get_dummy_raw_msg_chan_upgrade_ack(100);
+		let msg = MsgChannelUpgradeAck::try_from(raw.clone()).unwrap();
+		let raw_back = RawMsgChannelUpgradeAck::from(msg.clone());
+		let msg_back = MsgChannelUpgradeAck::try_from(raw_back.clone()).unwrap();
+		assert_eq!(raw, raw_back);
+		assert_eq!(msg, msg_back);
+	}
+}
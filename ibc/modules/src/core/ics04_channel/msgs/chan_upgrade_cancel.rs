@@ -0,0 +1,146 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	core::{
+		ics04_channel::{error::Error, upgrade::ErrorReceipt},
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	prelude::*,
+	proofs::Proofs,
+	signer::Signer,
+	tx_msg::Msg,
+};
+
+use ibc_proto::ibc::core::channel::v1::MsgChannelUpgradeCancel as RawMsgChannelUpgradeCancel;
+use tendermint_proto::Protobuf;
+
+pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelUpgradeCancel";
+
+///
+/// Message definition to cancel an upgrade aborted by the counterparty (`ChanUpgradeCancel`
+/// datagram).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MsgChannelUpgradeCancel {
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub error_receipt: ErrorReceipt,
+	/// Proof of the counterparty error receipt
+	pub proofs: Proofs,
+	pub signer: Signer,
+}
+
+impl Msg for MsgChannelUpgradeCancel {
+	type ValidationError = Error;
+	type Raw = RawMsgChannelUpgradeCancel;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		TYPE_URL.to_string()
+	}
+}
+
+impl Protobuf<RawMsgChannelUpgradeCancel> for MsgChannelUpgradeCancel {}
+
+impl TryFrom<RawMsgChannelUpgradeCancel> for MsgChannelUpgradeCancel {
+	type Error = Error;
+
+	fn try_from(raw_msg: RawMsgChannelUpgradeCancel) -> Result<Self, Self::Error> {
+		let proofs = Proofs::new(
+			raw_msg.proof_error_receipt.try_into().map_err(Error::invalid_proof)?,
+			None,
+			None,
+			None,
+			raw_msg.proof_height.ok_or_else(Error::missing_height)?.into(),
+		)
+		.map_err(Error::invalid_proof)?;
+
+		Ok(MsgChannelUpgradeCancel {
+			port_id: raw_msg.port_id.parse().map_err(Error::identifier)?,
+			channel_id: raw_msg.channel_id.parse().map_err(Error::identifier)?,
+			error_receipt: raw_msg.error_receipt.ok_or_else(Error::missing_error_receipt)?.into(),
+			proofs,
+			signer: raw_msg.signer.parse().map_err(Error::signer)?,
+		})
+	}
+}
+
+impl From<MsgChannelUpgradeCancel> for RawMsgChannelUpgradeCancel {
+	fn from(domain_msg: MsgChannelUpgradeCancel) -> Self {
+		RawMsgChannelUpgradeCancel {
+			port_id: domain_msg.port_id.to_string(),
+			channel_id: domain_msg.channel_id.to_string(),
+			error_receipt: Some(domain_msg.error_receipt.into()),
+			proof_error_receipt: domain_msg.proofs.object_proof().clone().into(),
+			proof_height: Some(domain_msg.proofs.height().into()),
+			signer: domain_msg.signer.to_string(),
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test_util {
+	use crate::{
+		core::ics24_host::identifier::{ChannelId, PortId},
+		prelude::*,
+		test_utils::{get_dummy_bech32_account, get_dummy_proof},
+	};
+	use ibc_proto::ibc::core::{
+		channel::v1::{ErrorReceipt, MsgChannelUpgradeCancel as RawMsgChannelUpgradeCancel},
+		client::v1::Height,
+	};
+
+	/// Returns a dummy `RawMsgChannelUpgradeCancel`, for testing only!
+	pub fn get_dummy_raw_msg_chan_upgrade_cancel(proof_height: u64) -> RawMsgChannelUpgradeCancel {
+		RawMsgChannelUpgradeCancel {
+			port_id: PortId::default().to_string(),
+			channel_id: ChannelId::default().to_string(),
+			error_receipt: Some(ErrorReceipt { sequence: 1, message: "aborted".to_string() }),
+			proof_error_receipt: get_dummy_proof(),
+			proof_height: Some(Height { revision_number: 0, revision_height: proof_height }),
+			signer: get_dummy_bech32_account(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::prelude::*;
+	use ibc_proto::ibc::core::channel::v1::MsgChannelUpgradeCancel as RawMsgChannelUpgradeCancel;
+	use test_log::test;
+
+	use super::{test_util::get_dummy_raw_msg_chan_upgrade_cancel, MsgChannelUpgradeCancel};
+
+	#[test]
+	fn parse_bad_msg_chan_upgrade_cancel() {
+		let raw = RawMsgChannelUpgradeCancel {
+			error_receipt: None,
+			..get_dummy_raw_msg_chan_upgrade_cancel(10)
+		};
+		assert!(MsgChannelUpgradeCancel::try_from(raw).is_err());
+	}
+
+	#[test]
+	fn to_and_from() {
+		let raw = get_dummy_raw_msg_chan_upgrade_cancel(100);
+		let msg = MsgChannelUpgradeCancel::try_from(raw.clone()).unwrap();
+		let raw_back = RawMsgChannelUpgradeCancel::from(msg.clone());
+		let msg_back = MsgChannelUpgradeCancel::try_from(raw_back.clone()).unwrap();
+		assert_eq!(raw, raw_back);
+		assert_eq!(msg, msg_back);
+	}
+}
@@ -0,0 +1,152 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	core::{
+		ics04_channel::{channel::ChannelEnd, error::Error},
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	prelude::*,
+	proofs::Proofs,
+	signer::Signer,
+	tx_msg::Msg,
+};
+
+use ibc_proto::ibc::core::channel::v1::MsgChannelUpgradeTimeout as RawMsgChannelUpgradeTimeout;
+use tendermint_proto::Protobuf;
+
+pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelUpgradeTimeout";
+
+///
+/// Message definition to abort an upgrade whose timeout has passed on the counterparty chain
+/// (`ChanUpgradeTimeout` datagram).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MsgChannelUpgradeTimeout {
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub counterparty_channel: ChannelEnd,
+	pub proofs: Proofs,
+	pub signer: Signer,
+}
+
+impl Msg for MsgChannelUpgradeTimeout {
+	type ValidationError = Error;
+	type Raw = RawMsgChannelUpgradeTimeout;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		TYPE_URL.to_string()
+	}
+}
+
+impl Protobuf<RawMsgChannelUpgradeTimeout> for MsgChannelUpgradeTimeout {}
+
+impl TryFrom<RawMsgChannelUpgradeTimeout> for MsgChannelUpgradeTimeout {
+	type Error = Error;
+
+	fn try_from(raw_msg: RawMsgChannelUpgradeTimeout) -> Result<Self, Self::Error> {
+		let proofs = Proofs::new(
+			raw_msg.proof_channel.try_into().map_err(Error::invalid_proof)?,
+			None,
+			None,
+			None,
+			raw_msg.proof_height.ok_or_else(Error::missing_height)?.into(),
+		)
+		.map_err(Error::invalid_proof)?;
+
+		Ok(MsgChannelUpgradeTimeout {
+			port_id: raw_msg.port_id.parse().map_err(Error::identifier)?,
+			channel_id: raw_msg.channel_id.parse().map_err(Error::identifier)?,
+			counterparty_channel: raw_msg
+				.counterparty_channel
+				.ok_or_else(Error::missing_channel)?
+				.try_into()?,
+			proofs,
+			signer: raw_msg.signer.parse().map_err(Error::signer)?,
+		})
+	}
+}
+
+impl From<MsgChannelUpgradeTimeout> for RawMsgChannelUpgradeTimeout {
+	fn from(domain_msg: MsgChannelUpgradeTimeout) -> Self {
+		RawMsgChannelUpgradeTimeout {
+			port_id: domain_msg.port_id.to_string(),
+			channel_id: domain_msg.channel_id.to_string(),
+			counterparty_channel: Some(domain_msg.counterparty_channel.into()),
+			proof_channel: domain_msg.proofs.object_proof().clone().into(),
+			proof_height: Some(domain_msg.proofs.height().into()),
+			signer: domain_msg.signer.to_string(),
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test_util {
+	use crate::{
+		core::{
+			ics04_channel::channel::test_util::get_dummy_raw_channel_end,
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		prelude::*,
+		test_utils::{get_dummy_bech32_account, get_dummy_proof},
+	};
+	use ibc_proto::ibc::core::{
+		channel::v1::MsgChannelUpgradeTimeout as RawMsgChannelUpgradeTimeout, client::v1::Height,
+	};
+
+	/// Returns a dummy `RawMsgChannelUpgradeTimeout`, for testing only!
+	pub fn get_dummy_raw_msg_chan_upgrade_timeout(
+		proof_height: u64,
+	) -> RawMsgChannelUpgradeTimeout {
+		RawMsgChannelUpgradeTimeout {
+			port_id: PortId::default().to_string(),
+			channel_id: ChannelId::default().to_string(),
+			counterparty_channel: Some(get_dummy_raw_channel_end()),
+			proof_channel: get_dummy_proof(),
+			proof_height: Some(Height { revision_number: 0, revision_height: proof_height }),
+			signer: get_dummy_bech32_account(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::prelude::*;
+	use ibc_proto::ibc::core::channel::v1::MsgChannelUpgradeTimeout as RawMsgChannelUpgradeTimeout;
+	use test_log::test;
+
+	use super::{test_util::get_dummy_raw_msg_chan_upgrade_timeout, MsgChannelUpgradeTimeout};
+
+	#[test]
+	fn parse_bad_msg_chan_upgrade_timeout() {
+		let raw = RawMsgChannelUpgradeTimeout {
+			counterparty_channel: None,
+			..get_dummy_raw_msg_chan_upgrade_timeout(10)
+		};
+		assert!(MsgChannelUpgradeTimeout::try_from(raw).is_err());
+	}
+
+	#[test]
+	fn to_and_from() {
+		let raw = get_dummy_raw_msg_chan_upgrade_timeout(100);
+		let msg = MsgChannelUpgradeTimeout::try_from(raw.clone()).unwrap();
+		let raw_back = RawMsgChannelUpgradeTimeout::from(msg.clone());
+		let msg_back = MsgChannelUpgradeTimeout::try_from(raw_back.clone()).unwrap();
+		assert_eq!(raw, raw_back);
+		assert_eq!(msg, msg_back);
+	}
+}
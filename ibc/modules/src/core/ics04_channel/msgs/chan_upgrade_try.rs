@@ -0,0 +1,180 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	core::{
+		ics04_channel::{error::Error, upgrade::UpgradeFields},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+	},
+	prelude::*,
+	proofs::Proofs,
+	signer::Signer,
+	tx_msg::Msg,
+};
+use core::str::FromStr;
+
+use ibc_proto::ibc::core::channel::v1::MsgChannelUpgradeTry as RawMsgChannelUpgradeTry;
+use tendermint_proto::Protobuf;
+
+pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelUpgradeTry";
+
+///
+/// Message definition for the second step in the channel upgrade handshake (`ChanUpgradeTry`
+/// datagram).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MsgChannelUpgradeTry {
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub proposed_upgrade_connection_hops: Vec<ConnectionId>,
+	pub counterparty_upgrade_fields: UpgradeFields,
+	pub counterparty_upgrade_sequence: u64,
+	/// Proof of the counterparty channel end in `proofs.object_proof` and of the counterparty
+	/// upgrade in `proofs.other_proof`
+	pub proofs: Proofs,
+	pub signer: Signer,
+}
+
+impl Msg for MsgChannelUpgradeTry {
+	type ValidationError = Error;
+	type Raw = RawMsgChannelUpgradeTry;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		TYPE_URL.to_string()
+	}
+}
+
+impl Protobuf<RawMsgChannelUpgradeTry> for MsgChannelUpgradeTry {}
+
+impl TryFrom<RawMsgChannelUpgradeTry> for MsgChannelUpgradeTry {
+	type Error = Error;
+
+	fn try_from(raw_msg: RawMsgChannelUpgradeTry) -> Result<Self, Self::Error> {
+		let proofs = Proofs::new(
+			raw_msg.proof_channel.try_into().map_err(Error::invalid_proof)?,
+			None,
+			None,
+			Some(raw_msg.proof_upgrade.try_into().map_err(Error::invalid_proof)?),
+			raw_msg.proof_height.ok_or_else(Error::missing_height)?.into(),
+		)
+		.map_err(Error::invalid_proof)?;
+
+		let proposed_upgrade_connection_hops = raw_msg
+			.proposed_upgrade_connection_hops
+			.into_iter()
+			.map(|conn_id| ConnectionId::from_str(conn_id.as_str()))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(Error::identifier)?;
+
+		Ok(MsgChannelUpgradeTry {
+			port_id: raw_msg.port_id.parse().map_err(Error::identifier)?,
+			channel_id: raw_msg.channel_id.parse().map_err(Error::identifier)?,
+			proposed_upgrade_connection_hops,
+			counterparty_upgrade_fields: raw_msg
+				.counterparty_upgrade_fields
+				.ok_or_else(Error::missing_upgrade_fields)?
+				.try_into()?,
+			counterparty_upgrade_sequence: raw_msg.counterparty_upgrade_sequence,
+			proofs,
+			signer: raw_msg.signer.parse().map_err(Error::signer)?,
+		})
+	}
+}
+
+impl From<MsgChannelUpgradeTry> for RawMsgChannelUpgradeTry {
+	fn from(domain_msg: MsgChannelUpgradeTry) -> Self {
+		RawMsgChannelUpgradeTry {
+			port_id: domain_msg.port_id.to_string(),
+			channel_id: domain_msg.channel_id.to_string(),
+			proposed_upgrade_connection_hops: domain_msg
+				.proposed_upgrade_connection_hops
+				.iter()
+				.map(|v| v.as_str().to_string())
+				.collect(),
+			counterparty_upgrade_fields: Some(domain_msg.counterparty_upgrade_fields.into()),
+			counterparty_upgrade_sequence: domain_msg.counterparty_upgrade_sequence,
+			proof_channel: domain_msg.proofs.object_proof().clone().into(),
+			proof_upgrade: domain_msg
+				.proofs
+				.other_proof()
+				.clone()
+				.map_or_else(Vec::new, |proof| proof.into()),
+			proof_height: Some(domain_msg.proofs.height().into()),
+			signer: domain_msg.signer.to_string(),
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test_util {
+	use crate::{
+		core::ics24_host::identifier::{ChannelId, PortId},
+		prelude::*,
+		test_utils::{get_dummy_bech32_account, get_dummy_proof},
+	};
+	use ibc_proto::ibc::core::{
+		channel::v1::{MsgChannelUpgradeTry as RawMsgChannelUpgradeTry, Order, UpgradeFields},
+		client::v1::Height,
+	};
+
+	/// Returns a dummy `RawMsgChannelUpgradeTry`, for testing only!
+	pub fn get_dummy_raw_msg_chan_upgrade_try(proof_height: u64) -> RawMsgChannelUpgradeTry {
+		RawMsgChannelUpgradeTry {
+			port_id: PortId::default().to_string(),
+			channel_id: ChannelId::default().to_string(),
+			proposed_upgrade_connection_hops: vec!["connection-0".to_string()],
+			counterparty_upgrade_fields: Some(UpgradeFields {
+				ordering: Order::Unordered as i32,
+				connection_hops: vec!["connection-0".to_string()],
+				version: "ics20-1".to_string(),
+			}),
+			counterparty_upgrade_sequence: 1,
+			proof_channel: get_dummy_proof(),
+			proof_upgrade: get_dummy_proof(),
+			proof_height: Some(Height { revision_number: 0, revision_height: proof_height }),
+			signer: get_dummy_bech32_account(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::prelude::*;
+	use ibc_proto::ibc::core::channel::v1::MsgChannelUpgradeTry as RawMsgChannelUpgradeTry;
+	use test_log::test;
+
+	use super::{test_util::get_dummy_raw_msg_chan_upgrade_try, MsgChannelUpgradeTry};
+
+	#[test]
+	fn parse_bad_msg_chan_upgrade_try() {
+		let raw = RawMsgChannelUpgradeTry {
+			proof_upgrade: Vec::new(),
+			..get_dummy_raw_msg_chan_upgrade_try(10)
+		};
+		assert!(MsgChannelUpgradeTry::try_from(raw).is_err());
+	}
+
+	#[test]
+	fn to_and_from() {
+		let raw = get_dummy_raw_msg_chan_upgrade_try(100);
+		let msg = MsgChannelUpgradeTry::try_from(raw.clone()).unwrap();
+		let raw_back = RawMsgChannelUpgradeTry::from(msg.clone());
+		let msg_back = MsgChannelUpgradeTry::try_from(raw_back.clone()).unwrap();
+		assert_eq!(raw, raw_back);
+		assert_eq!(msg, msg_back);
+	}
+}
@@ -18,7 +18,7 @@ use core::str::FromStr;
 
 use serde_derive::{Deserialize, Serialize};
 
-use ibc_proto::ibc::core::channel::v1::Packet as RawPacket;
+use ibc_proto::ibc::core::channel::v1::{Packet as RawPacket, PacketId as RawPacketId};
 
 use crate::{
 	core::{
@@ -57,6 +57,19 @@ pub enum PacketResult {
 #[derive(Clone, Debug)]
 pub enum Receipt {
 	Ok,
+	/// Written for a packet that timed out on an ordered channel allowing timeouts, proves to the
+	/// sender that the packet was skipped.
+	Timeout,
+}
+
+impl Receipt {
+	/// The value of the receipt in the provable store
+	pub fn as_bytes(&self) -> &'static [u8] {
+		match self {
+			Receipt::Ok => b"Ok",
+			Receipt::Timeout => b"Timeout",
+		}
+	}
 }
 
 impl core::fmt::Display for PacketMsgType {
@@ -116,6 +129,51 @@ impl core::fmt::Display for Sequence {
 	}
 }
 
+/// Identifies a packet by its source port, source channel and sequence.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct PacketId {
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub sequence: Sequence,
+}
+
+impl PacketId {
+	pub fn new(port_id: PortId, channel_id: ChannelId, sequence: Sequence) -> Self {
+		Self { port_id, channel_id, sequence }
+	}
+}
+
+impl core::fmt::Display for PacketId {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		write!(f, "{}/{}/{}", self.port_id, self.channel_id, self.sequence)
+	}
+}
+
+impl TryFrom<RawPacketId> for PacketId {
+	type Error = Error;
+
+	fn try_from(raw: RawPacketId) -> Result<Self, Self::Error> {
+		if Sequence::from(raw.sequence).is_zero() {
+			return Err(Error::zero_packet_sequence())
+		}
+		Ok(PacketId {
+			port_id: raw.port_id.parse().map_err(Error::identifier)?,
+			channel_id: raw.channel_id.parse().map_err(Error::identifier)?,
+			sequence: raw.sequence.into(),
+		})
+	}
+}
+
+impl From<PacketId> for RawPacketId {
+	fn from(packet_id: PacketId) -> Self {
+		RawPacketId {
+			port_id: packet_id.port_id.to_string(),
+			channel_id: packet_id.channel_id.to_string(),
+			sequence: packet_id.sequence.into(),
+		}
+	}
+}
+
 #[derive(Clone, Default, Hash, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Packet {
 	pub sequence: Sequence,
@@ -179,6 +237,11 @@ pub enum TimeoutVariant {
 }
 
 impl Packet {
+	/// Identifier of the packet on its source chain.
+	pub fn id(&self) -> PacketId {
+		PacketId::new(self.source_port.clone(), self.source_channel, self.sequence)
+	}
+
 	/// Checks whether a packet from a
 	/// [`SendPacket`](crate::core::ics04_channel::events::SendPacket)
 	/// event is timed-out relative to the current state of the
@@ -0,0 +1,222 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Types of the ICS4 channel upgrade handshake, which lets an open channel renegotiate its
+//! ordering, connection hops and version without being closed.
+
+use crate::prelude::*;
+
+use core::str::FromStr;
+
+use ibc_proto::ibc::core::channel::v1::{
+	ErrorReceipt as RawErrorReceipt, Timeout as RawTimeout, Upgrade as RawUpgrade,
+	UpgradeFields as RawUpgradeFields,
+};
+use serde::{Deserialize, Serialize};
+use tendermint_proto::Protobuf;
+
+use crate::{
+	core::{
+		ics04_channel::{channel::Order, error::Error, packet::Sequence, Version},
+		ics24_host::identifier::ConnectionId,
+	},
+	timestamp::{Expiry::Expired, Timestamp},
+	Height,
+};
+
+/// The parameters of a channel that can be renegotiated by an upgrade.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradeFields {
+	pub ordering: Order,
+	pub connection_hops: Vec<ConnectionId>,
+	pub version: Version,
+}
+
+impl UpgradeFields {
+	pub fn new(ordering: Order, connection_hops: Vec<ConnectionId>, version: Version) -> Self {
+		Self { ordering, connection_hops, version }
+	}
+}
+
+impl Protobuf<RawUpgradeFields> for UpgradeFields {}
+
+impl TryFrom<RawUpgradeFields> for UpgradeFields {
+	type Error = Error;
+
+	fn try_from(value: RawUpgradeFields) -> Result<Self, Self::Error> {
+		let connection_hops = value
+			.connection_hops
+			.into_iter()
+			.map(|conn_id| ConnectionId::from_str(conn_id.as_str()))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(Error::identifier)?;
+
+		Ok(UpgradeFields {
+			ordering: Order::from_i32(value.ordering)?,
+			connection_hops,
+			version: value.version.into(),
+		})
+	}
+}
+
+impl From<UpgradeFields> for RawUpgradeFields {
+	fn from(value: UpgradeFields) -> Self {
+		RawUpgradeFields {
+			ordering: value.ordering as i32,
+			connection_hops: value.connection_hops.iter().map(|v| v.as_str().to_string()).collect(),
+			version: value.version.to_string(),
+		}
+	}
+}
+
+/// Height and timestamp of the counterparty chain after which an upgrade is aborted.
+/// A zero height or an empty timestamp disables the corresponding check.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradeTimeout {
+	pub height: Height,
+	pub timestamp: Timestamp,
+}
+
+impl UpgradeTimeout {
+	pub fn new(height: Height, timestamp: Timestamp) -> Self {
+		Self { height, timestamp }
+	}
+
+	/// Returns true if the timeout has been reached on a chain at the given height and timestamp.
+	pub fn has_passed(&self, height: Height, timestamp: &Timestamp) -> bool {
+		(self.height != Height::zero() && self.height <= height) ||
+			(self.timestamp != Timestamp::none() &&
+				timestamp.check_expiry(&self.timestamp) == Expired)
+	}
+}
+
+impl Protobuf<RawTimeout> for UpgradeTimeout {}
+
+impl TryFrom<RawTimeout> for UpgradeTimeout {
+	type Error = Error;
+
+	fn try_from(value: RawTimeout) -> Result<Self, Self::Error> {
+		Ok(UpgradeTimeout {
+			height: value.height.map(Into::into).unwrap_or_else(Height::zero),
+			timestamp: Timestamp::from_nanoseconds(value.timestamp)
+				.map_err(Error::invalid_packet_timestamp)?,
+		})
+	}
+}
+
+impl From<UpgradeTimeout> for RawTimeout {
+	fn from(value: UpgradeTimeout) -> Self {
+		RawTimeout { height: Some(value.height.into()), timestamp: value.timestamp.nanoseconds() }
+	}
+}
+
+/// An upgrade proposed by one end of a channel. It is stored by the upgrading chain for the
+/// duration of the handshake and proven to the counterparty.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Upgrade {
+	pub fields: UpgradeFields,
+	pub timeout: UpgradeTimeout,
+	/// The next sequence that will be used to send packets once the upgrade is complete, every
+	/// packet with a lower sequence must be flushed before the upgrade can be opened.
+	pub next_sequence_send: Sequence,
+}
+
+impl Upgrade {
+	pub fn new(
+		fields: UpgradeFields,
+		timeout: UpgradeTimeout,
+		next_sequence_send: Sequence,
+	) -> Self {
+		Self { fields, timeout, next_sequence_send }
+	}
+}
+
+impl Protobuf<RawUpgrade> for Upgrade {}
+
+impl TryFrom<RawUpgrade> for Upgrade {
+	type Error = Error;
+
+	fn try_from(value: RawUpgrade) -> Result<Self, Self::Error> {
+		Ok(Upgrade {
+			fields: value.fields.ok_or_else(Error::missing_upgrade_fields)?.try_into()?,
+			timeout: value.timeout.ok_or_else(Error::missing_upgrade_timeout)?.try_into()?,
+			next_sequence_send: value.next_sequence_send.into(),
+		})
+	}
+}
+
+impl From<Upgrade> for RawUpgrade {
+	fn from(value: Upgrade) -> Self {
+		RawUpgrade {
+			fields: Some(value.fields.into()),
+			timeout: Some(value.timeout.into()),
+			next_sequence_send: value.next_sequence_send.into(),
+		}
+	}
+}
+
+/// Written by a chain that aborted an upgrade, so that the counterparty can cancel it as well.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorReceipt {
+	/// The upgrade sequence of the aborted upgrade
+	pub sequence: u64,
+	pub message: String,
+}
+
+impl ErrorReceipt {
+	pub fn new(sequence: u64, message: impl ToString) -> Self {
+		Self { sequence, message: message.to_string() }
+	}
+}
+
+impl Protobuf<RawErrorReceipt> for ErrorReceipt {}
+
+impl From<RawErrorReceipt> for ErrorReceipt {
+	fn from(value: RawErrorReceipt) -> Self {
+		ErrorReceipt { sequence: value.sequence, message: value.message }
+	}
+}
+
+impl From<ErrorReceipt> for RawErrorReceipt {
+	fn from(value: ErrorReceipt) -> Self {
+		RawErrorReceipt { sequence: value.sequence, message: value.message }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn upgrade_to_and_from() {
+		let upgrade = Upgrade::new(
+			UpgradeFields::new(
+				Order::Unordered,
+				vec![ConnectionId::default()],
+				Version::new("ics20-1".to_string()),
+			),
+			UpgradeTimeout::new(Height::new(0, 10), Timestamp::from_nanoseconds(1).unwrap()),
+			Sequence::from(5),
+		);
+		let raw = RawUpgrade::from(upgrade.clone());
+		assert_eq!(Upgrade::try_from(raw).unwrap(), upgrade);
+	}
+
+	#[test]
+	fn upgrade_timeout_has_passed() {
+		let timeout = UpgradeTimeout::new(Height::new(0, 10), Timestamp::none());
+		assert!(!timeout.has_passed(Height::new(0, 9), &Timestamp::none()));
+		assert!(timeout.has_passed(Height::new(0, 10), &Timestamp::none()));
+	}
+}
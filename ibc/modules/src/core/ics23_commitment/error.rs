@@ -48,6 +48,16 @@ define_error! {
 			|_| { "invalid merkle proof" },
 
 		VerificationFailure
-			|_| { "proof verification failed" }
+			|_| { "proof verification failed" },
+
+		EmptyBatch
+			|_| { "empty batch of keys to verify" },
+
+		InconsistentBatchPaths
+			|_| { "keys of a batch proof must only differ in their leaf key" },
+
+		DuplicateBatchKey
+			{ key: String }
+			|e| { format_args!("key {} appears more than once in the batch", e.key) },
 	}
 }
@@ -20,10 +20,12 @@ use crate::{
 	},
 	prelude::*,
 };
+use alloc::collections::{BTreeMap, BTreeSet};
 use core::marker::PhantomData;
 use ibc_proto::ibc::core::commitment::v1::{MerklePath, MerkleProof as RawMerkleProof, MerkleRoot};
 use ics23::{
-	calculate_existence_root, commitment_proof::Proof, verify_membership, verify_non_membership,
+	batch_entry, calculate_existence_root, commitment_proof::Proof, decompress,
+	verify_batch_membership, verify_batch_non_membership, verify_membership, verify_non_membership,
 	CommitmentProof, HostFunctionsProvider, NonExistenceProof,
 };
 
@@ -178,6 +180,124 @@ impl<H: HostFunctionsProvider> MerkleProof<H> {
 			_ => Err(Error::invalid_merkle_proof()),
 		}
 	}
+
+	/// Verifies a proof whose lowest subtree proof is a (possibly compressed) batch proof, that
+	/// every value is stored at its path. All the paths must only differ in their leaf key, as
+	/// they're proven in the same subtree.
+	pub fn verify_batch_membership(
+		&self,
+		specs: &ProofSpecs,
+		root: MerkleRoot,
+		items: Vec<(MerklePath, Vec<u8>)>,
+	) -> Result<(), Error> {
+		if items.iter().any(|(_, value)| value.is_empty()) {
+			return Err(Error::empty_verified_value())
+		}
+		let (keys, leaf_keys) =
+			self.batch_keys(specs, &root, items.iter().map(|(keys, _)| keys))?;
+		let items = leaf_keys
+			.iter()
+			.zip(items.iter())
+			.map(|(key, (_, value))| (key.as_bytes(), value.as_slice()))
+			.collect::<BTreeMap<_, _>>();
+
+		let proof = &self.proofs[0];
+		let spec = &Vec::<ics23::ProofSpec>::from(specs.clone())[0];
+		let subroot = calculate_batch_root::<H>(proof)?;
+		if !verify_batch_membership::<H>(proof, spec, &subroot, items) {
+			return Err(Error::verification_failure())
+		}
+		// verify membership proofs starting from index 1 with value = subroot
+		self.verify_membership(specs, root, keys, subroot, 1)
+	}
+
+	/// Verifies a proof whose lowest subtree proof is a (possibly compressed) batch proof, that
+	/// nothing is stored at any of the paths. All the paths must only differ in their leaf key.
+	pub fn verify_batch_non_membership(
+		&self,
+		specs: &ProofSpecs,
+		root: MerkleRoot,
+		keys: Vec<MerklePath>,
+	) -> Result<(), Error> {
+		let (keys, leaf_keys) = self.batch_keys(specs, &root, keys.iter())?;
+		let leaf_keys = leaf_keys.iter().map(|key| key.as_bytes()).collect::<Vec<_>>();
+
+		let proof = &self.proofs[0];
+		let spec = &Vec::<ics23::ProofSpec>::from(specs.clone())[0];
+		let subroot = calculate_batch_root::<H>(proof)?;
+		if !verify_batch_non_membership::<H>(proof, spec, &subroot, &leaf_keys) {
+			return Err(Error::verification_failure())
+		}
+		// verify membership proofs starting from index 1 with value = subroot
+		self.verify_membership(specs, root, keys, subroot, 1)
+	}
+
+	/// Validates the arguments of a batch verification, returns the path shared by all the keys
+	/// of the batch and their leaf keys. A key can only appear once, the values of duplicates would
+	/// be merged rather than all verified.
+	fn batch_keys<'a>(
+		&self,
+		specs: &ProofSpecs,
+		root: &MerkleRoot,
+		mut keys: impl Iterator<Item = &'a MerklePath>,
+	) -> Result<(MerklePath, Vec<String>), Error> {
+		if self.proofs.is_empty() {
+			return Err(Error::empty_merkle_proof())
+		}
+		if root.hash.is_empty() {
+			return Err(Error::empty_merkle_root())
+		}
+		let num = self.proofs.len();
+		if Vec::<ics23::ProofSpec>::from(specs.clone()).len() != num {
+			return Err(Error::number_of_specs_mismatch())
+		}
+		let first = keys.next().ok_or_else(Error::empty_batch)?;
+		if first.key_path.len() != num {
+			return Err(Error::number_of_keys_mismatch())
+		}
+		// keys are represented from root-to-leaf
+		let mut leaf_keys = vec![first.key_path[num - 1].clone()];
+		for path in keys {
+			if path.key_path.len() != num {
+				return Err(Error::number_of_keys_mismatch())
+			}
+			if path.key_path[..num - 1] != first.key_path[..num - 1] {
+				return Err(Error::inconsistent_batch_paths())
+			}
+			leaf_keys.push(path.key_path[num - 1].clone());
+		}
+		let duplicate = {
+			let mut unique = BTreeSet::new();
+			leaf_keys.iter().find(|key| !unique.insert(*key)).cloned()
+		};
+		if let Some(key) = duplicate {
+			return Err(Error::duplicate_batch_key(key))
+		}
+		Ok((first.clone(), leaf_keys))
+	}
+}
+
+/// Calculates the root of the subtree a batch proof was made against, from any of its entries.
+fn calculate_batch_root<H: HostFunctionsProvider>(
+	proof: &CommitmentProof,
+) -> Result<Vec<u8>, Error> {
+	let proof = decompress(proof).map_err(|_| Error::invalid_merkle_proof())?;
+	match &proof.proof {
+		Some(Proof::Exist(existence_proof)) => calculate_existence_root::<H>(existence_proof)
+			.map_err(|_| Error::invalid_merkle_proof()),
+		Some(Proof::Nonexist(non_existence_proof)) =>
+			calculate_non_existence_root::<H>(non_existence_proof),
+		Some(Proof::Batch(batch_proof)) =>
+			match batch_proof.entries.first().and_then(|entry| entry.proof.as_ref()) {
+				Some(batch_entry::Proof::Exist(existence_proof)) =>
+					calculate_existence_root::<H>(existence_proof)
+						.map_err(|_| Error::invalid_merkle_proof()),
+				Some(batch_entry::Proof::Nonexist(non_existence_proof)) =>
+					calculate_non_existence_root::<H>(non_existence_proof),
+				None => Err(Error::invalid_merkle_proof()),
+			},
+		_ => Err(Error::invalid_merkle_proof()),
+	}
 }
 
 // TODO move to ics23
@@ -237,3 +357,187 @@ fn calculate_non_existence_root<H: HostFunctionsProvider>(
 //         RawMerkleProof { proof: value.proof }
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::ics23_commitment::error::ErrorDetail;
+	use ics23::{compress, BatchEntry, BatchProof, ExistenceProof, HashOp, InnerOp};
+	use sha2::Digest;
+
+	#[derive(Clone, Debug, PartialEq)]
+	struct Sha256;
+
+	impl HostFunctionsProvider for Sha256 {
+		fn sha2_256(message: &[u8]) -> [u8; 32] {
+			sha2::Sha256::digest(message).into()
+		}
+
+		fn sha2_512(_message: &[u8]) -> [u8; 64] {
+			unimplemented!()
+		}
+
+		fn sha2_512_truncated(_message: &[u8]) -> [u8; 32] {
+			unimplemented!()
+		}
+
+		fn sha3_512(_message: &[u8]) -> [u8; 64] {
+			unimplemented!()
+		}
+
+		fn ripemd160(_message: &[u8]) -> [u8; 20] {
+			unimplemented!()
+		}
+	}
+
+	const STORE: &str = "ibc";
+
+	/// Store keys, in the order of the tree
+	const KEYS: [&str; 4] = ["b", "d", "f", "h"];
+
+	fn value(key: &str) -> Vec<u8> {
+		format!("value of {key}").into_bytes()
+	}
+
+	fn hash(data: &[u8]) -> Vec<u8> {
+		Sha256::sha2_256(data).to_vec()
+	}
+
+	/// Builds a tree of `items` with the tendermint spec, `items` must be sorted and a power of
+	/// two. Returns its root and the existence proof of every item.
+	fn tree(items: &[(&str, Vec<u8>)]) -> (Vec<u8>, Vec<ExistenceProof>) {
+		let leaf = ics23::tendermint_spec().leaf_spec.unwrap();
+		let mut proofs = items
+			.iter()
+			.map(|(key, value)| ExistenceProof {
+				key: key.as_bytes().to_vec(),
+				value: value.clone(),
+				leaf: Some(leaf.clone()),
+				path: vec![],
+			})
+			.collect::<Vec<_>>();
+		let mut nodes = proofs
+			.iter()
+			.map(|proof| {
+				let mut preimage = vec![0, proof.key.len() as u8];
+				preimage.extend(&proof.key);
+				preimage.push(32);
+				preimage.extend(hash(&proof.value));
+				hash(&preimage)
+			})
+			.collect::<Vec<_>>();
+		// number of leaves under each node
+		let mut width = 1;
+		while nodes.len() > 1 {
+			nodes = nodes
+				.chunks(2)
+				.enumerate()
+				.map(|(index, pair)| {
+					let (left, right) = (&pair[0], &pair[1]);
+					let leaves = index * 2 * width..(index + 1) * 2 * width;
+					for (offset, proof) in proofs[leaves].iter_mut().enumerate() {
+						let (prefix, suffix) = if offset < width {
+							(vec![1], right.clone())
+						} else {
+							([vec![1], left.clone()].concat(), vec![])
+						};
+						proof.path.push(InnerOp { hash: HashOp::Sha256 as i32, prefix, suffix });
+					}
+					hash(&[vec![1], left.clone(), right.clone()].concat())
+				})
+				.collect();
+			width *= 2;
+		}
+		(nodes.remove(0), proofs)
+	}
+
+	/// A store of [`KEYS`] under [`STORE`], next to another store. Returns the root and the
+	/// existence proofs of the store keys and of the store itself.
+	fn stores() -> (MerkleRoot, Vec<ExistenceProof>, CommitmentProof) {
+		let items = KEYS.iter().map(|key| (*key, value(key))).collect::<Vec<_>>();
+		let (store_root, proofs) = tree(&items);
+		let (root, stores) = tree(&[("bank", b"bank root".to_vec()), (STORE, store_root)]);
+		let store_proof =
+			CommitmentProof { proof: Some(Proof::Exist(stores.into_iter().nth(1).unwrap())) };
+		(MerkleRoot { hash: root }, proofs, store_proof)
+	}
+
+	fn merkle_proof(
+		entries: Vec<batch_entry::Proof>,
+		store_proof: CommitmentProof,
+	) -> MerkleProof<Sha256> {
+		let entries = entries.into_iter().map(|proof| BatchEntry { proof: Some(proof) }).collect();
+		let batch = CommitmentProof { proof: Some(Proof::Batch(BatchProof { entries })) };
+		MerkleProof { proofs: vec![batch, store_proof], _phantom: PhantomData }
+	}
+
+	fn specs() -> ProofSpecs {
+		vec![ics23::tendermint_spec(), ics23::tendermint_spec()].into()
+	}
+
+	fn path(key: &str) -> MerklePath {
+		MerklePath { key_path: vec![STORE.to_string(), key.to_string()] }
+	}
+
+	#[test]
+	fn verifies_batch_membership_proofs() {
+		let (root, proofs, store_proof) = stores();
+		let entries = proofs[1..3].iter().cloned().map(batch_entry::Proof::Exist).collect();
+		let proof = merkle_proof(entries, store_proof);
+		let items = || vec![(path("d"), value("d")), (path("f"), value("f"))];
+
+		proof.verify_batch_membership(&specs(), root.clone(), items()).unwrap();
+		let compressed = MerkleProof::<Sha256> {
+			proofs: vec![compress(&proof.proofs[0]), proof.proofs[1].clone()],
+			_phantom: PhantomData,
+		};
+		compressed.verify_batch_membership(&specs(), root.clone(), items()).unwrap();
+
+		let cases = [
+			vec![(path("d"), value("f"))],
+			vec![(path("b"), value("b"))],
+			vec![(MerklePath { key_path: vec!["bank".to_string(), "d".to_string()] }, value("d"))],
+		];
+		for items in cases {
+			assert!(proof.verify_batch_membership(&specs(), root.clone(), items).is_err());
+		}
+		let other_root = MerkleRoot { hash: hash(b"other root") };
+		assert!(proof.verify_batch_membership(&specs(), other_root, items()).is_err());
+	}
+
+	#[test]
+	fn rejects_batches_with_duplicate_keys() {
+		let (root, proofs, store_proof) = stores();
+		let entries = proofs[1..3].iter().cloned().map(batch_entry::Proof::Exist).collect();
+		let proof = merkle_proof(entries, store_proof);
+		// merged into a map, only the last value of "d" would be verified
+		let items = vec![(path("d"), b"forged".to_vec()), (path("d"), value("d"))];
+		let error = proof.verify_batch_membership(&specs(), root.clone(), items).unwrap_err();
+		assert!(matches!(error.detail(), ErrorDetail::DuplicateBatchKey(e) if e.key == "d"));
+
+		let paths = vec![path("c"), path("c")];
+		assert!(proof.verify_batch_non_membership(&specs(), root, paths).is_err());
+	}
+
+	#[test]
+	fn verifies_batch_non_membership_proofs() {
+		let (root, proofs, store_proof) = stores();
+		let non_existence = |key: &str, left: Option<usize>, right: Option<usize>| {
+			batch_entry::Proof::Nonexist(NonExistenceProof {
+				key: key.as_bytes().to_vec(),
+				left: left.map(|index| proofs[index].clone()),
+				right: right.map(|index| proofs[index].clone()),
+			})
+		};
+		let entries = vec![non_existence("a", None, Some(0)), non_existence("e", Some(1), Some(2))];
+		let proof = merkle_proof(entries, store_proof);
+
+		proof
+			.verify_batch_non_membership(&specs(), root.clone(), vec![path("a"), path("e")])
+			.unwrap();
+		for paths in [vec![path("d")], vec![path("c")], vec![path("a"), path("z")]] {
+			assert!(proof.verify_batch_non_membership(&specs(), root.clone(), paths).is_err());
+		}
+		assert!(proof.verify_batch_non_membership(&specs(), root, vec![]).is_err());
+	}
+}
@@ -186,6 +186,17 @@ impl FromStr for ClientId {
 	}
 }
 
+impl ClientId {
+	/// Parses an identifier validated according to the given [`ValidationPolicy`], e.g. one
+	/// chosen by a counterparty chain.
+	pub fn from_str_with_policy(
+		s: &str,
+		policy: ValidationPolicy,
+	) -> Result<Self, ValidationError> {
+		validate_client_identifier_with_policy(s, policy).map(|_| Self(s.to_string()))
+	}
+}
+
 #[cfg(not(test))]
 impl Default for ClientId {
 	fn default() -> Self {
@@ -257,6 +268,17 @@ impl FromStr for ConnectionId {
 	}
 }
 
+impl ConnectionId {
+	/// Parses an identifier validated according to the given [`ValidationPolicy`], e.g. one
+	/// chosen by a counterparty chain.
+	pub fn from_str_with_policy(
+		s: &str,
+		policy: ValidationPolicy,
+	) -> Result<Self, ValidationError> {
+		validate_connection_identifier_with_policy(s, policy).map(|_| Self(s.to_string()))
+	}
+}
+
 impl Default for ConnectionId {
 	fn default() -> Self {
 		Self::new(0)
@@ -312,6 +334,17 @@ impl FromStr for PortId {
 	}
 }
 
+impl PortId {
+	/// Parses an identifier validated according to the given [`ValidationPolicy`], e.g. one
+	/// chosen by a counterparty chain.
+	pub fn from_str_with_policy(
+		s: &str,
+		policy: ValidationPolicy,
+	) -> Result<Self, ValidationError> {
+		validate_port_identifier_with_policy(s, policy).map(|_| Self(s.to_string()))
+	}
+}
+
 impl AsRef<str> for PortId {
 	fn as_ref(&self) -> &str {
 		self.0.as_str()
@@ -59,6 +59,8 @@ pub enum Path {
 	Acks(AcksPath),
 	Receipts(ReceiptsPath),
 	Upgrade(ClientUpgradePath),
+	ChannelUpgrade(ChannelUpgradePath),
+	ChannelUpgradeError(ChannelUpgradeErrorPath),
 	Outside(OutsidePath),
 }
 
@@ -141,6 +143,14 @@ pub struct ReceiptsPath {
 	pub sequence: Sequence,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "channelUpgrades/upgrades/ports/{}/channels/{}", _0, _1)]
+pub struct ChannelUpgradePath(pub PortId, pub ChannelId);
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "channelUpgrades/upgradeError/ports/{}/channels/{}", _0, _1)]
+pub struct ChannelUpgradeErrorPath(pub PortId, pub ChannelId);
+
 /// Paths that are specific for client upgrades.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
 pub enum ClientUpgradePath {
@@ -195,6 +205,7 @@ impl FromStr for Path {
 			.or_else(|| parse_acks(&components))
 			.or_else(|| parse_receipts(&components))
 			.or_else(|| parse_upgrades(&components))
+			.or_else(|| parse_channel_upgrades(&components))
 			.or_else(|| parse_outside_paths(&components))
 			.ok_or_else(|| PathError::parse_failure(s.to_string()))
 	}
@@ -539,6 +550,36 @@ fn parse_upgrades(components: &[&str]) -> Option<Path> {
 	}
 }
 
+fn parse_channel_upgrades(components: &[&str]) -> Option<Path> {
+	if components.len() != 6 {
+		return None
+	}
+
+	let first = match components.first() {
+		Some(f) => *f,
+		None => return None,
+	};
+
+	if first != "channelUpgrades" {
+		return None
+	}
+
+	let port = parse_ports(&components[2..=3]);
+	let channel = parse_channels(&components[4..=5]);
+
+	let port_id =
+		if let Some(Path::Ports(PortsPath(port_id))) = port { port_id } else { return None };
+
+	let channel_id =
+		if let Some(SubPath::Channels(channel_id)) = channel { channel_id } else { return None };
+
+	match components[1] {
+		"upgrades" => Some(ChannelUpgradePath(port_id, channel_id).into()),
+		"upgradeError" => Some(ChannelUpgradeErrorPath(port_id, channel_id).into()),
+		_ => None,
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -915,4 +956,23 @@ mod tests {
 			Path::Upgrade(ClientUpgradePath::UpgradedClientConsensusState(0)),
 		);
 	}
+
+	#[test]
+	fn channel_upgrade_paths_parse() {
+		let path = Path::from_str("channelUpgrades/upgrades/ports/defaultPort/channels/channel-0");
+		assert_eq!(
+			path.unwrap(),
+			Path::ChannelUpgrade(ChannelUpgradePath(PortId::default(), ChannelId::default())),
+		);
+
+		let path =
+			Path::from_str("channelUpgrades/upgradeError/ports/defaultPort/channels/channel-0");
+		assert_eq!(
+			path.unwrap(),
+			Path::ChannelUpgradeError(ChannelUpgradeErrorPath(
+				PortId::default(),
+				ChannelId::default()
+			)),
+		);
+	}
 }
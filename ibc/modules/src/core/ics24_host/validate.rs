@@ -19,12 +19,39 @@ use super::error::ValidationError as Error;
 /// Path separator (ie. forward slash '/')
 const PATH_SEPARATOR: char = '/';
 const VALID_SPECIAL_CHARS: &str = "._+-#[]<>";
+/// Maximum length of an identifier accepted under [`ValidationPolicy::CounterpartyCompatible`].
+const MAX_COMPATIBLE_LENGTH: usize = 128;
+
+/// How strictly identifiers are validated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValidationPolicy {
+	/// Enforce the ICS-24 length bounds and character set. Used for identifiers generated by
+	/// this chain.
+	#[default]
+	Strict,
+	/// Accept identifiers chosen by counterparty chains that don't follow ICS-24 to the letter,
+	/// only rejecting the ones that can't be used as a path segment: empty identifiers,
+	/// identifiers containing the path separator or non-printable characters, and identifiers
+	/// longer than 128 characters.
+	CounterpartyCompatible,
+}
 
 /// Default validator function for identifiers.
 ///
 /// A valid identifier only contain lowercase alphabetic characters, and be of a given min and max
 /// length.
 pub fn validate_identifier(id: &str, min: usize, max: usize) -> Result<(), Error> {
+	validate_identifier_with_policy(id, min, max, ValidationPolicy::Strict)
+}
+
+/// Validates an identifier according to the given [`ValidationPolicy`]. The `min` and `max`
+/// lengths only apply to the strict policy.
+pub fn validate_identifier_with_policy(
+	id: &str,
+	min: usize,
+	max: usize,
+	policy: ValidationPolicy,
+) -> Result<(), Error> {
 	assert!(max >= min);
 
 	// Check identifier is not empty
@@ -37,17 +64,35 @@ pub fn validate_identifier(id: &str, min: usize, max: usize) -> Result<(), Error
 		return Err(Error::contain_separator(id.to_string()))
 	}
 
-	// Check identifier length is between given min/max
-	if id.len() < min || id.len() > max {
-		return Err(Error::invalid_length(id.to_string(), id.len(), min, max))
-	}
-
-	// Check that the identifier comprises only valid characters:
-	// - Alphanumeric
-	// - `.`, `_`, `+`, `-`, `#`
-	// - `[`, `]`, `<`, `>`
-	if !id.chars().all(|c| c.is_alphanumeric() || VALID_SPECIAL_CHARS.contains(c)) {
-		return Err(Error::invalid_character(id.to_string()))
+	match policy {
+		ValidationPolicy::Strict => {
+			// Check identifier length is between given min/max
+			if id.len() < min || id.len() > max {
+				return Err(Error::invalid_length(id.to_string(), id.len(), min, max))
+			}
+
+			// Check that the identifier comprises only valid characters:
+			// - Alphanumeric
+			// - `.`, `_`, `+`, `-`, `#`
+			// - `[`, `]`, `<`, `>`
+			if !id.chars().all(|c| c.is_alphanumeric() || VALID_SPECIAL_CHARS.contains(c)) {
+				return Err(Error::invalid_character(id.to_string()))
+			}
+		},
+		ValidationPolicy::CounterpartyCompatible => {
+			if id.len() > MAX_COMPATIBLE_LENGTH {
+				return Err(Error::invalid_length(
+					id.to_string(),
+					id.len(),
+					1,
+					MAX_COMPATIBLE_LENGTH,
+				))
+			}
+
+			if !id.chars().all(|c| c.is_ascii_graphic()) {
+				return Err(Error::invalid_character(id.to_string()))
+			}
+		},
 	}
 
 	// All good!
@@ -59,7 +104,15 @@ pub fn validate_identifier(id: &str, min: usize, max: usize) -> Result<(), Error
 /// A valid identifier must be between 9-64 characters and only contain lowercase
 /// alphabetic characters,
 pub fn validate_client_identifier(id: &str) -> Result<(), Error> {
-	validate_identifier(id, 9, 64)
+	validate_client_identifier_with_policy(id, ValidationPolicy::Strict)
+}
+
+/// Validator function for Client identifiers under the given [`ValidationPolicy`].
+pub fn validate_client_identifier_with_policy(
+	id: &str,
+	policy: ValidationPolicy,
+) -> Result<(), Error> {
+	validate_identifier_with_policy(id, 9, 64, policy)
 }
 
 /// Default validator function for Connection identifiers.
@@ -67,7 +120,15 @@ pub fn validate_client_identifier(id: &str) -> Result<(), Error> {
 /// A valid Identifier must be between 10-64 characters and only contain lowercase
 /// alphabetic characters,
 pub fn validate_connection_identifier(id: &str) -> Result<(), Error> {
-	validate_identifier(id, 10, 64)
+	validate_connection_identifier_with_policy(id, ValidationPolicy::Strict)
+}
+
+/// Validator function for Connection identifiers under the given [`ValidationPolicy`].
+pub fn validate_connection_identifier_with_policy(
+	id: &str,
+	policy: ValidationPolicy,
+) -> Result<(), Error> {
+	validate_identifier_with_policy(id, 10, 64, policy)
 }
 
 /// Default validator function for Port identifiers.
@@ -75,14 +136,24 @@ pub fn validate_connection_identifier(id: &str) -> Result<(), Error> {
 /// A valid Identifier must be between 2-128 characters and only contain lowercase
 /// alphabetic characters,
 pub fn validate_port_identifier(id: &str) -> Result<(), Error> {
-	validate_identifier(id, 2, 128)
+	validate_port_identifier_with_policy(id, ValidationPolicy::Strict)
+}
+
+/// Validator function for Port identifiers under the given [`ValidationPolicy`].
+pub fn validate_port_identifier_with_policy(
+	id: &str,
+	policy: ValidationPolicy,
+) -> Result<(), Error> {
+	validate_identifier_with_policy(id, 2, 128, policy)
 }
 
 #[cfg(test)]
 mod tests {
 	use crate::core::ics24_host::validate::{
-		validate_client_identifier, validate_connection_identifier, validate_identifier,
-		validate_port_identifier,
+		validate_client_identifier, validate_client_identifier_with_policy,
+		validate_connection_identifier, validate_connection_identifier_with_policy,
+		validate_identifier, validate_port_identifier, validate_port_identifier_with_policy,
+		ValidationPolicy,
 	};
 	use test_log::test;
 
@@ -154,4 +225,25 @@ mod tests {
 		let id = validate_identifier("id/1", 1, 10);
 		assert!(id.is_err())
 	}
+
+	#[test]
+	fn parse_counterparty_compatible_ids() {
+		let policy = ValidationPolicy::CounterpartyCompatible;
+		// too short for the strict policy
+		assert!(validate_client_identifier("client").is_err());
+		assert!(validate_client_identifier_with_policy("client", policy).is_ok());
+		assert!(validate_port_identifier_with_policy("p", policy).is_ok());
+		// characters outside of the strict set
+		assert!(validate_connection_identifier("connection@01").is_err());
+		assert!(validate_connection_identifier_with_policy("connection@01", policy).is_ok());
+	}
+
+	#[test]
+	fn parse_invalid_counterparty_compatible_ids() {
+		let policy = ValidationPolicy::CounterpartyCompatible;
+		assert!(validate_client_identifier_with_policy("", policy).is_err());
+		assert!(validate_client_identifier_with_policy("client/01", policy).is_err());
+		assert!(validate_client_identifier_with_policy("client 01", policy).is_err());
+		assert!(validate_port_identifier_with_policy(&"p".repeat(129), policy).is_err());
+	}
 }
@@ -15,6 +15,7 @@
 use crate::prelude::*;
 
 use crate::{
+	applications::cross_chain_queries::context::CrossChainQueryContext,
 	core::{
 		ics02_client::context::{ClientKeeper, ClientReader},
 		ics03_connection::context::{ConnectionKeeper, ConnectionReader},
@@ -47,7 +48,13 @@ pub trait ModuleCallbackContext: ConnectionReader + ChannelReader {}
 /// requires to be able to dispatch and process IBC messages. In other words, this is the
 /// representation of a chain from the perspective of the IBC module of that chain.
 pub trait Ics26Context:
-	Clone + ConnectionKeeper + ChannelKeeper + PortReader + ReaderContext + ModuleCallbackContext
+	Clone
+	+ ConnectionKeeper
+	+ ChannelKeeper
+	+ PortReader
+	+ ReaderContext
+	+ ModuleCallbackContext
+	+ CrossChainQueryContext
 {
 	type Router: Router;
 
@@ -180,6 +187,67 @@ pub trait Module: Send + Sync + AsAnyMut {
 		Ok(())
 	}
 
+	/// Called when this end proposes to upgrade the channel. Returns the version the module
+	/// proposes for the upgraded channel. Modules which do not support channel upgrades keep the
+	/// default implementation, which rejects the upgrade.
+	#[allow(clippy::too_many_arguments)]
+	fn on_chan_upgrade_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		_channel_id: &ChannelId,
+		_proposed_order: Order,
+		_proposed_connection_hops: &[ConnectionId],
+		_proposed_version: &Version,
+	) -> Result<Version, Error> {
+		Err(Error::upgrade_not_supported(port_id.clone()))
+	}
+
+	/// Called when the counterparty proposed to upgrade the channel. Returns the version the
+	/// module accepts for the upgraded channel.
+	#[allow(clippy::too_many_arguments)]
+	fn on_chan_upgrade_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		_channel_id: &ChannelId,
+		_proposed_order: Order,
+		_proposed_connection_hops: &[ConnectionId],
+		_counterparty_version: &Version,
+	) -> Result<Version, Error> {
+		Err(Error::upgrade_not_supported(port_id.clone()))
+	}
+
+	/// Called when the counterparty accepted the upgrade proposed by this end, possibly with a
+	/// different version.
+	fn on_chan_upgrade_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty_version: &Version,
+	) -> Result<(), Error> {
+		Ok(())
+	}
+
+	/// Called once the upgraded channel is open, with its new parameters.
+	#[allow(clippy::too_many_arguments)]
+	fn on_chan_upgrade_open(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_order: Order,
+		_connection_hops: &[ConnectionId],
+		_version: &Version,
+	) -> Result<(), Error> {
+		Ok(())
+	}
+
 	/// Modules can choose to write acknowledgement to storage in this callback
 	fn on_recv_packet(
 		&self,
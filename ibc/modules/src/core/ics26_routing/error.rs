@@ -16,7 +16,7 @@ use crate::prelude::*;
 use flex_error::{define_error, TraceError};
 
 use crate::{
-	applications::transfer,
+	applications::{cross_chain_queries, transfer},
 	core::{ics02_client, ics03_connection, ics04_channel},
 };
 
@@ -39,6 +39,10 @@ define_error! {
 			[ transfer::error::Error ]
 			| _ | { "ICS20 fungible token transfer error" },
 
+		Ics31CrossChainQuery
+			[ cross_chain_queries::error::Error ]
+			| _ | { "ICS31 cross-chain query error" },
+
 		UnknownMessageTypeUrl
 			{ url: String }
 			| e | { format_args!("unknown type URL {0}", e.url) },
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use crate::{
+	applications::cross_chain_queries::handler::submit_query_result as ics31_result_handler,
 	core::{
 		ics02_client::{
 			context::{ClientKeeper, ClientTypes},
@@ -22,6 +23,9 @@ use crate::{
 		ics04_channel::{
 			handler::{
 				channel_callback as ics4_callback, channel_dispatch as ics4_msg_dispatcher,
+				channel_upgrade_callback as ics4_upgrade_callback,
+				channel_upgrade_dispatch as ics4_upgrade_msg_dispatcher,
+				channel_upgrade_validate as ics4_upgrade_validate,
 				channel_validate as ics4_validate, get_module_for_packet_msg,
 				packet_callback as ics4_packet_callback,
 				packet_dispatch as ics4_packet_msg_dispatcher, recv_packet::RecvPacketResult,
@@ -31,7 +35,10 @@ use crate::{
 		ics26_routing::{
 			context::{Ics26Context, ModuleOutputBuilder, ReaderContext},
 			error::Error,
-			msgs::Ics26Envelope::{self, Ics2Msg, Ics3Msg, Ics4ChannelMsg, Ics4PacketMsg},
+			msgs::Ics26Envelope::{
+				self, Ics2Msg, Ics31Msg, Ics3Msg, Ics4ChannelMsg, Ics4ChannelUpgradeMsg,
+				Ics4PacketMsg,
+			},
 		},
 	},
 	events::IbcEvent,
@@ -131,6 +138,23 @@ where
 			handler_builder.with_result(())
 		},
 
+		Ics4ChannelUpgradeMsg(msg) => {
+			let module_id = ics4_upgrade_validate(ctx, &msg).map_err(Error::ics04_channel)?;
+			let (mut handler_builder, upgrade_result) =
+				ics4_upgrade_msg_dispatcher::<_>(ctx, &msg).map_err(Error::ics04_channel)?;
+
+			let mut module_output = ModuleOutputBuilder::new();
+			let cb_result =
+				ics4_upgrade_callback(ctx, &module_id, &msg, upgrade_result, &mut module_output);
+			handler_builder.merge(module_output);
+			let upgrade_result = cb_result.map_err(Error::ics04_channel)?;
+
+			// Apply any results to the host chain store.
+			ctx.store_channel_upgrade_result(upgrade_result).map_err(Error::ics04_channel)?;
+
+			handler_builder.with_result(())
+		},
+
 		Ics4PacketMsg(msg) => {
 			let module_id = get_module_for_packet_msg(ctx, &msg).map_err(Error::ics04_channel)?;
 			let (mut handler_builder, packet_result) =
@@ -140,6 +164,12 @@ where
 				return Ok(handler_builder.with_result(()))
 			}
 
+			// A packet skipped after timing out isn't delivered to the module
+			if matches!(packet_result, PacketResult::Recv(RecvPacketResult::TimedOut { .. })) {
+				ctx.store_packet_result(packet_result).map_err(Error::ics04_channel)?;
+				return Ok(handler_builder.with_result(()))
+			}
+
 			let mut module_output = ModuleOutputBuilder::new();
 			let cb_result = ics4_packet_callback(ctx, &module_id, &msg, &mut module_output);
 			handler_builder.merge(module_output);
@@ -150,6 +180,13 @@ where
 
 			handler_builder.with_result(())
 		},
+
+		Ics31Msg(msg) => {
+			let mut handler_builder = HandlerOutput::builder();
+			ics31_result_handler(ctx, &mut handler_builder, msg)
+				.map_err(Error::ics31_cross_chain_query)?;
+			handler_builder.with_result(())
+		},
 	};
 
 	Ok(output)
@@ -17,27 +17,37 @@ use core::fmt::{Debug, Display};
 
 use ibc_proto::google::protobuf::Any;
 
-use crate::core::{
-	ics02_client::msgs::{
-		create_client, create_client::MsgCreateAnyClient, update_client,
-		update_client::MsgUpdateAnyClient, upgrade_client, upgrade_client::MsgUpgradeAnyClient,
-		ClientMsg,
+use crate::{
+	applications::cross_chain_queries::msgs::{
+		MsgSubmitCrossChainQueryResult, SUBMIT_QUERY_RESULT_TYPE_URL,
 	},
-	ics03_connection::msgs::{
-		conn_open_ack, conn_open_ack::MsgConnectionOpenAck, conn_open_confirm, conn_open_init,
-		conn_open_try, conn_open_try::MsgConnectionOpenTry, ConnectionMsg,
+	core::{
+		ics02_client::msgs::{
+			create_client, create_client::MsgCreateAnyClient, misbehaviour,
+			misbehaviour::MsgSubmitMisbehaviour, update_client, update_client::MsgUpdateAnyClient,
+			upgrade_client, upgrade_client::MsgUpgradeAnyClient, ClientMsg,
+		},
+		ics03_connection::msgs::{
+			conn_open_ack, conn_open_ack::MsgConnectionOpenAck, conn_open_confirm, conn_open_init,
+			conn_open_try, conn_open_try::MsgConnectionOpenTry, ConnectionMsg,
+		},
+		ics04_channel::msgs::{
+			acknowledgement, chan_close_confirm, chan_close_init, chan_open_ack, chan_open_confirm,
+			chan_open_init, chan_open_try, chan_upgrade_ack, chan_upgrade_cancel,
+			chan_upgrade_confirm, chan_upgrade_init, chan_upgrade_open, chan_upgrade_timeout,
+			chan_upgrade_try, recv_packet, timeout, timeout_on_close, ChannelMsg,
+			ChannelUpgradeMsg, PacketMsg,
+		},
+		ics26_routing::error::Error,
 	},
-	ics04_channel::msgs::{
-		acknowledgement, chan_close_confirm, chan_close_init, chan_open_ack, chan_open_confirm,
-		chan_open_init, chan_open_try, recv_packet, timeout, timeout_on_close, ChannelMsg,
-		PacketMsg,
-	},
-	ics26_routing::error::Error,
 };
 
 use crate::core::ics02_client::context::ClientTypes;
 use ibc_proto::ibc::core::{
-	client::v1::{MsgCreateClient, MsgUpdateClient, MsgUpgradeClient},
+	client::v1::{
+		MsgCreateClient, MsgSubmitMisbehaviour as RawMsgSubmitMisbehaviour, MsgUpdateClient,
+		MsgUpgradeClient,
+	},
 	connection,
 };
 use tendermint_proto::Protobuf;
@@ -51,7 +61,9 @@ where
 	Ics2Msg(ClientMsg<C>),
 	Ics3Msg(ConnectionMsg<C>),
 	Ics4ChannelMsg(ChannelMsg),
+	Ics4ChannelUpgradeMsg(ChannelUpgradeMsg),
 	Ics4PacketMsg(PacketMsg),
+	Ics31Msg(MsgSubmitCrossChainQueryResult),
 }
 
 impl<C> TryFrom<Any> for Ics26Envelope<C>
@@ -69,6 +81,9 @@ where
 	MsgUpgradeAnyClient<C>: TryFrom<MsgUpgradeClient>,
 	<MsgUpgradeAnyClient<C> as TryFrom<MsgUpgradeClient>>::Error: Display,
 	MsgUpgradeAnyClient<C>: Protobuf<MsgUpgradeClient>,
+	MsgSubmitMisbehaviour<C>: TryFrom<RawMsgSubmitMisbehaviour>,
+	<MsgSubmitMisbehaviour<C> as TryFrom<RawMsgSubmitMisbehaviour>>::Error: Display,
+	MsgSubmitMisbehaviour<C>: Protobuf<RawMsgSubmitMisbehaviour>,
 	MsgConnectionOpenTry<C>: TryFrom<connection::v1::MsgConnectionOpenTry>,
 	<MsgConnectionOpenTry<C> as TryFrom<connection::v1::MsgConnectionOpenTry>>::Error: Display,
 	MsgConnectionOpenTry<C>: Protobuf<connection::v1::MsgConnectionOpenTry>,
@@ -99,6 +114,11 @@ where
 						.map_err(Error::malformed_message_bytes)?;
 				Ok(Ics26Envelope::Ics2Msg(ClientMsg::UpgradeClient(domain_msg)))
 			},
+			misbehaviour::TYPE_URL => {
+				let domain_msg = MsgSubmitMisbehaviour::<C>::decode_vec(&any_msg.value)
+					.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics2Msg(ClientMsg::Misbehaviour(domain_msg)))
+			},
 
 			// ICS03
 			conn_open_init::TYPE_URL => {
@@ -156,6 +176,61 @@ where
 						.map_err(Error::malformed_message_bytes)?;
 				Ok(Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelCloseConfirm(domain_msg)))
 			},
+			// ICS04 channel upgrade messages
+			chan_upgrade_init::TYPE_URL => {
+				let domain_msg =
+					chan_upgrade_init::MsgChannelUpgradeInit::decode_vec(&any_msg.value)
+						.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(ChannelUpgradeMsg::ChannelUpgradeInit(
+					domain_msg,
+				)))
+			},
+			chan_upgrade_try::TYPE_URL => {
+				let domain_msg = chan_upgrade_try::MsgChannelUpgradeTry::decode_vec(&any_msg.value)
+					.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(ChannelUpgradeMsg::ChannelUpgradeTry(
+					domain_msg,
+				)))
+			},
+			chan_upgrade_ack::TYPE_URL => {
+				let domain_msg = chan_upgrade_ack::MsgChannelUpgradeAck::decode_vec(&any_msg.value)
+					.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(ChannelUpgradeMsg::ChannelUpgradeAck(
+					domain_msg,
+				)))
+			},
+			chan_upgrade_confirm::TYPE_URL => {
+				let domain_msg =
+					chan_upgrade_confirm::MsgChannelUpgradeConfirm::decode_vec(&any_msg.value)
+						.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(ChannelUpgradeMsg::ChannelUpgradeConfirm(
+					domain_msg,
+				)))
+			},
+			chan_upgrade_open::TYPE_URL => {
+				let domain_msg =
+					chan_upgrade_open::MsgChannelUpgradeOpen::decode_vec(&any_msg.value)
+						.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(ChannelUpgradeMsg::ChannelUpgradeOpen(
+					domain_msg,
+				)))
+			},
+			chan_upgrade_timeout::TYPE_URL => {
+				let domain_msg =
+					chan_upgrade_timeout::MsgChannelUpgradeTimeout::decode_vec(&any_msg.value)
+						.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(ChannelUpgradeMsg::ChannelUpgradeTimeout(
+					domain_msg,
+				)))
+			},
+			chan_upgrade_cancel::TYPE_URL => {
+				let domain_msg =
+					chan_upgrade_cancel::MsgChannelUpgradeCancel::decode_vec(&any_msg.value)
+						.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(ChannelUpgradeMsg::ChannelUpgradeCancel(
+					domain_msg,
+				)))
+			},
 			// ICS04 packet messages
 			recv_packet::TYPE_URL => {
 				let domain_msg = recv_packet::MsgRecvPacket::decode_vec(&any_msg.value)
@@ -177,6 +252,12 @@ where
 					.map_err(Error::malformed_message_bytes)?;
 				Ok(Ics26Envelope::Ics4PacketMsg(PacketMsg::ToClosePacket(domain_msg)))
 			},
+			// ICS31 cross-chain query results
+			SUBMIT_QUERY_RESULT_TYPE_URL => {
+				let domain_msg = MsgSubmitCrossChainQueryResult::decode_vec(&any_msg.value)
+					.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics31Msg(domain_msg))
+			},
 			_ => Err(Error::unknown_message_type_url(any_msg.type_url)),
 		}
 	}
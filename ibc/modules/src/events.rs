@@ -142,6 +142,13 @@ const CHANNEL_OPEN_ACK_EVENT: &str = "channel_open_ack";
 const CHANNEL_OPEN_CONFIRM_EVENT: &str = "channel_open_confirm";
 const CHANNEL_CLOSE_INIT_EVENT: &str = "channel_close_init";
 const CHANNEL_CLOSE_CONFIRM_EVENT: &str = "channel_close_confirm";
+const CHANNEL_UPGRADE_INIT_EVENT: &str = "channel_upgrade_init";
+const CHANNEL_UPGRADE_TRY_EVENT: &str = "channel_upgrade_try";
+const CHANNEL_UPGRADE_ACK_EVENT: &str = "channel_upgrade_ack";
+const CHANNEL_UPGRADE_CONFIRM_EVENT: &str = "channel_upgrade_confirm";
+const CHANNEL_UPGRADE_OPEN_EVENT: &str = "channel_upgrade_open";
+const CHANNEL_UPGRADE_TIMEOUT_EVENT: &str = "channel_upgrade_timeout";
+const CHANNEL_UPGRADE_CANCEL_EVENT: &str = "channel_upgrade_cancelled";
 /// Packet event types
 const SEND_PACKET_EVENT: &str = "send_packet";
 const RECEIVE_PACKET_EVENT: &str = "recv_packet";
@@ -169,6 +176,13 @@ pub enum IbcEventType {
 	OpenConfirmChannel,
 	CloseInitChannel,
 	CloseConfirmChannel,
+	UpgradeInitChannel,
+	UpgradeTryChannel,
+	UpgradeAckChannel,
+	UpgradeConfirmChannel,
+	UpgradeOpenChannel,
+	UpgradeTimeoutChannel,
+	UpgradeCancelChannel,
 	SendPacket,
 	ReceivePacket,
 	WriteAck,
@@ -199,6 +213,13 @@ impl IbcEventType {
 			IbcEventType::OpenConfirmChannel => CHANNEL_OPEN_CONFIRM_EVENT,
 			IbcEventType::CloseInitChannel => CHANNEL_CLOSE_INIT_EVENT,
 			IbcEventType::CloseConfirmChannel => CHANNEL_CLOSE_CONFIRM_EVENT,
+			IbcEventType::UpgradeInitChannel => CHANNEL_UPGRADE_INIT_EVENT,
+			IbcEventType::UpgradeTryChannel => CHANNEL_UPGRADE_TRY_EVENT,
+			IbcEventType::UpgradeAckChannel => CHANNEL_UPGRADE_ACK_EVENT,
+			IbcEventType::UpgradeConfirmChannel => CHANNEL_UPGRADE_CONFIRM_EVENT,
+			IbcEventType::UpgradeOpenChannel => CHANNEL_UPGRADE_OPEN_EVENT,
+			IbcEventType::UpgradeTimeoutChannel => CHANNEL_UPGRADE_TIMEOUT_EVENT,
+			IbcEventType::UpgradeCancelChannel => CHANNEL_UPGRADE_CANCEL_EVENT,
 			IbcEventType::SendPacket => SEND_PACKET_EVENT,
 			IbcEventType::ReceivePacket => RECEIVE_PACKET_EVENT,
 			IbcEventType::WriteAck => WRITE_ACK_EVENT,
@@ -233,6 +254,13 @@ impl FromStr for IbcEventType {
 			CHANNEL_OPEN_CONFIRM_EVENT => Ok(IbcEventType::OpenConfirmChannel),
 			CHANNEL_CLOSE_INIT_EVENT => Ok(IbcEventType::CloseInitChannel),
 			CHANNEL_CLOSE_CONFIRM_EVENT => Ok(IbcEventType::CloseConfirmChannel),
+			CHANNEL_UPGRADE_INIT_EVENT => Ok(IbcEventType::UpgradeInitChannel),
+			CHANNEL_UPGRADE_TRY_EVENT => Ok(IbcEventType::UpgradeTryChannel),
+			CHANNEL_UPGRADE_ACK_EVENT => Ok(IbcEventType::UpgradeAckChannel),
+			CHANNEL_UPGRADE_CONFIRM_EVENT => Ok(IbcEventType::UpgradeConfirmChannel),
+			CHANNEL_UPGRADE_OPEN_EVENT => Ok(IbcEventType::UpgradeOpenChannel),
+			CHANNEL_UPGRADE_TIMEOUT_EVENT => Ok(IbcEventType::UpgradeTimeoutChannel),
+			CHANNEL_UPGRADE_CANCEL_EVENT => Ok(IbcEventType::UpgradeCancelChannel),
 			SEND_PACKET_EVENT => Ok(IbcEventType::SendPacket),
 			RECEIVE_PACKET_EVENT => Ok(IbcEventType::ReceivePacket),
 			WRITE_ACK_EVENT => Ok(IbcEventType::WriteAck),
@@ -270,6 +298,7 @@ pub enum IbcEvent {
 	OpenConfirmChannel(ChannelEvents::OpenConfirm),
 	CloseInitChannel(ChannelEvents::CloseInit),
 	CloseConfirmChannel(ChannelEvents::CloseConfirm),
+	UpgradeChannel(ChannelEvents::Upgrade),
 
 	SendPacket(ChannelEvents::SendPacket),
 	ReceivePacket(ChannelEvents::ReceivePacket),
@@ -324,6 +353,7 @@ impl fmt::Display for IbcEvent {
 			IbcEvent::OpenConfirmChannel(ev) => write!(f, "OpenConfirmChannelEv({:?})", ev),
 			IbcEvent::CloseInitChannel(ev) => write!(f, "CloseInitChannelEv({})", ev),
 			IbcEvent::CloseConfirmChannel(ev) => write!(f, "CloseConfirmChannelEv({:?})", ev),
+			IbcEvent::UpgradeChannel(ev) => write!(f, "UpgradeChannelEv({:?})", ev),
 
 			IbcEvent::SendPacket(ev) => write!(f, "SendPacketEv({})", ev),
 			IbcEvent::ReceivePacket(ev) => write!(f, "ReceivePacketEv({})", ev),
@@ -365,6 +395,7 @@ impl IbcEvent {
 			IbcEvent::OpenConfirmChannel(ev) => ev.height(),
 			IbcEvent::CloseInitChannel(ev) => ev.height(),
 			IbcEvent::CloseConfirmChannel(ev) => ev.height(),
+			IbcEvent::UpgradeChannel(ev) => ev.height(),
 			IbcEvent::SendPacket(ev) => ev.height(),
 			IbcEvent::ReceivePacket(ev) => ev.height(),
 			IbcEvent::WriteAcknowledgement(ev) => ev.height(),
@@ -392,6 +423,7 @@ impl IbcEvent {
 			IbcEvent::OpenConfirmChannel(ev) => ev.set_height(height),
 			IbcEvent::CloseInitChannel(ev) => ev.set_height(height),
 			IbcEvent::CloseConfirmChannel(ev) => ev.set_height(height),
+			IbcEvent::UpgradeChannel(ev) => ev.set_height(height),
 			IbcEvent::SendPacket(ev) => ev.set_height(height),
 			IbcEvent::ReceivePacket(ev) => ev.set_height(height),
 			IbcEvent::WriteAcknowledgement(ev) => ev.set_height(height),
@@ -420,6 +452,7 @@ impl IbcEvent {
 			IbcEvent::OpenConfirmChannel(_) => IbcEventType::OpenConfirmChannel,
 			IbcEvent::CloseInitChannel(_) => IbcEventType::CloseInitChannel,
 			IbcEvent::CloseConfirmChannel(_) => IbcEventType::CloseConfirmChannel,
+			IbcEvent::UpgradeChannel(ev) => ev.step.event_type(),
 			IbcEvent::SendPacket(_) => IbcEventType::SendPacket,
 			IbcEvent::ReceivePacket(_) => IbcEventType::ReceivePacket,
 			IbcEvent::WriteAcknowledgement(_) => IbcEventType::WriteAck,
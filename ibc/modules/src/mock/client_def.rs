@@ -254,9 +254,15 @@ impl ClientDef for MockClient {
 	fn update_state_on_misbehaviour(
 		&self,
 		client_state: Self::ClientState,
-		_client_msg: Self::ClientMessage,
+		client_msg: Self::ClientMessage,
 	) -> Result<Self::ClientState, Error> {
-		Ok(client_state)
+		match client_msg {
+			MockClientMessage::Misbehaviour(misbehaviour) => Ok(MockClientState {
+				frozen_height: Some(misbehaviour.header1.height()),
+				..client_state
+			}),
+			MockClientMessage::Header(_) => Ok(client_state),
+		}
 	}
 
 	fn check_for_misbehaviour<Ctx: ReaderContext>(
@@ -264,19 +270,86 @@ impl ClientDef for MockClient {
 		_ctx: &Ctx,
 		_client_id: ClientId,
 		_client_state: Self::ClientState,
-		_client_msg: Self::ClientMessage,
+		client_msg: Self::ClientMessage,
 	) -> Result<bool, Error> {
-		Ok(false)
+		// two different headers for the same height are evidence of misbehaviour
+		match client_msg {
+			MockClientMessage::Misbehaviour(misbehaviour) => Ok(misbehaviour.header1.height() ==
+				misbehaviour.header2.height() &&
+				misbehaviour.header1 != misbehaviour.header2),
+			MockClientMessage::Header(_) => Ok(false),
+		}
 	}
 
 	fn check_substitute_and_update_state<Ctx: ReaderContext>(
 		&self,
-		_ctx: &Ctx,
+		ctx: &Ctx,
 		_subject_client_id: ClientId,
-		_substitute_client_id: ClientId,
+		substitute_client_id: ClientId,
 		_old_client_state: Self::ClientState,
-		_substitute_client_state: Self::ClientState,
+		substitute_client_state: Self::ClientState,
 	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Error> {
-		todo!()
+		let consensus_state =
+			ctx.consensus_state(&substitute_client_id, substitute_client_state.latest_height())?;
+		let client_state = MockClientState { frozen_height: None, ..substitute_client_state };
+
+		Ok((client_state, ConsensusUpdateResult::Single(consensus_state)))
+	}
+
+	fn verify_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		_path: Path,
+		_value: Vec<u8>,
+	) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn verify_non_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		_path: Path,
+	) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn verify_batch_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		_items: Vec<(Path, Vec<u8>)>,
+	) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn verify_batch_non_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		_paths: Vec<Path>,
+	) -> Result<(), Error> {
+		Ok(())
 	}
 }
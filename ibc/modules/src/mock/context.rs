@@ -31,6 +31,11 @@ use tracing::debug;
 #[cfg(test)]
 use crate::core::ics02_client::events::Attributes;
 use crate::{
+	applications::cross_chain_queries::{
+		context::{CrossChainQueryContext, CrossChainQueryKeeper, CrossChainQueryReader},
+		error::Error as Ics31Error,
+		query::{CrossChainQuery, CrossChainQueryResult, QueryId},
+	},
 	core::{
 		ics02_client::{
 			client_state::{ClientState, ClientType},
@@ -49,6 +54,7 @@ use crate::{
 			error::Error as Ics04Error,
 			msgs::acknowledgement::Acknowledgement,
 			packet::{Receipt, Sequence},
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics05_port::{
 			context::PortReader,
@@ -244,6 +250,36 @@ where
 		self
 	}
 
+	/// Associates the upgrade proposed by this end of a channel to this context.
+	pub fn with_channel_upgrade(
+		self,
+		port_id: PortId,
+		chan_id: ChannelId,
+		upgrade: Upgrade,
+	) -> Self {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.channel_upgrades
+			.insert((port_id, chan_id), upgrade);
+		self
+	}
+
+	/// Associates the upgrade proposed by the counterparty of a channel to this context.
+	pub fn with_counterparty_channel_upgrade(
+		self,
+		port_id: PortId,
+		chan_id: ChannelId,
+		upgrade: Upgrade,
+	) -> Self {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.counterparty_channel_upgrades
+			.insert((port_id, chan_id), upgrade);
+		self
+	}
+
 	pub fn with_send_sequence(
 		self,
 		port_id: PortId,
@@ -542,6 +578,24 @@ pub struct MockIbcStore<C: HostBlockType> {
 
 	// Used by unordered channel
 	pub packet_receipt: BTreeMap<(PortId, ChannelId, Sequence), Receipt>,
+
+	/// The upgrades proposed by this chain for channels being upgraded.
+	pub channel_upgrades: BTreeMap<(PortId, ChannelId), Upgrade>,
+
+	/// The upgrades proposed by the counterparties of channels being upgraded.
+	pub counterparty_channel_upgrades: BTreeMap<(PortId, ChannelId), Upgrade>,
+
+	/// The receipts written when channel upgrades were aborted.
+	pub channel_upgrade_errors: BTreeMap<(PortId, ChannelId), ErrorReceipt>,
+
+	/// Counter for cross-chain query identifiers (see `increase_query_counter`).
+	pub query_ids_counter: u64,
+
+	/// The cross-chain queries awaiting a result.
+	pub cross_chain_queries: BTreeMap<QueryId, CrossChainQuery>,
+
+	/// The results of answered or timed out cross-chain queries.
+	pub cross_chain_query_results: BTreeMap<QueryId, CrossChainQueryResult>,
 }
 
 #[derive(Default)]
@@ -617,6 +671,65 @@ where
 	}
 }
 
+impl<C: HostBlockType> CrossChainQueryReader for MockContext<C> {
+	fn query_counter(&self) -> Result<u64, Ics31Error> {
+		Ok(self.ibc_store.lock().unwrap().query_ids_counter)
+	}
+
+	fn cross_chain_query(&self, query_id: QueryId) -> Result<CrossChainQuery, Ics31Error> {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.cross_chain_queries
+			.get(&query_id)
+			.cloned()
+			.ok_or_else(|| Ics31Error::query_not_found(query_id))
+	}
+
+	fn cross_chain_query_result(
+		&self,
+		query_id: QueryId,
+	) -> Result<CrossChainQueryResult, Ics31Error> {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.cross_chain_query_results
+			.get(&query_id)
+			.cloned()
+			.ok_or_else(|| Ics31Error::query_not_found(query_id))
+	}
+}
+
+impl<C: HostBlockType> CrossChainQueryKeeper for MockContext<C> {
+	fn increase_query_counter(&mut self) {
+		self.ibc_store.lock().unwrap().query_ids_counter += 1;
+	}
+
+	fn store_cross_chain_query(&mut self, query: CrossChainQuery) -> Result<(), Ics31Error> {
+		self.ibc_store.lock().unwrap().cross_chain_queries.insert(query.id, query);
+		Ok(())
+	}
+
+	fn delete_cross_chain_query(&mut self, query_id: QueryId) -> Result<(), Ics31Error> {
+		self.ibc_store.lock().unwrap().cross_chain_queries.remove(&query_id);
+		Ok(())
+	}
+
+	fn store_cross_chain_query_result(
+		&mut self,
+		result: CrossChainQueryResult,
+	) -> Result<(), Ics31Error> {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.cross_chain_query_results
+			.insert(result.id, result);
+		Ok(())
+	}
+}
+
+impl<C: HostBlockType> CrossChainQueryContext for MockContext<C> {}
+
 impl<C: HostBlockType> PortReader for MockContext<C> {
 	fn lookup_module_by_port(&self, port_id: &PortId) -> Result<ModuleId, Error> {
 		match self.ibc_store.lock().unwrap().port_to_module.get(port_id) {
@@ -746,6 +859,43 @@ impl<C: HostBlockType> ChannelReader for MockContext<C> {
 		Ok(self.ibc_store.lock().unwrap().channel_ids_counter)
 	}
 
+	fn channel_upgrade(&self, pcid: &(PortId, ChannelId)) -> Result<Upgrade, Ics04Error> {
+		match self.ibc_store.lock().unwrap().channel_upgrades.get(pcid) {
+			Some(upgrade) => Ok(upgrade.clone()),
+			None => Err(Ics04Error::missing_upgrade(pcid.0.clone(), pcid.1)),
+		}
+	}
+
+	fn channel_counterparty_upgrade(
+		&self,
+		pcid: &(PortId, ChannelId),
+	) -> Result<Upgrade, Ics04Error> {
+		match self.ibc_store.lock().unwrap().counterparty_channel_upgrades.get(pcid) {
+			Some(upgrade) => Ok(upgrade.clone()),
+			None => Err(Ics04Error::missing_upgrade(pcid.0.clone(), pcid.1)),
+		}
+	}
+
+	fn channel_upgrade_error_receipt(
+		&self,
+		pcid: &(PortId, ChannelId),
+	) -> Result<ErrorReceipt, Ics04Error> {
+		match self.ibc_store.lock().unwrap().channel_upgrade_errors.get(pcid) {
+			Some(error_receipt) => Ok(error_receipt.clone()),
+			None => Err(Ics04Error::missing_error_receipt()),
+		}
+	}
+
+	fn has_inflight_packets(&self, pcid: &(PortId, ChannelId)) -> Result<bool, Ics04Error> {
+		Ok(self
+			.ibc_store
+			.lock()
+			.unwrap()
+			.packet_commitment
+			.keys()
+			.any(|(port_id, channel_id, _)| port_id == &pcid.0 && channel_id == &pcid.1))
+	}
+
 	fn max_expected_time_per_block(&self) -> Duration {
 		self.block_time
 	}
@@ -841,6 +991,58 @@ impl<C: HostBlockType> ChannelKeeper for MockContext<C> {
 		self.ibc_store.lock().unwrap().channel_ids_counter += 1;
 	}
 
+	fn store_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: Upgrade,
+	) -> Result<(), Ics04Error> {
+		self.ibc_store.lock().unwrap().channel_upgrades.insert(port_channel_id, upgrade);
+		Ok(())
+	}
+
+	fn delete_upgrade(&mut self, port_channel_id: (PortId, ChannelId)) -> Result<(), Ics04Error> {
+		self.ibc_store.lock().unwrap().channel_upgrades.remove(&port_channel_id);
+		Ok(())
+	}
+
+	fn store_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: Upgrade,
+	) -> Result<(), Ics04Error> {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.counterparty_channel_upgrades
+			.insert(port_channel_id, upgrade);
+		Ok(())
+	}
+
+	fn delete_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+	) -> Result<(), Ics04Error> {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.counterparty_channel_upgrades
+			.remove(&port_channel_id);
+		Ok(())
+	}
+
+	fn store_upgrade_error_receipt(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		error_receipt: ErrorReceipt,
+	) -> Result<(), Ics04Error> {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.channel_upgrade_errors
+			.insert(port_channel_id, error_receipt);
+		Ok(())
+	}
+
 	fn delete_packet_commitment(
 		&mut self,
 		key: (PortId, ChannelId, Sequence),
@@ -13,15 +13,29 @@
 // limitations under the License.
 
 use std::{
+	collections::BTreeMap,
+	str::FromStr,
 	sync::{Arc, Mutex},
 	time::Duration,
 };
 
 use crate::{
-	applications::transfer::{
-		context::{BankKeeper, Ics20Context, Ics20Keeper, Ics20Reader},
-		error::Error as Ics20Error,
-		PrefixedCoin,
+	applications::{
+		interchain_accounts::{
+			controller::context::{IcaControllerContext, IcaControllerKeeper, IcaControllerReader},
+			error::Error as IcaError,
+			host::context::{IcaHostContext, IcaHostKeeper, IcaHostReader},
+		},
+		nft_transfer::{
+			context::{Metadata as NftMetadata, NftContext, NftKeeper, NftReader},
+			error::Error as NftError,
+			PrefixedClassId, TokenId, PORT_ID_STR as NFT_PORT_ID_STR,
+		},
+		transfer::{
+			context::{BankKeeper, Ics20Context, Ics20Keeper, Ics20Reader},
+			error::Error as Ics20Error,
+			PrefixedCoin,
+		},
 	},
 	core::{
 		ics02_client::{
@@ -38,6 +52,7 @@ use crate::{
 			context::{ChannelKeeper, ChannelReader},
 			error::Error,
 			packet::{Receipt, Sequence},
+			upgrade::{ErrorReceipt, Upgrade},
 			Version,
 		},
 		ics05_port::{context::PortReader, error::Error as PortError},
@@ -55,6 +70,7 @@ use crate::core::{
 	ics02_client::context::ClientTypes, ics04_channel::msgs::acknowledgement::Acknowledgement,
 	ics26_routing::context::ModuleCallbackContext,
 };
+use ibc_proto::google::protobuf::Any;
 use tendermint::{block, consensus, evidence, public_key::Algorithm};
 
 // Needed in mocks.
@@ -83,9 +99,32 @@ pub fn get_dummy_bech32_account() -> String {
 	"cosmos1wxeyh7zgn4tctjzs0vtqpc6p5cxq5t2muzl7ng".to_string()
 }
 
+/// State of the applications implemented by [`DummyTransferModule`].
+#[derive(Debug, Default)]
+pub struct DummyAppStore {
+	/// Metadata of the NFT classes
+	pub nft_classes: BTreeMap<PrefixedClassId, NftMetadata>,
+	/// Owner and metadata of the NFTs
+	pub nft_tokens: BTreeMap<(PrefixedClassId, TokenId), (Signer, NftMetadata)>,
+	/// Channels of the interchain accounts registered by this chain
+	pub ica_controller_channels: BTreeMap<(ConnectionId, PortId), ChannelId>,
+	/// Addresses of the interchain accounts registered by this chain
+	pub ica_addresses: BTreeMap<(ConnectionId, PortId), String>,
+	/// Channels of the interchain accounts hosted by this chain
+	pub ica_host_channels: BTreeMap<(ConnectionId, PortId), ChannelId>,
+	/// Interchain accounts hosted by this chain
+	pub ica_accounts: BTreeMap<(ConnectionId, PortId), Signer>,
+	/// Transactions executed by the hosted interchain accounts
+	pub ica_executed_txs: Vec<(Signer, Vec<Any>)>,
+}
+
+/// The only message the interchain accounts hosted by [`DummyTransferModule`] may execute
+pub const DUMMY_ICA_ALLOWED_MESSAGE: &str = "/cosmos.bank.v1beta1.MsgSend";
+
 #[derive(Debug, Clone)]
 pub struct DummyTransferModule<C: HostBlockType> {
 	ibc_store: Arc<Mutex<MockIbcStore<C>>>,
+	app_store: Arc<Mutex<DummyAppStore>>,
 }
 
 impl<C: HostBlockType> PartialEq for DummyTransferModule<C> {
@@ -98,7 +137,11 @@ impl<C: HostBlockType> Eq for DummyTransferModule<C> {}
 
 impl<C: HostBlockType> DummyTransferModule<C> {
 	pub fn new(ibc_store: Arc<Mutex<MockIbcStore<C>>>) -> Self {
-		Self { ibc_store }
+		Self { ibc_store, app_store: Default::default() }
+	}
+
+	pub fn app_store(&self) -> Arc<Mutex<DummyAppStore>> {
+		self.app_store.clone()
 	}
 }
 
@@ -352,6 +395,41 @@ impl<C: HostBlockType> ChannelKeeper for DummyTransferModule<C> {
 		unimplemented!()
 	}
 
+	fn store_upgrade(
+		&mut self,
+		_port_channel_id: (PortId, ChannelId),
+		_upgrade: Upgrade,
+	) -> Result<(), Error> {
+		unimplemented!()
+	}
+
+	fn delete_upgrade(&mut self, _port_channel_id: (PortId, ChannelId)) -> Result<(), Error> {
+		unimplemented!()
+	}
+
+	fn store_counterparty_upgrade(
+		&mut self,
+		_port_channel_id: (PortId, ChannelId),
+		_upgrade: Upgrade,
+	) -> Result<(), Error> {
+		unimplemented!()
+	}
+
+	fn delete_counterparty_upgrade(
+		&mut self,
+		_port_channel_id: (PortId, ChannelId),
+	) -> Result<(), Error> {
+		unimplemented!()
+	}
+
+	fn store_upgrade_error_receipt(
+		&mut self,
+		_port_channel_id: (PortId, ChannelId),
+		_error_receipt: ErrorReceipt,
+	) -> Result<(), Error> {
+		unimplemented!()
+	}
+
 	fn store_send_packet(
 		&mut self,
 		_key: (PortId, ChannelId, Sequence),
@@ -428,6 +506,228 @@ impl<C: HostBlockType> Ics20Reader for DummyTransferModule<C> {
 	}
 }
 
+impl<C: HostBlockType> NftReader for DummyTransferModule<C> {
+	type AccountId = Signer;
+
+	fn get_port(&self) -> Result<PortId, NftError> {
+		Ok(PortId::from_str(NFT_PORT_ID_STR).expect("nft-transfer is a valid port id"))
+	}
+
+	fn is_send_enabled(&self) -> bool {
+		true
+	}
+
+	fn is_receive_enabled(&self) -> bool {
+		true
+	}
+
+	fn class_metadata(&self, class_id: &PrefixedClassId) -> Result<NftMetadata, NftError> {
+		self.app_store
+			.lock()
+			.unwrap()
+			.nft_classes
+			.get(class_id)
+			.cloned()
+			.ok_or_else(|| NftError::class_not_found(class_id.to_string()))
+	}
+
+	fn token_metadata(
+		&self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+	) -> Result<NftMetadata, NftError> {
+		self.app_store
+			.lock()
+			.unwrap()
+			.nft_tokens
+			.get(&(class_id.clone(), token_id.clone()))
+			.map(|(_, metadata)| metadata.clone())
+			.ok_or_else(|| NftError::token_not_found(class_id.to_string(), token_id.to_string()))
+	}
+}
+
+impl<C: HostBlockType> NftKeeper for DummyTransferModule<C> {
+	type AccountId = Signer;
+
+	fn create_or_update_class(
+		&mut self,
+		class_id: &PrefixedClassId,
+		metadata: NftMetadata,
+	) -> Result<(), NftError> {
+		self.app_store.lock().unwrap().nft_classes.insert(class_id.clone(), metadata);
+		Ok(())
+	}
+
+	fn transfer_token(
+		&mut self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+		from: &Signer,
+		to: &Signer,
+	) -> Result<(), NftError> {
+		let mut app_store = self.app_store.lock().unwrap();
+		match app_store.nft_tokens.get_mut(&(class_id.clone(), token_id.clone())) {
+			Some((owner, _)) if owner == from => {
+				*owner = to.clone();
+				Ok(())
+			},
+			_ => Err(NftError::token_not_owned(class_id.to_string(), token_id.to_string())),
+		}
+	}
+
+	fn mint_token(
+		&mut self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+		metadata: NftMetadata,
+		to: &Signer,
+	) -> Result<(), NftError> {
+		let mut app_store = self.app_store.lock().unwrap();
+		if !app_store.nft_classes.contains_key(class_id) {
+			return Err(NftError::class_not_found(class_id.to_string()))
+		}
+		app_store
+			.nft_tokens
+			.insert((class_id.clone(), token_id.clone()), (to.clone(), metadata));
+		Ok(())
+	}
+
+	fn burn_token(
+		&mut self,
+		class_id: &PrefixedClassId,
+		token_id: &TokenId,
+		owner: &Signer,
+	) -> Result<(), NftError> {
+		let mut app_store = self.app_store.lock().unwrap();
+		let key = (class_id.clone(), token_id.clone());
+		match app_store.nft_tokens.get(&key) {
+			Some((token_owner, _)) if token_owner == owner => {
+				app_store.nft_tokens.remove(&key);
+				Ok(())
+			},
+			_ => Err(NftError::token_not_owned(class_id.to_string(), token_id.to_string())),
+		}
+	}
+}
+
+impl<C: HostBlockType> NftContext for DummyTransferModule<C> {
+	type AccountId = Signer;
+}
+
+impl<C: HostBlockType> IcaControllerReader for DummyTransferModule<C> {
+	fn is_controller_enabled(&self) -> bool {
+		true
+	}
+
+	fn active_channel(&self, connection_id: &ConnectionId, port_id: &PortId) -> Option<ChannelId> {
+		let app_store = self.app_store.lock().unwrap();
+		app_store
+			.ica_controller_channels
+			.get(&(connection_id.clone(), port_id.clone()))
+			.copied()
+	}
+
+	fn interchain_account_address(
+		&self,
+		connection_id: &ConnectionId,
+		port_id: &PortId,
+	) -> Option<String> {
+		let app_store = self.app_store.lock().unwrap();
+		app_store.ica_addresses.get(&(connection_id.clone(), port_id.clone())).cloned()
+	}
+}
+
+impl<C: HostBlockType> IcaControllerKeeper for DummyTransferModule<C> {
+	fn store_active_channel(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+		channel_id: ChannelId,
+	) -> Result<(), IcaError> {
+		let mut app_store = self.app_store.lock().unwrap();
+		app_store.ica_controller_channels.insert((connection_id, port_id), channel_id);
+		Ok(())
+	}
+
+	fn store_interchain_account_address(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+		address: String,
+	) -> Result<(), IcaError> {
+		let mut app_store = self.app_store.lock().unwrap();
+		app_store.ica_addresses.insert((connection_id, port_id), address);
+		Ok(())
+	}
+}
+
+impl<C: HostBlockType> IcaControllerContext for DummyTransferModule<C> {}
+
+impl<C: HostBlockType> IcaHostReader for DummyTransferModule<C> {
+	type AccountId = Signer;
+
+	fn is_host_enabled(&self) -> bool {
+		true
+	}
+
+	fn is_message_allowed(&self, type_url: &str) -> bool {
+		type_url == DUMMY_ICA_ALLOWED_MESSAGE
+	}
+
+	fn active_channel(&self, connection_id: &ConnectionId, port_id: &PortId) -> Option<ChannelId> {
+		let app_store = self.app_store.lock().unwrap();
+		app_store
+			.ica_host_channels
+			.get(&(connection_id.clone(), port_id.clone()))
+			.copied()
+	}
+
+	fn interchain_account(&self, connection_id: &ConnectionId, port_id: &PortId) -> Option<Signer> {
+		let app_store = self.app_store.lock().unwrap();
+		app_store.ica_accounts.get(&(connection_id.clone(), port_id.clone())).cloned()
+	}
+}
+
+impl<C: HostBlockType> IcaHostKeeper for DummyTransferModule<C> {
+	type AccountId = Signer;
+
+	fn register_interchain_account(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+	) -> Result<Signer, IcaError> {
+		let address = Signer::from_str(&format!("ica/{}/{}", connection_id, port_id))
+			.expect("the address is not empty");
+		let mut app_store = self.app_store.lock().unwrap();
+		Ok(app_store
+			.ica_accounts
+			.entry((connection_id, port_id))
+			.or_insert(address)
+			.clone())
+	}
+
+	fn store_active_channel(
+		&mut self,
+		connection_id: ConnectionId,
+		port_id: PortId,
+		channel_id: ChannelId,
+	) -> Result<(), IcaError> {
+		let mut app_store = self.app_store.lock().unwrap();
+		app_store.ica_host_channels.insert((connection_id, port_id), channel_id);
+		Ok(())
+	}
+
+	fn execute_tx(&mut self, account: &Signer, messages: Vec<Any>) -> Result<Vec<u8>, IcaError> {
+		let mut app_store = self.app_store.lock().unwrap();
+		app_store.ica_executed_txs.push((account.clone(), messages));
+		Ok(b"executed".to_vec())
+	}
+}
+
+impl<C: HostBlockType> IcaHostContext for DummyTransferModule<C> {
+	type AccountId = Signer;
+}
+
 impl<C: HostBlockType> ConnectionReader for DummyTransferModule<C> {
 	fn minimum_delay_period(&self) -> Duration {
 		Duration::from_secs(0)
@@ -512,7 +812,7 @@ impl<C: HostBlockType> ClientReader for DummyTransferModule<C> {
 	}
 
 	fn host_timestamp(&self) -> Timestamp {
-		todo!()
+		Timestamp::now()
 	}
 
 	fn client_counter(&self) -> Result<u64, Ics02Error> {
@@ -604,6 +904,28 @@ impl<C: HostBlockType> ChannelReader for DummyTransferModule<C> {
 		unimplemented!()
 	}
 
+	fn channel_upgrade(&self, _port_channel_id: &(PortId, ChannelId)) -> Result<Upgrade, Error> {
+		unimplemented!()
+	}
+
+	fn channel_counterparty_upgrade(
+		&self,
+		_port_channel_id: &(PortId, ChannelId),
+	) -> Result<Upgrade, Error> {
+		unimplemented!()
+	}
+
+	fn channel_upgrade_error_receipt(
+		&self,
+		_port_channel_id: &(PortId, ChannelId),
+	) -> Result<ErrorReceipt, Error> {
+		unimplemented!()
+	}
+
+	fn has_inflight_packets(&self, _port_channel_id: &(PortId, ChannelId)) -> Result<bool, Error> {
+		unimplemented!()
+	}
+
 	fn max_expected_time_per_block(&self) -> Duration {
 		unimplemented!()
 	}
@@ -31,8 +31,9 @@ pub trait Msg: Clone {
 	}
 
 	fn get_sign_bytes(self) -> Vec<u8> {
-		let mut buf = Vec::new();
 		let raw_msg: Self::Raw = self.into();
+		// sized upfront, messages carrying proofs or headers are large
+		let mut buf = Vec::with_capacity(prost::Message::encoded_len(&raw_msg));
 		match prost::Message::encode(&raw_msg, &mut buf) {
 			Ok(()) => buf,
 			// Severe error that cannot be recovered.
@@ -0,0 +1,263 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Golden encodings of the messages submitted by the relayer.
+//!
+//! Each file in `tests/golden` holds the hex of the `Any` ibc-go expects for the message built by
+//! the test of the same name, encoded from the ibc-go proto definitions at the commit of
+//! `ibc/proto/src/IBC_GO_COMMIT`. A test failing here means the encoding drifted from upstream,
+//! the golden files must only be regenerated by `ibc/scripts/generate-golden.sh` along with a bump
+//! of the proto definitions, never from the output of this crate.
+//!
+//! The messages carrying client states are checked the same way by the tests of the light clients:
+//! the tendermint states and the connection handshake against the golden files of
+//! `light-clients/ics07-tendermint/src/mock/golden`, the grandpa states wrapped in the 08-wasm ones
+//! and the client creation and update of cosmos chains against `contracts/pallet-ibc/src/golden`.
+
+use core::{fmt::Debug, str::FromStr, time::Duration};
+
+use ibc::{
+	applications::transfer::{msgs::transfer::MsgTransfer, Coin, PrefixedCoin, PrefixedDenom},
+	core::{
+		ics03_connection::{
+			connection::Counterparty as ConnectionCounterparty,
+			msgs::{
+				conn_open_confirm::MsgConnectionOpenConfirm, conn_open_init::MsgConnectionOpenInit,
+			},
+			version::Version as ConnectionVersion,
+		},
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty as ChannelCounterparty, Order, State},
+			msgs::{
+				acknowledgement::MsgAcknowledgement, chan_close_confirm::MsgChannelCloseConfirm,
+				chan_close_init::MsgChannelCloseInit, chan_open_ack::MsgChannelOpenAck,
+				chan_open_confirm::MsgChannelOpenConfirm, chan_open_init::MsgChannelOpenInit,
+				chan_open_try::MsgChannelOpenTry, recv_packet::MsgRecvPacket, timeout::MsgTimeout,
+				timeout_on_close::MsgTimeoutOnClose,
+			},
+			packet::{Packet, Sequence},
+			Version as ChannelVersion,
+		},
+		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes},
+		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+	},
+	proofs::Proofs,
+	signer::Signer,
+	timestamp::Timestamp,
+	tx_msg::Msg,
+	Height,
+};
+use ibc_proto::google::protobuf::Any;
+use prost::Message;
+
+const SIGNER: &str = "cosmos1hsk6jryyqjfhp5dhc55tc9jtckygx0eph6dd02";
+const SUBSTRATE_SIGNER: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+const TIMEOUT_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+macro_rules! golden {
+	($name:literal) => {
+		include_str!(concat!("golden/", $name, ".hex"))
+	};
+}
+
+/// Asserts that `msg` encodes to the `golden` hex, and that decoding the golden gives `msg` back.
+fn assert_golden<M>(golden: &str, msg: M)
+where
+	M: Msg + TryFrom<<M as Msg>::Raw> + PartialEq + Debug,
+	<M as Msg>::Raw: Default,
+	<M as TryFrom<<M as Msg>::Raw>>::Error: Debug,
+{
+	let golden = hex::decode(golden.trim()).expect("golden files hold hex");
+	assert_eq!(hex::encode(msg.clone().to_any().encode_to_vec()), hex::encode(&golden));
+
+	let any = Any::decode(golden.as_slice()).unwrap();
+	assert_eq!(any.type_url, msg.type_url());
+	let raw = <M as Msg>::Raw::decode(any.value.as_slice()).unwrap();
+	assert_eq!(M::try_from(raw).unwrap(), msg);
+}
+
+fn signer() -> Signer {
+	Signer::from_str(SIGNER).unwrap()
+}
+
+fn proofs() -> Proofs {
+	Proofs::new(
+		CommitmentProofBytes::try_from(b"commitment proof".to_vec()).unwrap(),
+		None,
+		None,
+		None,
+		Height::new(1, 42),
+	)
+	.unwrap()
+}
+
+fn packet() -> Packet {
+	Packet {
+		sequence: Sequence::from(7),
+		source_port: PortId::transfer(),
+		source_channel: ChannelId::new(0),
+		destination_port: PortId::transfer(),
+		destination_channel: ChannelId::new(1),
+		data: format!(
+			r#"{{"amount":"1000","denom":"ppica","receiver":"{SIGNER}","sender":"{SUBSTRATE_SIGNER}"}}"#
+		)
+		.into_bytes(),
+		timeout_height: Height::new(1, 1000),
+		timeout_timestamp: Timestamp::from_nanoseconds(TIMEOUT_TIMESTAMP).unwrap(),
+	}
+}
+
+#[test]
+fn msg_transfer() {
+	let msg: MsgTransfer<PrefixedCoin> = MsgTransfer {
+		source_port: PortId::transfer(),
+		source_channel: ChannelId::new(0),
+		token: Coin {
+			denom: PrefixedDenom::from_str("transfer/channel-1/uatom").unwrap(),
+			amount: 1000u64.into(),
+		},
+		sender: Signer::from_str(SUBSTRATE_SIGNER).unwrap(),
+		receiver: signer(),
+		timeout_height: Height::new(1, 1000),
+		timeout_timestamp: Timestamp::from_nanoseconds(TIMEOUT_TIMESTAMP).unwrap(),
+		memo: String::new(),
+	};
+	assert_golden(golden!("msg_transfer"), msg);
+}
+
+#[test]
+fn msg_recv_packet() {
+	assert_golden(golden!("msg_recv_packet"), MsgRecvPacket::new(packet(), proofs(), signer()));
+}
+
+#[test]
+fn msg_acknowledgement() {
+	let msg = MsgAcknowledgement::new(
+		packet(),
+		br#"{"result":"AQ=="}"#.to_vec().into(),
+		proofs(),
+		signer(),
+	);
+	assert_golden(golden!("msg_acknowledgement"), msg);
+}
+
+#[test]
+fn msg_timeout() {
+	let msg = MsgTimeout::new(packet(), Sequence::from(7), proofs(), signer());
+	assert_golden(golden!("msg_timeout"), msg);
+}
+
+#[test]
+fn msg_channel_open_init() {
+	let channel = ChannelEnd::new(
+		State::Init,
+		Order::Unordered,
+		ChannelCounterparty::new(PortId::transfer(), None),
+		vec![ConnectionId::new(0)],
+		ChannelVersion::ics20(),
+	);
+	let msg = MsgChannelOpenInit::new(PortId::transfer(), channel, signer());
+	assert_golden(golden!("msg_channel_open_init"), msg);
+}
+
+#[test]
+fn msg_channel_open_try() {
+	let channel = ChannelEnd::new(
+		State::TryOpen,
+		Order::Unordered,
+		ChannelCounterparty::new(PortId::transfer(), Some(ChannelId::new(0))),
+		vec![ConnectionId::new(1)],
+		ChannelVersion::ics20(),
+	);
+	let msg = MsgChannelOpenTry::new(
+		PortId::transfer(),
+		channel,
+		ChannelVersion::ics20(),
+		proofs(),
+		signer(),
+	);
+	assert_golden(golden!("msg_channel_open_try"), msg);
+}
+
+#[test]
+fn msg_channel_open_ack() {
+	let msg = MsgChannelOpenAck::new(
+		PortId::transfer(),
+		ChannelId::new(0),
+		ChannelId::new(1),
+		ChannelVersion::ics20(),
+		proofs(),
+		signer(),
+	);
+	assert_golden(golden!("msg_channel_open_ack"), msg);
+}
+
+#[test]
+fn msg_channel_open_confirm() {
+	let msg = MsgChannelOpenConfirm::new(PortId::transfer(), ChannelId::new(1), proofs(), signer());
+	assert_golden(golden!("msg_channel_open_confirm"), msg);
+}
+
+#[test]
+fn msg_channel_close_init() {
+	let msg = MsgChannelCloseInit::new(PortId::transfer(), ChannelId::new(0), signer());
+	assert_golden(golden!("msg_channel_close_init"), msg);
+}
+
+#[test]
+fn msg_channel_close_confirm() {
+	let msg =
+		MsgChannelCloseConfirm::new(PortId::transfer(), ChannelId::new(1), proofs(), signer());
+	assert_golden(golden!("msg_channel_close_confirm"), msg);
+}
+
+#[test]
+fn msg_timeout_on_close() {
+	let proofs = Proofs::new(
+		CommitmentProofBytes::try_from(b"commitment proof".to_vec()).unwrap(),
+		None,
+		None,
+		Some(CommitmentProofBytes::try_from(b"close proof".to_vec()).unwrap()),
+		Height::new(1, 42),
+	)
+	.unwrap();
+	let msg = MsgTimeoutOnClose::new(packet(), Sequence::from(7), proofs, signer());
+	assert_golden(golden!("msg_timeout_on_close"), msg);
+}
+
+#[test]
+fn msg_connection_open_init() {
+	let msg = MsgConnectionOpenInit {
+		client_id: ClientId::from_str("07-tendermint-0").unwrap(),
+		counterparty: ConnectionCounterparty::new(
+			ClientId::from_str("08-wasm-0").unwrap(),
+			None,
+			CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap(),
+		),
+		version: Some(ConnectionVersion::default()),
+		delay_period: Duration::from_secs(10),
+		signer: signer(),
+	};
+	assert_golden(golden!("msg_connection_open_init"), msg);
+}
+
+#[test]
+fn msg_connection_open_confirm() {
+	let msg = MsgConnectionOpenConfirm {
+		connection_id: ConnectionId::new(1),
+		proofs: proofs(),
+		signer: signer(),
+	};
+	assert_golden(golden!("msg_connection_open_confirm"), msg);
+}
@@ -43,7 +43,7 @@ macro_rules! include_proto {
 /// The version (commit hash) of the Cosmos SDK used when generating this library.
 pub const COSMOS_SDK_COMMIT: &str = include_str!("COSMOS_SDK_COMMIT");
 
-/// The version (commit hash) of IBC Go used when generating this library.
+/// The version (commit hash or tag) of IBC Go used when generating this library.
 pub const IBC_GO_COMMIT: &str = include_str!("IBC_GO_COMMIT");
 
 pub mod cosmos {
@@ -157,6 +157,11 @@ pub mod ibc {
 				include_proto!("ibc.applications.transfer.v2.rs");
 			}
 		}
+		pub mod fee {
+			pub mod v1 {
+				include_proto!("ibc.applications.fee.v1.rs");
+			}
+		}
 		pub mod interchain_accounts {
 			pub mod v1 {
 				include_proto!("ibc.applications.interchain_accounts.v1.rs");
@@ -22,6 +22,10 @@ pub struct Channel {
     /// opaque channel version, which is agreed upon during the handshake
     #[prost(string, tag = "5")]
     pub version: ::prost::alloc::string::String,
+    /// upgrade sequence indicates the latest upgrade attempt performed by this channel
+    /// the value of 0 indicates the channel has never been upgraded
+    #[prost(uint64, tag = "6")]
+    pub upgrade_sequence: u64,
 }
 /// IdentifiedChannel defines a channel with additional port and channel
 /// identifier fields.
@@ -51,6 +55,10 @@ pub struct IdentifiedChannel {
     /// channel identifier
     #[prost(string, tag = "7")]
     pub channel_id: ::prost::alloc::string::String,
+    /// upgrade sequence indicates the latest upgrade attempt performed by this channel
+    /// the value of 0 indicates the channel has never been upgraded
+    #[prost(uint64, tag = "8")]
+    pub upgrade_sequence: u64,
 }
 /// Counterparty defines a channel end counterparty
 #[derive(::serde::Serialize, ::serde::Deserialize)]
@@ -163,8 +171,22 @@ pub mod acknowledgement {
         Error(::prost::alloc::string::String),
     }
 }
+/// Timeout defines an execution deadline structure for 04-channel handlers.
+/// This includes packet lifecycle handlers as well as the upgrade handshake handlers.
+/// A valid Timeout contains either one or both of a timestamp and block height (sequence).
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Timeout {
+    /// block height after which the packet or upgrade times out
+    #[prost(message, optional, tag = "1")]
+    pub height: ::core::option::Option<super::super::client::v1::Height>,
+    /// block timestamp (in nanoseconds) after which the packet or upgrade times out
+    #[prost(uint64, tag = "2")]
+    pub timestamp: u64,
+}
 /// State defines if a channel is in one of the following states:
-/// CLOSED, INIT, TRYOPEN, OPEN or UNINITIALIZED.
+/// CLOSED, INIT, TRYOPEN, OPEN, FLUSHING, FLUSHCOMPLETE or UNINITIALIZED.
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -181,6 +203,10 @@ pub enum State {
     /// A channel has been closed and can no longer be used to send or receive
     /// packets.
     Closed = 4,
+    /// A channel has just accepted the upgrade handshake attempt and is flushing in-flight packets.
+    Flushing = 5,
+    /// A channel has just completed flushing any in-flight packets.
+    Flushcomplete = 6,
 }
 impl State {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -194,6 +220,8 @@ impl State {
             State::Tryopen => "STATE_TRYOPEN",
             State::Open => "STATE_OPEN",
             State::Closed => "STATE_CLOSED",
+            State::Flushing => "STATE_FLUSHING",
+            State::Flushcomplete => "STATE_FLUSHCOMPLETE",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -204,6 +232,8 @@ impl State {
             "STATE_TRYOPEN" => Some(Self::Tryopen),
             "STATE_OPEN" => Some(Self::Open),
             "STATE_CLOSED" => Some(Self::Closed),
+            "STATE_FLUSHING" => Some(Self::Flushing),
+            "STATE_FLUSHCOMPLETE" => Some(Self::Flushcomplete),
             _ => None,
         }
     }
@@ -220,6 +250,9 @@ pub enum Order {
     Unordered = 1,
     /// packets are delivered exactly in the order which they were sent
     Ordered = 2,
+    /// packets are delivered in the order which they were sent, a packet that timed out
+    /// is skipped instead of closing the channel
+    OrderedAllowTimeout = 3,
 }
 impl Order {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -231,6 +264,7 @@ impl Order {
             Order::NoneUnspecified => "ORDER_NONE_UNSPECIFIED",
             Order::Unordered => "ORDER_UNORDERED",
             Order::Ordered => "ORDER_ORDERED",
+            Order::OrderedAllowTimeout => "ORDER_ORDERED_ALLOW_TIMEOUT",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -239,10 +273,54 @@ impl Order {
             "ORDER_NONE_UNSPECIFIED" => Some(Self::NoneUnspecified),
             "ORDER_UNORDERED" => Some(Self::Unordered),
             "ORDER_ORDERED" => Some(Self::Ordered),
+            "ORDER_ORDERED_ALLOW_TIMEOUT" => Some(Self::OrderedAllowTimeout),
             _ => None,
         }
     }
 }
+/// Upgrade is a verifiable type which contains the relevant information
+/// for an attempted upgrade. It provides the proposed changes to the channel
+/// end, the timeout for this upgrade attempt and the next packet sequence
+/// which allows the counterparty to efficiently know the highest sequence it has received.
+/// The next sequence send is used for pruning and upgrading from unordered to ordered channels.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Upgrade {
+    #[prost(message, optional, tag = "1")]
+    pub fields: ::core::option::Option<UpgradeFields>,
+    #[prost(message, optional, tag = "2")]
+    pub timeout: ::core::option::Option<Timeout>,
+    #[prost(uint64, tag = "3")]
+    pub next_sequence_send: u64,
+}
+/// UpgradeFields are the fields in a channel end which may be changed
+/// during a channel upgrade.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpgradeFields {
+    #[prost(enumeration = "Order", tag = "1")]
+    pub ordering: i32,
+    #[prost(string, repeated, tag = "2")]
+    pub connection_hops: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "3")]
+    pub version: ::prost::alloc::string::String,
+}
+/// ErrorReceipt defines a type which encapsulates the upgrade sequence and error associated with the
+/// upgrade handshake failure. When a channel upgrade handshake is aborted both chains are expected to increment to the
+/// next sequence.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ErrorReceipt {
+    /// the channel upgrade sequence
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+    /// the error message detailing the cause of failure
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+}
 /// GenesisState defines the ibc channel submodule's genesis state.
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -521,6 +599,144 @@ pub struct MsgAcknowledgementResponse {
     #[prost(enumeration = "ResponseResultType", tag = "1")]
     pub result: i32,
 }
+/// MsgChannelUpgradeInit defines the request type for the ChannelUpgradeInit rpc
+/// WARNING: Initializing a channel upgrade in the same block as opening the channel
+/// may result in the counterparty being incapable of opening.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgChannelUpgradeInit {
+    #[prost(string, tag = "1")]
+    pub port_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub channel_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub fields: ::core::option::Option<UpgradeFields>,
+    #[prost(string, tag = "4")]
+    pub signer: ::prost::alloc::string::String,
+}
+/// MsgChannelUpgradeTry defines the request type for the ChannelUpgradeTry rpc
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgChannelUpgradeTry {
+    #[prost(string, tag = "1")]
+    pub port_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub channel_id: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "3")]
+    pub proposed_upgrade_connection_hops: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "4")]
+    pub counterparty_upgrade_fields: ::core::option::Option<UpgradeFields>,
+    #[prost(uint64, tag = "5")]
+    pub counterparty_upgrade_sequence: u64,
+    #[prost(bytes = "vec", tag = "6")]
+    pub proof_channel: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "7")]
+    pub proof_upgrade: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "8")]
+    pub proof_height: ::core::option::Option<super::super::client::v1::Height>,
+    #[prost(string, tag = "9")]
+    pub signer: ::prost::alloc::string::String,
+}
+/// MsgChannelUpgradeAck defines the request type for the ChannelUpgradeAck rpc
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgChannelUpgradeAck {
+    #[prost(string, tag = "1")]
+    pub port_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub channel_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub counterparty_upgrade: ::core::option::Option<Upgrade>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub proof_channel: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub proof_upgrade: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "6")]
+    pub proof_height: ::core::option::Option<super::super::client::v1::Height>,
+    #[prost(string, tag = "7")]
+    pub signer: ::prost::alloc::string::String,
+}
+/// MsgChannelUpgradeConfirm defines the request type for the ChannelUpgradeConfirm rpc
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgChannelUpgradeConfirm {
+    #[prost(string, tag = "1")]
+    pub port_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub channel_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "State", tag = "3")]
+    pub counterparty_channel_state: i32,
+    #[prost(message, optional, tag = "4")]
+    pub counterparty_upgrade: ::core::option::Option<Upgrade>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub proof_channel: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub proof_upgrade: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "7")]
+    pub proof_height: ::core::option::Option<super::super::client::v1::Height>,
+    #[prost(string, tag = "8")]
+    pub signer: ::prost::alloc::string::String,
+}
+/// MsgChannelUpgradeOpen defines the request type for the ChannelUpgradeOpen rpc
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgChannelUpgradeOpen {
+    #[prost(string, tag = "1")]
+    pub port_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub channel_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "State", tag = "3")]
+    pub counterparty_channel_state: i32,
+    #[prost(uint64, tag = "4")]
+    pub counterparty_upgrade_sequence: u64,
+    #[prost(bytes = "vec", tag = "5")]
+    pub proof_channel: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "6")]
+    pub proof_height: ::core::option::Option<super::super::client::v1::Height>,
+    #[prost(string, tag = "7")]
+    pub signer: ::prost::alloc::string::String,
+}
+/// MsgChannelUpgradeTimeout defines the request type for the ChannelUpgradeTimeout rpc
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgChannelUpgradeTimeout {
+    #[prost(string, tag = "1")]
+    pub port_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub channel_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub counterparty_channel: ::core::option::Option<Channel>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub proof_channel: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "5")]
+    pub proof_height: ::core::option::Option<super::super::client::v1::Height>,
+    #[prost(string, tag = "6")]
+    pub signer: ::prost::alloc::string::String,
+}
+/// MsgChannelUpgradeCancel defines the request type for the ChannelUpgradeCancel rpc
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgChannelUpgradeCancel {
+    #[prost(string, tag = "1")]
+    pub port_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub channel_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub error_receipt: ::core::option::Option<ErrorReceipt>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub proof_error_receipt: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "5")]
+    pub proof_height: ::core::option::Option<super::super::client::v1::Height>,
+    #[prost(string, tag = "6")]
+    pub signer: ::prost::alloc::string::String,
+}
 /// ResponseResultType defines the possible outcomes of the execution of a message
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
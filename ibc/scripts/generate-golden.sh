@@ -0,0 +1,57 @@
+#!/usr/bin/env bash
+
+set -eou pipefail
+
+# generate-golden.sh regenerates the golden encodings checked
+# by `ibc/modules/tests/golden.rs`, by the tests of the ics07
+# tendermint light client and by the light client tests of
+# pallet-ibc. The encodings are produced by ibc-go itself, at
+# the version of proto/src/IBC_GO_COMMIT (a commit ID or a
+# tag), so the golden files must be regenerated along with
+# every bump of the protobuf files, see sync-protobuf.sh.
+
+# This script should be run from the root directory of the
+# repository, and requires go.
+
+# As in sync-protobuf.sh, the ibc-go repository is cloned as a
+# bare repository to $IBC_GO_GIT, which may point to an
+# existing clone.
+
+CACHE_PATH="${XDG_CACHE_HOME:-$HOME/.cache}"
+IBC_GO_GIT="${IBC_GO_GIT:-$CACHE_PATH/ibc-go.git}"
+
+IBC_GO_COMMIT="$(cat ibc/proto/src/IBC_GO_COMMIT)"
+
+echo "IBC_GO_COMMIT: $IBC_GO_COMMIT"
+
+if [[ ! -e "$IBC_GO_GIT" ]]
+then
+	echo "Cloning ibc-go source code to as bare git repository to $IBC_GO_GIT"
+	git clone --mirror https://github.com/ComposableFi/ibc-go.git "$IBC_GO_GIT"
+else
+	echo "Using existing ibc-go bare git repository at $IBC_GO_GIT"
+fi
+
+pushd "$IBC_GO_GIT"
+git fetch
+popd
+
+IBC_GO_DIR=$(mktemp -d /tmp/ibc-go-XXXXXXXX)
+
+pushd "$IBC_GO_DIR"
+git clone "$IBC_GO_GIT" .
+git checkout "$IBC_GO_COMMIT"
+popd
+
+# The generator is built inside the ibc-go module, so that
+# it uses ibc-go and the dependencies of its go.mod.
+
+ROOT="$(pwd)"
+mkdir -p "$IBC_GO_DIR/golden"
+cp ibc/scripts/golden/main.go "$IBC_GO_DIR/golden/main.go"
+
+pushd "$IBC_GO_DIR"
+go run ./golden "$ROOT"
+popd
+
+rm -rf "$IBC_GO_DIR"
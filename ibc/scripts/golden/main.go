@@ -0,0 +1,401 @@
+// Generates the golden encodings checked by the encoding tests of the relayer messages and of the
+// light client states, see `ibc/modules/tests/golden.rs`.
+//
+// It is run by `ibc/scripts/generate-golden.sh` from a checkout of ibc-go at the commit of
+// `ibc/proto/src/IBC_GO_COMMIT`, so the core messages and the tendermint states are encoded by
+// ibc-go itself. The messages of 08-wasm live in a separate Go module and the grandpa light
+// client has no Go implementation, those are encoded field by field from `wasm.proto` and from
+// `light-clients/ics10-grandpa/src/proto/grandpa.proto`.
+//
+// The values must be kept in sync with the ones of the tests reading the golden files.
+package main
+
+import (
+	"encoding/hex"
+	"fmt"
+	"os"
+	"path/filepath"
+	"time"
+
+	sdkmath "cosmossdk.io/math"
+	codectypes "github.com/cosmos/cosmos-sdk/codec/types"
+	sdk "github.com/cosmos/cosmos-sdk/types"
+	"github.com/cosmos/gogoproto/proto"
+	"google.golang.org/protobuf/encoding/protowire"
+
+	transfertypes "github.com/cosmos/ibc-go/v8/modules/apps/transfer/types"
+	clienttypes "github.com/cosmos/ibc-go/v8/modules/core/02-client/types"
+	connectiontypes "github.com/cosmos/ibc-go/v8/modules/core/03-connection/types"
+	channeltypes "github.com/cosmos/ibc-go/v8/modules/core/04-channel/types"
+	commitmenttypes "github.com/cosmos/ibc-go/v8/modules/core/23-commitment/types"
+	ibctm "github.com/cosmos/ibc-go/v8/modules/light-clients/07-tendermint"
+)
+
+const (
+	signer          = "cosmos1hsk6jryyqjfhp5dhc55tc9jtckygx0eph6dd02"
+	substrateSigner = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+	timeoutNanos    = uint64(1_700_000_000_000_000_000)
+	paraID          = 2087
+)
+
+var (
+	proof       = []byte("commitment proof")
+	proofHeight = clienttypes.NewHeight(1, 42)
+)
+
+func main() {
+	if len(os.Args) != 2 {
+		fmt.Fprintln(os.Stderr, "usage: golden <repository root>")
+		os.Exit(1)
+	}
+	root := os.Args[1]
+
+	core := filepath.Join(root, "ibc/modules/tests/golden")
+	for name, msg := range coreMessages() {
+		writeMsg(core, name, msg)
+	}
+
+	tendermint := filepath.Join(root, "light-clients/ics07-tendermint/src/mock/golden")
+	for name, msg := range tendermintMessages() {
+		writeMsg(tendermint, name, msg)
+	}
+
+	wasm := filepath.Join(root, "contracts/pallet-ibc/src/golden")
+	for name, packed := range wasmMessages() {
+		writeAny(wasm, name, packed)
+	}
+}
+
+func packet() channeltypes.Packet {
+	data := fmt.Sprintf(
+		`{"amount":"1000","denom":"ppica","receiver":"%s","sender":"%s"}`, signer, substrateSigner,
+	)
+	return channeltypes.Packet{
+		Sequence:           7,
+		SourcePort:         "transfer",
+		SourceChannel:      "channel-0",
+		DestinationPort:    "transfer",
+		DestinationChannel: "channel-1",
+		Data:               []byte(data),
+		TimeoutHeight:      clienttypes.NewHeight(1, 1000),
+		TimeoutTimestamp:   timeoutNanos,
+	}
+}
+
+func connectionVersion() *connectiontypes.Version {
+	return &connectiontypes.Version{
+		Identifier: "1",
+		Features:   []string{"ORDER_ORDERED", "ORDER_UNORDERED"},
+	}
+}
+
+func coreMessages() map[string]proto.Message {
+	return map[string]proto.Message{
+		"msg_transfer": &transfertypes.MsgTransfer{
+			SourcePort:       "transfer",
+			SourceChannel:    "channel-0",
+			Token:            sdk.Coin{Denom: "transfer/channel-1/uatom", Amount: sdkmath.NewInt(1000)},
+			Sender:           substrateSigner,
+			Receiver:         signer,
+			TimeoutHeight:    clienttypes.NewHeight(1, 1000),
+			TimeoutTimestamp: timeoutNanos,
+		},
+		"msg_recv_packet": &channeltypes.MsgRecvPacket{
+			Packet:          packet(),
+			ProofCommitment: proof,
+			ProofHeight:     proofHeight,
+			Signer:          signer,
+		},
+		"msg_acknowledgement": &channeltypes.MsgAcknowledgement{
+			Packet:          packet(),
+			Acknowledgement: []byte(`{"result":"AQ=="}`),
+			ProofAcked:      proof,
+			ProofHeight:     proofHeight,
+			Signer:          signer,
+		},
+		"msg_timeout": &channeltypes.MsgTimeout{
+			Packet:           packet(),
+			ProofUnreceived:  proof,
+			ProofHeight:      proofHeight,
+			NextSequenceRecv: 7,
+			Signer:           signer,
+		},
+		"msg_timeout_on_close": &channeltypes.MsgTimeoutOnClose{
+			Packet:           packet(),
+			ProofUnreceived:  proof,
+			ProofClose:       []byte("close proof"),
+			ProofHeight:      proofHeight,
+			NextSequenceRecv: 7,
+			Signer:           signer,
+		},
+		"msg_channel_open_init": &channeltypes.MsgChannelOpenInit{
+			PortId: "transfer",
+			Channel: channeltypes.Channel{
+				State:          channeltypes.INIT,
+				Ordering:       channeltypes.UNORDERED,
+				Counterparty:   channeltypes.Counterparty{PortId: "transfer"},
+				ConnectionHops: []string{"connection-0"},
+				Version:        "ics20-1",
+			},
+			Signer: signer,
+		},
+		"msg_channel_open_try": &channeltypes.MsgChannelOpenTry{
+			PortId: "transfer",
+			Channel: channeltypes.Channel{
+				State:          channeltypes.TRYOPEN,
+				Ordering:       channeltypes.UNORDERED,
+				Counterparty:   channeltypes.Counterparty{PortId: "transfer", ChannelId: "channel-0"},
+				ConnectionHops: []string{"connection-1"},
+				Version:        "ics20-1",
+			},
+			CounterpartyVersion: "ics20-1",
+			ProofInit:           proof,
+			ProofHeight:         proofHeight,
+			Signer:              signer,
+		},
+		"msg_channel_open_ack": &channeltypes.MsgChannelOpenAck{
+			PortId:                "transfer",
+			ChannelId:             "channel-0",
+			CounterpartyChannelId: "channel-1",
+			CounterpartyVersion:   "ics20-1",
+			ProofTry:              proof,
+			ProofHeight:           proofHeight,
+			Signer:                signer,
+		},
+		"msg_channel_open_confirm": &channeltypes.MsgChannelOpenConfirm{
+			PortId:      "transfer",
+			ChannelId:   "channel-1",
+			ProofAck:    proof,
+			ProofHeight: proofHeight,
+			Signer:      signer,
+		},
+		"msg_channel_close_init": &channeltypes.MsgChannelCloseInit{
+			PortId:    "transfer",
+			ChannelId: "channel-0",
+			Signer:    signer,
+		},
+		"msg_channel_close_confirm": &channeltypes.MsgChannelCloseConfirm{
+			PortId:      "transfer",
+			ChannelId:   "channel-1",
+			ProofInit:   proof,
+			ProofHeight: proofHeight,
+			Signer:      signer,
+		},
+		"msg_connection_open_init": &connectiontypes.MsgConnectionOpenInit{
+			ClientId: "07-tendermint-0",
+			Counterparty: connectiontypes.Counterparty{
+				ClientId: "08-wasm-0",
+				Prefix:   commitmenttypes.NewMerklePrefix([]byte("ibc")),
+			},
+			Version:     connectionVersion(),
+			DelayPeriod: uint64(10 * time.Second),
+			Signer:      signer,
+		},
+		"msg_connection_open_confirm": &connectiontypes.MsgConnectionOpenConfirm{
+			ConnectionId: "connection-1",
+			ProofAck:     proof,
+			ProofHeight:  proofHeight,
+			Signer:       signer,
+		},
+	}
+}
+
+func tendermintClientState() *ibctm.ClientState {
+	return ibctm.NewClientState(
+		"centauri-1",
+		ibctm.DefaultTrustLevel,
+		1_209_600*time.Second,
+		1_814_400*time.Second,
+		10*time.Second,
+		clienttypes.NewHeight(1, 100),
+		commitmenttypes.GetSDKSpecs(),
+		[]string{"upgrade", "upgradedIBCState"},
+	)
+}
+
+func tendermintConsensusState() *ibctm.ConsensusState {
+	return ibctm.NewConsensusState(
+		time.Unix(1_700_000_000, 0).UTC(),
+		commitmenttypes.NewMerkleRoot(repeat(1, 32)),
+		repeat(2, 32),
+	)
+}
+
+func tendermintMessages() map[string]proto.Message {
+	clientState := mustPack(tendermintClientState())
+	consensusProof := []byte("consensus proof")
+	clientProof := []byte("client proof")
+	return map[string]proto.Message{
+		"client_state":    tendermintClientState(),
+		"consensus_state": tendermintConsensusState(),
+		"msg_create_client": &clienttypes.MsgCreateClient{
+			ClientState:    clientState,
+			ConsensusState: mustPack(tendermintConsensusState()),
+			Signer:         signer,
+		},
+		"msg_connection_open_try": &connectiontypes.MsgConnectionOpenTry{
+			ClientId:    "08-wasm-0",
+			ClientState: clientState,
+			Counterparty: connectiontypes.Counterparty{
+				ClientId:     "07-tendermint-0",
+				ConnectionId: "connection-0",
+				Prefix:       commitmenttypes.NewMerklePrefix([]byte("ibc")),
+			},
+			DelayPeriod:          uint64(10 * time.Second),
+			CounterpartyVersions: []*connectiontypes.Version{connectionVersion()},
+			ProofHeight:          proofHeight,
+			ProofInit:            proof,
+			ProofClient:          clientProof,
+			ProofConsensus:       consensusProof,
+			ConsensusHeight:      clienttypes.NewHeight(1, 100),
+			Signer:               signer,
+		},
+		"msg_connection_open_ack": &connectiontypes.MsgConnectionOpenAck{
+			ConnectionId:             "connection-0",
+			CounterpartyConnectionId: "connection-1",
+			Version:                  connectionVersion(),
+			ClientState:              clientState,
+			ProofHeight:              proofHeight,
+			ProofTry:                 proof,
+			ProofClient:              clientProof,
+			ProofConsensus:           consensusProof,
+			ConsensusHeight:          clienttypes.NewHeight(1, 100),
+			Signer:                   signer,
+		},
+	}
+}
+
+// Grandpa states wrapped in the 08-wasm ones, as the relayer creates and updates the grandpa
+// client of a cosmos chain.
+func wasmMessages() map[string]*codectypes.Any {
+	grandpaClientState := &codectypes.Any{
+		TypeUrl: "/ibc.lightclients.grandpa.v1.ClientState",
+		Value: concat(
+			bytesField(1, repeat(3, 32)), // latest_relay_hash
+			varintField(2, 200),          // latest_relay_height
+			varintField(3, 5),            // current_set_id
+			varintField(5, 2),            // relay_chain, ROCOCO
+			varintField(6, paraID),       // para_id
+			varintField(7, 100),          // latest_para_height
+			// current_authorities
+			bytesField(8, concat(bytesField(1, repeat(4, 32)), varintField(2, 1))),
+		),
+	}
+	grandpaConsensusState := &codectypes.Any{
+		TypeUrl: "/ibc.lightclients.grandpa.v1.ConsensusState",
+		Value: concat(
+			bytesField(1, varintField(1, 1_700_000_000)), // timestamp
+			bytesField(2, repeat(1, 32)),                 // root
+		),
+	}
+	parachainHeaderProofs := concat(
+		bytesField(1, []byte("state proof")),
+		bytesField(2, []byte("timestamp extrinsic")),
+		bytesField(3, []byte("extrinsic proof")),
+	)
+	grandpaHeader := &codectypes.Any{
+		TypeUrl: "/ibc.lightclients.grandpa.v1.Header",
+		Value: concat(
+			// finality_proof
+			bytesField(1, concat(bytesField(1, repeat(6, 32)), bytesField(2, []byte("justification")))),
+			// parachain_headers
+			bytesField(2, concat(bytesField(1, repeat(7, 32)), bytesField(2, parachainHeaderProofs))),
+			varintField(3, paraID), // para_id
+			varintField(4, 100),    // para_height
+		),
+	}
+
+	latestHeight := clienttypes.NewHeight(paraID, 100)
+	wasmClientState := &codectypes.Any{
+		TypeUrl: "/ibc.lightclients.wasm.v1.ClientState",
+		Value: concat(
+			bytesField(1, mustMarshal(grandpaClientState)), // data
+			bytesField(2, repeat(5, 32)),                   // checksum
+			bytesField(3, mustMarshal(&latestHeight)),      // latest_height
+		),
+	}
+	wasmConsensusState := &codectypes.Any{
+		TypeUrl: "/ibc.lightclients.wasm.v1.ConsensusState",
+		Value:   bytesField(1, mustMarshal(grandpaConsensusState)),
+	}
+	wasmClientMessage := &codectypes.Any{
+		TypeUrl: "/ibc.lightclients.wasm.v1.ClientMessage",
+		Value:   bytesField(1, mustMarshal(grandpaHeader)),
+	}
+
+	return map[string]*codectypes.Any{
+		"grandpa_client_state":    grandpaClientState,
+		"grandpa_consensus_state": grandpaConsensusState,
+		"wasm_client_state":       wasmClientState,
+		"wasm_consensus_state":    wasmConsensusState,
+		"msg_create_client": mustPack(&clienttypes.MsgCreateClient{
+			ClientState:    wasmClientState,
+			ConsensusState: wasmConsensusState,
+			Signer:         signer,
+		}),
+		"msg_update_client": mustPack(&clienttypes.MsgUpdateClient{
+			ClientId:      "08-wasm-0",
+			ClientMessage: wasmClientMessage,
+			Signer:        signer,
+		}),
+	}
+}
+
+func writeMsg(dir, name string, msg proto.Message) {
+	writeAny(dir, name, mustPack(msg))
+}
+
+func writeAny(dir, name string, packed *codectypes.Any) {
+	path := filepath.Join(dir, name+".hex")
+	if err := os.WriteFile(path, []byte(hex.EncodeToString(mustMarshal(packed))+"\n"), 0o644); err != nil {
+		panic(err)
+	}
+	fmt.Println("wrote", path)
+}
+
+func mustPack(msg proto.Message) *codectypes.Any {
+	packed, err := codectypes.NewAnyWithValue(msg)
+	if err != nil {
+		panic(err)
+	}
+	return packed
+}
+
+func mustMarshal(msg proto.Message) []byte {
+	bz, err := proto.Marshal(msg)
+	if err != nil {
+		panic(err)
+	}
+	return bz
+}
+
+// Fields are omitted when they hold the default value, as proto3 does.
+func varintField(num protowire.Number, v uint64) []byte {
+	if v == 0 {
+		return nil
+	}
+	return protowire.AppendVarint(protowire.AppendTag(nil, num, protowire.VarintType), v)
+}
+
+func bytesField(num protowire.Number, v []byte) []byte {
+	if len(v) == 0 {
+		return nil
+	}
+	return protowire.AppendBytes(protowire.AppendTag(nil, num, protowire.BytesType), v)
+}
+
+func concat(fields ...[]byte) []byte {
+	var out []byte
+	for _, field := range fields {
+		out = append(out, field...)
+	}
+	return out
+}
+
+func repeat(b byte, n int) []byte {
+	out := make([]byte, n)
+	for i := range out {
+		out[i] = b
+	}
+	return out
+}
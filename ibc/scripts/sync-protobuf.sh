@@ -6,9 +6,10 @@ set -eou pipefail
 # files using the proto-compiler project. It will check
 # out the protobuf files from the git versions specified in
 # proto/src/prost/COSMOS_SDK_COMMIT and
-# proto/src/prost/IBC_GO_COMMIT. If you want to sync
+# proto/src/prost/IBC_GO_COMMIT (a commit ID or a tag). If you want to sync
 # the protobuf files to a newer version, modify the
-# relevant files with the new commit IDs.
+# relevant files with the new commit IDs, and regenerate
+# the golden encodings with generate-golden.sh.
 
 # This script should be run from the root directory of ibc-rs
 
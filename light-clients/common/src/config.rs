@@ -21,7 +21,7 @@ use ibc_proto::google::protobuf::Any;
 use sp_core::H256;
 use subxt::{
 	client::OnlineClient,
-	config::ExtrinsicParams,
+	config::{extrinsic_params::Era, ExtrinsicParams, Header},
 	error::{Error, MetadataError, StorageAddressError},
 	events::{Phase, StaticEvent},
 	ext::{
@@ -317,9 +317,71 @@ pub trait Config: subxt::Config + Sized {
 	/// use the subxt client to fetch any neccessary data needed for the extrinsic metadata.
 	async fn custom_extrinsic_params(
 		client: &OnlineClient<Self>,
+		options: &ExtrinsicOptions,
 	) -> Result<CustomExtrinsicParams<Self>, Error>;
 }
 
+/// Options for the extrinsics signed by the relayer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ExtrinsicOptions {
+	/// Mortality of the extrinsics
+	#[serde(default)]
+	pub era: ExtrinsicEra,
+	/// Tip paid to the block author for every extrinsic
+	#[serde(default)]
+	pub tip: u128,
+	/// How the extrinsic nonces are determined
+	#[serde(default)]
+	pub nonce_strategy: NonceStrategy,
+}
+
+/// Mortality of an extrinsic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtrinsicEra {
+	/// The extrinsic is valid forever
+	#[default]
+	Immortal,
+	/// The extrinsic is only valid for `period` blocks after the latest finalized block
+	Mortal { period: u64 },
+}
+
+impl ExtrinsicEra {
+	/// Returns the era along with the hash of the block the era is checkpointed at.
+	pub async fn checkpoint<T: subxt::Config>(
+		&self,
+		client: &OnlineClient<T>,
+	) -> Result<(Era, T::Hash), Error> {
+		match self {
+			ExtrinsicEra::Immortal => Ok((Era::Immortal, client.genesis_hash())),
+			ExtrinsicEra::Mortal { period } => {
+				let hash = client.rpc().finalized_head().await?;
+				let header =
+					client.rpc().header(Some(hash)).await?.ok_or_else(|| {
+						Error::Other(format!("Header not found for block {hash:?}"))
+					})?;
+				Ok((Era::mortal(*period, header.number().into()), hash))
+			},
+		}
+	}
+}
+
+/// How the nonce of an extrinsic is determined.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NonceStrategy {
+	/// Ask the node for the next nonce, which accounts for the transactions in its pool
+	#[default]
+	Pool,
+	/// Use the account nonce at the latest finalized block. The pool rejects the extrinsic if one
+	/// with the same nonce is still pending, unless it pays a higher `tip`, which isn't raised
+	/// automatically
+	Finalized,
+	/// Track the nonce locally, so that multiple extrinsics can be submitted in the same block.
+	/// The nonce is resynchronized with the node whenever a submission fails
+	Local,
+}
+
 pub type CustomExtrinsicParams<T> = <<T as subxt::Config>::ExtrinsicParams as ExtrinsicParams<
 	<T as subxt::Config>::Index,
 	<T as subxt::Config>::Hash,
@@ -29,6 +29,7 @@ use ibc::core::{
 	},
 	ics23_commitment::{
 		commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+		error::Error as Ics23Error,
 		merkle::{apply_prefix, MerkleProof},
 	},
 	ics24_host::{
@@ -41,7 +42,7 @@ use ibc::core::{
 	},
 	ics26_routing::context::ReaderContext,
 };
-use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
+use ibc_proto::ibc::core::commitment::v1::{MerklePath, MerkleProof as RawMerkleProof};
 use prost::Message;
 use tendermint_light_client_verifier::{
 	types::{TrustedBlockState, UntrustedBlockState},
@@ -321,30 +322,123 @@ where
 		Ok(false)
 	}
 
+	/// Verifies the upgraded client and consensus states committed by the counterparty under the
+	/// client's upgrade path, against the root of the latest consensus state. Parameters chosen by
+	/// the host (trust level, trusting period and clock drift) are carried over from the old
+	/// client.
 	fn verify_upgrade_and_update_state<Ctx: ReaderContext>(
 		&self,
-		_ctx: &Ctx,
-		_client_id: ClientId,
-		_old_client_state: &Self::ClientState,
-		_upgrade_client_state: &Self::ClientState,
-		_upgrade_consensus_state: &Self::ConsensusState,
-		_proof_upgrade_client: Vec<u8>,
-		_proof_upgrade_consensus_state: Vec<u8>,
+		ctx: &Ctx,
+		client_id: ClientId,
+		old_client_state: &Self::ClientState,
+		upgrade_client_state: &Self::ClientState,
+		upgrade_consensus_state: &Self::ConsensusState,
+		proof_upgrade_client: Vec<u8>,
+		proof_upgrade_consensus_state: Vec<u8>,
 	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
-		// TODO: tendermint verify_upgrade_and_update_state
-		Err(Ics02Error::implementation_specific("Not implemented".to_string()))
+		let latest_height = old_client_state.latest_height;
+		let consensus_state = ctx.consensus_state(&client_id, latest_height)?;
+		let expired = ctx
+			.host_timestamp()
+			.duration_since(&consensus_state.timestamp())
+			.map_or(false, |elapsed| old_client_state.expired(elapsed));
+		if expired {
+			return Err(Error::upgrade_client_expired(latest_height).into())
+		}
+		let root = consensus_state.root().clone();
+
+		let committed_client_state =
+			Ctx::AnyClientState::wrap(&upgrade_client_state.clone().zero_custom_fields())
+				.ok_or_else(|| {
+					Ics02Error::unknown_client_state_type("Ctx::AnyClientState".to_string())
+				})?
+				.encode_to_vec()
+				.map_err(Ics02Error::encode)?;
+		let client_path = upgrade_merkle_path(
+			&old_client_state.upgrade_path,
+			latest_height,
+			UPGRADED_CLIENT_KEY,
+		)?;
+		verify_upgrade_proof::<H>(
+			old_client_state,
+			&root,
+			proof_upgrade_client,
+			client_path,
+			committed_client_state,
+		)?;
+
+		let committed_consensus_state = Ctx::AnyConsensusState::wrap(upgrade_consensus_state)
+			.ok_or_else(|| {
+				Ics02Error::unknown_consensus_state_type("Ctx::AnyConsensusState".to_string())
+			})?
+			.encode_to_vec()
+			.map_err(Ics02Error::encode)?;
+		let consensus_path = upgrade_merkle_path(
+			&old_client_state.upgrade_path,
+			latest_height,
+			UPGRADED_CONSENSUS_STATE_KEY,
+		)?;
+		verify_upgrade_proof::<H>(
+			old_client_state,
+			&root,
+			proof_upgrade_consensus_state,
+			consensus_path,
+			committed_consensus_state,
+		)?;
+
+		let client_state = ClientState {
+			chain_id: upgrade_client_state.chain_id.clone(),
+			unbonding_period: upgrade_client_state.unbonding_period,
+			latest_height: upgrade_client_state.latest_height,
+			proof_specs: upgrade_client_state.proof_specs.clone(),
+			upgrade_path: upgrade_client_state.upgrade_path.clone(),
+			frozen_height: None,
+			..old_client_state.clone()
+		};
+		// The upgraded consensus state can't be used to verify proofs, the root is only there to
+		// keep the state well-formed until the client is updated past the upgrade height.
+		let consensus_state = ConsensusState::new(
+			CommitmentRoot::from_bytes(SENTINEL_ROOT),
+			upgrade_consensus_state.timestamp,
+			upgrade_consensus_state.next_validators_hash,
+		);
+		let consensus_state = Ctx::AnyConsensusState::wrap(&consensus_state).ok_or_else(|| {
+			Ics02Error::unknown_consensus_state_type("Ctx::AnyConsensusState".to_string())
+		})?;
+
+		Ok((client_state, ConsensusUpdateResult::Single(consensus_state)))
 	}
 
+	/// Copies the state of the substitute over the subject client. The substitute may only differ
+	/// from the subject in the chain id, trusting period, latest height and frozen height.
 	fn check_substitute_and_update_state<Ctx: ReaderContext>(
 		&self,
-		_ctx: &Ctx,
+		ctx: &Ctx,
 		_subject_client_id: ClientId,
-		_substitute_client_id: ClientId,
-		_old_client_state: Self::ClientState,
-		_substitute_client_state: Self::ClientState,
+		substitute_client_id: ClientId,
+		old_client_state: Self::ClientState,
+		substitute_client_state: Self::ClientState,
 	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
-		// TODO: tendermint check_substitute_and_update_state
-		Err(Ics02Error::implementation_specific("Not implemented".to_string()))
+		if old_client_state.trust_level != substitute_client_state.trust_level ||
+			old_client_state.unbonding_period != substitute_client_state.unbonding_period ||
+			old_client_state.max_clock_drift != substitute_client_state.max_clock_drift ||
+			old_client_state.proof_specs != substitute_client_state.proof_specs ||
+			old_client_state.upgrade_path != substitute_client_state.upgrade_path
+		{
+			return Err(Error::mismatched_substitute_client_state().into())
+		}
+
+		let consensus_state =
+			ctx.consensus_state(&substitute_client_id, substitute_client_state.latest_height)?;
+		let client_state = ClientState {
+			chain_id: substitute_client_state.chain_id,
+			trusting_period: substitute_client_state.trusting_period,
+			latest_height: substitute_client_state.latest_height,
+			frozen_height: None,
+			..old_client_state
+		};
+
+		Ok((client_state, ConsensusUpdateResult::Single(consensus_state)))
 	}
 
 	fn verify_client_consensus_state<Ctx: ReaderContext>(
@@ -542,6 +636,67 @@ where
 			receipt_path,
 		)
 	}
+
+	fn verify_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		path: Path,
+		value: Vec<u8>,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_membership::<H, _>(client_state, prefix, proof, root, path, value)
+	}
+
+	fn verify_non_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		path: Path,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_non_membership::<H, _>(client_state, prefix, proof, root, path)
+	}
+
+	fn verify_batch_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		items: Vec<(Path, Vec<u8>)>,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_batch_membership::<H>(client_state, prefix, proof, root, items)
+	}
+
+	fn verify_batch_non_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		paths: Vec<Path>,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_batch_non_membership::<H>(client_state, prefix, proof, root, paths)
+	}
 }
 
 pub fn verify_membership<H, P>(
@@ -587,6 +742,92 @@ where
 		.map_err(|e| Error::ics23_error(e).into())
 }
 
+pub fn verify_batch_membership<H>(
+	client_state: &ClientState<H>,
+	prefix: &CommitmentPrefix,
+	proof: &CommitmentProofBytes,
+	root: &CommitmentRoot,
+	items: Vec<(Path, Vec<u8>)>,
+) -> Result<(), Ics02Error>
+where
+	H: ics23::HostFunctionsProvider,
+{
+	let items = items
+		.into_iter()
+		.map(|(path, value)| (apply_prefix(prefix, vec![path.to_string()]), value))
+		.collect();
+	let merkle_proof: MerkleProof<H> = RawMerkleProof::try_from(proof.clone())
+		.map_err(Ics02Error::invalid_commitment_proof)?
+		.into();
+
+	merkle_proof
+		.verify_batch_membership(&client_state.proof_specs, root.clone().into(), items)
+		.map_err(|e| Error::ics23_error(e).into())
+}
+
+pub fn verify_batch_non_membership<H>(
+	client_state: &ClientState<H>,
+	prefix: &CommitmentPrefix,
+	proof: &CommitmentProofBytes,
+	root: &CommitmentRoot,
+	paths: Vec<Path>,
+) -> Result<(), Ics02Error>
+where
+	H: ics23::HostFunctionsProvider,
+{
+	let merkle_paths = paths
+		.into_iter()
+		.map(|path| apply_prefix(prefix, vec![path.to_string()]))
+		.collect();
+	let merkle_proof: MerkleProof<H> = RawMerkleProof::try_from(proof.clone())
+		.map_err(Ics02Error::invalid_commitment_proof)?
+		.into();
+
+	merkle_proof
+		.verify_batch_non_membership(&client_state.proof_specs, root.clone().into(), merkle_paths)
+		.map_err(|e| Error::ics23_error(e).into())
+}
+
+/// Key under which the upgraded client state is committed, relative to the upgrade path.
+pub const UPGRADED_CLIENT_KEY: &str = "upgradedClient";
+/// Key under which the upgraded consensus state is committed, relative to the upgrade path.
+pub const UPGRADED_CONSENSUS_STATE_KEY: &str = "upgradedConsState";
+/// Root of the consensus state stored right after an upgrade.
+pub const SENTINEL_ROOT: &[u8] = b"sentinel_root";
+
+/// Merkle path of an upgraded state committed at `height`, i.e. the upgrade path with
+/// `/{height}/{key}` appended to its last segment.
+pub fn upgrade_merkle_path(
+	upgrade_path: &[String],
+	height: Height,
+	key: &str,
+) -> Result<MerklePath, Ics02Error> {
+	let (last, prefix) = upgrade_path.split_last().ok_or_else(Error::empty_upgrade_path)?;
+	let mut key_path = prefix.to_vec();
+	key_path.push(format!("{}/{}/{}", last, height.revision_height, key));
+	Ok(MerklePath { key_path })
+}
+
+fn verify_upgrade_proof<H>(
+	client_state: &ClientState<H>,
+	root: &CommitmentRoot,
+	proof: Vec<u8>,
+	merkle_path: MerklePath,
+	value: Vec<u8>,
+) -> Result<(), Ics02Error>
+where
+	H: ics23::HostFunctionsProvider,
+{
+	let merkle_proof: MerkleProof<H> = RawMerkleProof::decode(proof.as_slice())
+		.map_err(|e| Ics02Error::invalid_commitment_proof(Ics23Error::invalid_raw_merkle_proof(e)))?
+		.into();
+
+	let path = merkle_path.key_path.join("/");
+	merkle_proof
+		.verify_membership(&client_state.proof_specs, root.clone().into(), merkle_path, value, 0)
+		.map_err(|e| Error::invalid_upgrade_proof(path, e).into())
+}
+
 fn verify_delay_passed<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	height: Height,
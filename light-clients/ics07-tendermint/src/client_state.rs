@@ -39,7 +39,7 @@ use ibc::{
 		ics23_commitment::specs::ProofSpecs,
 		ics24_host::identifier::ChainId,
 	},
-	timestamp::Timestamp,
+	timestamp::{Timestamp, ZERO_DURATION},
 	Height,
 };
 
@@ -191,6 +191,18 @@ impl<H> ClientState<H> {
 		self
 	}
 
+	/// Resets the fields that are chosen by the client's host rather than the counterparty chain,
+	/// i.e. the form in which the upgraded client state is committed under the upgrade path.
+	pub fn zero_custom_fields(self) -> Self {
+		Self {
+			trust_level: TrustThreshold::ZERO,
+			trusting_period: ZERO_DURATION,
+			max_clock_drift: ZERO_DURATION,
+			frozen_height: None,
+			..self
+		}
+	}
+
 	/// Check if the state is expired when `elapsed` time has passed since the latest consensus
 	/// state timestamp
 	pub fn expired(&self, elapsed: Duration) -> bool {
@@ -367,10 +379,16 @@ mod tests {
 	use ibc::{prelude::*, Height};
 	use test_log::test;
 
-	use ibc_proto::cosmos::ics23::v1::ProofSpec as Ics23ProofSpec;
+	use ibc_proto::{cosmos::ics23::v1::ProofSpec as Ics23ProofSpec, google::protobuf::Any};
+	use prost::Message;
+	use tendermint_proto::Protobuf;
 	use tendermint_rpc::endpoint::abci_query::AbciQuery;
 
-	use crate::{client_state::ClientState, mock::Crypto};
+	use crate::{
+		client_def::{upgrade_merkle_path, UPGRADED_CLIENT_KEY},
+		client_state::ClientState,
+		mock::Crypto,
+	};
 	use ibc::{
 		core::{
 			ics02_client::trust_threshold::TrustThreshold, ics23_commitment::specs::ProofSpecs,
@@ -659,6 +677,62 @@ mod tests {
 			);
 		}
 	}
+
+	#[test]
+	fn upgraded_client_state_commitment() {
+		let client_state = ClientState::<Crypto>::new(
+			ChainId::new("ibc".to_string(), 1),
+			TrustThreshold::ONE_THIRD,
+			Duration::new(64000, 0),
+			Duration::new(128000, 0),
+			Duration::new(3, 0),
+			Height::new(1, 10),
+			ProofSpecs::default(),
+			vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
+		)
+		.unwrap()
+		.with_frozen_height(Height::new(1, 5))
+		.unwrap();
+
+		let committed = client_state.clone().zero_custom_fields();
+		assert_eq!(committed.trust_level, TrustThreshold::ZERO);
+		assert_eq!(committed.trusting_period, ZERO_DURATION);
+		assert_eq!(committed.max_clock_drift, ZERO_DURATION);
+		assert_eq!(committed.frozen_height, None);
+		assert_eq!(committed.chain_id, client_state.chain_id);
+		assert_eq!(committed.unbonding_period, client_state.unbonding_period);
+		assert_eq!(committed.upgrade_path, client_state.upgrade_path);
+
+		let path = upgrade_merkle_path(
+			&client_state.upgrade_path,
+			client_state.latest_height,
+			UPGRADED_CLIENT_KEY,
+		)
+		.unwrap();
+		assert_eq!(path.key_path, vec!["upgrade", "upgradedIBCState/10/upgradedClient"]);
+		assert!(upgrade_merkle_path(&[], client_state.latest_height, UPGRADED_CLIENT_KEY).is_err());
+	}
+
+	#[test]
+	fn client_state_matches_upstream_encoding() {
+		// encoding of ibc-go for the same client state, see `ibc/modules/tests/golden.rs`
+		let golden = hex::decode(include_str!("mock/golden/client_state.hex").trim()).unwrap();
+		let client_state = ClientState::<Crypto>::new(
+			ChainId::from_string("centauri-1"),
+			TrustThreshold::ONE_THIRD,
+			Duration::from_secs(1_209_600),
+			Duration::from_secs(1_814_400),
+			Duration::from_secs(10),
+			Height::new(1, 100),
+			ProofSpecs::default(),
+			vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
+		)
+		.unwrap();
+		assert_eq!(hex::encode(client_state.to_any().encode_to_vec()), hex::encode(&golden));
+
+		let any = Any::decode(golden.as_slice()).unwrap();
+		assert_eq!(ClientState::<Crypto>::decode_vec(&any.value).unwrap(), client_state);
+	}
 }
 
 #[cfg(any(test, feature = "mocks"))]
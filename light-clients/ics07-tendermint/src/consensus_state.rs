@@ -132,10 +132,17 @@ impl From<Header> for ConsensusState {
 
 #[cfg(test)]
 mod tests {
+	use ibc_proto::google::protobuf::Any;
+	use prost::Message;
+	use tendermint::{time::Time, Hash};
+	use tendermint_proto::Protobuf;
 	use tendermint_rpc::endpoint::abci_query::AbciQuery;
 	use test_log::test;
 
-	use ibc::test::test_serialization_roundtrip;
+	use super::ConsensusState;
+	use ibc::{
+		core::ics23_commitment::commitment::CommitmentRoot, test::test_serialization_roundtrip,
+	};
 
 	#[test]
 	fn serialization_roundtrip_no_proof() {
@@ -148,4 +155,19 @@ mod tests {
 		let json_data = include_str!("mock/query/serialization/consensus_state_proof.json");
 		test_serialization_roundtrip::<AbciQuery>(json_data);
 	}
+
+	#[test]
+	fn consensus_state_matches_upstream_encoding() {
+		// encoding of ibc-go for the same consensus state, see `ibc/modules/tests/golden.rs`
+		let golden = hex::decode(include_str!("mock/golden/consensus_state.hex").trim()).unwrap();
+		let consensus_state = ConsensusState::new(
+			CommitmentRoot::from_bytes(&[1; 32]),
+			Time::from_unix_timestamp(1_700_000_000, 0).unwrap(),
+			Hash::Sha256([2; 32]),
+		);
+		assert_eq!(hex::encode(consensus_state.to_any().encode_to_vec()), hex::encode(&golden));
+
+		let any = Any::decode(golden.as_slice()).unwrap();
+		assert_eq!(ConsensusState::decode_vec(&any.value).unwrap(), consensus_state);
+	}
 }
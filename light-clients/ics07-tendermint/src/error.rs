@@ -284,6 +284,25 @@ define_error! {
 			| e | {
 				format_args!("the client is frozen: frozen_height={0} target_height={1}", e.frozen_height, e.target_height)
 			},
+
+		MismatchedSubstituteClientState
+			| _ | { "the substitute client state parameters don't match the ones of the subject client" },
+
+		EmptyUpgradePath
+			| _ | { "the client state doesn't define an upgrade path" },
+
+		UpgradeClientExpired
+			{
+				latest_height: Height,
+			}
+			| e | {
+				format_args!("the client consensus state at height {0} has expired, cannot upgrade", e.latest_height)
+			},
+
+		InvalidUpgradeProof
+			{ path: String }
+			[ Ics23Error ]
+			| e | { format_args!("invalid proof for the upgraded state at {0}", e.path) },
 	}
 }
 
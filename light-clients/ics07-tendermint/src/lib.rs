@@ -97,20 +97,37 @@ mod tests {
 				},
 				trust_threshold::TrustThreshold,
 			},
-			ics23_commitment::specs::ProofSpecs,
-			ics24_host::identifier::ClientId,
+			ics03_connection::{
+				connection::Counterparty,
+				msgs::{conn_open_ack::MsgConnectionOpenAck, conn_open_try::MsgConnectionOpenTry},
+				version::Version,
+			},
+			ics23_commitment::{
+				commitment::{CommitmentPrefix, CommitmentProofBytes},
+				specs::ProofSpecs,
+			},
+			ics24_host::identifier::{ClientId, ConnectionId},
 		},
 		events::IbcEvent,
 		handler::HandlerOutput,
 		mock::context::MockContext,
 		prelude::*,
+		proofs::{ConsensusProof, Proofs},
+		signer::Signer,
 		test_utils::get_dummy_account_id,
+		tx_msg::Msg,
 		Height,
 	};
-	use ibc_proto::ibc::core::client::v1::{MsgCreateClient, MsgUpdateClient};
-	use std::time::Duration;
+	use ibc_proto::{
+		google::protobuf::Any,
+		ibc::core::client::v1::{MsgCreateClient, MsgUpdateClient},
+	};
+	use prost::Message;
+	use std::{fmt::Debug, str::FromStr, time::Duration};
 	use test_log::test;
 
+	const GOLDEN_SIGNER: &str = "cosmos1hsk6jryyqjfhp5dhc55tc9jtckygx0eph6dd02";
+
 	#[test]
 	fn msg_create_client_serialization() {
 		let signer = get_dummy_account_id();
@@ -210,4 +227,97 @@ mod tests {
 		assert_eq!(msg, msg_back);
 		assert_eq!(raw, raw_back);
 	}
+
+	/// Client and consensus states of `mock/golden`, whose encodings are checked against ibc-go by
+	/// `client_state_matches_upstream_encoding` and `consensus_state_matches_upstream_encoding`.
+	fn golden_states() -> (AnyClientState, AnyConsensusState) {
+		let any =
+			|golden: &str| Any::decode(hex::decode(golden.trim()).unwrap().as_slice()).unwrap();
+		(
+			AnyClientState::try_from(any(include_str!("mock/golden/client_state.hex"))).unwrap(),
+			AnyConsensusState::try_from(any(include_str!("mock/golden/consensus_state.hex")))
+				.unwrap(),
+		)
+	}
+
+	/// Asserts that `msg` encodes to the `golden` hex of ibc-go, see `ibc/modules/tests/golden.rs`,
+	/// and that decoding the golden gives `msg` back.
+	fn assert_golden<M>(golden: &str, msg: M)
+	where
+		M: Msg + TryFrom<<M as Msg>::Raw> + PartialEq + Debug,
+		<M as Msg>::Raw: Default,
+		<M as TryFrom<<M as Msg>::Raw>>::Error: Debug,
+	{
+		let golden = hex::decode(golden.trim()).unwrap();
+		assert_eq!(hex::encode(msg.clone().to_any().encode_to_vec()), hex::encode(&golden));
+
+		let any = Any::decode(golden.as_slice()).unwrap();
+		assert_eq!(any.type_url, msg.type_url());
+		let raw = <M as Msg>::Raw::decode(any.value.as_slice()).unwrap();
+		assert_eq!(M::try_from(raw).unwrap(), msg);
+	}
+
+	fn golden_proofs() -> Proofs {
+		Proofs::new(
+			CommitmentProofBytes::try_from(b"commitment proof".to_vec()).unwrap(),
+			Some(CommitmentProofBytes::try_from(b"client proof".to_vec()).unwrap()),
+			Some(
+				ConsensusProof::new(
+					CommitmentProofBytes::try_from(b"consensus proof".to_vec()).unwrap(),
+					Height::new(1, 100),
+				)
+				.unwrap(),
+			),
+			None,
+			Height::new(1, 42),
+		)
+		.unwrap()
+	}
+
+	#[test]
+	fn msg_create_client_matches_upstream_encoding() {
+		let (client_state, consensus_state) = golden_states();
+		let msg = MsgCreateAnyClient::<MockContext<MockClientTypes>>::new(
+			client_state,
+			consensus_state,
+			Signer::from_str(GOLDEN_SIGNER).unwrap(),
+		)
+		.unwrap();
+		assert_golden(include_str!("mock/golden/msg_create_client.hex"), msg);
+	}
+
+	#[test]
+	fn msg_connection_open_try_matches_upstream_encoding() {
+		let (client_state, _) = golden_states();
+		let msg = MsgConnectionOpenTry::<MockContext<MockClientTypes>> {
+			client_id: ClientId::from_str("08-wasm-0").unwrap(),
+			client_state: Some(client_state),
+			counterparty: Counterparty::new(
+				ClientId::from_str("07-tendermint-0").unwrap(),
+				Some(ConnectionId::new(0)),
+				CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap(),
+			),
+			counterparty_versions: vec![Version::default()],
+			proofs: golden_proofs(),
+			delay_period: Duration::from_secs(10),
+			signer: Signer::from_str(GOLDEN_SIGNER).unwrap(),
+			host_consensus_state_proof: vec![],
+		};
+		assert_golden(include_str!("mock/golden/msg_connection_open_try.hex"), msg);
+	}
+
+	#[test]
+	fn msg_connection_open_ack_matches_upstream_encoding() {
+		let (client_state, _) = golden_states();
+		let msg = MsgConnectionOpenAck::<MockContext<MockClientTypes>> {
+			connection_id: ConnectionId::new(0),
+			counterparty_connection_id: ConnectionId::new(1),
+			client_state: Some(client_state),
+			proofs: golden_proofs(),
+			host_consensus_state_proof: vec![],
+			version: Version::default(),
+			signer: Signer::from_str(GOLDEN_SIGNER).unwrap(),
+		};
+		assert_golden(include_str!("mock/golden/msg_connection_open_ack.hex"), msg);
+	}
 }
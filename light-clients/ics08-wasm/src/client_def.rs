@@ -36,7 +36,10 @@ use ibc::{
 			packet::Sequence,
 		},
 		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
-		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		ics24_host::{
+			identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			Path,
+		},
 		ics26_routing::context::ReaderContext,
 	},
 	Height,
@@ -411,4 +414,98 @@ where
 			sequence,
 		)
 	}
+
+	fn verify_membership<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		path: Path,
+		value: Vec<u8>,
+	) -> Result<(), Error> {
+		self.inner.verify_membership(
+			ctx,
+			client_id,
+			&client_state.inner,
+			height,
+			prefix,
+			proof,
+			root,
+			path,
+			value,
+		)
+	}
+
+	fn verify_non_membership<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		path: Path,
+	) -> Result<(), Error> {
+		self.inner.verify_non_membership(
+			ctx,
+			client_id,
+			&client_state.inner,
+			height,
+			prefix,
+			proof,
+			root,
+			path,
+		)
+	}
+
+	fn verify_batch_membership<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		items: Vec<(Path, Vec<u8>)>,
+	) -> Result<(), Error> {
+		self.inner.verify_batch_membership(
+			ctx,
+			client_id,
+			&client_state.inner,
+			height,
+			prefix,
+			proof,
+			root,
+			items,
+		)
+	}
+
+	fn verify_batch_non_membership<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		paths: Vec<Path>,
+	) -> Result<(), Error> {
+		self.inner.verify_batch_non_membership(
+			ctx,
+			client_id,
+			&client_state.inner,
+			height,
+			prefix,
+			proof,
+			root,
+			paths,
+		)
+	}
 }
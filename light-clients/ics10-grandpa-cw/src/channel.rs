@@ -23,6 +23,7 @@ use ibc::{
 			context::ChannelReader,
 			error::Error,
 			packet::{Receipt, Sequence},
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 	},
@@ -135,6 +136,36 @@ impl<'a, H: HostFunctions> ChannelReader for Context<'a, H> {
 		))
 	}
 
+	fn channel_upgrade(&self, _port_channel_id: &(PortId, ChannelId)) -> Result<Upgrade, Error> {
+		Err(Error::implementation_specific(
+			"'channel_upgrade' is unavailable from the client".to_string(),
+		))
+	}
+
+	fn channel_counterparty_upgrade(
+		&self,
+		_port_channel_id: &(PortId, ChannelId),
+	) -> Result<Upgrade, Error> {
+		Err(Error::implementation_specific(
+			"'channel_counterparty_upgrade' is unavailable from the client".to_string(),
+		))
+	}
+
+	fn channel_upgrade_error_receipt(
+		&self,
+		_port_channel_id: &(PortId, ChannelId),
+	) -> Result<ErrorReceipt, Error> {
+		Err(Error::implementation_specific(
+			"'channel_upgrade_error_receipt' is unavailable from the client".to_string(),
+		))
+	}
+
+	fn has_inflight_packets(&self, _port_channel_id: &(PortId, ChannelId)) -> Result<bool, Error> {
+		Err(Error::implementation_specific(
+			"'has_inflight_packets' is unavailable from the client".to_string(),
+		))
+	}
+
 	fn max_expected_time_per_block(&self) -> Duration {
 		unimplemented!("'max_expected_time_per_block' is unavailable from the client")
 	}
@@ -48,6 +48,7 @@ use ibc::{
 				AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath,
 				CommitmentsPath, ConnectionsPath, ReceiptsPath, SeqRecvsPath,
 			},
+			Path,
 		},
 		ics26_routing::context::ReaderContext,
 	},
@@ -462,13 +463,19 @@ where
 	/// `frozen_height`, `latest_para_height`, `current_set_id` and `current_authorities`).
 	fn check_substitute_and_update_state<Ctx: ReaderContext>(
 		&self,
-		_ctx: &Ctx,
+		ctx: &Ctx,
 		_subject_client_id: ClientId,
-		_substitute_client_id: ClientId,
+		substitute_client_id: ClientId,
 		_old_client_state: Self::ClientState,
-		_substitute_client_state: Self::ClientState,
+		substitute_client_state: Self::ClientState,
 	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
-		unimplemented!("check_substitute_and_update_state not implemented for Grandpa client")
+		// every parameter of the grandpa client state tracks the relay chain, so the substitute
+		// is taken over as is
+		let consensus_state =
+			ctx.consensus_state(&substitute_client_id, substitute_client_state.latest_height())?;
+		let client_state = ClientState { frozen_height: None, ..substitute_client_state };
+
+		Ok((client_state, ConsensusUpdateResult::Single(consensus_state)))
 	}
 
 	fn verify_client_consensus_state<Ctx: ReaderContext>(
@@ -672,4 +679,39 @@ where
 		.map_err(Error::Anyhow)?;
 		Ok(())
 	}
+
+	fn verify_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		path: Path,
+		value: Vec<u8>,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_membership::<H::BlakeTwo256, _>(prefix, proof, root, path, value)
+			.map_err(Error::Anyhow)?;
+		Ok(())
+	}
+
+	fn verify_non_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		path: Path,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_non_membership::<H::BlakeTwo256, _>(prefix, proof, root, path)
+			.map_err(Error::Anyhow)?;
+		Ok(())
+	}
 }
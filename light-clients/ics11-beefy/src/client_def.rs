@@ -47,6 +47,7 @@ use ibc::{
 				AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath,
 				CommitmentsPath, ConnectionsPath, ReceiptsPath, SeqRecvsPath,
 			},
+			Path,
 		},
 		ics26_routing::context::ReaderContext,
 	},
@@ -463,4 +464,39 @@ where
 		.map_err(Error::Anyhow)?;
 		Ok(())
 	}
+
+	fn verify_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		path: Path,
+		value: Vec<u8>,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_membership::<H::BlakeTwo256, _>(prefix, proof, root, path, value)
+			.map_err(Error::Anyhow)?;
+		Ok(())
+	}
+
+	fn verify_non_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		path: Path,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_non_membership::<H::BlakeTwo256, _>(prefix, proof, root, path)
+			.map_err(Error::Anyhow)?;
+		Ok(())
+	}
 }
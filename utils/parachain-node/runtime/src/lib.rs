@@ -720,6 +720,8 @@ parameter_types! {
 	pub const IbcTriePrefix : &'static [u8] = b"ibc/";
 	pub FeeAccount: <Runtime as pallet_ibc::Config>::AccountIdConversion = create_alice_key();
 	pub const CleanUpPacketsPeriod: BlockNumber = 100;
+	pub const MaxConsensusStatesPerClient: u32 = 256;
+	pub const ConsensusStateMaxAge: u64 = 30 * DAYS as u64 * MILLISECS_PER_BLOCK;
 	pub AssetIdUSDT: AssetId = 0;
 	pub FlatFeeUSDTAmount: Balance = 0;
 	pub IbcIcs20ServiceCharge: Perbill = Perbill::from_rational(0_u32, 1000_u32 );
@@ -810,11 +812,15 @@ impl pallet_ibc::Config for Runtime {
 	type IbcAccountId = Self::AccountId;
 	type FeeAccount = FeeAccount;
 	type CleanUpPacketsPeriod = CleanUpPacketsPeriod;
+	type MaxConsensusStatesPerClient = MaxConsensusStatesPerClient;
+	type ConsensusStateMaxAge = ConsensusStateMaxAge;
 	type ServiceChargeOut = IbcIcs20ServiceCharge;
 	type FlatFeeConverter = NonFlatFeeConverter<Runtime>;
 	type FlatFeeAssetId = AssetIdUSDT;
 	type FlatFeeAmount = FlatFeeUSDTAmount;
 	type SubstrateMultihopXcmHandler = SubstrateMultihopXcmHandlerNone<Runtime>;
+	type Ics20RateLimiter = ();
+	type TransferCallbacks = ();
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -1028,16 +1034,20 @@ impl_runtime_apis! {
 			Ibc::consensus_state(client_id, revision_number, revision_height, latest_cs).ok()
 		}
 
-		fn clients() -> Option<Vec<(Vec<u8>, Vec<u8>)>> {
-			Some(Ibc::clients())
+		fn clients(next_key: Option<Vec<u8>>, limit: Option<u64>) -> Option<ibc_primitives::QueryClientsResponse> {
+			Ibc::clients(next_key, limit).ok()
 		}
 
 		fn connection(connection_id: Vec<u8>) -> Option<ibc_primitives::QueryConnectionResponse>{
 			Ibc::connection(connection_id).ok()
 		}
 
-		fn connections() -> Option<ibc_primitives::QueryConnectionsResponse> {
-			Ibc::connections().ok()
+		fn connections(next_key: Option<Vec<u8>>, limit: Option<u64>) -> Option<ibc_primitives::QueryConnectionsResponse> {
+			Ibc::connections(next_key, limit).ok()
+		}
+
+		fn max_expected_time_per_block() -> u64 {
+			Ibc::max_expected_time_per_block()
 		}
 
 		fn connection_using_client(client_id: Vec<u8>) -> Option<Vec<ibc_primitives::IdentifiedConnection>>{
@@ -1056,12 +1066,12 @@ impl_runtime_apis! {
 			Ibc::channel_client(channel_id, port_id).ok()
 		}
 
-		fn connection_channels(connection_id: Vec<u8>) -> Option<ibc_primitives::QueryChannelsResponse> {
-			Ibc::connection_channels(connection_id).ok()
+		fn connection_channels(connection_id: Vec<u8>, next_key: Option<Vec<u8>>, limit: Option<u64>) -> Option<ibc_primitives::QueryChannelsResponse> {
+			Ibc::connection_channels(connection_id, next_key, limit).ok()
 		}
 
-		fn channels() -> Option<ibc_primitives::QueryChannelsResponse> {
-			Ibc::channels().ok()
+		fn channels(next_key: Option<Vec<u8>>, limit: Option<u64>) -> Option<ibc_primitives::QueryChannelsResponse> {
+			Ibc::channels(next_key, limit).ok()
 		}
 
 		fn packet_commitments(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryPacketCommitmentsResponse> {
@@ -1100,11 +1110,36 @@ impl_runtime_apis! {
 			Ibc::get_denom_trace(asset_id)
 		}
 
+		fn packet_fees(_channel_id: Vec<u8>, _port_id: Vec<u8>, _seq: u64) -> Option<Vec<u8>> {
+			// the fee middleware isn't part of this runtime, no fee is ever escrowed
+			None
+		}
+
+		fn total_escrow(denom: Vec<u8>) -> u128 {
+			Ibc::get_total_escrow(denom)
+		}
+
+		fn escrow_balances() -> Vec<ibc_primitives::EscrowBalance> {
+			Ibc::get_escrow_balances()
+		}
+
+		fn denom_trace_by_hash(denom_hash: Vec<u8>) -> Option<ibc_primitives::QueryDenomTraceResponse> {
+			Ibc::get_denom_trace_by_hash(denom_hash)
+		}
+
 		fn denom_traces(key: Option<AssetId>, offset: Option<u32>, limit: u64, count_total: bool) -> ibc_primitives::QueryDenomTracesResponse {
 			let key = key.map(Either::Left).or_else(|| offset.map(Either::Right));
 			Ibc::get_denom_traces(key, limit, count_total)
 		}
 
+		fn cross_chain_queries() -> Vec<Vec<u8>> {
+			Ibc::get_cross_chain_queries()
+		}
+
+		fn ibc_store_value(path: Vec<u8>) -> Option<Vec<u8>> {
+			Ibc::get_ibc_store_value(path)
+		}
+
 		fn block_events(extrinsic_index: Option<u32>) -> Vec<Result<pallet_ibc::events::IbcEvent, pallet_ibc::errors::IbcError>> {
 			let mut raw_events = frame_system::Pallet::<Self>::read_events_no_consensus();
 			if let Some(idx) = extrinsic_index {